@@ -1,4 +1,76 @@
+use std::path::PathBuf;
+
+const PROTOS: &[&str] = &[
+    "proto/file.proto",
+    "proto/file_system.proto",
+    "proto/device.proto",
+    "proto/device_manager.proto",
+    "proto/domain_manager.proto",
+    "proto/registrar.proto",
+    "proto/log_service.proto",
+    "proto/event_channel.proto",
+];
+
+/// The messages the REST gateway documents in its OpenAPI spec.
+const SCHEMAS: &[&str] = &[
+    "device.Property",
+    "domain_manager.ApplicationInfo",
+    "domain_manager.ApplicationFactoryInfo",
+    "domain_manager.InstallApplicationRequest",
+    "domain_manager.InstallApplicationReply",
+    "domain_manager.DeviceAssignment",
+    "domain_manager.CreateApplicationRequest",
+    "domain_manager.CreateApplicationReply",
+    "file_system.FileInformation",
+    "file_system.WriteReply",
+    "file_system.ChunkRange",
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::compile_protos("proto/file.proto")?;
+    // The protoc given by the environment prevails over the vendored one
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    let descriptor_path = PathBuf::from(std::env::var("OUT_DIR")?).join("descriptors.bin");
+    let mut builder = tonic_build::configure().file_descriptor_set_path(&descriptor_path);
+    for schema in SCHEMAS {
+        builder = builder.type_attribute(
+            schema,
+            "#[cfg_attr(feature = \"rest-gateway\", derive(utoipa::ToSchema))]",
+        );
+    }
+    // The JSON mapping of the enums is their name, of the 64-bit integers a string
+    let as_string = "#[cfg_attr(feature = \"rest-gateway\", schema(value_type = String))]";
+    builder
+        // The chunks read are slices of the pooled buffers of the files
+        .bytes([".file_system.FileChunk.data"])
+        .field_attribute("file_system.FileInformation.kind", as_string)
+        .field_attribute("file_system.FileInformation.size", as_string)
+        .field_attribute("file_system.WriteReply.size", as_string)
+        .field_attribute("file_system.ChunkRange.offset", as_string)
+        .field_attribute("file_system.ChunkRange.size", as_string)
+        .field_attribute(
+            "device.Property.value",
+            "#[cfg_attr(feature = \"rest-gateway\", schema(value_type = Object))]",
+        )
+        .compile(PROTOS, &["proto"])?;
+
+    // The property values being JSON documents, device.Property is mapped by hand
+    let descriptors = std::fs::read(descriptor_path)?;
+    pbjson_build::Builder::new()
+        .register_descriptors(&descriptors)?
+        .preserve_proto_field_names()
+        .emit_fields()
+        .exclude([".device.Property"])
+        .build(&[
+            ".file",
+            ".file_system",
+            ".device",
+            ".device_manager",
+            ".domain_manager",
+            ".registrar",
+            ".log_service",
+            ".event_channel",
+        ])?;
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,96 @@
+/*
+ * C API of the scars file system, file, property set and resource
+ * operations.
+ *
+ * The objects are opaque handles created and freed by the API. The
+ * operations return SCARS_OK on success, or else the CF ErrorNumberType
+ * of the error, its message being kept for the calling thread by
+ * scars_last_error_message. The strings are NUL terminated and UTF-8
+ * encoded.
+ */
+#ifndef SCARS_H
+#define SCARS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* CF::ErrorNumberType, in the order of the IDL */
+typedef enum {
+    CF_NOTSET, CF_E2BIG, CF_EACCES, CF_EAGAIN, CF_EBADF, CF_EBADMSG, CF_EBUSY, CF_ECANCELED,
+    CF_ECHILD, CF_EDEADLK, CF_EDOM, CF_EEXIST, CF_EFAULT, CF_EFBIG, CF_EINPROGRESS, CF_EINTR,
+    CF_EINVAL, CF_EIO, CF_EISDIR, CF_EMFILE, CF_EMLINK, CF_EMSGSIZE, CF_ENAMETOOLONG,
+    CF_ENFILE, CF_ENODEV, CF_ENOENT, CF_ENOEXEC, CF_ENOLCK, CF_ENOMEM, CF_ENOSPC, CF_ENOSYS,
+    CF_ENOTDIR, CF_ENOTEMPTY, CF_ENOTSUP, CF_ENOTTY, CF_ENXIO, CF_EPERM, CF_EPIPE, CF_ERANGE,
+    CF_EROFS, CF_ESPIPE, CF_ESRCH, CF_ETIMEDOUT, CF_EXDEV
+} scars_error_number_t;
+
+#define SCARS_OK CF_NOTSET
+
+typedef struct ScarsFileSystem scars_file_system_t;
+typedef struct ScarsFile scars_file_t;
+typedef struct ScarsProperties scars_properties_t;
+typedef struct ScarsResource scars_resource_t;
+
+/* Returns the message of the last error of the calling thread, valid until its next error. */
+const char *scars_last_error_message(void);
+
+/* File system over a local directory, of absolute pathnames. */
+int scars_file_system_new(const char *root, scars_file_system_t **file_system);
+void scars_file_system_free(scars_file_system_t *file_system);
+int scars_file_system_exists(scars_file_system_t *file_system, const char *file_name, bool *exists);
+int scars_file_system_remove(scars_file_system_t *file_system, const char *file_name);
+int scars_file_system_copy(scars_file_system_t *file_system, const char *source_file_name, const char *destination_file_name);
+int scars_file_system_mkdir(scars_file_system_t *file_system, const char *directory_name);
+int scars_file_system_rmdir(scars_file_system_t *file_system, const char *directory_name);
+
+/* File open on a file system for reading, or created, truncated, for writing when create is set; closing frees it. */
+int scars_file_open(scars_file_system_t *file_system, const char *file_name, bool create, scars_file_t **file);
+int scars_file_close(scars_file_t *file);
+int scars_file_read(scars_file_t *file, uint8_t *buffer, size_t length, size_t *read);
+int scars_file_write(scars_file_t *file, const uint8_t *data, size_t length);
+int scars_file_size(scars_file_t *file, uint64_t *size);
+int scars_file_pointer(scars_file_t *file, uint64_t *file_pointer);
+int scars_file_set_pointer(scars_file_t *file, uint64_t file_pointer);
+
+/*
+ * Property set. The strings are copied into buffers of a size, NUL
+ * terminated, their length returned even when truncated.
+ */
+scars_properties_t *scars_properties_new(void);
+void scars_properties_free(scars_properties_t *properties);
+int scars_properties_length(scars_properties_t *properties, size_t *length);
+int scars_properties_id(scars_properties_t *properties, size_t index, char *buffer, size_t size, size_t *length);
+int scars_properties_set_boolean(scars_properties_t *properties, const char *id, bool value);
+int scars_properties_set_long(scars_properties_t *properties, const char *id, int32_t value);
+int scars_properties_set_double(scars_properties_t *properties, const char *id, double value);
+int scars_properties_set_string(scars_properties_t *properties, const char *id, const char *value);
+int scars_properties_get_boolean(scars_properties_t *properties, const char *id, bool *value);
+int scars_properties_get_long(scars_properties_t *properties, const char *id, int32_t *value);
+int scars_properties_get_double(scars_properties_t *properties, const char *id, double *value);
+int scars_properties_get_string(scars_properties_t *properties, const char *id, char *buffer, size_t size, size_t *length);
+
+/*
+ * Resource hosted by the component, of the properties of a set, if
+ * any. The query replaces the values of the properties of the set, all
+ * of them being returned when it is empty.
+ */
+int scars_resource_new(const char *identifier, scars_properties_t *properties, scars_resource_t **resource);
+void scars_resource_free(scars_resource_t *resource);
+int scars_resource_initialize(scars_resource_t *resource);
+int scars_resource_release_object(scars_resource_t *resource);
+int scars_resource_start(scars_resource_t *resource);
+int scars_resource_stop(scars_resource_t *resource);
+int scars_resource_started(scars_resource_t *resource, bool *started);
+int scars_resource_configure(scars_resource_t *resource, scars_properties_t *properties);
+int scars_resource_query(scars_resource_t *resource, scars_properties_t *properties);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SCARS_H */
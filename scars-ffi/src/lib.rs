@@ -0,0 +1,626 @@
+//! C API of the scars file system, file, property set and resource
+//! operations, for the components written in C or C++ on the platform.
+//!
+//! The objects are opaque handles created and freed by the API. The
+//! operations return 0 on success, or else the ErrorNumberType of the
+//! error as its CF ordinal, its message being kept for the calling
+//! thread by scars_last_error_message. The pointers given must be
+//! valid for the duration of the call, the strings NUL terminated and
+//! UTF-8 encoded, and a handle must not be used after being freed.
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::fmt::Display;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType, Properties};
+use scars::cf::file::{File, FileError, FileTrait};
+use scars::cf::file_system::{relative_path, FileSystem, FileSystemError, FileSystemTrait};
+use scars::cf::resource::{Resource, ResourceError, ResourceTrait};
+
+/// The return of the operations succeeding, CF_NOTSET.
+pub const SCARS_OK: c_int = 0;
+
+thread_local! {
+    static LAST_ERROR_MESSAGE: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/**
+ * The error of an operation: its ErrorNumberType and message.
+ */
+struct FfiError {
+    error_number: ErrorNumberType,
+    message: String,
+}
+
+type FfiResult<T> = Result<T, FfiError>;
+
+impl FfiError {
+    fn new(error_number: ErrorNumberType, message: &str) -> FfiError {
+        FfiError {
+            error_number,
+            message: message.to_string(),
+        }
+    }
+}
+
+/**
+ * This trait gives the ErrorNumberType an error is returned as.
+ */
+trait ErrorNumber: Display {
+    fn error_number(&self) -> ErrorNumberType;
+}
+
+impl<E: ErrorNumber> From<E> for FfiError {
+    fn from(error: E) -> Self {
+        FfiError::new(error.error_number(), &error.to_string())
+    }
+}
+
+impl ErrorNumber for FileSystemError {
+    fn error_number(&self) -> ErrorNumberType {
+        match self {
+            FileSystemError::InvalidFileName { error_number, .. }
+            | FileSystemError::FileException { error_number, .. } => *error_number,
+        }
+    }
+}
+
+impl ErrorNumber for FileError {
+    fn error_number(&self) -> ErrorNumberType {
+        match self {
+            FileError::FileException { error_number, .. }
+            | FileError::IOException { error_number, .. } => *error_number,
+            FileError::InvalidFilePointer => ErrorNumberType::CF_EINVAL,
+        }
+    }
+}
+
+/// The resources not in the state of an operation return CF_EPERM, the invalid properties and ports CF_EINVAL.
+impl ErrorNumber for ResourceError {
+    fn error_number(&self) -> ErrorNumberType {
+        match self {
+            ResourceError::InvalidState { .. } => ErrorNumberType::CF_EPERM,
+            ResourceError::InitializeError { .. } | ResourceError::ReleaseError { .. } => {
+                ErrorNumberType::CF_EIO
+            }
+            ResourceError::StartError { error_number, .. }
+            | ResourceError::StopError { error_number, .. } => *error_number,
+            ResourceError::InvalidConfiguration { .. }
+            | ResourceError::PartialConfiguration { .. }
+            | ResourceError::UnknownProperties { .. }
+            | ResourceError::UnknownPort { .. }
+            | ResourceError::InvalidPort { .. } => ErrorNumberType::CF_EINVAL,
+        }
+    }
+}
+
+/// Runs an operation, returning its ErrorNumberType, a panic being an I/O error.
+fn call(operation: impl FnOnce() -> FfiResult<()>) -> c_int {
+    let error = match catch_unwind(AssertUnwindSafe(operation)) {
+        Ok(Ok(())) => return SCARS_OK,
+        Ok(Err(error)) => error,
+        Err(_) => FfiError::new(ErrorNumberType::CF_EIO, "internal error"),
+    };
+    let message = CString::new(error.message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR_MESSAGE.with(|m| *m.borrow_mut() = message);
+    error.error_number as c_int
+}
+
+unsafe fn string<'a>(s: *const c_char) -> FfiResult<&'a str> {
+    if s.is_null() {
+        return Err(FfiError::new(ErrorNumberType::CF_EINVAL, "null string"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| FfiError::new(ErrorNumberType::CF_EINVAL, &e.to_string()))
+}
+
+unsafe fn handle<'a, T>(handle: *mut T) -> FfiResult<&'a mut T> {
+    handle
+        .as_mut()
+        .ok_or_else(|| FfiError::new(ErrorNumberType::CF_EBADF, "null handle"))
+}
+
+unsafe fn output<'a, T>(output: *mut T) -> FfiResult<&'a mut T> {
+    output
+        .as_mut()
+        .ok_or_else(|| FfiError::new(ErrorNumberType::CF_EINVAL, "null output"))
+}
+
+/**
+ * Copies a string NUL terminated into a buffer of a size, its length
+ * returned even when truncated, so that the buffer can be grown.
+ */
+unsafe fn copy_string(
+    s: &str,
+    buffer: *mut c_char,
+    size: usize,
+    length: *mut usize,
+) -> FfiResult<()> {
+    *output(length)? = s.len();
+    if !buffer.is_null() && size > 0 {
+        let copied = s.len().min(size - 1);
+        std::ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, buffer, copied);
+        *buffer.add(copied) = 0;
+    }
+    Ok(())
+}
+
+/// Returns the message of the last error of the calling thread, valid until its next error.
+#[no_mangle]
+pub extern "C" fn scars_last_error_message() -> *const c_char {
+    LAST_ERROR_MESSAGE.with(|m| m.borrow().as_ptr())
+}
+
+/**
+ * File system over a local directory.
+ */
+pub struct ScarsFileSystem(FileSystem);
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_new(
+    root: *const c_char,
+    file_system: *mut *mut ScarsFileSystem,
+) -> c_int {
+    call(|| {
+        let root = Path::new(string(root)?);
+        if !root.is_dir() {
+            return Err(FfiError::new(
+                ErrorNumberType::CF_ENOTDIR,
+                &format!("'{}' is not a directory", root.display()),
+            ));
+        }
+        *output(file_system)? = Box::into_raw(Box::new(ScarsFileSystem(FileSystem::new(root))));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_free(file_system: *mut ScarsFileSystem) {
+    if !file_system.is_null() {
+        drop(Box::from_raw(file_system));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_exists(
+    file_system: *mut ScarsFileSystem,
+    file_name: *const c_char,
+    exists: *mut bool,
+) -> c_int {
+    call(|| {
+        *output(exists)? = handle(file_system)?.0.exists(string(file_name)?)?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_remove(
+    file_system: *mut ScarsFileSystem,
+    file_name: *const c_char,
+) -> c_int {
+    call(|| Ok(handle(file_system)?.0.remove(string(file_name)?)?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_copy(
+    file_system: *mut ScarsFileSystem,
+    source_file_name: *const c_char,
+    destination_file_name: *const c_char,
+) -> c_int {
+    call(|| {
+        Ok(handle(file_system)?
+            .0
+            .copy(string(source_file_name)?, string(destination_file_name)?)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_mkdir(
+    file_system: *mut ScarsFileSystem,
+    directory_name: *const c_char,
+) -> c_int {
+    call(|| Ok(handle(file_system)?.0.mkdir(string(directory_name)?)?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_system_rmdir(
+    file_system: *mut ScarsFileSystem,
+    directory_name: *const c_char,
+) -> c_int {
+    call(|| Ok(handle(file_system)?.0.rmdir(string(directory_name)?)?))
+}
+
+/// File open on a file system.
+pub struct ScarsFile {
+    file: File,
+}
+
+/**
+ * Opens a file of a file system for reading from its start, or creates
+ * it, truncated, for writing when create is set.
+ */
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_open(
+    file_system: *mut ScarsFileSystem,
+    file_name: *const c_char,
+    create: bool,
+    file: *mut *mut ScarsFile,
+) -> c_int {
+    call(|| {
+        let file_system = handle(file_system)?;
+        let relative_name = relative_path(string(file_name)?)?.display().to_string();
+        let opened = match create {
+            true => File::create(relative_name, file_system.0.root())?,
+            false => File::open(relative_name, file_system.0.root())?,
+        };
+        *output(file)? = Box::into_raw(Box::new(ScarsFile { file: opened }));
+        Ok(())
+    })
+}
+
+/// Closes a file and frees its handle.
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_close(file: *mut ScarsFile) -> c_int {
+    call(|| {
+        handle(file)?;
+        let mut file = Box::from_raw(file);
+        Ok(file.file.close()?)
+    })
+}
+
+/// Reads up to length octets, returning the number read, 0 at the end of the file.
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_read(
+    file: *mut ScarsFile,
+    buffer: *mut u8,
+    length: usize,
+    read: *mut usize,
+) -> c_int {
+    call(|| {
+        let file = handle(file)?;
+        let read = output(read)?;
+        let mut data = vec![0u8; length];
+        let count = file.file.read(&mut data)?;
+        if count > 0 {
+            std::ptr::copy_nonoverlapping(data.as_ptr(), output(buffer)?, count);
+        }
+        *read = count;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_write(
+    file: *mut ScarsFile,
+    data: *const u8,
+    length: usize,
+) -> c_int {
+    call(|| {
+        let file = handle(file)?;
+        let data = match length {
+            0 => &[][..],
+            _ if data.is_null() => {
+                return Err(FfiError::new(ErrorNumberType::CF_EINVAL, "null data"))
+            }
+            _ => std::slice::from_raw_parts(data, length),
+        };
+        Ok(file.file.write(data)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_size(file: *mut ScarsFile, size: *mut u64) -> c_int {
+    call(|| {
+        *output(size)? = handle(file)?.file.size_of()?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_pointer(file: *mut ScarsFile, file_pointer: *mut u64) -> c_int {
+    call(|| {
+        *output(file_pointer)? = handle(file)?.file.file_pointer();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_file_set_pointer(file: *mut ScarsFile, file_pointer: u64) -> c_int {
+    call(|| Ok(handle(file)?.file.set_file_pointer(file_pointer)?))
+}
+
+/**
+ * Property set: the id/value pairs configured and queried.
+ */
+pub struct ScarsProperties(Properties);
+
+impl ScarsProperties {
+    fn set(&mut self, id: &str, value: AnyValue) {
+        match self.0.iter_mut().find(|p| p.id == id) {
+            Some(property) => property.value = value,
+            None => self.0.push(DataType::new(id, value)),
+        }
+    }
+
+    fn get(&self, id: &str) -> FfiResult<&AnyValue> {
+        self.0
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| &p.value)
+            .ok_or_else(|| {
+                FfiError::new(
+                    ErrorNumberType::CF_ENOENT,
+                    &format!("unknown property '{id}'"),
+                )
+            })
+    }
+}
+
+fn wrong_type(id: &str, value: &AnyValue) -> FfiError {
+    FfiError::new(
+        ErrorNumberType::CF_EINVAL,
+        &format!("property '{id}' is {value:?}"),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn scars_properties_new() -> *mut ScarsProperties {
+    Box::into_raw(Box::new(ScarsProperties(Properties::new())))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_free(properties: *mut ScarsProperties) {
+    if !properties.is_null() {
+        drop(Box::from_raw(properties));
+    }
+}
+
+/// Returns the number of properties of the set.
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_length(
+    properties: *mut ScarsProperties,
+    length: *mut usize,
+) -> c_int {
+    call(|| {
+        *output(length)? = handle(properties)?.0.len();
+        Ok(())
+    })
+}
+
+/// Copies the id of the property at an index.
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_id(
+    properties: *mut ScarsProperties,
+    index: usize,
+    buffer: *mut c_char,
+    size: usize,
+    length: *mut usize,
+) -> c_int {
+    call(|| {
+        let property = handle(properties)?.0.get(index).ok_or_else(|| {
+            FfiError::new(
+                ErrorNumberType::CF_ERANGE,
+                &format!("no property at {index}"),
+            )
+        })?;
+        copy_string(&property.id, buffer, size, length)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_set_boolean(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: bool,
+) -> c_int {
+    call(|| {
+        handle(properties)?.set(string(id)?, AnyValue::Boolean(value));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_set_long(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: i32,
+) -> c_int {
+    call(|| {
+        handle(properties)?.set(string(id)?, AnyValue::Long(value));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_set_double(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: f64,
+) -> c_int {
+    call(|| {
+        handle(properties)?.set(string(id)?, AnyValue::Double(value));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_set_string(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: *const c_char,
+) -> c_int {
+    call(|| {
+        handle(properties)?.set(string(id)?, AnyValue::String(string(value)?.to_string()));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_get_boolean(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: *mut bool,
+) -> c_int {
+    call(|| {
+        let id = string(id)?;
+        match handle(properties)?.get(id)? {
+            AnyValue::Boolean(v) => *output(value)? = *v,
+            other => return Err(wrong_type(id, other)),
+        }
+        Ok(())
+    })
+}
+
+/// Gets an integer property, of any integer type fitting in a long.
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_get_long(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: *mut i32,
+) -> c_int {
+    call(|| {
+        let id = string(id)?;
+        let v = match handle(properties)?.get(id)? {
+            AnyValue::Octet(v) => *v as i32,
+            AnyValue::Short(v) => *v as i32,
+            AnyValue::UShort(v) => *v as i32,
+            AnyValue::Long(v) => *v,
+            AnyValue::ULong(v) => {
+                i32::try_from(*v).map_err(|_| FfiError::new(ErrorNumberType::CF_ERANGE, id))?
+            }
+            AnyValue::LongLong(v) => {
+                i32::try_from(*v).map_err(|_| FfiError::new(ErrorNumberType::CF_ERANGE, id))?
+            }
+            AnyValue::ULongLong(v) => {
+                i32::try_from(*v).map_err(|_| FfiError::new(ErrorNumberType::CF_ERANGE, id))?
+            }
+            other => return Err(wrong_type(id, other)),
+        };
+        *output(value)? = v;
+        Ok(())
+    })
+}
+
+/// Gets a numeric property as a double.
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_get_double(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    value: *mut f64,
+) -> c_int {
+    call(|| {
+        let id = string(id)?;
+        let property = handle(properties)?.get(id)?;
+        *output(value)? = property.as_f64().ok_or_else(|| wrong_type(id, property))?;
+        Ok(())
+    })
+}
+
+/// Copies a string property into a buffer of a size, returning its length.
+#[no_mangle]
+pub unsafe extern "C" fn scars_properties_get_string(
+    properties: *mut ScarsProperties,
+    id: *const c_char,
+    buffer: *mut c_char,
+    size: usize,
+    length: *mut usize,
+) -> c_int {
+    call(|| {
+        let id = string(id)?;
+        match handle(properties)?.get(id)? {
+            AnyValue::String(v) => copy_string(v, buffer, size, length),
+            other => Err(wrong_type(id, other)),
+        }
+    })
+}
+
+/**
+ * Resource hosted by the component, its properties stored and queried
+ * through its property set.
+ */
+pub struct ScarsResource(Resource);
+
+/// Creates a resource with the properties of a set, if any, as initial values.
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_new(
+    identifier: *const c_char,
+    properties: *mut ScarsProperties,
+    resource: *mut *mut ScarsResource,
+) -> c_int {
+    call(|| {
+        let mut created = Resource::new(string(identifier)?);
+        if !properties.is_null() {
+            for property in &handle(properties)?.0 {
+                created = created.with_property(&property.id, property.value.clone());
+            }
+        }
+        *output(resource)? = Box::into_raw(Box::new(ScarsResource(created)));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_free(resource: *mut ScarsResource) {
+    if !resource.is_null() {
+        drop(Box::from_raw(resource));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_initialize(resource: *mut ScarsResource) -> c_int {
+    call(|| Ok(handle(resource)?.0.initialize()?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_release_object(resource: *mut ScarsResource) -> c_int {
+    call(|| Ok(handle(resource)?.0.release_object()?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_start(resource: *mut ScarsResource) -> c_int {
+    call(|| Ok(handle(resource)?.0.start()?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_stop(resource: *mut ScarsResource) -> c_int {
+    call(|| Ok(handle(resource)?.0.stop()?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_started(
+    resource: *mut ScarsResource,
+    started: *mut bool,
+) -> c_int {
+    call(|| {
+        *output(started)? = handle(resource)?.0.started();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_configure(
+    resource: *mut ScarsResource,
+    properties: *mut ScarsProperties,
+) -> c_int {
+    call(|| {
+        let properties = &handle(properties)?.0;
+        Ok(handle(resource)?.0.configure(properties)?)
+    })
+}
+
+/// Queries the properties of a set, replacing their values, all of them when the set is empty.
+#[no_mangle]
+pub unsafe extern "C" fn scars_resource_query(
+    resource: *mut ScarsResource,
+    properties: *mut ScarsProperties,
+) -> c_int {
+    call(|| {
+        let properties = handle(properties)?;
+        properties.0 = handle(resource)?.0.query(&properties.0)?;
+        Ok(())
+    })
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    use scars::cf::common_types::ErrorNumberType;
+    use scars_ffi::*;
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    fn last_error_message() -> String {
+        unsafe { CStr::from_ptr(scars_last_error_message()) }.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_file_system() {
+        let root = tempfile::tempdir().unwrap();
+        unsafe {
+            let mut fs = ptr::null_mut();
+            assert_eq!(scars_file_system_new(c(root.path().to_str().unwrap()).as_ptr(), &mut fs), SCARS_OK);
+            assert_eq!(scars_file_system_mkdir(fs, c("/data").as_ptr()), SCARS_OK);
+
+            //a file written, read back and copied
+            let mut file = ptr::null_mut();
+            assert_eq!(scars_file_open(fs, c("/data/samples.bin").as_ptr(), true, &mut file), SCARS_OK);
+            assert_eq!(scars_file_write(file, b"0123456789".as_ptr(), 10), SCARS_OK);
+            let (mut size, mut pointer) = (0u64, 0u64);
+            assert_eq!(scars_file_size(file, &mut size), SCARS_OK);
+            assert_eq!(scars_file_pointer(file, &mut pointer), SCARS_OK);
+            assert_eq!((size, pointer), (10, 10));
+            assert_eq!(scars_file_close(file), SCARS_OK);
+            assert_eq!(scars_file_open(fs, c("/data/samples.bin").as_ptr(), false, &mut file), SCARS_OK);
+            assert_eq!(scars_file_set_pointer(file, 4), SCARS_OK);
+            let (mut buffer, mut read) = ([0u8; 8], 0usize);
+            assert_eq!(scars_file_read(file, buffer.as_mut_ptr(), buffer.len(), &mut read), SCARS_OK);
+            assert_eq!(&buffer[..read], b"456789");
+            assert_eq!(scars_file_set_pointer(file, 11), ErrorNumberType::CF_EINVAL as i32);
+            assert_eq!(scars_file_close(file), SCARS_OK);
+
+            assert_eq!(scars_file_system_copy(fs, c("/data/samples.bin").as_ptr(), c("/data/copy.bin").as_ptr()), SCARS_OK);
+            assert_eq!(std::fs::read(root.path().join("data/copy.bin")).unwrap(), b"0123456789");
+            let mut exists = false;
+            assert_eq!(scars_file_system_exists(fs, c("/data/copy.bin").as_ptr(), &mut exists), SCARS_OK);
+            assert!(exists);
+            assert_eq!(scars_file_system_remove(fs, c("/data/copy.bin").as_ptr()), SCARS_OK);
+
+            //the errors are returned as their ErrorNumberType with a message
+            let mut missing = ptr::null_mut();
+            assert_eq!(scars_file_open(fs, c("/data/missing.bin").as_ptr(), false, &mut missing), ErrorNumberType::CF_ENOENT as i32);
+            assert!(missing.is_null());
+            assert_eq!(scars_file_open(fs, c("/../etc/passwd").as_ptr(), false, &mut missing), ErrorNumberType::CF_EINVAL as i32);
+            assert!(last_error_message().contains("escapes the file system"));
+            assert_eq!(scars_file_system_exists(fs, ptr::null(), &mut exists), ErrorNumberType::CF_EINVAL as i32);
+            assert_eq!(scars_file_read(ptr::null_mut(), buffer.as_mut_ptr(), 1, &mut read), ErrorNumberType::CF_EBADF as i32);
+            scars_file_system_free(fs);
+        }
+    }
+
+    #[test]
+    fn test_resource() {
+        unsafe {
+            let properties = scars_properties_new();
+            assert_eq!(scars_properties_set_double(properties, c("frequency").as_ptr(), 1000.0), SCARS_OK);
+            assert_eq!(scars_properties_set_string(properties, c("shape").as_ptr(), c("sine").as_ptr()), SCARS_OK);
+            let mut resource = ptr::null_mut();
+            assert_eq!(scars_resource_new(c("DCE:siggen").as_ptr(), properties, &mut resource), SCARS_OK);
+            scars_properties_free(properties);
+
+            //the resource is configured and queried through property sets
+            assert_eq!(scars_resource_initialize(resource), SCARS_OK);
+            let configured = scars_properties_new();
+            assert_eq!(scars_properties_set_double(configured, c("frequency").as_ptr(), 2000.0), SCARS_OK);
+            assert_eq!(scars_resource_configure(resource, configured), SCARS_OK);
+            scars_properties_free(configured);
+            let queried = scars_properties_new();
+            assert_eq!(scars_resource_query(resource, queried), SCARS_OK);
+            let mut length = 0usize;
+            assert_eq!(scars_properties_length(queried, &mut length), SCARS_OK);
+            assert!(length >= 2);
+            let mut frequency = 0.0;
+            assert_eq!(scars_properties_get_double(queried, c("frequency").as_ptr(), &mut frequency), SCARS_OK);
+            assert_eq!(frequency, 2000.0);
+            let mut buffer = [0 as c_char; 4];
+            assert_eq!(scars_properties_get_string(queried, c("shape").as_ptr(), buffer.as_mut_ptr(), buffer.len(), &mut length), SCARS_OK);
+            assert_eq!((CStr::from_ptr(buffer.as_ptr()).to_str().unwrap(), length), ("sin", 4));
+            let mut id = [0 as c_char; 16];
+            assert_eq!(scars_properties_id(queried, 0, id.as_mut_ptr(), id.len(), &mut length), SCARS_OK);
+            assert_eq!(CStr::from_ptr(id.as_ptr()).to_str().unwrap(), "frequency");
+            let mut long = 0;
+            assert_eq!(scars_properties_get_long(queried, c("shape").as_ptr(), &mut long), ErrorNumberType::CF_EINVAL as i32);
+            assert_eq!(scars_properties_get_long(queried, c("amplitude").as_ptr(), &mut long), ErrorNumberType::CF_ENOENT as i32);
+            scars_properties_free(queried);
+
+            let mut started = false;
+            assert_eq!(scars_resource_start(resource), SCARS_OK);
+            assert_eq!(scars_resource_started(resource, &mut started), SCARS_OK);
+            assert!(started);
+            assert_eq!(scars_resource_stop(resource), SCARS_OK);
+            assert_eq!(scars_resource_release_object(resource), SCARS_OK);
+            assert_eq!(scars_resource_start(resource), ErrorNumberType::CF_EPERM as i32);
+            scars_resource_free(resource);
+        }
+    }
+}
@@ -0,0 +1,774 @@
+//! Python module of the scars sandbox, domain and file system clients,
+//! for the test teams scripting the verification of their waveforms.
+//!
+//! The property values are converted to the Python values of their
+//! type, the structs to Properties, and back from the Python values
+//! with their type inferred: Long, LongLong or ULongLong for the
+//! integers, Double for the floats, Sequence for the lists and Struct
+//! for the dicts. An AnyValue gives the value of another type, a plain
+//! value set on a property of a set taking the type of its former one.
+//! The errors are raised as ScarsError, with the message of the error.
+
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyKeyError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use pyo3::IntoPyObjectExt;
+use tokio::runtime::Runtime;
+use tonic::transport::Channel;
+
+use scars::cf::common_types::{AnyValue, DataType, Properties};
+use scars::cf::file_system::FileSystem;
+use scars::cf::file_transfer::{self, TransferTuning};
+use scars::cf::profile::sad::PortKind;
+use scars::cf::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use scars::cf::rpc::domain_manager::{
+    ApplicationFactoriesRequest, ApplicationsRequest, ConfigureApplicationRequest,
+    CreateApplicationRequest, InstallApplicationRequest, QueryApplicationRequest,
+    ReleaseApplicationRequest, StartApplicationRequest, StopApplicationRequest,
+    UninstallApplicationRequest,
+};
+use scars::cf::rpc::file_system::file_system_client::FileSystemClient;
+use scars::cf::rpc::file_system::{
+    CopyRequest, FileType, ListRequest, MkdirRequest, MoveRequest, RemoveRequest, RmdirRequest,
+};
+use scars::cf::rpc::{properties_from_wire, properties_to_wire};
+use scars::cf::sandbox::Sandbox;
+
+create_exception!(
+    scars,
+    ScarsError,
+    PyException,
+    "The error of a scars operation."
+);
+
+fn error(error: impl std::fmt::Display) -> PyErr {
+    ScarsError::new_err(error.to_string())
+}
+
+/// The message of the error of a call to a service, of its status.
+fn status_message(status: tonic::Status) -> String {
+    format!("{:?}: {}", status.code(), status.message())
+}
+
+/// The type names of the AnyValue variants, in their order.
+const KINDS: &[&str] = &[
+    "boolean",
+    "octet",
+    "short",
+    "ushort",
+    "long",
+    "ulong",
+    "longlong",
+    "ulonglong",
+    "float",
+    "double",
+    "string",
+    "sequence",
+    "struct",
+];
+
+fn kind_of(value: &AnyValue) -> &'static str {
+    let index = match value {
+        AnyValue::Boolean(_) => 0,
+        AnyValue::Octet(_) => 1,
+        AnyValue::Short(_) => 2,
+        AnyValue::UShort(_) => 3,
+        AnyValue::Long(_) => 4,
+        AnyValue::ULong(_) => 5,
+        AnyValue::LongLong(_) => 6,
+        AnyValue::ULongLong(_) => 7,
+        AnyValue::Float(_) => 8,
+        AnyValue::Double(_) => 9,
+        AnyValue::String(_) => 10,
+        AnyValue::Sequence(_) => 11,
+        AnyValue::Struct(_) => 12,
+    };
+    KINDS[index]
+}
+
+/// Converts a Python value to an AnyValue of a type.
+fn typed_value(kind: &str, value: &Bound<PyAny>) -> PyResult<AnyValue> {
+    Ok(match kind {
+        "boolean" => AnyValue::Boolean(value.extract()?),
+        "octet" => AnyValue::Octet(value.extract()?),
+        "short" => AnyValue::Short(value.extract()?),
+        "ushort" => AnyValue::UShort(value.extract()?),
+        "long" => AnyValue::Long(value.extract()?),
+        "ulong" => AnyValue::ULong(value.extract()?),
+        "longlong" => AnyValue::LongLong(value.extract()?),
+        "ulonglong" => AnyValue::ULongLong(value.extract()?),
+        "float" => AnyValue::Float(value.extract()?),
+        "double" => AnyValue::Double(value.extract()?),
+        "string" => AnyValue::String(value.extract()?),
+        "sequence" => AnyValue::Sequence(
+            value
+                .try_iter()?
+                .map(|item| to_any(&item?))
+                .collect::<PyResult<_>>()?,
+        ),
+        "struct" => AnyValue::Struct(to_properties(value)?),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unknown kind '{kind}', expected one of {}",
+                KINDS.join(", ")
+            )))
+        }
+    })
+}
+
+/// Converts a Python value to an AnyValue, of the inferred type unless an AnyValue.
+fn to_any(value: &Bound<PyAny>) -> PyResult<AnyValue> {
+    if let Ok(value) = value.downcast::<PyAnyValue>() {
+        return Ok(value.borrow().0.clone());
+    }
+    if value.is_instance_of::<PyBool>() {
+        return Ok(AnyValue::Boolean(value.extract()?));
+    }
+    if value.is_instance_of::<PyInt>() {
+        return value
+            .extract()
+            .map(AnyValue::Long)
+            .or_else(|_| value.extract().map(AnyValue::LongLong))
+            .or_else(|_| value.extract().map(AnyValue::ULongLong));
+    }
+    if value.is_instance_of::<PyFloat>() {
+        return Ok(AnyValue::Double(value.extract()?));
+    }
+    if value.is_instance_of::<PyString>() {
+        return Ok(AnyValue::String(value.extract()?));
+    }
+    if value.is_instance_of::<PyDict>() || value.is_instance_of::<PyProperties>() {
+        return Ok(AnyValue::Struct(to_properties(value)?));
+    }
+    if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        return typed_value("sequence", value);
+    }
+    Err(PyTypeError::new_err(format!(
+        "no property value of type '{}'",
+        value.get_type().name()?
+    )))
+}
+
+/// Converts a Python value to an AnyValue, a plain simple one taking the type of a former value.
+fn to_any_like(value: &Bound<PyAny>, like: Option<&AnyValue>) -> PyResult<AnyValue> {
+    match like {
+        Some(like) if like.is_simple() && !value.is_instance_of::<PyAnyValue>() => {
+            typed_value(kind_of(like), value)
+        }
+        _ => to_any(value),
+    }
+}
+
+/// Converts an AnyValue to its Python value.
+fn to_py(py: Python, value: &AnyValue) -> PyResult<PyObject> {
+    Ok(match value {
+        AnyValue::Boolean(v) => v.into_py_any(py)?,
+        AnyValue::Octet(v) => v.into_py_any(py)?,
+        AnyValue::Short(v) => v.into_py_any(py)?,
+        AnyValue::UShort(v) => v.into_py_any(py)?,
+        AnyValue::Long(v) => v.into_py_any(py)?,
+        AnyValue::ULong(v) => v.into_py_any(py)?,
+        AnyValue::LongLong(v) => v.into_py_any(py)?,
+        AnyValue::ULongLong(v) => v.into_py_any(py)?,
+        AnyValue::Float(v) => v.into_py_any(py)?,
+        AnyValue::Double(v) => v.into_py_any(py)?,
+        AnyValue::String(v) => v.into_py_any(py)?,
+        AnyValue::Sequence(v) => {
+            let items = v
+                .iter()
+                .map(|i| to_py(py, i))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_py_any(py)?
+        }
+        AnyValue::Struct(v) => PyProperties(v.clone()).into_py_any(py)?,
+    })
+}
+
+/// Converts a Properties or a dict of property values to properties.
+fn to_properties(value: &Bound<PyAny>) -> PyResult<Properties> {
+    if let Ok(properties) = value.downcast::<PyProperties>() {
+        return Ok(properties.borrow().0.clone());
+    }
+    let dict = value.downcast::<PyDict>()?;
+    dict.iter()
+        .map(|(id, value)| Ok(DataType::new(&id.extract::<String>()?, to_any(&value)?)))
+        .collect()
+}
+
+/// Converts the optional properties of an operation, none when not given.
+fn optional_properties(value: Option<&Bound<PyAny>>) -> PyResult<Properties> {
+    value.map_or_else(|| Ok(Properties::new()), to_properties)
+}
+
+/**
+ * Property value of a type: AnyValue("ushort", 3) gives the value 3 as
+ * an unsigned short, rather than the Long it is inferred as.
+ */
+#[pyclass(name = "AnyValue", module = "scars")]
+#[derive(Clone)]
+pub struct PyAnyValue(pub AnyValue);
+
+#[pymethods]
+impl PyAnyValue {
+    #[new]
+    fn new(kind: &str, value: &Bound<PyAny>) -> PyResult<PyAnyValue> {
+        typed_value(kind, value).map(PyAnyValue)
+    }
+
+    /// The name of the type of the value, e.g. "ushort".
+    #[getter]
+    fn kind(&self) -> &'static str {
+        kind_of(&self.0)
+    }
+
+    #[getter]
+    fn value(&self, py: Python) -> PyResult<PyObject> {
+        to_py(py, &self.0)
+    }
+
+    fn __eq__(&self, other: &Bound<PyAny>) -> bool {
+        to_any(other).is_ok_and(|other| other == self.0)
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("AnyValue('{}', {})", kind_of(&self.0), self.0)
+    }
+}
+
+/**
+ * Property set, of the values of the properties by id in their order,
+ * as given to configure and returned by query.
+ */
+#[pyclass(name = "Properties", module = "scars", sequence)]
+#[derive(Clone, Default)]
+pub struct PyProperties(pub Properties);
+
+#[pymethods]
+impl PyProperties {
+    #[new]
+    #[pyo3(signature = (values=None))]
+    fn new(values: Option<&Bound<PyAny>>) -> PyResult<PyProperties> {
+        optional_properties(values).map(PyProperties)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __contains__(&self, id: &str) -> bool {
+        self.0.iter().any(|p| p.id == id)
+    }
+
+    fn __getitem__(&self, py: Python, id: &str) -> PyResult<PyObject> {
+        to_py(py, &self.any(id)?.0)
+    }
+
+    fn __setitem__(&mut self, id: &str, value: &Bound<PyAny>) -> PyResult<()> {
+        match self.0.iter_mut().find(|p| p.id == id) {
+            Some(property) => property.value = to_any_like(value, Some(&property.value))?,
+            None => self.0.push(DataType::new(id, to_any(value)?)),
+        }
+        Ok(())
+    }
+
+    fn __delitem__(&mut self, id: &str) -> PyResult<()> {
+        let index = self
+            .0
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| PyKeyError::new_err(id.to_string()))?;
+        self.0.remove(index);
+        Ok(())
+    }
+
+    fn __iter__(&self, py: Python) -> PyResult<PyObject> {
+        PyList::new(py, self.ids())?.try_iter()?.into_py_any(py)
+    }
+
+    fn __eq__(&self, other: &Bound<PyAny>) -> bool {
+        to_properties(other).is_ok_and(|other| other == self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        let items: Vec<String> = self
+            .0
+            .iter()
+            .map(|p| format!("'{}': {}", p.id, PyAnyValue(p.value.clone()).__repr__()))
+            .collect();
+        format!("Properties({{{}}})", items.join(", "))
+    }
+
+    /// Returns the ids of the properties, in their order.
+    fn ids(&self) -> Vec<String> {
+        self.0.iter().map(|p| p.id.clone()).collect()
+    }
+
+    /// Returns the value of a property as an AnyValue, of its type.
+    fn any(&self, id: &str) -> PyResult<PyAnyValue> {
+        self.0
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| PyAnyValue(p.value.clone()))
+            .ok_or_else(|| PyKeyError::new_err(id.to_string()))
+    }
+
+    /// Returns the values as a dict, the structs as dicts.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for property in &self.0 {
+            let value = match &property.value {
+                AnyValue::Struct(members) => PyProperties(members.clone()).to_dict(py)?.into_any(),
+                value => to_py(py, value)?.into_bound(py),
+            };
+            dict.set_item(&property.id, value)?;
+        }
+        Ok(dict)
+    }
+}
+
+/**
+ * Sandbox hosting components in-process, launched from their SPD in the
+ * SDR directory it is given, for their verification without a domain.
+ */
+#[pyclass(name = "Sandbox", module = "scars")]
+pub struct PySandbox {
+    file_system: FileSystem,
+    sandbox: Sandbox,
+}
+
+#[pymethods]
+impl PySandbox {
+    #[new]
+    fn new(sdr_root: &str) -> PySandbox {
+        PySandbox {
+            file_system: FileSystem::new(Path::new(sdr_root)),
+            sandbox: Sandbox::new(),
+        }
+    }
+
+    /// Launches a component from the pathname of its SPD, returning its name.
+    #[pyo3(signature = (spd_file_name, name=None))]
+    fn launch(&mut self, spd_file_name: &str, name: Option<&str>) -> PyResult<String> {
+        let component = self
+            .sandbox
+            .launch(&self.file_system, spd_file_name, name)
+            .map_err(error)?;
+        Ok(component.name.clone())
+    }
+
+    /// Returns the names of the components, in launch order.
+    fn components(&self) -> Vec<String> {
+        self.sandbox
+            .components()
+            .iter()
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    /// Returns the (name, "uses" or "provides", repository id) of the ports of a component.
+    fn ports(&self, name: &str) -> PyResult<Vec<(String, &'static str, String)>> {
+        let component = self.sandbox.component(name).map_err(error)?;
+        Ok(component
+            .ports
+            .iter()
+            .map(|p| {
+                let kind = match p.kind {
+                    PortKind::USES => "uses",
+                    PortKind::PROVIDES => "provides",
+                };
+                (p.name.clone(), kind, p.repository_id.clone())
+            })
+            .collect())
+    }
+
+    /// Returns the (connection id, uses component, uses port, provides component, provides port) of the connections.
+    fn connections(&self) -> Vec<(String, String, String, String, String)> {
+        self.sandbox
+            .connections()
+            .iter()
+            .map(|c| {
+                (
+                    c.connection_id.clone(),
+                    c.uses_component.clone(),
+                    c.uses_port.clone(),
+                    c.provides_component.clone(),
+                    c.provides_port.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Connects a uses port to a provides port, returning the connection id.
+    fn connect(
+        &mut self,
+        uses_component: &str,
+        uses_port: &str,
+        provides_component: &str,
+        provides_port: &str,
+    ) -> PyResult<String> {
+        self.sandbox
+            .connect(uses_component, uses_port, provides_component, provides_port)
+            .map_err(error)
+    }
+
+    fn disconnect(&mut self, connection_id: &str) -> PyResult<()> {
+        self.sandbox.disconnect(connection_id).map_err(error)
+    }
+
+    fn configure(&self, name: &str, properties: &Bound<PyAny>) -> PyResult<()> {
+        self.sandbox
+            .configure(name, &to_properties(properties)?)
+            .map_err(error)
+    }
+
+    /// Returns the values of properties of a component, all of them when none is given.
+    #[pyo3(signature = (name, properties=None))]
+    fn query(&self, name: &str, properties: Option<&Bound<PyAny>>) -> PyResult<PyProperties> {
+        self.sandbox
+            .query(name, &optional_properties(properties)?)
+            .map(PyProperties)
+            .map_err(error)
+    }
+
+    fn start(&self, name: &str) -> PyResult<()> {
+        self.sandbox.start(name).map_err(error)
+    }
+
+    fn stop(&self, name: &str) -> PyResult<()> {
+        self.sandbox.stop(name).map_err(error)
+    }
+
+    fn release(&mut self, name: &str) -> PyResult<()> {
+        self.sandbox.release(name).map_err(error)
+    }
+
+    fn release_all(&mut self) -> PyResult<()> {
+        self.sandbox.release_all().map_err(error)
+    }
+
+    /**
+     * Returns the (time in seconds since the epoch, producer name, level,
+     * message) of the log records of the components named, of all of
+     * them when none is.
+     */
+    #[pyo3(signature = (names=None))]
+    fn logs(&self, names: Option<Vec<String>>) -> Vec<(f64, String, String, String)> {
+        let names = names.unwrap_or_default();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.sandbox
+            .logs(&names, None)
+            .into_iter()
+            .map(|r| {
+                let time = r.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+                (
+                    time.as_secs_f64(),
+                    r.producer_name,
+                    format!("{:?}", r.level),
+                    r.message,
+                )
+            })
+            .collect()
+    }
+}
+
+/**
+ * Client of the DomainManager service of a domain, installing, creating
+ * and driving its applications. The calls block until replied.
+ */
+#[pyclass(name = "DomainClient", module = "scars")]
+pub struct PyDomainClient {
+    runtime: Runtime,
+    client: DomainManagerClient<Channel>,
+}
+
+impl PyDomainClient {
+    fn call<T, F>(&mut self, py: Python, call: F) -> PyResult<T>
+    where
+        T: Send,
+        F: for<'a> FnOnce(
+                &'a mut DomainManagerClient<Channel>,
+            ) -> std::pin::Pin<
+                Box<dyn std::future::Future<Output = Result<T, tonic::Status>> + Send + 'a>,
+            > + Send,
+    {
+        let (runtime, client) = (&self.runtime, &mut self.client);
+        py.allow_threads(|| runtime.block_on(call(client)).map_err(status_message))
+            .map_err(ScarsError::new_err)
+    }
+}
+
+#[pymethods]
+impl PyDomainClient {
+    /// Connects to the DomainManager service of an endpoint, e.g. "http://localhost:50051".
+    #[new]
+    fn new(py: Python, endpoint: String) -> PyResult<PyDomainClient> {
+        let runtime = Runtime::new().map_err(error)?;
+        let client = py
+            .allow_threads(|| runtime.block_on(DomainManagerClient::connect(endpoint)))
+            .map_err(error)?;
+        Ok(PyDomainClient { runtime, client })
+    }
+
+    /// Returns the (identifier, name, profile, started) of the applications.
+    fn applications(&mut self, py: Python) -> PyResult<Vec<(String, String, String, bool)>> {
+        let reply = self.call(py, |c| Box::pin(c.applications(ApplicationsRequest {})))?;
+        Ok(reply
+            .into_inner()
+            .applications
+            .into_iter()
+            .map(|a| (a.identifier, a.name, a.profile, a.started))
+            .collect())
+    }
+
+    /// Returns the (identifier, name, software profile) of the application factories.
+    fn application_factories(&mut self, py: Python) -> PyResult<Vec<(String, String, String)>> {
+        let reply = self.call(py, |c| {
+            Box::pin(c.application_factories(ApplicationFactoriesRequest {}))
+        })?;
+        Ok(reply
+            .into_inner()
+            .application_factories
+            .into_iter()
+            .map(|f| (f.identifier, f.name, f.software_profile))
+            .collect())
+    }
+
+    /// Installs the SAD of a pathname of the domain FileManager, returning the factory identifier.
+    fn install_application(&mut self, py: Python, profile_file_name: String) -> PyResult<String> {
+        let request = InstallApplicationRequest { profile_file_name };
+        let reply = self.call(py, |c| Box::pin(c.install_application(request)))?;
+        Ok(reply.into_inner().identifier)
+    }
+
+    fn uninstall_application(&mut self, py: Python, identifier: String) -> PyResult<()> {
+        let request = UninstallApplicationRequest { identifier };
+        self.call(py, |c| Box::pin(c.uninstall_application(request)))?;
+        Ok(())
+    }
+
+    /// Creates an application of a factory, returning its identifier.
+    #[pyo3(signature = (factory_identifier, name, init_configuration=None))]
+    fn create_application(
+        &mut self,
+        py: Python,
+        factory_identifier: String,
+        name: String,
+        init_configuration: Option<&Bound<PyAny>>,
+    ) -> PyResult<String> {
+        let request = CreateApplicationRequest {
+            factory_identifier,
+            name,
+            init_configuration: properties_to_wire(&optional_properties(init_configuration)?),
+            device_assignments: Vec::new(),
+        };
+        let reply = self.call(py, |c| Box::pin(c.create_application(request)))?;
+        Ok(reply.into_inner().identifier)
+    }
+
+    fn release_application(&mut self, py: Python, identifier: String) -> PyResult<()> {
+        let request = ReleaseApplicationRequest { identifier };
+        self.call(py, |c| Box::pin(c.release_application(request)))?;
+        Ok(())
+    }
+
+    fn start_application(&mut self, py: Python, identifier: String) -> PyResult<()> {
+        let request = StartApplicationRequest { identifier };
+        self.call(py, |c| Box::pin(c.start_application(request)))?;
+        Ok(())
+    }
+
+    fn stop_application(&mut self, py: Python, identifier: String) -> PyResult<()> {
+        let request = StopApplicationRequest { identifier };
+        self.call(py, |c| Box::pin(c.stop_application(request)))?;
+        Ok(())
+    }
+
+    /// Sets properties of an application, or of a component of one.
+    fn configure(
+        &mut self,
+        py: Python,
+        identifier: String,
+        properties: &Bound<PyAny>,
+    ) -> PyResult<()> {
+        let request = ConfigureApplicationRequest {
+            identifier,
+            properties: properties_to_wire(&to_properties(properties)?),
+        };
+        self.call(py, |c| Box::pin(c.configure_application(request)))?;
+        Ok(())
+    }
+
+    /// Returns the values of properties of an application, or of a component of one, all of them when none is given.
+    #[pyo3(signature = (identifier, properties=None))]
+    fn query(
+        &mut self,
+        py: Python,
+        identifier: String,
+        properties: Option<&Bound<PyAny>>,
+    ) -> PyResult<PyProperties> {
+        let request = QueryApplicationRequest {
+            identifier,
+            properties: properties_to_wire(&optional_properties(properties)?),
+        };
+        let reply = self.call(py, |c| Box::pin(c.query_application(request)))?;
+        properties_from_wire(&reply.into_inner().properties)
+            .map(PyProperties)
+            .map_err(error)
+    }
+}
+
+/**
+ * Client of the FileSystem service of a DeviceManager, or of a
+ * DomainManager for the files of the domain FileManager, of absolute
+ * pathnames. The calls block until replied.
+ */
+#[pyclass(name = "FileSystemClient", module = "scars")]
+pub struct PyFileSystemClient {
+    runtime: Runtime,
+    client: FileSystemClient<Channel>,
+}
+
+impl PyFileSystemClient {
+    fn call<T, F>(&mut self, py: Python, call: F) -> PyResult<T>
+    where
+        T: Send,
+        F: for<'a> FnOnce(
+                &'a mut FileSystemClient<Channel>,
+            ) -> std::pin::Pin<
+                Box<dyn std::future::Future<Output = Result<T, tonic::Status>> + Send + 'a>,
+            > + Send,
+    {
+        let (runtime, client) = (&self.runtime, &mut self.client);
+        py.allow_threads(|| runtime.block_on(call(client)).map_err(status_message))
+            .map_err(ScarsError::new_err)
+    }
+}
+
+#[pymethods]
+impl PyFileSystemClient {
+    /// Connects to the FileSystem service of an endpoint, e.g. "http://localhost:50052".
+    #[new]
+    fn new(py: Python, endpoint: String) -> PyResult<PyFileSystemClient> {
+        let runtime = Runtime::new().map_err(error)?;
+        let client = py
+            .allow_threads(|| runtime.block_on(FileSystemClient::connect(endpoint)))
+            .map_err(error)?;
+        Ok(PyFileSystemClient { runtime, client })
+    }
+
+    /**
+     * Returns the (name, "plain", "directory" or "file_system", size) of
+     * the files of a pattern of the '*' and '?' wildcards, by name.
+     */
+    #[pyo3(signature = (pattern="/*"))]
+    fn list(&mut self, py: Python, pattern: &str) -> PyResult<Vec<(String, &'static str, u64)>> {
+        let request = ListRequest {
+            pattern: pattern.to_string(),
+        };
+        let mut files = self
+            .call(py, |c| Box::pin(c.list(request)))?
+            .into_inner()
+            .files;
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files
+            .into_iter()
+            .map(|f| {
+                let kind = match f.kind() {
+                    FileType::Plain => "plain",
+                    FileType::Directory => "directory",
+                    FileType::FileSystem => "file_system",
+                };
+                (f.name, kind, f.size)
+            })
+            .collect())
+    }
+
+    /// Reads a file, the chunks whose CRC32C differs being read again.
+    fn read<'py>(&mut self, py: Python<'py>, file_name: &str) -> PyResult<Bound<'py, PyBytes>> {
+        let file_name = file_name.to_string();
+        let (data, _) = self.call(py, |c| {
+            Box::pin(async move {
+                let tuning = TransferTuning::default();
+                Ok(file_transfer::read_file(c, &file_name, &tuning).await?)
+            })
+        })?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Writes a file, created or truncated, returning its size.
+    fn write(&mut self, py: Python, file_name: &str, data: &[u8]) -> PyResult<u64> {
+        let (file_name, data) = (file_name.to_string(), data.to_vec());
+        let stats = self.call(py, |c| {
+            Box::pin(async move {
+                let tuning = TransferTuning::default();
+                Ok(file_transfer::write_file(c, &file_name, data, &tuning).await?)
+            })
+        })?;
+        Ok(stats.size as u64)
+    }
+
+    fn remove(&mut self, py: Python, file_name: String) -> PyResult<()> {
+        self.call(py, |c| Box::pin(c.remove(RemoveRequest { file_name })))?;
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        py: Python,
+        source_file_name: String,
+        destination_file_name: String,
+    ) -> PyResult<()> {
+        let request = CopyRequest {
+            source_file_name,
+            destination_file_name,
+        };
+        self.call(py, |c| Box::pin(c.copy(request)))?;
+        Ok(())
+    }
+
+    #[pyo3(name = "move")]
+    fn move_file(
+        &mut self,
+        py: Python,
+        source_file_name: String,
+        destination_file_name: String,
+    ) -> PyResult<()> {
+        let request = MoveRequest {
+            source_file_name,
+            destination_file_name,
+        };
+        self.call(py, |c| Box::pin(c.r#move(request)))?;
+        Ok(())
+    }
+
+    fn mkdir(&mut self, py: Python, directory_name: String) -> PyResult<()> {
+        self.call(py, |c| Box::pin(c.mkdir(MkdirRequest { directory_name })))?;
+        Ok(())
+    }
+
+    fn rmdir(&mut self, py: Python, directory_name: String) -> PyResult<()> {
+        self.call(py, |c| Box::pin(c.rmdir(RmdirRequest { directory_name })))?;
+        Ok(())
+    }
+}
+
+/// The scars Python module.
+#[pymodule]
+#[pyo3(name = "scars")]
+pub fn scars_py(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add("ScarsError", m.py().get_type::<ScarsError>())?;
+    m.add_class::<PyAnyValue>()?;
+    m.add_class::<PyProperties>()?;
+    m.add_class::<PySandbox>()?;
+    m.add_class::<PyDomainClient>()?;
+    m.add_class::<PyFileSystemClient>()?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+    use std::path::Path;
+    use std::sync::{Arc, Once};
+
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use tokio::net::TcpListener;
+
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::file_system::FileSystem;
+    use scars_py::scars_py;
+
+    static INIT: Once = Once::new();
+
+    /// Runs a Python script importing the scars module, of the variables given.
+    fn run(script: &str, variables: &[(&str, &str)]) {
+        INIT.call_once(|| pyo3::append_to_inittab!(scars_py));
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            for (name, value) in variables {
+                locals.set_item(name, value).unwrap();
+            }
+            let script = CString::new(format!("import scars\n{script}")).unwrap();
+            if let Err(e) = py.run(&script, None, Some(&locals)) {
+                e.display(py);
+                panic!("{e}");
+            }
+        });
+    }
+
+    /// Writes files of their contents in the directory.
+    fn write_files(root: &Path, files: &[(&str, &str)]) {
+        for (name, xml) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, xml).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_properties() {
+        run(
+            r#"
+#the values get the type inferred from the Python values, or the one given
+p = scars.Properties({"frequency": 101.1, "channels": 2, "name": "fm", "taps": [1, 2], "gain": {"value": 0.5, "auto": False}})
+assert len(p) == 5 and list(p) == ["frequency", "channels", "name", "taps", "gain"]
+assert p.any("frequency").kind == "double" and p.any("channels").kind == "long" and p.any("taps").kind == "sequence"
+p["big"] = 2**40
+assert p.any("big").kind == "longlong"
+p["port"] = scars.AnyValue("ushort", 5000)
+assert p.any("port") == scars.AnyValue("ushort", 5000) and p["port"] == 5000
+assert repr(p.any("port")) == "AnyValue('ushort', 5000)"
+
+#a plain value set on a property takes its type
+p["port"] = 6000
+assert p.any("port").kind == "ushort"
+try:
+    p["port"] = 70000
+    raise AssertionError("overflow")
+except OverflowError:
+    pass
+
+#the structs are property sets
+assert p["gain"]["value"] == 0.5 and p["gain"].any("auto").kind == "boolean"
+assert p.to_dict()["gain"] == {"value": 0.5, "auto": False}
+del p["big"]
+assert "big" not in p
+
+try:
+    scars.AnyValue("quad", 1)
+    raise AssertionError("kind")
+except ValueError:
+    pass
+try:
+    scars.Properties({"file": object()})
+    raise AssertionError("type")
+except TypeError:
+    pass
+"#,
+            &[],
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let root = tempfile::tempdir().unwrap();
+        write_files(root.path(), &[
+            ("components/demod/demod.spd.xml", r#"<softpkg id="DCE:demod" name="demod"><propertyfile type="PRF"><localfile name="demod.prf.xml"/></propertyfile><descriptor><localfile name="demod.scd.xml"/></descriptor><implementation id="cpp"><code type="Executable"><localfile name="cpp"/></code></implementation></softpkg>"#),
+            ("components/demod/demod.scd.xml", r#"<softwarecomponent><corbaversion>2.2</corbaversion><componentrepid repid="IDL:CF/Resource:1.0"/><componenttype>resource</componenttype><componentfeatures><ports><provides repid="IDL:BULKIO/dataFloat:1.0" providesname="audio_in"/><uses repid="IDL:BULKIO/dataFloat:1.0" usesname="audio_out"/></ports></componentfeatures><interfaces/></softwarecomponent>"#),
+            ("components/demod/demod.prf.xml", r#"<properties><simple id="frequency" type="double"><value>101.1</value><kind kindtype="configure"/></simple><simple id="squelch" type="short"><value>-40</value><kind kindtype="configure"/></simple></properties>"#),
+        ]);
+        run(
+            r#"
+sandbox = scars.Sandbox(root)
+demod = sandbox.launch("/components/demod/demod.spd.xml")
+sink = sandbox.launch("/components/demod/demod.spd.xml", "sink")
+assert (demod, sandbox.components()) == ("demod_1", ["demod_1", "sink"])
+assert sandbox.ports(demod) == [("audio_in", "provides", "IDL:BULKIO/dataFloat:1.0"), ("audio_out", "uses", "IDL:BULKIO/dataFloat:1.0")]
+
+#the components are configured with the values of their query, of their types
+values = sandbox.query(demod)
+assert values["frequency"] == 101.1 and values.any("squelch").kind == "short"
+values["squelch"] = -30
+sandbox.configure(demod, values)
+assert sandbox.query(demod, {"squelch": 0}).any("squelch") == scars.AnyValue("short", -30)
+
+connection_id = sandbox.connect(demod, "audio_out", sink, "audio_in")
+assert sandbox.connections() == [(connection_id, demod, "audio_out", sink, "audio_in")]
+sandbox.start(demod)
+sandbox.stop(demod)
+assert [message for (_, name, _, message) in sandbox.logs([demod])][-2:] == ["started", "stopped"]
+
+#the errors are raised as ScarsError
+try:
+    sandbox.start("demod_2")
+    raise AssertionError("unknown")
+except scars.ScarsError as e:
+    assert "demod_2" in str(e)
+sandbox.release_all()
+assert sandbox.components() == [] and sandbox.connections() == []
+"#,
+            &[("root", root.path().to_str().unwrap())],
+        );
+    }
+
+    #[test]
+    fn test_domain_client() {
+        let root = tempfile::tempdir().unwrap();
+        write_files(root.path(), &[
+            ("waveforms/fm/fm.sad.xml", r#"<softwareassembly id="DCE:fm" name="fm"><componentfiles><componentfile id="demod_file" type="SPD"><localfile name="../../components/demod/demod.spd.xml"/></componentfile></componentfiles></softwareassembly>"#),
+            ("components/demod/demod.spd.xml", r#"<softpkg id="DCE:demod" name="demod"><propertyfile><localfile name="demod.prf.xml"/></propertyfile><descriptor><localfile name="demod.scd.xml"/></descriptor></softpkg>"#),
+            ("components/demod/demod.prf.xml", "<properties/>"),
+            ("components/demod/demod.scd.xml", "<softwarecomponent/>"),
+        ]);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(root.path()))).unwrap();
+        let listener = runtime.block_on(TcpListener::bind("127.0.0.1:0")).unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let served = runtime.spawn(domain.clone().run(listener));
+
+        run(
+            r#"
+#the waveforms are installed from the files of the domain
+domain = scars.DomainClient(endpoint)
+assert domain.install_application("/dom/waveforms/fm/fm.sad.xml") == "DCE:fm"
+assert domain.application_factories() == [("DCE:fm", "fm", "/dom/waveforms/fm/fm.sad.xml")]
+assert domain.applications() == []
+try:
+    domain.start_application("DCE:am")
+    raise AssertionError("unknown")
+except scars.ScarsError:
+    pass
+domain.uninstall_application("DCE:fm")
+assert domain.application_factories() == []
+
+#the files of the domain are served on the same endpoint
+fs = scars.FileSystemClient(endpoint)
+assert fs.list() == [("dom", "file_system", 0)]
+assert [(name, kind) for (name, kind, _) in fs.list("/dom/*")] == [("components", "directory"), ("waveforms", "directory")]
+fs.mkdir("/dom/captures")
+data = bytes(range(256)) * 1000
+assert fs.write("/dom/captures/iq.bin", data) == len(data)
+assert fs.read("/dom/captures/iq.bin") == data
+fs.copy("/dom/captures/iq.bin", "/dom/captures/iq.bak")
+fs.move("/dom/captures/iq.bak", "/dom/captures/iq.old")
+assert [name for (name, _, _) in fs.list("/dom/captures/*")] == ["iq.bin", "iq.old"]
+fs.remove("/dom/captures/iq.bin")
+fs.remove("/dom/captures/iq.old")
+fs.rmdir("/dom/captures")
+try:
+    fs.read("/dom/captures/iq.bin")
+    raise AssertionError("missing")
+except scars.ScarsError:
+    pass
+"#,
+            &[("endpoint", &endpoint)],
+        );
+        assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 2);
+
+        domain.shutdown();
+        runtime.block_on(served).unwrap().unwrap();
+    }
+}
@@ -0,0 +1,625 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::ErrorKind;
+
+use serde::{Deserialize, Serialize};
+
+/**
+ * This enum is used to pass error number information in various
+ * exceptions. Those exceptions starting with "CF_E" map to the POSIX
+ * definitions.
+ * The "CF_" has been added to the POSIX exceptions to avoid namespace
+ * conflicts. CF_NOTSET is not defined in the POSIX specification.
+ * CF_NOTSET is an SCA specific value that is applicable for any
+ * exception when the method specific or standard POSIX error values
+ * are not appropriate.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorNumberType {
+    CF_NOTSET,
+    CF_E2BIG,
+    CF_EACCES,
+    CF_EAGAIN,
+    CF_EBADF,
+    CF_EBADMSG,
+    CF_EBUSY,
+    CF_ECANCELED,
+    CF_ECHILD,
+    CF_EDEADLK,
+    CF_EDOM,
+    CF_EEXIST,
+    CF_EFAULT,
+    CF_EFBIG,
+    CF_EINPROGRESS,
+    CF_EINTR,
+    CF_EINVAL,
+    CF_EIO,
+    CF_EISDIR,
+    CF_EMFILE,
+    CF_EMLINK,
+    CF_EMSGSIZE,
+    CF_ENAMETOOLONG,
+    CF_ENFILE,
+    CF_ENODEV,
+    CF_ENOENT,
+    CF_ENOEXEC,
+    CF_ENOLCK,
+    CF_ENOMEM,
+    CF_ENOSPC,
+    CF_ENOSYS,
+    CF_ENOTDIR,
+    CF_ENOTEMPTY,
+    CF_ENOTSUP,
+    CF_ENOTTY,
+    CF_ENXIO,
+    CF_EPERM,
+    CF_EPIPE,
+    CF_ERANGE,
+    CF_EROFS,
+    CF_ESPIPE,
+    CF_ESRCH,
+    CF_ETIMEDOUT,
+    CF_EXDEV,
+}
+
+impl fmt::Display for ErrorNumberType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ErrorKind> for ErrorNumberType {
+    /// The kinds without POSIX counterpart are mapped to CF_EIO.
+    fn from(value: ErrorKind) -> Self {
+        match value {
+            ErrorKind::NotFound => ErrorNumberType::CF_ENOENT,
+            ErrorKind::PermissionDenied => ErrorNumberType::CF_EPERM,
+            ErrorKind::BrokenPipe => ErrorNumberType::CF_EPIPE,
+            ErrorKind::AlreadyExists => ErrorNumberType::CF_EEXIST,
+            ErrorKind::WouldBlock => ErrorNumberType::CF_EAGAIN,
+            ErrorKind::NotADirectory => ErrorNumberType::CF_ENOTDIR,
+            ErrorKind::IsADirectory => ErrorNumberType::CF_EISDIR,
+            ErrorKind::DirectoryNotEmpty => ErrorNumberType::CF_ENOTEMPTY,
+            ErrorKind::ReadOnlyFilesystem => ErrorNumberType::CF_EROFS,
+            ErrorKind::InvalidInput | ErrorKind::InvalidFilename => ErrorNumberType::CF_EINVAL,
+            ErrorKind::InvalidData => ErrorNumberType::CF_EBADMSG,
+            ErrorKind::TimedOut => ErrorNumberType::CF_ETIMEDOUT,
+            ErrorKind::StorageFull => ErrorNumberType::CF_ENOSPC,
+            ErrorKind::NotSeekable => ErrorNumberType::CF_ESPIPE,
+            ErrorKind::FileTooLarge => ErrorNumberType::CF_EFBIG,
+            ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy => ErrorNumberType::CF_EBUSY,
+            ErrorKind::Deadlock => ErrorNumberType::CF_EDEADLK,
+            ErrorKind::CrossesDevices => ErrorNumberType::CF_EXDEV,
+            ErrorKind::TooManyLinks => ErrorNumberType::CF_EMLINK,
+            ErrorKind::ArgumentListTooLong => ErrorNumberType::CF_E2BIG,
+            ErrorKind::Interrupted => ErrorNumberType::CF_EINTR,
+            ErrorKind::Unsupported => ErrorNumberType::CF_ENOTSUP,
+            ErrorKind::OutOfMemory => ErrorNumberType::CF_ENOMEM,
+            _ => ErrorNumberType::CF_EIO,
+        }
+    }
+}
+
+/**
+ * This exception indicates an invalid file name was passed
+ * to a file service operation. The message provides information
+ * describing why the filename was invalid.
+ */
+#[derive(Debug)]
+pub struct InvalidFileName {
+    pub error_number: ErrorNumberType,
+    pub message: String,
+}
+
+impl fmt::Display for InvalidFileName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/**
+ * This exception indicates an invalid object reference error.
+ */
+#[derive(Debug)]
+pub struct InvalidObjectReference {
+    pub message: String,
+}
+
+impl fmt::Display for InvalidObjectReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/**
+ * This type is a self-describing value used to carry property values
+ * (the CORBA any of the SCA IDL).
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AnyValue {
+    Boolean(bool),
+    Octet(u8),
+    Short(i16),
+    UShort(u16),
+    Long(i32),
+    ULong(u32),
+    LongLong(i64),
+    ULongLong(u64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Sequence(Vec<AnyValue>),
+    Struct(Properties),
+}
+
+impl AnyValue {
+    /// Returns true when the value is neither a sequence nor a struct.
+    pub fn is_simple(&self) -> bool {
+        !matches!(self, AnyValue::Sequence(_) | AnyValue::Struct(_))
+    }
+
+    /// Returns the value as a floating point number, when numeric.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            AnyValue::Octet(v) => Some(*v as f64),
+            AnyValue::Short(v) => Some(*v as f64),
+            AnyValue::UShort(v) => Some(*v as f64),
+            AnyValue::Long(v) => Some(*v as f64),
+            AnyValue::ULong(v) => Some(*v as f64),
+            AnyValue::LongLong(v) => Some(*v as f64),
+            AnyValue::ULongLong(v) => Some(*v as f64),
+            AnyValue::Float(v) => Some(*v as f64),
+            AnyValue::Double(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns true when both values have the same type.
+    pub fn same_type(&self, other: &AnyValue) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+
+    /// Returns the sum of two numeric values of the same type, unless it overflows.
+    pub fn checked_add(&self, other: &AnyValue) -> Option<AnyValue> {
+        match (self, other) {
+            (AnyValue::Octet(a), AnyValue::Octet(b)) => a.checked_add(*b).map(AnyValue::Octet),
+            (AnyValue::Short(a), AnyValue::Short(b)) => a.checked_add(*b).map(AnyValue::Short),
+            (AnyValue::UShort(a), AnyValue::UShort(b)) => a.checked_add(*b).map(AnyValue::UShort),
+            (AnyValue::Long(a), AnyValue::Long(b)) => a.checked_add(*b).map(AnyValue::Long),
+            (AnyValue::ULong(a), AnyValue::ULong(b)) => a.checked_add(*b).map(AnyValue::ULong),
+            (AnyValue::LongLong(a), AnyValue::LongLong(b)) => {
+                a.checked_add(*b).map(AnyValue::LongLong)
+            }
+            (AnyValue::ULongLong(a), AnyValue::ULongLong(b)) => {
+                a.checked_add(*b).map(AnyValue::ULongLong)
+            }
+            (AnyValue::Float(a), AnyValue::Float(b)) => Some(AnyValue::Float(a + b)),
+            (AnyValue::Double(a), AnyValue::Double(b)) => Some(AnyValue::Double(a + b)),
+            _ => None,
+        }
+    }
+
+    /**
+     * Returns the difference of two numeric values of the same type,
+     * unless it overflows or gets negative.
+     */
+    pub fn checked_sub(&self, other: &AnyValue) -> Option<AnyValue> {
+        let result = match (self, other) {
+            (AnyValue::Octet(a), AnyValue::Octet(b)) => a.checked_sub(*b).map(AnyValue::Octet),
+            (AnyValue::Short(a), AnyValue::Short(b)) => a.checked_sub(*b).map(AnyValue::Short),
+            (AnyValue::UShort(a), AnyValue::UShort(b)) => a.checked_sub(*b).map(AnyValue::UShort),
+            (AnyValue::Long(a), AnyValue::Long(b)) => a.checked_sub(*b).map(AnyValue::Long),
+            (AnyValue::ULong(a), AnyValue::ULong(b)) => a.checked_sub(*b).map(AnyValue::ULong),
+            (AnyValue::LongLong(a), AnyValue::LongLong(b)) => {
+                a.checked_sub(*b).map(AnyValue::LongLong)
+            }
+            (AnyValue::ULongLong(a), AnyValue::ULongLong(b)) => {
+                a.checked_sub(*b).map(AnyValue::ULongLong)
+            }
+            (AnyValue::Float(a), AnyValue::Float(b)) => Some(AnyValue::Float(a - b)),
+            (AnyValue::Double(a), AnyValue::Double(b)) => Some(AnyValue::Double(a - b)),
+            _ => None,
+        }?;
+        (result.as_f64()? >= 0.0).then_some(result)
+    }
+
+    /**
+     * Parses a value written in the literal syntax of its display:
+     * booleans, numbers, strings, double quoted when holding delimiters,
+     * [a,b] sequences and {id=value,...} structs. The value takes the
+     * type of the like value when given, its type being inferred
+     * otherwise: Long, LongLong or ULongLong for the integers, Double
+     * for the other numbers and String for the other words. Returns None
+     * when the text is not such a value, or nests its sequences and
+     * structs deeper than MAX_LITERAL_DEPTH.
+     */
+    pub fn parse_literal(text: &str, like: Option<&AnyValue>) -> Option<AnyValue> {
+        if !text.trim_start().starts_with(['[', '{', '"']) {
+            return AnyValue::parse_simple(text, like);
+        }
+        let mut parser = LiteralParser {
+            text,
+            position: 0,
+            depth: 0,
+        };
+        let value = parser.value(like)?;
+        parser.at_end().then_some(value)
+    }
+
+    /// Parses a simple value, a string keeping the text as is.
+    fn parse_simple(text: &str, like: Option<&AnyValue>) -> Option<AnyValue> {
+        let trimmed = text.trim();
+        match like {
+            Some(AnyValue::Boolean(_)) => trimmed.parse().ok().map(AnyValue::Boolean),
+            Some(AnyValue::Octet(_)) => trimmed.parse().ok().map(AnyValue::Octet),
+            Some(AnyValue::Short(_)) => trimmed.parse().ok().map(AnyValue::Short),
+            Some(AnyValue::UShort(_)) => trimmed.parse().ok().map(AnyValue::UShort),
+            Some(AnyValue::Long(_)) => trimmed.parse().ok().map(AnyValue::Long),
+            Some(AnyValue::ULong(_)) => trimmed.parse().ok().map(AnyValue::ULong),
+            Some(AnyValue::LongLong(_)) => trimmed.parse().ok().map(AnyValue::LongLong),
+            Some(AnyValue::ULongLong(_)) => trimmed.parse().ok().map(AnyValue::ULongLong),
+            Some(AnyValue::Float(_)) => trimmed.parse().ok().map(AnyValue::Float),
+            Some(AnyValue::Double(_)) => trimmed.parse().ok().map(AnyValue::Double),
+            Some(AnyValue::String(_)) => Some(AnyValue::String(text.to_string())),
+            Some(AnyValue::Sequence(_) | AnyValue::Struct(_)) => None,
+            None => Some(
+                trimmed
+                    .parse()
+                    .map(AnyValue::Boolean)
+                    .or_else(|_| trimmed.parse().map(AnyValue::Long))
+                    .or_else(|_| trimmed.parse().map(AnyValue::LongLong))
+                    .or_else(|_| trimmed.parse().map(AnyValue::ULongLong))
+                    .or_else(|_| trimmed.parse().map(AnyValue::Double))
+                    .unwrap_or_else(|_| AnyValue::String(text.to_string())),
+            ),
+        }
+    }
+
+    /// Returns the value as a signed integer, when integral.
+    fn as_i128(&self) -> Option<i128> {
+        match self {
+            AnyValue::Octet(v) => Some(*v as i128),
+            AnyValue::Short(v) => Some(*v as i128),
+            AnyValue::UShort(v) => Some(*v as i128),
+            AnyValue::Long(v) => Some(*v as i128),
+            AnyValue::ULong(v) => Some(*v as i128),
+            AnyValue::LongLong(v) => Some(*v as i128),
+            AnyValue::ULongLong(v) => Some(*v as i128),
+            _ => None,
+        }
+    }
+}
+
+/// The deepest nesting of the sequence and struct literals parsed.
+pub const MAX_LITERAL_DEPTH: usize = 64;
+
+/// Recursive descent parser of the sequence and struct literals.
+struct LiteralParser<'a> {
+    text: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl LiteralParser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.position..]
+    }
+
+    /// Skips the whitespaces, then the character when next.
+    fn eat(&mut self, c: char) -> bool {
+        self.position = self.text.len() - self.rest().trim_start().len();
+        let found = self.rest().starts_with(c);
+        if found {
+            self.position += c.len_utf8();
+        }
+        found
+    }
+
+    fn at_end(&self) -> bool {
+        self.rest().trim().is_empty()
+    }
+
+    /// Returns the text up to the next delimiter, trimmed.
+    fn token(&mut self, delimiters: &[char]) -> &str {
+        let start = self.position;
+        let length = self.rest().find(delimiters).unwrap_or(self.rest().len());
+        self.position += length;
+        self.text[start..self.position].trim()
+    }
+
+    /// Parses a nested value, bounding the depth of the recursion.
+    fn nested(&mut self, like: Option<&AnyValue>) -> Option<AnyValue> {
+        if self.depth == MAX_LITERAL_DEPTH {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.value(like);
+        self.depth -= 1;
+        value
+    }
+
+    fn value(&mut self, like: Option<&AnyValue>) -> Option<AnyValue> {
+        if self.eat('[') {
+            let element = match like {
+                Some(AnyValue::Sequence(items)) => items.first(),
+                Some(_) => return None,
+                None => None,
+            };
+            let mut items = Vec::new();
+            if !self.eat(']') {
+                loop {
+                    items.push(self.nested(element)?);
+                    if self.eat(']') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return None;
+                    }
+                }
+            }
+            Some(AnyValue::Sequence(items))
+        } else if self.eat('{') {
+            let fields = match like {
+                Some(AnyValue::Struct(fields)) => Some(fields),
+                Some(_) => return None,
+                None => None,
+            };
+            let mut properties = Properties::new();
+            if !self.eat('}') {
+                loop {
+                    let id = self.token(&['=', ',', '}', ']']).to_string();
+                    if id.is_empty() || !self.eat('=') {
+                        return None;
+                    }
+                    let field = fields
+                        .and_then(|f| f.iter().find(|p| p.id == id))
+                        .map(|p| &p.value);
+                    properties.push(DataType::new(&id, self.nested(field)?));
+                    if self.eat('}') {
+                        break;
+                    }
+                    if !self.eat(',') {
+                        return None;
+                    }
+                }
+            }
+            Some(AnyValue::Struct(properties))
+        } else if self.eat('"') {
+            let mut text = String::new();
+            let mut chars = self.rest().char_indices();
+            loop {
+                match chars.next()? {
+                    (index, '"') => {
+                        self.position += index + 1;
+                        break;
+                    }
+                    (_, '\\') => text.push(chars.next()?.1),
+                    (_, c) => text.push(c),
+                }
+            }
+            match like {
+                Some(AnyValue::String(_)) | None => Some(AnyValue::String(text)),
+                Some(_) => AnyValue::parse_simple(&text, like),
+            }
+        } else {
+            let token = self.token(&[',', ']', '}']);
+            if token.is_empty() {
+                return None;
+            }
+            AnyValue::parse_simple(token, like)
+        }
+    }
+}
+
+/**
+ * Values are ordered when they are both numeric, whatever their numeric
+ * type, both strings or both booleans. Sequences and structs are only
+ * compared for equality.
+ */
+impl PartialOrd for AnyValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if let (Some(a), Some(b)) = (self.as_i128(), other.as_i128()) {
+            return Some(a.cmp(&b));
+        }
+        if let (Some(a), Some(b)) = (self.as_f64(), other.as_f64()) {
+            return a.partial_cmp(&b);
+        }
+        match (self, other) {
+            (AnyValue::String(a), AnyValue::String(b)) => Some(a.cmp(b)),
+            (AnyValue::Boolean(a), AnyValue::Boolean(b)) => Some(a.cmp(b)),
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AnyValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnyValue::Boolean(v) => write!(f, "{v}"),
+            AnyValue::Octet(v) => write!(f, "{v}"),
+            AnyValue::Short(v) => write!(f, "{v}"),
+            AnyValue::UShort(v) => write!(f, "{v}"),
+            AnyValue::Long(v) => write!(f, "{v}"),
+            AnyValue::ULong(v) => write!(f, "{v}"),
+            AnyValue::LongLong(v) => write!(f, "{v}"),
+            AnyValue::ULongLong(v) => write!(f, "{v}"),
+            AnyValue::Float(v) => write!(f, "{v}"),
+            AnyValue::Double(v) => write!(f, "{v}"),
+            AnyValue::String(v) => write!(f, "{v}"),
+            AnyValue::Sequence(v) => {
+                let items: Vec<String> = v.iter().map(|i| i.to_string()).collect();
+                write!(f, "[{}]", items.join(","))
+            }
+            AnyValue::Struct(v) => {
+                let items: Vec<String> =
+                    v.iter().map(|i| format!("{}={}", i.id, i.value)).collect();
+                write!(f, "{{{}}}", items.join(","))
+            }
+        }
+    }
+}
+
+/**
+ * This type is used to define a property id and value pair.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataType {
+    pub id: String,
+    pub value: AnyValue,
+}
+
+impl DataType {
+    pub fn new(id: &str, value: AnyValue) -> DataType {
+        DataType {
+            id: id.to_string(),
+            value,
+        }
+    }
+}
+
+/**
+ * This type defines an unbounded sequence of properties, used as the
+ * argument of configure, query, execute and capacity operations.
+ */
+pub type Properties = Vec<DataType>;
+
+/**
+ * This trait converts the Rust types of the property values to and from
+ * AnyValue, the chars and object references being carried as strings.
+ */
+pub trait PropertyValue: Sized {
+    fn to_any(&self) -> AnyValue;
+
+    /// Returns None when the value is not of the type.
+    fn from_any(value: &AnyValue) -> Option<Self>;
+}
+
+macro_rules! simple_property_value {
+    ($type:ty, $variant:ident) => {
+        impl PropertyValue for $type {
+            fn to_any(&self) -> AnyValue {
+                AnyValue::$variant(self.clone())
+            }
+
+            fn from_any(value: &AnyValue) -> Option<Self> {
+                match value {
+                    AnyValue::$variant(v) => Some(v.clone()),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+simple_property_value!(bool, Boolean);
+simple_property_value!(u8, Octet);
+simple_property_value!(i16, Short);
+simple_property_value!(u16, UShort);
+simple_property_value!(i32, Long);
+simple_property_value!(u32, ULong);
+simple_property_value!(i64, LongLong);
+simple_property_value!(u64, ULongLong);
+simple_property_value!(f32, Float);
+simple_property_value!(f64, Double);
+simple_property_value!(String, String);
+
+impl PropertyValue for char {
+    fn to_any(&self) -> AnyValue {
+        AnyValue::String(self.to_string())
+    }
+
+    fn from_any(value: &AnyValue) -> Option<Self> {
+        match value {
+            AnyValue::String(v) if v.chars().count() == 1 => v.chars().next(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: PropertyValue> PropertyValue for Vec<T> {
+    fn to_any(&self) -> AnyValue {
+        AnyValue::Sequence(self.iter().map(T::to_any).collect())
+    }
+
+    fn from_any(value: &AnyValue) -> Option<Self> {
+        match value {
+            AnyValue::Sequence(values) => values.iter().map(T::from_any).collect(),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * This type defines how an allocation property is evaluated against
+ * the property of a device: either compared with the device value
+ * (eq, ne, gt, lt, ge, le), or handed to the device allocateCapacity
+ * operation (external).
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionType {
+    EQ,
+    NE,
+    GT,
+    LT,
+    GE,
+    LE,
+    EXTERNAL,
+}
+
+impl ActionType {
+    /**
+     * Evaluates the device value against the requested one. A sequence
+     * device value satisfies eq when it contains the requested value
+     * and ne when it does not. A requested struct is evaluated field by
+     * field against the device struct, all its fields being satisfied
+     * together; against a sequence of structs, any of them may satisfy it.
+     */
+    pub fn evaluate(&self, device_value: &AnyValue, requested: &AnyValue) -> bool {
+        if let AnyValue::Struct(fields) = requested {
+            let satisfies = |value: &AnyValue| match value {
+                AnyValue::Struct(own) => fields.iter().all(|f| {
+                    own.iter()
+                        .find(|o| o.id == f.id)
+                        .is_some_and(|o| self.evaluate(&o.value, &f.value))
+                }),
+                _ => false,
+            };
+            return match device_value {
+                AnyValue::Sequence(values) => values.iter().any(satisfies),
+                value => satisfies(value),
+            };
+        }
+
+        if let (AnyValue::Sequence(values), false) =
+            (device_value, matches!(requested, AnyValue::Sequence(_)))
+        {
+            let contained = values
+                .iter()
+                .any(|v| v.partial_cmp(requested) == Some(Ordering::Equal));
+            return match self {
+                ActionType::EQ => contained,
+                ActionType::NE => !contained,
+                _ => false,
+            };
+        }
+
+        let ordering = device_value.partial_cmp(requested);
+        match self {
+            ActionType::EQ => ordering == Some(Ordering::Equal),
+            ActionType::NE => ordering != Some(Ordering::Equal),
+            ActionType::GT => ordering == Some(Ordering::Greater),
+            ActionType::LT => ordering == Some(Ordering::Less),
+            ActionType::GE => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            ActionType::LE => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            ActionType::EXTERNAL => false,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! CF common types of scars: ErrorNumberType, AnyValue, DataType and the
+//! exceptions, with the wire encoding of the property values, shared by
+//! the framework and the firmware of its co-processors.
+//!
+//! The crate is no_std, of alloc, without its default std feature, e.g.
+//! for the DSP and MCU targets.
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod common_types;
+pub mod wire;
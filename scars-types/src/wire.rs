@@ -0,0 +1,13 @@
+use alloc::string::String;
+
+use super::common_types::AnyValue;
+
+/// Encodes a property value for the wire, as JSON.
+pub fn value_to_wire(value: &AnyValue) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Decodes a property value received from the wire.
+pub fn value_from_wire(text: &str) -> Result<AnyValue, serde_json::Error> {
+    serde_json::from_str(text)
+}
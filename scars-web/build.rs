@@ -0,0 +1,18 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The protoc given by the environment prevails over the vendored one
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    tonic_build::configure()
+        .build_server(false)
+        .build_transport(false)
+        .compile(
+            &[
+                "../proto/device.proto",
+                "../proto/domain_manager.proto",
+                "../proto/file_system.proto",
+            ],
+            &["../proto"],
+        )?;
+    Ok(())
+}
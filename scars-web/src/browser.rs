@@ -0,0 +1,70 @@
+use js_sys::{Array, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit, Response, Window, WorkerGlobalScope};
+
+use super::grpc_web::{FetchFuture, FetchResponse, FetchTrait, GrpcWebError, Result};
+
+/**
+ * HTTP binding of the grpc-web calls fetched by the browser, from a
+ * window or a worker. The endpoint must allow the origin of the page,
+ * as the DomainManager does.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BrowserFetch;
+
+fn fetch_error(value: JsValue) -> GrpcWebError {
+    GrpcWebError::FetchError {
+        message: value.as_string().unwrap_or_else(|| format!("{value:?}")),
+    }
+}
+
+impl FetchTrait for BrowserFetch {
+    fn fetch(&self, url: &str, headers: &[(String, String)], body: Vec<u8>) -> FetchFuture {
+        let url = url.to_string();
+        let headers = headers.to_vec();
+        Box::pin(async move { fetch(&url, &headers, &body).await.map_err(fetch_error) })
+    }
+}
+
+async fn fetch(
+    url: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Result<FetchResponse, JsValue> {
+    let request_headers = Headers::new()?;
+    for (name, value) in headers {
+        request_headers.set(name, value)?;
+    }
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&request_headers);
+    init.set_body(&Uint8Array::from(body));
+    let request = Request::new_with_str_and_init(url, &init)?;
+
+    let global = js_sys::global();
+    let promise = match global.dyn_ref::<Window>() {
+        Some(window) => window.fetch_with_request(&request),
+        None => global
+            .unchecked_into::<WorkerGlobalScope>()
+            .fetch_with_request(&request),
+    };
+    let response: Response = JsFuture::from(promise).await?.dyn_into()?;
+
+    let mut headers = Vec::new();
+    if let Some(entries) = js_sys::try_iter(&response.headers())? {
+        for entry in entries {
+            let entry: Array = entry?.dyn_into()?;
+            if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string())
+            {
+                headers.push((name, value));
+            }
+        }
+    }
+    let body = JsFuture::from(response.array_buffer()?).await?;
+    Ok(FetchResponse {
+        status: response.status(),
+        headers,
+        body: Uint8Array::new(&body).to_vec(),
+    })
+}
@@ -0,0 +1,252 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http_body::Body;
+use thiserror::Error;
+use tonic::body::BoxBody;
+use tower_service::Service;
+
+/// The content type of the grpc-web calls, of binary protobuf messages.
+pub const GRPC_WEB_CONTENT_TYPE: &str = "application/grpc-web+proto";
+
+/// The flag of the frame of the trailers, following the message frames.
+const TRAILERS_FLAG: u8 = 0x80;
+
+/// The request headers not sent, the browsers setting them.
+const FORBIDDEN_HEADERS: &[&str] = &["content-type", "te", "user-agent"];
+
+/**
+ * Convienence enum definition that includes all grpc-web errors.
+ */
+#[derive(Error, Debug)]
+pub enum GrpcWebError {
+    /**
+     * This exception indicates that the call could not be fetched, e.g.
+     * the endpoint being unreachable or refusing the origin.
+     */
+    #[error("FetchError: {message}")]
+    FetchError { message: String },
+    /**
+     * This exception indicates that the endpoint replied with an HTTP
+     * status other than 200, e.g. not serving grpc-web.
+     */
+    #[error("HttpError: status: {status}.")]
+    HttpError { status: u16 },
+    /**
+     * This exception indicates that the body of the reply is not made of
+     * grpc-web frames.
+     */
+    #[error("InvalidFrame: {message}")]
+    InvalidFrame { message: String },
+}
+
+/*
+ * Convienence type definition that includes all grpc-web returned errors.
+ */
+pub type Result<T, E = GrpcWebError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines the reply of an HTTP request: its status, headers
+ * and body.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Future of a fetched reply, not Send as the ones of the browsers.
+pub type FetchFuture = Pin<Box<dyn Future<Output = Result<FetchResponse>>>>;
+
+/**
+ * This trait binds the grpc-web calls to an HTTP client, posting a body
+ * with headers to a URL.
+ */
+pub trait FetchTrait {
+    fn fetch(&self, url: &str, headers: &[(String, String)], body: Vec<u8>) -> FetchFuture;
+}
+
+/// Convienence type definition of a reference to an HTTP binding.
+pub type FetchRef = Arc<dyn FetchTrait>;
+
+/**
+ * Channel of the generated clients calling the services of an endpoint
+ * over grpc-web, e.g. DomainManagerClient::new(GrpcWebChannel::new(
+ * "http://localhost:50051", fetch)). The replies being fetched whole,
+ * the messages of a server stream are received once it ends.
+ */
+#[derive(Clone)]
+pub struct GrpcWebChannel {
+    endpoint: String,
+    fetch: FetchRef,
+}
+
+impl std::fmt::Debug for GrpcWebChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("GrpcWebChannel")
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+impl GrpcWebChannel {
+    pub fn new(endpoint: &str, fetch: FetchRef) -> GrpcWebChannel {
+        GrpcWebChannel {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            fetch,
+        }
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+impl Service<http::Request<BoxBody>> for GrpcWebChannel {
+    type Response = http::Response<GrpcWebBody>;
+    type Error = GrpcWebError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>>>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        let url = format!("{}{}", self.endpoint, request.uri().path());
+        let fetch = self.fetch.clone();
+        Box::pin(async move {
+            let (parts, mut body) = request.into_parts();
+            let mut headers = vec![
+                (
+                    "content-type".to_string(),
+                    GRPC_WEB_CONTENT_TYPE.to_string(),
+                ),
+                ("x-grpc-web".to_string(), "1".to_string()),
+            ];
+            for (name, value) in &parts.headers {
+                if let (false, Ok(value)) =
+                    (FORBIDDEN_HEADERS.contains(&name.as_str()), value.to_str())
+                {
+                    headers.push((name.to_string(), value.to_string()));
+                }
+            }
+            //the message frames of gRPC and grpc-web are the same
+            let mut frames = Vec::new();
+            while let Some(data) = body.data().await {
+                let data = data.map_err(|e| GrpcWebError::FetchError {
+                    message: e.to_string(),
+                })?;
+                frames.extend_from_slice(&data);
+            }
+
+            let reply = fetch.fetch(&url, &headers, frames).await?;
+            if reply.status != 200 {
+                return Err(GrpcWebError::HttpError {
+                    status: reply.status,
+                });
+            }
+            let (data, trailers) = split_trailers(&reply.body)?;
+            let mut response = http::Response::new(GrpcWebBody {
+                data: Some(data),
+                trailers,
+            });
+            for (name, value) in &reply.headers {
+                if let (Ok(name), Ok(value)) = (
+                    HeaderName::from_bytes(name.to_ascii_lowercase().as_bytes()),
+                    HeaderValue::from_str(value),
+                ) {
+                    response.headers_mut().append(name, value);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+/**
+ * Splits the body of a grpc-web reply into its message frames, given
+ * to the generated clients as is, and the headers of its trailers frame,
+ * none when the status came with the headers.
+ */
+pub fn split_trailers(body: &[u8]) -> Result<(Bytes, Option<HeaderMap>)> {
+    let mut position = 0;
+    while position < body.len() {
+        let header =
+            body.get(position..position + 5)
+                .ok_or_else(|| GrpcWebError::InvalidFrame {
+                    message: format!("truncated frame header at {position}"),
+                })?;
+        let length = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let end = position + 5 + length;
+        let payload = body
+            .get(position + 5..end)
+            .ok_or_else(|| GrpcWebError::InvalidFrame {
+                message: format!("truncated frame of {length} bytes at {position}"),
+            })?;
+        if header[0] & TRAILERS_FLAG != 0 {
+            if end != body.len() {
+                return Err(GrpcWebError::InvalidFrame {
+                    message: format!("frame after the trailers at {end}"),
+                });
+            }
+            let data = Bytes::copy_from_slice(&body[..position]);
+            return Ok((data, Some(parse_trailers(payload)?)));
+        }
+        position = end;
+    }
+    Ok((Bytes::copy_from_slice(body), None))
+}
+
+/// Parses the "name:value" lines of a trailers frame.
+fn parse_trailers(payload: &[u8]) -> Result<HeaderMap> {
+    let text = std::str::from_utf8(payload).map_err(|e| GrpcWebError::InvalidFrame {
+        message: e.to_string(),
+    })?;
+    let mut trailers = HeaderMap::new();
+    for line in text.split("\r\n").filter(|l| !l.is_empty()) {
+        let invalid = || GrpcWebError::InvalidFrame {
+            message: format!("invalid trailer '{line}'"),
+        };
+        let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+        let name = HeaderName::from_bytes(name.trim().to_ascii_lowercase().as_bytes())
+            .map_err(|_| invalid())?;
+        let value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+        trailers.append(name, value);
+    }
+    Ok(trailers)
+}
+
+/**
+ * Body of a grpc-web reply fetched whole: its message frames, then its
+ * trailers.
+ */
+#[derive(Debug)]
+pub struct GrpcWebBody {
+    data: Option<Bytes>,
+    trailers: Option<HeaderMap>,
+}
+
+impl Body for GrpcWebBody {
+    type Data = Bytes;
+    type Error = GrpcWebError;
+
+    fn poll_data(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        Poll::Ready(self.data.take().filter(|d| !d.is_empty()).map(Ok))
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>>> {
+        Poll::Ready(Ok(self.trailers.take()))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.data.as_ref().is_none_or(|d| d.is_empty()) && self.trailers.is_none()
+    }
+}
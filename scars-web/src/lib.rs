@@ -0,0 +1,16 @@
+//! Client of the DomainManager and FileSystem services of a domain over
+//! grpc-web, building for wasm32-unknown-unknown so that the browser
+//! tooling, e.g. a domain dashboard, talks to the DomainManager
+//! directly.
+//!
+//! The generated clients are given a GrpcWebChannel, fetching their
+//! calls through the FetchTrait of an HTTP binding: the BrowserFetch of
+//! the browser feature in the browsers. The CF common types are the
+//! ones of scars, from scars-types.
+
+pub use scars_types::common_types;
+pub mod grpc_web;
+pub mod rpc;
+
+#[cfg(feature = "browser")]
+pub mod browser;
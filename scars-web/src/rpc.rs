@@ -0,0 +1,49 @@
+use scars_types::wire::{value_from_wire, value_to_wire};
+
+use super::common_types::{DataType, Properties};
+
+/**
+ * Generated client of the Device gRPC service, of the properties.
+ */
+pub mod device {
+    tonic::include_proto!("device");
+}
+
+/**
+ * Generated client of the DomainManager gRPC service. The streams of
+ * the snake case event subscriptions are named after them.
+ */
+#[allow(non_camel_case_types)]
+pub mod domain_manager {
+    tonic::include_proto!("domain_manager");
+}
+
+/**
+ * Generated client of the FileSystem gRPC service. The stream of the
+ * snake case read is named after it.
+ */
+#[allow(non_camel_case_types)]
+pub mod file_system {
+    tonic::include_proto!("file_system");
+}
+
+/// Encodes properties for the wire, their values as JSON.
+pub fn properties_to_wire(properties: &Properties) -> Vec<device::Property> {
+    properties
+        .iter()
+        .map(|p| device::Property {
+            id: p.id.clone(),
+            value: value_to_wire(&p.value),
+        })
+        .collect()
+}
+
+/// Decodes properties received from the wire.
+pub fn properties_from_wire(
+    properties: &[device::Property],
+) -> Result<Properties, serde_json::Error> {
+    properties
+        .iter()
+        .map(|p| value_from_wire(&p.value).map(|value| DataType::new(&p.id, value)))
+        .collect()
+}
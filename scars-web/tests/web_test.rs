@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use hyper::{Body, Client, Request};
+    use tokio::net::TcpListener;
+    use tonic::Code;
+
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::file_system::FileSystem;
+    use scars_web::common_types::{AnyValue, DataType};
+    use scars_web::grpc_web::{split_trailers, FetchFuture, FetchResponse, FetchTrait, GrpcWebChannel, GrpcWebError, GRPC_WEB_CONTENT_TYPE};
+    use scars_web::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+    use scars_web::rpc::domain_manager::{ApplicationFactoriesRequest, InstallApplicationRequest, QueryApplicationRequest, StartApplicationRequest};
+    use scars_web::rpc::file_system::file_system_client::FileSystemClient;
+    use scars_web::rpc::file_system::{ListRequest, ReadRequest};
+    use scars_web::rpc::{properties_from_wire, properties_to_wire};
+
+    /// HTTP binding of the calls posted with hyper, as the browsers fetch them.
+    struct HyperFetch;
+
+    impl FetchTrait for HyperFetch {
+        fn fetch(&self, url: &str, headers: &[(String, String)], body: Vec<u8>) -> FetchFuture {
+            let mut request = Request::post(url);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let request = request.body(Body::from(body)).unwrap();
+            Box::pin(async move {
+                let response = Client::new().request(request).await.map_err(|e| GrpcWebError::FetchError { message: e.to_string() })?;
+                let status = response.status().as_u16();
+                let headers = response.headers().iter().map(|(n, v)| (n.to_string(), v.to_str().unwrap().to_string())).collect();
+                let body = hyper::body::to_bytes(response.into_body()).await.unwrap().to_vec();
+                Ok(FetchResponse { status, headers, body })
+            })
+        }
+    }
+
+    /// HTTP binding replying with the same response to any call.
+    struct FixedFetch(FetchResponse);
+
+    impl FetchTrait for FixedFetch {
+        fn fetch(&self, _url: &str, _headers: &[(String, String)], _body: Vec<u8>) -> FetchFuture {
+            let response = self.0.clone();
+            Box::pin(async move { Ok(response) })
+        }
+    }
+
+    /// Writes a waveform with one component in the directory.
+    fn write_waveform(root: &Path) {
+        let files = [
+            ("waveforms/fm/fm.sad.xml", r#"<softwareassembly id="DCE:fm" name="fm"><componentfiles><componentfile id="demod_file" type="SPD"><localfile name="../../components/demod/demod.spd.xml"/></componentfile></componentfiles></softwareassembly>"#),
+            ("components/demod/demod.spd.xml", r#"<softpkg id="DCE:demod" name="demod"><propertyfile><localfile name="demod.prf.xml"/></propertyfile><descriptor><localfile name="demod.scd.xml"/></descriptor></softpkg>"#),
+            ("components/demod/demod.prf.xml", "<properties/>"),
+            ("components/demod/demod.scd.xml", "<softwarecomponent/>"),
+        ];
+        for (name, xml) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, xml).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_domain_manager() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(root.path()))).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}/", listener.local_addr().unwrap());
+        let served = tokio::spawn(domain.clone().run(listener));
+
+        //the DomainManager is called over grpc-web
+        let channel = GrpcWebChannel::new(&endpoint, Arc::new(HyperFetch));
+        assert_eq!(channel.endpoint(), endpoint.trim_end_matches('/'));
+        let mut client = DomainManagerClient::new(channel.clone());
+        let installed = client.install_application(InstallApplicationRequest { profile_file_name: "/dom/waveforms/fm/fm.sad.xml".to_string() }).await.unwrap();
+        assert_eq!(installed.into_inner().identifier, "DCE:fm");
+        let factories = client.application_factories(ApplicationFactoriesRequest {}).await.unwrap().into_inner().application_factories;
+        assert_eq!(factories.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["fm"]);
+
+        //the statuses of the failed calls come back with their code
+        let status = client.start_application(StartApplicationRequest { identifier: "DCE:am".to_string() }).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        let status = client.query_application(QueryApplicationRequest { identifier: "DCE:am".to_string(), properties: properties_to_wire(&vec![DataType::new("frequency", AnyValue::Double(0.0))]) }).await.unwrap_err();
+        assert_ne!(status.code(), Code::Ok);
+
+        //so is the FileSystem of the domain, the server streams being received once ended
+        let mut fs = FileSystemClient::new(channel);
+        let files = fs.list(ListRequest { pattern: "/dom/*".to_string() }).await.unwrap().into_inner().files;
+        assert_eq!(files.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), vec!["components", "waveforms"]);
+        let mut chunks = fs.read(ReadRequest { file_name: "/dom/components/demod/demod.prf.xml".to_string(), chunk_size: 4 }).await.unwrap().into_inner();
+        let mut data = Vec::new();
+        while let Some(chunk) = chunks.message().await.unwrap() {
+            data.extend(chunk.data);
+        }
+        assert_eq!(data, b"<properties/>");
+
+        domain.shutdown();
+        served.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web_channel() {
+        //the replies are message frames followed by a trailers frame
+        let mut body = vec![0, 0, 0, 0, 2, 8, 1];
+        let trailers = b"grpc-status:0\r\nGrpc-Message: done\r\n";
+        body.extend([0x80, 0, 0, 0, trailers.len() as u8]);
+        body.extend(trailers);
+        let (data, trailers) = split_trailers(&body).unwrap();
+        assert_eq!(data.as_ref(), &body[..7]);
+        let trailers = trailers.unwrap();
+        assert_eq!((trailers["grpc-status"].to_str().unwrap(), trailers["grpc-message"].to_str().unwrap()), ("0", "done"));
+        assert_eq!(split_trailers(&body[..7]).unwrap().1, None);
+        match split_trailers(&body[..10]) {
+            Err(GrpcWebError::InvalidFrame { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match split_trailers(&[0x80, 0, 0, 0, 4, b'g', b'r', b'p', b'c']) {
+            Err(GrpcWebError::InvalidFrame { message }) => assert!(message.contains("grpc")),
+            r => panic!("{:?}", r),
+        }
+
+        //the replies of an endpoint not serving grpc-web fail the calls
+        let fetch = FixedFetch(FetchResponse { status: 415, headers: vec![("content-type".to_string(), GRPC_WEB_CONTENT_TYPE.to_string())], body: vec![] });
+        let mut client = DomainManagerClient::new(GrpcWebChannel::new("http://localhost:1", Arc::new(fetch)));
+        let status = client.application_factories(ApplicationFactoriesRequest {}).await.unwrap_err();
+        assert!(status.message().contains("HttpError: status: 415."));
+
+        //a trailers-only reply gives its status with the headers
+        let fetch = FixedFetch(FetchResponse { status: 200, headers: vec![("grpc-status".to_string(), "5".to_string()), ("grpc-message".to_string(), "unknown".to_string())], body: vec![] });
+        let mut client = DomainManagerClient::new(GrpcWebChannel::new("http://localhost:1", Arc::new(fetch)));
+        let status = client.application_factories(ApplicationFactoriesRequest {}).await.unwrap_err();
+        assert_eq!((status.code(), status.message()), (Code::NotFound, "unknown"));
+        assert_eq!(properties_from_wire(&properties_to_wire(&vec![DataType::new("gain", AnyValue::Float(0.5))])).unwrap(), vec![DataType::new("gain", AnyValue::Float(0.5))]);
+    }
+}
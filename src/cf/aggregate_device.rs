@@ -0,0 +1,228 @@
+use thiserror::Error;
+
+use super::common_types::Properties;
+use super::device::{
+    self, AdminType, Device, DeviceRef, DeviceTrait, OperationalType, UsageType,
+};
+
+/**
+ * Convienence enum definition that includes all AggregateDeviceTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum AggregateDeviceError {
+    /**
+     * This exception indicates an invalid object reference error.
+     */
+    #[error("InvalidObjectReference: msg: '{message}'.")]
+    InvalidObjectReference { message: String },
+}
+
+/*
+ * Convienence type definition that includes all AggregateDeviceTrait returned errors.
+ */
+pub type Result<T, E = AggregateDeviceError> = anyhow::Result<T, E>;
+
+/**
+ * This interface provides the required behavior that is needed to add
+ * and remove child devices from a parent device, so that a composite
+ * device (e.g. a carrier card) can expose the devices it is made of.
+ */
+pub trait AggregateDeviceTrait {
+    /// The readonly devices attribute contains the child devices of this device.
+    fn devices(&self) -> Vec<DeviceRef>;
+
+    /// This operation adds a child device to this device.
+    fn add_device(&mut self, associated_device: DeviceRef, identifier: &str) -> Result<()>;
+
+    /// This operation removes a child device from this device.
+    fn remove_device(&mut self, identifier: &str) -> Result<()>;
+}
+
+/**
+ * Parent device whose states are aggregated with those of its children:
+ * it is DISABLED when any child is, BUSY only when all of them are, and
+ * it only reaches LOCKED once its whole aggregation is LOCKED.
+ */
+pub struct AggregateDevice {
+    device: Device,
+    children: Vec<(String, DeviceRef)>,
+}
+
+impl AggregateDevice {
+    pub fn new(device: Device) -> AggregateDevice {
+        AggregateDevice {
+            device,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the embedded device state model.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the mutable embedded device state model.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    /// Returns the child device registered under the identifier.
+    pub fn find_device(&self, identifier: &str) -> Option<DeviceRef> {
+        self.children
+            .iter()
+            .find(|(id, _)| id == identifier)
+            .map(|(_, d)| d.clone())
+    }
+}
+
+impl DeviceTrait for AggregateDevice {
+    fn identifier(&self) -> &str {
+        self.device.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.device.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.device.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        let states: Vec<UsageType> = std::iter::once(self.device.usage_state())
+            .chain(self.children.iter().map(|(_, d)| d.lock().unwrap().usage_state()))
+            .collect();
+
+        if states.iter().all(|s| *s == UsageType::BUSY) {
+            UsageType::BUSY
+        } else if states.iter().all(|s| *s == UsageType::IDLE) {
+            UsageType::IDLE
+        } else {
+            UsageType::ACTIVE
+        }
+    }
+
+    /**
+     * SCA245
+     * The adminState transitions to the LOCKED state when the device's usageState is
+     * IDLE and its entire aggregation of Device Components are LOCKED.
+     */
+    fn admin_state(&self) -> AdminType {
+        let admin_state = self.device.admin_state();
+        let aggregation_locked = self
+            .children
+            .iter()
+            .all(|(_, d)| d.lock().unwrap().admin_state() == AdminType::LOCKED);
+
+        if admin_state == AdminType::LOCKED && !aggregation_locked {
+            AdminType::SHUTTING_DOWN
+        } else {
+            admin_state
+        }
+    }
+
+    /**
+     * SCA245
+     * The adminState attribute, upon being commanded to be LOCKED, shall set the
+     * adminState to LOCKED for its entire aggregation of Device Components (if it
+     * has any).
+     */
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        for (_, d) in &self.children {
+            d.lock().unwrap().set_admin_state(admin_state);
+        }
+        self.device.set_admin_state(admin_state);
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        let disabled = std::iter::once(self.device.operational_state())
+            .chain(self.children.iter().map(|(_, d)| d.lock().unwrap().operational_state()))
+            .any(|s| s == OperationalType::DISABLED);
+
+        if disabled {
+            OperationalType::DISABLED
+        } else {
+            OperationalType::ENABLED
+        }
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.device.allocation_properties()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.device.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.device.deallocate_capacity(capacities)
+    }
+}
+
+impl AggregateDeviceTrait for AggregateDevice {
+    /**
+     * SCA292
+     * The readonly devices attribute shall return a list of devices that have been
+     * added to this device or a sequence length of zero if the device has no
+     * aggregation relationships with other devices.
+     */
+    fn devices(&self) -> Vec<DeviceRef> {
+        self.children.iter().map(|(_, d)| d.clone()).collect()
+    }
+
+    /**
+     * SCA293
+     * The addDevice operation shall add the input associatedDevice parameter to the
+     * AggregateDevice's devices attribute when the associatedDevice associated with
+     * the input identifier parameter does not exist in the devices attribute.
+     * SCA295
+     * The addDevice operation shall raise the CF::InvalidObjectReference when the input
+     * associatedDevice parameter is a nil object reference.
+     */
+    fn add_device(&mut self, associated_device: DeviceRef, identifier: &str) -> Result<()> {
+        //verify the reference designates the identified device
+        if associated_device.lock().unwrap().identifier() != identifier {
+            return Err(AggregateDeviceError::InvalidObjectReference {
+                message: format!("device reference is not '{identifier}'"),
+            });
+        }
+
+        //already registered devices are kept as they are
+        if self.children.iter().any(|(id, _)| id == identifier) {
+            return Ok(());
+        }
+
+        //a child joining a locked aggregation is locked as well
+        if self.device.admin_state() != AdminType::UNLOCKED {
+            associated_device
+                .lock()
+                .unwrap()
+                .set_admin_state(AdminType::LOCKED);
+        }
+
+        self.children.push((identifier.to_string(), associated_device));
+        Ok(())
+    }
+
+    /**
+     * SCA296
+     * The removeDevice operation shall remove the device that corresponds to the input
+     * identifier parameter from the AggregateDevice's devices attribute.
+     * SCA297
+     * The removeDevice operation shall raise the CF::InvalidObjectReference when the
+     * device that corresponds to the input identifier parameter is a nil object
+     * reference or does not exist in the AggregateDevice devices attribute.
+     */
+    fn remove_device(&mut self, identifier: &str) -> Result<()> {
+        let index = self
+            .children
+            .iter()
+            .position(|(id, _)| id == identifier)
+            .ok_or_else(|| AggregateDeviceError::InvalidObjectReference {
+                message: format!("device '{identifier}' not found"),
+            })?;
+
+        self.children.remove(index);
+        Ok(())
+    }
+}
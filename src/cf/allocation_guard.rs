@@ -0,0 +1,133 @@
+use std::fmt;
+
+use tonic::transport::Channel;
+use tonic::Status;
+
+use super::allocation_manager::{
+    self, AllocationManagerRef, AllocationRequest, AllocationResponse,
+};
+use super::common_types::Properties;
+use super::device::{self, DeviceRef};
+use super::rpc::device::device_client::DeviceClient;
+use super::rpc::device::CapacityRequest;
+use super::rpc::properties_to_wire;
+
+/**
+ * Guard over allocated capacities, giving them back when dropped unless
+ * committed. Callers creating an application keep the guards of the
+ * allocations made so far, so that any failure, early return or panic
+ * releases them; once the application is created the guards are
+ * committed and the application takes over the deallocation. The
+ * release also runs while unwinding, ignoring poisoned locks.
+ */
+pub struct AllocationGuard {
+    release: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl AllocationGuard {
+    /// Creates a guard running the release when dropped.
+    pub fn new<F: FnOnce() + Send + 'static>(release: F) -> AllocationGuard {
+        AllocationGuard {
+            release: Some(Box::new(release)),
+        }
+    }
+
+    /**
+     * Allocates the capacities from a device, returning None when they
+     * are not available.
+     */
+    pub fn allocate_capacity(
+        device: &DeviceRef,
+        capacities: &Properties,
+    ) -> device::Result<Option<AllocationGuard>> {
+        if !device.lock().unwrap().allocate_capacity(capacities)? {
+            return Ok(None);
+        }
+
+        let (device, capacities) = (device.clone(), capacities.clone());
+        Ok(Some(AllocationGuard::new(move || {
+            let mut device = device.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = device.deallocate_capacity(&capacities);
+        })))
+    }
+
+    /**
+     * Allocates the capacities from a device served over gRPC, returning
+     * None when they are not available. The release is sent from a task
+     * of the current tokio runtime.
+     */
+    pub async fn allocate_remote(
+        client: &mut DeviceClient<Channel>,
+        capacities: &Properties,
+    ) -> Result<Option<AllocationGuard>, Status> {
+        let request = CapacityRequest {
+            capacities: properties_to_wire(capacities),
+        };
+        if !client
+            .allocate_capacity(request.clone())
+            .await?
+            .into_inner()
+            .allocated
+        {
+            return Ok(None);
+        }
+
+        let mut client = client.clone();
+        let runtime = tokio::runtime::Handle::current();
+        Ok(Some(AllocationGuard::new(move || {
+            runtime.spawn(async move {
+                let _ = client.deallocate_capacity(request).await;
+            });
+        })))
+    }
+
+    /**
+     * Satisfies the requests through an allocation manager, the guard
+     * releasing all the allocations made.
+     */
+    pub fn allocate(
+        manager: &AllocationManagerRef,
+        requests: &[AllocationRequest],
+    ) -> allocation_manager::Result<(Vec<AllocationResponse>, AllocationGuard)> {
+        let responses = manager.lock().unwrap().allocate(requests)?;
+
+        let manager = manager.clone();
+        let allocation_ids: Vec<String> =
+            responses.iter().map(|r| r.allocation_id.clone()).collect();
+        Ok((
+            responses,
+            AllocationGuard::new(move || {
+                let mut manager = manager.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = manager.deallocate(&allocation_ids);
+            }),
+        ))
+    }
+
+    /// Keeps the allocations, the caller taking over their deallocation.
+    pub fn commit(mut self) {
+        self.release = None;
+    }
+
+    /// Releases the allocations now.
+    pub fn release(mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
+
+impl fmt::Debug for AllocationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AllocationGuard")
+            .field("committed", &self.release.is_none())
+            .finish()
+    }
+}
+
+impl Drop for AllocationGuard {
+    fn drop(&mut self) {
+        if let Some(release) = self.release.take() {
+            release();
+        }
+    }
+}
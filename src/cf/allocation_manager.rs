@@ -0,0 +1,771 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::allocation_guard::AllocationGuard;
+use super::blocking_pool::{BlockingError, BlockingPool};
+use super::common_types::{ActionType, DataType, Properties};
+use super::device::{AdminType, DeviceRef, OperationalType, UsageType};
+use super::events::{DomainManagementEvent, EventChannel, SourceCategoryType};
+
+/// The time given by default to a device to answer an allocateCapacity call.
+pub const DEFAULT_ALLOCATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Convienence enum definition that includes all AllocationManagerTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum AllocationManagerError {
+    /**
+     * This exception indicates that some allocation requests could not be
+     * satisfied by any registered device. No allocation has been made.
+     */
+    #[error("AllocationFailed: request: '{request_id}', msg: '{message}'.")]
+    AllocationFailed { request_id: String, message: String },
+    /**
+     * This exception indicates that some allocation requests could not be
+     * satisfied, the devices able to satisfy them not answering within
+     * the allocation timeout (CF_ETIMEDOUT). No allocation has been made.
+     */
+    #[error("AllocationTimedOut: request: '{request_id}', msg: '{message}'.")]
+    AllocationTimedOut { request_id: String, message: String },
+    /**
+     * This exception indicates that some allocation ids are unknown.
+     * The list contains the invalid ids.
+     */
+    #[error("InvalidAllocationId: {invalid_allocation_ids:?}.")]
+    InvalidAllocationId { invalid_allocation_ids: Vec<String> },
+}
+
+/*
+ * Convienence type definition that includes all AllocationManagerTrait returned errors.
+ */
+pub type Result<T, E = AllocationManagerError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines an allocation property together with the action
+ * used to evaluate it against the devices.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocationProperty {
+    pub property: DataType,
+    pub action: ActionType,
+}
+
+impl AllocationProperty {
+    pub fn new(property: DataType, action: ActionType) -> AllocationProperty {
+        AllocationProperty { property, action }
+    }
+}
+
+/**
+ * This type defines a request for allocating a set of properties on a
+ * single device. When requested devices are given, they are tried
+ * before the other registered devices. When candidate devices are
+ * given, the request is only evaluated against them.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct AllocationRequest {
+    pub request_id: String,
+    pub allocation_properties: Vec<AllocationProperty>,
+    pub requested_devices: Vec<String>,
+    pub candidate_devices: Vec<String>,
+    pub source_id: String,
+}
+
+/**
+ * This type defines the outcome of a satisfied allocation request.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllocationResponse {
+    pub request_id: String,
+    pub allocation_id: String,
+    pub allocated_device: String,
+}
+
+/**
+ * This type describes an outstanding allocation.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AllocationStatus {
+    pub allocation_id: String,
+    pub request_id: String,
+    pub source_id: String,
+    pub allocated_device: String,
+    /// The capacities handed to the device allocateCapacity operation.
+    pub allocated_capacities: Properties,
+    /// True once the allocated device has vanished from the domain.
+    pub failed: bool,
+}
+
+/**
+ * This type describes the capacities of a registered device: its
+ * states, its current allocation properties and the allocations
+ * outstanding on it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceCapacities {
+    pub identifier: String,
+    pub label: String,
+    pub admin_state: AdminType,
+    pub operational_state: OperationalType,
+    pub usage_state: UsageType,
+    pub allocation_properties: Properties,
+    /// The failed allocations of a previous registration of the device included.
+    pub allocations: Vec<AllocationStatus>,
+}
+
+/**
+ * This interface provides the ability to allocate properties across
+ * the registered devices and to keep track of the allocations made.
+ */
+pub trait AllocationManagerTrait {
+    /// This operation satisfies all the requests, or none of them.
+    fn allocate(&mut self, requests: &[AllocationRequest]) -> Result<Vec<AllocationResponse>>;
+
+    /// This operation releases the allocations designated by the ids.
+    fn deallocate(&mut self, allocation_ids: &[String]) -> Result<()>;
+
+    /// This operation returns the outstanding allocations.
+    fn list_allocations(&self) -> Vec<AllocationStatus>;
+
+    /// This operation returns the devices the allocations are made on.
+    fn registered_devices(&self) -> Vec<DeviceRef>;
+
+    /**
+     * This operation returns a copy of the capacities of the devices
+     * available to the allocations, evaluating requests without
+     * allocating from the devices.
+     */
+    fn capacity_model(&self) -> CapacityModel {
+        CapacityModel::new(&self.registered_devices(), self.list_allocations())
+    }
+
+    /// This operation returns the capacities of the registered devices.
+    fn device_capacities(&self) -> Vec<DeviceCapacities> {
+        let allocations = self.list_allocations();
+        self.registered_devices()
+            .iter()
+            .map(|device| {
+                let device = device.lock().unwrap();
+                DeviceCapacities {
+                    identifier: device.identifier().to_string(),
+                    label: device.label().to_string(),
+                    admin_state: device.admin_state(),
+                    operational_state: device.operational_state(),
+                    usage_state: device.usage_state(),
+                    allocation_properties: device.allocation_properties(),
+                    allocations: allocations
+                        .iter()
+                        .filter(|a| a.allocated_device == device.identifier())
+                        .cloned()
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+/**
+ * Shared reference to an allocation manager.
+ */
+pub type AllocationManagerRef = Arc<Mutex<dyn AllocationManagerTrait + Send>>;
+
+/**
+ * Allocation manager evaluating allocation requests against a set of
+ * registered devices. Matching properties are compared with the device
+ * allocation properties, external properties and the properties the
+ * device does not advertise (e.g. struct tuner allocations) are handed
+ * to the device allocateCapacity operation.
+ */
+pub struct AllocationManager {
+    devices: Vec<DeviceRef>,
+    /// The identifiers of the devices, read without locking a device that may be wedged.
+    identifiers: Vec<String>,
+    /// The devices withdrawn from the allocations while unavailable, by identifier.
+    unavailable: Vec<String>,
+    allocations: Vec<(AllocationStatus, DeviceRef)>,
+    next_allocation: u64,
+    event_channel: Option<(String, EventChannel<DomainManagementEvent>)>,
+    allocation_timeout: Duration,
+    /// The pool the devices evaluate the requests on.
+    blocking_pool: BlockingPool,
+}
+
+impl Default for AllocationManager {
+    fn default() -> Self {
+        AllocationManager {
+            devices: Vec::new(),
+            identifiers: Vec::new(),
+            unavailable: Vec::new(),
+            allocations: Vec::new(),
+            next_allocation: 0,
+            event_channel: None,
+            allocation_timeout: DEFAULT_ALLOCATION_TIMEOUT,
+            blocking_pool: BlockingPool::shared(),
+        }
+    }
+}
+
+impl fmt::Debug for AllocationManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let allocations: Vec<&AllocationStatus> = self.allocations.iter().map(|(a, _)| a).collect();
+        f.debug_struct("AllocationManager")
+            .field("devices", &self.identifiers)
+            .field("allocations", &allocations)
+            .field("allocation_timeout", &self.allocation_timeout)
+            .finish()
+    }
+}
+
+impl AllocationManager {
+    pub fn new() -> AllocationManager {
+        AllocationManager::default()
+    }
+
+    /**
+     * Sets the time given to a device to evaluate a request, its
+     * allocateCapacity call included. A device not answering in time is
+     * passed over, the request timing out when no other device
+     * satisfies it.
+     */
+    pub fn with_allocation_timeout(mut self, allocation_timeout: Duration) -> AllocationManager {
+        self.allocation_timeout = allocation_timeout;
+        self
+    }
+
+    /**
+     * Sets the pool the devices evaluate the requests on, bounding the
+     * threads left waiting for the devices not answering.
+     */
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> AllocationManager {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /**
+     * Sets the channel the device registrations and the failed
+     * allocations are published onto, on behalf of the producer.
+     */
+    pub fn with_event_channel(
+        mut self,
+        event_channel: EventChannel<DomainManagementEvent>,
+        producer_id: &str,
+    ) -> AllocationManager {
+        self.event_channel = Some((producer_id.to_string(), event_channel));
+        self
+    }
+
+    /**
+     * Makes a device available to the allocations. A device registering
+     * again under the same identifier (e.g. a rebooted FPGA) replaces the
+     * previous one, whose allocations are lost.
+     */
+    pub fn register_device(&mut self, device: DeviceRef) {
+        let (identifier, label) = {
+            let d = device.lock().unwrap();
+            (d.identifier().to_string(), d.label().to_string())
+        };
+        self.unregister_device(&identifier);
+        self.devices.push(device);
+        self.identifiers.push(identifier.clone());
+
+        self.publish(|producer_id| DomainManagementEvent::ObjectAdded {
+            producer_id,
+            source_id: identifier,
+            source_name: label,
+            source_category: SourceCategoryType::DEVICE,
+        });
+    }
+
+    /**
+     * Removes a device from the allocations, returning it when registered.
+     * The outstanding allocations made on the device are marked as failed
+     * and reported to their requesters; they are kept until deallocated.
+     */
+    pub fn unregister_device(&mut self, identifier: &str) -> Option<DeviceRef> {
+        let index = self.identifiers.iter().position(|i| i == identifier)?;
+        let device = self.devices.remove(index);
+        self.identifiers.remove(index);
+        self.unavailable.retain(|i| i != identifier);
+
+        let mut lost = Vec::new();
+        for (status, _) in &mut self.allocations {
+            if status.allocated_device == identifier && !status.failed {
+                status.failed = true;
+                lost.push(status.clone());
+            }
+        }
+        for status in lost {
+            self.publish(|producer_id| DomainManagementEvent::AllocationFailed {
+                producer_id,
+                allocation_id: status.allocation_id,
+                source_id: status.source_id,
+                allocated_device: status.allocated_device,
+            });
+        }
+
+        let label = device.lock().unwrap().label().to_string();
+        self.publish(|producer_id| DomainManagementEvent::ObjectRemoved {
+            producer_id,
+            source_id: identifier.to_string(),
+            source_name: label,
+            source_category: SourceCategoryType::DEVICE,
+        });
+        Some(device)
+    }
+
+    /**
+     * Withdraws a registered device from the allocations while it is
+     * unavailable, e.g. its DeviceManager not answering the heartbeats,
+     * or makes it available again. The outstanding allocations made on
+     * the device are kept.
+     */
+    pub fn set_available(&mut self, identifier: &str, available: bool) {
+        self.unavailable.retain(|i| i != identifier);
+        if !available && self.identifiers.iter().any(|i| i == identifier) {
+            self.unavailable.push(identifier.to_string());
+        }
+    }
+
+    /// Publishes an event built for the producer, when a channel is set.
+    fn publish<F: FnOnce(String) -> DomainManagementEvent>(&self, event: F) {
+        if let Some((producer_id, channel)) = &self.event_channel {
+            channel.push(event(producer_id.clone()));
+        }
+    }
+
+    /// Returns the registered devices.
+    pub fn devices(&self) -> &[DeviceRef] {
+        &self.devices
+    }
+
+    /**
+     * Returns the candidate devices in evaluation order for the request:
+     * the requested devices first, then the others, the unavailable ones
+     * being passed over.
+     */
+    fn candidates(&self, request: &AllocationRequest) -> Vec<DeviceRef> {
+        let (mut requested, others): (Vec<_>, Vec<_>) = self
+            .identifiers
+            .iter()
+            .zip(&self.devices)
+            .filter(|(id, _)| {
+                (request.candidate_devices.is_empty() || request.candidate_devices.contains(id))
+                    && !self.unavailable.contains(id)
+            })
+            .partition(|(id, _)| request.requested_devices.contains(id));
+        requested.extend(others);
+        requested.into_iter().map(|(_, d)| d.clone()).collect()
+    }
+
+    /**
+     * Tries to satisfy the request on a device: matching properties are
+     * evaluated first, then the external ones are allocated under a guard.
+     */
+    fn try_allocate(
+        device: &DeviceRef,
+        request: &AllocationRequest,
+    ) -> Option<(Properties, AllocationGuard)> {
+        let d = device.lock().unwrap();
+
+        if d.admin_state() != AdminType::UNLOCKED
+            || d.operational_state() == OperationalType::DISABLED
+            || d.usage_state() == UsageType::BUSY
+        {
+            return None;
+        }
+
+        //properties not advertised by the device are left to its allocateCapacity
+        let properties = d.allocation_properties();
+        let advertised = |p: &AllocationProperty| {
+            properties
+                .iter()
+                .find(|dp| p.action != ActionType::EXTERNAL && dp.id == p.property.id)
+        };
+        let matching = request.allocation_properties.iter().all(|p| {
+            advertised(p).is_none_or(|dp| p.action.evaluate(&dp.value, &p.property.value))
+        });
+        if !matching {
+            return None;
+        }
+
+        let capacities: Properties = request
+            .allocation_properties
+            .iter()
+            .filter(|p| advertised(p).is_none())
+            .map(|p| p.property.clone())
+            .collect();
+        drop(d);
+        match AllocationGuard::allocate_capacity(device, &capacities) {
+            Ok(Some(guard)) => Some((capacities, guard)),
+            _ => None,
+        }
+    }
+
+    /**
+     * Tries to satisfy the request on a device from a thread of the pool,
+     * giving up once the timeout elapsed, e.g. on a wedged device. The
+     * capacities allocated past the timeout are given back by dropping
+     * their guard.
+     */
+    fn try_allocate_within(
+        &self,
+        device: &DeviceRef,
+        request: &AllocationRequest,
+    ) -> std::result::Result<Option<(Properties, AllocationGuard)>, BlockingError> {
+        let (device, request) = (device.clone(), request.clone());
+        self.blocking_pool.run_within(
+            self.allocation_timeout,
+            move || AllocationManager::try_allocate(&device, &request),
+            drop,
+        )
+    }
+}
+
+impl AllocationManagerTrait for AllocationManager {
+    /**
+     * Each request is satisfied by the first candidate device matching
+     * all its properties. When a request cannot be satisfied, the
+     * guards of the previous requests give their capacities back.
+     */
+    fn allocate(&mut self, requests: &[AllocationRequest]) -> Result<Vec<AllocationResponse>> {
+        let mut allocated: Vec<(AllocationStatus, DeviceRef)> = Vec::new();
+        let mut guards: Vec<AllocationGuard> = Vec::new();
+
+        for request in requests {
+            let mut timed_out = Vec::new();
+            let outcome = self.candidates(request).into_iter().find_map(|d| {
+                match self.try_allocate_within(&d, request) {
+                    Ok(allocated) => allocated.map(|c| (d, c)),
+                    Err(BlockingError::TimedOut { .. }) => {
+                        timed_out.push(d);
+                        None
+                    }
+                    Err(_) => None,
+                }
+            });
+
+            let Some((device, (capacities, guard))) = outcome else {
+                if timed_out.is_empty() {
+                    return Err(AllocationManagerError::AllocationFailed {
+                        request_id: request.request_id.clone(),
+                        message: "no registered device satisfies the request".to_string(),
+                    });
+                }
+                let identifiers: Vec<&str> = timed_out
+                    .iter()
+                    .filter_map(|d| self.devices.iter().position(|r| Arc::ptr_eq(r, d)))
+                    .map(|i| self.identifiers[i].as_str())
+                    .collect();
+                return Err(AllocationManagerError::AllocationTimedOut {
+                    request_id: request.request_id.clone(),
+                    message: format!(
+                        "{identifiers:?} did not answer within {:?}",
+                        self.allocation_timeout
+                    ),
+                });
+            };
+
+            guards.push(guard);
+            self.next_allocation += 1;
+            let allocated_device = device.lock().unwrap().identifier().to_string();
+            allocated.push((
+                AllocationStatus {
+                    allocation_id: format!("{}:{}", allocated_device, self.next_allocation),
+                    request_id: request.request_id.clone(),
+                    source_id: request.source_id.clone(),
+                    allocated_device,
+                    allocated_capacities: capacities,
+                    failed: false,
+                },
+                device,
+            ));
+        }
+
+        let responses = allocated
+            .iter()
+            .map(|(s, _)| AllocationResponse {
+                request_id: s.request_id.clone(),
+                allocation_id: s.allocation_id.clone(),
+                allocated_device: s.allocated_device.clone(),
+            })
+            .collect();
+        guards.into_iter().for_each(AllocationGuard::commit);
+        self.allocations.extend(allocated);
+
+        Ok(responses)
+    }
+
+    /**
+     * The ids are all verified before any capacity is given back, so
+     * that an invalid id leaves every allocation in place. Failed
+     * allocations are dropped without reaching their vanished device.
+     */
+    fn deallocate(&mut self, allocation_ids: &[String]) -> Result<()> {
+        let invalid_allocation_ids: Vec<String> = allocation_ids
+            .iter()
+            .filter(|id| !self.allocations.iter().any(|(s, _)| s.allocation_id == **id))
+            .cloned()
+            .collect();
+        if !invalid_allocation_ids.is_empty() {
+            return Err(AllocationManagerError::InvalidAllocationId {
+                invalid_allocation_ids,
+            });
+        }
+
+        let (released, kept) = std::mem::take(&mut self.allocations)
+            .into_iter()
+            .partition(|(s, _)| allocation_ids.contains(&s.allocation_id));
+        self.allocations = kept;
+
+        for (status, device) in released.into_iter().filter(|(s, _)| !s.failed) {
+            let _ = device
+                .lock()
+                .unwrap()
+                .deallocate_capacity(&status.allocated_capacities);
+        }
+        Ok(())
+    }
+
+    fn list_allocations(&self) -> Vec<AllocationStatus> {
+        self.allocations.iter().map(|(s, _)| s.clone()).collect()
+    }
+
+    fn registered_devices(&self) -> Vec<DeviceRef> {
+        self.devices.clone()
+    }
+
+    /// The unavailable devices are left out of the copy.
+    fn capacity_model(&self) -> CapacityModel {
+        let devices: Vec<DeviceRef> = self
+            .identifiers
+            .iter()
+            .zip(&self.devices)
+            .filter(|(id, _)| !self.unavailable.contains(id))
+            .map(|(_, d)| d.clone())
+            .collect();
+        CapacityModel::new(&devices, self.list_allocations())
+    }
+}
+
+/**
+ * Copy of the capacities of a set of devices, evaluating allocation
+ * requests as the AllocationManager does, without allocating anything
+ * from the devices, e.g. for the dry run of a deployment. The states
+ * and allocation properties of the devices are copied once; the
+ * capacities they advertise are taken from, and given back to, the
+ * copy. The capacities a device does not advertise (e.g. struct tuner
+ * allocations) cannot be evaluated without allocating them from the
+ * device, the copy not satisfying them.
+ */
+#[derive(Clone, Default)]
+pub struct CapacityModel {
+    devices: Vec<(DeviceCapacities, DeviceRef)>,
+    next_allocation: u64,
+}
+
+impl fmt::Debug for CapacityModel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let devices: Vec<&DeviceCapacities> = self.devices.iter().map(|(d, _)| d).collect();
+        f.debug_struct("CapacityModel")
+            .field("devices", &devices)
+            .finish()
+    }
+}
+
+impl CapacityModel {
+    /// Copies the capacities of the devices, along with their outstanding allocations.
+    pub fn new(devices: &[DeviceRef], allocations: Vec<AllocationStatus>) -> CapacityModel {
+        let devices = devices
+            .iter()
+            .map(|device| {
+                let d = device.lock().unwrap();
+                let capacities = DeviceCapacities {
+                    identifier: d.identifier().to_string(),
+                    label: d.label().to_string(),
+                    admin_state: d.admin_state(),
+                    operational_state: d.operational_state(),
+                    usage_state: d.usage_state(),
+                    allocation_properties: d.allocation_properties(),
+                    allocations: allocations
+                        .iter()
+                        .filter(|a| a.allocated_device == d.identifier())
+                        .cloned()
+                        .collect(),
+                };
+                (capacities, device.clone())
+            })
+            .collect();
+        CapacityModel {
+            devices,
+            next_allocation: 0,
+        }
+    }
+
+    /**
+     * Tries to satisfy the request on a copied device: matching
+     * properties are evaluated first, then the capacities are reduced
+     * in the copy. Returns the capacities allocated.
+     */
+    fn try_allocate(
+        device: &mut DeviceCapacities,
+        request: &AllocationRequest,
+    ) -> Option<Properties> {
+        if device.admin_state != AdminType::UNLOCKED
+            || device.operational_state == OperationalType::DISABLED
+            || device.usage_state == UsageType::BUSY
+        {
+            return None;
+        }
+
+        let properties = &mut device.allocation_properties;
+        let advertised = |p: &AllocationProperty| {
+            properties
+                .iter()
+                .find(|dp| p.action != ActionType::EXTERNAL && dp.id == p.property.id)
+        };
+        let matching = request.allocation_properties.iter().all(|p| {
+            advertised(p).is_none_or(|dp| p.action.evaluate(&dp.value, &p.property.value))
+        });
+        if !matching {
+            return None;
+        }
+
+        let capacities: Properties = request
+            .allocation_properties
+            .iter()
+            .filter(|p| advertised(p).is_none())
+            .map(|p| p.property.clone())
+            .collect();
+        for capacity in &capacities {
+            let available = properties.iter_mut().find(|dp| dp.id == capacity.id)?;
+            available.value = available.value.checked_sub(&capacity.value)?;
+        }
+        Some(capacities)
+    }
+}
+
+impl AllocationManagerTrait for CapacityModel {
+    /**
+     * Each request is satisfied by the first candidate device of the copy
+     * matching all its properties, the copy being left untouched when a
+     * request cannot be satisfied.
+     */
+    fn allocate(&mut self, requests: &[AllocationRequest]) -> Result<Vec<AllocationResponse>> {
+        let mut model = self.clone();
+        let mut responses = Vec::new();
+        for request in requests {
+            let mut order: Vec<usize> = (0..model.devices.len())
+                .filter(|i| {
+                    let id = &model.devices[*i].0.identifier;
+                    request.candidate_devices.is_empty() || request.candidate_devices.contains(id)
+                })
+                .collect();
+            order.sort_by_key(|i| {
+                !request
+                    .requested_devices
+                    .contains(&model.devices[*i].0.identifier)
+            });
+
+            let allocated = order.into_iter().find_map(|i| {
+                let mut device = model.devices[i].0.clone();
+                let capacities = CapacityModel::try_allocate(&mut device, request)?;
+                Some((i, device, capacities))
+            });
+            let Some((index, mut device, capacities)) = allocated else {
+                return Err(AllocationManagerError::AllocationFailed {
+                    request_id: request.request_id.clone(),
+                    message: "no registered device satisfies the request".to_string(),
+                });
+            };
+
+            //the ids of the outstanding allocations copied are passed over
+            let allocation_ids: Vec<String> = model
+                .list_allocations()
+                .into_iter()
+                .map(|a| a.allocation_id)
+                .collect();
+            let allocation_id = loop {
+                model.next_allocation += 1;
+                let id = format!("{}:{}", device.identifier, model.next_allocation);
+                if !allocation_ids.contains(&id) {
+                    break id;
+                }
+            };
+            let status = AllocationStatus {
+                allocation_id,
+                request_id: request.request_id.clone(),
+                source_id: request.source_id.clone(),
+                allocated_device: device.identifier.clone(),
+                allocated_capacities: capacities,
+                failed: false,
+            };
+            responses.push(AllocationResponse {
+                request_id: status.request_id.clone(),
+                allocation_id: status.allocation_id.clone(),
+                allocated_device: status.allocated_device.clone(),
+            });
+            device.allocations.push(status);
+            model.devices[index].0 = device;
+        }
+
+        *self = model;
+        Ok(responses)
+    }
+
+    /// The capacities released are given back to the copy.
+    fn deallocate(&mut self, allocation_ids: &[String]) -> Result<()> {
+        let allocations = self.list_allocations();
+        let invalid_allocation_ids: Vec<String> = allocation_ids
+            .iter()
+            .filter(|id| !allocations.iter().any(|a| a.allocation_id == **id))
+            .cloned()
+            .collect();
+        if !invalid_allocation_ids.is_empty() {
+            return Err(AllocationManagerError::InvalidAllocationId {
+                invalid_allocation_ids,
+            });
+        }
+
+        for (device, _) in &mut self.devices {
+            let (released, kept): (Vec<AllocationStatus>, _) =
+                std::mem::take(&mut device.allocations)
+                    .into_iter()
+                    .partition(|a| allocation_ids.contains(&a.allocation_id));
+            device.allocations = kept;
+            for capacity in released.iter().flat_map(|a| &a.allocated_capacities) {
+                let properties = &mut device.allocation_properties;
+                if let Some(available) = properties.iter_mut().find(|dp| dp.id == capacity.id) {
+                    if let Some(value) = available.value.checked_add(&capacity.value) {
+                        available.value = value;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn list_allocations(&self) -> Vec<AllocationStatus> {
+        self.devices
+            .iter()
+            .flat_map(|(d, _)| d.allocations.iter().cloned())
+            .collect()
+    }
+
+    fn registered_devices(&self) -> Vec<DeviceRef> {
+        self.devices.iter().map(|(_, d)| d.clone()).collect()
+    }
+
+    /// The capacities of the copy, those allocated from it deducted.
+    fn device_capacities(&self) -> Vec<DeviceCapacities> {
+        self.devices.iter().map(|(d, _)| d.clone()).collect()
+    }
+
+    fn capacity_model(&self) -> CapacityModel {
+        self.clone()
+    }
+}
@@ -0,0 +1,990 @@
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::allocation_manager::{AllocationManagerRef, AllocationStatus};
+use super::application_factory::UsesDeviceAssignmentType;
+use super::blocking_pool::{BlockingError, BlockingPool};
+use super::common_types::{AnyValue, DataType, Properties};
+use super::component_registry::ComponentRegistry;
+use super::executable_device::{
+    ExecutableDeviceError, ExecutableDeviceRef, ProcessId, ProcessStatus,
+};
+use super::profile::sad::{
+    ConnectionTarget, ExternalPort, ExternalProperty, PortKind, PortReference, SoftwareAssembly,
+};
+use super::property_store::PropertyReader;
+use super::resource::{self, ResourceError, ResourceRef, ResourceTrait};
+
+/// The time given by default to a component to start or stop.
+pub const DEFAULT_COMPONENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Convienence enum definition that includes all Application errors.
+ */
+#[derive(Error, Debug)]
+pub enum ApplicationError {
+    /**
+     * This exception indicates that a component failed or timed out to
+     * start. The components started before it are left started, the
+     * following ones are not started. The report tells the outcome for
+     * each component.
+     */
+    #[error("StartError: component: '{component_id}', msg: '{message}'.")]
+    StartError {
+        component_id: String,
+        message: String,
+        report: Vec<ComponentReport>,
+    },
+    /**
+     * This exception indicates that some components failed or timed out
+     * to stop, the first one being identified. The stop goes on past the
+     * failures, the report telling the outcome for each component.
+     */
+    #[error("StopError: component: '{component_id}', msg: '{message}'.")]
+    StopError {
+        component_id: String,
+        message: String,
+        report: Vec<ComponentReport>,
+    },
+    /**
+     * This exception indicates that some steps of the release failed.
+     * The release goes on past the failures, which are all reported.
+     */
+    #[error("ReleaseError: {messages:?}.")]
+    ReleaseError { messages: Vec<String> },
+}
+
+/*
+ * Convienence type definition that includes all Application returned errors.
+ */
+pub type Result<T, E = ApplicationError> = anyhow::Result<T, E>;
+
+/**
+ * This type describes a component deployed for an application, with
+ * the device it has been loaded and executed on.
+ */
+#[derive(Clone)]
+pub struct ApplicationComponent {
+    /// The component identifier handed to the execute operation.
+    pub identifier: String,
+    /// The id of the component instantiation in the SAD.
+    pub instantiation_id: String,
+    pub implementation_id: String,
+    pub device_id: String,
+    pub device: ExecutableDeviceRef,
+    /// The file loaded on the device.
+    pub loaded_file: String,
+    pub process_id: Option<ProcessId>,
+    /// The name the component registers itself under.
+    pub name_binding: String,
+    /// The endpoint the component registered with the Registrar service, if any.
+    pub endpoint: Option<String>,
+    pub resource: Option<ResourceRef>,
+    /// The snapshots of the properties of the resource, when it publishes them.
+    pub properties: Option<PropertyReader>,
+}
+
+impl ApplicationComponent {
+    /**
+     * Returns the values of properties of the component, read from their
+     * snapshots when the resource publishes them, so that the queries do
+     * not wait for the component to be done with another operation.
+     */
+    pub fn query(&self, properties: &Properties) -> resource::Result<Properties> {
+        if let Some(reader) = &self.properties {
+            return reader.query(properties);
+        }
+        match &self.resource {
+            Some(resource) => resource.lock().unwrap().query(properties),
+            None => Err(ResourceError::InvalidState {
+                message: format!("'{}' is not registered", self.identifier),
+            }),
+        }
+    }
+}
+
+impl fmt::Debug for ApplicationComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ApplicationComponent")
+            .field("identifier", &self.identifier)
+            .field("implementation_id", &self.implementation_id)
+            .field("device_id", &self.device_id)
+            .field("loaded_file", &self.loaded_file)
+            .field("process_id", &self.process_id)
+            .field("name_binding", &self.name_binding)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+/**
+ * This type defines the outcome of starting or stopping a component.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentOutcome {
+    DONE,
+    FAILED(String),
+    /// The component did not answer within the component timeout.
+    TIMED_OUT,
+    /// The component was not attempted, a previous one having failed.
+    SKIPPED,
+}
+
+impl fmt::Display for ComponentOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComponentOutcome::DONE => write!(f, "done"),
+            ComponentOutcome::FAILED(message) => write!(f, "{message}"),
+            ComponentOutcome::TIMED_OUT => write!(f, "timed out"),
+            ComponentOutcome::SKIPPED => write!(f, "skipped"),
+        }
+    }
+}
+
+/**
+ * This type reports the outcome of starting or stopping a component of
+ * an application, those of the nested applications included.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentReport {
+    pub component_id: String,
+    pub outcome: ComponentOutcome,
+}
+
+/**
+ * This type associates a component of an application with an element,
+ * e.g. its device or its implementation.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentElementType {
+    pub component_id: String,
+    pub element_id: String,
+}
+
+/**
+ * This type describes a connection made between the components of an
+ * application.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationConnection {
+    pub connection_id: String,
+    pub uses_port: PortReference,
+    pub target: ConnectionTarget,
+    /// The endpoint of the target the uses port is connected to.
+    pub endpoint: String,
+}
+
+/**
+ * This type reports the resource usage of a component of an
+ * application, as collected by the device executing it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentMetrics {
+    pub component_id: String,
+    pub device_id: String,
+    pub process_id: Option<ProcessId>,
+    /// The CPU usage, in percent of one core.
+    pub cpu_usage: f32,
+    /// The resident memory, in bytes.
+    pub memory: u64,
+    pub status: ProcessStatus,
+}
+
+/**
+ * This type aggregates the resource usage of the components of an
+ * application, those of its nested applications included.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplicationMetrics {
+    pub application_id: String,
+    /// The summed CPU usage of the components, in percent of one core.
+    pub cpu_usage: f32,
+    /// The summed resident memory of the components, in bytes.
+    pub memory: u64,
+    pub components: Vec<ComponentMetrics>,
+}
+
+/**
+ * Application created by an ApplicationFactory: the deployed components
+ * and nested applications along with the connections and the allocations
+ * made for them.
+ */
+pub struct Application {
+    identifier: String,
+    name: String,
+    profile: String,
+    components: Vec<ApplicationComponent>,
+    /// The applications of the nested assemblies, by instantiation id.
+    applications: Vec<(String, Application)>,
+    connections: Vec<ApplicationConnection>,
+    uses_devices: Vec<UsesDeviceAssignmentType>,
+    allocations: Option<(AllocationManagerRef, Vec<String>)>,
+    registry: ComponentRegistry,
+    /// The instantiation ids in start order, the assembly controller last.
+    start_order: Vec<String>,
+    assembly_controller: Option<String>,
+    /// The time given to each component to start or stop.
+    component_timeout: Duration,
+    /// The pool the components are started and stopped on.
+    component_pool: BlockingPool,
+    external_ports: Vec<ExternalPort>,
+    external_properties: Vec<ExternalProperty>,
+    started: bool,
+}
+
+impl fmt::Debug for Application {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Application")
+            .field("identifier", &self.identifier)
+            .field("name", &self.name)
+            .field("profile", &self.profile)
+            .field("components", &self.components)
+            .field("applications", &self.applications)
+            .field("connections", &self.connections)
+            .field("uses_devices", &self.uses_devices)
+            .field("allocation_ids", &self.allocation_ids())
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+impl Application {
+    /**
+     * Creates the application of an assembly, the components being
+     * started in ascending startorder, the ones without startorder
+     * next and the assembly controller last.
+     */
+    pub(crate) fn new(
+        identifier: &str,
+        name: &str,
+        profile: &str,
+        assembly: &SoftwareAssembly,
+        registry: ComponentRegistry,
+    ) -> Application {
+        let mut instantiations: Vec<_> = assembly
+            .placements
+            .iter()
+            .flat_map(|p| &p.instantiations)
+            .filter(|i| Some(&i.id) != assembly.assembly_controller.as_ref())
+            .collect();
+        instantiations.sort_by_key(|i| (i.start_order.is_none(), i.start_order));
+        let start_order = instantiations
+            .into_iter()
+            .map(|i| i.id.clone())
+            .chain(assembly.assembly_controller.clone())
+            .collect();
+
+        Application {
+            identifier: identifier.to_string(),
+            name: name.to_string(),
+            profile: profile.to_string(),
+            components: Vec::new(),
+            applications: Vec::new(),
+            connections: Vec::new(),
+            uses_devices: Vec::new(),
+            allocations: None,
+            registry,
+            start_order,
+            assembly_controller: assembly.assembly_controller.clone(),
+            component_timeout: DEFAULT_COMPONENT_TIMEOUT,
+            component_pool: BlockingPool::shared(),
+            external_ports: assembly.external_ports.clone(),
+            external_properties: assembly.external_properties.clone(),
+            started: false,
+        }
+    }
+
+    /// Sets the time given to each component to start or stop.
+    pub(crate) fn with_component_timeout(mut self, component_timeout: Duration) -> Application {
+        self.component_timeout = component_timeout;
+        self
+    }
+
+    /// Sets the pool the components are started and stopped on.
+    pub(crate) fn with_component_pool(mut self, component_pool: BlockingPool) -> Application {
+        self.component_pool = component_pool;
+        self
+    }
+
+    /// The readonly identifier attribute contains the instance-unique identifier of the application.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The readonly name attribute contains the name given to the create operation.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The readonly profile attribute contains the SAD pathname.
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// The readonly started attribute tells whether the application is started.
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    /// The readonly componentDevices attribute contains the device of each component.
+    pub fn component_devices(&self) -> Vec<ComponentElementType> {
+        self.component_elements(|c| c.device_id.clone())
+    }
+
+    /// The readonly componentImplementations attribute contains the SPD implementation of each component.
+    pub fn component_implementations(&self) -> Vec<ComponentElementType> {
+        self.component_elements(|c| c.implementation_id.clone())
+    }
+
+    /// The readonly componentNamingContexts attribute contains the name binding of each component.
+    pub fn component_naming_contexts(&self) -> Vec<ComponentElementType> {
+        self.component_elements(|c| c.name_binding.clone())
+    }
+
+    /// The readonly externalPorts attribute contains the ports visible on the application.
+    pub fn external_ports(&self) -> &[ExternalPort] {
+        &self.external_ports
+    }
+
+    /// The readonly externalProperties attribute contains the properties visible on the application.
+    pub fn external_properties(&self) -> &[ExternalProperty] {
+        &self.external_properties
+    }
+
+    fn component_elements<F>(&self, element: F) -> Vec<ComponentElementType>
+    where
+        F: Fn(&ApplicationComponent) -> String,
+    {
+        self.components
+            .iter()
+            .map(|c| ComponentElementType {
+                component_id: c.identifier.clone(),
+                element_id: element(c),
+            })
+            .collect()
+    }
+
+    /**
+     * Starts the components in start order, stopping at the first one
+     * failing or not answering within the component timeout. A nested
+     * application is started as a whole at the place of its
+     * instantiation.
+     */
+    pub fn start(&mut self) -> Result<()> {
+        let mut report = Vec::new();
+        self.started = self.start_components(&mut report);
+        match report.iter().find(|r| failed(&r.outcome)) {
+            Some(failure) => Err(ApplicationError::StartError {
+                component_id: failure.component_id.clone(),
+                message: failure.outcome.to_string(),
+                report,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /**
+     * Starts the components in start order into the report, the ones
+     * following a failure being skipped. Returns whether all started.
+     */
+    fn start_components(&mut self, report: &mut Vec<ComponentReport>) -> bool {
+        let mut failure = false;
+        for id in self.start_order.clone() {
+            if let Some(application) = self.application_mut(&id) {
+                if failure {
+                    application.skip(report);
+                } else {
+                    failure = !application.start_components(report);
+                    application.started = !failure;
+                }
+            } else if let Some(component) = self.component(&id) {
+                let Some(resource) = &component.resource else {
+                    continue;
+                };
+                let outcome = if failure {
+                    ComponentOutcome::SKIPPED
+                } else {
+                    call(
+                        &self.component_pool,
+                        resource,
+                        self.component_timeout,
+                        |r| r.start(),
+                    )
+                };
+                failure = failure || failed(&outcome);
+                report.push(ComponentReport {
+                    component_id: component.identifier.clone(),
+                    outcome,
+                });
+            }
+        }
+        !failure
+    }
+
+    /// Reports the components, those of the nested applications included, as skipped.
+    fn skip(&self, report: &mut Vec<ComponentReport>) {
+        for id in &self.start_order {
+            if let Some(application) = self.application(id) {
+                application.skip(report);
+            } else if let Some(component) = self.component(id).filter(|c| c.resource.is_some()) {
+                report.push(ComponentReport {
+                    component_id: component.identifier.clone(),
+                    outcome: ComponentOutcome::SKIPPED,
+                });
+            }
+        }
+    }
+
+    /**
+     * Stops the components in the reverse start order, the assembly
+     * controller first, going on past the ones failing or not answering
+     * within the component timeout.
+     */
+    pub fn stop(&mut self) -> Result<()> {
+        let mut report = Vec::new();
+        self.stop_components(&mut report);
+        match report.iter().find(|r| failed(&r.outcome)) {
+            Some(failure) => Err(ApplicationError::StopError {
+                component_id: failure.component_id.clone(),
+                message: failure.outcome.to_string(),
+                report,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Stops the components in the reverse start order into the report.
+    fn stop_components(&mut self, report: &mut Vec<ComponentReport>) {
+        for id in self.start_order.clone().iter().rev() {
+            if let Some(application) = self.application_mut(id) {
+                application.stop_components(report);
+            } else if let Some(component) = self.component(id) {
+                let Some(resource) = &component.resource else {
+                    continue;
+                };
+                report.push(ComponentReport {
+                    component_id: component.identifier.clone(),
+                    outcome: call(
+                        &self.component_pool,
+                        resource,
+                        self.component_timeout,
+                        |r| r.stop(),
+                    ),
+                });
+            }
+        }
+        self.started = false;
+    }
+
+    /**
+     * Returns the endpoint of a provides port of the application, mapped
+     * to a component port by the externalports of the SAD.
+     */
+    pub fn get_port(&self, name: &str) -> resource::Result<String> {
+        let (provider, identifier) = self.external_port_resource(name, PortKind::PROVIDES)?;
+        let endpoint = provider.lock().unwrap().get_provides_port(&identifier);
+        endpoint
+    }
+
+    /**
+     * SCA55
+     * The connectUsesPorts operation shall make a connection to the
+     * application components by input portConnections parameter, which
+     * identifies the application external uses ports to be connected to.
+     * Application external ports are associated with the application
+     * components.
+     */
+    pub fn connect_uses_port(
+        &self,
+        name: &str,
+        connection_id: &str,
+        endpoint: &str,
+    ) -> resource::Result<()> {
+        let (user, identifier) = self.external_port_resource(name, PortKind::USES)?;
+        let connected =
+            user.lock()
+                .unwrap()
+                .connect_uses_port(&identifier, connection_id, endpoint);
+        connected
+    }
+
+    /**
+     * SCA58
+     * The disconnectPorts operation shall break the connection(s) to the
+     * application external ports as identified by the connectionIds
+     * referenced in the input portDisconnections parameter.
+     */
+    pub fn disconnect_port(&self, name: &str, connection_id: &str) -> resource::Result<()> {
+        let (user, identifier) = self.external_port_resource(name, PortKind::USES)?;
+        let disconnected = user
+            .lock()
+            .unwrap()
+            .disconnect_port(&identifier, connection_id);
+        disconnected
+    }
+
+    /// Returns the component resource owning an external port of the kind.
+    fn external_port_resource(
+        &self,
+        name: &str,
+        kind: PortKind,
+    ) -> resource::Result<(ResourceRef, String)> {
+        self.external_ports
+            .iter()
+            .find(|p| p.name == name && p.kind == kind)
+            .and_then(|p| self.port_resource(&p.port))
+            .ok_or_else(|| ResourceError::UnknownPort {
+                name: name.to_string(),
+            })
+    }
+
+    /**
+     * Sets properties of the application: the external properties are
+     * set on the component owning them, the other ones on the assembly
+     * controller.
+     */
+    pub fn configure(&self, properties: &Properties) -> resource::Result<()> {
+        let mut invalid_properties = Properties::new();
+        let mut configured = false;
+        for property in properties {
+            let Some((component, property_id)) = self.property_component(&property.id) else {
+                invalid_properties.push(property.clone());
+                continue;
+            };
+            let value = DataType::new(&property_id, property.value.clone());
+            let resource = component.resource.as_ref().unwrap();
+            let result = resource.lock().unwrap().configure(&vec![value]);
+            match result {
+                Ok(()) => configured = true,
+                Err(
+                    ResourceError::InvalidConfiguration { .. }
+                    | ResourceError::PartialConfiguration { .. },
+                ) => invalid_properties.push(property.clone()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        if invalid_properties.is_empty() {
+            Ok(())
+        } else if configured {
+            Err(ResourceError::PartialConfiguration { invalid_properties })
+        } else {
+            Err(ResourceError::InvalidConfiguration {
+                message: "unknown properties".to_string(),
+                invalid_properties,
+            })
+        }
+    }
+
+    /**
+     * Returns the values of properties of the application, queried from
+     * the component owning them under their application id. All the
+     * external properties, followed by the other properties of the
+     * assembly controller, are returned when none is given.
+     */
+    pub fn query(&self, properties: &Properties) -> resource::Result<Properties> {
+        if properties.is_empty() {
+            let mut values = Properties::new();
+            for external in &self.external_properties {
+                let query = DataType::new(&external.id, AnyValue::String(String::new()));
+                values.extend(self.query(&vec![query])?);
+            }
+            if let Some(controller) = self.assembly_controller_component() {
+                let own = controller.query(&Properties::new())?;
+                values.extend(
+                    own.into_iter()
+                        .filter(|p| !self.external_properties.iter().any(|e| e.id == p.id)),
+                );
+            }
+            return Ok(values);
+        }
+
+        let invalid_properties: Properties = properties
+            .iter()
+            .filter(|p| self.property_component(&p.id).is_none())
+            .cloned()
+            .collect();
+        if !invalid_properties.is_empty() {
+            return Err(ResourceError::UnknownProperties { invalid_properties });
+        }
+        let mut values = Properties::new();
+        for property in properties {
+            let (component, property_id) = self.property_component(&property.id).unwrap();
+            let query = DataType::new(&property_id, property.value.clone());
+            let result = component.query(&vec![query]);
+            values.extend(result?.into_iter().map(|p| DataType {
+                id: property.id.clone(),
+                ..p
+            }));
+        }
+        Ok(values)
+    }
+
+    /**
+     * Returns the registered component owning an application property
+     * along with the property id on the component: the externalproperty
+     * of the id, or the property of the assembly controller.
+     */
+    fn property_component(&self, id: &str) -> Option<(&ApplicationComponent, String)> {
+        match self.external_properties.iter().find(|p| p.id == id) {
+            Some(external) => {
+                self.component_property(&external.component_ref, &external.property_id)
+            }
+            None => self.component_property(self.assembly_controller.as_ref()?, id),
+        }
+    }
+
+    /**
+     * Returns the registered component owning a property of a component
+     * instantiation, or of a nested application.
+     */
+    fn component_property(
+        &self,
+        component_ref: &str,
+        property_id: &str,
+    ) -> Option<(&ApplicationComponent, String)> {
+        if let Some(component) = self.component(component_ref) {
+            component.resource.as_ref()?;
+            return Some((component, property_id.to_string()));
+        }
+        self.application(component_ref)?
+            .property_component(property_id)
+    }
+
+    /// Returns the assembly controller, when it is a registered component.
+    fn assembly_controller_component(&self) -> Option<&ApplicationComponent> {
+        self.component(self.assembly_controller.as_ref()?)
+            .filter(|c| c.resource.is_some())
+    }
+
+    /// Returns the deployed components, in deployment order.
+    pub fn components(&self) -> &[ApplicationComponent] {
+        &self.components
+    }
+
+    /// Returns a deployed component by instantiation id.
+    pub fn component(&self, instantiation_id: &str) -> Option<&ApplicationComponent> {
+        self.components
+            .iter()
+            .find(|c| c.instantiation_id == instantiation_id)
+    }
+
+    /**
+     * Returns the resource of a component by component identifier, those
+     * of the nested applications included.
+     */
+    pub fn component_resource(&self, identifier: &str) -> Option<ResourceRef> {
+        self.registered_component(identifier)?.resource.clone()
+    }
+
+    /**
+     * Returns a registered component by component identifier, those of
+     * the nested applications included.
+     */
+    pub fn registered_component(&self, identifier: &str) -> Option<&ApplicationComponent> {
+        match self.components.iter().find(|c| c.identifier == identifier) {
+            Some(component) => Some(component).filter(|c| c.resource.is_some()),
+            None => self
+                .applications
+                .iter()
+                .find_map(|(_, a)| a.registered_component(identifier)),
+        }
+    }
+
+    /// Returns the applications of the nested assemblies as (instantiation id, application) pairs.
+    pub fn applications(&self) -> &[(String, Application)] {
+        &self.applications
+    }
+
+    /// Returns the application of a nested assembly by instantiation id.
+    pub fn application(&self, instantiation_id: &str) -> Option<&Application> {
+        self.applications
+            .iter()
+            .find(|(id, _)| id == instantiation_id)
+            .map(|(_, a)| a)
+    }
+
+    fn application_mut(&mut self, instantiation_id: &str) -> Option<&mut Application> {
+        self.applications
+            .iter_mut()
+            .find(|(id, _)| id == instantiation_id)
+            .map(|(_, a)| a)
+    }
+
+    /**
+     * Returns the resource owning a port along with the identifier of the
+     * port on it. The ports of a nested application are its external
+     * ports, followed down to the component exposing them.
+     */
+    pub(crate) fn port_resource(&self, port: &PortReference) -> Option<(ResourceRef, String)> {
+        if let Some(component) = self.component(&port.component_ref) {
+            return Some((component.resource.clone()?, port.identifier.clone()));
+        }
+        let application = self.application(&port.component_ref)?;
+        let external = application
+            .external_ports
+            .iter()
+            .find(|p| p.name == port.identifier)?;
+        application.port_resource(&external.port)
+    }
+
+    /// Returns the connections made between the components.
+    pub fn connections(&self) -> &[ApplicationConnection] {
+        &self.connections
+    }
+
+    /// Returns the devices assigned to the usesdevice dependencies of the SAD.
+    pub fn uses_devices(&self) -> &[UsesDeviceAssignmentType] {
+        &self.uses_devices
+    }
+
+    /// Returns the ids of the allocations made for the components.
+    pub fn allocation_ids(&self) -> &[String] {
+        self.allocations.as_ref().map_or(&[], |(_, ids)| ids)
+    }
+
+    pub(crate) fn component_mut(
+        &mut self,
+        instantiation_id: &str,
+    ) -> Option<&mut ApplicationComponent> {
+        self.components
+            .iter_mut()
+            .find(|c| c.instantiation_id == instantiation_id)
+    }
+
+    pub(crate) fn add_component(&mut self, component: ApplicationComponent) {
+        self.components.push(component);
+    }
+
+    pub(crate) fn add_application(&mut self, instantiation_id: &str, application: Application) {
+        self.applications
+            .push((instantiation_id.to_string(), application));
+    }
+
+    pub(crate) fn add_connection(&mut self, connection: ApplicationConnection) {
+        self.connections.push(connection);
+    }
+
+    pub(crate) fn add_uses_device(&mut self, assignment: UsesDeviceAssignmentType) {
+        self.uses_devices.push(assignment);
+    }
+
+    /// Returns the outstanding allocations made for the components.
+    pub fn allocations(&self) -> Vec<AllocationStatus> {
+        let Some((manager, ids)) = &self.allocations else {
+            return Vec::new();
+        };
+        manager
+            .lock()
+            .unwrap()
+            .list_allocations()
+            .into_iter()
+            .filter(|s| ids.contains(&s.allocation_id))
+            .collect()
+    }
+
+    /**
+     * Collects the resource usage of the components from the devices
+     * executing them. The components without process, or whose device
+     * fails to report, have an UNKNOWN status; those whose process is
+     * gone are TERMINATED.
+     */
+    pub fn metrics(&self) -> ApplicationMetrics {
+        let mut components = Vec::new();
+        self.collect_metrics(&mut components);
+        ApplicationMetrics {
+            application_id: self.identifier.clone(),
+            cpu_usage: components.iter().map(|c| c.cpu_usage).sum(),
+            memory: components.iter().map(|c| c.memory).sum(),
+            components,
+        }
+    }
+
+    /// Collects the usage of the components of the application and its nested ones.
+    fn collect_metrics(&self, metrics: &mut Vec<ComponentMetrics>) {
+        for component in &self.components {
+            let mut collected = ComponentMetrics {
+                component_id: component.identifier.clone(),
+                device_id: component.device_id.clone(),
+                process_id: component.process_id,
+                cpu_usage: 0.0,
+                memory: 0,
+                status: ProcessStatus::UNKNOWN,
+            };
+            if let Some(process_id) = component.process_id {
+                match component.device.lock().unwrap().process_metrics(process_id) {
+                    Ok(process) => {
+                        collected.cpu_usage = process.cpu_usage;
+                        collected.memory = process.memory;
+                        collected.status = process.status;
+                    }
+                    Err(ExecutableDeviceError::InvalidProcess { .. }) => {
+                        collected.status = ProcessStatus::TERMINATED;
+                    }
+                    Err(_) => {}
+                }
+            }
+            metrics.push(collected);
+        }
+        for (_, application) in &self.applications {
+            application.collect_metrics(metrics);
+        }
+    }
+
+    /// Hands the deallocation of the allocations over to the application.
+    pub(crate) fn set_allocations(&mut self, manager: AllocationManagerRef, ids: Vec<String>) {
+        self.allocations = Some((manager, ids));
+    }
+
+    /**
+     * Tears the application down in the reverse order of its creation,
+     * going on past the failing steps, once stopped when started. The
+     * nested applications are released after the components. A released
+     * application has no component left, so that releasing it again
+     * does nothing.
+     * SCA42
+     * The ApplicationManager::releaseObject operation shall release each
+     * application component by utilizing the LifeCycle::releaseObject operation.
+     * SCA43
+     * The ApplicationManager::releaseObject operation shall terminate the
+     * processes/tasks on allocated ExecutableDeviceComponents belonging to
+     * each application component.
+     * SCA44
+     * The ApplicationManager::releaseObject operation shall unload each
+     * application component instance from its allocated LoadableDeviceComponent.
+     * SCA45
+     * The ApplicationManager::releaseObject operation shall deallocate the
+     * Device Component capacities that were allocated during application creation.
+     * SCA46
+     * The ApplicationManager::releaseObject operation shall release all object
+     * references to the components making up the application.
+     */
+    pub fn release_object(&mut self) -> Result<()> {
+        let mut messages = Vec::new();
+
+        //stop the processing
+        if self.started {
+            if let Err(e) = self.stop() {
+                messages.push(e.to_string());
+            }
+            self.started = false;
+        }
+
+        //break the connections
+        for connection in std::mem::take(&mut self.connections).into_iter().rev() {
+            if let Some((user, identifier)) = self.port_resource(&connection.uses_port) {
+                if let Err(e) = user
+                    .lock()
+                    .unwrap()
+                    .disconnect_port(&identifier, &connection.connection_id)
+                {
+                    messages.push(e.to_string());
+                }
+            }
+        }
+
+        //release the components and their processes
+        for component in std::mem::take(&mut self.components).into_iter().rev() {
+            if let Some(resource) = &component.resource {
+                if let Err(e) = resource.lock().unwrap().release_object() {
+                    messages.push(e.to_string());
+                }
+            }
+            self.registry.unregister_component(&component.name_binding);
+
+            let mut device = component.device.lock().unwrap();
+            if let Some(process_id) = component.process_id {
+                if let Err(e) = device.terminate(process_id) {
+                    messages.push(e.to_string());
+                }
+            }
+            if let Err(e) = device.unload(&component.loaded_file) {
+                messages.push(e.to_string());
+            }
+        }
+
+        //release the nested applications
+        for (_, mut application) in std::mem::take(&mut self.applications).into_iter().rev() {
+            if let Err(e) = application.release_object() {
+                messages.push(e.to_string());
+            }
+        }
+
+        //give the capacities back
+        if let Some((manager, ids)) = self.allocations.take() {
+            if let Err(e) = manager.lock().unwrap().deallocate(&ids) {
+                messages.push(e.to_string());
+            }
+        }
+
+        if !messages.is_empty() {
+            return Err(ApplicationError::ReleaseError { messages });
+        }
+        Ok(())
+    }
+}
+
+/// Tells whether an outcome is a failure of the component.
+fn failed(outcome: &ComponentOutcome) -> bool {
+    matches!(
+        outcome,
+        ComponentOutcome::FAILED(_) | ComponentOutcome::TIMED_OUT
+    )
+}
+
+/**
+ * Runs an operation of a component, giving up once the timeout elapsed.
+ * The operation of a component not answering goes on on a thread of the
+ * pool, its resource staying locked until it returns.
+ */
+fn call(
+    pool: &BlockingPool,
+    resource: &ResourceRef,
+    timeout: Duration,
+    operation: fn(&mut (dyn ResourceTrait + Send + 'static)) -> resource::Result<()>,
+) -> ComponentOutcome {
+    match call_within(pool, resource, timeout, operation) {
+        Ok(Ok(())) => ComponentOutcome::DONE,
+        Ok(Err(e)) => ComponentOutcome::FAILED(e.to_string()),
+        Err(BlockingError::TimedOut { .. }) => ComponentOutcome::TIMED_OUT,
+        Err(BlockingError::OperationPanicked { message }) => {
+            ComponentOutcome::FAILED(format!("the component panicked: {message}"))
+        }
+        Err(e) => ComponentOutcome::FAILED(e.to_string()),
+    }
+}
+
+/**
+ * Runs an operation of a component on a thread of the pool, returning
+ * its result unless the timeout elapsed. A panicking operation is
+ * reported as such, the resource being unlocked before the panic goes
+ * on so that it is not poisoned.
+ */
+pub(crate) fn call_within<R, F>(
+    pool: &BlockingPool,
+    resource: &ResourceRef,
+    timeout: Duration,
+    operation: F,
+) -> std::result::Result<resource::Result<R>, BlockingError>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut (dyn ResourceTrait + Send + 'static)) -> resource::Result<R> + Send + 'static,
+{
+    let resource = resource.clone();
+    pool.run_within(
+        timeout,
+        move || {
+            let mut guard = resource.lock().unwrap_or_else(PoisonError::into_inner);
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| operation(&mut *guard)));
+            drop(guard);
+            outcome.unwrap_or_else(|payload| panic::resume_unwind(payload))
+        },
+        drop,
+    )
+}
@@ -0,0 +1,1852 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::allocation_guard::AllocationGuard;
+use super::allocation_manager::{
+    AllocationManagerError, AllocationManagerRef, AllocationProperty, AllocationRequest,
+};
+use super::application::{
+    call_within, Application, ApplicationComponent, ApplicationConnection, ComponentElementType,
+    DEFAULT_COMPONENT_TIMEOUT,
+};
+use super::blocking_pool::{BlockingError, BlockingPool};
+use super::common_types::{ActionType, AnyValue, DataType, Properties};
+use super::component_registry::ComponentRegistry;
+use super::executable_device::{
+    ExecutableDeviceRef, COMPONENT_IDENTIFIER, ENTRY_POINT_ID, NAME_BINDING, PROFILE_NAME,
+};
+use super::file_manager::FileManagerRef;
+use super::file_system::FileSystemTrait;
+use super::loadable_device::LoadType;
+use super::profile::cache::ProfileCache;
+use super::profile::prf::{PropertiesDescriptor, Property, PropertyKind, PropertyType};
+use super::profile::sad::{ConnectionTarget, FindBy, PortReference, SoftwareAssembly, UsesDevice};
+use super::profile::scd::SoftwareComponent;
+use super::profile::spd::{Implementation, SoftPkg};
+use super::profile::{self, resolve_file_name, ComponentInstantiation};
+use super::resource::{self, ResourceError, ResourceRef, ResourceTrait};
+
+/// The time given by default to a launched component to register itself.
+pub const DEFAULT_RESOLVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Convienence enum definition that includes all ApplicationFactory errors.
+ */
+#[derive(Error, Debug)]
+pub enum ApplicationFactoryError {
+    /**
+     * This exception indicates that some device assignments are invalid.
+     * The list contains the invalid assignments.
+     */
+    #[error("CreateApplicationRequestError: {invalid_assignments:?}.")]
+    CreateApplicationRequestError {
+        invalid_assignments: Vec<DeviceAssignmentType>,
+    },
+    /**
+     * This exception indicates that the create request is valid but the
+     * application cannot be instantiated. Everything deployed so far has
+     * been released.
+     */
+    #[error("CreateApplicationError: msg: '{message}'.")]
+    CreateApplicationError { message: String },
+    /**
+     * This exception indicates that the initial configuration contains
+     * properties unknown by the assembly controller. The list contains
+     * the invalid properties.
+     */
+    #[error("InvalidInitConfiguration: {invalid_properties:?}.")]
+    InvalidInitConfiguration { invalid_properties: Properties },
+}
+
+/*
+ * Convienence type definition that includes all ApplicationFactory returned errors.
+ */
+pub type Result<T, E = ApplicationFactoryError> = anyhow::Result<T, E>;
+
+/**
+ * This type assigns a component instantiation of the SAD to a device.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAssignmentType {
+    pub component_id: String,
+    pub assigned_device_id: String,
+}
+
+/**
+ * This type assigns a usesdevice dependency of the SAD to the device
+ * satisfying it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsesDeviceAssignmentType {
+    pub uses_device_id: String,
+    pub assigned_device_id: String,
+}
+
+/**
+ * This type reports a dry run of the create operation: where the
+ * components would be placed and the problems found. The components of
+ * the nested assemblies are identified by their instantiation path,
+ * e.g. 'radio_1/demod_1'.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeploymentPlan {
+    /// The device each component would be deployed on.
+    pub device_assignments: Vec<DeviceAssignmentType>,
+    /// The SPD implementation each component would be deployed with.
+    pub component_implementations: Vec<ComponentElementType>,
+    pub uses_devices: Vec<UsesDeviceAssignmentType>,
+    pub errors: Vec<String>,
+    /// Why the implementations not chosen were rejected, e.g. "demod_1: 'arm' on 'gpp': ...".
+    pub rejected_implementations: Vec<String>,
+}
+
+impl DeploymentPlan {
+    /// Tells whether the application would be deployed.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/**
+ * The domain objects the applications are deployed with: the file
+ * manager holding the component files, the allocation manager placing
+ * the components, the executable devices running them along with their
+ * hosts and the registry the launched components register with. Clones
+ * share the devices, those added or removed once the context is given
+ * to the factories included.
+ */
+#[derive(Clone)]
+pub struct DeploymentContext {
+    file_manager: FileManagerRef,
+    allocation_manager: AllocationManagerRef,
+    devices: Arc<Mutex<Vec<ExecutableDeviceRef>>>,
+    /// The host of the devices, by device identifier.
+    hosts: Arc<Mutex<HashMap<String, String>>>,
+    /// The devices the components are not deployed on while unavailable, by identifier.
+    unavailable: Arc<Mutex<Vec<String>>>,
+    registry: ComponentRegistry,
+    resolve_timeout: Duration,
+    component_timeout: Duration,
+    /// The pool the operations of the components are called on.
+    component_pool: BlockingPool,
+}
+
+impl DeploymentContext {
+    pub fn new(
+        file_manager: FileManagerRef,
+        allocation_manager: AllocationManagerRef,
+        registry: ComponentRegistry,
+    ) -> DeploymentContext {
+        DeploymentContext {
+            file_manager,
+            allocation_manager,
+            devices: Arc::default(),
+            hosts: Arc::default(),
+            unavailable: Arc::default(),
+            registry,
+            resolve_timeout: DEFAULT_RESOLVE_TIMEOUT,
+            component_timeout: DEFAULT_COMPONENT_TIMEOUT,
+            component_pool: BlockingPool::shared(),
+        }
+    }
+
+    /**
+     * Adds an executable device the components can be deployed on. The
+     * device shall also be registered with the allocation manager. It
+     * is its own host, collocated with no other device.
+     */
+    pub fn with_device(self, device: ExecutableDeviceRef) -> DeploymentContext {
+        self.devices.lock().unwrap().push(device);
+        self
+    }
+
+    /**
+     * Adds an executable device running on a host, e.g. the node of its
+     * DeviceManager, the components of a hostcollocation being deployed
+     * on the devices of a single host.
+     */
+    pub fn with_host_device(self, device: ExecutableDeviceRef, host: &str) -> DeploymentContext {
+        self.add_device(device, host);
+        self
+    }
+
+    /// Sets the allocation manager the devices are allocated through.
+    pub fn with_allocation_manager(
+        mut self,
+        allocation_manager: AllocationManagerRef,
+    ) -> DeploymentContext {
+        self.allocation_manager = allocation_manager;
+        self
+    }
+
+    /**
+     * Adds an executable device running on a host to the context and to
+     * its clones, e.g. a device registered with the domain. A device
+     * added again under the same identifier replaces the previous one.
+     */
+    pub fn add_device(&self, device: ExecutableDeviceRef, host: &str) {
+        let identifier = device.lock().unwrap().identifier().to_string();
+        self.remove_device(&identifier);
+        self.devices.lock().unwrap().push(device);
+        self.hosts
+            .lock()
+            .unwrap()
+            .insert(identifier, host.to_string());
+    }
+
+    /// Removes a device from the context and its clones, returning it when added.
+    pub fn remove_device(&self, identifier: &str) -> Option<ExecutableDeviceRef> {
+        let mut devices = self.devices.lock().unwrap();
+        let index = devices
+            .iter()
+            .position(|d| d.lock().unwrap().identifier() == identifier)?;
+        self.hosts.lock().unwrap().remove(identifier);
+        self.unavailable.lock().unwrap().retain(|id| id != identifier);
+        Some(devices.remove(index))
+    }
+
+    /**
+     * Withdraws a device of the context and its clones from the
+     * deployments while it is unavailable, e.g. its DeviceManager not
+     * answering the heartbeats, or makes it available again. The
+     * components already deployed on the device are left in place.
+     */
+    pub fn set_available(&self, identifier: &str, available: bool) {
+        let mut unavailable = self.unavailable.lock().unwrap();
+        unavailable.retain(|id| id != identifier);
+        if !available {
+            unavailable.push(identifier.to_string());
+        }
+    }
+
+    /// Tells whether a device of the context is available to the deployments.
+    pub fn is_available(&self, identifier: &str) -> bool {
+        !self
+            .unavailable
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|id| id == identifier)
+    }
+
+    /// Sets the time given to a launched component to register itself.
+    pub fn with_resolve_timeout(mut self, resolve_timeout: Duration) -> DeploymentContext {
+        self.resolve_timeout = resolve_timeout;
+        self
+    }
+
+    /// Sets the time given to each component to initialize, configure, start or stop.
+    pub fn with_component_timeout(mut self, component_timeout: Duration) -> DeploymentContext {
+        self.component_timeout = component_timeout;
+        self
+    }
+
+    /**
+     * Sets the pool the operations of the components are called on,
+     * bounding the threads left waiting for the components not answering.
+     */
+    pub fn with_component_pool(mut self, component_pool: BlockingPool) -> DeploymentContext {
+        self.component_pool = component_pool;
+        self
+    }
+
+    /// Returns the allocation manager the devices are allocated through.
+    pub fn allocation_manager(&self) -> &AllocationManagerRef {
+        &self.allocation_manager
+    }
+
+    /// Returns the registry the launched components register with.
+    pub(crate) fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+
+    /// Returns the host of a device of the context.
+    pub fn host(&self, device_id: &str) -> String {
+        self.hosts
+            .lock()
+            .unwrap()
+            .get(device_id)
+            .cloned()
+            .unwrap_or_else(|| device_id.to_string())
+    }
+
+    /// Returns the identifiers of the devices of the context available to the deployments.
+    fn device_ids(&self) -> Vec<String> {
+        self.devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|d| d.lock().unwrap().identifier().to_string())
+            .filter(|id| self.is_available(id))
+            .collect()
+    }
+
+    /// Returns a device of the context by identifier.
+    pub(crate) fn device(&self, identifier: &str) -> Option<ExecutableDeviceRef> {
+        self.devices
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|d| d.lock().unwrap().identifier() == identifier)
+            .cloned()
+    }
+}
+
+impl fmt::Debug for DeploymentContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeploymentContext")
+            .field("devices", &self.device_ids())
+            .field("hosts", &self.hosts)
+            .field("registry", &self.registry)
+            .field("resolve_timeout", &self.resolve_timeout)
+            .field("component_timeout", &self.component_timeout)
+            .finish()
+    }
+}
+
+/**
+ * This type describes a component file of an installed application
+ * with its parsed SPD.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentProfile {
+    /// The id of the componentfile in the SAD.
+    pub file_id: String,
+    /// The pathname of the SPD in the domain FileManager.
+    pub spd_file_name: String,
+    pub softpkg: SoftPkg,
+    /// The parsed PRFs of the SPD and of its implementations, by pathname.
+    pub property_files: HashMap<String, PropertiesDescriptor>,
+}
+
+impl ComponentProfile {
+    /**
+     * Returns the properties of an implementation, those of its PRF
+     * adding to, or replacing, those of the PRF of the SPD. None when
+     * neither has a PRF.
+     */
+    pub fn properties(&self, implementation: &Implementation) -> Option<PropertiesDescriptor> {
+        let mut properties: Option<PropertiesDescriptor> = None;
+        let property_files = self
+            .softpkg
+            .property_file
+            .iter()
+            .chain(&implementation.property_file);
+        for property_file in property_files {
+            let property_file = resolve_file_name(&self.spd_file_name, property_file);
+            let Some(loaded) = self.property_files.get(&property_file) else {
+                continue;
+            };
+            match &mut properties {
+                Some(properties) => {
+                    for property in &loaded.properties {
+                        properties.properties.retain(|p| p.id() != property.id());
+                        properties.properties.push(property.clone());
+                    }
+                }
+                None => properties = Some(loaded.clone()),
+            }
+        }
+        properties
+    }
+}
+
+/**
+ * This type describes a component file of an installed application
+ * referencing another SAD, whose applications are created as components
+ * of the applications of the referencing one.
+ */
+#[derive(Debug, Clone)]
+pub struct AssemblyProfile {
+    /// The id of the componentfile in the SAD.
+    pub file_id: String,
+    pub factory: ApplicationFactory,
+}
+
+/**
+ * The ApplicationFactory of an installed SAD, creating the applications
+ * of the assembly.
+ */
+#[derive(Debug, Clone)]
+pub struct ApplicationFactory {
+    software_profile: String,
+    assembly: SoftwareAssembly,
+    components: Vec<ComponentProfile>,
+    assemblies: Vec<AssemblyProfile>,
+    deployment: Option<DeploymentContext>,
+}
+
+impl ApplicationFactory {
+    /**
+     * Loads the SAD from the file system, verifying that every SPD, SCD
+     * and PRF it references exists and parses. Relative localfile names
+     * are relative to the directory of the referencing profile. The
+     * componentfiles of type SAD are loaded as nested assemblies, an
+     * assembly nesting itself being invalid.
+     */
+    pub fn load(
+        file_system: &dyn FileSystemTrait,
+        software_profile: &str,
+    ) -> profile::Result<ApplicationFactory> {
+        ApplicationFactory::load_cached(file_system, software_profile, &ProfileCache::default())
+    }
+
+    /**
+     * Loads the SAD as load does, the descriptors being parsed through
+     * the cache, e.g. the SPDs shared by several assemblies.
+     */
+    pub fn load_cached(
+        file_system: &dyn FileSystemTrait,
+        software_profile: &str,
+        cache: &ProfileCache,
+    ) -> profile::Result<ApplicationFactory> {
+        ApplicationFactory::load_nested(file_system, software_profile, cache, &mut Vec::new())
+    }
+
+    /// Loads a SAD nested in the assemblies being loaded.
+    fn load_nested(
+        file_system: &dyn FileSystemTrait,
+        software_profile: &str,
+        cache: &ProfileCache,
+        loading: &mut Vec<String>,
+    ) -> profile::Result<ApplicationFactory> {
+        if loading.iter().any(|f| f == software_profile) {
+            return Err(profile::invalid(software_profile, "assembly nests itself"));
+        }
+        let assembly = cache
+            .load::<SoftwareAssembly>(file_system, software_profile)?
+            .as_ref()
+            .clone();
+
+        let mut components = Vec::new();
+        let mut assemblies = Vec::new();
+        for file in &assembly.component_files {
+            if file.file_type == "SAD" {
+                let sad_file_name = resolve_file_name(software_profile, &file.local_file);
+                loading.push(software_profile.to_string());
+                let factory =
+                    ApplicationFactory::load_nested(file_system, &sad_file_name, cache, loading);
+                loading.pop();
+                assemblies.push(AssemblyProfile {
+                    file_id: file.id.clone(),
+                    factory: factory?,
+                });
+                continue;
+            }
+
+            let spd_file_name = resolve_file_name(software_profile, &file.local_file);
+            let softpkg = cache
+                .load::<SoftPkg>(file_system, &spd_file_name)?
+                .as_ref()
+                .clone();
+
+            //verify the descriptors referenced by the SPD
+            let property_files = softpkg.property_file.iter().chain(
+                softpkg
+                    .implementations
+                    .iter()
+                    .flat_map(|i| &i.property_file),
+            );
+            let mut loaded_files = HashMap::new();
+            for prf in property_files {
+                let prf = resolve_file_name(&spd_file_name, prf);
+                let properties = cache.load::<PropertiesDescriptor>(file_system, &prf)?;
+                loaded_files.insert(prf, properties.as_ref().clone());
+            }
+            if let Some(scd) = &softpkg.descriptor {
+                let scd = resolve_file_name(&spd_file_name, scd);
+                cache.load::<SoftwareComponent>(file_system, &scd)?;
+            }
+
+            components.push(ComponentProfile {
+                file_id: file.id.clone(),
+                spd_file_name,
+                softpkg,
+                property_files: loaded_files,
+            });
+        }
+
+        //verify the host collocations collocate components
+        for collocation in &assembly.host_collocations {
+            let nested = collocation.instantiations.iter().find(|id| {
+                assembly
+                    .placements
+                    .iter()
+                    .find(|p| p.instantiations.iter().any(|i| &i.id == *id))
+                    .is_some_and(|p| assemblies.iter().any(|a| a.file_id == p.file_ref))
+            });
+            if let Some(id) = nested {
+                return Err(profile::invalid(
+                    software_profile,
+                    &format!("nested assembly '{id}' in a hostcollocation"),
+                ));
+            }
+        }
+
+        Ok(ApplicationFactory {
+            software_profile: software_profile.to_string(),
+            assembly,
+            components,
+            assemblies,
+            deployment: None,
+        })
+    }
+
+    /// Sets the domain objects the applications are deployed with.
+    pub fn with_deployment(mut self, deployment: DeploymentContext) -> ApplicationFactory {
+        self.deployment = Some(deployment);
+        self
+    }
+
+    /// The readonly identifier attribute contains the SAD id.
+    pub fn identifier(&self) -> &str {
+        &self.assembly.id
+    }
+
+    /// The readonly name attribute contains the SAD name.
+    pub fn name(&self) -> &str {
+        &self.assembly.name
+    }
+
+    /// The readonly softwareProfile attribute contains the SAD pathname.
+    pub fn software_profile(&self) -> &str {
+        &self.software_profile
+    }
+
+    /// Returns the identifier of the application created under the name.
+    pub fn application_identifier(&self, name: &str) -> String {
+        format!("{}:{}", self.assembly.id, name)
+    }
+
+    /// Returns the parsed SAD.
+    pub fn assembly(&self) -> &SoftwareAssembly {
+        &self.assembly
+    }
+
+    /// Returns the component profile of a componentfile of the SAD.
+    pub fn component(&self, file_id: &str) -> Option<&ComponentProfile> {
+        self.components.iter().find(|c| c.file_id == file_id)
+    }
+
+    /// Returns the nested assembly of a componentfile of the SAD.
+    pub fn nested_assembly(&self, file_id: &str) -> Option<&AssemblyProfile> {
+        self.assemblies.iter().find(|a| a.file_id == file_id)
+    }
+
+    /**
+     * Creates an application of the assembly. Each component is placed
+     * on a device satisfying the dependencies of one of its
+     * implementations, loaded and executed, then resolved through the
+     * registry, initialized and configured. The connections of the SAD
+     * are made last. Any failure releases everything deployed so far,
+     * a component not initialized or configured within the component
+     * timeout failing the create.
+     * The components having a PRF are executed with its execparams and
+     * configured with its writable configure properties, the
+     * componentproperties of the SAD, then the initial configuration for
+     * the assembly controller, overriding the default values.
+     * The instantiations of a hostcollocation are placed together on the
+     * devices of a single host, the hosts being tried in turn.
+     * The assigned components are only placed on their device, and the
+     * usesdevice dependencies of the SAD are allocated first.
+     * The instantiations of nested assemblies are created as applications
+     * named after the instantiation, the componentproperties being their
+     * initial configuration, and the device assignments of their
+     * components being prefixed by the instantiation id, e.g.
+     * 'radio_1/demod_1'.
+     * SCA74
+     * The create operation shall deploy the ApplicationComponents as
+     * specified in the SAD.
+     * SCA76
+     * When the create operation deploys an ApplicationComponent via an
+     * ExecutableDeviceComponent, it shall include a Component Identifier in
+     * the parameters parameter of the ExecutableInterface::execute operation.
+     * SCA72
+     * The create operation shall deallocate any capacity allocations on
+     * DeviceComponents that are not utilized due to an unsuccessful
+     * application creation.
+     * SCA85
+     * The create operation shall establish connections for an
+     * AssemblyComponent which are specified in the SAD connections element.
+     * SCA91
+     * The create operation shall use the property values contained in the
+     * input initConfiguration parameter over the property values of the SAD's
+     * assemblycontroller element when they reference the same property.
+     * SCA103
+     * The create operation shall raise the CreateApplicationRequestError
+     * exception when the input deviceAssignments parameter contains one or
+     * more invalid application component to device assignment(s).
+     * SCA107
+     * The create operation shall raise the InvalidInitConfiguration exception
+     * when the input initConfiguration parameter contains properties that are
+     * unknown by a SAD's assemblycontroller element.
+     */
+    pub fn create(
+        &self,
+        name: &str,
+        init_configuration: &Properties,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<Application> {
+        let deployment = self
+            .deployment
+            .as_ref()
+            .ok_or_else(|| create_error("no deployment context".to_string()))?;
+        self.create_in(deployment, name, init_configuration, device_assignments)
+    }
+
+    /**
+     * Runs the placement of create without loading or executing
+     * anything: the device assignments are verified, the usesdevice
+     * dependencies and the components placed, their componentproperties
+     * checked against their PRF. The capacities are allocated from a
+     * copy of those of the devices, the devices being left untouched.
+     * Every problem found is reported in the plan rather than stopping
+     * at the first.
+     */
+    pub fn validate(
+        &self,
+        name: &str,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<DeploymentPlan> {
+        let deployment = self
+            .deployment
+            .as_ref()
+            .ok_or_else(|| create_error("no deployment context".to_string()))?;
+        let capacity_model = deployment
+            .allocation_manager
+            .lock()
+            .unwrap()
+            .capacity_model();
+        let deployment = deployment
+            .clone()
+            .with_allocation_manager(Arc::new(Mutex::new(capacity_model)));
+        let mut plan = DeploymentPlan::default();
+        let mut allocations = Vec::new();
+        self.plan_in(
+            &deployment,
+            name,
+            device_assignments,
+            "",
+            &mut plan,
+            &mut allocations,
+        );
+        Ok(plan)
+    }
+
+    /**
+     * Places the application in the plan, the components of a nested
+     * assembly being identified with the prefix.
+     */
+    fn plan_in(
+        &self,
+        deployment: &DeploymentContext,
+        name: &str,
+        device_assignments: &[DeviceAssignmentType],
+        prefix: &str,
+        plan: &mut DeploymentPlan,
+        allocations: &mut Vec<(String, AllocationGuard)>,
+    ) {
+        let identifier = self.application_identifier(name);
+
+        //the components of the invalid assignments are placed as if unassigned
+        let invalid_assignments = self.invalid_assignments(deployment, device_assignments);
+        for a in &invalid_assignments {
+            plan.errors.push(format!(
+                "invalid assignment of '{prefix}{}' to '{}'",
+                a.component_id, a.assigned_device_id
+            ));
+        }
+        let device_assignments: Vec<DeviceAssignmentType> = device_assignments
+            .iter()
+            .filter(|a| !invalid_assignments.contains(a))
+            .cloned()
+            .collect();
+
+        for uses_device in &self.assembly.uses_devices {
+            match allocate_uses_device(deployment, uses_device, &identifier) {
+                Ok((assignment, allocation)) => {
+                    plan.uses_devices.push(assignment);
+                    allocations.push(allocation);
+                }
+                Err(e) => plan.errors.push(format!("{prefix}{e}")),
+            }
+        }
+
+        let collocated = self
+            .collocate(deployment, allocations, &device_assignments, &identifier)
+            .unwrap_or_else(|e| {
+                plan.errors.push(format!("{prefix}{e}"));
+                HashMap::new()
+            });
+
+        let device_ids = deployment.device_ids();
+        for placement in &self.assembly.placements {
+            if let Some(nested) = self.nested_assembly(&placement.file_ref) {
+                for instantiation in &placement.instantiations {
+                    let nested_prefix = format!("{}/", instantiation.id);
+                    nested.factory.plan_in(
+                        deployment,
+                        &format!("{name}/{}", instantiation.id),
+                        &nested_assignments(&device_assignments, &nested_prefix),
+                        &format!("{prefix}{nested_prefix}"),
+                        plan,
+                        allocations,
+                    );
+                }
+                continue;
+            }
+
+            let Some(component) = self.component(&placement.file_ref) else {
+                plan.errors
+                    .push(format!("{prefix}no SPD for '{}'", placement.file_ref));
+                continue;
+            };
+            for instantiation in &placement.instantiations {
+                //the members of a failed hostcollocation are already reported
+                let placed = match collocated.get(instantiation.id.as_str()) {
+                    Some((implementation, device_id)) => Some((*implementation, device_id.clone())),
+                    None if self
+                        .assembly
+                        .host_collocations
+                        .iter()
+                        .any(|c| c.instantiations.contains(&instantiation.id)) =>
+                    {
+                        None
+                    }
+                    None => match self.allocate(
+                        deployment,
+                        component,
+                        instantiation,
+                        &device_assignments,
+                        &device_ids,
+                        &identifier,
+                    ) {
+                        Ok(allocated) => {
+                            allocations.push(allocated.allocation);
+                            plan.rejected_implementations.extend(
+                                allocated
+                                    .rejections
+                                    .iter()
+                                    .map(|r| format!("{prefix}{}: {r}", instantiation.id)),
+                            );
+                            Some((allocated.implementation, allocated.device_id))
+                        }
+                        Err(e) => {
+                            plan.errors.push(format!("{prefix}{e}"));
+                            None
+                        }
+                    },
+                };
+                if let Some((implementation, device_id)) = placed {
+                    if let Err(e) = self.configuration(
+                        component,
+                        implementation,
+                        instantiation,
+                        &Properties::new(),
+                    ) {
+                        plan.errors.push(format!("{prefix}{e}"));
+                    }
+                    let component_id = format!("{prefix}{}", instantiation.id);
+                    plan.device_assignments.push(DeviceAssignmentType {
+                        component_id: component_id.clone(),
+                        assigned_device_id: device_id,
+                    });
+                    plan.component_implementations.push(ComponentElementType {
+                        component_id,
+                        element_id: implementation.id.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Creates an application with the deployment context.
+    fn create_in(
+        &self,
+        deployment: &DeploymentContext,
+        name: &str,
+        init_configuration: &Properties,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<Application> {
+        let invalid_assignments = self.invalid_assignments(deployment, device_assignments);
+        if !invalid_assignments.is_empty() {
+            return Err(ApplicationFactoryError::CreateApplicationRequestError {
+                invalid_assignments,
+            });
+        }
+
+        let identifier = self.application_identifier(name);
+        let mut application = Application::new(
+            &identifier,
+            name,
+            &self.software_profile,
+            &self.assembly,
+            deployment.registry.clone(),
+        )
+        .with_component_timeout(deployment.component_timeout)
+        .with_component_pool(deployment.component_pool.clone());
+        let mut allocations = Vec::new();
+        let deployed = self.deploy(
+            deployment,
+            &mut application,
+            &mut allocations,
+            init_configuration,
+            device_assignments,
+        );
+
+        //the guards give the capacities back once the components are released
+        if let Err(e) = deployed {
+            let _ = application.release_object();
+            drop(allocations);
+            return Err(e);
+        }
+
+        let (allocation_ids, guards): (Vec<String>, Vec<AllocationGuard>) =
+            allocations.into_iter().unzip();
+        guards.into_iter().for_each(AllocationGuard::commit);
+        application.set_allocations(deployment.allocation_manager.clone(), allocation_ids);
+        Ok(application)
+    }
+
+    /**
+     * Returns the invalid device assignments, those of a hostcollocation
+     * having to share a host.
+     */
+    fn invalid_assignments(
+        &self,
+        deployment: &DeploymentContext,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Vec<DeviceAssignmentType> {
+        let mut invalid_assignments: Vec<DeviceAssignmentType> = device_assignments
+            .iter()
+            .filter(|a| !self.valid_assignment(deployment, a))
+            .cloned()
+            .collect();
+        for collocation in &self.assembly.host_collocations {
+            let mut assigned = device_assignments
+                .iter()
+                .filter(|a| collocation.instantiations.contains(&a.component_id));
+            if let Some(first) = assigned.next() {
+                let host = deployment.host(&first.assigned_device_id);
+                invalid_assignments.extend(
+                    assigned
+                        .filter(|a| deployment.host(&a.assigned_device_id) != host)
+                        .cloned(),
+                );
+            }
+        }
+        invalid_assignments
+    }
+
+    /**
+     * Tells whether an assignment references a device of the context and
+     * a component instantiation, nested ones included, the device
+     * satisfying the dependencies of one of the implementations of the
+     * component.
+     */
+    fn valid_assignment(
+        &self,
+        deployment: &DeploymentContext,
+        assignment: &DeviceAssignmentType,
+    ) -> bool {
+        if deployment.device(&assignment.assigned_device_id).is_none()
+            || !deployment.is_available(&assignment.assigned_device_id)
+        {
+            return false;
+        }
+        match assignment.component_id.split_once('/') {
+            Some((id, component_id)) => self.nested_factory(id).is_some_and(|f| {
+                f.valid_assignment(
+                    deployment,
+                    &DeviceAssignmentType {
+                        component_id: component_id.to_string(),
+                        assigned_device_id: assignment.assigned_device_id.clone(),
+                    },
+                )
+            }),
+            None => self
+                .component_instantiation(&assignment.component_id)
+                .zip(deployment.device(&assignment.assigned_device_id))
+                .is_some_and(|((component, _), device)| {
+                    let properties = device.lock().unwrap().allocation_properties();
+                    component
+                        .softpkg
+                        .implementations
+                        .iter()
+                        .filter(|i| i.code.is_some())
+                        .any(|i| {
+                            i.platform_requirements().iter().any(|platform| {
+                                allocation_properties(
+                                    platform.iter().chain(&i.dependencies),
+                                    &properties,
+                                )
+                                .is_ok()
+                            })
+                        })
+                }),
+        }
+    }
+
+    /// Returns the factory of a nested assembly instantiation.
+    fn nested_factory(&self, instantiation_id: &str) -> Option<&ApplicationFactory> {
+        self.assembly
+            .placements
+            .iter()
+            .find(|p| p.instantiations.iter().any(|i| i.id == instantiation_id))
+            .and_then(|p| self.nested_assembly(&p.file_ref))
+            .map(|a| &a.factory)
+    }
+
+    /// Runs the deployment steps of create, recording what is deployed.
+    fn deploy(
+        &self,
+        deployment: &DeploymentContext,
+        application: &mut Application,
+        allocations: &mut Vec<(String, AllocationGuard)>,
+        init_configuration: &Properties,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<()> {
+        //allocate the devices used by the application
+        for uses_device in &self.assembly.uses_devices {
+            let (assignment, allocation) =
+                allocate_uses_device(deployment, uses_device, application.identifier())?;
+            allocations.push(allocation);
+            application.add_uses_device(assignment);
+        }
+
+        let collocated = self.collocate(
+            deployment,
+            allocations,
+            device_assignments,
+            application.identifier(),
+        )?;
+
+        //place, load and execute the components
+        let device_ids = deployment.device_ids();
+        let mut configurations = HashMap::new();
+        let mut init_configured = false;
+        for placement in &self.assembly.placements {
+            if let Some(nested) = self.nested_assembly(&placement.file_ref) {
+                for instantiation in &placement.instantiations {
+                    let prefix = format!("{}/", instantiation.id);
+                    let nested_assignments = nested_assignments(device_assignments, &prefix);
+                    let nested_application = nested
+                        .factory
+                        .create_in(
+                            deployment,
+                            &format!("{}/{}", application.name(), instantiation.id),
+                            &instantiation.properties,
+                            &nested_assignments,
+                        )
+                        .map_err(|e| match e {
+                            ApplicationFactoryError::CreateApplicationRequestError {
+                                invalid_assignments,
+                            } => ApplicationFactoryError::CreateApplicationRequestError {
+                                invalid_assignments: invalid_assignments
+                                    .into_iter()
+                                    .map(|a| DeviceAssignmentType {
+                                        component_id: format!("{prefix}{}", a.component_id),
+                                        ..a
+                                    })
+                                    .collect(),
+                            },
+                            e => create_error(format!("'{}': {e}", instantiation.id)),
+                        })?;
+                    application.add_application(&instantiation.id, nested_application);
+                }
+                continue;
+            }
+
+            let component = self
+                .component(&placement.file_ref)
+                .ok_or_else(|| create_error(format!("no SPD for '{}'", placement.file_ref)))?;
+            for instantiation in &placement.instantiations {
+                let (implementation, device_id) = match collocated.get(instantiation.id.as_str()) {
+                    Some((implementation, device_id)) => (*implementation, device_id.clone()),
+                    None => {
+                        let allocated = self.allocate(
+                            deployment,
+                            component,
+                            instantiation,
+                            device_assignments,
+                            &device_ids,
+                            application.identifier(),
+                        )?;
+                        allocations.push(allocated.allocation);
+                        (allocated.implementation, allocated.device_id)
+                    }
+                };
+                let configuration = self.configuration(
+                    component,
+                    implementation,
+                    instantiation,
+                    init_configuration,
+                )?;
+                ApplicationFactory::launch(
+                    deployment,
+                    application,
+                    component,
+                    implementation,
+                    instantiation,
+                    &device_id,
+                    &configuration.exec_params,
+                )?;
+                init_configured |= configuration.init_configured;
+                configurations.insert(instantiation.id.as_str(), configuration.configure);
+            }
+        }
+
+        //resolve the launched components
+        for instantiation in self.component_instantiations() {
+            let component = application.component_mut(&instantiation.id).unwrap();
+            let resource = deployment
+                .registry
+                .resolve(&component.name_binding, deployment.resolve_timeout)
+                .ok_or_else(|| {
+                    create_error(format!(
+                        "'{}' did not register as '{}'",
+                        instantiation.id, component.name_binding
+                    ))
+                })?;
+            component.properties = resource.lock().unwrap().property_reader();
+            component.resource = Some(resource);
+            component.endpoint = deployment.registry.endpoint(&component.name_binding);
+        }
+
+        //initialize and configure the components
+        for instantiation in self.component_instantiations() {
+            let resource = application
+                .component(&instantiation.id)
+                .unwrap()
+                .resource
+                .clone()
+                .unwrap();
+            component_call(
+                &instantiation.id,
+                "initialize",
+                &resource,
+                deployment,
+                |r| r.initialize(),
+            )?;
+            let configure = configurations[instantiation.id.as_str()].clone();
+            if !configure.is_empty() {
+                component_call(
+                    &instantiation.id,
+                    "configure",
+                    &resource,
+                    deployment,
+                    move |r| r.configure(&configure),
+                )?;
+            }
+        }
+        if !init_configured {
+            self.configure_assembly_controller(application, init_configuration, deployment)?;
+        }
+
+        //make the connections
+        for (index, connection) in self.assembly.connections.iter().enumerate() {
+            let resource = |port: &PortReference| {
+                application.port_resource(port).ok_or_else(|| {
+                    create_error(format!(
+                        "port '{}' of '{}' not deployed",
+                        port.identifier, port.component_ref
+                    ))
+                })
+            };
+            let (user, uses_identifier) = resource(&connection.uses_port)?;
+
+            let connection_id = format!(
+                "{}/{}",
+                application.identifier(),
+                connection
+                    .id
+                    .clone()
+                    .unwrap_or_else(|| format!("connection_{}", index + 1))
+            );
+            let endpoint = match &connection.target {
+                ConnectionTarget::ProvidesPort(port) => {
+                    let (provider, provides_identifier) = resource(port)?;
+                    let endpoint = provider
+                        .lock()
+                        .unwrap()
+                        .get_provides_port(&provides_identifier);
+                    endpoint.map_err(|e| create_error(format!("'{connection_id}': {e}")))?
+                }
+                //the component itself is served at its registered endpoint
+                ConnectionTarget::SupportedInterface(port) => application
+                    .component(&port.component_ref)
+                    .and_then(|c| c.endpoint.clone())
+                    .ok_or_else(|| {
+                        create_error(format!(
+                            "'{connection_id}': '{}' serves no endpoint",
+                            port.component_ref
+                        ))
+                    })?,
+                ConnectionTarget::FindBy(FindBy::NamingService { name }) => {
+                    deployment.registry.endpoint(name).ok_or_else(|| {
+                        create_error(format!("'{connection_id}': '{name}' not registered"))
+                    })?
+                }
+                ConnectionTarget::FindBy(FindBy::StringifiedObjectRef(endpoint)) => {
+                    endpoint.clone()
+                }
+                ConnectionTarget::FindBy(FindBy::DomainFinder { finder_type, .. }) => {
+                    return Err(create_error(format!(
+                        "'{connection_id}': domainfinder '{finder_type}' not supported"
+                    )))
+                }
+            };
+            user.lock()
+                .unwrap()
+                .connect_uses_port(&uses_identifier, &connection_id, &endpoint)
+                .map_err(|e| create_error(format!("'{connection_id}': {e}")))?;
+
+            application.add_connection(ApplicationConnection {
+                connection_id,
+                uses_port: connection.uses_port.clone(),
+                target: connection.target.clone(),
+                endpoint,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns the instantiations of the SPD components, nested assemblies excluded.
+    fn component_instantiations(&self) -> impl Iterator<Item = &ComponentInstantiation> {
+        self.assembly
+            .placements
+            .iter()
+            .filter(|p| self.component(&p.file_ref).is_some())
+            .flat_map(|p| &p.instantiations)
+    }
+
+    /**
+     * Places the components of the host collocations, trying the hosts
+     * of the devices in turn until one satisfies every component of a
+     * collocation, the hosts of the assigned devices first. Returns the
+     * chosen implementation and device of the collocated instantiations.
+     */
+    fn collocate(
+        &self,
+        deployment: &DeploymentContext,
+        allocations: &mut Vec<(String, AllocationGuard)>,
+        device_assignments: &[DeviceAssignmentType],
+        source_id: &str,
+    ) -> Result<HashMap<&str, (&Implementation, String)>> {
+        let device_ids = deployment.device_ids();
+        let mut placed = HashMap::new();
+        for collocation in &self.assembly.host_collocations {
+            let mut hosts: Vec<String> = Vec::new();
+            let assigned = collocation
+                .instantiations
+                .iter()
+                .filter_map(|id| assigned_device(device_assignments, id));
+            for device_id in assigned.chain(device_ids.iter().map(String::as_str)) {
+                let host = deployment.host(device_id);
+                if !hosts.contains(&host) {
+                    hosts.push(host);
+                }
+            }
+
+            //the allocations made on a host are given back when another one is tried
+            let mut failures = Vec::new();
+            let mut members = None;
+            for host in &hosts {
+                let candidate_devices: Vec<String> = device_ids
+                    .iter()
+                    .filter(|d| deployment.host(d) == *host)
+                    .cloned()
+                    .collect();
+                let allocated = self.collocate_members(
+                    deployment,
+                    &collocation.instantiations,
+                    device_assignments,
+                    &candidate_devices,
+                    source_id,
+                );
+                match allocated {
+                    Ok(allocated) => {
+                        members = Some(allocated);
+                        break;
+                    }
+                    Err(ApplicationFactoryError::CreateApplicationError { message }) => {
+                        failures.push(format!("host '{host}': {message}"))
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let members = members.ok_or_else(|| {
+                create_error(format!(
+                    "no host satisfies the hostcollocation '{}' of {:?} [{}]",
+                    collocation
+                        .name
+                        .as_ref()
+                        .or(collocation.id.as_ref())
+                        .map_or("", String::as_str),
+                    collocation.instantiations,
+                    failures.join("; ")
+                ))
+            })?;
+            for (id, implementation, device_id, allocation) in members {
+                allocations.push(allocation);
+                placed.insert(id, (implementation, device_id));
+            }
+        }
+        Ok(placed)
+    }
+
+    /**
+     * Places the members of a hostcollocation on the candidate devices
+     * of a host, in turn. When the following members cannot be placed,
+     * the device chosen for a member is given back and the member placed
+     * on the remaining candidates, until every assignment of the members
+     * to the devices is tried. Returns the failure of the first
+     * assignment tried when none fits.
+     */
+    fn collocate_members<'a>(
+        &'a self,
+        deployment: &DeploymentContext,
+        members: &'a [String],
+        device_assignments: &[DeviceAssignmentType],
+        candidate_devices: &[String],
+        source_id: &str,
+    ) -> Result<Vec<CollocatedMember<'a>>> {
+        let Some((id, others)) = members.split_first() else {
+            return Ok(Vec::new());
+        };
+        let (component, instantiation) = self
+            .component_instantiation(id)
+            .ok_or_else(|| create_error(format!("unknown collocated '{id}'")))?;
+
+        let mut remaining = candidate_devices.to_vec();
+        let mut failure = None;
+        loop {
+            let allocated = match self.allocate(
+                deployment,
+                component,
+                instantiation,
+                device_assignments,
+                &remaining,
+                source_id,
+            ) {
+                Ok(allocated) => allocated,
+                Err(e) => return Err(failure.unwrap_or(e)),
+            };
+
+            //the allocation of the member is given back when the others do not fit
+            match self.collocate_members(
+                deployment,
+                others,
+                device_assignments,
+                candidate_devices,
+                source_id,
+            ) {
+                Ok(mut placed) => {
+                    placed.insert(
+                        0,
+                        (
+                            id.as_str(),
+                            allocated.implementation,
+                            allocated.device_id,
+                            allocated.allocation,
+                        ),
+                    );
+                    return Ok(placed);
+                }
+                Err(e @ ApplicationFactoryError::CreateApplicationError { .. }) => {
+                    failure.get_or_insert(e);
+                    remaining.retain(|d| *d != allocated.device_id);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Returns an instantiation of an SPD component along with its profile.
+    fn component_instantiation(
+        &self,
+        instantiation_id: &str,
+    ) -> Option<(&ComponentProfile, &ComponentInstantiation)> {
+        self.assembly.placements.iter().find_map(|p| {
+            let instantiation = p.instantiations.iter().find(|i| i.id == instantiation_id)?;
+            Some((self.component(&p.file_ref)?, instantiation))
+        })
+    }
+
+    /**
+     * Allocates a device among the candidates to a component
+     * instantiation, the assigned device being the only candidate of an
+     * assigned component. The implementations are matched against the
+     * allocation properties of each candidate: their processor, os and
+     * dependencies shall be advertised and satisfied by the device. The
+     * best match, the implementation with the most requirements, is
+     * allocated first, the SPD and candidate orders breaking ties.
+     * Returns the chosen implementation, the device, the allocation with
+     * its guard, and why the other implementations were rejected.
+     */
+    fn allocate<'a>(
+        &self,
+        deployment: &DeploymentContext,
+        component: &'a ComponentProfile,
+        instantiation: &ComponentInstantiation,
+        device_assignments: &[DeviceAssignmentType],
+        candidate_devices: &[String],
+        source_id: &str,
+    ) -> Result<Allocated<'a>> {
+        let assignment = device_assignments
+            .iter()
+            .find(|a| a.component_id == instantiation.id);
+        let candidate_devices: Vec<&String> = candidate_devices
+            .iter()
+            .filter(|d| assignment.is_none_or(|a| a.assigned_device_id == **d))
+            .collect();
+        if candidate_devices.is_empty() {
+            return Err(create_error(format!(
+                "no candidate device for '{}'",
+                instantiation.id
+            )));
+        }
+
+        //the implementations matching each candidate device
+        let devices: Vec<(&String, Properties)> = candidate_devices
+            .into_iter()
+            .filter_map(|id| {
+                Some((
+                    id,
+                    deployment
+                        .device(id)?
+                        .lock()
+                        .unwrap()
+                        .allocation_properties(),
+                ))
+            })
+            .collect();
+        let mut rejections = Vec::new();
+        let mut matches = Vec::new();
+        for implementation in &component.softpkg.implementations {
+            if implementation.code.is_none() {
+                rejections.push(format!("'{}' has no code", implementation.id));
+                continue;
+            }
+            for platform in implementation.platform_requirements() {
+                for (device_id, device_properties) in &devices {
+                    let requirements = platform.iter().chain(&implementation.dependencies);
+                    match allocation_properties(requirements, device_properties) {
+                        Ok(properties) => matches.push((implementation, *device_id, properties)),
+                        Err(reason) => rejections.push(format!(
+                            "'{}' on '{device_id}': {reason}",
+                            implementation.id
+                        )),
+                    }
+                }
+            }
+        }
+        matches.sort_by_key(|(_, _, properties)| std::cmp::Reverse(properties.len()));
+
+        for (implementation, device_id, allocation_properties) in matches {
+            let request = AllocationRequest {
+                request_id: instantiation.id.clone(),
+                allocation_properties,
+                requested_devices: Vec::new(),
+                candidate_devices: vec![device_id.clone()],
+                source_id: source_id.to_string(),
+            };
+            match AllocationGuard::allocate(&deployment.allocation_manager, &[request]) {
+                Ok((responses, guard)) => {
+                    let response = &responses[0];
+                    return Ok(Allocated {
+                        implementation,
+                        device_id: response.allocated_device.clone(),
+                        allocation: (response.allocation_id.clone(), guard),
+                        rejections,
+                    });
+                }
+                Err(AllocationManagerError::AllocationTimedOut { message, .. }) => rejections.push(
+                    format!("'{}' on '{device_id}': {message}", implementation.id),
+                ),
+                Err(_) => rejections.push(format!(
+                    "'{}' on '{device_id}': not allocated",
+                    implementation.id
+                )),
+            }
+        }
+        match assignment {
+            Some(assignment) => Err(ApplicationFactoryError::CreateApplicationRequestError {
+                invalid_assignments: vec![assignment.clone()],
+            }),
+            None => Err(create_error(format!(
+                "no device satisfies the implementations of '{}' [{}]",
+                instantiation.id,
+                rejections.join("; ")
+            ))),
+        }
+    }
+
+    /**
+     * Returns the property values a component instantiation is launched
+     * with. With a PRF, the defaults of the execparams and of the
+     * writable configure properties are overridden by the
+     * componentproperties of the SAD then, for the assembly controller,
+     * by the initial configuration, the values given as strings being
+     * parsed after their property type. Without a PRF, the
+     * componentproperties are configured as given, and the initial
+     * configuration is left to the assembly controller.
+     */
+    fn configuration(
+        &self,
+        component: &ComponentProfile,
+        implementation: &Implementation,
+        instantiation: &ComponentInstantiation,
+        init_configuration: &Properties,
+    ) -> Result<ComponentConfiguration> {
+        let Some(prf) = component.properties(implementation) else {
+            return Ok(ComponentConfiguration {
+                configure: instantiation.properties.clone(),
+                ..ComponentConfiguration::default()
+            });
+        };
+
+        let mut configuration = ComponentConfiguration::default();
+        for property in &prf.properties {
+            if let (Some(property_use), Some(value)) = (property_use(property), property.value()) {
+                configuration.set(property_use, DataType::new(property.id(), value));
+            }
+        }
+        for property in &instantiation.properties {
+            let (property_use, property) = override_value(&prf, property)
+                .map_err(|message| create_error(format!("'{}': {message}", instantiation.id)))?;
+            configuration.set(property_use, property);
+        }
+
+        if self.assembly.assembly_controller.as_ref() != Some(&instantiation.id) {
+            return Ok(configuration);
+        }
+        //SCA91, SCA107
+        let invalid_properties: Properties = init_configuration
+            .iter()
+            .filter(|p| override_value(&prf, p).is_err())
+            .cloned()
+            .collect();
+        if !invalid_properties.is_empty() {
+            return Err(ApplicationFactoryError::InvalidInitConfiguration { invalid_properties });
+        }
+        for property in init_configuration {
+            let (property_use, property) = override_value(&prf, property).unwrap();
+            configuration.set(property_use, property);
+        }
+        configuration.init_configured = true;
+        Ok(configuration)
+    }
+
+    /**
+     * Loads the code of an implementation on the device and executes it
+     * with the execparams, recording the component in the application.
+     */
+    fn launch(
+        deployment: &DeploymentContext,
+        application: &mut Application,
+        component: &ComponentProfile,
+        implementation: &Implementation,
+        instantiation: &ComponentInstantiation,
+        device_id: &str,
+        exec_params: &Properties,
+    ) -> Result<()> {
+        let device = deployment
+            .device(device_id)
+            .ok_or_else(|| create_error(format!("unknown device '{device_id}'")))?;
+        let code = implementation.code.as_ref().unwrap();
+
+        //executables run their entry point, libraries are entered through it
+        let (load_kind, file, options) = match code.code_type.as_str() {
+            "Executable" => (
+                LoadType::EXECUTABLE,
+                code.entry_point.as_ref().unwrap_or(&code.local_file),
+                Properties::new(),
+            ),
+            "SharedLibrary" => (
+                LoadType::SHARED_LIBRARY,
+                &code.local_file,
+                code.entry_point
+                    .iter()
+                    .map(|e| DataType::new(ENTRY_POINT_ID, AnyValue::String(e.clone())))
+                    .collect(),
+            ),
+            code_type => {
+                return Err(create_error(format!(
+                    "'{}' code of '{}' cannot be executed",
+                    code_type, component.spd_file_name
+                )))
+            }
+        };
+
+        let file_name = resolve_file_name(&component.spd_file_name, file);
+        let (root, loaded_file) = deployment
+            .file_manager
+            .lock()
+            .unwrap()
+            .local_file(&file_name)
+            .map_err(|e| create_error(format!("'{file_name}': {e}")))?;
+        device
+            .lock()
+            .unwrap()
+            .load(&root, &loaded_file, load_kind)
+            .map_err(|e| create_error(format!("'{device_id}' loading '{file_name}': {e}")))?;
+
+        let component_identifier = format!("{}:{}", instantiation.id, application.identifier());
+        let name_binding = format!("{}/{}", application.name(), instantiation.id);
+        application.add_component(ApplicationComponent {
+            identifier: component_identifier.clone(),
+            instantiation_id: instantiation.id.clone(),
+            implementation_id: implementation.id.clone(),
+            device_id: device_id.to_string(),
+            device: device.clone(),
+            loaded_file: loaded_file.clone(),
+            process_id: None,
+            name_binding: name_binding.clone(),
+            endpoint: None,
+            resource: None,
+            properties: None,
+        });
+
+        let mut parameters = vec![
+            DataType::new(COMPONENT_IDENTIFIER, AnyValue::String(component_identifier)),
+            DataType::new(NAME_BINDING, AnyValue::String(name_binding)),
+            DataType::new(
+                PROFILE_NAME,
+                AnyValue::String(component.spd_file_name.clone()),
+            ),
+        ];
+        parameters.extend(exec_params.iter().cloned());
+        let process_id = device
+            .lock()
+            .unwrap()
+            .execute(&loaded_file, &options, &parameters)
+            .map_err(|e| create_error(format!("'{device_id}' executing '{file_name}': {e}")))?;
+        application
+            .component_mut(&instantiation.id)
+            .unwrap()
+            .process_id = Some(process_id);
+        Ok(())
+    }
+
+    /**
+     * Configures the assembly controller with the initial configuration,
+     * whose values prevail over the ones of the SAD.
+     */
+    fn configure_assembly_controller(
+        &self,
+        application: &Application,
+        init_configuration: &Properties,
+        deployment: &DeploymentContext,
+    ) -> Result<()> {
+        if init_configuration.is_empty() {
+            return Ok(());
+        }
+        let resource = self
+            .assembly
+            .assembly_controller
+            .as_ref()
+            .and_then(|id| application.component(id))
+            .and_then(|c| c.resource.clone())
+            .ok_or_else(|| ApplicationFactoryError::InvalidInitConfiguration {
+                invalid_properties: init_configuration.clone(),
+            })?;
+
+        let properties = init_configuration.clone();
+        let timeout = deployment.component_timeout;
+        let configured = call_within(&deployment.component_pool, &resource, timeout, move |r| {
+            r.configure(&properties)
+        });
+        match configured {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(
+                ResourceError::InvalidConfiguration {
+                    invalid_properties, ..
+                }
+                | ResourceError::PartialConfiguration { invalid_properties },
+            )) => Err(ApplicationFactoryError::InvalidInitConfiguration { invalid_properties }),
+            Ok(Err(e)) => Err(create_error(format!("assembly controller: {e}"))),
+            Err(e) => Err(create_error(format!(
+                "assembly controller: configure {}",
+                call_failure(e, timeout)
+            ))),
+        }
+    }
+}
+
+/**
+ * Runs an operation of a component being deployed within the component
+ * timeout, so that a wedged component fails the create rather than
+ * freezing it.
+ */
+fn component_call<F>(
+    instantiation_id: &str,
+    operation_name: &str,
+    resource: &ResourceRef,
+    deployment: &DeploymentContext,
+    operation: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut (dyn ResourceTrait + Send + 'static)) -> resource::Result<()> + Send + 'static,
+{
+    let timeout = deployment.component_timeout;
+    match call_within(&deployment.component_pool, resource, timeout, operation) {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(create_error(format!("'{instantiation_id}': {e}"))),
+        Err(e) => Err(create_error(format!(
+            "'{instantiation_id}': {operation_name} {}",
+            call_failure(e, timeout)
+        ))),
+    }
+}
+
+/// Describes an operation of a component that did not return.
+fn call_failure(error: BlockingError, timeout: Duration) -> String {
+    match error {
+        BlockingError::TimedOut { .. } => format!("timed out after {timeout:?}"),
+        BlockingError::OperationPanicked { message } => format!("panicked: {message}"),
+        e => e.to_string(),
+    }
+}
+
+/// Returns the device assigned to a component instantiation.
+fn assigned_device<'a>(
+    device_assignments: &'a [DeviceAssignmentType],
+    instantiation_id: &str,
+) -> Option<&'a str> {
+    device_assignments
+        .iter()
+        .find(|a| a.component_id == instantiation_id)
+        .map(|a| a.assigned_device_id.as_str())
+}
+
+/// Returns the assignments of the components of a nested assembly, without the prefix.
+fn nested_assignments(
+    device_assignments: &[DeviceAssignmentType],
+    prefix: &str,
+) -> Vec<DeviceAssignmentType> {
+    device_assignments
+        .iter()
+        .filter_map(|a| {
+            Some(DeviceAssignmentType {
+                component_id: a.component_id.strip_prefix(prefix)?.to_string(),
+                assigned_device_id: a.assigned_device_id.clone(),
+            })
+        })
+        .collect()
+}
+
+/**
+ * Allocates a device satisfying a usesdevice dependency of the SAD.
+ * Returns the assignment and the allocation with its guard.
+ */
+fn allocate_uses_device(
+    deployment: &DeploymentContext,
+    uses_device: &UsesDevice,
+    source_id: &str,
+) -> Result<(UsesDeviceAssignmentType, (String, AllocationGuard))> {
+    let request = AllocationRequest {
+        request_id: uses_device.id.clone(),
+        allocation_properties: uses_device
+            .properties
+            .iter()
+            .map(|p| AllocationProperty::new(p.clone(), ActionType::EQ))
+            .collect(),
+        source_id: source_id.to_string(),
+        ..AllocationRequest::default()
+    };
+    let (responses, guard) = AllocationGuard::allocate(&deployment.allocation_manager, &[request])
+        .map_err(|e| create_error(format!("usesdevice '{}': {e}", uses_device.id)))?;
+    let assignment = UsesDeviceAssignmentType {
+        uses_device_id: uses_device.id.clone(),
+        assigned_device_id: responses[0].allocated_device.clone(),
+    };
+    Ok((assignment, (responses[0].allocation_id.clone(), guard)))
+}
+
+/**
+ * The outcome of the allocation of a device to a component instantiation.
+ */
+struct Allocated<'a> {
+    implementation: &'a Implementation,
+    device_id: String,
+    allocation: (String, AllocationGuard),
+    /// Why the other implementations were rejected, on which device.
+    rejections: Vec<String>,
+}
+
+/// A member of a hostcollocation placed on a device: its id, implementation, device and allocation.
+type CollocatedMember<'a> = (
+    &'a str,
+    &'a Implementation,
+    String,
+    (String, AllocationGuard),
+);
+
+/**
+ * The property values a component is launched with: the execparams are
+ * passed to execute, the configure properties are configured once the
+ * component is initialized.
+ */
+#[derive(Debug, Default)]
+struct ComponentConfiguration {
+    exec_params: Properties,
+    configure: Properties,
+    /// Whether the initial configuration of the application is merged in.
+    init_configured: bool,
+}
+
+impl ComponentConfiguration {
+    /// Sets a property value, replacing the previous one.
+    fn set(&mut self, property_use: PropertyUse, property: DataType) {
+        let properties = match property_use {
+            PropertyUse::ExecParam => &mut self.exec_params,
+            PropertyUse::Configure => &mut self.configure,
+        };
+        match properties.iter_mut().find(|p| p.id == property.id) {
+            Some(p) => *p = property,
+            None => properties.push(property),
+        }
+    }
+}
+
+/// How the value of a property is given to a component.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PropertyUse {
+    ExecParam,
+    Configure,
+}
+
+/**
+ * Returns how a property is given to a component: execparams on the
+ * command line, writable configure properties by configure, none for the
+ * other properties.
+ */
+fn property_use(property: &Property) -> Option<PropertyUse> {
+    if property.is_kind(PropertyKind::EXECPARAM) {
+        return Some(PropertyUse::ExecParam);
+    }
+    let configurable =
+        property.is_kind(PropertyKind::CONFIGURE) || property.is_kind(PropertyKind::PROPERTY);
+    (configurable && property.mode().is_writable()).then_some(PropertyUse::Configure)
+}
+
+/**
+ * Returns how an overriding property value is given to a component,
+ * with the value parsed after the property type, or why it is invalid.
+ */
+fn override_value(
+    prf: &PropertiesDescriptor,
+    property: &DataType,
+) -> std::result::Result<(PropertyUse, DataType), String> {
+    let descriptor = prf
+        .property(&property.id)
+        .ok_or_else(|| format!("no property '{}'", property.id))?;
+    let property_use = property_use(descriptor)
+        .ok_or_else(|| format!("'{}' is neither an execparam nor configurable", property.id))?;
+    let value = match (descriptor, &property.value) {
+        (Property::Simple(simple), AnyValue::String(text)) => simple
+            .enumerations
+            .iter()
+            .find(|e| e.label == *text)
+            .map(|e| e.value.clone())
+            .or_else(|| simple.value_type.parse_value(text)),
+        (_, value) => Some(value.clone()),
+    };
+    let in_range = |value: &AnyValue| match descriptor {
+        Property::Simple(simple) => simple.range.as_ref().is_none_or(|r| r.contains(value)),
+        _ => true,
+    };
+    match value {
+        Some(value) if in_range(&value) => Ok((property_use, DataType::new(&property.id, value))),
+        _ => Err(format!(
+            "'{}' is not a value of '{}'",
+            property.value, property.id
+        )),
+    }
+}
+
+/**
+ * Returns the allocation properties of the requirements of an
+ * implementation, typed after the allocation properties of a device, or
+ * why the device does not satisfy them. The requirements shall be
+ * advertised by the device: the numeric ones are capacities allocated
+ * from the device, the others shall equal, or be contained by, the
+ * device values.
+ */
+fn allocation_properties<'a>(
+    requirements: impl Iterator<Item = &'a DataType>,
+    device_properties: &Properties,
+) -> std::result::Result<Vec<AllocationProperty>, String> {
+    requirements
+        .map(|requirement| {
+            let advertised = device_properties
+                .iter()
+                .find(|p| p.id == requirement.id)
+                .ok_or_else(|| format!("'{}' is not advertised", requirement.id))?;
+            let value = match &requirement.value {
+                AnyValue::String(text) => typed(&advertised.value, text),
+                value => Some(value.clone()),
+            }
+            .ok_or_else(|| {
+                format!(
+                    "'{}' is not a value of '{}'",
+                    requirement.value, requirement.id
+                )
+            })?;
+
+            if value.as_f64().is_some() {
+                return match advertised.value.checked_sub(&value) {
+                    Some(_) => Ok(AllocationProperty::new(
+                        DataType::new(&requirement.id, value),
+                        ActionType::EXTERNAL,
+                    )),
+                    None => Err(format!(
+                        "'{}' is {}, {value} required",
+                        requirement.id, advertised.value
+                    )),
+                };
+            }
+            match ActionType::EQ.evaluate(&advertised.value, &value) {
+                true => Ok(AllocationProperty::new(
+                    DataType::new(&requirement.id, value),
+                    ActionType::EQ,
+                )),
+                false => Err(format!(
+                    "'{}' is {}, {value} required",
+                    requirement.id, advertised.value
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Parses a value of the type of a device value, the elements type for a sequence.
+fn typed(device_value: &AnyValue, text: &str) -> Option<AnyValue> {
+    let value_type = match device_value {
+        AnyValue::Boolean(_) => PropertyType::BOOLEAN,
+        AnyValue::Octet(_) => PropertyType::OCTET,
+        AnyValue::Short(_) => PropertyType::SHORT,
+        AnyValue::UShort(_) => PropertyType::USHORT,
+        AnyValue::Long(_) => PropertyType::LONG,
+        AnyValue::ULong(_) => PropertyType::ULONG,
+        AnyValue::LongLong(_) => PropertyType::LONGLONG,
+        AnyValue::ULongLong(_) => PropertyType::ULONGLONG,
+        AnyValue::Float(_) => PropertyType::FLOAT,
+        AnyValue::Double(_) => PropertyType::DOUBLE,
+        AnyValue::Sequence(values) if !values.is_empty() => return typed(&values[0], text),
+        _ => PropertyType::STRING,
+    };
+    value_type.parse_value(text)
+}
+
+/// Returns the error for an application that cannot be created.
+fn create_error(message: String) -> ApplicationFactoryError {
+    ApplicationFactoryError::CreateApplicationError { message }
+}
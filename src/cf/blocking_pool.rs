@@ -0,0 +1,379 @@
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+/// The number of threads a pool runs at most by default.
+pub const DEFAULT_THREADS: usize = 8;
+
+/**
+ * Convienence enum definition that includes all blocking pool errors.
+ */
+#[derive(Error, Debug)]
+pub enum BlockingError {
+    /**
+     * This exception indicates that the pool has no thread left to run
+     * the operation.
+     */
+    #[error("PoolClosed: msg: '{message}'.")]
+    PoolClosed { message: String },
+    /**
+     * This exception indicates that the operation panicked while run by
+     * the pool.
+     */
+    #[error("OperationPanicked: msg: '{message}'.")]
+    OperationPanicked { message: String },
+    /**
+     * This exception indicates that the operation did not return within
+     * the time given to it.
+     */
+    #[error("TimedOut: msg: '{message}'.")]
+    TimedOut { message: String },
+    /**
+     * This exception indicates that the pool shared by the process was
+     * already in use when sized.
+     */
+    #[error("AlreadyInUse: msg: '{message}'.")]
+    AlreadyInUse { message: String },
+}
+
+/*
+ * Convienence type definition that includes all blocking pool returned errors.
+ */
+pub type Result<T, E = BlockingError> = anyhow::Result<T, E>;
+
+/**
+ * Metrics of a blocking pool.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct BlockingPoolMetrics {
+    /// The number of threads of the pool running, at most its size.
+    pub threads: usize,
+    /// The number of operations waiting for a thread.
+    pub queue_depth: usize,
+    /// The highest number of operations waiting for a thread so far.
+    pub max_queue_depth: usize,
+    /// The number of operations being run, the ones timed by the threads of the pool included.
+    pub active: usize,
+    /// The number of operations run.
+    pub completed: u64,
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The delivery of the outcome of a job.
+type Delivery = Box<dyn FnOnce() + Send>;
+
+/// The pool shared by the objects of the process.
+static SHARED: OnceLock<BlockingPool> = OnceLock::new();
+
+thread_local! {
+    /// The pool the current thread runs the jobs of, if any, by the address of its queue.
+    static POOL: Cell<usize> = const { Cell::new(0) };
+}
+
+/**
+ * Pool of the threads running the blocking operations of the async
+ * services, the file and process operations, rather than the blocking
+ * threads of tokio, so that a heavy file traffic neither exhausts them
+ * nor starves the other services of the runtime. The threads are started
+ * as the operations are queued, up to the size of the pool, the
+ * operations beyond waiting in the queue. The clones of a pool share its
+ * threads, which stop once the last clone is dropped. The objects of a
+ * process share the pool of the process, sized by its booter.
+ */
+#[derive(Clone)]
+pub struct BlockingPool {
+    sender: Sender<Job>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+    metrics: Arc<Mutex<BlockingPoolMetrics>>,
+    size: usize,
+    /// The number of threads running the operations timed by the threads of the pool.
+    nested: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for BlockingPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BlockingPool")
+            .field("size", &self.size)
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+/// The pool shared by the process.
+impl Default for BlockingPool {
+    fn default() -> Self {
+        BlockingPool::shared()
+    }
+}
+
+impl BlockingPool {
+    /// Returns a pool of at most size threads, at least one.
+    pub fn new(size: usize) -> BlockingPool {
+        let (sender, receiver) = mpsc::channel();
+        BlockingPool {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            metrics: Arc::default(),
+            size: size.max(1),
+            nested: Arc::default(),
+        }
+    }
+
+    /**
+     * Returns the pool shared by the objects of the process, of
+     * DEFAULT_THREADS threads unless sized first by init_shared.
+     */
+    pub fn shared() -> BlockingPool {
+        SHARED
+            .get_or_init(|| BlockingPool::new(DEFAULT_THREADS))
+            .clone()
+    }
+
+    /**
+     * Sizes the pool shared by the process, e.g. from the
+     * --blocking-threads option of its booter, before its objects are
+     * created. Fails once the shared pool is in use.
+     */
+    pub fn init_shared(size: usize) -> Result<BlockingPool> {
+        SHARED
+            .set(BlockingPool::new(size))
+            .map_err(|_| BlockingError::AlreadyInUse {
+                message: format!(
+                    "the shared pool already runs at most {} threads",
+                    BlockingPool::shared().size
+                ),
+            })?;
+        Ok(BlockingPool::shared())
+    }
+
+    /// Returns the number of threads the pool runs at most.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the metrics of the pool.
+    pub fn metrics(&self) -> BlockingPoolMetrics {
+        *self.metrics.lock().unwrap()
+    }
+
+    /**
+     * Runs a blocking operation on a thread of the pool, returning its
+     * result once run.
+     */
+    pub async fn run<R, F>(&self, operation: F) -> Result<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        self.queue(self.metered(move || {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(operation));
+            Box::new(move || {
+                let _ = reply.send(outcome);
+            })
+        }))?;
+        match result.await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(payload)) => Err(BlockingError::OperationPanicked {
+                message: panic_message(payload.as_ref()),
+            }),
+            Err(_) => Err(BlockingError::OperationPanicked {
+                message: "the operation was dropped".to_string(),
+            }),
+        }
+    }
+
+    /**
+     * Runs a blocking operation on a thread of the pool for a synchronous
+     * caller, giving up once the timeout elapsed, e.g. on a hung mount or
+     * a wedged component. An operation still queued then is skipped, and
+     * the result of one returning past the timeout is handed to late,
+     * e.g. to undo its effect, the caller having been told it failed.
+     * Called by a thread of the pool, e.g. a file opened while served,
+     * the operation is run on a thread of its own rather than queued
+     * behind the operations waiting for it, at most size such threads
+     * running at once.
+     */
+    pub fn run_within<R, F, L>(&self, timeout: Duration, operation: F, late: L) -> Result<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+        L: FnOnce(R) + Send + 'static,
+    {
+        let (reply, result) = mpsc::sync_channel(1);
+        //the reply is taken by the first of the operation returning and the caller giving up
+        let waiting = Arc::new(Mutex::new(Some(reply)));
+        let waiter = waiting.clone();
+        self.queue_timed(self.metered(move || -> Delivery {
+            if waiter.lock().unwrap().is_none() {
+                return Box::new(|| {});
+            }
+            let outcome = panic::catch_unwind(AssertUnwindSafe(operation));
+            let reply = waiter.lock().unwrap().take();
+            match (reply, outcome) {
+                (Some(reply), outcome) => Box::new(move || {
+                    let _ = reply.send(outcome);
+                }),
+                (None, Ok(value)) => Box::new(move || late(value)),
+                (None, Err(_)) => Box::new(|| {}),
+            }
+        }))?;
+
+        let outcome = match result.recv_timeout(timeout) {
+            Ok(outcome) => Ok(outcome),
+            Err(RecvTimeoutError::Timeout) => match waiting.lock().unwrap().take() {
+                Some(_) => {
+                    return Err(BlockingError::TimedOut {
+                        message: format!("no answer within {timeout:?}"),
+                    })
+                }
+                //the operation returned meanwhile
+                None => result.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            },
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(payload)) => Err(BlockingError::OperationPanicked {
+                message: panic_message(payload.as_ref()),
+            }),
+            Err(_) => Err(BlockingError::OperationPanicked {
+                message: "the operation was dropped".to_string(),
+            }),
+        }
+    }
+
+    /**
+     * Returns the job running an operation, accounted in the metrics of
+     * the pool before its outcome is delivered.
+     */
+    fn metered(&self, operation: impl FnOnce() -> Delivery + Send + 'static) -> Job {
+        let metrics = self.metrics.clone();
+        Box::new(move || {
+            {
+                let mut metrics = metrics.lock().unwrap();
+                metrics.queue_depth -= 1;
+                metrics.active += 1;
+            }
+            let deliver = operation();
+            {
+                let mut metrics = metrics.lock().unwrap();
+                metrics.active -= 1;
+                metrics.completed += 1;
+            }
+            deliver();
+        })
+    }
+
+    /// Returns the address identifying the pool to its threads.
+    fn address(&self) -> usize {
+        Arc::as_ptr(&self.receiver) as usize
+    }
+
+    /**
+     * Queues a job run within a timeout, on a thread of its own when
+     * queued by a thread of the pool and fewer than size such threads run.
+     */
+    fn queue_timed(&self, job: Job) -> Result<()> {
+        let address = self.address();
+        let from_pool = POOL.get() == address;
+        if !from_pool || self.nested.fetch_add(1, Ordering::SeqCst) >= self.size {
+            if from_pool {
+                self.nested.fetch_sub(1, Ordering::SeqCst);
+            }
+            return self.queue(job);
+        }
+
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.queue_depth += 1;
+        metrics.max_queue_depth = metrics.max_queue_depth.max(metrics.queue_depth);
+        drop(metrics);
+        //the job is handed back when the thread does not start
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let nested = self.nested.clone();
+        let started = std::thread::Builder::new()
+            .name("scars-blocking-nested".to_string())
+            .spawn(move || {
+                POOL.set(address);
+                if let Ok(job) = receiver.recv() {
+                    job();
+                }
+                nested.fetch_sub(1, Ordering::SeqCst);
+            });
+        match started {
+            Ok(_) => {
+                let _ = sender.send(job);
+                Ok(())
+            }
+            Err(_) => {
+                self.nested.fetch_sub(1, Ordering::SeqCst);
+                self.metrics.lock().unwrap().queue_depth -= 1;
+                self.queue(job)
+            }
+        }
+    }
+
+    /**
+     * Queues a job, starting a thread when none is idle and the pool is
+     * not full. The operations timed by the threads of the pool running on
+     * threads of their own, more operations may be active than threads.
+     */
+    fn queue(&self, job: Job) -> Result<()> {
+        let mut metrics = self.metrics.lock().unwrap();
+        metrics.queue_depth += 1;
+        metrics.max_queue_depth = metrics.max_queue_depth.max(metrics.queue_depth);
+        let idle = metrics.threads.saturating_sub(metrics.active);
+        if metrics.queue_depth > idle && metrics.threads < self.size {
+            let receiver = self.receiver.clone();
+            let stopped = self.metrics.clone();
+            let address = self.address();
+            let started = std::thread::Builder::new()
+                .name(format!("scars-blocking-{}", metrics.threads))
+                .spawn(move || {
+                    POOL.set(address);
+                    loop {
+                        //the receiver is not locked while the job runs, the thread stopping once the pool is dropped
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    }
+                    stopped.lock().unwrap().threads -= 1;
+                });
+            if started.is_ok() {
+                metrics.threads += 1;
+            }
+        }
+        if metrics.threads == 0 {
+            metrics.queue_depth -= 1;
+            return Err(BlockingError::PoolClosed {
+                message: "no thread could be started".to_string(),
+            });
+        }
+        drop(metrics);
+        self.sender.send(job).map_err(|_| {
+            self.metrics.lock().unwrap().queue_depth -= 1;
+            BlockingError::PoolClosed {
+                message: "the threads of the pool have stopped".to_string(),
+            }
+        })
+    }
+}
+
+/// Returns the message of the payload of a panic.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
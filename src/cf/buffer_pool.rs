@@ -0,0 +1,168 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// The number of buffers a pool keeps by default.
+pub const DEFAULT_MAX_BUFFERS: usize = 16;
+
+/// The capacity of the largest buffer a pool keeps by default, the size of the largest chunks the files are streamed in.
+pub const DEFAULT_CHUNK_SIZE: usize = 1024 * 1024;
+
+/**
+ * Metrics of a buffer pool.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferPoolMetrics {
+    /// The number of buffers acquired.
+    pub acquired: u64,
+    /// The number of the buffers acquired that were pooled ones.
+    pub reused: u64,
+    /// The number of the buffers released and dropped, the pool being full or them grown past the chunk size.
+    pub discarded: u64,
+    /// The number of buffers acquired and not released yet.
+    pub in_use: usize,
+    /// The highest number of buffers in use so far.
+    pub max_in_use: usize,
+    /// The number of buffers pooled.
+    pub pooled: usize,
+    /// The capacity of the buffers pooled, in bytes.
+    pub pooled_bytes: usize,
+}
+
+struct PoolState {
+    buffers: Vec<Vec<u8>>,
+    metrics: BufferPoolMetrics,
+}
+
+/**
+ * Pool of the chunk buffers of the file transfers, the buffers released
+ * being kept, up to a number of them, for the next chunks to reuse their
+ * allocation, so that a transfer of any size runs on a few buffers. The
+ * buffers are allocated as the chunks need, up to the chunk size, the
+ * ones grown past it being dropped once released. The clones of a pool
+ * share its buffers.
+ */
+#[derive(Clone)]
+pub struct BufferPool {
+    state: Arc<Mutex<PoolState>>,
+    max_buffers: usize,
+    chunk_size: usize,
+}
+
+impl std::fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("max_buffers", &self.max_buffers)
+            .field("chunk_size", &self.chunk_size)
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        BufferPool::new(DEFAULT_MAX_BUFFERS, DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl BufferPool {
+    pub fn new(max_buffers: usize, chunk_size: usize) -> BufferPool {
+        BufferPool {
+            state: Arc::new(Mutex::new(PoolState {
+                buffers: Vec::new(),
+                metrics: BufferPoolMetrics::default(),
+            })),
+            max_buffers,
+            chunk_size,
+        }
+    }
+
+    /// Returns the capacity of the largest buffers the pool keeps, the size of the largest chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns an empty buffer, the last one released when pooled.
+    pub fn acquire(&self) -> PooledBuffer {
+        let mut state = self.state.lock().unwrap();
+        let buffer = state.buffers.pop();
+        let metrics = &mut state.metrics;
+        metrics.acquired += 1;
+        if let Some(buffer) = &buffer {
+            metrics.reused += 1;
+            metrics.pooled -= 1;
+            metrics.pooled_bytes -= buffer.capacity();
+        }
+        metrics.in_use += 1;
+        metrics.max_in_use = metrics.max_in_use.max(metrics.in_use);
+        PooledBuffer {
+            buffer: buffer.unwrap_or_default(),
+            pool: self.clone(),
+        }
+    }
+
+    /// Returns the metrics of the pool.
+    pub fn metrics(&self) -> BufferPoolMetrics {
+        self.state.lock().unwrap().metrics
+    }
+
+    fn release(&self, mut buffer: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.metrics.in_use -= 1;
+        if buffer.capacity() == 0 {
+            return;
+        }
+        if state.buffers.len() >= self.max_buffers || buffer.capacity() > self.chunk_size {
+            state.metrics.discarded += 1;
+            return;
+        }
+        buffer.clear();
+        state.metrics.pooled += 1;
+        state.metrics.pooled_bytes += buffer.capacity();
+        state.buffers.push(buffer);
+    }
+}
+
+/**
+ * Buffer of a pool, released to it once dropped. Wrapped in a Bytes by
+ * Bytes::from_owner, the buffer is released once its last slice is
+ * dropped.
+ */
+pub struct PooledBuffer {
+    buffer: Vec<u8>,
+    pool: BufferPool,
+}
+
+impl std::fmt::Debug for PooledBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PooledBuffer")
+            .field("len", &self.buffer.len())
+            .field("capacity", &self.buffer.capacity())
+            .finish()
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+}
+
+impl AsRef<[u8]> for PooledBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        self.pool.release(std::mem::take(&mut self.buffer));
+    }
+}
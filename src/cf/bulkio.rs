@@ -0,0 +1,677 @@
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::events::{EventChannel, EventChannelRef};
+
+/// The time code mode of the time stamps not set.
+pub const TCM_OFF: i16 = 0;
+/// The time code mode of the time stamps taken from the CPU clock.
+pub const TCM_CPU: i16 = 1;
+/// The time code status of the time stamps not to be relied on.
+pub const TCS_INVALID: i16 = 0;
+/// The time code status of the valid time stamps.
+pub const TCS_VALID: i16 = 1;
+
+/// The xunits of the samples spaced in time.
+pub const UNITS_TIME: i16 = 1;
+
+/**
+ * This type defines the BulkIO time stamp of the first sample of a
+ * packet: whole and fractional seconds since the epoch, with the time
+ * code mode and status of the source and its offset in samples.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PrecisionUtcTime {
+    pub tcmode: i16,
+    pub tcstatus: i16,
+    pub toff: f64,
+    pub twsec: f64,
+    pub tfsec: f64,
+}
+
+impl PrecisionUtcTime {
+    /// Returns the time stamp of now, from the CPU clock.
+    pub fn now() -> PrecisionUtcTime {
+        PrecisionUtcTime::from_system_time(SystemTime::now())
+    }
+
+    /// Returns the time stamp of a time, from the CPU clock.
+    pub fn from_system_time(time: SystemTime) -> PrecisionUtcTime {
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        PrecisionUtcTime {
+            tcmode: TCM_CPU,
+            tcstatus: TCS_VALID,
+            toff: 0.0,
+            twsec: since_epoch.as_secs() as f64,
+            tfsec: since_epoch.subsec_nanos() as f64 / 1e9,
+        }
+    }
+
+    /// Returns the time stamp of the packets whose time is unknown.
+    pub fn not_set() -> PrecisionUtcTime {
+        PrecisionUtcTime {
+            tcmode: TCM_OFF,
+            tcstatus: TCS_INVALID,
+            toff: 0.0,
+            twsec: 0.0,
+            tfsec: 0.0,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.tcstatus == TCS_VALID
+    }
+
+    /// Returns the time of a valid time stamp.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        if !self.is_valid() || self.twsec < 0.0 {
+            return None;
+        }
+        UNIX_EPOCH.checked_add(Duration::from_secs_f64(self.twsec + self.tfsec))
+    }
+
+    /**
+     * Returns the time stamp moved by a number of seconds, e.g. the
+     * duration of the samples of a packet to time stamp the next one,
+     * its fractional seconds kept within [0, 1).
+     */
+    pub fn offset(&self, seconds: f64) -> PrecisionUtcTime {
+        let whole = seconds.trunc();
+        let mut twsec = self.twsec + whole;
+        let mut tfsec = self.tfsec + (seconds - whole);
+        let carry = tfsec.floor();
+        twsec += carry;
+        tfsec -= carry;
+        PrecisionUtcTime {
+            twsec,
+            tfsec,
+            ..*self
+        }
+    }
+}
+
+/**
+ * This type defines the BulkIO Signal Related Information of a stream:
+ * the spacing and units of its samples along x, and along y for the
+ * framed ones, of subsize samples per frame, its mode, complex when 1,
+ * whether its packets may be dropped or block the pushing component,
+ * and the keywords of its source, e.g. COL_RF.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamSri {
+    pub hversion: i32,
+    pub xstart: f64,
+    pub xdelta: f64,
+    pub xunits: i16,
+    pub subsize: i32,
+    pub ystart: f64,
+    pub ydelta: f64,
+    pub yunits: i16,
+    pub mode: i16,
+    pub stream_id: String,
+    pub blocking: bool,
+    pub keywords: Properties,
+}
+
+impl StreamSri {
+    /// Returns the SRI REDHAWK gives the streams pushed without one.
+    pub fn new(stream_id: &str) -> StreamSri {
+        StreamSri {
+            hversion: 1,
+            xstart: 0.0,
+            xdelta: 1.0,
+            xunits: UNITS_TIME,
+            subsize: 0,
+            ystart: 0.0,
+            ydelta: 0.0,
+            yunits: 0,
+            mode: 0,
+            stream_id: stream_id.to_string(),
+            blocking: false,
+            keywords: Properties::new(),
+        }
+    }
+
+    /// Spaces the samples after a sample rate.
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> StreamSri {
+        self.xdelta = 1.0 / sample_rate;
+        self
+    }
+
+    /// Makes the samples complex, as interleaved real and imaginary parts.
+    pub fn with_complex(mut self, complex: bool) -> StreamSri {
+        self.mode = complex as i16;
+        self
+    }
+
+    /// Sets a keyword, replacing the one of the same id.
+    pub fn with_keyword(mut self, id: &str, value: AnyValue) -> StreamSri {
+        self.keywords.retain(|k| k.id != id);
+        self.keywords.push(DataType::new(id, value));
+        self
+    }
+
+    pub fn keyword(&self, id: &str) -> Option<&AnyValue> {
+        self.keywords.iter().find(|k| k.id == id).map(|k| &k.value)
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        1.0 / self.xdelta
+    }
+
+    pub fn complex(&self) -> bool {
+        self.mode == 1
+    }
+}
+
+/**
+ * This trait gives the BulkIO interface of the samples of a type, e.g.
+ * IDL:BULKIO/dataFloat:1.0 for f32, as the repository id of the ports
+ * in the SCDs.
+ */
+pub trait BulkioSample: Clone + Send + 'static {
+    const REPID: &'static str;
+}
+
+macro_rules! bulkio_sample {
+    ($type:ty, $interface:literal) => {
+        impl BulkioSample for $type {
+            const REPID: &'static str = concat!("IDL:BULKIO/", $interface, ":1.0");
+        }
+    };
+}
+
+bulkio_sample!(i8, "dataChar");
+bulkio_sample!(u8, "dataOctet");
+bulkio_sample!(i16, "dataShort");
+bulkio_sample!(u16, "dataUshort");
+bulkio_sample!(i32, "dataLong");
+bulkio_sample!(u32, "dataUlong");
+bulkio_sample!(i64, "dataLongLong");
+bulkio_sample!(u64, "dataUlongLong");
+bulkio_sample!(f32, "dataFloat");
+bulkio_sample!(f64, "dataDouble");
+
+/**
+ * This type defines the messages a BulkIO port pushes on the event
+ * channel of its connections: the SRI of a stream, before its first
+ * packet and on each of its changes, and the packets of samples, the
+ * end of the stream being flagged on its last packet.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BulkioMessage<T> {
+    Sri(StreamSri),
+    Packet {
+        data: Vec<T>,
+        time: PrecisionUtcTime,
+        eos: bool,
+        stream_id: String,
+    },
+}
+
+/**
+ * This type defines what a uses port does with a packet when the
+ * consumer of a flow-controlled connection has no credit left.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowPolicy {
+    /// The packet is dropped.
+    #[default]
+    Drop,
+    /// The producer waits up to the timeout for a credit, the packet being dropped past it.
+    Block(Duration),
+}
+
+/**
+ * Metrics of the flow control of a connection of a uses port.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FlowMetrics {
+    /// The credits granted and not used yet.
+    pub credits: usize,
+    /// The packets sent and not credited back yet, queued by the consumer.
+    pub queue_depth: usize,
+    /// The highest queue depth so far.
+    pub max_queue_depth: usize,
+    pub sent: u64,
+    /// The packets dropped, no credit being granted in time.
+    pub dropped: u64,
+    /// The times the producer waited for a credit.
+    pub blocked: u64,
+}
+
+/// The credits of a consumer, negative when owing the packets sent regardless.
+struct CreditState {
+    credits: i64,
+    closed: bool,
+    metrics: FlowMetrics,
+}
+
+/// The credits shared by a consumer and its producer, the blocked producer waiting on the condition.
+struct CreditQueue {
+    state: Mutex<CreditState>,
+    granted: Condvar,
+}
+
+/**
+ * The credits the consumer of the packets of a connection grants its
+ * producer, a packet being sent for each credit: the consumer grants a
+ * window of credits first, the packets it can queue, then a credit back
+ * for each packet it takes, so that a stalled consumer stops the
+ * packets instead of queueing them without bound. Cloned credits are
+ * shared.
+ */
+#[derive(Clone)]
+pub struct PacketCredits {
+    queue: Arc<CreditQueue>,
+}
+
+impl std::fmt::Debug for PacketCredits {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PacketCredits")
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+impl PacketCredits {
+    /// Returns the credits of a consumer queueing up to window packets.
+    pub fn new(window: usize) -> PacketCredits {
+        PacketCredits {
+            queue: Arc::new(CreditQueue {
+                state: Mutex::new(CreditState {
+                    credits: window as i64,
+                    closed: false,
+                    metrics: FlowMetrics::default(),
+                }),
+                granted: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Grants credits to the producer, e.g. one for each packet taken, waking it when blocked.
+    pub fn grant(&self, credits: usize) {
+        {
+            let mut state = self.queue.state.lock().unwrap();
+            state.credits += credits as i64;
+            state.metrics.queue_depth = state.metrics.queue_depth.saturating_sub(credits);
+        }
+        self.queue.granted.notify_all();
+    }
+
+    /// Ends the flow control, e.g. the consumer being gone, the packets being sent from now on.
+    pub fn close(&self) {
+        self.queue.state.lock().unwrap().closed = true;
+        self.queue.granted.notify_all();
+    }
+
+    pub fn metrics(&self) -> FlowMetrics {
+        let state = self.queue.state.lock().unwrap();
+        FlowMetrics {
+            credits: state.credits.max(0) as usize,
+            ..state.metrics
+        }
+    }
+
+    /// Takes a credit for a packet, applying the policy when none is left, returning whether to send it.
+    fn acquire(&self, policy: FlowPolicy) -> bool {
+        let mut state = self.queue.state.lock().unwrap();
+        if state.credits <= 0 && !state.closed {
+            let FlowPolicy::Block(timeout) = policy else {
+                state.metrics.dropped += 1;
+                return false;
+            };
+            state.metrics.blocked += 1;
+            state = self
+                .queue
+                .granted
+                .wait_timeout_while(state, timeout, |s| s.credits <= 0 && !s.closed)
+                .unwrap()
+                .0;
+            if state.credits <= 0 && !state.closed {
+                state.metrics.dropped += 1;
+                return false;
+            }
+        }
+        Self::sent(&mut state);
+        true
+    }
+
+    /// Takes a credit for a packet sent regardless, owing it when none is left.
+    fn force(&self) {
+        Self::sent(&mut self.queue.state.lock().unwrap());
+    }
+
+    fn sent(state: &mut CreditState) {
+        if state.closed {
+            return;
+        }
+        state.credits -= 1;
+        state.metrics.sent += 1;
+        state.metrics.queue_depth += 1;
+        state.metrics.max_queue_depth =
+            state.metrics.max_queue_depth.max(state.metrics.queue_depth);
+    }
+}
+
+/**
+ * Uses port of samples pushed with the BulkIO semantics over an event
+ * channel: the SRI of the streams is pushed when set or changed, and
+ * the default one before the first packet of a stream pushed without
+ * one. The SRI of a stream is forgotten at its end, a stream pushed
+ * with the same id afterwards being a new one.
+ *
+ * The messages are also pushed on the transport of each connection of
+ * the port, selected per connection, e.g. a DdsEventChannel for the
+ * programs whose data distribution backbone is DDS.
+ *
+ * The packets of the channel and of the connections given the credits
+ * of their consumer are flow-controlled, the flow policy of the port
+ * applying when a consumer has no credit left. The SRIs and the packets
+ * ending a stream are sent regardless, for the consumers to follow the
+ * streams.
+ */
+pub struct BulkioOutPort<T> {
+    name: String,
+    channel: EventChannel<BulkioMessage<T>>,
+    /// The credits of the consumer of the channel, when flow-controlled.
+    credits: Option<PacketCredits>,
+    connections: Vec<Connection<T>>,
+    flow_policy: FlowPolicy,
+    active_sris: HashMap<String, StreamSri>,
+}
+
+/// A connection of a uses port: its id, transport and the credits of its consumer.
+type Connection<T> = (
+    String,
+    EventChannelRef<BulkioMessage<T>>,
+    Option<PacketCredits>,
+);
+
+impl<T: BulkioSample> BulkioOutPort<T> {
+    pub fn new(name: &str, channel: EventChannel<BulkioMessage<T>>) -> BulkioOutPort<T> {
+        BulkioOutPort {
+            name: name.to_string(),
+            channel,
+            credits: None,
+            connections: Vec::new(),
+            flow_policy: FlowPolicy::default(),
+            active_sris: HashMap::new(),
+        }
+    }
+
+    /// Flow-controls the packets pushed on the channel by the credits of its consumer.
+    pub fn with_credits(mut self, credits: PacketCredits) -> BulkioOutPort<T> {
+        self.credits = Some(credits);
+        self
+    }
+
+    /// Sets the policy applied to the consumers with no credit left.
+    pub fn with_flow_policy(mut self, flow_policy: FlowPolicy) -> BulkioOutPort<T> {
+        self.flow_policy = flow_policy;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn repository_id(&self) -> &'static str {
+        T::REPID
+    }
+
+    /// Returns the SRI of the streams pushed and not ended.
+    pub fn active_sris(&self) -> Vec<&StreamSri> {
+        self.active_sris.values().collect()
+    }
+
+    /**
+     * Connects the port to the transport of a connection, replacing the
+     * one of the same id, the SRI of the active streams being pushed on
+     * it first.
+     */
+    pub fn connect(&mut self, connection_id: &str, transport: EventChannelRef<BulkioMessage<T>>) {
+        self.attach(connection_id, transport, None);
+    }
+
+    /// Connects the port to the transport of a connection flow-controlled by the credits of its consumer.
+    pub fn connect_with_credits(
+        &mut self,
+        connection_id: &str,
+        transport: EventChannelRef<BulkioMessage<T>>,
+        credits: PacketCredits,
+    ) {
+        self.attach(connection_id, transport, Some(credits));
+    }
+
+    fn attach(
+        &mut self,
+        connection_id: &str,
+        transport: EventChannelRef<BulkioMessage<T>>,
+        credits: Option<PacketCredits>,
+    ) {
+        self.disconnect(connection_id);
+        for sri in self.active_sris.values() {
+            transport.push(BulkioMessage::Sri(sri.clone()));
+        }
+        self.connections
+            .push((connection_id.to_string(), transport, credits));
+    }
+
+    /// Disconnects the transport of a connection, returning whether connected.
+    pub fn disconnect(&mut self, connection_id: &str) -> bool {
+        let connections = self.connections.len();
+        self.connections.retain(|(id, _, _)| id != connection_id);
+        self.connections.len() != connections
+    }
+
+    /// Returns the ids of the connections, in the order connected.
+    pub fn connection_ids(&self) -> Vec<&str> {
+        self.connections
+            .iter()
+            .map(|(id, _, _)| id.as_str())
+            .collect()
+    }
+
+    /**
+     * Returns the metrics of the flow control of the channel, under its
+     * name, and of the connections, under their ids, when flow-controlled.
+     */
+    pub fn flow_metrics(&self) -> Vec<(String, FlowMetrics)> {
+        let channel = self.credits.iter().map(|c| (self.channel.name(), c));
+        let connections = self
+            .connections
+            .iter()
+            .filter_map(|(id, _, credits)| Some((id.as_str(), credits.as_ref()?)));
+        channel
+            .chain(connections)
+            .map(|(id, credits)| (id.to_string(), credits.metrics()))
+            .collect()
+    }
+
+    /// Pushes the SRI of a stream, unless unchanged.
+    pub fn push_sri(&mut self, sri: StreamSri) {
+        if self.active_sris.get(&sri.stream_id) == Some(&sri) {
+            return;
+        }
+        self.active_sris.insert(sri.stream_id.clone(), sri.clone());
+        self.send(BulkioMessage::Sri(sri));
+    }
+
+    /// Pushes a packet of samples of a stream, ending it when eos is set.
+    pub fn push_packet(
+        &mut self,
+        data: Vec<T>,
+        time: PrecisionUtcTime,
+        eos: bool,
+        stream_id: &str,
+    ) {
+        if !self.active_sris.contains_key(stream_id) {
+            self.push_sri(StreamSri::new(stream_id));
+        }
+        self.send(BulkioMessage::Packet {
+            data,
+            time,
+            eos,
+            stream_id: stream_id.to_string(),
+        });
+        if eos {
+            self.active_sris.remove(stream_id);
+        }
+    }
+
+    fn send(&self, message: BulkioMessage<T>) {
+        let admitted = |credits: &Option<PacketCredits>| match (credits, &message) {
+            (None, _) | (_, BulkioMessage::Sri(_)) => true,
+            (Some(credits), BulkioMessage::Packet { eos: true, .. }) => {
+                credits.force();
+                true
+            }
+            (Some(credits), _) => credits.acquire(self.flow_policy),
+        };
+        for (_, transport, credits) in &self.connections {
+            if admitted(credits) {
+                transport.push(message.clone());
+            }
+        }
+        if admitted(&self.credits) {
+            self.channel.push(message);
+        }
+    }
+}
+
+/**
+ * This type defines a packet received by a BulkIO provides port with
+ * the SRI of its stream, sri_changed telling that the SRI differs from
+ * the one of the previous packet of the stream, if any.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataBlock<T> {
+    pub data: Vec<T>,
+    pub time: PrecisionUtcTime,
+    pub eos: bool,
+    pub sri: StreamSri,
+    pub sri_changed: bool,
+}
+
+/**
+ * Provides port of the samples pushed with the BulkIO semantics on an
+ * event channel, received as data blocks: a stream whose packet comes
+ * without SRI gets the default one, and a stream ends with the block
+ * flagged with eos. A port given a window of credits grants a credit
+ * back to its producer for each packet it takes.
+ */
+pub struct BulkioInPort<T> {
+    name: String,
+    receiver: mpsc::Receiver<BulkioMessage<T>>,
+    /// The SRI of the active streams, and whether changed since their last block.
+    active_sris: HashMap<String, (StreamSri, bool)>,
+    credits: Option<PacketCredits>,
+}
+
+impl<T: BulkioSample> BulkioInPort<T> {
+    /// Receives the messages pushed on the channel from now on.
+    pub fn new(name: &str, channel: &EventChannel<BulkioMessage<T>>) -> BulkioInPort<T> {
+        BulkioInPort {
+            name: name.to_string(),
+            receiver: channel.subscribe(),
+            active_sris: HashMap::new(),
+            credits: None,
+        }
+    }
+
+    /// Flow-controls the port, queueing up to window packets.
+    pub fn with_credits(mut self, window: usize) -> BulkioInPort<T> {
+        self.credits = Some(PacketCredits::new(window));
+        self
+    }
+
+    /// Returns the credits of the port, to flow-control its producer with.
+    pub fn credits(&self) -> Option<PacketCredits> {
+        self.credits.clone()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn repository_id(&self) -> &'static str {
+        T::REPID
+    }
+
+    /// Returns the SRI of the streams received and not ended.
+    pub fn active_sris(&self) -> Vec<&StreamSri> {
+        self.active_sris.values().map(|(sri, _)| sri).collect()
+    }
+
+    /**
+     * Returns the next data block, waiting for it up to a timeout, none
+     * when the timeout expires or the channel is dropped.
+     */
+    pub fn get_packet(&mut self, timeout: Duration) -> Option<DataBlock<T>> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            let message = self.receiver.recv_timeout(remaining).ok()?;
+            if let Some(block) = self.accept(message) {
+                return Some(block);
+            }
+        }
+    }
+
+    /// Applies a message to the streams, returning the data block of a packet.
+    pub fn accept(&mut self, message: BulkioMessage<T>) -> Option<DataBlock<T>> {
+        match message {
+            BulkioMessage::Sri(sri) => {
+                let changed = match self.active_sris.get(&sri.stream_id) {
+                    Some((active, changed)) => *changed || *active != sri,
+                    None => true,
+                };
+                self.active_sris
+                    .insert(sri.stream_id.clone(), (sri, changed));
+                None
+            }
+            BulkioMessage::Packet {
+                data,
+                time,
+                eos,
+                stream_id,
+            } => {
+                if let Some(credits) = &self.credits {
+                    credits.grant(1);
+                }
+                let (sri, sri_changed) = match eos {
+                    true => self.active_sris.remove(&stream_id),
+                    false => self
+                        .active_sris
+                        .get_mut(&stream_id)
+                        .map(|(sri, changed)| (sri.clone(), std::mem::take(changed))),
+                }
+                .unwrap_or_else(|| (StreamSri::new(&stream_id), true));
+                if !eos && sri_changed && !self.active_sris.contains_key(&stream_id) {
+                    self.active_sris.insert(stream_id, (sri.clone(), false));
+                }
+                Some(DataBlock {
+                    data,
+                    time,
+                    eos,
+                    sri,
+                    sri_changed,
+                })
+            }
+        }
+    }
+}
+
+/// The producer no longer waits for the credits of a port dropped.
+impl<T> Drop for BulkioInPort<T> {
+    fn drop(&mut self) {
+        if let Some(credits) = &self.credits {
+            credits.close();
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use sha3::{Digest, Sha3_256};
+
+/// The reflected Castagnoli polynomial.
+const CRC32C_POLYNOMIAL: u32 = 0x82f6_3b78;
+
+/// The tables of the slice-by-8 CRC32C, each one advancing the previous one by an octet.
+const CRC32C_TABLES: [[u32; 256]; 8] = crc32c_tables();
+
+const fn crc32c_tables() -> [[u32; 256]; 8] {
+    let mut tables = [[0u32; 256]; 8];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32C_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+    let mut k = 1;
+    while k < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let previous = tables[k - 1][i];
+            tables[k][i] = (previous >> 8) ^ tables[0][(previous & 0xff) as usize];
+            i += 1;
+        }
+        k += 1;
+    }
+    tables
+}
+
+/**
+ * Returns the CRC32C (Castagnoli) of the octets, the checksum of the
+ * chunks of the file transfers, computed 8 octets at a time.
+ */
+pub fn crc32c(data: &[u8]) -> u32 {
+    let t = &CRC32C_TABLES;
+    let mut crc = !0u32;
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        let low = u32::from_le_bytes([word[0], word[1], word[2], word[3]]) ^ crc;
+        let high = u32::from_le_bytes([word[4], word[5], word[6], word[7]]);
+        crc = t[7][(low & 0xff) as usize]
+            ^ t[6][((low >> 8) & 0xff) as usize]
+            ^ t[5][((low >> 16) & 0xff) as usize]
+            ^ t[4][(low >> 24) as usize]
+            ^ t[3][(high & 0xff) as usize]
+            ^ t[2][((high >> 8) & 0xff) as usize]
+            ^ t[1][((high >> 16) & 0xff) as usize]
+            ^ t[0][(high >> 24) as usize];
+    }
+    for octet in words.remainder() {
+        crc = t[0][((crc ^ *octet as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Returns the SHA3-256 digest of the octets, the end-to-end check of the file transfers.
+pub fn digest(data: &[u8]) -> Vec<u8> {
+    Sha3_256::digest(data).to_vec()
+}
+
+/// The SHA3-256 digest of a file read in chunks, the one of their concatenation.
+#[derive(Default, Clone)]
+pub struct ChunkedDigest {
+    hasher: Sha3_256,
+}
+
+impl ChunkedDigest {
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.hasher.finalize().to_vec()
+    }
+}
+
+/**
+ * The weak checksum of rsync over a window of octets, rolled an octet at
+ * a time: the sum of the octets and the sum of the partial sums, modulo
+ * 2^16, the checksum of the blocks of the delta transfers.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollingChecksum {
+    a: u16,
+    b: u16,
+    window: u16,
+}
+
+impl RollingChecksum {
+    pub fn new(window: &[u8]) -> RollingChecksum {
+        let (mut a, mut b) = (0u16, 0u16);
+        for octet in window {
+            a = a.wrapping_add(*octet as u16);
+            b = b.wrapping_add(a);
+        }
+        RollingChecksum {
+            a,
+            b,
+            window: window.len() as u16,
+        }
+    }
+
+    /// Slides the window by an octet, the first one going out and the next one coming in.
+    pub fn roll(&mut self, out: u8, next: u8) {
+        self.a = self.a.wrapping_sub(out as u16).wrapping_add(next as u16);
+        self.b = self
+            .b
+            .wrapping_sub(self.window.wrapping_mul(out as u16))
+            .wrapping_add(self.a);
+    }
+
+    pub fn value(&self) -> u32 {
+        ((self.b as u32) << 16) | self.a as u32
+    }
+}
@@ -0,0 +1,407 @@
+use std::fmt::{Display, Write};
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+
+/**
+ * Convienence enum definition that includes all command line errors.
+ */
+#[derive(Error, Debug)]
+pub enum CliError {
+    /**
+     * This exception indicates that an option is given without its value.
+     */
+    #[error("MissingValue: option: '{option}'.")]
+    MissingValue { option: String },
+    /**
+     * This exception indicates that the value of an option is not one it
+     * takes, e.g. a negative or infinite number of seconds.
+     */
+    #[error("InvalidValue: option: '{option}', value: '{value}'.")]
+    InvalidValue { option: String, value: String },
+    /**
+     * This exception indicates that the output format is neither text
+     * nor json.
+     */
+    #[error("UnknownFormat: format: '{format}'.")]
+    UnknownFormat { format: String },
+    /**
+     * This exception indicates that no completion script is generated
+     * for the shell.
+     */
+    #[error("UnknownShell: shell: '{shell}'.")]
+    UnknownShell { shell: String },
+}
+
+/*
+ * Convienence type definition that includes all command line returned errors.
+ */
+pub type Result<T, E = CliError> = anyhow::Result<T, E>;
+
+/**
+ * The output format of the command line interfaces: human-readable
+ * text, or JSON documents for the scripts.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl OutputFormat {
+    /**
+     * Takes the --format option out of the arguments, wherever given,
+     * the format being text without it.
+     */
+    pub fn take(args: &mut Vec<String>) -> Result<OutputFormat> {
+        match take_value(args, "--format")? {
+            Some(format) => OutputFormat::from_name(&format),
+            None => Ok(OutputFormat::Text),
+        }
+    }
+
+    /// Returns the format named "text" or "json".
+    pub fn from_name(name: &str) -> Result<OutputFormat> {
+        match name {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(CliError::UnknownFormat {
+                format: name.to_string(),
+            }),
+        }
+    }
+}
+
+/**
+ * Takes an option along with its value out of the arguments, wherever
+ * given, returning the value or None without the option.
+ */
+pub fn take_value(args: &mut Vec<String>, option: &str) -> Result<Option<String>> {
+    let Some(index) = args.iter().position(|a| a == option) else {
+        return Ok(None);
+    };
+    if index + 1 >= args.len() {
+        return Err(CliError::MissingValue {
+            option: option.to_string(),
+        });
+    }
+    Ok(args.drain(index..index + 2).nth(1))
+}
+
+/**
+ * Takes an option valued with a count out of the arguments, e.g. a
+ * number of threads. The count must be positive.
+ */
+pub fn take_count(args: &mut Vec<String>, option: &str) -> Result<Option<usize>> {
+    let Some(value) = take_value(args, option)? else {
+        return Ok(None);
+    };
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(Some(count)),
+        _ => Err(CliError::InvalidValue {
+            option: option.to_string(),
+            value,
+        }),
+    }
+}
+
+/**
+ * Takes an option valued with a number of milliseconds out of the
+ * arguments, e.g. a timeout.
+ */
+pub fn take_millis(args: &mut Vec<String>, option: &str) -> Result<Option<Duration>> {
+    let Some(value) = take_value(args, option)? else {
+        return Ok(None);
+    };
+    match value.parse::<u64>() {
+        Ok(millis) => Ok(Some(Duration::from_millis(millis))),
+        Err(_) => Err(CliError::InvalidValue {
+            option: option.to_string(),
+            value,
+        }),
+    }
+}
+
+/**
+ * Returns the duration of the value of an option given in seconds,
+ * fractions included. The value must be a positive and finite number of
+ * seconds a duration can hold, not rounded down to zero.
+ */
+pub fn parse_seconds(option: &str, value: &str) -> Result<Duration> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| CliError::InvalidValue {
+            option: option.to_string(),
+            value: value.to_string(),
+        })
+}
+
+/// Prints a value as a pretty JSON document.
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/**
+ * Prints the address a service listens on, as is in text, as a single
+ * line {"service":...,"address":...} document in JSON, the services of
+ * the booters being printed one per line as they are bound.
+ */
+pub fn print_address(format: OutputFormat, service: &str, address: impl Display) {
+    match format {
+        OutputFormat::Text => println!("{address}"),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({ "service": service, "address": address.to_string() })
+        ),
+    }
+}
+
+/**
+ * The shells the completion scripts are generated for.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn from_name(name: &str) -> Result<Shell> {
+        match name {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(CliError::UnknownShell {
+                shell: name.to_string(),
+            }),
+        }
+    }
+}
+
+/**
+ * This type describes an option of a command line: a flag without
+ * values, or an option taking a value among choices, any value, e.g. a
+ * file, when there are none.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CliOption {
+    pub name: &'static str,
+    /// None for a flag.
+    pub values: Option<&'static [&'static str]>,
+}
+
+impl CliOption {
+    pub const fn flag(name: &'static str) -> CliOption {
+        CliOption { name, values: None }
+    }
+
+    pub const fn value(name: &'static str) -> CliOption {
+        CliOption {
+            name,
+            values: Some(&[]),
+        }
+    }
+
+    pub const fn choice(name: &'static str, values: &'static [&'static str]) -> CliOption {
+        CliOption {
+            name,
+            values: Some(values),
+        }
+    }
+}
+
+/// The --format option of the command lines printing JSON documents.
+pub const FORMAT_OPTION: CliOption = CliOption::choice("--format", &["text", "json"]);
+/// The --completions option every command line takes.
+pub const COMPLETIONS_OPTION: CliOption =
+    CliOption::choice("--completions", &["bash", "zsh", "fish"]);
+
+/**
+ * This type describes a command line for its completion: its options
+ * and its commands, the other words being completed as files.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandLine {
+    pub name: &'static str,
+    pub options: &'static [CliOption],
+    pub commands: &'static [&'static str],
+}
+
+impl CommandLine {
+    /**
+     * Returns the completion script requested by the arguments, when they
+     * are the --completions option.
+     */
+    pub fn requested_completions(&self, args: &[String]) -> Result<Option<String>> {
+        match args {
+            [option] if option == COMPLETIONS_OPTION.name => Err(CliError::MissingValue {
+                option: option.clone(),
+            }),
+            [option, shell] if option == COMPLETIONS_OPTION.name => {
+                Ok(Some(self.completions(Shell::from_name(shell)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /**
+     * Returns the completion script of the command line: the options
+     * and their choices, then the commands until one is given.
+     */
+    pub fn completions(&self, shell: Shell) -> String {
+        match shell {
+            Shell::Bash => self.bash_completions(),
+            Shell::Zsh => self.zsh_completions(),
+            Shell::Fish => self.fish_completions(),
+        }
+    }
+
+    /// The name of the completion function.
+    fn function(&self) -> String {
+        format!("_{}", self.name.replace('-', "_"))
+    }
+
+    fn option_names(&self) -> String {
+        self.options
+            .iter()
+            .map(|o| o.name)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn bash_completions(&self) -> String {
+        let mut script = String::new();
+        let _ = writeln!(script, "# bash completion of {}", self.name);
+        let _ = writeln!(script, "{}() {{", self.function());
+        script.push_str(
+            "    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n",
+        );
+        script.push_str("    case \"$prev\" in\n");
+        for option in self.options {
+            match option.values {
+                Some([]) => {
+                    let _ = writeln!(script, "        {}) return ;;", option.name);
+                }
+                Some(values) => {
+                    let _ = writeln!(
+                        script,
+                        "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+                        option.name,
+                        values.join(" ")
+                    );
+                }
+                None => {}
+            }
+        }
+        script.push_str("    esac\n");
+        script.push_str("    if [[ \"$cur\" == -* ]]; then\n");
+        let _ = writeln!(
+            script,
+            "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return",
+            self.option_names()
+        );
+        script.push_str("    fi\n");
+        if !self.commands.is_empty() {
+            let commands = self.commands.join(" ");
+            script.push_str("    local word\n");
+            script.push_str("    for word in \"${COMP_WORDS[@]:1:COMP_CWORD-1}\"; do\n");
+            let _ = writeln!(
+                script,
+                "        case \" {commands} \" in *\" $word \"*) return ;; esac"
+            );
+            script.push_str("    done\n");
+            let _ = writeln!(
+                script,
+                "    COMPREPLY=($(compgen -W \"{commands}\" -- \"$cur\"))"
+            );
+        }
+        script.push_str("}\n");
+        let _ = writeln!(
+            script,
+            "complete -o default -F {} {}",
+            self.function(),
+            self.name
+        );
+        script
+    }
+
+    fn zsh_completions(&self) -> String {
+        let mut script = String::new();
+        let _ = writeln!(script, "#compdef {}", self.name);
+        let _ = writeln!(script, "{}() {{", self.function());
+        script.push_str("    case \"${words[CURRENT-1]}\" in\n");
+        for option in self.options {
+            match option.values {
+                Some([]) => {
+                    let _ = writeln!(script, "        {}) _files; return ;;", option.name);
+                }
+                Some(values) => {
+                    let _ = writeln!(
+                        script,
+                        "        {}) compadd -- {}; return ;;",
+                        option.name,
+                        values.join(" ")
+                    );
+                }
+                None => {}
+            }
+        }
+        script.push_str("    esac\n");
+        script.push_str("    if [[ \"$PREFIX\" == -* ]]; then\n");
+        let _ = writeln!(script, "        compadd -- {}; return", self.option_names());
+        script.push_str("    fi\n");
+        if !self.commands.is_empty() {
+            let commands = self.commands.join(" ");
+            script.push_str("    local word\n");
+            script.push_str("    for word in \"${(@)words[2,CURRENT-1]}\"; do\n");
+            let _ = writeln!(
+                script,
+                "        case \" {commands} \" in *\" $word \"*) _files; return ;; esac"
+            );
+            script.push_str("    done\n");
+            let _ = writeln!(script, "    compadd -- {commands}");
+        }
+        script.push_str("    _files\n");
+        script.push_str("}\n");
+        let _ = writeln!(script, "compdef {} {}", self.function(), self.name);
+        script
+    }
+
+    fn fish_completions(&self) -> String {
+        let mut script = String::new();
+        let _ = writeln!(script, "# fish completion of {}", self.name);
+        for option in self.options {
+            let name = match option.name.strip_prefix("--") {
+                Some(long) => format!("-l {long}"),
+                None => format!("-s {}", option.name.trim_start_matches('-')),
+            };
+            let _ = match option.values {
+                None => writeln!(script, "complete -c {} {name}", self.name),
+                Some([]) => writeln!(script, "complete -c {} {name} -r", self.name),
+                Some(values) => writeln!(
+                    script,
+                    "complete -c {} {name} -x -a \"{}\"",
+                    self.name,
+                    values.join(" ")
+                ),
+            };
+        }
+        if !self.commands.is_empty() {
+            let commands = self.commands.join(" ");
+            let _ = writeln!(
+                script,
+                "complete -c {} -n \"not __fish_seen_subcommand_from {commands}\" -a \"{commands}\"",
+                self.name
+            );
+        }
+        script
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use scars::cf::cli::{self, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION};
+use scars::cf::profile::codegen;
+use scars::cf::profile::prf::PropertiesDescriptor;
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-codegen",
+    options: &[FORMAT_OPTION, COMPLETIONS_OPTION],
+    commands: &["properties"],
+};
+
+/**
+ * Code generator command line interface: prints the Rust source
+ * generated from a domain profile, as JSON along with the profile and
+ * the type generated.
+ *
+ * usage: scars-codegen [--format text|json] properties <prf file> <struct name>
+ *        scars-codegen --completions bash|zsh|fish
+ */
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    match args.as_slice() {
+        [command, file, type_name] if command == "properties" => {
+            let path = Path::new(file);
+            let prf = PropertiesDescriptor::from_file(path)?;
+            let source = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| file.clone());
+            let code = codegen::properties_struct(&prf, type_name, &source);
+            match format {
+                OutputFormat::Text => print!("{code}"),
+                OutputFormat::Json => cli::print_json(&json!({
+                    "profile": file,
+                    "type_name": type_name,
+                    "code": code,
+                }))?,
+            }
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+fn usage() -> Box<dyn std::error::Error> {
+    "usage: scars-codegen [--format text|json] properties <prf file> <struct name>".into()
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use super::events::EventChannel;
+use super::resource::ResourceRef;
+
+/// The name of the channel the names of the newly registered components are pushed on.
+pub const REGISTRATIONS_CHANNEL_NAME: &str = "ComponentRegistrations";
+
+/**
+ * Name bindings of the registry: the components running in process and
+ * the endpoints of those registering over gRPC.
+ */
+#[derive(Default)]
+struct Bindings {
+    components: HashMap<String, ResourceRef>,
+    endpoints: HashMap<String, String>,
+}
+
+/**
+ * Registry where the launched components register themselves under
+ * their name binding, the deployment resolving them once executed. The
+ * components running out of process register the endpoint they are
+ * served at through the Registrar service instead of any external name
+ * service. Clones share the same registrations.
+ */
+#[derive(Clone)]
+pub struct ComponentRegistry {
+    bindings: Arc<(Mutex<Bindings>, Condvar)>,
+    registrations: EventChannel<String>,
+}
+
+impl Default for ComponentRegistry {
+    fn default() -> Self {
+        ComponentRegistry {
+            bindings: Arc::default(),
+            registrations: EventChannel::new(REGISTRATIONS_CHANNEL_NAME),
+        }
+    }
+}
+
+impl ComponentRegistry {
+    pub fn new() -> ComponentRegistry {
+        ComponentRegistry::default()
+    }
+
+    /**
+     * SCA131
+     * The registerComponent operation shall register the component indicated
+     * by the input registeringComponent parameter, if it does not already
+     * exist. The registerComponent operation ignores already existing
+     * registrations.
+     */
+    pub fn register_component(&self, name: &str, component: ResourceRef) {
+        let (bindings, registered) = &*self.bindings;
+        let added = {
+            let mut bindings = bindings.lock().unwrap();
+            let added = !bindings.components.contains_key(name);
+            bindings
+                .components
+                .entry(name.to_string())
+                .or_insert(component);
+            added
+        };
+        registered.notify_all();
+
+        //publish once the registration is visible
+        if added {
+            self.registrations.push(name.to_string());
+        }
+    }
+
+    /**
+     * Binds the name to the endpoint a component is served at. A
+     * component registering again, e.g. once restarted, replaces its
+     * previous endpoint.
+     */
+    pub fn register_endpoint(&self, name: &str, endpoint: &str) {
+        let (bindings, registered) = &*self.bindings;
+        let changed = {
+            let mut bindings = bindings.lock().unwrap();
+            let previous = bindings
+                .endpoints
+                .insert(name.to_string(), endpoint.to_string());
+            previous.as_deref() != Some(endpoint)
+        };
+        registered.notify_all();
+
+        if changed {
+            self.registrations.push(name.to_string());
+        }
+    }
+
+    /// Returns the channel the names of the newly registered components are pushed on.
+    pub fn registrations(&self) -> EventChannel<String> {
+        self.registrations.clone()
+    }
+
+    /**
+     * Removes a registration, its endpoint included, returning the
+     * component when registered.
+     */
+    pub fn unregister_component(&self, name: &str) -> Option<ResourceRef> {
+        let mut bindings = self.bindings.0.lock().unwrap();
+        bindings.endpoints.remove(name);
+        bindings.components.remove(name)
+    }
+
+    /// Removes the endpoint bound to the name, returning it when bound.
+    pub fn unregister_endpoint(&self, name: &str) -> Option<String> {
+        self.bindings.0.lock().unwrap().endpoints.remove(name)
+    }
+
+    /// Returns the names of the registered components.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .bindings
+            .0
+            .lock()
+            .unwrap()
+            .components
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the endpoints registered, as (name, endpoint) pairs sorted by name.
+    pub fn endpoints(&self) -> Vec<(String, String)> {
+        let mut endpoints: Vec<(String, String)> = self
+            .bindings
+            .0
+            .lock()
+            .unwrap()
+            .endpoints
+            .iter()
+            .map(|(name, endpoint)| (name.clone(), endpoint.clone()))
+            .collect();
+        endpoints.sort();
+        endpoints
+    }
+
+    /// Returns the endpoint bound to the name, if any, without waiting.
+    pub fn endpoint(&self, name: &str) -> Option<String> {
+        self.bindings.0.lock().unwrap().endpoints.get(name).cloned()
+    }
+
+    /**
+     * Returns the component registered under the name, waiting up to the
+     * timeout for it to register.
+     */
+    pub fn resolve(&self, name: &str, timeout: Duration) -> Option<ResourceRef> {
+        let (bindings, registered) = &*self.bindings;
+        let (bindings, _) = registered
+            .wait_timeout_while(bindings.lock().unwrap(), timeout, |b| {
+                !b.components.contains_key(name)
+            })
+            .unwrap();
+        bindings.components.get(name).cloned()
+    }
+
+    /**
+     * Returns the endpoint bound to the name, waiting up to the timeout
+     * for a component to register it.
+     */
+    pub fn resolve_endpoint(&self, name: &str, timeout: Duration) -> Option<String> {
+        let (bindings, registered) = &*self.bindings;
+        let (bindings, _) = registered
+            .wait_timeout_while(bindings.lock().unwrap(), timeout, |b| {
+                !b.endpoints.contains_key(name)
+            })
+            .unwrap();
+        bindings.endpoints.get(name).cloned()
+    }
+}
+
+impl fmt::Debug for ComponentRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ComponentRegistry")
+            .field("names", &self.names())
+            .field("endpoints", &self.endpoints())
+            .finish()
+    }
+}
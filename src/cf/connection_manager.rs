@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::component_registry::ComponentRegistry;
+use super::resource::ResourceRef;
+
+/**
+ * Convienence enum definition that includes all ConnectionManager errors.
+ */
+#[derive(Error, Debug)]
+pub enum ConnectionManagerError {
+    /**
+     * This exception indicates that an endpoint of the connection cannot
+     * take part in it, e.g. a uses endpoint which is not a component.
+     */
+    #[error("InvalidEndpoint: msg: '{message}'.")]
+    InvalidEndpoint { message: String },
+    /**
+     * This exception indicates that the connection already exists or
+     * that its existing endpoints refused it.
+     */
+    #[error("InvalidConnection: connection: '{record_id}', msg: '{message}'.")]
+    InvalidConnection { record_id: String, message: String },
+    /**
+     * This exception indicates that no connection is recorded under the
+     * connection record id.
+     */
+    #[error("InvalidConnectionId: connection: '{record_id}'.")]
+    InvalidConnectionId { record_id: String },
+}
+
+/*
+ * Convienence type definition that includes all ConnectionManager returned errors.
+ */
+pub type Result<T, E = ConnectionManagerError> = anyhow::Result<T, E>;
+
+/**
+ * This type identifies the object an endpoint of a connection is
+ * resolved from.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EndpointResolution {
+    /// A component registered in the ComponentRegistry, by name binding.
+    COMPONENT(String),
+    /// A device registered with the domain, by identifier.
+    DEVICE(String),
+    /// A service registered with the domain, by name.
+    SERVICE(String),
+    /// A domain object, by the endpoint it is served at.
+    OBJECT(String),
+}
+
+/**
+ * This type describes an endpoint of a connection: a port of a
+ * component, or a device, service or domain object whose own endpoint
+ * is connected to, the port name being ignored for them.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointRequest {
+    pub resolution: EndpointResolution,
+    pub port_name: String,
+}
+
+/**
+ * This type describes a connection recorded by the ConnectionManager,
+ * connected once both of its endpoints exist.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionStatus {
+    pub record_id: String,
+    pub connection_id: String,
+    /// The requester of the connection, e.g. the application or the operator.
+    pub requester_id: String,
+    pub uses_endpoint: EndpointRequest,
+    pub provides_endpoint: EndpointRequest,
+    pub connected: bool,
+}
+
+/**
+ * Recorded connections and the endpoints of the registered devices and
+ * services.
+ */
+#[derive(Debug)]
+struct ConnectionState {
+    registry: ComponentRegistry,
+    connections: Vec<ConnectionStatus>,
+    objects: HashMap<EndpointResolution, String>,
+}
+
+impl ConnectionState {
+    /**
+     * Connects the uses port to the provides endpoint, returning false
+     * when an endpoint does not exist yet.
+     */
+    fn establish(&self, connection: &ConnectionStatus) -> Result<bool> {
+        let invalid = |message: String| ConnectionManagerError::InvalidConnection {
+            record_id: connection.record_id.clone(),
+            message,
+        };
+
+        let Some(uses) = self.component(&connection.uses_endpoint.resolution) else {
+            return Ok(false);
+        };
+        let provides = &connection.provides_endpoint;
+        let endpoint = match &provides.resolution {
+            EndpointResolution::COMPONENT(_) => match self.component(&provides.resolution) {
+                Some(component) => Some(
+                    component
+                        .lock()
+                        .unwrap()
+                        .get_provides_port(&provides.port_name)
+                        .map_err(|e| invalid(e.to_string()))?,
+                ),
+                None => None,
+            },
+            EndpointResolution::DEVICE(_) | EndpointResolution::SERVICE(_) => {
+                self.objects.get(&provides.resolution).cloned()
+            }
+            EndpointResolution::OBJECT(endpoint) => Some(endpoint.clone()),
+        };
+        let Some(endpoint) = endpoint else {
+            return Ok(false);
+        };
+
+        uses.lock()
+            .unwrap()
+            .connect_uses_port(
+                &connection.uses_endpoint.port_name,
+                &connection.connection_id,
+                &endpoint,
+            )
+            .map_err(|e| invalid(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// Breaks an established connection, ignoring a vanished uses component.
+    fn break_connection(&self, connection: &ConnectionStatus) -> Result<()> {
+        match self.component(&connection.uses_endpoint.resolution) {
+            Some(uses) => uses
+                .lock()
+                .unwrap()
+                .disconnect_port(
+                    &connection.uses_endpoint.port_name,
+                    &connection.connection_id,
+                )
+                .map_err(|e| ConnectionManagerError::InvalidConnection {
+                    record_id: connection.record_id.clone(),
+                    message: e.to_string(),
+                }),
+            None => Ok(()),
+        }
+    }
+
+    /// Establishes the pending connections whose endpoints now exist.
+    fn complete(&mut self) {
+        for index in 0..self.connections.len() {
+            if !self.connections[index].connected {
+                let connected = matches!(self.establish(&self.connections[index]), Ok(true));
+                self.connections[index].connected = connected;
+            }
+        }
+    }
+
+    /// Returns the component registered under the name binding of a component endpoint.
+    fn component(&self, resolution: &EndpointResolution) -> Option<ResourceRef> {
+        match resolution {
+            EndpointResolution::COMPONENT(name) => self.registry.resolve(name, Duration::ZERO),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * ConnectionManager of the domain: records the connections requested
+ * by the deployments and the operators, and makes them as soon as both
+ * endpoints exist. The connections whose endpoints are not there yet
+ * stay pending and are completed when the component registers in the
+ * ComponentRegistry or the device or service registers with the
+ * domain; those whose provides object leaves the domain are broken and
+ * pending again. Clones share the same connections.
+ */
+#[derive(Debug, Clone)]
+pub struct ConnectionManager {
+    state: Arc<Mutex<ConnectionState>>,
+}
+
+impl ConnectionManager {
+    /// Creates a ConnectionManager resolving the components in the registry.
+    pub fn new(registry: ComponentRegistry) -> ConnectionManager {
+        let manager = ConnectionManager {
+            state: Arc::new(Mutex::new(ConnectionState {
+                registry: registry.clone(),
+                connections: Vec::new(),
+                objects: HashMap::new(),
+            })),
+        };
+        manager.watch(&registry);
+        manager
+    }
+
+    /**
+     * Resolves the components in another registry from now on, keeping
+     * the recorded connections and the registered objects.
+     */
+    pub fn with_registry(self, registry: ComponentRegistry) -> ConnectionManager {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.registry = registry.clone();
+            state.complete();
+        }
+        self.watch(&registry);
+        self
+    }
+
+    /// Completes the pending connections when a component registers.
+    fn watch(&self, registry: &ComponentRegistry) {
+        let state: Weak<Mutex<ConnectionState>> = Arc::downgrade(&self.state);
+        registry
+            .registrations()
+            .connect(move |_: &String| match state.upgrade() {
+                Some(state) => {
+                    state.lock().unwrap().complete();
+                    true
+                }
+                None => false,
+            });
+    }
+
+    /**
+     * The readonly connections attribute contains the recorded
+     * connections, pending ones included.
+     */
+    pub fn connections(&self) -> Vec<ConnectionStatus> {
+        self.state.lock().unwrap().connections.clone()
+    }
+
+    /**
+     * Records a connection from a uses port of a component to a provides
+     * endpoint, connecting it right away when both endpoints exist and
+     * once they do otherwise. Returns the connection record id, the
+     * connection id qualified by the requester.
+     */
+    pub fn connect(
+        &self,
+        uses_endpoint: EndpointRequest,
+        provides_endpoint: EndpointRequest,
+        requester_id: &str,
+        connection_id: &str,
+    ) -> Result<String> {
+        //verify the uses endpoint is a port of a component
+        if !matches!(uses_endpoint.resolution, EndpointResolution::COMPONENT(_)) {
+            return Err(ConnectionManagerError::InvalidEndpoint {
+                message: format!(
+                    "uses endpoint {:?} is not a component",
+                    uses_endpoint.resolution
+                ),
+            });
+        }
+
+        let record_id = format!("{requester_id}:{connection_id}");
+        let mut state = self.state.lock().unwrap();
+        if state.connections.iter().any(|c| c.record_id == record_id) {
+            return Err(ConnectionManagerError::InvalidConnection {
+                record_id,
+                message: "connection already exists".to_string(),
+            });
+        }
+
+        let mut connection = ConnectionStatus {
+            record_id: record_id.clone(),
+            connection_id: connection_id.to_string(),
+            requester_id: requester_id.to_string(),
+            uses_endpoint,
+            provides_endpoint,
+            connected: false,
+        };
+        connection.connected = state.establish(&connection)?;
+        state.connections.push(connection);
+        Ok(record_id)
+    }
+
+    /// Breaks a connection when established and forgets it.
+    pub fn disconnect(&self, record_id: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .connections
+            .iter()
+            .position(|c| c.record_id == record_id)
+            .ok_or_else(|| ConnectionManagerError::InvalidConnectionId {
+                record_id: record_id.to_string(),
+            })?;
+
+        let connection = state.connections.remove(index);
+        if connection.connected {
+            state.break_connection(&connection)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Registers the endpoint of a device, service or domain object,
+     * completing the pending connections to it. An object registering
+     * again at another endpoint has its connections made again.
+     */
+    pub fn register_object(&self, object: EndpointResolution, endpoint: &str) {
+        let mut state = self.state.lock().unwrap();
+        match state.objects.get(&object) {
+            Some(previous) if previous == endpoint => return,
+            Some(_) => disconnect_object(&mut state, &object),
+            None => {}
+        }
+        state.objects.insert(object, endpoint.to_string());
+        state.complete();
+    }
+
+    /// Unregisters an object, its established connections becoming pending again.
+    pub fn unregister_object(&self, object: &EndpointResolution) {
+        let mut state = self.state.lock().unwrap();
+        if state.objects.remove(object).is_some() {
+            disconnect_object(&mut state, object);
+        }
+    }
+}
+
+/// Breaks the established connections to an object, leaving them pending.
+fn disconnect_object(state: &mut ConnectionState, object: &EndpointResolution) {
+    for index in 0..state.connections.len() {
+        let connection = &state.connections[index];
+        if connection.connected && connection.provides_endpoint.resolution == *object {
+            let _ = state.break_connection(connection);
+            state.connections[index].connected = false;
+        }
+    }
+}
@@ -0,0 +1,1237 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, ErrorNumberType, Properties, PropertyValue};
+use super::device::{
+    self, AdminType, DeviceError, DeviceRef, DeviceTrait, OperationalType, UsageType,
+};
+use super::file::{self, FileError, FileTrait};
+use super::resource::{self, ResourceError, ResourceRef, ResourceTrait};
+
+/// The repository id of the CF Resource interface.
+pub const RESOURCE_REPID: &str = "IDL:CF/Resource:1.0";
+
+/// The repository id of the CF Device interface.
+pub const DEVICE_REPID: &str = "IDL:CF/Device:1.0";
+
+/// The repository id of the CF File interface.
+pub const FILE_REPID: &str = "IDL:CF/File:1.0";
+
+/// The repository id of the CF Port interface.
+pub const PORT_REPID: &str = "IDL:CF/Port:1.0";
+
+pub(crate) const INITIALIZE_ERROR_REPID: &str = "IDL:CF/LifeCycle/InitializeError:1.0";
+pub(crate) const RELEASE_ERROR_REPID: &str = "IDL:CF/LifeCycle/ReleaseError:1.0";
+pub(crate) const INVALID_CONFIGURATION_REPID: &str = "IDL:CF/PropertySet/InvalidConfiguration:1.0";
+pub(crate) const PARTIAL_CONFIGURATION_REPID: &str = "IDL:CF/PropertySet/PartialConfiguration:1.0";
+pub(crate) const UNKNOWN_PROPERTIES_REPID: &str = "IDL:CF/UnknownProperties:1.0";
+pub(crate) const START_ERROR_REPID: &str = "IDL:CF/Resource/StartError:1.0";
+pub(crate) const STOP_ERROR_REPID: &str = "IDL:CF/Resource/StopError:1.0";
+pub(crate) const UNKNOWN_PORT_REPID: &str = "IDL:CF/PortSupplier/UnknownPort:1.0";
+pub(crate) const INVALID_PORT_REPID: &str = "IDL:CF/Port/InvalidPort:1.0";
+pub(crate) const OCCUPIED_PORT_REPID: &str = "IDL:CF/Port/OccupiedPort:1.0";
+pub(crate) const INVALID_STATE_REPID: &str = "IDL:CF/Device/InvalidState:1.0";
+pub(crate) const INVALID_CAPACITY_REPID: &str = "IDL:CF/Device/InvalidCapacity:1.0";
+pub(crate) const FILE_EXCEPTION_REPID: &str = "IDL:CF/FileException:1.0";
+pub(crate) const IO_EXCEPTION_REPID: &str = "IDL:CF/File/IOException:1.0";
+pub(crate) const INVALID_FILE_POINTER_REPID: &str = "IDL:CF/File/InvalidFilePointer:1.0";
+
+/// The values of the IDL enums, in the order of their ordinals.
+const USAGE_STATES: [UsageType; 3] = [UsageType::IDLE, UsageType::ACTIVE, UsageType::BUSY];
+const ADMIN_STATES: [AdminType; 3] = [
+    AdminType::LOCKED,
+    AdminType::SHUTTING_DOWN,
+    AdminType::UNLOCKED,
+];
+const OPERATIONAL_STATES: [OperationalType; 2] =
+    [OperationalType::ENABLED, OperationalType::DISABLED];
+const ERROR_NUMBERS: [ErrorNumberType; 44] = {
+    use ErrorNumberType::*;
+    [
+        CF_NOTSET,
+        CF_E2BIG,
+        CF_EACCES,
+        CF_EAGAIN,
+        CF_EBADF,
+        CF_EBADMSG,
+        CF_EBUSY,
+        CF_ECANCELED,
+        CF_ECHILD,
+        CF_EDEADLK,
+        CF_EDOM,
+        CF_EEXIST,
+        CF_EFAULT,
+        CF_EFBIG,
+        CF_EINPROGRESS,
+        CF_EINTR,
+        CF_EINVAL,
+        CF_EIO,
+        CF_EISDIR,
+        CF_EMFILE,
+        CF_EMLINK,
+        CF_EMSGSIZE,
+        CF_ENAMETOOLONG,
+        CF_ENFILE,
+        CF_ENODEV,
+        CF_ENOENT,
+        CF_ENOEXEC,
+        CF_ENOLCK,
+        CF_ENOMEM,
+        CF_ENOSPC,
+        CF_ENOSYS,
+        CF_ENOTDIR,
+        CF_ENOTEMPTY,
+        CF_ENOTSUP,
+        CF_ENOTTY,
+        CF_ENXIO,
+        CF_EPERM,
+        CF_EPIPE,
+        CF_ERANGE,
+        CF_EROFS,
+        CF_ESPIPE,
+        CF_ESRCH,
+        CF_ETIMEDOUT,
+        CF_EXDEV,
+    ]
+};
+
+/**
+ * Convienence enum definition that includes all CORBA bridge errors.
+ */
+#[derive(Error, Debug)]
+pub enum CorbaError {
+    /**
+     * This exception indicates the ORB failed to carry out a request,
+     * as a standard system exception, e.g. TRANSIENT, OBJECT_NOT_EXIST,
+     * BAD_OPERATION or MARSHAL.
+     */
+    #[error("SystemException: name: '{name}', msg: '{message}'.")]
+    SystemException { name: String, message: String },
+    /**
+     * This exception indicates the object raised an exception of its
+     * IDL, the members given by name.
+     */
+    #[error("UserException: repid: '{repository_id}', members: {members:?}.")]
+    UserException {
+        repository_id: String,
+        members: Properties,
+    },
+}
+
+/*
+ * Convienence type definition that includes all CORBA bridge returned errors.
+ */
+pub type Result<T, E = CorbaError> = anyhow::Result<T, E>;
+
+impl CorbaError {
+    /// Returns a standard system exception.
+    pub fn system_exception(name: &str, message: &str) -> CorbaError {
+        CorbaError::SystemException {
+            name: name.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns an exception of the IDL.
+    pub fn user_exception(repository_id: &str, members: Properties) -> CorbaError {
+        CorbaError::UserException {
+            repository_id: repository_id.to_string(),
+            members,
+        }
+    }
+}
+
+/**
+ * This interface defines a reference to a CORBA object as provided by
+ * an ORB: the dynamic invocation of the operations of its interface,
+ * the ORB marshaling the arguments and the results in CDR over IIOP
+ * after the IDL of the interface. The attributes are
+ * read and written through their _get_ and _set_ operations, the void
+ * operations return None, and the object references are carried as
+ * their stringified IORs.
+ */
+pub trait CorbaObjectTrait: Send + Sync {
+    /// Returns the stringified IOR of the object.
+    fn ior(&self) -> &str;
+
+    /// This operation invokes an operation of the object.
+    fn invoke(&self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>>;
+}
+
+/**
+ * Convienence type definition to share a CORBA object reference.
+ */
+pub type CorbaObjectRef = Arc<dyn CorbaObjectTrait>;
+
+/**
+ * This interface defines the servant of a CORBA object: the dispatch
+ * of the requests the ORB receives for the object to its
+ * implementation, the same way they are invoked on CorbaObjectTrait.
+ */
+pub trait ServantTrait: Send {
+    /// Returns the repository id of the interface, e.g. IDL:CF/Resource:1.0.
+    fn repository_id(&self) -> &str;
+
+    /// This operation dispatches a request, raising BAD_OPERATION for the unknown operations.
+    fn dispatch(&mut self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>>;
+}
+
+/**
+ * Convienence type definition to share a servant.
+ */
+pub type ServantRef = Arc<Mutex<dyn ServantTrait>>;
+
+/**
+ * This interface defines the ORB of a binding: the resolution of the
+ * object references, stringified IORs or corbaloc and corbaname URLs,
+ * and the activation of the servants in its POA. IiopOrb speaks IIOP
+ * to the ORBs of the fielded components, e.g. omniORB or TAO, and
+ * LoopbackOrb serves the objects of the process.
+ */
+pub trait OrbTrait: Send + Sync {
+    /// This operation returns the object of a reference.
+    fn string_to_object(&self, reference: &str) -> Result<CorbaObjectRef>;
+
+    /// This operation activates a servant, returning the IOR of its object.
+    fn activate(&self, servant: ServantRef) -> Result<String>;
+
+    /// This operation deactivates the servant of an object.
+    fn deactivate(&self, ior: &str) -> Result<()>;
+}
+
+/**
+ * Convienence type definition to share an ORB.
+ */
+pub type OrbRef = Arc<dyn OrbTrait>;
+
+type Servants = Arc<Mutex<HashMap<String, ServantRef>>>;
+
+/**
+ * ORB of the objects activated and invoked in the same process, for
+ * the bridges used without a transport, e.g. in tests.
+ */
+#[derive(Clone, Default)]
+pub struct LoopbackOrb {
+    servants: Servants,
+    next_key: Arc<AtomicU64>,
+}
+
+impl LoopbackOrb {
+    pub fn new() -> LoopbackOrb {
+        LoopbackOrb::default()
+    }
+}
+
+impl OrbTrait for LoopbackOrb {
+    fn string_to_object(&self, reference: &str) -> Result<CorbaObjectRef> {
+        if !self.servants.lock().unwrap().contains_key(reference) {
+            return Err(CorbaError::system_exception("INV_OBJREF", reference));
+        }
+        Ok(Arc::new(LoopbackObject {
+            ior: reference.to_string(),
+            servants: self.servants.clone(),
+        }))
+    }
+
+    fn activate(&self, servant: ServantRef) -> Result<String> {
+        let ior = format!("IOR:{:016x}", self.next_key.fetch_add(1, Ordering::SeqCst));
+        self.servants.lock().unwrap().insert(ior.clone(), servant);
+        Ok(ior)
+    }
+
+    fn deactivate(&self, ior: &str) -> Result<()> {
+        match self.servants.lock().unwrap().remove(ior) {
+            Some(_) => Ok(()),
+            None => Err(CorbaError::system_exception("OBJECT_NOT_EXIST", ior)),
+        }
+    }
+}
+
+struct LoopbackObject {
+    ior: String,
+    servants: Servants,
+}
+
+impl CorbaObjectTrait for LoopbackObject {
+    fn ior(&self) -> &str {
+        &self.ior
+    }
+
+    /// The servant is dispatched to with the servants released, so that it may activate others.
+    fn invoke(&self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        let servant = self.servants.lock().unwrap().get(&self.ior).cloned();
+        let servant =
+            servant.ok_or_else(|| CorbaError::system_exception("OBJECT_NOT_EXIST", &self.ior))?;
+        let mut servant = servant.lock().unwrap();
+        servant.dispatch(operation, arguments)
+    }
+}
+
+/**
+ * Bridge between the CF objects of scars and CORBA, through an ORB:
+ * the Resource, Device and File objects of the fielded SCA components
+ * are proxied into the domain, e.g. registered in a ComponentRegistry,
+ * an AllocationManager or a DeviceService, and the scars ones are
+ * exposed to the CORBA clients until withdrawn. The fielded components
+ * are reached through the ORB given, e.g. an IiopOrb.
+ */
+#[derive(Clone)]
+pub struct CorbaBridge {
+    orb: OrbRef,
+}
+
+impl CorbaBridge {
+    pub fn new(orb: OrbRef) -> CorbaBridge {
+        CorbaBridge { orb }
+    }
+
+    pub fn orb(&self) -> &OrbRef {
+        &self.orb
+    }
+
+    /// Proxies the Resource of a reference.
+    pub fn resource(&self, reference: &str) -> Result<CorbaResource> {
+        CorbaResource::new(self.orb.clone(), self.orb.string_to_object(reference)?)
+    }
+
+    /// Proxies the Device of a reference.
+    pub fn device(&self, reference: &str) -> Result<CorbaDevice> {
+        CorbaDevice::new(self.orb.string_to_object(reference)?)
+    }
+
+    /// Proxies the File of a reference.
+    pub fn file(&self, reference: &str) -> Result<CorbaFile> {
+        CorbaFile::new(self.orb.string_to_object(reference)?)
+    }
+
+    /// Exposes a resource, returning the IOR of its object.
+    pub fn expose_resource(&self, resource: ResourceRef) -> Result<String> {
+        let servant = ResourceServant::new(resource, self.orb.clone());
+        self.orb.activate(Arc::new(Mutex::new(servant)))
+    }
+
+    /// Exposes a device, returning the IOR of its object.
+    pub fn expose_device(&self, device: DeviceRef) -> Result<String> {
+        self.orb
+            .activate(Arc::new(Mutex::new(DeviceServant::new(device))))
+    }
+
+    /// Exposes an open file, returning the IOR of its object.
+    pub fn expose_file<F: FileTrait + Send + 'static>(&self, file: F) -> Result<String> {
+        self.orb
+            .activate(Arc::new(Mutex::new(FileServant::new(file))))
+    }
+
+    /// Withdraws an object exposed.
+    pub fn withdraw(&self, ior: &str) -> Result<()> {
+        self.orb.deactivate(ior)
+    }
+}
+
+/**
+ * Resource of a CORBA object, e.g. a fielded SCA component: its
+ * identifier is read once, the operations are invoked on the object
+ * and the CF exceptions it raises become the ResourceError ones. The
+ * endpoints of its ports are the IORs of its port objects, through
+ * which its uses ports are connected.
+ */
+pub struct CorbaResource {
+    orb: OrbRef,
+    object: CorbaObjectRef,
+    identifier: String,
+}
+
+impl CorbaResource {
+    pub fn new(orb: OrbRef, object: CorbaObjectRef) -> Result<CorbaResource> {
+        let identifier = result("_get_identifier", object.invoke("_get_identifier", &[])?)?;
+        Ok(CorbaResource {
+            orb,
+            object,
+            identifier,
+        })
+    }
+
+    pub fn ior(&self) -> &str {
+        self.object.ior()
+    }
+
+    fn port(&self, name: &str) -> resource::Result<CorbaObjectRef> {
+        let port = self.get_provides_port(name)?;
+        self.orb
+            .string_to_object(&port)
+            .map_err(|e| ResourceError::InvalidPort {
+                name: name.to_string(),
+                message: e.to_string(),
+            })
+    }
+}
+
+impl ResourceTrait for CorbaResource {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// A resource failing to answer is not started.
+    fn started(&self) -> bool {
+        self.object
+            .invoke("_get_started", &[])
+            .and_then(|started| result("_get_started", started))
+            .unwrap_or(false)
+    }
+
+    fn initialize(&mut self) -> resource::Result<()> {
+        self.object
+            .invoke("initialize", &[])
+            .map(|_| ())
+            .map_err(|e| {
+                resource_error(e, |message| ResourceError::InitializeError {
+                    messages: vec![message],
+                })
+            })
+    }
+
+    fn release_object(&mut self) -> resource::Result<()> {
+        self.object
+            .invoke("releaseObject", &[])
+            .map(|_| ())
+            .map_err(|e| {
+                resource_error(e, |message| ResourceError::ReleaseError {
+                    messages: vec![message],
+                })
+            })
+    }
+
+    fn configure(&mut self, properties: &Properties) -> resource::Result<()> {
+        self.object
+            .invoke("configure", &[AnyValue::Struct(properties.clone())])
+            .map(|_| ())
+            .map_err(|e| {
+                resource_error(e, |message| ResourceError::InvalidConfiguration {
+                    message,
+                    invalid_properties: properties.clone(),
+                })
+            })
+    }
+
+    fn query(&self, properties: &Properties) -> resource::Result<Properties> {
+        self.object
+            .invoke("query", &[AnyValue::Struct(properties.clone())])
+            .and_then(|queried| properties_result("query", queried))
+            .map_err(|e| resource_error(e, invalid_state))
+    }
+
+    fn start(&mut self) -> resource::Result<()> {
+        self.object.invoke("start", &[]).map(|_| ()).map_err(|e| {
+            resource_error(e, |message| ResourceError::StartError {
+                error_number: ErrorNumberType::CF_EIO,
+                message,
+            })
+        })
+    }
+
+    fn stop(&mut self) -> resource::Result<()> {
+        self.object.invoke("stop", &[]).map(|_| ()).map_err(|e| {
+            resource_error(e, |message| ResourceError::StopError {
+                error_number: ErrorNumberType::CF_EIO,
+                message,
+            })
+        })
+    }
+
+    /// Returns the IOR of the port object, be it a provides or a uses port.
+    fn get_provides_port(&self, name: &str) -> resource::Result<String> {
+        self.object
+            .invoke("getPort", &[AnyValue::String(name.to_string())])
+            .and_then(|port| result("getPort", port))
+            .map_err(|e| port_error(resource_error(e, invalid_state), name))
+    }
+
+    fn connect_uses_port(
+        &mut self,
+        name: &str,
+        connection_id: &str,
+        endpoint: &str,
+    ) -> resource::Result<()> {
+        let arguments = [
+            AnyValue::String(endpoint.to_string()),
+            AnyValue::String(connection_id.to_string()),
+        ];
+        self.port(name)?
+            .invoke("connectPort", &arguments)
+            .map(|_| ())
+            .map_err(|e| port_error(resource_error(e, invalid_state), name))
+    }
+
+    fn disconnect_port(&mut self, name: &str, connection_id: &str) -> resource::Result<()> {
+        self.port(name)?
+            .invoke(
+                "disconnectPort",
+                &[AnyValue::String(connection_id.to_string())],
+            )
+            .map(|_| ())
+            .map_err(|e| port_error(resource_error(e, invalid_state), name))
+    }
+}
+
+/**
+ * Device of a CORBA object, e.g. a fielded SCA device: its identifier,
+ * label and composite device are read once, its states and capacities
+ * on each call. A device failing to answer is reported DISABLED, LOCKED
+ * and BUSY, without allocation properties, so that it is never
+ * allocated.
+ */
+pub struct CorbaDevice {
+    object: CorbaObjectRef,
+    identifier: String,
+    label: String,
+    composite_device: Option<String>,
+}
+
+impl CorbaDevice {
+    pub fn new(object: CorbaObjectRef) -> Result<CorbaDevice> {
+        let read = |attribute: &str| -> Result<String> {
+            result(attribute, object.invoke(attribute, &[])?)
+        };
+        let (identifier, label) = (read("_get_identifier")?, read("_get_label")?);
+        let composite_device = Some(read("_get_compositeDevice")?).filter(|c| !c.is_empty());
+        Ok(CorbaDevice {
+            object,
+            identifier,
+            label,
+            composite_device,
+        })
+    }
+
+    pub fn ior(&self) -> &str {
+        self.object.ior()
+    }
+
+    fn state<T: Copy>(&self, attribute: &str, states: &[T]) -> Option<T> {
+        let state = self.object.invoke(attribute, &[]).ok()??;
+        enum_from_any(states, &state)
+    }
+}
+
+impl DeviceTrait for CorbaDevice {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.composite_device.as_deref()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.state("_get_usageState", &USAGE_STATES)
+            .unwrap_or(UsageType::BUSY)
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.state("_get_adminState", &ADMIN_STATES)
+            .unwrap_or(AdminType::LOCKED)
+    }
+
+    /// A device failing to be commanded keeps its state.
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        let _ = self.object.invoke(
+            "_set_adminState",
+            &[enum_to_any(&ADMIN_STATES, &admin_state)],
+        );
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.state("_get_operationalState", &OPERATIONAL_STATES)
+            .unwrap_or(OperationalType::DISABLED)
+    }
+
+    /// The allocation properties are all the properties the device is queried for.
+    fn allocation_properties(&self) -> Properties {
+        self.object
+            .invoke("query", &[AnyValue::Struct(Vec::new())])
+            .and_then(|queried| properties_result("query", queried))
+            .unwrap_or_default()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.object
+            .invoke("allocateCapacity", &[AnyValue::Struct(capacities.clone())])
+            .and_then(|allocated| result("allocateCapacity", allocated))
+            .map_err(device_error)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.object
+            .invoke(
+                "deallocateCapacity",
+                &[AnyValue::Struct(capacities.clone())],
+            )
+            .map(|_| ())
+            .map_err(device_error)
+    }
+}
+
+/**
+ * File of a CORBA object, e.g. opened on the file system of a fielded
+ * SCA domain: its name is read once, and its pointer kept as last
+ * moved when the object fails to answer.
+ */
+pub struct CorbaFile {
+    object: CorbaObjectRef,
+    file_name: String,
+    file_pointer: u64,
+}
+
+impl CorbaFile {
+    pub fn new(object: CorbaObjectRef) -> Result<CorbaFile> {
+        let file_name = result("_get_fileName", object.invoke("_get_fileName", &[])?)?;
+        let file_pointer =
+            unsigned_result("_get_filePointer", object.invoke("_get_filePointer", &[])?)?;
+        Ok(CorbaFile {
+            object,
+            file_name,
+            file_pointer,
+        })
+    }
+
+    pub fn ior(&self) -> &str {
+        self.object.ior()
+    }
+}
+
+impl FileTrait for CorbaFile {
+    fn file_name(&self) -> &String {
+        &self.file_name
+    }
+
+    fn file_pointer(&self) -> u64 {
+        self.object
+            .invoke("_get_filePointer", &[])
+            .and_then(|file_pointer| unsigned_result("_get_filePointer", file_pointer))
+            .unwrap_or(self.file_pointer)
+    }
+
+    /// Reads as many octets as the buffer holds at most.
+    fn read(&mut self, buffer: &mut Vec<u8>) -> file::Result<usize> {
+        let length = unsigned_to_any(buffer.len() as u64);
+        let data: Vec<u8> = self
+            .object
+            .invoke("read", &[length])
+            .and_then(|data| result("read", data))
+            .map_err(file_error)?;
+        let read = data.len().min(buffer.len());
+        buffer[..read].copy_from_slice(&data[..read]);
+        self.file_pointer += read as u64;
+        Ok(read)
+    }
+
+    fn write(&mut self, data: &[u8]) -> file::Result<()> {
+        self.object
+            .invoke("write", &[data.to_vec().to_any()])
+            .map_err(file_error)?;
+        self.file_pointer += data.len() as u64;
+        Ok(())
+    }
+
+    fn size_of(&self) -> file::Result<u64> {
+        self.object
+            .invoke("sizeOf", &[])
+            .and_then(|size| unsigned_result("sizeOf", size))
+            .map_err(file_error)
+    }
+
+    fn close(&mut self) -> file::Result<()> {
+        self.object
+            .invoke("close", &[])
+            .map(|_| ())
+            .map_err(file_error)
+    }
+
+    fn set_file_pointer(&mut self, file_pointer: u64) -> file::Result<()> {
+        self.object
+            .invoke("setFilePointer", &[unsigned_to_any(file_pointer)])
+            .map_err(file_error)?;
+        self.file_pointer = file_pointer;
+        Ok(())
+    }
+}
+
+/**
+ * Servant of a resource exposed to the CORBA clients. The getPort
+ * operation returns the endpoint of a provides port, and for the other
+ * names the IOR of a port object activated to connect the uses port.
+ */
+pub struct ResourceServant {
+    resource: ResourceRef,
+    orb: OrbRef,
+    /// The IORs of the uses port objects activated, by port name.
+    uses_ports: HashMap<String, String>,
+}
+
+impl ResourceServant {
+    pub fn new(resource: ResourceRef, orb: OrbRef) -> ResourceServant {
+        ResourceServant {
+            resource,
+            orb,
+            uses_ports: HashMap::new(),
+        }
+    }
+
+    fn get_port(&mut self, name: &str) -> Result<Option<AnyValue>> {
+        let endpoint = self.resource.lock().unwrap().get_provides_port(name);
+        match endpoint {
+            Ok(endpoint) => Ok(Some(AnyValue::String(endpoint))),
+            Err(ResourceError::UnknownPort { .. }) => {
+                if let Some(ior) = self.uses_ports.get(name) {
+                    return Ok(Some(AnyValue::String(ior.clone())));
+                }
+                let servant = UsesPortServant {
+                    resource: self.resource.clone(),
+                    name: name.to_string(),
+                };
+                let ior = self.orb.activate(Arc::new(Mutex::new(servant)))?;
+                self.uses_ports.insert(name.to_string(), ior.clone());
+                Ok(Some(AnyValue::String(ior)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl ServantTrait for ResourceServant {
+    fn repository_id(&self) -> &str {
+        RESOURCE_REPID
+    }
+
+    fn dispatch(&mut self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        if operation == "getPort" {
+            return self.get_port(&argument::<String>(operation, arguments, 0)?);
+        }
+        let mut resource = self.resource.lock().unwrap();
+        match operation {
+            "_get_identifier" => Ok(Some(AnyValue::String(resource.identifier().to_string()))),
+            "_get_started" => Ok(Some(AnyValue::Boolean(resource.started()))),
+            "initialize" => Ok(resource.initialize().map(|_| None)?),
+            "releaseObject" => Ok(resource.release_object().map(|_| None)?),
+            "configure" => {
+                let properties = properties_argument(operation, arguments, 0)?;
+                Ok(resource.configure(&properties).map(|_| None)?)
+            }
+            "query" => {
+                let properties = properties_argument(operation, arguments, 0)?;
+                Ok(Some(AnyValue::Struct(resource.query(&properties)?)))
+            }
+            "start" => Ok(resource.start().map(|_| None)?),
+            "stop" => Ok(resource.stop().map(|_| None)?),
+            _ => Err(bad_operation(operation)),
+        }
+    }
+}
+
+/**
+ * Servant of a uses port of a resource exposed, the connections being
+ * made to the endpoints given as the object references.
+ */
+struct UsesPortServant {
+    resource: ResourceRef,
+    name: String,
+}
+
+impl ServantTrait for UsesPortServant {
+    fn repository_id(&self) -> &str {
+        PORT_REPID
+    }
+
+    fn dispatch(&mut self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        let mut resource = self.resource.lock().unwrap();
+        let connected = match operation {
+            "connectPort" => {
+                let endpoint = argument::<String>(operation, arguments, 0)?;
+                let connection_id = argument::<String>(operation, arguments, 1)?;
+                resource.connect_uses_port(&self.name, &connection_id, &endpoint)
+            }
+            "disconnectPort" => {
+                let connection_id = argument::<String>(operation, arguments, 0)?;
+                resource.disconnect_port(&self.name, &connection_id)
+            }
+            _ => return Err(bad_operation(operation)),
+        };
+        //the port object exists for any name, the unknown ones are invalid ports
+        match connected {
+            Ok(()) => Ok(None),
+            Err(ResourceError::UnknownPort { name }) => Err(ResourceError::InvalidPort {
+                message: format!("unknown port '{name}'"),
+                name,
+            }
+            .into()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/**
+ * Servant of a device exposed to the CORBA clients, queried for its
+ * allocation properties.
+ */
+pub struct DeviceServant {
+    device: DeviceRef,
+}
+
+impl DeviceServant {
+    pub fn new(device: DeviceRef) -> DeviceServant {
+        DeviceServant { device }
+    }
+}
+
+impl ServantTrait for DeviceServant {
+    fn repository_id(&self) -> &str {
+        DEVICE_REPID
+    }
+
+    fn dispatch(&mut self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        let mut device = self.device.lock().unwrap();
+        match operation {
+            "_get_identifier" => Ok(Some(AnyValue::String(device.identifier().to_string()))),
+            "_get_label" => Ok(Some(AnyValue::String(device.label().to_string()))),
+            "_get_compositeDevice" => Ok(Some(AnyValue::String(
+                device.composite_device().unwrap_or_default().to_string(),
+            ))),
+            "_get_usageState" => Ok(Some(enum_to_any(&USAGE_STATES, &device.usage_state()))),
+            "_get_adminState" => Ok(Some(enum_to_any(&ADMIN_STATES, &device.admin_state()))),
+            "_set_adminState" => {
+                let admin_state = arguments
+                    .first()
+                    .and_then(|state| enum_from_any(&ADMIN_STATES, state))
+                    .ok_or_else(|| bad_param(operation))?;
+                device.set_admin_state(admin_state);
+                Ok(None)
+            }
+            "_get_operationalState" => Ok(Some(enum_to_any(
+                &OPERATIONAL_STATES,
+                &device.operational_state(),
+            ))),
+            "query" => {
+                let ids = properties_argument(operation, arguments, 0)?;
+                let properties = device.allocation_properties();
+                if ids.is_empty() {
+                    return Ok(Some(AnyValue::Struct(properties)));
+                }
+                let (known, unknown): (Properties, Properties) = ids
+                    .into_iter()
+                    .partition(|id| properties.iter().any(|p| p.id == id.id));
+                if !unknown.is_empty() {
+                    return Err(ResourceError::UnknownProperties {
+                        invalid_properties: unknown,
+                    }
+                    .into());
+                }
+                let queried = known
+                    .iter()
+                    .filter_map(|id| properties.iter().find(|p| p.id == id.id).cloned())
+                    .collect();
+                Ok(Some(AnyValue::Struct(queried)))
+            }
+            "allocateCapacity" => {
+                let capacities = properties_argument(operation, arguments, 0)?;
+                Ok(Some(AnyValue::Boolean(
+                    device.allocate_capacity(&capacities)?,
+                )))
+            }
+            "deallocateCapacity" => {
+                let capacities = properties_argument(operation, arguments, 0)?;
+                Ok(device.deallocate_capacity(&capacities).map(|_| None)?)
+            }
+            _ => Err(bad_operation(operation)),
+        }
+    }
+}
+
+/**
+ * Servant of an open file exposed to the CORBA clients.
+ */
+pub struct FileServant<F> {
+    file: F,
+}
+
+impl<F: FileTrait> FileServant<F> {
+    pub fn new(file: F) -> FileServant<F> {
+        FileServant { file }
+    }
+}
+
+impl<F: FileTrait + Send> ServantTrait for FileServant<F> {
+    fn repository_id(&self) -> &str {
+        FILE_REPID
+    }
+
+    fn dispatch(&mut self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        match operation {
+            "_get_fileName" => Ok(Some(AnyValue::String(self.file.file_name().clone()))),
+            "_get_filePointer" => Ok(Some(unsigned_to_any(self.file.file_pointer()))),
+            "read" => {
+                let length = unsigned_argument(operation, arguments, 0)?;
+                let mut buffer = vec![0u8; length as usize];
+                let read = self.file.read(&mut buffer)?;
+                buffer.truncate(read);
+                Ok(Some(buffer.to_any()))
+            }
+            "write" => {
+                let data = argument::<Vec<u8>>(operation, arguments, 0)?;
+                Ok(self.file.write(&data).map(|_| None)?)
+            }
+            "sizeOf" => Ok(Some(unsigned_to_any(self.file.size_of()?))),
+            "close" => Ok(self.file.close().map(|_| None)?),
+            "setFilePointer" => {
+                let file_pointer = unsigned_argument(operation, arguments, 0)?;
+                Ok(self.file.set_file_pointer(file_pointer).map(|_| None)?)
+            }
+            _ => Err(bad_operation(operation)),
+        }
+    }
+}
+
+impl From<ResourceError> for CorbaError {
+    fn from(error: ResourceError) -> Self {
+        let messages =
+            |messages: Vec<String>| vec![DataType::new("errorMessages", messages.to_any())];
+        match error {
+            ResourceError::InvalidState { message } => {
+                CorbaError::system_exception("BAD_INV_ORDER", &message)
+            }
+            ResourceError::InitializeError { messages: m } => {
+                CorbaError::user_exception(INITIALIZE_ERROR_REPID, messages(m))
+            }
+            ResourceError::ReleaseError { messages: m } => {
+                CorbaError::user_exception(RELEASE_ERROR_REPID, messages(m))
+            }
+            ResourceError::InvalidConfiguration {
+                message,
+                invalid_properties,
+            } => CorbaError::user_exception(
+                INVALID_CONFIGURATION_REPID,
+                vec![
+                    DataType::new("msg", AnyValue::String(message)),
+                    DataType::new("invalidProperties", AnyValue::Struct(invalid_properties)),
+                ],
+            ),
+            ResourceError::PartialConfiguration { invalid_properties } => {
+                CorbaError::user_exception(
+                    PARTIAL_CONFIGURATION_REPID,
+                    vec![DataType::new(
+                        "invalidProperties",
+                        AnyValue::Struct(invalid_properties),
+                    )],
+                )
+            }
+            ResourceError::UnknownProperties { invalid_properties } => CorbaError::user_exception(
+                UNKNOWN_PROPERTIES_REPID,
+                vec![DataType::new(
+                    "invalidProperties",
+                    AnyValue::Struct(invalid_properties),
+                )],
+            ),
+            ResourceError::StartError {
+                error_number,
+                message,
+            } => {
+                CorbaError::user_exception(START_ERROR_REPID, error_members(error_number, message))
+            }
+            ResourceError::StopError {
+                error_number,
+                message,
+            } => CorbaError::user_exception(STOP_ERROR_REPID, error_members(error_number, message)),
+            ResourceError::UnknownPort { .. } => {
+                CorbaError::user_exception(UNKNOWN_PORT_REPID, Vec::new())
+            }
+            ResourceError::InvalidPort { message, .. } => CorbaError::user_exception(
+                INVALID_PORT_REPID,
+                vec![
+                    DataType::new("errorCode", AnyValue::UShort(1)),
+                    DataType::new("msg", AnyValue::String(message)),
+                ],
+            ),
+        }
+    }
+}
+
+impl From<DeviceError> for CorbaError {
+    fn from(error: DeviceError) -> Self {
+        match error {
+            DeviceError::InvalidState { message } => CorbaError::user_exception(
+                INVALID_STATE_REPID,
+                vec![DataType::new("msg", AnyValue::String(message))],
+            ),
+            DeviceError::InvalidCapacity {
+                message,
+                capacities,
+            } => CorbaError::user_exception(
+                INVALID_CAPACITY_REPID,
+                vec![
+                    DataType::new("msg", AnyValue::String(message)),
+                    DataType::new("capacities", AnyValue::Struct(capacities)),
+                ],
+            ),
+        }
+    }
+}
+
+impl From<FileError> for CorbaError {
+    fn from(error: FileError) -> Self {
+        match error {
+            FileError::FileException {
+                error_number,
+                message,
+            } => CorbaError::user_exception(
+                FILE_EXCEPTION_REPID,
+                error_members(error_number, message),
+            ),
+            FileError::IOException {
+                error_number,
+                message,
+            } => {
+                CorbaError::user_exception(IO_EXCEPTION_REPID, error_members(error_number, message))
+            }
+            FileError::InvalidFilePointer => {
+                CorbaError::user_exception(INVALID_FILE_POINTER_REPID, Vec::new())
+            }
+        }
+    }
+}
+
+/// Returns the ResourceError of the CF exception, or else the fallback one.
+fn resource_error(
+    error: CorbaError,
+    fallback: impl FnOnce(String) -> ResourceError,
+) -> ResourceError {
+    let (repository_id, members) = match &error {
+        CorbaError::SystemException { name, message } if name == "BAD_INV_ORDER" => {
+            return ResourceError::InvalidState {
+                message: message.clone(),
+            }
+        }
+        CorbaError::SystemException { .. } => return fallback(error.to_string()),
+        CorbaError::UserException {
+            repository_id,
+            members,
+        } => (repository_id.as_str(), members),
+    };
+    let messages = || member::<Vec<String>>(members, "errorMessages").unwrap_or_default();
+    let invalid_properties = || properties_member(members, "invalidProperties");
+    match repository_id {
+        INITIALIZE_ERROR_REPID => ResourceError::InitializeError {
+            messages: messages(),
+        },
+        RELEASE_ERROR_REPID => ResourceError::ReleaseError {
+            messages: messages(),
+        },
+        INVALID_CONFIGURATION_REPID => ResourceError::InvalidConfiguration {
+            message: member(members, "msg").unwrap_or_default(),
+            invalid_properties: invalid_properties(),
+        },
+        PARTIAL_CONFIGURATION_REPID => ResourceError::PartialConfiguration {
+            invalid_properties: invalid_properties(),
+        },
+        UNKNOWN_PROPERTIES_REPID => ResourceError::UnknownProperties {
+            invalid_properties: invalid_properties(),
+        },
+        START_ERROR_REPID => ResourceError::StartError {
+            error_number: error_number_member(members),
+            message: member(members, "msg").unwrap_or_default(),
+        },
+        STOP_ERROR_REPID => ResourceError::StopError {
+            error_number: error_number_member(members),
+            message: member(members, "msg").unwrap_or_default(),
+        },
+        UNKNOWN_PORT_REPID => ResourceError::UnknownPort {
+            name: String::new(),
+        },
+        INVALID_PORT_REPID => ResourceError::InvalidPort {
+            name: String::new(),
+            message: member(members, "msg").unwrap_or_default(),
+        },
+        _ => fallback(error.to_string()),
+    }
+}
+
+/// Names the port of the port errors, the CF exceptions not carrying it.
+fn port_error(error: ResourceError, port: &str) -> ResourceError {
+    match error {
+        ResourceError::UnknownPort { .. } => ResourceError::UnknownPort {
+            name: port.to_string(),
+        },
+        ResourceError::InvalidPort { message, .. } => ResourceError::InvalidPort {
+            name: port.to_string(),
+            message,
+        },
+        error => error,
+    }
+}
+
+fn invalid_state(message: String) -> ResourceError {
+    ResourceError::InvalidState { message }
+}
+
+/// Returns the DeviceError of the CF exception, the other errors making the device invalid.
+fn device_error(error: CorbaError) -> DeviceError {
+    match &error {
+        CorbaError::UserException {
+            repository_id,
+            members,
+        } if repository_id == INVALID_CAPACITY_REPID => DeviceError::InvalidCapacity {
+            message: member(members, "msg").unwrap_or_default(),
+            capacities: properties_member(members, "capacities"),
+        },
+        CorbaError::UserException {
+            repository_id,
+            members,
+        } if repository_id == INVALID_STATE_REPID => DeviceError::InvalidState {
+            message: member(members, "msg").unwrap_or_default(),
+        },
+        _ => DeviceError::InvalidState {
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Returns the FileError of the CF exception, the other errors being file exceptions.
+fn file_error(error: CorbaError) -> FileError {
+    match &error {
+        CorbaError::UserException {
+            repository_id,
+            members,
+        } => match repository_id.as_str() {
+            IO_EXCEPTION_REPID => FileError::IOException {
+                error_number: error_number_member(members),
+                message: member(members, "msg").unwrap_or_default(),
+            },
+            FILE_EXCEPTION_REPID => FileError::FileException {
+                error_number: error_number_member(members),
+                message: member(members, "msg").unwrap_or_default(),
+            },
+            INVALID_FILE_POINTER_REPID => FileError::InvalidFilePointer,
+            _ => FileError::FileException {
+                error_number: ErrorNumberType::CF_EIO,
+                message: error.to_string(),
+            },
+        },
+        CorbaError::SystemException { .. } => FileError::FileException {
+            error_number: ErrorNumberType::CF_EIO,
+            message: error.to_string(),
+        },
+    }
+}
+
+fn error_members(error_number: ErrorNumberType, message: String) -> Properties {
+    vec![
+        DataType::new("errorNumber", enum_to_any(&ERROR_NUMBERS, &error_number)),
+        DataType::new("msg", AnyValue::String(message)),
+    ]
+}
+
+fn error_number_member(members: &Properties) -> ErrorNumberType {
+    members
+        .iter()
+        .find(|m| m.id == "errorNumber")
+        .and_then(|m| enum_from_any(&ERROR_NUMBERS, &m.value))
+        .unwrap_or(ErrorNumberType::CF_NOTSET)
+}
+
+fn member<T: PropertyValue>(members: &Properties, id: &str) -> Option<T> {
+    members
+        .iter()
+        .find(|m| m.id == id)
+        .and_then(|m| T::from_any(&m.value))
+}
+
+fn properties_member(members: &Properties, id: &str) -> Properties {
+    match members.iter().find(|m| m.id == id).map(|m| &m.value) {
+        Some(AnyValue::Struct(properties)) => properties.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// The IDL enums are carried as their ordinals.
+fn enum_to_any<T: PartialEq>(values: &[T], value: &T) -> AnyValue {
+    AnyValue::ULong(values.iter().position(|v| v == value).unwrap_or_default() as u32)
+}
+
+fn enum_from_any<T: Copy>(values: &[T], value: &AnyValue) -> Option<T> {
+    unsigned(value).and_then(|ordinal| values.get(ordinal as usize).copied())
+}
+
+/// The sizes and file pointers are unsigned longs, or long longs beyond.
+fn unsigned_to_any(value: u64) -> AnyValue {
+    match u32::try_from(value) {
+        Ok(value) => AnyValue::ULong(value),
+        Err(_) => AnyValue::ULongLong(value),
+    }
+}
+
+fn unsigned(value: &AnyValue) -> Option<u64> {
+    match value {
+        AnyValue::UShort(v) => Some(*v as u64),
+        AnyValue::ULong(v) => Some(*v as u64),
+        AnyValue::ULongLong(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn bad_operation(operation: &str) -> CorbaError {
+    CorbaError::system_exception("BAD_OPERATION", operation)
+}
+
+fn bad_param(operation: &str) -> CorbaError {
+    CorbaError::system_exception("BAD_PARAM", operation)
+}
+
+fn argument<T: PropertyValue>(operation: &str, arguments: &[AnyValue], index: usize) -> Result<T> {
+    arguments
+        .get(index)
+        .and_then(T::from_any)
+        .ok_or_else(|| bad_param(operation))
+}
+
+fn unsigned_argument(operation: &str, arguments: &[AnyValue], index: usize) -> Result<u64> {
+    arguments
+        .get(index)
+        .and_then(unsigned)
+        .ok_or_else(|| bad_param(operation))
+}
+
+fn properties_argument(
+    operation: &str,
+    arguments: &[AnyValue],
+    index: usize,
+) -> Result<Properties> {
+    match arguments.get(index) {
+        Some(AnyValue::Struct(properties)) => Ok(properties.clone()),
+        _ => Err(bad_param(operation)),
+    }
+}
+
+fn result<T: PropertyValue>(operation: &str, value: Option<AnyValue>) -> Result<T> {
+    value
+        .as_ref()
+        .and_then(T::from_any)
+        .ok_or_else(|| CorbaError::system_exception("MARSHAL", operation))
+}
+
+fn unsigned_result(operation: &str, value: Option<AnyValue>) -> Result<u64> {
+    value
+        .as_ref()
+        .and_then(unsigned)
+        .ok_or_else(|| CorbaError::system_exception("MARSHAL", operation))
+}
+
+fn properties_result(operation: &str, value: Option<AnyValue>) -> Result<Properties> {
+    match value {
+        Some(AnyValue::Struct(properties)) => Ok(properties),
+        _ => Err(CorbaError::system_exception("MARSHAL", operation)),
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::cf::cli;
+use crate::cf::rpc::device::StatusReply;
+use crate::cf::rpc::domain_manager::domain_management_event::Event as OdmEvent;
+use crate::cf::rpc::domain_manager::{
+    ApplicationInfo, ApplicationMetricsReply, DomainManagementEvent, DomainObject, ProcessStatus,
+    SourceCategoryType, StateChangeCategoryType, StateChangeEvent, StateChangeType,
+};
+
+/// The number of recent events kept on screen.
+pub const EVENT_LINES: usize = 200;
+/// The refresh interval of the dashboard without the --interval option.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A device of a node, along with its state when it answered.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceRow {
+    pub node: String,
+    pub identifier: String,
+    pub label: String,
+    pub status: Option<StatusReply>,
+    pub capacities: String,
+}
+
+/// A running application, along with the usage of its components.
+#[derive(Debug, Clone, Default)]
+pub struct ApplicationRow {
+    pub info: ApplicationInfo,
+    pub metrics: Option<ApplicationMetricsReply>,
+}
+
+/**
+ * The domain state shown on screen by scars-top: the device managers,
+ * along with their availability, their devices, the running applications
+ * and the recent domain events.
+ */
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    /// The identifier, the label and the availability of the nodes.
+    pub nodes: Vec<(String, String, bool)>,
+    pub devices: Vec<DeviceRow>,
+    pub applications: Vec<ApplicationRow>,
+    pub events: VecDeque<String>,
+    pub refreshed: Option<SystemTime>,
+    pub error: Option<String>,
+}
+
+impl Dashboard {
+    /// Adds an event line on top of the recent ones.
+    pub fn push_event(&mut self, time: SystemTime, line: String) {
+        self.events.push_front(format!("{} {line}", clock(time)));
+        self.events.truncate(EVENT_LINES);
+    }
+}
+
+/**
+ * Returns the domain manager endpoint and the refresh interval given by
+ * the arguments of scars-top, none when they are not a command line of
+ * it. The interval must be a positive and finite number of seconds.
+ */
+pub fn parse_args(args: &[String]) -> Option<cli::Result<(String, Duration)>> {
+    match args {
+        [endpoint] => Some(Ok((endpoint.clone(), DEFAULT_INTERVAL))),
+        [endpoint, option, seconds] if option == "--interval" => {
+            Some(cli::parse_seconds(option, seconds).map(|interval| (endpoint.clone(), interval)))
+        }
+        _ => None,
+    }
+}
+
+/// Draws the dashboard of the domain manager at the endpoint.
+pub fn draw(frame: &mut Frame, endpoint: &str, dashboard: &Dashboard) {
+    let [devices, applications, events, footer] = Layout::vertical([
+        Constraint::Percentage(35),
+        Constraint::Percentage(35),
+        Constraint::Fill(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let header = |titles: &[&'static str]| Row::new(titles.to_vec()).style(Style::new().bold());
+
+    let mut rows = Vec::new();
+    for (identifier, label, available) in &dashboard.nodes {
+        let style = if *available {
+            Style::new()
+        } else {
+            Style::new().fg(Color::Red)
+        };
+        let availability = if *available {
+            "available"
+        } else {
+            "unavailable"
+        };
+        rows.push(
+            Row::new(vec![label.clone(), String::new(), availability.to_string()])
+                .style(style.bold()),
+        );
+        for device in dashboard.devices.iter().filter(|d| &d.node == identifier) {
+            let (states, style) = match &device.status {
+                Some(status) => (
+                    format!(
+                        "{} {} {}",
+                        status.admin_state().as_str_name(),
+                        status.operational_state().as_str_name(),
+                        status.usage_state().as_str_name()
+                    ),
+                    Style::new(),
+                ),
+                None => ("not answering".to_string(), Style::new().fg(Color::Yellow)),
+            };
+            rows.push(
+                Row::new(vec![
+                    format!("  {}", device.label),
+                    device.identifier.clone(),
+                    states,
+                    device.capacities.clone(),
+                ])
+                .style(style),
+            );
+        }
+    }
+    let widths = [
+        Constraint::Percentage(20),
+        Constraint::Percentage(25),
+        Constraint::Percentage(20),
+        Constraint::Fill(1),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header(&[
+            "DEVICE MANAGER / DEVICE",
+            "IDENTIFIER",
+            "STATE",
+            "CAPACITIES",
+        ]))
+        .block(Block::bordered().title(" Devices "));
+    frame.render_widget(table, devices);
+
+    let mut rows = Vec::new();
+    for application in &dashboard.applications {
+        let state = if application.info.started {
+            "started"
+        } else {
+            "stopped"
+        };
+        let (cpu_usage, memory) = application
+            .metrics
+            .as_ref()
+            .map(|m| (format!("{:.1}", m.cpu_usage), m.memory.to_string()))
+            .unwrap_or_default();
+        rows.push(
+            Row::new(vec![
+                application.info.name.clone(),
+                application.info.identifier.clone(),
+                state.to_string(),
+                cpu_usage,
+                memory,
+            ])
+            .style(Style::new().bold()),
+        );
+        for component in application.metrics.iter().flat_map(|m| &m.components) {
+            let status = component.status();
+            let style = match status {
+                ProcessStatus::Running | ProcessStatus::Sleeping => Style::new().fg(Color::Green),
+                ProcessStatus::Stopped | ProcessStatus::Unknown => Style::new().fg(Color::Yellow),
+                ProcessStatus::Zombie | ProcessStatus::Terminated => Style::new().fg(Color::Red),
+            };
+            rows.push(
+                Row::new(vec![
+                    format!("  {}", component.component_id),
+                    component.device_id.clone(),
+                    status.as_str_name().to_string(),
+                    format!("{:.1}", component.cpu_usage),
+                    component.memory.to_string(),
+                ])
+                .style(style),
+            );
+        }
+    }
+    let widths = [
+        Constraint::Percentage(25),
+        Constraint::Percentage(35),
+        Constraint::Percentage(14),
+        Constraint::Percentage(10),
+        Constraint::Fill(1),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header(&[
+            "APPLICATION / COMPONENT",
+            "IDENTIFIER / DEVICE",
+            "STATE",
+            "CPU %",
+            "MEMORY",
+        ]))
+        .block(Block::bordered().title(" Applications "));
+    frame.render_widget(table, applications);
+
+    let items: Vec<ListItem> = dashboard
+        .events
+        .iter()
+        .take(events.height as usize)
+        .map(|e| ListItem::new(e.as_str()))
+        .collect();
+    frame.render_widget(
+        List::new(items).block(Block::bordered().title(" Events ")),
+        events,
+    );
+
+    let status = match (&dashboard.error, dashboard.refreshed) {
+        (Some(error), _) => error.clone().red(),
+        (None, Some(refreshed)) => format!("refreshed at {}", clock(refreshed)).into(),
+        (None, None) => "connecting...".into(),
+    };
+    let line = Line::from(vec![
+        format!(" {endpoint}  ").bold(),
+        status,
+        "  q: quit".dim(),
+    ]);
+    frame.render_widget(Paragraph::new(line), footer);
+}
+
+/// Returns the UTC time of day of a time, as HH:MM:SS.
+pub fn clock(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        % 86400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn object_line(object: &DomainObject) -> String {
+    let category = SourceCategoryType::try_from(object.source_category)
+        .map(|c| c.as_str_name())
+        .unwrap_or("?");
+    format!("{category} {} ({})", object.source_name, object.source_id)
+}
+
+fn state_name(state: i32) -> &'static str {
+    StateChangeType::try_from(state)
+        .map(|s| s.as_str_name())
+        .unwrap_or("?")
+}
+
+/// Returns the line shown for an event of the ODM channel.
+pub fn odm_line(event: &DomainManagementEvent) -> String {
+    match &event.event {
+        Some(OdmEvent::ObjectAdded(object)) => format!("ObjectAdded {}", object_line(object)),
+        Some(OdmEvent::ObjectRemoved(object)) => format!("ObjectRemoved {}", object_line(object)),
+        Some(OdmEvent::AllocationFailed(failed)) => format!(
+            "AllocationFailed {} on {} ({})",
+            failed.allocation_id, failed.allocated_device, failed.source_id
+        ),
+        Some(OdmEvent::AdministrativeStateChanged(changed)) => format!(
+            "AdministrativeStateChanged {} {} -> {}",
+            changed.source_id,
+            state_name(changed.state_change_from),
+            state_name(changed.state_change_to)
+        ),
+        Some(OdmEvent::AvailabilityChanged(changed)) => format!(
+            "AvailabilityChanged {} {}",
+            changed.object.as_ref().map(object_line).unwrap_or_default(),
+            if changed.available {
+                "available"
+            } else {
+                "unavailable"
+            }
+        ),
+        None => "unknown event".to_string(),
+    }
+}
+
+/// Returns the line shown for an event of the IDM channel.
+pub fn idm_line(event: &StateChangeEvent) -> String {
+    let category = StateChangeCategoryType::try_from(event.state_change_category)
+        .map(|c| c.as_str_name())
+        .unwrap_or("?");
+    format!(
+        "{category} {} {} -> {}",
+        event.source_id,
+        state_name(event.state_change_from),
+        state_name(event.state_change_to)
+    )
+}
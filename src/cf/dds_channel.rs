@@ -0,0 +1,289 @@
+use std::sync::{Arc, Mutex};
+
+use rustdds::no_key::{DataReader, DataWriter};
+use rustdds::policy::{Durability, History};
+use rustdds::{
+    CDRDeserializerAdapter, CDRSerializerAdapter, DomainParticipant, QosPolicies, QosPolicyBuilder,
+    Subscriber, Topic, TopicKind,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
+use tokio::task::JoinHandle;
+
+use super::events::{EventChannel, EventChannelTrait, EventStream, DEFAULT_QUEUE_SIZE};
+
+/**
+ * Convienence enum definition that includes all DDS channel errors.
+ */
+#[derive(Error, Debug)]
+pub enum DdsError {
+    /**
+     * This exception indicates the DDS binding failed to write or read a
+     * topic.
+     */
+    #[error("TopicError: topic: '{topic}', message: '{message}'.")]
+    TopicError { topic: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all DDS channel returned errors.
+ */
+pub type Result<T, E = DdsError> = anyhow::Result<T, E>;
+
+/**
+ * This interface defines a DDS topic as provided by a DDS binding: the
+ * data writer and data readers of the samples of an event type, the
+ * binding encoding the samples after the type registered for the topic,
+ * e.g. in CDR through their serde implementation. RtpsTopic speaks RTPS
+ * to the other participants of a DDS domain, e.g. Cyclone DDS or Connext
+ * ones, and LoopbackTopic shares the samples in-process.
+ */
+pub trait DdsTopicTrait<T>: Send + Sync {
+    /// Returns the name of the topic in the DDS domain.
+    fn topic_name(&self) -> &str;
+
+    /// This operation writes a sample to the topic.
+    fn write(&self, sample: &T) -> Result<()>;
+
+    /**
+     * This operation returns a stream of the samples written to the
+     * topic from now on, by the local writer and by the other
+     * participants of the DDS domain, until the stream is dropped.
+     */
+    fn read(&self) -> Result<EventStream<T>>;
+}
+
+/**
+ * Convienence type definition to share a DDS topic.
+ */
+pub type DdsTopicRef<T> = Arc<dyn DdsTopicTrait<T>>;
+
+/**
+ * DDS topic of the samples written and read in the same process, for
+ * the channels used without a DDS domain, e.g. in tests.
+ */
+#[derive(Clone)]
+pub struct LoopbackTopic<T> {
+    topic_name: String,
+    sender: broadcast::Sender<T>,
+}
+
+impl<T: Clone + Send + 'static> LoopbackTopic<T> {
+    pub fn new(topic_name: &str) -> LoopbackTopic<T> {
+        LoopbackTopic {
+            topic_name: topic_name.to_string(),
+            sender: broadcast::channel(DEFAULT_QUEUE_SIZE).0,
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> DdsTopicTrait<T> for LoopbackTopic<T> {
+    fn topic_name(&self) -> &str {
+        &self.topic_name
+    }
+
+    fn write(&self, sample: &T) -> Result<()> {
+        let _ = self.sender.send(sample.clone());
+        Ok(())
+    }
+
+    fn read(&self) -> Result<EventStream<T>> {
+        let samples = BroadcastStream::new(self.sender.subscribe());
+        Ok(Box::pin(samples.filter_map(|sample| sample.ok())))
+    }
+}
+
+/**
+ * DDS topic of a participant of a DDS domain, its samples exchanged
+ * over RTPS with the other participants, e.g. Cyclone DDS, OpenDDS or
+ * Connext ones, encoded in CDR through their serde implementation. The
+ * samples are written reliably, the last DEFAULT_QUEUE_SIZE ones being
+ * kept for the readers lagging behind, and each read creates a data
+ * reader of its own, dropped with its stream. The topic is created
+ * unkeyed, with the type name the other participants register for it.
+ */
+pub struct RtpsTopic<T: Serialize> {
+    topic_name: String,
+    topic: Topic,
+    subscriber: Subscriber,
+    writer: Mutex<DataWriter<T, CDRSerializerAdapter<T>>>,
+}
+
+impl<T: Serialize + DeserializeOwned + Clone + Send + 'static> RtpsTopic<T> {
+    /**
+     * Returns the topic of a participant, e.g. of the domain 0 with
+     * DomainParticipant::new(0), creating its writer.
+     */
+    pub fn new(
+        participant: &DomainParticipant,
+        topic_name: &str,
+        type_name: &str,
+    ) -> Result<RtpsTopic<T>> {
+        let error = |message: String| DdsError::TopicError {
+            topic: topic_name.to_string(),
+            message,
+        };
+        let qos = RtpsTopic::<T>::qos();
+        let topic = participant
+            .create_topic(
+                topic_name.to_string(),
+                type_name.to_string(),
+                &qos,
+                TopicKind::NoKey,
+            )
+            .map_err(|e| error(e.to_string()))?;
+        let publisher = participant
+            .create_publisher(&qos)
+            .map_err(|e| error(e.to_string()))?;
+        let subscriber = participant
+            .create_subscriber(&qos)
+            .map_err(|e| error(e.to_string()))?;
+        let writer = publisher
+            .create_datawriter_no_key(&topic, None)
+            .map_err(|e| error(e.to_string()))?;
+        Ok(RtpsTopic {
+            topic_name: topic_name.to_string(),
+            topic,
+            subscriber,
+            writer: Mutex::new(writer),
+        })
+    }
+
+    fn qos() -> QosPolicies {
+        QosPolicyBuilder::new()
+            .reliable(rustdds::Duration::from_millis(100))
+            .durability(Durability::Volatile)
+            .history(History::KeepLast {
+                depth: DEFAULT_QUEUE_SIZE as i32,
+            })
+            .build()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Clone + Send + 'static> DdsTopicTrait<T> for RtpsTopic<T> {
+    fn topic_name(&self) -> &str {
+        &self.topic_name
+    }
+
+    fn write(&self, sample: &T) -> Result<()> {
+        self.writer
+            .lock()
+            .unwrap()
+            .write(sample.clone(), None)
+            .map_err(|e| DdsError::TopicError {
+                topic: self.topic_name.clone(),
+                message: e.to_string(),
+            })
+    }
+
+    /// The samples failing to be decoded are dropped.
+    fn read(&self) -> Result<EventStream<T>> {
+        let reader: DataReader<T, CDRDeserializerAdapter<T>> = self
+            .subscriber
+            .create_datareader_no_key(&self.topic, None)
+            .map_err(|e| DdsError::TopicError {
+                topic: self.topic_name.clone(),
+                message: e.to_string(),
+            })?;
+        let samples = reader.async_sample_stream();
+        Ok(Box::pin(samples.filter_map(|sample| {
+            sample.ok().map(|sample| sample.into_value())
+        })))
+    }
+}
+
+/**
+ * Event channel sharing its events on a DDS topic with the other
+ * participants of the DDS domain, e.g. mission systems publishing and
+ * subscribing to standard topics, the channel being named after the
+ * topic. DDS giving no domain wide order, the events are numbered from
+ * 1 in the order each subscriber receives them, and the subscribers
+ * attaching with a sequence number receive the events from now on.
+ * Cloned channels share the same topic, the other participants being
+ * reached through the topic given, e.g. an RtpsTopic.
+ */
+#[derive(Clone)]
+pub struct DdsEventChannel<T> {
+    topic: DdsTopicRef<T>,
+    /// The number of the samples the write of failed.
+    failed_writes: Arc<Mutex<u64>>,
+}
+
+impl<T> std::fmt::Debug for DdsEventChannel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DdsEventChannel")
+            .field("topic", &self.topic.topic_name())
+            .field("failed_writes", &*self.failed_writes.lock().unwrap())
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> DdsEventChannel<T> {
+    pub fn new(topic: DdsTopicRef<T>) -> DdsEventChannel<T> {
+        DdsEventChannel {
+            topic,
+            failed_writes: Arc::default(),
+        }
+    }
+
+    /// Writes an event to the topic, returning the error of the binding.
+    pub fn publish(&self, event: &T) -> Result<()> {
+        self.topic.write(event).inspect_err(|_| {
+            *self.failed_writes.lock().unwrap() += 1;
+        })
+    }
+
+    /// Returns the number of the events pushed the write of failed.
+    pub fn failed_writes(&self) -> u64 {
+        *self.failed_writes.lock().unwrap()
+    }
+
+    /**
+     * Pushes the events read from the topic on a channel of the process,
+     * e.g. the one a BulkIO provides port receives from for a connection
+     * over DDS, in a task of the runtime ending with the topic stream.
+     */
+    pub fn forward(&self, channel: &EventChannel<T>) -> JoinHandle<()>
+    where
+        T: Clone,
+    {
+        let mut events = self.subscribe();
+        let channel = channel.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                channel.push(event);
+            }
+        })
+    }
+}
+
+impl<T: Send + 'static> EventChannelTrait<T> for DdsEventChannel<T> {
+    fn name(&self) -> &str {
+        self.topic.topic_name()
+    }
+
+    /// The events failing to be written are counted, and dropped.
+    fn push(&self, event: T) {
+        let _ = self.publish(&event);
+    }
+
+    /// The stream of a topic failing to be read is empty.
+    fn subscribe(&self) -> EventStream<T> {
+        self.topic
+            .read()
+            .unwrap_or_else(|_| Box::pin(tokio_stream::empty()))
+    }
+
+    fn subscribe_from(&self, _last_seen: u64) -> EventStream<(u64, T)> {
+        let mut sequence = 0;
+        Box::pin(self.subscribe().map(move |event| {
+            sequence += 1;
+            (sequence, event)
+        }))
+    }
+}
@@ -0,0 +1,557 @@
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::events::{EventChannel, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+use super::log::{LogLevelType, LogProducer, Logger};
+use super::property_store::{PropertyReader, PropertyStore};
+
+/**
+ * This type defines the administrative states of a device.
+ * LOCKED: the device is not permitted to be used.
+ * SHUTTING_DOWN: the device has been commanded to be locked but is
+ * still in use, so it only completes the outstanding requests.
+ * UNLOCKED: the device is permitted to be used.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminType {
+    LOCKED,
+    SHUTTING_DOWN,
+    UNLOCKED,
+}
+
+/**
+ * This type defines the operational states of a device.
+ * ENABLED: the device is functioning.
+ * DISABLED: the device is not functioning.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationalType {
+    ENABLED,
+    DISABLED,
+}
+
+/**
+ * This type defines the usage states of a device.
+ * IDLE: not in use.
+ * ACTIVE: in use, with capacity remaining for allocation.
+ * BUSY: in use, with no capacity remaining for allocation.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageType {
+    IDLE,
+    ACTIVE,
+    BUSY,
+}
+
+/**
+ * Convienence enum definition that includes all DeviceTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum DeviceError {
+    /**
+     * This exception indicates that the device is not capable of the
+     * behavior being attempted due to its current state.
+     */
+    #[error("InvalidState: msg: '{message}'.")]
+    InvalidState { message: String },
+    /**
+     * This exception indicates the capacities are invalid: unknown ids
+     * or values of the wrong data type. The list contains the invalid
+     * capacities.
+     */
+    #[error("InvalidCapacity: msg: '{message}', capacities: {capacities:?}.")]
+    InvalidCapacity {
+        message: String,
+        capacities: Properties,
+    },
+}
+
+/*
+ * Convienence type definition that includes all DeviceTrait returned errors.
+ */
+pub type Result<T, E = DeviceError> = anyhow::Result<T, E>;
+
+/**
+ * This interface defines the common attributes of a logical device
+ * (an abstraction of an underlying hardware element) together with
+ * the state model every DeviceComponent shall implement.
+ */
+pub trait DeviceTrait {
+    /// The readonly identifier attribute contains the unique identifier of the device.
+    fn identifier(&self) -> &str;
+
+    /// The readonly label attribute contains the device's label as given in the DCD.
+    fn label(&self) -> &str;
+
+    /// The readonly compositeDevice attribute contains the identifier of the parent device, if any.
+    fn composite_device(&self) -> Option<&str>;
+
+    /// The readonly usageState attribute contains the device's usage state.
+    fn usage_state(&self) -> UsageType;
+
+    /// The adminState attribute contains the device's admin state.
+    fn admin_state(&self) -> AdminType;
+
+    /// This operation commands the device to the requested admin state.
+    fn set_admin_state(&mut self, admin_state: AdminType);
+
+    /// The readonly operationalState attribute contains the device's operational state.
+    fn operational_state(&self) -> OperationalType;
+
+    /// The readonly allocation properties describe the device and its available capacities.
+    fn allocation_properties(&self) -> Properties;
+
+    /// This operation allocates capacities from the device, returning false when not available.
+    fn allocate_capacity(&mut self, capacities: &Properties) -> Result<bool>;
+
+    /// This operation returns previously allocated capacities to the device.
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> Result<()>;
+
+    /**
+     * Returns a reader of the snapshots of the allocation properties, to
+     * query them without locking the device, when the device publishes them.
+     */
+    fn allocation_property_reader(&self) -> Option<PropertyReader> {
+        None
+    }
+}
+
+/**
+ * Shared reference to a device, as held by its aggregate and managers.
+ */
+pub type DeviceRef = Arc<Mutex<dyn DeviceTrait + Send>>;
+
+/**
+ * Capacity tracked by a device, with its whole and available amounts.
+ */
+#[derive(Debug, Clone)]
+struct Capacity {
+    id: String,
+    total: AnyValue,
+    available: AnyValue,
+}
+
+/**
+ * Basic implementation of the device state model, meant to be embedded
+ * by concrete devices that delegate their DeviceTrait to it. The device
+ * keeps track of its numeric capacities, driving the usageState from
+ * the allocations made: IDLE while nothing is allocated, BUSY once any
+ * capacity is exhausted and ACTIVE in between. The logger of the device
+ * is configured by the LOG_LEVEL and LOGGING_CONFIG_URI execparams. The
+ * allocation properties are published as snapshots on each change, to
+ * be queried without contending with the allocations.
+ */
+#[derive(Debug)]
+pub struct Device {
+    identifier: String,
+    label: String,
+    composite_device: Option<String>,
+    admin_state: AdminType,
+    operational_state: OperationalType,
+    usage_state: UsageType,
+    properties: Properties,
+    capacities: Vec<Capacity>,
+    /// The snapshots of the allocation properties.
+    allocation_properties: PropertyStore,
+    event_channel: Option<EventChannel<StateChangeEvent>>,
+    logger: Logger,
+}
+
+impl Device {
+    pub fn new(identifier: &str, label: &str) -> Device {
+        Device {
+            identifier: identifier.to_string(),
+            label: label.to_string(),
+            composite_device: None,
+            admin_state: AdminType::UNLOCKED,
+            operational_state: OperationalType::ENABLED,
+            usage_state: UsageType::IDLE,
+            properties: Properties::new(),
+            capacities: Vec::new(),
+            allocation_properties: PropertyStore::default(),
+            event_channel: None,
+            logger: Logger::new(identifier, label),
+        }
+    }
+
+    /// Returns the logger of the device, its clones sharing the configuration.
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Sets the channel the device state changes are published onto.
+    pub fn with_event_channel(mut self, event_channel: EventChannel<StateChangeEvent>) -> Device {
+        self.event_channel = Some(event_channel);
+        self
+    }
+
+    /**
+     * Adds a descriptive allocation property (e.g. processor name) that
+     * allocation requests are matched against.
+     */
+    pub fn with_allocation_property(mut self, id: &str, value: AnyValue) -> Device {
+        self.properties.push(DataType::new(id, value));
+        self.publish_allocation_properties();
+        self
+    }
+
+    /**
+     * Adds a numeric capacity, consumed by allocateCapacity requests with
+     * the same id and a value of the same type.
+     */
+    pub fn with_capacity(mut self, id: &str, value: AnyValue) -> Device {
+        self.capacities.push(Capacity {
+            id: id.to_string(),
+            total: value.clone(),
+            available: value,
+        });
+        self.publish_allocation_properties();
+        self
+    }
+
+    /// Publishes the snapshot of the descriptive properties followed by the available capacities.
+    fn publish_allocation_properties(&mut self) {
+        let properties = self
+            .properties
+            .iter()
+            .cloned()
+            .chain(
+                self.capacities
+                    .iter()
+                    .map(|c| DataType::new(&c.id, c.available.clone())),
+            )
+            .collect();
+        self.allocation_properties.store(properties);
+    }
+
+    /// Returns the available amount of a capacity.
+    pub fn available_capacity(&self, id: &str) -> Option<&AnyValue> {
+        self.capacities.iter().find(|c| c.id == id).map(|c| &c.available)
+    }
+
+    /**
+     * Returns the amounts currently allocated from the capacities, leaving
+     * out the capacities with nothing allocated.
+     */
+    pub fn allocated_capacities(&self) -> Properties {
+        self.capacities
+            .iter()
+            .filter_map(|c| {
+                let allocated = c.total.checked_sub(&c.available)?;
+                (allocated.as_f64() != Some(0.0)).then(|| DataType::new(&c.id, allocated))
+            })
+            .collect()
+    }
+
+    /**
+     * Sets the parent device this device is part of, as given by the
+     * COMPOSITE_DEVICE_IOR execparam.
+     */
+    pub fn with_composite_device(mut self, composite_device: &str) -> Device {
+        self.composite_device = Some(composite_device.to_string());
+        self
+    }
+
+    /// This operation updates the operational state as reported by the underlying hardware.
+    pub fn set_operational_state(&mut self, operational_state: OperationalType) {
+        let from = std::mem::replace(&mut self.operational_state, operational_state);
+        self.publish(StateChangeCategoryType::OPERATIONAL_STATE_EVENT, from, operational_state);
+    }
+
+    /**
+     * This operation updates the usage state as computed by the device
+     * capacity model. A device waiting in SHUTTING_DOWN becomes LOCKED
+     * as soon as it returns IDLE.
+     */
+    pub fn set_usage_state(&mut self, usage_state: UsageType) {
+        let from = std::mem::replace(&mut self.usage_state, usage_state);
+        self.publish(StateChangeCategoryType::USAGE_STATE_EVENT, from, usage_state);
+
+        if usage_state == UsageType::IDLE && self.admin_state == AdminType::SHUTTING_DOWN {
+            self.admin_state = AdminType::LOCKED;
+            self.publish(
+                StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT,
+                AdminType::SHUTTING_DOWN,
+                AdminType::LOCKED,
+            );
+        }
+    }
+
+    /**
+     * SCA247
+     * The Device Component shall send a StateChangeEventType event to the Incoming
+     * Domain Management event channel, whenever the adminState attribute changes.
+     * SCA249
+     * The Device Component shall send a StateChangeEventType event to the Incoming
+     * Domain Management event channel, whenever the usageState attribute changes.
+     * For these events the producerId and sourceId fields are the identifier
+     * attribute of the Device Component.
+     */
+    fn publish<S: Into<StateChangeType>>(&self, category: StateChangeCategoryType, from: S, to: S) {
+        let (from, to) = (from.into(), to.into());
+        if let (Some(channel), true) = (&self.event_channel, from != to) {
+            channel.push(StateChangeEvent {
+                producer_id: self.identifier.clone(),
+                source_id: self.identifier.clone(),
+                state_change_category: category,
+                state_change_from: from,
+                state_change_to: to,
+            });
+        }
+    }
+
+    /**
+     * SCA256
+     * The allocateCapacity operation shall raise the CF::InvalidState exception when
+     * the DeviceComponent's adminState is not UNLOCKED.
+     * SCA511
+     * The allocateCapacity operation shall raise the CF::InvalidState exception when
+     * the DeviceComponent's operationalState is DISABLED.
+     */
+    pub fn check_allocate_state(&self) -> Result<()> {
+        if self.admin_state != AdminType::UNLOCKED {
+            return Err(DeviceError::InvalidState {
+                message: format!("adminState is {:?}", self.admin_state),
+            });
+        }
+        if self.operational_state == OperationalType::DISABLED {
+            return Err(DeviceError::InvalidState {
+                message: "operationalState is DISABLED".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /**
+     * SCA262
+     * The deallocateCapacity operation shall raise the CF::InvalidState exception, when
+     * the DeviceComponent's adminState is LOCKED.
+     * SCA516
+     * The deallocateCapacity operation shall raise the CF::InvalidState exception, when
+     * the DeviceComponent's operationalState is DISABLED.
+     */
+    pub fn check_deallocate_state(&self) -> Result<()> {
+        if self.admin_state == AdminType::LOCKED {
+            return Err(DeviceError::InvalidState {
+                message: "adminState is LOCKED".to_string(),
+            });
+        }
+        if self.operational_state == OperationalType::DISABLED {
+            return Err(DeviceError::InvalidState {
+                message: "operationalState is DISABLED".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the capacities with unknown ids or values of the wrong
+     * data type (SCA255, SCA261).
+     */
+    fn invalid_capacities(&self, capacities: &Properties) -> Properties {
+        capacities
+            .iter()
+            .filter(|c| {
+                !self.capacities.iter().any(|own| {
+                    own.id == c.id
+                        && own.total.same_type(&c.value)
+                        && c.value.as_f64().is_some_and(|v| v >= 0.0)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /**
+     * SCA251
+     * The allocateCapacity operation shall set the device's usageState attribute to
+     * BUSY, when the device determines that it is not possible to allocate any further
+     * capacity.
+     * SCA252
+     * The allocateCapacity operation shall set the usageState attribute to ACTIVE, when
+     * capacity is being used and any capacity is still available for allocation.
+     * SCA258
+     * The deallocateCapacity operation shall set the usageState attribute to ACTIVE
+     * when, after adjusting capacities, any of the device's capacities are still being
+     * used.
+     * SCA259
+     * The deallocateCapacity operation shall set the usageState attribute to IDLE when,
+     * after adjusting capacities, none of the device's capacities are still being used.
+     */
+    fn update_usage_state(&mut self) {
+        let usage_state = if self.capacities.iter().all(|c| c.available == c.total) {
+            UsageType::IDLE
+        } else if self
+            .capacities
+            .iter()
+            .any(|c| c.available.as_f64() == Some(0.0) && c.total.as_f64() != Some(0.0))
+        {
+            UsageType::BUSY
+        } else {
+            UsageType::ACTIVE
+        };
+        self.set_usage_state(usage_state);
+    }
+}
+
+impl DeviceTrait for Device {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    /**
+     * SCA266
+     * The readonly compositeDevice attribute shall return the object reference of the
+     * AggregateDeviceComponent.
+     */
+    fn composite_device(&self) -> Option<&str> {
+        self.composite_device.as_deref()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.usage_state
+    }
+
+    /**
+     * SCA243
+     * The adminState attribute shall return the device's admin state value.
+     */
+    fn admin_state(&self) -> AdminType {
+        self.admin_state
+    }
+
+    /**
+     * SCA245
+     * The adminState attribute, upon being commanded to be LOCKED, shall set the
+     * adminState to LOCKED for its entire aggregation of Device Components (if it
+     * has any). The adminState transitions to the LOCKED state when the device's
+     * usageState is IDLE, otherwise it remains SHUTTING_DOWN until then.
+     */
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        let to = match admin_state {
+            AdminType::UNLOCKED => AdminType::UNLOCKED,
+            _ if self.admin_state == AdminType::LOCKED => AdminType::LOCKED,
+            _ if self.usage_state == UsageType::IDLE => AdminType::LOCKED,
+            _ => AdminType::SHUTTING_DOWN,
+        };
+        let from = std::mem::replace(&mut self.admin_state, to);
+        self.publish(StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT, from, to);
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.operational_state
+    }
+
+    /**
+     * The allocation properties of the device are its descriptive
+     * properties followed by its available capacities.
+     */
+    fn allocation_properties(&self) -> Properties {
+        Properties::clone(&self.allocation_properties.load())
+    }
+
+    fn allocation_property_reader(&self) -> Option<PropertyReader> {
+        Some(self.allocation_properties.reader())
+    }
+
+    /**
+     * SCA250
+     * The allocateCapacity operation shall reduce the current capacities of the device
+     * based upon the input capacities parameter, when usageState attribute is not BUSY.
+     * SCA254
+     * The allocateCapacity operation shall return TRUE, if the capacities have been
+     * allocated, or FALSE, if not allocated.
+     * SCA255
+     * The allocateCapacity operation shall raise the InvalidCapacity exception, when
+     * the input capacities parameter contains invalid properties or when attributes of
+     * those CF::Properties contain an unknown id or a value of the wrong data type.
+     */
+    fn allocate_capacity(&mut self, capacities: &Properties) -> Result<bool> {
+        //verify device state and request
+        self.check_allocate_state()?;
+        let invalid = self.invalid_capacities(capacities);
+        if !invalid.is_empty() {
+            return Err(DeviceError::InvalidCapacity {
+                message: "unknown capacity id or wrong data type".to_string(),
+                capacities: invalid,
+            });
+        }
+        if self.usage_state == UsageType::BUSY {
+            return Ok(false);
+        }
+
+        //reduce capacities, leaving them untouched when not available
+        let mut reduced = self.capacities.clone();
+        for c in capacities {
+            let own = reduced.iter_mut().find(|own| own.id == c.id).unwrap();
+            match own.available.checked_sub(&c.value) {
+                Some(available) => own.available = available,
+                None => return Ok(false),
+            }
+        }
+        self.capacities = reduced;
+        self.publish_allocation_properties();
+        self.update_usage_state();
+
+        Ok(true)
+    }
+
+    /**
+     * SCA257
+     * The deallocateCapacity operation shall increment the current capacities of the
+     * device based upon the input capacities parameter.
+     * SCA261
+     * The deallocateCapacity operation shall raise the InvalidCapacity exception, when
+     * the capacity ID is invalid or the capacity value is the wrong type.
+     */
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> Result<()> {
+        //verify device state and request
+        self.check_deallocate_state()?;
+        let invalid = self.invalid_capacities(capacities);
+        if !invalid.is_empty() {
+            return Err(DeviceError::InvalidCapacity {
+                message: "unknown capacity id or wrong data type".to_string(),
+                capacities: invalid,
+            });
+        }
+
+        //increment capacities, never beyond the whole ones
+        for c in capacities {
+            let own = self.capacities.iter_mut().find(|own| own.id == c.id).unwrap();
+            own.available = match own.available.checked_add(&c.value) {
+                Some(available) if available <= own.total => available,
+                _ => own.total.clone(),
+            };
+        }
+        self.publish_allocation_properties();
+        self.update_usage_state();
+
+        Ok(())
+    }
+}
+
+impl LogProducer for Device {
+    fn log_level(&self) -> LogLevelType {
+        self.logger.log_level()
+    }
+
+    fn set_log_level(&mut self, log_level: LogLevelType) {
+        self.logger.set_log_level(log_level);
+    }
+
+    fn log_uri(&self) -> Option<String> {
+        self.logger.log_uri()
+    }
+
+    fn set_log_uri(&mut self, log_uri: Option<&str>) {
+        self.logger.set_log_uri(log_uri);
+    }
+}
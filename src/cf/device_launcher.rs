@@ -0,0 +1,104 @@
+use tokio::net::TcpListener;
+use std::sync::Arc;
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::Notify;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::Status;
+
+use scars::cf::cli::{self, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION};
+use scars::cf::device_service::DeviceService;
+use scars::cf::launcher::{instantiate_device, ExecParams};
+use scars::cf::retry::RetryPolicy;
+use scars::cf::rpc::device::device_server::DeviceServer;
+use scars::cf::rpc::device_manager::device_manager_client::DeviceManagerClient;
+use scars::cf::rpc::device_manager::{RegisterDeviceRequest, UnregisterDeviceRequest};
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-device-launcher",
+    options: &[FORMAT_OPTION, COMPLETIONS_OPTION],
+    commands: &[],
+};
+
+/**
+ * Standard device launcher: instantiates the device implementation
+ * selected by the PROFILE_NAME execparam, serves it as a Device gRPC
+ * service, its loads and executes included when it is executable, and
+ * registers it with the DeviceManager at DEVICE_MGR_IOR, retrying with
+ * backoff while unreachable, unregistering it on termination. The
+ * address the device listens on is printed once registered.
+ *
+ * usage: scars-device-launcher [--format text|json] <execparam id> <value>...
+ *        scars-device-launcher --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    let params = ExecParams::parse(args)?;
+    let launched = instantiate_device(&params)?;
+
+    //serve the device on an ephemeral port
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let endpoint = format!("http://{address}");
+    let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|e| e.to_string())?;
+    let release = Arc::new(Notify::new());
+    let mut service = DeviceService::new(launched.device).with_release(release.clone());
+    if let Some(executable) = launched.executable {
+        //the files loaded by the domain are staged apart from the device cache
+        let staging_dir = std::env::temp_dir()
+            .join("scars")
+            .join("staging")
+            .join(&params.device_label);
+        service = service.with_executable(executable, &staging_dir);
+    }
+    let server = tokio::spawn(
+        Server::builder()
+            .add_service(DeviceServer::new(service))
+            .serve_with_incoming_shutdown(incoming, terminated(release)),
+    );
+
+    //register with the device manager, retrying while it or its domain is unreachable
+    let request = &RegisterDeviceRequest {
+        identifier: params.device_id.clone(),
+        label: params.device_label.clone(),
+        profile_name: params.profile_name.clone(),
+        endpoint,
+    };
+    let device_mgr = &params.device_mgr;
+    let mut device_manager = RetryPolicy::default()
+        .retry(|| async move {
+            let mut device_manager = DeviceManagerClient::connect(device_mgr.clone())
+                .await
+                .map_err(|e| Status::unavailable(e.to_string()))?;
+            device_manager.register_device(request.clone()).await?;
+            Ok(device_manager)
+        })
+        .await?;
+    cli::print_address(format, "Device", address);
+
+    server.await??;
+
+    device_manager
+        .unregister_device(UnregisterDeviceRequest {
+            identifier: params.device_id,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Resolves once the launcher is asked to terminate (SIGTERM, SIGINT or releaseObject).
+async fn terminated(release: Arc<Notify>) {
+    let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+        _ = release.notified() => {}
+    }
+}
@@ -0,0 +1,562 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Response, Status};
+
+use super::blocking_pool::BlockingPool;
+use super::common_types::AnyValue;
+use super::file_system::FileSystem;
+use super::file_system_service::FileSystemService;
+use super::log::{log_file, log_file_name, LOGGING_CONFIG_URI_ID};
+use super::profile::dcd::DeviceConfiguration;
+use super::profile::ComponentInstantiation;
+use super::launcher::{
+    implementation_name, COMPOSITE_DEVICE_IOR, DEVICE_ID, DEVICE_LABEL, DEVICE_MGR_IOR,
+    PROFILE_NAME,
+};
+use super::rpc::device::device_client::DeviceClient;
+use super::rpc::device::ReleaseObjectRequest;
+use super::rpc::device_manager::device_manager_server::{self, DeviceManagerServer};
+use super::rpc::file_system::file_system_server::FileSystemServer;
+use super::retry::RetryPolicy;
+use super::rpc::device_manager::{
+    GetComponentImplementationIdReply, GetComponentImplementationIdRequest, HeartbeatReply,
+    HeartbeatRequest, RegisterDeviceReply,
+    RegisterDeviceRequest, RegisteredDevicesReply, RegisteredDevicesRequest, ShutdownReply,
+    ShutdownRequest, UnregisterDeviceReply, UnregisterDeviceRequest,
+};
+use super::rpc::domain_manager::{self, domain_manager_client::DomainManagerClient};
+use super::rpc::domain_manager::{RegisterDeviceManagerRequest, UnregisterDeviceManagerRequest};
+
+/// The time given to the released devices to terminate before being killed.
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/**
+ * Convienence enum definition that includes all DeviceManager errors.
+ */
+#[derive(Error, Debug)]
+pub enum DeviceManagerError {
+    /**
+     * This exception indicates that the device to unregister is not
+     * registered.
+     */
+    #[error("InvalidObjectReference: msg: '{message}'.")]
+    InvalidObjectReference { message: String },
+    /**
+     * This exception indicates that a device or service of the node
+     * could not be launched.
+     */
+    #[error("LaunchFailed: msg: '{message}'.")]
+    LaunchFailed { message: String },
+    /**
+     * This exception indicates that the DeviceManager could not serve or
+     * register itself with the DomainManager.
+     */
+    #[error("RegisterError: msg: '{message}'.")]
+    RegisterError { message: String },
+}
+
+/*
+ * Convienence type definition that includes all DeviceManager returned errors.
+ */
+pub type Result<T, E = DeviceManagerError> = anyhow::Result<T, E>;
+
+/**
+ * This type describes a device registered with the DeviceManager.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredDevice {
+    pub identifier: String,
+    pub label: String,
+    pub profile_name: String,
+    /// The endpoint serving the Device service of the device.
+    pub endpoint: String,
+}
+
+/**
+ * Book keeping of the node: registered devices, implementations of the
+ * launched components and their processes.
+ */
+#[derive(Debug, Default)]
+struct NodeState {
+    registered: Vec<RegisteredDevice>,
+    implementations: HashMap<String, String>,
+    processes: Vec<(String, Child)>,
+}
+
+/**
+ * DeviceManager of a node: launches the devices and services of its
+ * DCD through the device launcher, keeps track of their registrations,
+ * serves the DeviceManager gRPC service and registers the node with its
+ * DomainManager. The processes are launched and the files of the node
+ * served on the threads of its blocking pool. Clones share the same node.
+ */
+#[derive(Debug, Clone)]
+pub struct DeviceManager {
+    configuration: Arc<DeviceConfiguration>,
+    fs_root: PathBuf,
+    launcher: PathBuf,
+    domain_manager: Option<String>,
+    retry_policy: RetryPolicy,
+    blocking_pool: BlockingPool,
+    state: Arc<Mutex<NodeState>>,
+    shutdown: Arc<Notify>,
+}
+
+impl DeviceManager {
+    /**
+     * Creates the DeviceManager of the configuration, the profiles of the
+     * components being found relative to the file system root. The
+     * DomainManager is the one referenced by the DCD, if any.
+     */
+    pub fn new(configuration: DeviceConfiguration, fs_root: &Path) -> DeviceManager {
+        let launcher = std::env::current_exe()
+            .map(|exe| exe.with_file_name("scars-device-launcher"))
+            .unwrap_or_else(|_| PathBuf::from("scars-device-launcher"));
+
+        DeviceManager {
+            domain_manager: configuration.domain_manager.clone(),
+            configuration: Arc::new(configuration),
+            fs_root: fs_root.to_path_buf(),
+            launcher,
+            retry_policy: RetryPolicy::default(),
+            blocking_pool: BlockingPool::shared(),
+            state: Arc::default(),
+            shutdown: Arc::default(),
+        }
+    }
+
+    /// Sets the device launcher executable.
+    pub fn with_launcher(mut self, launcher: &Path) -> DeviceManager {
+        self.launcher = launcher.to_path_buf();
+        self
+    }
+
+    /// Sets the endpoint of the DomainManager to register with.
+    pub fn with_domain_manager(mut self, endpoint: &str) -> DeviceManager {
+        self.domain_manager = Some(endpoint.to_string());
+        self
+    }
+
+    /// Sets the policy of the registration retried while the DomainManager is unreachable.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> DeviceManager {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the pool of the threads of the services, file and process operations of the node.
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> DeviceManager {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /// Returns the pool of the threads of the file and process operations of the node.
+    pub fn blocking_pool(&self) -> BlockingPool {
+        self.blocking_pool.clone()
+    }
+
+    /// The readonly identifier attribute contains the DCD id.
+    pub fn identifier(&self) -> &str {
+        &self.configuration.id
+    }
+
+    /// The readonly label attribute contains the DCD name.
+    pub fn label(&self) -> &str {
+        &self.configuration.name
+    }
+
+    /// Returns the devices registered with the DeviceManager.
+    pub fn registered_devices(&self) -> Vec<RegisteredDevice> {
+        self.state.lock().unwrap().registered.clone()
+    }
+
+    /// Returns the implementation id a component instantiation has been launched with.
+    pub fn component_implementation_id(&self, component_instantiation_id: &str) -> Option<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .implementations
+            .get(component_instantiation_id)
+            .cloned()
+    }
+
+    /// Registers a device, replacing a previous registration with the same identifier.
+    pub fn register_device(&self, device: RegisteredDevice) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .registered
+            .retain(|d| d.identifier != device.identifier);
+        state.registered.push(device);
+    }
+
+    /// Unregisters a device.
+    pub fn unregister_device(&self, identifier: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .registered
+            .iter()
+            .position(|d| d.identifier == identifier)
+            .ok_or_else(|| DeviceManagerError::InvalidObjectReference {
+                message: format!("device '{identifier}' not registered"),
+            })?;
+        state.registered.remove(index);
+        Ok(())
+    }
+
+    /// Asks the running DeviceManager to shut the node down.
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /**
+     * Runs the node until shut down: serves the DeviceManager service,
+     * and the FileSystem service of the node, on the listener, launches
+     * the components of the DCD and registers the node with the
+     * DomainManager. On shutdown the node is unregistered and its
+     * devices are released.
+     */
+    pub async fn run(self, listener: TcpListener) -> Result<()> {
+        let register_error = |e: &dyn std::fmt::Display| DeviceManagerError::RegisterError {
+            message: e.to_string(),
+        };
+        let address = listener.local_addr().map_err(|e| register_error(&e))?;
+        let endpoint = format!("http://{address}");
+        let incoming =
+            TcpIncoming::from_listener(listener, true, None).map_err(|e| register_error(&e))?;
+
+        //the service outlives the devices, which unregister while released
+        let stopped = Arc::new(Notify::new());
+        let server = tokio::spawn({
+            let stopped = stopped.clone();
+            Server::builder()
+                .add_service(DeviceManagerServer::new(DeviceManagerService {
+                    manager: self.clone(),
+                }))
+                .add_service(FileSystemServer::new(
+                    FileSystemService::new(Arc::new(
+                        FileSystem::new(&self.fs_root).with_blocking_pool(self.blocking_pool.clone()),
+                    ))
+                    .with_blocking_pool(self.blocking_pool.clone()),
+                ))
+                .serve_with_incoming_shutdown(incoming, async move { stopped.notified().await })
+        });
+
+        //the devices register with the domain through the node
+        let mut outcome = self.register_with_domain(&endpoint).await;
+        if outcome.is_ok() {
+            outcome = self.launch_components_blocking(&endpoint).await;
+            if outcome.is_ok() {
+                self.shutdown.notified().await;
+            }
+            self.release_components().await;
+            self.unregister_from_domain().await;
+        }
+
+        stopped.notify_one();
+        let _ = server.await;
+        outcome
+    }
+
+    /// Launches the component instantiations on a thread of the blocking pool.
+    async fn launch_components_blocking(&self, endpoint: &str) -> Result<()> {
+        let (manager, endpoint) = (self.clone(), endpoint.to_string());
+        self.blocking_pool
+            .run(move || manager.launch_components(&endpoint))
+            .await
+            .unwrap_or_else(|e| {
+                Err(DeviceManagerError::LaunchFailed {
+                    message: e.to_string(),
+                })
+            })
+    }
+
+    /**
+     * Launches the component instantiations of the DCD, the parents of
+     * aggregate devices first.
+     */
+    fn launch_components(&self, endpoint: &str) -> Result<()> {
+        let mut placements: Vec<_> = self.configuration.placements.iter().collect();
+        placements.sort_by_key(|p| p.composite_part_of.is_some());
+
+        for placement in placements {
+            let file = self
+                .configuration
+                .component_file(placement)
+                .ok_or_else(|| DeviceManagerError::LaunchFailed {
+                    message: format!("unknown componentfile '{}'", placement.file_ref),
+                })?;
+            let profile_name = self.fs_root.join(file.local_file.trim_start_matches('/'));
+            let profile_name = profile_name.display().to_string();
+
+            for instantiation in &placement.instantiations {
+                let label = instantiation
+                    .usage_name
+                    .clone()
+                    .unwrap_or_else(|| instantiation.id.clone());
+                let mut command = Command::new(&self.launcher);
+                command
+                    .args([DEVICE_ID, &instantiation.id])
+                    .args([DEVICE_LABEL, &label])
+                    .args([DEVICE_MGR_IOR, endpoint])
+                    .args([PROFILE_NAME, &profile_name]);
+                if let Some(parent) = &placement.composite_part_of {
+                    command.args([COMPOSITE_DEVICE_IOR, parent]);
+                }
+                for p in &instantiation.properties {
+                    command.args([p.id.clone(), p.value.to_string()]);
+                }
+
+                let child = command
+                    .spawn()
+                    .map_err(|e| DeviceManagerError::LaunchFailed {
+                        message: format!("'{}': {e}", instantiation.id),
+                    })?;
+                self.register_log_file(instantiation);
+
+                let mut state = self.state.lock().unwrap();
+                state
+                    .implementations
+                    .insert(instantiation.id.clone(), implementation_name(&profile_name));
+                state.processes.push((instantiation.id.clone(), child));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Makes the log file of a component, when its LOGGING_CONFIG_URI
+     * points at one, available at its LOG_FILES_DIRECTORY pathname of the
+     * node FileSystem, to be fetched through the domain FileManager. The
+     * registration is best effort, the launch going on without it.
+     */
+    fn register_log_file(&self, instantiation: &ComponentInstantiation) {
+        let file = instantiation.properties.iter().find_map(|p| match &p.value {
+            AnyValue::String(uri) if p.id == LOGGING_CONFIG_URI_ID => log_file(uri),
+            _ => None,
+        });
+        if let Some(file) = file {
+            let file_system =
+                FileSystem::new(&self.fs_root).with_blocking_pool(self.blocking_pool.clone());
+            let _ = file_system.link(&log_file_name(&instantiation.id), &file);
+        }
+    }
+
+    /// Connects to the DomainManager, when one is set.
+    async fn domain_manager_client(&self) -> Result<Option<DomainManagerClient<Channel>>, Status> {
+        let Some(domain_manager) = &self.domain_manager else {
+            return Ok(None);
+        };
+        let client = DomainManagerClient::connect(domain_manager.clone())
+            .await
+            .map_err(|e| Status::unavailable(format!("DomainManager '{domain_manager}': {e}")))?;
+        Ok(Some(client))
+    }
+
+    /**
+     * Registers the node with the DomainManager, when one is set,
+     * retrying while the DomainManager is unreachable.
+     */
+    async fn register_with_domain(&self, endpoint: &str) -> Result<()> {
+        let Some(domain_manager) = &self.domain_manager else {
+            return Ok(());
+        };
+        let request = &RegisterDeviceManagerRequest {
+            identifier: self.identifier().to_string(),
+            label: self.label().to_string(),
+            endpoint: endpoint.to_string(),
+        };
+
+        self.retry_policy
+            .retry(|| async move {
+                let mut client = DomainManagerClient::connect(domain_manager.clone())
+                    .await
+                    .map_err(|e| Status::unavailable(e.to_string()))?;
+                client.register_device_manager(request.clone()).await
+            })
+            .await
+            .map_err(|e| DeviceManagerError::RegisterError {
+                message: format!("DomainManager '{domain_manager}': {e}"),
+            })?;
+        Ok(())
+    }
+
+    /// Unregisters the node from the DomainManager, when one is set.
+    async fn unregister_from_domain(&self) {
+        let Some(domain_manager) = &self.domain_manager else {
+            return;
+        };
+        if let Ok(mut client) = DomainManagerClient::connect(domain_manager.clone()).await {
+            let _ = client
+                .unregister_device_manager(UnregisterDeviceManagerRequest {
+                    identifier: self.identifier().to_string(),
+                })
+                .await;
+        }
+    }
+
+    /**
+     * Releases the registered devices and waits for the launched
+     * processes to terminate, killing those still running after the
+     * release timeout.
+     */
+    async fn release_components(&self) {
+        let mut released = Vec::new();
+        let deadline = tokio::time::Instant::now() + RELEASE_TIMEOUT;
+        loop {
+            //devices may still be registering while the node shuts down
+            for device in self.registered_devices() {
+                if released.contains(&device.identifier) {
+                    continue;
+                }
+                if let Ok(mut client) = DeviceClient::connect(device.endpoint).await {
+                    let _ = client.release_object(ReleaseObjectRequest {}).await;
+                }
+                released.push(device.identifier);
+            }
+
+            let done = {
+                let mut state = self.state.lock().unwrap();
+                state
+                    .processes
+                    .retain_mut(|(_, child)| matches!(child.try_wait(), Ok(None)));
+                if tokio::time::Instant::now() >= deadline {
+                    for (_, child) in &mut state.processes {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    state.processes.clear();
+                }
+                state.processes.is_empty()
+            };
+            if done {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        //killed devices could not unregister
+        self.state.lock().unwrap().registered.clear();
+    }
+}
+
+/**
+ * gRPC DeviceManager service of a node.
+ */
+struct DeviceManagerService {
+    manager: DeviceManager,
+}
+
+#[tonic::async_trait]
+impl device_manager_server::DeviceManager for DeviceManagerService {
+    /// Devices are registered with the domain before being registered with the node.
+    async fn register_device(
+        &self,
+        request: Request<RegisterDeviceRequest>,
+    ) -> Result<Response<RegisterDeviceReply>, Status> {
+        let r = request.into_inner();
+        //a hung DomainManager fails the registration, the device retrying it
+        self.manager
+            .retry_policy
+            .attempt(async {
+                if let Some(mut domain_manager) = self.manager.domain_manager_client().await? {
+                    domain_manager
+                        .register_device(domain_manager::RegisterDeviceRequest {
+                            device_manager_id: self.manager.identifier().to_string(),
+                            identifier: r.identifier.clone(),
+                            label: r.label.clone(),
+                            profile_name: r.profile_name.clone(),
+                            endpoint: r.endpoint.clone(),
+                        })
+                        .await?;
+                }
+                Ok::<_, Status>(())
+            })
+            .await?;
+
+        self.manager.register_device(RegisteredDevice {
+            identifier: r.identifier,
+            label: r.label,
+            profile_name: r.profile_name,
+            endpoint: r.endpoint,
+        });
+        Ok(Response::new(RegisterDeviceReply {}))
+    }
+
+    /// Unregistering from the domain is best effort, the domain may be gone.
+    async fn unregister_device(
+        &self,
+        request: Request<UnregisterDeviceRequest>,
+    ) -> Result<Response<UnregisterDeviceReply>, Status> {
+        let identifier = request.into_inner().identifier;
+        self.manager
+            .unregister_device(&identifier)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        if let Ok(Some(mut domain_manager)) = self.manager.domain_manager_client().await {
+            let _ = domain_manager
+                .unregister_device(domain_manager::UnregisterDeviceRequest { identifier })
+                .await;
+        }
+        Ok(Response::new(UnregisterDeviceReply {}))
+    }
+
+    async fn registered_devices(
+        &self,
+        _request: Request<RegisteredDevicesRequest>,
+    ) -> Result<Response<RegisteredDevicesReply>, Status> {
+        let devices = self
+            .manager
+            .registered_devices()
+            .into_iter()
+            .map(|d| RegisterDeviceRequest {
+                identifier: d.identifier,
+                label: d.label,
+                profile_name: d.profile_name,
+                endpoint: d.endpoint,
+            })
+            .collect();
+        Ok(Response::new(RegisteredDevicesReply { devices }))
+    }
+
+    /**
+     * The implementation id is empty for the components not launched by
+     * the DeviceManager.
+     */
+    async fn heartbeat(
+        &self,
+        _request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatReply>, Status> {
+        Ok(Response::new(HeartbeatReply {}))
+    }
+
+    async fn get_component_implementation_id(
+        &self,
+        request: Request<GetComponentImplementationIdRequest>,
+    ) -> Result<Response<GetComponentImplementationIdReply>, Status> {
+        let implementation_id = self
+            .manager
+            .component_implementation_id(&request.into_inner().component_instantiation_id)
+            .unwrap_or_default();
+        Ok(Response::new(GetComponentImplementationIdReply {
+            implementation_id,
+        }))
+    }
+
+    async fn shutdown(
+        &self,
+        _request: Request<ShutdownRequest>,
+    ) -> Result<Response<ShutdownReply>, Status> {
+        self.manager.shutdown();
+        Ok(Response::new(ShutdownReply {}))
+    }
+}
@@ -0,0 +1,341 @@
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+use tonic::{Request, Response, Status, Streaming};
+
+use super::blocking_pool::BlockingPool;
+use super::checksum;
+use super::device::DeviceRef;
+use super::executable_device::{ExecutableDeviceRef, ExecutableDeviceTrait};
+use super::property_store::PropertyReader;
+use super::rpc::device::device_server::Device;
+use super::rpc::device::{
+    AllocateCapacityReply, AllocationPropertiesReply, AllocationPropertiesRequest, CapacityRequest,
+    DeallocateCapacityReply, ExecuteReply, ExecuteRequest, LoadReply, LoadRequest,
+    ProcessMetricsReply, ProcessMetricsRequest, ReleaseObjectReply, ReleaseObjectRequest,
+    SetAdminStateReply, SetAdminStateRequest, StatusReply, StatusRequest, TerminateReply,
+    TerminateRequest, UnloadReply, UnloadRequest,
+};
+use super::rpc::{self, properties_from_wire, properties_to_wire};
+
+/// The loads staged so far by the process, each one being staged apart.
+static STAGED_LOADS: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * gRPC Device service exposing a device to the DeviceManager and the
+ * other framework components running out of the device process. The
+ * file and process operations of the executable interface are run on
+ * the threads of a blocking pool.
+ */
+pub struct DeviceService {
+    device: DeviceRef,
+    /// The snapshots of the allocation properties, when the device publishes them.
+    allocation_properties: Option<PropertyReader>,
+    /// The executable interface of the device, and the directory the files loaded are staged in.
+    executable: Option<(ExecutableDeviceRef, PathBuf)>,
+    release: Option<Arc<Notify>>,
+    blocking_pool: BlockingPool,
+}
+
+impl DeviceService {
+    pub fn new(device: DeviceRef) -> DeviceService {
+        let allocation_properties = device.lock().unwrap().allocation_property_reader();
+        DeviceService {
+            device,
+            allocation_properties,
+            executable: None,
+            release: None,
+            blocking_pool: BlockingPool::shared(),
+        }
+    }
+
+    /**
+     * Serves the loads, unloads, executes and terminates of the device
+     * through its executable interface. The files streamed by the loads
+     * are staged under the directory, and removed once loaded.
+     */
+    pub fn with_executable(
+        mut self,
+        executable: ExecutableDeviceRef,
+        staging_dir: &Path,
+    ) -> DeviceService {
+        self.executable = Some((executable, staging_dir.to_path_buf()));
+        self
+    }
+
+    /// Sets the notification raised when the device is asked to release itself.
+    pub fn with_release(mut self, release: Arc<Notify>) -> DeviceService {
+        self.release = Some(release);
+        self
+    }
+
+    /// Sets the pool of the threads of the file and process operations.
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> DeviceService {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /// Calls an operation of the executable interface on a thread of the blocking pool.
+    async fn call<R, E>(
+        &self,
+        operation: impl FnOnce(&mut dyn ExecutableDeviceTrait) -> Result<R, E> + Send + 'static,
+    ) -> Result<R, Status>
+    where
+        R: Send + 'static,
+        E: Send + 'static,
+        Status: From<E>,
+    {
+        let (executable, _) = self.executable.as_ref().ok_or_else(not_executable)?;
+        let executable = executable.clone();
+        let result = self
+            .blocking_pool
+            .run(move || operation(&mut *executable.lock().unwrap()))
+            .await?;
+        Ok(result?)
+    }
+
+    /**
+     * Writes the data of the load requests to the staged file, executable
+     * when an executable is loaded, failing with DATA_LOSS on a chunk whose
+     * CRC32C differs. The chunks are written on the threads of the
+     * blocking pool as they are received.
+     */
+    async fn stage(
+        &self,
+        path: &Path,
+        executable: bool,
+        first: LoadRequest,
+        requests: &mut Streaming<LoadRequest>,
+    ) -> Result<(), Status> {
+        let staged = path.to_path_buf();
+        let mut file = self
+            .blocking_pool
+            .run(move || create_staged(&staged, executable))
+            .await?
+            .map_err(|e| staging_error(path, e))?;
+        let mut offset = 0;
+        let mut next = Some(first);
+        while let Some(chunk) = next {
+            if chunk.crc32c.is_some_and(|crc| crc != checksum::crc32c(&chunk.data)) {
+                return Err(Status::data_loss(format!(
+                    "the chunk at offset {offset} is corrupted"
+                )));
+            }
+            offset += chunk.data.len();
+            file = self
+                .blocking_pool
+                .run(move || file.write_all(&chunk.data).map(|()| file))
+                .await?
+                .map_err(|e| staging_error(path, e))?;
+            next = requests.message().await?;
+        }
+        self.blocking_pool
+            .run(move || file.flush())
+            .await?
+            .map_err(|e| staging_error(path, e))
+    }
+}
+
+#[tonic::async_trait]
+impl Device for DeviceService {
+    async fn status(&self, _request: Request<StatusRequest>) -> Result<Response<StatusReply>, Status> {
+        let d = self.device.lock().unwrap();
+        let reply = StatusReply {
+            identifier: d.identifier().to_string(),
+            label: d.label().to_string(),
+            composite_device: d.composite_device().map(str::to_string),
+            usage_state: rpc::device::UsageType::from(d.usage_state()).into(),
+            admin_state: rpc::device::AdminType::from(d.admin_state()).into(),
+            operational_state: rpc::device::OperationalType::from(d.operational_state()).into(),
+        };
+        Ok(Response::new(reply))
+    }
+
+    async fn set_admin_state(
+        &self,
+        request: Request<SetAdminStateRequest>,
+    ) -> Result<Response<SetAdminStateReply>, Status> {
+        let admin_state = request.into_inner().admin_state();
+        self.device.lock().unwrap().set_admin_state(admin_state.into());
+        Ok(Response::new(SetAdminStateReply {}))
+    }
+
+    async fn allocation_properties(
+        &self,
+        _request: Request<AllocationPropertiesRequest>,
+    ) -> Result<Response<AllocationPropertiesReply>, Status> {
+        //the snapshots are read without contending with the allocations
+        let properties = match &self.allocation_properties {
+            Some(reader) => reader.load().to_vec(),
+            None => self.device.lock().unwrap().allocation_properties(),
+        };
+        Ok(Response::new(AllocationPropertiesReply {
+            properties: properties_to_wire(&properties),
+        }))
+    }
+
+    async fn allocate_capacity(
+        &self,
+        request: Request<CapacityRequest>,
+    ) -> Result<Response<AllocateCapacityReply>, Status> {
+        let capacities = properties_from_wire(&request.into_inner().capacities)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let allocated = self.device.lock().unwrap().allocate_capacity(&capacities)?;
+        Ok(Response::new(AllocateCapacityReply { allocated }))
+    }
+
+    async fn deallocate_capacity(
+        &self,
+        request: Request<CapacityRequest>,
+    ) -> Result<Response<DeallocateCapacityReply>, Status> {
+        let capacities = properties_from_wire(&request.into_inner().capacities)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.device.lock().unwrap().deallocate_capacity(&capacities)?;
+        Ok(Response::new(DeallocateCapacityReply {}))
+    }
+
+    /**
+     * The device process is notified to unregister the device and to
+     * terminate.
+     */
+    async fn release_object(
+        &self,
+        _request: Request<ReleaseObjectRequest>,
+    ) -> Result<Response<ReleaseObjectReply>, Status> {
+        let release = self
+            .release
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("device cannot be released"))?;
+        release.notify_one();
+        Ok(Response::new(ReleaseObjectReply {}))
+    }
+
+    /**
+     * The file streamed is staged under a directory of its own, handed
+     * to the device to load, then removed.
+     */
+    async fn load(
+        &self,
+        request: Request<Streaming<LoadRequest>>,
+    ) -> Result<Response<LoadReply>, Status> {
+        let (_, staging_dir) = self.executable.as_ref().ok_or_else(not_executable)?;
+        let mut requests = request.into_inner();
+        let first = requests
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("no file loaded"))?;
+        let (file_name, load_kind) = (first.file_name.clone(), first.load_kind());
+        if file_name.is_empty()
+            || !Path::new(&file_name)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(Status::invalid_argument(format!(
+                "'{file_name}' is not a relative file name"
+            )));
+        }
+
+        let staging = staging_dir.join(STAGED_LOADS.fetch_add(1, Ordering::Relaxed).to_string());
+        let executable_file = load_kind == rpc::device::LoadType::Executable;
+        let staged = self.stage(&staging.join(&file_name), executable_file, first, &mut requests).await;
+        let loaded = match staged {
+            Ok(()) => {
+                let staged = staging.clone();
+                self.call(move |executable| executable.load(&staged, &file_name, load_kind.into()))
+                    .await
+            }
+            Err(status) => Err(status),
+        };
+        let _ = self
+            .blocking_pool
+            .run(move || std::fs::remove_dir_all(&staging))
+            .await;
+        loaded?;
+        Ok(Response::new(LoadReply {}))
+    }
+
+    async fn unload(
+        &self,
+        request: Request<UnloadRequest>,
+    ) -> Result<Response<UnloadReply>, Status> {
+        let file_name = request.into_inner().file_name;
+        self.call(move |executable| executable.unload(&file_name))
+            .await?;
+        Ok(Response::new(UnloadReply {}))
+    }
+
+    async fn execute(
+        &self,
+        request: Request<ExecuteRequest>,
+    ) -> Result<Response<ExecuteReply>, Status> {
+        let r = request.into_inner();
+        let invalid = |e: String| Status::invalid_argument(e);
+        let options = properties_from_wire(&r.options).map_err(|e| invalid(e.to_string()))?;
+        let parameters = properties_from_wire(&r.parameters).map_err(|e| invalid(e.to_string()))?;
+        let process_id = self
+            .call(move |executable| executable.execute(&r.name, &options, &parameters))
+            .await?;
+        Ok(Response::new(ExecuteReply { process_id }))
+    }
+
+    async fn terminate(
+        &self,
+        request: Request<TerminateRequest>,
+    ) -> Result<Response<TerminateReply>, Status> {
+        let process_id = request.into_inner().process_id;
+        self.call(move |executable| executable.terminate(process_id))
+            .await?;
+        Ok(Response::new(TerminateReply {}))
+    }
+
+    async fn process_metrics(
+        &self,
+        request: Request<ProcessMetricsRequest>,
+    ) -> Result<Response<ProcessMetricsReply>, Status> {
+        let process_id = request.into_inner().process_id;
+        let metrics = self
+            .call(move |executable| executable.process_metrics(process_id))
+            .await?;
+        Ok(Response::new(ProcessMetricsReply {
+            cpu_usage: metrics.cpu_usage,
+            memory: metrics.memory,
+            status: rpc::device::ProcessStatus::from(metrics.status).into(),
+        }))
+    }
+}
+
+fn not_executable() -> Status {
+    Status::unimplemented("device is not executable")
+}
+
+/// Returns the status of a failure to stage a file.
+fn staging_error(path: &Path, error: std::io::Error) -> Status {
+    Status::internal(format!("'{}': {error}", path.display()))
+}
+
+/// Creates the staged file, and its directory, executable when an executable is loaded.
+fn create_staged(path: &Path, executable: bool) -> std::io::Result<std::fs::File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    if executable {
+        set_executable(&file)?;
+    }
+    Ok(file)
+}
+
+#[cfg(unix)]
+fn set_executable(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(std::fs::Permissions::from_mode(0o755))
+}
+
+/// The files of the other platforms are executable by their name.
+#[cfg(not(unix))]
+fn set_executable(_file: &std::fs::File) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+
+use scars::cf::blocking_pool::BlockingPool;
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::domain_manager::DomainManager;
+use scars::cf::domain_recorder::DomainRecorder;
+
+const USAGE: &str = "usage: scars-domain-manager [--record <recording file>] \
+    [--blocking-threads <n>] [--open-timeout <ms>] [--format text|json] \
+    <identifier> <label> [state file]";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-domain-manager",
+    options: &[
+        CliOption::value("--record"),
+        CliOption::value("--blocking-threads"),
+        CliOption::value("--open-timeout"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &[],
+};
+
+/**
+ * Domain booter: runs the DomainManager until SIGTERM, SIGINT or the
+ * shutdown operation, releasing the domain objects before exiting. The
+ * applications are deployed on the devices the nodes register.
+ *
+ * With --record, the operations issued to the domain are appended to
+ * the recording file, to be replayed with scars-domain replay. With
+ * --blocking-threads, the blocking operations of the domain, its
+ * services, files, allocations and components, are run on at most n
+ * threads. With --open-timeout, the files of the node file
+ * systems not opened within ms milliseconds fail with CF_ETIMEDOUT.
+ * The address the DomainManager listens on is printed once bound.
+ *
+ * usage: scars-domain-manager [--record <recording file>] [--blocking-threads <n>]
+ *        [--open-timeout <ms>] [--format text|json] <identifier> <label> [state file]
+ *        scars-domain-manager --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let usage = |e: cli::CliError| format!("{e}\n{USAGE}");
+    let format = OutputFormat::take(&mut args).map_err(usage)?;
+    let recording_file = cli::take_value(&mut args, "--record").map_err(usage)?;
+    let blocking_threads = cli::take_count(&mut args, "--blocking-threads").map_err(usage)?;
+    let open_timeout = cli::take_millis(&mut args, "--open-timeout").map_err(usage)?;
+    let (identifier, label) = match args.as_slice() {
+        [identifier, label, ..] => (identifier, label),
+        _ => return Err(USAGE.into()),
+    };
+
+    //the pool is sized before the domain objects share it
+    let blocking_pool = match blocking_threads {
+        Some(threads) => BlockingPool::init_shared(threads)?,
+        None => BlockingPool::shared(),
+    };
+    let mut manager = DomainManager::new(identifier, label).with_blocking_pool(blocking_pool);
+    if let Some(open_timeout) = open_timeout {
+        manager = manager.with_open_timeout(open_timeout);
+    }
+    if let Some(state_file) = args.get(2) {
+        manager = manager.with_persistence(Path::new(state_file))?;
+    }
+    if let Some(recording_file) = recording_file {
+        manager = manager.with_recorder(DomainRecorder::with_file(Path::new(&recording_file))?);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    cli::print_address(format, "DomainManager", listener.local_addr()?);
+
+    //shut the domain down on termination signals
+    let handle = manager.clone();
+    tokio::spawn(async move {
+        let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+        handle.shutdown();
+    });
+
+    manager.run(listener).await?;
+    Ok(())
+}
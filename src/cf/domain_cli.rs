@@ -0,0 +1,584 @@
+use std::path::Path;
+
+use serde_json::json;
+use tonic::transport::Channel;
+
+use scars::cf::cli::{self, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION};
+use scars::cf::common_types::{AnyValue, DataType, Properties};
+use scars::cf::domain_recorder::{self, DomainOperation, ReplayedOperation};
+use scars::cf::executable_device::ProcessStatus;
+use scars::cf::file_system::FileSystem;
+use scars::cf::log::{LogFormat, LogLevelType};
+use scars::cf::profile::validate;
+use scars::cf::rpc;
+use scars::cf::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use scars::cf::rpc::domain_manager::{
+    self, ApplicationFactoriesRequest, ApplicationMetricsRequest, ApplicationsRequest,
+    ConfigureApplicationRequest, CreateApplicationRequest, DeviceCapacitiesRequest,
+    DeviceManagersRequest, InstallApplicationRequest, QueryApplicationRequest,
+    ReleaseApplicationRequest, StartApplicationRequest, StopApplicationRequest,
+    SubscribeLogRecordsRequest, UninstallApplicationRequest,
+};
+
+const USAGE: &str = "usage: scars-domain [--format text|json] <domain manager endpoint> <command>
+       scars-domain [--format text|json] validate <sad file> [<dcd file>...]
+       scars-domain --completions bash|zsh|fish
+commands:
+  nodes                                      list the device managers and their devices
+  devices [--capacities]                     list the devices, with their capacities and allocations
+  factories                                  list the installed applications
+  applications                               list the running applications
+  install <sad file>                         install the SAD of the domain FileManager
+  uninstall <factory id>
+  create <factory id> <name> [<id>=<value>]  create an application
+  release <application id>
+  start <application id>
+  stop <application id>
+  props <application|component id> [<property id>...]
+  set <application|component id> <id>=<value>...
+  metrics <application id>
+  logs [--follow] [--level <level>] [--producer <pattern>]...
+                                             print the log records of the domain
+  replay <recording file>                    re-issue the operations of a domain recording";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-domain",
+    options: &[FORMAT_OPTION, COMPLETIONS_OPTION],
+    commands: &[
+        "validate",
+        "nodes",
+        "devices",
+        "factories",
+        "applications",
+        "install",
+        "uninstall",
+        "create",
+        "release",
+        "start",
+        "stop",
+        "props",
+        "set",
+        "metrics",
+        "logs",
+        "replay",
+    ],
+};
+
+/**
+ * Domain command line interface: manages the devices and applications
+ * of the DomainManager served at the endpoint.
+ *
+ * The devices command lists the devices registered with the domain,
+ * or with --capacities those of its AllocationManager: their states,
+ * their current allocation properties and the allocations outstanding
+ * on them, with the requester owning each of them.
+ *
+ * The props and set commands operate the properties of a running
+ * application or of one of its components. Property values are written
+ * as literals, e.g. 2.5, "a, b", [1,2] or {gain=2,label=rx}, parsed in
+ * the type of the current value of the property.
+ *
+ * The validate command checks a waveform offline: the profile linter
+ * and, given node configurations, the deployment dry-run on their
+ * devices. It fails when the waveform is invalid.
+ *
+ * The logs command prints the records retained by the domain LOG
+ * channel, then the new ones as they are pushed when following them.
+ * The level is a CosLwLog level name or value, or a tracing level,
+ * e.g. warn, the less severe records being skipped. The producers are
+ * identifiers or names with '*' and '?' wildcards, e.g. "*GPP*".
+ *
+ * The replay command re-issues the application operations of a domain
+ * recording one after the other, printing their recorded and replayed
+ * outcomes. It fails when an operation succeeds in one run only.
+ *
+ * As JSON, the listings are printed as arrays of objects, the created
+ * identifiers as objects, the property values as AnyValue objects and
+ * the log records as an object per line.
+ *
+ * usage: scars-domain [--format text|json] <domain manager endpoint> <command> [arguments]
+ *        scars-domain [--format text|json] validate <sad file> [<dcd file>...]
+ *        scars-domain --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    if args.first().is_some_and(|a| a == "validate") {
+        return validate(&args[1..], format);
+    }
+    let (endpoint, command) = match args.as_slice() {
+        [endpoint, command @ ..] => (endpoint, command),
+        _ => return Err(usage()),
+    };
+
+    let mut domain = DomainManagerClient::connect(endpoint.clone()).await?;
+    let arguments: Vec<&str> = command.iter().map(String::as_str).collect();
+    match arguments.as_slice() {
+        ["nodes"] => {
+            let reply = domain.device_managers(DeviceManagersRequest {}).await?;
+            let nodes = reply.into_inner().device_managers;
+            if format == OutputFormat::Json {
+                cli::print_json(&nodes)?;
+                return Ok(());
+            }
+            for node in nodes {
+                let Some(device_manager) = node.device_manager else {
+                    continue;
+                };
+                let availability = if node.available { "" } else { " (unavailable)" };
+                println!(
+                    "{} {} {}{availability}",
+                    device_manager.identifier, device_manager.label, device_manager.endpoint
+                );
+                for device in node.devices {
+                    println!(
+                        "  device  {} {} {}",
+                        device.identifier, device.label, device.endpoint
+                    );
+                }
+                for service in node.services {
+                    println!("  service {} {}", service.name, service.endpoint);
+                }
+            }
+        }
+        ["devices"] => {
+            let reply = domain.device_managers(DeviceManagersRequest {}).await?;
+            let devices: Vec<_> = reply
+                .into_inner()
+                .device_managers
+                .into_iter()
+                .flat_map(|node| node.devices)
+                .collect();
+            if format == OutputFormat::Json {
+                cli::print_json(&devices)?;
+                return Ok(());
+            }
+            for device in devices {
+                println!(
+                    "{:<40} {:<24} {:<24} {}",
+                    device.identifier, device.label, device.device_manager_id, device.endpoint
+                );
+            }
+        }
+        ["devices", "--capacities"] => {
+            let devices = domain
+                .device_capacities(DeviceCapacitiesRequest {})
+                .await?
+                .into_inner()
+                .devices;
+            if format == OutputFormat::Json {
+                cli::print_json(&devices)?;
+                return Ok(());
+            }
+            let devices = devices
+                .into_iter()
+                .map(rpc::device_capacities_from_wire)
+                .collect::<Option<Vec<_>>>()
+                .ok_or("a device has invalid capacities")?;
+            for device in devices {
+                println!(
+                    "{} {} {:?} {:?} {:?}",
+                    device.identifier,
+                    device.label,
+                    device.admin_state,
+                    device.operational_state,
+                    device.usage_state
+                );
+                for property in &device.allocation_properties {
+                    println!("  capacity   {}={}", property.id, property.value);
+                }
+                for allocation in &device.allocations {
+                    let capacities: Vec<String> = allocation
+                        .allocated_capacities
+                        .iter()
+                        .map(|c| format!("{}={}", c.id, c.value))
+                        .collect();
+                    let failed = if allocation.failed { " (failed)" } else { "" };
+                    println!(
+                        "  allocation {} owner {} {}{failed}",
+                        allocation.allocation_id,
+                        allocation.source_id,
+                        capacities.join(" ")
+                    );
+                }
+            }
+        }
+        ["factories"] => {
+            let reply = domain
+                .application_factories(ApplicationFactoriesRequest {})
+                .await?;
+            let factories = reply.into_inner().application_factories;
+            if format == OutputFormat::Json {
+                cli::print_json(&factories)?;
+                return Ok(());
+            }
+            for factory in factories {
+                println!(
+                    "{:<40} {:<24} {}",
+                    factory.identifier, factory.name, factory.software_profile
+                );
+            }
+        }
+        ["applications"] => {
+            let reply = domain.applications(ApplicationsRequest {}).await?;
+            let applications = reply.into_inner().applications;
+            if format == OutputFormat::Json {
+                cli::print_json(&applications)?;
+                return Ok(());
+            }
+            for application in applications {
+                let state = if application.started {
+                    "started"
+                } else {
+                    "stopped"
+                };
+                println!(
+                    "{:<40} {:<24} {:<8} {}",
+                    application.identifier, application.name, state, application.profile
+                );
+            }
+        }
+        ["install", profile_file_name] => {
+            let reply = domain
+                .install_application(InstallApplicationRequest {
+                    profile_file_name: profile_file_name.to_string(),
+                })
+                .await?;
+            print_identifier(&reply.into_inner().identifier, format)?;
+        }
+        ["uninstall", identifier] => {
+            domain
+                .uninstall_application(UninstallApplicationRequest {
+                    identifier: identifier.to_string(),
+                })
+                .await?;
+        }
+        ["create", factory_identifier, name, assignments @ ..] => {
+            let init_configuration = parse_properties(assignments, &Properties::new())?;
+            let reply = domain
+                .create_application(CreateApplicationRequest {
+                    factory_identifier: factory_identifier.to_string(),
+                    name: name.to_string(),
+                    init_configuration: rpc::properties_to_wire(&init_configuration),
+                    device_assignments: Vec::new(),
+                })
+                .await?;
+            print_identifier(&reply.into_inner().identifier, format)?;
+        }
+        ["release", identifier] => {
+            domain
+                .release_application(ReleaseApplicationRequest {
+                    identifier: identifier.to_string(),
+                })
+                .await?;
+        }
+        ["start", identifier] => {
+            domain
+                .start_application(StartApplicationRequest {
+                    identifier: identifier.to_string(),
+                })
+                .await?;
+        }
+        ["stop", identifier] => {
+            domain
+                .stop_application(StopApplicationRequest {
+                    identifier: identifier.to_string(),
+                })
+                .await?;
+        }
+        ["props", identifier, property_ids @ ..] => {
+            let properties = query(&mut domain, identifier, property_ids).await?;
+            if format == OutputFormat::Json {
+                cli::print_json(&rpc::properties_to_wire(&properties))?;
+                return Ok(());
+            }
+            for property in properties {
+                println!("{}={}", property.id, property.value);
+            }
+        }
+        ["set", identifier, assignments @ ..] if !assignments.is_empty() => {
+            let ids: Vec<&str> = assignments
+                .iter()
+                .map(|a| a.split_once('=').map_or(*a, |(id, _)| id))
+                .collect();
+            let current = query(&mut domain, identifier, &ids).await?;
+            let properties = parse_properties(assignments, &current)?;
+            domain
+                .configure_application(ConfigureApplicationRequest {
+                    identifier: identifier.to_string(),
+                    properties: rpc::properties_to_wire(&properties),
+                })
+                .await?;
+        }
+        ["metrics", identifier] => {
+            let metrics = domain
+                .application_metrics(ApplicationMetricsRequest {
+                    identifier: identifier.to_string(),
+                })
+                .await?
+                .into_inner();
+            let status = |status: i32| {
+                domain_manager::ProcessStatus::try_from(status)
+                    .map(ProcessStatus::from)
+                    .unwrap_or(ProcessStatus::UNKNOWN)
+            };
+            if format == OutputFormat::Json {
+                cli::print_json(&metrics)?;
+                return Ok(());
+            }
+            println!(
+                "{:<32} {:<24} {:>8} {:>8} {:>12} STATUS",
+                "COMPONENT", "DEVICE", "PID", "CPU %", "MEMORY"
+            );
+            for component in &metrics.components {
+                println!(
+                    "{:<32} {:<24} {:>8} {:>8.1} {:>12} {:?}",
+                    component.component_id,
+                    component.device_id,
+                    component
+                        .process_id
+                        .map(|p| p.to_string())
+                        .unwrap_or_default(),
+                    component.cpu_usage,
+                    component.memory,
+                    status(component.status)
+                );
+            }
+            println!(
+                "{:<32} {:<24} {:>8} {:>8.1} {:>12}",
+                metrics.identifier, "", "", metrics.cpu_usage, metrics.memory
+            );
+        }
+        ["logs", options @ ..] => {
+            let log_format = match format {
+                OutputFormat::Text => LogFormat::Text,
+                OutputFormat::Json => LogFormat::Json,
+            };
+            let request = logs_request(options)?;
+            let mut records = domain.subscribe_log_records(request).await?.into_inner();
+            while let Some(record) = records.message().await? {
+                let record =
+                    rpc::log_record_from_wire(record).ok_or("a record has an unknown level")?;
+                println!("{}", log_format.format(&record));
+            }
+        }
+        ["replay", recording_file] => {
+            let recording = domain_recorder::load(Path::new(recording_file))?;
+            let mut replayed = Vec::new();
+            for record in domain_recorder::replayed_operations(&recording) {
+                let error = issue(&mut domain, &record.operation).await;
+                replayed.push(ReplayedOperation {
+                    operation: record.operation.clone(),
+                    recorded_error: record.error.clone(),
+                    error,
+                });
+            }
+            match format {
+                OutputFormat::Text => {
+                    for operation in &replayed {
+                        let outcome = |error: &Option<String>| match error {
+                            Some(message) => format!("failed: {message}"),
+                            None => "ok".to_string(),
+                        };
+                        println!(
+                            "{}{:?}\n    recorded: {}\n    replayed: {}",
+                            if operation.diverges() { "! " } else { "" },
+                            operation.operation,
+                            outcome(&operation.recorded_error),
+                            outcome(&operation.error)
+                        );
+                    }
+                }
+                OutputFormat::Json => cli::print_json(&replayed)?,
+            }
+            let diverging = replayed.iter().filter(|r| r.diverges()).count();
+            if diverging > 0 {
+                return Err(format!("{diverging} operations diverge from the recording").into());
+            }
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+/// Issues a recorded operation to the domain, returning the error it failed with.
+async fn issue(
+    domain: &mut DomainManagerClient<Channel>,
+    operation: &DomainOperation,
+) -> Option<String> {
+    let result = match operation.clone() {
+        DomainOperation::InstallApplication { profile_file_name } => domain
+            .install_application(InstallApplicationRequest { profile_file_name })
+            .await
+            .map(|_| ()),
+        DomainOperation::UninstallApplication { identifier } => domain
+            .uninstall_application(UninstallApplicationRequest { identifier })
+            .await
+            .map(|_| ()),
+        DomainOperation::CreateApplication {
+            factory_identifier,
+            name,
+            init_configuration,
+            device_assignments,
+        } => domain
+            .create_application(CreateApplicationRequest {
+                factory_identifier,
+                name,
+                init_configuration: rpc::properties_to_wire(&init_configuration),
+                device_assignments: device_assignments
+                    .into_iter()
+                    .map(|a| domain_manager::DeviceAssignment {
+                        component_id: a.component_id,
+                        assigned_device_id: a.assigned_device_id,
+                    })
+                    .collect(),
+            })
+            .await
+            .map(|_| ()),
+        DomainOperation::ReleaseApplication { identifier } => domain
+            .release_application(ReleaseApplicationRequest { identifier })
+            .await
+            .map(|_| ()),
+        DomainOperation::StartApplication { identifier } => domain
+            .start_application(StartApplicationRequest { identifier })
+            .await
+            .map(|_| ()),
+        DomainOperation::StopApplication { identifier } => domain
+            .stop_application(StopApplicationRequest { identifier })
+            .await
+            .map(|_| ()),
+        DomainOperation::ConfigureApplication {
+            identifier,
+            properties,
+        } => domain
+            .configure_application(ConfigureApplicationRequest {
+                identifier,
+                properties: rpc::properties_to_wire(&properties),
+            })
+            .await
+            .map(|_| ()),
+        DomainOperation::Allocate { .. } | DomainOperation::Deallocate { .. } => Ok(()),
+    };
+    result.err().map(|status| status.message().to_string())
+}
+
+/// Parses the options of the logs command into its subscription.
+fn logs_request(
+    mut options: &[&str],
+) -> Result<SubscribeLogRecordsRequest, Box<dyn std::error::Error>> {
+    let mut request = SubscribeLogRecordsRequest {
+        retained: true,
+        retained_only: true,
+        ..Default::default()
+    };
+    loop {
+        match options {
+            ["--follow", rest @ ..] => (request.retained_only, options) = (false, rest),
+            ["--level", level, rest @ ..] => {
+                let level = parse_level(level).ok_or_else(|| format!("unknown level '{level}'"))?;
+                (request.log_level, options) = (level.value().into(), rest)
+            }
+            ["--producer", pattern, rest @ ..] => {
+                request.producers.push(pattern.to_string());
+                options = rest
+            }
+            [] => return Ok(request),
+            _ => return Err(usage()),
+        }
+    }
+}
+
+/// Returns the level of a CosLwLog name or value, or of a tracing level, e.g. warn.
+fn parse_level(level: &str) -> Option<LogLevelType> {
+    LogLevelType::from_name(&level.to_uppercase())
+        .or_else(|| LogLevelType::from_value(level.parse().ok()?))
+        .or_else(|| Some(level.parse::<tracing::Level>().ok()?.into()))
+}
+
+/// Validates a waveform of the local file system, printing the report.
+fn validate(files: &[String], format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let [sad_file, dcd_files @ ..] = files else {
+        return Err(usage());
+    };
+
+    //the references of the profiles may climb up to the root
+    let canonical = |file: &String| -> std::io::Result<String> {
+        Ok(std::fs::canonicalize(file)?.to_string_lossy().into_owned())
+    };
+    let sad_path = canonical(sad_file)?;
+    let dcd_paths = dcd_files
+        .iter()
+        .map(canonical)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let dcd_paths: Vec<&str> = dcd_paths.iter().map(String::as_str).collect();
+    let file_system = FileSystem::new(Path::new("/"));
+    let report = validate::validate(&file_system, &sad_path, &dcd_paths)?;
+
+    match format {
+        OutputFormat::Text => print!("{}", report.to_text()),
+        OutputFormat::Json => cli::print_json(&report.to_json())?,
+    }
+    if !report.is_valid() {
+        return Err(format!("'{sad_file}' is invalid").into());
+    }
+    Ok(())
+}
+
+/// Prints the identifier of an object created.
+fn print_identifier(
+    identifier: &str,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Text => println!("{identifier}"),
+        OutputFormat::Json => cli::print_json(&json!({ "identifier": identifier }))?,
+    }
+    Ok(())
+}
+
+/// Queries properties of an application or component, all of them when none is given.
+async fn query(
+    domain: &mut DomainManagerClient<Channel>,
+    identifier: &str,
+    property_ids: &[&str],
+) -> Result<Properties, Box<dyn std::error::Error>> {
+    let properties = property_ids
+        .iter()
+        .map(|id| DataType::new(id, AnyValue::String(String::new())))
+        .collect();
+    let reply = domain
+        .query_application(QueryApplicationRequest {
+            identifier: identifier.to_string(),
+            properties: rpc::properties_to_wire(&properties),
+        })
+        .await?;
+    Ok(rpc::properties_from_wire(&reply.into_inner().properties)?)
+}
+
+/// Parses <id>=<value> assignments, typed after the current values.
+fn parse_properties(
+    assignments: &[&str],
+    current: &Properties,
+) -> Result<Properties, Box<dyn std::error::Error>> {
+    assignments
+        .iter()
+        .map(|assignment| {
+            let (id, text) = assignment
+                .split_once('=')
+                .ok_or_else(|| format!("'{assignment}' is not <id>=<value>"))?;
+            let typed = current.iter().find(|p| p.id == id).map(|p| &p.value);
+            let value = AnyValue::parse_literal(text, typed)
+                .ok_or_else(|| format!("'{text}' is not a value of '{id}'"))?;
+            Ok(DataType::new(id, value))
+        })
+        .collect()
+}
+
+fn usage() -> Box<dyn std::error::Error> {
+    USAGE.into()
+}
@@ -0,0 +1,2780 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tokio_stream::StreamExt;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Response, Status};
+
+use super::allocation_manager::{
+    AllocationManager, AllocationManagerTrait, AllocationStatus, DeviceCapacities,
+};
+use super::application::{
+    Application, ApplicationComponent, ApplicationConnection, ApplicationError, ApplicationMetrics,
+};
+use super::application_factory::{
+    ApplicationFactory, ApplicationFactoryError, DeploymentContext, DeviceAssignmentType,
+};
+use super::blocking_pool::BlockingPool;
+use super::common_types::{AnyValue, DataType, Properties};
+use super::component_registry::ComponentRegistry;
+use super::connection_manager::{ConnectionManager, EndpointResolution};
+use super::domain_recorder::{DomainOperation, DomainRecorder, RecordingAllocationManager};
+use super::event_service::EventChannelService;
+use super::events::{
+    DomainManagementEvent, EventChannel, EventChannelManager, EventStream, FilteredEvent,
+    SourceCategoryType, StateChangeCategoryType, StateChangeEvent, IDM_CHANNEL_NAME,
+    LOG_CHANNEL_NAME, ODM_CHANNEL_NAME,
+};
+use super::executable_device::ProcessId;
+use super::file_manager::{FileManager, FileManagerRef};
+use super::file_system::DEFAULT_OPEN_TIMEOUT;
+use super::file_system_service::FileSystemService;
+use super::log::LogRecord;
+use super::profile::cache::ProfileCache;
+use super::profile::ProfileError;
+use super::registrar::RegistrarService;
+use super::remote_device::RemoteDevice;
+use super::remote_file_system::RemoteFileSystem;
+use super::resource::{self, ResourceError};
+use super::rpc;
+use super::rpc::device_manager::device_manager_client::DeviceManagerClient;
+use super::rpc::device_manager::{HeartbeatRequest, RegisteredDevicesRequest};
+use super::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use super::rpc::domain_manager::domain_manager_server::{self, DomainManagerServer};
+use super::rpc::domain_manager::{
+    ApplicationFactoriesReply, ApplicationFactoriesRequest, ApplicationMetricsReply,
+    ApplicationMetricsRequest, ApplicationsReply, ApplicationsRequest, ConfigureApplicationReply,
+    ConfigureApplicationRequest, ConnectEndpointsReply, ConnectEndpointsRequest,
+    CreateApplicationReply, CreateApplicationRequest, DeviceCapacitiesReply,
+    DeviceCapacitiesRequest, DeviceManagersReply, DeviceManagersRequest, DisconnectEndpointsReply,
+    DisconnectEndpointsRequest, InstallApplicationReply, InstallApplicationRequest,
+    ListConnectionsReply, ListConnectionsRequest, PushLogRecordsReply, PushLogRecordsRequest,
+    PushStateChangeEventReply, QueryApplicationReply, QueryApplicationRequest,
+    RegisterDeviceManagerReply, RegisterDeviceManagerRequest, RegisterDeviceReply,
+    RegisterDeviceRequest, RegisterRemoteDomainManagerReply, RegisterRemoteDomainManagerRequest,
+    RegisterServiceReply, RegisterServiceRequest, ReleaseApplicationReply,
+    ReleaseApplicationRequest, RemoteDomainManagersReply, RemoteDomainManagersRequest,
+    ShutdownReply, ShutdownRequest, StartApplicationReply, StartApplicationRequest,
+    StopApplicationReply, StopApplicationRequest, SubscribeLogRecordsRequest, SubscribeRequest,
+    UninstallApplicationReply, UninstallApplicationRequest, UnregisterDeviceManagerReply,
+    UnregisterDeviceManagerRequest, UnregisterDeviceReply, UnregisterDeviceRequest,
+    UnregisterRemoteDomainManagerReply, UnregisterRemoteDomainManagerRequest,
+    UnregisterServiceReply, UnregisterServiceRequest,
+};
+use super::rpc::event_channel::event_channel_manager_server::EventChannelManagerServer;
+use super::rpc::file_system::file_system_server::FileSystemServer;
+use super::rpc::registrar::registrar_server::RegistrarServer;
+
+/**
+ * Convienence enum definition that includes all DomainManager errors.
+ */
+#[derive(Error, Debug)]
+pub enum DomainManagerError {
+    /**
+     * This exception indicates that the object to unregister is not
+     * registered with the domain.
+     */
+    #[error("InvalidObjectReference: msg: '{message}'.")]
+    InvalidObjectReference { message: String },
+    /**
+     * This exception indicates that the DeviceManager of a registering
+     * device or service is not registered with the domain.
+     */
+    #[error("DeviceMgrNotRegistered: device manager: '{identifier}'.")]
+    DeviceMgrNotRegistered { identifier: String },
+    /**
+     * This exception indicates that an internal error has occurred and
+     * the registration could not be done.
+     */
+    #[error("RegisterError: msg: '{message}'.")]
+    RegisterError { message: String },
+    /**
+     * This exception indicates that the profile file to install does not
+     * exist in the domain FileManager.
+     */
+    #[error("InvalidFileName: msg: '{message}'.")]
+    InvalidFileName { message: String },
+    /**
+     * This exception indicates that the SAD or one of the profiles it
+     * references is not valid.
+     */
+    #[error("InvalidProfile: file: '{file_name}', msg: '{message}'.")]
+    InvalidProfile { file_name: String, message: String },
+    /**
+     * This exception indicates that the application could not be
+     * installed, e.g. a profile referenced by the SAD is missing.
+     */
+    #[error("ApplicationInstallationError: msg: '{message}'.")]
+    ApplicationInstallationError { message: String },
+    /**
+     * This exception indicates that an application with the same SAD id
+     * is already installed.
+     */
+    #[error("ApplicationAlreadyInstalled: identifier: '{identifier}'.")]
+    ApplicationAlreadyInstalled { identifier: String },
+    /**
+     * This exception indicates that no installed application has the
+     * identifier.
+     */
+    #[error("InvalidIdentifier: identifier: '{identifier}'.")]
+    InvalidIdentifier { identifier: String },
+    /**
+     * This exception indicates that the ApplicationFactory failed to
+     * create the application.
+     */
+    #[error("CreateApplicationError: {source}")]
+    CreateApplicationError { source: ApplicationFactoryError },
+    /**
+     * This exception indicates that the components of a running
+     * application failed to start or stop.
+     */
+    #[error("ApplicationControlError: {source}")]
+    ApplicationControlError { source: ApplicationError },
+    /**
+     * This exception indicates that the properties of a running
+     * application could not be configured or queried.
+     */
+    #[error("ApplicationPropertiesError: {source}")]
+    ApplicationPropertiesError { source: ResourceError },
+    /**
+     * This exception indicates that some steps of the release of an
+     * application failed. The application is forgotten nonetheless.
+     */
+    #[error("ReleaseError: {messages:?}.")]
+    ReleaseError { messages: Vec<String> },
+    /**
+     * This exception indicates that the domain state could not be read
+     * from or written to the state file.
+     */
+    #[error("PersistenceError: msg: '{message}'.")]
+    PersistenceError { message: String },
+    /**
+     * This exception indicates that the allowlist does not grant the
+     * peer domain the requested operation.
+     */
+    #[error("AccessDenied: domain: '{identifier}', msg: '{message}'.")]
+    AccessDenied { identifier: String, message: String },
+    /**
+     * This exception indicates that a peer domain could not be reached
+     * or failed a forwarded request.
+     */
+    #[error("RemoteDomainError: domain: '{identifier}', msg: '{message}'.")]
+    RemoteDomainError { identifier: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all DomainManager returned errors.
+ */
+pub type Result<T, E = DomainManagerError> = anyhow::Result<T, E>;
+
+/**
+ * This type describes a DeviceManager registered with the domain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredDeviceManager {
+    pub identifier: String,
+    pub label: String,
+    /// The endpoint serving the DeviceManager and FileSystem services of the node.
+    pub endpoint: String,
+}
+
+impl RegisteredDeviceManager {
+    /// The mount point of the node's FileSystem in the domain FileManager.
+    pub fn mount_point(&self) -> String {
+        format!("/{}", self.label)
+    }
+}
+
+/**
+ * This type describes a device registered with the domain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomainDevice {
+    pub device_manager_id: String,
+    pub identifier: String,
+    pub label: String,
+    pub profile_name: String,
+    /// The endpoint serving the Device service of the device.
+    pub endpoint: String,
+}
+
+/**
+ * This type describes a service registered with the domain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisteredService {
+    pub device_manager_id: String,
+    pub name: String,
+    pub endpoint: String,
+}
+
+/**
+ * This type defines the access the allowlist grants to a peer domain:
+ * the applications and devices of the allowlisted domains are browsed
+ * read-only, applications being deployed on them with DEPLOY only.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteDomainAccess {
+    BROWSE,
+    DEPLOY,
+}
+
+/**
+ * This type describes a peer DomainManager registered with the domain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteDomainManager {
+    pub identifier: String,
+    pub label: String,
+    /// The endpoint serving the DomainManager service of the peer domain.
+    pub endpoint: String,
+}
+
+/**
+ * This type describes an application running in a peer domain.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteApplication {
+    pub identifier: String,
+    pub name: String,
+    pub profile: String,
+}
+
+/**
+ * This type defines the states of the applications known to the domain.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApplicationStatus {
+    /// Created during the current run of the DomainManager.
+    RUNNING,
+    /// Restored from the state file, its devices still registered.
+    RECOVERED,
+    /// Restored from the state file, some of its devices having vanished.
+    DEAD,
+}
+
+/**
+ * This type describes a component of an application running in the
+ * domain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentInfo {
+    pub identifier: String,
+    pub device_id: String,
+    pub loaded_file: String,
+    pub process_id: Option<ProcessId>,
+    pub name_binding: String,
+}
+
+/**
+ * This type describes an application running in the domain, with what
+ * is needed to tear it down after a restart of the DomainManager.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationInfo {
+    pub identifier: String,
+    pub name: String,
+    pub profile: String,
+    /// The components, those of the nested applications included.
+    pub components: Vec<ComponentInfo>,
+    pub connections: Vec<ApplicationConnection>,
+    pub allocations: Vec<AllocationStatus>,
+    pub status: ApplicationStatus,
+}
+
+impl ApplicationInfo {
+    fn new(application: &Application) -> ApplicationInfo {
+        let mut info = ApplicationInfo {
+            identifier: application.identifier().to_string(),
+            name: application.name().to_string(),
+            profile: application.profile().to_string(),
+            components: Vec::new(),
+            connections: Vec::new(),
+            allocations: Vec::new(),
+            status: ApplicationStatus::RUNNING,
+        };
+        info.add(application);
+        info
+    }
+
+    /// Adds the components, connections and allocations of an application and its nested ones.
+    fn add(&mut self, application: &Application) {
+        self.components
+            .extend(application.components().iter().map(|c| ComponentInfo {
+                identifier: c.identifier.clone(),
+                device_id: c.device_id.clone(),
+                loaded_file: c.loaded_file.clone(),
+                process_id: c.process_id,
+                name_binding: c.name_binding.clone(),
+            }));
+        self.connections
+            .extend_from_slice(application.connections());
+        self.allocations.extend(application.allocations());
+        for (_, nested) in application.applications() {
+            self.add(nested);
+        }
+    }
+}
+
+/**
+ * This type defines how the DomainManager watches the registered
+ * DeviceManagers: heartbeats are sent every interval, a DeviceManager
+ * not answering them for the timeout being marked unavailable along
+ * with its devices until it answers again.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatPolicy {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for HeartbeatPolicy {
+    fn default() -> Self {
+        HeartbeatPolicy {
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// The number of events queued for a slow subscriber of the ODM, IDM and LOG channels, the following ones being dropped.
+pub const SUBSCRIBER_QUEUE_SIZE: usize = 64;
+
+/// The number of the last ODM, IDM and LOG events retained for the reconnecting subscribers, fitting their queue.
+pub const EVENT_HISTORY_SIZE: usize = SUBSCRIBER_QUEUE_SIZE;
+
+/// How long the DeviceManagers asked to shut down are waited for on shutdown.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The period the DeviceManagers shutting down are polled at.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The suffix of the temporary file the state file is replaced with.
+const TEMP_SUFFIX: &str = "tmp";
+
+/**
+ * Objects registered with the domain.
+ */
+#[derive(Debug, Default)]
+struct DomainState {
+    device_managers: Vec<RegisteredDeviceManager>,
+    devices: Vec<DomainDevice>,
+    services: Vec<RegisteredService>,
+    remote_domain_managers: Vec<RemoteDomainManager>,
+    application_factories: Vec<ApplicationFactory>,
+    /// The SADs of the installed applications restored but not loaded yet.
+    pending_profiles: Vec<String>,
+    applications: Vec<ApplicationInfo>,
+    /// The applications created during the current run.
+    running: Vec<Application>,
+    /// The last time each DeviceManager answered, or registered.
+    last_heard: HashMap<String, Instant>,
+    /// The DeviceManagers not answering the heartbeats.
+    unavailable: Vec<String>,
+}
+
+/// The holder of the properties operated by the domain.
+enum PropertyTarget<'a> {
+    Application(&'a Application),
+    Component(&'a ApplicationComponent),
+}
+
+/**
+ * Domain state persisted across DomainManager restarts.
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistentState {
+    device_managers: Vec<RegisteredDeviceManager>,
+    devices: Vec<DomainDevice>,
+    services: Vec<RegisteredService>,
+    #[serde(default)]
+    remote_domain_managers: Vec<RemoteDomainManager>,
+    /// The SAD pathnames of the installed applications.
+    application_profiles: Vec<String>,
+    applications: Vec<ApplicationInfo>,
+}
+
+impl DomainState {
+    fn verify_device_manager(&self, identifier: &str) -> Result<()> {
+        if !self
+            .device_managers
+            .iter()
+            .any(|d| d.identifier == identifier)
+        {
+            return Err(DomainManagerError::DeviceMgrNotRegistered {
+                identifier: identifier.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/**
+ * DomainManager of the domain: the hub keeping track of the registered
+ * nodes, devices and services, of the installed application factories
+ * and of the running applications, and gathering the file systems of
+ * the nodes in the domain FileManager. The changes in the domain are
+ * published on the ODM channel, the devices reporting their state
+ * changes on the IDM channel and the log records of the domain being
+ * published on the LOG channel. Its ConnectionManager completes the
+ * connections to the devices and services as they register. The peer
+ * domains of its allowlist federate with it, their applications and
+ * devices being browsed and, when allowed, applications deployed on
+ * them. Clones share the same domain.
+ */
+#[derive(Debug, Clone)]
+pub struct DomainManager {
+    identifier: String,
+    label: String,
+    file_manager: FileManagerRef,
+    state: Arc<Mutex<DomainState>>,
+    odm_channel: EventChannel<DomainManagementEvent>,
+    idm_channel: EventChannel<StateChangeEvent>,
+    log_channel: EventChannel<LogRecord>,
+    /// The channels of the domain by name, the ODM, IDM and LOG ones included.
+    event_channel_manager: EventChannelManager,
+    connection_manager: ConnectionManager,
+    registry: ComponentRegistry,
+    /// The registered devices the allocations are made on.
+    allocation_manager: Arc<Mutex<AllocationManager>>,
+    /// The registered devices the applications are deployed on, hosted by their DeviceManager.
+    registered: DeploymentContext,
+    /// The deployment context set in place of the registered devices, if any.
+    deployment: Option<DeploymentContext>,
+    /// The descriptors parsed when installing applications.
+    profile_cache: ProfileCache,
+    /// The pool of the threads of the file operations of the domain FileManager.
+    blocking_pool: BlockingPool,
+    /// The time given to the files of the node file systems to open.
+    open_timeout: Duration,
+    /// The peer domains allowed to federate, by identifier.
+    allowlist: HashMap<String, RemoteDomainAccess>,
+    state_file: Option<PathBuf>,
+    heartbeat: Option<HeartbeatPolicy>,
+    /// The recorder of the domain-level operations, if any.
+    recorder: Option<DomainRecorder>,
+    shutdown: Arc<Notify>,
+}
+
+impl DomainManager {
+    pub fn new(identifier: &str, label: &str) -> DomainManager {
+        let odm_channel = EventChannel::new(ODM_CHANNEL_NAME)
+            .with_history(EVENT_HISTORY_SIZE)
+            .with_queue_size(SUBSCRIBER_QUEUE_SIZE);
+        let idm_channel = idm_channel(identifier, &odm_channel);
+        let log_channel = EventChannel::new(LOG_CHANNEL_NAME)
+            .with_history(EVENT_HISTORY_SIZE)
+            .with_queue_size(SUBSCRIBER_QUEUE_SIZE);
+        let registry = ComponentRegistry::new();
+        let file_manager: FileManagerRef = Arc::new(Mutex::new(FileManager::new()));
+        let allocation_manager = Arc::new(Mutex::new(AllocationManager::new()));
+        let registered = DeploymentContext::new(
+            file_manager.clone(),
+            allocation_manager.clone(),
+            registry.clone(),
+        );
+        DomainManager {
+            identifier: identifier.to_string(),
+            label: label.to_string(),
+            file_manager,
+            state: Arc::default(),
+            event_channel_manager: event_channel_manager(&odm_channel, &idm_channel, &log_channel),
+            odm_channel,
+            idm_channel,
+            log_channel,
+            connection_manager: ConnectionManager::new(registry.clone()),
+            registry,
+            allocation_manager,
+            registered,
+            deployment: None,
+            profile_cache: ProfileCache::default(),
+            blocking_pool: BlockingPool::shared(),
+            open_timeout: DEFAULT_OPEN_TIMEOUT,
+            allowlist: HashMap::new(),
+            state_file: None,
+            heartbeat: None,
+            recorder: None,
+            shutdown: Arc::default(),
+        }
+    }
+
+    /**
+     * Replaces the ODM channel the domain management events are
+     * published to, along with the IDM channel forwarding to it.
+     */
+    pub fn with_event_channel(
+        mut self,
+        event_channel: EventChannel<DomainManagementEvent>,
+    ) -> DomainManager {
+        self.idm_channel = idm_channel(&self.identifier, &event_channel);
+        self.odm_channel = event_channel;
+        self.event_channel_manager =
+            event_channel_manager(&self.odm_channel, &self.idm_channel, &self.log_channel);
+        self
+    }
+
+    /**
+     * Sets the domain objects the installed applications are deployed
+     * with in place of the registered devices, their file manager being
+     * the domain FileManager. The ConnectionManager and the Registrar
+     * service use its registry.
+     */
+    pub fn with_deployment(mut self, deployment: DeploymentContext) -> DomainManager {
+        self.registry = deployment.registry().clone();
+        self.connection_manager = self.connection_manager.with_registry(self.registry.clone());
+        self.deployment = Some(deployment);
+        self
+    }
+
+    /**
+     * Allows a peer domain to register with the domain, granting it the
+     * access. The peer domains not allowlisted are refused.
+     */
+    pub fn with_remote_domain(
+        mut self,
+        identifier: &str,
+        access: RemoteDomainAccess,
+    ) -> DomainManager {
+        self.allowlist.insert(identifier.to_string(), access);
+        self
+    }
+
+    /// Heartbeats the registered DeviceManagers while running.
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatPolicy) -> DomainManager {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /**
+     * Records the control operations of the applications and the
+     * allocations on the registered devices. A deployment context set in
+     * their place shall record its allocations itself, its allocation
+     * manager being a RecordingAllocationManager.
+     */
+    pub fn with_recorder(mut self, recorder: DomainRecorder) -> DomainManager {
+        let allocation_manager =
+            RecordingAllocationManager::new(self.allocation_manager.clone(), recorder.clone());
+        self.registered = self
+            .registered
+            .with_allocation_manager(Arc::new(Mutex::new(allocation_manager)));
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Returns the recorder of the domain-level operations, if any.
+    pub fn recorder(&self) -> Option<&DomainRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Parses the descriptors of the installed applications through the cache.
+    pub fn with_profile_cache(mut self, profile_cache: ProfileCache) -> DomainManager {
+        self.profile_cache = profile_cache;
+        self
+    }
+
+    /**
+     * Sets the pool of the threads of the domain: the ones of its
+     * services, of the file operations of its FileManager, of the
+     * allocations on the registered devices and of the operations of the
+     * components deployed on them.
+     */
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> DomainManager {
+        {
+            let mut allocation_manager = self.allocation_manager.lock().unwrap();
+            *allocation_manager =
+                std::mem::take(&mut *allocation_manager).with_blocking_pool(blocking_pool.clone());
+        }
+        self.registered = self.registered.with_component_pool(blocking_pool.clone());
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /**
+     * Sets the time given to the files of the file systems of the
+     * registered DeviceManagers to open, e.g. on a hung network mount,
+     * past which the file operations fail with CF_ETIMEDOUT. It shall be
+     * set before the registrations are restored.
+     */
+    pub fn with_open_timeout(mut self, open_timeout: Duration) -> DomainManager {
+        self.open_timeout = open_timeout;
+        self
+    }
+
+    /**
+     * Keeps the registrations, the installed applications and the
+     * running applications in a state file, restoring those left by a
+     * previous run of the DomainManager. The file systems holding the
+     * installed SADs shall be mounted before the factories are first
+     * used, the installed SADs being loaded on their first lookup
+     * rather than while booting, and the applications whose SAD does
+     * not load being kept pending until it does. The restored
+     * applications are RECOVERED until reconcile tells otherwise.
+     */
+    pub fn with_persistence(mut self, state_file: &Path) -> Result<DomainManager> {
+        if state_file.is_file() {
+            let persisted: PersistentState = std::fs::read(state_file)
+                .map_err(|e| e.to_string())
+                .and_then(|state| serde_json::from_slice(&state).map_err(|e| e.to_string()))
+                .map_err(|message| DomainManagerError::PersistenceError {
+                    message: format!("'{}': {message}", state_file.display()),
+                })?;
+
+            //mount the file systems of the nodes again
+            for device_manager in persisted.device_managers {
+                self.register_device_manager(device_manager)?;
+            }
+            for device in &persisted.devices {
+                self.connection_manager
+                    .register_object(device_object(&device.identifier), &device.endpoint);
+                self.add_device(device)?;
+            }
+            for service in &persisted.services {
+                self.connection_manager
+                    .register_object(service_object(&service.name), &service.endpoint);
+            }
+
+            let mut state = self.state.lock().unwrap();
+            state.devices = persisted.devices;
+            state.services = persisted.services;
+            state.remote_domain_managers = persisted.remote_domain_managers;
+            state.pending_profiles = persisted.application_profiles;
+            state.applications = persisted.applications;
+            for application in &mut state.applications {
+                if application.status == ApplicationStatus::RUNNING {
+                    application.status = ApplicationStatus::RECOVERED;
+                }
+            }
+        }
+
+        self.state_file = Some(state_file.to_path_buf());
+        self.persist(&self.state.lock().unwrap())?;
+        Ok(self)
+    }
+
+    /// The readonly identifier attribute contains the DMD id.
+    pub fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// The readonly label attribute contains the domain name.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the Outgoing Domain Management channel.
+    pub fn odm_channel(&self) -> EventChannel<DomainManagementEvent> {
+        self.odm_channel.clone()
+    }
+
+    /// Returns the Incoming Domain Management channel the devices push their state changes to.
+    pub fn idm_channel(&self) -> EventChannel<StateChangeEvent> {
+        self.idm_channel.clone()
+    }
+
+    /**
+     * Returns the LOG channel the log records of the domain are published
+     * on, a consumer the loggers of the domain objects may write to.
+     */
+    pub fn log_channel(&self) -> EventChannel<LogRecord> {
+        self.log_channel.clone()
+    }
+
+    /// Returns the manager looking the event channels of the domain up by name.
+    pub fn event_channel_manager(&self) -> EventChannelManager {
+        self.event_channel_manager.clone()
+    }
+
+    /// Returns the ConnectionManager of the domain.
+    pub fn connection_manager(&self) -> ConnectionManager {
+        self.connection_manager.clone()
+    }
+
+    /// Returns the registry the Registrar service binds the component endpoints in.
+    pub fn registry(&self) -> ComponentRegistry {
+        self.registry.clone()
+    }
+
+    /**
+     * Returns the domain objects the applications are deployed with: the
+     * deployment context set, or else the registered devices.
+     */
+    pub fn deployment(&self) -> &DeploymentContext {
+        self.deployment.as_ref().unwrap_or(&self.registered)
+    }
+
+    /// Returns the cache of the descriptors parsed when installing applications.
+    pub fn profile_cache(&self) -> ProfileCache {
+        self.profile_cache.clone()
+    }
+
+    /// The readonly fileMgr attribute contains the domain FileManager.
+    pub fn file_manager(&self) -> FileManagerRef {
+        self.file_manager.clone()
+    }
+
+    /// The readonly deviceManagers attribute contains the registered DeviceManagers.
+    pub fn device_managers(&self) -> Vec<RegisteredDeviceManager> {
+        self.state.lock().unwrap().device_managers.clone()
+    }
+
+    /**
+     * Tells whether a DeviceManager or device is available, i.e. its
+     * DeviceManager answers the heartbeats.
+     */
+    pub fn is_available(&self, identifier: &str) -> bool {
+        let state = self.state.lock().unwrap();
+        let device_manager_id = state
+            .devices
+            .iter()
+            .find(|d| d.identifier == identifier)
+            .map_or(identifier, |d| &d.device_manager_id);
+        !state.unavailable.iter().any(|id| id == device_manager_id)
+    }
+
+    /// Returns the devices registered with the domain.
+    pub fn devices(&self) -> Vec<DomainDevice> {
+        self.state.lock().unwrap().devices.clone()
+    }
+
+    /// Returns the services registered with the domain.
+    pub fn services(&self) -> Vec<RegisteredService> {
+        self.state.lock().unwrap().services.clone()
+    }
+
+    /// Returns the peer DomainManagers registered with the domain.
+    pub fn remote_domain_managers(&self) -> Vec<RemoteDomainManager> {
+        self.state.lock().unwrap().remote_domain_managers.clone()
+    }
+
+    /// The readonly applicationFactories attribute contains the installed application factories.
+    pub fn application_factories(&self) -> Vec<ApplicationFactory> {
+        self.load_pending_profiles(None);
+        self.state.lock().unwrap().application_factories.clone()
+    }
+
+    /**
+     * Returns an installed ApplicationFactory by identifier, the pending
+     * installed applications being loaded until it is found.
+     */
+    fn application_factory(&self, identifier: &str) -> Option<ApplicationFactory> {
+        self.load_pending_profiles(Some(identifier));
+        self.state
+            .lock()
+            .unwrap()
+            .application_factories
+            .iter()
+            .find(|f| f.identifier() == identifier)
+            .cloned()
+    }
+
+    /**
+     * Loads the ApplicationFactories of the installed applications
+     * restored and not loaded yet, which are otherwise loaded on their
+     * first use. The SADs are loaded in parallel, the applications whose
+     * SAD does not load being kept pending to be retried on their next
+     * use. Returns the number of factories loaded.
+     */
+    pub fn load_application_factories(&self) -> usize {
+        self.load_pending_profiles(None)
+    }
+
+    /**
+     * Loads the ApplicationFactories of the pending installed
+     * applications, until the one of an identifier when given. The SADs
+     * are parsed without holding the domain state, the factories being
+     * added to it once loaded, and the SADs failing to load are left
+     * pending. Returns the number of factories loaded.
+     */
+    fn load_pending_profiles(&self, identifier: Option<&str>) -> usize {
+        let profiles = {
+            let state = self.state.lock().unwrap();
+            let loaded = identifier.is_some_and(|identifier| {
+                state
+                    .application_factories
+                    .iter()
+                    .any(|f| f.identifier() == identifier)
+            });
+            if loaded || state.pending_profiles.is_empty() {
+                return 0;
+            }
+            state.pending_profiles.clone()
+        };
+        //the SADs are loaded from a snapshot of the mounts, the FileManager not being locked meanwhile
+        let file_manager = self.file_manager.lock().unwrap().clone();
+        let factories: Vec<(String, ApplicationFactory)> = match identifier {
+            //the pending SADs are loaded in turn until the one looked up
+            Some(identifier) => {
+                let mut factories = Vec::new();
+                for profile in profiles {
+                    if let Ok(factory) = self.load_factory_from(&file_manager, &profile) {
+                        let found = factory.identifier() == identifier;
+                        factories.push((profile, factory));
+                        if found {
+                            break;
+                        }
+                    }
+                }
+                factories
+            }
+            None => {
+                let threads = std::thread::available_parallelism()
+                    .map_or(1, |n| n.get())
+                    .min(profiles.len());
+                let chunk_size = profiles.len().div_ceil(threads);
+                std::thread::scope(|scope| {
+                    let loaders: Vec<_> = profiles
+                        .chunks(chunk_size)
+                        .map(|chunk| {
+                            let file_manager = &file_manager;
+                            scope.spawn(move || {
+                                chunk
+                                    .iter()
+                                    .filter_map(|profile| {
+                                        let factory =
+                                            self.load_factory_from(file_manager, profile).ok()?;
+                                        Some((profile.clone(), factory))
+                                    })
+                                    .collect::<Vec<_>>()
+                            })
+                        })
+                        .collect();
+                    loaders
+                        .into_iter()
+                        .flat_map(|loader| loader.join().unwrap_or_default())
+                        .collect()
+                })
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let mut loaded = 0;
+        let mut duplicates = false;
+        for (profile, factory) in factories {
+            //the profile may have been loaded by a concurrent lookup meanwhile
+            let Some(index) = state.pending_profiles.iter().position(|p| *p == profile) else {
+                continue;
+            };
+            state.pending_profiles.remove(index);
+            if state
+                .application_factories
+                .iter()
+                .any(|f| f.identifier() == factory.identifier())
+            {
+                duplicates = true;
+                continue;
+            }
+            state.application_factories.push(factory);
+            loaded += 1;
+        }
+        //the applications installed twice are uninstalled, best effort
+        if duplicates {
+            let _ = self.persist(&state);
+        }
+        loaded
+    }
+
+    /// The readonly applications attribute contains the running applications.
+    pub fn applications(&self) -> Vec<ApplicationInfo> {
+        self.state.lock().unwrap().applications.clone()
+    }
+
+    /**
+     * Registers a DeviceManager, mounting the FileSystem served at its
+     * endpoint under its label in the domain FileManager. A DeviceManager
+     * registering again replaces its previous registration.
+     */
+    pub fn register_device_manager(&self, device_manager: RegisteredDeviceManager) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let mut file_manager = self.file_manager.lock().unwrap();
+
+        let previous = state
+            .device_managers
+            .iter()
+            .position(|d| d.identifier == device_manager.identifier)
+            .map(|index| state.device_managers.remove(index));
+        if let Some(previous) = &previous {
+            let _ = file_manager.unmount(&previous.mount_point());
+        }
+
+        let register_error = |e: &dyn std::fmt::Display| DomainManagerError::RegisterError {
+            message: format!("DeviceManager '{}': {e}", device_manager.identifier),
+        };
+        let file_system = RemoteFileSystem::new(&device_manager.endpoint)
+            .map_err(|e| register_error(&e))?
+            .with_timeout(self.open_timeout);
+        file_manager
+            .mount(&device_manager.mount_point(), Arc::new(file_system))
+            .map_err(|e| register_error(&e))?;
+
+        if previous.is_none() {
+            self.object_added(
+                &device_manager.identifier,
+                &device_manager.label,
+                SourceCategoryType::DEVICE_MANAGER,
+            );
+        }
+        state.device_managers.push(device_manager.clone());
+        self.heard(&mut state, &device_manager.identifier);
+        self.persist(&state)
+    }
+
+    /**
+     * Unregisters a DeviceManager along with its devices and services,
+     * unmounting its FileSystem. The allocations made on its devices are
+     * marked failed.
+     */
+    pub fn unregister_device_manager(&self, identifier: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .device_managers
+            .iter()
+            .position(|d| d.identifier == identifier)
+            .ok_or_else(|| DomainManagerError::InvalidObjectReference {
+                message: format!("DeviceManager '{identifier}' not registered"),
+            })?;
+
+        let device_manager = state.device_managers.remove(index);
+        for device in state
+            .devices
+            .iter()
+            .filter(|d| d.device_manager_id == identifier)
+        {
+            self.connection_manager
+                .unregister_object(&device_object(&device.identifier));
+            self.remove_device(&device.identifier);
+            self.object_removed(
+                &device.identifier,
+                &device.label,
+                SourceCategoryType::DEVICE,
+            );
+        }
+        for service in state
+            .services
+            .iter()
+            .filter(|s| s.device_manager_id == identifier)
+        {
+            self.connection_manager
+                .unregister_object(&service_object(&service.name));
+            self.object_removed(&service.name, &service.name, SourceCategoryType::SERVICE);
+        }
+        self.object_removed(
+            &device_manager.identifier,
+            &device_manager.label,
+            SourceCategoryType::DEVICE_MANAGER,
+        );
+        state.devices.retain(|d| d.device_manager_id != identifier);
+        state.services.retain(|s| s.device_manager_id != identifier);
+        state.last_heard.remove(identifier);
+        state.unavailable.retain(|id| id != identifier);
+        let _ = self
+            .file_manager
+            .lock()
+            .unwrap()
+            .unmount(&device_manager.mount_point());
+        self.persist(&state)
+    }
+
+    /**
+     * Registers a device of a registered DeviceManager. A device
+     * registering again replaces its previous registration.
+     */
+    pub fn register_device(&self, device: DomainDevice) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.verify_device_manager(&device.device_manager_id)?;
+
+        if !state
+            .devices
+            .iter()
+            .any(|d| d.identifier == device.identifier)
+        {
+            self.object_added(
+                &device.identifier,
+                &device.label,
+                SourceCategoryType::DEVICE,
+            );
+        }
+        self.connection_manager
+            .register_object(device_object(&device.identifier), &device.endpoint);
+        self.add_device(&device)?;
+        //a device of an unavailable DeviceManager is withdrawn until it answers
+        if state.unavailable.contains(&device.device_manager_id) {
+            self.allocation_manager
+                .lock()
+                .unwrap()
+                .set_available(&device.identifier, false);
+            self.registered.set_available(&device.identifier, false);
+        }
+        state.devices.retain(|d| d.identifier != device.identifier);
+        state.devices.push(device);
+        self.persist(&state)
+    }
+
+    /**
+     * Makes a registered device available to the allocations and to the
+     * deployments, through the Device service at its endpoint, the
+     * device being hosted by its DeviceManager.
+     */
+    fn add_device(&self, device: &DomainDevice) -> Result<()> {
+        let remote = RemoteDevice::new(&device.identifier, &device.label, &device.endpoint)
+            .map_err(|e| DomainManagerError::RegisterError {
+                message: format!("device '{}': {e}", device.identifier),
+            })?;
+        let remote = Arc::new(Mutex::new(remote));
+        self.allocation_manager
+            .lock()
+            .unwrap()
+            .register_device(remote.clone());
+        self.registered
+            .add_device(remote, &device.device_manager_id);
+        Ok(())
+    }
+
+    /**
+     * Withdraws a device from the allocations and the deployments. Its
+     * outstanding allocations are marked failed, and published on the
+     * ODM channel for the applications they were made for.
+     */
+    fn remove_device(&self, identifier: &str) {
+        let lost: Vec<AllocationStatus> = {
+            let mut allocation_manager = self.allocation_manager.lock().unwrap();
+            let lost = allocation_manager
+                .list_allocations()
+                .into_iter()
+                .filter(|a| a.allocated_device == identifier && !a.failed)
+                .collect();
+            allocation_manager.unregister_device(identifier);
+            lost
+        };
+        self.registered.remove_device(identifier);
+        for allocation in lost {
+            self.odm_channel
+                .push(DomainManagementEvent::AllocationFailed {
+                    producer_id: self.identifier.clone(),
+                    allocation_id: allocation.allocation_id,
+                    source_id: allocation.source_id,
+                    allocated_device: allocation.allocated_device,
+                });
+        }
+    }
+
+    /**
+     * Unregisters a device, the allocations made on it being marked
+     * failed until their applications are released.
+     */
+    pub fn unregister_device(&self, identifier: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .devices
+            .iter()
+            .position(|d| d.identifier == identifier)
+            .ok_or_else(|| DomainManagerError::InvalidObjectReference {
+                message: format!("device '{identifier}' not registered"),
+            })?;
+        let device = state.devices.remove(index);
+        self.connection_manager
+            .unregister_object(&device_object(&device.identifier));
+        self.remove_device(&device.identifier);
+        self.object_removed(
+            &device.identifier,
+            &device.label,
+            SourceCategoryType::DEVICE,
+        );
+        self.persist(&state)
+    }
+
+    /**
+     * Registers a service of a registered DeviceManager. Services are
+     * identified by their name, unique in the domain.
+     */
+    pub fn register_service(&self, service: RegisteredService) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.verify_device_manager(&service.device_manager_id)?;
+
+        //verify the name is not taken by a service of another node
+        if state
+            .services
+            .iter()
+            .any(|s| s.name == service.name && s.device_manager_id != service.device_manager_id)
+        {
+            return Err(DomainManagerError::RegisterError {
+                message: format!("service '{}' already registered", service.name),
+            });
+        }
+
+        if !state.services.iter().any(|s| s.name == service.name) {
+            self.object_added(&service.name, &service.name, SourceCategoryType::SERVICE);
+        }
+        self.connection_manager
+            .register_object(service_object(&service.name), &service.endpoint);
+        state.services.retain(|s| s.name != service.name);
+        state.services.push(service);
+        self.persist(&state)
+    }
+
+    /// Unregisters a service.
+    pub fn unregister_service(&self, name: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .services
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| DomainManagerError::InvalidObjectReference {
+                message: format!("service '{name}' not registered"),
+            })?;
+        state.services.remove(index);
+        self.connection_manager
+            .unregister_object(&service_object(name));
+        self.object_removed(name, name, SourceCategoryType::SERVICE);
+        self.persist(&state)
+    }
+
+    /**
+     * Registers a peer DomainManager, which shall be allowlisted. A peer
+     * registering again replaces its previous registration.
+     */
+    pub fn register_remote_domain_manager(&self, remote: RemoteDomainManager) -> Result<()> {
+        if remote.identifier == self.identifier {
+            return Err(DomainManagerError::RegisterError {
+                message: format!("domain '{}' cannot federate with itself", remote.identifier),
+            });
+        }
+        self.access(&remote.identifier)?;
+
+        let mut state = self.state.lock().unwrap();
+        if !state
+            .remote_domain_managers
+            .iter()
+            .any(|r| r.identifier == remote.identifier)
+        {
+            self.object_added(
+                &remote.identifier,
+                &remote.label,
+                SourceCategoryType::DOMAIN_MANAGER,
+            );
+        }
+        state
+            .remote_domain_managers
+            .retain(|r| r.identifier != remote.identifier);
+        state.remote_domain_managers.push(remote);
+        self.persist(&state)
+    }
+
+    /// Unregisters a peer DomainManager.
+    pub fn unregister_remote_domain_manager(&self, identifier: &str) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let index = state
+            .remote_domain_managers
+            .iter()
+            .position(|r| r.identifier == identifier)
+            .ok_or_else(|| DomainManagerError::InvalidObjectReference {
+                message: format!("DomainManager '{identifier}' not registered"),
+            })?;
+        let remote = state.remote_domain_managers.remove(index);
+        self.object_removed(
+            &remote.identifier,
+            &remote.label,
+            SourceCategoryType::DOMAIN_MANAGER,
+        );
+        self.persist(&state)
+    }
+
+    /// Returns the access the allowlist grants to a peer domain.
+    fn access(&self, identifier: &str) -> Result<RemoteDomainAccess> {
+        self.allowlist
+            .get(identifier)
+            .copied()
+            .ok_or_else(|| DomainManagerError::AccessDenied {
+                identifier: identifier.to_string(),
+                message: "not allowlisted".to_string(),
+            })
+    }
+
+    /**
+     * Connects to a registered peer domain, the allowlist being checked
+     * again as the registrations may have been restored from the state
+     * file.
+     */
+    async fn remote_domain(
+        &self,
+        identifier: &str,
+        access: RemoteDomainAccess,
+    ) -> Result<DomainManagerClient<Channel>> {
+        let granted = self.access(identifier)?;
+        if access == RemoteDomainAccess::DEPLOY && granted != RemoteDomainAccess::DEPLOY {
+            return Err(DomainManagerError::AccessDenied {
+                identifier: identifier.to_string(),
+                message: "not allowed to deploy".to_string(),
+            });
+        }
+
+        let endpoint = self
+            .remote_domain_managers()
+            .into_iter()
+            .find(|r| r.identifier == identifier)
+            .map(|r| r.endpoint)
+            .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                identifier: identifier.to_string(),
+            })?;
+        DomainManagerClient::connect(endpoint)
+            .await
+            .map_err(|e| remote_error(identifier, e.to_string()))
+    }
+
+    /// Returns the applications running in a peer domain.
+    pub async fn remote_applications(&self, identifier: &str) -> Result<Vec<RemoteApplication>> {
+        let mut remote = self
+            .remote_domain(identifier, RemoteDomainAccess::BROWSE)
+            .await?;
+        let reply = remote
+            .applications(ApplicationsRequest {})
+            .await
+            .map_err(|e| remote_error(identifier, e.message()))?;
+        Ok(reply
+            .into_inner()
+            .applications
+            .into_iter()
+            .map(|a| RemoteApplication {
+                identifier: a.identifier,
+                name: a.name,
+                profile: a.profile,
+            })
+            .collect())
+    }
+
+    /// Returns the devices registered with a peer domain.
+    pub async fn remote_devices(&self, identifier: &str) -> Result<Vec<DomainDevice>> {
+        let mut remote = self
+            .remote_domain(identifier, RemoteDomainAccess::BROWSE)
+            .await?;
+        let reply = remote
+            .device_managers(DeviceManagersRequest {})
+            .await
+            .map_err(|e| remote_error(identifier, e.message()))?;
+        Ok(reply
+            .into_inner()
+            .device_managers
+            .into_iter()
+            .flat_map(|dm| dm.devices)
+            .map(|d| DomainDevice {
+                device_manager_id: d.device_manager_id,
+                identifier: d.identifier,
+                label: d.label,
+                profile_name: d.profile_name,
+                endpoint: d.endpoint,
+            })
+            .collect())
+    }
+
+    /**
+     * Creates an application with an ApplicationFactory installed in a
+     * peer domain the allowlist grants the DEPLOY access to. Returns the
+     * identifier of the application, running in the peer domain.
+     */
+    pub async fn create_remote_application(
+        &self,
+        identifier: &str,
+        factory_identifier: &str,
+        name: &str,
+        init_configuration: &Properties,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<String> {
+        let mut remote = self
+            .remote_domain(identifier, RemoteDomainAccess::DEPLOY)
+            .await?;
+        let reply = remote
+            .create_application(CreateApplicationRequest {
+                factory_identifier: factory_identifier.to_string(),
+                name: name.to_string(),
+                init_configuration: rpc::properties_to_wire(init_configuration),
+                device_assignments: device_assignments
+                    .iter()
+                    .map(|a| rpc::domain_manager::DeviceAssignment {
+                        component_id: a.component_id.clone(),
+                        assigned_device_id: a.assigned_device_id.clone(),
+                    })
+                    .collect(),
+            })
+            .await
+            .map_err(|e| remote_error(identifier, e.message()))?;
+        Ok(reply.into_inner().identifier)
+    }
+
+    /**
+     * Releases an application running in a peer domain the allowlist
+     * grants the DEPLOY access to.
+     */
+    pub async fn release_remote_application(
+        &self,
+        identifier: &str,
+        application_identifier: &str,
+    ) -> Result<()> {
+        let mut remote = self
+            .remote_domain(identifier, RemoteDomainAccess::DEPLOY)
+            .await?;
+        remote
+            .release_application(ReleaseApplicationRequest {
+                identifier: application_identifier.to_string(),
+            })
+            .await
+            .map_err(|e| remote_error(identifier, e.message()))?;
+        Ok(())
+    }
+
+    /**
+     * Installs the application of a SAD of the domain FileManager,
+     * verifying the SAD and every SPD, SCD and PRF it references, and
+     * creates its ApplicationFactory. Returns the identifier of the
+     * factory, the SAD id.
+     */
+    pub fn install_application(&self, profile_file_name: &str) -> Result<String> {
+        let operation = || DomainOperation::InstallApplication {
+            profile_file_name: profile_file_name.to_string(),
+        };
+        self.recorded(operation, || {
+            let factory = self.load_factory(profile_file_name)?;
+
+            self.load_pending_profiles(Some(factory.identifier()));
+            let mut state = self.state.lock().unwrap();
+            if state
+                .application_factories
+                .iter()
+                .any(|f| f.identifier() == factory.identifier())
+            {
+                return Err(DomainManagerError::ApplicationAlreadyInstalled {
+                    identifier: factory.identifier().to_string(),
+                });
+            }
+
+            let identifier = factory.identifier().to_string();
+            self.object_added(
+                &identifier,
+                factory.name(),
+                SourceCategoryType::APPLICATION_FACTORY,
+            );
+            state.application_factories.push(factory);
+            self.persist(&state)?;
+            Ok(identifier)
+        })
+    }
+
+    /// Runs a domain-level operation, recording it once completed when a recorder is set.
+    fn recorded<T, O, F>(&self, operation: O, run: F) -> Result<T>
+    where
+        O: FnOnce() -> DomainOperation,
+        F: FnOnce() -> Result<T>,
+    {
+        let Some(recorder) = &self.recorder else {
+            return run();
+        };
+        let time = SystemTime::now();
+        let result = run();
+        recorder.record(
+            time,
+            operation(),
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+        result
+    }
+
+    /// Loads the ApplicationFactory of a SAD of the domain FileManager.
+    fn load_factory(&self, profile_file_name: &str) -> Result<ApplicationFactory> {
+        let file_manager = self.file_manager.lock().unwrap();
+        self.load_factory_from(&file_manager, profile_file_name)
+    }
+
+    /// Loads the ApplicationFactory of a SAD of a file manager.
+    fn load_factory_from(
+        &self,
+        file_manager: &FileManager,
+        profile_file_name: &str,
+    ) -> Result<ApplicationFactory> {
+        let factory =
+            ApplicationFactory::load_cached(file_manager, profile_file_name, &self.profile_cache)
+                .map_err(|e| match e {
+                ProfileError::ProfileNotFound { file_name, message }
+                    if file_name == profile_file_name =>
+                {
+                    DomainManagerError::InvalidFileName {
+                        message: format!("'{file_name}': {message}"),
+                    }
+                }
+                ProfileError::ProfileNotFound { file_name, message } => {
+                    DomainManagerError::ApplicationInstallationError {
+                        message: format!(
+                            "'{profile_file_name}' references '{file_name}': {message}"
+                        ),
+                    }
+                }
+                ProfileError::InvalidProfile { file_name, message } => {
+                    DomainManagerError::InvalidProfile { file_name, message }
+                }
+            })?;
+
+        Ok(factory.with_deployment(self.deployment().clone()))
+    }
+
+    /// Uninstalls an application, removing its ApplicationFactory.
+    pub fn uninstall_application(&self, identifier: &str) -> Result<()> {
+        let operation = || DomainOperation::UninstallApplication {
+            identifier: identifier.to_string(),
+        };
+        self.recorded(operation, || {
+            self.load_pending_profiles(Some(identifier));
+            let mut state = self.state.lock().unwrap();
+            let index = state
+                .application_factories
+                .iter()
+                .position(|f| f.identifier() == identifier)
+                .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                    identifier: identifier.to_string(),
+                })?;
+
+            let factory = state.application_factories.remove(index);
+            self.object_removed(
+                identifier,
+                factory.name(),
+                SourceCategoryType::APPLICATION_FACTORY,
+            );
+            self.persist(&state)
+        })
+    }
+
+    /**
+     * Creates an application with an installed ApplicationFactory and
+     * adds it to the running applications. Returns the identifier of
+     * the application.
+     */
+    pub fn create_application(
+        &self,
+        factory_identifier: &str,
+        name: &str,
+        init_configuration: &Properties,
+        device_assignments: &[DeviceAssignmentType],
+    ) -> Result<String> {
+        let operation = || DomainOperation::CreateApplication {
+            factory_identifier: factory_identifier.to_string(),
+            name: name.to_string(),
+            init_configuration: init_configuration.clone(),
+            device_assignments: device_assignments.to_vec(),
+        };
+        self.recorded(operation, || {
+            //the domain is not locked while the components are deployed
+            let factory = self
+                .application_factory(factory_identifier)
+                .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                    identifier: factory_identifier.to_string(),
+                })?;
+            let identifier = factory.application_identifier(name);
+            if self
+                .applications()
+                .iter()
+                .any(|a| a.identifier == identifier)
+            {
+                return Err(DomainManagerError::CreateApplicationError {
+                    source: ApplicationFactoryError::CreateApplicationError {
+                        message: format!("application '{identifier}' already exists"),
+                    },
+                });
+            }
+            let application = factory
+                .create(name, init_configuration, device_assignments)
+                .map_err(|source| DomainManagerError::CreateApplicationError { source })?;
+
+            let mut state = self.state.lock().unwrap();
+
+            self.object_added(&identifier, name, SourceCategoryType::APPLICATION);
+            state.applications.push(ApplicationInfo::new(&application));
+            state.running.push(application);
+            self.persist(&state)?;
+            Ok(identifier)
+        })
+    }
+
+    /**
+     * Releases a running application. The applications restored from
+     * the state file are torn down through the devices of the deployment
+     * context: their processes are terminated, their files unloaded and
+     * their capacities deallocated.
+     */
+    pub fn release_application(&self, identifier: &str) -> Result<()> {
+        let operation = || DomainOperation::ReleaseApplication {
+            identifier: identifier.to_string(),
+        };
+        self.recorded(operation, || {
+            let mut state = self.state.lock().unwrap();
+            let index = state
+                .applications
+                .iter()
+                .position(|a| a.identifier == identifier)
+                .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                    identifier: identifier.to_string(),
+                })?;
+
+            let info = state.applications.remove(index);
+            let running = state
+                .running
+                .iter()
+                .position(|a| a.identifier() == identifier);
+            let messages = match running {
+                Some(index) => match state.running.remove(index).release_object() {
+                    Ok(()) => Vec::new(),
+                    Err(ApplicationError::ReleaseError { messages }) => messages,
+                    Err(e) => vec![e.to_string()],
+                },
+                None => self.release_recovered(&info),
+            };
+
+            self.object_removed(identifier, &info.name, SourceCategoryType::APPLICATION);
+            self.persist(&state)?;
+            if !messages.is_empty() {
+                return Err(DomainManagerError::ReleaseError { messages });
+            }
+            Ok(())
+        })
+    }
+
+    /// Starts the components of a running application.
+    pub fn start_application(&self, identifier: &str) -> Result<()> {
+        let operation = || DomainOperation::StartApplication {
+            identifier: identifier.to_string(),
+        };
+        self.recorded(operation, || {
+            self.with_running(identifier, |application| {
+                application
+                    .start()
+                    .map_err(|source| DomainManagerError::ApplicationControlError { source })
+            })
+        })
+    }
+
+    /// Stops the components of a running application.
+    pub fn stop_application(&self, identifier: &str) -> Result<()> {
+        let operation = || DomainOperation::StopApplication {
+            identifier: identifier.to_string(),
+        };
+        self.recorded(operation, || {
+            self.with_running(identifier, |application| {
+                application
+                    .stop()
+                    .map_err(|source| DomainManagerError::ApplicationControlError { source })
+            })
+        })
+    }
+
+    /**
+     * Sets properties of a running application, or of a component of one
+     * by component identifier.
+     */
+    pub fn configure_application(&self, identifier: &str, properties: &Properties) -> Result<()> {
+        let operation = || DomainOperation::ConfigureApplication {
+            identifier: identifier.to_string(),
+            properties: properties.clone(),
+        };
+        self.recorded(operation, || {
+            self.with_properties(identifier, |target| match target {
+                PropertyTarget::Application(application) => application.configure(properties),
+                PropertyTarget::Component(component) => {
+                    let resource = component.resource.as_ref().unwrap();
+                    resource.lock().unwrap().configure(properties)
+                }
+            })
+        })
+    }
+
+    /**
+     * Returns the values of properties of a running application, or of a
+     * component of one by component identifier, all of them when none is
+     * given.
+     */
+    pub fn query_application(
+        &self,
+        identifier: &str,
+        properties: &Properties,
+    ) -> Result<Properties> {
+        self.with_properties(identifier, |target| match target {
+            PropertyTarget::Application(application) => application.query(properties),
+            PropertyTarget::Component(component) => component.query(properties),
+        })
+    }
+
+    /**
+     * Calls a function with the running application, or else the
+     * component of a running application, of an identifier.
+     */
+    fn with_properties<T>(
+        &self,
+        identifier: &str,
+        function: impl FnOnce(PropertyTarget) -> resource::Result<T>,
+    ) -> Result<T> {
+        let state = self.state.lock().unwrap();
+        let target = match state.running.iter().find(|a| a.identifier() == identifier) {
+            Some(application) => PropertyTarget::Application(application),
+            None => state
+                .running
+                .iter()
+                .find_map(|a| a.registered_component(identifier))
+                .map(PropertyTarget::Component)
+                .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                    identifier: identifier.to_string(),
+                })?,
+        };
+        function(target).map_err(|source| DomainManagerError::ApplicationPropertiesError { source })
+    }
+
+    /**
+     * Calls a function with an application created during the current
+     * run, the restored ones having no components to operate.
+     */
+    fn with_running<T>(
+        &self,
+        identifier: &str,
+        function: impl FnOnce(&mut Application) -> Result<T>,
+    ) -> Result<T> {
+        let mut state = self.state.lock().unwrap();
+        let application = state
+            .running
+            .iter_mut()
+            .find(|a| a.identifier() == identifier)
+            .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                identifier: identifier.to_string(),
+            })?;
+        function(application)
+    }
+
+    /**
+     * Collects the resource usage of the components of a running
+     * application from the devices executing them.
+     */
+    pub fn application_metrics(&self, identifier: &str) -> Result<ApplicationMetrics> {
+        let state = self.state.lock().unwrap();
+        state
+            .running
+            .iter()
+            .find(|a| a.identifier() == identifier)
+            .map(Application::metrics)
+            .ok_or_else(|| DomainManagerError::InvalidIdentifier {
+                identifier: identifier.to_string(),
+            })
+    }
+
+    /**
+     * Returns the capacities of the registered devices, or of the devices
+     * of the AllocationManager of the deployment context set in their
+     * place.
+     */
+    pub fn device_capacities(&self) -> Vec<DeviceCapacities> {
+        self.deployment()
+            .allocation_manager()
+            .lock()
+            .unwrap()
+            .device_capacities()
+    }
+
+    /**
+     * Returns the outstanding allocations on the registered devices, or
+     * on the devices of the deployment context set in their place, those
+     * on a vanished device being marked failed.
+     */
+    pub fn allocations(&self) -> Vec<AllocationStatus> {
+        self.deployment()
+            .allocation_manager()
+            .lock()
+            .unwrap()
+            .list_allocations()
+    }
+
+    /**
+     * Tears down an application restored from the state file, going on
+     * past the failing steps. The components still registered are
+     * released first.
+     */
+    fn release_recovered(&self, info: &ApplicationInfo) -> Vec<String> {
+        let deployment = self.deployment();
+        let mut messages = Vec::new();
+
+        for component in info.components.iter().rev() {
+            if let Some(resource) = deployment
+                .registry()
+                .unregister_component(&component.name_binding)
+            {
+                if let Err(e) = resource.lock().unwrap().release_object() {
+                    messages.push(e.to_string());
+                }
+            }
+
+            let Some(device) = deployment.device(&component.device_id) else {
+                messages.push(format!(
+                    "device '{}' of '{}' not found",
+                    component.device_id, component.identifier
+                ));
+                continue;
+            };
+            let mut device = device.lock().unwrap();
+            if let Some(process_id) = component.process_id {
+                if let Err(e) = device.terminate(process_id) {
+                    messages.push(e.to_string());
+                }
+            }
+            if let Err(e) = device.unload(&component.loaded_file) {
+                messages.push(e.to_string());
+            }
+        }
+
+        //give the capacities back to the devices themselves
+        for allocation in info
+            .allocations
+            .iter()
+            .filter(|a| !a.allocated_capacities.is_empty())
+        {
+            match deployment.device(&allocation.allocated_device) {
+                Some(device) => {
+                    if let Err(e) = device
+                        .lock()
+                        .unwrap()
+                        .deallocate_capacity(&allocation.allocated_capacities)
+                    {
+                        messages.push(e.to_string());
+                    }
+                }
+                None => messages.push(format!(
+                    "device '{}' of allocation '{}' not found",
+                    allocation.allocated_device, allocation.allocation_id
+                )),
+            }
+        }
+        messages
+    }
+
+    /**
+     * Reconciles the restored state with the nodes: the DeviceManagers
+     * not answering are unregistered along with their devices, the
+     * devices are replaced by the ones registered with their
+     * DeviceManager, and the RECOVERED applications having components on
+     * a vanished device are marked DEAD.
+     */
+    pub async fn reconcile(&self) -> Result<()> {
+        for device_manager in self.device_managers() {
+            let devices = match DeviceManagerClient::connect(device_manager.endpoint.clone()).await
+            {
+                Ok(mut client) => client
+                    .registered_devices(RegisteredDevicesRequest {})
+                    .await
+                    .ok()
+                    .map(|r| r.into_inner().devices),
+                Err(_) => None,
+            };
+            let Some(devices) = devices else {
+                self.unregister_device_manager(&device_manager.identifier)?;
+                continue;
+            };
+
+            let mut state = self.state.lock().unwrap();
+            for vanished in state.devices.iter().filter(|d| {
+                d.device_manager_id == device_manager.identifier
+                    && !devices.iter().any(|r| r.identifier == d.identifier)
+            }) {
+                self.connection_manager
+                    .unregister_object(&device_object(&vanished.identifier));
+                self.remove_device(&vanished.identifier);
+            }
+            let devices: Vec<DomainDevice> = devices
+                .into_iter()
+                .map(|d| DomainDevice {
+                    device_manager_id: device_manager.identifier.clone(),
+                    identifier: d.identifier,
+                    label: d.label,
+                    profile_name: d.profile_name,
+                    endpoint: d.endpoint,
+                })
+                .collect();
+            for device in &devices {
+                self.connection_manager
+                    .register_object(device_object(&device.identifier), &device.endpoint);
+                self.add_device(device)?;
+            }
+            state
+                .devices
+                .retain(|d| d.device_manager_id != device_manager.identifier);
+            state.devices.extend(devices);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let DomainState {
+            devices,
+            applications,
+            ..
+        } = &mut *state;
+        for application in applications
+            .iter_mut()
+            .filter(|a| a.status == ApplicationStatus::RECOVERED)
+        {
+            if application
+                .components
+                .iter()
+                .any(|c| !devices.iter().any(|d| d.identifier == c.device_id))
+            {
+                application.status = ApplicationStatus::DEAD;
+            }
+        }
+        self.persist(&state)
+    }
+
+    /**
+     * Sends a heartbeat to every registered DeviceManager, marking
+     * unavailable those not answering for the timeout of the heartbeat
+     * policy, and available again those answering. The changes of
+     * availability of the DeviceManagers and of their devices are
+     * published on the ODM channel.
+     */
+    pub async fn heartbeat(&self) {
+        let Some(policy) = self.heartbeat else {
+            return;
+        };
+
+        for device_manager in self.device_managers() {
+            let answered = tokio::time::timeout(policy.interval, async {
+                let mut client = DeviceManagerClient::connect(device_manager.endpoint.clone())
+                    .await
+                    .ok()?;
+                client.heartbeat(HeartbeatRequest {}).await.ok()
+            })
+            .await;
+
+            let mut state = self.state.lock().unwrap();
+            if matches!(answered, Ok(Some(_))) {
+                self.heard(&mut state, &device_manager.identifier);
+            } else if state
+                .last_heard
+                .get(&device_manager.identifier)
+                .is_some_and(|heard| heard.elapsed() >= policy.timeout)
+                && !state.unavailable.contains(&device_manager.identifier)
+            {
+                state.unavailable.push(device_manager.identifier.clone());
+                self.devices_available(&state, &device_manager.identifier, false);
+                self.availability_changed(&state, &device_manager.identifier, false);
+            }
+        }
+    }
+
+    /// Records a DeviceManager answered, marking it available again.
+    fn heard(&self, state: &mut DomainState, identifier: &str) {
+        state
+            .last_heard
+            .insert(identifier.to_string(), Instant::now());
+        if let Some(index) = state.unavailable.iter().position(|id| id == identifier) {
+            state.unavailable.remove(index);
+            self.devices_available(state, identifier, true);
+            self.availability_changed(state, identifier, true);
+        }
+    }
+
+    /**
+     * Withdraws the devices of a DeviceManager from the allocations and
+     * the deployments while it is unavailable, or restores them once it
+     * answers again, their outstanding allocations being kept.
+     */
+    fn devices_available(&self, state: &DomainState, identifier: &str, available: bool) {
+        for device in state
+            .devices
+            .iter()
+            .filter(|d| d.device_manager_id == identifier)
+        {
+            self.allocation_manager
+                .lock()
+                .unwrap()
+                .set_available(&device.identifier, available);
+            self.registered.set_available(&device.identifier, available);
+        }
+    }
+
+    /// Publishes the change of availability of a DeviceManager and its devices on the ODM channel.
+    fn availability_changed(&self, state: &DomainState, identifier: &str, available: bool) {
+        let changed = |source_id: &str, source_name: &str, source_category| {
+            self.odm_channel
+                .push(DomainManagementEvent::AvailabilityChanged {
+                    producer_id: self.identifier.clone(),
+                    source_id: source_id.to_string(),
+                    source_name: source_name.to_string(),
+                    source_category,
+                    available,
+                });
+        };
+
+        for device_manager in state
+            .device_managers
+            .iter()
+            .filter(|d| d.identifier == identifier)
+        {
+            changed(
+                &device_manager.identifier,
+                &device_manager.label,
+                SourceCategoryType::DEVICE_MANAGER,
+            );
+        }
+        for device in state
+            .devices
+            .iter()
+            .filter(|d| d.device_manager_id == identifier)
+        {
+            changed(
+                &device.identifier,
+                &device.label,
+                SourceCategoryType::DEVICE,
+            );
+        }
+    }
+
+    /// Writes the domain state to the state file, when persistence is enabled.
+    fn persist(&self, state: &DomainState) -> Result<()> {
+        let Some(state_file) = &self.state_file else {
+            return Ok(());
+        };
+        let persisted = PersistentState {
+            device_managers: state.device_managers.clone(),
+            devices: state.devices.clone(),
+            services: state.services.clone(),
+            remote_domain_managers: state.remote_domain_managers.clone(),
+            application_profiles: state
+                .application_factories
+                .iter()
+                .map(|f| f.software_profile().to_string())
+                .chain(state.pending_profiles.iter().cloned())
+                .collect(),
+            applications: state.applications.clone(),
+        };
+
+        //replace the state file atomically
+        let temp = state_file.with_extension(TEMP_SUFFIX);
+        serde_json::to_vec_pretty(&persisted)
+            .map_err(|e| e.to_string())
+            .and_then(|state| std::fs::write(&temp, state).map_err(|e| e.to_string()))
+            .and_then(|()| std::fs::rename(&temp, state_file).map_err(|e| e.to_string()))
+            .map_err(|message| DomainManagerError::PersistenceError {
+                message: format!("'{}': {message}", state_file.display()),
+            })
+    }
+
+    /// Publishes the addition of an object to the domain on the ODM channel.
+    fn object_added(
+        &self,
+        source_id: &str,
+        source_name: &str,
+        source_category: SourceCategoryType,
+    ) {
+        self.odm_channel.push(DomainManagementEvent::ObjectAdded {
+            producer_id: self.identifier.clone(),
+            source_id: source_id.to_string(),
+            source_name: source_name.to_string(),
+            source_category,
+        });
+    }
+
+    /// Publishes the removal of an object from the domain on the ODM channel.
+    fn object_removed(
+        &self,
+        source_id: &str,
+        source_name: &str,
+        source_category: SourceCategoryType,
+    ) {
+        self.odm_channel.push(DomainManagementEvent::ObjectRemoved {
+            producer_id: self.identifier.clone(),
+            source_id: source_id.to_string(),
+            source_name: source_name.to_string(),
+            source_category,
+        });
+    }
+
+    /// Asks the running DomainManager to shut down.
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+
+    /**
+     * Releases the domain objects before the DomainManager exits: the
+     * running applications are released, then the registered
+     * DeviceManagers are asked to shut their devices and services down.
+     * They are shut down concurrently, those neither unregistering nor
+     * going silent within the SHUTDOWN_TIMEOUT being unregistered, their
+     * file systems unmounted, and the final state persisted. Goes on past the failing steps.
+     */
+    async fn release_domain(&self) -> Result<()> {
+        let mut messages = Vec::new();
+        for application in self.applications() {
+            let manager = self.clone();
+            let released = self
+                .blocking_pool
+                .run(move || manager.release_application(&application.identifier))
+                .await;
+            match released {
+                Ok(Ok(())) => {}
+                Ok(Err(DomainManagerError::ReleaseError { messages: failed })) => {
+                    messages.extend(failed)
+                }
+                Ok(Err(e)) => messages.push(e.to_string()),
+                Err(e) => messages.push(e.to_string()),
+            }
+        }
+
+        //the DeviceManagers unregister once their devices are released,
+        //all of them being shut down at once under the same deadline
+        let deadline = tokio::time::Instant::now() + SHUTDOWN_TIMEOUT;
+        let mut shutdowns = tokio::task::JoinSet::new();
+        for device_manager in self.device_managers() {
+            let manager = self.clone();
+            shutdowns.spawn(tokio::time::timeout_at(deadline, async move {
+                let registered = || {
+                    manager
+                        .device_managers()
+                        .iter()
+                        .any(|d| d.identifier == device_manager.identifier)
+                };
+                let mut client = DeviceManagerClient::connect(device_manager.endpoint.clone())
+                    .await
+                    .ok()?;
+                client
+                    .shutdown(rpc::device_manager::ShutdownRequest {})
+                    .await
+                    .ok()?;
+                while registered() {
+                    tokio::time::sleep(SHUTDOWN_POLL_INTERVAL).await;
+                    client.heartbeat(HeartbeatRequest {}).await.ok()?;
+                }
+                Some(())
+            }));
+        }
+        while shutdowns.join_next().await.is_some() {}
+        for device_manager in self.device_managers() {
+            if let Err(e) = self.unregister_device_manager(&device_manager.identifier) {
+                messages.push(e.to_string());
+            }
+        }
+
+        if let Err(e) = self.persist(&self.state.lock().unwrap()) {
+            messages.push(e.to_string());
+        }
+        if !messages.is_empty() {
+            return Err(DomainManagerError::ReleaseError { messages });
+        }
+        Ok(())
+    }
+
+    /**
+     * Serves the DomainManager, Registrar, EventChannelManager and
+     * FileSystem services on the listener until shut down, the
+     * FileSystem one serving the domain FileManager. The DomainManager
+     * and FileSystem services are also served over grpc-web, for the
+     * browser clients of scars-web. On shutdown the
+     * domain objects are released while still serving, the
+     * DeviceManagers unregistering through the service, then the event
+     * subscriptions are closed.
+     */
+    pub async fn run(self, listener: TcpListener) -> Result<()> {
+        let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|e| {
+            DomainManagerError::RegisterError {
+                message: e.to_string(),
+            }
+        })?;
+
+        //heartbeat the DeviceManagers while serving
+        let heartbeat = self.heartbeat.map(|policy| {
+            let manager = self.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(policy.interval);
+                loop {
+                    interval.tick().await;
+                    manager.heartbeat().await;
+                }
+            })
+        });
+
+        let stopped = Arc::new(Notify::new());
+        let subscriptions: Subscriptions = Arc::default();
+        let closed = subscriptions.clone();
+        let event_service = EventChannelService::new(self.event_channel_manager.clone());
+        let bridged = event_service.clone();
+        let server = tokio::spawn({
+            let stopped = stopped.clone();
+            Server::builder()
+                .accept_http1(true)
+                .add_service(tonic_web::enable(DomainManagerServer::new(
+                    DomainManagerService {
+                        manager: self.clone(),
+                        subscriptions,
+                    },
+                )))
+                .add_service(RegistrarServer::new(
+                    RegistrarService::new(self.registry.clone())
+                        .with_blocking_pool(self.blocking_pool.clone()),
+                ))
+                .add_service(EventChannelManagerServer::new(event_service))
+                .add_service(tonic_web::enable(FileSystemServer::new(
+                    FileSystemService::from_file_manager(self.file_manager.clone())
+                        .with_blocking_pool(self.blocking_pool.clone()),
+                )))
+                .serve_with_incoming_shutdown(incoming, async move {
+                    stopped.notified().await;
+                    closed.lock().unwrap().drain(..).for_each(|close| close());
+                    bridged.close_subscriptions();
+                })
+        });
+
+        self.shutdown.notified().await;
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.abort();
+        }
+        let released = self.release_domain().await;
+
+        stopped.notify_one();
+        server
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|served| served.map_err(|e| e.to_string()))
+            .map_err(|message| DomainManagerError::RegisterError { message })?;
+        released
+    }
+}
+
+/// Returns the error of a request to a peer domain.
+fn remote_error(identifier: &str, message: impl Into<String>) -> DomainManagerError {
+    DomainManagerError::RemoteDomainError {
+        identifier: identifier.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Returns the ConnectionManager object of a device.
+fn device_object(identifier: &str) -> EndpointResolution {
+    EndpointResolution::DEVICE(identifier.to_string())
+}
+
+/// Returns the ConnectionManager object of a service.
+fn service_object(name: &str) -> EndpointResolution {
+    EndpointResolution::SERVICE(name.to_string())
+}
+
+/**
+ * Returns an IDM channel forwarding the administrative state changes of
+ * the devices to the ODM channel.
+ */
+fn idm_channel(
+    identifier: &str,
+    odm_channel: &EventChannel<DomainManagementEvent>,
+) -> EventChannel<StateChangeEvent> {
+    let idm_channel = EventChannel::new(IDM_CHANNEL_NAME)
+        .with_history(EVENT_HISTORY_SIZE)
+        .with_queue_size(SUBSCRIBER_QUEUE_SIZE);
+    let (identifier, odm_channel) = (identifier.to_string(), odm_channel.clone());
+    idm_channel.connect(move |event: &StateChangeEvent| {
+        if event.state_change_category == StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT {
+            odm_channel.push(DomainManagementEvent::AdministrativeStateChanged {
+                producer_id: identifier.clone(),
+                source_id: event.source_id.clone(),
+                state_change_from: event.state_change_from,
+                state_change_to: event.state_change_to,
+            });
+        }
+        true
+    });
+    idm_channel
+}
+
+/**
+ * Returns a manager of the ODM, IDM and LOG channels, bridged to the
+ * remote tools, a replaced ODM channel being managed under its own name.
+ */
+fn event_channel_manager(
+    odm_channel: &EventChannel<DomainManagementEvent>,
+    idm_channel: &EventChannel<StateChangeEvent>,
+    log_channel: &EventChannel<LogRecord>,
+) -> EventChannelManager {
+    let manager = EventChannelManager::new();
+    let _ = manager.add_remote(odm_channel.clone());
+    let _ = manager.add_remote(idm_channel.clone());
+    let _ = manager.add_remote(log_channel.clone());
+    manager
+}
+
+/**
+ * The closers of the event streams handed to the gRPC subscribers, the
+ * server waiting for the streams to end before shutting down.
+ */
+type Subscriptions = Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>;
+
+/**
+ * The wire events streamed with their sequence number on their channel.
+ */
+trait Sequenced {
+    fn with_sequence(self, sequence: u64) -> Self;
+}
+
+impl Sequenced for rpc::domain_manager::DomainManagementEvent {
+    fn with_sequence(self, sequence: u64) -> Self {
+        rpc::domain_manager::DomainManagementEvent { sequence, ..self }
+    }
+}
+
+impl Sequenced for rpc::domain_manager::StateChangeEvent {
+    fn with_sequence(self, sequence: u64) -> Self {
+        rpc::domain_manager::StateChangeEvent { sequence, ..self }
+    }
+}
+
+/**
+ * gRPC DomainManager service of the domain.
+ */
+struct DomainManagerService {
+    manager: DomainManager,
+    subscriptions: Subscriptions,
+}
+
+impl DomainManagerService {
+    /**
+     * Runs an operation of the DomainManager on a thread of its blocking
+     * pool, the profiles being parsed and the components deployed
+     * without holding the runtime worker.
+     */
+    async fn call<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&DomainManager) -> Result<R> + Send + 'static,
+    ) -> Result<R, Status> {
+        let manager = self.manager.clone();
+        let result = self
+            .manager
+            .blocking_pool
+            .run(move || operation(&manager))
+            .await?;
+        Ok(result?)
+    }
+
+    /**
+     * Returns a stream of the events pushed on a channel from now on, or
+     * following the last one seen, passing the filter of the request, in
+     * their wire form with their sequence numbers, until the service shuts
+     * down. A full queue is handled after the backpressure policy of the
+     * channel.
+     */
+    fn subscribe<T, W>(
+        &self,
+        channel: &EventChannel<T>,
+        request: SubscribeRequest,
+    ) -> EventStream<Result<W, Status>>
+    where
+        T: Clone + FilteredEvent + Send + 'static,
+        W: for<'a> From<&'a T> + Sequenced + Send + 'static,
+    {
+        let filter = rpc::event_filter_from_wire(&request);
+        self.subscribe_filtered(
+            channel,
+            request.last_sequence,
+            move |event| filter.matches(event),
+            |sequence, event| W::from(event).with_sequence(sequence),
+        )
+    }
+
+    /**
+     * Returns a stream of the events pushed on a channel that pass a
+     * filter, following the last one seen when given, in the wire form
+     * they are encoded to with their sequence numbers.
+     */
+    fn subscribe_filtered<T, W, F, E>(
+        &self,
+        channel: &EventChannel<T>,
+        last_seen: Option<u64>,
+        filter: F,
+        encode: E,
+    ) -> EventStream<Result<W, Status>>
+    where
+        T: Clone + Send + 'static,
+        W: Send + 'static,
+        F: Fn(&T) -> bool + Send + 'static,
+        E: Fn(u64, &T) -> W + Send + 'static,
+    {
+        let subscription = channel.subscribe_filtered(last_seen, filter);
+        self.subscriptions
+            .lock()
+            .unwrap()
+            .push(Box::new(subscription.closer()));
+        Box::pin(
+            subscription
+                .map(move |(sequence, event)| encode(sequence, &event))
+                .map(Ok),
+        )
+    }
+}
+
+#[tonic::async_trait]
+impl domain_manager_server::DomainManager for DomainManagerService {
+    async fn register_device_manager(
+        &self,
+        request: Request<RegisterDeviceManagerRequest>,
+    ) -> Result<Response<RegisterDeviceManagerReply>, Status> {
+        let r = request.into_inner();
+        self.manager
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: r.identifier,
+                label: r.label,
+                endpoint: r.endpoint,
+            })?;
+        Ok(Response::new(RegisterDeviceManagerReply {}))
+    }
+
+    async fn unregister_device_manager(
+        &self,
+        request: Request<UnregisterDeviceManagerRequest>,
+    ) -> Result<Response<UnregisterDeviceManagerReply>, Status> {
+        self.manager
+            .unregister_device_manager(&request.into_inner().identifier)?;
+        Ok(Response::new(UnregisterDeviceManagerReply {}))
+    }
+
+    async fn register_device(
+        &self,
+        request: Request<RegisterDeviceRequest>,
+    ) -> Result<Response<RegisterDeviceReply>, Status> {
+        let r = request.into_inner();
+        self.manager.register_device(DomainDevice {
+            device_manager_id: r.device_manager_id,
+            identifier: r.identifier,
+            label: r.label,
+            profile_name: r.profile_name,
+            endpoint: r.endpoint,
+        })?;
+        Ok(Response::new(RegisterDeviceReply {}))
+    }
+
+    async fn unregister_device(
+        &self,
+        request: Request<UnregisterDeviceRequest>,
+    ) -> Result<Response<UnregisterDeviceReply>, Status> {
+        self.manager
+            .unregister_device(&request.into_inner().identifier)?;
+        Ok(Response::new(UnregisterDeviceReply {}))
+    }
+
+    async fn register_service(
+        &self,
+        request: Request<RegisterServiceRequest>,
+    ) -> Result<Response<RegisterServiceReply>, Status> {
+        let r = request.into_inner();
+        self.manager.register_service(RegisteredService {
+            device_manager_id: r.device_manager_id,
+            name: r.name,
+            endpoint: r.endpoint,
+        })?;
+        Ok(Response::new(RegisterServiceReply {}))
+    }
+
+    async fn unregister_service(
+        &self,
+        request: Request<UnregisterServiceRequest>,
+    ) -> Result<Response<UnregisterServiceReply>, Status> {
+        self.manager
+            .unregister_service(&request.into_inner().name)?;
+        Ok(Response::new(UnregisterServiceReply {}))
+    }
+
+    async fn device_managers(
+        &self,
+        _request: Request<DeviceManagersRequest>,
+    ) -> Result<Response<DeviceManagersReply>, Status> {
+        let state = self.manager.state.lock().unwrap();
+        let device_managers = state
+            .device_managers
+            .iter()
+            .map(|dm| rpc::domain_manager::DeviceManager {
+                device_manager: Some(RegisterDeviceManagerRequest {
+                    identifier: dm.identifier.clone(),
+                    label: dm.label.clone(),
+                    endpoint: dm.endpoint.clone(),
+                }),
+                devices: state
+                    .devices
+                    .iter()
+                    .filter(|d| d.device_manager_id == dm.identifier)
+                    .map(|d| RegisterDeviceRequest {
+                        device_manager_id: d.device_manager_id.clone(),
+                        identifier: d.identifier.clone(),
+                        label: d.label.clone(),
+                        profile_name: d.profile_name.clone(),
+                        endpoint: d.endpoint.clone(),
+                    })
+                    .collect(),
+                services: state
+                    .services
+                    .iter()
+                    .filter(|s| s.device_manager_id == dm.identifier)
+                    .map(|s| RegisterServiceRequest {
+                        device_manager_id: s.device_manager_id.clone(),
+                        name: s.name.clone(),
+                        endpoint: s.endpoint.clone(),
+                    })
+                    .collect(),
+                available: !state.unavailable.contains(&dm.identifier),
+            })
+            .collect();
+        Ok(Response::new(DeviceManagersReply { device_managers }))
+    }
+
+    async fn applications(
+        &self,
+        _request: Request<ApplicationsRequest>,
+    ) -> Result<Response<ApplicationsReply>, Status> {
+        let state = self.manager.state.lock().unwrap();
+        let applications = state
+            .applications
+            .iter()
+            .map(|a| rpc::domain_manager::ApplicationInfo {
+                identifier: a.identifier.clone(),
+                name: a.name.clone(),
+                profile: a.profile.clone(),
+                started: state
+                    .running
+                    .iter()
+                    .any(|r| r.identifier() == a.identifier && r.started()),
+            })
+            .collect();
+        Ok(Response::new(ApplicationsReply { applications }))
+    }
+
+    async fn application_factories(
+        &self,
+        _request: Request<ApplicationFactoriesRequest>,
+    ) -> Result<Response<ApplicationFactoriesReply>, Status> {
+        let application_factories = self
+            .call(|manager| Ok(manager.application_factories()))
+            .await?
+            .into_iter()
+            .map(|f| rpc::domain_manager::ApplicationFactoryInfo {
+                identifier: f.identifier().to_string(),
+                name: f.name().to_string(),
+                software_profile: f.software_profile().to_string(),
+            })
+            .collect();
+        Ok(Response::new(ApplicationFactoriesReply {
+            application_factories,
+        }))
+    }
+
+    async fn install_application(
+        &self,
+        request: Request<InstallApplicationRequest>,
+    ) -> Result<Response<InstallApplicationReply>, Status> {
+        let profile_file_name = request.into_inner().profile_file_name;
+        let identifier = self
+            .call(move |manager| manager.install_application(&profile_file_name))
+            .await?;
+        Ok(Response::new(InstallApplicationReply { identifier }))
+    }
+
+    async fn uninstall_application(
+        &self,
+        request: Request<UninstallApplicationRequest>,
+    ) -> Result<Response<UninstallApplicationReply>, Status> {
+        let identifier = request.into_inner().identifier;
+        self.call(move |manager| manager.uninstall_application(&identifier))
+            .await?;
+        Ok(Response::new(UninstallApplicationReply {}))
+    }
+
+    async fn create_application(
+        &self,
+        request: Request<CreateApplicationRequest>,
+    ) -> Result<Response<CreateApplicationReply>, Status> {
+        let r = request.into_inner();
+        let init_configuration = rpc::properties_from_wire(&r.init_configuration)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let device_assignments: Vec<DeviceAssignmentType> = r
+            .device_assignments
+            .into_iter()
+            .map(|a| DeviceAssignmentType {
+                component_id: a.component_id,
+                assigned_device_id: a.assigned_device_id,
+            })
+            .collect();
+
+        let identifier = self
+            .call(move |manager| {
+                manager.create_application(
+                    &r.factory_identifier,
+                    &r.name,
+                    &init_configuration,
+                    &device_assignments,
+                )
+            })
+            .await?;
+        Ok(Response::new(CreateApplicationReply { identifier }))
+    }
+
+    async fn release_application(
+        &self,
+        request: Request<ReleaseApplicationRequest>,
+    ) -> Result<Response<ReleaseApplicationReply>, Status> {
+        let identifier = request.into_inner().identifier;
+        self.call(move |manager| manager.release_application(&identifier))
+            .await?;
+        Ok(Response::new(ReleaseApplicationReply {}))
+    }
+
+    async fn start_application(
+        &self,
+        request: Request<StartApplicationRequest>,
+    ) -> Result<Response<StartApplicationReply>, Status> {
+        let identifier = request.into_inner().identifier;
+        self.call(move |manager| manager.start_application(&identifier))
+            .await?;
+        Ok(Response::new(StartApplicationReply {}))
+    }
+
+    async fn stop_application(
+        &self,
+        request: Request<StopApplicationRequest>,
+    ) -> Result<Response<StopApplicationReply>, Status> {
+        let identifier = request.into_inner().identifier;
+        self.call(move |manager| manager.stop_application(&identifier))
+            .await?;
+        Ok(Response::new(StopApplicationReply {}))
+    }
+
+    async fn configure_application(
+        &self,
+        request: Request<ConfigureApplicationRequest>,
+    ) -> Result<Response<ConfigureApplicationReply>, Status> {
+        let r = request.into_inner();
+        let properties = rpc::properties_from_wire(&r.properties)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.call(move |manager| manager.configure_application(&r.identifier, &properties))
+            .await?;
+        Ok(Response::new(ConfigureApplicationReply {}))
+    }
+
+    /// The values of the queried properties are ignored.
+    async fn query_application(
+        &self,
+        request: Request<QueryApplicationRequest>,
+    ) -> Result<Response<QueryApplicationReply>, Status> {
+        let r = request.into_inner();
+        let properties: Properties = r
+            .properties
+            .iter()
+            .map(|p| DataType::new(&p.id, AnyValue::String(String::new())))
+            .collect();
+        let values = self
+            .call(move |manager| manager.query_application(&r.identifier, &properties))
+            .await?;
+        Ok(Response::new(QueryApplicationReply {
+            properties: rpc::properties_to_wire(&values),
+        }))
+    }
+
+    /// The domain objects are released once replied.
+    async fn shutdown(
+        &self,
+        _request: Request<ShutdownRequest>,
+    ) -> Result<Response<ShutdownReply>, Status> {
+        self.manager.shutdown();
+        Ok(Response::new(ShutdownReply {}))
+    }
+
+    async fn register_remote_domain_manager(
+        &self,
+        request: Request<RegisterRemoteDomainManagerRequest>,
+    ) -> Result<Response<RegisterRemoteDomainManagerReply>, Status> {
+        let r = request.into_inner();
+        self.manager
+            .register_remote_domain_manager(RemoteDomainManager {
+                identifier: r.identifier,
+                label: r.label,
+                endpoint: r.endpoint,
+            })?;
+        Ok(Response::new(RegisterRemoteDomainManagerReply {}))
+    }
+
+    async fn unregister_remote_domain_manager(
+        &self,
+        request: Request<UnregisterRemoteDomainManagerRequest>,
+    ) -> Result<Response<UnregisterRemoteDomainManagerReply>, Status> {
+        self.manager
+            .unregister_remote_domain_manager(&request.into_inner().identifier)?;
+        Ok(Response::new(UnregisterRemoteDomainManagerReply {}))
+    }
+
+    async fn remote_domain_managers(
+        &self,
+        _request: Request<RemoteDomainManagersRequest>,
+    ) -> Result<Response<RemoteDomainManagersReply>, Status> {
+        let remote_domain_managers = self
+            .manager
+            .remote_domain_managers()
+            .into_iter()
+            .map(|r| RegisterRemoteDomainManagerRequest {
+                identifier: r.identifier,
+                label: r.label,
+                endpoint: r.endpoint,
+            })
+            .collect();
+        Ok(Response::new(RemoteDomainManagersReply {
+            remote_domain_managers,
+        }))
+    }
+
+    async fn push_state_change_event(
+        &self,
+        request: Request<rpc::domain_manager::StateChangeEvent>,
+    ) -> Result<Response<PushStateChangeEventReply>, Status> {
+        let event = rpc::state_change_event_from_wire(request.into_inner())
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        self.manager.idm_channel.push(event);
+        Ok(Response::new(PushStateChangeEventReply {}))
+    }
+
+    type subscribe_odm_eventsStream =
+        EventStream<Result<rpc::domain_manager::DomainManagementEvent, Status>>;
+
+    async fn subscribe_odm_events(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::subscribe_odm_eventsStream>, Status> {
+        let events = self.subscribe(&self.manager.odm_channel, request.into_inner());
+        Ok(Response::new(events))
+    }
+
+    type subscribe_idm_eventsStream =
+        EventStream<Result<rpc::domain_manager::StateChangeEvent, Status>>;
+
+    async fn subscribe_idm_events(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::subscribe_idm_eventsStream>, Status> {
+        let events = self.subscribe(&self.manager.idm_channel, request.into_inner());
+        Ok(Response::new(events))
+    }
+
+    /// The records with an unknown level are refused.
+    async fn push_log_records(
+        &self,
+        request: Request<PushLogRecordsRequest>,
+    ) -> Result<Response<PushLogRecordsReply>, Status> {
+        let records: Vec<LogRecord> = request
+            .into_inner()
+            .records
+            .into_iter()
+            .map(rpc::log_record_from_wire)
+            .collect::<Option<_>>()
+            .ok_or_else(|| Status::invalid_argument("unknown log level"))?;
+        for record in records {
+            self.manager.log_channel.push(record);
+        }
+        Ok(Response::new(PushLogRecordsReply {}))
+    }
+
+    type subscribe_log_recordsStream = EventStream<Result<rpc::domain_manager::LogRecord, Status>>;
+
+    /**
+     * The records are filtered after the level and the producers requested,
+     * the retained ones first when requested, the stream ending after them
+     * when they are the only ones requested.
+     */
+    async fn subscribe_log_records(
+        &self,
+        request: Request<SubscribeLogRecordsRequest>,
+    ) -> Result<Response<Self::subscribe_log_recordsStream>, Status> {
+        let request = request.into_inner();
+        let (retained, retained_only) = (request.retained, request.retained_only);
+        let filter = rpc::log_filter_from_wire(request)
+            .ok_or_else(|| Status::invalid_argument("unknown log level"))?;
+        if retained_only {
+            let records: Vec<rpc::domain_manager::LogRecord> = self
+                .manager
+                .log_channel
+                .retained()
+                .iter()
+                .filter(|(_, record)| filter.matches(record))
+                .map(|(_, record)| record.into())
+                .collect();
+            return Ok(Response::new(Box::pin(tokio_stream::iter(records).map(Ok))));
+        }
+        Ok(Response::new(self.subscribe_filtered(
+            &self.manager.log_channel,
+            retained.then_some(0),
+            move |record| filter.matches(record),
+            |_, record| record.into(),
+        )))
+    }
+
+    async fn connect_endpoints(
+        &self,
+        request: Request<ConnectEndpointsRequest>,
+    ) -> Result<Response<ConnectEndpointsReply>, Status> {
+        let r = request.into_inner();
+        let (Some(uses_endpoint), Some(provides_endpoint)) = (
+            rpc::endpoint_request_from_wire(r.uses_endpoint),
+            rpc::endpoint_request_from_wire(r.provides_endpoint),
+        ) else {
+            return Err(Status::invalid_argument(
+                "an endpoint misses its resolution",
+            ));
+        };
+        let connection_record_id = self.manager.connection_manager.connect(
+            uses_endpoint,
+            provides_endpoint,
+            &r.requester_id,
+            &r.connection_id,
+        )?;
+        Ok(Response::new(ConnectEndpointsReply {
+            connection_record_id,
+        }))
+    }
+
+    async fn disconnect_endpoints(
+        &self,
+        request: Request<DisconnectEndpointsRequest>,
+    ) -> Result<Response<DisconnectEndpointsReply>, Status> {
+        self.manager
+            .connection_manager
+            .disconnect(&request.into_inner().connection_record_id)?;
+        Ok(Response::new(DisconnectEndpointsReply {}))
+    }
+
+    async fn list_connections(
+        &self,
+        _request: Request<ListConnectionsRequest>,
+    ) -> Result<Response<ListConnectionsReply>, Status> {
+        let connections = self
+            .manager
+            .connection_manager
+            .connections()
+            .iter()
+            .map(Into::into)
+            .collect();
+        Ok(Response::new(ListConnectionsReply { connections }))
+    }
+
+    async fn application_metrics(
+        &self,
+        request: Request<ApplicationMetricsRequest>,
+    ) -> Result<Response<ApplicationMetricsReply>, Status> {
+        let metrics = self
+            .manager
+            .application_metrics(&request.into_inner().identifier)?;
+        Ok(Response::new((&metrics).into()))
+    }
+
+    async fn device_capacities(
+        &self,
+        _request: Request<DeviceCapacitiesRequest>,
+    ) -> Result<Response<DeviceCapacitiesReply>, Status> {
+        let devices = self.call(|manager| Ok(manager.device_capacities())).await?;
+        Ok(Response::new(DeviceCapacitiesReply {
+            devices: devices.iter().map(Into::into).collect(),
+        }))
+    }
+}
@@ -0,0 +1,453 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::allocation_manager::{
+    self, AllocationManagerRef, AllocationManagerTrait, AllocationRequest, AllocationResponse,
+    AllocationStatus, CapacityModel,
+};
+use super::application_factory::DeviceAssignmentType;
+use super::common_types::Properties;
+use super::device::DeviceRef;
+use super::domain_manager::DomainManager;
+
+/**
+ * Convienence enum definition that includes all recording errors.
+ */
+#[derive(Error, Debug)]
+pub enum RecordingError {
+    /**
+     * This exception indicates that the recording file cannot be
+     * created, written or read.
+     */
+    #[error("FileError: file: '{file_name}', msg: '{message}'.")]
+    FileError { file_name: String, message: String },
+    /**
+     * This exception indicates that a line of a recording is not a
+     * recorded operation.
+     */
+    #[error("InvalidRecord: file: '{file_name}', line: {line}, msg: '{message}'.")]
+    InvalidRecord {
+        file_name: String,
+        line: usize,
+        message: String,
+    },
+}
+
+/*
+ * Convienence type definition that includes all recording returned errors.
+ */
+pub type Result<T, E = RecordingError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines the domain-level operations recorded: the control
+ * of the applications issued to the DomainManager, and the allocations
+ * made by its AllocationManager while deploying them.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum DomainOperation {
+    InstallApplication {
+        profile_file_name: String,
+    },
+    UninstallApplication {
+        identifier: String,
+    },
+    CreateApplication {
+        factory_identifier: String,
+        name: String,
+        init_configuration: Properties,
+        device_assignments: Vec<DeviceAssignmentType>,
+    },
+    ReleaseApplication {
+        identifier: String,
+    },
+    StartApplication {
+        identifier: String,
+    },
+    StopApplication {
+        identifier: String,
+    },
+    /// The identifier of an application or of one of its components.
+    ConfigureApplication {
+        identifier: String,
+        properties: Properties,
+    },
+    /// The allocation ids made, none when the allocation failed.
+    Allocate {
+        request_ids: Vec<String>,
+        source_ids: Vec<String>,
+        allocation_ids: Vec<String>,
+    },
+    Deallocate {
+        allocation_ids: Vec<String>,
+    },
+}
+
+impl DomainOperation {
+    /**
+     * Tells whether the operation is an allocation, made again by the
+     * replayed operation deploying the application rather than replayed.
+     */
+    pub fn is_allocation(&self) -> bool {
+        matches!(
+            self,
+            DomainOperation::Allocate { .. } | DomainOperation::Deallocate { .. }
+        )
+    }
+}
+
+/**
+ * This type is a recorded operation: the time it was issued at and
+ * the error it failed with, if any.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedOperation {
+    pub time: SystemTime,
+    pub operation: DomainOperation,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    records: Vec<RecordedOperation>,
+    file: Option<File>,
+}
+
+/**
+ * Recorder of the domain-level operations, kept in memory and, when
+ * given a file, appended to it as a JSON object per line as they
+ * complete. The write errors are ignored. Clones share the same
+ * recording.
+ */
+#[derive(Clone, Default)]
+pub struct DomainRecorder {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl DomainRecorder {
+    pub fn new() -> DomainRecorder {
+        DomainRecorder::default()
+    }
+
+    /// Returns a recorder appending to a file, created when missing.
+    pub fn with_file(file_name: &Path) -> Result<DomainRecorder> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file_name)
+            .map_err(|e| file_error(file_name, e))?;
+        let recorder = DomainRecorder::new();
+        recorder.state.lock().unwrap().file = Some(file);
+        Ok(recorder)
+    }
+
+    /// Records an operation issued at a time, completed now.
+    pub fn record(&self, time: SystemTime, operation: DomainOperation, error: Option<String>) {
+        let record = RecordedOperation {
+            time,
+            operation,
+            error,
+        };
+        let mut state = self.state.lock().unwrap();
+        if let Some(file) = &mut state.file {
+            if let Ok(line) = serde_json::to_string(&record) {
+                let _ = writeln!(file, "{line}");
+            }
+        }
+        state.records.push(record);
+    }
+
+    /// Returns the operations recorded, in the order they completed.
+    pub fn records(&self) -> Vec<RecordedOperation> {
+        self.state.lock().unwrap().records.clone()
+    }
+}
+
+impl fmt::Debug for DomainRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("DomainRecorder")
+            .field("records", &state.records.len())
+            .field("file", &state.file.is_some())
+            .finish()
+    }
+}
+
+/// Loads the operations of a recording file.
+pub fn load(file_name: &Path) -> Result<Vec<RecordedOperation>> {
+    let file = File::open(file_name).map_err(|e| file_error(file_name, e))?;
+    let mut records = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| file_error(file_name, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| RecordingError::InvalidRecord {
+            file_name: file_name.display().to_string(),
+            line: index + 1,
+            message: e.to_string(),
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn file_error(file_name: &Path, error: std::io::Error) -> RecordingError {
+    RecordingError::FileError {
+        file_name: file_name.display().to_string(),
+        message: error.to_string(),
+    }
+}
+
+/**
+ * Allocation manager recording the allocations made through another
+ * one, e.g. the AllocationManager of a deployment context.
+ */
+pub struct RecordingAllocationManager {
+    manager: AllocationManagerRef,
+    recorder: DomainRecorder,
+}
+
+impl RecordingAllocationManager {
+    pub fn new(
+        manager: AllocationManagerRef,
+        recorder: DomainRecorder,
+    ) -> RecordingAllocationManager {
+        RecordingAllocationManager { manager, recorder }
+    }
+}
+
+impl AllocationManagerTrait for RecordingAllocationManager {
+    fn allocate(
+        &mut self,
+        requests: &[AllocationRequest],
+    ) -> allocation_manager::Result<Vec<AllocationResponse>> {
+        let time = SystemTime::now();
+        let result = self.manager.lock().unwrap().allocate(requests);
+        let allocation_ids = match &result {
+            Ok(responses) => responses.iter().map(|r| r.allocation_id.clone()).collect(),
+            Err(_) => Vec::new(),
+        };
+        let operation = DomainOperation::Allocate {
+            request_ids: requests.iter().map(|r| r.request_id.clone()).collect(),
+            source_ids: requests.iter().map(|r| r.source_id.clone()).collect(),
+            allocation_ids,
+        };
+        self.recorder.record(
+            time,
+            operation,
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+        result
+    }
+
+    fn deallocate(&mut self, allocation_ids: &[String]) -> allocation_manager::Result<()> {
+        let time = SystemTime::now();
+        let result = self.manager.lock().unwrap().deallocate(allocation_ids);
+        let operation = DomainOperation::Deallocate {
+            allocation_ids: allocation_ids.to_vec(),
+        };
+        self.recorder.record(
+            time,
+            operation,
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+        result
+    }
+
+    fn list_allocations(&self) -> Vec<AllocationStatus> {
+        self.manager.lock().unwrap().list_allocations()
+    }
+
+    fn registered_devices(&self) -> Vec<DeviceRef> {
+        self.manager.lock().unwrap().registered_devices()
+    }
+
+    fn capacity_model(&self) -> CapacityModel {
+        self.manager.lock().unwrap().capacity_model()
+    }
+}
+
+/**
+ * Returns the operations of a recording to replay: the domain-level
+ * ones, in the order they were issued.
+ */
+pub fn replayed_operations(recording: &[RecordedOperation]) -> Vec<&RecordedOperation> {
+    let mut operations: Vec<&RecordedOperation> = recording
+        .iter()
+        .filter(|r| !r.operation.is_allocation())
+        .collect();
+    operations.sort_by_key(|r| r.time);
+    operations
+}
+
+/**
+ * This type defines how the operations are replayed: one after the
+ * other, or each at its recorded offset from the first one, divided by
+ * the speed, on its own thread so that the operations that overlapped
+ * overlap again.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReplayPace {
+    #[default]
+    Sequential,
+    Timed {
+        speed: f64,
+    },
+}
+
+/**
+ * This type reports the replay of an operation: the error it failed
+ * with in the recording and in the replay.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayedOperation {
+    pub operation: DomainOperation,
+    pub recorded_error: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ReplayedOperation {
+    /// Tells whether the operation succeeded in one run and failed in the other.
+    pub fn diverges(&self) -> bool {
+        self.recorded_error.is_some() != self.error.is_some()
+    }
+}
+
+/**
+ * The report of a replay: the outcome of the operations replayed, and
+ * the allocations of the recording and of the replay, those of the
+ * replay being known when the replayed domain records them.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayReport {
+    pub operations: Vec<ReplayedOperation>,
+    pub recorded_allocations: Vec<DomainOperation>,
+    pub allocations: Option<Vec<DomainOperation>>,
+}
+
+impl ReplayReport {
+    /**
+     * Tells whether the replay diverges from the recording: an operation
+     * failing in one run only, or other allocations made.
+     */
+    pub fn diverges(&self) -> bool {
+        self.operations.iter().any(ReplayedOperation::diverges)
+            || self
+                .allocations
+                .as_ref()
+                .is_some_and(|a| *a != self.recorded_allocations)
+    }
+}
+
+/**
+ * Re-issues the domain-level operations of a recording against a
+ * domain, e.g. one deploying on simulated devices, with the files of
+ * the recorded domain mounted. The allocations are made again by the
+ * operations deploying the applications; when the domain records
+ * them, they are compared with the recorded ones.
+ */
+pub fn replay(
+    domain: &DomainManager,
+    recording: &[RecordedOperation],
+    pace: ReplayPace,
+) -> ReplayReport {
+    let operations = replayed_operations(recording);
+    let recorded_before = domain.recorder().map(|r| r.records().len());
+
+    let errors: Vec<Option<String>> = match pace {
+        ReplayPace::Sequential => operations
+            .iter()
+            .map(|r| issue(domain, &r.operation))
+            .collect(),
+        ReplayPace::Timed { speed } => {
+            let start = operations.first().map(|r| r.time);
+            std::thread::scope(|scope| {
+                let threads: Vec<_> = operations
+                    .iter()
+                    .map(|r| {
+                        let offset = start
+                            .and_then(|s| r.time.duration_since(s).ok())
+                            .unwrap_or_default();
+                        let delay = Duration::from_secs_f64(offset.as_secs_f64() / speed);
+                        scope.spawn(move || {
+                            std::thread::sleep(delay);
+                            issue(domain, &r.operation)
+                        })
+                    })
+                    .collect();
+                threads
+                    .into_iter()
+                    .map(|t| t.join().unwrap_or_else(|_| Some("panicked".to_string())))
+                    .collect()
+            })
+        }
+    };
+
+    let allocations = |records: &[RecordedOperation]| -> Vec<DomainOperation> {
+        records
+            .iter()
+            .filter(|r| r.operation.is_allocation())
+            .map(|r| r.operation.clone())
+            .collect()
+    };
+    ReplayReport {
+        operations: operations
+            .iter()
+            .zip(errors)
+            .map(|(r, error)| ReplayedOperation {
+                operation: r.operation.clone(),
+                recorded_error: r.error.clone(),
+                error,
+            })
+            .collect(),
+        recorded_allocations: allocations(recording),
+        allocations: domain
+            .recorder()
+            .zip(recorded_before)
+            .map(|(r, before)| allocations(&r.records()[before..])),
+    }
+}
+
+/// Issues an operation to a domain, returning the error it failed with.
+fn issue(domain: &DomainManager, operation: &DomainOperation) -> Option<String> {
+    let result = match operation {
+        DomainOperation::InstallApplication { profile_file_name } => {
+            domain.install_application(profile_file_name).map(|_| ())
+        }
+        DomainOperation::UninstallApplication { identifier } => {
+            domain.uninstall_application(identifier)
+        }
+        DomainOperation::CreateApplication {
+            factory_identifier,
+            name,
+            init_configuration,
+            device_assignments,
+        } => domain
+            .create_application(
+                factory_identifier,
+                name,
+                init_configuration,
+                device_assignments,
+            )
+            .map(|_| ()),
+        DomainOperation::ReleaseApplication { identifier } => {
+            domain.release_application(identifier)
+        }
+        DomainOperation::StartApplication { identifier } => domain.start_application(identifier),
+        DomainOperation::StopApplication { identifier } => domain.stop_application(identifier),
+        DomainOperation::ConfigureApplication {
+            identifier,
+            properties,
+        } => domain.configure_application(identifier, properties),
+        DomainOperation::Allocate { .. } | DomainOperation::Deallocate { .. } => Ok(()),
+    };
+    result.err().map(|e| e.to_string())
+}
@@ -0,0 +1,128 @@
+use std::sync::{Arc, Mutex};
+
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+
+use super::events::{EventChannelManager, EventChannelManagerError, EventStream};
+use super::rpc;
+use super::rpc::event_channel::event_channel_manager_server;
+use super::rpc::event_channel::{
+    ChannelEvent, ListChannelsReply, ListChannelsRequest, PublishReply, PublishRequest,
+    SubscribeChannelRequest,
+};
+
+/**
+ * The closers of the event streams handed to the subscribers.
+ */
+type Closers = Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>;
+
+/**
+ * gRPC EventChannelManager service bridging the channels of remote
+ * events of a manager to the off-node tools: the tools subscribe to a
+ * channel by name, with a filter, and publish to it, the events being
+ * encoded as JSON documents. Cloned services share the same
+ * subscriptions.
+ */
+#[derive(Clone, Default)]
+pub struct EventChannelService {
+    manager: EventChannelManager,
+    closers: Closers,
+}
+
+impl std::fmt::Debug for EventChannelService {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("EventChannelService")
+            .field("manager", &self.manager)
+            .field("subscriptions", &self.closers.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl EventChannelService {
+    pub fn new(manager: EventChannelManager) -> EventChannelService {
+        EventChannelService {
+            manager,
+            closers: Arc::default(),
+        }
+    }
+
+    /// Returns the manager of the channels bridged.
+    pub fn manager(&self) -> &EventChannelManager {
+        &self.manager
+    }
+
+    /**
+     * Closes the event streams handed to the subscribers, the streams
+     * ending once their queued events are received, e.g. for the server
+     * to shut down.
+     */
+    pub fn close_subscriptions(&self) {
+        self.closers
+            .lock()
+            .unwrap()
+            .drain(..)
+            .for_each(|close| close());
+    }
+}
+
+/// Returns the status of a refused channel request.
+fn channel_status(error: EventChannelManagerError) -> Status {
+    match error {
+        EventChannelManagerError::ChannelDoesNotExist { .. } => {
+            Status::not_found(error.to_string())
+        }
+        EventChannelManagerError::ChannelNotRemote { .. } => {
+            Status::failed_precondition(error.to_string())
+        }
+        _ => Status::invalid_argument(error.to_string()),
+    }
+}
+
+#[tonic::async_trait]
+impl event_channel_manager_server::EventChannelManager for EventChannelService {
+    async fn list_channels(
+        &self,
+        _request: Request<ListChannelsRequest>,
+    ) -> Result<Response<ListChannelsReply>, Status> {
+        Ok(Response::new(ListChannelsReply {
+            names: self.manager.remote_channel_names(),
+        }))
+    }
+
+    type subscribeStream = EventStream<Result<ChannelEvent, Status>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeChannelRequest>,
+    ) -> Result<Response<Self::subscribeStream>, Status> {
+        let r = request.into_inner();
+        let subscription = self
+            .manager
+            .subscribe_remote(
+                &r.channel,
+                r.last_sequence,
+                rpc::channel_filter_from_wire(&r),
+            )
+            .map_err(channel_status)?;
+        self.closers.lock().unwrap().push(subscription.closer);
+        Ok(Response::new(Box::pin(
+            subscription.events.map(ChannelEvent::from).map(Ok),
+        )))
+    }
+
+    /// The events are published in order, up to the first refused one.
+    async fn publish(
+        &self,
+        request: Request<Streaming<PublishRequest>>,
+    ) -> Result<Response<PublishReply>, Status> {
+        let mut requests = request.into_inner();
+        let mut published = 0;
+        while let Some(r) = requests.message().await? {
+            self.manager
+                .publish_remote(&r.channel, &r.payload)
+                .map_err(channel_status)?;
+            published += 1;
+        }
+        Ok(Response::new(PublishReply { published }))
+    }
+}
@@ -0,0 +1,1168 @@
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, SystemTime};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+
+use super::common_types::Properties;
+use super::device::{AdminType, OperationalType, UsageType};
+use super::file_system::wildcard_match;
+
+/// The name of the Incoming Domain Management event channel.
+pub const IDM_CHANNEL_NAME: &str = "IDM_Channel";
+/// The name of the Outgoing Domain Management event channel.
+pub const ODM_CHANNEL_NAME: &str = "ODM_Channel";
+/// The name of the event channel the log records of the domain are published on.
+pub const LOG_CHANNEL_NAME: &str = "LOG_Channel";
+/// The number of events queued for each subscriber of a channel by default.
+pub const DEFAULT_QUEUE_SIZE: usize = 64;
+
+/**
+ * This type defines the category of state change reported by a
+ * StateChangeEvent.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateChangeCategoryType {
+    ADMINISTRATIVE_STATE_EVENT,
+    OPERATIONAL_STATE_EVENT,
+    USAGE_STATE_EVENT,
+}
+
+/**
+ * This type defines the states reported by a StateChangeEvent.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StateChangeType {
+    LOCKED,
+    UNLOCKED,
+    SHUTTING_DOWN,
+    ENABLED,
+    DISABLED,
+    IDLE,
+    ACTIVE,
+    BUSY,
+}
+
+impl From<AdminType> for StateChangeType {
+    fn from(value: AdminType) -> Self {
+        match value {
+            AdminType::LOCKED => StateChangeType::LOCKED,
+            AdminType::SHUTTING_DOWN => StateChangeType::SHUTTING_DOWN,
+            AdminType::UNLOCKED => StateChangeType::UNLOCKED,
+        }
+    }
+}
+
+impl From<OperationalType> for StateChangeType {
+    fn from(value: OperationalType) -> Self {
+        match value {
+            OperationalType::ENABLED => StateChangeType::ENABLED,
+            OperationalType::DISABLED => StateChangeType::DISABLED,
+        }
+    }
+}
+
+impl From<UsageType> for StateChangeType {
+    fn from(value: UsageType) -> Self {
+        match value {
+            UsageType::IDLE => StateChangeType::IDLE,
+            UsageType::ACTIVE => StateChangeType::ACTIVE,
+            UsageType::BUSY => StateChangeType::BUSY,
+        }
+    }
+}
+
+/**
+ * This type is used to notify that a state change has occurred.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChangeEvent {
+    pub producer_id: String,
+    pub source_id: String,
+    pub state_change_category: StateChangeCategoryType,
+    pub state_change_from: StateChangeType,
+    pub state_change_to: StateChangeType,
+}
+
+/**
+ * This type defines the kinds of domain objects reported by the
+ * domain management events.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SourceCategoryType {
+    DEVICE_MANAGER,
+    DEVICE,
+    APPLICATION_FACTORY,
+    APPLICATION,
+    SERVICE,
+    DOMAIN_MANAGER,
+}
+
+/**
+ * This type is used to notify the changes in the domain: objects added
+ * to or removed from the domain, allocations lost along with the device
+ * they were made on, administrative state changes of the devices, and
+ * objects becoming unavailable or available again.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DomainManagementEvent {
+    ObjectAdded {
+        producer_id: String,
+        source_id: String,
+        source_name: String,
+        source_category: SourceCategoryType,
+    },
+    ObjectRemoved {
+        producer_id: String,
+        source_id: String,
+        source_name: String,
+        source_category: SourceCategoryType,
+    },
+    AllocationFailed {
+        producer_id: String,
+        allocation_id: String,
+        /// The requester of the allocation, e.g. the application.
+        source_id: String,
+        allocated_device: String,
+    },
+    AdministrativeStateChanged {
+        producer_id: String,
+        source_id: String,
+        state_change_from: StateChangeType,
+        state_change_to: StateChangeType,
+    },
+    AvailabilityChanged {
+        producer_id: String,
+        source_id: String,
+        source_name: String,
+        source_category: SourceCategoryType,
+        available: bool,
+    },
+}
+
+/**
+ * This type is used to notify that properties of a resource changed
+ * value, e.g. through a configure, with their new values.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropertyChangeEvent {
+    pub producer_id: String,
+    /// The identifier of the resource whose properties changed.
+    pub source_id: String,
+    pub properties: Properties,
+    pub time: SystemTime,
+}
+
+/**
+ * This type is used to notify that a component of an application
+ * terminated on its own, e.g. crashed, on the device executing it.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbnormalComponentTerminationEvent {
+    pub producer_id: String,
+    pub device_id: String,
+    pub component_id: String,
+    pub application_id: String,
+}
+
+/**
+ * This trait is implemented by the events filtered on their source and
+ * their type.
+ */
+pub trait FilteredEvent {
+    /// The identifier of the object the event is about.
+    fn source_id(&self) -> &str;
+
+    /// The name of the type of the event, e.g. "ObjectAdded".
+    fn event_type(&self) -> &str;
+}
+
+/// The ODM events are typed after their variant.
+impl FilteredEvent for DomainManagementEvent {
+    fn source_id(&self) -> &str {
+        match self {
+            DomainManagementEvent::ObjectAdded { source_id, .. }
+            | DomainManagementEvent::ObjectRemoved { source_id, .. }
+            | DomainManagementEvent::AllocationFailed { source_id, .. }
+            | DomainManagementEvent::AdministrativeStateChanged { source_id, .. }
+            | DomainManagementEvent::AvailabilityChanged { source_id, .. } => source_id,
+        }
+    }
+
+    fn event_type(&self) -> &str {
+        match self {
+            DomainManagementEvent::ObjectAdded { .. } => "ObjectAdded",
+            DomainManagementEvent::ObjectRemoved { .. } => "ObjectRemoved",
+            DomainManagementEvent::AllocationFailed { .. } => "AllocationFailed",
+            DomainManagementEvent::AdministrativeStateChanged { .. } => {
+                "AdministrativeStateChanged"
+            }
+            DomainManagementEvent::AvailabilityChanged { .. } => "AvailabilityChanged",
+        }
+    }
+}
+
+/// The state change events are typed after their category, e.g. "USAGE_STATE_EVENT".
+impl FilteredEvent for StateChangeEvent {
+    fn source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    fn event_type(&self) -> &str {
+        match self.state_change_category {
+            StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT => "ADMINISTRATIVE_STATE_EVENT",
+            StateChangeCategoryType::OPERATIONAL_STATE_EVENT => "OPERATIONAL_STATE_EVENT",
+            StateChangeCategoryType::USAGE_STATE_EVENT => "USAGE_STATE_EVENT",
+        }
+    }
+}
+
+impl FilteredEvent for PropertyChangeEvent {
+    fn source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    fn event_type(&self) -> &str {
+        "PropertyChangeEvent"
+    }
+}
+
+/// The terminations are sourced by the component.
+impl FilteredEvent for AbnormalComponentTerminationEvent {
+    fn source_id(&self) -> &str {
+        &self.component_id
+    }
+
+    fn event_type(&self) -> &str {
+        "AbnormalComponentTerminationEvent"
+    }
+}
+
+/**
+ * Filter of the events a subscriber receives: the events of a source
+ * matching one of its patterns, of the '*' and '?' wildcards, and of one
+ * of its types. No patterns or no types let all the events through.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventFilter {
+    pub source_ids: Vec<String>,
+    pub event_types: Vec<String>,
+}
+
+impl EventFilter {
+    /// Tells whether an event passes the filter.
+    pub fn matches<E: FilteredEvent>(&self, event: &E) -> bool {
+        (self.source_ids.is_empty()
+            || self
+                .source_ids
+                .iter()
+                .any(|p| wildcard_match(p, event.source_id())))
+            && (self.event_types.is_empty()
+                || self.event_types.iter().any(|t| t == event.event_type()))
+    }
+}
+
+/**
+ * This trait is implemented by the events carried to the remote tools,
+ * encoded as JSON documents.
+ */
+pub trait RemoteEvent:
+    FilteredEvent + Serialize + DeserializeOwned + Clone + Send + 'static
+{
+}
+
+impl<T> RemoteEvent for T where
+    T: FilteredEvent + Serialize + DeserializeOwned + Clone + Send + 'static
+{
+}
+
+/**
+ * An event of a channel encoded for the remote tools, with its
+ * sequence number, source and type.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncodedEvent {
+    pub sequence: u64,
+    pub source_id: String,
+    pub event_type: String,
+    /// The JSON document of the event.
+    pub payload: String,
+}
+
+/**
+ * Stream of the events pushed on a channel from the subscription on.
+ */
+pub type EventStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/**
+ * This interface defines an event channel: a named channel delivering
+ * the events pushed by its publishers to its subscribers.
+ */
+pub trait EventChannelTrait<T> {
+    /// Returns the name of the channel.
+    fn name(&self) -> &str;
+
+    /// This operation delivers an event to the current subscribers.
+    fn push(&self, event: T);
+
+    /**
+     * This operation returns a stream of the events pushed from now on,
+     * the subscription ending when the stream is dropped.
+     */
+    fn subscribe(&self) -> EventStream<T>;
+
+    /**
+     * This operation returns a stream of the events following the last
+     * one seen, with their sequence numbers: the events the channel
+     * still retains, then the events pushed from now on.
+     */
+    fn subscribe_from(&self, last_seen: u64) -> EventStream<(u64, T)>;
+}
+
+/**
+ * Convienence type definition to share an event channel.
+ */
+pub type EventChannelRef<T> = Arc<dyn EventChannelTrait<T> + Send + Sync>;
+
+/**
+ * This type defines what a channel does with the events of a subscriber
+ * whose queue is full, the subscriber not keeping up with the channel.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackpressurePolicy {
+    /// The oldest queued event is dropped for the new one.
+    DropOldest,
+    /// The new event is dropped.
+    #[default]
+    DropNewest,
+    /// The publisher waits up to the timeout for room, the event being dropped past it.
+    Block(Duration),
+    /// The subscriber is disconnected, its stream ending after the queued events.
+    Disconnect,
+}
+
+/**
+ * Metrics of the queue of a subscriber of a channel.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubscriberMetrics {
+    /// The number of events queued.
+    pub queue_depth: usize,
+    /// The highest number of events queued so far.
+    pub max_queue_depth: usize,
+    /// The number of events dropped by the backpressure policy.
+    pub dropped: u64,
+    /// Whether the subscriber was disconnected, or its stream closed.
+    pub disconnected: bool,
+}
+
+/**
+ * Consumer called with the events pushed on a channel and their
+ * sequence numbers, disconnected once it returns false.
+ */
+type Consumer<T> = Box<dyn Fn(u64, &T) -> bool + Send>;
+
+/**
+ * The events queued for a subscriber, and the waker of the task
+ * receiving them.
+ */
+struct QueueState<T> {
+    events: VecDeque<(u64, T)>,
+    closed: bool,
+    /// Whether the receiving stream was dropped.
+    dropped: bool,
+    waker: Option<Waker>,
+    metrics: SubscriberMetrics,
+}
+
+/**
+ * Queue of the events of a subscriber, shared by its channel and its
+ * stream, the blocked publishers waiting for room on its condition.
+ */
+struct SubscriberQueue<T> {
+    state: Mutex<QueueState<T>>,
+    room: Condvar,
+}
+
+impl<T> SubscriberQueue<T> {
+    fn new() -> SubscriberQueue<T> {
+        SubscriberQueue {
+            state: Mutex::new(QueueState {
+                events: VecDeque::new(),
+                closed: false,
+                dropped: false,
+                waker: None,
+                metrics: SubscriberMetrics::default(),
+            }),
+            room: Condvar::new(),
+        }
+    }
+
+    /// Queues an event regardless of the queue size, waking the receiving task.
+    fn enqueue(state: &mut QueueState<T>, sequence: u64, event: T) {
+        state.events.push_back((sequence, event));
+        state.metrics.queue_depth = state.events.len();
+        state.metrics.max_queue_depth = state.metrics.max_queue_depth.max(state.events.len());
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Queues an event, applying the policy once the queue holds its size.
+    fn offer(&self, sequence: u64, event: T, queue_size: usize, policy: BackpressurePolicy) {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return;
+        }
+        if state.events.len() >= queue_size {
+            match policy {
+                BackpressurePolicy::DropOldest => {
+                    state.events.pop_front();
+                    state.metrics.dropped += 1;
+                }
+                BackpressurePolicy::DropNewest => {
+                    state.metrics.dropped += 1;
+                    return;
+                }
+                BackpressurePolicy::Block(timeout) => {
+                    state = self
+                        .room
+                        .wait_timeout_while(state, timeout, |s| {
+                            !s.closed && s.events.len() >= queue_size
+                        })
+                        .unwrap()
+                        .0;
+                    if state.closed {
+                        return;
+                    }
+                    if state.events.len() >= queue_size {
+                        state.metrics.dropped += 1;
+                        return;
+                    }
+                }
+                BackpressurePolicy::Disconnect => {
+                    state.metrics.dropped += 1;
+                    Self::close_locked(&mut state);
+                    return;
+                }
+            }
+        }
+        Self::enqueue(&mut state, sequence, event);
+    }
+
+    fn close_locked(state: &mut QueueState<T>) {
+        state.closed = true;
+        state.metrics.disconnected = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Closes the queue, its stream ending after the queued events.
+    fn close(&self) {
+        Self::close_locked(&mut self.state.lock().unwrap());
+        self.room.notify_all();
+    }
+}
+
+/**
+ * Stream of the events queued for a subscriber of a channel, with their
+ * sequence numbers, ending once the subscriber is disconnected or its
+ * stream closed. Dropping the stream unsubscribes.
+ */
+pub struct Subscription<T> {
+    queue: Arc<SubscriberQueue<T>>,
+}
+
+impl<T> fmt::Debug for Subscription<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Returns the metrics of the queue of the subscriber.
+    pub fn metrics(&self) -> SubscriberMetrics {
+        self.queue.state.lock().unwrap().metrics
+    }
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// Returns a closer ending the stream once the queued events are received.
+    pub fn closer(&self) -> impl FnOnce() + Send + 'static {
+        let queue = self.queue.clone();
+        move || queue.close()
+    }
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = (u64, T);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut state = self.queue.state.lock().unwrap();
+        if let Some(event) = state.events.pop_front() {
+            state.metrics.queue_depth = state.events.len();
+            drop(state);
+            self.queue.room.notify_all();
+            return Poll::Ready(Some(event));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        {
+            let mut state = self.queue.state.lock().unwrap();
+            state.dropped = true;
+            state.closed = true;
+            state.events.clear();
+        }
+        self.queue.room.notify_all();
+    }
+}
+
+/**
+ * A subscriber of a channel: its queue and the filter of the events
+ * queued.
+ */
+struct QueuedSubscriber<T> {
+    queue: Arc<SubscriberQueue<T>>,
+    filter: Box<dyn Fn(&T) -> bool + Send>,
+}
+
+/**
+ * The subscribers and consumers of a channel, and the last events
+ * retained for the subscribers attaching with a sequence number.
+ */
+struct ChannelState<T> {
+    last_sequence: u64,
+    history_size: usize,
+    history: VecDeque<(u64, T)>,
+    policy: BackpressurePolicy,
+    queue_size: usize,
+    queues: Vec<QueuedSubscriber<T>>,
+    subscribers: Vec<mpsc::Sender<T>>,
+    consumers: Vec<Consumer<T>>,
+}
+
+/**
+ * In-process event channel delivering every pushed event to all of its
+ * current subscribers and consumers. The events are numbered from 1 in
+ * the order they are pushed, a durable channel retaining its last ones
+ * for the subscribers attaching with the sequence number of the last
+ * event they saw, e.g. after reconnecting. The events of the stream
+ * subscribers are queued up to the queue size of the channel, its
+ * backpressure policy handling the subscribers not keeping up. Cloned
+ * channels share the same subscribers and consumers.
+ */
+#[derive(Clone)]
+pub struct EventChannel<T> {
+    name: String,
+    state: Arc<Mutex<ChannelState<T>>>,
+}
+
+impl<T> fmt::Debug for EventChannel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("EventChannel")
+            .field("name", &self.name)
+            .field("last_sequence", &state.last_sequence)
+            .field("history", &state.history.len())
+            .field("policy", &state.policy)
+            .field("queue_size", &state.queue_size)
+            .field("queues", &state.queues.len())
+            .field("subscribers", &state.subscribers.len())
+            .field("consumers", &state.consumers.len())
+            .finish()
+    }
+}
+
+impl<T: Clone> EventChannel<T> {
+    pub fn new(name: &str) -> EventChannel<T> {
+        EventChannel {
+            name: name.to_string(),
+            state: Arc::new(Mutex::new(ChannelState {
+                last_sequence: 0,
+                history_size: 0,
+                history: VecDeque::new(),
+                policy: BackpressurePolicy::default(),
+                queue_size: DEFAULT_QUEUE_SIZE,
+                queues: Vec::new(),
+                subscribers: Vec::new(),
+                consumers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Makes the channel durable, retaining its last events up to the history size.
+    pub fn with_history(self, history_size: usize) -> EventChannel<T> {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.history_size = history_size;
+            let excess = state.history.len().saturating_sub(history_size);
+            state.history.drain(..excess);
+        }
+        self
+    }
+
+    /// Sets the policy applied to the subscribers whose queue is full.
+    pub fn with_backpressure(self, policy: BackpressurePolicy) -> EventChannel<T> {
+        self.state.lock().unwrap().policy = policy;
+        self
+    }
+
+    /// Sets the number of events queued for each subscriber, at least 1.
+    pub fn with_queue_size(self, queue_size: usize) -> EventChannel<T> {
+        self.state.lock().unwrap().queue_size = queue_size.max(1);
+        self
+    }
+
+    /// Returns the name of the channel.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the retained events with their sequence numbers, the oldest first.
+    pub fn retained(&self) -> Vec<(u64, T)> {
+        self.state.lock().unwrap().history.iter().cloned().collect()
+    }
+
+    /// Returns the sequence number of the last event pushed, 0 before the first one.
+    pub fn last_sequence(&self) -> u64 {
+        self.state.lock().unwrap().last_sequence
+    }
+
+    /**
+     * Delivers the event to the subscribers and consumers, dropping the
+     * disconnected ones. The publisher is blocked by the subscribers
+     * whose queue is full under the Block policy.
+     */
+    pub fn push(&self, event: T) {
+        let mut state = self.state.lock().unwrap();
+        state.last_sequence += 1;
+        let sequence = state.last_sequence;
+        if state.history_size > 0 {
+            if state.history.len() == state.history_size {
+                state.history.pop_front();
+            }
+            state.history.push_back((sequence, event.clone()));
+        }
+        state.consumers.retain(|c| c(sequence, &event));
+        state.subscribers.retain(|s| s.send(event.clone()).is_ok());
+        let (queue_size, policy) = (state.queue_size, state.policy);
+        state.queues.retain(|s| {
+            if (s.filter)(&event) {
+                s.queue.offer(sequence, event.clone(), queue_size, policy);
+            }
+            !s.queue.state.lock().unwrap().dropped
+        });
+    }
+
+    /**
+     * Connects a consumer called with the events pushed from now on,
+     * in the pushing thread, until it returns false.
+     */
+    pub fn connect<F>(&self, consumer: F)
+    where
+        F: Fn(&T) -> bool + Send + 'static,
+    {
+        self.connect_from(None, move |_, event: &T| consumer(event));
+    }
+
+    /**
+     * Connects a consumer called with the events and their sequence
+     * numbers: first the retained events following the last one seen,
+     * when given, then the events pushed from now on, in the pushing
+     * thread, until it returns false. The events no longer retained are
+     * missed, as told by the gap in the sequence numbers.
+     */
+    pub fn connect_from<F>(&self, last_seen: Option<u64>, consumer: F)
+    where
+        F: Fn(u64, &T) -> bool + Send + 'static,
+    {
+        let mut state = self.state.lock().unwrap();
+        if let Some(last_seen) = last_seen {
+            for (sequence, event) in state.history.iter().filter(|(s, _)| *s > last_seen) {
+                if !consumer(*sequence, event) {
+                    return;
+                }
+            }
+        }
+        state.consumers.push(Box::new(consumer));
+    }
+
+    /**
+     * Returns a receiver for the events pushed from now on, to be
+     * received in a blocking thread, the stream of the EventChannelTrait
+     * subscription suiting the async tasks.
+     */
+    pub fn subscribe(&self) -> mpsc::Receiver<T> {
+        let (tx, rx) = mpsc::channel();
+        self.state.lock().unwrap().subscribers.push(tx);
+        rx
+    }
+
+    /**
+     * Returns the stream of the events passing a filter, with their
+     * sequence numbers: first the retained events following the last one
+     * seen, when given, queued regardless of the queue size, then the
+     * events pushed from now on.
+     */
+    pub fn subscribe_filtered<F>(&self, last_seen: Option<u64>, filter: F) -> Subscription<T>
+    where
+        F: Fn(&T) -> bool + Send + 'static,
+    {
+        let queue = Arc::new(SubscriberQueue::new());
+        let mut state = self.state.lock().unwrap();
+        if let Some(last_seen) = last_seen {
+            let mut queued = queue.state.lock().unwrap();
+            for (sequence, event) in state
+                .history
+                .iter()
+                .filter(|(s, e)| *s > last_seen && filter(e))
+            {
+                SubscriberQueue::enqueue(&mut queued, *sequence, event.clone());
+            }
+        }
+        state.queues.push(QueuedSubscriber {
+            queue: queue.clone(),
+            filter: Box::new(filter),
+        });
+        Subscription { queue }
+    }
+
+    /// Returns the metrics of the queues of the current stream subscribers.
+    pub fn subscriber_metrics(&self) -> Vec<SubscriberMetrics> {
+        let mut state = self.state.lock().unwrap();
+        state
+            .queues
+            .retain(|s| !s.queue.state.lock().unwrap().dropped);
+        state
+            .queues
+            .iter()
+            .map(|s| s.queue.state.lock().unwrap().metrics)
+            .collect()
+    }
+}
+
+impl<T: Clone + Send + 'static> EventChannelTrait<T> for EventChannel<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn push(&self, event: T) {
+        EventChannel::push(self, event)
+    }
+
+    fn subscribe(&self) -> EventStream<T> {
+        Box::pin(
+            self.subscribe_filtered(None, |_| true)
+                .map(|(_, event)| event),
+        )
+    }
+
+    fn subscribe_from(&self, last_seen: u64) -> EventStream<(u64, T)> {
+        Box::pin(self.subscribe_filtered(Some(last_seen), |_| true))
+    }
+}
+
+/**
+ * Event channel for the publishers and subscribers colocated in one
+ * process and runtime, e.g. a sandbox or a component host, built on a
+ * tokio broadcast channel. The subscribers falling behind by more than
+ * the capacity of the channel miss the oldest events, as told by the
+ * gap in the sequence numbers. No events are retained for the
+ * subscribers attaching later. Cloned channels share the same
+ * subscribers.
+ */
+#[derive(Clone)]
+pub struct BroadcastEventChannel<T> {
+    name: String,
+    /// The sequence number of the last event, locked while sending it for the events to be sent in order.
+    last_sequence: Arc<Mutex<u64>>,
+    sender: broadcast::Sender<(u64, T)>,
+}
+
+impl<T> fmt::Debug for BroadcastEventChannel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BroadcastEventChannel")
+            .field("name", &self.name)
+            .field("last_sequence", &*self.last_sequence.lock().unwrap())
+            .field("subscribers", &self.sender.receiver_count())
+            .finish()
+    }
+}
+
+impl<T: Clone + Send + 'static> BroadcastEventChannel<T> {
+    /// Returns a channel keeping up to a capacity of events for the subscribers falling behind.
+    pub fn new(name: &str, capacity: usize) -> BroadcastEventChannel<T> {
+        BroadcastEventChannel {
+            name: name.to_string(),
+            last_sequence: Arc::default(),
+            sender: broadcast::channel(capacity.max(1)).0,
+        }
+    }
+
+    /// Returns the sequence number of the last event pushed, 0 before the first one.
+    pub fn last_sequence(&self) -> u64 {
+        *self.last_sequence.lock().unwrap()
+    }
+
+    /// Returns the number of the current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl<T: Clone + Send + 'static> EventChannelTrait<T> for BroadcastEventChannel<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn push(&self, event: T) {
+        let mut last_sequence = self.last_sequence.lock().unwrap();
+        *last_sequence += 1;
+        let _ = self.sender.send((*last_sequence, event));
+    }
+
+    fn subscribe(&self) -> EventStream<T> {
+        Box::pin(self.subscribe_from(0).map(|(_, event)| event))
+    }
+
+    /// The channel retaining no events, only the events pushed from now on are received.
+    fn subscribe_from(&self, _last_seen: u64) -> EventStream<(u64, T)> {
+        Box::pin(BroadcastStream::new(self.sender.subscribe()).filter_map(|event| event.ok()))
+    }
+}
+
+/**
+ * The encoded events of a remote subscription, and the closer ending
+ * their stream once the queued events are received.
+ */
+pub struct RemoteSubscription {
+    pub events: EventStream<EncodedEvent>,
+    pub closer: Box<dyn FnOnce() + Send>,
+}
+
+impl fmt::Debug for RemoteSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteSubscription").finish_non_exhaustive()
+    }
+}
+
+/**
+ * A channel of remote events with their type erased, as managed for
+ * the remote tools.
+ */
+trait RemoteChannel: Send + Sync {
+    fn subscribe(&self, last_seen: Option<u64>, filter: EventFilter) -> RemoteSubscription;
+
+    fn publish(&self, payload: &str) -> serde_json::Result<()>;
+}
+
+impl<T: RemoteEvent> RemoteChannel for EventChannel<T> {
+    fn subscribe(&self, last_seen: Option<u64>, filter: EventFilter) -> RemoteSubscription {
+        let subscription = self.subscribe_filtered(last_seen, move |event| filter.matches(event));
+        let closer = Box::new(subscription.closer());
+        let events = subscription.map(|(sequence, event)| EncodedEvent {
+            sequence,
+            source_id: event.source_id().to_string(),
+            event_type: event.event_type().to_string(),
+            payload: serde_json::to_string(&event).unwrap_or_default(),
+        });
+        RemoteSubscription {
+            events: Box::pin(events),
+            closer,
+        }
+    }
+
+    fn publish(&self, payload: &str) -> serde_json::Result<()> {
+        self.push(serde_json::from_str(payload)?);
+        Ok(())
+    }
+}
+
+/**
+ * Convienence enum definition that includes all EventChannelManager errors.
+ */
+#[derive(Error, Debug)]
+pub enum EventChannelManagerError {
+    /**
+     * This exception indicates a channel of the same name is already
+     * managed.
+     */
+    #[error("ChannelAlreadyExists: name: '{name}'.")]
+    ChannelAlreadyExists { name: String },
+    /**
+     * This exception indicates no channel of the name, and of the event
+     * type, is managed.
+     */
+    #[error("ChannelDoesNotExist: name: '{name}'.")]
+    ChannelDoesNotExist { name: String },
+    /**
+     * This exception indicates the events of the channel are not
+     * carried to the remote tools.
+     */
+    #[error("ChannelNotRemote: name: '{name}'.")]
+    ChannelNotRemote { name: String },
+    /**
+     * This exception indicates a remote event is not a JSON document of
+     * an event of the channel.
+     */
+    #[error("InvalidEvent: name: '{name}', message: '{message}'.")]
+    InvalidEvent { name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all EventChannelManager returned errors.
+ */
+pub type Result<T, E = EventChannelManagerError> = anyhow::Result<T, E>;
+
+/**
+ * A managed channel, and its type erased form for the remote tools when
+ * its events are remote ones.
+ */
+struct ManagedChannel {
+    channel: Box<dyn Any + Send>,
+    remote: Option<Arc<dyn RemoteChannel>>,
+}
+
+/**
+ * The event channel manager creates the in-process event channels of
+ * the domain and looks them up by name, each channel carrying events of
+ * a single type. The channels of remote events are also subscribed and
+ * published to by name, their events encoded as JSON documents, for the
+ * remote tools. Clones share the same channels.
+ */
+#[derive(Clone, Default)]
+pub struct EventChannelManager {
+    channels: Arc<Mutex<HashMap<String, ManagedChannel>>>,
+}
+
+impl fmt::Debug for EventChannelManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EventChannelManager")
+            .field("channels", &self.channel_names())
+            .finish()
+    }
+}
+
+impl EventChannelManager {
+    pub fn new() -> EventChannelManager {
+        EventChannelManager::default()
+    }
+
+    /// Creates a channel of a name not managed yet.
+    pub fn create<T>(&self, name: &str) -> Result<EventChannel<T>>
+    where
+        T: Clone + Send + 'static,
+    {
+        let channel = EventChannel::new(name);
+        self.add(channel.clone())?;
+        Ok(channel)
+    }
+
+    /// Manages an existing channel under its name.
+    pub fn add<T>(&self, channel: EventChannel<T>) -> Result<()>
+    where
+        T: Clone + Send + 'static,
+    {
+        self.insert(channel, None)
+    }
+
+    /// Creates a channel of remote events, of a name not managed yet.
+    pub fn create_remote<T: RemoteEvent>(&self, name: &str) -> Result<EventChannel<T>> {
+        let channel = EventChannel::new(name);
+        self.add_remote(channel.clone())?;
+        Ok(channel)
+    }
+
+    /// Manages an existing channel of remote events under its name.
+    pub fn add_remote<T: RemoteEvent>(&self, channel: EventChannel<T>) -> Result<()> {
+        let remote: Arc<dyn RemoteChannel> = Arc::new(channel.clone());
+        self.insert(channel, Some(remote))
+    }
+
+    fn insert<T>(
+        &self,
+        channel: EventChannel<T>,
+        remote: Option<Arc<dyn RemoteChannel>>,
+    ) -> Result<()>
+    where
+        T: Clone + Send + 'static,
+    {
+        let mut channels = self.channels.lock().unwrap();
+        if channels.contains_key(channel.name()) {
+            return Err(EventChannelManagerError::ChannelAlreadyExists {
+                name: channel.name().to_string(),
+            });
+        }
+        channels.insert(
+            channel.name().to_string(),
+            ManagedChannel {
+                channel: Box::new(channel),
+                remote,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns the channel of a name, none when unknown or carrying events of another type.
+    pub fn get<T>(&self, name: &str) -> Option<EventChannel<T>>
+    where
+        T: Clone + Send + 'static,
+    {
+        self.channels
+            .lock()
+            .unwrap()
+            .get(name)
+            .and_then(|managed| managed.channel.downcast_ref::<EventChannel<T>>())
+            .cloned()
+    }
+
+    /**
+     * Returns the channel of a name, to publish or subscribe to, created
+     * when unknown: a BroadcastEventChannel when opened from a tokio
+     * runtime, the publishers and subscribers sharing it, an
+     * EventChannel otherwise.
+     */
+    pub fn open<T>(&self, name: &str) -> Result<EventChannelRef<T>>
+    where
+        T: Clone + Send + 'static,
+    {
+        let mut channels = self.channels.lock().unwrap();
+        if let Some(managed) = channels.get(name) {
+            if let Some(channel) = managed.channel.downcast_ref::<EventChannel<T>>() {
+                return Ok(Arc::new(channel.clone()));
+            }
+            if let Some(channel) = managed.channel.downcast_ref::<BroadcastEventChannel<T>>() {
+                return Ok(Arc::new(channel.clone()));
+            }
+            return Err(EventChannelManagerError::ChannelAlreadyExists {
+                name: name.to_string(),
+            });
+        }
+
+        let (channel, opened): (Box<dyn Any + Send>, EventChannelRef<T>) =
+            if tokio::runtime::Handle::try_current().is_ok() {
+                let channel = BroadcastEventChannel::new(name, DEFAULT_QUEUE_SIZE);
+                (Box::new(channel.clone()), Arc::new(channel))
+            } else {
+                let channel = EventChannel::new(name);
+                (Box::new(channel.clone()), Arc::new(channel))
+            };
+        channels.insert(
+            name.to_string(),
+            ManagedChannel {
+                channel,
+                remote: None,
+            },
+        );
+        Ok(opened)
+    }
+
+    /// Returns the remote form of the channel of a name.
+    fn remote(&self, name: &str) -> Result<Arc<dyn RemoteChannel>> {
+        match self.channels.lock().unwrap().get(name) {
+            Some(ManagedChannel {
+                remote: Some(remote),
+                ..
+            }) => Ok(remote.clone()),
+            Some(_) => Err(EventChannelManagerError::ChannelNotRemote {
+                name: name.to_string(),
+            }),
+            None => Err(EventChannelManagerError::ChannelDoesNotExist {
+                name: name.to_string(),
+            }),
+        }
+    }
+
+    /**
+     * Subscribes to the encoded events of a channel of remote events
+     * passing a filter, following the last one seen when given.
+     */
+    pub fn subscribe_remote(
+        &self,
+        name: &str,
+        last_seen: Option<u64>,
+        filter: EventFilter,
+    ) -> Result<RemoteSubscription> {
+        Ok(self.remote(name)?.subscribe(last_seen, filter))
+    }
+
+    /// Pushes an event, as a JSON document, on a channel of remote events.
+    pub fn publish_remote(&self, name: &str, payload: &str) -> Result<()> {
+        self.remote(name)?
+            .publish(payload)
+            .map_err(|e| EventChannelManagerError::InvalidEvent {
+                name: name.to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Returns the channel of a name, created when unknown.
+    pub fn get_or_create<T>(&self, name: &str) -> Result<EventChannel<T>>
+    where
+        T: Clone + Send + 'static,
+    {
+        match self.get(name) {
+            Some(channel) => Ok(channel),
+            None => self.create(name),
+        }
+    }
+
+    /**
+     * Stops managing the channel of a name, its current subscribers
+     * being still delivered the events of its publishers.
+     */
+    pub fn remove(&self, name: &str) -> Result<()> {
+        self.channels
+            .lock()
+            .unwrap()
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| EventChannelManagerError::ChannelDoesNotExist {
+                name: name.to_string(),
+            })
+    }
+
+    /// Returns the names of the managed channels, sorted.
+    pub fn channel_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.channels.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Returns the names of the managed channels of remote events, sorted.
+    pub fn remote_channel_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .channels
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, managed)| managed.remote.is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+}
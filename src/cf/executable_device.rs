@@ -0,0 +1,679 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, ErrorKind, Read},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{mpsc, Arc, Mutex, Weak},
+    thread,
+    time::Duration,
+};
+use sysinfo::{Pid, System};
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, ErrorNumberType, Properties};
+use super::device::{self, AdminType, DeviceTrait, OperationalType, UsageType};
+use super::loadable_device::{self, LoadType, LoadableDevice, LoadableDeviceTrait};
+use super::property_store::PropertyReader;
+
+/// This type identifies a process started by the execute operation.
+pub type ProcessId = u32;
+
+/// The identifier for the execute options parameter specifying the stack size.
+pub const STACK_SIZE_ID: &str = "STACK_SIZE";
+/// The identifier for the execute options parameter specifying the priority.
+pub const PRIORITY_ID: &str = "PRIORITY";
+/// The identifier for the execute options parameter specifying the process collocation.
+pub const PROCESS_COLLOCATION_ID: &str = "PROCESS_COLLOCATION";
+/// The identifier for the execute options parameter specifying the entry point.
+pub const ENTRY_POINT_ID: &str = "ENTRY_POINT";
+/// The identifier for the execute options parameter specifying the core affinity.
+pub const CORE_AFFINITY_ID: &str = "CORE_AFFINITY";
+
+/// The execparam carrying the component instantiation identifier.
+pub const COMPONENT_IDENTIFIER: &str = "COMPONENT_IDENTIFIER";
+/// The execparam carrying the endpoint the component registers itself with.
+pub const NAMING_CONTEXT_IOR: &str = "NAMING_CONTEXT_IOR";
+/// The execparam carrying the name the component registers itself under.
+pub const NAME_BINDING: &str = "NAME_BINDING";
+/// The execparam carrying the SPD file name of the component.
+pub const PROFILE_NAME: &str = "PROFILE_NAME";
+
+/// The number of stdout and stderr lines retained for each process.
+const OUTPUT_LINES: usize = 256;
+
+/// The period used by the supervisor to poll running processes.
+const SUPERVISOR_PERIOD: Duration = Duration::from_millis(50);
+
+/**
+ * Convienence enum definition that includes all ExecutableDeviceTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum ExecutableDeviceError {
+    /**
+     * This exception indicates that the device is not capable of the
+     * behavior being attempted due to its current state.
+     */
+    #[error("InvalidState: msg: '{message}'.")]
+    InvalidState { message: String },
+    /**
+     * This exception indicates the file name to be executed does not
+     * exist for the device.
+     */
+    #[error("InvalidFileName: num: {error_number:?}, msg: '{message}'.")]
+    InvalidFileName {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates the input parameters are invalid on the
+     * execute operation. The list contains the invalid parameters.
+     */
+    #[error("InvalidParameters: {invalid_parms:?}.")]
+    InvalidParameters { invalid_parms: Properties },
+    /**
+     * This exception indicates the input options are invalid on the
+     * execute operation. The list contains the invalid options.
+     */
+    #[error("InvalidOptions: {invalid_opts:?}.")]
+    InvalidOptions { invalid_opts: Properties },
+    /**
+     * This exception indicates that the execute operation failed due to
+     * device dependent reasons.
+     */
+    #[error("ExecuteFail: num: {error_number:?}, msg: '{message}'.")]
+    ExecuteFail {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates that a process, as identified by the
+     * processId parameter, does not exist on this device.
+     */
+    #[error("InvalidProcess: num: {error_number:?}, msg: '{message}'.")]
+    InvalidProcess {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+}
+
+impl From<std::io::Error> for ExecutableDeviceError {
+    /// The spawn failures are reported as ExecuteFail, CF_EIO by default.
+    fn from(value: std::io::Error) -> Self {
+        const ENOEXEC: i32 = 8;
+
+        let error_number = match value.kind() {
+            _ if value.raw_os_error() == Some(ENOEXEC) => ErrorNumberType::CF_ENOEXEC,
+            ErrorKind::NotFound => ErrorNumberType::CF_ENOENT,
+            ErrorKind::PermissionDenied => ErrorNumberType::CF_EACCES,
+            ErrorKind::ExecutableFileBusy | ErrorKind::ResourceBusy => ErrorNumberType::CF_EBUSY,
+            ErrorKind::ArgumentListTooLong => ErrorNumberType::CF_E2BIG,
+            ErrorKind::OutOfMemory => ErrorNumberType::CF_ENOMEM,
+            ErrorKind::WouldBlock => ErrorNumberType::CF_EAGAIN,
+            ErrorKind::InvalidInput => ErrorNumberType::CF_EINVAL,
+            _ => ErrorNumberType::CF_EIO,
+        };
+        ExecutableDeviceError::ExecuteFail {
+            error_number,
+            message: value.to_string(),
+        }
+    }
+}
+
+/*
+ * Convienence type definition that includes all ExecutableDeviceTrait returned errors.
+ */
+pub type Result<T, E = ExecutableDeviceError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines the status of a process started by the execute
+ * operation.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    RUNNING,
+    SLEEPING,
+    STOPPED,
+    ZOMBIE,
+    /// The process is gone.
+    TERMINATED,
+    /// The status could not be collected.
+    UNKNOWN,
+}
+
+impl From<sysinfo::ProcessStatus> for ProcessStatus {
+    fn from(value: sysinfo::ProcessStatus) -> Self {
+        match value {
+            sysinfo::ProcessStatus::Run => ProcessStatus::RUNNING,
+            sysinfo::ProcessStatus::Idle
+            | sysinfo::ProcessStatus::Sleep
+            | sysinfo::ProcessStatus::UninterruptibleDiskSleep => ProcessStatus::SLEEPING,
+            sysinfo::ProcessStatus::Stop | sysinfo::ProcessStatus::Tracing => {
+                ProcessStatus::STOPPED
+            }
+            sysinfo::ProcessStatus::Zombie => ProcessStatus::ZOMBIE,
+            sysinfo::ProcessStatus::Dead => ProcessStatus::TERMINATED,
+            _ => ProcessStatus::UNKNOWN,
+        }
+    }
+}
+
+/**
+ * This type reports the resource usage of a process started by the
+ * execute operation.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessMetrics {
+    pub process_id: ProcessId,
+    /// The CPU usage since the previous collection, in percent of one core.
+    pub cpu_usage: f32,
+    /// The resident memory, in bytes.
+    pub memory: u64,
+    pub status: ProcessStatus,
+}
+
+/**
+ * This interface extends the LoadableDeviceTrait by adding execute and
+ * terminate behavior to a device.
+ */
+pub trait ExecutableDeviceTrait: LoadableDeviceTrait {
+    /// This operation executes a loaded file as a process using the input options and parameters.
+    fn execute(&mut self, name: &str, options: &Properties, parameters: &Properties)
+        -> Result<ProcessId>;
+
+    /// This operation terminates a process previously started by execute.
+    fn terminate(&mut self, process_id: ProcessId) -> Result<()>;
+
+    /// This operation collects the resource usage of a process previously started by execute.
+    fn process_metrics(&mut self, process_id: ProcessId) -> Result<ProcessMetrics>;
+}
+
+/**
+ * Shared reference to an executable device.
+ */
+pub type ExecutableDeviceRef = Arc<Mutex<dyn ExecutableDeviceTrait + Send>>;
+
+/**
+ * Report of a process that ended on its own, without being terminated.
+ */
+#[derive(Debug, Clone)]
+pub struct ProcessExit {
+    pub process_id: ProcessId,
+    pub name: String,
+    /// The exit code, if the process was not ended by a signal.
+    pub code: Option<i32>,
+    /// True when the process ended with a failure status or by a signal.
+    pub abnormal: bool,
+    /// The last lines written by the process on its stderr.
+    pub stderr: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct ProcessOutput {
+    stdout: VecDeque<String>,
+    stderr: VecDeque<String>,
+}
+
+#[derive(Debug)]
+struct Process {
+    name: String,
+    child: Child,
+    output: Arc<Mutex<ProcessOutput>>,
+    readers: Vec<thread::JoinHandle<()>>,
+}
+
+type ProcessTable = Arc<Mutex<HashMap<ProcessId, Process>>>;
+type ExitListeners = Arc<Mutex<Vec<mpsc::Sender<ProcessExit>>>>;
+
+/**
+ * Executable device running the loaded files as native processes. A
+ * supervisor thread watches the spawned processes, retaining the tail
+ * of their stdout/stderr and reporting the ones exiting on their own.
+ */
+#[derive(Debug)]
+pub struct ExecutableDevice {
+    loadable: LoadableDevice,
+    registration_endpoint: Option<String>,
+    processes: ProcessTable,
+    exit_listeners: ExitListeners,
+    executions: u64,
+    system: System,
+}
+
+impl ExecutableDevice {
+    pub fn new(loadable: LoadableDevice) -> ExecutableDevice {
+        let processes: ProcessTable = Arc::default();
+        let exit_listeners: ExitListeners = Arc::default();
+
+        let table = Arc::downgrade(&processes);
+        let listeners = exit_listeners.clone();
+        thread::spawn(move || supervise(table, listeners));
+
+        ExecutableDevice {
+            loadable,
+            registration_endpoint: None,
+            processes,
+            exit_listeners,
+            executions: 0,
+            system: System::new(),
+        }
+    }
+
+    /**
+     * Sets the endpoint passed to the executed components, through the
+     * NAMING_CONTEXT_IOR execparam, to register themselves with.
+     */
+    pub fn with_registration_endpoint(mut self, endpoint: &str) -> ExecutableDevice {
+        self.registration_endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    /// Returns the embedded loadable device.
+    pub fn loadable(&self) -> &LoadableDevice {
+        &self.loadable
+    }
+
+    /// Returns the mutable embedded loadable device.
+    pub fn loadable_mut(&mut self) -> &mut LoadableDevice {
+        &mut self.loadable
+    }
+
+    /// Returns the identifiers of the running processes.
+    pub fn process_ids(&self) -> Vec<ProcessId> {
+        self.processes.lock().unwrap().keys().copied().collect()
+    }
+
+    /// Returns the last lines written by a running process on its stdout.
+    pub fn stdout(&self, process_id: ProcessId) -> Option<Vec<String>> {
+        let processes = self.processes.lock().unwrap();
+        let output = processes.get(&process_id)?.output.lock().unwrap();
+        Some(output.stdout.iter().cloned().collect())
+    }
+
+    /// Returns the last lines written by a running process on its stderr.
+    pub fn stderr(&self, process_id: ProcessId) -> Option<Vec<String>> {
+        let processes = self.processes.lock().unwrap();
+        let output = processes.get(&process_id)?.output.lock().unwrap();
+        Some(output.stderr.iter().cloned().collect())
+    }
+
+    /// Returns a channel receiving a report for each process exiting on its own.
+    pub fn subscribe_exits(&self) -> mpsc::Receiver<ProcessExit> {
+        let (tx, rx) = mpsc::channel();
+        self.exit_listeners.lock().unwrap().push(tx);
+        rx
+    }
+
+    /**
+     * Builds the process arguments, as ID/value pairs, injecting the
+     * standard execparams that have not been explicitly provided.
+     */
+    fn exec_params(&mut self, name: &str, parameters: &Properties) -> Vec<String> {
+        self.executions += 1;
+
+        let stem = Path::new(name)
+            .file_stem()
+            .map_or(name.to_string(), |s| s.to_string_lossy().to_string());
+        let mut injected = vec![
+            DataType::new(
+                COMPONENT_IDENTIFIER,
+                AnyValue::String(format!("{stem}_{}:{}", self.executions, self.identifier())),
+            ),
+            DataType::new(NAME_BINDING, AnyValue::String(format!("{stem}_{}", self.executions))),
+            DataType::new(PROFILE_NAME, AnyValue::String(name.to_string())),
+        ];
+        if let Some(endpoint) = &self.registration_endpoint {
+            injected.push(DataType::new(NAMING_CONTEXT_IOR, AnyValue::String(endpoint.clone())));
+        }
+        injected.retain(|i| parameters.iter().all(|p| p.id != i.id));
+
+        injected
+            .iter()
+            .chain(parameters.iter())
+            .flat_map(|p| [p.id.clone(), p.value.to_string()])
+            .collect()
+    }
+
+    fn check_state(&self, allow_shutting_down: bool) -> Result<()> {
+        match self.admin_state() {
+            AdminType::LOCKED => Err(ExecutableDeviceError::InvalidState {
+                message: "adminState is LOCKED".to_string(),
+            }),
+            AdminType::SHUTTING_DOWN if !allow_shutting_down => {
+                Err(ExecutableDeviceError::InvalidState {
+                    message: "adminState is SHUTTING_DOWN".to_string(),
+                })
+            }
+            _ if self.operational_state() == OperationalType::DISABLED => {
+                Err(ExecutableDeviceError::InvalidState {
+                    message: "operationalState is DISABLED".to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for ExecutableDevice {
+    fn drop(&mut self) {
+        //do not leak processes beyond the device lifetime
+        for (_, mut p) in self.processes.lock().unwrap().drain() {
+            let _ = p.child.kill();
+            let _ = p.child.wait();
+        }
+    }
+}
+
+impl DeviceTrait for ExecutableDevice {
+    fn identifier(&self) -> &str {
+        self.loadable.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.loadable.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.loadable.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.loadable.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.loadable.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.loadable.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.loadable.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.loadable.allocation_properties()
+    }
+
+    fn allocation_property_reader(&self) -> Option<PropertyReader> {
+        self.loadable.allocation_property_reader()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.loadable.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.loadable.deallocate_capacity(capacities)
+    }
+}
+
+impl LoadableDeviceTrait for ExecutableDevice {
+    fn load(&mut self, fs: &Path, file_name: &str, load_kind: LoadType) -> loadable_device::Result<()> {
+        self.loadable.load(fs, file_name, load_kind)
+    }
+
+    fn unload(&mut self, file_name: &str) -> loadable_device::Result<()> {
+        self.loadable.unload(file_name)
+    }
+}
+
+impl ExecutableDeviceTrait for ExecutableDevice {
+    /**
+     * SCA279
+     * The execute operation shall execute the file identified by the input file name
+     * parameter using the input parameters and options parameters.
+     * SCA280
+     * The execute operation shall map the input parameters (id/value string pairs)
+     * parameter as an argument to the operating system "execute/thread" function.
+     * SCA283
+     * The execute operation shall raise the CF::InvalidState exception if upon entry
+     * the ExecutableDeviceComponent's adminState attribute is either LOCKED or
+     * SHUTTING_DOWN.
+     * SCA514
+     * The execute operation shall raise the CF::InvalidState exception if upon entry
+     * the ExecutableDeviceComponent's operationalState attribute is DISABLED.
+     * SCA285
+     * The execute operation shall raise the CF::InvalidFileName exception when the
+     * file name indicated by the input file name parameter does not exist for the
+     * device to be executed.
+     * SCA286
+     * The execute operation shall raise the InvalidParameters exception when the
+     * input parameter ID or value attributes are not valid strings.
+     * SCA287
+     * The execute operation shall raise the InvalidOptions exception when the input
+     * options parameter does not comply with STACK_SIZE_ID, PRIORITY_ID,
+     * PROCESS_COLLOCATION_ID, ENTRY_POINT_ID and CORE_AFFINITY_ID.
+     * SCA288
+     * The execute operation shall raise the ExecuteFail exception when the operating
+     * system "execute/thread" function is not successful.
+     */
+    fn execute(
+        &mut self,
+        name: &str,
+        options: &Properties,
+        parameters: &Properties,
+    ) -> Result<ProcessId> {
+        //verify device state
+        self.check_state(false)?;
+
+        //verify file has been loaded on the device
+        let path = self.loadable.loaded_path(name).ok_or_else(|| {
+            ExecutableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{name}' not loaded"),
+            }
+        })?;
+
+        //verify options
+        let invalid_opts: Properties = options
+            .iter()
+            .filter(|o| match (o.id.as_str(), &o.value) {
+                (STACK_SIZE_ID | PRIORITY_ID, AnyValue::ULong(_)) => false,
+                (PROCESS_COLLOCATION_ID | ENTRY_POINT_ID, AnyValue::String(_)) => false,
+                (CORE_AFFINITY_ID, AnyValue::Sequence(v)) => !v.iter().all(AnyValue::is_simple),
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        if !invalid_opts.is_empty() {
+            return Err(ExecutableDeviceError::InvalidOptions { invalid_opts });
+        }
+
+        //verify parameters
+        let invalid_parms: Properties = parameters
+            .iter()
+            .filter(|p| p.id.is_empty() || p.id.contains(char::is_whitespace) || !p.value.is_simple())
+            .cloned()
+            .collect();
+        if !invalid_parms.is_empty() {
+            return Err(ExecutableDeviceError::InvalidParameters { invalid_parms });
+        }
+
+        //spawn native process
+        let args = self.exec_params(name, parameters);
+        let mut command = Command::new(&path);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = spawn(&mut command)?;
+
+        //capture process output
+        let output: Arc<Mutex<ProcessOutput>> = Arc::default();
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            let output = output.clone();
+            readers.push(thread::spawn(move || {
+                capture(stdout, |line| push_line(&mut output.lock().unwrap().stdout, line))
+            }));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            let output = output.clone();
+            readers.push(thread::spawn(move || {
+                capture(stderr, |line| push_line(&mut output.lock().unwrap().stderr, line))
+            }));
+        }
+
+        //update internal state
+        let process_id = child.id();
+        self.processes.lock().unwrap().insert(
+            process_id,
+            Process {
+                name: name.to_string(),
+                child,
+                output,
+                readers,
+            },
+        );
+
+        //return process identifier
+        Ok(process_id)
+    }
+
+    /**
+     * SCA289
+     * The terminate operation shall terminate the execution of the process designated
+     * by the input processId parameter on the device.
+     * SCA290
+     * The terminate operation shall raise the CF::InvalidState exception if upon entry
+     * the ExecutableDeviceComponent's adminState attribute is LOCKED.
+     * SCA515
+     * The terminate operation shall raise the CF::InvalidState exception if upon entry
+     * the ExecutableDeviceComponent's operationalState attribute is DISABLED.
+     * SCA291
+     * The terminate operation shall raise the InvalidProcess exception when the
+     * processId does not exist for the device.
+     */
+    fn terminate(&mut self, process_id: ProcessId) -> Result<()> {
+        //verify device state
+        self.check_state(true)?;
+
+        //verify process existence
+        let mut p = self
+            .processes
+            .lock()
+            .unwrap()
+            .remove(&process_id)
+            .ok_or_else(|| ExecutableDeviceError::InvalidProcess {
+                error_number: ErrorNumberType::CF_ESRCH,
+                message: format!("process {process_id} not found"),
+            })?;
+
+        //stop native process
+        let _ = p.child.kill();
+        p.child.wait()?;
+
+        //return ok
+        Ok(())
+    }
+
+    /**
+     * Collects the usage of a running process from the operating system,
+     * the CPU usage being measured since the previous collection.
+     */
+    fn process_metrics(&mut self, process_id: ProcessId) -> Result<ProcessMetrics> {
+        let not_found = || ExecutableDeviceError::InvalidProcess {
+            error_number: ErrorNumberType::CF_ESRCH,
+            message: format!("process {process_id} not found"),
+        };
+
+        //verify process existence
+        if !self.processes.lock().unwrap().contains_key(&process_id) {
+            return Err(not_found());
+        }
+
+        let pid = Pid::from_u32(process_id);
+        self.system.refresh_process(pid);
+        let process = self.system.process(pid).ok_or_else(not_found)?;
+        Ok(ProcessMetrics {
+            process_id,
+            cpu_usage: process.cpu_usage(),
+            memory: process.memory(),
+            status: process.status().into(),
+        })
+    }
+}
+
+/**
+ * Spawns the command, retrying while the freshly loaded file is still
+ * held open for writing by a concurrently forked process (ETXTBSY).
+ */
+fn spawn(command: &mut Command) -> std::io::Result<Child> {
+    const ETXTBSY: i32 = 26;
+
+    let mut retries = 10;
+    loop {
+        match command.spawn() {
+            Err(e) if e.raw_os_error() == Some(ETXTBSY) && retries > 0 => {
+                retries -= 1;
+                thread::sleep(SUPERVISOR_PERIOD / 10);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn push_line(lines: &mut VecDeque<String>, line: String) {
+    if lines.len() == OUTPUT_LINES {
+        lines.pop_front();
+    }
+    lines.push_back(line);
+}
+
+fn capture(stream: impl Read, mut sink: impl FnMut(String)) {
+    for line in BufReader::new(stream).lines() {
+        match line {
+            Ok(line) => sink(line),
+            Err(_) => break,
+        }
+    }
+}
+
+/**
+ * Polls the running processes until the owning device is dropped,
+ * reporting the ones exiting on their own to the exit listeners.
+ */
+fn supervise(table: Weak<Mutex<HashMap<ProcessId, Process>>>, listeners: ExitListeners) {
+    while let Some(processes) = table.upgrade() {
+        let mut exited = Vec::new();
+        {
+            let mut processes = processes.lock().unwrap();
+            let ids: Vec<ProcessId> = processes.keys().copied().collect();
+            for id in ids {
+                let status = processes.get_mut(&id).and_then(|p| p.child.try_wait().ok().flatten());
+                if let Some(status) = status {
+                    exited.push((processes.remove(&id).unwrap(), status));
+                }
+            }
+        }
+        drop(processes);
+
+        for (p, status) in exited {
+            //give the readers a chance to drain the pipes
+            for _ in 0..10 {
+                if p.readers.iter().all(|r| r.is_finished()) {
+                    break;
+                }
+                thread::sleep(SUPERVISOR_PERIOD / 10);
+            }
+
+            let report = ProcessExit {
+                process_id: p.child.id(),
+                name: p.name,
+                code: status.code(),
+                abnormal: !status.success(),
+                stderr: p.output.lock().unwrap().stderr.iter().cloned().collect(),
+            };
+            listeners
+                .lock()
+                .unwrap()
+                .retain(|l| l.send(report.clone()).is_ok());
+        }
+
+        thread::sleep(SUPERVISOR_PERIOD);
+    }
+}
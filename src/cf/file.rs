@@ -1,4 +1,7 @@
 use std::{io::{Read, Seek, SeekFrom, Write}, path::Path};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
 use thiserror::Error;
 
 use super::common_types::ErrorNumberType;
@@ -67,7 +70,7 @@ pub trait FileTrait {
     fn read(&mut self, buffer: &mut Vec<u8>) -> Result<usize, FileError>;
 
     /// This operation writes data to the file referenced.
-    fn write(&mut self, data: &Vec<u8>) -> Result<()>;
+    fn write(&mut self, data: &[u8]) -> Result<()>;
 
     /// This operation returns the current size of the file.
     fn size_of(&self) -> Result<u64>;
@@ -77,6 +80,32 @@ pub trait FileTrait {
 
     /// This operation positions the file pointer where next read or write will occur.
     fn set_file_pointer(&mut self, file_pointer: u64) -> Result<()>;
+
+    /**
+     * This operation reads up to length octets at an offset, neither using
+     * nor moving the file pointer, so that concurrent readers of disjoint
+     * ranges do not serialize on it. Fewer octets are returned past the end
+     * of the file.
+     */
+    fn read_at(&self, _offset: u64, _length: usize) -> Result<Vec<u8>> {
+        Err(FileError::FileException {
+            error_number: ErrorNumberType::CF_ENOTSUP,
+            message: "positional reads are not supported".to_string(),
+        })
+    }
+
+    /// This operation writes the octets the file buffers, none by default.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /**
+     * This operation flushes the file, then waits for its data to reach
+     * the storage: the durability point of the writes returned before.
+     */
+    fn sync_all(&mut self) -> Result<()> {
+        self.flush()
+    }
 }
 
 #[derive(Error, Debug)]
@@ -92,38 +121,228 @@ impl From<NoneFileHandleError> for FileError {
     }
 }
 
+/**
+ * The reads and writes of the native handles at an offset: by pread and
+ * pwrite on unix, leaving the position as is, by the seeking ones on
+ * Windows, and by a seek then a read or a write elsewhere, the position
+ * being moved there. The reads and writes of the file seek to its file
+ * pointer first.
+ */
+pub(crate) trait PositionalFile {
+    fn read_at(&self, buffer: &mut [u8], offset: u64) -> std::io::Result<usize>;
+
+    fn write_all_at(&self, data: &[u8], offset: u64) -> std::io::Result<()>;
+}
+
+#[cfg(unix)]
+impl PositionalFile for std::fs::File {
+    fn read_at(&self, buffer: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buffer, offset)
+    }
+
+    fn write_all_at(&self, data: &[u8], offset: u64) -> std::io::Result<()> {
+        std::os::unix::fs::FileExt::write_all_at(self, data, offset)
+    }
+}
+
+#[cfg(windows)]
+impl PositionalFile for std::fs::File {
+    fn read_at(&self, buffer: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buffer, offset)
+    }
+
+    fn write_all_at(&self, mut data: &[u8], mut offset: u64) -> std::io::Result<()> {
+        while !data.is_empty() {
+            match std::os::windows::fs::FileExt::seek_write(self, data, offset) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    data = &data[written..];
+                    offset += written as u64;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+impl PositionalFile for std::fs::File {
+    fn read_at(&self, buffer: &mut [u8], offset: u64) -> std::io::Result<usize> {
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read(buffer)
+    }
+
+    fn write_all_at(&self, data: &[u8], offset: u64) -> std::io::Result<()> {
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        file.write_all(data)
+    }
+}
+
+/**
+ * The octets written behind, contiguous from an offset, and the error
+ * of the last background flush, returned by the next operation.
+ */
+#[derive(Debug)]
+struct DirtyBuffer {
+    handle: std::fs::File,
+    offset: u64,
+    data: Vec<u8>,
+    error: Option<std::io::Error>,
+    closed: bool,
+}
+
+impl DirtyBuffer {
+    fn flush(&mut self) -> std::io::Result<()> {
+        if !self.data.is_empty() {
+            self.handle.write_all_at(&self.data, self.offset)?;
+            self.offset += self.data.len() as u64;
+            self.data.clear();
+        }
+        Ok(())
+    }
+}
+
+/**
+ * The write-behind mode of a file: its dirty buffer, bounded by a
+ * capacity, and the background flusher writing it at an interval.
+ * Dropped, the flusher writes the last octets and stops.
+ */
+#[derive(Debug)]
+struct WriteBehind {
+    capacity: usize,
+    dirty: Arc<(Mutex<DirtyBuffer>, Condvar)>,
+    flusher: Option<JoinHandle<()>>,
+}
+
+impl WriteBehind {
+    /// Writes the dirty octets, returning the error of a background flush first.
+    fn flush(&self) -> Result<()> {
+        let mut dirty = self.dirty.0.lock().unwrap();
+        if let Some(e) = dirty.error.take() {
+            return Err(e.into());
+        }
+        Ok(dirty.flush()?)
+    }
+}
+
+impl Drop for WriteBehind {
+    fn drop(&mut self) {
+        let (lock, wake) = &*self.dirty;
+        lock.lock().unwrap().closed = true;
+        wake.notify_all();
+        if let Some(flusher) = self.flusher.take() {
+            let _ = flusher.join();
+        }
+    }
+}
+
+/// Writes the dirty octets at each interval, and once more when closed.
+fn flush_behind(dirty: &(Mutex<DirtyBuffer>, Condvar), interval: Duration) {
+    let (lock, wake) = dirty;
+    let mut buffer = lock.lock().unwrap();
+    loop {
+        let closed = buffer.closed;
+        if buffer.error.is_none() {
+            if let Err(e) = buffer.flush() {
+                buffer.error = Some(e);
+            }
+        }
+        if closed {
+            return;
+        }
+        buffer = wake.wait_timeout(buffer, interval).unwrap().0;
+    }
+}
+
 #[derive(Debug)]
-pub struct File<'a> {
-    file_name: &'a String,
+pub struct File {
+    file_name: String,
     file_handle: Option<std::fs::File>,
     file_pointer: u64,
+    write_behind: Option<WriteBehind>,
 }
 
-impl<'a> File<'a> {
-    pub fn open(file_name: &'a String, root_path: &Path) -> Result<File<'a>> {
+impl File {
+    pub fn open(file_name: impl Into<String>, root_path: &Path) -> Result<File> {
+        let file_name = file_name.into();
 
-        let file_handle = std::fs::File::open(root_path.join(file_name))?;
+        let file_handle = std::fs::File::open(root_path.join(&file_name))?;
 
         Ok(File {
             file_name,
             file_handle: Some(file_handle),
             file_pointer: 0u64,
+            write_behind: None,
         })
     }
 
-    pub fn create(file_name: &'a String, root_path: &Path) -> Result<File<'a>> {
+    /// Wraps a file already opened, e.g. by a file system bounding its opens.
+    pub fn from_handle(file_name: impl Into<String>, file_handle: std::fs::File) -> File {
+        File {
+            file_name: file_name.into(),
+            file_handle: Some(file_handle),
+            file_pointer: 0u64,
+            write_behind: None,
+        }
+    }
 
-        let file_handle = std::fs::File::create(root_path.join(file_name))?;
+    pub fn create(file_name: impl Into<String>, root_path: &Path) -> Result<File> {
+        let file_name = file_name.into();
+
+        // read and written, the octets written being read back
+        let file_handle = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(root_path.join(&file_name))?;
 
         Ok(File {
             file_name,
             file_handle: Some(file_handle),
             file_pointer: 0u64,
+            write_behind: None,
         })
     }
+
+    /**
+     * Writes the file behind: the octets written are buffered, up to the
+     * capacity past which the writes flush them, and written by a
+     * background flusher at the interval, so that the bursts of writes
+     * do not wait for the storage. The flush and sync_all operations are
+     * the durability points of the writes.
+     */
+    pub fn with_write_behind(mut self, capacity: usize, interval: Duration) -> Result<File> {
+        let h = self.file_handle.as_ref().ok_or(NoneFileHandleError)?;
+
+        let dirty = Arc::new((
+            Mutex::new(DirtyBuffer {
+                handle: h.try_clone()?,
+                offset: self.file_pointer,
+                data: Vec::with_capacity(capacity),
+                error: None,
+                closed: false,
+            }),
+            Condvar::new(),
+        ));
+        let flusher = {
+            let dirty = dirty.clone();
+            std::thread::spawn(move || flush_behind(&dirty, interval))
+        };
+        self.write_behind = Some(WriteBehind {
+            capacity,
+            dirty,
+            flusher: Some(flusher),
+        });
+        Ok(self)
+    }
 }
 
-impl<'a> FileTrait for File<'a> {
+impl FileTrait for File {
 
     /** 
      * SCA320
@@ -131,8 +350,8 @@ impl<'a> FileTrait for File<'a> {
      * fileName parameter of the FileSystem::create operation when the file was
      * created.
      */
-    fn file_name(&self) -> &'a String {
-        self.file_name
+    fn file_name(&self) -> &String {
+        &self.file_name
     }
 
     /**
@@ -165,6 +384,13 @@ impl<'a> FileTrait for File<'a> {
         //verify if 'file_handle' is still valid
         let h = self.file_handle.as_mut().ok_or(NoneFileHandleError)?;
 
+        // the octets written behind are read once flushed
+        if let Some(write_behind) = &self.write_behind {
+            write_behind.flush()?;
+        }
+        // from the file pointer, the positional operations moving the position elsewhere
+        h.seek(SeekFrom::Start(self.file_pointer))?;
+
         let result = h.read(buffer)?;
         self.file_pointer += result as u64;
 
@@ -183,11 +409,34 @@ impl<'a> FileTrait for File<'a> {
      * SCA330
      * The write operation shall raise the IOException when a write error occurs.
      */
-    fn write(&mut self, buffer: &Vec<u8>) -> Result<()> {
+    fn write(&mut self, buffer: &[u8]) -> Result<()> {
         //verify if 'file_handle' is still valid
         let h = self.file_handle.as_mut().ok_or(NoneFileHandleError)?;
 
-        // write to native file until whole buffer is consumed
+        // written behind, the octets are appended to the dirty ones, flushed past the capacity
+        if let Some(write_behind) = &self.write_behind {
+            let mut dirty = write_behind.dirty.0.lock().unwrap();
+            if let Some(e) = dirty.error.take() {
+                return Err(e.into());
+            }
+            if dirty.offset + dirty.data.len() as u64 != self.file_pointer {
+                dirty.flush()?;
+                dirty.offset = self.file_pointer;
+            }
+            let dirty_length = dirty.data.len();
+            dirty.data.extend_from_slice(buffer);
+            if dirty.data.len() > write_behind.capacity {
+                if let Err(e) = dirty.flush() {
+                    dirty.data.truncate(dirty_length);
+                    return Err(e.into());
+                }
+            }
+            self.file_pointer += buffer.len() as u64;
+            return Ok(());
+        }
+
+        // write to native file, from the file pointer, until whole buffer is consumed
+        h.seek(SeekFrom::Start(self.file_pointer))?;
         let target = buffer.len();
         let mut actual = 0;
         while actual < target {
@@ -211,6 +460,12 @@ impl<'a> FileTrait for File<'a> {
         let h = self.file_handle.as_ref().ok_or(NoneFileHandleError)?;
 
         let metadata = h.metadata()?;
+
+        // the octets written behind extend the file
+        if let Some(write_behind) = &self.write_behind {
+            let dirty = write_behind.dirty.0.lock().unwrap();
+            return Ok(metadata.len().max(dirty.offset + dirty.data.len() as u64));
+        }
         Ok(metadata.len())
     }
 
@@ -222,8 +477,13 @@ impl<'a> FileTrait for File<'a> {
      * close the file.
      */
     fn close(&mut self) -> Result<()> {
+        // the octets written behind are flushed, the file closed whatever the outcome
+        let flushed = match self.write_behind.take() {
+            Some(write_behind) => write_behind.flush(),
+            None => Ok(()),
+        };
         self.file_handle = None;
-        Ok(())
+        flushed
     }
 
 
@@ -241,15 +501,13 @@ impl<'a> FileTrait for File<'a> {
      */
     fn set_file_pointer(&mut self, file_pointer: u64) -> Result<()> {
         
-        //verify if 'file_handle' is still valid
-        let h = self.file_handle.as_mut().ok_or(NoneFileHandleError)?;
-
-        //not allowed to move beyond end of file
-        if file_pointer > h.metadata()?.len() {
+        //not allowed to move beyond end of file, 'size_of' verifying 'file_handle'
+        if file_pointer > self.size_of()? {
             return Err(FileError::InvalidFilePointer);
         }
 
         //move native handler to requested position
+        let h = self.file_handle.as_mut().ok_or(NoneFileHandleError)?;
         h.seek(SeekFrom::Start(file_pointer))?;
 
         //update internal state
@@ -258,4 +516,60 @@ impl<'a> FileTrait for File<'a> {
         //return ok
         Ok(())
     }
+
+    /**
+     * The octets are read by pread, the native handle being shared by the
+     * concurrent readers without moving its position on unix.
+     */
+    fn read_at(&self, offset: u64, length: usize) -> Result<Vec<u8>> {
+        //verify if 'file_handle' is still valid
+        let h = self.file_handle.as_ref().ok_or(NoneFileHandleError)?;
+
+        // the octets written behind are read once flushed
+        if let Some(write_behind) = &self.write_behind {
+            write_behind.flush()?;
+        }
+
+        // the octets past the end of file are not allocated, the offset plus the length read not overflowing
+        let available = self.size_of()?.saturating_sub(offset);
+        let length = length.min(usize::try_from(available).unwrap_or(usize::MAX));
+
+        // read until the length is reached or the end of file
+        let mut buffer = vec![0u8; length];
+        let mut actual = 0;
+        while actual < length {
+            match h.read_at(&mut buffer[actual..], offset + actual as u64) {
+                Ok(0) => break,
+                Ok(result) => actual += result,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        buffer.truncate(actual);
+
+        Ok(buffer)
+    }
+
+    /**
+     * The octets written behind are written, the error of a background
+     * flush returned first.
+     */
+    fn flush(&mut self) -> Result<()> {
+        //verify if 'file_handle' is still valid
+        self.file_handle.as_ref().ok_or(NoneFileHandleError)?;
+
+        match &self.write_behind {
+            Some(write_behind) => write_behind.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn sync_all(&mut self) -> Result<()> {
+        self.flush()?;
+
+        let h = self.file_handle.as_ref().ok_or(NoneFileHandleError)?;
+        h.sync_all()?;
+
+        Ok(())
+    }
 }
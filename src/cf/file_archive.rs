@@ -0,0 +1,158 @@
+use std::collections::VecDeque;
+
+use thiserror::Error;
+
+use super::checksum;
+use super::file_system::relative_path;
+use super::rpc::file_system::{ArchiveChunk, ArchiveEntry};
+
+/**
+ * Convienence enum definition that includes all file archive errors.
+ */
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    /**
+     * This exception indicates that the CRC32C of a chunk of an archive
+     * differs from the one of its data.
+     */
+    #[error("CorruptedChunk: offset: {offset}.")]
+    CorruptedChunk { offset: u64 },
+    /**
+     * This exception indicates that the data of an archive does not match
+     * its entries, e.g. data past the last file or a file cut short, or
+     * that an entry names a file escaping the file system.
+     */
+    #[error("MalformedArchive: msg: '{message}'.")]
+    MalformedArchive { message: String },
+}
+
+/*
+ * Convienence type definition that includes all file archive returned errors.
+ */
+pub type Result<T, E = ArchiveError> = anyhow::Result<T, E>;
+
+/**
+ * Packs files into the chunks of an archive, the small files sharing a
+ * chunk and the large ones spanning several, each chunk holding up to
+ * chunk_size octets of data. An archive of no file is a single empty
+ * chunk.
+ */
+pub fn pack<N: Into<String>, D: AsRef<[u8]>>(
+    files: impl IntoIterator<Item = (N, D)>,
+    chunk_size: usize,
+) -> Vec<ArchiveChunk> {
+    let chunk_size = chunk_size.max(1);
+    let mut chunks = Vec::new();
+    let mut chunk = ArchiveChunk::default();
+    for (file_name, data) in files {
+        let mut data = data.as_ref();
+        chunk.entries.push(ArchiveEntry {
+            file_name: file_name.into(),
+            size: data.len() as u64,
+        });
+        while !data.is_empty() {
+            let length = data.len().min(chunk_size - chunk.data.len());
+            chunk.data.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if chunk.data.len() == chunk_size {
+                chunks.push(seal(std::mem::take(&mut chunk)));
+            }
+        }
+    }
+    if chunks.is_empty() || !chunk.entries.is_empty() || !chunk.data.is_empty() {
+        chunks.push(seal(chunk));
+    }
+    chunks
+}
+
+fn seal(mut chunk: ArchiveChunk) -> ArchiveChunk {
+    chunk.crc32c = Some(checksum::crc32c(&chunk.data));
+    chunk
+}
+
+/**
+ * Unpacks the files of the chunks of an archive, pushed in their order,
+ * the chunks of a CRC32C being verified and the names of the entries
+ * being absolute pathnames within the file system.
+ */
+#[derive(Debug, Default)]
+pub struct Unpacker {
+    entries: VecDeque<ArchiveEntry>,
+    file: Option<(String, usize, Vec<u8>)>,
+    files: Vec<(String, Vec<u8>)>,
+    offset: u64,
+}
+
+impl Unpacker {
+    pub fn new() -> Unpacker {
+        Unpacker::default()
+    }
+
+    /// Unpacks a chunk, completing the files its data ends.
+    pub fn push(&mut self, chunk: ArchiveChunk) -> Result<()> {
+        if chunk
+            .crc32c
+            .is_some_and(|crc| crc != checksum::crc32c(&chunk.data))
+        {
+            return Err(ArchiveError::CorruptedChunk {
+                offset: self.offset,
+            });
+        }
+        if let Some(entry) = chunk
+            .entries
+            .iter()
+            .find(|e| relative_path(&e.file_name).is_err())
+        {
+            return Err(ArchiveError::MalformedArchive {
+                message: format!("'{}' is not a pathname of the file system", entry.file_name),
+            });
+        }
+        self.entries.extend(chunk.entries);
+        let mut data = &chunk.data[..];
+        loop {
+            if self.file.is_none() {
+                let Some(entry) = self.entries.pop_front() else {
+                    break;
+                };
+                // The size announced is not trusted for the allocation
+                let size =
+                    usize::try_from(entry.size).map_err(|_| ArchiveError::MalformedArchive {
+                        message: format!(
+                            "'{}' of {} octets does not fit in memory",
+                            entry.file_name, entry.size
+                        ),
+                    })?;
+                let content = Vec::with_capacity(size.min(data.len()));
+                self.file = Some((entry.file_name, size, content));
+            }
+            let (_, size, content) = self.file.as_mut().unwrap();
+            let length = (*size - content.len()).min(data.len());
+            content.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if content.len() < *size {
+                break;
+            }
+            let (file_name, _, content) = self.file.take().unwrap();
+            self.files.push((file_name, content));
+        }
+        if !data.is_empty() {
+            return Err(ArchiveError::MalformedArchive {
+                message: format!("{} octets past the last file", data.len()),
+            });
+        }
+        self.offset += chunk.data.len() as u64;
+        Ok(())
+    }
+
+    /// Returns the files unpacked, in the order of their entries.
+    pub fn finish(self) -> Result<Vec<(String, Vec<u8>)>> {
+        let cut = match (self.file, self.entries.front()) {
+            (Some((file_name, _, _)), _) => file_name,
+            (None, Some(entry)) => entry.file_name.clone(),
+            (None, None) => return Ok(self.files),
+        };
+        Err(ArchiveError::MalformedArchive {
+            message: format!("the archive ends before the end of '{cut}'"),
+        })
+    }
+}
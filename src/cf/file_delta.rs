@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use super::checksum::{self, RollingChecksum};
+use super::rpc::file_system::{BlockSignature, DeltaRequest, SignatureReply};
+
+/// The bounds of the block size of the signatures.
+pub const MIN_BLOCK_SIZE: usize = 1024;
+pub const MAX_BLOCK_SIZE: usize = 128 * 1024;
+
+/**
+ * Convienence enum definition that includes all file delta errors.
+ */
+#[derive(Error, Debug)]
+pub enum DeltaError {
+    /**
+     * This exception indicates that the CRC32C of the data of a request of
+     * a delta differs from the one of its data.
+     */
+    #[error("CorruptedChunk: offset: {offset}.")]
+    CorruptedChunk { offset: u64 },
+    /**
+     * This exception indicates that a delta does not match its basis, e.g.
+     * blocks copied past the end of the basis or a block size of zero.
+     */
+    #[error("MalformedDelta: msg: '{message}'.")]
+    MalformedDelta { message: String },
+}
+
+/*
+ * Convienence type definition that includes all file delta returned errors.
+ */
+pub type Result<T, E = DeltaError> = anyhow::Result<T, E>;
+
+/// Returns the block size fitting a file, the square root of its size within the bounds, as rsync.
+pub fn block_size(size: u64) -> usize {
+    ((size as f64).sqrt() as usize).clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE)
+}
+
+/// Returns the signature of a file, the checksums of its blocks.
+pub fn signature(data: &[u8], block_size: usize) -> SignatureReply {
+    SignatureReply {
+        size: data.len() as u64,
+        block_size: block_size as u64,
+        blocks: data
+            .chunks(block_size.max(1))
+            .map(block_signature)
+            .collect(),
+    }
+}
+
+/// Returns the checksums of a block, e.g. of a file read block by block.
+pub fn block_signature(block: &[u8]) -> BlockSignature {
+    BlockSignature {
+        weak: RollingChecksum::new(block).value(),
+        strong: checksum::digest(block),
+    }
+}
+
+/// An operation of a delta, the file written being the concatenation of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeltaOp {
+    /// The data of a range of the file, sent.
+    Data(Range<usize>),
+    /// Consecutive blocks of the basis, copied.
+    Copy { block: u64, blocks: u64 },
+}
+
+/**
+ * Returns the delta of a file against the signature of its basis, as
+ * rsync: the rolling checksum of a window of the block size is looked up
+ * at each offset of the file, and the blocks of the same weak and strong
+ * checksums are copied, the data between them being sent. The short last
+ * block of the basis only matches the end of the file.
+ */
+pub fn delta(signature: &SignatureReply, data: &[u8]) -> Vec<DeltaOp> {
+    let block_size = usize::try_from(signature.block_size).unwrap_or_default();
+    let mut ops = Vec::new();
+    if block_size == 0 {
+        push_data(&mut ops, 0..data.len());
+        return ops;
+    }
+    let full_blocks = usize::try_from(signature.size / signature.block_size).unwrap_or(usize::MAX);
+    let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (block, s) in signature.blocks.iter().enumerate().take(full_blocks) {
+        index.entry(s.weak).or_default().push(block);
+    }
+    let matched = |weak: u32, window: &[u8], expected: u64| {
+        let candidates = index.get(&weak)?;
+        let strong = checksum::digest(window);
+        // The block following the previous one copied is preferred, extending the copy
+        candidates
+            .iter()
+            .copied()
+            .filter(|block| signature.blocks[*block].strong == strong)
+            .min_by_key(|block| *block as u64 != expected)
+    };
+
+    let (mut start, mut offset) = (0, 0);
+    let mut rolling: Option<RollingChecksum> = None;
+    while offset + block_size <= data.len() {
+        let window = &data[offset..offset + block_size];
+        let weak = rolling
+            .get_or_insert_with(|| RollingChecksum::new(window))
+            .value();
+        let expected = match ops.last() {
+            Some(DeltaOp::Copy { block, blocks }) if start == offset => block + blocks,
+            _ => u64::MAX,
+        };
+        if let Some(block) = matched(weak, window, expected) {
+            push_data(&mut ops, start..offset);
+            push_copy(&mut ops, block as u64);
+            offset += block_size;
+            start = offset;
+            rolling = None;
+            continue;
+        }
+        if let (Some(rolling), Some(next)) = (rolling.as_mut(), data.get(offset + block_size)) {
+            rolling.roll(data[offset], *next);
+        }
+        offset += 1;
+    }
+
+    let tail = (signature.size % signature.block_size) as usize;
+    if tail > 0 && data.len() >= start + tail {
+        let window = &data[data.len() - tail..];
+        let last = signature.blocks.last().filter(|s| {
+            s.weak == RollingChecksum::new(window).value() && s.strong == checksum::digest(window)
+        });
+        if last.is_some() {
+            push_data(&mut ops, start..data.len() - tail);
+            push_copy(&mut ops, signature.blocks.len() as u64 - 1);
+            start = data.len();
+        }
+    }
+    push_data(&mut ops, start..data.len());
+    ops
+}
+
+fn push_data(ops: &mut Vec<DeltaOp>, range: Range<usize>) {
+    if !range.is_empty() {
+        ops.push(DeltaOp::Data(range));
+    }
+}
+
+fn push_copy(ops: &mut Vec<DeltaOp>, block: u64) {
+    match ops.last_mut() {
+        Some(DeltaOp::Copy {
+            block: first,
+            blocks,
+        }) if *first + *blocks == block => *blocks += 1,
+        _ => ops.push(DeltaOp::Copy { block, blocks: 1 }),
+    }
+}
+
+/**
+ * Returns the requests of a delta, the data sent in chunks of at most
+ * chunk_size octets, of their CRC32C, each followed by the blocks copied
+ * after it. The delta of an empty file is a single empty request.
+ */
+pub fn requests(
+    file_name: &str,
+    block_size: usize,
+    data: &[u8],
+    ops: &[DeltaOp],
+    chunk_size: usize,
+) -> Vec<DeltaRequest> {
+    let mut requests: Vec<DeltaRequest> = Vec::new();
+    for op in ops {
+        match op {
+            DeltaOp::Data(range) => {
+                for chunk in data[range.clone()].chunks(chunk_size.max(1)) {
+                    requests.push(DeltaRequest {
+                        data: chunk.to_vec(),
+                        crc32c: Some(checksum::crc32c(chunk)),
+                        ..Default::default()
+                    });
+                }
+            }
+            DeltaOp::Copy { block, blocks } => match requests.last_mut() {
+                Some(last) if last.blocks == 0 => (last.block, last.blocks) = (*block, *blocks),
+                _ => requests.push(DeltaRequest {
+                    block: *block,
+                    blocks: *blocks,
+                    ..Default::default()
+                }),
+            },
+        }
+    }
+    if requests.is_empty() {
+        requests.push(DeltaRequest::default());
+    }
+    requests[0].file_name = file_name.to_string();
+    requests[0].block_size = block_size as u64;
+    requests
+}
+
+/**
+ * Appends a request of a delta to the file patched: its data, verified
+ * by its CRC32C, then the blocks of the basis it copies.
+ */
+pub fn patch(
+    basis: &[u8],
+    block_size: usize,
+    request: &DeltaRequest,
+    file: &mut Vec<u8>,
+) -> Result<()> {
+    verify(request, file.len() as u64)?;
+    file.extend_from_slice(&request.data);
+    if let Some(copied) = copied(request, block_size, basis.len() as u64)? {
+        file.extend_from_slice(&basis[copied.start as usize..copied.end as usize]);
+    }
+    Ok(())
+}
+
+/// Verifies the CRC32C of the data of a request of a delta, appended at an offset of the file patched.
+pub fn verify(request: &DeltaRequest, offset: u64) -> Result<()> {
+    if request
+        .crc32c
+        .is_some_and(|crc| crc != checksum::crc32c(&request.data))
+    {
+        return Err(DeltaError::CorruptedChunk { offset });
+    }
+    Ok(())
+}
+
+/**
+ * Returns the range of the basis of size octets copied by a request of a
+ * delta, none when it copies no block, e.g. to copy a large basis in
+ * chunks rather than loading it.
+ */
+pub fn copied(request: &DeltaRequest, block_size: usize, size: u64) -> Result<Option<Range<u64>>> {
+    if request.blocks == 0 {
+        return Ok(None);
+    }
+    let block_size = block_size as u64;
+    // The last block copied starts within the basis
+    let last = request
+        .block
+        .checked_add(request.blocks - 1)
+        .and_then(|last| last.checked_mul(block_size))
+        .filter(|last| block_size > 0 && *last < size);
+    if last.is_none() {
+        return Err(DeltaError::MalformedDelta {
+            message: format!(
+                "the blocks {}..{} are out of the basis of {size} octets in blocks of {block_size}",
+                request.block,
+                request.block.saturating_add(request.blocks)
+            ),
+        });
+    }
+    let start = request.block * block_size;
+    let end = request
+        .block
+        .saturating_add(request.blocks)
+        .saturating_mul(block_size)
+        .min(size);
+    Ok(Some(start..end))
+}
@@ -0,0 +1,336 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use super::common_types::ErrorNumberType;
+use super::file_system::{
+    self, relative_path, wildcard_match, FileInformationType, FileSystemError, FileSystemRef,
+    FileSystemSpace, FileSystemTrait, FileType,
+};
+
+/**
+ * Convienence enum definition that includes all FileManager errors.
+ */
+#[derive(Error, Debug)]
+pub enum FileManagerError {
+    /**
+     * This exception indicates the mount point does not conform to the
+     * file name syntax.
+     */
+    #[error("InvalidFileName: num: {error_number:?}, msg: '{message}'.")]
+    InvalidFileName {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates a mount point is already in use in the
+     * file manager.
+     */
+    #[error("MountPointAlreadyExists: mount point: '{mount_point}'.")]
+    MountPointAlreadyExists { mount_point: String },
+    /**
+     * This exception indicates a mount point does not exist within the
+     * file manager.
+     */
+    #[error("NonExistentMount: mount point: '{mount_point}'.")]
+    NonExistentMount { mount_point: String },
+}
+
+/*
+ * Convienence type definition that includes all FileManager returned errors.
+ */
+pub type Result<T, E = FileManagerError> = anyhow::Result<T, E>;
+
+/**
+ * This type describes a file system mounted in the file manager.
+ */
+#[derive(Clone)]
+pub struct MountType {
+    pub mount_point: String,
+    pub file_system: FileSystemRef,
+}
+
+impl std::fmt::Debug for MountType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MountType")
+            .field("mount_point", &self.mount_point)
+            .finish()
+    }
+}
+
+/**
+ * Convienence type definition to share the file manager.
+ */
+pub type FileManagerRef = Arc<Mutex<FileManager>>;
+
+/**
+ * The file manager gathers the file systems of the domain under a
+ * single namespace, each mounted file system being reached through the
+ * pathnames starting with its mount point.
+ */
+#[derive(Debug, Default, Clone)]
+pub struct FileManager {
+    mounts: Vec<MountType>,
+}
+
+impl FileManager {
+    pub fn new() -> FileManager {
+        FileManager::default()
+    }
+
+    /**
+     * SCA376
+     * The mount operation shall associate the specified file system with the
+     * mount point referenced by the input mountPoint parameter.
+     * SCA377
+     * A mount point name shall begin with a "/" (forward slash character).
+     * SCA378
+     * The mount operation shall raise the MountPointAlreadyExists exception
+     * when the mount point already exists in the file manager.
+     */
+    pub fn mount(&mut self, mount_point: &str, file_system: FileSystemRef) -> Result<()> {
+        //verify the mount point is an absolute name below the root
+        if relative_path(mount_point).is_err()
+            || mount_point.len() < 2
+            || mount_point.ends_with('/')
+        {
+            return Err(FileManagerError::InvalidFileName {
+                error_number: ErrorNumberType::CF_EINVAL,
+                message: format!("invalid mount point '{mount_point}'"),
+            });
+        }
+
+        if self.mounts.iter().any(|m| m.mount_point == mount_point) {
+            return Err(FileManagerError::MountPointAlreadyExists {
+                mount_point: mount_point.to_string(),
+            });
+        }
+
+        self.mounts.push(MountType {
+            mount_point: mount_point.to_string(),
+            file_system,
+        });
+        Ok(())
+    }
+
+    /**
+     * SCA380
+     * The unmount operation shall remove a mounted file system from the file
+     * manager whose mounted name matches the input mountPoint name.
+     */
+    pub fn unmount(&mut self, mount_point: &str) -> Result<()> {
+        let index = self
+            .mounts
+            .iter()
+            .position(|m| m.mount_point == mount_point)
+            .ok_or_else(|| FileManagerError::NonExistentMount {
+                mount_point: mount_point.to_string(),
+            })?;
+        self.mounts.remove(index);
+        Ok(())
+    }
+
+    /// This operation returns the file systems mounted in the file manager.
+    pub fn get_mounts(&self) -> &[MountType] {
+        &self.mounts
+    }
+
+    /**
+     * Returns the local directory of the file system a pathname belongs
+     * to and the path of the file relative to it, as expected by the
+     * load operation of the devices.
+     */
+    pub fn local_file(&self, file_name: &str) -> file_system::Result<(PathBuf, String)> {
+        let (file_system, name) = self.resolve(file_name)?;
+        let root = file_system
+            .local_root()
+            .ok_or_else(|| FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOTSUP,
+                message: format!("'{file_name}' is not on a local file system"),
+            })?;
+        Ok((root.to_path_buf(), name.trim_start_matches('/').to_string()))
+    }
+
+    /**
+     * Returns the file system of a pathname, the deepest mount point
+     * winning, and the pathname within that file system.
+     */
+    fn resolve(&self, file_name: &str) -> file_system::Result<(&FileSystemRef, String)> {
+        relative_path(file_name)?;
+
+        self.mounts
+            .iter()
+            .filter_map(|m| {
+                let rest = file_name.strip_prefix(&m.mount_point)?;
+                match rest {
+                    "" => Some((m, "/".to_string())),
+                    _ if rest.starts_with('/') => Some((m, rest.to_string())),
+                    _ => None,
+                }
+            })
+            .max_by_key(|(m, _)| m.mount_point.len())
+            .map(|(m, rest)| (&m.file_system, rest))
+            .ok_or_else(|| FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("no file system mounted for '{file_name}'"),
+            })
+    }
+}
+
+/**
+ * SCA405
+ * A FileManagerComponent shall propagate exceptions raised by a mounted
+ * file system.
+ */
+impl FileSystemTrait for FileManager {
+    fn exists(&self, file_name: &str) -> file_system::Result<bool> {
+        match self.resolve(file_name) {
+            Ok((file_system, name)) => file_system.exists(&name),
+            Err(FileSystemError::FileException { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+     * The mount points below the listed directory are returned as
+     * FILE_SYSTEM entries, along with the files of the file system the
+     * directory belongs to.
+     */
+    fn list(&self, pattern: &str) -> file_system::Result<Vec<FileInformationType>> {
+        let (directory, file_pattern) =
+            pattern
+                .rsplit_once('/')
+                .ok_or_else(|| FileSystemError::InvalidFileName {
+                    error_number: ErrorNumberType::CF_EINVAL,
+                    message: format!("'{pattern}' is not an absolute pathname"),
+                })?;
+
+        let mut files: Vec<FileInformationType> = self
+            .mounts
+            .iter()
+            .filter_map(|m| {
+                let (parent, name) = m.mount_point.rsplit_once('/')?;
+                (parent == directory && wildcard_match(file_pattern, name)).then(|| {
+                    FileInformationType {
+                        name: name.to_string(),
+                        kind: FileType::FILE_SYSTEM,
+                        size: 0,
+                    }
+                })
+            })
+            .collect();
+
+        if let Ok((file_system, name)) = self.resolve(pattern) {
+            files.extend(file_system.list(&name)?);
+        }
+        Ok(files)
+    }
+
+    fn remove(&self, file_name: &str) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.remove(&name)
+    }
+
+    /// Files copied between mounted file systems go through the file manager.
+    fn copy(&self, source_file_name: &str, destination_file_name: &str) -> file_system::Result<()> {
+        let (source, source_name) = self.resolve(source_file_name)?;
+        let (destination, destination_name) = self.resolve(destination_file_name)?;
+        if Arc::ptr_eq(source, destination) {
+            return source.copy(&source_name, &destination_name);
+        }
+        destination.write(&destination_name, &source.read(&source_name)?)
+    }
+
+    /**
+     * Files renamed between mounted file systems are copied through the
+     * file manager then removed, the destination not being replaced
+     * atomically.
+     */
+    fn rename(
+        &self,
+        source_file_name: &str,
+        destination_file_name: &str,
+    ) -> file_system::Result<()> {
+        let (source, source_name) = self.resolve(source_file_name)?;
+        let (destination, destination_name) = self.resolve(destination_file_name)?;
+        if Arc::ptr_eq(source, destination) {
+            return source.rename(&source_name, &destination_name);
+        }
+        destination.write(&destination_name, &source.read(&source_name)?)?;
+        source.remove(&source_name)
+    }
+
+    fn mkdir(&self, directory_name: &str) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(directory_name)?;
+        file_system.mkdir(&name)
+    }
+
+    fn rmdir(&self, directory_name: &str) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(directory_name)?;
+        file_system.rmdir(&name)
+    }
+
+    fn read(&self, file_name: &str) -> file_system::Result<Vec<u8>> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.read(&name)
+    }
+
+    fn read_into(&self, file_name: &str, buffer: &mut Vec<u8>) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.read_into(&name, buffer)
+    }
+
+    fn read_at(
+        &self,
+        file_name: &str,
+        offset: u64,
+        length: usize,
+    ) -> file_system::Result<Vec<u8>> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.read_at(&name, offset, length)
+    }
+
+    fn read_at_into(
+        &self,
+        file_name: &str,
+        offset: u64,
+        length: usize,
+        buffer: &mut Vec<u8>,
+    ) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.read_at_into(&name, offset, length, buffer)
+    }
+
+    fn size(&self, file_name: &str) -> file_system::Result<u64> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.size(&name)
+    }
+
+    fn write(&self, file_name: &str, data: &[u8]) -> file_system::Result<()> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.write(&name, data)
+    }
+
+    fn write_at(&self, file_name: &str, offset: u64, data: &[u8]) -> file_system::Result<u64> {
+        let (file_system, name) = self.resolve(file_name)?;
+        file_system.write_at(&name, offset, data)
+    }
+
+    /// The space of the mounted file systems is reported under their mount point.
+    fn query(&self) -> file_system::Result<Vec<FileSystemSpace>> {
+        let mut spaces = Vec::new();
+        for m in &self.mounts {
+            spaces.extend(m.file_system.query()?.into_iter().map(|space| {
+                FileSystemSpace {
+                    mount_point: match space.mount_point.as_str() {
+                        "/" => m.mount_point.clone(),
+                        mount_point => format!("{}{mount_point}", m.mount_point),
+                    },
+                    ..space
+                }
+            }));
+        }
+        Ok(spaces)
+    }
+}
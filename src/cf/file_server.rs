@@ -1,14 +1,101 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use tonic::{transport::Server, Request, Response, Status};
 
 use file::file_server::{File, FileServer};
-use file::{SizeOfRequest, SizeOfReply};
+use file::{
+    CloseReply, CloseRequest, FlushReply, FlushRequest, OpenReply, OpenRequest, ReadAtReply,
+    ReadAtRequest, SizeOfRequest, SizeOfReply, SyncAllReply, SyncAllRequest, WriteReply,
+    WriteRequest,
+};
+use scars::cf::blocking_pool::BlockingPool;
+use scars::cf::file::{self as cf_file, FileTrait};
+use scars::cf::file_system::{self, relative_path, FileSystem, FileSystemTrait};
+use scars::cf::file_system_service::MAX_CHUNK_SIZE;
 
 pub mod file {
     tonic::include_proto!("file");
+    include!(concat!(env!("OUT_DIR"), "/file.serde.rs"));
+}
+
+/// The octets a created file buffers before its writes wait for the storage.
+const WRITE_BEHIND_CAPACITY: usize = 1024 * 1024;
+
+/// The interval the octets written behind are written at.
+const WRITE_BEHIND_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The time a file open for a client is kept unused, e.g. the client gone without closing it.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// File open for a client, and the last time it was used.
+#[derive(Debug)]
+struct OpenFile {
+    file: Arc<Mutex<cf_file::File>>,
+    used: Instant,
 }
 
+/// Opens a file of the current directory, or creates it to be written behind.
+fn open_file(local_name: String, create: bool) -> cf_file::Result<cf_file::File> {
+    match create {
+        true => cf_file::File::create(local_name, Path::new("."))?
+            .with_write_behind(WRITE_BEHIND_CAPACITY, WRITE_BEHIND_INTERVAL),
+        false => cf_file::File::open(local_name, Path::new(".")),
+    }
+}
+
+/**
+ * Serves the files of the current directory. The files open for the
+ * clients are kept by handle, so that their flush and sync_all reach the
+ * octets written behind, and closed once unused for the idle timeout,
+ * when the next file is opened. The file operations are run on the
+ * blocking pool, off the threads of the runtime.
+ */
 #[derive(Debug, Default)]
-pub struct MyFileServer {}
+pub struct MyFileServer {
+    /// The files open for the clients, by handle.
+    files: Mutex<HashMap<u64, OpenFile>>,
+    next_handle: AtomicU64,
+    /// The pool the file operations are run on.
+    blocking_pool: BlockingPool,
+}
+
+impl MyFileServer {
+    /// Returns a file open for a client, marked used.
+    fn file(&self, handle: u64) -> Option<Arc<Mutex<cf_file::File>>> {
+        let mut files = self.files.lock().unwrap();
+        let open_file = files.get_mut(&handle)?;
+        open_file.used = Instant::now();
+        Some(open_file.file.clone())
+    }
+
+    /// Removes the files unused for the idle timeout, returning them to be closed.
+    fn expire(&self) -> Vec<Arc<Mutex<cf_file::File>>> {
+        let mut expired = Vec::new();
+        self.files.lock().unwrap().retain(|_, open_file| {
+            let idle = open_file.used.elapsed() >= IDLE_TIMEOUT;
+            if idle {
+                expired.push(open_file.file.clone());
+            }
+            !idle
+        });
+        expired
+    }
+
+    /// Runs a file operation on a thread of the blocking pool.
+    async fn run<R, E, F>(&self, operation: F) -> Result<R, Status>
+    where
+        F: FnOnce() -> Result<R, E> + Send + 'static,
+        R: Send + 'static,
+        E: Send + 'static,
+        Status: From<E>,
+    {
+        Ok(self.blocking_pool.run(operation).await??)
+    }
+}
 
 #[tonic::async_trait]
 impl File for MyFileServer {
@@ -16,13 +103,142 @@ impl File for MyFileServer {
 
     async fn size_of(
         &self,
-        request: Request<SizeOfRequest>
+        _request: Request<SizeOfRequest>
     ) -> Result<Response<SizeOfReply>, Status> {
         let reply = file::SizeOfReply {
             size: 1234u64,
         };
         Ok(Response::new(reply))
     }
+
+    /// Reads a range of a file of the current directory, the concurrent calls reading in parallel.
+    async fn read_at(
+        &self,
+        request: Request<ReadAtRequest>
+    ) -> Result<Response<ReadAtReply>, Status> {
+        let request = request.into_inner();
+        // The octets past the end of the file, or past a chunk, are not read
+        let length = usize::try_from(request.length)
+            .unwrap_or(usize::MAX)
+            .min(MAX_CHUNK_SIZE);
+        let data = self
+            .run(move || {
+                FileSystem::new(Path::new(".")).read_at(&request.name, request.offset, length)
+            })
+            .await?;
+        Ok(Response::new(ReadAtReply { data }))
+    }
+
+    async fn open(
+        &self,
+        request: Request<OpenRequest>
+    ) -> Result<Response<OpenReply>, Status> {
+        let request = request.into_inner();
+        let name = local_name(&request.name)?;
+        // the files the clients left open are closed, their octets written behind flushed
+        let expired = self.expire();
+        let file = self
+            .run(move || {
+                for file in expired {
+                    let _ = file.lock().unwrap().close();
+                }
+                open_file(name, request.create)
+            })
+            .await?;
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed) + 1;
+        let open_file = OpenFile {
+            file: Arc::new(Mutex::new(file)),
+            used: Instant::now(),
+        };
+        self.files.lock().unwrap().insert(handle, open_file);
+        Ok(Response::new(OpenReply { handle }))
+    }
+
+    async fn write(
+        &self,
+        request: Request<WriteRequest>
+    ) -> Result<Response<WriteReply>, Status> {
+        let request = request.into_inner();
+        let file = self
+            .file(request.handle)
+            .ok_or_else(|| not_open(request.handle))?;
+        self.run(move || file.lock().unwrap().write(&request.data))
+            .await?;
+        Ok(Response::new(WriteReply {}))
+    }
+
+    /// The file is closed whatever the outcome of the flush of its octets written behind.
+    async fn close(
+        &self,
+        request: Request<CloseRequest>
+    ) -> Result<Response<CloseReply>, Status> {
+        let handle = request.into_inner().handle;
+        let file = self
+            .files
+            .lock()
+            .unwrap()
+            .remove(&handle)
+            .ok_or_else(|| not_open(handle))?
+            .file;
+        self.run(move || file.lock().unwrap().close())
+            .await?;
+        Ok(Response::new(CloseReply {}))
+    }
+
+    /**
+     * The octets written behind to the open file are written; without
+     * a handle, the server buffering none of the writes to the file
+     * named, it is only checked.
+     */
+    async fn flush(
+        &self,
+        request: Request<FlushRequest>
+    ) -> Result<Response<FlushReply>, Status> {
+        let request = request.into_inner();
+        match request.handle {
+            0 => {
+                let name = local_name(&request.name)?;
+                self.run(move || cf_file::File::open(name, Path::new("."))?.flush())
+                    .await?
+            }
+            handle => {
+                let file = self.file(handle).ok_or_else(|| not_open(handle))?;
+                self.run(move || file.lock().unwrap().flush())
+                    .await?
+            }
+        }
+        Ok(Response::new(FlushReply {}))
+    }
+
+    async fn sync_all(
+        &self,
+        request: Request<SyncAllRequest>
+    ) -> Result<Response<SyncAllReply>, Status> {
+        let request = request.into_inner();
+        match request.handle {
+            0 => {
+                let name = local_name(&request.name)?;
+                self.run(move || cf_file::File::open(name, Path::new("."))?.sync_all())
+                    .await?
+            }
+            handle => {
+                let file = self.file(handle).ok_or_else(|| not_open(handle))?;
+                self.run(move || file.lock().unwrap().sync_all())
+                    .await?
+            }
+        }
+        Ok(Response::new(SyncAllReply {}))
+    }
+}
+
+/// Returns the error of a call on a handle no file is open as.
+fn not_open(handle: u64) -> Status {
+    Status::not_found(format!("no file open as {handle}"))
+}
+
+/// Returns the name of a file in the current directory, checked as a FileSystem pathname.
+fn local_name(name: &str) -> file_system::Result<String> {
+    Ok(relative_path(name)?.to_string_lossy().into_owned())
 }
 
 #[tokio::main]
@@ -36,4 +252,4 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await?;
 
     Ok(())
-}
\ No newline at end of file
+}
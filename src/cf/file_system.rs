@@ -0,0 +1,728 @@
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::blocking_pool::{BlockingError, BlockingPool};
+use super::common_types::ErrorNumberType;
+use super::file::{File, FileError, FileTrait, PositionalFile};
+
+/// The time given by default to a file of a mounted node file system to open.
+pub const DEFAULT_OPEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Convienence enum definition that includes all FileSystemTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum FileSystemError {
+    /**
+     * This exception indicates an invalid file name was passed to a
+     * file service operation.
+     */
+    #[error("InvalidFileName: num: {error_number:?}, msg: '{message}'.")]
+    InvalidFileName {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates a file-related error occurred.
+     * The message provides information describing the error.
+     */
+    #[error("FileException: num: {error_number:?}, msg: '{message}'.")]
+    FileException {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+}
+
+impl From<FileError> for FileSystemError {
+    fn from(value: FileError) -> Self {
+        match value {
+            FileError::FileException {
+                error_number,
+                message,
+            }
+            | FileError::IOException {
+                error_number,
+                message,
+            } => FileSystemError::FileException {
+                error_number,
+                message,
+            },
+            FileError::InvalidFilePointer => FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EINVAL,
+                message: value.to_string(),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for FileSystemError {
+    fn from(value: std::io::Error) -> Self {
+        let error_number = match value.kind() {
+            std::io::ErrorKind::NotFound => ErrorNumberType::CF_ENOENT,
+            std::io::ErrorKind::PermissionDenied => ErrorNumberType::CF_EPERM,
+            std::io::ErrorKind::TimedOut => ErrorNumberType::CF_ETIMEDOUT,
+            _ => ErrorNumberType::CF_EIO,
+        };
+        FileSystemError::FileException {
+            error_number,
+            message: value.to_string(),
+        }
+    }
+}
+
+/*
+ * Convienence type definition that includes all FileSystemTrait returned errors.
+ */
+pub type Result<T, E = FileSystemError> = anyhow::Result<T, E>;
+
+/**
+ * This type indicates the type of a file entry.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    PLAIN,
+    DIRECTORY,
+    FILE_SYSTEM,
+}
+
+/**
+ * This type describes a file entry returned by the list operation.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInformationType {
+    pub name: String,
+    pub kind: FileType,
+    pub size: u64,
+}
+
+/**
+ * This type reports the space of a file system, as returned by the
+ * query operation, the file systems mounted in a file manager being
+ * reported under their mount point.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystemSpace {
+    pub mount_point: String,
+    /// The size in bytes of the file system.
+    pub size: u64,
+    /// The space in bytes left on the file system.
+    pub available_space: u64,
+}
+
+/**
+ * This interface defines the operations to remove, copy, list and
+ * query files, and to manage directories, in a file system whose file
+ * names are absolute pathnames.
+ */
+pub trait FileSystemTrait {
+    /// This operation checks if a file or a directory exists.
+    fn exists(&self, file_name: &str) -> Result<bool>;
+
+    /// This operation returns the files matching a search pattern.
+    fn list(&self, pattern: &str) -> Result<Vec<FileInformationType>>;
+
+    /// This operation removes a plain file.
+    fn remove(&self, file_name: &str) -> Result<()>;
+
+    /// This operation copies a plain file to another plain file.
+    fn copy(&self, source_file_name: &str, destination_file_name: &str) -> Result<()>;
+
+    /**
+     * This operation renames a plain file, replacing the destination
+     * file if any. The local file systems replace it atomically, the
+     * readers seeing either the previous file or the renamed one.
+     */
+    fn rename(&self, source_file_name: &str, destination_file_name: &str) -> Result<()>;
+
+    /// This operation creates a directory and its missing parents.
+    fn mkdir(&self, directory_name: &str) -> Result<()>;
+
+    /// This operation removes an empty directory.
+    fn rmdir(&self, directory_name: &str) -> Result<()>;
+
+    /// This operation returns the content of a plain file.
+    fn read(&self, file_name: &str) -> Result<Vec<u8>>;
+
+    /**
+     * This operation reads the content of a plain file into a buffer,
+     * replacing its content, the file systems reading in place reusing
+     * its capacity.
+     */
+    fn read_into(&self, file_name: &str, buffer: &mut Vec<u8>) -> Result<()> {
+        *buffer = self.read(file_name)?;
+        Ok(())
+    }
+
+    /**
+     * This operation reads up to length octets of a plain file at an
+     * offset, fewer past its end. The file systems of local files read
+     * them in place, without loading the whole file.
+     */
+    fn read_at(&self, file_name: &str, offset: u64, length: usize) -> Result<Vec<u8>> {
+        let data = self.read(file_name)?;
+        let start = usize::try_from(offset).map_or(data.len(), |start| start.min(data.len()));
+        let end = start.saturating_add(length).min(data.len());
+        Ok(data[start..end].to_vec())
+    }
+
+    /**
+     * This operation reads up to length octets of a plain file at an
+     * offset into a buffer, replacing its content, the file systems of
+     * local files reading in place reusing its capacity, e.g. the chunks
+     * of a file streamed.
+     */
+    fn read_at_into(
+        &self,
+        file_name: &str,
+        offset: u64,
+        length: usize,
+        buffer: &mut Vec<u8>,
+    ) -> Result<()> {
+        *buffer = self.read_at(file_name, offset, length)?;
+        Ok(())
+    }
+
+    /**
+     * This operation returns the size of a plain file, as listed. The
+     * file systems of local files query it without listing its directory.
+     */
+    fn size(&self, file_name: &str) -> Result<u64> {
+        let name = file_name.rsplit('/').next().unwrap_or_default();
+        self.list(file_name)?
+            .into_iter()
+            .find(|f| f.name == name && f.kind == FileType::PLAIN)
+            .map(|f| f.size)
+            .ok_or_else(|| FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{file_name}' is not a plain file"),
+            })
+    }
+
+    /// This operation creates or overwrites a plain file with the data.
+    fn write(&self, file_name: &str, data: &[u8]) -> Result<()>;
+
+    /**
+     * This operation writes data at an offset of a plain file, at most its
+     * size, extending it past its end, a file missing being created when
+     * written at offset 0. Returns the size of the file. The file systems
+     * of local files write them in place, without loading the whole file.
+     */
+    fn write_at(&self, file_name: &str, offset: u64, data: &[u8]) -> Result<u64> {
+        let mut content = Vec::new();
+        if offset > 0 || self.exists(file_name)? {
+            content = self.read(file_name)?;
+        }
+        let start = usize::try_from(offset)
+            .ok()
+            .filter(|start| *start <= content.len())
+            .ok_or_else(|| past_end(file_name, offset))?;
+        let end = start
+            .checked_add(data.len())
+            .ok_or_else(|| past_end(file_name, offset))?;
+        content.resize(content.len().max(end), 0);
+        content[start..end].copy_from_slice(data);
+        self.write(file_name, &content)?;
+        Ok(content.len() as u64)
+    }
+
+    /**
+     * This operation returns the contents of plain files, in the order of
+     * their names, batching the transfers of many small files.
+     */
+    fn get_many(&self, file_names: &[&str]) -> Result<Vec<Vec<u8>>> {
+        file_names.iter().map(|f| self.read(f)).collect()
+    }
+
+    /// This operation creates or overwrites plain files with their data, in their order.
+    fn put_many(&self, files: &[(&str, &[u8])]) -> Result<()> {
+        files
+            .iter()
+            .try_for_each(|(file_name, data)| self.write(file_name, data))
+    }
+
+    /// Returns the local directory of the file system, when it has one.
+    fn local_root(&self) -> Option<&Path> {
+        None
+    }
+
+    /**
+     * This operation returns the space of the file system, the one of
+     * the disk holding its local directory by default, none when
+     * unknown.
+     */
+    fn query(&self) -> Result<Vec<FileSystemSpace>> {
+        Ok(self
+            .local_root()
+            .and_then(disk_space)
+            .map(|(size, available_space)| FileSystemSpace {
+                mount_point: "/".to_string(),
+                size,
+                available_space,
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+/// Returns the size and the available space of the disk holding a local directory.
+fn disk_space(root: &Path) -> Option<(u64, u64)> {
+    let root = root.canonicalize().ok()?;
+    sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|disk| root.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| (disk.total_space(), disk.available_space()))
+}
+
+/**
+ * Convienence type definition to share a file system.
+ */
+pub type FileSystemRef = Arc<dyn FileSystemTrait + Send + Sync>;
+
+/**
+ * File system over a local directory, the root of its pathnames.
+ */
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    root: PathBuf,
+    open_timeout: Option<Duration>,
+    /// The pool the files are opened on when an open timeout is set.
+    blocking_pool: BlockingPool,
+}
+
+impl FileSystem {
+    pub fn new(root: &Path) -> FileSystem {
+        FileSystem {
+            root: root.to_path_buf(),
+            open_timeout: None,
+            blocking_pool: BlockingPool::shared(),
+        }
+    }
+
+    /**
+     * Sets the time given to the files to open, e.g. on a network mount,
+     * past which the operations fail with CF_ETIMEDOUT. The files are
+     * opened without timeout by default.
+     */
+    pub fn with_open_timeout(mut self, open_timeout: Duration) -> FileSystem {
+        self.open_timeout = Some(open_timeout);
+        self
+    }
+
+    /**
+     * Sets the pool the files are opened on when an open timeout is set,
+     * bounding the threads left waiting for the opens not answering.
+     */
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> FileSystem {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /// The local directory of the file system.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the local path of an absolute pathname of the file system.
+    pub fn local_path(&self, file_name: &str) -> Result<PathBuf> {
+        let relative = relative_path(file_name)?;
+        Ok(self.root.join(relative))
+    }
+
+    /**
+     * Makes a local file, e.g. outside of the root, available at a
+     * pathname of the file system, as a symbolic link replacing a
+     * previous one. The missing parent directories are created.
+     */
+    pub fn link(&self, file_name: &str, target: &Path) -> Result<()> {
+        let link = self.local_path(file_name)?;
+        if let Some(parent) = link.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if link.is_symlink() {
+            std::fs::remove_file(&link)?;
+        }
+        symlink(target, &link)?;
+        Ok(())
+    }
+
+    /**
+     * Opens a file of the file system, giving up once the open timeout
+     * elapsed. An open not answering, e.g. on a hung network mount, goes
+     * on on a thread of the pool, the file being closed once opened and
+     * removed when the open created it, the caller having been told it
+     * failed. The files are thus not truncated on open, but once opened.
+     */
+    fn open(&self, file_name: &str, options: &std::fs::OpenOptions) -> Result<std::fs::File> {
+        let path = self.local_path(file_name)?;
+        let Some(timeout) = self.open_timeout else {
+            return Ok(options.open(path)?);
+        };
+
+        let options = options.clone();
+        let opened = self.blocking_pool.run_within(
+            timeout,
+            move || {
+                let created = !path.exists();
+                options
+                    .open(&path)
+                    .map(|file| (file, created.then_some(path)))
+            },
+            |opened| {
+                if let Ok((file, Some(created))) = opened {
+                    drop(file);
+                    let _ = std::fs::remove_file(created);
+                }
+            },
+        );
+        match opened {
+            Ok(opened) => Ok(opened?.0),
+            Err(BlockingError::TimedOut { .. }) => Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ETIMEDOUT,
+                message: format!("'{file_name}' not opened within {timeout:?}"),
+            }),
+            Err(e) => Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EIO,
+                message: format!("'{file_name}' not opened: {e}"),
+            }),
+        }
+    }
+}
+
+impl FileSystemTrait for FileSystem {
+    /**
+     * SCA347
+     * The exists operation shall check to see if a file exists based on the
+     * fileName parameter.
+     * SCA348
+     * The exists operation shall return TRUE if the file exists, or FALSE if
+     * it does not.
+     */
+    fn exists(&self, file_name: &str) -> Result<bool> {
+        Ok(self.local_path(file_name)?.exists())
+    }
+
+    /**
+     * SCA448
+     * The list operation shall support the "*" and "?" wildcard characters.
+     * SCA350
+     * These wildcards shall only be applied following the right-most
+     * forward-slash character ("/") in the pathname contained in the input
+     * pattern parameter.
+     * SCA352
+     * The list operation shall return a zero length sequence when no file is
+     * found which matches the search pattern.
+     * SCA353
+     * The list operation shall raise the CF::InvalidFileName exception when the
+     * input pattern parameter is not an absolute pathname.
+     */
+    fn list(&self, pattern: &str) -> Result<Vec<FileInformationType>> {
+        let (directory, file_pattern) = pattern.rsplit_once('/').ok_or_else(|| {
+            invalid_file_name(&format!("'{pattern}' is not an absolute pathname"))
+        })?;
+        let directory = self.local_path(&format!("{directory}/"))?;
+        if !directory.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(directory)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !wildcard_match(file_pattern, &name) {
+                continue;
+            }
+            //the linked files are listed as their target
+            let metadata = std::fs::metadata(entry.path()).or_else(|_| entry.metadata())?;
+            files.push(FileInformationType {
+                name,
+                kind: if metadata.is_dir() {
+                    FileType::DIRECTORY
+                } else {
+                    FileType::PLAIN
+                },
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+            });
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    /**
+     * SCA339
+     * The remove operation shall remove the plain file which corresponds to
+     * the input fileName parameter.
+     * SCA340
+     * The remove operation shall raise the CF::InvalidFileName exception when
+     * the input fileName parameter is not a valid absolute pathname.
+     */
+    fn remove(&self, file_name: &str) -> Result<()> {
+        std::fs::remove_file(self.local_path(file_name)?)?;
+        Ok(())
+    }
+
+    /**
+     * SCA342
+     * The copy operation shall copy the source file identified by the input
+     * sourceFileName parameter to the destination file identified by the
+     * input destinationFileName parameter.
+     * SCA345
+     * The copy operation shall raise the CF::InvalidFileName exception when
+     * the destination pathname is identical to the source pathname.
+     */
+    fn copy(&self, source_file_name: &str, destination_file_name: &str) -> Result<()> {
+        let source = self.local_path(source_file_name)?;
+        let destination = self.local_path(destination_file_name)?;
+        if source == destination {
+            return Err(invalid_file_name(&format!(
+                "'{destination_file_name}' is the source file"
+            )));
+        }
+        std::fs::copy(source, destination)?;
+        Ok(())
+    }
+
+    /// The file is renamed in place, the destination being replaced atomically.
+    fn rename(&self, source_file_name: &str, destination_file_name: &str) -> Result<()> {
+        let source = self.local_path(source_file_name)?;
+        let destination = self.local_path(destination_file_name)?;
+        if source == destination {
+            return Err(invalid_file_name(&format!(
+                "'{destination_file_name}' is the source file"
+            )));
+        }
+        std::fs::rename(source, destination)?;
+        Ok(())
+    }
+
+    /**
+     * SCA366
+     * The mkdir operation shall create a file system directory based on the
+     * directoryName given.
+     * SCA367
+     * The mkdir operation shall create all parent directories required to
+     * create the directoryName path given.
+     * SCA368
+     * The mkdir operation shall raise the CF::FileException if the directory
+     * indicated by the input directoryName parameter already exists.
+     */
+    fn mkdir(&self, directory_name: &str) -> Result<()> {
+        let directory = self.local_path(directory_name)?;
+        if directory.exists() {
+            return Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EEXIST,
+                message: format!("'{directory_name}' already exists"),
+            });
+        }
+        std::fs::create_dir_all(directory)?;
+        Ok(())
+    }
+
+    /**
+     * SCA370
+     * The rmdir operation shall remove the directory identified by the input
+     * directoryName parameter.
+     * SCA371
+     * The rmdir operation shall not remove the directory identified by the
+     * input directoryName parameter when the directory contains files.
+     */
+    fn rmdir(&self, directory_name: &str) -> Result<()> {
+        let directory = self.local_path(directory_name)?;
+        if std::fs::read_dir(&directory)?.next().is_some() {
+            return Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOTEMPTY,
+                message: format!("'{directory_name}' contains files"),
+            });
+        }
+        std::fs::remove_dir(directory)?;
+        Ok(())
+    }
+
+    fn read(&self, file_name: &str) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.read_into(file_name, &mut data)?;
+        Ok(data)
+    }
+
+    fn read_into(&self, file_name: &str, buffer: &mut Vec<u8>) -> Result<()> {
+        buffer.clear();
+        self.open(file_name, std::fs::OpenOptions::new().read(true))?
+            .read_to_end(buffer)?;
+        Ok(())
+    }
+
+    fn read_at(&self, file_name: &str, offset: u64, length: usize) -> Result<Vec<u8>> {
+        let handle = self.open(file_name, std::fs::OpenOptions::new().read(true))?;
+        if handle.metadata()?.is_dir() {
+            return Err(is_directory(file_name));
+        }
+        let relative = relative_path(file_name)?.to_string_lossy().into_owned();
+        Ok(File::from_handle(relative, handle).read_at(offset, length)?)
+    }
+
+    /// The octets are read at the offset, by pread on unix, into the capacity of the buffer.
+    fn read_at_into(
+        &self,
+        file_name: &str,
+        offset: u64,
+        length: usize,
+        buffer: &mut Vec<u8>,
+    ) -> Result<()> {
+        let handle = self.open(file_name, std::fs::OpenOptions::new().read(true))?;
+        let metadata = handle.metadata()?;
+        if metadata.is_dir() {
+            return Err(is_directory(file_name));
+        }
+        // the octets past the end of file are not read
+        let available = metadata.len().saturating_sub(offset);
+        let length = length.min(usize::try_from(available).unwrap_or(usize::MAX));
+        buffer.clear();
+        buffer.resize(length, 0);
+        let mut actual = 0;
+        while actual < length {
+            match handle.read_at(&mut buffer[actual..], offset + actual as u64) {
+                Ok(0) => break,
+                Ok(read) => actual += read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        buffer.truncate(actual);
+        Ok(())
+    }
+
+    fn size(&self, file_name: &str) -> Result<u64> {
+        let handle = self.open(file_name, std::fs::OpenOptions::new().read(true))?;
+        let metadata = handle.metadata()?;
+        if metadata.is_dir() {
+            return Err(is_directory(file_name));
+        }
+        Ok(metadata.len())
+    }
+
+    fn write(&self, file_name: &str, data: &[u8]) -> Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(false);
+        let mut file = self.open(file_name, &options)?;
+        file.set_len(0)?;
+        file.write_all(data)?;
+        Ok(())
+    }
+
+    /// The data is written at the offset, by pwrite on unix, the offsets being 64-bit whatever the platform.
+    fn write_at(&self, file_name: &str, offset: u64, data: &[u8]) -> Result<u64> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(offset == 0).truncate(false);
+        let file = self.open(file_name, &options)?;
+        let size = file.metadata()?.len();
+        if offset > size {
+            return Err(past_end(file_name, offset));
+        }
+        let end = offset
+            .checked_add(data.len() as u64)
+            .ok_or_else(|| past_end(file_name, offset))?;
+        file.write_all_at(data, offset)?;
+        Ok(size.max(end))
+    }
+
+    fn local_root(&self) -> Option<&Path> {
+        Some(&self.root)
+    }
+}
+
+/**
+ * Returns the path of an absolute pathname relative to the root of its
+ * file system, rejecting the names escaping it.
+ */
+pub fn relative_path(file_name: &str) -> Result<PathBuf> {
+    let relative = file_name
+        .strip_prefix('/')
+        .ok_or_else(|| invalid_file_name(&format!("'{file_name}' is not an absolute pathname")))?;
+
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(invalid_file_name(&format!(
+            "'{file_name}' escapes the file system"
+        )));
+    }
+    Ok(relative.to_path_buf())
+}
+
+/// Links the target, relative to the link or absolute, at the link.
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+/// The links of Windows are either to a directory or to a file.
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Returns the error of a plain file operation on a directory.
+fn is_directory(file_name: &str) -> FileSystemError {
+    FileSystemError::FileException {
+        error_number: ErrorNumberType::CF_EISDIR,
+        message: format!("'{file_name}' is a directory"),
+    }
+}
+
+/// Returns the error of a write at an offset past the end of a file.
+fn past_end(file_name: &str, offset: u64) -> FileSystemError {
+    FileSystemError::FileException {
+        error_number: ErrorNumberType::CF_EINVAL,
+        message: format!("offset {offset} past the end of '{file_name}'"),
+    }
+}
+
+fn invalid_file_name(message: &str) -> FileSystemError {
+    FileSystemError::InvalidFileName {
+        error_number: ErrorNumberType::CF_EINVAL,
+        message: message.to_string(),
+    }
+}
+
+/// Matches a name against a pattern of '*' and '?' wildcards.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    //backtracking over the last '*'
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
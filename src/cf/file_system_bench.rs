@@ -0,0 +1,355 @@
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tonic::body::BoxBody;
+use tonic::client::GrpcService;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::Status;
+
+use super::rpc::file_system::file_system_client::FileSystemClient;
+use super::rpc::file_system::{
+    ReadRangeRequest, ReadRequest, RemoveRequest, WriteRangeRequest, WriteRequest,
+};
+
+/**
+ * Convienence enum definition that includes all benchmark errors.
+ */
+#[derive(Error, Debug)]
+pub enum BenchError {
+    /**
+     * This exception indicates that the benchmark parameters are invalid,
+     * e.g. a chunk size or a parallelism of zero.
+     */
+    #[error("InvalidConfiguration: msg: '{message}'.")]
+    InvalidConfiguration { message: String },
+    /**
+     * This exception indicates that a transfer failed, or transferred
+     * another size than the size of the file.
+     */
+    #[error("TransferFailed: file: '{file_name}', msg: '{message}'.")]
+    TransferFailed { file_name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all benchmark returned errors.
+ */
+pub type Result<T, E = BenchError> = anyhow::Result<T, E>;
+
+/**
+ * The parameters of a benchmark of a FileSystem service: each transfer
+ * mode and operation is measured for every chunk size and parallelism,
+ * the parallel tasks transferring files of their own.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchConfig {
+    /// The directory of the service the files are written in.
+    pub directory: String,
+    pub file_size: usize,
+    pub chunk_sizes: Vec<usize>,
+    pub parallelism: Vec<usize>,
+    /// The transfers of each file per measure.
+    pub iterations: usize,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        BenchConfig {
+            directory: "/".to_string(),
+            file_size: 1024 * 1024,
+            chunk_sizes: vec![4 * 1024, 64 * 1024, 1024 * 1024],
+            parallelism: vec![1, 4],
+            iterations: 4,
+        }
+    }
+}
+
+/**
+ * The transfer modes: a request per chunk, read or written by range, or
+ * the chunks of the file streamed in a single call.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TransferMode {
+    UNARY,
+    STREAMING,
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TransferOperation {
+    WRITE,
+    READ,
+}
+
+/**
+ * This type reports the measure of a transfer mode and operation for a
+ * chunk size and a parallelism.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchResult {
+    pub mode: TransferMode,
+    pub operation: TransferOperation,
+    pub chunk_size: usize,
+    pub parallelism: usize,
+    /// The files transferred, all the tasks together.
+    pub transfers: usize,
+    /// The bytes transferred per second, all the tasks together.
+    pub throughput: f64,
+    /// The mean duration of the transfer of a file, in seconds.
+    pub mean_latency: f64,
+    /// The 95th percentile of the durations, in seconds.
+    pub p95_latency: f64,
+    pub max_latency: f64,
+}
+
+/**
+ * Benchmarks a FileSystem service, writing then reading the files of the
+ * parallel tasks in each mode. The files are removed once measured.
+ */
+pub async fn bench<T>(client: FileSystemClient<T>, config: &BenchConfig) -> Result<Vec<BenchResult>>
+where
+    T: GrpcService<BoxBody> + Clone + Send + 'static,
+    T::Error: Into<StdError>,
+    T::Future: Send,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let invalid = |message: &str| BenchError::InvalidConfiguration {
+        message: message.to_string(),
+    };
+    if config.chunk_sizes.is_empty() || config.chunk_sizes.contains(&0) {
+        return Err(invalid("the chunk sizes must be positive"));
+    }
+    if config.parallelism.is_empty() || config.parallelism.contains(&0) {
+        return Err(invalid("the parallelism must be positive"));
+    }
+    if config.iterations == 0 {
+        return Err(invalid("the iterations must be positive"));
+    }
+
+    let data: Arc<Vec<u8>> = Arc::new((0..config.file_size).map(|i| i as u8).collect());
+    let file_names: Vec<String> = (0..*config.parallelism.iter().max().unwrap())
+        .map(|task| {
+            format!(
+                "{}/scars-bench-{task}",
+                config.directory.trim_end_matches('/')
+            )
+        })
+        .collect();
+    let mut results = Vec::new();
+    let mut outcome = Ok(());
+    'measures: for mode in [TransferMode::UNARY, TransferMode::STREAMING] {
+        for &chunk_size in &config.chunk_sizes {
+            for &parallelism in &config.parallelism {
+                for operation in [TransferOperation::WRITE, TransferOperation::READ] {
+                    let transfer = Transfer {
+                        mode,
+                        operation,
+                        chunk_size,
+                        data: data.clone(),
+                    };
+                    let files = &file_names[..parallelism];
+                    match measure(&client, transfer, files, config.iterations).await {
+                        Ok(result) => results.push(result),
+                        Err(e) => {
+                            outcome = Err(e);
+                            break 'measures;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut client = client;
+    for file_name in file_names {
+        let _ = client
+            .remove(RemoveRequest {
+                file_name: file_name.clone(),
+            })
+            .await;
+    }
+    outcome.map(|_| results)
+}
+
+/// The transfer of a file measured.
+#[derive(Clone)]
+struct Transfer {
+    mode: TransferMode,
+    operation: TransferOperation,
+    chunk_size: usize,
+    data: Arc<Vec<u8>>,
+}
+
+/// Measures the transfers of files by parallel tasks.
+async fn measure<T>(
+    client: &FileSystemClient<T>,
+    transfer: Transfer,
+    file_names: &[String],
+    iterations: usize,
+) -> Result<BenchResult>
+where
+    T: GrpcService<BoxBody> + Clone + Send + 'static,
+    T::Error: Into<StdError>,
+    T::Future: Send,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let started = Instant::now();
+    let mut tasks = JoinSet::new();
+    for file_name in file_names {
+        let (mut client, transfer) = (client.clone(), transfer.clone());
+        let file_name = file_name.clone();
+        tasks.spawn(async move {
+            let mut latencies = Vec::new();
+            for _ in 0..iterations {
+                let transfer_started = Instant::now();
+                transfer
+                    .run(&mut client, &file_name)
+                    .await
+                    .map_err(|status| BenchError::TransferFailed {
+                        file_name: file_name.clone(),
+                        message: status.message().to_string(),
+                    })?;
+                latencies.push(transfer_started.elapsed());
+            }
+            Ok(latencies)
+        });
+    }
+    let mut latencies: Vec<Duration> = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let task_latencies = joined.map_err(|e| BenchError::TransferFailed {
+            file_name: String::new(),
+            message: e.to_string(),
+        })??;
+        latencies.extend(task_latencies);
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    latencies.sort();
+    let seconds: Vec<f64> = latencies.iter().map(Duration::as_secs_f64).collect();
+    let p95 = seconds[(seconds.len() * 95).div_ceil(100).max(1) - 1];
+    Ok(BenchResult {
+        mode: transfer.mode,
+        operation: transfer.operation,
+        chunk_size: transfer.chunk_size,
+        parallelism: file_names.len(),
+        transfers: seconds.len(),
+        throughput: (seconds.len() * transfer.data.len()) as f64 / elapsed,
+        mean_latency: seconds.iter().sum::<f64>() / seconds.len() as f64,
+        p95_latency: p95,
+        max_latency: *seconds.last().unwrap(),
+    })
+}
+
+impl Transfer {
+    /// Transfers a file once, verifying the size transferred.
+    async fn run<T>(
+        &self,
+        client: &mut FileSystemClient<T>,
+        file_name: &str,
+    ) -> std::result::Result<(), Box<Status>>
+    where
+        T: GrpcService<BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let size = self.data.len();
+        let transferred = match (self.mode, self.operation) {
+            (TransferMode::UNARY, TransferOperation::WRITE) => {
+                let mut written = 0;
+                for (index, chunk) in self.data.chunks(self.chunk_size).enumerate() {
+                    let request = WriteRangeRequest {
+                        file_name: file_name.to_string(),
+                        offset: (index * self.chunk_size) as u64,
+                        data: chunk.to_vec(),
+                        crc32c: None,
+                    };
+                    written = client.write_range(request).await?.into_inner().size;
+                }
+                written as usize
+            }
+            (TransferMode::STREAMING, TransferOperation::WRITE) => {
+                let mut chunks: Vec<WriteRequest> = self
+                    .data
+                    .chunks(self.chunk_size)
+                    .map(|chunk| WriteRequest {
+                        file_name: String::new(),
+                        data: chunk.to_vec(),
+                        crc32c: None,
+                    })
+                    .collect();
+                if chunks.is_empty() {
+                    chunks.push(WriteRequest::default());
+                }
+                chunks[0].file_name = file_name.to_string();
+                let reply = client.write(tokio_stream::iter(chunks)).await?;
+                reply.into_inner().size as usize
+            }
+            (TransferMode::UNARY, TransferOperation::READ) => {
+                let mut read = 0;
+                while read < size {
+                    let request = ReadRangeRequest {
+                        file_name: file_name.to_string(),
+                        offset: read as u64,
+                        size: self.chunk_size as u64,
+                    };
+                    let chunk = client.read_range(request).await?.into_inner();
+                    if chunk.data.is_empty() {
+                        break;
+                    }
+                    read += chunk.data.len();
+                }
+                read
+            }
+            (TransferMode::STREAMING, TransferOperation::READ) => {
+                let request = ReadRequest {
+                    file_name: file_name.to_string(),
+                    chunk_size: self.chunk_size as u64,
+                };
+                let mut chunks = client.read(request).await?.into_inner();
+                let mut read = 0;
+                while let Some(chunk) = chunks.message().await? {
+                    read += chunk.data.len();
+                }
+                read
+            }
+        };
+        if transferred != size {
+            return Err(Box::new(Status::data_loss(format!(
+                "{transferred} bytes transferred out of {size}"
+            ))));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the results as a table, the throughputs in MiB/s and the latencies in milliseconds.
+pub fn to_table(results: &[BenchResult]) -> String {
+    let mut table = String::new();
+    let _ = writeln!(
+        table,
+        "{:<10} {:<6} {:>10} {:>8} {:>10} {:>10} {:>10} {:>10}",
+        "MODE", "OP", "CHUNK", "PARALLEL", "MIB/S", "MEAN MS", "P95 MS", "MAX MS"
+    );
+    for result in results {
+        let _ = writeln!(
+            table,
+            "{:<10} {:<6} {:>10} {:>8} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+            format!("{:?}", result.mode).to_lowercase(),
+            format!("{:?}", result.operation).to_lowercase(),
+            result.chunk_size,
+            result.parallelism,
+            result.throughput / (1024.0 * 1024.0),
+            result.mean_latency * 1000.0,
+            result.p95_latency * 1000.0,
+            result.max_latency * 1000.0
+        );
+    }
+    table
+}
@@ -0,0 +1,671 @@
+use std::ops::Range;
+
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+
+use super::blocking_pool::{BlockingPool, BlockingPoolMetrics};
+use super::buffer_pool::{self, BufferPool, BufferPoolMetrics, PooledBuffer};
+use super::checksum::{self, ChunkedDigest};
+use super::events::EventStream;
+use super::file_archive::{self, Unpacker};
+use super::file_delta;
+use super::file_manager::FileManagerRef;
+use super::file_system::{self, FileSystemRef, FileSystemTrait};
+use super::rpc::file_system::file_system_server;
+use super::rpc::file_system::{
+    ArchiveChunk, ChunkRange, CopyReply, CopyRequest, DeltaRequest, DigestReply, DigestRequest,
+    FileChunk, GetManyRequest, ListReply, ListRequest, MetricsReply, MetricsRequest, MkdirReply,
+    MkdirRequest, MoveReply, MoveRequest, PutManyReply, QueryReply, QueryRequest, ReadRangeRequest,
+    ReadRequest, RemoveReply, RemoveRequest, RmdirReply, RmdirRequest, SignatureReply,
+    SignatureRequest, WriteRangeRequest, WriteReply, WriteRequest,
+};
+
+/// The size of the chunks the files are streamed in by default.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The size of the largest chunks the files are streamed in, the one of the largest buffers pooled.
+pub const MAX_CHUNK_SIZE: usize = buffer_pool::DEFAULT_CHUNK_SIZE;
+
+/// The size of the largest archive of files written at once by default.
+pub const MAX_ARCHIVE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The number of chunks of a file read ahead of the ones sent.
+const READ_AHEAD: usize = 2;
+
+/// The file system served: a file system of a node, or the FileManager of a domain.
+#[derive(Clone)]
+enum Served {
+    FileSystem(FileSystemRef),
+    FileManager(FileManagerRef),
+}
+
+/**
+ * gRPC FileSystem service giving the remote tools access to the files
+ * of a file system, or of all the file systems mounted in a FileManager,
+ * the files being streamed in chunks, or read and written by range with
+ * a request per chunk. The errors of the file system are the status of
+ * the replies.
+ *
+ * The files are read and written a chunk at a time, never loaded whole,
+ * the chunks read being buffers of a pool, so that the transfers reuse
+ * the allocations of the previous chunks. The chunks requested are at
+ * most the size of the largest buffers pooled, and the archives of
+ * files written at once are bounded. The operations of the file system
+ * are run on the threads of a blocking pool.
+ */
+#[derive(Clone)]
+pub struct FileSystemService {
+    served: Served,
+    buffer_pool: BufferPool,
+    blocking_pool: BlockingPool,
+    max_archive_size: u64,
+}
+
+impl std::fmt::Debug for FileSystemService {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let served = match &self.served {
+            Served::FileSystem(_) => "FileSystem",
+            Served::FileManager(_) => "FileManager",
+        };
+        f.debug_struct("FileSystemService")
+            .field("served", &served)
+            .field("buffer_pool", &self.buffer_pool)
+            .field("blocking_pool", &self.blocking_pool)
+            .field("max_archive_size", &self.max_archive_size)
+            .finish()
+    }
+}
+
+impl FileSystemService {
+    pub fn new(file_system: FileSystemRef) -> FileSystemService {
+        FileSystemService {
+            served: Served::FileSystem(file_system),
+            buffer_pool: BufferPool::default(),
+            blocking_pool: BlockingPool::shared(),
+            max_archive_size: MAX_ARCHIVE_SIZE,
+        }
+    }
+
+    /// Returns a service of the file systems mounted in a FileManager.
+    pub fn from_file_manager(file_manager: FileManagerRef) -> FileSystemService {
+        FileSystemService {
+            served: Served::FileManager(file_manager),
+            buffer_pool: BufferPool::default(),
+            blocking_pool: BlockingPool::shared(),
+            max_archive_size: MAX_ARCHIVE_SIZE,
+        }
+    }
+
+    /**
+     * Sets the pool of the chunk buffers, e.g. shared by the services of
+     * a node, its chunk size bounding the chunks requested.
+     */
+    pub fn with_buffer_pool(mut self, buffer_pool: BufferPool) -> Self {
+        self.buffer_pool = buffer_pool;
+        self
+    }
+
+    /// Sets the size of the largest archive of files written at once.
+    pub fn with_max_archive_size(mut self, max_archive_size: u64) -> Self {
+        self.max_archive_size = max_archive_size;
+        self
+    }
+
+    /**
+     * Returns the size of the chunks a request is served in: the one
+     * requested, the default one for none, at most the chunk size of the
+     * pool.
+     */
+    fn chunk_size(&self, requested: u64) -> usize {
+        let chunk_size = match requested {
+            0 => CHUNK_SIZE,
+            size => usize::try_from(size).unwrap_or(usize::MAX),
+        };
+        chunk_size.min(self.buffer_pool.chunk_size()).max(1)
+    }
+
+    /// Sets the pool of the threads of the file operations, e.g. shared by the services of a node.
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> Self {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /// Returns the metrics of the pool of the buffers.
+    pub fn buffer_pool_metrics(&self) -> BufferPoolMetrics {
+        self.buffer_pool.metrics()
+    }
+
+    /// Returns the metrics of the pool of the threads of the file operations.
+    pub fn blocking_pool_metrics(&self) -> BlockingPoolMetrics {
+        self.blocking_pool.metrics()
+    }
+
+    /// Reads a chunk of a file at an offset into a buffer of the pool.
+    async fn read_chunk(
+        &self,
+        file_name: String,
+        offset: u64,
+        length: usize,
+    ) -> Result<PooledBuffer, Status> {
+        let mut buffer = self.buffer_pool.acquire();
+        self.call(move |fs| {
+            fs.read_at_into(&file_name, offset, length, &mut buffer)?;
+            Ok(buffer)
+        })
+        .await
+    }
+
+    /**
+     * Writes the data of a delta patched at an offset of the patched
+     * file. Returns the offset past it, and the buffer emptied.
+     */
+    async fn write_patched(
+        &self,
+        patched: &str,
+        offset: u64,
+        mut data: PooledBuffer,
+    ) -> Result<(u64, PooledBuffer), Status> {
+        let patched = patched.to_string();
+        self.call(move |fs| {
+            let offset = write_chunk(fs, &patched, offset, &data)?;
+            data.clear();
+            Ok((offset, data))
+        })
+        .await
+    }
+
+    /**
+     * Copies a range of the basis of a delta at an offset of the patched
+     * file, a chunk at a time. Returns the offset past it.
+     */
+    async fn copy_basis(
+        &self,
+        file_name: &str,
+        patched: &str,
+        offset: u64,
+        range: Range<u64>,
+    ) -> Result<u64, Status> {
+        let (file_name, patched) = (file_name.to_string(), patched.to_string());
+        let mut buffer = self.buffer_pool.acquire();
+        self.call(move |fs| {
+            let (mut offset, mut start) = (offset, range.start);
+            while start < range.end {
+                let length = usize::try_from(range.end - start)
+                    .unwrap_or(usize::MAX)
+                    .min(CHUNK_SIZE);
+                fs.read_at_into(&file_name, start, length, &mut buffer)?;
+                // The basis truncated meanwhile ends the copy
+                if buffer.is_empty() {
+                    break;
+                }
+                offset = write_chunk(fs, &patched, offset, &buffer)?;
+                start += buffer.len() as u64;
+            }
+            Ok(offset)
+        })
+        .await
+    }
+
+    /**
+     * Patches a file from its basis into the patched file, as the
+     * requests of the delta are received. Returns the size of the
+     * patched file.
+     */
+    async fn patch(
+        &self,
+        file_name: &str,
+        patched: &str,
+        first: DeltaRequest,
+        requests: &mut Streaming<DeltaRequest>,
+    ) -> Result<u64, Status> {
+        let block_size = usize::try_from(first.block_size).map_err(|_| {
+            Status::invalid_argument(format!("block size {} too large", first.block_size))
+        })?;
+        let basis = file_name.to_string();
+        let basis_size = self.call(move |fs| fs.size(&basis)).await?;
+        // The data received is written a chunk at a time, and before the blocks copied
+        let (mut offset, mut data) = (0, self.buffer_pool.acquire());
+        let mut request = Some(first);
+        while let Some(r) = request {
+            file_delta::verify(&r, offset + data.len() as u64)?;
+            // The data is written before it outgrows the chunk buffer
+            if data.len() + r.data.len() > CHUNK_SIZE && !data.is_empty() {
+                (offset, data) = self.write_patched(patched, offset, data).await?;
+            }
+            data.extend_from_slice(&r.data);
+            let copied = file_delta::copied(&r, block_size, basis_size)?;
+            if data.len() >= CHUNK_SIZE || copied.is_some() {
+                (offset, data) = self.write_patched(patched, offset, data).await?;
+            }
+            if let Some(copied) = copied {
+                offset = self.copy_basis(file_name, patched, offset, copied).await?;
+            }
+            request = requests.message().await?;
+        }
+        // The patched file is created even when empty
+        if offset == 0 || !data.is_empty() {
+            (offset, _) = self.write_patched(patched, offset, data).await?;
+        }
+        Ok(offset)
+    }
+
+    /// Calls an operation of the file system served on a thread of the blocking pool.
+    async fn call<R: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&dyn FileSystemTrait) -> file_system::Result<R> + Send + 'static,
+    ) -> Result<R, Status> {
+        let served = self.served.clone();
+        let result = self
+            .blocking_pool
+            .run(move || match &served {
+                Served::FileSystem(file_system) => operation(file_system.as_ref()),
+                Served::FileManager(file_manager) => operation(&*file_manager.lock().unwrap()),
+            })
+            .await?;
+        Ok(result?)
+    }
+}
+
+#[tonic::async_trait]
+impl file_system_server::FileSystem for FileSystemService {
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListReply>, Status> {
+        let pattern = request.into_inner().pattern;
+        let files = self.call(move |fs| fs.list(&pattern)).await?;
+        Ok(Response::new(ListReply {
+            files: files.iter().map(Into::into).collect(),
+        }))
+    }
+
+    type readStream = EventStream<Result<FileChunk, Status>>;
+
+    /**
+     * The chunks are read as they are sent, a few ahead, each released
+     * to the pool once sent. The first one is read before replying, the
+     * errors of the file being the status of the reply. An empty file is
+     * streamed as a single empty chunk.
+     */
+    async fn read(
+        &self,
+        request: Request<ReadRequest>,
+    ) -> Result<Response<Self::readStream>, Status> {
+        let request = request.into_inner();
+        let chunk_size = self.chunk_size(request.chunk_size);
+        let file_name = request.file_name;
+        let first = self.read_chunk(file_name.clone(), 0, chunk_size).await?;
+        let (sender, receiver) = mpsc::channel(READ_AHEAD);
+        let service = self.clone();
+        tokio::spawn(async move {
+            let (mut offset, mut data) = (0, first);
+            loop {
+                let size = data.len();
+                let sent = sender.send(Ok(chunk(offset, Bytes::from_owner(data))));
+                // The client gone, the file is read no further
+                if sent.await.is_err() || size < chunk_size {
+                    return;
+                }
+                offset += size as u64;
+                data = match service
+                    .read_chunk(file_name.clone(), offset, chunk_size)
+                    .await
+                {
+                    Ok(data) if data.is_empty() => return,
+                    Ok(data) => data,
+                    Err(status) => {
+                        let _ = sender.send(Err(status)).await;
+                        return;
+                    }
+                };
+            }
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(receiver))))
+    }
+
+    /**
+     * The chunks are written as received, the first one overwriting the
+     * file, the ones whose CRC32C differs written as received and
+     * replied, for the client to write them again.
+     */
+    async fn write(
+        &self,
+        request: Request<Streaming<WriteRequest>>,
+    ) -> Result<Response<WriteReply>, Status> {
+        let mut requests = request.into_inner();
+        let mut file_name = None;
+        let (mut size, mut corrupted) = (0, Vec::new());
+        while let Some(r) = requests.message().await? {
+            let file_name = file_name.get_or_insert(r.file_name).clone();
+            if r.crc32c.is_some_and(|crc| crc != checksum::crc32c(&r.data)) {
+                corrupted.push(ChunkRange {
+                    offset: size,
+                    size: r.data.len() as u64,
+                });
+            }
+            let (offset, data) = (size, r.data);
+            size = self
+                .call(move |fs| write_chunk(fs, &file_name, offset, &data))
+                .await?;
+        }
+        if file_name.is_none() {
+            return Err(Status::invalid_argument("no file written"));
+        }
+        Ok(Response::new(WriteReply { size, corrupted }))
+    }
+
+    async fn read_range(
+        &self,
+        request: Request<ReadRangeRequest>,
+    ) -> Result<Response<FileChunk>, Status> {
+        let request = request.into_inner();
+        // The octets past the end of the file, or past a chunk of the pool, are not read
+        let size = usize::try_from(request.size)
+            .unwrap_or(usize::MAX)
+            .min(self.buffer_pool.chunk_size());
+        let (file_name, offset) = (request.file_name, request.offset);
+        let data = self
+            .call(move |fs| fs.read_at(&file_name, offset, size))
+            .await?;
+        Ok(Response::new(chunk(offset, data.into())))
+    }
+
+    /**
+     * The data replaces the bytes at the offset, extending the file past
+     * its end, the file being written in place rather than rewritten.
+     */
+    async fn write_range(
+        &self,
+        request: Request<WriteRangeRequest>,
+    ) -> Result<Response<WriteReply>, Status> {
+        let request = request.into_inner();
+        let offset = request.offset;
+        if request
+            .crc32c
+            .is_some_and(|crc| crc != checksum::crc32c(&request.data))
+        {
+            return Err(Status::data_loss(format!(
+                "the chunk at offset {offset} is corrupted"
+            )));
+        }
+        let size = self
+            .call(move |fs| fs.write_at(&request.file_name, offset, &request.data))
+            .await?;
+        Ok(Response::new(WriteReply {
+            size,
+            corrupted: Vec::new(),
+        }))
+    }
+
+    async fn remove(
+        &self,
+        request: Request<RemoveRequest>,
+    ) -> Result<Response<RemoveReply>, Status> {
+        let file_name = request.into_inner().file_name;
+        self.call(move |fs| fs.remove(&file_name)).await?;
+        Ok(Response::new(RemoveReply {}))
+    }
+
+    async fn copy(&self, request: Request<CopyRequest>) -> Result<Response<CopyReply>, Status> {
+        let r = request.into_inner();
+        self.call(move |fs| fs.copy(&r.source_file_name, &r.destination_file_name))
+            .await?;
+        Ok(Response::new(CopyReply {}))
+    }
+
+    /// The file is copied, then the source removed.
+    async fn r#move(&self, request: Request<MoveRequest>) -> Result<Response<MoveReply>, Status> {
+        let r = request.into_inner();
+        self.call(move |fs| fs.rename(&r.source_file_name, &r.destination_file_name))
+            .await?;
+        Ok(Response::new(MoveReply {}))
+    }
+
+    async fn mkdir(&self, request: Request<MkdirRequest>) -> Result<Response<MkdirReply>, Status> {
+        let directory_name = request.into_inner().directory_name;
+        self.call(move |fs| fs.mkdir(&directory_name)).await?;
+        Ok(Response::new(MkdirReply {}))
+    }
+
+    async fn rmdir(&self, request: Request<RmdirRequest>) -> Result<Response<RmdirReply>, Status> {
+        let directory_name = request.into_inner().directory_name;
+        self.call(move |fs| fs.rmdir(&directory_name)).await?;
+        Ok(Response::new(RmdirReply {}))
+    }
+
+    async fn query(&self, _request: Request<QueryRequest>) -> Result<Response<QueryReply>, Status> {
+        let spaces = self.call(|fs| fs.query()).await?;
+        Ok(Response::new(QueryReply {
+            spaces: spaces.iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn digest(
+        &self,
+        request: Request<DigestRequest>,
+    ) -> Result<Response<DigestReply>, Status> {
+        let file_name = request.into_inner().file_name;
+        let mut buffer = self.buffer_pool.acquire();
+        // The digest of a large file is computed off the runtime threads too
+        let (size, sha3_256) = self
+            .call(move |fs| {
+                let mut digest = ChunkedDigest::default();
+                let size = read_chunks(fs, &file_name, CHUNK_SIZE, &mut buffer, |chunk| {
+                    digest.update(chunk)
+                })?;
+                Ok((size, digest.finish()))
+            })
+            .await?;
+        Ok(Response::new(DigestReply { size, sha3_256 }))
+    }
+
+    type get_manyStream = EventStream<Result<ArchiveChunk, Status>>;
+
+    /**
+     * The files are read, then streamed in the chunks of an archive, files
+     * larger together than the largest archive being refused with
+     * RESOURCE_EXHAUSTED before any is read.
+     */
+    async fn get_many(
+        &self,
+        request: Request<GetManyRequest>,
+    ) -> Result<Response<Self::get_manyStream>, Status> {
+        let request = request.into_inner();
+        let file_names = request.file_names.clone();
+        let max_archive_size = self.max_archive_size;
+        let contents = self
+            .call(move |fs| {
+                let size = file_names
+                    .iter()
+                    .map(|file_name| fs.size(file_name))
+                    .sum::<file_system::Result<u64>>()?;
+                if size > max_archive_size {
+                    return Ok(None);
+                }
+                let file_names: Vec<&str> = file_names.iter().map(String::as_str).collect();
+                fs.get_many(&file_names).map(Some)
+            })
+            .await?
+            .ok_or_else(|| {
+                Status::resource_exhausted(format!("archive larger than {max_archive_size} octets"))
+            })?;
+        let chunk_size = self.chunk_size(request.chunk_size);
+        let chunks = file_archive::pack(request.file_names.iter().zip(contents), chunk_size);
+        Ok(Response::new(Box::pin(tokio_stream::iter(chunks).map(Ok))))
+    }
+
+    /**
+     * The files are written once all of the chunks of the archive are
+     * received, an archive larger than the largest one being refused
+     * with RESOURCE_EXHAUSTED as soon as its chunks exceed it.
+     */
+    async fn put_many(
+        &self,
+        request: Request<Streaming<ArchiveChunk>>,
+    ) -> Result<Response<PutManyReply>, Status> {
+        let mut chunks = request.into_inner();
+        let mut unpacker = Unpacker::new();
+        let mut received = 0;
+        while let Some(chunk) = chunks.message().await? {
+            received += chunk.data.len() as u64;
+            if received > self.max_archive_size {
+                return Err(Status::resource_exhausted(format!(
+                    "archive larger than {} octets",
+                    self.max_archive_size
+                )));
+            }
+            unpacker.push(chunk)?;
+        }
+        let files = unpacker.finish()?;
+        let reply = PutManyReply {
+            files: files.len() as u64,
+            size: files.iter().map(|(_, data)| data.len() as u64).sum(),
+        };
+        self.call(move |fs| {
+            let files: Vec<(&str, &[u8])> = files
+                .iter()
+                .map(|(file_name, data)| (file_name.as_str(), data.as_slice()))
+                .collect();
+            fs.put_many(&files)
+        })
+        .await?;
+        Ok(Response::new(reply))
+    }
+
+    /**
+     * The block size requested is kept within the bounds of the
+     * signatures, the file being read a chunk of whole blocks at a time.
+     */
+    async fn signature(
+        &self,
+        request: Request<SignatureRequest>,
+    ) -> Result<Response<SignatureReply>, Status> {
+        let request = request.into_inner();
+        let mut buffer = self.buffer_pool.acquire();
+        let signature = self
+            .call(move |fs| {
+                let file_name = request.file_name;
+                let block_size = match request.block_size {
+                    0 => file_delta::block_size(fs.size(&file_name)?),
+                    size => usize::try_from(size)
+                        .unwrap_or(usize::MAX)
+                        .clamp(file_delta::MIN_BLOCK_SIZE, file_delta::MAX_BLOCK_SIZE),
+                };
+                let chunk_size = (CHUNK_SIZE / block_size).max(1) * block_size;
+                let mut blocks = Vec::new();
+                let size = read_chunks(fs, &file_name, chunk_size, &mut buffer, |chunk| {
+                    blocks.extend(chunk.chunks(block_size).map(file_delta::block_signature))
+                })?;
+                Ok(SignatureReply {
+                    size,
+                    block_size: block_size as u64,
+                    blocks,
+                })
+            })
+            .await?;
+        Ok(Response::new(signature))
+    }
+
+    /**
+     * The file is patched from its previous content, the basis, into a
+     * file beside it as the requests of the delta are received, then
+     * renamed over it, the file being replaced atomically. A corrupted
+     * request fails the write, the file left as is, for the client to
+     * send the delta again.
+     */
+    async fn write_delta(
+        &self,
+        request: Request<Streaming<DeltaRequest>>,
+    ) -> Result<Response<WriteReply>, Status> {
+        let mut requests = request.into_inner();
+        let first = requests
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("no file written"))?;
+        let file_name = first.file_name.clone();
+        let patched = patched_file_name(&file_name);
+        let size = match self.patch(&file_name, &patched, first, &mut requests).await {
+            Ok(size) => {
+                let (source, destination) = (patched.clone(), file_name);
+                self.call(move |fs| fs.rename(&source, &destination).map(|()| size))
+                    .await
+            }
+            Err(status) => Err(status),
+        };
+        // The patched file is removed when it failed to replace the file
+        if size.is_err() {
+            let _ = self.call(move |fs| fs.remove(&patched)).await;
+        }
+        Ok(Response::new(WriteReply {
+            size: size?,
+            corrupted: Vec::new(),
+        }))
+    }
+
+    async fn metrics(
+        &self,
+        _request: Request<MetricsRequest>,
+    ) -> Result<Response<MetricsReply>, Status> {
+        Ok(Response::new(MetricsReply {
+            buffer_pool: Some(self.buffer_pool_metrics().into()),
+            blocking_pool: Some(self.blocking_pool_metrics().into()),
+        }))
+    }
+}
+
+/**
+ * Writes a chunk of a file at an offset, the first one overwriting the
+ * file. Returns the offset past the chunk.
+ */
+fn write_chunk(
+    fs: &dyn FileSystemTrait,
+    file_name: &str,
+    offset: u64,
+    data: &[u8],
+) -> file_system::Result<u64> {
+    if offset == 0 {
+        fs.write(file_name, data)?;
+        return Ok(data.len() as u64);
+    }
+    fs.write_at(file_name, offset, data)
+}
+
+/**
+ * Reads a plain file a chunk at a time into a buffer, handing each chunk
+ * to visit, so that a large file is never loaded whole. Returns the size
+ * of the file read.
+ */
+fn read_chunks(
+    fs: &dyn FileSystemTrait,
+    file_name: &str,
+    chunk_size: usize,
+    buffer: &mut Vec<u8>,
+    mut visit: impl FnMut(&[u8]),
+) -> file_system::Result<u64> {
+    let mut offset = 0;
+    loop {
+        fs.read_at_into(file_name, offset, chunk_size, buffer)?;
+        visit(buffer);
+        offset += buffer.len() as u64;
+        if buffer.len() < chunk_size {
+            return Ok(offset);
+        }
+    }
+}
+
+/// Returns the name of the hidden file a delta is patched into, beside the file patched.
+fn patched_file_name(file_name: &str) -> String {
+    match file_name.rsplit_once('/') {
+        Some((directory, name)) => format!("{directory}/.{name}.delta"),
+        None => format!(".{file_name}.delta"),
+    }
+}
+
+/// Returns a chunk of a file read, of its CRC32C.
+fn chunk(offset: u64, data: Bytes) -> FileChunk {
+    FileChunk {
+        crc32c: Some(checksum::crc32c(&data)),
+        offset,
+        data,
+    }
+}
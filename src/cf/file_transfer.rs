@@ -0,0 +1,742 @@
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tokio::task::JoinSet;
+use tokio_stream::Stream;
+use tonic::body::BoxBody;
+use tonic::client::GrpcService;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{Code, Status};
+
+use super::checksum;
+use super::file_archive::{self, Unpacker};
+use super::file_delta;
+use super::file_system_service::{CHUNK_SIZE, MAX_CHUNK_SIZE};
+use super::rpc::file_system::file_system_client::FileSystemClient;
+use super::rpc::file_system::{
+    DigestRequest, GetManyRequest, ReadRangeRequest, SignatureRequest, WriteRangeRequest,
+    WriteRequest,
+};
+
+/**
+ * Convienence enum definition that includes all file transfer errors.
+ */
+#[derive(Error, Debug)]
+pub enum TransferError {
+    /**
+     * This exception indicates that the tuning of a transfer is invalid,
+     * e.g. a chunk size of zero or an initial chunk size out of bounds.
+     */
+    #[error("InvalidTuning: msg: '{message}'.")]
+    InvalidTuning { message: String },
+    /**
+     * This exception indicates that a transfer failed, of the gRPC status
+     * code of the call, or that the file changed while read.
+     */
+    #[error("TransferFailed: file: '{file_name}', code: '{code:?}', msg: '{message}'.")]
+    TransferFailed {
+        file_name: String,
+        code: Code,
+        message: String,
+    },
+}
+
+/*
+ * Convienence type definition that includes all file transfer returned errors.
+ */
+pub type Result<T, E = TransferError> = anyhow::Result<T, E>;
+
+/**
+ * The bounds the chunk size and the in-flight window of a transfer adapt
+ * within, the gigabit LANs reaching the largest chunks and the radio
+ * links of a few Mb/s the smallest ones.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferTuning {
+    pub min_chunk_size: usize,
+    /// At most the 4 MiB a gRPC message is limited to by default, the streams being served in chunks of at most MAX_CHUNK_SIZE.
+    pub max_chunk_size: usize,
+    /// The size of the chunks transferred before any measure.
+    pub initial_chunk_size: usize,
+    /// The most chunks requested and not received yet.
+    pub max_in_flight: usize,
+    /// The time a chunk is aimed to take at the throughput measured.
+    pub chunk_time: Duration,
+    /// The most times a chunk whose CRC32C differs is transferred again.
+    pub max_retransmissions: usize,
+    /// Whether the digest of the file transferred is checked against the one of the service.
+    pub verify_digest: bool,
+}
+
+impl Default for TransferTuning {
+    fn default() -> Self {
+        TransferTuning {
+            min_chunk_size: 4 * 1024,
+            max_chunk_size: MAX_CHUNK_SIZE,
+            initial_chunk_size: CHUNK_SIZE,
+            max_in_flight: 8,
+            chunk_time: Duration::from_millis(100),
+            max_retransmissions: 3,
+            verify_digest: true,
+        }
+    }
+}
+
+impl TransferTuning {
+    fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| {
+            Err(TransferError::InvalidTuning {
+                message: message.to_string(),
+            })
+        };
+        if self.min_chunk_size == 0 || self.min_chunk_size > self.max_chunk_size {
+            return invalid(
+                "the chunk sizes must be positive, the minimum one at most the maximum one",
+            );
+        }
+        if !(self.min_chunk_size..=self.max_chunk_size).contains(&self.initial_chunk_size) {
+            return invalid("the initial chunk size must be within the bounds");
+        }
+        if self.max_in_flight == 0 {
+            return invalid("the in-flight window must be positive");
+        }
+        if self.chunk_time.is_zero() {
+            return invalid("the chunk time must be positive");
+        }
+        Ok(())
+    }
+}
+
+/**
+ * The adaptation of the chunk size and of the in-flight window of a
+ * transfer to the throughput and the round trip time measured on its
+ * chunks: the chunks are sized to take the chunk time at the throughput,
+ * and enough of them are kept in flight to cover the product of the
+ * throughput by the round trip time, plus one probing for more.
+ */
+#[derive(Debug, Clone)]
+pub struct ChunkController {
+    tuning: TransferTuning,
+    chunk_size: usize,
+    in_flight: usize,
+    /// The smoothed bytes received per second, once measured.
+    throughput: Option<f64>,
+    /// The smoothed round trip time of the chunks, once measured.
+    rtt: Option<Duration>,
+    last_completion: Option<Instant>,
+}
+
+impl ChunkController {
+    pub fn new(tuning: TransferTuning) -> Result<ChunkController> {
+        tuning.validate()?;
+        Ok(ChunkController {
+            chunk_size: tuning.initial_chunk_size,
+            in_flight: 1,
+            throughput: None,
+            rtt: None,
+            last_completion: None,
+            tuning,
+        })
+    }
+
+    pub fn tuning(&self) -> &TransferTuning {
+        &self.tuning
+    }
+
+    /// Returns the size of the next chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the number of the chunks to keep in flight.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight
+    }
+
+    /// Returns the bytes per second measured, smoothed.
+    pub fn throughput(&self) -> Option<f64> {
+        self.throughput
+    }
+
+    /// Returns the round trip time of the chunks measured, smoothed.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /**
+     * Records the completion of a chunk, of its size and round trip time,
+     * at an instant. The throughput is measured on the interval since the
+     * previous completion, the round trip time at most, so that the
+     * chunks in flight together are measured and not the idle times.
+     */
+    pub fn record(&mut self, size: usize, rtt: Duration, completed: Instant) {
+        let interval = match self.last_completion {
+            Some(last) => completed.saturating_duration_since(last).min(rtt),
+            None => rtt,
+        };
+        self.last_completion = Some(completed);
+        // Smoothed as the TCP round trip time, the throughput following faster
+        self.rtt = Some(match self.rtt {
+            Some(smoothed) => (smoothed * 7 + rtt) / 8,
+            None => rtt,
+        });
+        if !interval.is_zero() {
+            let sample = size as f64 / interval.as_secs_f64();
+            self.throughput = Some(match self.throughput {
+                Some(smoothed) => 0.75 * smoothed + 0.25 * sample,
+                None => sample,
+            });
+        }
+        self.adapt();
+    }
+
+    fn adapt(&mut self) {
+        let (Some(throughput), Some(rtt)) = (self.throughput, self.rtt) else {
+            return;
+        };
+        let chunk_size = throughput * self.tuning.chunk_time.as_secs_f64();
+        self.chunk_size =
+            (chunk_size as usize).clamp(self.tuning.min_chunk_size, self.tuning.max_chunk_size);
+        let bandwidth_delay = throughput * rtt.as_secs_f64() / self.chunk_size as f64;
+        self.in_flight = (bandwidth_delay.ceil() as usize + 1).clamp(1, self.tuning.max_in_flight);
+    }
+}
+
+/**
+ * This type reports a transfer, the chunk size and in-flight window
+ * being the ones adapted to by its end.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferStats {
+    pub size: usize,
+    /// The bytes of the content transferred, fewer than the size by the blocks a delta copies.
+    pub transferred: usize,
+    pub chunks: usize,
+    /// The duration of the transfer, in seconds.
+    pub elapsed: f64,
+    /// The bytes transferred per second.
+    pub throughput: f64,
+    pub chunk_size: usize,
+    pub in_flight: usize,
+    /// The chunks transferred again, their CRC32C differing.
+    pub retransmissions: usize,
+}
+
+impl TransferStats {
+    fn new(
+        size: usize,
+        chunks: usize,
+        retransmissions: usize,
+        started: Instant,
+        controller: &ChunkController,
+    ) -> Self {
+        let elapsed = started.elapsed().as_secs_f64();
+        TransferStats {
+            size,
+            transferred: size,
+            chunks,
+            retransmissions,
+            elapsed,
+            throughput: if elapsed > 0.0 {
+                size as f64 / elapsed
+            } else {
+                0.0
+            },
+            chunk_size: controller.chunk_size(),
+            in_flight: controller.in_flight(),
+        }
+    }
+}
+
+/**
+ * Reads a remote file by range, the chunk size and the number of the
+ * ranges requested in parallel adapting to the link. The chunks are
+ * requested until one comes back short, the end of the file, the ones
+ * whose CRC32C differs being requested again.
+ */
+pub async fn read_file<T>(
+    client: &FileSystemClient<T>,
+    file_name: &str,
+    tuning: &TransferTuning,
+) -> Result<(Vec<u8>, TransferStats)>
+where
+    T: GrpcService<BoxBody> + Clone + Send + 'static,
+    T::Error: Into<StdError>,
+    T::Future: Send,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: file_name.to_string(),
+        code,
+        message,
+    };
+    let mut controller = ChunkController::new(tuning.clone())?;
+    let started = Instant::now();
+    let mut tasks = JoinSet::new();
+    let request = |tasks: &mut JoinSet<_>, offset: u64, size: usize| {
+        let request = ReadRangeRequest {
+            file_name: file_name.to_string(),
+            offset,
+            size: size as u64,
+        };
+        let mut client = client.clone();
+        tasks.spawn(async move {
+            let sent = Instant::now();
+            let reply = client.read_range(request).await;
+            (offset, size, sent.elapsed(), reply.map(|r| r.into_inner()))
+        });
+    };
+    let mut chunks = BTreeMap::new();
+    let mut retransmissions: BTreeMap<u64, usize> = BTreeMap::new();
+    let (mut offset, mut end) = (0, None);
+    loop {
+        while end.is_none() && tasks.len() < controller.in_flight() {
+            let size = controller.chunk_size();
+            request(&mut tasks, offset, size);
+            offset += size as u64;
+        }
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        let (chunk_offset, size, rtt, chunk) =
+            joined.map_err(|e| failed(Code::Internal, e.to_string()))?;
+        let chunk = chunk.map_err(|status| failed(status.code(), status.message().to_string()))?;
+        controller.record(chunk.data.len(), rtt, Instant::now());
+        if chunk
+            .crc32c
+            .is_some_and(|crc| crc != checksum::crc32c(&chunk.data))
+        {
+            let attempts = retransmissions.entry(chunk_offset).or_default();
+            if *attempts == tuning.max_retransmissions {
+                return Err(failed(
+                    Code::DataLoss,
+                    format!("the chunk at offset {chunk_offset} stays corrupted"),
+                ));
+            }
+            *attempts += 1;
+            request(&mut tasks, chunk_offset, size);
+            continue;
+        }
+        let data = chunk.data;
+        if data.len() < size {
+            let chunk_end = chunk_offset + data.len() as u64;
+            end = Some(end.map_or(chunk_end, |end: u64| end.min(chunk_end)));
+        }
+        chunks.insert(chunk_offset, data);
+    }
+
+    let end = end.unwrap_or_default();
+    let capacity = usize::try_from(end).map_err(|_| {
+        failed(
+            Code::OutOfRange,
+            format!("the file of {end} octets does not fit in memory"),
+        )
+    })?;
+    let mut data = Vec::with_capacity(capacity);
+    for (chunk_offset, chunk) in chunks.range(..end) {
+        if *chunk_offset != data.len() as u64 {
+            return Err(failed(
+                Code::Aborted,
+                "the file changed while read".to_string(),
+            ));
+        }
+        let length = usize::try_from(end - chunk_offset).unwrap_or(usize::MAX);
+        data.extend_from_slice(&chunk[..chunk.len().min(length)]);
+    }
+    if tuning.verify_digest {
+        verify_digest(client, file_name, &data).await?;
+    }
+    // The empty file is a single empty chunk
+    let count = chunks.range(..end.max(1)).count();
+    let retransmissions = retransmissions.values().sum();
+    let stats = TransferStats::new(data.len(), count, retransmissions, started, &controller);
+    Ok((data, stats))
+}
+
+/**
+ * Writes a remote file, created or truncated, streaming its chunks in a
+ * single call, the ranges written in parallel not truncating the file.
+ * The chunks are sized after the rate the transport takes them at, the
+ * flow control of the transport bounding the data in flight. The chunks
+ * the service received corrupted are written again by range.
+ */
+pub async fn write_file<T>(
+    client: &FileSystemClient<T>,
+    file_name: &str,
+    data: Vec<u8>,
+    tuning: &TransferTuning,
+) -> Result<TransferStats>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: file_name.to_string(),
+        code,
+        message,
+    };
+    let controller = Arc::new(Mutex::new(ChunkController::new(tuning.clone())?));
+    let started = Instant::now();
+    let data = Bytes::from(data);
+    let size = data.len();
+    let chunks = AdaptiveChunks {
+        file_name: Some(file_name.to_string()),
+        data: data.clone(),
+        offset: 0,
+        previous: None,
+        chunks: Arc::default(),
+        controller: controller.clone(),
+    };
+    let count = chunks.chunks.clone();
+    let status_failed = |status: Status| failed(status.code(), status.message().to_string());
+    let reply = client
+        .clone()
+        .write(chunks)
+        .await
+        .map_err(status_failed)?
+        .into_inner();
+    if reply.size != size as u64 {
+        return Err(failed(
+            Code::DataLoss,
+            "the file written has another size".to_string(),
+        ));
+    }
+
+    let mut retransmissions = 0;
+    for range in reply.corrupted {
+        // The range is checked in 64 bits, before being narrowed
+        let chunk = match range.offset.checked_add(range.size) {
+            Some(end) if end <= size as u64 => data.slice(range.offset as usize..end as usize),
+            _ => {
+                return Err(failed(
+                    Code::DataLoss,
+                    format!(
+                        "the corrupted chunk at offset {} is out of the file",
+                        range.offset
+                    ),
+                ))
+            }
+        };
+        let mut attempts = 0;
+        loop {
+            retransmissions += 1;
+            let request = WriteRangeRequest {
+                file_name: file_name.to_string(),
+                offset: range.offset,
+                data: chunk.to_vec(),
+                crc32c: Some(checksum::crc32c(&chunk)),
+            };
+            match client.clone().write_range(request).await {
+                Ok(_) => break,
+                Err(status)
+                    if status.code() == Code::DataLoss && attempts < tuning.max_retransmissions =>
+                {
+                    attempts += 1
+                }
+                Err(status) => return Err(status_failed(status)),
+            }
+        }
+    }
+    if tuning.verify_digest {
+        verify_digest(client, file_name, &data).await?;
+    }
+    let controller = controller.lock().unwrap();
+    Ok(TransferStats::new(
+        size,
+        count.load(Ordering::Relaxed),
+        retransmissions,
+        started,
+        &controller,
+    ))
+}
+
+/**
+ * Writes a remote file from its previous content, as rsync: the service
+ * returns the signature of the blocks of the file, and only the data
+ * that differs from them is sent, the blocks found being copied by the
+ * service. The delta is sent again when the service received it
+ * corrupted. The file missing, or the file written differing, e.g. of a
+ * block matched wrongly or of a file changed meanwhile, it is written
+ * whole.
+ */
+pub async fn sync_file<T>(
+    client: &FileSystemClient<T>,
+    file_name: &str,
+    data: Vec<u8>,
+    tuning: &TransferTuning,
+) -> Result<TransferStats>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: file_name.to_string(),
+        code,
+        message,
+    };
+    let controller = ChunkController::new(tuning.clone())?;
+    let started = Instant::now();
+    let request = SignatureRequest {
+        file_name: file_name.to_string(),
+        block_size: 0,
+    };
+    let signature = match client.clone().signature(request).await {
+        Ok(reply) => reply.into_inner(),
+        Err(status) if status.code() == Code::NotFound => {
+            return write_file(client, file_name, data, tuning).await
+        }
+        Err(status) => return Err(failed(status.code(), status.message().to_string())),
+    };
+    let Ok(block_size) = usize::try_from(signature.block_size) else {
+        return write_file(client, file_name, data, tuning).await;
+    };
+    let ops = file_delta::delta(&signature, &data);
+    let requests =
+        file_delta::requests(file_name, block_size, &data, &ops, controller.chunk_size());
+
+    let mut retransmissions = 0;
+    let reply = loop {
+        match client
+            .clone()
+            .write_delta(tokio_stream::iter(requests.clone()))
+            .await
+        {
+            Ok(reply) => break reply.into_inner(),
+            Err(status)
+                if status.code() == Code::DataLoss
+                    && retransmissions < tuning.max_retransmissions =>
+            {
+                retransmissions += 1
+            }
+            Err(status) => return Err(failed(status.code(), status.message().to_string())),
+        }
+    };
+    let written = reply.size == data.len() as u64
+        && (!tuning.verify_digest
+            || match verify_digest(client, file_name, &data).await {
+                Ok(()) => true,
+                Err(TransferError::TransferFailed {
+                    code: Code::DataLoss,
+                    ..
+                }) => false,
+                Err(e) => return Err(e),
+            });
+    if !written {
+        return write_file(client, file_name, data, tuning).await;
+    }
+    let mut stats = TransferStats::new(
+        data.len(),
+        requests.len(),
+        retransmissions,
+        started,
+        &controller,
+    );
+    stats.transferred = requests.iter().map(|r| r.data.len()).sum();
+    Ok(stats)
+}
+
+/**
+ * Reads many small files of a remote file system in a single exchange,
+ * the files streamed in the chunks of an archive of the initial chunk
+ * size. The chunks being verified by their CRC32C, the archive is read
+ * again when one of them is corrupted.
+ */
+pub async fn get_many<T>(
+    client: &FileSystemClient<T>,
+    file_names: &[&str],
+    tuning: &TransferTuning,
+) -> Result<(Vec<Vec<u8>>, TransferStats)>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let controller = ChunkController::new(tuning.clone())?;
+    let started = Instant::now();
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: file_names.join(","),
+        code,
+        message,
+    };
+    let request = GetManyRequest {
+        file_names: file_names.iter().map(|f| f.to_string()).collect(),
+        chunk_size: controller.chunk_size() as u64,
+    };
+    let mut retransmissions = 0;
+    loop {
+        let received = async {
+            let mut chunks = client.clone().get_many(request.clone()).await?.into_inner();
+            let (mut unpacker, mut count) = (Unpacker::new(), 0);
+            while let Some(chunk) = chunks.message().await? {
+                unpacker.push(chunk)?;
+                count += 1;
+            }
+            Ok::<_, Status>((unpacker.finish()?, count))
+        };
+        match received.await {
+            Ok((files, count)) => {
+                let contents: Vec<Vec<u8>> = files.into_iter().map(|(_, data)| data).collect();
+                if contents.len() != file_names.len() {
+                    return Err(failed(
+                        Code::DataLoss,
+                        format!("{} files received", contents.len()),
+                    ));
+                }
+                let size = contents.iter().map(Vec::len).sum();
+                let stats = TransferStats::new(size, count, retransmissions, started, &controller);
+                return Ok((contents, stats));
+            }
+            Err(status)
+                if status.code() == Code::DataLoss
+                    && retransmissions < tuning.max_retransmissions =>
+            {
+                retransmissions += 1
+            }
+            Err(status) => return Err(failed(status.code(), status.message().to_string())),
+        }
+    }
+}
+
+/**
+ * Writes many small files of a remote file system, created or truncated,
+ * in a single exchange, the files streamed in the chunks of an archive of
+ * the initial chunk size. The archive is written again when the service
+ * received one of its chunks corrupted.
+ */
+pub async fn put_many<T>(
+    client: &FileSystemClient<T>,
+    files: &[(&str, &[u8])],
+    tuning: &TransferTuning,
+) -> Result<TransferStats>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let controller = ChunkController::new(tuning.clone())?;
+    let started = Instant::now();
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: files.iter().map(|(f, _)| *f).collect::<Vec<_>>().join(","),
+        code,
+        message,
+    };
+    let chunks = file_archive::pack(files.iter().copied(), controller.chunk_size());
+    let size: usize = files.iter().map(|(_, data)| data.len()).sum();
+    let mut retransmissions = 0;
+    loop {
+        match client
+            .clone()
+            .put_many(tokio_stream::iter(chunks.clone()))
+            .await
+        {
+            Ok(reply) if reply.get_ref().size != size as u64 => {
+                return Err(failed(
+                    Code::DataLoss,
+                    "the files written have another size".to_string(),
+                ))
+            }
+            Ok(_) => {
+                return Ok(TransferStats::new(
+                    size,
+                    chunks.len(),
+                    retransmissions,
+                    started,
+                    &controller,
+                ))
+            }
+            Err(status)
+                if status.code() == Code::DataLoss
+                    && retransmissions < tuning.max_retransmissions =>
+            {
+                retransmissions += 1
+            }
+            Err(status) => return Err(failed(status.code(), status.message().to_string())),
+        }
+    }
+}
+
+/// Checks the digest of a remote file against the one of its content transferred.
+async fn verify_digest<T>(client: &FileSystemClient<T>, file_name: &str, data: &[u8]) -> Result<()>
+where
+    T: GrpcService<BoxBody> + Clone,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    let failed = |code: Code, message: String| TransferError::TransferFailed {
+        file_name: file_name.to_string(),
+        code,
+        message,
+    };
+    let reply = client
+        .clone()
+        .digest(DigestRequest {
+            file_name: file_name.to_string(),
+        })
+        .await
+        .map_err(|status| failed(status.code(), status.message().to_string()))?
+        .into_inner();
+    if reply.size != data.len() as u64 || reply.sha3_256 != checksum::digest(data) {
+        return Err(failed(
+            Code::DataLoss,
+            "the digest of the file transferred differs".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The chunks of a file written, sized when the transport takes them.
+struct AdaptiveChunks {
+    /// The name of the file, given by the first chunk.
+    file_name: Option<String>,
+    data: Bytes,
+    offset: usize,
+    /// The size of the previous chunk and when it was taken.
+    previous: Option<(usize, Instant)>,
+    chunks: Arc<AtomicUsize>,
+    controller: Arc<Mutex<ChunkController>>,
+}
+
+impl Stream for AdaptiveChunks {
+    type Item = WriteRequest;
+
+    /// An empty file is written as a single empty chunk.
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<WriteRequest>> {
+        let now = Instant::now();
+        let previous = self.previous.take();
+        let chunk_size = {
+            let mut controller = self.controller.lock().unwrap();
+            // The next chunk is taken once the transport accepted the previous one
+            if let Some((size, taken)) = previous {
+                controller.record(size, now.saturating_duration_since(taken), now);
+            }
+            controller.chunk_size()
+        };
+        if self.offset >= self.data.len() && self.file_name.is_none() {
+            return Poll::Ready(None);
+        }
+        let end = (self.offset + chunk_size).min(self.data.len());
+        let data = self.data[self.offset..end].to_vec();
+        let chunk = WriteRequest {
+            file_name: self.file_name.take().unwrap_or_default(),
+            crc32c: Some(checksum::crc32c(&data)),
+            data,
+        };
+        self.previous = Some((end - self.offset, now));
+        self.offset = end;
+        self.chunks.fetch_add(1, Ordering::Relaxed);
+        Poll::Ready(Some(chunk))
+    }
+}
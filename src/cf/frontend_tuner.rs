@@ -0,0 +1,611 @@
+use super::common_types::{AnyValue, DataType, Properties};
+use super::device::{
+    self, AdminType, Device, DeviceError, DeviceTrait, OperationalType, UsageType,
+};
+
+/// The struct allocation property requesting control of (or listening to) a tuner.
+pub const TUNER_ALLOCATION_ID: &str = "FRONTEND::tuner_allocation";
+/// The struct allocation property requesting to listen to an existing tuner allocation.
+pub const LISTENER_ALLOCATION_ID: &str = "FRONTEND::listener_allocation";
+/// The property holding the sequence of tuner status structs.
+pub const TUNER_STATUS_ID: &str = "FRONTEND::tuner_status";
+
+/// The tuner_allocation field holding the tuner type (string).
+pub const TUNER_TYPE_ID: &str = "FRONTEND::tuner_allocation::tuner_type";
+/// The tuner_allocation field holding the allocation identifier (string).
+pub const ALLOCATION_ID_ID: &str = "FRONTEND::tuner_allocation::allocation_id";
+/// The tuner_allocation field holding the center frequency in Hz (double).
+pub const CENTER_FREQUENCY_ID: &str = "FRONTEND::tuner_allocation::center_frequency";
+/// The tuner_allocation field holding the bandwidth in Hz (double).
+pub const BANDWIDTH_ID: &str = "FRONTEND::tuner_allocation::bandwidth";
+/// The tuner_allocation field holding the bandwidth tolerance in percent (double).
+pub const BANDWIDTH_TOLERANCE_ID: &str = "FRONTEND::tuner_allocation::bandwidth_tolerance";
+/// The tuner_allocation field holding the sample rate in samples/s (double).
+pub const SAMPLE_RATE_ID: &str = "FRONTEND::tuner_allocation::sample_rate";
+/// The tuner_allocation field holding the sample rate tolerance in percent (double).
+pub const SAMPLE_RATE_TOLERANCE_ID: &str = "FRONTEND::tuner_allocation::sample_rate_tolerance";
+/// The tuner_allocation field requesting control of the tuner (boolean).
+pub const DEVICE_CONTROL_ID: &str = "FRONTEND::tuner_allocation::device_control";
+/// The tuner_allocation field holding the tuner group identifier (string).
+pub const GROUP_ID_ID: &str = "FRONTEND::tuner_allocation::group_id";
+/// The tuner_allocation field holding the RF flow identifier (string).
+pub const RF_FLOW_ID_ID: &str = "FRONTEND::tuner_allocation::rf_flow_id";
+
+/// The listener_allocation field holding the allocation listened to (string).
+pub const EXISTING_ALLOCATION_ID_ID: &str = "FRONTEND::listener_allocation::existing_allocation_id";
+/// The listener_allocation field holding the listener allocation identifier (string).
+pub const LISTENER_ALLOCATION_ID_ID: &str = "FRONTEND::listener_allocation::listener_allocation_id";
+
+/**
+ * This type defines the kinds of tuner, as carried by the tuner_type
+ * field of the allocation and status structs.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TunerType {
+    RX,
+    TX,
+    RX_DIGITIZER,
+    RX_DIGITIZER_CHANNELIZER,
+    CHANNELIZER,
+    DDC,
+}
+
+impl TunerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TunerType::RX => "RX",
+            TunerType::TX => "TX",
+            TunerType::RX_DIGITIZER => "RX_DIGITIZER",
+            TunerType::RX_DIGITIZER_CHANNELIZER => "RX_DIGITIZER_CHANNELIZER",
+            TunerType::CHANNELIZER => "CHANNELIZER",
+            TunerType::DDC => "DDC",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<TunerType> {
+        [
+            TunerType::RX,
+            TunerType::TX,
+            TunerType::RX_DIGITIZER,
+            TunerType::RX_DIGITIZER_CHANNELIZER,
+            TunerType::CHANNELIZER,
+            TunerType::DDC,
+        ]
+        .into_iter()
+        .find(|t| t.as_str() == value)
+    }
+}
+
+/// Returns the value of a struct field.
+fn field<'a>(fields: &'a Properties, id: &str) -> Option<&'a AnyValue> {
+    fields.iter().find(|f| f.id == id).map(|f| &f.value)
+}
+
+/// Returns the value of a string struct field.
+fn string_field(fields: &Properties, id: &str) -> Option<String> {
+    match field(fields, id)? {
+        AnyValue::String(value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the value of a numeric struct field.
+fn f64_field(fields: &Properties, id: &str) -> Option<f64> {
+    field(fields, id)?.as_f64()
+}
+
+/**
+ * This type defines the standard tuner allocation struct. A zero
+ * bandwidth or sample rate accepts any value the tuner supports; the
+ * tolerances (percent) bound how far above the request the tuner may be
+ * set. Without device control the allocation listens to an existing
+ * tuner allocation with the same settings.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunerAllocation {
+    pub tuner_type: TunerType,
+    pub allocation_id: String,
+    pub center_frequency: f64,
+    pub bandwidth: f64,
+    pub bandwidth_tolerance: f64,
+    pub sample_rate: f64,
+    pub sample_rate_tolerance: f64,
+    pub device_control: bool,
+    pub group_id: String,
+    pub rf_flow_id: String,
+}
+
+impl TunerAllocation {
+    /// Parses the allocation out of a tuner_allocation struct value.
+    pub fn from_value(value: &AnyValue) -> Option<TunerAllocation> {
+        let AnyValue::Struct(fields) = value else {
+            return None;
+        };
+        Some(TunerAllocation {
+            tuner_type: TunerType::parse(&string_field(fields, TUNER_TYPE_ID)?)?,
+            allocation_id: string_field(fields, ALLOCATION_ID_ID)?,
+            center_frequency: f64_field(fields, CENTER_FREQUENCY_ID)?,
+            bandwidth: f64_field(fields, BANDWIDTH_ID).unwrap_or(0.0),
+            bandwidth_tolerance: f64_field(fields, BANDWIDTH_TOLERANCE_ID).unwrap_or(10.0),
+            sample_rate: f64_field(fields, SAMPLE_RATE_ID).unwrap_or(0.0),
+            sample_rate_tolerance: f64_field(fields, SAMPLE_RATE_TOLERANCE_ID).unwrap_or(10.0),
+            device_control: match field(fields, DEVICE_CONTROL_ID) {
+                Some(AnyValue::Boolean(value)) => *value,
+                _ => true,
+            },
+            group_id: string_field(fields, GROUP_ID_ID).unwrap_or_default(),
+            rf_flow_id: string_field(fields, RF_FLOW_ID_ID).unwrap_or_default(),
+        })
+    }
+
+    /// Returns the allocation as a tuner_allocation struct property.
+    pub fn to_property(&self) -> DataType {
+        DataType::new(
+            TUNER_ALLOCATION_ID,
+            AnyValue::Struct(vec![
+                DataType::new(
+                    TUNER_TYPE_ID,
+                    AnyValue::String(self.tuner_type.as_str().to_string()),
+                ),
+                DataType::new(
+                    ALLOCATION_ID_ID,
+                    AnyValue::String(self.allocation_id.clone()),
+                ),
+                DataType::new(CENTER_FREQUENCY_ID, AnyValue::Double(self.center_frequency)),
+                DataType::new(BANDWIDTH_ID, AnyValue::Double(self.bandwidth)),
+                DataType::new(
+                    BANDWIDTH_TOLERANCE_ID,
+                    AnyValue::Double(self.bandwidth_tolerance),
+                ),
+                DataType::new(SAMPLE_RATE_ID, AnyValue::Double(self.sample_rate)),
+                DataType::new(
+                    SAMPLE_RATE_TOLERANCE_ID,
+                    AnyValue::Double(self.sample_rate_tolerance),
+                ),
+                DataType::new(DEVICE_CONTROL_ID, AnyValue::Boolean(self.device_control)),
+                DataType::new(GROUP_ID_ID, AnyValue::String(self.group_id.clone())),
+                DataType::new(RF_FLOW_ID_ID, AnyValue::String(self.rf_flow_id.clone())),
+            ]),
+        )
+    }
+}
+
+/**
+ * This type defines the standard listener allocation struct, attaching
+ * a new allocation to the output of an existing tuner allocation.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListenerAllocation {
+    pub existing_allocation_id: String,
+    pub listener_allocation_id: String,
+}
+
+impl ListenerAllocation {
+    /// Parses the allocation out of a listener_allocation struct value.
+    pub fn from_value(value: &AnyValue) -> Option<ListenerAllocation> {
+        let AnyValue::Struct(fields) = value else {
+            return None;
+        };
+        Some(ListenerAllocation {
+            existing_allocation_id: string_field(fields, EXISTING_ALLOCATION_ID_ID)?,
+            listener_allocation_id: string_field(fields, LISTENER_ALLOCATION_ID_ID)?,
+        })
+    }
+
+    /// Returns the allocation as a listener_allocation struct property.
+    pub fn to_property(&self) -> DataType {
+        DataType::new(
+            LISTENER_ALLOCATION_ID,
+            AnyValue::Struct(vec![
+                DataType::new(
+                    EXISTING_ALLOCATION_ID_ID,
+                    AnyValue::String(self.existing_allocation_id.clone()),
+                ),
+                DataType::new(
+                    LISTENER_ALLOCATION_ID_ID,
+                    AnyValue::String(self.listener_allocation_id.clone()),
+                ),
+            ]),
+        )
+    }
+}
+
+/**
+ * This type defines the tuning ranges supported by a tuner of the device.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunerCapabilities {
+    pub tuner_type: TunerType,
+    pub min_frequency: f64,
+    pub max_frequency: f64,
+    pub min_bandwidth: f64,
+    pub max_bandwidth: f64,
+    pub min_sample_rate: f64,
+    pub max_sample_rate: f64,
+    pub group_id: String,
+    pub rf_flow_id: String,
+}
+
+/**
+ * This type defines the status reported for each tuner of the device.
+ * The first allocation id is the controlling one, the others are the
+ * listeners.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TunerStatus {
+    pub tuner_type: TunerType,
+    pub allocation_ids: Vec<String>,
+    pub center_frequency: f64,
+    pub bandwidth: f64,
+    pub sample_rate: f64,
+    pub group_id: String,
+    pub rf_flow_id: String,
+    pub enabled: bool,
+}
+
+impl TunerStatus {
+    /// Returns the status as a tuner_status struct value.
+    pub fn to_value(&self) -> AnyValue {
+        let id = |field: &str| format!("{TUNER_STATUS_ID}::{field}");
+        AnyValue::Struct(vec![
+            DataType::new(
+                &id("tuner_type"),
+                AnyValue::String(self.tuner_type.as_str().to_string()),
+            ),
+            DataType::new(
+                &id("allocation_id_csv"),
+                AnyValue::String(self.allocation_ids.join(",")),
+            ),
+            DataType::new(
+                &id("center_frequency"),
+                AnyValue::Double(self.center_frequency),
+            ),
+            DataType::new(&id("bandwidth"), AnyValue::Double(self.bandwidth)),
+            DataType::new(&id("sample_rate"), AnyValue::Double(self.sample_rate)),
+            DataType::new(&id("group_id"), AnyValue::String(self.group_id.clone())),
+            DataType::new(&id("rf_flow_id"), AnyValue::String(self.rf_flow_id.clone())),
+            DataType::new(&id("enabled"), AnyValue::Boolean(self.enabled)),
+        ])
+    }
+}
+
+/**
+ * Book keeping of a tuner of the device.
+ */
+#[derive(Debug, Clone)]
+struct Tuner {
+    capabilities: TunerCapabilities,
+    status: TunerStatus,
+}
+
+impl Tuner {
+    /// Returns the value a tuner setting can be set to for a request, if any.
+    fn setting(requested: f64, tolerance: f64, min: f64, max: f64) -> Option<f64> {
+        let value = requested.max(min);
+        let acceptable = requested == 0.0 || value <= requested * (1.0 + tolerance / 100.0);
+        (acceptable && value <= max).then_some(value)
+    }
+
+    /// Returns whether the tuner is controlled with the settings of the request.
+    fn tuned_as(&self, request: &TunerAllocation) -> bool {
+        let status = &self.status;
+        !status.allocation_ids.is_empty()
+            && status.center_frequency == request.center_frequency
+            && (request.bandwidth == 0.0
+                || Tuner::setting(
+                    request.bandwidth,
+                    request.bandwidth_tolerance,
+                    status.bandwidth,
+                    status.bandwidth,
+                )
+                .is_some())
+            && (request.sample_rate == 0.0
+                || Tuner::setting(
+                    request.sample_rate,
+                    request.sample_rate_tolerance,
+                    status.sample_rate,
+                    status.sample_rate,
+                )
+                .is_some())
+    }
+
+    /// Returns whether the tuner may serve the request, as for its type, group and flow.
+    fn suits(&self, request: &TunerAllocation) -> bool {
+        let c = &self.capabilities;
+        c.tuner_type == request.tuner_type
+            && (request.group_id.is_empty() || c.group_id == request.group_id)
+            && (request.rf_flow_id.is_empty() || c.rf_flow_id == request.rf_flow_id)
+    }
+
+    /// Takes control of the tuner, tuning it for the request.
+    fn control(&mut self, request: &TunerAllocation) -> bool {
+        let c = &self.capabilities;
+        if !self.status.allocation_ids.is_empty()
+            || request.center_frequency < c.min_frequency
+            || request.center_frequency > c.max_frequency
+        {
+            return false;
+        }
+        let settings = (
+            Tuner::setting(
+                request.bandwidth,
+                request.bandwidth_tolerance,
+                c.min_bandwidth,
+                c.max_bandwidth,
+            ),
+            Tuner::setting(
+                request.sample_rate,
+                request.sample_rate_tolerance,
+                c.min_sample_rate,
+                c.max_sample_rate,
+            ),
+        );
+        let (Some(bandwidth), Some(sample_rate)) = settings else {
+            return false;
+        };
+
+        self.status.allocation_ids = vec![request.allocation_id.clone()];
+        self.status.center_frequency = request.center_frequency;
+        self.status.bandwidth = bandwidth;
+        self.status.sample_rate = sample_rate;
+        self.status.enabled = true;
+        true
+    }
+}
+
+/**
+ * Front-end tuner device: a device made of tuners allocated through the
+ * standard tuner_allocation and listener_allocation struct properties.
+ * A controlling allocation tunes a free tuner; listeners share the
+ * output of a tuner without controlling it. The device is BUSY once all
+ * its tuners are controlled. Other allocation properties are handed to
+ * the embedded device.
+ */
+#[derive(Debug)]
+pub struct FrontendTunerDevice {
+    device: Device,
+    tuners: Vec<Tuner>,
+}
+
+impl FrontendTunerDevice {
+    pub fn new(device: Device) -> FrontendTunerDevice {
+        FrontendTunerDevice {
+            device,
+            tuners: Vec::new(),
+        }
+    }
+
+    /// Adds a tuner supporting the given tuning ranges.
+    pub fn with_tuner(mut self, capabilities: TunerCapabilities) -> FrontendTunerDevice {
+        self.tuners.push(Tuner {
+            status: TunerStatus {
+                tuner_type: capabilities.tuner_type,
+                allocation_ids: Vec::new(),
+                center_frequency: 0.0,
+                bandwidth: 0.0,
+                sample_rate: 0.0,
+                group_id: capabilities.group_id.clone(),
+                rf_flow_id: capabilities.rf_flow_id.clone(),
+                enabled: false,
+            },
+            capabilities,
+        });
+        self
+    }
+
+    /// Returns the embedded device state model.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the mutable embedded device state model.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    /// Returns the status of the tuners.
+    pub fn tuner_status(&self) -> Vec<TunerStatus> {
+        self.tuners.iter().map(|t| t.status.clone()).collect()
+    }
+
+    /// Returns the index of the tuner serving an allocation id.
+    pub fn tuner_index(&self, allocation_id: &str) -> Option<usize> {
+        self.tuners
+            .iter()
+            .position(|t| t.status.allocation_ids.iter().any(|id| id == allocation_id))
+    }
+
+    /// Returns whether an allocation id is already in use on the device.
+    fn in_use(tuners: &[Tuner], allocation_id: &str) -> bool {
+        tuners
+            .iter()
+            .any(|t| t.status.allocation_ids.iter().any(|id| id == allocation_id))
+    }
+
+    /**
+     * Allocates the tuner properties on the tuners, returning false when
+     * they cannot be satisfied and the invalid ones as an error.
+     */
+    fn allocate_tuners(tuners: &mut [Tuner], capacities: &Properties) -> device::Result<bool> {
+        for c in capacities {
+            if c.id == TUNER_ALLOCATION_ID {
+                let request = TunerAllocation::from_value(&c.value).ok_or_else(|| invalid(c))?;
+                if FrontendTunerDevice::in_use(tuners, &request.allocation_id) {
+                    return Err(invalid(c));
+                }
+
+                let allocated = if request.device_control {
+                    tuners
+                        .iter_mut()
+                        .filter(|t| t.suits(&request))
+                        .any(|t| t.control(&request))
+                } else {
+                    tuners
+                        .iter_mut()
+                        .find(|t| t.suits(&request) && t.tuned_as(&request))
+                        .map(|t| t.status.allocation_ids.push(request.allocation_id.clone()))
+                        .is_some()
+                };
+                if !allocated {
+                    return Ok(false);
+                }
+            } else {
+                let request = ListenerAllocation::from_value(&c.value).ok_or_else(|| invalid(c))?;
+                if FrontendTunerDevice::in_use(tuners, &request.listener_allocation_id) {
+                    return Err(invalid(c));
+                }
+
+                let Some(tuner) = tuners.iter_mut().find(|t| {
+                    t.status
+                        .allocation_ids
+                        .contains(&request.existing_allocation_id)
+                }) else {
+                    return Ok(false);
+                };
+                tuner
+                    .status
+                    .allocation_ids
+                    .push(request.listener_allocation_id);
+            }
+        }
+        Ok(true)
+    }
+
+    /**
+     * Updates the usageState out of the tuners and the capacities of the
+     * embedded device: BUSY once all tuners are controlled.
+     */
+    fn update_usage_state(&mut self) {
+        let controlled = self
+            .tuners
+            .iter()
+            .filter(|t| !t.status.allocation_ids.is_empty())
+            .count();
+
+        let usage_state = if !self.tuners.is_empty() && controlled == self.tuners.len() {
+            UsageType::BUSY
+        } else if controlled > 0 || !self.device.allocated_capacities().is_empty() {
+            UsageType::ACTIVE
+        } else {
+            UsageType::IDLE
+        };
+        self.device.set_usage_state(usage_state);
+    }
+}
+
+/// Returns the error for an allocation property not understood by the device.
+fn invalid(capacity: &DataType) -> DeviceError {
+    DeviceError::InvalidCapacity {
+        message: "invalid tuner allocation".to_string(),
+        capacities: vec![capacity.clone()],
+    }
+}
+
+/// Returns whether a property is a tuner or listener allocation.
+fn is_tuner_property(capacity: &DataType) -> bool {
+    capacity.id == TUNER_ALLOCATION_ID || capacity.id == LISTENER_ALLOCATION_ID
+}
+
+impl DeviceTrait for FrontendTunerDevice {
+    fn identifier(&self) -> &str {
+        self.device.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.device.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.device.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.device.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.device.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.device.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.device.operational_state()
+    }
+
+    /**
+     * The allocation properties of the device are those of the embedded
+     * device followed by the status of its tuners.
+     */
+    fn allocation_properties(&self) -> Properties {
+        let mut properties = self.device.allocation_properties();
+        properties.push(DataType::new(
+            TUNER_STATUS_ID,
+            AnyValue::Sequence(self.tuners.iter().map(|t| t.status.to_value()).collect()),
+        ));
+        properties
+    }
+
+    /**
+     * The tuner allocations are made on a copy of the tuners, kept only
+     * when the whole request, including the capacities of the embedded
+     * device, has been allocated.
+     */
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.device.check_allocate_state()?;
+        let (tuner_capacities, others): (Properties, Properties) =
+            capacities.iter().cloned().partition(is_tuner_property);
+
+        let mut tuners = self.tuners.clone();
+        if !FrontendTunerDevice::allocate_tuners(&mut tuners, &tuner_capacities)? {
+            return Ok(false);
+        }
+        if !others.is_empty() && !self.device.allocate_capacity(&others)? {
+            return Ok(false);
+        }
+
+        self.tuners = tuners;
+        self.update_usage_state();
+        Ok(true)
+    }
+
+    /**
+     * Deallocating a controlling allocation releases the tuner together
+     * with its listeners.
+     */
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.device.check_deallocate_state()?;
+        let (tuner_capacities, others): (Properties, Properties) =
+            capacities.iter().cloned().partition(is_tuner_property);
+
+        let mut released = Vec::new();
+        for c in &tuner_capacities {
+            let allocation_id = if c.id == TUNER_ALLOCATION_ID {
+                TunerAllocation::from_value(&c.value).map(|a| a.allocation_id)
+            } else {
+                ListenerAllocation::from_value(&c.value).map(|a| a.listener_allocation_id)
+            };
+            let allocation_id = allocation_id.ok_or_else(|| invalid(c))?;
+            let index = self.tuner_index(&allocation_id).ok_or_else(|| invalid(c))?;
+            released.push((index, allocation_id));
+        }
+        if !others.is_empty() {
+            self.device.deallocate_capacity(&others)?;
+        }
+
+        for (index, allocation_id) in released {
+            let status = &mut self.tuners[index].status;
+            if status.allocation_ids.first() == Some(&allocation_id) {
+                status.allocation_ids.clear();
+                status.enabled = false;
+            } else {
+                status.allocation_ids.retain(|id| *id != allocation_id);
+            }
+        }
+        self.update_usage_state();
+        Ok(())
+    }
+}
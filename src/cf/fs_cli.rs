@@ -0,0 +1,331 @@
+use std::path::Path;
+
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+use tonic::{Request, Status};
+
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::file_system_bench::{self, BenchConfig};
+use scars::cf::file_transfer::{self, TransferTuning};
+use scars::cf::rpc::file_system::file_system_client::FileSystemClient;
+use scars::cf::rpc::file_system::{
+    CopyRequest, FileType, ListRequest, MetricsRequest, MkdirRequest, MoveRequest, QueryRequest,
+    RemoveRequest,
+};
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-fs",
+    options: &[
+        CliOption::value("--ca"),
+        CliOption::value("--domain-name"),
+        CliOption::value("--cert"),
+        CliOption::value("--key"),
+        CliOption::value("--token"),
+        CliOption::value("--min-chunk-size"),
+        CliOption::value("--max-chunk-size"),
+        CliOption::value("--max-in-flight"),
+        CliOption::value("--max-retransmissions"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &[
+        "ls", "cat", "get", "put", "sync", "mget", "mput", "rm", "mkdir", "cp", "mv", "df",
+        "metrics", "bench",
+    ],
+};
+
+/**
+ * File system command line interface: operates on the files of the
+ * FileSystem service of a DeviceManager, or of a DomainManager for the
+ * files of the domain FileManager. As JSON, the files listed and the
+ * spaces of the file systems are printed as arrays of objects.
+ *
+ * The bench command measures the throughput and the latencies of the
+ * unary and streaming transfers of files written in a directory of the
+ * service, for each chunk size and parallelism, e.g. to tune the chunk
+ * size of deployments over constrained links.
+ *
+ * The files are read and written in chunks adapting to the throughput
+ * and the round trip time of the link, within the chunk sizes and the
+ * in-flight window of the options. The metrics command prints the
+ * metrics of the pool of the buffers of the service.
+ *
+ * usage: scars-fs [options] <endpoint> <command> [arguments]
+ *        scars-fs --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    let mut tls: Option<ClientTlsConfig> = None;
+    let (mut cert, mut key, mut token) = (None, None, None);
+    let mut tuning = TransferTuning::default();
+    while args.first().is_some_and(|a| a.starts_with("--")) {
+        let option = args.remove(0);
+        if args.is_empty() {
+            return Err(usage());
+        }
+        let value = args.remove(0);
+        match option.as_str() {
+            "--ca" => {
+                let ca = Certificate::from_pem(std::fs::read(value)?);
+                tls = Some(tls.unwrap_or_default().ca_certificate(ca));
+            }
+            "--domain-name" => tls = Some(tls.unwrap_or_default().domain_name(value)),
+            "--cert" => cert = Some(std::fs::read(value)?),
+            "--key" => key = Some(std::fs::read(value)?),
+            "--token" => token = Some(format!("Bearer {value}").parse::<MetadataValue<Ascii>>()?),
+            "--min-chunk-size" => tuning.min_chunk_size = value.parse()?,
+            "--max-chunk-size" => tuning.max_chunk_size = value.parse()?,
+            "--max-in-flight" => tuning.max_in_flight = value.parse()?,
+            "--max-retransmissions" => tuning.max_retransmissions = value.parse()?,
+            _ => return Err(usage()),
+        }
+    }
+    match (cert, key) {
+        (Some(cert), Some(key)) => {
+            tls = Some(
+                tls.unwrap_or_default()
+                    .identity(Identity::from_pem(cert, key)),
+            )
+        }
+        (None, None) => {}
+        _ => return Err("--cert and --key go together".into()),
+    }
+    // The bounds given are validated by the transfers
+    tuning.initial_chunk_size = tuning
+        .initial_chunk_size
+        .min(tuning.max_chunk_size)
+        .max(tuning.min_chunk_size);
+    let (endpoint, command) = match args.as_slice() {
+        [endpoint, command @ ..] if !command.is_empty() => (endpoint.clone(), command),
+        _ => return Err(usage()),
+    };
+
+    let mut endpoint = Endpoint::from_shared(endpoint)?;
+    if let Some(tls) = tls {
+        endpoint = endpoint.tls_config(tls)?;
+    }
+    let channel = endpoint.connect().await?;
+    let mut fs = FileSystemClient::with_interceptor(channel, Authorization(token));
+
+    let arguments: Vec<&str> = command.iter().map(String::as_str).collect();
+    match arguments.as_slice() {
+        ["ls", pattern @ ..] if pattern.len() <= 1 => {
+            let pattern = match pattern.first().copied().unwrap_or("/") {
+                p if p.contains(['*', '?']) => p.to_string(),
+                p => format!("{}/*", p.trim_end_matches('/')),
+            };
+            let mut files = fs.list(ListRequest { pattern }).await?.into_inner().files;
+            files.sort_by(|a, b| a.name.cmp(&b.name));
+            if format == OutputFormat::Json {
+                cli::print_json(&files)?;
+                return Ok(());
+            }
+            for file in files {
+                let kind = match file.kind() {
+                    FileType::Plain => "-",
+                    FileType::Directory => "d",
+                    FileType::FileSystem => "m",
+                };
+                println!("{kind} {:>12} {}", file.size, file.name);
+            }
+        }
+        ["cat", file_name] => {
+            let (data, _) = file_transfer::read_file(&fs, file_name, &tuning).await?;
+            std::io::Write::write_all(&mut std::io::stdout(), &data)?;
+        }
+        ["get", file_name, local @ ..] if local.len() <= 1 => {
+            let local = local.first().copied().unwrap_or(base_name(file_name));
+            let (data, _) = file_transfer::read_file(&fs, file_name, &tuning).await?;
+            std::fs::write(local, data)?;
+        }
+        ["put", local, file_name] => {
+            let data = std::fs::read(Path::new(local))?;
+            file_transfer::write_file(&fs, file_name, data, &tuning).await?;
+        }
+        ["sync", local, file_name] => {
+            let data = std::fs::read(Path::new(local))?;
+            let stats = file_transfer::sync_file(&fs, file_name, data, &tuning).await?;
+            if format == OutputFormat::Json {
+                cli::print_json(&serde_json::json!({
+                    "size": stats.size,
+                    "transferred": stats.transferred,
+                }))?;
+            } else {
+                println!("{} of {} bytes sent", stats.transferred, stats.size);
+            }
+        }
+        ["mget", directory, file_names @ ..] if !file_names.is_empty() => {
+            let (contents, _) = file_transfer::get_many(&fs, file_names, &tuning).await?;
+            for (file_name, data) in file_names.iter().zip(contents) {
+                std::fs::write(Path::new(directory).join(base_name(file_name)), data)?;
+            }
+        }
+        ["mput", directory, locals @ ..] if !locals.is_empty() => {
+            let mut files = Vec::new();
+            for local in locals {
+                let file_name = format!("{}/{}", directory.trim_end_matches('/'), base_name(local));
+                files.push((file_name, std::fs::read(Path::new(local))?));
+            }
+            let files: Vec<(&str, &[u8])> = files
+                .iter()
+                .map(|(file_name, data)| (file_name.as_str(), data.as_slice()))
+                .collect();
+            file_transfer::put_many(&fs, &files, &tuning).await?;
+        }
+        ["rm", file_name] => {
+            fs.remove(RemoveRequest {
+                file_name: file_name.to_string(),
+            })
+            .await?;
+        }
+        ["mkdir", directory_name] => {
+            fs.mkdir(MkdirRequest {
+                directory_name: directory_name.to_string(),
+            })
+            .await?;
+        }
+        ["cp", source, destination] => {
+            fs.copy(CopyRequest {
+                source_file_name: source.to_string(),
+                destination_file_name: destination.to_string(),
+            })
+            .await?;
+        }
+        ["mv", source, destination] => {
+            fs.r#move(MoveRequest {
+                source_file_name: source.to_string(),
+                destination_file_name: destination.to_string(),
+            })
+            .await?;
+        }
+        ["df"] => {
+            let spaces = fs.query(QueryRequest {}).await?.into_inner().spaces;
+            if format == OutputFormat::Json {
+                cli::print_json(&spaces)?;
+                return Ok(());
+            }
+            println!(
+                "{:<32} {:>16} {:>16} {:>6}",
+                "MOUNT POINT", "SIZE", "AVAILABLE", "USE %"
+            );
+            for space in spaces {
+                let used = space.size.saturating_sub(space.available_space);
+                let percent = match space.size {
+                    0 => 0.0,
+                    size => 100.0 * used as f64 / size as f64,
+                };
+                println!(
+                    "{:<32} {:>16} {:>16} {:>6.1}",
+                    space.mount_point, space.size, space.available_space, percent
+                );
+            }
+        }
+        ["metrics"] => {
+            let metrics = fs.metrics(MetricsRequest {}).await?.into_inner();
+            if format == OutputFormat::Json {
+                cli::print_json(&metrics)?;
+                return Ok(());
+            }
+            let pool = metrics.buffer_pool.unwrap_or_default();
+            println!(
+                "buffer pool: acquired {} reused {} discarded {} in use {} (max {}) pooled {} ({} bytes)",
+                pool.acquired,
+                pool.reused,
+                pool.discarded,
+                pool.in_use,
+                pool.max_in_use,
+                pool.pooled,
+                pool.pooled_bytes
+            );
+            let threads = metrics.blocking_pool.unwrap_or_default();
+            println!(
+                "blocking pool: threads {} active {} queued {} (max {}) completed {}",
+                threads.threads,
+                threads.active,
+                threads.queue_depth,
+                threads.max_queue_depth,
+                threads.completed
+            );
+        }
+        ["bench", options @ ..] => {
+            let config = bench_config(options)?;
+            let results = file_system_bench::bench(fs, &config).await?;
+            if format == OutputFormat::Json {
+                cli::print_json(&results)?;
+                return Ok(());
+            }
+            print!("{}", file_system_bench::to_table(&results));
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+/// Returns the last component of a file name.
+fn base_name(file_name: &str) -> &str {
+    file_name.rsplit('/').next().unwrap_or(file_name)
+}
+
+/// Parses the options of the bench command, the defaults otherwise.
+fn bench_config(mut options: &[&str]) -> Result<BenchConfig, Box<dyn std::error::Error>> {
+    let mut config = BenchConfig::default();
+    let list = |value: &str| -> Result<Vec<usize>, std::num::ParseIntError> {
+        value.split(',').map(str::parse).collect()
+    };
+    loop {
+        match options {
+            ["--size", value, rest @ ..] => (config.file_size, options) = (value.parse()?, rest),
+            ["--chunk-sizes", value, rest @ ..] => {
+                (config.chunk_sizes, options) = (list(value)?, rest)
+            }
+            ["--parallelism", value, rest @ ..] => {
+                (config.parallelism, options) = (list(value)?, rest)
+            }
+            ["--iterations", value, rest @ ..] => {
+                (config.iterations, options) = (value.parse()?, rest)
+            }
+            [directory] if !directory.starts_with("--") => {
+                config.directory = directory.to_string();
+                return Ok(config);
+            }
+            [] => return Ok(config),
+            _ => return Err(usage()),
+        }
+    }
+}
+
+/**
+ * Interceptor adding the bearer token, when given, to the requests.
+ */
+#[derive(Clone)]
+struct Authorization(Option<MetadataValue<Ascii>>);
+
+impl Interceptor for Authorization {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.0 {
+            request
+                .metadata_mut()
+                .insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
+}
+
+fn usage() -> Box<dyn std::error::Error> {
+    "usage: scars-fs [--ca <pem>] [--domain-name <name>] [--cert <pem> --key <pem>] [--token <token>] \
+     [--min-chunk-size <bytes>] [--max-chunk-size <bytes>] [--max-in-flight <chunks>] [--max-retransmissions <n>] \
+     [--format text|json] <endpoint> ls [<directory or pattern>] | cat <file> | get <file> [<local file>] | put <local file> <file> \
+     | sync <local file> <file> | mget <local directory> <file>... | mput <directory> <local file>... \
+     | rm <file> | mkdir <directory> | cp <file> <file> | mv <file> <file> | df | metrics \
+     | bench [--size <bytes>] [--chunk-sizes <bytes>,...] [--parallelism <tasks>,...] [--iterations <n>] [<directory>]"
+        .into()
+}
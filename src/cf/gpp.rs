@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use sysinfo::{Networks, System};
+
+use super::common_types::{AnyValue, Properties};
+use super::device::{self, AdminType, Device, DeviceTrait, OperationalType, UsageType};
+use super::executable_device::{self, ExecutableDevice, ExecutableDeviceTrait, ProcessId, ProcessMetrics};
+use super::loadable_device::{self, LoadType, LoadableDevice, LoadableDeviceTrait};
+
+/// The allocation property holding the processor architecture (e.g. x86_64).
+pub const PROCESSOR_NAME_ID: &str = "processor_name";
+/// The allocation property holding the operating system name.
+pub const OS_NAME_ID: &str = "os_name";
+/// The allocation property holding the operating system version.
+pub const OS_VERSION_ID: &str = "os_version";
+/// The allocation property holding the network interface names.
+pub const NIC_NAMES_ID: &str = "nic_names";
+/// The capacity holding the available processor cores (ULong).
+pub const PROCESSOR_CORES_ID: &str = "processor_cores";
+/// The capacity holding the available memory in MiB (ULongLong).
+pub const MEMORY_CAPACITY_ID: &str = "memory_capacity";
+/// The capacity holding the available network throughput in Mb/s (Double).
+pub const NIC_CAPACITY_ID: &str = "nic_capacity";
+
+/// The throughput assumed for interfaces not reporting their link speed.
+const DEFAULT_NIC_SPEED: f64 = 1000.0;
+
+/**
+ * General purpose processor device: an executable device running
+ * component binaries on the host, advertising the host processor cores,
+ * memory and network throughput as allocation capacities.
+ */
+#[derive(Debug)]
+pub struct Gpp {
+    executable: ExecutableDevice,
+}
+
+impl Gpp {
+    /**
+     * Creates a GPP advertising the capacities measured on the host:
+     * logical cores, total memory and the summed link speed of the
+     * network interfaces.
+     */
+    pub fn new(device: Device, cache_dir: &Path) -> Gpp {
+        let mut sys = System::new();
+        sys.refresh_cpu();
+        sys.refresh_memory();
+
+        let networks = Networks::new_with_refreshed_list();
+        let nic_names: Vec<String> = networks
+            .keys()
+            .filter(|name| *name != "lo")
+            .cloned()
+            .collect();
+        let nic = nic_names.iter().map(|name| nic_speed(name)).sum();
+        let nic_names = nic_names.into_iter().map(AnyValue::String).collect();
+
+        Gpp::with_capacities(
+            device.with_allocation_property(NIC_NAMES_ID, AnyValue::Sequence(nic_names)),
+            cache_dir,
+            sys.cpus().len() as u32,
+            sys.total_memory() / (1024 * 1024),
+            nic,
+        )
+    }
+
+    /// Creates a GPP advertising the given cores, memory (MiB) and network (Mb/s) capacities.
+    pub fn with_capacities(
+        device: Device,
+        cache_dir: &Path,
+        cores: u32,
+        memory: u64,
+        nic: f64,
+    ) -> Gpp {
+        let processor_name = System::cpu_arch().unwrap_or(std::env::consts::ARCH.to_string());
+        let os_name = System::name().unwrap_or(std::env::consts::OS.to_string());
+        let os_version = System::os_version().unwrap_or_default();
+
+        let device = device
+            .with_allocation_property(PROCESSOR_NAME_ID, AnyValue::String(processor_name))
+            .with_allocation_property(OS_NAME_ID, AnyValue::String(os_name))
+            .with_allocation_property(OS_VERSION_ID, AnyValue::String(os_version))
+            .with_capacity(PROCESSOR_CORES_ID, AnyValue::ULong(cores))
+            .with_capacity(MEMORY_CAPACITY_ID, AnyValue::ULongLong(memory))
+            .with_capacity(NIC_CAPACITY_ID, AnyValue::Double(nic));
+
+        Gpp {
+            executable: ExecutableDevice::new(LoadableDevice::new(device, cache_dir)),
+        }
+    }
+
+    /**
+     * Keeps the outstanding allocations and loaded files of the GPP
+     * across restarts, restoring those left by a previous run.
+     */
+    pub fn with_persistence(mut self) -> loadable_device::Result<Gpp> {
+        self.executable.loadable_mut().enable_persistence()?;
+        Ok(self)
+    }
+
+    /// Returns the embedded executable device.
+    pub fn executable(&self) -> &ExecutableDevice {
+        &self.executable
+    }
+
+    /// Returns the mutable embedded executable device.
+    pub fn executable_mut(&mut self) -> &mut ExecutableDevice {
+        &mut self.executable
+    }
+}
+
+/// Returns the link speed in Mb/s of a network interface.
+fn nic_speed(name: &str) -> f64 {
+    std::fs::read_to_string(format!("/sys/class/net/{name}/speed"))
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|s| *s > 0.0)
+        .unwrap_or(DEFAULT_NIC_SPEED)
+}
+
+impl DeviceTrait for Gpp {
+    fn identifier(&self) -> &str {
+        self.executable.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.executable.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.executable.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.executable.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.executable.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.executable.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.executable.operational_state()
+    }
+
+    /**
+     * Returns the allocation properties of the GPP: the host description
+     * followed by the capacities still available for allocation.
+     */
+    fn allocation_properties(&self) -> Properties {
+        self.executable.allocation_properties()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.executable.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.executable.deallocate_capacity(capacities)
+    }
+}
+
+impl LoadableDeviceTrait for Gpp {
+    fn load(&mut self, fs: &Path, file_name: &str, load_kind: LoadType) -> loadable_device::Result<()> {
+        self.executable.load(fs, file_name, load_kind)
+    }
+
+    fn unload(&mut self, file_name: &str) -> loadable_device::Result<()> {
+        self.executable.unload(file_name)
+    }
+}
+
+impl ExecutableDeviceTrait for Gpp {
+    fn execute(
+        &mut self,
+        name: &str,
+        options: &Properties,
+        parameters: &Properties,
+    ) -> executable_device::Result<ProcessId> {
+        self.executable.execute(name, options, parameters)
+    }
+
+    fn terminate(&mut self, process_id: ProcessId) -> executable_device::Result<()> {
+        self.executable.terminate(process_id)
+    }
+
+    fn process_metrics(&mut self, process_id: ProcessId) -> executable_device::Result<ProcessMetrics> {
+        self.executable.process_metrics(process_id)
+    }
+}
@@ -0,0 +1,1867 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::common_types::{AnyValue, DataType, Properties, PropertyValue};
+use super::corba_bridge::{
+    CorbaError, CorbaObjectRef, CorbaObjectTrait, OrbTrait, Result, ServantRef, DEVICE_REPID,
+    FILE_EXCEPTION_REPID, INITIALIZE_ERROR_REPID, INVALID_CAPACITY_REPID,
+    INVALID_CONFIGURATION_REPID, INVALID_FILE_POINTER_REPID, INVALID_PORT_REPID,
+    INVALID_STATE_REPID, IO_EXCEPTION_REPID, OCCUPIED_PORT_REPID, PARTIAL_CONFIGURATION_REPID,
+    RELEASE_ERROR_REPID, RESOURCE_REPID, START_ERROR_REPID, STOP_ERROR_REPID, UNKNOWN_PORT_REPID,
+    UNKNOWN_PROPERTIES_REPID,
+};
+
+/// The time a call waits to connect to the server of an object, then for its reply, by default.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The port of the corbaloc URLs giving none.
+pub const DEFAULT_CORBALOC_PORT: u16 = 2809;
+
+/// The largest GIOP message read, its fragments included.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// The deepest the type codes and the values of the anys are nested.
+const MAX_DEPTH: usize = 32;
+
+/// The LOCATION_FORWARD replies a call follows at most.
+const MAX_FORWARDS: usize = 8;
+
+const GIOP_HEADER_SIZE: usize = 12;
+
+//the GIOP message types
+const REQUEST: u8 = 0;
+const REPLY: u8 = 1;
+const LOCATE_REQUEST: u8 = 3;
+const LOCATE_REPLY: u8 = 4;
+const CLOSE_CONNECTION: u8 = 5;
+const MESSAGE_ERROR: u8 = 6;
+const FRAGMENT: u8 = 7;
+
+//the reply statuses
+const NO_EXCEPTION: u32 = 0;
+const USER_EXCEPTION: u32 = 1;
+const SYSTEM_EXCEPTION: u32 = 2;
+const LOCATION_FORWARD: u32 = 3;
+const LOCATION_FORWARD_PERM: u32 = 4;
+
+//the locate statuses
+const UNKNOWN_OBJECT: u32 = 0;
+const OBJECT_HERE: u32 = 1;
+
+//the completion statuses of the system exceptions
+const COMPLETED_NO: u32 = 1;
+const COMPLETED_MAYBE: u32 = 2;
+const COMPLETIONS: [&str; 3] = ["COMPLETED_YES", "COMPLETED_NO", "COMPLETED_MAYBE"];
+
+const TAG_INTERNET_IOP: u32 = 0;
+const TAG_CODE_SETS: u32 = 1;
+const CODE_SETS_CONTEXT: u32 = 1;
+const UTF_8: u32 = 0x0501_0001;
+const UTF_16: u32 = 0x0001_0109;
+
+const OBJECT_REPID: &str = "IDL:omg.org/CORBA/Object:1.0";
+const PROPERTIES_REPID: &str = "IDL:CF/Properties:1.0";
+const DATA_TYPE_REPID: &str = "IDL:CF/DataType:1.0";
+
+/// The interfaces the CF Resource interface inherits.
+const RESOURCE_BASES: [&str; 4] = [
+    "IDL:CF/LifeCycle:1.0",
+    "IDL:CF/TestableObject:1.0",
+    "IDL:CF/PropertySet:1.0",
+    "IDL:CF/PortSupplier:1.0",
+];
+
+//the kinds of the type codes
+const TK_NULL: u32 = 0;
+const TK_VOID: u32 = 1;
+const TK_SHORT: u32 = 2;
+const TK_LONG: u32 = 3;
+const TK_USHORT: u32 = 4;
+const TK_ULONG: u32 = 5;
+const TK_FLOAT: u32 = 6;
+const TK_DOUBLE: u32 = 7;
+const TK_BOOLEAN: u32 = 8;
+const TK_CHAR: u32 = 9;
+const TK_OCTET: u32 = 10;
+const TK_ANY: u32 = 11;
+const TK_OBJREF: u32 = 14;
+const TK_STRUCT: u32 = 15;
+const TK_ENUM: u32 = 17;
+const TK_STRING: u32 = 18;
+const TK_SEQUENCE: u32 = 19;
+const TK_ARRAY: u32 = 20;
+const TK_ALIAS: u32 = 21;
+const TK_EXCEPT: u32 = 22;
+const TK_LONGLONG: u32 = 23;
+const TK_ULONGLONG: u32 = 24;
+const TK_INDIRECTION: u32 = 0xffff_ffff;
+
+fn marshal(message: &str) -> CorbaError {
+    CorbaError::system_exception("MARSHAL", message)
+}
+
+fn transient(message: &str) -> CorbaError {
+    CorbaError::system_exception("TRANSIENT", message)
+}
+
+fn inv_objref(reference: &str) -> CorbaError {
+    CorbaError::system_exception("INV_OBJREF", reference)
+}
+
+/**
+ * Encoder of a CDR stream, in big-endian order, the values being
+ * aligned from the start of the stream. The chars are encoded in UTF-8
+ * once negotiated, in ISO 8859-1 otherwise.
+ */
+struct CdrWriter {
+    data: Vec<u8>,
+    utf8: bool,
+}
+
+impl CdrWriter {
+    fn new(utf8: bool) -> CdrWriter {
+        CdrWriter {
+            data: Vec::new(),
+            utf8,
+        }
+    }
+
+    fn align(&mut self, alignment: usize) {
+        while !self.data.len().is_multiple_of(alignment) {
+            self.data.push(0);
+        }
+    }
+
+    fn octet(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn boolean(&mut self, value: bool) {
+        self.data.push(value as u8);
+    }
+
+    fn aligned(&mut self, bytes: &[u8]) {
+        self.align(bytes.len());
+        self.data.extend_from_slice(bytes);
+    }
+
+    fn ushort(&mut self, value: u16) {
+        self.aligned(&value.to_be_bytes());
+    }
+
+    fn ulong(&mut self, value: u32) {
+        self.aligned(&value.to_be_bytes());
+    }
+
+    fn octets(&mut self, value: &[u8]) {
+        self.ulong(value.len() as u32);
+        self.data.extend_from_slice(value);
+    }
+
+    /// The chars not in ISO 8859-1 are written as '?' until UTF-8 is negotiated.
+    fn string(&mut self, value: &str) {
+        let bytes: Vec<u8> = match self.utf8 {
+            true => value.as_bytes().to_vec(),
+            false => value
+                .chars()
+                .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                .collect(),
+        };
+        self.ulong(bytes.len() as u32 + 1);
+        self.data.extend_from_slice(&bytes);
+        self.data.push(0);
+    }
+
+    /// Writes an encapsulation, its values aligned from its own start.
+    fn encapsulation<T>(&mut self, encode: impl FnOnce(&mut CdrWriter) -> T) -> T {
+        let mut encapsulated = CdrWriter::new(self.utf8);
+        encapsulated.octet(0);
+        let result = encode(&mut encapsulated);
+        self.octets(&encapsulated.data);
+        result
+    }
+}
+
+/**
+ * Decoder of a CDR stream, in the byte order of the stream or of the
+ * encapsulation read, the encapsulations being read in place so that
+ * the indirections of their type codes reach the enclosing ones.
+ */
+struct CdrReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// The offset the values are aligned from, the start of the stream or of the encapsulation.
+    base: usize,
+    end: usize,
+    little_endian: bool,
+    utf8: bool,
+    depth: usize,
+    /// The type codes read, by offset, for the indirections.
+    type_codes: HashMap<usize, TypeCode>,
+}
+
+impl<'a> CdrReader<'a> {
+    fn new(data: &'a [u8], little_endian: bool, utf8: bool) -> CdrReader<'a> {
+        CdrReader {
+            data,
+            position: 0,
+            base: 0,
+            end: data.len(),
+            little_endian,
+            utf8,
+            depth: 0,
+            type_codes: HashMap::new(),
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if length > self.remaining() {
+            return Err(marshal("message truncated"));
+        }
+        self.position += length;
+        Ok(&self.data[self.position - length..self.position])
+    }
+
+    fn align(&mut self, alignment: usize) -> Result<()> {
+        let misalignment = (self.position - self.base) % alignment;
+        if misalignment != 0 {
+            self.take(alignment - misalignment)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the big-endian bytes of an aligned value.
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.align(N)?;
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.take(N)?);
+        if self.little_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+
+    fn octet(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn boolean(&mut self) -> Result<bool> {
+        Ok(self.octet()? != 0)
+    }
+
+    fn short(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.bytes()?))
+    }
+
+    fn ushort(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes()?))
+    }
+
+    fn long(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.bytes()?))
+    }
+
+    fn ulong(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes()?))
+    }
+
+    /// Returns the length of a sequence, at most the octets left.
+    fn count(&mut self) -> Result<usize> {
+        let count = self.ulong()? as usize;
+        if count > self.remaining() {
+            return Err(marshal("sequence longer than the message"));
+        }
+        Ok(count)
+    }
+
+    fn octets(&mut self) -> Result<Vec<u8>> {
+        let count = self.count()?;
+        Ok(self.take(count)?.to_vec())
+    }
+
+    /// The strings not in UTF-8 are read as ISO 8859-1.
+    fn string(&mut self) -> Result<String> {
+        let count = self.count()?;
+        let bytes = self.take(count)?;
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        match (self.utf8, std::str::from_utf8(bytes)) {
+            (true, Ok(string)) => Ok(string.to_string()),
+            _ => Ok(bytes.iter().map(|&b| b as char).collect()),
+        }
+    }
+
+    /// Reads an encapsulation in place, in its own byte order.
+    fn encapsulation<T>(&mut self, decode: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let length = self.count()?;
+        let start = self.position;
+        let enclosing = (self.base, self.end, self.little_endian);
+        (self.base, self.end) = (start, start + length);
+        let result = self.octet().and_then(|order| {
+            self.little_endian = order & 1 == 1;
+            decode(self)
+        });
+        (self.base, self.end, self.little_endian) = enclosing;
+        self.position = start + length;
+        result
+    }
+
+    fn nested<T>(&mut self, decode: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= MAX_DEPTH {
+            return Err(marshal("values nested too deep"));
+        }
+        self.depth += 1;
+        let result = decode(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn type_code(&mut self) -> Result<TypeCode> {
+        self.nested(|r| {
+            r.align(4)?;
+            let start = r.position;
+            let type_code = match r.ulong()? {
+                TK_NULL => TypeCode::Null,
+                TK_VOID => TypeCode::Void,
+                TK_SHORT => TypeCode::Short,
+                TK_LONG => TypeCode::Long,
+                TK_USHORT => TypeCode::UShort,
+                TK_ULONG => TypeCode::ULong,
+                TK_FLOAT => TypeCode::Float,
+                TK_DOUBLE => TypeCode::Double,
+                TK_BOOLEAN => TypeCode::Boolean,
+                TK_CHAR => TypeCode::Char,
+                TK_OCTET => TypeCode::Octet,
+                TK_ANY => TypeCode::Any,
+                TK_LONGLONG => TypeCode::LongLong,
+                TK_ULONGLONG => TypeCode::ULongLong,
+                TK_STRING => {
+                    r.ulong()?;
+                    TypeCode::String
+                }
+                TK_OBJREF => r.encapsulation(|r| {
+                    r.string()?;
+                    r.string()?;
+                    Ok(TypeCode::ObjRef)
+                })?,
+                TK_STRUCT | TK_EXCEPT => r.encapsulation(|r| {
+                    let id = r.string()?;
+                    r.string()?;
+                    let mut members = Vec::new();
+                    for _ in 0..r.count()? {
+                        let name = r.string()?;
+                        members.push((name, r.type_code()?));
+                    }
+                    Ok(TypeCode::Struct { id, members })
+                })?,
+                TK_ENUM => r.encapsulation(|r| {
+                    r.string()?;
+                    r.string()?;
+                    for _ in 0..r.count()? {
+                        r.string()?;
+                    }
+                    Ok(TypeCode::Enum)
+                })?,
+                TK_SEQUENCE => r.encapsulation(|r| {
+                    let element = r.type_code()?;
+                    r.ulong()?;
+                    Ok(TypeCode::Sequence(Box::new(element)))
+                })?,
+                TK_ARRAY => r.encapsulation(|r| {
+                    let element = r.type_code()?;
+                    let length = r.ulong()? as usize;
+                    Ok(TypeCode::Array(Box::new(element), length))
+                })?,
+                TK_ALIAS => r.encapsulation(|r| {
+                    let id = r.string()?;
+                    r.string()?;
+                    let content = r.type_code()?;
+                    Ok(TypeCode::Alias {
+                        id,
+                        content: Box::new(content),
+                    })
+                })?,
+                //the offset is relative to its own position
+                TK_INDIRECTION => {
+                    let offset = r.position as i64;
+                    let target = offset + r.long()? as i64;
+                    return usize::try_from(target)
+                        .ok()
+                        .and_then(|target| r.type_codes.get(&target).cloned())
+                        .ok_or_else(|| marshal("type code indirection out of the message"));
+                }
+                kind => return Err(marshal(&format!("type code kind {kind} not supported"))),
+            };
+            r.type_codes.insert(start, type_code.clone());
+            Ok(type_code)
+        })
+    }
+
+    fn value(&mut self, type_code: &TypeCode) -> Result<AnyValue> {
+        self.nested(|r| {
+            Ok(match type_code {
+                TypeCode::Null | TypeCode::Void => AnyValue::Sequence(Vec::new()),
+                TypeCode::Short => AnyValue::Short(r.short()?),
+                TypeCode::Long => AnyValue::Long(r.long()?),
+                TypeCode::UShort => AnyValue::UShort(r.ushort()?),
+                TypeCode::ULong | TypeCode::Enum => AnyValue::ULong(r.ulong()?),
+                TypeCode::Float => AnyValue::Float(f32::from_be_bytes(r.bytes()?)),
+                TypeCode::Double => AnyValue::Double(f64::from_be_bytes(r.bytes()?)),
+                TypeCode::Boolean => AnyValue::Boolean(r.boolean()?),
+                TypeCode::Char => AnyValue::String((r.octet()? as char).to_string()),
+                TypeCode::Octet => AnyValue::Octet(r.octet()?),
+                TypeCode::Any => r.any()?,
+                TypeCode::LongLong => AnyValue::LongLong(i64::from_be_bytes(r.bytes()?)),
+                TypeCode::ULongLong => AnyValue::ULongLong(u64::from_be_bytes(r.bytes()?)),
+                TypeCode::String => AnyValue::String(r.string()?),
+                TypeCode::ObjRef => AnyValue::String(Ior::read(r)?.to_string()),
+                TypeCode::Struct { members, .. } => {
+                    let mut properties = Properties::new();
+                    for (name, member) in members {
+                        properties.push(DataType::new(name, r.value(member)?));
+                    }
+                    AnyValue::Struct(properties)
+                }
+                TypeCode::Sequence(element) if element.is_data_type() => {
+                    AnyValue::Struct(r.properties()?)
+                }
+                TypeCode::Sequence(element) => {
+                    let count = r.count()?;
+                    r.values(element, count)?
+                }
+                TypeCode::Array(element, length) => {
+                    if *length > r.remaining() {
+                        return Err(marshal("array longer than the message"));
+                    }
+                    r.values(element, *length)?
+                }
+                TypeCode::Alias { content, .. } => r.value(content)?,
+            })
+        })
+    }
+
+    fn values(&mut self, element: &TypeCode, count: usize) -> Result<AnyValue> {
+        if matches!(element.unaliased(), TypeCode::Null | TypeCode::Void) {
+            return Err(marshal("sequence of nil values"));
+        }
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            values.push(self.value(element)?);
+        }
+        Ok(AnyValue::Sequence(values))
+    }
+
+    fn any(&mut self) -> Result<AnyValue> {
+        let type_code = self.type_code()?;
+        self.value(&type_code)
+    }
+
+    /// Reads CF::Properties, a sequence of CF::DataType.
+    fn properties(&mut self) -> Result<Properties> {
+        let mut properties = Properties::new();
+        for _ in 0..self.count()? {
+            let id = self.string()?;
+            properties.push(DataType::new(&id, self.any()?));
+        }
+        Ok(properties)
+    }
+
+    /// Reads a service context list, returning whether UTF-8 was negotiated for the chars.
+    fn service_contexts(&mut self) -> Result<Option<bool>> {
+        let mut utf8 = None;
+        for _ in 0..self.count()? {
+            let id = self.ulong()?;
+            if id != CODE_SETS_CONTEXT {
+                self.octets()?;
+                continue;
+            }
+            let char_data = self.encapsulation(|r| r.ulong())?;
+            utf8 = Some(char_data == UTF_8);
+        }
+        Ok(utf8)
+    }
+}
+
+/**
+ * The type code of an any, kept as far as the values are decoded: the
+ * enums as their ordinals, the object references as their stringified
+ * IORs, the structs and the CF::Properties as properties.
+ */
+#[derive(Debug, Clone)]
+enum TypeCode {
+    Null,
+    Void,
+    Short,
+    Long,
+    UShort,
+    ULong,
+    Float,
+    Double,
+    Boolean,
+    Char,
+    Octet,
+    Any,
+    LongLong,
+    ULongLong,
+    String,
+    ObjRef,
+    Enum,
+    Struct {
+        id: String,
+        members: Vec<(String, TypeCode)>,
+    },
+    Sequence(Box<TypeCode>),
+    Array(Box<TypeCode>, usize),
+    Alias {
+        id: String,
+        content: Box<TypeCode>,
+    },
+}
+
+impl TypeCode {
+    fn unaliased(&self) -> &TypeCode {
+        match self {
+            TypeCode::Alias { content, .. } => content.unaliased(),
+            type_code => type_code,
+        }
+    }
+
+    /// Returns true for a struct of an id and an any, the CF::DataType.
+    fn is_data_type(&self) -> bool {
+        match self.unaliased() {
+            TypeCode::Struct { members, .. } => {
+                matches!(
+                    &members[..],
+                    [(_, id), (_, value)]
+                        if matches!(id.unaliased(), TypeCode::String)
+                            && matches!(value.unaliased(), TypeCode::Any)
+                )
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the type code of CF::Properties.
+    fn properties() -> TypeCode {
+        TypeCode::Alias {
+            id: PROPERTIES_REPID.to_string(),
+            content: Box::new(TypeCode::Sequence(Box::new(TypeCode::Struct {
+                id: DATA_TYPE_REPID.to_string(),
+                members: vec![
+                    ("id".to_string(), TypeCode::String),
+                    ("value".to_string(), TypeCode::Any),
+                ],
+            }))),
+        }
+    }
+
+    /**
+     * Returns the type code a value is carried as in an any: the structs
+     * as CF::Properties, and the sequences of simple values of a type as
+     * sequences of the type, the other ones as sequences of anys.
+     */
+    fn of(value: &AnyValue) -> TypeCode {
+        match value {
+            AnyValue::Boolean(_) => TypeCode::Boolean,
+            AnyValue::Octet(_) => TypeCode::Octet,
+            AnyValue::Short(_) => TypeCode::Short,
+            AnyValue::UShort(_) => TypeCode::UShort,
+            AnyValue::Long(_) => TypeCode::Long,
+            AnyValue::ULong(_) => TypeCode::ULong,
+            AnyValue::LongLong(_) => TypeCode::LongLong,
+            AnyValue::ULongLong(_) => TypeCode::ULongLong,
+            AnyValue::Float(_) => TypeCode::Float,
+            AnyValue::Double(_) => TypeCode::Double,
+            AnyValue::String(_) => TypeCode::String,
+            AnyValue::Struct(_) => TypeCode::properties(),
+            AnyValue::Sequence(values) => {
+                let element = match values.first() {
+                    Some(first)
+                        if first.is_simple() && values.iter().all(|v| v.same_type(first)) =>
+                    {
+                        TypeCode::of(first)
+                    }
+                    _ => TypeCode::Any,
+                };
+                TypeCode::Sequence(Box::new(element))
+            }
+        }
+    }
+
+    fn write(&self, w: &mut CdrWriter) {
+        let name = |id: &str| {
+            let name = id.rsplit_once('/').map_or(id, |(_, name)| name);
+            name.split(':').next().unwrap_or_default().to_string()
+        };
+        match self {
+            TypeCode::Null => w.ulong(TK_NULL),
+            TypeCode::Void => w.ulong(TK_VOID),
+            TypeCode::Short => w.ulong(TK_SHORT),
+            TypeCode::Long => w.ulong(TK_LONG),
+            TypeCode::UShort => w.ulong(TK_USHORT),
+            TypeCode::ULong | TypeCode::Enum => w.ulong(TK_ULONG),
+            TypeCode::Float => w.ulong(TK_FLOAT),
+            TypeCode::Double => w.ulong(TK_DOUBLE),
+            TypeCode::Boolean => w.ulong(TK_BOOLEAN),
+            TypeCode::Char => w.ulong(TK_CHAR),
+            TypeCode::Octet => w.ulong(TK_OCTET),
+            TypeCode::Any => w.ulong(TK_ANY),
+            TypeCode::LongLong => w.ulong(TK_LONGLONG),
+            TypeCode::ULongLong => w.ulong(TK_ULONGLONG),
+            TypeCode::String => {
+                w.ulong(TK_STRING);
+                w.ulong(0);
+            }
+            TypeCode::ObjRef => {
+                w.ulong(TK_OBJREF);
+                w.encapsulation(|w| {
+                    w.string(OBJECT_REPID);
+                    w.string("Object");
+                });
+            }
+            TypeCode::Struct { id, members } => {
+                w.ulong(TK_STRUCT);
+                w.encapsulation(|w| {
+                    w.string(id);
+                    w.string(&name(id));
+                    w.ulong(members.len() as u32);
+                    for (member, type_code) in members {
+                        w.string(member);
+                        type_code.write(w);
+                    }
+                });
+            }
+            TypeCode::Sequence(element) => {
+                w.ulong(TK_SEQUENCE);
+                w.encapsulation(|w| {
+                    element.write(w);
+                    w.ulong(0);
+                });
+            }
+            TypeCode::Array(element, length) => {
+                w.ulong(TK_ARRAY);
+                w.encapsulation(|w| {
+                    element.write(w);
+                    w.ulong(*length as u32);
+                });
+            }
+            TypeCode::Alias { id, content } => {
+                w.ulong(TK_ALIAS);
+                w.encapsulation(|w| {
+                    w.string(id);
+                    w.string(&name(id));
+                    content.write(w);
+                });
+            }
+        }
+    }
+}
+
+fn write_any(w: &mut CdrWriter, value: &AnyValue) -> Result<()> {
+    let type_code = TypeCode::of(value);
+    type_code.write(w);
+    write_value(w, &type_code, value)
+}
+
+fn write_value(w: &mut CdrWriter, type_code: &TypeCode, value: &AnyValue) -> Result<()> {
+    match (type_code, value) {
+        (TypeCode::Boolean, AnyValue::Boolean(v)) => w.boolean(*v),
+        (TypeCode::Octet, AnyValue::Octet(v)) => w.octet(*v),
+        (TypeCode::Short, AnyValue::Short(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::UShort, AnyValue::UShort(v)) => w.ushort(*v),
+        (TypeCode::Long, AnyValue::Long(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::ULong, AnyValue::ULong(v)) => w.ulong(*v),
+        (TypeCode::LongLong, AnyValue::LongLong(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::ULongLong, AnyValue::ULongLong(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::Float, AnyValue::Float(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::Double, AnyValue::Double(v)) => w.aligned(&v.to_be_bytes()),
+        (TypeCode::String, AnyValue::String(v)) => w.string(v),
+        (TypeCode::Any, value) => write_any(w, value)?,
+        (TypeCode::Alias { content, .. }, value) => write_value(w, content, value)?,
+        (TypeCode::Sequence(_), AnyValue::Struct(properties)) => write_properties(w, properties)?,
+        (TypeCode::Sequence(element), AnyValue::Sequence(values)) => {
+            w.ulong(values.len() as u32);
+            for value in values {
+                write_value(w, element, value)?;
+            }
+        }
+        _ => return Err(marshal("value of another type than its type code")),
+    }
+    Ok(())
+}
+
+/// Writes CF::Properties, a sequence of CF::DataType.
+fn write_properties(w: &mut CdrWriter, properties: &Properties) -> Result<()> {
+    w.ulong(properties.len() as u32);
+    for property in properties {
+        w.string(&property.id);
+        write_any(w, &property.value)?;
+    }
+    Ok(())
+}
+
+/**
+ * The IDL types of the parameters, results and exception members of the
+ * CF operations bridged, the enums being carried as their ordinals and
+ * the object references as their stringified IORs.
+ */
+#[derive(Debug, Clone, Copy)]
+enum Idl {
+    Boolean,
+    UShort,
+    ULong,
+    String,
+    Object,
+    /// An object reference, the strings other than references being carried as nil.
+    OptionalObject,
+    Properties,
+    Octets,
+    Strings,
+}
+
+impl Idl {
+    fn default_value(self) -> AnyValue {
+        match self {
+            Idl::Boolean => AnyValue::Boolean(false),
+            Idl::UShort => AnyValue::UShort(0),
+            Idl::ULong => AnyValue::ULong(0),
+            Idl::String | Idl::Object | Idl::OptionalObject => AnyValue::String(String::new()),
+            Idl::Properties => AnyValue::Struct(Properties::new()),
+            Idl::Octets | Idl::Strings => AnyValue::Sequence(Vec::new()),
+        }
+    }
+
+    fn write(self, w: &mut CdrWriter, value: &AnyValue) -> Result<()> {
+        let unsigned = match value {
+            AnyValue::UShort(v) => Some(*v as u64),
+            AnyValue::ULong(v) => Some(*v as u64),
+            AnyValue::ULongLong(v) => Some(*v),
+            _ => None,
+        };
+        let mismatch = || marshal(&format!("{value:?} is not of the IDL type {self:?}"));
+        match (self, value) {
+            (Idl::Boolean, AnyValue::Boolean(v)) => w.boolean(*v),
+            (Idl::UShort, _) => w.ushort(
+                unsigned
+                    .and_then(|v| v.try_into().ok())
+                    .ok_or_else(mismatch)?,
+            ),
+            (Idl::ULong, _) => w.ulong(
+                unsigned
+                    .and_then(|v| v.try_into().ok())
+                    .ok_or_else(mismatch)?,
+            ),
+            (Idl::String, AnyValue::String(v)) => w.string(v),
+            (Idl::Object, AnyValue::String(v)) => Ior::parse_object(v)?.write(w),
+            (Idl::OptionalObject, AnyValue::String(v)) => {
+                Ior::parse_object(v).unwrap_or_else(|_| Ior::nil()).write(w)
+            }
+            (Idl::Properties, AnyValue::Struct(properties)) => write_properties(w, properties)?,
+            (Idl::Octets, AnyValue::Sequence(values)) => {
+                let octets: Option<Vec<u8>> = values.iter().map(u8::from_any).collect();
+                w.octets(&octets.ok_or_else(mismatch)?);
+            }
+            (Idl::Strings, AnyValue::Sequence(values)) => {
+                w.ulong(values.len() as u32);
+                for value in values {
+                    w.string(&String::from_any(value).ok_or_else(mismatch)?);
+                }
+            }
+            _ => return Err(mismatch()),
+        }
+        Ok(())
+    }
+
+    fn read(self, r: &mut CdrReader) -> Result<AnyValue> {
+        Ok(match self {
+            Idl::Boolean => AnyValue::Boolean(r.boolean()?),
+            Idl::UShort => AnyValue::UShort(r.ushort()?),
+            Idl::ULong => AnyValue::ULong(r.ulong()?),
+            Idl::String => AnyValue::String(r.string()?),
+            Idl::Object | Idl::OptionalObject => AnyValue::String(Ior::read(r)?.to_string()),
+            Idl::Properties => AnyValue::Struct(r.properties()?),
+            Idl::Octets => r.octets()?.to_any(),
+            Idl::Strings => {
+                let mut strings = Vec::new();
+                for _ in 0..r.count()? {
+                    strings.push(r.string()?);
+                }
+                strings.to_any()
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    In,
+    Out,
+    InOut,
+}
+
+/**
+ * The signature of an operation: its parameters in the IDL order and
+ * its result. The arguments invoked with are the in and inout ones, and
+ * the value returned the result or, for the void operations, the first
+ * out or inout parameter.
+ */
+struct Signature {
+    parameters: &'static [(Mode, Idl)],
+    result: Option<Idl>,
+}
+
+impl Signature {
+    fn inputs(&self) -> impl Iterator<Item = Idl> + '_ {
+        self.parameters
+            .iter()
+            .filter(|(mode, _)| *mode != Mode::Out)
+            .map(|(_, idl)| *idl)
+    }
+
+    fn outputs(&self) -> impl Iterator<Item = Idl> + '_ {
+        self.parameters
+            .iter()
+            .filter(|(mode, _)| *mode != Mode::In)
+            .map(|(_, idl)| *idl)
+    }
+}
+
+/// Returns the signature of an operation of the CF Resource, Device, Port and File interfaces.
+fn signature(operation: &str) -> Option<Signature> {
+    use Idl::*;
+    use Mode::*;
+    let (parameters, result): (&'static [(Mode, Idl)], Option<Idl>) = match operation {
+        "_get_identifier" | "_get_label" | "_get_fileName" => (&[], Some(String)),
+        "_get_started" | "_non_existent" => (&[], Some(Boolean)),
+        "_is_a" => (&[(In, String)], Some(Boolean)),
+        "_get_usageState" | "_get_adminState" | "_get_operationalState" => (&[], Some(ULong)),
+        "_set_adminState" => (&[(In, ULong)], None),
+        "_get_compositeDevice" => (&[], Some(OptionalObject)),
+        "_get_filePointer" | "sizeOf" => (&[], Some(ULong)),
+        "initialize" | "releaseObject" | "start" | "stop" | "close" => (&[], None),
+        "configure" | "deallocateCapacity" => (&[(In, Properties)], None),
+        "query" => (&[(InOut, Properties)], None),
+        "allocateCapacity" => (&[(In, Properties)], Some(Boolean)),
+        "getPort" => (&[(In, String)], Some(Object)),
+        "connectPort" => (&[(In, Object), (In, String)], None),
+        "disconnectPort" => (&[(In, String)], None),
+        "read" => (&[(Out, Octets), (In, ULong)], None),
+        "write" => (&[(In, Octets)], None),
+        "setFilePointer" => (&[(In, ULong)], None),
+        _ => return None,
+    };
+    Some(Signature { parameters, result })
+}
+
+/// Returns the members of a CF exception, by name.
+fn exception_members(repository_id: &str) -> Option<&'static [(&'static str, Idl)]> {
+    use Idl::*;
+    Some(match repository_id {
+        INITIALIZE_ERROR_REPID | RELEASE_ERROR_REPID => &[("errorMessages", Strings)],
+        INVALID_CONFIGURATION_REPID => &[("msg", String), ("invalidProperties", Properties)],
+        PARTIAL_CONFIGURATION_REPID | UNKNOWN_PROPERTIES_REPID => {
+            &[("invalidProperties", Properties)]
+        }
+        START_ERROR_REPID | STOP_ERROR_REPID | FILE_EXCEPTION_REPID | IO_EXCEPTION_REPID => {
+            &[("errorNumber", ULong), ("msg", String)]
+        }
+        UNKNOWN_PORT_REPID | OCCUPIED_PORT_REPID | INVALID_FILE_POINTER_REPID => &[],
+        INVALID_PORT_REPID => &[("errorCode", UShort), ("msg", String)],
+        INVALID_STATE_REPID => &[("msg", String)],
+        INVALID_CAPACITY_REPID => &[("msg", String), ("capacities", Properties)],
+        _ => return None,
+    })
+}
+
+/// Returns true when an object of an interface is also of another one.
+fn is_a(repository_id: &str, interface: &str) -> bool {
+    repository_id == interface
+        || interface == OBJECT_REPID
+        || (matches!(repository_id, RESOURCE_REPID | DEVICE_REPID)
+            && RESOURCE_BASES.contains(&interface))
+        || (repository_id == DEVICE_REPID && interface == RESOURCE_REPID)
+}
+
+/**
+ * The IIOP profile of an object reference: the GIOP version, the
+ * address and the object key, and whether the server takes the chars in
+ * UTF-8.
+ */
+#[derive(Debug, Clone)]
+struct IiopProfile {
+    minor: u8,
+    host: String,
+    port: u16,
+    object_key: Vec<u8>,
+    utf8: bool,
+}
+
+impl IiopProfile {
+    fn read(data: &[u8]) -> Result<IiopProfile> {
+        let mut r = CdrReader::new(data, false, false);
+        r.little_endian = r.octet()? & 1 == 1;
+        let (major, minor) = (r.octet()?, r.octet()?);
+        if major != 1 {
+            return Err(marshal(&format!("IIOP {major}.{minor} not supported")));
+        }
+        let (host, port, object_key) = (r.string()?, r.ushort()?, r.octets()?);
+        let mut utf8 = false;
+        if minor >= 1 && r.remaining() > 0 {
+            for _ in 0..r.count()? {
+                let tag = r.ulong()?;
+                if tag != TAG_CODE_SETS {
+                    r.octets()?;
+                    continue;
+                }
+                //the native code set of the chars, then their conversion code sets
+                utf8 = r.encapsulation(|r| {
+                    let native = r.ulong()?;
+                    let mut conversions = Vec::new();
+                    for _ in 0..r.count()? {
+                        conversions.push(r.ulong()?);
+                    }
+                    Ok(native == UTF_8 || conversions.contains(&UTF_8))
+                })?;
+            }
+        }
+        Ok(IiopProfile {
+            minor: minor.min(2),
+            host,
+            port,
+            object_key,
+            utf8,
+        })
+    }
+
+    /// Returns the profile data of an IIOP 1.2 profile, the chars taken in UTF-8.
+    fn write(&self) -> Vec<u8> {
+        let mut w = CdrWriter::new(true);
+        w.octet(0);
+        w.octet(1);
+        w.octet(2);
+        w.string(&self.host);
+        w.ushort(self.port);
+        w.octets(&self.object_key);
+        w.ulong(1);
+        w.ulong(TAG_CODE_SETS);
+        w.encapsulation(|w| {
+            w.ulong(UTF_8);
+            w.ulong(0);
+            w.ulong(UTF_16);
+            w.ulong(0);
+        });
+        w.data
+    }
+}
+
+/// An interoperable object reference: the repository id of its interface, and its tagged profiles.
+#[derive(Debug, Clone)]
+struct Ior {
+    type_id: String,
+    profiles: Vec<(u32, Vec<u8>)>,
+}
+
+impl Ior {
+    fn nil() -> Ior {
+        Ior {
+            type_id: String::new(),
+            profiles: Vec::new(),
+        }
+    }
+
+    fn is_nil(&self) -> bool {
+        self.type_id.is_empty() && self.profiles.is_empty()
+    }
+
+    /// Parses a stringified IOR, or a corbaloc URL of the iiop protocol.
+    fn parse(reference: &str) -> Result<Ior> {
+        if let Some(url) = reference.strip_prefix("corbaloc:") {
+            return Ior::corbaloc(url).ok_or_else(|| inv_objref(reference));
+        }
+        let hex = reference
+            .strip_prefix("IOR:")
+            .ok_or_else(|| inv_objref(reference))?;
+        let octets = (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(|| inv_objref(reference))?;
+        let mut r = CdrReader::new(&octets, false, false);
+        r.little_endian = r.octet()? & 1 == 1;
+        Ior::read(&mut r)
+    }
+
+    /// Parses an object reference, the empty string being the nil one.
+    fn parse_object(reference: &str) -> Result<Ior> {
+        match reference.is_empty() {
+            true => Ok(Ior::nil()),
+            false => Ior::parse(reference)
+                .map_err(|_| marshal(&format!("'{reference}' is not an object reference"))),
+        }
+    }
+
+    /// Parses the first iiop address of a corbaloc URL, e.g. iiop:1.2@host:2809/key.
+    fn corbaloc(url: &str) -> Option<Ior> {
+        let (addresses, key) = url.split_once('/')?;
+        let address = addresses
+            .split(',')
+            .find_map(|a| a.strip_prefix("iiop:").or_else(|| a.strip_prefix(':')))?;
+        let (version, address) = match address.split_once('@') {
+            Some((version, address)) => (Some(version), address),
+            None => (None, address),
+        };
+        let minor = match version {
+            Some(version) => version.strip_prefix("1.")?.parse().ok()?,
+            None => 0,
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port.parse().ok()?),
+            _ => (address, DEFAULT_CORBALOC_PORT),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let profile = IiopProfile {
+            minor,
+            host: host.to_string(),
+            port,
+            object_key: percent_decode(key)?,
+            utf8: false,
+        };
+        let mut w = CdrWriter::new(false);
+        w.octet(0);
+        w.octet(1);
+        w.octet(minor);
+        w.string(&profile.host);
+        w.ushort(profile.port);
+        w.octets(&profile.object_key);
+        if minor >= 1 {
+            w.ulong(0);
+        }
+        Some(Ior {
+            type_id: String::new(),
+            profiles: vec![(TAG_INTERNET_IOP, w.data)],
+        })
+    }
+
+    fn read(r: &mut CdrReader) -> Result<Ior> {
+        let type_id = r.string()?;
+        let mut profiles = Vec::new();
+        for _ in 0..r.count()? {
+            let tag = r.ulong()?;
+            profiles.push((tag, r.octets()?));
+        }
+        Ok(Ior { type_id, profiles })
+    }
+
+    fn write(&self, w: &mut CdrWriter) {
+        w.string(&self.type_id);
+        w.ulong(self.profiles.len() as u32);
+        for (tag, data) in &self.profiles {
+            w.ulong(*tag);
+            w.octets(data);
+        }
+    }
+
+    fn iiop(&self) -> Result<IiopProfile> {
+        let (_, data) = self
+            .profiles
+            .iter()
+            .find(|(tag, _)| *tag == TAG_INTERNET_IOP)
+            .ok_or_else(|| inv_objref("no IIOP profile"))?;
+        IiopProfile::read(data)
+    }
+}
+
+/// The stringified IOR, empty for the nil reference.
+impl std::fmt::Display for Ior {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_nil() {
+            return Ok(());
+        }
+        let mut w = CdrWriter::new(true);
+        w.octet(0);
+        self.write(&mut w);
+        write!(f, "IOR:")?;
+        w.data.iter().try_for_each(|octet| write!(f, "{octet:02x}"))
+    }
+}
+
+fn percent_decode(text: &str) -> Option<Vec<u8>> {
+    let mut octets = Vec::new();
+    let mut bytes = text.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                octets.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            byte => octets.push(byte),
+        }
+    }
+    Some(octets)
+}
+
+/// A GIOP message, its fragments reassembled, the header included.
+struct Message {
+    minor: u8,
+    kind: u8,
+    little_endian: bool,
+    data: Vec<u8>,
+}
+
+impl Message {
+    /// Returns a reader of the message past its header.
+    fn reader(&self, utf8: bool) -> CdrReader<'_> {
+        let mut r = CdrReader::new(&self.data, self.little_endian, utf8);
+        r.position = GIOP_HEADER_SIZE;
+        r
+    }
+}
+
+/// Reads a GIOP header and its body, the errors of the protocol being InvalidData.
+fn read_frame(stream: &mut impl Read) -> std::io::Result<(u8, u8, u8, Vec<u8>)> {
+    let invalid = |message: &str| std::io::Error::new(ErrorKind::InvalidData, message.to_string());
+    let mut header = [0u8; GIOP_HEADER_SIZE];
+    stream.read_exact(&mut header)?;
+    if &header[..4] != b"GIOP" || header[4] != 1 || header[5] > 2 {
+        return Err(invalid("not a GIOP 1.0 to 1.2 message"));
+    }
+    let size = [header[8], header[9], header[10], header[11]];
+    let size = match header[6] & 1 {
+        0 => u32::from_be_bytes(size),
+        _ => u32::from_le_bytes(size),
+    } as usize;
+    if size > MAX_MESSAGE_SIZE {
+        return Err(invalid("message too large"));
+    }
+    let mut data = header.to_vec();
+    data.resize(GIOP_HEADER_SIZE + size, 0);
+    stream.read_exact(&mut data[GIOP_HEADER_SIZE..])?;
+    Ok((header[5], header[6], header[7], data))
+}
+
+/**
+ * Reads a GIOP message, followed by its fragments when it has more: the
+ * fragment bodies, past their request id from GIOP 1.2, continue the
+ * aligned stream of the message.
+ */
+fn read_message(stream: &mut impl Read) -> std::io::Result<Message> {
+    let (minor, flags, kind, mut data) = read_frame(stream)?;
+    let mut more = minor >= 1 && flags & 2 != 0;
+    while more {
+        let (_, flags, fragment_kind, fragment) = read_frame(stream)?;
+        let start = GIOP_HEADER_SIZE + if minor >= 2 { 4 } else { 0 };
+        if fragment_kind != FRAGMENT
+            || fragment.len() < start
+            || data.len() + fragment.len() > MAX_MESSAGE_SIZE
+        {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "invalid fragment",
+            ));
+        }
+        data.extend_from_slice(&fragment[start..]);
+        more = flags & 2 != 0;
+    }
+    Ok(Message {
+        minor,
+        kind,
+        little_endian: flags & 1 == 1,
+        data,
+    })
+}
+
+/// Returns a big-endian GIOP message, its body written after the header.
+fn message<T>(
+    minor: u8,
+    kind: u8,
+    utf8: bool,
+    body: impl FnOnce(&mut CdrWriter) -> T,
+) -> (T, Vec<u8>) {
+    let mut w = CdrWriter::new(utf8);
+    w.data.extend_from_slice(b"GIOP");
+    w.data.extend_from_slice(&[1, minor, 0, kind, 0, 0, 0, 0]);
+    let result = body(&mut w);
+    let size = (w.data.len() - GIOP_HEADER_SIZE) as u32;
+    w.data[8..GIOP_HEADER_SIZE].copy_from_slice(&size.to_be_bytes());
+    (result, w.data)
+}
+
+/// The outcome of a request dispatched to a servant.
+enum Outcome {
+    Result(Signature, Option<AnyValue>),
+    UserException(String, Properties),
+    SystemException(String, u32),
+}
+
+type Connection = Arc<Mutex<TcpStream>>;
+
+/// The state an ORB shares with its listener, its connections and its objects.
+struct Shared {
+    /// The host and the port the objects are published at.
+    host: String,
+    port: u16,
+    local_address: SocketAddr,
+    servants: Mutex<HashMap<Vec<u8>, ServantRef>>,
+    next_key: AtomicU64,
+    next_request_id: AtomicU32,
+    /// The connections to the servers of the objects invoked, by address.
+    connections: Mutex<HashMap<(String, u16), Connection>>,
+    /// The connections of the clients served, closed by the shutdown.
+    served: Mutex<HashMap<u64, TcpStream>>,
+    next_connection: AtomicU64,
+    shutdown: AtomicBool,
+}
+
+impl Shared {
+    fn accept(self: Arc<Self>, listener: TcpListener) {
+        for stream in listener.incoming() {
+            if self.shutdown.load(Ordering::SeqCst) {
+                return;
+            }
+            let Ok(stream) = stream else { continue };
+            let _ = stream.set_nodelay(true);
+            let id = self.next_connection.fetch_add(1, Ordering::SeqCst);
+            if let Ok(served) = stream.try_clone() {
+                self.served.lock().unwrap().insert(id, served);
+            }
+            let shared = self.clone();
+            std::thread::spawn(move || {
+                shared.serve(stream);
+                shared.served.lock().unwrap().remove(&id);
+            });
+        }
+    }
+
+    /// Serves the requests of a connection in turn, until the client closes it.
+    fn serve(&self, mut stream: TcpStream) {
+        let mut utf8 = false;
+        loop {
+            let message = match read_message(&mut stream) {
+                Ok(message) => message,
+                Err(e) if e.kind() == ErrorKind::InvalidData => {
+                    let _ = stream.write_all(&control(2, MESSAGE_ERROR, |_| ()));
+                    return;
+                }
+                Err(_) => return,
+            };
+            let reply = match message.kind {
+                REQUEST => self.request(&message, &mut utf8),
+                LOCATE_REQUEST => self.locate(&message),
+                CLOSE_CONNECTION | MESSAGE_ERROR => return,
+                //the cancels are ignored, the replies being sent anyway
+                _ => Ok(None),
+            };
+            let reply = match reply {
+                Ok(reply) => reply,
+                Err(_) => Some(control(message.minor, MESSAGE_ERROR, |_| ())),
+            };
+            if let Some(reply) = reply {
+                if stream.write_all(&reply).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns the reply of a request, None for a oneway one, an error for a malformed header.
+    fn request(&self, message: &Message, utf8: &mut bool) -> Result<Option<Vec<u8>>> {
+        let mut r = message.reader(*utf8);
+        let (request_id, response_expected, object_key, operation);
+        if message.minor >= 2 {
+            request_id = r.ulong()?;
+            response_expected = r.octet()? & 3 != 0;
+            r.take(3)?;
+            object_key = target_key(&mut r)?;
+            operation = r.string()?;
+            if let Some(negotiated) = r.service_contexts()? {
+                *utf8 = negotiated;
+            }
+            if r.remaining() > 0 {
+                r.align(8)?;
+            }
+        } else {
+            if let Some(negotiated) = r.service_contexts()? {
+                *utf8 = negotiated;
+            }
+            request_id = r.ulong()?;
+            response_expected = r.boolean()?;
+            if message.minor == 1 {
+                r.take(3)?;
+            }
+            object_key = r.octets()?;
+            operation = r.string()?;
+            r.octets()?;
+        }
+        r.utf8 = *utf8;
+        let outcome = self.dispatch(&object_key, &operation, &mut r);
+        if !response_expected {
+            return Ok(None);
+        }
+        Ok(Some(reply(message.minor, request_id, *utf8, outcome)))
+    }
+
+    fn dispatch(&self, object_key: &[u8], operation: &str, r: &mut CdrReader) -> Outcome {
+        let system = |name: &str, completed| Outcome::SystemException(name.to_string(), completed);
+        let servant = self.servants.lock().unwrap().get(object_key).cloned();
+        let Some(signature) = signature(operation) else {
+            return system("BAD_OPERATION", COMPLETED_NO);
+        };
+        let mut arguments = Vec::new();
+        for idl in signature.inputs() {
+            match idl.read(r) {
+                Ok(argument) => arguments.push(argument),
+                Err(_) => return system("MARSHAL", COMPLETED_NO),
+            }
+        }
+        //the object not existing is the answer of _non_existent, not an exception
+        if operation == "_non_existent" {
+            return Outcome::Result(signature, Some(AnyValue::Boolean(servant.is_none())));
+        }
+        let Some(servant) = servant else {
+            return system("OBJECT_NOT_EXIST", COMPLETED_NO);
+        };
+        let mut servant = servant.lock().unwrap();
+        if operation == "_is_a" {
+            let interface = String::from_any(&arguments[0]).unwrap_or_default();
+            let is_a = is_a(servant.repository_id(), &interface);
+            return Outcome::Result(signature, Some(AnyValue::Boolean(is_a)));
+        }
+        match servant.dispatch(operation, &arguments) {
+            Ok(value) => Outcome::Result(signature, value),
+            Err(CorbaError::UserException {
+                repository_id,
+                members,
+            }) => match exception_members(&repository_id) {
+                Some(_) => Outcome::UserException(repository_id, members),
+                None => system("UNKNOWN", COMPLETED_MAYBE),
+            },
+            Err(CorbaError::SystemException { name, .. }) => system(&name, COMPLETED_MAYBE),
+        }
+    }
+
+    fn locate(&self, message: &Message) -> Result<Option<Vec<u8>>> {
+        let mut r = message.reader(false);
+        let request_id = r.ulong()?;
+        let object_key = match message.minor {
+            2 => target_key(&mut r)?,
+            _ => r.octets()?,
+        };
+        let status = match self.servants.lock().unwrap().contains_key(&object_key) {
+            true => OBJECT_HERE,
+            false => UNKNOWN_OBJECT,
+        };
+        let reply = control(message.minor, LOCATE_REPLY, |w| {
+            w.ulong(request_id);
+            w.ulong(status);
+        });
+        Ok(Some(reply))
+    }
+
+    /// Returns the connection to a server, connecting to it unless already.
+    fn connection(&self, host: &str, port: u16, timeout: Duration) -> Result<Connection> {
+        let address = (host.to_string(), port);
+        if let Some(connection) = self.connections.lock().unwrap().get(&address) {
+            return Ok(connection.clone());
+        }
+        let unreachable = |e: &dyn std::fmt::Display| transient(&format!("{host}:{port}: {e}"));
+        let addresses = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| unreachable(&e))?;
+        let mut error = None;
+        for socket_address in addresses {
+            match TcpStream::connect_timeout(&socket_address, timeout) {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    let connection = Arc::new(Mutex::new(stream));
+                    self.connections
+                        .lock()
+                        .unwrap()
+                        .insert(address, connection.clone());
+                    return Ok(connection);
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        Err(match error {
+            Some(e) => unreachable(&e),
+            None => unreachable(&"no address"),
+        })
+    }
+
+    /**
+     * Sends a request to the server of an object, returning its result
+     * or the reference it is forwarded to. The connection is dropped on
+     * its errors, the call being retried once on a new one when the
+     * server closed it before answering.
+     */
+    fn call(
+        &self,
+        profile: &IiopProfile,
+        operation: &str,
+        signature: &Signature,
+        arguments: &[AnyValue],
+        timeout: Duration,
+    ) -> Result<Reply> {
+        if arguments.len() != signature.inputs().count() {
+            return Err(CorbaError::system_exception("BAD_PARAM", operation));
+        }
+        let request_id = self.next_request_id.fetch_add(1, Ordering::SeqCst);
+        let utf8 = profile.utf8 && profile.minor >= 1;
+        let (written, request) = message(profile.minor, REQUEST, utf8, |w| {
+            write_request(w, profile, request_id, operation, utf8);
+            for (idl, argument) in signature.inputs().zip(arguments) {
+                idl.write(w, argument)?;
+            }
+            Ok::<(), CorbaError>(())
+        });
+        written.map_err(|e| match e {
+            CorbaError::SystemException { message, .. } => {
+                CorbaError::system_exception("BAD_PARAM", &message)
+            }
+            e => e,
+        })?;
+        let address = (profile.host.clone(), profile.port);
+        for attempt in 0..2 {
+            let connection = self.connection(&profile.host, profile.port, timeout)?;
+            let mut stream = connection.lock().unwrap();
+            match exchange(&mut stream, &request, request_id, timeout) {
+                Ok(message) => return read_reply(&message, utf8, signature),
+                Err(e) => {
+                    drop(stream);
+                    self.connections.lock().unwrap().remove(&address);
+                    let retried = e.kind() == ErrorKind::ConnectionAborted && attempt == 0;
+                    if !retried {
+                        let name = match e.kind() {
+                            ErrorKind::WouldBlock | ErrorKind::TimedOut => "TIMEOUT",
+                            ErrorKind::InvalidData => "COMM_FAILURE",
+                            _ => "TRANSIENT",
+                        };
+                        let message = format!("{}:{}: {e}", profile.host, profile.port);
+                        return Err(CorbaError::system_exception(name, &message));
+                    }
+                }
+            }
+        }
+        Err(transient("connection closed by the server"))
+    }
+}
+
+/// Returns a GIOP message of no chars, e.g. a LocateReply or a MessageError.
+fn control(minor: u8, kind: u8, body: impl FnOnce(&mut CdrWriter)) -> Vec<u8> {
+    message(minor, kind, false, body).1
+}
+
+/// Reads the object key of a GIOP 1.2 target address, by key or by IIOP profile.
+fn target_key(r: &mut CdrReader) -> Result<Vec<u8>> {
+    match r.short()? {
+        0 => r.octets(),
+        1 => {
+            let tag = r.ulong()?;
+            let data = r.octets()?;
+            match tag {
+                TAG_INTERNET_IOP => Ok(IiopProfile::read(&data)?.object_key),
+                _ => Err(marshal("target address of another profile than IIOP")),
+            }
+        }
+        2 => {
+            let index = r.ulong()? as usize;
+            let ior = Ior::read(r)?;
+            let (_, data) = ior
+                .profiles
+                .get(index)
+                .ok_or_else(|| marshal("target address of a missing profile"))?;
+            Ok(IiopProfile::read(data)?.object_key)
+        }
+        disposition => Err(marshal(&format!(
+            "target address disposition {disposition}"
+        ))),
+    }
+}
+
+/// Writes the header of a request in the GIOP version of the profile.
+fn write_request(
+    w: &mut CdrWriter,
+    profile: &IiopProfile,
+    request_id: u32,
+    operation: &str,
+    utf8: bool,
+) {
+    let service_contexts = |w: &mut CdrWriter| match utf8 {
+        //the code sets the chars and the wchars are sent in
+        true => {
+            w.ulong(1);
+            w.ulong(CODE_SETS_CONTEXT);
+            w.encapsulation(|w| {
+                w.ulong(UTF_8);
+                w.ulong(UTF_16);
+            });
+        }
+        false => w.ulong(0),
+    };
+    let has_body = signature(operation).is_some_and(|s| s.inputs().count() > 0);
+    if profile.minor >= 2 {
+        w.ulong(request_id);
+        w.octet(3);
+        w.data.extend_from_slice(&[0, 0, 0]);
+        w.aligned(&0i16.to_be_bytes());
+        w.octets(&profile.object_key);
+        w.string(operation);
+        service_contexts(w);
+        if has_body {
+            w.align(8);
+        }
+    } else {
+        service_contexts(w);
+        w.ulong(request_id);
+        w.boolean(true);
+        if profile.minor == 1 {
+            w.data.extend_from_slice(&[0, 0, 0]);
+        }
+        w.octets(&profile.object_key);
+        w.string(operation);
+        w.octets(&[]);
+    }
+}
+
+/// Returns the reply of a request, in the GIOP version of the request.
+fn reply(minor: u8, request_id: u32, utf8: bool, outcome: Outcome) -> Vec<u8> {
+    let header = |w: &mut CdrWriter, status: u32| {
+        if minor >= 2 {
+            w.ulong(request_id);
+            w.ulong(status);
+            w.ulong(0);
+            //the header being 24 octets long, the body is aligned on 8 already
+        } else {
+            w.ulong(0);
+            w.ulong(request_id);
+            w.ulong(status);
+        }
+    };
+    let system_exception = |name: &str, completed: u32| {
+        message(minor, REPLY, utf8, |w| {
+            header(w, SYSTEM_EXCEPTION);
+            w.string(&format!("IDL:omg.org/CORBA/{name}:1.0"));
+            w.ulong(0);
+            w.ulong(completed);
+        })
+        .1
+    };
+    let (written, reply) = match outcome {
+        Outcome::Result(signature, mut value) => message(minor, REPLY, utf8, |w| {
+            header(w, NO_EXCEPTION);
+            if let Some(idl) = signature.result {
+                idl.write(w, &value.take().unwrap_or_else(|| idl.default_value()))?;
+            }
+            for idl in signature.outputs() {
+                idl.write(w, &value.take().unwrap_or_else(|| idl.default_value()))?;
+            }
+            Ok::<(), CorbaError>(())
+        }),
+        Outcome::UserException(repository_id, members) => message(minor, REPLY, utf8, |w| {
+            header(w, USER_EXCEPTION);
+            w.string(&repository_id);
+            for (name, idl) in exception_members(&repository_id).unwrap_or_default() {
+                let member = members.iter().find(|m| m.id == *name).map(|m| &m.value);
+                idl.write(w, member.unwrap_or(&idl.default_value()))?;
+            }
+            Ok(())
+        }),
+        Outcome::SystemException(name, completed) => {
+            return system_exception(&name, completed);
+        }
+    };
+    match written {
+        Ok(()) => reply,
+        Err(_) => system_exception("MARSHAL", COMPLETED_MAYBE),
+    }
+}
+
+/// The reply of a call: its result, or the reference the object is forwarded to.
+enum Reply {
+    Result(Option<AnyValue>),
+    Forward(Ior),
+}
+
+/**
+ * Sends a request on a connection and reads the messages until its
+ * reply, the server closing the connection being ConnectionAborted.
+ */
+fn exchange(
+    stream: &mut TcpStream,
+    request: &[u8],
+    request_id: u32,
+    timeout: Duration,
+) -> std::io::Result<Message> {
+    stream.set_read_timeout(Some(timeout))?;
+    stream.write_all(request)?;
+    loop {
+        let message = read_message(stream)?;
+        match message.kind {
+            REPLY if reply_id(&message) == Some(request_id) => return Ok(message),
+            CLOSE_CONNECTION => {
+                return Err(std::io::Error::new(
+                    ErrorKind::ConnectionAborted,
+                    "connection closed by the server",
+                ))
+            }
+            MESSAGE_ERROR => {
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    "message rejected by the server",
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn reply_id(message: &Message) -> Option<u32> {
+    let mut r = message.reader(false);
+    if message.minor < 2 {
+        r.service_contexts().ok()?;
+    }
+    r.ulong().ok()
+}
+
+fn read_reply(message: &Message, utf8: bool, signature: &Signature) -> Result<Reply> {
+    let mut r = message.reader(utf8);
+    let status = match message.minor {
+        2 => {
+            r.ulong()?;
+            let status = r.ulong()?;
+            r.service_contexts()?;
+            if r.remaining() > 0 {
+                r.align(8)?;
+            }
+            status
+        }
+        _ => {
+            r.service_contexts()?;
+            r.ulong()?;
+            r.ulong()?
+        }
+    };
+    match status {
+        NO_EXCEPTION => {
+            let mut value = match signature.result {
+                Some(idl) => Some(idl.read(&mut r)?),
+                None => None,
+            };
+            for idl in signature.outputs() {
+                let output = idl.read(&mut r)?;
+                value.get_or_insert(output);
+            }
+            Ok(Reply::Result(value))
+        }
+        USER_EXCEPTION => {
+            let repository_id = r.string()?;
+            let mut members = Properties::new();
+            for (name, idl) in exception_members(&repository_id).unwrap_or_default() {
+                members.push(DataType::new(name, idl.read(&mut r)?));
+            }
+            Err(CorbaError::user_exception(&repository_id, members))
+        }
+        SYSTEM_EXCEPTION => {
+            let repository_id = r.string()?;
+            let (minor_code, completed) = (r.ulong()?, r.ulong()?);
+            let name = repository_id
+                .trim_start_matches("IDL:omg.org/CORBA/")
+                .trim_end_matches(":1.0");
+            let completed = COMPLETIONS
+                .get(completed as usize)
+                .unwrap_or(&"COMPLETED_MAYBE");
+            Err(CorbaError::system_exception(
+                name,
+                &format!("minor code {minor_code:#x}, {completed}"),
+            ))
+        }
+        LOCATION_FORWARD | LOCATION_FORWARD_PERM => Ok(Reply::Forward(Ior::read(&mut r)?)),
+        status => Err(marshal(&format!("reply status {status} not supported"))),
+    }
+}
+
+/**
+ * ORB speaking GIOP 1.2 over IIOP, down to GIOP 1.0 for the servers of
+ * the older profiles, so that the bridges reach the CF objects of the
+ * fielded SCA components through their ORB, e.g. omniORB or TAO, and
+ * serve the scars ones to their clients. The arguments and the results
+ * are marshaled in CDR after the IDL of the CF Resource, Device, Port
+ * and File operations bridged, and the properties as CF::Properties,
+ * their values as anys. The objects activated are published in IORs of
+ * the host given to bind, keyed scars/<n> so that they are also reached
+ * at corbaloc:iiop:1.2@<host>:<port>/scars/<n>. A connection is kept per
+ * server, the calls to the objects of a server taking it in turn, and
+ * the requests of a client connection are served in turn on a thread of
+ * their own. The chars are carried in UTF-8 when the server of an object
+ * takes it, in ISO 8859-1 otherwise.
+ */
+#[derive(Clone)]
+pub struct IiopOrb {
+    shared: Arc<Shared>,
+    timeout: Duration,
+}
+
+impl IiopOrb {
+    /**
+     * Returns the ORB listening at an address, e.g. "radio1:2809", the
+     * port 0 binding an ephemeral one. Its objects are published at the
+     * host of the address and the port bound.
+     */
+    pub fn bind(address: &str) -> Result<IiopOrb> {
+        let unbound = |e: std::io::Error| {
+            CorbaError::system_exception("INITIALIZE", &format!("{address}: {e}"))
+        };
+        let listener = TcpListener::bind(address).map_err(unbound)?;
+        let local_address = listener.local_addr().map_err(unbound)?;
+        let host = address
+            .rsplit_once(':')
+            .map_or(address, |(host, _)| host)
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let shared = Arc::new(Shared {
+            host: host.to_string(),
+            port: local_address.port(),
+            local_address,
+            servants: Mutex::default(),
+            next_key: AtomicU64::new(1),
+            next_request_id: AtomicU32::new(1),
+            connections: Mutex::default(),
+            served: Mutex::default(),
+            next_connection: AtomicU64::new(0),
+            shutdown: AtomicBool::new(false),
+        });
+        let accepting = shared.clone();
+        std::thread::spawn(move || accepting.accept(listener));
+        Ok(IiopOrb {
+            shared,
+            timeout: DEFAULT_TIMEOUT,
+        })
+    }
+
+    /// Sets the time the calls wait to connect, then for their replies.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Returns the host and the port the objects are published at.
+    pub fn endpoint(&self) -> (&str, u16) {
+        (&self.shared.host, self.shared.port)
+    }
+
+    /**
+     * Stops listening and closes the connections of the clients, telling
+     * them so that they retry their pending requests elsewhere, and the
+     * connections to the servers.
+     */
+    pub fn shutdown(&self) {
+        if self.shared.shutdown.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        //the listener is woken up to see the shutdown
+        let mut wake_up = self.shared.local_address;
+        if wake_up.ip().is_unspecified() {
+            wake_up.set_ip(match wake_up {
+                SocketAddr::V4(_) => std::net::Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => std::net::Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        let _ = TcpStream::connect_timeout(&wake_up, self.timeout);
+        let close = control(2, CLOSE_CONNECTION, |_| ());
+        for (_, mut stream) in self.shared.served.lock().unwrap().drain() {
+            let _ = stream.write_all(&close);
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        for (_, connection) in self.shared.connections.lock().unwrap().drain() {
+            let _ = connection.lock().unwrap().shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl OrbTrait for IiopOrb {
+    /// The reference is resolved without contacting the server of the object.
+    fn string_to_object(&self, reference: &str) -> Result<CorbaObjectRef> {
+        let ior = Ior::parse(reference)?;
+        let profile = ior.iiop().map_err(|_| inv_objref(reference))?;
+        let ior = match reference.starts_with("IOR:") {
+            true => reference.to_string(),
+            false => ior.to_string(),
+        };
+        Ok(Arc::new(IiopObject {
+            ior,
+            profile,
+            shared: self.shared.clone(),
+            timeout: self.timeout,
+        }))
+    }
+
+    fn activate(&self, servant: ServantRef) -> Result<String> {
+        let key = self.shared.next_key.fetch_add(1, Ordering::SeqCst);
+        let object_key = format!("scars/{key}").into_bytes();
+        let profile = IiopProfile {
+            minor: 2,
+            host: self.shared.host.clone(),
+            port: self.shared.port,
+            object_key: object_key.clone(),
+            utf8: true,
+        };
+        let ior = Ior {
+            type_id: servant.lock().unwrap().repository_id().to_string(),
+            profiles: vec![(TAG_INTERNET_IOP, profile.write())],
+        };
+        self.shared
+            .servants
+            .lock()
+            .unwrap()
+            .insert(object_key, servant);
+        Ok(ior.to_string())
+    }
+
+    fn deactivate(&self, ior: &str) -> Result<()> {
+        let object_key = Ior::parse(ior)?.iiop()?.object_key;
+        match self.shared.servants.lock().unwrap().remove(&object_key) {
+            Some(_) => Ok(()),
+            None => Err(CorbaError::system_exception("OBJECT_NOT_EXIST", ior)),
+        }
+    }
+}
+
+/// CORBA object of an IIOP profile, its calls following the LOCATION_FORWARD replies.
+struct IiopObject {
+    ior: String,
+    profile: IiopProfile,
+    shared: Arc<Shared>,
+    timeout: Duration,
+}
+
+impl CorbaObjectTrait for IiopObject {
+    fn ior(&self) -> &str {
+        &self.ior
+    }
+
+    fn invoke(&self, operation: &str, arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+        let signature = signature(operation)
+            .ok_or_else(|| CorbaError::system_exception("BAD_OPERATION", operation))?;
+        let mut profile = self.profile.clone();
+        for _ in 0..=MAX_FORWARDS {
+            match self
+                .shared
+                .call(&profile, operation, &signature, arguments, self.timeout)?
+            {
+                Reply::Result(value) => return Ok(value),
+                Reply::Forward(ior) => profile = ior.iiop()?,
+            }
+        }
+        Err(transient("too many LOCATION_FORWARD replies"))
+    }
+}
@@ -0,0 +1,252 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::device::{Device, DeviceRef};
+use super::executable_device::ExecutableDeviceRef;
+use super::gpp::Gpp;
+use super::log::{
+    log_file, LogFormat, LogLevelType, LogProducer, LogWriter, LOGGING_CONFIG_URI_ID,
+    LOG_LEVEL_ID,
+};
+use super::log_file::{LogFile, RotationPolicy};
+use super::sim_device::{SimExecutableDevice, SimLoadableDevice};
+
+/// The execparam holding the unique identifier of the device.
+pub const DEVICE_ID: &str = "DEVICE_ID";
+/// The execparam holding the label of the device, as given in the DCD.
+pub const DEVICE_LABEL: &str = "DEVICE_LABEL";
+/// The execparam holding the endpoint of the DeviceManager to register with.
+pub const DEVICE_MGR_IOR: &str = "DEVICE_MGR_IOR";
+/// The execparam holding the SPD profile of the device.
+pub const PROFILE_NAME: &str = "PROFILE_NAME";
+/// The execparam holding the identifier of the parent aggregate device.
+pub const COMPOSITE_DEVICE_IOR: &str = "COMPOSITE_DEVICE_IOR";
+/// The execparam selecting the format of the log records written to the standard error, "text" or "json".
+pub const LOG_FORMAT: &str = "LOG_FORMAT";
+
+/**
+ * Convienence enum definition that includes all device launcher errors.
+ */
+#[derive(Error, Debug)]
+pub enum LauncherError {
+    /**
+     * This exception indicates that the execparams are malformed or that
+     * a mandatory one is missing.
+     */
+    #[error("InvalidExecParams: msg: '{message}'.")]
+    InvalidExecParams { message: String },
+    /**
+     * This exception indicates that no device implementation is known
+     * for the profile.
+     */
+    #[error("UnknownImplementation: profile: '{profile_name}'.")]
+    UnknownImplementation { profile_name: String },
+    /**
+     * This exception indicates that the device implementation could not
+     * be instantiated.
+     */
+    #[error("LaunchFailed: msg: '{message}'.")]
+    LaunchFailed { message: String },
+}
+
+/*
+ * Convienence type definition that includes all device launcher returned errors.
+ */
+pub type Result<T, E = LauncherError> = anyhow::Result<T, E>;
+
+/**
+ * The standard execparams a DeviceManager launches a device with. The
+ * execparams not known to the launcher are kept as string parameters.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecParams {
+    pub device_id: String,
+    pub device_label: String,
+    pub device_mgr: String,
+    pub profile_name: String,
+    pub composite_device: Option<String>,
+    /// The level given by the LOG_LEVEL execparam, as a name or a CosLwLog value.
+    pub log_level: Option<LogLevelType>,
+    pub logging_config_uri: Option<String>,
+    /// The format of the log records written to the standard error, none for no output.
+    pub log_format: Option<LogFormat>,
+    pub parameters: Properties,
+}
+
+impl ExecParams {
+    /**
+     * Parses the command line arguments, each execparam being passed as
+     * a pair of its id and its value.
+     */
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<ExecParams> {
+        let mut args = args.into_iter();
+        let mut parameters = Properties::new();
+        while let Some(id) = args.next() {
+            let value = args.next().ok_or_else(|| LauncherError::InvalidExecParams {
+                message: format!("missing value of '{id}'"),
+            })?;
+            parameters.push(DataType::new(&id, AnyValue::String(value)));
+        }
+
+        let mut take = |id: &str| -> Option<String> {
+            let index = parameters.iter().position(|p| p.id == id)?;
+            match parameters.remove(index).value {
+                AnyValue::String(value) => Some(value),
+                _ => None,
+            }
+        };
+        let log_level = match take(LOG_LEVEL_ID) {
+            Some(value) => Some(
+                LogLevelType::from_any(&AnyValue::String(value.clone())).ok_or_else(|| {
+                    LauncherError::InvalidExecParams {
+                        message: format!("invalid '{LOG_LEVEL_ID}' value '{value}'"),
+                    }
+                })?,
+            ),
+            None => None,
+        };
+        let logging_config_uri = take(LOGGING_CONFIG_URI_ID).filter(|uri| !uri.is_empty());
+        if let Some(uri) = &logging_config_uri {
+            RotationPolicy::from_uri(uri).ok_or_else(|| LauncherError::InvalidExecParams {
+                message: format!("invalid '{LOGGING_CONFIG_URI_ID}' value '{uri}'"),
+            })?;
+        }
+        let log_format = match take(LOG_FORMAT) {
+            Some(name) => Some(LogFormat::from_name(&name).ok_or_else(|| {
+                LauncherError::InvalidExecParams {
+                    message: format!("invalid '{LOG_FORMAT}' value '{name}'"),
+                }
+            })?),
+            None => None,
+        };
+        let mut required = |id: &str| {
+            take(id).ok_or_else(|| LauncherError::InvalidExecParams {
+                message: format!("missing '{id}'"),
+            })
+        };
+
+        Ok(ExecParams {
+            device_id: required(DEVICE_ID)?,
+            device_label: required(DEVICE_LABEL)?,
+            device_mgr: required(DEVICE_MGR_IOR)?,
+            profile_name: required(PROFILE_NAME)?,
+            composite_device: take(COMPOSITE_DEVICE_IOR),
+            log_level,
+            logging_config_uri,
+            log_format,
+            parameters,
+        })
+    }
+
+    /**
+     * Returns the device state model described by the execparams, its
+     * logger configured. The records are appended to the file a file
+     * LOGGING_CONFIG_URI points at, rotated after the query of the URI.
+     */
+    pub fn device(&self) -> Device {
+        let mut device = Device::new(&self.device_id, &self.device_label);
+        if let Some(log_level) = self.log_level {
+            device.set_log_level(log_level);
+        }
+        device.set_log_uri(self.logging_config_uri.as_deref());
+        if let Some(log_format) = self.log_format {
+            let writer = LogWriter::new(std::io::stderr(), log_format);
+            device.logger().add_consumer(Arc::new(Mutex::new(writer)));
+        }
+        if let Some(uri) = &self.logging_config_uri {
+            if let Some(path) = log_file(uri) {
+                let policy = RotationPolicy::from_uri(uri).unwrap_or_default();
+                let format = self.log_format.unwrap_or_default();
+                let file = LogFile::new(&path, format, policy);
+                device.logger().add_consumer(Arc::new(Mutex::new(file)));
+            }
+        }
+        match &self.composite_device {
+            Some(composite_device) => device.with_composite_device(composite_device),
+            None => device,
+        }
+    }
+}
+
+/**
+ * A device instantiated by the launcher, along with its executable
+ * interface when it loads and executes files.
+ */
+#[derive(Clone)]
+pub struct LaunchedDevice {
+    pub device: DeviceRef,
+    pub executable: Option<ExecutableDeviceRef>,
+}
+
+impl LaunchedDevice {
+    pub fn new(device: DeviceRef) -> LaunchedDevice {
+        LaunchedDevice {
+            device,
+            executable: None,
+        }
+    }
+
+    /// Returns the executable device, served as both interfaces.
+    pub fn executable(executable: ExecutableDeviceRef) -> LaunchedDevice {
+        LaunchedDevice {
+            device: executable.clone(),
+            executable: Some(executable),
+        }
+    }
+}
+
+/**
+ * Factory instantiating a device implementation out of its execparams.
+ */
+pub type DeviceFactory = fn(&ExecParams) -> Result<LaunchedDevice>;
+
+/**
+ * The device implementations known to the launcher, by name. A profile
+ * selects the implementation named after its file name, e.g.
+ * "devices/GPP/GPP.spd.xml" selects "gpp".
+ */
+pub const DEVICE_IMPLEMENTATIONS: &[(&str, DeviceFactory)] = &[
+    ("device", |params| {
+        Ok(LaunchedDevice::new(Arc::new(Mutex::new(params.device()))))
+    }),
+    ("gpp", |params| {
+        let cache_dir = std::env::temp_dir()
+            .join("scars")
+            .join(&params.device_label);
+        let gpp = Gpp::new(params.device(), &cache_dir)
+            .with_persistence()
+            .map_err(|e| LauncherError::LaunchFailed {
+                message: e.to_string(),
+            })?;
+        Ok(LaunchedDevice::executable(Arc::new(Mutex::new(gpp))))
+    }),
+    ("simexecutabledevice", |params| {
+        let sim = SimExecutableDevice::new(SimLoadableDevice::new(params.device()));
+        Ok(LaunchedDevice::executable(Arc::new(Mutex::new(sim))))
+    }),
+];
+
+/// Returns the name of the implementation selected by a profile.
+pub fn implementation_name(profile_name: &str) -> String {
+    Path::new(profile_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split('.').next())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Instantiates the device implementation selected by the profile.
+pub fn instantiate_device(params: &ExecParams) -> Result<LaunchedDevice> {
+    let name = implementation_name(&params.profile_name);
+    let (_, factory) = DEVICE_IMPLEMENTATIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .ok_or_else(|| LauncherError::UnknownImplementation {
+            profile_name: params.profile_name.clone(),
+        })?;
+    factory(params)
+}
@@ -0,0 +1,507 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::common_types::{ErrorNumberType, Properties};
+use super::device::{
+    self, AdminType, Device, DeviceError, DeviceTrait, OperationalType, UsageType,
+};
+use super::property_store::PropertyReader;
+
+/**
+ * This type defines the type of load to be performed. The load types are
+ * in accordance with the code element type of the SPD implementation.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LoadType {
+    KERNEL_MODULE,
+    DRIVER,
+    SHARED_LIBRARY,
+    EXECUTABLE,
+}
+
+/**
+ * Convienence enum definition that includes all LoadableDeviceTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum LoadableDeviceError {
+    /**
+     * This exception indicates that the device is not capable of the
+     * behavior being attempted due to its current state.
+     */
+    #[error("InvalidState: msg: '{message}'.")]
+    InvalidState { message: String },
+    /**
+     * This exception indicates that the device is unable to load the type
+     * of file designated by the loadKind parameter.
+     */
+    #[error("InvalidLoadKind.")]
+    InvalidLoadKind,
+    /**
+     * This exception indicates an invalid file name was passed to the
+     * load or unload operation.
+     */
+    #[error("InvalidFileName: num: {error_number:?}, msg: '{message}'.")]
+    InvalidFileName {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates that the load operation failed due to
+     * device dependent reasons.
+     */
+    #[error("LoadFail: num: {error_number:?}, msg: '{message}'.")]
+    LoadFail {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+}
+
+impl From<std::io::Error> for LoadableDeviceError {
+    /// The staging failures are reported as LoadFail, CF_EIO by default.
+    fn from(value: std::io::Error) -> Self {
+        let error_number = match value.kind() {
+            ErrorKind::NotFound => ErrorNumberType::CF_ENOENT,
+            ErrorKind::PermissionDenied => ErrorNumberType::CF_EACCES,
+            ErrorKind::AlreadyExists => ErrorNumberType::CF_EEXIST,
+            ErrorKind::StorageFull => ErrorNumberType::CF_ENOSPC,
+            ErrorKind::ReadOnlyFilesystem => ErrorNumberType::CF_EROFS,
+            ErrorKind::NotADirectory => ErrorNumberType::CF_ENOTDIR,
+            ErrorKind::IsADirectory => ErrorNumberType::CF_EISDIR,
+            ErrorKind::DirectoryNotEmpty => ErrorNumberType::CF_ENOTEMPTY,
+            ErrorKind::FileTooLarge => ErrorNumberType::CF_EFBIG,
+            ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy => ErrorNumberType::CF_EBUSY,
+            _ => ErrorNumberType::CF_EIO,
+        };
+        LoadableDeviceError::LoadFail {
+            error_number,
+            message: value.to_string(),
+        }
+    }
+}
+
+/*
+ * Convienence type definition that includes all LoadableDeviceTrait returned errors.
+ */
+pub type Result<T, E = LoadableDeviceError> = anyhow::Result<T, E>;
+
+/**
+ * This interface extends the DeviceTrait by adding software loading and
+ * unloading behavior to a device.
+ */
+pub trait LoadableDeviceTrait: DeviceTrait {
+    /// This operation loads a file, found relative to the fs root, onto the device.
+    fn load(&mut self, fs: &Path, file_name: &str, load_kind: LoadType) -> Result<()>;
+
+    /// This operation unloads a file previously loaded onto the device.
+    fn unload(&mut self, file_name: &str) -> Result<()>;
+}
+
+/// The file, under the cache directory, holding the persisted device bookkeeping.
+pub const STATE_FILE_NAME: &str = ".device_state.json";
+
+/**
+ * Book keeping of a file loaded onto the device.
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LoadedFile {
+    load_kind: LoadType,
+    load_count: usize,
+}
+
+/**
+ * Bookkeeping persisted across device restarts: the capacities handed
+ * out to the applications and the files loaded on their behalf.
+ */
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistentState {
+    allocated_capacities: Properties,
+    loaded_files: HashMap<String, LoadedFile>,
+}
+
+/**
+ * Loadable device that loads files by staging them into a local cache
+ * directory, counting the load requests for each file name so that a
+ * file shared by several applications is only removed after the last
+ * of them unloads it.
+ */
+#[derive(Debug)]
+pub struct LoadableDevice {
+    device: Device,
+    cache_dir: PathBuf,
+    supported_load_types: Vec<LoadType>,
+    loaded_files: HashMap<String, LoadedFile>,
+    persistent: bool,
+}
+
+impl LoadableDevice {
+    pub fn new(device: Device, cache_dir: &Path) -> LoadableDevice {
+        LoadableDevice {
+            device,
+            cache_dir: cache_dir.to_path_buf(),
+            supported_load_types: Vec::new(),
+            loaded_files: HashMap::new(),
+            persistent: false,
+        }
+    }
+
+    /**
+     * Keeps the outstanding allocations and loaded files in a state file
+     * under the cache directory, restoring those left by a previous run
+     * of the device. Loaded files no longer found in the cache are
+     * forgotten.
+     */
+    pub fn enable_persistence(&mut self) -> Result<()> {
+        let path = self.cache_dir.join(STATE_FILE_NAME);
+        if path.is_file() {
+            let state: PersistentState = serde_json::from_slice(&std::fs::read(&path)?)
+                .map_err(|e| LoadableDeviceError::LoadFail {
+                    error_number: ErrorNumberType::CF_EIO,
+                    message: format!("invalid state file: {e}"),
+                })?;
+
+            //restore the capacities handed out before the restart
+            if !state.allocated_capacities.is_empty() {
+                match self.device.allocate_capacity(&state.allocated_capacities) {
+                    Ok(true) => {}
+                    r => {
+                        return Err(LoadableDeviceError::LoadFail {
+                            error_number: ErrorNumberType::CF_EINVAL,
+                            message: format!("persisted capacities not restored: {r:?}"),
+                        })
+                    }
+                }
+            }
+
+            //restore the files still staged in the cache
+            self.loaded_files = state
+                .loaded_files
+                .into_iter()
+                .filter(|(name, _)| {
+                    verify_file_name(name).is_ok() && self.cache_dir.join(name).is_file()
+                })
+                .collect();
+        }
+
+        self.persistent = true;
+        self.persist()
+    }
+
+    /// Writes the device bookkeeping to the state file, when persistence is enabled.
+    fn persist(&self) -> Result<()> {
+        if !self.persistent {
+            return Ok(());
+        }
+
+        let state = serde_json::to_vec_pretty(&PersistentState {
+            allocated_capacities: self.device.allocated_capacities(),
+            loaded_files: self.loaded_files.clone(),
+        })
+        .map_err(|e| LoadableDeviceError::LoadFail {
+            error_number: ErrorNumberType::CF_EIO,
+            message: e.to_string(),
+        })?;
+
+        //replace the state file atomically
+        std::fs::create_dir_all(&self.cache_dir)?;
+        let temp = self.cache_dir.join(format!("{STATE_FILE_NAME}.tmp"));
+        std::fs::write(&temp, state)?;
+        std::fs::rename(temp, self.cache_dir.join(STATE_FILE_NAME))?;
+        Ok(())
+    }
+
+    /**
+     * Restricts the load types accepted by the device, as stated by
+     * the supported_load_types allocation property of its profile.
+     */
+    pub fn with_supported_load_types(mut self, load_types: &[LoadType]) -> LoadableDevice {
+        self.supported_load_types = load_types.to_vec();
+        self
+    }
+
+    /// Returns the embedded device state model.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the mutable embedded device state model.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    /// Returns the local path where a loaded file has been staged.
+    pub fn loaded_path(&self, file_name: &str) -> Option<PathBuf> {
+        self.loaded_files
+            .contains_key(file_name)
+            .then(|| self.cache_dir.join(file_name))
+    }
+
+    /// Returns the number of outstanding loads of a file.
+    pub fn load_count(&self, file_name: &str) -> usize {
+        self.loaded_files
+            .get(file_name)
+            .map_or(0, |f| f.load_count)
+    }
+
+    /// Returns the load kind a file has been loaded with.
+    pub fn load_kind(&self, file_name: &str) -> Option<LoadType> {
+        self.loaded_files.get(file_name).map(|f| f.load_kind)
+    }
+}
+
+impl DeviceTrait for LoadableDevice {
+    fn identifier(&self) -> &str {
+        self.device.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.device.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.device.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.device.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.device.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.device.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.device.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.device.allocation_properties()
+    }
+
+    fn allocation_property_reader(&self) -> Option<PropertyReader> {
+        self.device.allocation_property_reader()
+    }
+
+    /**
+     * The capacities allocated stand only once persisted: an allocation
+     * whose state file cannot be written is undone, failing with
+     * InvalidState.
+     */
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        let allocated = self.device.allocate_capacity(capacities)?;
+        if allocated {
+            if let Err(e) = self.persist() {
+                self.device.deallocate_capacity(capacities)?;
+                return Err(not_persisted(e));
+            }
+        }
+        Ok(allocated)
+    }
+
+    /// Likewise, a deallocation not persisted is undone.
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.device.deallocate_capacity(capacities)?;
+        if let Err(e) = self.persist() {
+            self.device.allocate_capacity(capacities)?;
+            return Err(not_persisted(e));
+        }
+        Ok(())
+    }
+}
+
+impl LoadableDeviceTrait for LoadableDevice {
+    /**
+     * SCA268
+     * The load operation shall load the file identified by the input fileName
+     * parameter on the DeviceComponent based upon the input loadKind parameter.
+     * SCA269
+     * Multiple loads of the same file as indicated by the input fileName parameter
+     * shall not result in an exception. However, the load operation should account
+     * for this multiple load so that the unload operation behavior can be performed.
+     * SCA270
+     * The load operation shall raise the CF::InvalidState exception if upon entry the
+     * LoadableDeviceComponent's adminState attribute is either LOCKED or SHUTTING_DOWN.
+     * SCA512
+     * The load operation shall raise the CF::InvalidState exception if upon entry the
+     * LoadableDeviceComponent's operationalState attribute is DISABLED.
+     * SCA271
+     * The load operation shall raise the InvalidLoadKind exception when the input
+     * loadKind parameter is not supported.
+     * SCA272
+     * The load operation shall raise the CF::InvalidFileName exception when the file
+     * designated by the input fileName parameter cannot be found.
+     * SCA273
+     * The load operation shall raise the LoadFail exception when an attempt to load
+     * the device is unsuccessful.
+     * SCA306
+     * The load operation shall support the load types as stated in the
+     * LoadableDeviceComponent's profile supported_load_types allocation property.
+     * SCA307
+     * When a LoadType is not defined for the LoadableDeviceComponent, the load
+     * operation shall support all SPD code element types.
+     */
+    fn load(&mut self, fs: &Path, file_name: &str, load_kind: LoadType) -> Result<()> {
+        //verify device state
+        if self.admin_state() != AdminType::UNLOCKED {
+            return Err(LoadableDeviceError::InvalidState {
+                message: format!("adminState is {:?}", self.admin_state()),
+            });
+        }
+        if self.operational_state() == OperationalType::DISABLED {
+            return Err(LoadableDeviceError::InvalidState {
+                message: "operationalState is DISABLED".to_string(),
+            });
+        }
+
+        //verify requested load kind
+        if !self.supported_load_types.is_empty() && !self.supported_load_types.contains(&load_kind)
+        {
+            return Err(LoadableDeviceError::InvalidLoadKind);
+        }
+
+        //verify the file is staged within the device cache
+        verify_file_name(file_name)?;
+
+        //already loaded files only need to be accounted for, unless not persisted
+        if let Some(f) = self.loaded_files.get_mut(file_name) {
+            f.load_count += 1;
+            let persisted = self.persist();
+            if persisted.is_err() {
+                if let Some(f) = self.loaded_files.get_mut(file_name) {
+                    f.load_count -= 1;
+                }
+            }
+            return persisted;
+        }
+
+        //verify source file existence
+        let source = fs.join(file_name);
+        if !source.is_file() {
+            return Err(LoadableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{file_name}' not found"),
+            });
+        }
+
+        //stage file into device cache
+        let target = self.cache_dir.join(file_name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &target)?;
+
+        //update internal state, unstaging the file when not persisted
+        self.loaded_files.insert(
+            file_name.to_string(),
+            LoadedFile {
+                load_kind,
+                load_count: 1,
+            },
+        );
+        if let Err(e) = self.persist() {
+            self.loaded_files.remove(file_name);
+            let _ = std::fs::remove_file(&target);
+            return Err(e);
+        }
+
+        //return ok
+        Ok(())
+    }
+
+    /**
+     * SCA274
+     * The unload operation shall unload the file identified by the input fileName
+     * parameter from the loadable device when the number of unload requests matches
+     * the number of load requests for the indicated file.
+     * SCA275
+     * The unload operation shall raise the CF::InvalidState exception if upon entry
+     * the LoadableDeviceComponent's adminState attribute is LOCKED.
+     * SCA513
+     * The unload operation shall raise the CF::InvalidState exception if upon entry
+     * the LoadableDeviceComponent's operationalState attribute is DISABLED.
+     */
+    fn unload(&mut self, file_name: &str) -> Result<()> {
+        //verify device state
+        if self.admin_state() == AdminType::LOCKED {
+            return Err(LoadableDeviceError::InvalidState {
+                message: "adminState is LOCKED".to_string(),
+            });
+        }
+        if self.operational_state() == OperationalType::DISABLED {
+            return Err(LoadableDeviceError::InvalidState {
+                message: "operationalState is DISABLED".to_string(),
+            });
+        }
+
+        //verify file has been loaded
+        verify_file_name(file_name)?;
+        let f = self.loaded_files.get_mut(file_name).ok_or_else(|| {
+            LoadableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{file_name}' not loaded"),
+            }
+        })?;
+
+        //remove file only when the last load is released, and persisted
+        f.load_count -= 1;
+        let unloaded = match f.load_count {
+            0 => self.loaded_files.remove(file_name),
+            _ => None,
+        };
+        if let Err(e) = self.persist() {
+            match unloaded {
+                Some(mut f) => {
+                    f.load_count = 1;
+                    self.loaded_files.insert(file_name.to_string(), f);
+                }
+                None => {
+                    if let Some(f) = self.loaded_files.get_mut(file_name) {
+                        f.load_count += 1;
+                    }
+                }
+            }
+            return Err(e);
+        }
+        if unloaded.is_some() {
+            std::fs::remove_file(self.cache_dir.join(file_name))?;
+        }
+
+        //return ok
+        Ok(())
+    }
+}
+
+/// Returns the failure of the capacities whose bookkeeping could not be persisted.
+fn not_persisted(error: LoadableDeviceError) -> DeviceError {
+    DeviceError::InvalidState {
+        message: format!("bookkeeping not persisted: {error}"),
+    }
+}
+
+/**
+ * Verifies that a file name is relative and stays within the fs root and
+ * the cache directory, naming neither a parent directory nor the state
+ * file, so that the files are never staged nor removed outside the cache.
+ */
+fn verify_file_name(file_name: &str) -> Result<()> {
+    let path = Path::new(file_name);
+    if file_name.is_empty()
+        || path == Path::new(STATE_FILE_NAME)
+        || !path.components().all(|c| matches!(c, Component::Normal(_)))
+    {
+        return Err(LoadableDeviceError::InvalidFileName {
+            error_number: ErrorNumberType::CF_EINVAL,
+            message: format!("'{file_name}' is not a relative file name"),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,504 @@
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::events::{EventChannel, FilteredEvent};
+use super::file_system::wildcard_match;
+
+/// The property, and execparam, holding the log level of a producer.
+pub const LOG_LEVEL_ID: &str = "LOG_LEVEL";
+/// The property, and execparam, holding the URI the output of a producer is configured by.
+pub const LOGGING_CONFIG_URI_ID: &str = "LOGGING_CONFIG_URI";
+
+/**
+ * This type defines the levels of the log records, from the most to the
+ * least severe, after the CosLwLog LogLevel values.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LogLevelType {
+    SECURITY_ALARM = 1,
+    FAILURE_ALARM,
+    DEGRADED_ALARM,
+    EXCEPTION_ERROR,
+    FLOW_CONTROL_ERROR,
+    RANGE_ERROR,
+    USAGE_ERROR,
+    ADMINISTRATIVE_EVENT,
+    STATISTIC_REPORT,
+    PROGRAMMER_DEBUG1,
+    PROGRAMMER_DEBUG2,
+    PROGRAMMER_DEBUG3,
+    PROGRAMMER_DEBUG4,
+    PROGRAMMER_DEBUG5,
+    PROGRAMMER_DEBUG6,
+    PROGRAMMER_DEBUG7,
+    PROGRAMMER_DEBUG8,
+    PROGRAMMER_DEBUG9,
+    PROGRAMMER_DEBUG10,
+    PROGRAMMER_DEBUG11,
+    PROGRAMMER_DEBUG12,
+    PROGRAMMER_DEBUG13,
+    PROGRAMMER_DEBUG14,
+    PROGRAMMER_DEBUG15,
+    PROGRAMMER_DEBUG16,
+}
+
+/// The levels in the order of their values.
+const LOG_LEVELS: [LogLevelType; 25] = [
+    LogLevelType::SECURITY_ALARM,
+    LogLevelType::FAILURE_ALARM,
+    LogLevelType::DEGRADED_ALARM,
+    LogLevelType::EXCEPTION_ERROR,
+    LogLevelType::FLOW_CONTROL_ERROR,
+    LogLevelType::RANGE_ERROR,
+    LogLevelType::USAGE_ERROR,
+    LogLevelType::ADMINISTRATIVE_EVENT,
+    LogLevelType::STATISTIC_REPORT,
+    LogLevelType::PROGRAMMER_DEBUG1,
+    LogLevelType::PROGRAMMER_DEBUG2,
+    LogLevelType::PROGRAMMER_DEBUG3,
+    LogLevelType::PROGRAMMER_DEBUG4,
+    LogLevelType::PROGRAMMER_DEBUG5,
+    LogLevelType::PROGRAMMER_DEBUG6,
+    LogLevelType::PROGRAMMER_DEBUG7,
+    LogLevelType::PROGRAMMER_DEBUG8,
+    LogLevelType::PROGRAMMER_DEBUG9,
+    LogLevelType::PROGRAMMER_DEBUG10,
+    LogLevelType::PROGRAMMER_DEBUG11,
+    LogLevelType::PROGRAMMER_DEBUG12,
+    LogLevelType::PROGRAMMER_DEBUG13,
+    LogLevelType::PROGRAMMER_DEBUG14,
+    LogLevelType::PROGRAMMER_DEBUG15,
+    LogLevelType::PROGRAMMER_DEBUG16,
+];
+
+impl LogLevelType {
+    /// Returns the CosLwLog value of the level.
+    pub fn value(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Returns the level of a CosLwLog value.
+    pub fn from_value(value: u16) -> Option<LogLevelType> {
+        LOG_LEVELS.get(usize::from(value).checked_sub(1)?).copied()
+    }
+
+    /// Returns the level named as the variant, e.g. "FAILURE_ALARM".
+    pub fn from_name(name: &str) -> Option<LogLevelType> {
+        LOG_LEVELS.into_iter().find(|l| format!("{l:?}") == name)
+    }
+
+    /// Returns the level of a property value: a CosLwLog value, or a name or a value as a string.
+    pub fn from_any(value: &AnyValue) -> Option<LogLevelType> {
+        if let AnyValue::String(text) = value {
+            let text = text.trim();
+            return LogLevelType::from_name(text)
+                .or_else(|| LogLevelType::from_value(text.parse().ok()?));
+        }
+        let value = value.as_f64()?;
+        if value.fract() != 0.0 || !(0.0..=f64::from(u16::MAX)).contains(&value) {
+            return None;
+        }
+        LogLevelType::from_value(value as u16)
+    }
+}
+
+impl fmt::Display for LogLevelType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/**
+ * This type is a record written by a log producer.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    /// The identifier of the producer, e.g. the component identifier.
+    pub producer_id: String,
+    pub producer_name: String,
+    pub level: LogLevelType,
+    /// The time the record was produced.
+    pub time: SystemTime,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Returns a record produced now.
+    pub fn new(
+        producer_id: &str,
+        producer_name: &str,
+        level: LogLevelType,
+        message: &str,
+    ) -> LogRecord {
+        LogRecord {
+            producer_id: producer_id.to_string(),
+            producer_name: producer_name.to_string(),
+            level,
+            time: SystemTime::now(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = self.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        write!(
+            f,
+            "{}.{:06} {} {}: {}",
+            time.as_secs(),
+            time.subsec_micros(),
+            self.level,
+            self.producer_name,
+            self.message
+        )
+    }
+}
+
+/**
+ * This type defines the formats the records are written in: the text
+ * of their Display, or a JSON object per line for the log aggregators,
+ * e.g. {"level":"USAGE_ERROR","level_value":7,"message":"unknown mode",
+ * "producer_id":"demod_1:DCE:fm:fm_1","producer_name":"demod_1",
+ * "time":"2026-10-16T08:30:00.000000Z"}
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    /// Returns the format named "text" or "json".
+    pub fn from_name(name: &str) -> Option<LogFormat> {
+        match name {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Returns a record in the format, without line terminator.
+    pub fn format(&self, record: &LogRecord) -> String {
+        match self {
+            LogFormat::Text => record.to_string(),
+            LogFormat::Json => serde_json::json!({
+                "time": rfc3339(record.time),
+                "level": record.level.to_string(),
+                "level_value": record.level.value(),
+                "producer_id": record.producer_id,
+                "producer_name": record.producer_name,
+                "message": record.message,
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Returns a time as an RFC 3339 UTC timestamp with microseconds.
+fn rfc3339(time: SystemTime) -> String {
+    let time = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let (days, secs) = (time.as_secs() / 86400, time.as_secs() % 86400);
+
+    //civil date of the days since 1970-01-01, after H. Hinnant's algorithm
+    let z = days + 719468;
+    let (era, doe) = (z / 146097, z % 146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:06}Z",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_micros()
+    )
+}
+
+/**
+ * This trait is implemented by the receivers of the log records, e.g.
+ * the log service or a file.
+ */
+pub trait LogConsumer {
+    /// This operation writes records, in the order they were produced.
+    fn write_records(&mut self, records: &[LogRecord]);
+}
+
+/**
+ * A log event channel consumes the records by publishing them to its
+ * subscribers.
+ */
+impl LogConsumer for EventChannel<LogRecord> {
+    fn write_records(&mut self, records: &[LogRecord]) {
+        for record in records {
+            self.push(record.clone());
+        }
+    }
+}
+
+/**
+ * Log consumer writing the records to an output, e.g. the standard
+ * error or a file, one per line in a format. The write errors are
+ * ignored.
+ */
+pub struct LogWriter<W> {
+    output: W,
+    format: LogFormat,
+}
+
+impl<W: Write> LogWriter<W> {
+    pub fn new(output: W, format: LogFormat) -> LogWriter<W> {
+        LogWriter { output, format }
+    }
+
+    /// Returns the output the records are written to.
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write> LogConsumer for LogWriter<W> {
+    fn write_records(&mut self, records: &[LogRecord]) {
+        for record in records {
+            let _ = writeln!(self.output, "{}", self.format.format(record));
+        }
+        let _ = self.output.flush();
+    }
+}
+
+/**
+ * Filter of the log records a subscriber receives: the records of its
+ * level or more severe, of its producers. No level or no producers
+ * let all the records through.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFilter {
+    pub log_level: Option<LogLevelType>,
+    /// The identifiers or names of the producers, with '*' and '?' wildcards, e.g. "*GPP*".
+    pub producers: Vec<String>,
+}
+
+impl LogFilter {
+    /// Tells whether a record passes the filter.
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.log_level.is_none_or(|l| record.level <= l)
+            && (self.producers.is_empty()
+                || self.producers.iter().any(|p| {
+                    wildcard_match(p, &record.producer_id)
+                        || wildcard_match(p, &record.producer_name)
+                }))
+    }
+}
+
+/// The records are sourced by their producer.
+impl FilteredEvent for LogRecord {
+    fn source_id(&self) -> &str {
+        &self.producer_id
+    }
+
+    fn event_type(&self) -> &str {
+        "LogRecord"
+    }
+}
+
+/**
+ * Shared reference to a log consumer, as held by the producers writing
+ * to it.
+ */
+pub type LogConsumerRef = Arc<Mutex<dyn LogConsumer + Send>>;
+
+/**
+ * This trait provides the configuration of a log producer: the level
+ * of the least severe records it produces, and the URI of the
+ * configuration of its output.
+ */
+pub trait LogProducer {
+    /// The level of the least severe records produced.
+    fn log_level(&self) -> LogLevelType;
+
+    /// This operation sets the level of the least severe records produced.
+    fn set_log_level(&mut self, log_level: LogLevelType);
+
+    /// The URI the output is configured by, none for the default output.
+    fn log_uri(&self) -> Option<String>;
+
+    /// This operation sets the URI the output is configured by.
+    fn set_log_uri(&mut self, log_uri: Option<&str>);
+}
+
+/// The configuration shared by the clones of a logger.
+struct LoggerConfiguration {
+    log_level: LogLevelType,
+    log_uri: Option<String>,
+    consumers: Vec<LogConsumerRef>,
+}
+
+/**
+ * Log producer writing its records to consumers, the records less
+ * severe than its level being dropped. The level is ADMINISTRATIVE_EVENT
+ * by default. Cloned loggers share the same level, URI and consumers.
+ */
+#[derive(Clone)]
+pub struct Logger {
+    producer_id: String,
+    producer_name: String,
+    configuration: Arc<Mutex<LoggerConfiguration>>,
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let configuration = self.configuration.lock().unwrap();
+        f.debug_struct("Logger")
+            .field("producer_id", &self.producer_id)
+            .field("producer_name", &self.producer_name)
+            .field("log_level", &configuration.log_level)
+            .field("log_uri", &configuration.log_uri)
+            .field("consumers", &configuration.consumers.len())
+            .finish()
+    }
+}
+
+impl Logger {
+    pub fn new(producer_id: &str, producer_name: &str) -> Logger {
+        Logger {
+            producer_id: producer_id.to_string(),
+            producer_name: producer_name.to_string(),
+            configuration: Arc::new(Mutex::new(LoggerConfiguration {
+                log_level: LogLevelType::ADMINISTRATIVE_EVENT,
+                log_uri: None,
+                consumers: Vec::new(),
+            })),
+        }
+    }
+
+    /// Adds a consumer the records are written to.
+    pub fn with_consumer(self, consumer: LogConsumerRef) -> Logger {
+        self.add_consumer(consumer);
+        self
+    }
+
+    /// Sets the level of the least severe records produced.
+    pub fn with_log_level(mut self, log_level: LogLevelType) -> Logger {
+        self.set_log_level(log_level);
+        self
+    }
+
+    pub fn producer_id(&self) -> &str {
+        &self.producer_id
+    }
+
+    pub fn producer_name(&self) -> &str {
+        &self.producer_name
+    }
+
+    /// Adds a consumer the records are written to.
+    pub fn add_consumer(&self, consumer: LogConsumerRef) {
+        self.configuration.lock().unwrap().consumers.push(consumer);
+    }
+
+    /// Tells whether the records of a level are produced.
+    pub fn is_enabled(&self, level: LogLevelType) -> bool {
+        level <= self.configuration.lock().unwrap().log_level
+    }
+
+    /**
+     * Writes a record to the consumers, unless less severe than the
+     * level. The consumers are called without the configuration locked,
+     * so they may log in turn.
+     */
+    pub fn log(&self, level: LogLevelType, message: &str) {
+        let consumers = {
+            let configuration = self.configuration.lock().unwrap();
+            if level > configuration.log_level {
+                return;
+            }
+            configuration.consumers.clone()
+        };
+        let record = LogRecord::new(&self.producer_id, &self.producer_name, level, message);
+        for consumer in &consumers {
+            consumer
+                .lock()
+                .unwrap()
+                .write_records(std::slice::from_ref(&record));
+        }
+    }
+}
+
+impl LogProducer for Logger {
+    fn log_level(&self) -> LogLevelType {
+        self.configuration.lock().unwrap().log_level
+    }
+
+    fn set_log_level(&mut self, log_level: LogLevelType) {
+        self.configuration.lock().unwrap().log_level = log_level;
+    }
+
+    fn log_uri(&self) -> Option<String> {
+        self.configuration.lock().unwrap().log_uri.clone()
+    }
+
+    fn set_log_uri(&mut self, log_uri: Option<&str>) {
+        self.configuration.lock().unwrap().log_uri = log_uri.map(str::to_string);
+    }
+}
+
+/// Tells whether a property is one of the logging properties of the producers.
+pub fn is_logging_property(id: &str) -> bool {
+    id == LOG_LEVEL_ID || id == LOGGING_CONFIG_URI_ID
+}
+
+/**
+ * Sets a logging property of a producer, an empty URI restoring the
+ * default output. Returns false when the property is not a logging
+ * property or its value is invalid.
+ */
+pub fn set_logging_property(producer: &mut dyn LogProducer, property: &DataType) -> bool {
+    match (property.id.as_str(), &property.value) {
+        (LOG_LEVEL_ID, value) => match LogLevelType::from_any(value) {
+            Some(log_level) => producer.set_log_level(log_level),
+            None => return false,
+        },
+        (LOGGING_CONFIG_URI_ID, AnyValue::String(uri)) => {
+            producer.set_log_uri(Some(uri.as_str()).filter(|u| !u.is_empty()))
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Returns the logging properties of a producer, the level as its CosLwLog value.
+pub fn logging_properties(producer: &dyn LogProducer) -> Properties {
+    vec![
+        DataType::new(LOG_LEVEL_ID, AnyValue::UShort(producer.log_level().value())),
+        DataType::new(
+            LOGGING_CONFIG_URI_ID,
+            AnyValue::String(producer.log_uri().unwrap_or_default()),
+        ),
+    ]
+}
+
+/// The directory of a node FileSystem the log files of its components are found in.
+pub const LOG_FILES_DIRECTORY: &str = "/logs";
+
+/// Returns the local file a log URI points at, a file URI or an absolute path, without its query.
+pub fn log_file(log_uri: &str) -> Option<PathBuf> {
+    let path = log_uri.strip_prefix("file://").unwrap_or(log_uri);
+    let path = path.split('?').next().unwrap_or_default();
+    path.starts_with('/').then(|| PathBuf::from(path))
+}
+
+/// Returns the pathname the log file of a component is found at in the FileSystem of its node.
+pub fn log_file_name(component_id: &str) -> String {
+    format!("{LOG_FILES_DIRECTORY}/{}.log", component_id.replace('/', "_"))
+}
@@ -0,0 +1,199 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use super::log::{LogConsumer, LogFormat, LogRecord};
+
+/// The size a log file is rotated at by default.
+pub const DEFAULT_MAX_SIZE: u64 = 1024 * 1024;
+/// The number of rotated files kept by default.
+pub const DEFAULT_RETAINED_FILES: usize = 4;
+
+/**
+ * Policy of the rotation of a log file: the file is rotated once it
+ * reaches its maximum size or age, the rotated files being renamed
+ * <file>.1, <file>.2, ... from the newest, optionally gzip compressed
+ * as <file>.1.gz, ..., the files beyond the retained count being
+ * removed.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    /// The size in bytes the file is rotated at, none for no limit.
+    pub max_size: Option<u64>,
+    /// The age the file is rotated at, none for no limit.
+    pub max_age: Option<Duration>,
+    /// The number of rotated files kept, 0 for none.
+    pub retained_files: usize,
+    pub compress: bool,
+}
+
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        RotationPolicy {
+            max_size: Some(DEFAULT_MAX_SIZE),
+            max_age: None,
+            retained_files: DEFAULT_RETAINED_FILES,
+            compress: false,
+        }
+    }
+}
+
+impl RotationPolicy {
+    /// Returns the policy never rotating the file.
+    pub fn never() -> RotationPolicy {
+        RotationPolicy {
+            max_size: None,
+            max_age: None,
+            ..RotationPolicy::default()
+        }
+    }
+
+    /**
+     * Returns the policy given by the query of a log URI, e.g.
+     * "file:///var/log/gpp_1.log?max_size=65536&max_age=86400&retained=2&compress=true",
+     * the age in seconds, a 0 size or age for no limit. The parameters
+     * left out keep their default, none for an invalid parameter.
+     */
+    pub fn from_uri(log_uri: &str) -> Option<RotationPolicy> {
+        let mut policy = RotationPolicy::default();
+        let Some((_, query)) = log_uri.split_once('?') else {
+            return Some(policy);
+        };
+        for parameter in query.split('&').filter(|p| !p.is_empty()) {
+            let (name, value) = parameter.split_once('=')?;
+            match name {
+                "max_size" => policy.max_size = Some(value.parse().ok()?).filter(|s| *s > 0),
+                "max_age" => {
+                    policy.max_age = Some(Duration::from_secs(value.parse().ok()?))
+                        .filter(|a| !a.is_zero())
+                }
+                "retained" => policy.retained_files = value.parse().ok()?,
+                "compress" => policy.compress = value.parse().ok()?,
+                _ => return None,
+            }
+        }
+        Some(policy)
+    }
+
+    /// Tells whether a file of a size, created at a time, is due for rotation.
+    pub fn is_due(&self, size: u64, created: SystemTime) -> bool {
+        self.max_size.is_some_and(|max_size| size >= max_size)
+            || self.max_age.is_some_and(|max_age| {
+                created.elapsed().is_ok_and(|age| age >= max_age)
+            })
+    }
+}
+
+/**
+ * Log consumer appending the records to a local file, one per line in
+ * a format, rotating the file after its policy. The write errors are
+ * ignored, the records being lost while the file cannot be opened.
+ */
+pub struct LogFile {
+    path: PathBuf,
+    format: LogFormat,
+    policy: RotationPolicy,
+    file: Option<File>,
+    size: u64,
+    created: SystemTime,
+}
+
+impl LogFile {
+    pub fn new(path: &Path, format: LogFormat, policy: RotationPolicy) -> LogFile {
+        LogFile {
+            path: path.to_path_buf(),
+            format,
+            policy,
+            file: None,
+            size: 0,
+            created: SystemTime::now(),
+        }
+    }
+
+    /// The path of the file the records are appended to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the rotated file of an index, counted from 1.
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let extension = if self.policy.compress { ".gz" } else { "" };
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{index}{extension}"));
+        PathBuf::from(name)
+    }
+
+    /// Opens the file, an existing one being appended to.
+    fn open(&mut self) -> std::io::Result<&mut File> {
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            let metadata = file.metadata()?;
+            self.size = metadata.len();
+            self.created = metadata.created().unwrap_or_else(|_| SystemTime::now());
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    /**
+     * Rotates the file: shifts the rotated files, removing the oldest
+     * one, and renames, or compresses, the file as the first one.
+     */
+    pub fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        if !self.path.exists() {
+            return Ok(());
+        }
+        if self.policy.retained_files == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        let _ = std::fs::remove_file(self.rotated_path(self.policy.retained_files));
+        for index in (1..self.policy.retained_files).rev() {
+            let rotated = self.rotated_path(index);
+            if rotated.exists() {
+                std::fs::rename(rotated, self.rotated_path(index + 1))?;
+            }
+        }
+        if self.policy.compress {
+            let rotated = File::create(self.rotated_path(1))?;
+            let mut encoder = GzEncoder::new(rotated, Compression::default());
+            std::io::copy(&mut File::open(&self.path)?, &mut encoder)?;
+            encoder.finish()?;
+            std::fs::remove_file(&self.path)
+        } else {
+            std::fs::rename(&self.path, self.rotated_path(1))
+        }
+    }
+
+    fn write_record(&mut self, record: &LogRecord) -> std::io::Result<()> {
+        let line = format!("{}\n", self.format.format(record));
+        self.open()?;
+        if self.size > 0 && self.policy.is_due(self.size, self.created) {
+            self.rotate()?;
+        }
+        self.open()?.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl LogConsumer for LogFile {
+    fn write_records(&mut self, records: &[LogRecord]) {
+        for record in records {
+            if self.write_record(record).is_err() {
+                self.file = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use tonic::{Request, Response, Status};
+
+use super::file_manager::FileManagerRef;
+use super::file_system::{self, FileSystemTrait};
+use super::log::{LogConsumer, LogFilter, LogRecord};
+use super::rpc;
+use super::rpc::log_service::log_service_server;
+use super::rpc::log_service::{
+    RetrieveRecordsReply, RetrieveRecordsRequest, WriteRecordsReply, WriteRecordsRequest,
+};
+
+/// The number of records kept by default.
+pub const DEFAULT_LOG_CAPACITY: usize = 1024;
+
+/**
+ * Query of the records retrieved from a log service: the records
+ * produced from a time on and before another one, passing a filter.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogQuery {
+    pub from: Option<SystemTime>,
+    pub to: Option<SystemTime>,
+    pub filter: LogFilter,
+}
+
+impl LogQuery {
+    /// Tells whether a record answers the query.
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        self.from.is_none_or(|from| record.time >= from)
+            && self.to.is_none_or(|to| record.time < to)
+            && self.filter.matches(record)
+    }
+}
+
+/// The records kept by a log service, and the file the oldest ones are spilled to.
+struct LogStore {
+    capacity: usize,
+    records: VecDeque<LogRecord>,
+    spill_file: Option<(FileManagerRef, String)>,
+}
+
+/**
+ * Log service keeping the last records written to it in a ring buffer,
+ * the oldest records being dropped or, when given a spill file on the
+ * domain FileSystem, appended to it as text lines. The queries retrieve
+ * the records of the buffer. Cloned services share the same records.
+ */
+#[derive(Clone)]
+pub struct LogService {
+    store: Arc<Mutex<LogStore>>,
+}
+
+impl std::fmt::Debug for LogService {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let store = self.store.lock().unwrap();
+        f.debug_struct("LogService")
+            .field("capacity", &store.capacity)
+            .field("records", &store.records.len())
+            .field("spill_file", &store.spill_file.as_ref().map(|(_, f)| f))
+            .finish()
+    }
+}
+
+impl Default for LogService {
+    fn default() -> Self {
+        LogService::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl LogService {
+    pub fn new(capacity: usize) -> LogService {
+        LogService {
+            store: Arc::new(Mutex::new(LogStore {
+                capacity,
+                records: VecDeque::with_capacity(capacity),
+                spill_file: None,
+            })),
+        }
+    }
+
+    /// Sets the file of the domain FileSystem the records dropped from the buffer are appended to.
+    pub fn with_spill_file(self, file_manager: FileManagerRef, file_name: &str) -> LogService {
+        self.store.lock().unwrap().spill_file = Some((file_manager, file_name.to_string()));
+        self
+    }
+
+    /// Returns the number of records in the buffer.
+    pub fn len(&self) -> usize {
+        self.store.lock().unwrap().records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the records of the buffer answering a query, the oldest first.
+    pub fn records(&self, query: &LogQuery) -> Vec<LogRecord> {
+        let store = self.store.lock().unwrap();
+        store
+            .records
+            .iter()
+            .filter(|r| query.matches(r))
+            .cloned()
+            .collect()
+    }
+}
+
+impl LogConsumer for LogService {
+    /// The records that cannot be spilled are dropped.
+    fn write_records(&mut self, records: &[LogRecord]) {
+        let mut store = self.store.lock().unwrap();
+        store.records.extend(records.iter().cloned());
+        let excess = store.records.len().saturating_sub(store.capacity);
+        let dropped: Vec<LogRecord> = store.records.drain(..excess).collect();
+        if let (Some((file_manager, file_name)), false) = (&store.spill_file, dropped.is_empty()) {
+            let text: String = dropped.iter().map(|r| format!("{r}\n")).collect();
+            let _ = append(file_manager, file_name, text.as_bytes());
+        }
+    }
+}
+
+/**
+ * Appends data to a file of the domain FileSystem, in place when the
+ * file is on a local file system.
+ */
+fn append(file_manager: &FileManagerRef, file_name: &str, data: &[u8]) -> file_system::Result<()> {
+    let file_manager = file_manager.lock().unwrap();
+    if let Ok((root, name)) = file_manager.local_file(file_name) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(root.join(name))?;
+        file.write_all(data)?;
+        return Ok(());
+    }
+
+    let mut content = match file_manager.exists(file_name)? {
+        true => file_manager.read(file_name)?,
+        false => Vec::new(),
+    };
+    content.extend_from_slice(data);
+    file_manager.write(file_name, &content)
+}
+
+#[tonic::async_trait]
+impl log_service_server::LogService for LogService {
+    /// The records with an unknown level are refused.
+    async fn write_records(
+        &self,
+        request: Request<WriteRecordsRequest>,
+    ) -> Result<Response<WriteRecordsReply>, Status> {
+        let records: Vec<LogRecord> = request
+            .into_inner()
+            .records
+            .into_iter()
+            .map(rpc::log_record_from_wire)
+            .collect::<Option<_>>()
+            .ok_or_else(|| Status::invalid_argument("unknown log level"))?;
+        LogConsumer::write_records(&mut self.clone(), &records);
+        Ok(Response::new(WriteRecordsReply {}))
+    }
+
+    async fn retrieve_records(
+        &self,
+        request: Request<RetrieveRecordsRequest>,
+    ) -> Result<Response<RetrieveRecordsReply>, Status> {
+        let query = rpc::log_query_from_wire(request.into_inner())
+            .ok_or_else(|| Status::invalid_argument("unknown log level"))?;
+        let records = self.records(&query).iter().map(Into::into).collect();
+        Ok(Response::new(RetrieveRecordsReply { records }))
+    }
+}
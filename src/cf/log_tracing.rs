@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::log::{LogConsumer, LogFormat, LogLevelType, LogRecord, LogWriter, Logger};
+
+/**
+ * The target of the tracing events re-emitting log records, which the
+ * LogLayer does not capture back.
+ */
+pub const LOG_RECORD_TARGET: &str = "scars::log_record";
+
+impl From<Level> for LogLevelType {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::ERROR => LogLevelType::EXCEPTION_ERROR,
+            Level::WARN => LogLevelType::USAGE_ERROR,
+            Level::INFO => LogLevelType::ADMINISTRATIVE_EVENT,
+            Level::DEBUG => LogLevelType::PROGRAMMER_DEBUG1,
+            Level::TRACE => LogLevelType::PROGRAMMER_DEBUG2,
+        }
+    }
+}
+
+impl From<LogLevelType> for Level {
+    /**
+     * The alarms and the exception errors are errors, the other errors
+     * warnings, the events and reports infos, the first debug level is
+     * debug and the others trace.
+     */
+    fn from(level: LogLevelType) -> Self {
+        match level {
+            LogLevelType::SECURITY_ALARM
+            | LogLevelType::FAILURE_ALARM
+            | LogLevelType::DEGRADED_ALARM
+            | LogLevelType::EXCEPTION_ERROR => Level::ERROR,
+            LogLevelType::FLOW_CONTROL_ERROR
+            | LogLevelType::RANGE_ERROR
+            | LogLevelType::USAGE_ERROR => Level::WARN,
+            LogLevelType::ADMINISTRATIVE_EVENT | LogLevelType::STATISTIC_REPORT => Level::INFO,
+            LogLevelType::PROGRAMMER_DEBUG1 => Level::DEBUG,
+            _ => Level::TRACE,
+        }
+    }
+}
+
+/**
+ * Tracing layer capturing the events as log records of a logger, so
+ * that component code may use the tracing macros. The message of a
+ * record is the one of the event followed by its other fields, e.g.
+ * "tuned frequency=101.1". The level of the logger applies, and the
+ * events re-emitting log records are left out.
+ */
+#[derive(Debug, Clone)]
+pub struct LogLayer {
+    logger: Logger,
+}
+
+impl LogLayer {
+    pub fn new(logger: Logger) -> LogLayer {
+        LogLayer { logger }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = LogLevelType::from(*metadata.level());
+        if metadata.target() == LOG_RECORD_TARGET || !self.logger.is_enabled(level) {
+            return;
+        }
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        self.logger.log(level, &message.to_string());
+    }
+}
+
+/**
+ * Collects the message and the other fields of an event, the producer
+ * of a re-emitted log record apart.
+ */
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+    producer_id: Option<String>,
+    producer_name: Option<String>,
+    level: Option<LogLevelType>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "producer_id" => self.producer_id = Some(value.to_string()),
+            "producer_name" => self.producer_name = Some(value.to_string()),
+            name => self.fields.push(format!("{name}={value}")),
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "log_level" => self.level = LogLevelType::from_value(value.try_into().unwrap_or(0)),
+            name => self.fields.push(format!("{name}={value}")),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            name => self.fields.push(format!("{name}={value:?}")),
+        }
+    }
+}
+
+impl fmt::Display for MessageVisitor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<&str> = std::iter::once(self.message.as_str())
+            .chain(self.fields.iter().map(String::as_str))
+            .filter(|p| !p.is_empty())
+            .collect();
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/**
+ * Re-emits a log record as a tracing event of the LOG_RECORD_TARGET
+ * target, with the producer_id, producer_name and log_level fields.
+ */
+pub fn emit(record: &LogRecord) {
+    macro_rules! emit {
+        ($level:expr) => {
+            tracing::event!(
+                target: LOG_RECORD_TARGET,
+                $level,
+                producer_id = record.producer_id.as_str(),
+                producer_name = record.producer_name.as_str(),
+                log_level = record.level.value(),
+                "{}",
+                record.message
+            )
+        };
+    }
+    match Level::from(record.level) {
+        Level::ERROR => emit!(Level::ERROR),
+        Level::WARN => emit!(Level::WARN),
+        Level::INFO => emit!(Level::INFO),
+        Level::DEBUG => emit!(Level::DEBUG),
+        Level::TRACE => emit!(Level::TRACE),
+    }
+}
+
+/**
+ * Log consumer re-emitting the records it receives, e.g. from remote
+ * components, as tracing events for the local output.
+ */
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingConsumer;
+
+impl LogConsumer for TracingConsumer {
+    fn write_records(&mut self, records: &[LogRecord]) {
+        records.iter().for_each(emit);
+    }
+}
+
+/**
+ * Tracing layer writing the events to an output in a format, as the log
+ * records of their producer: the producer of a re-emitted record, or
+ * else the target of the event. With the LogLayer capturing the events
+ * of the components and the TracingConsumer re-emitting the remote
+ * records, the whole output of a process comes in the same format.
+ */
+pub struct FormatLayer<W> {
+    writer: Mutex<LogWriter<W>>,
+}
+
+impl<W: Write> FormatLayer<W> {
+    pub fn new(output: W, format: LogFormat) -> FormatLayer<W> {
+        FormatLayer {
+            writer: Mutex::new(LogWriter::new(output, format)),
+        }
+    }
+}
+
+impl<S: Subscriber, W: Write + Send + 'static> Layer<S> for FormatLayer<W> {
+    fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let producer_id = visitor.producer_id.clone();
+        let producer_id = producer_id.as_deref().unwrap_or(metadata.target());
+        let producer_name = visitor.producer_name.clone();
+        let producer_name = producer_name.as_deref().unwrap_or(producer_id);
+        let record = LogRecord::new(
+            producer_id,
+            producer_name,
+            visitor
+                .level
+                .unwrap_or_else(|| LogLevelType::from(*metadata.level())),
+            &visitor.to_string(),
+        );
+        self.writer
+            .lock()
+            .unwrap()
+            .write_records(std::slice::from_ref(&record));
+    }
+}
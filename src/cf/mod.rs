@@ -1,2 +1,64 @@
-pub mod common_types;
+pub mod aggregate_device;
+pub mod application;
+pub mod application_factory;
+pub mod allocation_guard;
+pub mod allocation_manager;
+pub mod blocking_pool;
+pub mod buffer_pool;
+pub mod bulkio;
+pub mod checksum;
+pub mod cli;
+pub use scars_types::common_types;
+pub mod component_registry;
+pub mod connection_manager;
+#[cfg(feature = "corba")]
+pub mod corba_bridge;
+pub mod dashboard;
+#[cfg(feature = "dds")]
+pub mod dds_channel;
+pub mod device;
+pub mod device_manager;
+pub mod device_service;
+pub mod domain_manager;
+pub mod domain_recorder;
+pub mod event_service;
+pub mod events;
+pub mod executable_device;
 pub mod file;
+pub mod file_archive;
+pub mod file_delta;
+pub mod file_manager;
+pub mod file_system;
+pub mod file_system_bench;
+pub mod file_system_service;
+pub mod file_transfer;
+pub mod frontend_tuner;
+pub mod gpp;
+#[cfg(feature = "corba")]
+pub mod iiop_orb;
+pub mod launcher;
+pub mod loadable_device;
+pub mod log;
+pub mod log_file;
+pub mod log_service;
+pub mod log_tracing;
+pub mod profile;
+pub mod property_store;
+pub mod redhawk_import;
+pub mod registrar;
+pub mod remote_device;
+pub mod remote_file_system;
+pub mod resource;
+pub mod retry;
+pub mod rpc;
+pub mod sandbox;
+pub mod scaffold;
+pub mod sim_device;
+#[cfg(feature = "zmq")]
+pub mod zmq_channel;
+#[cfg(feature = "rest-gateway")]
+pub mod rest_gateway;
+#[cfg(feature = "mqtt")]
+pub mod mqtt_bridge;
+#[cfg(feature = "zenoh")]
+pub mod zenoh_channel;
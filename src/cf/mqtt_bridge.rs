@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use rumqttc::{AsyncClient, Event, EventLoop, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::events::{
+    DomainManagementEvent, EventStream, PropertyChangeEvent, SourceCategoryType,
+    DEFAULT_QUEUE_SIZE, IDM_CHANNEL_NAME, ODM_CHANNEL_NAME,
+};
+use super::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use super::rpc::domain_manager::{
+    ConfigureApplicationRequest, CreateApplicationRequest, QueryApplicationRequest,
+    ReleaseApplicationRequest, StartApplicationRequest, StopApplicationRequest,
+};
+use super::rpc::event_channel::event_channel_manager_client::EventChannelManagerClient;
+use super::rpc::event_channel::SubscribeChannelRequest;
+use super::rpc::{properties_from_wire, properties_to_wire};
+
+/**
+ * Convienence enum definition that includes all MQTT bridge errors.
+ */
+#[derive(Error, Debug)]
+pub enum MqttError {
+    /**
+     * This exception indicates the MQTT binding failed to publish or
+     * subscribe.
+     */
+    #[error("ClientError: topic: '{topic}', message: '{message}'.")]
+    ClientError { topic: String, message: String },
+    /**
+     * This exception indicates the bridge failed to subscribe to the
+     * events of the domain.
+     */
+    #[error("DomainError: message: '{message}'.")]
+    DomainError { message: String },
+}
+
+/*
+ * Convienence type definition that includes all MQTT bridge returned errors.
+ */
+pub type Result<T, E = MqttError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines a message published on an MQTT topic.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+}
+
+/**
+ * This interface defines the MQTT client of the bridge as provided by
+ * an MQTT binding: the publication of messages and the subscription to
+ * the topics matching a filter, of the '+' and '#' wildcards.
+ */
+pub trait MqttClientTrait: Send + Sync {
+    /// This operation publishes a message, at least once.
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()>;
+
+    /**
+     * This operation returns a stream of the messages published from
+     * now on to the topics matching a filter, until the stream is
+     * dropped.
+     */
+    fn subscribe(&self, filter: &str) -> Result<EventStream<MqttMessage>>;
+}
+
+/**
+ * Convienence type definition to share an MQTT client.
+ */
+pub type MqttClientRef = Arc<dyn MqttClientTrait>;
+
+/**
+ * MQTT broker of the messages published and subscribed to in the same
+ * process, for the bridges used without a broker, e.g. in tests.
+ */
+#[derive(Clone)]
+pub struct LoopbackBroker {
+    sender: broadcast::Sender<MqttMessage>,
+}
+
+impl Default for LoopbackBroker {
+    fn default() -> LoopbackBroker {
+        LoopbackBroker {
+            sender: broadcast::channel(DEFAULT_QUEUE_SIZE).0,
+        }
+    }
+}
+
+impl LoopbackBroker {
+    pub fn new() -> LoopbackBroker {
+        LoopbackBroker::default()
+    }
+}
+
+impl MqttClientTrait for LoopbackBroker {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()> {
+        let _ = self.sender.send(MqttMessage {
+            topic: topic.to_string(),
+            payload,
+        });
+        Ok(())
+    }
+
+    fn subscribe(&self, filter: &str) -> Result<EventStream<MqttMessage>> {
+        let filter = filter.to_string();
+        let messages = BroadcastStream::new(self.sender.subscribe());
+        Ok(Box::pin(messages.filter_map(move |message| {
+            message.ok().filter(|m| rumqttc::matches(&m.topic, &filter))
+        })))
+    }
+}
+
+/**
+ * MQTT client of a broker reached by rumqttc, its event loop polled in
+ * a task of the runtime, reconnecting to the broker on failure.
+ */
+#[derive(Clone)]
+pub struct RumqttcClient {
+    client: AsyncClient,
+    sender: broadcast::Sender<MqttMessage>,
+}
+
+impl RumqttcClient {
+    /// Connects to the broker, e.g. of MqttOptions::new("scars", "localhost", 1883).
+    pub fn connect(options: rumqttc::MqttOptions) -> RumqttcClient {
+        let (client, event_loop) = AsyncClient::new(options, DEFAULT_QUEUE_SIZE);
+        let sender = broadcast::channel(DEFAULT_QUEUE_SIZE).0;
+        tokio::spawn(poll(event_loop, sender.clone()));
+        RumqttcClient { client, sender }
+    }
+}
+
+/// Dispatches the publications received, waiting before reconnecting after a failure.
+async fn poll(mut event_loop: EventLoop, sender: broadcast::Sender<MqttMessage>) {
+    loop {
+        match event_loop.poll().await {
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                let _ = sender.send(MqttMessage {
+                    topic: publish.topic,
+                    payload: publish.payload.to_vec(),
+                });
+            }
+            Ok(_) => {}
+            Err(_) => tokio::time::sleep(Duration::from_secs(1)).await,
+        }
+    }
+}
+
+impl MqttClientTrait for RumqttcClient {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<()> {
+        self.client
+            .try_publish(topic, QoS::AtLeastOnce, false, payload)
+            .map_err(|e| MqttError::ClientError {
+                topic: topic.to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    fn subscribe(&self, filter: &str) -> Result<EventStream<MqttMessage>> {
+        let messages = BroadcastStream::new(self.sender.subscribe());
+        self.client
+            .try_subscribe(filter, QoS::AtLeastOnce)
+            .map_err(|e| MqttError::ClientError {
+                topic: filter.to_string(),
+                message: e.to_string(),
+            })?;
+        let filter = filter.to_string();
+        Ok(Box::pin(messages.filter_map(move |message| {
+            message.ok().filter(|m| rumqttc::matches(&m.topic, &filter))
+        })))
+    }
+}
+
+/**
+ * This type defines the topics of the bridge, {source_id} standing for
+ * the identifier of the device or application in the topics of their
+ * events.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttTopics {
+    /// The state changes of the devices, of the IDM channel.
+    pub device_state: String,
+    /// The applications added to and removed from the domain, of the ODM channel.
+    pub application: String,
+    /// The changes of the selected properties of an application or component.
+    pub property: String,
+    /// The commands accepted.
+    pub commands: String,
+    /// The replies to the commands.
+    pub replies: String,
+}
+
+impl Default for MqttTopics {
+    fn default() -> MqttTopics {
+        MqttTopics::with_prefix("scars")
+    }
+}
+
+impl MqttTopics {
+    /// Returns the default topics under a prefix, e.g. "fleet/vehicle_7/scars".
+    pub fn with_prefix(prefix: &str) -> MqttTopics {
+        MqttTopics {
+            device_state: format!("{prefix}/devices/{{source_id}}/state"),
+            application: format!("{prefix}/applications/{{source_id}}"),
+            property: format!("{prefix}/properties/{{source_id}}"),
+            commands: format!("{prefix}/commands"),
+            replies: format!("{prefix}/replies"),
+        }
+    }
+}
+
+fn topic(template: &str, source_id: &str) -> String {
+    template.replace("{source_id}", source_id)
+}
+
+/**
+ * This type defines the commands accepted by the bridge, as JSON
+ * documents tagged with the command, e.g. {"command": "start",
+ * "identifier": "DCE:fm_1"}, the property values tagged with their
+ * type, e.g. {"id": "gain", "value": {"Double": 3.0}}.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum MqttCommand {
+    Create {
+        factory: String,
+        name: String,
+        #[serde(default)]
+        properties: Properties,
+    },
+    Start {
+        identifier: String,
+    },
+    Stop {
+        identifier: String,
+    },
+    Release {
+        identifier: String,
+    },
+    Configure {
+        identifier: String,
+        properties: Properties,
+    },
+}
+
+/**
+ * A command along with the id the reply to it is correlated with.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub command: MqttCommand,
+}
+
+/**
+ * The reply to a command: the identifier of the created application,
+ * or the error of the failed command, the commands failing to be
+ * decoded being replied to with the error only.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttReply {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/**
+ * Bridge of the domain events onto the topics of an MQTT broker, for
+ * the vehicle and IoT management buses: the state changes of the
+ * devices and the applications added to and removed from the domain,
+ * as the JSON documents of the event channels, and the changes of the
+ * selected properties, polled at an interval, as property change
+ * events. The commands published on the commands topic are executed on
+ * the DomainManager and replied to on the replies topic. The messages
+ * failing to be published, e.g. the client queue being full, are
+ * dropped.
+ */
+pub struct MqttBridge {
+    client: MqttClientRef,
+    topics: MqttTopics,
+    /// The properties polled, by application or component identifier.
+    properties: Vec<(String, Vec<String>)>,
+    poll_interval: Duration,
+}
+
+impl MqttBridge {
+    pub fn new(client: MqttClientRef) -> MqttBridge {
+        MqttBridge {
+            client,
+            topics: MqttTopics::default(),
+            properties: Vec::new(),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_topics(mut self, topics: MqttTopics) -> MqttBridge {
+        self.topics = topics;
+        self
+    }
+
+    /// Publishes the changes of properties of an application or component, all of them when none.
+    pub fn with_properties(mut self, identifier: &str, ids: &[&str]) -> MqttBridge {
+        let ids = ids.iter().map(|id| id.to_string()).collect();
+        self.properties.push((identifier.to_string(), ids));
+        self
+    }
+
+    /// Sets the interval the selected properties are polled at, 1 second by default.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> MqttBridge {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn topics(&self) -> &MqttTopics {
+        &self.topics
+    }
+
+    /**
+     * Bridges the domain served on a channel until its event channels
+     * end, e.g. on its shutdown.
+     */
+    pub async fn run(self, channel: Channel) -> Result<()> {
+        let domain_error = |e: tonic::Status| MqttError::DomainError {
+            message: e.message().to_string(),
+        };
+        let mut events = EventChannelManagerClient::new(channel.clone());
+        let mut domain = DomainManagerClient::new(channel);
+        let subscribe = |channel: &str, event_types: &[&str]| SubscribeChannelRequest {
+            channel: channel.to_string(),
+            last_sequence: None,
+            source_ids: Vec::new(),
+            event_types: event_types.iter().map(|t| t.to_string()).collect(),
+        };
+        let mut idm = events
+            .subscribe(subscribe(IDM_CHANNEL_NAME, &[]))
+            .await
+            .map_err(domain_error)?
+            .into_inner();
+        let mut odm = events
+            .subscribe(subscribe(
+                ODM_CHANNEL_NAME,
+                &["ObjectAdded", "ObjectRemoved"],
+            ))
+            .await
+            .map_err(domain_error)?
+            .into_inner();
+        let mut commands = self.client.subscribe(&self.topics.commands)?;
+        let mut poll = tokio::time::interval(self.poll_interval);
+        let mut values = HashMap::new();
+
+        loop {
+            tokio::select! {
+                event = idm.message() => match event {
+                    Ok(Some(event)) => {
+                        let topic = topic(&self.topics.device_state, &event.source_id);
+                        let _ = self.client.publish(&topic, event.payload.into_bytes());
+                    }
+                    _ => return Ok(()),
+                },
+                event = odm.message() => match event {
+                    Ok(Some(event)) => {
+                        let application = matches!(
+                            serde_json::from_str(&event.payload),
+                            Ok(DomainManagementEvent::ObjectAdded { source_category: SourceCategoryType::APPLICATION, .. }
+                                | DomainManagementEvent::ObjectRemoved { source_category: SourceCategoryType::APPLICATION, .. })
+                        );
+                        if application {
+                            let topic = topic(&self.topics.application, &event.source_id);
+                            let _ = self.client.publish(&topic, event.payload.into_bytes());
+                        }
+                    }
+                    _ => return Ok(()),
+                },
+                Some(command) = commands.next() => {
+                    let reply = self.execute(&mut domain, &command.payload).await;
+                    if let Ok(reply) = serde_json::to_vec(&reply) {
+                        let _ = self.client.publish(&self.topics.replies, reply);
+                    }
+                }
+                _ = poll.tick(), if !self.properties.is_empty() => {
+                    self.poll(&mut domain, &mut values).await;
+                }
+            }
+        }
+    }
+
+    /// Executes a command, returning its reply.
+    async fn execute(
+        &self,
+        domain: &mut DomainManagerClient<Channel>,
+        payload: &[u8],
+    ) -> MqttReply {
+        let request: MqttRequest = match serde_json::from_slice(payload) {
+            Ok(request) => request,
+            Err(e) => {
+                return MqttReply {
+                    request_id: None,
+                    ok: false,
+                    identifier: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        let outcome = match request.command {
+            MqttCommand::Create {
+                factory,
+                name,
+                properties,
+            } => domain
+                .create_application(CreateApplicationRequest {
+                    factory_identifier: factory,
+                    name,
+                    init_configuration: properties_to_wire(&properties),
+                    device_assignments: Vec::new(),
+                })
+                .await
+                .map(|reply| Some(reply.into_inner().identifier)),
+            MqttCommand::Start { identifier } => domain
+                .start_application(StartApplicationRequest { identifier })
+                .await
+                .map(|_| None),
+            MqttCommand::Stop { identifier } => domain
+                .stop_application(StopApplicationRequest { identifier })
+                .await
+                .map(|_| None),
+            MqttCommand::Release { identifier } => domain
+                .release_application(ReleaseApplicationRequest { identifier })
+                .await
+                .map(|_| None),
+            MqttCommand::Configure {
+                identifier,
+                properties,
+            } => domain
+                .configure_application(ConfigureApplicationRequest {
+                    identifier,
+                    properties: properties_to_wire(&properties),
+                })
+                .await
+                .map(|_| None),
+        };
+        match outcome {
+            Ok(identifier) => MqttReply {
+                request_id: request.request_id,
+                ok: true,
+                identifier,
+                error: None,
+            },
+            Err(status) => MqttReply {
+                request_id: request.request_id,
+                ok: false,
+                identifier: None,
+                error: Some(status.message().to_string()),
+            },
+        }
+    }
+
+    /**
+     * Publishes the selected properties whose value changed since the
+     * previous poll, all of them on the first one. The properties of the
+     * objects failing to be queried are polled again at the next tick.
+     */
+    async fn poll(
+        &self,
+        domain: &mut DomainManagerClient<Channel>,
+        values: &mut HashMap<String, Properties>,
+    ) {
+        for (identifier, ids) in &self.properties {
+            let queried: Properties = ids
+                .iter()
+                .map(|id| DataType::new(id, AnyValue::Boolean(false)))
+                .collect();
+            let request = QueryApplicationRequest {
+                identifier: identifier.clone(),
+                properties: properties_to_wire(&queried),
+            };
+            let Ok(reply) = domain.query_application(request).await else {
+                continue;
+            };
+            let Ok(properties) = properties_from_wire(&reply.into_inner().properties) else {
+                continue;
+            };
+            let previous = values.insert(identifier.clone(), properties.clone());
+            let changed: Properties = properties
+                .into_iter()
+                .filter(|p| {
+                    previous
+                        .as_ref()
+                        .and_then(|previous| previous.iter().find(|v| v.id == p.id))
+                        != Some(p)
+                })
+                .collect();
+            if changed.is_empty() {
+                continue;
+            }
+            let event = PropertyChangeEvent {
+                producer_id: identifier.clone(),
+                source_id: identifier.clone(),
+                properties: changed,
+                time: SystemTime::now(),
+            };
+            if let Ok(payload) = serde_json::to_vec(&event) {
+                let _ = self
+                    .client
+                    .publish(&topic(&self.topics.property, identifier), payload);
+            }
+        }
+    }
+}
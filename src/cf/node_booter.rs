@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+
+use scars::cf::blocking_pool::BlockingPool;
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::profile::dcd::DeviceConfiguration;
+use scars::cf::device_manager::DeviceManager;
+
+const USAGE: &str = "usage: scars-device-manager [--blocking-threads <n>] [--format text|json] \
+    <dcd> <fs root> [domain manager]";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-device-manager",
+    options: &[
+        CliOption::value("--blocking-threads"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &[],
+};
+
+/**
+ * Node booter: runs the DeviceManager of a DCD until SIGTERM, SIGINT or
+ * the shutdown operation. With --blocking-threads, the blocking
+ * operations of the node, its services, files and processes, are run on
+ * at most n threads. The address the
+ * DeviceManager listens on is printed once bound.
+ *
+ * usage: scars-device-manager [--blocking-threads <n>] [--format text|json] <dcd file>
+ *        <file system root> [domain manager endpoint]
+ *        scars-device-manager --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let usage = |e: cli::CliError| format!("{e}\n{USAGE}");
+    let format = OutputFormat::take(&mut args).map_err(usage)?;
+    let blocking_threads = cli::take_count(&mut args, "--blocking-threads").map_err(usage)?;
+    let (dcd, fs_root) = match args.as_slice() {
+        [dcd, fs_root, ..] => (dcd, fs_root),
+        _ => return Err(USAGE.into()),
+    };
+
+    //the pool is sized before the node objects share it
+    let blocking_pool = match blocking_threads {
+        Some(threads) => BlockingPool::init_shared(threads)?,
+        None => BlockingPool::shared(),
+    };
+    let configuration = DeviceConfiguration::from_file(Path::new(dcd))?;
+    let mut manager =
+        DeviceManager::new(configuration, Path::new(fs_root)).with_blocking_pool(blocking_pool);
+    if let Some(domain_manager) = args.get(2) {
+        manager = manager.with_domain_manager(domain_manager);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    cli::print_address(format, "DeviceManager", listener.local_addr()?);
+
+    //shut the node down on termination signals
+    let handle = manager.clone();
+    tokio::spawn(async move {
+        let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+        tokio::select! {
+            _ = sigterm.recv() => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+        handle.shutdown();
+    });
+
+    manager.run(listener).await?;
+    Ok(())
+}
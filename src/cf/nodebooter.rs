@@ -0,0 +1,349 @@
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::JoinHandle;
+
+use scars::cf::blocking_pool::BlockingPool;
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::device_manager::DeviceManager;
+use scars::cf::domain_manager::DomainManager;
+use scars::cf::file_system::FileSystem;
+use scars::cf::profile::dcd::DeviceConfiguration;
+use scars::cf::profile::dmd::DomainManagerConfiguration;
+
+const USAGE: &str = "usage: scars-nodebooter [-D <dmd>] [-d <dcd>] [--sdrroot <dir>] \
+[--domain-endpoint <address>] [--node-endpoint <address>] [--domain-manager <endpoint>] \
+[--state-file <file>] [--pid-file <file>] [--blocking-threads <n>] [--daemon] \
+[--format text|json]";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-nodebooter",
+    options: &[
+        CliOption::value("-D"),
+        CliOption::value("-d"),
+        CliOption::value("--sdrroot"),
+        CliOption::value("--domain-endpoint"),
+        CliOption::value("--node-endpoint"),
+        CliOption::value("--domain-manager"),
+        CliOption::value("--state-file"),
+        CliOption::value("--pid-file"),
+        CliOption::value("--blocking-threads"),
+        CliOption::flag("--daemon"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &[],
+};
+
+/// The options of the booter, given on the command line.
+#[derive(Default)]
+struct Options {
+    dmd: Option<String>,
+    dcd: Option<String>,
+    sdrroot: Option<PathBuf>,
+    domain_endpoint: Option<String>,
+    node_endpoint: Option<String>,
+    domain_manager: Option<String>,
+    state_file: Option<PathBuf>,
+    pid_file: Option<PathBuf>,
+    blocking_threads: Option<usize>,
+    daemon: bool,
+    format: OutputFormat,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Options, Box<dyn std::error::Error>> {
+        let mut options = Options::default();
+        let mut args = args.to_vec();
+        options.blocking_threads = cli::take_count(&mut args, "--blocking-threads")
+            .map_err(|e| format!("{e}\n{USAGE}"))?;
+        let mut args = args.iter();
+        while let Some(option) = args.next() {
+            if option == "--daemon" {
+                options.daemon = true;
+                continue;
+            }
+            let value = args.next().ok_or(USAGE)?.clone();
+            match option.as_str() {
+                "-D" => options.dmd = Some(value),
+                "-d" => options.dcd = Some(value),
+                "--sdrroot" => options.sdrroot = Some(PathBuf::from(value)),
+                "--domain-endpoint" => options.domain_endpoint = Some(value),
+                "--node-endpoint" => options.node_endpoint = Some(value),
+                "--domain-manager" => options.domain_manager = Some(value),
+                "--state-file" => options.state_file = Some(PathBuf::from(value)),
+                "--pid-file" => options.pid_file = Some(PathBuf::from(value)),
+                "--format" => {
+                    options.format =
+                        OutputFormat::from_name(&value).map_err(|e| format!("{e}\n{USAGE}"))?
+                }
+                _ => return Err(USAGE.into()),
+            }
+        }
+        if options.dmd.is_none() && options.dcd.is_none() {
+            return Err(USAGE.into());
+        }
+        if options.dmd.is_some() && options.domain_manager.is_some() {
+            return Err("--domain-manager only applies to a DeviceManager booted alone".into());
+        }
+        Ok(options)
+    }
+
+    /// The SDR root, from the option, $SDRROOT or the current directory.
+    fn sdrroot(&self) -> PathBuf {
+        self.sdrroot
+            .clone()
+            .or_else(|| std::env::var_os("SDRROOT").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /**
+     * Returns the path of a descriptor: relative to the directory of
+     * the SDR root holding the descriptors of its kind, "dom" or "dev",
+     * when found there, as given otherwise.
+     */
+    fn descriptor(&self, directory: &str, file_name: &str) -> PathBuf {
+        let in_sdrroot = self
+            .sdrroot()
+            .join(directory)
+            .join(file_name.trim_start_matches('/'));
+        if in_sdrroot.exists() {
+            in_sdrroot
+        } else {
+            PathBuf::from(file_name)
+        }
+    }
+}
+
+/**
+ * The pid file of the booter, locked as long as the booter runs and
+ * removed when dropped.
+ */
+struct PidFile {
+    path: PathBuf,
+    _file: File,
+}
+
+impl PidFile {
+    /**
+     * Creates the pid file, failing while another booter holds it. The
+     * lock being released along with its process, the pid file left by
+     * a killed booter is taken over.
+     */
+    fn create(path: &Path) -> std::io::Result<PidFile> {
+        use std::os::fd::AsRawFd;
+        use std::os::unix::fs::MetadataExt;
+
+        loop {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+                let error = std::io::Error::last_os_error();
+                if error.kind() == ErrorKind::WouldBlock {
+                    let message = format!("{} is held by a running booter", path.display());
+                    return Err(std::io::Error::new(ErrorKind::AlreadyExists, message));
+                }
+                return Err(error);
+            }
+            //the booter holding the file may have removed it meanwhile
+            let locked = file.metadata()?;
+            match std::fs::metadata(path) {
+                Ok(current) if (current.dev(), current.ino()) == (locked.dev(), locked.ino()) => {}
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+            file.set_len(0)?;
+            writeln!(file, "{}", std::process::id())?;
+            return Ok(PidFile {
+                path: path.to_path_buf(),
+                _file: file,
+            });
+        }
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/**
+ * Node booter: boots the DomainManager of a DMD, the DeviceManager of a
+ * DCD, or both, the DeviceManager then registering with the booted
+ * DomainManager, until SIGTERM, SIGINT or the shutdown operations. The
+ * DeviceManager is shut down before the DomainManager.
+ *
+ * The descriptors are looked up in the dom and dev directories of the
+ * SDR root, the dom one being mounted as /dom in the domain FileManager.
+ * The endpoints the managers listen on are printed, one per line, once
+ * bound, as JSON documents with --format json. As a daemon the booter
+ * runs detached in its own session, printing its pid. The pid file is
+ * held by a single booter at once. With --blocking-threads, the blocking
+ * operations of both managers, their services, files, allocations and
+ * processes, are run on at most n threads.
+ *
+ * usage: scars-nodebooter [-D <dmd>] [-d <dcd>] [options]
+ *        scars-nodebooter --completions bash|zsh|fish
+ */
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let options = Options::parse(&args)?;
+
+    if options.daemon {
+        return daemonize(&args, options.format);
+    }
+    tokio::runtime::Runtime::new()?.block_on(boot(options))
+}
+
+/**
+ * Re-runs the booter detached from the terminal, as the leader of a new
+ * session with its standard streams closed, and prints the pid of the
+ * daemon.
+ */
+fn daemonize(args: &[String], format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::process::CommandExt;
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(args.iter().filter(|a| *a != "--daemon"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    //setsid is async-signal-safe, as required between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let child = command.spawn()?;
+    match format {
+        OutputFormat::Text => println!("{}", child.id()),
+        OutputFormat::Json => cli::print_json(&serde_json::json!({ "pid": child.id() }))?,
+    }
+    Ok(())
+}
+
+async fn boot(options: Options) -> Result<(), Box<dyn std::error::Error>> {
+    //the signals are handled before the pid file tells whom to signal
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    let _pid_file = options
+        .pid_file
+        .as_deref()
+        .map(PidFile::create)
+        .transpose()?;
+    let sdrroot = options.sdrroot();
+    //the pool is sized before the managers share it
+    let blocking_pool = match options.blocking_threads {
+        Some(threads) => BlockingPool::init_shared(threads)?,
+        None => BlockingPool::shared(),
+    };
+
+    let mut domain = None;
+    if let Some(dmd) = &options.dmd {
+        let configuration = DomainManagerConfiguration::from_file(&options.descriptor("dom", dmd))?;
+        let mut manager = DomainManager::new(&configuration.id, &configuration.name)
+            .with_blocking_pool(blocking_pool.clone());
+        if let Some(state_file) = &options.state_file {
+            manager = manager.with_persistence(state_file)?;
+        }
+        let dom = sdrroot.join("dom");
+        if dom.is_dir() {
+            manager.file_manager().lock().unwrap().mount(
+                "/dom",
+                Arc::new(FileSystem::new(&dom).with_blocking_pool(blocking_pool.clone())),
+            )?;
+        }
+        let endpoint = options.domain_endpoint.as_deref().unwrap_or("127.0.0.1:0");
+        let listener = TcpListener::bind(endpoint).await?;
+        let address = listener.local_addr()?;
+        cli::print_address(options.format, "DomainManager", address);
+        domain = Some((manager, listener, format!("http://{address}")));
+    }
+
+    let mut node = None;
+    if let Some(dcd) = &options.dcd {
+        let configuration = DeviceConfiguration::from_file(&options.descriptor("dev", dcd))?;
+        let mut manager = DeviceManager::new(configuration, &sdrroot.join("dev"))
+            .with_blocking_pool(blocking_pool.clone());
+        let domain_manager = match &domain {
+            Some((_, _, endpoint)) => Some(endpoint),
+            None => options.domain_manager.as_ref(),
+        };
+        if let Some(domain_manager) = domain_manager {
+            manager = manager.with_domain_manager(domain_manager);
+        }
+        let endpoint = options.node_endpoint.as_deref().unwrap_or("127.0.0.1:0");
+        let listener = TcpListener::bind(endpoint).await?;
+        cli::print_address(options.format, "DeviceManager", listener.local_addr()?);
+        node = Some((manager, listener));
+    }
+
+    //the domain serves before the node registers with it
+    let (domain, mut domain_task) = match domain {
+        Some((manager, listener, _)) => {
+            let task = tokio::spawn(manager.clone().run(listener));
+            (Some(manager), Some(task))
+        }
+        None => (None, None),
+    };
+    let (node, mut node_task) = match node {
+        Some((manager, listener)) => {
+            let task = tokio::spawn(manager.clone().run(listener));
+            (Some(manager), Some(task))
+        }
+        None => (None, None),
+    };
+
+    //on termination signals, or once a manager stopped on its shutdown
+    //operation, the node is shut down before the domain
+    let outcome = tokio::select! {
+        _ = sigterm.recv() => Ok(()),
+        _ = sigint.recv() => Ok(()),
+        served = served(&mut node_task) => served,
+        served = served(&mut domain_task) => served,
+    };
+    if let (Some(manager), true) = (&node, node_task.is_some()) {
+        manager.shutdown();
+        served(&mut node_task).await?;
+    }
+    if let (Some(manager), true) = (&domain, domain_task.is_some()) {
+        manager.shutdown();
+        served(&mut domain_task).await?;
+    }
+    outcome
+}
+
+/**
+ * Waits for the task of a manager to stop serving, the task being taken
+ * once stopped. Never completes without a task.
+ */
+async fn served<E: std::error::Error + 'static>(
+    task: &mut Option<JoinHandle<Result<(), E>>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(handle) = task.as_mut() else {
+        return std::future::pending().await;
+    };
+    let served = handle.await;
+    *task = None;
+    Ok(served??)
+}
@@ -0,0 +1,194 @@
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+
+use super::super::file_system::FileSystemTrait;
+use super::dcd::DeviceConfiguration;
+use super::prf::PropertiesDescriptor;
+use super::sad::SoftwareAssembly;
+use super::scd::SoftwareComponent;
+use super::spd::SoftPkg;
+use super::{self as profile, read_file};
+
+/// The number of descriptors a cache holds by default.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/**
+ * This trait is implemented by the descriptors a ProfileCache holds.
+ */
+pub trait Descriptor: Any + Send + Sync + Sized {
+    /// Parses the descriptor, the file name only qualifying the errors.
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self>;
+}
+
+impl Descriptor for SoftPkg {
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self> {
+        SoftPkg::parse(xml, file_name)
+    }
+}
+
+impl Descriptor for PropertiesDescriptor {
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self> {
+        PropertiesDescriptor::parse(xml, file_name)
+    }
+}
+
+impl Descriptor for SoftwareComponent {
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self> {
+        SoftwareComponent::parse(xml, file_name)
+    }
+}
+
+impl Descriptor for SoftwareAssembly {
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self> {
+        SoftwareAssembly::parse(xml, file_name)
+    }
+}
+
+impl Descriptor for DeviceConfiguration {
+    fn parse_descriptor(xml: &str, file_name: &str) -> profile::Result<Self> {
+        DeviceConfiguration::parse(xml, file_name)
+    }
+}
+
+/**
+ * A parsed descriptor along with the checksum of the document it was
+ * parsed from.
+ */
+struct Entry {
+    checksum: u64,
+    descriptor: Arc<dyn Any + Send + Sync>,
+    /// The tick of the last use, the least recently used entry being evicted first.
+    used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    entries: HashMap<String, Entry>,
+    tick: u64,
+}
+
+/**
+ * Cache of the parsed descriptors of a file system, keyed by file name.
+ * The document is still read through the file system on every load, an
+ * entry being reused only while the checksum of the document is
+ * unchanged, so the parsing is saved but never a modification missed.
+ * The least recently used entries are evicted beyond the capacity.
+ * Clones share the same entries.
+ */
+#[derive(Clone)]
+pub struct ProfileCache {
+    entries: Arc<Mutex<Entries>>,
+    capacity: usize,
+}
+
+impl Default for ProfileCache {
+    fn default() -> Self {
+        ProfileCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl ProfileCache {
+    pub fn new(capacity: usize) -> ProfileCache {
+        ProfileCache {
+            entries: Arc::default(),
+            capacity,
+        }
+    }
+
+    /// Returns the number of descriptors held.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+     * Returns the descriptor of a file of the file system, parsing it
+     * unless cached for the same document. A file cached as another
+     * type of descriptor is parsed again.
+     */
+    pub fn load<D: Descriptor>(
+        &self,
+        file_system: &dyn FileSystemTrait,
+        file_name: &str,
+    ) -> profile::Result<Arc<D>> {
+        let xml = read_file(file_system, file_name)?;
+        let checksum = checksum(&xml);
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.tick += 1;
+            let tick = entries.tick;
+            if let Some(entry) = entries.entries.get_mut(file_name) {
+                if entry.checksum == checksum {
+                    if let Ok(descriptor) = entry.descriptor.clone().downcast::<D>() {
+                        entry.used = tick;
+                        return Ok(descriptor);
+                    }
+                }
+            }
+        }
+
+        //parse without holding the lock
+        let descriptor = Arc::new(D::parse_descriptor(&xml, file_name)?);
+        let mut entries = self.entries.lock().unwrap();
+        let used = entries.tick;
+        entries.entries.insert(
+            file_name.to_string(),
+            Entry {
+                checksum,
+                descriptor: descriptor.clone(),
+                used,
+            },
+        );
+        while entries.entries.len() > self.capacity {
+            let Some(oldest) = entries
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.used)
+                .map(|(name, _)| name.clone())
+            else {
+                break;
+            };
+            entries.entries.remove(&oldest);
+        }
+        Ok(descriptor)
+    }
+
+    /// Removes the descriptor of a file, returning whether it was cached.
+    pub fn invalidate(&self, file_name: &str) -> bool {
+        self.entries
+            .lock()
+            .unwrap()
+            .entries
+            .remove(file_name)
+            .is_some()
+    }
+
+    /// Removes every descriptor.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().entries.clear();
+    }
+}
+
+impl fmt::Debug for ProfileCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProfileCache")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+/// Returns the checksum of a document.
+fn checksum(xml: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    xml.hash(&mut hasher);
+    hasher.finish()
+}
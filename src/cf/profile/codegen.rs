@@ -0,0 +1,638 @@
+use std::fmt::Write;
+
+use super::super::common_types::AnyValue;
+use super::prf::{
+    PropertiesDescriptor, Property, PropertyType, Range, Simple, SimpleSequence, Struct,
+};
+
+/// The Rust keywords, the fields named after them getting a trailing underscore.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/**
+ * Generates the Rust source of a struct holding the properties of a PRF
+ * with their Rust types: the properties without default value are
+ * optional, the ranges and enumerations are enforced by the setters,
+ * the struct properties have a struct of their own. The struct converts
+ * from and to Properties and configures itself as the configure
+ * operation does, so that the component code never matches property
+ * ids. The source name only appears in the header comment.
+ */
+pub fn properties_struct(prf: &PropertiesDescriptor, type_name: &str, source: &str) -> String {
+    let mut code = String::new();
+    let _ = writeln!(
+        code,
+        "// Generated by scars-codegen from {source}, do not edit."
+    );
+    code.push('\n');
+    let fields: Vec<Field> = prf
+        .properties
+        .iter()
+        .map(|p| Field::new(p, false))
+        .collect();
+    let checked = fields.iter().any(|f| !f.checks.is_empty());
+    let any_value = match checked || fields.iter().any(|f| f.verified) {
+        true => "AnyValue, ",
+        false => "",
+    };
+    let _ = writeln!(
+        code,
+        "use scars::cf::common_types::{{{any_value}DataType, Properties, PropertyValue}};"
+    );
+    code.push_str("use scars::cf::resource::ResourceError;\n");
+
+    //the struct types of the struct and structsequence properties
+    for p in &prf.properties {
+        match p {
+            Property::Struct(s) => write_struct_type(&mut code, s),
+            Property::StructSequence(s) => write_struct_type(&mut code, &s.structure),
+            _ => {}
+        }
+    }
+
+    code.push('\n');
+    match &prf.description {
+        Some(description) => {
+            let _ = writeln!(code, "/// {description}");
+        }
+        None => {
+            let _ = writeln!(code, "/// The properties of {source}.");
+        }
+    }
+    code.push_str("#[derive(Debug, Clone, PartialEq");
+    if fields.iter().all(Field::has_default_default) {
+        code.push_str(", Default");
+    }
+    let _ = writeln!(code, ")]\npub struct {type_name} {{");
+    for field in &fields {
+        let _ = writeln!(code, "    {}: {},", field.name, field.field_type());
+    }
+    code.push_str("}\n");
+    if !fields.iter().all(Field::has_default_default) {
+        write_default(&mut code, type_name, &fields);
+    }
+
+    //the ids, the accessors and the configuration
+    let _ = writeln!(code, "\nimpl {type_name} {{");
+    for field in &fields {
+        let _ = writeln!(
+            code,
+            "    pub const {}_ID: &'static str = {:?};",
+            field.name.trim_end_matches('_').to_uppercase(),
+            field.id
+        );
+    }
+    for field in &fields {
+        code.push('\n');
+        write_accessors(&mut code, field);
+    }
+    let _ = write!(
+        code,
+        r#"
+    /**
+     * Configures the writable properties as the configure operation
+     * does: none being set is an InvalidConfiguration, some not being set
+     * a PartialConfiguration.
+     */
+    pub fn configure(&mut self, properties: &Properties) -> Result<(), ResourceError> {{
+        self.apply(properties, true)
+    }}
+
+    fn apply(&mut self, properties: &Properties, configuring: bool) -> Result<(), ResourceError> {{
+        let mut invalid_properties = Properties::new();
+        for p in properties {{
+            let applied = match p.id.as_str() {{
+"#
+    );
+    for field in &fields {
+        let guard = match field.writable {
+            true => String::new(),
+            false => " if !configuring".to_string(),
+        };
+        let _ = writeln!(
+            code,
+            "                {:?}{guard} => PropertyValue::from_any(&p.value).map(|v| self.set_{}(v)),",
+            field.id,
+            field.name.trim_end_matches('_')
+        );
+    }
+    code.push_str(
+        r#"                _ => None,
+            };
+            if !matches!(applied, Some(Ok(()))) {
+                invalid_properties.push(p.clone());
+            }
+        }
+
+        if invalid_properties.is_empty() {
+            return Ok(());
+        }
+        if invalid_properties.len() == properties.len() {
+            return Err(ResourceError::InvalidConfiguration {
+                message: "invalid properties".to_string(),
+                invalid_properties,
+            });
+        }
+        Err(ResourceError::PartialConfiguration { invalid_properties })
+    }
+}
+"#,
+    );
+
+    //the conversions
+    let _ = write!(
+        code,
+        "\nimpl From<&{type_name}> for Properties {{\n    fn from(p: &{type_name}) -> Properties {{\n        let mut properties = Properties::new();\n"
+    );
+    for field in &fields {
+        write_push(&mut code, "properties", "p", field, "        ");
+    }
+    code.push_str("        properties\n    }\n}\n");
+    let _ = write!(
+        code,
+        r#"
+impl TryFrom<&Properties> for {type_name} {{
+    type Error = ResourceError;
+
+    /// The properties not given keep their default value.
+    fn try_from(properties: &Properties) -> Result<Self, ResourceError> {{
+        let mut p = {type_name}::default();
+        p.apply(properties, false)?;
+        Ok(p)
+    }}
+}}
+"#
+    );
+
+    if checked {
+        code.push_str(
+            r#"
+fn invalid(id: &str, value: AnyValue, message: &str) -> ResourceError {
+    ResourceError::InvalidConfiguration {
+        message: format!("'{id}' {message}"),
+        invalid_properties: vec![DataType::new(id, value)],
+    }
+}
+"#,
+        );
+    }
+    code
+}
+
+/**
+ * A property as a field of a generated struct.
+ */
+struct Field {
+    id: String,
+    name: String,
+    /// The Rust type of the value.
+    value_type: String,
+    optional: bool,
+    writable: bool,
+    /// The default value, when given, as a Rust expression.
+    default: Option<String>,
+    /// The conditions a value 'v' shall not meet, along with the message.
+    checks: Vec<(String, String)>,
+    /// Tells whether the value is a struct with its own verification.
+    verified: bool,
+    description: Option<String>,
+}
+
+impl Field {
+    /// Returns the field of a property, the fields of a struct dropping the struct prefix of their id.
+    fn new(property: &Property, in_struct: bool) -> Field {
+        let source = property.name().unwrap_or(property.id());
+        let source = match in_struct {
+            true => source.rsplit("::").next().unwrap_or(source),
+            false => source,
+        };
+        let (value_type, default, checks, verified, description) = match property {
+            Property::Simple(s) => (
+                rust_type(s.value_type).to_string(),
+                s.value.as_ref().map(|v| literal(v, s.value_type)),
+                simple_checks(s),
+                false,
+                s.description.clone(),
+            ),
+            Property::SimpleSequence(s) => (
+                format!("Vec<{}>", rust_type(s.value_type)),
+                s.values
+                    .as_ref()
+                    .map(|v| literal(&AnyValue::Sequence(v.clone()), s.value_type)),
+                sequence_checks(s),
+                false,
+                s.description.clone(),
+            ),
+            Property::Struct(s) => (
+                struct_name(s),
+                Some(format!("{}::default()", struct_name(s))),
+                Vec::new(),
+                true,
+                s.description.clone(),
+            ),
+            Property::StructSequence(s) => {
+                let values: Vec<String> = s
+                    .values
+                    .iter()
+                    .map(|v| struct_literal(v, &s.structure))
+                    .collect();
+                (
+                    format!("Vec<{}>", struct_name(&s.structure)),
+                    Some(format!("vec![{}]", values.join(", "))),
+                    Vec::new(),
+                    true,
+                    s.description.clone(),
+                )
+            }
+        };
+        Field {
+            id: property.id().to_string(),
+            name: field_name(source),
+            value_type,
+            optional: default.is_none(),
+            writable: property.mode().is_writable(),
+            default,
+            checks,
+            verified,
+            description,
+        }
+    }
+
+    fn field_type(&self) -> String {
+        match self.optional {
+            true => format!("Option<{}>", self.value_type),
+            false => self.value_type.clone(),
+        }
+    }
+
+    fn default_value(&self) -> String {
+        self.default.clone().unwrap_or_else(|| "None".to_string())
+    }
+
+    /// Tells whether the default value is the one of the Default trait.
+    fn has_default_default(&self) -> bool {
+        let default = self.default_value();
+        ["None", "false", "0", "0.0", "vec![]", "\"\".to_string()"].contains(&default.as_str())
+            || default == format!("{}::default()", self.value_type)
+    }
+}
+
+/// Writes the type of a struct property, with its verification and conversion.
+fn write_struct_type(code: &mut String, structure: &Struct) {
+    let name = struct_name(structure);
+    let fields: Vec<Field> = structure
+        .fields
+        .iter()
+        .map(|f| Field::new(f, true))
+        .collect();
+
+    code.push('\n');
+    let _ = writeln!(
+        code,
+        "/// {}",
+        structure
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("The value of the '{}' struct.", structure.id))
+    );
+    code.push_str("#[derive(Debug, Clone, PartialEq");
+    if fields.iter().all(Field::has_default_default) {
+        code.push_str(", Default");
+    }
+    let _ = writeln!(code, ")]\npub struct {name} {{");
+    for field in &fields {
+        let _ = writeln!(code, "    pub {}: {},", field.name, field.field_type());
+    }
+    code.push_str("}\n");
+    if !fields.iter().all(Field::has_default_default) {
+        write_default(code, &name, &fields);
+    }
+
+    let _ = write!(
+        code,
+        "\nimpl {name} {{\n    /// Verifies the fields are within their range and enumerations.\n    pub fn verify(&self) -> Result<(), String> {{\n"
+    );
+    for field in fields.iter().filter(|f| !f.checks.is_empty()) {
+        let binding = match field.optional {
+            true => format!("if let Some(v) = &self.{} {{", field.name),
+            false => format!("{{\n            let v = &self.{};", field.name),
+        };
+        let _ = writeln!(code, "        {binding}");
+        for (condition, message) in &field.checks {
+            let _ = writeln!(
+                code,
+                "            if {condition} {{\n                return Err({:?}.to_string());\n            }}",
+                format!("'{}' {message}", field.id)
+            );
+        }
+        code.push_str("        }\n");
+    }
+    code.push_str("        Ok(())\n    }\n}\n");
+
+    let _ = write!(
+        code,
+        "\nimpl PropertyValue for {name} {{\n    fn to_any(&self) -> AnyValue {{\n        let mut fields = Properties::new();\n"
+    );
+    for field in &fields {
+        write_push(code, "fields", "self", field, "        ");
+    }
+    let _ = write!(
+        code,
+        "        AnyValue::Struct(fields)\n    }}\n\n    fn from_any(value: &AnyValue) -> Option<Self> {{\n        let AnyValue::Struct(fields) = value else {{\n            return None;\n        }};\n        let mut s = {name}::default();\n        for field in fields {{\n            match field.id.as_str() {{\n"
+    );
+    for field in &fields {
+        let value = match field.optional {
+            true => "Some(PropertyValue::from_any(&field.value)?)",
+            false => "PropertyValue::from_any(&field.value)?",
+        };
+        let _ = writeln!(
+            code,
+            "                {:?} => s.{} = {value},",
+            field.id, field.name
+        );
+    }
+    code.push_str(
+        "                _ => return None,\n            }\n        }\n        Some(s)\n    }\n}\n",
+    );
+}
+
+/// Writes the Default implementation of a struct.
+fn write_default(code: &mut String, name: &str, fields: &[Field]) {
+    let _ = write!(
+        code,
+        "\nimpl Default for {name} {{\n    fn default() -> Self {{\n        {name} {{\n"
+    );
+    for field in fields {
+        let _ = writeln!(
+            code,
+            "            {}: {},",
+            field.name,
+            field.default_value()
+        );
+    }
+    code.push_str("        }\n    }\n}\n");
+}
+
+/// Writes the getter and the verifying setter of a field.
+fn write_accessors(code: &mut String, field: &Field) {
+    let name = field.name.trim_end_matches('_');
+    let doc = field
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("The '{}' property.", field.id));
+    let (getter_type, getter) = match field.optional {
+        true => (
+            format!("Option<&{}>", field.value_type),
+            format!("self.{}.as_ref()", field.name),
+        ),
+        false => (
+            format!("&{}", field.value_type),
+            format!("&self.{}", field.name),
+        ),
+    };
+    let _ = writeln!(
+        code,
+        "    /// {doc}\n    pub fn {}(&self) -> {getter_type} {{\n        {getter}\n    }}\n",
+        field.name
+    );
+
+    let _ = writeln!(
+        code,
+        "    pub fn set_{name}(&mut self, value: {}) -> Result<(), ResourceError> {{",
+        field.value_type
+    );
+    if !field.checks.is_empty() {
+        code.push_str("        let v = &value;\n");
+    }
+    for (condition, message) in &field.checks {
+        let _ = writeln!(
+            code,
+            "        if {condition} {{\n            return Err(invalid({:?}, value.to_any(), {message:?}));\n        }}",
+            field.id
+        );
+    }
+    if field.verified {
+        let verify = match field.value_type.starts_with("Vec<") {
+            true => "value.iter().try_for_each(|v| v.verify())",
+            false => "value.verify()",
+        };
+        let _ = writeln!(
+            code,
+            "        if let Err(message) = {verify} {{\n            return Err(ResourceError::InvalidConfiguration {{\n                message,\n                invalid_properties: vec![DataType::new({:?}, value.to_any())],\n            }});\n        }}",
+            field.id
+        );
+    }
+    let value = match field.optional {
+        true => "Some(value)",
+        false => "value",
+    };
+    let _ = writeln!(
+        code,
+        "        self.{} = {value};\n        Ok(())\n    }}",
+        field.name
+    );
+}
+
+/// Writes the push of the value of a field, when given, to properties.
+fn write_push(code: &mut String, properties: &str, owner: &str, field: &Field, indent: &str) {
+    match field.optional {
+        true => {
+            let _ = writeln!(
+                code,
+                "{indent}if let Some(v) = &{owner}.{} {{\n{indent}    {properties}.push(DataType::new({:?}, v.to_any()));\n{indent}}}",
+                field.name, field.id
+            );
+        }
+        false => {
+            let _ = writeln!(
+                code,
+                "{indent}{properties}.push(DataType::new({:?}, {owner}.{}.to_any()));",
+                field.id, field.name
+            );
+        }
+    }
+}
+
+/// Returns the conditions a simple value 'v' shall not meet.
+fn simple_checks(simple: &Simple) -> Vec<(String, String)> {
+    let mut checks = Vec::new();
+    if let Some(range) = &simple.range {
+        checks.extend(range_check(range, simple.value_type, "v"));
+    }
+    if !simple.enumerations.is_empty() {
+        let values: Vec<String> = simple
+            .enumerations
+            .iter()
+            .map(|e| match simple.value_type {
+                PropertyType::STRING | PropertyType::OBJREF => format!("{:?}", e.value.to_string()),
+                value_type => literal(&e.value, value_type),
+            })
+            .collect();
+        let value = match simple.value_type {
+            PropertyType::STRING | PropertyType::OBJREF => "&v.as_str()",
+            _ => "v",
+        };
+        checks.push((
+            format!("![{}].contains({value})", values.join(", ")),
+            "is not one of the enumerations".to_string(),
+        ));
+    }
+    checks
+}
+
+/// Returns the conditions a sequence value 'v' shall not meet.
+fn sequence_checks(sequence: &SimpleSequence) -> Vec<(String, String)> {
+    let Some(range) = &sequence.range else {
+        return Vec::new();
+    };
+    range_check(range, sequence.value_type, "e")
+        .into_iter()
+        .map(|(condition, message)| (format!("v.iter().any(|e| {condition})"), message))
+        .collect()
+}
+
+/// Returns the condition of a value out of a numeric range.
+fn range_check(range: &Range, value_type: PropertyType, value: &str) -> Option<(String, String)> {
+    if matches!(
+        value_type,
+        PropertyType::BOOLEAN | PropertyType::CHAR | PropertyType::OBJREF | PropertyType::STRING
+    ) {
+        return None;
+    }
+    Some((
+        format!(
+            "!({}..={}).contains({value})",
+            literal(&range.min, value_type),
+            literal(&range.max, value_type)
+        ),
+        format!("is out of range [{}, {}]", range.min, range.max),
+    ))
+}
+
+/// Returns the Rust type of a simple type.
+fn rust_type(value_type: PropertyType) -> &'static str {
+    match value_type {
+        PropertyType::BOOLEAN => "bool",
+        PropertyType::CHAR => "char",
+        PropertyType::DOUBLE => "f64",
+        PropertyType::FLOAT => "f32",
+        PropertyType::SHORT => "i16",
+        PropertyType::LONG => "i32",
+        PropertyType::OBJREF | PropertyType::STRING => "String",
+        PropertyType::OCTET => "u8",
+        PropertyType::ULONG => "u32",
+        PropertyType::USHORT => "u16",
+        PropertyType::LONGLONG => "i64",
+        PropertyType::ULONGLONG => "u64",
+    }
+}
+
+/// Returns a value of a simple type, or a sequence of them, as a Rust expression.
+fn literal(value: &AnyValue, value_type: PropertyType) -> String {
+    match value {
+        AnyValue::Double(v) => float_literal(*v, "f64"),
+        AnyValue::Float(v) => float_literal(*v as f64, "f32"),
+        AnyValue::String(v) if value_type == PropertyType::CHAR => {
+            format!("{:?}", v.chars().next().unwrap_or_default())
+        }
+        AnyValue::String(v) => format!("{v:?}.to_string()"),
+        AnyValue::Sequence(values) => {
+            let values: Vec<String> = values.iter().map(|v| literal(v, value_type)).collect();
+            format!("vec![{}]", values.join(", "))
+        }
+        value => value.to_string(),
+    }
+}
+
+/// Returns a floating point value as a Rust expression.
+fn float_literal(value: f64, float_type: &str) -> String {
+    match value {
+        v if v.is_nan() => format!("{float_type}::NAN"),
+        v if v == f64::INFINITY => format!("{float_type}::INFINITY"),
+        v if v == f64::NEG_INFINITY => format!("{float_type}::NEG_INFINITY"),
+        v if float_type == "f32" => format!("{:?}", v as f32),
+        v => format!("{v:?}"),
+    }
+}
+
+/// Returns a value of a structsequence as a Rust expression.
+fn struct_literal(value: &AnyValue, structure: &Struct) -> String {
+    let name = struct_name(structure);
+    let AnyValue::Struct(values) = value else {
+        return format!("{name}::default()");
+    };
+    let fields: Vec<Field> = structure
+        .fields
+        .iter()
+        .map(|f| Field::new(f, true))
+        .collect();
+    let mut assigned = Vec::new();
+    for (field, property) in fields.iter().zip(&structure.fields) {
+        let Some(v) = values.iter().find(|v| v.id == field.id) else {
+            continue;
+        };
+        let value_type = match property {
+            Property::Simple(s) => s.value_type,
+            Property::SimpleSequence(s) => s.value_type,
+            _ => PropertyType::STRING,
+        };
+        let value = literal(&v.value, value_type);
+        assigned.push(match field.optional {
+            true => format!("{}: Some({value})", field.name),
+            false => format!("{}: {value}", field.name),
+        });
+    }
+    if assigned.len() < fields.len() {
+        assigned.push(format!("..{name}::default()"));
+    }
+    format!("{name} {{ {} }}", assigned.join(", "))
+}
+
+/// Returns the name of the type of a struct property.
+fn struct_name(structure: &Struct) -> String {
+    let source = structure.name.as_deref().unwrap_or(&structure.id);
+    let mut name: String = source
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or(String::new(), |c| {
+                c.to_ascii_uppercase().to_string() + chars.as_str()
+            })
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'S');
+    }
+    name
+}
+
+/// Returns the snake case field name of a property name or id.
+fn field_name(source: &str) -> String {
+    let mut name = String::new();
+    let mut previous: Option<char> = None;
+    for c in source.chars() {
+        if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase()) {
+            name.push('_');
+        }
+        match c.is_ascii_alphanumeric() {
+            true => name.push(c.to_ascii_lowercase()),
+            false if !name.ends_with('_') => name.push('_'),
+            false => {}
+        }
+        previous = Some(c);
+    }
+    let mut name = name.trim_matches('_').to_string();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert_str(0, "p_");
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use super::writer::{self, Element};
+use super::{
+    self as profile, attribute, child, component_files, local_file, placements, ComponentFile,
+    ComponentPlacement,
+};
+
+/**
+ * Device Configuration Descriptor: the devices and services a
+ * DeviceManager launches on its node and the domain it joins.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceConfiguration {
+    pub id: String,
+    pub name: String,
+    pub device_manager_softpkg: Option<String>,
+    pub component_files: Vec<ComponentFile>,
+    pub placements: Vec<ComponentPlacement>,
+    /// The reference of the DomainManager to register with.
+    pub domain_manager: Option<String>,
+}
+
+impl DeviceConfiguration {
+    /// Parses the DCD file.
+    pub fn from_file(path: &Path) -> profile::Result<DeviceConfiguration> {
+        let xml = profile::read_profile(path)?;
+        DeviceConfiguration::parse(&xml, &path.display().to_string())
+    }
+
+    /// Parses a DCD document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<DeviceConfiguration> {
+        let document = profile::parse_document(xml, "deviceconfiguration", file_name)?;
+        let root = document.root_element();
+
+        let component_files = component_files(root, file_name)?;
+        let placements = placements(root, &component_files, file_name)?;
+
+        let domain_manager = child(root, "domainmanager").and_then(|dm| {
+            child(dm, "namingservice")
+                .and_then(|n| n.attribute("name"))
+                .or_else(|| child(dm, "stringifiedobjectref").and_then(|n| n.text()))
+                .map(|r| r.trim().to_string())
+        });
+
+        Ok(DeviceConfiguration {
+            id: attribute(root, "id", file_name)?,
+            name: attribute(root, "name", file_name)?,
+            device_manager_softpkg: child(root, "devicemanagersoftpkg")
+                .map(|n| local_file(n, file_name))
+                .transpose()?,
+            component_files,
+            placements,
+            domain_manager,
+        })
+    }
+
+    /// Returns the component file of a placement.
+    pub fn component_file(&self, placement: &ComponentPlacement) -> Option<&ComponentFile> {
+        self.component_files
+            .iter()
+            .find(|f| f.id == placement.file_ref)
+    }
+
+    /// Returns the DCD document describing the configuration.
+    pub fn to_xml(&self) -> String {
+        let placements = self.placements.iter().map(writer::placement).collect();
+        Element::new("deviceconfiguration")
+            .with_attribute("id", &self.id)
+            .with_attribute("name", &self.name)
+            .with_optional_child(
+                self.device_manager_softpkg
+                    .as_ref()
+                    .map(|f| writer::local_file("devicemanagersoftpkg", f)),
+            )
+            .with_container(
+                "componentfiles",
+                writer::component_files(&self.component_files),
+            )
+            .with_container("partitioning", placements)
+            .with_optional_child(self.domain_manager.as_ref().map(|d| {
+                Element::new("domainmanager")
+                    .with_child(Element::new("namingservice").with_attribute("name", d))
+            }))
+            .to_document()
+    }
+}
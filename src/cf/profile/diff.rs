@@ -0,0 +1,406 @@
+use std::fmt;
+
+use roxmltree::{Document, ParsingOptions};
+use serde::Serialize;
+
+use super::super::common_types::AnyValue;
+use super::super::file_system::FileSystemTrait;
+use super::cache::ProfileCache;
+use super::prf::{PropertiesDescriptor, Property};
+use super::sad::{Connection, SoftwareAssembly};
+use super::spd::SoftPkg;
+use super::{self as profile, invalid, resolve_file_name};
+
+/// The values of properties by id, none for those without a value, in document order.
+type Values = Vec<(String, Option<AnyValue>)>;
+
+/**
+ * This type describes a difference between two versions of a
+ * descriptor. The properties belong to a component instantiation of an
+ * assembly, or to the descriptor itself for an SPD or a PRF.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change")]
+pub enum Change {
+    ComponentAdded {
+        id: String,
+    },
+    ComponentRemoved {
+        id: String,
+    },
+    ImplementationAdded {
+        id: String,
+    },
+    ImplementationRemoved {
+        id: String,
+    },
+    PropertyAdded {
+        component_id: Option<String>,
+        property_id: String,
+        value: Option<AnyValue>,
+    },
+    PropertyRemoved {
+        component_id: Option<String>,
+        property_id: String,
+    },
+    /// The default value of a property, or its componentproperties override, changed.
+    PropertyChanged {
+        component_id: Option<String>,
+        property_id: String,
+        old: Option<AnyValue>,
+        new: Option<AnyValue>,
+    },
+    /// A connection, identified by its id or else by its uses port.
+    ConnectionAdded {
+        id: String,
+    },
+    ConnectionRemoved {
+        id: String,
+    },
+    /// The ports a connection joins changed.
+    ConnectionChanged {
+        id: String,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let property = |component_id: &Option<String>, property_id: &str| match component_id {
+            Some(component_id) => format!("property '{property_id}' of '{component_id}'"),
+            None => format!("property '{property_id}'"),
+        };
+        let value = |value: &Option<AnyValue>| match value {
+            Some(value) => value.to_string(),
+            None => "none".to_string(),
+        };
+        match self {
+            Change::ComponentAdded { id } => write!(f, "component '{id}' added"),
+            Change::ComponentRemoved { id } => write!(f, "component '{id}' removed"),
+            Change::ImplementationAdded { id } => write!(f, "implementation '{id}' added"),
+            Change::ImplementationRemoved { id } => write!(f, "implementation '{id}' removed"),
+            Change::PropertyAdded {
+                component_id,
+                property_id,
+                value: added,
+            } => write!(
+                f,
+                "{} added: {}",
+                property(component_id, property_id),
+                value(added)
+            ),
+            Change::PropertyRemoved {
+                component_id,
+                property_id,
+            } => write!(f, "{} removed", property(component_id, property_id)),
+            Change::PropertyChanged {
+                component_id,
+                property_id,
+                old,
+                new,
+            } => write!(
+                f,
+                "{} changed: {} -> {}",
+                property(component_id, property_id),
+                value(old),
+                value(new)
+            ),
+            Change::ConnectionAdded { id } => write!(f, "connection '{id}' added"),
+            Change::ConnectionRemoved { id } => write!(f, "connection '{id}' removed"),
+            Change::ConnectionChanged { id } => write!(f, "connection '{id}' changed"),
+        }
+    }
+}
+
+/**
+ * Compares two versions of a SAD, an SPD or a PRF, the kind of
+ * descriptor being told by the root element. The components, the
+ * connections and the property values of the instantiations are
+ * compared for a SAD, a property value being the default of the PRFs of
+ * its SPD overridden by the componentproperties. The implementations and
+ * the default values of the PRFs are compared for an SPD, the default
+ * values for a PRF. The removals are reported in the order of the old
+ * version, the additions and changes in the order of the new one.
+ */
+pub fn diff(
+    file_system: &dyn FileSystemTrait,
+    old: &str,
+    new: &str,
+) -> profile::Result<Vec<Change>> {
+    let old_xml = profile::read_file(file_system, old)?;
+    let new_xml = profile::read_file(file_system, new)?;
+    let old_root = root_element(&old_xml, old)?;
+    let new_root = root_element(&new_xml, new)?;
+    if old_root != new_root {
+        return Err(invalid(
+            new,
+            &format!("<{new_root}> is not comparable with <{old_root}>"),
+        ));
+    }
+
+    let cache = ProfileCache::default();
+    let mut changes = Vec::new();
+    match old_root.as_str() {
+        "softwareassembly" => {
+            let old_assembly = SoftwareAssembly::parse(&old_xml, old)?;
+            let new_assembly = SoftwareAssembly::parse(&new_xml, new)?;
+            diff_assemblies(
+                file_system,
+                &cache,
+                &old_assembly,
+                old,
+                &new_assembly,
+                new,
+                &mut changes,
+            )?;
+        }
+        "softpkg" => {
+            let old_softpkg = SoftPkg::parse(&old_xml, old)?;
+            let new_softpkg = SoftPkg::parse(&new_xml, new)?;
+            let ids = |softpkg: &SoftPkg| {
+                softpkg
+                    .implementations
+                    .iter()
+                    .map(|i| i.id.clone())
+                    .collect::<Vec<_>>()
+            };
+            let (removed, added) = removed_added(&ids(&old_softpkg), &ids(&new_softpkg));
+            changes.extend(
+                removed
+                    .into_iter()
+                    .map(|id| Change::ImplementationRemoved { id }),
+            );
+            changes.extend(
+                added
+                    .into_iter()
+                    .map(|id| Change::ImplementationAdded { id }),
+            );
+            diff_values(
+                None,
+                &values(&softpkg_properties(file_system, &cache, &old_softpkg, old)?),
+                &values(&softpkg_properties(file_system, &cache, &new_softpkg, new)?),
+                &mut changes,
+            );
+        }
+        "properties" => {
+            let old_prf = PropertiesDescriptor::parse(&old_xml, old)?;
+            let new_prf = PropertiesDescriptor::parse(&new_xml, new)?;
+            diff_values(
+                None,
+                &values(&old_prf.properties),
+                &values(&new_prf.properties),
+                &mut changes,
+            );
+        }
+        root => {
+            return Err(invalid(
+                old,
+                &format!("<{root}> is not a SAD, an SPD or a PRF"),
+            ))
+        }
+    }
+    Ok(changes)
+}
+
+/// Returns the name of the root element of a descriptor.
+fn root_element(xml: &str, file_name: &str) -> profile::Result<String> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options)
+        .map_err(|e| invalid(file_name, &e.to_string()))?;
+    Ok(document.root_element().tag_name().name().to_string())
+}
+
+/// Compares the instantiations and the connections of two assemblies.
+fn diff_assemblies(
+    file_system: &dyn FileSystemTrait,
+    cache: &ProfileCache,
+    old: &SoftwareAssembly,
+    old_file_name: &str,
+    new: &SoftwareAssembly,
+    new_file_name: &str,
+    changes: &mut Vec<Change>,
+) -> profile::Result<()> {
+    let instantiation_ids = |assembly: &SoftwareAssembly| {
+        assembly
+            .placements
+            .iter()
+            .flat_map(|p| &p.instantiations)
+            .map(|i| i.id.clone())
+            .collect::<Vec<_>>()
+    };
+    let old_ids = instantiation_ids(old);
+    let new_ids = instantiation_ids(new);
+    let (removed, added) = removed_added(&old_ids, &new_ids);
+    changes.extend(
+        removed
+            .into_iter()
+            .map(|id| Change::ComponentRemoved { id }),
+    );
+    changes.extend(added.into_iter().map(|id| Change::ComponentAdded { id }));
+
+    //the property values of the instantiations in both versions
+    for id in new_ids.iter().filter(|id| old_ids.contains(id)) {
+        let old_values = instantiation_values(file_system, cache, old, old_file_name, id)?;
+        let new_values = instantiation_values(file_system, cache, new, new_file_name, id)?;
+        diff_values(Some(id), &old_values, &new_values, changes);
+    }
+
+    let old_connections: Vec<(String, &Connection)> = old
+        .connections
+        .iter()
+        .map(|c| (connection_id(c), c))
+        .collect();
+    for (id, _) in &old_connections {
+        if !new.connections.iter().any(|c| connection_id(c) == *id) {
+            changes.push(Change::ConnectionRemoved { id: id.clone() });
+        }
+    }
+    for connection in &new.connections {
+        let id = connection_id(connection);
+        match old_connections.iter().find(|(old_id, _)| *old_id == id) {
+            None => changes.push(Change::ConnectionAdded { id }),
+            Some((_, old)) if *old != connection => changes.push(Change::ConnectionChanged { id }),
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Returns the id of a connection, that of its uses port when not given.
+fn connection_id(connection: &Connection) -> String {
+    connection.id.clone().unwrap_or_else(|| {
+        format!(
+            "{}/{}",
+            connection.uses_port.component_ref, connection.uses_port.identifier
+        )
+    })
+}
+
+/**
+ * Returns the property values of a component instantiation: the
+ * defaults of the PRFs of its SPD, overridden by its componentproperties
+ * parsed after the property types. The instantiations of nested
+ * assemblies only have their componentproperties.
+ */
+fn instantiation_values(
+    file_system: &dyn FileSystemTrait,
+    cache: &ProfileCache,
+    assembly: &SoftwareAssembly,
+    file_name: &str,
+    instantiation_id: &str,
+) -> profile::Result<Values> {
+    let Some((placement, instantiation)) = assembly.placements.iter().find_map(|p| {
+        p.instantiations
+            .iter()
+            .find(|i| i.id == instantiation_id)
+            .map(|i| (p, i))
+    }) else {
+        return Ok(Values::new());
+    };
+    let file = assembly
+        .component_files
+        .iter()
+        .find(|f| f.id == placement.file_ref);
+
+    let mut properties = Vec::new();
+    if let Some(file) = file.filter(|f| f.file_type != "SAD") {
+        let spd_file_name = resolve_file_name(file_name, &file.local_file);
+        let softpkg = cache.load::<SoftPkg>(file_system, &spd_file_name)?;
+        properties = softpkg_properties(file_system, cache, &softpkg, &spd_file_name)?;
+    }
+
+    let mut values = values(&properties);
+    for property in &instantiation.properties {
+        let value = match (
+            properties.iter().find(|p| p.id() == property.id),
+            &property.value,
+        ) {
+            (Some(Property::Simple(simple)), AnyValue::String(text)) => simple
+                .value_type
+                .parse_value(text)
+                .unwrap_or_else(|| property.value.clone()),
+            (_, value) => value.clone(),
+        };
+        match values.iter_mut().find(|(id, _)| *id == property.id) {
+            Some((_, v)) => *v = Some(value),
+            None => values.push((property.id.clone(), Some(value))),
+        }
+    }
+    Ok(values)
+}
+
+/**
+ * Returns the properties of the PRFs of an SPD and of its
+ * implementations, a property of a later PRF replacing the one with the
+ * same id.
+ */
+fn softpkg_properties(
+    file_system: &dyn FileSystemTrait,
+    cache: &ProfileCache,
+    softpkg: &SoftPkg,
+    spd_file_name: &str,
+) -> profile::Result<Vec<Property>> {
+    let mut properties: Vec<Property> = Vec::new();
+    let property_files = softpkg.property_file.iter().chain(
+        softpkg
+            .implementations
+            .iter()
+            .flat_map(|i| &i.property_file),
+    );
+    for property_file in property_files {
+        let property_file = resolve_file_name(spd_file_name, property_file);
+        let prf = cache.load::<PropertiesDescriptor>(file_system, &property_file)?;
+        for property in &prf.properties {
+            properties.retain(|p| p.id() != property.id());
+            properties.push(property.clone());
+        }
+    }
+    Ok(properties)
+}
+
+/// Returns the default values of properties.
+fn values(properties: &[Property]) -> Values {
+    properties
+        .iter()
+        .map(|p| (p.id().to_string(), p.value()))
+        .collect()
+}
+
+/// Compares the values of the properties of two versions.
+fn diff_values(component_id: Option<&str>, old: &Values, new: &Values, changes: &mut Vec<Change>) {
+    let component_id = component_id.map(str::to_string);
+    for (id, _) in old {
+        if !new.iter().any(|(new_id, _)| new_id == id) {
+            changes.push(Change::PropertyRemoved {
+                component_id: component_id.clone(),
+                property_id: id.clone(),
+            });
+        }
+    }
+    for (id, value) in new {
+        match old.iter().find(|(old_id, _)| old_id == id) {
+            None => changes.push(Change::PropertyAdded {
+                component_id: component_id.clone(),
+                property_id: id.clone(),
+                value: value.clone(),
+            }),
+            Some((_, old_value)) if old_value != value => changes.push(Change::PropertyChanged {
+                component_id: component_id.clone(),
+                property_id: id.clone(),
+                old: old_value.clone(),
+                new: value.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+}
+
+/// Returns the ids removed from the old list, then the ids added to the new one.
+fn removed_added(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    (
+        old.iter().filter(|id| !new.contains(id)).cloned().collect(),
+        new.iter().filter(|id| !old.contains(id)).cloned().collect(),
+    )
+}
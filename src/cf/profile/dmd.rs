@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use super::writer::{self, Element};
+use super::{self as profile, attribute, child, child_text, local_file};
+
+/**
+ * Domain Manager Configuration Descriptor: the identity of the domain
+ * a DomainManager runs.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainManagerConfiguration {
+    pub id: String,
+    /// The name of the domain.
+    pub name: String,
+    pub description: Option<String>,
+    pub domain_manager_softpkg: Option<String>,
+}
+
+impl DomainManagerConfiguration {
+    /// Parses the DMD file.
+    pub fn from_file(path: &Path) -> profile::Result<DomainManagerConfiguration> {
+        let xml = profile::read_profile(path)?;
+        DomainManagerConfiguration::parse(&xml, &path.display().to_string())
+    }
+
+    /// Parses a DMD document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<DomainManagerConfiguration> {
+        let document = profile::parse_document(xml, "domainmanagerconfiguration", file_name)?;
+        let root = document.root_element();
+
+        Ok(DomainManagerConfiguration {
+            id: attribute(root, "id", file_name)?,
+            name: attribute(root, "name", file_name)?,
+            description: child_text(root, "description"),
+            domain_manager_softpkg: child(root, "domainmanagersoftpkg")
+                .map(|n| local_file(n, file_name))
+                .transpose()?,
+        })
+    }
+
+    /// Returns the DMD document describing the configuration.
+    pub fn to_xml(&self) -> String {
+        Element::new("domainmanagerconfiguration")
+            .with_attribute("id", &self.id)
+            .with_attribute("name", &self.name)
+            .with_text_child("description", self.description.as_ref())
+            .with_optional_child(
+                self.domain_manager_softpkg
+                    .as_ref()
+                    .map(|f| writer::local_file("domainmanagersoftpkg", f)),
+            )
+            .to_document()
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use super::super::file_system::FileSystemTrait;
+use super::cache::{Descriptor, ProfileCache};
+use super::prf::PropertiesDescriptor;
+use super::sad::{ConnectionTarget, PortKind, PortReference, SoftwareAssembly};
+use super::scd::SoftwareComponent;
+use super::spd::SoftPkg;
+use super::{self as profile, resolve_file_name};
+
+/// The repository id of the interfaces every object supports.
+const OBJECT_REPOSITORY_ID: &str = "IDL:omg.org/CORBA/Object:1.0";
+
+/**
+ * This type describes an inconsistency between the descriptors of an
+ * assembly, which would make its deployment fail.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    /// The descriptor the inconsistency is found in.
+    pub file_name: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.file_name, self.message)
+    }
+}
+
+/**
+ * The descriptors of a component of the assembly, the missing ones
+ * having been reported already.
+ */
+struct Component {
+    spd_file_name: String,
+    scd: Option<SoftwareComponent>,
+    prf: Option<PropertiesDescriptor>,
+}
+
+/**
+ * Checks a SAD against the descriptors it references, nested assemblies
+ * included: every componentfile resolves to a valid descriptor, the
+ * assembly controller is given, the start orders are unique, the
+ * connected ports exist with compatible interfaces, the external ports
+ * and properties exist. An error is only returned when the SAD itself
+ * is unreadable or invalid.
+ */
+pub fn lint(
+    file_system: &dyn FileSystemTrait,
+    software_profile: &str,
+) -> profile::Result<Vec<Issue>> {
+    let assembly = SoftwareAssembly::parse(
+        &profile::read_file(file_system, software_profile)?,
+        software_profile,
+    )?;
+    let mut linter = Linter {
+        file_system,
+        cache: ProfileCache::default(),
+        issues: Vec::new(),
+        linting: vec![software_profile.to_string()],
+    };
+    linter.lint_assembly(&assembly, software_profile);
+    Ok(linter.issues)
+}
+
+struct Linter<'a> {
+    file_system: &'a dyn FileSystemTrait,
+    cache: ProfileCache,
+    issues: Vec<Issue>,
+    /// The assemblies being linted, to report those nesting themselves.
+    linting: Vec<String>,
+}
+
+impl Linter<'_> {
+    fn issue(&mut self, file_name: &str, message: String) {
+        self.issues.push(Issue {
+            file_name: file_name.to_string(),
+            message,
+        });
+    }
+
+    /// Returns the descriptor of a file, reporting it when unreadable or invalid.
+    fn load<D: Descriptor + Clone>(&mut self, referrer: &str, file_name: &str) -> Option<D> {
+        match self.cache.load::<D>(self.file_system, file_name) {
+            Ok(descriptor) => Some(descriptor.as_ref().clone()),
+            Err(e) => {
+                self.issue(referrer, e.to_string());
+                None
+            }
+        }
+    }
+
+    fn lint_assembly(&mut self, assembly: &SoftwareAssembly, file_name: &str) {
+        //the component files, indexed by id
+        let mut components = HashMap::new();
+        for file in &assembly.component_files {
+            let local_file = resolve_file_name(file_name, &file.local_file);
+            if file.file_type == "SAD" {
+                if self.linting.contains(&local_file) {
+                    self.issue(file_name, format!("assembly '{}' nests itself", file.id));
+                } else if let Some(nested) = self.load::<SoftwareAssembly>(file_name, &local_file) {
+                    self.linting.push(local_file.clone());
+                    self.lint_assembly(&nested, &local_file);
+                    self.linting.pop();
+                }
+                continue;
+            }
+            if let Some(component) = self.load_component(file_name, &local_file) {
+                components.insert(file.id.clone(), component);
+            }
+        }
+        let component_of = |id: &str| {
+            assembly
+                .placements
+                .iter()
+                .find(|p| p.instantiations.iter().any(|i| i.id == id))
+                .and_then(|p| components.get(&p.file_ref))
+        };
+
+        if assembly.assembly_controller.is_none() {
+            self.issue(file_name, "no assembly controller".to_string());
+        }
+
+        //the start orders
+        let mut start_orders: HashMap<u32, &str> = HashMap::new();
+        for instantiation in assembly.placements.iter().flat_map(|p| &p.instantiations) {
+            let Some(start_order) = instantiation.start_order else {
+                continue;
+            };
+            if let Some(other) = start_orders.insert(start_order, &instantiation.id) {
+                self.issue(
+                    file_name,
+                    format!(
+                        "'{}' and '{other}' have the same start order {start_order}",
+                        instantiation.id
+                    ),
+                );
+            }
+        }
+
+        //the component properties
+        for instantiation in assembly.placements.iter().flat_map(|p| &p.instantiations) {
+            let Some(prf) = component_of(&instantiation.id).and_then(|c| c.prf.as_ref()) else {
+                continue;
+            };
+            for property in &instantiation.properties {
+                if prf.property(&property.id).is_none() {
+                    self.issue(
+                        file_name,
+                        format!(
+                            "'{}' has no property '{}' to override",
+                            instantiation.id, property.id
+                        ),
+                    );
+                }
+            }
+        }
+
+        //the connections
+        for connection in &assembly.connections {
+            let name = connection
+                .id
+                .clone()
+                .unwrap_or_else(|| connection.uses_port.identifier.clone());
+            let uses = component_of(&connection.uses_port.component_ref)
+                .map(|c| port(c, &connection.uses_port, PortKind::USES));
+            let uses = match uses {
+                Some(Err(message)) => {
+                    self.issue(file_name, format!("connection '{name}': {message}"));
+                    continue;
+                }
+                Some(Ok(uses)) => uses,
+                None => None,
+            };
+            let provided = match &connection.target {
+                ConnectionTarget::ProvidesPort(p) => component_of(&p.component_ref)
+                    .map(|c| port(c, p, PortKind::PROVIDES).map(|r| r.map(|r| (r, c)))),
+                ConnectionTarget::SupportedInterface(p) => component_of(&p.component_ref)
+                    .map(|c| supported_interface(c, &p.identifier).map(|r| r.map(|r| (r, c)))),
+                ConnectionTarget::FindBy(_) => None,
+            };
+            let provided = match provided {
+                Some(Err(message)) => {
+                    self.issue(file_name, format!("connection '{name}': {message}"));
+                    continue;
+                }
+                Some(Ok(provided)) => provided,
+                None => None,
+            };
+            if let (Some(uses), Some((provides, component))) = (uses, provided) {
+                if !inherits(component, &provides, &uses) {
+                    self.issue(
+                        file_name,
+                        format!("connection '{name}': '{provides}' is not a '{uses}'"),
+                    );
+                }
+            }
+        }
+
+        //the external ports and properties
+        for external in &assembly.external_ports {
+            if let Some(Err(message)) = component_of(&external.port.component_ref)
+                .map(|c| port(c, &external.port, external.kind))
+            {
+                self.issue(
+                    file_name,
+                    format!("external port '{}': {message}", external.name),
+                );
+            }
+        }
+        for external in &assembly.external_properties {
+            let prf = component_of(&external.component_ref).and_then(|c| c.prf.as_ref());
+            if prf.is_some_and(|prf| prf.property(&external.property_id).is_none()) {
+                self.issue(
+                    file_name,
+                    format!(
+                        "external property '{}': '{}' has no property '{}'",
+                        external.id, external.component_ref, external.property_id
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Returns the descriptors of a component, reporting those unreadable or invalid.
+    fn load_component(&mut self, referrer: &str, spd_file_name: &str) -> Option<Component> {
+        let softpkg = self.load::<SoftPkg>(referrer, spd_file_name)?;
+        let scd = softpkg.descriptor.as_ref().and_then(|scd| {
+            let scd = resolve_file_name(spd_file_name, scd);
+            self.load::<SoftwareComponent>(spd_file_name, &scd)
+        });
+
+        //the properties of the implementations are merged with those of the component
+        let mut prf: Option<PropertiesDescriptor> = None;
+        let property_files = softpkg.property_file.iter().chain(
+            softpkg
+                .implementations
+                .iter()
+                .flat_map(|i| &i.property_file),
+        );
+        for property_file in property_files {
+            let property_file = resolve_file_name(spd_file_name, property_file);
+            if let Some(loaded) = self.load::<PropertiesDescriptor>(spd_file_name, &property_file) {
+                match &mut prf {
+                    Some(prf) => prf.properties.extend(loaded.properties),
+                    None => prf = Some(loaded),
+                }
+            }
+        }
+        Some(Component {
+            spd_file_name: spd_file_name.to_string(),
+            scd,
+            prf,
+        })
+    }
+}
+
+/**
+ * Returns the repository id of a port of a component, none when the
+ * component has no SCD, an error message when it has no such port.
+ */
+fn port(
+    component: &Component,
+    port: &PortReference,
+    kind: PortKind,
+) -> Result<Option<String>, String> {
+    let Some(scd) = &component.scd else {
+        return Ok(None);
+    };
+    match scd.port(&port.identifier) {
+        Some(p) if p.kind == kind => Ok(Some(p.repository_id.clone())),
+        Some(_) => Err(format!(
+            "'{}' of '{}' is not a {} port",
+            port.identifier,
+            port.component_ref,
+            match kind {
+                PortKind::USES => "uses",
+                PortKind::PROVIDES => "provides",
+            }
+        )),
+        None => Err(format!(
+            "'{}' has no port '{}' in {}",
+            port.component_ref, port.identifier, component.spd_file_name
+        )),
+    }
+}
+
+/**
+ * Returns the interface of a component supporting a repository id, none
+ * when the component has no SCD, an error message when unsupported.
+ */
+fn supported_interface(
+    component: &Component,
+    repository_id: &str,
+) -> Result<Option<String>, String> {
+    let Some(scd) = &component.scd else {
+        return Ok(None);
+    };
+    let supported = scd
+        .supported_interfaces
+        .iter()
+        .map(|s| s.repository_id.as_str())
+        .chain(scd.repository_id.as_deref())
+        .any(|r| inherits(component, r, repository_id));
+    match supported {
+        true => Ok(Some(repository_id.to_string())),
+        false => Err(format!(
+            "{} does not support '{repository_id}'",
+            component.spd_file_name
+        )),
+    }
+}
+
+/// Tells whether an interface of a component is, or inherits, another one.
+fn inherits(component: &Component, repository_id: &str, base: &str) -> bool {
+    if repository_id == base || base == OBJECT_REPOSITORY_ID {
+        return true;
+    }
+    let mut visited = vec![repository_id];
+    let mut index = 0;
+    while let Some(current) = visited.get(index).copied() {
+        index += 1;
+        let interface = component
+            .scd
+            .iter()
+            .flat_map(|scd| &scd.interfaces)
+            .find(|i| i.repository_id == current);
+        for inherited in interface.iter().flat_map(|i| &i.inherits) {
+            if inherited == base {
+                return true;
+            }
+            if !visited.contains(&inherited.as_str()) {
+                visited.push(inherited);
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,315 @@
+use std::path::Path;
+
+use roxmltree::{Document, Node, ParsingOptions};
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, Properties};
+use super::file_system::FileSystemTrait;
+
+pub mod cache;
+pub mod codegen;
+pub mod dcd;
+pub mod diff;
+pub mod dmd;
+pub mod lint;
+pub mod plan;
+pub mod prf;
+pub mod sad;
+pub mod scd;
+#[cfg(feature = "schema-validation")]
+pub mod schema;
+pub mod spd;
+pub mod validate;
+mod writer;
+
+/**
+ * Convienence enum definition that includes all profile parsing errors.
+ */
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    /**
+     * This exception indicates that the profile cannot be read.
+     */
+    #[error("ProfileNotFound: file: '{file_name}', msg: '{message}'.")]
+    ProfileNotFound { file_name: String, message: String },
+    /**
+     * This exception indicates that the profile is not well formed or
+     * misses mandatory elements or attributes.
+     */
+    #[error("InvalidProfile: file: '{file_name}', msg: '{message}'.")]
+    InvalidProfile { file_name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all profile parsing returned errors.
+ */
+pub type Result<T, E = ProfileError> = anyhow::Result<T, E>;
+
+/// Reads a profile file.
+pub fn read_profile(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| ProfileError::ProfileNotFound {
+        file_name: path.display().to_string(),
+        message: e.to_string(),
+    })
+}
+
+/// Reads a profile file from a file system of the domain.
+pub fn read_file(file_system: &dyn FileSystemTrait, file_name: &str) -> Result<String> {
+    let data = file_system
+        .read(file_name)
+        .map_err(|e| ProfileError::ProfileNotFound {
+            file_name: file_name.to_string(),
+            message: e.to_string(),
+        })?;
+    String::from_utf8(data).map_err(|e| invalid(file_name, &e.to_string()))
+}
+
+/**
+ * Returns the pathname of a localfile referenced by a profile: absolute
+ * names are kept, relative ones are relative to the directory of the
+ * referencing profile.
+ */
+pub fn resolve_file_name(profile_file_name: &str, local_file: &str) -> String {
+    let joined = match profile_file_name.rsplit_once('/') {
+        _ if local_file.starts_with('/') => local_file.to_string(),
+        Some((directory, _)) => format!("{directory}/{local_file}"),
+        None => local_file.to_string(),
+    };
+
+    //collapse the '.' and '..' segments, which stop at the root
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in joined.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    let resolved = segments.join("/");
+    if joined.starts_with('/') {
+        format!("/{resolved}")
+    } else {
+        resolved
+    }
+}
+
+/**
+ * This type describes a file referenced by an assembly or a
+ * configuration, usually the SPD of a component.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentFile {
+    pub id: String,
+    pub file_type: String,
+    pub local_file: String,
+}
+
+/**
+ * This type describes an instance of a component to be launched, with
+ * the property values overriding the ones of its profile.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentInstantiation {
+    pub id: String,
+    pub usage_name: Option<String>,
+    /// The position of the component in the start sequence of its application.
+    pub start_order: Option<u32>,
+    pub properties: Properties,
+}
+
+/**
+ * This type describes the instances of a component file, optionally as
+ * parts of an aggregate device.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentPlacement {
+    pub file_ref: String,
+    pub composite_part_of: Option<String>,
+    pub instantiations: Vec<ComponentInstantiation>,
+}
+
+/**
+ * Parses a profile document, verifying its root element and, with the
+ * schema-validation feature, its conformance to the bundled SCA DTD. The
+ * DOCTYPE declaration is allowed, the DTD it refers to not being loaded.
+ * The file name only qualifies the errors.
+ */
+pub(crate) fn parse_document<'a>(
+    xml: &'a str,
+    root: &str,
+    file_name: &str,
+) -> Result<Document<'a>> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options)
+        .map_err(|e| invalid(file_name, &e.to_string()))?;
+    let name = document.root_element().tag_name().name();
+    if name != root {
+        return Err(invalid(
+            file_name,
+            &format!("root element is <{name}>, not <{root}>"),
+        ));
+    }
+    #[cfg(feature = "schema-validation")]
+    schema::validate(&document, file_name)?;
+    Ok(document)
+}
+
+/// Returns the error for an invalid profile.
+pub(crate) fn invalid(file_name: &str, message: &str) -> ProfileError {
+    ProfileError::InvalidProfile {
+        file_name: file_name.to_string(),
+        message: message.to_string(),
+    }
+}
+
+/// Returns the first child element with the tag name.
+pub(crate) fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+/// Returns the child elements with the tag name.
+pub(crate) fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    tag: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children().filter(move |n| n.has_tag_name(tag))
+}
+
+/// Returns the text of the first child element with the tag name.
+pub(crate) fn child_text(node: Node, tag: &str) -> Option<String> {
+    child(node, tag)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+}
+
+/// Returns a mandatory attribute of an element.
+pub(crate) fn attribute(node: Node, name: &str, file_name: &str) -> Result<String> {
+    node.attribute(name).map(str::to_string).ok_or_else(|| {
+        let position = node.document().text_pos_at(node.range().start);
+        invalid(
+            file_name,
+            &format!(
+                "<{}> at line {} misses the '{name}' attribute",
+                node.tag_name().name(),
+                position.row
+            ),
+        )
+    })
+}
+
+/// Returns the name of the localfile child of an element.
+pub(crate) fn local_file(node: Node, file_name: &str) -> Result<String> {
+    let local_file = child(node, "localfile").ok_or_else(|| {
+        invalid(
+            file_name,
+            &format!("<{}> misses <localfile>", node.tag_name().name()),
+        )
+    })?;
+    attribute(local_file, "name", file_name)
+}
+
+/// Parses the componentfiles element of an assembly or a configuration.
+pub(crate) fn component_files(root: Node, file_name: &str) -> Result<Vec<ComponentFile>> {
+    let Some(files) = child(root, "componentfiles") else {
+        return Ok(Vec::new());
+    };
+    children(files, "componentfile")
+        .map(|f| {
+            Ok(ComponentFile {
+                id: attribute(f, "id", file_name)?,
+                file_type: attribute(f, "type", file_name)?,
+                local_file: local_file(f, file_name)?,
+            })
+        })
+        .collect()
+}
+
+/**
+ * Parses the componentplacement elements of the partitioning of an
+ * assembly or a configuration, those of its hostcollocation elements
+ * included, verifying they reference declared component files.
+ */
+pub(crate) fn placements(
+    root: Node,
+    component_files: &[ComponentFile],
+    file_name: &str,
+) -> Result<Vec<ComponentPlacement>> {
+    let Some(partitioning) = child(root, "partitioning") else {
+        return Ok(Vec::new());
+    };
+    //the placements of the host collocations are placements of the partitioning too
+    let placements = partitioning
+        .children()
+        .flat_map(|n| match n.has_tag_name("hostcollocation") {
+            true => children(n, "componentplacement").collect(),
+            false => vec![n],
+        })
+        .filter(|n| n.has_tag_name("componentplacement"))
+        .map(|p| placement(p, file_name))
+        .collect::<Result<Vec<_>>>()?;
+
+    //verify the placements reference declared files
+    if let Some(p) = placements
+        .iter()
+        .find(|p| !component_files.iter().any(|f| f.id == p.file_ref))
+    {
+        return Err(invalid(
+            file_name,
+            &format!("unknown componentfile '{}'", p.file_ref),
+        ));
+    }
+    Ok(placements)
+}
+
+/// Parses a componentplacement element.
+fn placement(node: Node, file_name: &str) -> Result<ComponentPlacement> {
+    let file_ref = child(node, "componentfileref")
+        .ok_or_else(|| invalid(file_name, "<componentplacement> misses <componentfileref>"))?;
+
+    let instantiations = children(node, "componentinstantiation")
+        .map(|i| {
+            let properties = child(i, "componentproperties")
+                .map(|props| {
+                    children(props, "simpleref")
+                        .map(|s| {
+                            Ok(DataType::new(
+                                &attribute(s, "refid", file_name)?,
+                                AnyValue::String(attribute(s, "value", file_name)?),
+                            ))
+                        })
+                        .collect::<Result<Properties>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+
+            let start_order = i
+                .attribute("startorder")
+                .map(|o| {
+                    o.trim()
+                        .parse()
+                        .map_err(|_| invalid(file_name, &format!("invalid startorder '{o}'")))
+                })
+                .transpose()?;
+
+            Ok(ComponentInstantiation {
+                id: attribute(i, "id", file_name)?,
+                usage_name: child_text(i, "usagename"),
+                start_order,
+                properties,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ComponentPlacement {
+        file_ref: attribute(file_ref, "refid", file_name)?,
+        composite_part_of: child(node, "compositepartofdevice")
+            .map(|n| attribute(n, "refid", file_name))
+            .transpose()?,
+        instantiations,
+    })
+}
@@ -0,0 +1,580 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::super::common_types::{ActionType, AnyValue, Properties};
+use super::super::file_system::FileSystemTrait;
+use super::cache::ProfileCache;
+use super::dcd::DeviceConfiguration;
+use super::prf::{PropertiesDescriptor, Property, PropertyKind, PropertyType};
+use super::sad::SoftwareAssembly;
+use super::scd::SoftwareComponent;
+use super::spd::SoftPkg;
+use super::{self as profile, resolve_file_name};
+
+/// The number of placements tried before giving up the search of a feasible plan.
+const SEARCH_LIMIT: usize = 100_000;
+
+/// The capacities left on a device, by id.
+type Capacities = HashMap<String, AnyValue>;
+
+/**
+ * This type describes a component instantiation, or a usesdevice of the
+ * assembly, assigned to a device.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Assignment {
+    pub component_id: String,
+    pub device_id: String,
+    /// The implementation deployed, none for a usesdevice.
+    pub implementation_id: Option<String>,
+}
+
+/**
+ * This type describes a capacity of a device lacking for a component.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Shortfall {
+    pub component_id: String,
+    pub device_id: String,
+    pub capacity_id: String,
+    pub required: AnyValue,
+    /// The capacity left by the components assigned before.
+    pub available: AnyValue,
+}
+
+/**
+ * This type describes a component no device can take, along with the
+ * reason each device rejects it.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rejection {
+    pub component_id: String,
+    pub reasons: Vec<String>,
+}
+
+/**
+ * The assignment of the components of an assembly to the devices of
+ * node configurations. When no feasible assignment exists, the
+ * components are assigned to the first device taking them, the others
+ * being rejected.
+ */
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct DeploymentPlan {
+    pub assignments: Vec<Assignment>,
+    pub rejections: Vec<Rejection>,
+    /// The shortfalls of the devices rejecting the components for want of capacity.
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl DeploymentPlan {
+    /// Tells whether every component is assigned.
+    pub fn is_feasible(&self) -> bool {
+        self.rejections.is_empty()
+    }
+
+    /// Returns the device a component is assigned to.
+    pub fn device_of(&self, component_id: &str) -> Option<&str> {
+        self.assignments
+            .iter()
+            .find(|a| a.component_id == component_id)
+            .map(|a| a.device_id.as_str())
+    }
+}
+
+/**
+ * Computes offline the deployment of an assembly, nested assemblies
+ * included, on the devices of node configurations. The devices
+ * advertise the allocation properties of their PRF, overridden by the
+ * componentproperties of the DCD, the external ones being capacities.
+ * A component requires the processor, os and allocation dependencies
+ * of one of its implementations, its capacity dependencies being
+ * consumed; the components of a hostcollocation share a device. Only
+ * the devices whose SCD is of the executabledevice type, or without
+ * SCD, execute components.
+ */
+pub fn plan(
+    file_system: &dyn FileSystemTrait,
+    software_profile: &str,
+    device_configurations: &[&str],
+) -> profile::Result<DeploymentPlan> {
+    let cache = ProfileCache::default();
+    let mut devices = Vec::new();
+    for dcd in device_configurations {
+        devices.extend(load_devices(file_system, &cache, dcd)?);
+    }
+    let mut units = Vec::new();
+    load_units(
+        file_system,
+        &cache,
+        software_profile,
+        &mut units,
+        &mut vec![software_profile.to_string()],
+    )?;
+
+    let mut planner = Planner {
+        devices: &devices,
+        units: &units,
+        budget: SEARCH_LIMIT,
+    };
+    let mut available: Vec<Capacities> = devices.iter().map(Device::capacities).collect();
+    let mut placed = Vec::new();
+    if !planner.search(0, &mut available, &mut placed) {
+        return Ok(planner.first_fit());
+    }
+
+    let assignments = units
+        .iter()
+        .zip(placed)
+        .flat_map(|(unit, (device, implementations))| {
+            let device_id = &devices[device].id;
+            unit.members
+                .iter()
+                .zip(implementations)
+                .map(move |(member, implementation_id)| Assignment {
+                    component_id: member.id.clone(),
+                    device_id: device_id.clone(),
+                    implementation_id,
+                })
+        })
+        .collect();
+    Ok(DeploymentPlan {
+        assignments,
+        ..DeploymentPlan::default()
+    })
+}
+
+/**
+ * An allocation property of a device.
+ */
+struct DeviceProperty {
+    id: String,
+    value: AnyValue,
+    value_type: PropertyType,
+    action: ActionType,
+}
+
+struct Device {
+    id: String,
+    executable: bool,
+    properties: Vec<DeviceProperty>,
+}
+
+impl Device {
+    /// Returns the whole capacities of the device.
+    fn capacities(&self) -> Capacities {
+        self.properties
+            .iter()
+            .filter(|p| p.action == ActionType::EXTERNAL)
+            .map(|p| (p.id.clone(), p.value.clone()))
+            .collect()
+    }
+
+    /**
+     * Allocates the requirements of a component on the remaining
+     * capacities of the device, returning the reason of the rejection or
+     * the shortfall otherwise.
+     */
+    fn allocate(
+        &self,
+        component_id: &str,
+        requirements: &Properties,
+        available: &mut Capacities,
+    ) -> Result<(), Mismatch> {
+        for requirement in requirements {
+            let Some(property) = self.properties.iter().find(|p| p.id == requirement.id) else {
+                return Err(Mismatch::Reason(format!(
+                    "'{}' has no '{}' allocation property",
+                    self.id, requirement.id
+                )));
+            };
+            let required = match &requirement.value {
+                AnyValue::String(text) => property.value_type.parse_value(text),
+                value => Some(value.clone()),
+            };
+            let Some(required) = required else {
+                return Err(Mismatch::Reason(format!(
+                    "'{}' is not a value of '{}'",
+                    requirement.value, requirement.id
+                )));
+            };
+
+            if property.action != ActionType::EXTERNAL {
+                if !property.action.evaluate(&property.value, &required) {
+                    return Err(Mismatch::Reason(format!(
+                        "'{}' of '{}' is {}, {:?} {} required",
+                        requirement.id, self.id, property.value, property.action, required
+                    )));
+                }
+                continue;
+            }
+            let capacity = available.get_mut(&requirement.id).unwrap();
+            match capacity.checked_sub(&required) {
+                Some(remaining) => *capacity = remaining,
+                None => {
+                    return Err(Mismatch::Shortfall(Box::new(Shortfall {
+                        component_id: component_id.to_string(),
+                        device_id: self.id.clone(),
+                        capacity_id: requirement.id.clone(),
+                        required,
+                        available: capacity.clone(),
+                    })))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+enum Mismatch {
+    Reason(String),
+    Shortfall(Box<Shortfall>),
+}
+
+impl Mismatch {
+    fn reason(&self) -> String {
+        match self {
+            Mismatch::Reason(reason) => reason.clone(),
+            Mismatch::Shortfall(s) => format!(
+                "'{}' of '{}' is {}, {} required",
+                s.capacity_id, s.device_id, s.available, s.required
+            ),
+        }
+    }
+}
+
+/**
+ * A component instantiation, or a usesdevice, along with the alternative
+ * requirements of its implementations in SPD order.
+ */
+struct Member {
+    id: String,
+    alternatives: Vec<(Option<String>, Properties)>,
+}
+
+/**
+ * The members placed on a single device: a component, the components of
+ * a hostcollocation or a usesdevice.
+ */
+struct Unit {
+    members: Vec<Member>,
+    /// Tells whether the members are components, executed by the device.
+    executed: bool,
+}
+
+struct Planner<'a> {
+    devices: &'a [Device],
+    units: &'a [Unit],
+    /// The placements left to try.
+    budget: usize,
+}
+
+impl Planner<'_> {
+    /**
+     * Places a unit on a device, each member taking the first alternative
+     * fitting the remaining capacities. Returns the capacities left and
+     * the chosen implementations, or the mismatches of the first member
+     * not fitting.
+     */
+    fn place(
+        &self,
+        unit: &Unit,
+        device: &Device,
+        available: &Capacities,
+    ) -> Result<(Capacities, Vec<Option<String>>), Vec<Mismatch>> {
+        if unit.executed && !device.executable {
+            return Err(vec![Mismatch::Reason(format!(
+                "'{}' does not execute components",
+                device.id
+            ))]);
+        }
+        let mut available = available.clone();
+        let mut implementations = Vec::new();
+        for member in &unit.members {
+            let mut mismatches = Vec::new();
+            let chosen =
+                member
+                    .alternatives
+                    .iter()
+                    .find_map(|(implementation_id, requirements)| {
+                        let mut remaining = available.clone();
+                        match device.allocate(&member.id, requirements, &mut remaining) {
+                            Ok(()) => Some((implementation_id.clone(), remaining)),
+                            Err(mismatch) => {
+                                mismatches.push(mismatch);
+                                None
+                            }
+                        }
+                    });
+            let Some((implementation_id, remaining)) = chosen else {
+                if mismatches.is_empty() {
+                    mismatches.push(Mismatch::Reason(format!(
+                        "'{}' has no implementation with code",
+                        member.id
+                    )));
+                }
+                return Err(mismatches);
+            };
+            available = remaining;
+            implementations.push(implementation_id);
+        }
+        Ok((available, implementations))
+    }
+
+    /// Searches the devices of the units from an index on, backtracking on failures.
+    fn search(
+        &mut self,
+        index: usize,
+        available: &mut Vec<Capacities>,
+        placed: &mut Vec<(usize, Vec<Option<String>>)>,
+    ) -> bool {
+        let Some(unit) = self.units.get(index) else {
+            return true;
+        };
+        for (d, device) in self.devices.iter().enumerate() {
+            if self.budget == 0 {
+                return false;
+            }
+            self.budget -= 1;
+            let Ok((remaining, implementations)) = self.place(unit, device, &available[d]) else {
+                continue;
+            };
+            let previous = std::mem::replace(&mut available[d], remaining);
+            placed.push((d, implementations));
+            if self.search(index + 1, available, placed) {
+                return true;
+            }
+            placed.pop();
+            available[d] = previous;
+        }
+        false
+    }
+
+    /// Places each unit on the first device taking it, reporting the others.
+    fn first_fit(&self) -> DeploymentPlan {
+        let mut plan = DeploymentPlan::default();
+        let mut available: Vec<Capacities> = self.devices.iter().map(Device::capacities).collect();
+        for unit in self.units {
+            let mut reasons = Vec::new();
+            let mut shortfalls = Vec::new();
+            let mut assigned = false;
+            for (d, device) in self.devices.iter().enumerate() {
+                match self.place(unit, device, &available[d]) {
+                    Ok((remaining, implementations)) => {
+                        available[d] = remaining;
+                        plan.assignments
+                            .extend(unit.members.iter().zip(implementations).map(
+                                |(member, implementation_id)| Assignment {
+                                    component_id: member.id.clone(),
+                                    device_id: device.id.clone(),
+                                    implementation_id,
+                                },
+                            ));
+                        assigned = true;
+                        break;
+                    }
+                    Err(mismatches) => {
+                        for mismatch in mismatches {
+                            reasons.push(mismatch.reason());
+                            if let Mismatch::Shortfall(shortfall) = mismatch {
+                                shortfalls.push(*shortfall);
+                            }
+                        }
+                    }
+                }
+            }
+            if assigned {
+                continue;
+            }
+            if self.devices.is_empty() {
+                reasons.push("no device".to_string());
+            }
+            plan.shortfalls.extend(shortfalls);
+            plan.rejections
+                .extend(unit.members.iter().map(|member| Rejection {
+                    component_id: member.id.clone(),
+                    reasons: reasons.clone(),
+                }));
+        }
+        plan
+    }
+}
+
+/// Loads the devices of a DCD with their allocation properties.
+fn load_devices(
+    file_system: &dyn FileSystemTrait,
+    cache: &ProfileCache,
+    dcd_file_name: &str,
+) -> profile::Result<Vec<Device>> {
+    let dcd = cache.load::<DeviceConfiguration>(file_system, dcd_file_name)?;
+    let mut devices = Vec::new();
+    for placement in &dcd.placements {
+        let Some(file) = dcd.component_file(placement) else {
+            continue;
+        };
+        let spd_file_name = resolve_file_name(dcd_file_name, &file.local_file);
+        let softpkg = cache.load::<SoftPkg>(file_system, &spd_file_name)?;
+        let executable = match &softpkg.descriptor {
+            Some(scd) => {
+                let scd = resolve_file_name(&spd_file_name, scd);
+                let scd = cache.load::<SoftwareComponent>(file_system, &scd)?;
+                scd.component_type
+                    .as_deref()
+                    .is_none_or(|t| t == "executabledevice")
+            }
+            None => true,
+        };
+
+        let mut properties = Vec::new();
+        let property_files = softpkg.property_file.iter().chain(
+            softpkg
+                .implementations
+                .iter()
+                .flat_map(|i| &i.property_file),
+        );
+        for property_file in property_files {
+            let property_file = resolve_file_name(&spd_file_name, property_file);
+            let prf = cache.load::<PropertiesDescriptor>(file_system, &property_file)?;
+            properties.extend(allocation_properties(&prf));
+        }
+
+        for instantiation in &placement.instantiations {
+            let mut properties: Vec<DeviceProperty> = properties
+                .iter()
+                .map(|p| DeviceProperty {
+                    id: p.id.clone(),
+                    value: p.value.clone(),
+                    value_type: p.value_type,
+                    action: p.action,
+                })
+                .collect();
+            for overridden in &instantiation.properties {
+                let property = properties.iter_mut().find(|p| p.id == overridden.id);
+                if let (Some(property), AnyValue::String(text)) = (property, &overridden.value) {
+                    property.value = property.value_type.parse_value(text).ok_or_else(|| {
+                        profile::invalid(
+                            dcd_file_name,
+                            &format!("'{text}' is not a value of '{}'", overridden.id),
+                        )
+                    })?;
+                }
+            }
+            devices.push(Device {
+                id: instantiation.id.clone(),
+                executable,
+                properties,
+            });
+        }
+    }
+    Ok(devices)
+}
+
+/// Returns the valued simple and simplesequence allocation properties of a PRF.
+fn allocation_properties(prf: &PropertiesDescriptor) -> Vec<DeviceProperty> {
+    prf.properties_of_kind(PropertyKind::ALLOCATION)
+        .filter_map(|p| {
+            let (value_type, action) = match p {
+                Property::Simple(s) => (s.value_type, s.action),
+                Property::SimpleSequence(s) => (s.value_type, s.action),
+                _ => return None,
+            };
+            Some(DeviceProperty {
+                id: p.id().to_string(),
+                value: p.value()?,
+                value_type,
+                action: action.unwrap_or(ActionType::EQ),
+            })
+        })
+        .collect()
+}
+
+/// Loads the units of an assembly and of its nested assemblies, those being loaded excluded.
+fn load_units(
+    file_system: &dyn FileSystemTrait,
+    cache: &ProfileCache,
+    sad_file_name: &str,
+    units: &mut Vec<Unit>,
+    loading: &mut Vec<String>,
+) -> profile::Result<()> {
+    let assembly = cache.load::<SoftwareAssembly>(file_system, sad_file_name)?;
+
+    for uses_device in &assembly.uses_devices {
+        units.push(Unit {
+            members: vec![Member {
+                id: uses_device.id.clone(),
+                alternatives: vec![(None, uses_device.properties.clone())],
+            }],
+            executed: false,
+        });
+    }
+
+    let mut members: HashMap<&str, Member> = HashMap::new();
+    for placement in &assembly.placements {
+        let Some(file) = assembly.component_file(placement) else {
+            continue;
+        };
+        let local_file = resolve_file_name(sad_file_name, &file.local_file);
+        if file.file_type == "SAD" {
+            if loading.contains(&local_file) {
+                return Err(profile::invalid(sad_file_name, "assembly nests itself"));
+            }
+            loading.push(local_file.clone());
+            load_units(file_system, cache, &local_file, units, loading)?;
+            loading.pop();
+            continue;
+        }
+        let softpkg = cache.load::<SoftPkg>(file_system, &local_file)?;
+        for instantiation in &placement.instantiations {
+            members.insert(&instantiation.id, member(&instantiation.id, &softpkg));
+        }
+    }
+
+    //the members of a host collocation are placed where the first one stands
+    let ids = assembly
+        .placements
+        .iter()
+        .flat_map(|p| &p.instantiations)
+        .map(|i| &i.id);
+    for id in ids {
+        let unit_members: Vec<Member> = match assembly
+            .host_collocations
+            .iter()
+            .find(|c| c.instantiations.contains(id))
+        {
+            Some(c) => c
+                .instantiations
+                .iter()
+                .filter_map(|id| members.remove(id.as_str()))
+                .collect(),
+            None => members.remove(id.as_str()).into_iter().collect(),
+        };
+        if !unit_members.is_empty() {
+            units.push(Unit {
+                members: unit_members,
+                executed: true,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns a component instantiation with the requirements of the implementations having code.
+fn member(id: &str, softpkg: &SoftPkg) -> Member {
+    let alternatives = softpkg
+        .implementations
+        .iter()
+        .filter(|i| i.code.is_some())
+        .flat_map(|i| {
+            i.platform_requirements()
+                .into_iter()
+                .map(|mut requirements| {
+                    requirements.extend(i.dependencies.iter().cloned());
+                    (Some(i.id.clone()), requirements)
+                })
+        })
+        .collect();
+    Member {
+        id: id.to_string(),
+        alternatives,
+    }
+}
@@ -0,0 +1,806 @@
+use std::path::Path;
+
+use roxmltree::Node;
+
+use super::super::common_types::{ActionType, AnyValue, DataType, Properties};
+use super::writer::Element;
+use super::{self as profile, attribute, child, child_text, children};
+
+/**
+ * This type defines the types of the simple values.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyType {
+    BOOLEAN,
+    CHAR,
+    DOUBLE,
+    FLOAT,
+    SHORT,
+    LONG,
+    OBJREF,
+    OCTET,
+    STRING,
+    ULONG,
+    USHORT,
+    LONGLONG,
+    ULONGLONG,
+}
+
+impl PropertyType {
+    /// Returns the type named by a type attribute.
+    fn from_name(name: &str) -> Option<PropertyType> {
+        match name {
+            "boolean" => Some(PropertyType::BOOLEAN),
+            "char" => Some(PropertyType::CHAR),
+            "double" => Some(PropertyType::DOUBLE),
+            "float" => Some(PropertyType::FLOAT),
+            "short" => Some(PropertyType::SHORT),
+            "long" => Some(PropertyType::LONG),
+            "objref" => Some(PropertyType::OBJREF),
+            "octet" => Some(PropertyType::OCTET),
+            "string" => Some(PropertyType::STRING),
+            "ulong" => Some(PropertyType::ULONG),
+            "ushort" => Some(PropertyType::USHORT),
+            "longlong" => Some(PropertyType::LONGLONG),
+            "ulonglong" => Some(PropertyType::ULONGLONG),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the type in a type attribute.
+    fn name(&self) -> &'static str {
+        match self {
+            PropertyType::BOOLEAN => "boolean",
+            PropertyType::CHAR => "char",
+            PropertyType::DOUBLE => "double",
+            PropertyType::FLOAT => "float",
+            PropertyType::SHORT => "short",
+            PropertyType::LONG => "long",
+            PropertyType::OBJREF => "objref",
+            PropertyType::OCTET => "octet",
+            PropertyType::STRING => "string",
+            PropertyType::ULONG => "ulong",
+            PropertyType::USHORT => "ushort",
+            PropertyType::LONGLONG => "longlong",
+            PropertyType::ULONGLONG => "ulonglong",
+        }
+    }
+
+    /**
+     * Parses a value of the type, the chars and object references being
+     * kept as strings. Returns None when the text is not such a value.
+     */
+    pub fn parse_value(&self, text: &str) -> Option<AnyValue> {
+        let trimmed = text.trim();
+        match self {
+            PropertyType::BOOLEAN => trimmed.parse().ok().map(AnyValue::Boolean),
+            PropertyType::CHAR => {
+                (text.chars().count() == 1).then(|| AnyValue::String(text.to_string()))
+            }
+            PropertyType::DOUBLE => trimmed.parse().ok().map(AnyValue::Double),
+            PropertyType::FLOAT => trimmed.parse().ok().map(AnyValue::Float),
+            PropertyType::SHORT => trimmed.parse().ok().map(AnyValue::Short),
+            PropertyType::LONG => trimmed.parse().ok().map(AnyValue::Long),
+            PropertyType::OBJREF | PropertyType::STRING => Some(AnyValue::String(text.to_string())),
+            PropertyType::OCTET => trimmed.parse().ok().map(AnyValue::Octet),
+            PropertyType::ULONG => trimmed.parse().ok().map(AnyValue::ULong),
+            PropertyType::USHORT => trimmed.parse().ok().map(AnyValue::UShort),
+            PropertyType::LONGLONG => trimmed.parse().ok().map(AnyValue::LongLong),
+            PropertyType::ULONGLONG => trimmed.parse().ok().map(AnyValue::ULongLong),
+        }
+    }
+}
+
+/**
+ * This type defines whether a property can be queried, configured or
+ * both.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessMode {
+    READONLY,
+    READWRITE,
+    WRITEONLY,
+}
+
+impl AccessMode {
+    /// Tells whether the property can be queried.
+    pub fn is_readable(&self) -> bool {
+        *self != AccessMode::WRITEONLY
+    }
+
+    /// Tells whether the property can be configured.
+    pub fn is_writable(&self) -> bool {
+        *self != AccessMode::READONLY
+    }
+
+    /// Returns the name of the mode in a mode attribute.
+    fn name(&self) -> &'static str {
+        match self {
+            AccessMode::READONLY => "readonly",
+            AccessMode::READWRITE => "readwrite",
+            AccessMode::WRITEONLY => "writeonly",
+        }
+    }
+}
+
+/**
+ * This type defines the kinds of properties, i.e. the operations they
+ * are used with: configure and query, the execute parameters, the
+ * allocation of capacities, the create operation of a factory, the
+ * runTest operation, the events and messages, or the property kind of
+ * the properties which are both configured and queried.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyKind {
+    CONFIGURE,
+    EXECPARAM,
+    ALLOCATION,
+    FACTORYPARAM,
+    TEST,
+    EVENT,
+    MESSAGE,
+    PROPERTY,
+}
+
+impl PropertyKind {
+    /// Returns the kind named by a kindtype attribute.
+    fn from_name(name: &str) -> Option<PropertyKind> {
+        match name {
+            "configure" => Some(PropertyKind::CONFIGURE),
+            "execparam" => Some(PropertyKind::EXECPARAM),
+            "allocation" => Some(PropertyKind::ALLOCATION),
+            "factoryparam" => Some(PropertyKind::FACTORYPARAM),
+            "test" => Some(PropertyKind::TEST),
+            "event" => Some(PropertyKind::EVENT),
+            "message" => Some(PropertyKind::MESSAGE),
+            "property" => Some(PropertyKind::PROPERTY),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the kind in a kindtype attribute.
+    fn name(&self) -> &'static str {
+        match self {
+            PropertyKind::CONFIGURE => "configure",
+            PropertyKind::EXECPARAM => "execparam",
+            PropertyKind::ALLOCATION => "allocation",
+            PropertyKind::FACTORYPARAM => "factoryparam",
+            PropertyKind::TEST => "test",
+            PropertyKind::EVENT => "event",
+            PropertyKind::MESSAGE => "message",
+            PropertyKind::PROPERTY => "property",
+        }
+    }
+}
+
+/**
+ * This type describes the inclusive range of the values of a simple
+ * property.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Range {
+    pub min: AnyValue,
+    pub max: AnyValue,
+}
+
+impl Range {
+    /// Tells whether a value is within the range.
+    pub fn contains(&self, value: &AnyValue) -> bool {
+        ActionType::GE.evaluate(value, &self.min) && ActionType::LE.evaluate(value, &self.max)
+    }
+}
+
+/**
+ * This type describes a labelled value of a simple property.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enumeration {
+    pub label: String,
+    pub value: AnyValue,
+}
+
+/**
+ * This type describes a simple property: a single value of a simple
+ * type.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simple {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub value_type: PropertyType,
+    pub mode: AccessMode,
+    pub kinds: Vec<PropertyKind>,
+    /// How the property is evaluated when allocating.
+    pub action: Option<ActionType>,
+    pub units: Option<String>,
+    pub range: Option<Range>,
+    pub enumerations: Vec<Enumeration>,
+    /// The default value.
+    pub value: Option<AnyValue>,
+}
+
+/**
+ * This type describes a simplesequence property: a sequence of values
+ * of a simple type.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimpleSequence {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub value_type: PropertyType,
+    pub mode: AccessMode,
+    pub kinds: Vec<PropertyKind>,
+    /// How the property is evaluated when allocating.
+    pub action: Option<ActionType>,
+    pub units: Option<String>,
+    pub range: Option<Range>,
+    /// The default values.
+    pub values: Option<Vec<AnyValue>>,
+}
+
+/**
+ * This type describes a struct property: named fields made of simple
+ * and simplesequence properties.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub mode: AccessMode,
+    pub kinds: Vec<PropertyKind>,
+    pub fields: Vec<Property>,
+}
+
+/**
+ * This type describes a structsequence property: a sequence of values
+ * of a struct.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructSequence {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub mode: AccessMode,
+    pub kinds: Vec<PropertyKind>,
+    /// The struct of the values.
+    pub structure: Struct,
+    /// The default values, each one a struct.
+    pub values: Vec<AnyValue>,
+}
+
+/**
+ * This type describes a property of a PRF.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Property {
+    Simple(Simple),
+    SimpleSequence(SimpleSequence),
+    Struct(Struct),
+    StructSequence(StructSequence),
+}
+
+impl Property {
+    pub fn id(&self) -> &str {
+        match self {
+            Property::Simple(p) => &p.id,
+            Property::SimpleSequence(p) => &p.id,
+            Property::Struct(p) => &p.id,
+            Property::StructSequence(p) => &p.id,
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Property::Simple(p) => p.name.as_deref(),
+            Property::SimpleSequence(p) => p.name.as_deref(),
+            Property::Struct(p) => p.name.as_deref(),
+            Property::StructSequence(p) => p.name.as_deref(),
+        }
+    }
+
+    pub fn mode(&self) -> AccessMode {
+        match self {
+            Property::Simple(p) => p.mode,
+            Property::SimpleSequence(p) => p.mode,
+            Property::Struct(p) => p.mode,
+            Property::StructSequence(p) => p.mode,
+        }
+    }
+
+    pub fn kinds(&self) -> &[PropertyKind] {
+        match self {
+            Property::Simple(p) => &p.kinds,
+            Property::SimpleSequence(p) => &p.kinds,
+            Property::Struct(p) => &p.kinds,
+            Property::StructSequence(p) => &p.kinds,
+        }
+    }
+
+    /// Tells whether the property is of the kind.
+    pub fn is_kind(&self, kind: PropertyKind) -> bool {
+        self.kinds().contains(&kind)
+    }
+
+    /// Returns the element describing the property.
+    fn to_element(&self) -> Element {
+        match self {
+            Property::Simple(p) => {
+                let enumerations = p
+                    .enumerations
+                    .iter()
+                    .map(|e| {
+                        Element::new("enumeration")
+                            .with_attribute("label", &e.label)
+                            .with_attribute("value", &e.value)
+                    })
+                    .collect();
+                header("simple", &p.id, p.name.as_ref(), p.mode)
+                    .with_attribute("type", p.value_type.name())
+                    .with_text_child("description", p.description.as_ref())
+                    .with_text_child("value", p.value.as_ref())
+                    .with_text_child("units", p.units.as_ref())
+                    .with_optional_child(p.range.as_ref().map(range_element))
+                    .with_container("enumerations", enumerations)
+                    .with_children(kind_elements("kind", &p.kinds))
+                    .with_optional_child(p.action.map(action_element))
+            }
+            Property::SimpleSequence(p) => header("simplesequence", &p.id, p.name.as_ref(), p.mode)
+                .with_attribute("type", p.value_type.name())
+                .with_text_child("description", p.description.as_ref())
+                .with_optional_child(p.values.as_deref().map(values_element))
+                .with_text_child("units", p.units.as_ref())
+                .with_optional_child(p.range.as_ref().map(range_element))
+                .with_children(kind_elements("kind", &p.kinds))
+                .with_optional_child(p.action.map(action_element)),
+            Property::Struct(p) => struct_element(p),
+            Property::StructSequence(p) => {
+                let values = p.values.iter().map(|v| {
+                    let fields = match v {
+                        AnyValue::Struct(fields) => fields.as_slice(),
+                        _ => &[],
+                    };
+                    Element::new("structvalue").with_children(fields.iter().map(|f| {
+                        match &f.value {
+                            AnyValue::Sequence(values) => Element::new("simplesequenceref")
+                                .with_attribute("refid", &f.id)
+                                .with_child(values_element(values)),
+                            value => Element::new("simpleref")
+                                .with_attribute("refid", &f.id)
+                                .with_attribute("value", value),
+                        }
+                    }))
+                });
+                header("structsequence", &p.id, p.name.as_ref(), p.mode)
+                    .with_text_child("description", p.description.as_ref())
+                    .with_child(struct_element(&p.structure))
+                    .with_children(values)
+                    .with_children(kind_elements("configurationkind", &p.kinds))
+            }
+        }
+    }
+
+    /**
+     * Returns the default value of the property: a struct holds the
+     * fields having a default value, a structsequence its values.
+     */
+    pub fn value(&self) -> Option<AnyValue> {
+        match self {
+            Property::Simple(p) => p.value.clone(),
+            Property::SimpleSequence(p) => p.values.clone().map(AnyValue::Sequence),
+            Property::Struct(p) => {
+                let fields: Properties = p
+                    .fields
+                    .iter()
+                    .filter_map(|f| Some(DataType::new(f.id(), f.value()?)))
+                    .collect();
+                (!fields.is_empty()).then_some(AnyValue::Struct(fields))
+            }
+            Property::StructSequence(p) => {
+                (!p.values.is_empty()).then(|| AnyValue::Sequence(p.values.clone()))
+            }
+        }
+    }
+}
+
+/**
+ * Properties Descriptor: the properties of a component or device, along
+ * with their types, kinds and default values.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertiesDescriptor {
+    pub description: Option<String>,
+    pub properties: Vec<Property>,
+}
+
+impl PropertiesDescriptor {
+    /// Parses the PRF file.
+    pub fn from_file(path: &Path) -> profile::Result<PropertiesDescriptor> {
+        let xml = profile::read_profile(path)?;
+        PropertiesDescriptor::parse(&xml, &path.display().to_string())
+    }
+
+    /// Parses a PRF document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<PropertiesDescriptor> {
+        let document = profile::parse_document(xml, "properties", file_name)?;
+        let root = document.root_element();
+
+        //the test definitions are not properties
+        let properties = root
+            .children()
+            .filter(|n| n.is_element() && !n.has_tag_name("description") && !n.has_tag_name("test"))
+            .map(|n| property(n, file_name))
+            .collect::<profile::Result<Vec<_>>>()?;
+
+        //verify the ids are unique
+        for (index, p) in properties.iter().enumerate() {
+            if properties[..index].iter().any(|o| o.id() == p.id()) {
+                return Err(profile::invalid(
+                    file_name,
+                    &format!("duplicate property '{}'", p.id()),
+                ));
+            }
+        }
+
+        Ok(PropertiesDescriptor {
+            description: child_text(root, "description"),
+            properties,
+        })
+    }
+
+    /// Returns a property by id.
+    pub fn property(&self, id: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.id() == id)
+    }
+
+    /// Returns the properties of the kind.
+    pub fn properties_of_kind(&self, kind: PropertyKind) -> impl Iterator<Item = &Property> {
+        self.properties.iter().filter(move |p| p.is_kind(kind))
+    }
+
+    /**
+     * Returns the default values of the properties of the kind, those
+     * without a default value being left out.
+     */
+    pub fn values(&self, kind: PropertyKind) -> Properties {
+        self.properties_of_kind(kind)
+            .filter_map(|p| Some(DataType::new(p.id(), p.value()?)))
+            .collect()
+    }
+
+    /**
+     * Returns the PRF document describing the properties. The default
+     * mode and kind are left implicit.
+     */
+    pub fn to_xml(&self) -> String {
+        Element::new("properties")
+            .with_text_child("description", self.description.as_ref())
+            .with_children(self.properties.iter().map(Property::to_element))
+            .to_document()
+    }
+}
+
+/// Returns a property element with its id, name and mode attributes.
+fn header(tag: &str, id: &str, name: Option<&String>, mode: AccessMode) -> Element {
+    Element::new(tag)
+        .with_attribute("id", id)
+        .with_optional_attribute("name", name)
+        .with_optional_attribute("mode", (mode != AccessMode::READWRITE).then(|| mode.name()))
+}
+
+/// Returns a struct element.
+fn struct_element(structure: &Struct) -> Element {
+    header(
+        "struct",
+        &structure.id,
+        structure.name.as_ref(),
+        structure.mode,
+    )
+    .with_text_child("description", structure.description.as_ref())
+    .with_children(structure.fields.iter().map(Property::to_element))
+    .with_children(kind_elements("configurationkind", &structure.kinds))
+}
+
+/// Returns the kind elements of a property, none for the default configure kind.
+fn kind_elements(tag: &str, kinds: &[PropertyKind]) -> Vec<Element> {
+    if kinds == [PropertyKind::CONFIGURE] {
+        return Vec::new();
+    }
+    kinds
+        .iter()
+        .map(|k| Element::new(tag).with_attribute("kindtype", k.name()))
+        .collect()
+}
+
+/// Returns a values element.
+fn values_element(values: &[AnyValue]) -> Element {
+    Element::new("values").with_children(values.iter().map(|v| Element::new("value").with_text(v)))
+}
+
+/// Returns a range element.
+fn range_element(range: &Range) -> Element {
+    Element::new("range")
+        .with_attribute("min", &range.min)
+        .with_attribute("max", &range.max)
+}
+
+/// Returns an action element.
+fn action_element(action: ActionType) -> Element {
+    let name = match action {
+        ActionType::EQ => "eq",
+        ActionType::NE => "ne",
+        ActionType::GT => "gt",
+        ActionType::LT => "lt",
+        ActionType::GE => "ge",
+        ActionType::LE => "le",
+        ActionType::EXTERNAL => "external",
+    };
+    Element::new("action").with_attribute("type", name)
+}
+
+/// Parses a property element.
+fn property(node: Node, file_name: &str) -> profile::Result<Property> {
+    match node.tag_name().name() {
+        "simple" => Ok(Property::Simple(simple(node, file_name)?)),
+        "simplesequence" => Ok(Property::SimpleSequence(simple_sequence(node, file_name)?)),
+        "struct" => Ok(Property::Struct(structure(node, file_name)?)),
+        "structsequence" => {
+            let structure = child(node, "struct")
+                .ok_or_else(|| {
+                    profile::invalid(
+                        file_name,
+                        &format!(
+                            "<structsequence> '{}' misses <struct>",
+                            node.attribute("id").unwrap_or_default()
+                        ),
+                    )
+                })
+                .and_then(|s| structure(s, file_name))?;
+            let values = children(node, "structvalue")
+                .map(|v| struct_value(v, &structure, file_name))
+                .collect::<profile::Result<Vec<_>>>()?;
+            Ok(Property::StructSequence(StructSequence {
+                id: attribute(node, "id", file_name)?,
+                name: node.attribute("name").map(str::to_string),
+                description: child_text(node, "description"),
+                mode: mode(node, file_name)?,
+                kinds: kinds(node, file_name)?,
+                structure,
+                values,
+            }))
+        }
+        other => Err(profile::invalid(
+            file_name,
+            &format!("unknown property element <{other}>"),
+        )),
+    }
+}
+
+/// Parses a simple element.
+fn simple(node: Node, file_name: &str) -> profile::Result<Simple> {
+    let id = attribute(node, "id", file_name)?;
+    let value_type = value_type(node, &id, file_name)?;
+    let default = child(node, "value")
+        .map(|v| value(value_type, v.text().unwrap_or_default(), &id, file_name))
+        .transpose()?;
+    let enumerations = child(node, "enumerations")
+        .map(|e| {
+            children(e, "enumeration")
+                .map(|e| {
+                    Ok(Enumeration {
+                        label: attribute(e, "label", file_name)?,
+                        value: value(
+                            value_type,
+                            &attribute(e, "value", file_name)?,
+                            &id,
+                            file_name,
+                        )?,
+                    })
+                })
+                .collect::<profile::Result<Vec<_>>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(Simple {
+        name: node.attribute("name").map(str::to_string),
+        description: child_text(node, "description"),
+        value_type,
+        mode: mode(node, file_name)?,
+        kinds: kinds(node, file_name)?,
+        action: action(node, file_name)?,
+        units: child_text(node, "units"),
+        range: range(node, value_type, &id, file_name)?,
+        enumerations,
+        value: default,
+        id,
+    })
+}
+
+/// Parses a simplesequence element.
+fn simple_sequence(node: Node, file_name: &str) -> profile::Result<SimpleSequence> {
+    let id = attribute(node, "id", file_name)?;
+    let value_type = value_type(node, &id, file_name)?;
+    let values = child(node, "values")
+        .map(|v| sequence_values(v, value_type, &id, file_name))
+        .transpose()?;
+
+    Ok(SimpleSequence {
+        name: node.attribute("name").map(str::to_string),
+        description: child_text(node, "description"),
+        value_type,
+        mode: mode(node, file_name)?,
+        kinds: kinds(node, file_name)?,
+        action: action(node, file_name)?,
+        units: child_text(node, "units"),
+        range: range(node, value_type, &id, file_name)?,
+        values,
+        id,
+    })
+}
+
+/// Parses a struct element, its fields being simple and simplesequence elements.
+fn structure(node: Node, file_name: &str) -> profile::Result<Struct> {
+    let fields = node
+        .children()
+        .filter(|n| n.has_tag_name("simple") || n.has_tag_name("simplesequence"))
+        .map(|n| property(n, file_name))
+        .collect::<profile::Result<Vec<_>>>()?;
+
+    Ok(Struct {
+        id: attribute(node, "id", file_name)?,
+        name: node.attribute("name").map(str::to_string),
+        description: child_text(node, "description"),
+        mode: mode(node, file_name)?,
+        kinds: kinds(node, file_name)?,
+        fields,
+    })
+}
+
+/**
+ * Parses a structvalue element of a structsequence, verifying it only
+ * references fields of the struct.
+ */
+fn struct_value(node: Node, structure: &Struct, file_name: &str) -> profile::Result<AnyValue> {
+    let mut fields = Properties::new();
+    for reference in node.children().filter(|n| n.is_element()) {
+        let refid = attribute(reference, "refid", file_name)?;
+        let field = structure.fields.iter().find(|f| f.id() == refid);
+        let value = match (reference.tag_name().name(), field) {
+            ("simpleref", Some(Property::Simple(s))) => value(
+                s.value_type,
+                &attribute(reference, "value", file_name)?,
+                &refid,
+                file_name,
+            )?,
+            ("simplesequenceref", Some(Property::SimpleSequence(s))) => {
+                let values = child(reference, "values")
+                    .map(|v| sequence_values(v, s.value_type, &refid, file_name))
+                    .transpose()?
+                    .unwrap_or_default();
+                AnyValue::Sequence(values)
+            }
+            _ => {
+                return Err(profile::invalid(
+                    file_name,
+                    &format!("'{refid}' is not a field of struct '{}'", structure.id),
+                ))
+            }
+        };
+        fields.push(DataType::new(&refid, value));
+    }
+    Ok(AnyValue::Struct(fields))
+}
+
+/// Parses the value children of a values element.
+fn sequence_values(
+    node: Node,
+    value_type: PropertyType,
+    id: &str,
+    file_name: &str,
+) -> profile::Result<Vec<AnyValue>> {
+    children(node, "value")
+        .map(|v| value(value_type, v.text().unwrap_or_default(), id, file_name))
+        .collect()
+}
+
+/// Parses a value of a property.
+fn value(
+    value_type: PropertyType,
+    text: &str,
+    id: &str,
+    file_name: &str,
+) -> profile::Result<AnyValue> {
+    value_type.parse_value(text).ok_or_else(|| {
+        profile::invalid(
+            file_name,
+            &format!("invalid {value_type:?} value '{text}' of '{id}'"),
+        )
+    })
+}
+
+/// Returns the type of a simple or simplesequence element.
+fn value_type(node: Node, id: &str, file_name: &str) -> profile::Result<PropertyType> {
+    let name = attribute(node, "type", file_name)?;
+    PropertyType::from_name(&name)
+        .ok_or_else(|| profile::invalid(file_name, &format!("unknown type '{name}' of '{id}'")))
+}
+
+/// Returns the mode of a property, readwrite by default.
+fn mode(node: Node, file_name: &str) -> profile::Result<AccessMode> {
+    match node.attribute("mode").unwrap_or("readwrite") {
+        "readonly" => Ok(AccessMode::READONLY),
+        "readwrite" => Ok(AccessMode::READWRITE),
+        "writeonly" => Ok(AccessMode::WRITEONLY),
+        other => Err(profile::invalid(
+            file_name,
+            &format!("unknown mode '{other}'"),
+        )),
+    }
+}
+
+/**
+ * Returns the kinds of a property, given by its kind elements or, for
+ * the structs, configurationkind elements. Properties are of the
+ * configure kind by default.
+ */
+fn kinds(node: Node, file_name: &str) -> profile::Result<Vec<PropertyKind>> {
+    let kinds = node
+        .children()
+        .filter(|n| n.has_tag_name("kind") || n.has_tag_name("configurationkind"))
+        .map(|k| {
+            let name = k.attribute("kindtype").unwrap_or("configure");
+            PropertyKind::from_name(name)
+                .ok_or_else(|| profile::invalid(file_name, &format!("unknown kindtype '{name}'")))
+        })
+        .collect::<profile::Result<Vec<_>>>()?;
+    if kinds.is_empty() {
+        return Ok(vec![PropertyKind::CONFIGURE]);
+    }
+    Ok(kinds)
+}
+
+/// Returns the action of a property, when given.
+fn action(node: Node, file_name: &str) -> profile::Result<Option<ActionType>> {
+    let Some(action) = child(node, "action") else {
+        return Ok(None);
+    };
+    let action = match attribute(action, "type", file_name)?.as_str() {
+        "eq" => ActionType::EQ,
+        "ne" => ActionType::NE,
+        "gt" => ActionType::GT,
+        "lt" => ActionType::LT,
+        "ge" => ActionType::GE,
+        "le" => ActionType::LE,
+        "external" => ActionType::EXTERNAL,
+        other => {
+            return Err(profile::invalid(
+                file_name,
+                &format!("unknown action '{other}'"),
+            ))
+        }
+    };
+    Ok(Some(action))
+}
+
+/// Returns the range of a property, when given.
+fn range(
+    node: Node,
+    value_type: PropertyType,
+    id: &str,
+    file_name: &str,
+) -> profile::Result<Option<Range>> {
+    child(node, "range")
+        .map(|r| {
+            Ok(Range {
+                min: value(value_type, &attribute(r, "min", file_name)?, id, file_name)?,
+                max: value(value_type, &attribute(r, "max", file_name)?, id, file_name)?,
+            })
+        })
+        .transpose()
+}
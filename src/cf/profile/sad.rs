@@ -0,0 +1,564 @@
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+
+use super::super::common_types::{AnyValue, DataType, Properties};
+use super::writer::{self, Element};
+use super::{
+    self as profile, attribute, child, child_text, children, component_files, placements,
+    ComponentFile, ComponentInstantiation, ComponentPlacement,
+};
+
+/**
+ * This type references a port of a component instantiation.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortReference {
+    /// The name of the port.
+    pub identifier: String,
+    /// The id of the component instantiation owning the port.
+    pub component_ref: String,
+}
+
+/**
+ * This type describes a findby element: an endpoint found through the
+ * naming service, a stringified object reference or the domain finder.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FindBy {
+    NamingService {
+        name: String,
+    },
+    /// The reference itself, i.e. the endpoint.
+    StringifiedObjectRef(String),
+    DomainFinder {
+        finder_type: String,
+        name: Option<String>,
+    },
+}
+
+/**
+ * This type describes the provides side of a connection: a provides
+ * port, an interface supported by a component itself, or an endpoint
+ * found by other means.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConnectionTarget {
+    ProvidesPort(PortReference),
+    /// The identifier is the supportedidentifier, the repository id of the interface.
+    SupportedInterface(PortReference),
+    FindBy(FindBy),
+}
+
+impl ConnectionTarget {
+    /// Returns the component the target belongs to, when any.
+    pub fn component_ref(&self) -> Option<&str> {
+        match self {
+            ConnectionTarget::ProvidesPort(p) | ConnectionTarget::SupportedInterface(p) => {
+                Some(&p.component_ref)
+            }
+            ConnectionTarget::FindBy(_) => None,
+        }
+    }
+}
+
+/**
+ * This type describes a connection from a uses port to its target.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    /// The id of the connectinterface element, when given.
+    pub id: Option<String>,
+    pub uses_port: PortReference,
+    pub target: ConnectionTarget,
+}
+
+/**
+ * This type defines the kinds of component ports.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortKind {
+    USES,
+    PROVIDES,
+}
+
+/**
+ * This type describes a component port made visible as a port of the
+ * application.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalPort {
+    /// The name of the port on the application: its externalname, or the port name.
+    pub name: String,
+    pub kind: PortKind,
+    pub port: PortReference,
+}
+
+/**
+ * This type describes a component property made visible as a property
+ * of the application.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalProperty {
+    /// The id of the property on the application: its externalpropid, or the property id.
+    pub id: String,
+    pub property_id: String,
+    /// The id of the component instantiation owning the property.
+    pub component_ref: String,
+}
+
+/**
+ * This type describes a hostcollocation element: component
+ * instantiations to be deployed on the same host.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostCollocation {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    /// The ids of the collocated component instantiations.
+    pub instantiations: Vec<String>,
+}
+
+/**
+ * This type describes a usesdevice element: a device the application
+ * uses, whose allocation properties shall match the property
+ * references.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsesDevice {
+    pub id: String,
+    pub device_type: Option<String>,
+    pub properties: Properties,
+}
+
+/**
+ * Software Assembly Descriptor: the components of an application, their
+ * placement and the assembly controller driving them.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftwareAssembly {
+    pub id: String,
+    pub name: String,
+    pub component_files: Vec<ComponentFile>,
+    pub placements: Vec<ComponentPlacement>,
+    pub host_collocations: Vec<HostCollocation>,
+    pub uses_devices: Vec<UsesDevice>,
+    /// The id of the component instantiation acting as assembly controller.
+    pub assembly_controller: Option<String>,
+    pub connections: Vec<Connection>,
+    pub external_ports: Vec<ExternalPort>,
+    pub external_properties: Vec<ExternalProperty>,
+}
+
+impl SoftwareAssembly {
+    /// Parses a SAD document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<SoftwareAssembly> {
+        let document = profile::parse_document(xml, "softwareassembly", file_name)?;
+        let root = document.root_element();
+
+        let component_files = component_files(root, file_name)?;
+        let placements = placements(root, &component_files, file_name)?;
+        let host_collocations = child(root, "partitioning")
+            .map(|p| {
+                children(p, "hostcollocation")
+                    .map(|h| HostCollocation {
+                        id: h.attribute("id").map(str::to_string),
+                        name: h.attribute("name").map(str::to_string),
+                        instantiations: children(h, "componentplacement")
+                            .flat_map(|c| children(c, "componentinstantiation"))
+                            .filter_map(|i| i.attribute("id").map(str::to_string))
+                            .collect(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let uses_devices = child(root, "usesdevicedependencies")
+            .map(|u| {
+                children(u, "usesdevice")
+                    .map(|d| uses_device(d, file_name))
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let assembly_controller = child(root, "assemblycontroller")
+            .and_then(|a| child(a, "componentinstantiationref"))
+            .map(|r| attribute(r, "refid", file_name))
+            .transpose()?;
+
+        let connections = child(root, "connections")
+            .map(|c| {
+                children(c, "connectinterface")
+                    .map(|i| connection(i, file_name))
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let external_ports = child(root, "externalports")
+            .map(|e| {
+                children(e, "port")
+                    .map(|p| external_port(p, file_name))
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let external_properties = child(root, "externalproperties")
+            .map(|e| {
+                children(e, "property")
+                    .map(|p| {
+                        let property_id = attribute(p, "propid", file_name)?;
+                        Ok(ExternalProperty {
+                            id: p
+                                .attribute("externalpropid")
+                                .map_or_else(|| property_id.clone(), str::to_string),
+                            property_id,
+                            component_ref: attribute(p, "comprefid", file_name)?,
+                        })
+                    })
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let assembly = SoftwareAssembly {
+            id: attribute(root, "id", file_name)?,
+            name: attribute(root, "name", file_name)?,
+            component_files,
+            placements,
+            host_collocations,
+            uses_devices,
+            assembly_controller,
+            connections,
+            external_ports,
+            external_properties,
+        };
+
+        //verify the assembly controller is a placed component
+        if let Some(refid) = &assembly.assembly_controller {
+            if assembly.instantiation(refid).is_none() {
+                return Err(profile::invalid(
+                    file_name,
+                    &format!("unknown assembly controller '{refid}'"),
+                ));
+            }
+        }
+
+        //verify the connections reference placed components
+        if let Some(component_ref) = assembly
+            .connections
+            .iter()
+            .flat_map(|c| {
+                [
+                    Some(c.uses_port.component_ref.as_str()),
+                    c.target.component_ref(),
+                ]
+            })
+            .flatten()
+            .find(|r| assembly.instantiation(r).is_none())
+        {
+            return Err(profile::invalid(
+                file_name,
+                &format!("unknown connected component '{component_ref}'"),
+            ));
+        }
+
+        //verify the external ports and properties belong to placed components
+        let external = assembly
+            .external_ports
+            .iter()
+            .map(|p| &p.port.component_ref)
+            .chain(
+                assembly
+                    .external_properties
+                    .iter()
+                    .map(|p| &p.component_ref),
+            );
+        for component_ref in external {
+            if assembly.instantiation(component_ref).is_none() {
+                return Err(profile::invalid(
+                    file_name,
+                    &format!("unknown external component '{component_ref}'"),
+                ));
+            }
+        }
+        Ok(assembly)
+    }
+
+    /// Returns the component file of a placement.
+    pub fn component_file(&self, placement: &ComponentPlacement) -> Option<&ComponentFile> {
+        self.component_files
+            .iter()
+            .find(|f| f.id == placement.file_ref)
+    }
+
+    /// Returns a component instantiation by id.
+    pub fn instantiation(&self, id: &str) -> Option<&ComponentInstantiation> {
+        self.placements
+            .iter()
+            .flat_map(|p| &p.instantiations)
+            .find(|i| i.id == id)
+    }
+
+    /**
+     * Returns the SAD document describing the assembly. The placements
+     * of a host collocation are written within it, where its first
+     * placement stands.
+     */
+    pub fn to_xml(&self) -> String {
+        let collocation = |p: &ComponentPlacement| {
+            self.host_collocations.iter().position(|h| {
+                p.instantiations
+                    .iter()
+                    .any(|i| h.instantiations.contains(&i.id))
+            })
+        };
+        let mut partitioning = Vec::new();
+        let mut written = Vec::new();
+        for placement in &self.placements {
+            match collocation(placement) {
+                None => partitioning.push(writer::placement(placement)),
+                Some(index) if !written.contains(&index) => {
+                    written.push(index);
+                    let h = &self.host_collocations[index];
+                    partitioning.push(
+                        Element::new("hostcollocation")
+                            .with_optional_attribute("id", h.id.as_ref())
+                            .with_optional_attribute("name", h.name.as_ref())
+                            .with_children(
+                                self.placements
+                                    .iter()
+                                    .filter(|p| collocation(p) == Some(index))
+                                    .map(writer::placement),
+                            ),
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+
+        let connections = self
+            .connections
+            .iter()
+            .map(|c| {
+                let target = match &c.target {
+                    ConnectionTarget::ProvidesPort(p) => {
+                        port_element("providesport", "providesidentifier", p)
+                    }
+                    ConnectionTarget::SupportedInterface(p) => {
+                        port_element("componentsupportedinterface", "supportedidentifier", p)
+                    }
+                    ConnectionTarget::FindBy(f) => find_by_element(f),
+                };
+                Element::new("connectinterface")
+                    .with_optional_attribute("id", c.id.as_ref())
+                    .with_child(port_element("usesport", "usesidentifier", &c.uses_port))
+                    .with_child(target)
+            })
+            .collect();
+
+        let external_ports = self
+            .external_ports
+            .iter()
+            .map(|p| {
+                let tag = match p.kind {
+                    PortKind::USES => "usesidentifier",
+                    PortKind::PROVIDES => "providesidentifier",
+                };
+                Element::new("port")
+                    .with_optional_attribute(
+                        "externalname",
+                        (p.name != p.port.identifier).then_some(&p.name),
+                    )
+                    .with_text_child(tag, Some(&p.port.identifier))
+                    .with_child(component_ref(&p.port.component_ref))
+            })
+            .collect();
+
+        let external_properties = self
+            .external_properties
+            .iter()
+            .map(|p| {
+                Element::new("property")
+                    .with_attribute("comprefid", &p.component_ref)
+                    .with_attribute("propid", &p.property_id)
+                    .with_optional_attribute(
+                        "externalpropid",
+                        (p.id != p.property_id).then_some(&p.id),
+                    )
+            })
+            .collect();
+
+        let uses_devices = self
+            .uses_devices
+            .iter()
+            .map(|d| {
+                Element::new("usesdevice")
+                    .with_attribute("id", &d.id)
+                    .with_optional_attribute("type", d.device_type.as_ref())
+                    .with_children(d.properties.iter().map(|p| {
+                        Element::new("propertyref")
+                            .with_attribute("refid", &p.id)
+                            .with_attribute("value", &p.value)
+                    }))
+            })
+            .collect();
+
+        Element::new("softwareassembly")
+            .with_attribute("id", &self.id)
+            .with_attribute("name", &self.name)
+            .with_container(
+                "componentfiles",
+                writer::component_files(&self.component_files),
+            )
+            .with_container("partitioning", partitioning)
+            .with_optional_child(
+                self.assembly_controller
+                    .as_ref()
+                    .map(|a| Element::new("assemblycontroller").with_child(component_ref(a))),
+            )
+            .with_container("connections", connections)
+            .with_container("externalports", external_ports)
+            .with_container("externalproperties", external_properties)
+            .with_container("usesdevicedependencies", uses_devices)
+            .to_document()
+    }
+}
+
+/// Parses a usesdevice element.
+fn uses_device(node: Node, file_name: &str) -> profile::Result<UsesDevice> {
+    Ok(UsesDevice {
+        id: attribute(node, "id", file_name)?,
+        device_type: node.attribute("type").map(str::to_string),
+        properties: children(node, "propertyref")
+            .map(|p| {
+                Ok(DataType::new(
+                    &attribute(p, "refid", file_name)?,
+                    AnyValue::String(attribute(p, "value", file_name)?),
+                ))
+            })
+            .collect::<profile::Result<Properties>>()?,
+    })
+}
+
+/**
+ * Parses a connectinterface element, its uses port being a port of a
+ * component instantiation.
+ */
+fn connection(node: Node, file_name: &str) -> profile::Result<Connection> {
+    let target = if child(node, "providesport").is_some() {
+        ConnectionTarget::ProvidesPort(port(node, "providesport", "providesidentifier", file_name)?)
+    } else if child(node, "componentsupportedinterface").is_some() {
+        ConnectionTarget::SupportedInterface(port(
+            node,
+            "componentsupportedinterface",
+            "supportedidentifier",
+            file_name,
+        )?)
+    } else if let Some(find_by) = child(node, "findby") {
+        ConnectionTarget::FindBy(self::find_by(find_by, file_name)?)
+    } else {
+        return Err(profile::invalid(
+            file_name,
+            "<connectinterface> misses <providesport>, <componentsupportedinterface> or <findby>",
+        ));
+    };
+
+    Ok(Connection {
+        id: node.attribute("id").map(str::to_string),
+        uses_port: port(node, "usesport", "usesidentifier", file_name)?,
+        target,
+    })
+}
+
+/// Parses a findby element.
+fn find_by(node: Node, file_name: &str) -> profile::Result<FindBy> {
+    if let Some(naming_service) = child(node, "namingservice") {
+        return Ok(FindBy::NamingService {
+            name: attribute(naming_service, "name", file_name)?,
+        });
+    }
+    if let Some(reference) = child_text(node, "stringifiedobjectref") {
+        return Ok(FindBy::StringifiedObjectRef(reference));
+    }
+    if let Some(finder) = child(node, "domainfinder") {
+        return Ok(FindBy::DomainFinder {
+            finder_type: attribute(finder, "type", file_name)?,
+            name: finder.attribute("name").map(str::to_string),
+        });
+    }
+    Err(profile::invalid(file_name, "<findby> misses its reference"))
+}
+
+/// Parses the port element of a connectinterface element.
+fn port(
+    node: Node,
+    tag: &str,
+    identifier: &str,
+    file_name: &str,
+) -> profile::Result<PortReference> {
+    let missing = |name: &str| profile::invalid(file_name, &format!("<{tag}> misses <{name}>"));
+
+    let port = child(node, tag).ok_or_else(|| {
+        profile::invalid(file_name, &format!("<connectinterface> misses <{tag}>"))
+    })?;
+    let component = child(port, "componentinstantiationref")
+        .ok_or_else(|| missing("componentinstantiationref"))?;
+    Ok(PortReference {
+        identifier: child_text(port, identifier).ok_or_else(|| missing(identifier))?,
+        component_ref: attribute(component, "refid", file_name)?,
+    })
+}
+
+/// Parses a port element of the externalports element.
+fn external_port(node: Node, file_name: &str) -> profile::Result<ExternalPort> {
+    let (kind, tag) = match child(node, "usesidentifier") {
+        Some(_) => (PortKind::USES, "usesidentifier"),
+        None => (PortKind::PROVIDES, "providesidentifier"),
+    };
+    let identifier = child_text(node, tag)
+        .ok_or_else(|| profile::invalid(file_name, &format!("<port> misses <{tag}>")))?;
+    let component = child(node, "componentinstantiationref")
+        .ok_or_else(|| profile::invalid(file_name, "<port> misses <componentinstantiationref>"))?;
+
+    Ok(ExternalPort {
+        name: node
+            .attribute("externalname")
+            .map_or_else(|| identifier.clone(), str::to_string),
+        kind,
+        port: PortReference {
+            identifier,
+            component_ref: attribute(component, "refid", file_name)?,
+        },
+    })
+}
+
+/// Returns a componentinstantiationref element.
+fn component_ref(refid: &str) -> Element {
+    Element::new("componentinstantiationref").with_attribute("refid", refid)
+}
+
+/// Returns the port element of a connectinterface element.
+fn port_element(tag: &str, identifier: &str, port: &PortReference) -> Element {
+    Element::new(tag)
+        .with_text_child(identifier, Some(&port.identifier))
+        .with_child(component_ref(&port.component_ref))
+}
+
+/// Returns a findby element.
+fn find_by_element(find_by: &FindBy) -> Element {
+    let reference = match find_by {
+        FindBy::NamingService { name } => {
+            Element::new("namingservice").with_attribute("name", name)
+        }
+        FindBy::StringifiedObjectRef(reference) => {
+            Element::new("stringifiedobjectref").with_text(reference)
+        }
+        FindBy::DomainFinder { finder_type, name } => Element::new("domainfinder")
+            .with_attribute("type", finder_type)
+            .with_optional_attribute("name", name.as_ref()),
+    };
+    Element::new("findby").with_child(reference)
+}
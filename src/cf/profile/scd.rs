@@ -0,0 +1,207 @@
+use roxmltree::Node;
+
+use super::sad::PortKind;
+use super::writer::{self, Element};
+use super::{self as profile, attribute, child, child_text, children, local_file};
+
+/**
+ * This type describes an interface supported by the component itself.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportedInterface {
+    pub repository_id: String,
+    pub name: String,
+}
+
+/**
+ * This type describes a port of the component.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Port {
+    pub name: String,
+    /// The repository id of the interface of the port.
+    pub repository_id: String,
+    pub kind: PortKind,
+    /// The types of the port: data, control, responses or test.
+    pub port_types: Vec<String>,
+}
+
+/**
+ * This type describes an interface of the component, supported or of a
+ * port, along with the interfaces it inherits.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub repository_id: String,
+    pub name: String,
+    /// The repository ids of the inherited interfaces.
+    pub inherits: Vec<String>,
+}
+
+/**
+ * Software Component Descriptor: the type of a component, the interfaces
+ * it supports and its ports.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftwareComponent {
+    pub corba_version: Option<String>,
+    /// The repository id of the component interface.
+    pub repository_id: Option<String>,
+    /// The type of the component: resource, device, loadabledevice, executabledevice...
+    pub component_type: Option<String>,
+    pub supported_interfaces: Vec<SupportedInterface>,
+    pub ports: Vec<Port>,
+    pub interfaces: Vec<Interface>,
+    pub property_file: Option<String>,
+}
+
+impl SoftwareComponent {
+    /// Parses a SCD document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<SoftwareComponent> {
+        let document = profile::parse_document(xml, "softwarecomponent", file_name)?;
+        let root = document.root_element();
+
+        let features = child(root, "componentfeatures");
+        let supported_interfaces = features
+            .map(|f| {
+                children(f, "supportsinterface")
+                    .map(|s| {
+                        Ok(SupportedInterface {
+                            repository_id: attribute(s, "repid", file_name)?,
+                            name: attribute(s, "supportsname", file_name)?,
+                        })
+                    })
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let ports = features
+            .and_then(|f| child(f, "ports"))
+            .map(|p| {
+                p.children()
+                    .filter(|n| n.is_element())
+                    .map(|n| port(n, file_name))
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let interfaces = child(root, "interfaces")
+            .map(|i| {
+                children(i, "interface")
+                    .map(|i| {
+                        Ok(Interface {
+                            repository_id: attribute(i, "repid", file_name)?,
+                            name: attribute(i, "name", file_name)?,
+                            inherits: children(i, "inheritsinterface")
+                                .map(|n| attribute(n, "repid", file_name))
+                                .collect::<profile::Result<Vec<_>>>()?,
+                        })
+                    })
+                    .collect::<profile::Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(SoftwareComponent {
+            corba_version: child_text(root, "corbaversion"),
+            repository_id: child(root, "componentrepid")
+                .map(|r| attribute(r, "repid", file_name))
+                .transpose()?,
+            component_type: child_text(root, "componenttype"),
+            supported_interfaces,
+            ports,
+            interfaces,
+            property_file: child(root, "propertyfile")
+                .map(|p| local_file(p, file_name))
+                .transpose()?,
+        })
+    }
+
+    /// Returns a port by name.
+    pub fn port(&self, name: &str) -> Option<&Port> {
+        self.ports.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the SCD document describing the component.
+    pub fn to_xml(&self) -> String {
+        let supported_interfaces = self.supported_interfaces.iter().map(|s| {
+            Element::new("supportsinterface")
+                .with_attribute("repid", &s.repository_id)
+                .with_attribute("supportsname", &s.name)
+        });
+        let ports = self.ports.iter().map(|p| {
+            let (tag, name) = match p.kind {
+                PortKind::USES => ("uses", "usesname"),
+                PortKind::PROVIDES => ("provides", "providesname"),
+            };
+            Element::new(tag)
+                .with_attribute("repid", &p.repository_id)
+                .with_attribute(name, &p.name)
+                .with_children(
+                    p.port_types
+                        .iter()
+                        .map(|t| Element::new("porttype").with_attribute("type", t)),
+                )
+        });
+        let features =
+            (!self.supported_interfaces.is_empty() || !self.ports.is_empty()).then(|| {
+                Element::new("componentfeatures")
+                    .with_children(supported_interfaces)
+                    .with_child(Element::new("ports").with_children(ports))
+            });
+        let interfaces = self
+            .interfaces
+            .iter()
+            .map(|i| {
+                Element::new("interface")
+                    .with_attribute("repid", &i.repository_id)
+                    .with_attribute("name", &i.name)
+                    .with_children(
+                        i.inherits
+                            .iter()
+                            .map(|r| Element::new("inheritsinterface").with_attribute("repid", r)),
+                    )
+            })
+            .collect();
+
+        Element::new("softwarecomponent")
+            .with_text_child("corbaversion", self.corba_version.as_ref())
+            .with_optional_child(
+                self.repository_id
+                    .as_ref()
+                    .map(|r| Element::new("componentrepid").with_attribute("repid", r)),
+            )
+            .with_text_child("componenttype", self.component_type.as_ref())
+            .with_optional_child(features)
+            .with_container("interfaces", interfaces)
+            .with_optional_child(
+                self.property_file
+                    .as_ref()
+                    .map(|f| writer::local_file("propertyfile", f)),
+            )
+            .to_document()
+    }
+}
+
+/// Parses a provides or uses element of the ports.
+fn port(node: Node, file_name: &str) -> profile::Result<Port> {
+    let (kind, name) = match node.tag_name().name() {
+        "uses" => (PortKind::USES, "usesname"),
+        "provides" => (PortKind::PROVIDES, "providesname"),
+        other => {
+            return Err(profile::invalid(
+                file_name,
+                &format!("unknown port element <{other}>"),
+            ))
+        }
+    };
+    Ok(Port {
+        name: attribute(node, name, file_name)?,
+        repository_id: attribute(node, "repid", file_name)?,
+        kind,
+        port_types: children(node, "porttype")
+            .map(|t| attribute(t, "type", file_name))
+            .collect::<profile::Result<Vec<_>>>()?,
+    })
+}
@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use super as profile;
+
+/// The bundled descriptor DTDs, as (root element, file name, DTD).
+const SCHEMAS: &[(&str, &str, &str)] = &[
+    (
+        "softpkg",
+        "softpkg.dtd",
+        include_str!("../../../resources/schemas/softpkg.dtd"),
+    ),
+    (
+        "properties",
+        "properties.dtd",
+        include_str!("../../../resources/schemas/properties.dtd"),
+    ),
+    (
+        "softwareassembly",
+        "softwareassembly.dtd",
+        include_str!("../../../resources/schemas/softwareassembly.dtd"),
+    ),
+    (
+        "deviceconfiguration",
+        "deviceconfiguration.dtd",
+        include_str!("../../../resources/schemas/deviceconfiguration.dtd"),
+    ),
+    (
+        "domainmanagerconfiguration",
+        "domainmanagerconfiguration.dtd",
+        include_str!("../../../resources/schemas/domainmanagerconfiguration.dtd"),
+    ),
+    (
+        "softwarecomponent",
+        "softwarecomponent.dtd",
+        include_str!("../../../resources/schemas/softwarecomponent.dtd"),
+    ),
+];
+
+/**
+ * This type defines how many times a particle of a content model occurs:
+ * once, '?', '*' or '+'.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Occurrence {
+    Once,
+    Optional,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+/**
+ * This type describes a particle of a content model: an element, a
+ * sequence or a choice of particles.
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    Element(String),
+    Sequence(Vec<Particle>),
+    Choice(Vec<Particle>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Particle {
+    term: Term,
+    occurrence: Occurrence,
+}
+
+/**
+ * This type describes the content allowed in an element.
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Empty,
+    Any,
+    /// Text mixed with the elements, if any.
+    Mixed(Vec<String>),
+    Children(Particle),
+}
+
+/**
+ * This type describes the default declaration of an attribute.
+ */
+#[derive(Debug, Clone, PartialEq)]
+enum AttributeDefault {
+    Required,
+    Implied,
+    Fixed(String),
+    Value(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AttributeDefinition {
+    name: String,
+    /// The values allowed, any value being allowed when empty.
+    values: Vec<String>,
+    default: AttributeDefault,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ElementDefinition {
+    /// The content model as written in the DTD, for the diagnostics.
+    model: String,
+    content: Content,
+    attributes: Vec<AttributeDefinition>,
+}
+
+/**
+ * Document Type Definition a profile is validated against: its element
+ * and attribute list declarations.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    elements: HashMap<String, ElementDefinition>,
+}
+
+impl Schema {
+    /// Returns the bundled schema of the descriptors with the root element, if any.
+    pub fn bundled(root: &str) -> Option<Schema> {
+        let (_, file_name, dtd) = SCHEMAS.iter().find(|(r, _, _)| *r == root)?;
+        Some(Schema::parse(dtd, file_name).expect("bundled DTD"))
+    }
+
+    /// Parses a DTD, the file name only qualifying the errors.
+    pub fn parse(dtd: &str, file_name: &str) -> profile::Result<Schema> {
+        let mut schema = Schema::default();
+        for declaration in declarations(dtd) {
+            let tokens = tokenize(&declaration);
+            match tokens.first().map(String::as_str) {
+                Some("<!ELEMENT") => {
+                    let (name, definition) = element(&tokens[1..]).ok_or_else(|| {
+                        profile::invalid(file_name, &format!("invalid declaration '{declaration}'"))
+                    })?;
+                    let attributes = schema
+                        .elements
+                        .remove(&name)
+                        .map(|e| e.attributes)
+                        .unwrap_or_default();
+                    schema.elements.insert(
+                        name,
+                        ElementDefinition {
+                            attributes,
+                            ..definition
+                        },
+                    );
+                }
+                Some("<!ATTLIST") => {
+                    let (name, attributes) = attribute_list(&tokens[1..]).ok_or_else(|| {
+                        profile::invalid(file_name, &format!("invalid declaration '{declaration}'"))
+                    })?;
+                    let definition = schema.elements.entry(name).or_insert(ElementDefinition {
+                        model: "ANY".to_string(),
+                        content: Content::Any,
+                        attributes: Vec::new(),
+                    });
+                    definition.attributes.extend(attributes);
+                }
+                _ => {}
+            }
+        }
+        Ok(schema)
+    }
+
+    /**
+     * Validates a document, reporting every element and attribute not
+     * conforming to the declarations along with its line.
+     */
+    pub fn validate(&self, document: &Document, file_name: &str) -> profile::Result<()> {
+        let mut diagnostics = Vec::new();
+        self.validate_element(document.root_element(), &mut diagnostics);
+        if !diagnostics.is_empty() {
+            return Err(profile::invalid(file_name, &diagnostics.join("; ")));
+        }
+        Ok(())
+    }
+
+    fn validate_element(&self, node: Node, diagnostics: &mut Vec<String>) {
+        let name = node.tag_name().name();
+        let Some(definition) = self.elements.get(name) else {
+            diagnostics.push(format!("<{name}> at line {} is not declared", line(node)));
+            return;
+        };
+
+        //verify the attributes
+        for a in node.attributes().filter(|a| a.namespace().is_none()) {
+            match definition.attributes.iter().find(|d| d.name == a.name()) {
+                None => diagnostics.push(format!(
+                    "<{name}> at line {} has the undeclared '{}' attribute",
+                    line(node),
+                    a.name()
+                )),
+                Some(d) if !d.values.is_empty() && !d.values.iter().any(|v| v == a.value()) => {
+                    diagnostics.push(format!(
+                        "<{name}> at line {}: '{}' is not a value of the '{}' attribute, expected one of {}",
+                        line(node),
+                        a.value(),
+                        a.name(),
+                        d.values.join(", ")
+                    ))
+                }
+                Some(AttributeDefinition {
+                    default: AttributeDefault::Fixed(fixed),
+                    ..
+                }) if fixed != a.value() => diagnostics.push(format!(
+                    "<{name}> at line {}: the '{}' attribute is fixed to '{fixed}'",
+                    line(node),
+                    a.name()
+                )),
+                Some(_) => {}
+            }
+        }
+        for d in &definition.attributes {
+            if d.default == AttributeDefault::Required && node.attribute(d.name.as_str()).is_none()
+            {
+                diagnostics.push(format!(
+                    "<{name}> at line {} misses the '{}' attribute",
+                    line(node),
+                    d.name
+                ));
+            }
+        }
+
+        //verify the content
+        let children: Vec<Node> = node.children().filter(|n| n.is_element()).collect();
+        let has_text = node
+            .children()
+            .any(|n| n.is_text() && !n.text().unwrap_or_default().trim().is_empty());
+        match &definition.content {
+            Content::Any => {}
+            Content::Empty => {
+                if has_text || !children.is_empty() {
+                    diagnostics.push(format!("<{name}> at line {} shall be empty", line(node)));
+                }
+            }
+            Content::Mixed(allowed) => {
+                if let Some(child) = children
+                    .iter()
+                    .find(|c| !allowed.iter().any(|a| c.has_tag_name(a.as_str())))
+                {
+                    diagnostics.push(unexpected(node, *child, &definition.model));
+                }
+            }
+            Content::Children(particle) => {
+                if has_text {
+                    diagnostics.push(format!(
+                        "<{name}> at line {} does not allow text",
+                        line(node)
+                    ));
+                }
+                let names: Vec<&str> = children.iter().map(|c| c.tag_name().name()).collect();
+                if !particle.matches(&names, false) {
+                    //report the first child past the longest prefix that may be completed
+                    let prefix = (0..=names.len())
+                        .rev()
+                        .find(|end| particle.matches(&names[..*end], true))
+                        .unwrap_or_default();
+                    match children.get(prefix) {
+                        Some(child) => {
+                            diagnostics.push(unexpected(node, *child, &definition.model))
+                        }
+                        None => diagnostics.push(format!(
+                            "<{name}> at line {} is incomplete, expected {}",
+                            line(node),
+                            definition.model
+                        )),
+                    }
+                }
+            }
+        }
+
+        for child in children {
+            self.validate_element(child, diagnostics);
+        }
+    }
+}
+
+impl Particle {
+    /**
+     * Returns whether the particle matches the element names, or when open
+     * whether they are the start of a match.
+     */
+    fn matches(&self, names: &[&str], open: bool) -> bool {
+        self.ends(names, 0, open).contains(&names.len())
+    }
+
+    /**
+     * Returns the positions the particle may end at when matched against
+     * the element names from the start position. When open, the elements
+     * past the names are assumed to match.
+     */
+    fn ends(&self, names: &[&str], start: usize, open: bool) -> Vec<usize> {
+        let mut ends = match self.occurrence {
+            Occurrence::Once | Occurrence::OneOrMore => self.term_ends(names, start, open),
+            Occurrence::Optional | Occurrence::ZeroOrMore => {
+                let mut ends = self.term_ends(names, start, open);
+                ends.push(start);
+                ends
+            }
+        };
+
+        //repeat the term from every new end
+        if matches!(
+            self.occurrence,
+            Occurrence::ZeroOrMore | Occurrence::OneOrMore
+        ) {
+            let mut frontier = ends.clone();
+            while let Some(position) = frontier.pop() {
+                for end in self.term_ends(names, position, open) {
+                    if !ends.contains(&end) {
+                        ends.push(end);
+                        frontier.push(end);
+                    }
+                }
+            }
+        }
+        ends.sort_unstable();
+        ends.dedup();
+        ends
+    }
+
+    fn term_ends(&self, names: &[&str], start: usize, open: bool) -> Vec<usize> {
+        match &self.term {
+            Term::Element(name) => match names.get(start) {
+                Some(n) if n == name => vec![start + 1],
+                None if open => vec![start],
+                _ => Vec::new(),
+            },
+            Term::Sequence(particles) => particles.iter().fold(vec![start], |positions, p| {
+                let mut ends: Vec<usize> = positions
+                    .iter()
+                    .flat_map(|s| p.ends(names, *s, open))
+                    .collect();
+                ends.sort_unstable();
+                ends.dedup();
+                ends
+            }),
+            Term::Choice(particles) => particles
+                .iter()
+                .flat_map(|p| p.ends(names, start, open))
+                .collect(),
+        }
+    }
+}
+
+/**
+ * Validates a profile document against the bundled schema of its root
+ * element, the documents of other roots being accepted as is.
+ */
+pub fn validate(document: &Document, file_name: &str) -> profile::Result<()> {
+    match Schema::bundled(document.root_element().tag_name().name()) {
+        Some(schema) => schema.validate(document, file_name),
+        None => Ok(()),
+    }
+}
+
+/// Returns the line of an element.
+fn line(node: Node) -> u32 {
+    node.document().text_pos_at(node.range().start).row
+}
+
+/// Returns the diagnostic of a child not allowed at its position.
+fn unexpected(node: Node, child: Node, model: &str) -> String {
+    format!(
+        "unexpected <{}> at line {} in <{}>, expected {model}",
+        child.tag_name().name(),
+        line(child),
+        node.tag_name().name()
+    )
+}
+
+/// Returns the markup declarations of a DTD, comments left out.
+fn declarations(dtd: &str) -> Vec<String> {
+    let mut declarations = Vec::new();
+    let mut rest = dtd;
+    while let Some(start) = rest.find("<!") {
+        rest = &rest[start..];
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+        //the quoted values may hold '>'
+        let mut quote = None;
+        let end = rest.char_indices().find(|(_, c)| {
+            match (quote, *c) {
+                (None, '"' | '\'') => quote = Some(*c),
+                (Some(q), c) if q == c => quote = None,
+                (None, '>') => return true,
+                _ => {}
+            }
+            false
+        });
+        let Some((end, _)) = end else {
+            break;
+        };
+        declarations.push(rest[..end].to_string());
+        rest = &rest[end + 1..];
+    }
+    declarations
+}
+
+/// Splits a declaration into names, quoted values and punctuation.
+fn tokenize(declaration: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = declaration.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '|' | ',' | '?' | '*' | '+' => tokens.push(c.to_string()),
+            '"' | '\'' => {
+                let value: String = chars.by_ref().take_while(|v| *v != c).collect();
+                tokens.push(format!("\"{value}"));
+            }
+            _ => {
+                let mut token = c.to_string();
+                while let Some(n) =
+                    chars.next_if(|n| !n.is_whitespace() && !"()|,?*+\"'".contains(*n))
+                {
+                    token.push(n);
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    tokens
+}
+
+/// Parses the tokens of an element declaration following '<!ELEMENT'.
+fn element(tokens: &[String]) -> Option<(String, ElementDefinition)> {
+    let (name, spec) = tokens.split_first()?;
+    let content = match spec.first()?.as_str() {
+        "EMPTY" => Content::Empty,
+        "ANY" => Content::Any,
+        _ if spec.get(1).is_some_and(|t| t == "#PCDATA") => Content::Mixed(
+            spec.iter()
+                .filter(|t| !"()|*".contains(t.as_str()) && *t != "#PCDATA")
+                .cloned()
+                .collect(),
+        ),
+        _ => {
+            let mut position = 0;
+            let particle = particle(spec, &mut position)?;
+            if position != spec.len() {
+                return None;
+            }
+            Content::Children(particle)
+        }
+    };
+
+    //the model as written, without the layout
+    let mut model = String::new();
+    for token in spec {
+        match token.as_str() {
+            "|" => model.push_str(" | "),
+            "," => model.push_str(", "),
+            t => model.push_str(t),
+        }
+    }
+    Some((
+        name.clone(),
+        ElementDefinition {
+            model,
+            content,
+            attributes: Vec::new(),
+        },
+    ))
+}
+
+/// Parses a particle of a content model, with its occurrence.
+fn particle(tokens: &[String], position: &mut usize) -> Option<Particle> {
+    let term = match tokens.get(*position)?.as_str() {
+        "(" => {
+            *position += 1;
+            let mut particles = vec![particle(tokens, position)?];
+            let separator = tokens.get(*position)?.clone();
+            while tokens.get(*position)? == &separator && separator != ")" {
+                *position += 1;
+                particles.push(particle(tokens, position)?);
+            }
+            if tokens.get(*position)? != ")" {
+                return None;
+            }
+            *position += 1;
+            match separator.as_str() {
+                "|" => Term::Choice(particles),
+                _ => Term::Sequence(particles),
+            }
+        }
+        name if name
+            .chars()
+            .all(|c| c.is_alphanumeric() || "_-.:".contains(c)) =>
+        {
+            *position += 1;
+            Term::Element(name.to_string())
+        }
+        _ => return None,
+    };
+
+    let occurrence = match tokens.get(*position).map(String::as_str) {
+        Some("?") => Occurrence::Optional,
+        Some("*") => Occurrence::ZeroOrMore,
+        Some("+") => Occurrence::OneOrMore,
+        _ => Occurrence::Once,
+    };
+    if occurrence != Occurrence::Once {
+        *position += 1;
+    }
+    Some(Particle { term, occurrence })
+}
+
+/// Parses the tokens of an attribute list declaration following '<!ATTLIST'.
+fn attribute_list(tokens: &[String]) -> Option<(String, Vec<AttributeDefinition>)> {
+    let (element, mut rest) = tokens.split_first()?;
+    let mut attributes = Vec::new();
+    while let Some((name, tail)) = rest.split_first() {
+        //the type is a name or an enumeration of values
+        let (values, tail) = match tail.first()?.as_str() {
+            "(" => {
+                let end = tail.iter().position(|t| t == ")")?;
+                let values = tail[1..end].iter().filter(|t| *t != "|").cloned().collect();
+                (values, &tail[end + 1..])
+            }
+            _ => (Vec::new(), &tail[1..]),
+        };
+        let (default, tail) = match tail.first()?.as_str() {
+            "#REQUIRED" => (AttributeDefault::Required, &tail[1..]),
+            "#IMPLIED" => (AttributeDefault::Implied, &tail[1..]),
+            "#FIXED" => (
+                AttributeDefault::Fixed(tail.get(1)?.strip_prefix('"')?.to_string()),
+                &tail[2..],
+            ),
+            value => (
+                AttributeDefault::Value(value.strip_prefix('"')?.to_string()),
+                &tail[1..],
+            ),
+        };
+        attributes.push(AttributeDefinition {
+            name: name.clone(),
+            values,
+            default,
+        });
+        rest = tail;
+    }
+    Some((element.clone(), attributes))
+}
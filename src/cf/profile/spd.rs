@@ -0,0 +1,219 @@
+use roxmltree::Node;
+
+use super::super::common_types::{AnyValue, DataType, Properties};
+use super::super::gpp::{OS_NAME_ID, OS_VERSION_ID, PROCESSOR_NAME_ID};
+
+use super::writer::{self, Element};
+use super::{self as profile, attribute, child, child_text, children, local_file};
+
+/**
+ * This type describes the code of an implementation.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Code {
+    /// The type of the code: Executable, SharedLibrary, KernelModule or Driver.
+    pub code_type: String,
+    pub local_file: String,
+    pub entry_point: Option<String>,
+}
+
+/**
+ * This type describes an operating system an implementation runs on.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Os {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/**
+ * This type describes an implementation of a component.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Implementation {
+    pub id: String,
+    pub code: Option<Code>,
+    /// The PRF of the properties specific to the implementation.
+    pub property_file: Option<String>,
+    pub processors: Vec<String>,
+    pub os: Vec<Os>,
+    /// The allocation properties required by the propertyref dependencies, valued as strings.
+    pub dependencies: Properties,
+}
+
+impl Implementation {
+    /**
+     * Returns the alternative processor and os requirements of the
+     * implementation as allocation properties valued as strings, one set
+     * for each processor and os combination.
+     */
+    pub fn platform_requirements(&self) -> Vec<Properties> {
+        let text = |id: &str, value: &str| DataType::new(id, AnyValue::String(value.to_string()));
+        let processors: Vec<Option<&String>> = match self.processors.is_empty() {
+            true => vec![None],
+            false => self.processors.iter().map(Some).collect(),
+        };
+        let os: Vec<Option<&Os>> = match self.os.is_empty() {
+            true => vec![None],
+            false => self.os.iter().map(Some).collect(),
+        };
+
+        processors
+            .iter()
+            .flat_map(|processor| {
+                os.iter().map(move |os| {
+                    let mut properties = Properties::new();
+                    if let Some(processor) = processor {
+                        properties.push(text(PROCESSOR_NAME_ID, processor));
+                    }
+                    if let Some(os) = os {
+                        properties.push(text(OS_NAME_ID, &os.name));
+                        if let Some(version) = &os.version {
+                            properties.push(text(OS_VERSION_ID, version));
+                        }
+                    }
+                    properties
+                })
+            })
+            .collect()
+    }
+}
+
+/**
+ * Software Package Descriptor: the implementations of a component along
+ * with its properties and interfaces descriptors.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoftPkg {
+    pub id: String,
+    pub name: String,
+    /// The PRF of the component properties.
+    pub property_file: Option<String>,
+    /// The SCD of the component.
+    pub descriptor: Option<String>,
+    pub implementations: Vec<Implementation>,
+}
+
+impl SoftPkg {
+    /// Parses a SPD document, the file name only qualifying the errors.
+    pub fn parse(xml: &str, file_name: &str) -> profile::Result<SoftPkg> {
+        let document = profile::parse_document(xml, "softpkg", file_name)?;
+        let root = document.root_element();
+
+        let implementations = children(root, "implementation")
+            .map(|i| implementation(i, file_name))
+            .collect::<profile::Result<Vec<_>>>()?;
+
+        Ok(SoftPkg {
+            id: attribute(root, "id", file_name)?,
+            name: attribute(root, "name", file_name)?,
+            property_file: child(root, "propertyfile")
+                .map(|p| local_file(p, file_name))
+                .transpose()?,
+            descriptor: child(root, "descriptor")
+                .map(|d| local_file(d, file_name))
+                .transpose()?,
+            implementations,
+        })
+    }
+
+    /// Returns an implementation by id.
+    pub fn implementation(&self, id: &str) -> Option<&Implementation> {
+        self.implementations.iter().find(|i| i.id == id)
+    }
+
+    /// Returns the SPD document describing the package.
+    pub fn to_xml(&self) -> String {
+        let implementations = self.implementations.iter().map(|i| {
+            let code = i.code.as_ref().map(|c| {
+                writer::local_file("code", &c.local_file)
+                    .with_attribute("type", &c.code_type)
+                    .with_text_child("entrypoint", c.entry_point.as_ref())
+            });
+            Element::new("implementation")
+                .with_attribute("id", &i.id)
+                .with_optional_child(
+                    i.property_file
+                        .as_ref()
+                        .map(|f| writer::local_file("propertyfile", f)),
+                )
+                .with_optional_child(code)
+                .with_children(i.os.iter().map(|o| {
+                    Element::new("os")
+                        .with_attribute("name", &o.name)
+                        .with_optional_attribute("version", o.version.as_ref())
+                }))
+                .with_children(
+                    i.processors
+                        .iter()
+                        .map(|p| Element::new("processor").with_attribute("name", p)),
+                )
+                .with_children(i.dependencies.iter().map(|d| {
+                    Element::new("dependency")
+                        .with_attribute("type", "allocation")
+                        .with_child(
+                            Element::new("propertyref")
+                                .with_attribute("refid", &d.id)
+                                .with_attribute("value", d.value.to_string()),
+                        )
+                }))
+        });
+
+        Element::new("softpkg")
+            .with_attribute("id", &self.id)
+            .with_attribute("name", &self.name)
+            .with_optional_child(
+                self.property_file
+                    .as_ref()
+                    .map(|f| writer::local_file("propertyfile", f)),
+            )
+            .with_optional_child(
+                self.descriptor
+                    .as_ref()
+                    .map(|f| writer::local_file("descriptor", f)),
+            )
+            .with_children(implementations)
+            .to_document()
+    }
+}
+
+/// Parses an implementation element.
+fn implementation(node: Node, file_name: &str) -> profile::Result<Implementation> {
+    let code = child(node, "code")
+        .map(|c| {
+            Ok(Code {
+                code_type: c.attribute("type").unwrap_or("Executable").to_string(),
+                local_file: local_file(c, file_name)?,
+                entry_point: child_text(c, "entrypoint"),
+            })
+        })
+        .transpose()?;
+
+    Ok(Implementation {
+        id: attribute(node, "id", file_name)?,
+        code,
+        property_file: child(node, "propertyfile")
+            .map(|p| local_file(p, file_name))
+            .transpose()?,
+        processors: children(node, "processor")
+            .map(|p| attribute(p, "name", file_name))
+            .collect::<profile::Result<Vec<_>>>()?,
+        os: children(node, "os")
+            .map(|o| {
+                Ok(Os {
+                    name: attribute(o, "name", file_name)?,
+                    version: o.attribute("version").map(str::to_string),
+                })
+            })
+            .collect::<profile::Result<Vec<_>>>()?,
+        dependencies: children(node, "dependency")
+            .filter_map(|d| child(d, "propertyref"))
+            .map(|p| {
+                Ok(DataType::new(
+                    &attribute(p, "refid", file_name)?,
+                    AnyValue::String(attribute(p, "value", file_name)?),
+                ))
+            })
+            .collect::<profile::Result<Properties>>()?,
+    })
+}
@@ -0,0 +1,139 @@
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+
+use super::super::file_system::FileSystemTrait;
+use super::lint::{self, Issue};
+use super::plan::{self, DeploymentPlan};
+use super::{self as profile, ProfileError};
+
+/**
+ * The report of the validation of a waveform: the issues found by the
+ * linter in its descriptors and, when node configurations are given,
+ * the dry-run of its deployment on their devices.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub software_profile: String,
+    pub issues: Vec<Issue>,
+    /// None without node configurations, or when the deployment cannot be planned.
+    pub plan: Option<DeploymentPlan>,
+}
+
+impl ValidationReport {
+    /// Tells whether no issue is found and every component is deployed.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty() && self.plan.as_ref().is_none_or(DeploymentPlan::is_feasible)
+    }
+
+    /// Returns the human-readable report.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let verdict = if self.is_valid() { "valid" } else { "invalid" };
+        let _ = writeln!(text, "{}: {verdict}", self.software_profile);
+        for issue in &self.issues {
+            let _ = writeln!(text, "issue: {issue}");
+        }
+        let Some(plan) = &self.plan else {
+            return text;
+        };
+        for assignment in &plan.assignments {
+            let _ = writeln!(
+                text,
+                "deploy: '{}' on '{}'{}",
+                assignment.component_id,
+                assignment.device_id,
+                assignment
+                    .implementation_id
+                    .as_ref()
+                    .map(|i| format!(" ({i})"))
+                    .unwrap_or_default()
+            );
+        }
+        for rejection in &plan.rejections {
+            let _ = writeln!(text, "rejected: '{}'", rejection.component_id);
+            for reason in &rejection.reasons {
+                let _ = writeln!(text, "    {reason}");
+            }
+        }
+        for shortfall in &plan.shortfalls {
+            let _ = writeln!(
+                text,
+                "shortfall: '{}' of '{}' for '{}': {} required, {} available",
+                shortfall.capacity_id,
+                shortfall.device_id,
+                shortfall.component_id,
+                shortfall.required,
+                shortfall.available
+            );
+        }
+        text
+    }
+
+    /// Returns the JSON report, the property values as text.
+    pub fn to_json(&self) -> Value {
+        let deployment = self.plan.as_ref().map(|plan| {
+            json!({
+                "feasible": plan.is_feasible(),
+                "assignments": plan.assignments.iter().map(|a| json!({
+                    "component_id": a.component_id,
+                    "device_id": a.device_id,
+                    "implementation_id": a.implementation_id,
+                })).collect::<Vec<_>>(),
+                "rejections": plan.rejections.iter().map(|r| json!({
+                    "component_id": r.component_id,
+                    "reasons": r.reasons,
+                })).collect::<Vec<_>>(),
+                "shortfalls": plan.shortfalls.iter().map(|s| json!({
+                    "component_id": s.component_id,
+                    "device_id": s.device_id,
+                    "capacity_id": s.capacity_id,
+                    "required": s.required.to_string(),
+                    "available": s.available.to_string(),
+                })).collect::<Vec<_>>(),
+            })
+        });
+        json!({
+            "software_profile": self.software_profile,
+            "valid": self.is_valid(),
+            "issues": self.issues.iter().map(|i| json!({
+                "file_name": i.file_name,
+                "message": i.message,
+            })).collect::<Vec<_>>(),
+            "deployment": deployment,
+        })
+    }
+}
+
+/**
+ * Validates a SAD: lints its descriptors and plans its deployment on
+ * the devices of the node configurations, if any. The descriptors the
+ * planning fails on are reported as issues. An error is only returned
+ * when the SAD itself is unreadable or invalid.
+ */
+pub fn validate(
+    file_system: &dyn FileSystemTrait,
+    software_profile: &str,
+    device_configurations: &[&str],
+) -> profile::Result<ValidationReport> {
+    let mut issues = lint::lint(file_system, software_profile)?;
+    let mut plan = None;
+    if !device_configurations.is_empty() {
+        match plan::plan(file_system, software_profile, device_configurations) {
+            Ok(deployment) => plan = Some(deployment),
+            Err(e) => {
+                let (ProfileError::ProfileNotFound { file_name, .. }
+                | ProfileError::InvalidProfile { file_name, .. }) = &e;
+                issues.push(Issue {
+                    file_name: file_name.clone(),
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+    Ok(ValidationReport {
+        software_profile: software_profile.to_string(),
+        issues,
+        plan,
+    })
+}
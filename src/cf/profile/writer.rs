@@ -0,0 +1,163 @@
+use super::{ComponentFile, ComponentPlacement};
+
+/**
+ * Element of a profile document being written: its attributes, in
+ * order, and either its text or its child elements.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Element {
+        Element {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            text: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_attribute(mut self, name: &str, value: impl ToString) -> Element {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds the attribute when it has a value.
+    pub fn with_optional_attribute(self, name: &str, value: Option<impl ToString>) -> Element {
+        match value {
+            Some(value) => self.with_attribute(name, value),
+            None => self,
+        }
+    }
+
+    pub fn with_text(mut self, text: impl ToString) -> Element {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn with_child(mut self, child: Element) -> Element {
+        self.children.push(child);
+        self
+    }
+
+    /// Adds the child when given.
+    pub fn with_optional_child(self, child: Option<Element>) -> Element {
+        match child {
+            Some(child) => self.with_child(child),
+            None => self,
+        }
+    }
+
+    pub fn with_children(mut self, children: impl IntoIterator<Item = Element>) -> Element {
+        self.children.extend(children);
+        self
+    }
+
+    /// Adds a child element holding the text, when given.
+    pub fn with_text_child(self, name: &str, text: Option<impl ToString>) -> Element {
+        self.with_optional_child(text.map(|t| Element::new(name).with_text(t)))
+    }
+
+    /**
+     * Adds a child element holding the children when there are any,
+     * e.g. the componentfiles of an assembly.
+     */
+    pub fn with_container(self, name: &str, children: Vec<Element>) -> Element {
+        self.with_optional_child(
+            (!children.is_empty()).then(|| Element::new(name).with_children(children)),
+        )
+    }
+
+    /// Returns the XML document the element is the root of.
+    pub fn to_document(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        self.write(&mut xml, 0);
+        xml
+    }
+
+    fn write(&self, xml: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        xml.push_str(&format!("{indent}<{}", self.name));
+        for (name, value) in &self.attributes {
+            xml.push_str(&format!(" {name}=\"{}\"", escape(value)));
+        }
+        match &self.text {
+            Some(text) => xml.push_str(&format!(">{}</{}>\n", escape(text), self.name)),
+            None if self.children.is_empty() => xml.push_str("/>\n"),
+            None => {
+                xml.push_str(">\n");
+                for child in &self.children {
+                    child.write(xml, depth + 1);
+                }
+                xml.push_str(&format!("{indent}</{}>\n", self.name));
+            }
+        }
+    }
+}
+
+/// Returns an element referencing a file through its localfile child.
+pub(crate) fn local_file(name: &str, file_name: &str) -> Element {
+    Element::new(name).with_child(Element::new("localfile").with_attribute("name", file_name))
+}
+
+/// Returns the componentfile elements of an assembly or a configuration.
+pub(crate) fn component_files(files: &[ComponentFile]) -> Vec<Element> {
+    files
+        .iter()
+        .map(|f| {
+            local_file("componentfile", &f.local_file)
+                .with_attribute("id", &f.id)
+                .with_attribute("type", &f.file_type)
+        })
+        .collect()
+}
+
+/// Returns a componentplacement element.
+pub(crate) fn placement(placement: &ComponentPlacement) -> Element {
+    let instantiations = placement.instantiations.iter().map(|i| {
+        let properties = i
+            .properties
+            .iter()
+            .map(|p| {
+                Element::new("simpleref")
+                    .with_attribute("refid", &p.id)
+                    .with_attribute("value", &p.value)
+            })
+            .collect();
+        Element::new("componentinstantiation")
+            .with_attribute("id", &i.id)
+            .with_optional_attribute("startorder", i.start_order)
+            .with_text_child("usagename", i.usage_name.as_ref())
+            .with_container("componentproperties", properties)
+    });
+
+    Element::new("componentplacement")
+        .with_child(Element::new("componentfileref").with_attribute("refid", &placement.file_ref))
+        .with_optional_child(
+            placement
+                .composite_part_of
+                .as_ref()
+                .map(|r| Element::new("compositepartofdevice").with_attribute("refid", r)),
+        )
+        .with_children(instantiations)
+}
+
+/// Escapes the markup characters of a text or an attribute value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
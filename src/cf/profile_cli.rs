@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use scars::cf::cli::{self, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION};
+use scars::cf::file_system::FileSystem;
+use scars::cf::profile::{diff, lint, plan};
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-profile",
+    options: &[FORMAT_OPTION, COMPLETIONS_OPTION],
+    commands: &["lint", "plan", "diff"],
+};
+
+/**
+ * Profile command line interface: checks the domain profiles of the
+ * local file system, plans their deployment and compares their versions.
+ * As JSON, the issues, the plan and the changes are printed as arrays
+ * and objects of their fields.
+ *
+ * usage: scars-profile [--format text|json] lint <sad file>
+ *        scars-profile [--format text|json] plan <sad file> <dcd file>...
+ *        scars-profile [--format text|json] diff <old file> <new file>
+ *        scars-profile --completions bash|zsh|fish
+ */
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    match args.as_slice() {
+        [command, file] if command == "lint" => {
+            //the references of the profile may climb up to the root
+            let path = std::fs::canonicalize(file)?;
+            let file_system = FileSystem::new(Path::new("/"));
+            let issues = lint::lint(&file_system, &path.to_string_lossy())?;
+            match format {
+                OutputFormat::Text => issues.iter().for_each(|issue| println!("{issue}")),
+                OutputFormat::Json => cli::print_json(&issues)?,
+            }
+            if !issues.is_empty() {
+                return Err(format!("{} issue(s) found", issues.len()).into());
+            }
+        }
+        [command, file, dcd_files @ ..] if command == "plan" && !dcd_files.is_empty() => {
+            let path = std::fs::canonicalize(file)?;
+            let dcd_paths = dcd_files
+                .iter()
+                .map(|f| Ok(std::fs::canonicalize(f)?.to_string_lossy().into_owned()))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            let dcd_paths: Vec<&str> = dcd_paths.iter().map(String::as_str).collect();
+            let file_system = FileSystem::new(Path::new("/"));
+            let plan = plan::plan(&file_system, &path.to_string_lossy(), &dcd_paths)?;
+            if format == OutputFormat::Json {
+                cli::print_json(&plan)?;
+            } else {
+                print_plan(&plan);
+            }
+            if !plan.is_feasible() {
+                return Err(format!("{} component(s) rejected", plan.rejections.len()).into());
+            }
+        }
+        [command, old, new] if command == "diff" => {
+            let old = std::fs::canonicalize(old)?;
+            let new = std::fs::canonicalize(new)?;
+            let file_system = FileSystem::new(Path::new("/"));
+            let changes = diff::diff(&file_system, &old.to_string_lossy(), &new.to_string_lossy())?;
+            match format {
+                OutputFormat::Text => changes.iter().for_each(|change| println!("{change}")),
+                OutputFormat::Json => cli::print_json(&changes)?,
+            }
+        }
+        _ => return Err(usage()),
+    }
+    Ok(())
+}
+
+/// Prints the assignments of a plan as a table, then its rejections and shortfalls.
+fn print_plan(plan: &plan::DeploymentPlan) {
+    println!("{:<32} {:<32} IMPLEMENTATION", "COMPONENT", "DEVICE");
+    for assignment in &plan.assignments {
+        println!(
+            "{:<32} {:<32} {}",
+            assignment.component_id,
+            assignment.device_id,
+            assignment.implementation_id.as_deref().unwrap_or("")
+        );
+    }
+    for rejection in &plan.rejections {
+        println!("{:<32} rejected", rejection.component_id);
+        for reason in &rejection.reasons {
+            println!("    {reason}");
+        }
+    }
+    for shortfall in &plan.shortfalls {
+        println!(
+            "shortfall: '{}' of '{}' for '{}': {} required, {} available",
+            shortfall.capacity_id,
+            shortfall.device_id,
+            shortfall.component_id,
+            shortfall.required,
+            shortfall.available
+        );
+    }
+}
+
+fn usage() -> Box<dyn std::error::Error> {
+    "usage: scars-profile [--format text|json] lint <sad file> | plan <sad file> <dcd file>... \
+     | diff <old file> <new file>"
+        .into()
+}
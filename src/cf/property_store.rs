@@ -0,0 +1,152 @@
+use std::fmt;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use super::common_types::{AnyValue, Properties};
+use super::log::{logging_properties, Logger};
+use super::resource::{self, ResourceError};
+
+/**
+ * Storage of the properties of a resource or a device, RCU-style: the
+ * properties are published as immutable snapshots, the writer updating
+ * a copy of the current snapshot and swapping it in, and the readers
+ * loading the current one without locking. The frequent queries of the
+ * monitoring tools thus never contend with the processing thread of the
+ * component doing an occasional configure. Cloned stores are
+ * independent, their readers sharing the snapshots of one store.
+ */
+pub struct PropertyStore {
+    current: Arc<ArcSwap<Properties>>,
+}
+
+impl PropertyStore {
+    pub fn new(properties: Properties) -> PropertyStore {
+        PropertyStore {
+            current: Arc::new(ArcSwap::from_pointee(properties)),
+        }
+    }
+
+    /// Returns the current snapshot of the properties.
+    pub fn load(&self) -> Arc<Properties> {
+        self.current.load_full()
+    }
+
+    /**
+     * Updates the properties, the readers seeing the snapshot updated
+     * at once, as a whole, once the update returns.
+     */
+    pub fn update<R>(&mut self, update: impl FnOnce(&mut Properties) -> R) -> R {
+        let mut properties = Properties::clone(&self.current.load());
+        let result = update(&mut properties);
+        self.current.store(Arc::new(properties));
+        result
+    }
+
+    /// Replaces the properties.
+    pub fn store(&mut self, properties: Properties) {
+        self.current.store(Arc::new(properties));
+    }
+
+    /// Returns a reader of the snapshots, e.g. for a monitoring thread.
+    pub fn reader(&self) -> PropertyReader {
+        PropertyReader {
+            current: self.current.clone(),
+            logger: None,
+        }
+    }
+}
+
+impl Default for PropertyStore {
+    fn default() -> Self {
+        PropertyStore::new(Properties::new())
+    }
+}
+
+impl Clone for PropertyStore {
+    fn clone(&self) -> Self {
+        PropertyStore {
+            current: Arc::new(ArcSwap::new(self.load())),
+        }
+    }
+}
+
+impl fmt::Debug for PropertyStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.load().iter()).finish()
+    }
+}
+
+/**
+ * Reader of the snapshots of the properties of a PropertyStore, loading
+ * them without locking. Cloned readers share the same store.
+ */
+#[derive(Clone)]
+pub struct PropertyReader {
+    current: Arc<ArcSwap<Properties>>,
+    /// The logger whose logging properties are queried along with the snapshots.
+    logger: Option<Logger>,
+}
+
+impl PropertyReader {
+    /**
+     * Queries the logging properties of a logger along with the
+     * snapshots, the logger sharing its configuration with the one of
+     * the component.
+     */
+    pub fn with_logger(mut self, logger: Logger) -> PropertyReader {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Returns the current snapshot of the properties.
+    pub fn load(&self) -> Arc<Properties> {
+        self.current.load_full()
+    }
+
+    /**
+     * SCA29
+     * The query operation shall return all component properties when the
+     * inout parameter configProperties is zero size.
+     * SCA30
+     * The query operation shall return only those id/value pairs specified
+     * in the configProperties parameter if the parameter is not zero size.
+     */
+    pub fn query(&self, properties: &Properties) -> resource::Result<Properties> {
+        let mut own = Properties::clone(&self.current.load());
+        if let Some(logger) = &self.logger {
+            own.extend(logging_properties(logger));
+        }
+        if properties.is_empty() {
+            return Ok(own);
+        }
+
+        let invalid_properties: Properties = properties
+            .iter()
+            .filter(|p| !own.iter().any(|o| o.id == p.id))
+            .cloned()
+            .collect();
+        if !invalid_properties.is_empty() {
+            return Err(ResourceError::UnknownProperties { invalid_properties });
+        }
+        Ok(own
+            .into_iter()
+            .filter(|own| properties.iter().any(|p| p.id == own.id))
+            .collect())
+    }
+
+    /// Returns the current value of a property.
+    pub fn get(&self, id: &str) -> Option<AnyValue> {
+        self.current
+            .load()
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.value.clone())
+    }
+}
+
+impl fmt::Debug for PropertyReader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.load().iter()).finish()
+    }
+}
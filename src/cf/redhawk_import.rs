@@ -0,0 +1,360 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use super::profile::prf::PropertiesDescriptor;
+use super::profile::sad::SoftwareAssembly;
+use super::profile::scd::SoftwareComponent;
+use super::profile::spd::{Code, Implementation, SoftPkg};
+use super::profile::{self, resolve_file_name, ProfileError};
+use super::scaffold::{ComponentDescriptors, Scaffold, ScaffoldError, ScaffoldKind};
+
+/**
+ * Convienence enum definition that includes all import errors.
+ */
+#[derive(Error, Debug)]
+pub enum ImportError {
+    /**
+     * This exception indicates that the directory holds neither a SPD
+     * nor a SAD at its root, or several of them.
+     */
+    #[error("NotAProject: directory: '{directory}', msg: '{message}'.")]
+    NotAProject { directory: String, message: String },
+    /**
+     * This exception indicates that the component is of a type not
+     * imported, e.g. a device or a service.
+     */
+    #[error("UnsupportedComponent: file: '{file_name}', type: '{component_type}'.")]
+    UnsupportedComponent {
+        file_name: String,
+        component_type: String,
+    },
+    /**
+     * This exception indicates that a descriptor of the project is
+     * missing or invalid.
+     */
+    #[error("ProfileError: {source}")]
+    ProfileError { source: ProfileError },
+    /**
+     * This exception indicates that a crate cannot be generated.
+     */
+    #[error("ScaffoldError: {source}")]
+    ScaffoldError { source: ScaffoldError },
+    /**
+     * This exception indicates that a descriptor cannot be written.
+     */
+    #[error("FileError: file: '{file_name}', msg: '{message}'.")]
+    FileError { file_name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all import returned errors.
+ */
+pub type Result<T, E = ImportError> = anyhow::Result<T, E>;
+
+impl From<ProfileError> for ImportError {
+    fn from(source: ProfileError) -> Self {
+        ImportError::ProfileError { source }
+    }
+}
+
+impl From<ScaffoldError> for ImportError {
+    fn from(source: ScaffoldError) -> Self {
+        ImportError::ScaffoldError { source }
+    }
+}
+
+/**
+ * This type reports a component imported: the crate generated from the
+ * SPD of the REDHAWK project.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedComponent {
+    pub softpkg_id: String,
+    pub spd_file_name: PathBuf,
+    pub crate_name: String,
+    pub directory: PathBuf,
+}
+
+/**
+ * The report of an import: the components imported, the SAD written
+ * for a waveform, and the parts of the projects left behind.
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportReport {
+    pub components: Vec<ImportedComponent>,
+    pub waveform: Option<PathBuf>,
+    pub warnings: Vec<String>,
+}
+
+/**
+ * Importer of REDHAWK projects: a component project, its SPD at the
+ * root of the directory, becomes a crate with the SPD, SCD and PRF and
+ * a Rust skeleton generated over them, its C++, Python and Java
+ * implementations replaced by the Rust one. A waveform project, its SAD
+ * at the root, gets the components it refers to imported under
+ * components/, the absolute references being to the dom directory of
+ * the SDR root, and its SAD written under waveforms/ referring to them.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Importer {
+    sdrroot: Option<PathBuf>,
+    scars_path: Option<PathBuf>,
+}
+
+impl Importer {
+    pub fn new() -> Importer {
+        Importer::default()
+    }
+
+    /// Sets the SDR root the absolute references of the SADs are resolved in.
+    pub fn with_sdrroot(mut self, sdrroot: &Path) -> Importer {
+        self.sdrroot = Some(sdrroot.to_path_buf());
+        self
+    }
+
+    /// Makes the crates depend on the scars crate at a path.
+    pub fn with_scars_path(mut self, scars_path: &Path) -> Importer {
+        self.scars_path = Some(scars_path.to_path_buf());
+        self
+    }
+
+    /**
+     * Imports the project of a directory into a destination directory:
+     * a component as a crate directory of its own, a waveform as its
+     * components/ and waveforms/ directories.
+     */
+    pub fn import(&self, project: &Path, destination: &Path) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        match project_descriptor(project)? {
+            ProjectDescriptor::Component(spd) => {
+                self.import_component(&spd, destination, &mut report)?;
+            }
+            ProjectDescriptor::Waveform(sad) => {
+                self.import_waveform(&sad, destination, &mut report)?;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Imports a component, unless already imported, returning its SPD in the crate.
+    fn import_component(
+        &self,
+        spd_file_name: &Path,
+        destination: &Path,
+        report: &mut ImportReport,
+    ) -> Result<PathBuf> {
+        if let Some(imported) = report
+            .components
+            .iter()
+            .find(|c| c.spd_file_name == spd_file_name)
+        {
+            return Ok(imported
+                .directory
+                .join(format!("{}.spd.xml", imported.crate_name)));
+        }
+
+        let file_name = spd_file_name.display().to_string();
+        let softpkg = SoftPkg::parse(&profile::read_profile(spd_file_name)?, &file_name)?;
+        let referenced =
+            |local_file: &str| PathBuf::from(resolve_file_name(&file_name, local_file));
+        let scd = match &softpkg.descriptor {
+            Some(scd_file) => {
+                let path = referenced(scd_file);
+                SoftwareComponent::parse(
+                    &profile::read_profile(&path)?,
+                    &path.display().to_string(),
+                )?
+            }
+            None => {
+                report.warnings.push(format!(
+                    "'{file_name}' has no SCD, a resource without ports is assumed"
+                ));
+                resource_scd()
+            }
+        };
+        let component_type = scd.component_type.clone().unwrap_or_default();
+        if !component_type.is_empty() && component_type != "resource" {
+            return Err(ImportError::UnsupportedComponent {
+                file_name,
+                component_type,
+            });
+        }
+        let prf = match &softpkg.property_file {
+            Some(prf_file) => PropertiesDescriptor::from_file(&referenced(prf_file))?,
+            None => PropertiesDescriptor {
+                description: None,
+                properties: Vec::new(),
+            },
+        };
+
+        //the implementations give way to the Rust one, keeping the dependencies of the first
+        let crate_name = crate_name(&softpkg.name);
+        for implementation in &softpkg.implementations {
+            report.warnings.push(format!(
+                "'{file_name}': implementation '{}' replaced by the Rust implementation",
+                implementation.id
+            ));
+            if implementation.property_file.is_some() {
+                report.warnings.push(format!(
+                    "'{file_name}': the PRF of implementation '{}' is not imported",
+                    implementation.id
+                ));
+            }
+        }
+        let dependencies = softpkg
+            .implementations
+            .first()
+            .map(|i| i.dependencies.clone())
+            .unwrap_or_default();
+        let softpkg = SoftPkg {
+            implementations: vec![Implementation {
+                id: "rust".to_string(),
+                code: Some(Code {
+                    code_type: "SharedLibrary".to_string(),
+                    local_file: format!("lib{}.rlib", crate_name.replace('-', "_")),
+                    entry_point: None,
+                }),
+                property_file: None,
+                processors: Vec::new(),
+                os: Vec::new(),
+                dependencies,
+            }],
+            ..softpkg
+        };
+
+        let mut scaffold = Scaffold::new(ScaffoldKind::COMPONENT, &crate_name)?.with_descriptors(
+            ComponentDescriptors {
+                softpkg: softpkg.clone(),
+                scd,
+                prf,
+            },
+        );
+        if let Some(scars_path) = &self.scars_path {
+            scaffold = scaffold.with_scars_path(scars_path);
+        }
+        let directory = scaffold.write(destination)?;
+        report.components.push(ImportedComponent {
+            softpkg_id: softpkg.id,
+            spd_file_name: spd_file_name.to_path_buf(),
+            crate_name: crate_name.clone(),
+            directory: directory.clone(),
+        });
+        Ok(directory.join(format!("{crate_name}.spd.xml")))
+    }
+
+    fn import_waveform(
+        &self,
+        sad_file_name: &Path,
+        destination: &Path,
+        report: &mut ImportReport,
+    ) -> Result<()> {
+        let file_name = sad_file_name.display().to_string();
+        let mut sad = SoftwareAssembly::parse(&profile::read_profile(sad_file_name)?, &file_name)?;
+        let waveform_directory = destination.join("waveforms").join(crate_name(&sad.name));
+        let components_directory = destination.join("components");
+
+        for component_file in &mut sad.component_files {
+            let spd_file_name = match component_file.local_file.strip_prefix('/') {
+                Some(dom_file) => self.dom().join(dom_file),
+                None => PathBuf::from(resolve_file_name(&file_name, &component_file.local_file)),
+            };
+            let imported = self.import_component(&spd_file_name, &components_directory, report)?;
+            let crate_directory = imported.parent().unwrap().file_name().unwrap();
+            component_file.local_file = format!(
+                "../../components/{}/{}",
+                crate_directory.to_string_lossy(),
+                imported.file_name().unwrap().to_string_lossy()
+            );
+        }
+
+        let waveform_file = waveform_directory.join(format!("{}.sad.xml", crate_name(&sad.name)));
+        let file_error = |e: std::io::Error| ImportError::FileError {
+            file_name: waveform_file.display().to_string(),
+            message: e.to_string(),
+        };
+        std::fs::create_dir_all(&waveform_directory).map_err(file_error)?;
+        std::fs::write(&waveform_file, sad.to_xml()).map_err(file_error)?;
+        report.waveform = Some(waveform_file);
+        Ok(())
+    }
+
+    /// The dom directory of the SDR root, from the importer or $SDRROOT.
+    fn dom(&self) -> PathBuf {
+        self.sdrroot
+            .clone()
+            .or_else(|| std::env::var_os("SDRROOT").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("dom")
+    }
+}
+
+enum ProjectDescriptor {
+    Component(PathBuf),
+    Waveform(PathBuf),
+}
+
+/// Returns the SPD or SAD at the root of a project directory.
+fn project_descriptor(project: &Path) -> Result<ProjectDescriptor> {
+    let not_a_project = |message: &str| ImportError::NotAProject {
+        directory: project.display().to_string(),
+        message: message.to_string(),
+    };
+    let entries = std::fs::read_dir(project).map_err(|e| not_a_project(&e.to_string()))?;
+    let mut descriptors: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(".spd.xml") || n.ends_with(".sad.xml"))
+        })
+        .collect();
+    descriptors.sort();
+    match descriptors.as_slice() {
+        [] => Err(not_a_project("no SPD nor SAD")),
+        [descriptor] if descriptor.to_string_lossy().ends_with(".sad.xml") => {
+            Ok(ProjectDescriptor::Waveform(descriptor.clone()))
+        }
+        [descriptor] => Ok(ProjectDescriptor::Component(descriptor.clone())),
+        _ => Err(not_a_project("several SPDs or SADs")),
+    }
+}
+
+fn resource_scd() -> SoftwareComponent {
+    SoftwareComponent {
+        corba_version: None,
+        repository_id: Some("IDL:CF/Resource:1.0".to_string()),
+        component_type: Some("resource".to_string()),
+        supported_interfaces: Vec::new(),
+        ports: Vec::new(),
+        interfaces: Vec::new(),
+        property_file: None,
+    }
+}
+
+/**
+ * Returns the crate name of a REDHAWK name, its namespaces and words
+ * in snake case, e.g. rh_sig_gen for rh.SigGen.
+ */
+pub fn crate_name(name: &str) -> String {
+    let mut crate_name = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c.is_ascii_uppercase()
+            && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            crate_name.push('_');
+        }
+        match c.is_ascii_alphanumeric() {
+            true => crate_name.push(c.to_ascii_lowercase()),
+            false if !crate_name.ends_with('_') => crate_name.push('_'),
+            false => {}
+        }
+        previous = Some(c);
+    }
+    let mut crate_name = crate_name.trim_matches('_').to_string();
+    if !crate_name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        crate_name.insert_str(0, "c_");
+    }
+    crate_name
+}
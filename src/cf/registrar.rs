@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use tonic::{Request, Response, Status};
+
+use super::blocking_pool::BlockingPool;
+use super::component_registry::ComponentRegistry;
+use super::retry::RetryPolicy;
+use super::rpc::registrar::registrar_client::RegistrarClient;
+use super::rpc::registrar::registrar_server::Registrar;
+use super::rpc::registrar::{
+    Binding, ListReply, ListRequest, RegisterReply, RegisterRequest, ResolveReply, ResolveRequest,
+    UnregisterReply, UnregisterRequest,
+};
+
+/// The longest time a client may wait for a registration by default.
+pub const DEFAULT_MAX_RESOLVE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/**
+ * gRPC Registrar service where the launched components register the
+ * endpoint they are served at under their naming context string, i.e.
+ * the NAME_BINDING execparam, with the endpoint handed to them through
+ * the NAMING_CONTEXT_IOR execparam. It replaces the CORBA NamingService,
+ * the bindings being kept in a ComponentRegistry.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct RegistrarService {
+    registry: ComponentRegistry,
+    blocking_pool: BlockingPool,
+    max_resolve_timeout: Duration,
+}
+
+impl RegistrarService {
+    pub fn new(registry: ComponentRegistry) -> RegistrarService {
+        RegistrarService {
+            registry,
+            blocking_pool: BlockingPool::shared(),
+            max_resolve_timeout: DEFAULT_MAX_RESOLVE_TIMEOUT,
+        }
+    }
+
+    /**
+     * Sets the longest time a client may wait for a registration, the
+     * resolve requests asking for more being rejected, so that the waits
+     * cannot hold the threads of the pool indefinitely.
+     */
+    pub fn with_max_resolve_timeout(mut self, max_resolve_timeout: Duration) -> RegistrarService {
+        self.max_resolve_timeout = max_resolve_timeout;
+        self
+    }
+
+    /// Sets the pool the waits for the registrations are run on.
+    pub fn with_blocking_pool(mut self, blocking_pool: BlockingPool) -> RegistrarService {
+        self.blocking_pool = blocking_pool;
+        self
+    }
+
+    /// Returns the registry holding the bindings.
+    pub fn registry(&self) -> &ComponentRegistry {
+        &self.registry
+    }
+}
+
+#[tonic::async_trait]
+impl Registrar for RegistrarService {
+    async fn register(
+        &self,
+        request: Request<RegisterRequest>,
+    ) -> Result<Response<RegisterReply>, Status> {
+        let r = request.into_inner();
+        //verify the binding
+        if r.name.is_empty() {
+            return Err(Status::invalid_argument("empty name"));
+        }
+        if r.endpoint.is_empty() {
+            return Err(Status::invalid_argument(format!(
+                "empty endpoint for '{}'",
+                r.name
+            )));
+        }
+        self.registry.register_endpoint(&r.name, &r.endpoint);
+        Ok(Response::new(RegisterReply {}))
+    }
+
+    async fn unregister(
+        &self,
+        request: Request<UnregisterRequest>,
+    ) -> Result<Response<UnregisterReply>, Status> {
+        let name = request.into_inner().name;
+        self.registry
+            .unregister_endpoint(&name)
+            .ok_or_else(|| Status::not_found(format!("'{name}' not registered")))?;
+        Ok(Response::new(UnregisterReply {}))
+    }
+
+    /// The wait for the registration is run on a thread of the blocking pool.
+    async fn resolve(
+        &self,
+        request: Request<ResolveRequest>,
+    ) -> Result<Response<ResolveReply>, Status> {
+        let r = request.into_inner();
+        let timeout = Duration::from_millis(r.timeout_ms);
+        if timeout > self.max_resolve_timeout {
+            return Err(Status::invalid_argument(format!(
+                "timeout of {}ms beyond the maximum of {:?}",
+                r.timeout_ms, self.max_resolve_timeout
+            )));
+        }
+        let registry = self.registry.clone();
+        let name = r.name.clone();
+        let endpoint = self
+            .blocking_pool
+            .run(move || registry.resolve_endpoint(&name, timeout))
+            .await?
+            .ok_or_else(|| Status::not_found(format!("'{}' not registered", r.name)))?;
+        Ok(Response::new(ResolveReply { endpoint }))
+    }
+
+    async fn list(&self, _request: Request<ListRequest>) -> Result<Response<ListReply>, Status> {
+        let bindings = self
+            .registry
+            .endpoints()
+            .into_iter()
+            .map(|(name, endpoint)| Binding { name, endpoint })
+            .collect();
+        Ok(Response::new(ListReply { bindings }))
+    }
+}
+
+/**
+ * Registers the endpoint of a launched component under its name with the
+ * registrar, retrying while the registrar is unreachable.
+ */
+pub async fn register_endpoint(
+    registrar: &str,
+    name: &str,
+    endpoint: &str,
+    retry_policy: RetryPolicy,
+) -> Result<(), Status> {
+    let request = &RegisterRequest {
+        name: name.to_string(),
+        endpoint: endpoint.to_string(),
+    };
+    retry_policy
+        .retry(|| async move {
+            let mut client = RegistrarClient::connect(registrar.to_string())
+                .await
+                .map_err(|e| Status::unavailable(e.to_string()))?;
+            client.register(request.clone()).await?;
+            Ok(())
+        })
+        .await
+}
+
+/**
+ * Resolves the endpoint registered under the name with the registrar,
+ * waiting up to the timeout for the component to register.
+ */
+pub async fn resolve_endpoint(
+    registrar: &str,
+    name: &str,
+    timeout: Duration,
+) -> Result<String, Status> {
+    let mut client = RegistrarClient::connect(registrar.to_string())
+        .await
+        .map_err(|e| Status::unavailable(e.to_string()))?;
+    let reply = client
+        .resolve(ResolveRequest {
+            name: name.to_string(),
+            timeout_ms: timeout.as_millis() as u64,
+        })
+        .await?;
+    Ok(reply.into_inner().endpoint)
+}
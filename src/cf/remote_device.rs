@@ -0,0 +1,289 @@
+use std::future::Future;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use tonic::transport::Channel;
+use tonic::Status;
+
+use super::checksum;
+use super::common_types::Properties;
+use super::device::{self, AdminType, DeviceTrait, OperationalType, UsageType};
+use super::executable_device::{self, ExecutableDeviceTrait, ProcessId, ProcessMetrics};
+use super::file_system_service::CHUNK_SIZE;
+use super::loadable_device::{self, LoadType, LoadableDeviceTrait};
+use super::rpc::device::device_client::DeviceClient;
+use super::rpc::device::{
+    AllocationPropertiesRequest, CapacityRequest, ExecuteRequest, LoadRequest,
+    ProcessMetricsRequest, SetAdminStateRequest, StatusReply, StatusRequest, TerminateRequest,
+    UnloadRequest,
+};
+use super::rpc::{
+    self, device_error_from_status, execute_error_from_status, loadable_device_error_from_status,
+    process_error_from_status, properties_from_wire, properties_to_wire,
+};
+
+/// The time given by default to a device to answer a call.
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**
+ * Device of a node served by its Device gRPC service, e.g. registered
+ * with the domain by its DeviceManager. Its composite device is read
+ * on first use, its states and capacities on each call. A device
+ * failing to answer is reported DISABLED, LOCKED and BUSY, without
+ * allocation properties, so that it is never allocated. The files it
+ * loads are read from the local directory given to the load and
+ * streamed to the device.
+ */
+#[derive(Debug)]
+pub struct RemoteDevice {
+    client: DeviceClient<Channel>,
+    identifier: String,
+    label: String,
+    endpoint: String,
+    composite_device: OnceLock<Option<String>>,
+    timeout: Duration,
+}
+
+impl RemoteDevice {
+    /// Returns the device served at the endpoint, connected on its first call.
+    pub fn new(
+        identifier: &str,
+        label: &str,
+        endpoint: &str,
+    ) -> Result<RemoteDevice, tonic::transport::Error> {
+        Ok(RemoteDevice {
+            client: DeviceClient::new(rpc::client_channel(endpoint)?),
+            identifier: identifier.to_string(),
+            label: label.to_string(),
+            endpoint: endpoint.to_string(),
+            composite_device: OnceLock::new(),
+            timeout: DEFAULT_CALL_TIMEOUT,
+        })
+    }
+
+    /// Sets the time given to the device to answer a call, a load streaming its file included.
+    pub fn with_timeout(mut self, timeout: Duration) -> RemoteDevice {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The endpoint serving the device.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /**
+     * Makes a call of the service, its failure decoded from its status,
+     * DEADLINE_EXCEEDED once the timeout elapsed.
+     */
+    fn call<R, E, F, C>(&self, call: C, error: impl FnOnce(&Status) -> E) -> Result<R, E>
+    where
+        R: Send + 'static,
+        F: Future<Output = Result<R, Status>> + Send + 'static,
+        C: FnOnce(DeviceClient<Channel>) -> F,
+    {
+        let timeout = self.timeout;
+        let call = call(self.client.clone());
+        match rpc::block_on(async move { tokio::time::timeout(timeout, call).await }) {
+            Ok(outcome) => outcome.map_err(|status| error(&status)),
+            Err(_) => Err(error(&Status::deadline_exceeded(format!(
+                "'{}' did not answer within {timeout:?}",
+                self.endpoint
+            )))),
+        }
+    }
+
+    fn status(&self) -> Option<StatusReply> {
+        self.call(
+            |mut client| async move { Ok(client.status(StatusRequest {}).await?.into_inner()) },
+            |_| (),
+        )
+        .ok()
+    }
+}
+
+impl DeviceTrait for RemoteDevice {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// A device failing to answer is taken as a device of no composite device.
+    fn composite_device(&self) -> Option<&str> {
+        self.composite_device
+            .get_or_init(|| self.status().and_then(|s| s.composite_device))
+            .as_deref()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.status()
+            .map_or(UsageType::BUSY, |s| s.usage_state().into())
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.status()
+            .map_or(AdminType::LOCKED, |s| s.admin_state().into())
+    }
+
+    /// A device failing to be commanded keeps its state.
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        let request = SetAdminStateRequest {
+            admin_state: rpc::device::AdminType::from(admin_state).into(),
+        };
+        let _ = self.call(
+            |mut client| async move {
+                client.set_admin_state(request).await?;
+                Ok(())
+            },
+            |_| (),
+        );
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.status()
+            .map_or(OperationalType::DISABLED, |s| s.operational_state().into())
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.call(
+            |mut client| async move {
+                let reply = client
+                    .allocation_properties(AllocationPropertiesRequest {})
+                    .await?;
+                Ok(reply.into_inner().properties)
+            },
+            |_| (),
+        )
+        .ok()
+        .and_then(|properties| properties_from_wire(&properties).ok())
+        .unwrap_or_default()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        let request = CapacityRequest {
+            capacities: properties_to_wire(capacities),
+        };
+        self.call(
+            |mut client| async move {
+                Ok(client
+                    .allocate_capacity(request)
+                    .await?
+                    .into_inner()
+                    .allocated)
+            },
+            |status| device_error_from_status(status, capacities),
+        )
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        let request = CapacityRequest {
+            capacities: properties_to_wire(capacities),
+        };
+        self.call(
+            |mut client| async move {
+                client.deallocate_capacity(request).await?;
+                Ok(())
+            },
+            |status| device_error_from_status(status, capacities),
+        )
+    }
+}
+
+impl LoadableDeviceTrait for RemoteDevice {
+    /// The file is streamed in chunks, staged on the node of the device before it loads it.
+    fn load(
+        &mut self,
+        fs: &Path,
+        file_name: &str,
+        load_kind: LoadType,
+    ) -> loadable_device::Result<()> {
+        let data = std::fs::read(fs.join(file_name))?;
+        let load_kind = rpc::device::LoadType::from(load_kind).into();
+        let mut requests: Vec<LoadRequest> = data
+            .chunks(CHUNK_SIZE)
+            .map(|chunk| LoadRequest {
+                data: chunk.to_vec(),
+                crc32c: Some(checksum::crc32c(chunk)),
+                ..Default::default()
+            })
+            .collect();
+        if requests.is_empty() {
+            requests.push(LoadRequest::default());
+        }
+        requests[0].file_name = file_name.to_string();
+        requests[0].load_kind = load_kind;
+
+        self.call(
+            |mut client| async move {
+                client.load(tokio_stream::iter(requests)).await?;
+                Ok(())
+            },
+            loadable_device_error_from_status,
+        )
+    }
+
+    fn unload(&mut self, file_name: &str) -> loadable_device::Result<()> {
+        let file_name = file_name.to_string();
+        self.call(
+            |mut client| async move {
+                client.unload(UnloadRequest { file_name }).await?;
+                Ok(())
+            },
+            loadable_device_error_from_status,
+        )
+    }
+}
+
+impl ExecutableDeviceTrait for RemoteDevice {
+    fn execute(
+        &mut self,
+        name: &str,
+        options: &Properties,
+        parameters: &Properties,
+    ) -> executable_device::Result<ProcessId> {
+        let request = ExecuteRequest {
+            name: name.to_string(),
+            options: properties_to_wire(options),
+            parameters: properties_to_wire(parameters),
+        };
+        self.call(
+            |mut client| async move { Ok(client.execute(request).await?.into_inner().process_id) },
+            execute_error_from_status,
+        )
+    }
+
+    fn terminate(&mut self, process_id: ProcessId) -> executable_device::Result<()> {
+        self.call(
+            |mut client| async move {
+                client.terminate(TerminateRequest { process_id }).await?;
+                Ok(())
+            },
+            process_error_from_status,
+        )
+    }
+
+    fn process_metrics(
+        &mut self,
+        process_id: ProcessId,
+    ) -> executable_device::Result<ProcessMetrics> {
+        let reply = self.call(
+            |mut client| async move {
+                let reply = client
+                    .process_metrics(ProcessMetricsRequest { process_id })
+                    .await?;
+                Ok(reply.into_inner())
+            },
+            process_error_from_status,
+        )?;
+        Ok(ProcessMetrics {
+            process_id,
+            cpu_usage: reply.cpu_usage,
+            memory: reply.memory,
+            status: reply.status().into(),
+        })
+    }
+}
@@ -0,0 +1,278 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+use tonic::Status;
+
+use super::checksum;
+use super::common_types::ErrorNumberType;
+use super::file_system::{
+    self, FileInformationType, FileSystemError, FileSystemSpace, FileSystemTrait, FileType,
+    DEFAULT_OPEN_TIMEOUT,
+};
+use super::file_system_service::CHUNK_SIZE;
+use super::rpc::file_system::file_system_client::FileSystemClient;
+use super::rpc::file_system::{
+    CopyRequest, ListRequest, MkdirRequest, MoveRequest, QueryRequest, ReadRangeRequest,
+    ReadRequest, RemoveRequest, RmdirRequest, WriteRangeRequest, WriteRequest,
+};
+use super::rpc::{self, file_system_error_from_status};
+
+/**
+ * File system of a node served by its FileSystem gRPC service, e.g. the
+ * one of a DeviceManager, mounted in the domain FileManager. The files
+ * are streamed in chunks, the ones of a write whose CRC32C differs being
+ * written again by range. A node not answering a call, or a chunk of a
+ * file, within the timeout fails it with CF_ETIMEDOUT.
+ */
+#[derive(Debug, Clone)]
+pub struct RemoteFileSystem {
+    client: FileSystemClient<Channel>,
+    endpoint: String,
+    timeout: Duration,
+}
+
+impl RemoteFileSystem {
+    /// Returns the file system served at the endpoint, connected on its first call.
+    pub fn new(endpoint: &str) -> file_system::Result<RemoteFileSystem> {
+        let channel =
+            rpc::client_channel(endpoint).map_err(|e| FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EINVAL,
+                message: format!("'{endpoint}': {e}"),
+            })?;
+        Ok(RemoteFileSystem {
+            client: FileSystemClient::new(channel),
+            endpoint: endpoint.to_string(),
+            timeout: DEFAULT_OPEN_TIMEOUT,
+        })
+    }
+
+    /// Sets the time given to the node to answer a call, or to stream a chunk of a file.
+    pub fn with_timeout(mut self, timeout: Duration) -> RemoteFileSystem {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The endpoint serving the file system.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Makes a unary call of the service, within the timeout.
+    fn call<R, F, C>(&self, call: C) -> file_system::Result<R>
+    where
+        R: Send + 'static,
+        F: Future<Output = Result<R, Status>> + Send + 'static,
+        C: FnOnce(FileSystemClient<Channel>) -> F,
+    {
+        let (timeout, endpoint) = (self.timeout, self.endpoint.clone());
+        let call = call(self.client.clone());
+        rpc::block_on(async move { within(timeout, &endpoint, call).await })
+    }
+}
+
+/// Awaits the outcome of a call, failing with CF_ETIMEDOUT once the timeout elapsed.
+async fn within<R>(
+    timeout: Duration,
+    endpoint: &str,
+    call: impl Future<Output = Result<R, Status>>,
+) -> file_system::Result<R> {
+    match tokio::time::timeout(timeout, call).await {
+        Ok(outcome) => outcome.map_err(|status| file_system_error_from_status(&status)),
+        Err(_) => Err(FileSystemError::FileException {
+            error_number: ErrorNumberType::CF_ETIMEDOUT,
+            message: format!("'{endpoint}' did not answer within {timeout:?}"),
+        }),
+    }
+}
+
+impl FileSystemTrait for RemoteFileSystem {
+    /// The file is looked up by listing its name in its directory.
+    fn exists(&self, file_name: &str) -> file_system::Result<bool> {
+        let file_name = file_name.trim_end_matches('/');
+        let Some((_, name)) = file_name.rsplit_once('/') else {
+            return Ok(file_name.is_empty());
+        };
+        let name = name.to_string();
+        Ok(self.list(file_name)?.iter().any(|f| f.name == name))
+    }
+
+    fn list(&self, pattern: &str) -> file_system::Result<Vec<FileInformationType>> {
+        let pattern = pattern.to_string();
+        let files = self.call(|mut client| async move {
+            Ok(client.list(ListRequest { pattern }).await?.into_inner().files)
+        })?;
+        Ok(files
+            .into_iter()
+            .map(|f| FileInformationType {
+                kind: match f.kind() {
+                    rpc::file_system::FileType::Plain => FileType::PLAIN,
+                    rpc::file_system::FileType::Directory => FileType::DIRECTORY,
+                    rpc::file_system::FileType::FileSystem => FileType::FILE_SYSTEM,
+                },
+                name: f.name,
+                size: f.size,
+            })
+            .collect())
+    }
+
+    fn remove(&self, file_name: &str) -> file_system::Result<()> {
+        let file_name = file_name.to_string();
+        self.call(|mut client| async move {
+            client.remove(RemoveRequest { file_name }).await?;
+            Ok(())
+        })
+    }
+
+    fn copy(&self, source_file_name: &str, destination_file_name: &str) -> file_system::Result<()> {
+        let request = CopyRequest {
+            source_file_name: source_file_name.to_string(),
+            destination_file_name: destination_file_name.to_string(),
+        };
+        self.call(|mut client| async move {
+            client.copy(request).await?;
+            Ok(())
+        })
+    }
+
+    /// The file is renamed by the service, through its move operation.
+    fn rename(
+        &self,
+        source_file_name: &str,
+        destination_file_name: &str,
+    ) -> file_system::Result<()> {
+        let request = MoveRequest {
+            source_file_name: source_file_name.to_string(),
+            destination_file_name: destination_file_name.to_string(),
+        };
+        self.call(|mut client| async move {
+            client.r#move(request).await?;
+            Ok(())
+        })
+    }
+
+    fn mkdir(&self, directory_name: &str) -> file_system::Result<()> {
+        let directory_name = directory_name.to_string();
+        self.call(|mut client| async move {
+            client.mkdir(MkdirRequest { directory_name }).await?;
+            Ok(())
+        })
+    }
+
+    fn rmdir(&self, directory_name: &str) -> file_system::Result<()> {
+        let directory_name = directory_name.to_string();
+        self.call(|mut client| async move {
+            client.rmdir(RmdirRequest { directory_name }).await?;
+            Ok(())
+        })
+    }
+
+    /// The chunks are each given the timeout, a chunk whose CRC32C differs failing the read.
+    fn read(&self, file_name: &str) -> file_system::Result<Vec<u8>> {
+        let request = ReadRequest {
+            file_name: file_name.to_string(),
+            chunk_size: CHUNK_SIZE as u64,
+        };
+        let (mut client, timeout) = (self.client.clone(), self.timeout);
+        let endpoint = self.endpoint.clone();
+        rpc::block_on(async move {
+            let mut chunks = within(timeout, &endpoint, async {
+                Ok(client.read(request).await?.into_inner())
+            })
+            .await?;
+            let mut data = Vec::new();
+            while let Some(chunk) = within(timeout, &endpoint, async {
+                chunks.next().await.transpose()
+            })
+            .await?
+            {
+                if chunk.crc32c.is_some_and(|crc| crc != checksum::crc32c(&chunk.data)) {
+                    return Err(FileSystemError::FileException {
+                        error_number: ErrorNumberType::CF_EIO,
+                        message: format!("the chunk at offset {} is corrupted", chunk.offset),
+                    });
+                }
+                data.extend_from_slice(&chunk.data);
+            }
+            Ok(data)
+        })
+    }
+
+    /// The range is read in chunks, the service replying at most a chunk of its pool.
+    fn read_at(&self, file_name: &str, offset: u64, length: usize) -> file_system::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        while data.len() < length {
+            let request = ReadRangeRequest {
+                file_name: file_name.to_string(),
+                offset: offset + data.len() as u64,
+                size: (length - data.len()) as u64,
+            };
+            let chunk = self.call(|mut client| async move {
+                Ok(client.read_range(request).await?.into_inner().data)
+            })?;
+            if chunk.is_empty() {
+                break;
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
+    /// The chunks replied corrupted are written again by range.
+    fn write(&self, file_name: &str, data: &[u8]) -> file_system::Result<()> {
+        let requests: Vec<WriteRequest> = match data.is_empty() {
+            true => vec![WriteRequest {
+                file_name: file_name.to_string(),
+                data: Default::default(),
+                crc32c: Some(checksum::crc32c(&[])),
+            }],
+            false => data
+                .chunks(CHUNK_SIZE)
+                .map(|chunk| WriteRequest {
+                    file_name: file_name.to_string(),
+                    data: chunk.to_vec(),
+                    crc32c: Some(checksum::crc32c(chunk)),
+                })
+                .collect(),
+        };
+        let corrupted = self.call(|mut client| async move {
+            let reply = client.write(tokio_stream::iter(requests)).await?;
+            Ok(reply.into_inner().corrupted)
+        })?;
+        for range in corrupted {
+            let start = usize::try_from(range.offset).unwrap_or(usize::MAX).min(data.len());
+            let end = start
+                .saturating_add(usize::try_from(range.size).unwrap_or(usize::MAX))
+                .min(data.len());
+            self.write_at(file_name, range.offset, &data[start..end])?;
+        }
+        Ok(())
+    }
+
+    fn write_at(&self, file_name: &str, offset: u64, data: &[u8]) -> file_system::Result<u64> {
+        let request = WriteRangeRequest {
+            file_name: file_name.to_string(),
+            offset,
+            crc32c: Some(checksum::crc32c(data)),
+            data: data.to_vec(),
+        };
+        self.call(|mut client| async move {
+            Ok(client.write_range(request).await?.into_inner().size)
+        })
+    }
+
+    fn query(&self) -> file_system::Result<Vec<FileSystemSpace>> {
+        let spaces = self.call(|mut client| async move {
+            Ok(client.query(QueryRequest {}).await?.into_inner().spaces)
+        })?;
+        Ok(spaces
+            .into_iter()
+            .map(|s| FileSystemSpace {
+                mount_point: s.mount_point,
+                size: s.size,
+                available_space: s.available_space,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,450 @@
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use super::common_types::{AnyValue, DataType, ErrorNumberType, Properties};
+use super::log::{is_logging_property, set_logging_property, LogLevelType, LogProducer, Logger};
+use super::property_store::{PropertyReader, PropertyStore};
+
+/**
+ * Convienence enum definition that includes all ResourceTrait errors.
+ */
+#[derive(Error, Debug)]
+pub enum ResourceError {
+    /**
+     * This exception indicates that the resource is not capable of the
+     * behavior being attempted due to its current state.
+     */
+    #[error("InvalidState: msg: '{message}'.")]
+    InvalidState { message: String },
+    /**
+     * This exception indicates that an error occurred during the
+     * initialization of the resource.
+     */
+    #[error("InitializeError: {messages:?}.")]
+    InitializeError { messages: Vec<String> },
+    /**
+     * This exception indicates that an error occurred during the release
+     * of the resource.
+     */
+    #[error("ReleaseError: {messages:?}.")]
+    ReleaseError { messages: Vec<String> },
+    /**
+     * This exception indicates that none of the configuration properties
+     * could be set. The list contains the invalid properties.
+     */
+    #[error("InvalidConfiguration: msg: '{message}', properties: {invalid_properties:?}.")]
+    InvalidConfiguration {
+        message: String,
+        invalid_properties: Properties,
+    },
+    /**
+     * This exception indicates that some configuration properties were
+     * set and some were not. The list contains the invalid properties.
+     */
+    #[error("PartialConfiguration: {invalid_properties:?}.")]
+    PartialConfiguration { invalid_properties: Properties },
+    /**
+     * This exception indicates that the queried properties are not known
+     * by the resource. The list contains the unknown properties.
+     */
+    #[error("UnknownProperties: {invalid_properties:?}.")]
+    UnknownProperties { invalid_properties: Properties },
+    /**
+     * This exception indicates that an error occurred while starting the
+     * resource.
+     */
+    #[error("StartError: num: {error_number:?}, msg: '{message}'.")]
+    StartError {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates that an error occurred while stopping the
+     * resource.
+     */
+    #[error("StopError: num: {error_number:?}, msg: '{message}'.")]
+    StopError {
+        error_number: ErrorNumberType,
+        message: String,
+    },
+    /**
+     * This exception indicates that the port name is not known by the
+     * resource.
+     */
+    #[error("UnknownPort: port: '{name}'.")]
+    UnknownPort { name: String },
+    /**
+     * This exception indicates that a connection or disconnection of a
+     * port is not valid.
+     */
+    #[error("InvalidPort: port: '{name}', msg: '{message}'.")]
+    InvalidPort { name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all ResourceTrait returned errors.
+ */
+pub type Result<T, E = ResourceError> = anyhow::Result<T, E>;
+
+/**
+ * This interface provides the common operations of the application
+ * components: life cycle, configuration of the properties, control of
+ * the processing and access to the ports.
+ */
+pub trait ResourceTrait {
+    /// The readonly identifier attribute contains the instance-unique identifier of the resource.
+    fn identifier(&self) -> &str;
+
+    /// The readonly started attribute tells whether the resource is started.
+    fn started(&self) -> bool;
+
+    /// This operation initializes the resource to a known initial state.
+    fn initialize(&mut self) -> Result<()>;
+
+    /// This operation releases the resource from the operating environment.
+    fn release_object(&mut self) -> Result<()>;
+
+    /// This operation sets the properties of the resource.
+    fn configure(&mut self, properties: &Properties) -> Result<()>;
+
+    /// This operation returns the values of the properties, all of them when none is given.
+    fn query(&self, properties: &Properties) -> Result<Properties>;
+
+    /// This operation starts the processing of the resource.
+    fn start(&mut self) -> Result<()>;
+
+    /// This operation stops the processing of the resource.
+    fn stop(&mut self) -> Result<()>;
+
+    /// This operation returns the endpoint of a provides port.
+    fn get_provides_port(&self, name: &str) -> Result<String>;
+
+    /// This operation connects a uses port to the endpoint of a provides port.
+    fn connect_uses_port(&mut self, name: &str, connection_id: &str, endpoint: &str) -> Result<()>;
+
+    /// This operation breaks a connection made by connect_uses_port.
+    fn disconnect_port(&mut self, name: &str, connection_id: &str) -> Result<()>;
+
+    /**
+     * Returns a reader of the snapshots of the properties, to query them
+     * without locking the resource, when the resource publishes them.
+     */
+    fn property_reader(&self) -> Option<PropertyReader> {
+        None
+    }
+}
+
+/**
+ * Shared reference to a resource.
+ */
+pub type ResourceRef = Arc<Mutex<dyn ResourceTrait + Send>>;
+
+/**
+ * This type describes a uses port of a resource with its connections,
+ * as (connection id, endpoint) pairs.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct UsesPort {
+    name: String,
+    connections: Vec<(String, String)>,
+}
+
+/**
+ * Resource running in the process of its creator: the properties are
+ * only stored and the ports only keep track of their connections, so
+ * that components can be hosted in-process or stand in for remote ones.
+ * The LOG_LEVEL and LOGGING_CONFIG_URI properties configure the logger
+ * of the resource. The properties are queried from their snapshots,
+ * without contending with their configuration.
+ */
+#[derive(Debug, Clone)]
+pub struct Resource {
+    identifier: String,
+    started: bool,
+    released: bool,
+    properties: PropertyStore,
+    provides_ports: Vec<(String, String)>,
+    uses_ports: Vec<UsesPort>,
+    logger: Logger,
+}
+
+impl Resource {
+    pub fn new(identifier: &str) -> Resource {
+        Resource {
+            identifier: identifier.to_string(),
+            started: false,
+            released: false,
+            properties: PropertyStore::default(),
+            provides_ports: Vec::new(),
+            uses_ports: Vec::new(),
+            logger: Logger::new(identifier, identifier),
+        }
+    }
+
+    /**
+     * Applies the logging execparams the resource is executed with, the
+     * other parameters being left to the component. The invalid values
+     * are reported by the logger.
+     */
+    pub fn with_exec_params(mut self, exec_params: &Properties) -> Resource {
+        for p in exec_params.iter().filter(|p| is_logging_property(&p.id)) {
+            if !set_logging_property(&mut self.logger, p) {
+                self.logger.log(
+                    LogLevelType::USAGE_ERROR,
+                    &format!("invalid {} '{}'", p.id, p.value),
+                );
+            }
+        }
+        self
+    }
+
+    /// Adds a property with its initial value.
+    pub fn with_property(mut self, id: &str, value: AnyValue) -> Resource {
+        self.properties
+            .update(|properties| properties.push(DataType::new(id, value)));
+        self
+    }
+
+    /// Adds a provides port served at the endpoint.
+    pub fn with_provides_port(mut self, name: &str, endpoint: &str) -> Resource {
+        self.provides_ports
+            .push((name.to_string(), endpoint.to_string()));
+        self
+    }
+
+    /// Adds a uses port.
+    pub fn with_uses_port(mut self, name: &str) -> Resource {
+        self.uses_ports.push(UsesPort {
+            name: name.to_string(),
+            connections: Vec::new(),
+        });
+        self
+    }
+
+    /// Returns the connections of a uses port as (connection id, endpoint) pairs.
+    pub fn connections(&self, name: &str) -> Vec<(String, String)> {
+        self.uses_ports
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.connections.clone())
+            .unwrap_or_default()
+    }
+
+    /// Tells whether the resource has been released.
+    pub fn released(&self) -> bool {
+        self.released
+    }
+
+    /// Returns the logger of the resource, its clones sharing the configuration.
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Verifies the resource has not been released.
+    fn check_state(&self) -> Result<()> {
+        if self.released {
+            return Err(ResourceError::InvalidState {
+                message: format!("'{}' has been released", self.identifier),
+            });
+        }
+        Ok(())
+    }
+
+    fn uses_port(&mut self, name: &str) -> Result<&mut UsesPort> {
+        self.uses_ports
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| ResourceError::UnknownPort {
+                name: name.to_string(),
+            })
+    }
+}
+
+impl ResourceTrait for Resource {
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn started(&self) -> bool {
+        self.started
+    }
+
+    fn initialize(&mut self) -> Result<()> {
+        self.check_state()
+    }
+
+    /**
+     * SCA518
+     * The releaseObject operation shall disconnect any ports that are still
+     * connected.
+     * SCA17
+     * The releaseObject operation shall tear down the component and release
+     * it from the operating environment.
+     */
+    fn release_object(&mut self) -> Result<()> {
+        self.check_state()?;
+        self.uses_ports
+            .iter_mut()
+            .for_each(|p| p.connections.clear());
+        self.started = false;
+        self.released = true;
+        Ok(())
+    }
+
+    /**
+     * SCA27
+     * The configure operation shall raise a PartialConfiguration exception
+     * when some configuration properties were successfully set and some
+     * configuration properties were not successfully set.
+     */
+    fn configure(&mut self, properties: &Properties) -> Result<()> {
+        self.check_state()?;
+
+        let mut invalid_properties = Properties::new();
+        let mut configured = 0;
+        // The properties configured are published at once
+        let logger = &mut self.logger;
+        self.properties.update(|own_properties| {
+            for p in properties {
+                let set = if is_logging_property(&p.id) {
+                    set_logging_property(logger, p)
+                } else if let Some(own) = own_properties.iter_mut().find(|own| own.id == p.id) {
+                    own.value = p.value.clone();
+                    true
+                } else {
+                    false
+                };
+                match set {
+                    true => configured += 1,
+                    false => invalid_properties.push(p.clone()),
+                }
+            }
+        });
+        if configured == 0 && !invalid_properties.is_empty() {
+            return Err(ResourceError::InvalidConfiguration {
+                message: "unknown properties".to_string(),
+                invalid_properties,
+            });
+        }
+        if !invalid_properties.is_empty() {
+            return Err(ResourceError::PartialConfiguration { invalid_properties });
+        }
+        Ok(())
+    }
+
+    /**
+     * SCA29
+     * The query operation shall return all component properties when the
+     * inout parameter configProperties is zero size.
+     * SCA30
+     * The query operation shall return only those id/value pairs specified
+     * in the configProperties parameter if the parameter is not zero size.
+     */
+    fn query(&self, properties: &Properties) -> Result<Properties> {
+        self.properties
+            .reader()
+            .with_logger(self.logger.clone())
+            .query(properties)
+    }
+
+    /**
+     * The snapshots are those of the properties followed by the logging
+     * properties.
+     */
+    fn property_reader(&self) -> Option<PropertyReader> {
+        Some(self.properties.reader().with_logger(self.logger.clone()))
+    }
+
+    /**
+     * SCA33
+     * The start operation shall set the started attribute to a value of TRUE.
+     */
+    fn start(&mut self) -> Result<()> {
+        self.check_state()?;
+        self.started = true;
+        Ok(())
+    }
+
+    /**
+     * SCA36
+     * The stop operation shall set the started attribute to a value of FALSE.
+     */
+    fn stop(&mut self) -> Result<()> {
+        self.check_state()?;
+        self.started = false;
+        Ok(())
+    }
+
+    /**
+     * SCA13
+     * The getProvidesPorts operation shall return the object references that
+     * are associated with the input port names and the connectionIds.
+     */
+    fn get_provides_port(&self, name: &str) -> Result<String> {
+        self.provides_ports
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, endpoint)| endpoint.clone())
+            .ok_or_else(|| ResourceError::UnknownPort {
+                name: name.to_string(),
+            })
+    }
+
+    /**
+     * SCA7
+     * The connectUsesPorts operation shall make the connection(s) to the
+     * component identified by its input portConnections parameter. A port may
+     * support several connections.
+     */
+    fn connect_uses_port(&mut self, name: &str, connection_id: &str, endpoint: &str) -> Result<()> {
+        self.check_state()?;
+        let port = self.uses_port(name)?;
+        if port.connections.iter().any(|(id, _)| id == connection_id) {
+            return Err(ResourceError::InvalidPort {
+                name: name.to_string(),
+                message: format!("connection '{connection_id}' already exists"),
+            });
+        }
+        port.connections
+            .push((connection_id.to_string(), endpoint.to_string()));
+        Ok(())
+    }
+
+    /**
+     * SCA10
+     * The disconnectPorts operation shall break the connection(s) to the
+     * component identified by the input portDisconnections parameter.
+     */
+    fn disconnect_port(&mut self, name: &str, connection_id: &str) -> Result<()> {
+        let port = self.uses_port(name)?;
+        let index = port
+            .connections
+            .iter()
+            .position(|(id, _)| id == connection_id)
+            .ok_or_else(|| ResourceError::InvalidPort {
+                name: name.to_string(),
+                message: format!("connection '{connection_id}' not found"),
+            })?;
+        port.connections.remove(index);
+        Ok(())
+    }
+}
+
+impl LogProducer for Resource {
+    fn log_level(&self) -> LogLevelType {
+        self.logger.log_level()
+    }
+
+    fn set_log_level(&mut self, log_level: LogLevelType) {
+        self.logger.set_log_level(log_level);
+    }
+
+    fn log_uri(&self) -> Option<String> {
+        self.logger.log_uri()
+    }
+
+    fn set_log_uri(&mut self, log_uri: Option<&str>) {
+        self.logger.set_log_uri(log_uri);
+    }
+}
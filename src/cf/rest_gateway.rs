@@ -0,0 +1,494 @@
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::net::TcpListener;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Status};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+
+use super::file_transfer::{self, TransferError, TransferTuning};
+use super::rpc::device::Property;
+use super::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use super::rpc::domain_manager::{
+    ApplicationFactoriesRequest, ApplicationFactoryInfo, ApplicationInfo, ApplicationsRequest,
+    ConfigureApplicationRequest, CreateApplicationReply, CreateApplicationRequest,
+    DeviceAssignment, InstallApplicationReply, InstallApplicationRequest, QueryApplicationRequest,
+    ReleaseApplicationRequest, StartApplicationRequest, StopApplicationRequest,
+    UninstallApplicationRequest,
+};
+use super::rpc::file_system::file_system_client::FileSystemClient;
+use super::rpc::file_system::{
+    ChunkRange, FileInformation, ListRequest, RemoveRequest, WriteReply,
+};
+
+/**
+ * Convienence enum definition that includes all REST gateway errors.
+ */
+#[derive(Error, Debug)]
+pub enum GatewayError {
+    /**
+     * This exception indicates the gateway failed to connect to the
+     * DomainManager or to serve its listener.
+     */
+    #[error("TransportError: endpoint: '{endpoint}', message: '{message}'.")]
+    TransportError { endpoint: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all REST gateway returned errors.
+ */
+pub type Result<T, E = GatewayError> = anyhow::Result<T, E>;
+
+/// The error of a request, the gRPC status code of the service and its message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ApiError {
+    pub code: String,
+    pub message: String,
+}
+
+/// The properties queried, all of them when none.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PropertiesQuery {
+    /// The comma separated ids of the properties.
+    pub ids: Option<String>,
+}
+
+/// The files listed.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FilesQuery {
+    /// The absolute pattern of the files, of the '*' and '?' wildcards, "/*" by default.
+    pub pattern: Option<String>,
+}
+
+/// The gRPC status of a failed call, answered with the matching HTTP status.
+struct StatusResponse(Status);
+
+impl From<Status> for StatusResponse {
+    fn from(status: Status) -> StatusResponse {
+        StatusResponse(status)
+    }
+}
+
+impl From<TransferError> for StatusResponse {
+    fn from(error: TransferError) -> StatusResponse {
+        StatusResponse(error.into())
+    }
+}
+
+impl IntoResponse for StatusResponse {
+    fn into_response(self) -> Response {
+        let status = match self.0.code() {
+            Code::InvalidArgument | Code::OutOfRange => StatusCode::BAD_REQUEST,
+            Code::NotFound => StatusCode::NOT_FOUND,
+            Code::AlreadyExists | Code::FailedPrecondition | Code::Aborted => StatusCode::CONFLICT,
+            Code::PermissionDenied => StatusCode::FORBIDDEN,
+            Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+            Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+            Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let error = ApiError {
+            code: format!("{:?}", self.0.code()),
+            message: self.0.message().to_string(),
+        };
+        (status, Json(error)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, StatusResponse>;
+
+/**
+ * REST/JSON gateway translating the requests of the web HMIs and of the
+ * scripts without gRPC support onto the DomainManager and FileSystem
+ * services of a domain, served by the DomainManager on one endpoint.
+ * The bodies are the canonical JSON of the messages of the services,
+ * see rpc::message_to_json, and the OpenAPI spec of the gateway is
+ * served at /openapi.json.
+ */
+#[derive(Clone)]
+pub struct RestGateway {
+    domain: DomainManagerClient<Channel>,
+    file_system: FileSystemClient<Channel>,
+    tuning: TransferTuning,
+}
+
+impl RestGateway {
+    /// Connects to the DomainManager endpoint, e.g. "http://127.0.0.1:5000".
+    pub async fn connect(endpoint: &str) -> Result<RestGateway> {
+        let transport_error = |e: tonic::transport::Error| GatewayError::TransportError {
+            endpoint: endpoint.to_string(),
+            message: e.to_string(),
+        };
+        let channel = Endpoint::from_shared(endpoint.to_string())
+            .map_err(transport_error)?
+            .connect()
+            .await
+            .map_err(transport_error)?;
+        Ok(RestGateway::new(channel))
+    }
+
+    pub fn new(channel: Channel) -> RestGateway {
+        RestGateway {
+            domain: DomainManagerClient::new(channel.clone()),
+            file_system: FileSystemClient::new(channel),
+            tuning: TransferTuning::default(),
+        }
+    }
+
+    /// Sets the bounds the chunks of the files read and written adapt within.
+    pub fn with_transfer_tuning(mut self, tuning: TransferTuning) -> Self {
+        self.tuning = tuning;
+        self
+    }
+
+    /// Returns the routes of the REST API.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/openapi.json", get(openapi_json))
+            .route(
+                "/applications",
+                get(list_applications).post(create_application),
+            )
+            .route(
+                "/applications/:identifier",
+                axum::routing::delete(release_application),
+            )
+            .route("/applications/:identifier/start", post(start_application))
+            .route("/applications/:identifier/stop", post(stop_application))
+            .route(
+                "/applications/:identifier/properties",
+                get(query_properties).put(configure_properties),
+            )
+            .route(
+                "/application-factories",
+                get(list_application_factories).post(install_application),
+            )
+            .route(
+                "/application-factories/:identifier",
+                axum::routing::delete(uninstall_application),
+            )
+            .route("/files", get(list_files))
+            .route(
+                "/files/*path",
+                get(read_file).put(write_file).delete(remove_file),
+            )
+            .with_state(self.clone())
+    }
+
+    /// Serves the REST API on a listener, until the runtime stops.
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        let endpoint = listener
+            .local_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default();
+        let transport_error = |e: &dyn std::error::Error| GatewayError::TransportError {
+            endpoint: endpoint.clone(),
+            message: e.to_string(),
+        };
+        let std_listener = listener.into_std().map_err(|e| transport_error(&e))?;
+        axum::Server::from_tcp(std_listener)
+            .map_err(|e| transport_error(&e))?
+            .serve(self.router().into_make_service())
+            .await
+            .map_err(|e| transport_error(&e))
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "scars REST gateway",
+        description = "REST/JSON API of the DomainManager and FileSystem services of a domain."
+    ),
+    paths(
+        list_applications,
+        create_application,
+        release_application,
+        start_application,
+        stop_application,
+        query_properties,
+        configure_properties,
+        list_application_factories,
+        install_application,
+        uninstall_application,
+        list_files,
+        read_file,
+        write_file,
+        remove_file
+    ),
+    components(schemas(
+        ApplicationInfo,
+        ApplicationFactoryInfo,
+        Property,
+        InstallApplicationRequest,
+        InstallApplicationReply,
+        DeviceAssignment,
+        CreateApplicationRequest,
+        CreateApplicationReply,
+        FileInformation,
+        WriteReply,
+        ChunkRange,
+        ApiError
+    ))
+)]
+struct ApiDoc;
+
+/// Returns the OpenAPI spec of the REST API of the gateway.
+pub fn openapi() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(openapi())
+}
+
+/// Lists the running applications.
+#[utoipa::path(get, path = "/applications", responses(
+    (status = 200, body = [ApplicationInfo]),
+))]
+async fn list_applications(
+    State(gateway): State<RestGateway>,
+) -> ApiResult<Json<Vec<ApplicationInfo>>> {
+    let reply = gateway
+        .domain
+        .clone()
+        .applications(ApplicationsRequest {})
+        .await?;
+    Ok(Json(reply.into_inner().applications))
+}
+
+/// Creates an application from an installed factory.
+#[utoipa::path(post, path = "/applications", request_body = CreateApplicationRequest, responses(
+    (status = 201, body = CreateApplicationReply),
+    (status = 404, body = ApiError),
+))]
+async fn create_application(
+    State(gateway): State<RestGateway>,
+    Json(request): Json<CreateApplicationRequest>,
+) -> ApiResult<(StatusCode, Json<CreateApplicationReply>)> {
+    let reply = gateway.domain.clone().create_application(request).await?;
+    Ok((StatusCode::CREATED, Json(reply.into_inner())))
+}
+
+/// Releases an application.
+#[utoipa::path(delete, path = "/applications/{identifier}", responses(
+    (status = 204),
+    (status = 404, body = ApiError),
+))]
+async fn release_application(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+) -> ApiResult<StatusCode> {
+    let request = ReleaseApplicationRequest { identifier };
+    gateway.domain.clone().release_application(request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Starts the components of an application.
+#[utoipa::path(post, path = "/applications/{identifier}/start", responses(
+    (status = 204),
+    (status = 404, body = ApiError),
+))]
+async fn start_application(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+) -> ApiResult<StatusCode> {
+    let request = StartApplicationRequest { identifier };
+    gateway.domain.clone().start_application(request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stops the components of an application.
+#[utoipa::path(post, path = "/applications/{identifier}/stop", responses(
+    (status = 204),
+    (status = 404, body = ApiError),
+))]
+async fn stop_application(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+) -> ApiResult<StatusCode> {
+    let request = StopApplicationRequest { identifier };
+    gateway.domain.clone().stop_application(request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Queries the properties of an application, or of a component of one.
+#[utoipa::path(get, path = "/applications/{identifier}/properties", params(PropertiesQuery), responses(
+    (status = 200, body = [Property]),
+    (status = 404, body = ApiError),
+))]
+async fn query_properties(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+    Query(query): Query<PropertiesQuery>,
+) -> ApiResult<Json<Vec<Property>>> {
+    let properties = query
+        .ids
+        .iter()
+        .flat_map(|ids| ids.split(',').filter(|id| !id.is_empty()))
+        .map(|id| Property {
+            id: id.to_string(),
+            value: String::new(),
+        })
+        .collect();
+    let request = QueryApplicationRequest {
+        identifier,
+        properties,
+    };
+    let reply = gateway.domain.clone().query_application(request).await?;
+    Ok(Json(reply.into_inner().properties))
+}
+
+/// Configures the properties of an application, or of a component of one.
+#[utoipa::path(put, path = "/applications/{identifier}/properties", request_body = [Property], responses(
+    (status = 204),
+    (status = 400, body = ApiError),
+    (status = 404, body = ApiError),
+))]
+async fn configure_properties(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+    Json(properties): Json<Vec<Property>>,
+) -> ApiResult<StatusCode> {
+    let request = ConfigureApplicationRequest {
+        identifier,
+        properties,
+    };
+    gateway
+        .domain
+        .clone()
+        .configure_application(request)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the installed application factories.
+#[utoipa::path(get, path = "/application-factories", responses(
+    (status = 200, body = [ApplicationFactoryInfo]),
+))]
+async fn list_application_factories(
+    State(gateway): State<RestGateway>,
+) -> ApiResult<Json<Vec<ApplicationFactoryInfo>>> {
+    let request = ApplicationFactoriesRequest {};
+    let reply = gateway
+        .domain
+        .clone()
+        .application_factories(request)
+        .await?;
+    Ok(Json(reply.into_inner().application_factories))
+}
+
+/// Installs the application factory of a SAD.
+#[utoipa::path(post, path = "/application-factories", request_body = InstallApplicationRequest, responses(
+    (status = 201, body = InstallApplicationReply),
+    (status = 400, body = ApiError),
+))]
+async fn install_application(
+    State(gateway): State<RestGateway>,
+    Json(request): Json<InstallApplicationRequest>,
+) -> ApiResult<(StatusCode, Json<InstallApplicationReply>)> {
+    let reply = gateway.domain.clone().install_application(request).await?;
+    Ok((StatusCode::CREATED, Json(reply.into_inner())))
+}
+
+/// Uninstalls an application factory.
+#[utoipa::path(delete, path = "/application-factories/{identifier}", responses(
+    (status = 204),
+    (status = 404, body = ApiError),
+))]
+async fn uninstall_application(
+    State(gateway): State<RestGateway>,
+    Path(identifier): Path<String>,
+) -> ApiResult<StatusCode> {
+    let request = UninstallApplicationRequest { identifier };
+    gateway
+        .domain
+        .clone()
+        .uninstall_application(request)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists the files of the domain FileManager matching a pattern.
+#[utoipa::path(get, path = "/files", params(FilesQuery), responses(
+    (status = 200, body = [FileInformation]),
+    (status = 400, body = ApiError),
+))]
+async fn list_files(
+    State(gateway): State<RestGateway>,
+    Query(query): Query<FilesQuery>,
+) -> ApiResult<Json<Vec<FileInformation>>> {
+    let request = ListRequest {
+        pattern: query.pattern.unwrap_or_else(|| "/*".to_string()),
+    };
+    let mut files = gateway
+        .file_system
+        .clone()
+        .list(request)
+        .await?
+        .into_inner()
+        .files;
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Json(files))
+}
+
+/// Reads a file of the domain FileManager.
+#[utoipa::path(get, path = "/files/{path}", responses(
+    (status = 200, content_type = "application/octet-stream", body = Vec<u8>),
+    (status = 404, body = ApiError),
+))]
+async fn read_file(
+    State(gateway): State<RestGateway>,
+    Path(path): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let (data, _) =
+        file_transfer::read_file(&gateway.file_system, &format!("/{path}"), &gateway.tuning)
+            .await?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], data))
+}
+
+/// Writes a file of the domain FileManager, created or truncated.
+#[utoipa::path(put, path = "/files/{path}", request_body(content = Vec<u8>, content_type = "application/octet-stream"), responses(
+    (status = 200, body = WriteReply),
+    (status = 400, body = ApiError),
+))]
+async fn write_file(
+    State(gateway): State<RestGateway>,
+    Path(path): Path<String>,
+    data: Bytes,
+) -> ApiResult<Json<WriteReply>> {
+    let stats = file_transfer::write_file(
+        &gateway.file_system,
+        &format!("/{path}"),
+        data.to_vec(),
+        &gateway.tuning,
+    )
+    .await?;
+    // The corrupted chunks are written again by the transfer
+    Ok(Json(WriteReply {
+        size: stats.size as u64,
+        corrupted: Vec::new(),
+    }))
+}
+
+/// Removes a file of the domain FileManager.
+#[utoipa::path(delete, path = "/files/{path}", responses(
+    (status = 204),
+    (status = 404, body = ApiError),
+))]
+async fn remove_file(
+    State(gateway): State<RestGateway>,
+    Path(path): Path<String>,
+) -> ApiResult<StatusCode> {
+    let request = RemoveRequest {
+        file_name: format!("/{path}"),
+    };
+    gateway.file_system.clone().remove(request).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,52 @@
+use tokio::net::TcpListener;
+
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::rest_gateway::{openapi, RestGateway};
+
+const USAGE: &str = "usage: scars-rest-gateway [--format text|json] <domain manager endpoint> <listen address>\n       scars-rest-gateway --openapi";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-rest-gateway",
+    options: &[
+        CliOption::flag("--openapi"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &[],
+};
+
+/**
+ * REST gateway: serves the REST/JSON API of a domain on the listen
+ * address, e.g. 0.0.0.0:8080, for the web HMIs and the scripts without
+ * gRPC support, printing the address once bound. With --openapi, the
+ * OpenAPI spec of the API is printed.
+ *
+ * usage: scars-rest-gateway [--format text|json] <domain manager endpoint> <listen address>
+ *        scars-rest-gateway --openapi
+ *        scars-rest-gateway --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args).map_err(|e| format!("{e}\n{USAGE}"))?;
+    let (endpoint, address) = match args.as_slice() {
+        [option] if option == "--openapi" => {
+            println!("{}", openapi().to_pretty_json()?);
+            return Ok(());
+        }
+        [endpoint, address] => (endpoint, address),
+        _ => return Err(USAGE.into()),
+    };
+
+    let gateway = RestGateway::connect(endpoint).await?;
+    let listener = TcpListener::bind(address).await?;
+    cli::print_address(format, "RestGateway", listener.local_addr()?);
+    gateway.serve(listener).await?;
+    Ok(())
+}
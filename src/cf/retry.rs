@@ -0,0 +1,92 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tonic::{Code, Status};
+
+/**
+ * Policy of the registrations retried while their registrar is
+ * unreachable: the delay between two attempts doubles from the initial
+ * delay up to the maximum delay, until the attempts are exhausted. Each
+ * attempt is given up once the attempt timeout elapsed, so that a hung
+ * registrar is retried like an unreachable one.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// The number of attempts, the first one included.
+    pub max_attempts: u32,
+    /// The time given to each attempt, the connection included.
+    pub attempt_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            max_attempts: 10,
+            attempt_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the policy making a single attempt.
+    pub fn once() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Returns the delay following the failed attempt, counted from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    /**
+     * Runs a single attempt of an operation, failing with a
+     * DEADLINE_EXCEEDED status once the attempt timeout elapsed.
+     */
+    pub async fn attempt<T, Fut>(&self, operation: Fut) -> Result<T, Status>
+    where
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        match tokio::time::timeout(self.attempt_timeout, operation).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(Status::deadline_exceeded(format!(
+                "no answer within {:?}",
+                self.attempt_timeout
+            ))),
+        }
+    }
+
+    /**
+     * Runs the operation until it succeeds, fails otherwise than with an
+     * UNAVAILABLE or DEADLINE_EXCEEDED status, or the attempts are
+     * exhausted, returning the outcome of the last attempt.
+     */
+    pub async fn retry<T, F, Fut>(&self, mut operation: F) -> Result<T, Status>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match self.attempt(operation()).await {
+                Err(status)
+                    if matches!(status.code(), Code::Unavailable | Code::DeadlineExceeded)
+                        && attempt < self.max_attempts =>
+                {
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+}
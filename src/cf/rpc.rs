@@ -0,0 +1,1183 @@
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::{Duration, UNIX_EPOCH};
+
+use scars_types::wire::{value_from_wire, value_to_wire};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::runtime::Runtime;
+use tonic::transport::{Channel, Endpoint};
+use tonic::Status;
+
+use super::allocation_manager::{AllocationStatus, DeviceCapacities};
+use super::application::{ApplicationMetrics, ComponentMetrics};
+use super::blocking_pool::{BlockingError, BlockingPoolMetrics};
+use super::buffer_pool::BufferPoolMetrics;
+use super::common_types::{DataType, ErrorNumberType, Properties};
+use super::connection_manager::{
+    ConnectionManagerError, ConnectionStatus, EndpointRequest, EndpointResolution,
+};
+use super::device::{AdminType, DeviceError, OperationalType, UsageType};
+use super::domain_manager::DomainManagerError;
+use super::events::{
+    AbnormalComponentTerminationEvent, DomainManagementEvent, EncodedEvent, EventFilter,
+    PropertyChangeEvent, SourceCategoryType, StateChangeCategoryType, StateChangeEvent,
+    StateChangeType,
+};
+use super::executable_device::{ExecutableDeviceError, ProcessStatus};
+use super::file::FileError;
+use super::file_archive::ArchiveError;
+use super::file_delta::DeltaError;
+use super::file_system::{FileInformationType, FileSystemError, FileSystemSpace, FileType};
+use super::file_transfer::TransferError;
+use super::loadable_device::{LoadType, LoadableDeviceError};
+use super::log::{LogFilter, LogLevelType, LogRecord};
+use super::log_service::LogQuery;
+
+/**
+ * Generated bindings of the FileSystem gRPC service. The stream of the
+ * snake case read is named after it.
+ */
+#[allow(non_camel_case_types)]
+pub mod file_system {
+    tonic::include_proto!("file_system");
+    include!(concat!(env!("OUT_DIR"), "/file_system.serde.rs"));
+}
+
+/**
+ * Generated bindings of the Device gRPC service.
+ */
+pub mod device {
+    tonic::include_proto!("device");
+    include!(concat!(env!("OUT_DIR"), "/device.serde.rs"));
+}
+
+/**
+ * Generated bindings of the DeviceManager gRPC service.
+ */
+pub mod device_manager {
+    tonic::include_proto!("device_manager");
+    include!(concat!(env!("OUT_DIR"), "/device_manager.serde.rs"));
+}
+
+/**
+ * Generated bindings of the DomainManager gRPC service. The streams of
+ * the snake case event subscriptions are named after them.
+ */
+#[allow(non_camel_case_types)]
+pub mod domain_manager {
+    tonic::include_proto!("domain_manager");
+    include!(concat!(env!("OUT_DIR"), "/domain_manager.serde.rs"));
+}
+
+/**
+ * Generated bindings of the Registrar gRPC service.
+ */
+pub mod registrar {
+    tonic::include_proto!("registrar");
+    include!(concat!(env!("OUT_DIR"), "/registrar.serde.rs"));
+}
+
+/**
+ * Generated bindings of the LogService gRPC service.
+ */
+pub mod log_service {
+    tonic::include_proto!("log_service");
+    include!(concat!(env!("OUT_DIR"), "/log_service.serde.rs"));
+}
+
+/**
+ * Generated bindings of the EventChannelManager gRPC service. The stream
+ * of the snake case subscription is named after it.
+ */
+#[allow(non_camel_case_types)]
+pub mod event_channel {
+    tonic::include_proto!("event_channel");
+    include!(concat!(env!("OUT_DIR"), "/event_channel.serde.rs"));
+}
+
+impl From<AdminType> for device::AdminType {
+    fn from(value: AdminType) -> Self {
+        match value {
+            AdminType::LOCKED => device::AdminType::Locked,
+            AdminType::SHUTTING_DOWN => device::AdminType::ShuttingDown,
+            AdminType::UNLOCKED => device::AdminType::Unlocked,
+        }
+    }
+}
+
+impl From<device::AdminType> for AdminType {
+    fn from(value: device::AdminType) -> Self {
+        match value {
+            device::AdminType::Locked => AdminType::LOCKED,
+            device::AdminType::ShuttingDown => AdminType::SHUTTING_DOWN,
+            device::AdminType::Unlocked => AdminType::UNLOCKED,
+        }
+    }
+}
+
+impl From<OperationalType> for device::OperationalType {
+    fn from(value: OperationalType) -> Self {
+        match value {
+            OperationalType::ENABLED => device::OperationalType::Enabled,
+            OperationalType::DISABLED => device::OperationalType::Disabled,
+        }
+    }
+}
+
+impl From<device::OperationalType> for OperationalType {
+    fn from(value: device::OperationalType) -> Self {
+        match value {
+            device::OperationalType::Enabled => OperationalType::ENABLED,
+            device::OperationalType::Disabled => OperationalType::DISABLED,
+        }
+    }
+}
+
+impl From<UsageType> for device::UsageType {
+    fn from(value: UsageType) -> Self {
+        match value {
+            UsageType::IDLE => device::UsageType::Idle,
+            UsageType::ACTIVE => device::UsageType::Active,
+            UsageType::BUSY => device::UsageType::Busy,
+        }
+    }
+}
+
+impl From<device::UsageType> for UsageType {
+    fn from(value: device::UsageType) -> Self {
+        match value {
+            device::UsageType::Idle => UsageType::IDLE,
+            device::UsageType::Active => UsageType::ACTIVE,
+            device::UsageType::Busy => UsageType::BUSY,
+        }
+    }
+}
+
+impl From<DeviceError> for Status {
+    fn from(value: DeviceError) -> Self {
+        match value {
+            DeviceError::InvalidState { .. } => Status::failed_precondition(value.to_string()),
+            DeviceError::InvalidCapacity { .. } => Status::invalid_argument(value.to_string()),
+        }
+    }
+}
+
+/**
+ * Decodes the error of a call of a remote device from its status, the
+ * capacities being the ones of the call.
+ */
+pub fn device_error_from_status(status: &Status, capacities: &Properties) -> DeviceError {
+    let message = status.message().to_string();
+    match status.code() {
+        tonic::Code::InvalidArgument => DeviceError::InvalidCapacity {
+            message,
+            capacities: capacities.clone(),
+        },
+        _ => DeviceError::InvalidState { message },
+    }
+}
+
+impl From<LoadType> for device::LoadType {
+    fn from(value: LoadType) -> Self {
+        match value {
+            LoadType::KERNEL_MODULE => device::LoadType::KernelModule,
+            LoadType::DRIVER => device::LoadType::Driver,
+            LoadType::SHARED_LIBRARY => device::LoadType::SharedLibrary,
+            LoadType::EXECUTABLE => device::LoadType::Executable,
+        }
+    }
+}
+
+impl From<device::LoadType> for LoadType {
+    fn from(value: device::LoadType) -> Self {
+        match value {
+            device::LoadType::KernelModule => LoadType::KERNEL_MODULE,
+            device::LoadType::Driver => LoadType::DRIVER,
+            device::LoadType::SharedLibrary => LoadType::SHARED_LIBRARY,
+            device::LoadType::Executable => LoadType::EXECUTABLE,
+        }
+    }
+}
+
+impl From<ProcessStatus> for device::ProcessStatus {
+    fn from(value: ProcessStatus) -> Self {
+        match value {
+            ProcessStatus::RUNNING => device::ProcessStatus::Running,
+            ProcessStatus::SLEEPING => device::ProcessStatus::Sleeping,
+            ProcessStatus::STOPPED => device::ProcessStatus::Stopped,
+            ProcessStatus::ZOMBIE => device::ProcessStatus::Zombie,
+            ProcessStatus::TERMINATED => device::ProcessStatus::Terminated,
+            ProcessStatus::UNKNOWN => device::ProcessStatus::Unknown,
+        }
+    }
+}
+
+impl From<device::ProcessStatus> for ProcessStatus {
+    fn from(value: device::ProcessStatus) -> Self {
+        match value {
+            device::ProcessStatus::Running => ProcessStatus::RUNNING,
+            device::ProcessStatus::Sleeping => ProcessStatus::SLEEPING,
+            device::ProcessStatus::Stopped => ProcessStatus::STOPPED,
+            device::ProcessStatus::Zombie => ProcessStatus::ZOMBIE,
+            device::ProcessStatus::Terminated => ProcessStatus::TERMINATED,
+            device::ProcessStatus::Unknown => ProcessStatus::UNKNOWN,
+        }
+    }
+}
+
+impl From<LoadableDeviceError> for Status {
+    fn from(value: LoadableDeviceError) -> Self {
+        match value {
+            LoadableDeviceError::InvalidState { .. } => {
+                Status::failed_precondition(value.to_string())
+            }
+            LoadableDeviceError::InvalidLoadKind => Status::unimplemented(value.to_string()),
+            LoadableDeviceError::InvalidFileName { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            LoadableDeviceError::LoadFail {
+                error_number: ErrorNumberType::CF_ENOENT,
+                ..
+            } => Status::not_found(value.to_string()),
+            LoadableDeviceError::LoadFail {
+                error_number: ErrorNumberType::CF_ENOSPC,
+                ..
+            } => Status::resource_exhausted(value.to_string()),
+            LoadableDeviceError::LoadFail { .. } => Status::internal(value.to_string()),
+        }
+    }
+}
+
+/**
+ * Decodes the error of a load or unload of a remote device from its
+ * status, the error numbers the status does not tell being CF_EIO.
+ */
+pub fn loadable_device_error_from_status(status: &Status) -> LoadableDeviceError {
+    let message = status.message().to_string();
+    let error_number = match status.code() {
+        tonic::Code::FailedPrecondition => return LoadableDeviceError::InvalidState { message },
+        tonic::Code::Unimplemented => return LoadableDeviceError::InvalidLoadKind,
+        tonic::Code::InvalidArgument => {
+            return LoadableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_EINVAL,
+                message,
+            }
+        }
+        tonic::Code::NotFound => ErrorNumberType::CF_ENOENT,
+        tonic::Code::ResourceExhausted => ErrorNumberType::CF_ENOSPC,
+        tonic::Code::DeadlineExceeded => ErrorNumberType::CF_ETIMEDOUT,
+        _ => ErrorNumberType::CF_EIO,
+    };
+    LoadableDeviceError::LoadFail {
+        error_number,
+        message,
+    }
+}
+
+impl From<ExecutableDeviceError> for Status {
+    fn from(value: ExecutableDeviceError) -> Self {
+        match value {
+            ExecutableDeviceError::InvalidState { .. } => {
+                Status::failed_precondition(value.to_string())
+            }
+            ExecutableDeviceError::InvalidFileName { .. }
+            | ExecutableDeviceError::InvalidProcess { .. } => Status::not_found(value.to_string()),
+            ExecutableDeviceError::InvalidParameters { .. }
+            | ExecutableDeviceError::InvalidOptions { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            ExecutableDeviceError::ExecuteFail { .. } => Status::internal(value.to_string()),
+        }
+    }
+}
+
+/**
+ * Decodes the error of an execute of a remote device from its status, a
+ * file not found being an invalid file name, the invalid options and
+ * parameters failing the execute with CF_EINVAL.
+ */
+pub fn execute_error_from_status(status: &Status) -> ExecutableDeviceError {
+    let message = status.message().to_string();
+    let error_number = match status.code() {
+        tonic::Code::FailedPrecondition => return ExecutableDeviceError::InvalidState { message },
+        tonic::Code::NotFound => {
+            return ExecutableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message,
+            }
+        }
+        tonic::Code::InvalidArgument => ErrorNumberType::CF_EINVAL,
+        tonic::Code::DeadlineExceeded => ErrorNumberType::CF_ETIMEDOUT,
+        _ => ErrorNumberType::CF_EIO,
+    };
+    ExecutableDeviceError::ExecuteFail {
+        error_number,
+        message,
+    }
+}
+
+/**
+ * Decodes the error of a terminate, or a collection of the metrics of a
+ * process, of a remote device from its status, a process not found
+ * being an invalid process.
+ */
+pub fn process_error_from_status(status: &Status) -> ExecutableDeviceError {
+    match status.code() {
+        tonic::Code::NotFound => ExecutableDeviceError::InvalidProcess {
+            error_number: ErrorNumberType::CF_ESRCH,
+            message: status.message().to_string(),
+        },
+        _ => execute_error_from_status(status),
+    }
+}
+
+impl From<StateChangeCategoryType> for domain_manager::StateChangeCategoryType {
+    fn from(value: StateChangeCategoryType) -> Self {
+        match value {
+            StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT => {
+                domain_manager::StateChangeCategoryType::AdministrativeStateEvent
+            }
+            StateChangeCategoryType::OPERATIONAL_STATE_EVENT => {
+                domain_manager::StateChangeCategoryType::OperationalStateEvent
+            }
+            StateChangeCategoryType::USAGE_STATE_EVENT => {
+                domain_manager::StateChangeCategoryType::UsageStateEvent
+            }
+        }
+    }
+}
+
+impl From<domain_manager::StateChangeCategoryType> for StateChangeCategoryType {
+    fn from(value: domain_manager::StateChangeCategoryType) -> Self {
+        match value {
+            domain_manager::StateChangeCategoryType::AdministrativeStateEvent => {
+                StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT
+            }
+            domain_manager::StateChangeCategoryType::OperationalStateEvent => {
+                StateChangeCategoryType::OPERATIONAL_STATE_EVENT
+            }
+            domain_manager::StateChangeCategoryType::UsageStateEvent => {
+                StateChangeCategoryType::USAGE_STATE_EVENT
+            }
+        }
+    }
+}
+
+impl From<StateChangeType> for domain_manager::StateChangeType {
+    fn from(value: StateChangeType) -> Self {
+        match value {
+            StateChangeType::LOCKED => domain_manager::StateChangeType::Locked,
+            StateChangeType::UNLOCKED => domain_manager::StateChangeType::Unlocked,
+            StateChangeType::SHUTTING_DOWN => domain_manager::StateChangeType::ShuttingDown,
+            StateChangeType::ENABLED => domain_manager::StateChangeType::Enabled,
+            StateChangeType::DISABLED => domain_manager::StateChangeType::Disabled,
+            StateChangeType::IDLE => domain_manager::StateChangeType::Idle,
+            StateChangeType::ACTIVE => domain_manager::StateChangeType::Active,
+            StateChangeType::BUSY => domain_manager::StateChangeType::Busy,
+        }
+    }
+}
+
+impl From<domain_manager::StateChangeType> for StateChangeType {
+    fn from(value: domain_manager::StateChangeType) -> Self {
+        match value {
+            domain_manager::StateChangeType::Locked => StateChangeType::LOCKED,
+            domain_manager::StateChangeType::Unlocked => StateChangeType::UNLOCKED,
+            domain_manager::StateChangeType::ShuttingDown => StateChangeType::SHUTTING_DOWN,
+            domain_manager::StateChangeType::Enabled => StateChangeType::ENABLED,
+            domain_manager::StateChangeType::Disabled => StateChangeType::DISABLED,
+            domain_manager::StateChangeType::Idle => StateChangeType::IDLE,
+            domain_manager::StateChangeType::Active => StateChangeType::ACTIVE,
+            domain_manager::StateChangeType::Busy => StateChangeType::BUSY,
+        }
+    }
+}
+
+impl From<SourceCategoryType> for domain_manager::SourceCategoryType {
+    fn from(value: SourceCategoryType) -> Self {
+        match value {
+            SourceCategoryType::DEVICE_MANAGER => domain_manager::SourceCategoryType::DeviceManager,
+            SourceCategoryType::DEVICE => domain_manager::SourceCategoryType::Device,
+            SourceCategoryType::APPLICATION_FACTORY => {
+                domain_manager::SourceCategoryType::ApplicationFactory
+            }
+            SourceCategoryType::APPLICATION => domain_manager::SourceCategoryType::Application,
+            SourceCategoryType::SERVICE => domain_manager::SourceCategoryType::Service,
+            SourceCategoryType::DOMAIN_MANAGER => domain_manager::SourceCategoryType::DomainManager,
+        }
+    }
+}
+
+impl From<domain_manager::SourceCategoryType> for SourceCategoryType {
+    fn from(value: domain_manager::SourceCategoryType) -> Self {
+        match value {
+            domain_manager::SourceCategoryType::DeviceManager => SourceCategoryType::DEVICE_MANAGER,
+            domain_manager::SourceCategoryType::Device => SourceCategoryType::DEVICE,
+            domain_manager::SourceCategoryType::ApplicationFactory => {
+                SourceCategoryType::APPLICATION_FACTORY
+            }
+            domain_manager::SourceCategoryType::Application => SourceCategoryType::APPLICATION,
+            domain_manager::SourceCategoryType::Service => SourceCategoryType::SERVICE,
+            domain_manager::SourceCategoryType::DomainManager => SourceCategoryType::DOMAIN_MANAGER,
+        }
+    }
+}
+
+impl From<&StateChangeEvent> for domain_manager::StateChangeEvent {
+    fn from(value: &StateChangeEvent) -> Self {
+        let mut event = domain_manager::StateChangeEvent {
+            producer_id: value.producer_id.clone(),
+            source_id: value.source_id.clone(),
+            ..Default::default()
+        };
+        event.set_state_change_category(value.state_change_category.into());
+        event.set_state_change_from(value.state_change_from.into());
+        event.set_state_change_to(value.state_change_to.into());
+        event
+    }
+}
+
+impl From<&EndpointRequest> for domain_manager::EndpointRequest {
+    fn from(value: &EndpointRequest) -> Self {
+        use domain_manager::endpoint_request::Resolution;
+
+        let resolution = match &value.resolution {
+            EndpointResolution::COMPONENT(name) => Resolution::Component(name.clone()),
+            EndpointResolution::DEVICE(identifier) => Resolution::Device(identifier.clone()),
+            EndpointResolution::SERVICE(name) => Resolution::Service(name.clone()),
+            EndpointResolution::OBJECT(endpoint) => Resolution::Object(endpoint.clone()),
+        };
+        domain_manager::EndpointRequest {
+            resolution: Some(resolution),
+            port_name: value.port_name.clone(),
+        }
+    }
+}
+
+/// Decodes an endpoint request from the wire, returning None when it misses its resolution.
+pub fn endpoint_request_from_wire(
+    request: Option<domain_manager::EndpointRequest>,
+) -> Option<EndpointRequest> {
+    use domain_manager::endpoint_request::Resolution;
+
+    let request = request?;
+    let resolution = match request.resolution? {
+        Resolution::Component(name) => EndpointResolution::COMPONENT(name),
+        Resolution::Device(identifier) => EndpointResolution::DEVICE(identifier),
+        Resolution::Service(name) => EndpointResolution::SERVICE(name),
+        Resolution::Object(endpoint) => EndpointResolution::OBJECT(endpoint),
+    };
+    Some(EndpointRequest {
+        resolution,
+        port_name: request.port_name,
+    })
+}
+
+impl From<&ConnectionStatus> for domain_manager::ConnectionStatus {
+    fn from(value: &ConnectionStatus) -> Self {
+        domain_manager::ConnectionStatus {
+            connection_record_id: value.record_id.clone(),
+            connection_id: value.connection_id.clone(),
+            requester_id: value.requester_id.clone(),
+            uses_endpoint: Some((&value.uses_endpoint).into()),
+            provides_endpoint: Some((&value.provides_endpoint).into()),
+            connected: value.connected,
+        }
+    }
+}
+
+impl From<ProcessStatus> for domain_manager::ProcessStatus {
+    fn from(value: ProcessStatus) -> Self {
+        match value {
+            ProcessStatus::RUNNING => domain_manager::ProcessStatus::Running,
+            ProcessStatus::SLEEPING => domain_manager::ProcessStatus::Sleeping,
+            ProcessStatus::STOPPED => domain_manager::ProcessStatus::Stopped,
+            ProcessStatus::ZOMBIE => domain_manager::ProcessStatus::Zombie,
+            ProcessStatus::TERMINATED => domain_manager::ProcessStatus::Terminated,
+            ProcessStatus::UNKNOWN => domain_manager::ProcessStatus::Unknown,
+        }
+    }
+}
+
+impl From<domain_manager::ProcessStatus> for ProcessStatus {
+    fn from(value: domain_manager::ProcessStatus) -> Self {
+        match value {
+            domain_manager::ProcessStatus::Running => ProcessStatus::RUNNING,
+            domain_manager::ProcessStatus::Sleeping => ProcessStatus::SLEEPING,
+            domain_manager::ProcessStatus::Stopped => ProcessStatus::STOPPED,
+            domain_manager::ProcessStatus::Zombie => ProcessStatus::ZOMBIE,
+            domain_manager::ProcessStatus::Terminated => ProcessStatus::TERMINATED,
+            domain_manager::ProcessStatus::Unknown => ProcessStatus::UNKNOWN,
+        }
+    }
+}
+
+impl From<&ComponentMetrics> for domain_manager::ComponentMetrics {
+    fn from(value: &ComponentMetrics) -> Self {
+        domain_manager::ComponentMetrics {
+            component_id: value.component_id.clone(),
+            device_id: value.device_id.clone(),
+            process_id: value.process_id,
+            cpu_usage: value.cpu_usage,
+            memory: value.memory,
+            status: domain_manager::ProcessStatus::from(value.status).into(),
+        }
+    }
+}
+
+impl From<&ApplicationMetrics> for domain_manager::ApplicationMetricsReply {
+    fn from(value: &ApplicationMetrics) -> Self {
+        domain_manager::ApplicationMetricsReply {
+            identifier: value.application_id.clone(),
+            cpu_usage: value.cpu_usage,
+            memory: value.memory,
+            components: value.components.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&AllocationStatus> for domain_manager::Allocation {
+    fn from(value: &AllocationStatus) -> Self {
+        domain_manager::Allocation {
+            allocation_id: value.allocation_id.clone(),
+            request_id: value.request_id.clone(),
+            source_id: value.source_id.clone(),
+            allocated_capacities: properties_to_wire(&value.allocated_capacities),
+            failed: value.failed,
+        }
+    }
+}
+
+impl From<&DeviceCapacities> for domain_manager::DeviceCapacities {
+    fn from(value: &DeviceCapacities) -> Self {
+        domain_manager::DeviceCapacities {
+            identifier: value.identifier.clone(),
+            label: value.label.clone(),
+            admin_state: device::AdminType::from(value.admin_state).into(),
+            operational_state: device::OperationalType::from(value.operational_state).into(),
+            usage_state: device::UsageType::from(value.usage_state).into(),
+            allocation_properties: properties_to_wire(&value.allocation_properties),
+            allocations: value.allocations.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/**
+ * Decodes the capacities of a device from the wire, returning None
+ * when a state or a property value is invalid.
+ */
+pub fn device_capacities_from_wire(
+    value: domain_manager::DeviceCapacities,
+) -> Option<DeviceCapacities> {
+    let allocations = value
+        .allocations
+        .into_iter()
+        .map(|a| {
+            Some(AllocationStatus {
+                allocated_device: value.identifier.clone(),
+                allocated_capacities: properties_from_wire(&a.allocated_capacities).ok()?,
+                allocation_id: a.allocation_id,
+                request_id: a.request_id,
+                source_id: a.source_id,
+                failed: a.failed,
+            })
+        })
+        .collect::<Option<_>>()?;
+    Some(DeviceCapacities {
+        admin_state: device::AdminType::try_from(value.admin_state).ok()?.into(),
+        operational_state: device::OperationalType::try_from(value.operational_state)
+            .ok()?
+            .into(),
+        usage_state: device::UsageType::try_from(value.usage_state).ok()?.into(),
+        allocation_properties: properties_from_wire(&value.allocation_properties).ok()?,
+        allocations,
+        identifier: value.identifier,
+        label: value.label,
+    })
+}
+
+/**
+ * Decodes a state change event from the wire, the unknown enumeration
+ * values being invalid.
+ */
+pub fn state_change_event_from_wire(
+    event: domain_manager::StateChangeEvent,
+) -> Result<StateChangeEvent, prost::DecodeError> {
+    Ok(StateChangeEvent {
+        state_change_category: domain_manager::StateChangeCategoryType::try_from(
+            event.state_change_category,
+        )?
+        .into(),
+        state_change_from: domain_manager::StateChangeType::try_from(event.state_change_from)?
+            .into(),
+        state_change_to: domain_manager::StateChangeType::try_from(event.state_change_to)?.into(),
+        producer_id: event.producer_id,
+        source_id: event.source_id,
+    })
+}
+
+impl From<&PropertyChangeEvent> for domain_manager::PropertyChangeEvent {
+    fn from(value: &PropertyChangeEvent) -> Self {
+        let time = value.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        domain_manager::PropertyChangeEvent {
+            producer_id: value.producer_id.clone(),
+            source_id: value.source_id.clone(),
+            properties: properties_to_wire(&value.properties),
+            time: time.as_micros() as u64,
+        }
+    }
+}
+
+/// Decodes a property change event from the wire, its property values from JSON.
+pub fn property_change_event_from_wire(
+    event: domain_manager::PropertyChangeEvent,
+) -> Result<PropertyChangeEvent, serde_json::Error> {
+    Ok(PropertyChangeEvent {
+        properties: properties_from_wire(&event.properties)?,
+        time: UNIX_EPOCH + Duration::from_micros(event.time),
+        producer_id: event.producer_id,
+        source_id: event.source_id,
+    })
+}
+
+impl From<&AbnormalComponentTerminationEvent>
+    for domain_manager::AbnormalComponentTerminationEvent
+{
+    fn from(value: &AbnormalComponentTerminationEvent) -> Self {
+        domain_manager::AbnormalComponentTerminationEvent {
+            producer_id: value.producer_id.clone(),
+            device_id: value.device_id.clone(),
+            component_id: value.component_id.clone(),
+            application_id: value.application_id.clone(),
+        }
+    }
+}
+
+impl From<domain_manager::AbnormalComponentTerminationEvent> for AbnormalComponentTerminationEvent {
+    fn from(value: domain_manager::AbnormalComponentTerminationEvent) -> Self {
+        AbnormalComponentTerminationEvent {
+            producer_id: value.producer_id,
+            device_id: value.device_id,
+            component_id: value.component_id,
+            application_id: value.application_id,
+        }
+    }
+}
+
+impl From<&LogRecord> for domain_manager::LogRecord {
+    fn from(value: &LogRecord) -> Self {
+        let time = value.time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        domain_manager::LogRecord {
+            producer_id: value.producer_id.clone(),
+            producer_name: value.producer_name.clone(),
+            level: value.level.value().into(),
+            time: time.as_micros() as u64,
+            message: value.message.clone(),
+        }
+    }
+}
+
+/// Decodes a log record from the wire, returning None when its level is unknown.
+pub fn log_record_from_wire(record: domain_manager::LogRecord) -> Option<LogRecord> {
+    Some(LogRecord {
+        producer_id: record.producer_id,
+        producer_name: record.producer_name,
+        level: LogLevelType::from_value(record.level.try_into().ok()?)?,
+        time: UNIX_EPOCH + Duration::from_micros(record.time),
+        message: record.message,
+    })
+}
+
+impl From<EncodedEvent> for event_channel::ChannelEvent {
+    fn from(value: EncodedEvent) -> Self {
+        event_channel::ChannelEvent {
+            sequence: value.sequence,
+            source_id: value.source_id,
+            event_type: value.event_type,
+            payload: value.payload,
+        }
+    }
+}
+
+/// Decodes the filter of an event subscription.
+pub fn event_filter_from_wire(request: &domain_manager::SubscribeRequest) -> EventFilter {
+    EventFilter {
+        source_ids: request.source_ids.clone(),
+        event_types: request.event_types.clone(),
+    }
+}
+
+/// Decodes the filter of a remote channel subscription.
+pub fn channel_filter_from_wire(request: &event_channel::SubscribeChannelRequest) -> EventFilter {
+    EventFilter {
+        source_ids: request.source_ids.clone(),
+        event_types: request.event_types.clone(),
+    }
+}
+
+/// Decodes the filter of a log subscription, returning None when its level is unknown.
+pub fn log_filter_from_wire(
+    request: domain_manager::SubscribeLogRecordsRequest,
+) -> Option<LogFilter> {
+    Some(LogFilter {
+        log_level: log_level_from_wire(request.log_level)?,
+        producers: request.producers,
+    })
+}
+
+/// Decodes a log retrieval query, returning None when its level is unknown.
+pub fn log_query_from_wire(request: log_service::RetrieveRecordsRequest) -> Option<LogQuery> {
+    let time = |micros: Option<u64>| micros.map(|m| UNIX_EPOCH + Duration::from_micros(m));
+    Some(LogQuery {
+        from: time(request.from_time),
+        to: time(request.to_time),
+        filter: LogFilter {
+            log_level: log_level_from_wire(request.log_level)?,
+            producers: request.producers,
+        },
+    })
+}
+
+/// Decodes the level of a log filter, 0 standing for no level.
+fn log_level_from_wire(level: u32) -> Option<Option<LogLevelType>> {
+    match level {
+        0 => Some(None),
+        level => LogLevelType::from_value(level.try_into().ok()?).map(Some),
+    }
+}
+
+impl From<&DomainManagementEvent> for domain_manager::DomainManagementEvent {
+    fn from(value: &DomainManagementEvent) -> Self {
+        use domain_manager::domain_management_event::Event;
+
+        let object = |producer_id: &String,
+                      source_id: &String,
+                      source_name: &String,
+                      source_category: &SourceCategoryType| {
+            let mut object = domain_manager::DomainObject {
+                producer_id: producer_id.clone(),
+                source_id: source_id.clone(),
+                source_name: source_name.clone(),
+                ..Default::default()
+            };
+            object.set_source_category((*source_category).into());
+            object
+        };
+        let event = match value {
+            DomainManagementEvent::ObjectAdded {
+                producer_id,
+                source_id,
+                source_name,
+                source_category,
+            } => Event::ObjectAdded(object(producer_id, source_id, source_name, source_category)),
+            DomainManagementEvent::ObjectRemoved {
+                producer_id,
+                source_id,
+                source_name,
+                source_category,
+            } => Event::ObjectRemoved(object(producer_id, source_id, source_name, source_category)),
+            DomainManagementEvent::AllocationFailed {
+                producer_id,
+                allocation_id,
+                source_id,
+                allocated_device,
+            } => Event::AllocationFailed(domain_manager::AllocationFailed {
+                producer_id: producer_id.clone(),
+                allocation_id: allocation_id.clone(),
+                source_id: source_id.clone(),
+                allocated_device: allocated_device.clone(),
+            }),
+            DomainManagementEvent::AdministrativeStateChanged {
+                producer_id,
+                source_id,
+                state_change_from,
+                state_change_to,
+            } => {
+                let mut changed = domain_manager::AdministrativeStateChanged {
+                    producer_id: producer_id.clone(),
+                    source_id: source_id.clone(),
+                    ..Default::default()
+                };
+                changed.set_state_change_from((*state_change_from).into());
+                changed.set_state_change_to((*state_change_to).into());
+                Event::AdministrativeStateChanged(changed)
+            }
+            DomainManagementEvent::AvailabilityChanged {
+                producer_id,
+                source_id,
+                source_name,
+                source_category,
+                available,
+            } => Event::AvailabilityChanged(domain_manager::AvailabilityChanged {
+                object: Some(object(producer_id, source_id, source_name, source_category)),
+                available: *available,
+            }),
+        };
+        domain_manager::DomainManagementEvent {
+            event: Some(event),
+            sequence: 0,
+        }
+    }
+}
+
+impl From<DomainManagerError> for Status {
+    fn from(value: DomainManagerError) -> Self {
+        match value {
+            DomainManagerError::InvalidObjectReference { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            DomainManagerError::DeviceMgrNotRegistered { .. } => {
+                Status::failed_precondition(value.to_string())
+            }
+            DomainManagerError::RegisterError { .. } => Status::internal(value.to_string()),
+            DomainManagerError::InvalidFileName { .. }
+            | DomainManagerError::InvalidProfile { .. }
+            | DomainManagerError::ApplicationInstallationError { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            DomainManagerError::ApplicationAlreadyInstalled { .. } => {
+                Status::already_exists(value.to_string())
+            }
+            DomainManagerError::InvalidIdentifier { .. } => Status::not_found(value.to_string()),
+            DomainManagerError::CreateApplicationError { .. }
+            | DomainManagerError::ApplicationControlError { .. } => {
+                Status::failed_precondition(value.to_string())
+            }
+            DomainManagerError::ApplicationPropertiesError { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            DomainManagerError::AccessDenied { .. } => Status::permission_denied(value.to_string()),
+            DomainManagerError::RemoteDomainError { .. } => Status::unavailable(value.to_string()),
+            DomainManagerError::ReleaseError { .. }
+            | DomainManagerError::PersistenceError { .. } => Status::internal(value.to_string()),
+        }
+    }
+}
+
+impl From<FileError> for Status {
+    fn from(value: FileError) -> Self {
+        match value {
+            FileError::FileException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                ..
+            }
+            | FileError::IOException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                ..
+            } => Status::not_found(value.to_string()),
+            FileError::IOException {
+                error_number: ErrorNumberType::CF_ETIMEDOUT,
+                ..
+            } => Status::deadline_exceeded(value.to_string()),
+            FileError::InvalidFilePointer => Status::out_of_range(value.to_string()),
+            FileError::FileException { .. } => Status::failed_precondition(value.to_string()),
+            FileError::IOException { .. } => Status::internal(value.to_string()),
+        }
+    }
+}
+
+impl From<FileSystemError> for Status {
+    fn from(value: FileSystemError) -> Self {
+        match value {
+            FileSystemError::InvalidFileName { .. } => Status::invalid_argument(value.to_string()),
+            FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                ..
+            } => Status::not_found(value.to_string()),
+            FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EPERM | ErrorNumberType::CF_EACCES,
+                ..
+            } => Status::permission_denied(value.to_string()),
+            FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EEXIST,
+                ..
+            } => Status::already_exists(value.to_string()),
+            // e.g. an offset past the end of a file
+            FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EINVAL,
+                ..
+            } => Status::invalid_argument(value.to_string()),
+            // e.g. an open on a hung network mount
+            FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ETIMEDOUT,
+                ..
+            } => Status::deadline_exceeded(value.to_string()),
+            FileSystemError::FileException { .. } => Status::failed_precondition(value.to_string()),
+        }
+    }
+}
+
+/**
+ * Decodes the error of a call of a remote FileSystem from its status,
+ * the error numbers the status does not tell being CF_EIO.
+ */
+pub fn file_system_error_from_status(status: &Status) -> FileSystemError {
+    let message = status.message().to_string();
+    let error_number = match status.code() {
+        tonic::Code::InvalidArgument => {
+            return FileSystemError::InvalidFileName {
+                error_number: ErrorNumberType::CF_EINVAL,
+                message,
+            }
+        }
+        tonic::Code::NotFound => ErrorNumberType::CF_ENOENT,
+        tonic::Code::PermissionDenied => ErrorNumberType::CF_EPERM,
+        tonic::Code::AlreadyExists => ErrorNumberType::CF_EEXIST,
+        tonic::Code::DeadlineExceeded => ErrorNumberType::CF_ETIMEDOUT,
+        _ => ErrorNumberType::CF_EIO,
+    };
+    FileSystemError::FileException {
+        error_number,
+        message,
+    }
+}
+
+impl From<ArchiveError> for Status {
+    fn from(value: ArchiveError) -> Self {
+        match value {
+            ArchiveError::CorruptedChunk { .. } => Status::data_loss(value.to_string()),
+            ArchiveError::MalformedArchive { .. } => Status::invalid_argument(value.to_string()),
+        }
+    }
+}
+
+impl From<DeltaError> for Status {
+    fn from(value: DeltaError) -> Self {
+        match value {
+            DeltaError::CorruptedChunk { .. } => Status::data_loss(value.to_string()),
+            DeltaError::MalformedDelta { .. } => Status::invalid_argument(value.to_string()),
+        }
+    }
+}
+
+impl From<BlockingError> for Status {
+    fn from(value: BlockingError) -> Self {
+        match value {
+            BlockingError::PoolClosed { .. } => Status::unavailable(value.to_string()),
+            BlockingError::OperationPanicked { .. } => Status::internal(value.to_string()),
+            BlockingError::TimedOut { .. } => Status::deadline_exceeded(value.to_string()),
+            BlockingError::AlreadyInUse { .. } => Status::failed_precondition(value.to_string()),
+        }
+    }
+}
+
+/// The failed transfers answer the status of the call that failed.
+impl From<TransferError> for Status {
+    fn from(value: TransferError) -> Self {
+        match value {
+            TransferError::InvalidTuning { .. } => Status::invalid_argument(value.to_string()),
+            TransferError::TransferFailed { code, message, .. } => Status::new(code, message),
+        }
+    }
+}
+
+impl From<BufferPoolMetrics> for file_system::BufferPoolMetrics {
+    fn from(value: BufferPoolMetrics) -> Self {
+        file_system::BufferPoolMetrics {
+            acquired: value.acquired,
+            reused: value.reused,
+            discarded: value.discarded,
+            in_use: value.in_use as u64,
+            max_in_use: value.max_in_use as u64,
+            pooled: value.pooled as u64,
+            pooled_bytes: value.pooled_bytes as u64,
+        }
+    }
+}
+
+impl From<BlockingPoolMetrics> for file_system::BlockingPoolMetrics {
+    fn from(value: BlockingPoolMetrics) -> Self {
+        file_system::BlockingPoolMetrics {
+            threads: value.threads as u64,
+            queue_depth: value.queue_depth as u64,
+            max_queue_depth: value.max_queue_depth as u64,
+            active: value.active as u64,
+            completed: value.completed,
+        }
+    }
+}
+
+impl From<FileType> for file_system::FileType {
+    fn from(value: FileType) -> Self {
+        match value {
+            FileType::PLAIN => file_system::FileType::Plain,
+            FileType::DIRECTORY => file_system::FileType::Directory,
+            FileType::FILE_SYSTEM => file_system::FileType::FileSystem,
+        }
+    }
+}
+
+impl From<file_system::FileType> for FileType {
+    fn from(value: file_system::FileType) -> Self {
+        match value {
+            file_system::FileType::Plain => FileType::PLAIN,
+            file_system::FileType::Directory => FileType::DIRECTORY,
+            file_system::FileType::FileSystem => FileType::FILE_SYSTEM,
+        }
+    }
+}
+
+impl From<&FileInformationType> for file_system::FileInformation {
+    fn from(value: &FileInformationType) -> Self {
+        file_system::FileInformation {
+            name: value.name.clone(),
+            kind: file_system::FileType::from(value.kind).into(),
+            size: value.size,
+        }
+    }
+}
+
+impl From<&FileSystemSpace> for file_system::FileSystemSpace {
+    fn from(value: &FileSystemSpace) -> Self {
+        file_system::FileSystemSpace {
+            mount_point: value.mount_point.clone(),
+            size: value.size,
+            available_space: value.available_space,
+        }
+    }
+}
+
+impl From<ConnectionManagerError> for Status {
+    fn from(value: ConnectionManagerError) -> Self {
+        match value {
+            ConnectionManagerError::InvalidEndpoint { .. } => {
+                Status::invalid_argument(value.to_string())
+            }
+            ConnectionManagerError::InvalidConnection { .. } => {
+                Status::failed_precondition(value.to_string())
+            }
+            ConnectionManagerError::InvalidConnectionId { .. } => {
+                Status::not_found(value.to_string())
+            }
+        }
+    }
+}
+
+/// Encodes properties for the wire, their values as JSON.
+pub fn properties_to_wire(properties: &Properties) -> Vec<device::Property> {
+    properties
+        .iter()
+        .map(|p| device::Property {
+            id: p.id.clone(),
+            value: value_to_wire(&p.value),
+        })
+        .collect()
+}
+
+/// Decodes properties received from the wire.
+pub fn properties_from_wire(
+    properties: &[device::Property],
+) -> Result<Properties, serde_json::Error> {
+    properties
+        .iter()
+        .map(|p| value_from_wire(&p.value).map(|value| DataType::new(&p.id, value)))
+        .collect()
+}
+
+/**
+ * Encodes a CF message in its canonical JSON, the one of the REST
+ * gateway and of the JSON output of the CLIs: the proto3 JSON mapping,
+ * the fields under their proto names and all of them emitted, the enums
+ * by name and the 64-bit integers as strings.
+ */
+pub fn message_to_json<M: prost::Message + Serialize>(
+    message: &M,
+) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::to_value(message)
+}
+
+/**
+ * Decodes a CF message from its canonical JSON, the fields being
+ * accepted under their lowerCamelCase JSON names too and defaulted when
+ * missing.
+ */
+pub fn message_from_json<M: prost::Message + DeserializeOwned>(
+    json: serde_json::Value,
+) -> Result<M, serde_json::Error> {
+    serde_json::from_value(json)
+}
+
+/**
+ * The JSON mapping of a property embeds its value, a JSON document on
+ * the wire, e.g. {"id": "frequency", "value": {"Double": 101100000.0}},
+ * an empty value being mapped to null and a malformed one to a string.
+ */
+impl Serialize for device::Property {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match self.value.as_str() {
+            "" => serde_json::Value::Null,
+            text => serde_json::from_str(text)
+                .unwrap_or_else(|_| serde_json::Value::String(text.to_string())),
+        };
+        let mut property = serializer.serialize_struct("device.Property", 2)?;
+        property.serialize_field("id", &self.id)?;
+        property.serialize_field("value", &value)?;
+        property.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for device::Property {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Property {
+            #[serde(default)]
+            id: String,
+            #[serde(default)]
+            value: serde_json::Value,
+        }
+        let property = Property::deserialize(deserializer)?;
+        Ok(device::Property {
+            id: property.id,
+            value: match property.value {
+                serde_json::Value::Null => String::new(),
+                value => value.to_string(),
+            },
+        })
+    }
+}
+
+/**
+ * Returns the runtime the calls of the synchronous clients of the remote
+ * objects are made on, apart from the runtime of their callers.
+ */
+fn client_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("scars-rpc-client")
+            .enable_all()
+            .build()
+            .expect("runtime of the rpc clients")
+    })
+}
+
+/**
+ * Returns a channel to an endpoint, connected on its first call, for
+ * the synchronous clients of the remote objects, e.g. the mounted node
+ * FileSystems and the registered devices.
+ */
+pub fn client_channel(endpoint: &str) -> Result<Channel, tonic::transport::Error> {
+    let _runtime = client_runtime().enter();
+    Ok(Endpoint::from_shared(endpoint.to_string())?.connect_lazy())
+}
+
+/**
+ * Makes a call of a synchronous client of a remote object, blocking the
+ * calling thread until it completes. The call is run by the runtime of
+ * the clients, so that the operations of the services, whatever the
+ * thread they are run on, may call the remote objects.
+ */
+pub fn block_on<F>(call: F) -> F::Output
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (sender, receiver) = std::sync::mpsc::channel();
+    client_runtime().spawn(async move {
+        let _ = sender.send(call.await);
+    });
+    receiver.recv().expect("call of an rpc client")
+}
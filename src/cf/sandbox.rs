@@ -0,0 +1,491 @@
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use super::common_types::Properties;
+use super::file_system::FileSystemTrait;
+use super::log::{LogConsumerRef, LogFilter, LogLevelType, LogRecord, Logger};
+use super::log_service::{LogQuery, LogService, DEFAULT_LOG_CAPACITY};
+use super::profile::prf::{PropertiesDescriptor, PropertyKind};
+use super::profile::sad::PortKind;
+use super::profile::scd::{Port, SoftwareComponent};
+use super::profile::spd::SoftPkg;
+use super::profile::{self, resolve_file_name, ProfileError};
+use super::resource::{Resource, ResourceError, ResourceRef, ResourceTrait};
+
+/**
+ * Convienence enum definition that includes all Sandbox errors.
+ */
+#[derive(Error, Debug)]
+pub enum SandboxError {
+    /**
+     * This exception indicates that a descriptor of the component is
+     * missing or invalid.
+     */
+    #[error("ProfileError: {source}")]
+    ProfileError { source: ProfileError },
+    /**
+     * This exception indicates that a component of the sandbox already
+     * has the name.
+     */
+    #[error("DuplicateName: name: '{name}'.")]
+    DuplicateName { name: String },
+    /**
+     * This exception indicates that no component of the sandbox has the
+     * name.
+     */
+    #[error("UnknownComponent: name: '{name}'.")]
+    UnknownComponent { name: String },
+    /**
+     * This exception indicates that no connection of the sandbox has the
+     * identifier.
+     */
+    #[error("UnknownConnection: connection: '{connection_id}'.")]
+    UnknownConnection { connection_id: String },
+    /**
+     * This exception indicates that an operation of a component failed.
+     */
+    #[error("ResourceError: {source}")]
+    ResourceError { source: ResourceError },
+}
+
+/*
+ * Convienence type definition that includes all Sandbox returned errors.
+ */
+pub type Result<T, E = SandboxError> = anyhow::Result<T, E>;
+
+impl From<ProfileError> for SandboxError {
+    fn from(source: ProfileError) -> Self {
+        SandboxError::ProfileError { source }
+    }
+}
+
+impl From<ResourceError> for SandboxError {
+    fn from(source: ResourceError) -> Self {
+        SandboxError::ResourceError { source }
+    }
+}
+
+/**
+ * This type describes a component hosted by a sandbox, with the ports
+ * of its SCD.
+ */
+#[derive(Clone)]
+pub struct SandboxComponent {
+    pub name: String,
+    pub spd_file_name: String,
+    pub resource: ResourceRef,
+    pub ports: Vec<Port>,
+    /// The logger the sandbox reports the operations on the component with.
+    logger: Logger,
+}
+
+impl std::fmt::Debug for SandboxComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("SandboxComponent")
+            .field("name", &self.name)
+            .field("spd_file_name", &self.spd_file_name)
+            .field("ports", &self.ports)
+            .finish()
+    }
+}
+
+/**
+ * This type describes a connection made by a sandbox from a uses port
+ * to the endpoint of a provides port.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxConnection {
+    pub connection_id: String,
+    pub uses_component: String,
+    pub uses_port: String,
+    pub provides_component: String,
+    pub provides_port: String,
+    pub endpoint: String,
+}
+
+/**
+ * Sandbox hosting components in-process, without domain nor devices,
+ * for their developers to exercise them: the components are launched
+ * from their SPD, connected port to port, configured, started and
+ * stopped, the records of their loggers and the operations of the
+ * sandbox being kept in a log service.
+ *
+ * A component is launched as a stand-in Resource holding the configure
+ * and property properties of its PRF and the ports of its SCD, unless
+ * its own implementation is given.
+ */
+pub struct Sandbox {
+    components: Vec<SandboxComponent>,
+    connections: Vec<SandboxConnection>,
+    log: LogService,
+}
+
+impl std::fmt::Debug for Sandbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Sandbox")
+            .field("components", &self.components)
+            .field("connections", &self.connections)
+            .finish()
+    }
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Sandbox::new()
+    }
+}
+
+impl Sandbox {
+    pub fn new() -> Sandbox {
+        Sandbox {
+            components: Vec::new(),
+            connections: Vec::new(),
+            log: LogService::new(DEFAULT_LOG_CAPACITY),
+        }
+    }
+
+    /**
+     * Launches a component from its SPD as a stand-in Resource, named
+     * after the SPD name and an index unless a name is given.
+     */
+    pub fn launch(
+        &mut self,
+        file_system: &dyn FileSystemTrait,
+        spd_file_name: &str,
+        name: Option<&str>,
+    ) -> Result<&SandboxComponent> {
+        let descriptors = Descriptors::load(file_system, spd_file_name)?;
+        let name = self.component_name(&descriptors.softpkg, name)?;
+        let mut resource = Resource::new(&name);
+        for property in descriptors.properties.iter().flat_map(|p| {
+            p.properties_of_kind(PropertyKind::CONFIGURE)
+                .chain(p.properties_of_kind(PropertyKind::PROPERTY))
+        }) {
+            if let Some(value) = property.value() {
+                resource = resource.with_property(property.id(), value);
+            }
+        }
+        for port in &descriptors.ports {
+            resource = match port.kind {
+                PortKind::USES => resource.with_uses_port(&port.name),
+                PortKind::PROVIDES => resource
+                    .with_provides_port(&port.name, &format!("sandbox://{name}/{}", port.name)),
+            };
+        }
+        resource.logger().add_consumer(self.consumer());
+        resource.initialize()?;
+        self.host(
+            name,
+            spd_file_name,
+            Arc::new(Mutex::new(resource)),
+            descriptors,
+        )
+    }
+
+    /**
+     * Launches a component of its own implementation, initialized then
+     * configured with the default values of the configure properties of
+     * its PRF.
+     */
+    pub fn launch_resource(
+        &mut self,
+        file_system: &dyn FileSystemTrait,
+        spd_file_name: &str,
+        name: Option<&str>,
+        resource: ResourceRef,
+    ) -> Result<&SandboxComponent> {
+        let descriptors = Descriptors::load(file_system, spd_file_name)?;
+        let name = self.component_name(&descriptors.softpkg, name)?;
+        resource.lock().unwrap().initialize()?;
+        if let Some(properties) = &descriptors.properties {
+            let values = properties.values(PropertyKind::CONFIGURE);
+            if !values.is_empty() {
+                resource.lock().unwrap().configure(&values)?;
+            }
+        }
+        self.host(name, spd_file_name, resource, descriptors)
+    }
+
+    fn host(
+        &mut self,
+        name: String,
+        spd_file_name: &str,
+        resource: ResourceRef,
+        descriptors: Descriptors,
+    ) -> Result<&SandboxComponent> {
+        let logger = Logger::new(&name, &name).with_consumer(self.consumer());
+        logger.log(
+            LogLevelType::ADMINISTRATIVE_EVENT,
+            &format!("launched from '{spd_file_name}'"),
+        );
+        self.components.push(SandboxComponent {
+            name,
+            spd_file_name: spd_file_name.to_string(),
+            resource,
+            ports: descriptors.ports,
+            logger,
+        });
+        Ok(self.components.last().unwrap())
+    }
+
+    /// Returns the name given, when free, or the first free indexed name of the SPD.
+    fn component_name(&self, softpkg: &SoftPkg, name: Option<&str>) -> Result<String> {
+        match name {
+            Some(name) if self.components.iter().any(|c| c.name == name) => {
+                Err(SandboxError::DuplicateName {
+                    name: name.to_string(),
+                })
+            }
+            Some(name) => Ok(name.to_string()),
+            None => Ok((1..)
+                .map(|index| format!("{}_{index}", softpkg.name))
+                .find(|n| self.components.iter().all(|c| c.name != *n))
+                .unwrap()),
+        }
+    }
+
+    fn consumer(&self) -> LogConsumerRef {
+        Arc::new(Mutex::new(self.log.clone()))
+    }
+
+    /// Returns the hosted components, in launch order.
+    pub fn components(&self) -> &[SandboxComponent] {
+        &self.components
+    }
+
+    /// Returns a hosted component by name.
+    pub fn component(&self, name: &str) -> Result<&SandboxComponent> {
+        self.components
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| SandboxError::UnknownComponent {
+                name: name.to_string(),
+            })
+    }
+
+    /// Returns the connections made, in connection order.
+    pub fn connections(&self) -> &[SandboxConnection] {
+        &self.connections
+    }
+
+    /**
+     * Connects a uses port of a component to a provides port of another
+     * one, returning the connection id.
+     */
+    pub fn connect(
+        &mut self,
+        uses_component: &str,
+        uses_port: &str,
+        provides_component: &str,
+        provides_port: &str,
+    ) -> Result<String> {
+        let provides = self.component(provides_component)?;
+        let endpoint = provides
+            .resource
+            .lock()
+            .unwrap()
+            .get_provides_port(provides_port)?;
+        let uses = self.component(uses_component)?;
+        let connection_id =
+            format!("{uses_component}.{uses_port}:{provides_component}.{provides_port}");
+        uses.resource
+            .lock()
+            .unwrap()
+            .connect_uses_port(uses_port, &connection_id, &endpoint)?;
+        uses.logger.log(
+            LogLevelType::ADMINISTRATIVE_EVENT,
+            &format!("connected '{uses_port}' to '{endpoint}'"),
+        );
+        self.connections.push(SandboxConnection {
+            connection_id: connection_id.clone(),
+            uses_component: uses_component.to_string(),
+            uses_port: uses_port.to_string(),
+            provides_component: provides_component.to_string(),
+            provides_port: provides_port.to_string(),
+            endpoint,
+        });
+        Ok(connection_id)
+    }
+
+    /// Breaks a connection made by connect.
+    pub fn disconnect(&mut self, connection_id: &str) -> Result<()> {
+        let index = self
+            .connections
+            .iter()
+            .position(|c| c.connection_id == connection_id)
+            .ok_or_else(|| SandboxError::UnknownConnection {
+                connection_id: connection_id.to_string(),
+            })?;
+        let connection = &self.connections[index];
+        let uses = self.component(&connection.uses_component)?;
+        uses.resource
+            .lock()
+            .unwrap()
+            .disconnect_port(&connection.uses_port, connection_id)?;
+        uses.logger.log(
+            LogLevelType::ADMINISTRATIVE_EVENT,
+            &format!("disconnected '{}'", connection.uses_port),
+        );
+        self.connections.remove(index);
+        Ok(())
+    }
+
+    /// Sets properties of a component.
+    pub fn configure(&self, name: &str, properties: &Properties) -> Result<()> {
+        let component = self.component(name)?;
+        component.resource.lock().unwrap().configure(properties)?;
+        for property in properties {
+            component.logger.log(
+                LogLevelType::ADMINISTRATIVE_EVENT,
+                &format!("configured {}={}", property.id, property.value),
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the values of properties of a component, all of them when none is given.
+    pub fn query(&self, name: &str, properties: &Properties) -> Result<Properties> {
+        let component = self.component(name)?;
+        let values = component.resource.lock().unwrap().query(properties)?;
+        Ok(values)
+    }
+
+    /// Starts the processing of a component.
+    pub fn start(&self, name: &str) -> Result<()> {
+        let component = self.component(name)?;
+        component.resource.lock().unwrap().start()?;
+        component
+            .logger
+            .log(LogLevelType::ADMINISTRATIVE_EVENT, "started");
+        Ok(())
+    }
+
+    /// Stops the processing of a component.
+    pub fn stop(&self, name: &str) -> Result<()> {
+        let component = self.component(name)?;
+        component.resource.lock().unwrap().stop()?;
+        component
+            .logger
+            .log(LogLevelType::ADMINISTRATIVE_EVENT, "stopped");
+        Ok(())
+    }
+
+    /**
+     * Releases a component, its connections being broken first and the
+     * connections to its provides ports being forgotten.
+     */
+    pub fn release(&mut self, name: &str) -> Result<()> {
+        self.component(name)?;
+        let connections: Vec<String> = self
+            .connections
+            .iter()
+            .filter(|c| c.uses_component == name)
+            .map(|c| c.connection_id.clone())
+            .collect();
+        for connection_id in connections {
+            self.disconnect(&connection_id)?;
+        }
+        self.connections.retain(|c| c.provides_component != name);
+
+        let index = self.components.iter().position(|c| c.name == name).unwrap();
+        let component = self.components.remove(index);
+        let mut resource = component.resource.lock().unwrap();
+        if resource.started() {
+            resource.stop()?;
+        }
+        resource.release_object()?;
+        component
+            .logger
+            .log(LogLevelType::ADMINISTRATIVE_EVENT, "released");
+        Ok(())
+    }
+
+    /// Releases all the components, in the reverse launch order.
+    pub fn release_all(&mut self) -> Result<()> {
+        while let Some(component) = self.components.last() {
+            let name = component.name.clone();
+            self.release(&name)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * Returns the log records of the components named, of all of them
+     * when none is, produced after a time when given.
+     */
+    pub fn logs(&self, names: &[&str], after: Option<std::time::SystemTime>) -> Vec<LogRecord> {
+        let query = LogQuery {
+            filter: LogFilter {
+                log_level: None,
+                producers: names.iter().map(|n| n.to_string()).collect(),
+            },
+            ..LogQuery::default()
+        };
+        self.log
+            .records(&query)
+            .into_iter()
+            .filter(|r| after.is_none_or(|after| r.time > after))
+            .collect()
+    }
+}
+
+/// The descriptors of a component launched in a sandbox.
+struct Descriptors {
+    softpkg: SoftPkg,
+    properties: Option<PropertiesDescriptor>,
+    ports: Vec<Port>,
+}
+
+impl Descriptors {
+    /**
+     * Loads the SPD of a component along with its PRF, those of its first
+     * implementation adding to or replacing those of the SPD, and the
+     * ports of its SCD.
+     */
+    fn load(
+        file_system: &dyn FileSystemTrait,
+        spd_file_name: &str,
+    ) -> profile::Result<Descriptors> {
+        let softpkg = SoftPkg::parse(
+            &profile::read_file(file_system, spd_file_name)?,
+            spd_file_name,
+        )?;
+        let mut properties: Option<PropertiesDescriptor> = None;
+        let property_files = softpkg.property_file.iter().chain(
+            softpkg
+                .implementations
+                .first()
+                .and_then(|i| i.property_file.as_ref()),
+        );
+        for property_file in property_files {
+            let file_name = resolve_file_name(spd_file_name, property_file);
+            let loaded = PropertiesDescriptor::parse(
+                &profile::read_file(file_system, &file_name)?,
+                &file_name,
+            )?;
+            match &mut properties {
+                Some(properties) => {
+                    for property in loaded.properties {
+                        properties.properties.retain(|p| p.id() != property.id());
+                        properties.properties.push(property);
+                    }
+                }
+                None => properties = Some(loaded),
+            }
+        }
+        let ports = match &softpkg.descriptor {
+            Some(scd) => {
+                let file_name = resolve_file_name(spd_file_name, scd);
+                SoftwareComponent::parse(&profile::read_file(file_system, &file_name)?, &file_name)?
+                    .ports
+            }
+            None => Vec::new(),
+        };
+        Ok(Descriptors {
+            softpkg,
+            properties,
+            ports,
+        })
+    }
+}
@@ -0,0 +1,772 @@
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use super::common_types::{ActionType, AnyValue};
+use super::profile::codegen;
+use super::profile::dcd::DeviceConfiguration;
+use super::profile::prf::{
+    AccessMode, PropertiesDescriptor, Property, PropertyKind, PropertyType, Range, Simple,
+};
+use super::profile::sad::PortKind;
+use super::profile::scd::{Port, SoftwareComponent};
+use super::profile::spd::{Code, Implementation, SoftPkg};
+use super::profile::{ComponentFile, ComponentInstantiation, ComponentPlacement};
+
+/**
+ * Convienence enum definition that includes all scaffolding errors.
+ */
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    /**
+     * This exception indicates that the name is not a crate name
+     * starting with a letter.
+     */
+    #[error("InvalidName: name: '{name}'.")]
+    InvalidName { name: String },
+    /**
+     * This exception indicates that the directory of the crate already
+     * exists.
+     */
+    #[error("AlreadyExists: directory: '{directory}'.")]
+    AlreadyExists { directory: String },
+    /**
+     * This exception indicates that a file of the crate cannot be
+     * written.
+     */
+    #[error("FileError: file: '{file_name}', msg: '{message}'.")]
+    FileError { file_name: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all scaffolding returned errors.
+ */
+pub type Result<T, E = ScaffoldError> = anyhow::Result<T, E>;
+
+/// The scars dependency of the generated crates, unless a path is given.
+pub const SCARS_GIT: &str = "https://github.com/mad4j/scars";
+
+/**
+ * The kinds of crates generated: a component, hosted in-process, or a
+ * node, its DCD launching a device of its own.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaffoldKind {
+    COMPONENT,
+    NODE,
+}
+
+impl ScaffoldKind {
+    pub fn from_name(name: &str) -> Option<ScaffoldKind> {
+        match name {
+            "component" => Some(ScaffoldKind::COMPONENT),
+            "node" => Some(ScaffoldKind::NODE),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * This type describes a file of a generated crate, its path relative to
+ * the directory of the crate.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaffoldFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/**
+ * The descriptors of a component crate, e.g. imported from another
+ * project, in place of the generated ones. The SPD refers to the SCD
+ * and PRF by the names of the crate files.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDescriptors {
+    pub softpkg: SoftPkg,
+    pub scd: SoftwareComponent,
+    pub prf: PropertiesDescriptor,
+}
+
+/**
+ * Generator of the crate of a new component or node: the domain
+ * profile descriptors, the properties struct generated from the PRF as
+ * scars-codegen does, a skeleton implementing the ResourceTrait, or the
+ * DeviceTrait and its launcher, over it, and a test exercising it.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaffold {
+    kind: ScaffoldKind,
+    name: String,
+    scars_path: Option<PathBuf>,
+    descriptors: Option<ComponentDescriptors>,
+}
+
+impl Scaffold {
+    /// Returns the generator of a crate, named as given.
+    pub fn new(kind: ScaffoldKind, name: &str) -> Result<Scaffold> {
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+            && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid {
+            return Err(ScaffoldError::InvalidName {
+                name: name.to_string(),
+            });
+        }
+        Ok(Scaffold {
+            kind,
+            name: name.to_string(),
+            scars_path: None,
+            descriptors: None,
+        })
+    }
+
+    /// Depends on the scars crate at a path rather than on its repository.
+    pub fn with_scars_path(mut self, scars_path: &Path) -> Scaffold {
+        self.scars_path = Some(scars_path.to_path_buf());
+        self
+    }
+
+    /// Generates a component crate from its own descriptors, the skeleton and the test checking them.
+    pub fn with_descriptors(mut self, descriptors: ComponentDescriptors) -> Scaffold {
+        self.descriptors = Some(descriptors);
+        self
+    }
+
+    pub fn kind(&self) -> ScaffoldKind {
+        self.kind
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the crate as a Rust identifier.
+    fn module(&self) -> String {
+        self.name.replace('-', "_")
+    }
+
+    /// The name of the crate as a Rust type.
+    fn type_name(&self) -> String {
+        self.module()
+            .split('_')
+            .filter(|w| !w.is_empty())
+            .map(|w| w[..1].to_uppercase() + &w[1..])
+            .collect()
+    }
+
+    /// Returns the files of the crate.
+    pub fn files(&self) -> Vec<ScaffoldFile> {
+        match self.kind {
+            ScaffoldKind::COMPONENT => self.component_files(),
+            ScaffoldKind::NODE => self.node_files(),
+        }
+    }
+
+    /**
+     * Writes the crate in a directory of its name under a parent
+     * directory, which shall not exist yet. Returns the directory.
+     */
+    pub fn write(&self, parent: &Path) -> Result<PathBuf> {
+        let directory = parent.join(&self.name);
+        if directory.exists() {
+            return Err(ScaffoldError::AlreadyExists {
+                directory: directory.display().to_string(),
+            });
+        }
+        for file in self.files() {
+            let path = directory.join(&file.path);
+            let file_error = |e: std::io::Error| ScaffoldError::FileError {
+                file_name: path.display().to_string(),
+                message: e.to_string(),
+            };
+            std::fs::create_dir_all(path.parent().unwrap()).map_err(file_error)?;
+            std::fs::write(&path, &file.contents).map_err(file_error)?;
+        }
+        Ok(directory)
+    }
+
+    fn file(path: &str, contents: String) -> ScaffoldFile {
+        ScaffoldFile {
+            path: PathBuf::from(path),
+            contents,
+        }
+    }
+
+    fn manifest(&self, dependencies: &str, binary: bool) -> String {
+        let scars = match &self.scars_path {
+            Some(path) => format!("{{ path = {:?} }}", path.display().to_string()),
+            None => format!("{{ git = {SCARS_GIT:?} }}"),
+        };
+        let mut manifest = format!(
+            "[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n",
+            self.name
+        );
+        if binary {
+            manifest.push_str(&format!(
+                "[[bin]]\nname = \"{}\"\npath = \"src/main.rs\"\n\n",
+                self.name
+            ));
+        }
+        manifest.push_str(&format!("[dependencies]\nscars = {scars}\n{dependencies}"));
+        manifest
+    }
+
+    /// The PRF of a component: a frequency configured within a range.
+    fn component_properties(&self) -> PropertiesDescriptor {
+        PropertiesDescriptor {
+            description: Some(format!("The properties of the {} component.", self.name)),
+            properties: vec![Property::Simple(Simple {
+                id: "frequency".to_string(),
+                name: Some("frequency".to_string()),
+                description: Some("The frequency of the processing.".to_string()),
+                value_type: PropertyType::DOUBLE,
+                mode: AccessMode::READWRITE,
+                kinds: vec![PropertyKind::PROPERTY],
+                action: None,
+                units: Some("Hz".to_string()),
+                range: Some(Range {
+                    min: AnyValue::Double(0.0),
+                    max: AnyValue::Double(1.0e9),
+                }),
+                enumerations: Vec::new(),
+                value: Some(AnyValue::Double(1000.0)),
+            })],
+        }
+    }
+
+    /// The PRF of the device of a node: a capacity allocated externally.
+    fn device_properties(&self) -> PropertiesDescriptor {
+        PropertiesDescriptor {
+            description: Some(format!("The properties of the {} device.", self.name)),
+            properties: vec![Property::Simple(Simple {
+                id: "capacity".to_string(),
+                name: Some("capacity".to_string()),
+                description: Some("The capacity allocated to the components.".to_string()),
+                value_type: PropertyType::LONG,
+                mode: AccessMode::READONLY,
+                kinds: vec![PropertyKind::ALLOCATION],
+                action: Some(ActionType::EXTERNAL),
+                units: None,
+                range: None,
+                enumerations: Vec::new(),
+                value: Some(AnyValue::Long(100)),
+            })],
+        }
+    }
+
+    fn softpkg(&self, name: &str, code: Code) -> SoftPkg {
+        SoftPkg {
+            id: format!("DCE:{name}"),
+            name: name.to_string(),
+            property_file: Some(format!("{name}.prf.xml")),
+            descriptor: Some(format!("{name}.scd.xml")),
+            implementations: vec![Implementation {
+                id: "rust".to_string(),
+                code: Some(code),
+                property_file: None,
+                processors: Vec::new(),
+                os: Vec::new(),
+                dependencies: Vec::new(),
+            }],
+        }
+    }
+
+    /// The descriptors of a new component: a provides and a uses port, and a frequency.
+    fn component_descriptors(&self) -> ComponentDescriptors {
+        let code = Code {
+            code_type: "SharedLibrary".to_string(),
+            local_file: format!("lib{}.rlib", self.module()),
+            entry_point: None,
+        };
+        let port = |name: &str, kind: PortKind| Port {
+            name: name.to_string(),
+            repository_id: "IDL:BULKIO/dataFloat:1.0".to_string(),
+            kind,
+            port_types: vec!["data".to_string()],
+        };
+        ComponentDescriptors {
+            softpkg: self.softpkg(&self.name, code),
+            scd: SoftwareComponent {
+                corba_version: Some("2.2".to_string()),
+                repository_id: Some("IDL:CF/Resource:1.0".to_string()),
+                component_type: Some("resource".to_string()),
+                supported_interfaces: Vec::new(),
+                ports: vec![
+                    port("data_in", PortKind::PROVIDES),
+                    port("data_out", PortKind::USES),
+                ],
+                interfaces: Vec::new(),
+                property_file: None,
+            },
+            prf: self.component_properties(),
+        }
+    }
+
+    fn component_files(&self) -> Vec<ScaffoldFile> {
+        let (name, module, type_name) = (&self.name, self.module(), self.type_name());
+        let properties_type = format!("{type_name}Properties");
+        let (spd_file, scd_file, prf_file) = (
+            format!("{name}.spd.xml"),
+            format!("{name}.scd.xml"),
+            format!("{name}.prf.xml"),
+        );
+        let ComponentDescriptors {
+            mut softpkg,
+            scd,
+            prf,
+        } = self
+            .descriptors
+            .clone()
+            .unwrap_or_else(|| self.component_descriptors());
+        softpkg.descriptor = Some(scd_file.clone());
+        softpkg.property_file = Some(prf_file.clone());
+
+        let ports: String = scd
+            .ports
+            .iter()
+            .map(|p| match p.kind {
+                PortKind::PROVIDES => format!(
+                    "\n            .with_provides_port({:?}, &format!(\"local://{{identifier}}/{}\"))",
+                    p.name, p.name
+                ),
+                PortKind::USES => format!("\n            .with_uses_port({:?})", p.name),
+            })
+            .collect();
+        let (common_types, checks) = match self.descriptors {
+            Some(_) => ("Properties", ""),
+            None => ("AnyValue, DataType, Properties", FREQUENCY_CHECKS),
+        };
+        let replace = |template: &str| {
+            template
+                .replace("{checks}", checks)
+                .replace("{common_types}", common_types)
+                .replace("{ports}", &ports)
+                .replace("{name}", name)
+                .replace("{module}", &module)
+                .replace("{type_name}", &type_name)
+                .replace("{properties_type}", &properties_type)
+        };
+
+        vec![
+            Scaffold::file("Cargo.toml", self.manifest("", false)),
+            Scaffold::file(&spd_file, softpkg.to_xml()),
+            Scaffold::file(&scd_file, scd.to_xml()),
+            Scaffold::file(&prf_file, prf.to_xml()),
+            Scaffold::file(
+                "src/properties.rs",
+                codegen::properties_struct(&prf, &properties_type, &prf_file),
+            ),
+            Scaffold::file("src/lib.rs", replace(COMPONENT_LIB)),
+            Scaffold::file(&format!("tests/{module}_test.rs"), replace(COMPONENT_TEST)),
+        ]
+    }
+
+    fn node_files(&self) -> Vec<ScaffoldFile> {
+        let (name, module, type_name) = (&self.name, self.module(), self.type_name());
+        let device = format!("{name}_device");
+        let device_type = format!("{type_name}Device");
+        let properties_type = format!("{type_name}DeviceProperties");
+        let prf = self.device_properties();
+        let code = Code {
+            code_type: "Executable".to_string(),
+            local_file: name.to_string(),
+            entry_point: None,
+        };
+        let scd = SoftwareComponent {
+            corba_version: Some("2.2".to_string()),
+            repository_id: Some("IDL:CF/Device:1.0".to_string()),
+            component_type: Some("device".to_string()),
+            supported_interfaces: Vec::new(),
+            ports: Vec::new(),
+            interfaces: Vec::new(),
+            property_file: None,
+        };
+        let dcd = DeviceConfiguration {
+            id: format!("DCE:{name}"),
+            name: name.to_string(),
+            device_manager_softpkg: None,
+            component_files: vec![ComponentFile {
+                id: format!("{device}_file"),
+                file_type: "SPD".to_string(),
+                local_file: format!("{device}.spd.xml"),
+            }],
+            placements: vec![ComponentPlacement {
+                file_ref: format!("{device}_file"),
+                composite_part_of: None,
+                instantiations: vec![ComponentInstantiation {
+                    id: format!("DCE:{name}:{device}_1"),
+                    usage_name: Some(format!("{device}_1")),
+                    start_order: None,
+                    properties: Vec::new(),
+                }],
+            }],
+            domain_manager: None,
+        };
+        let replace = |template: &str| {
+            template
+                .replace("{name}", name)
+                .replace("{module}", &module)
+                .replace("{device}", &device)
+                .replace("{device_type}", &device_type)
+                .replace("{properties_type}", &properties_type)
+        };
+
+        vec![
+            Scaffold::file(
+                "Cargo.toml",
+                self.manifest(
+                    "tokio = { version = \"1.0\", features = [\"macros\", \"rt-multi-thread\", \"net\", \"signal\", \"sync\"] }\ntonic = \"0.11\"\n",
+                    true,
+                ),
+            ),
+            Scaffold::file("DeviceManager.dcd.xml", dcd.to_xml()),
+            Scaffold::file(&format!("{device}.spd.xml"), self.softpkg(&device, code).to_xml()),
+            Scaffold::file(&format!("{device}.scd.xml"), scd.to_xml()),
+            Scaffold::file(&format!("{device}.prf.xml"), prf.to_xml()),
+            Scaffold::file(
+                "src/properties.rs",
+                codegen::properties_struct(&prf, &properties_type, &format!("{device}.prf.xml")),
+            ),
+            Scaffold::file("src/lib.rs", replace(NODE_LIB)),
+            Scaffold::file("src/main.rs", replace(NODE_MAIN)),
+            Scaffold::file(&format!("tests/{module}_test.rs"), replace(NODE_TEST)),
+        ]
+    }
+}
+
+/// The skeleton of a component, a Resource holding the generated properties.
+const COMPONENT_LIB: &str = r#"mod properties;
+
+use scars::cf::common_types::Properties;
+use scars::cf::log::is_logging_property;
+use scars::cf::resource::{Resource, ResourceTrait, Result};
+
+pub use properties::{properties_type};
+
+/**
+ * The {name} component: the Resource keeps the life cycle, the ports
+ * and the values queried, the generated struct the typed properties the
+ * processing reads.
+ */
+pub struct {type_name} {
+    resource: Resource,
+    properties: {properties_type},
+}
+
+impl {type_name} {
+    pub fn new(identifier: &str) -> {type_name} {
+        let properties = {properties_type}::default();
+        let mut resource = Resource::new(identifier){ports};
+        for p in Properties::from(&properties) {
+            resource = resource.with_property(&p.id, p.value);
+        }
+        {type_name} {
+            resource,
+            properties,
+        }
+    }
+
+    pub fn properties(&self) -> &{properties_type} {
+        &self.properties
+    }
+}
+
+impl ResourceTrait for {type_name} {
+    fn identifier(&self) -> &str {
+        self.resource.identifier()
+    }
+
+    fn started(&self) -> bool {
+        self.resource.started()
+    }
+
+    fn initialize(&mut self) -> Result<()> {
+        self.resource.initialize()
+    }
+
+    fn release_object(&mut self) -> Result<()> {
+        self.resource.release_object()
+    }
+
+    fn configure(&mut self, properties: &Properties) -> Result<()> {
+        //the properties of the PRF are checked by their struct first
+        let own: Properties = properties
+            .iter()
+            .filter(|p| !is_logging_property(&p.id))
+            .cloned()
+            .collect();
+        if !own.is_empty() {
+            self.properties.configure(&own)?;
+        }
+        self.resource.configure(properties)
+    }
+
+    fn query(&self, properties: &Properties) -> Result<Properties> {
+        self.resource.query(properties)
+    }
+
+    fn start(&mut self) -> Result<()> {
+        //TODO start the processing, reading self.properties
+        self.resource.start()
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        self.resource.stop()
+    }
+
+    fn get_provides_port(&self, name: &str) -> Result<String> {
+        self.resource.get_provides_port(name)
+    }
+
+    fn connect_uses_port(&mut self, name: &str, connection_id: &str, endpoint: &str) -> Result<()> {
+        self.resource.connect_uses_port(name, connection_id, endpoint)
+    }
+
+    fn disconnect_port(&mut self, name: &str, connection_id: &str) -> Result<()> {
+        self.resource.disconnect_port(name, connection_id)
+    }
+}
+"#;
+
+/// The test of a component, hosted by a Sandbox.
+const COMPONENT_TEST: &str = r#"use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use scars::cf::common_types::{{common_types}};
+use scars::cf::file_system::FileSystem;
+use scars::cf::sandbox::Sandbox;
+
+use {module}::{{type_name}, {properties_type}};
+
+#[test]
+fn test_{module}() {
+    let file_system = FileSystem::new(Path::new(env!("CARGO_MANIFEST_DIR")));
+    let mut sandbox = Sandbox::new();
+    let component = Arc::new(Mutex::new({type_name}::new("{name}_1")));
+    sandbox
+        .launch_resource(&file_system, "/{name}.spd.xml", Some("{name}_1"), component.clone())
+        .unwrap();
+
+    //the properties are queried with their default values
+    let defaults = Properties::from(&{properties_type}::default());
+    let properties = sandbox.query("{name}_1", &Properties::new()).unwrap();
+    assert!(defaults.iter().all(|p| properties.contains(p)));
+{checks}
+    sandbox.start("{name}_1").unwrap();
+    sandbox.stop("{name}_1").unwrap();
+    sandbox.release_all().unwrap();
+}
+"#;
+
+/// The checks of the frequency of a new component.
+const FREQUENCY_CHECKS: &str = r#"
+    let frequency = vec![DataType::new({properties_type}::FREQUENCY_ID, AnyValue::Double(2000.0))];
+    sandbox.configure("{name}_1", &frequency).unwrap();
+    assert_eq!(*component.lock().unwrap().properties().frequency(), 2000.0);
+    assert_eq!(sandbox.query("{name}_1", &frequency).unwrap(), frequency);
+    let out_of_range = vec![DataType::new({properties_type}::FREQUENCY_ID, AnyValue::Double(-1.0))];
+    assert!(sandbox.configure("{name}_1", &out_of_range).is_err());
+"#;
+
+/// The skeleton of the device of a node, a Device holding the generated properties.
+const NODE_LIB: &str = r#"mod properties;
+
+use scars::cf::common_types::{AnyValue, Properties};
+use scars::cf::device::{AdminType, Device, DeviceTrait, OperationalType, Result, UsageType};
+use scars::cf::launcher::ExecParams;
+
+pub use properties::{properties_type};
+
+/**
+ * The device of the {name} node: the Device keeps the state model and
+ * the capacity allocated, the generated struct the properties of its
+ * PRF.
+ */
+pub struct {device_type} {
+    device: Device,
+    properties: {properties_type},
+}
+
+impl {device_type} {
+    pub fn new(device: Device) -> {device_type} {
+        let properties = {properties_type}::default();
+        let device = device.with_capacity(
+            {properties_type}::CAPACITY_ID,
+            AnyValue::Long(*properties.capacity()),
+        );
+        {device_type} { device, properties }
+    }
+
+    /// Returns the device described by the execparams it is launched with.
+    pub fn from_exec_params(params: &ExecParams) -> {device_type} {
+        {device_type}::new(params.device())
+    }
+
+    pub fn properties(&self) -> &{properties_type} {
+        &self.properties
+    }
+}
+
+impl DeviceTrait for {device_type} {
+    fn identifier(&self) -> &str {
+        self.device.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.device.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.device.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.device.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.device.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.device.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.device.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.device.allocation_properties()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> Result<bool> {
+        //TODO reserve the hardware backing the capacities
+        self.device.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> Result<()> {
+        self.device.deallocate_capacity(capacities)
+    }
+}
+"#;
+
+/// The launcher of the device of a node, given to its DeviceManager.
+const NODE_MAIN: &str = r#"use std::sync::{Arc, Mutex};
+
+use tokio::net::TcpListener;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::Notify;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+
+use scars::cf::device::DeviceRef;
+use scars::cf::device_service::DeviceService;
+use scars::cf::launcher::ExecParams;
+use scars::cf::rpc::device::device_server::DeviceServer;
+use scars::cf::rpc::device_manager::device_manager_client::DeviceManagerClient;
+use scars::cf::rpc::device_manager::{RegisterDeviceRequest, UnregisterDeviceRequest};
+
+use {module}::{device_type};
+
+/**
+ * Launcher of the {device} device: serves it as a Device gRPC service
+ * and registers it with the DeviceManager at DEVICE_MGR_IOR, until
+ * terminated. The DeviceManager of the node is given it as launcher.
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let params = ExecParams::parse(std::env::args().skip(1))?;
+    let device: DeviceRef = Arc::new(Mutex::new({device_type}::from_exec_params(&params)));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    let incoming = TcpIncoming::from_listener(listener, true, None).map_err(|e| e.to_string())?;
+    let release = Arc::new(Notify::new());
+    let server = tokio::spawn(
+        Server::builder()
+            .add_service(DeviceServer::new(
+                DeviceService::new(device).with_release(release.clone()),
+            ))
+            .serve_with_incoming_shutdown(incoming, terminated(release)),
+    );
+
+    let mut device_manager = DeviceManagerClient::connect(params.device_mgr.clone()).await?;
+    device_manager
+        .register_device(RegisterDeviceRequest {
+            identifier: params.device_id.clone(),
+            label: params.device_label.clone(),
+            profile_name: params.profile_name.clone(),
+            endpoint,
+        })
+        .await?;
+
+    server.await??;
+
+    device_manager
+        .unregister_device(UnregisterDeviceRequest {
+            identifier: params.device_id,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Resolves once the launcher is asked to terminate (SIGTERM, SIGINT or releaseObject).
+async fn terminated(release: Arc<Notify>) {
+    let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+    tokio::select! {
+        _ = sigterm.recv() => {}
+        _ = tokio::signal::ctrl_c() => {}
+        _ = release.notified() => {}
+    }
+}
+"#;
+
+/// The test of a node, its device allocated by an AllocationManager.
+const NODE_TEST: &str = r#"use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use scars::cf::allocation_manager::{
+    AllocationManager, AllocationManagerTrait, AllocationProperty, AllocationRequest,
+};
+use scars::cf::common_types::{ActionType, AnyValue, DataType};
+use scars::cf::device::{Device, DeviceTrait, UsageType};
+use scars::cf::profile::dcd::DeviceConfiguration;
+use scars::cf::profile::spd::SoftPkg;
+
+use {module}::{{device_type}, {properties_type}};
+
+#[test]
+fn test_{module}() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let dcd = DeviceConfiguration::from_file(&root.join("DeviceManager.dcd.xml")).unwrap();
+    let file = dcd.component_file(&dcd.placements[0]).unwrap();
+    let spd = std::fs::read_to_string(root.join(&file.local_file)).unwrap();
+    assert_eq!(SoftPkg::parse(&spd, &file.local_file).unwrap().name, "{device}");
+
+    let instantiation = &dcd.placements[0].instantiations[0];
+    let device = Arc::new(Mutex::new({device_type}::new(Device::new(&instantiation.id, "{device}_1"))));
+    let mut allocation_manager = AllocationManager::new();
+    allocation_manager.register_device(device.clone());
+
+    let capacity = *device.lock().unwrap().properties().capacity();
+    let request = |value: i32| AllocationRequest {
+        request_id: "request_1".to_string(),
+        allocation_properties: vec![AllocationProperty::new(
+            DataType::new({properties_type}::CAPACITY_ID, AnyValue::Long(value)),
+            ActionType::EXTERNAL,
+        )],
+        ..Default::default()
+    };
+    assert!(allocation_manager.allocate(&[request(capacity + 1)]).is_err());
+    let responses = allocation_manager.allocate(&[request(capacity)]).unwrap();
+    assert_eq!(responses.len(), 1);
+    assert_eq!(device.lock().unwrap().usage_state(), UsageType::BUSY);
+
+    let allocation_ids: Vec<String> = responses.into_iter().map(|r| r.allocation_id).collect();
+    allocation_manager.deallocate(&allocation_ids).unwrap();
+    assert_eq!(device.lock().unwrap().usage_state(), UsageType::IDLE);
+}
+"#;
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+
+use scars::cf::cli::{
+    self, CliOption, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION,
+};
+use scars::cf::redhawk_import::Importer;
+use scars::cf::scaffold::{Scaffold, ScaffoldKind};
+
+const USAGE: &str = "usage: scars [--format text|json] new component|node <name> [--directory <dir>] [--scars-path <dir>]
+       scars [--format text|json] import <redhawk project> [--sdrroot <dir>] [--directory <dir>] [--scars-path <dir>]
+       scars --completions bash|zsh|fish";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars",
+    options: &[
+        CliOption::value("--directory"),
+        CliOption::value("--scars-path"),
+        CliOption::value("--sdrroot"),
+        FORMAT_OPTION,
+        COMPLETIONS_OPTION,
+    ],
+    commands: &["new", "import", "component", "node"],
+};
+
+/// The options of the commands, given after their arguments.
+#[derive(Default)]
+struct Options {
+    directory: Option<PathBuf>,
+    scars_path: Option<PathBuf>,
+    sdrroot: Option<PathBuf>,
+}
+
+impl Options {
+    fn parse(mut options: &[&str]) -> Result<Options, Box<dyn std::error::Error>> {
+        let mut parsed = Options::default();
+        loop {
+            match options {
+                ["--directory", dir, rest @ ..] => {
+                    (parsed.directory, options) = (Some(PathBuf::from(dir)), rest)
+                }
+                ["--scars-path", path, rest @ ..] => {
+                    let path = std::fs::canonicalize(Path::new(path))?;
+                    (parsed.scars_path, options) = (Some(path), rest)
+                }
+                ["--sdrroot", dir, rest @ ..] => {
+                    (parsed.sdrroot, options) = (Some(PathBuf::from(dir)), rest)
+                }
+                [] => return Ok(parsed),
+                _ => return Err(USAGE.into()),
+            }
+        }
+    }
+
+    /// The directory the crates are generated in, the current one by default.
+    fn directory(&self) -> PathBuf {
+        self.directory.clone().unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+/**
+ * Project command line interface: the new command generates the crate
+ * of a component or of a node in a directory of its name, under the
+ * current directory unless another is given. The crates depend on the
+ * scars repository, or on the scars crate at the --scars-path given.
+ *
+ * A component crate holds its SPD, SCD and PRF, a Resource skeleton
+ * over the properties struct generated from its PRF and a test hosting
+ * it in a Sandbox. A node crate holds its DCD, the descriptors of its
+ * device, a Device skeleton, the launcher serving it and a test
+ * allocating it.
+ *
+ * The import command converts a REDHAWK component project into such a
+ * component crate, or a waveform project into the crates of its
+ * components and its SAD, the components being looked up in the dom
+ * directory of the --sdrroot given or of $SDRROOT.
+ *
+ * As JSON, the files generated are printed as an object.
+ *
+ * usage: scars [--format text|json] new component|node <name> [--directory <dir>] [--scars-path <dir>]
+ *        scars [--format text|json] import <redhawk project> [--sdrroot <dir>] [--directory <dir>] [--scars-path <dir>]
+ *        scars --completions bash|zsh|fish
+ */
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    let arguments: Vec<&str> = args.iter().map(String::as_str).collect();
+    match arguments.as_slice() {
+        ["new", kind, name, options @ ..] => {
+            let options = Options::parse(options)?;
+            let kind = ScaffoldKind::from_name(kind).ok_or(USAGE)?;
+            let mut scaffold = Scaffold::new(kind, name)?;
+            if let Some(scars_path) = &options.scars_path {
+                scaffold = scaffold.with_scars_path(scars_path);
+            }
+
+            let crate_directory = scaffold.write(&options.directory())?;
+            let files: Vec<String> = scaffold
+                .files()
+                .iter()
+                .map(|f| f.path.display().to_string())
+                .collect();
+            match format {
+                OutputFormat::Text => {
+                    println!("created {}", crate_directory.display());
+                    for file in files {
+                        println!("  {file}");
+                    }
+                }
+                OutputFormat::Json => cli::print_json(&json!({
+                    "directory": crate_directory.display().to_string(),
+                    "files": files,
+                }))?,
+            }
+        }
+        ["import", project, options @ ..] => {
+            let options = Options::parse(options)?;
+            let mut importer = Importer::new();
+            if let Some(sdrroot) = &options.sdrroot {
+                importer = importer.with_sdrroot(sdrroot);
+            }
+            if let Some(scars_path) = &options.scars_path {
+                importer = importer.with_scars_path(scars_path);
+            }
+
+            let report = importer.import(Path::new(project), &options.directory())?;
+            match format {
+                OutputFormat::Text => {
+                    for component in &report.components {
+                        println!(
+                            "imported {} from {} into {}",
+                            component.softpkg_id,
+                            component.spd_file_name.display(),
+                            component.directory.display()
+                        );
+                    }
+                    if let Some(waveform) = &report.waveform {
+                        println!("imported the waveform into {}", waveform.display());
+                    }
+                    for warning in &report.warnings {
+                        eprintln!("warning: {warning}");
+                    }
+                }
+                OutputFormat::Json => cli::print_json(&json!({
+                    "components": report.components.iter().map(|c| json!({
+                        "softpkg_id": c.softpkg_id,
+                        "spd_file_name": c.spd_file_name.display().to_string(),
+                        "crate_name": c.crate_name,
+                        "directory": c.directory.display().to_string(),
+                    })).collect::<Vec<_>>(),
+                    "waveform": report.waveform.as_ref().map(|w| w.display().to_string()),
+                    "warnings": report.warnings,
+                }))?,
+            }
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
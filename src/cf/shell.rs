@@ -0,0 +1,296 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde_json::json;
+
+use scars::cf::cli::{self, CommandLine, OutputFormat, COMPLETIONS_OPTION, FORMAT_OPTION};
+use scars::cf::common_types::{AnyValue, DataType, Properties};
+use scars::cf::file_system::FileSystem;
+use scars::cf::log::LogFormat;
+use scars::cf::profile::sad::PortKind;
+use scars::cf::rpc;
+use scars::cf::sandbox::Sandbox;
+
+const HELP: &str = "commands:
+  launch <spd file> [<name>]                 launch a component in the sandbox
+  components                                 list the components and their state
+  ports <name>                               list the ports of a component
+  connect <name>.<port> <name>.<port>        connect a uses port to a provides port
+  disconnect <connection id>
+  connections
+  query <name> [<property id>...]
+  configure <name> <id>=<value>...
+  start <name>
+  stop <name>
+  release <name>
+  logs [<name>...]                           print the log records
+  tail [<name>...]                           print the new log records after each command
+  tail off
+  help
+  exit";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-shell",
+    options: &[FORMAT_OPTION, COMPLETIONS_OPTION],
+    commands: &[],
+};
+
+/**
+ * Sandbox shell: launches components from their SPD in an in-process
+ * sandbox and operates them interactively, one command per line of the
+ * standard input, until its end or the exit command. The files are
+ * those of the local file system.
+ *
+ * Property values are written as literals, e.g. 2.5, "a b", [1,2] or
+ * {gain=2,label=rx}, parsed in the type of the current value. With
+ * --format json, the outputs of the commands are printed as JSON
+ * documents, without prompts.
+ *
+ * usage: scars-shell [--format text|json]
+ *        scars-shell --completions bash|zsh|fish
+ */
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let format = OutputFormat::take(&mut args)?;
+    if !args.is_empty() {
+        return Err("usage: scars-shell [--format text|json]".into());
+    }
+    let file_system = FileSystem::new(Path::new("/"));
+    let mut sandbox = Sandbox::new();
+    let mut tailed: Option<Vec<String>> = None;
+    let mut last_tailed = SystemTime::now();
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if format == OutputFormat::Text {
+            print!("scars> ");
+            std::io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            if format == OutputFormat::Text {
+                println!();
+            }
+            break;
+        };
+        let words = words(&line);
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            [] => {}
+            ["exit" | "quit"] => break,
+            ["tail", "off"] => tailed = None,
+            ["tail", names @ ..] => {
+                tailed = Some(names.iter().map(|n| n.to_string()).collect());
+                last_tailed = SystemTime::now();
+            }
+            command => {
+                if let Err(e) = execute(&mut sandbox, &file_system, command, format) {
+                    eprintln!("{e}");
+                }
+            }
+        }
+        if let Some(names) = &tailed {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            for record in sandbox.logs(&names, Some(last_tailed)) {
+                last_tailed = record.time;
+                println!("{}", log_format(format).format(&record));
+            }
+        }
+    }
+    sandbox.release_all()?;
+    Ok(())
+}
+
+/// Executes a command of the shell on the sandbox, printing its output in the format.
+fn execute(
+    sandbox: &mut Sandbox,
+    file_system: &FileSystem,
+    command: &[&str],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ["help"] => println!("{HELP}"),
+        ["launch", spd_file, name @ ..] if name.len() <= 1 => {
+            let spd_path = std::fs::canonicalize(spd_file)?;
+            let component = sandbox.launch(
+                file_system,
+                &spd_path.to_string_lossy(),
+                name.first().copied(),
+            )?;
+            match format {
+                OutputFormat::Text => println!("{}", component.name),
+                OutputFormat::Json => cli::print_json(&json!({ "name": component.name }))?,
+            }
+        }
+        ["components"] => {
+            let mut components = Vec::new();
+            for component in sandbox.components() {
+                let state = match component.resource.lock().unwrap().started() {
+                    true => "started",
+                    false => "stopped",
+                };
+                match format {
+                    OutputFormat::Text => println!(
+                        "{:<24} {:<8} {}",
+                        component.name, state, component.spd_file_name
+                    ),
+                    OutputFormat::Json => components.push(json!({
+                        "name": component.name,
+                        "state": state,
+                        "spd_file_name": component.spd_file_name,
+                    })),
+                }
+            }
+            if format == OutputFormat::Json {
+                cli::print_json(&components)?;
+            }
+        }
+        ["ports", name] => {
+            let mut ports = Vec::new();
+            for port in &sandbox.component(name)?.ports {
+                let kind = match port.kind {
+                    PortKind::USES => "uses",
+                    PortKind::PROVIDES => "provides",
+                };
+                match format {
+                    OutputFormat::Text => {
+                        println!("{:<24} {:<8} {}", port.name, kind, port.repository_id)
+                    }
+                    OutputFormat::Json => ports.push(json!({
+                        "name": port.name,
+                        "kind": kind,
+                        "repository_id": port.repository_id,
+                    })),
+                }
+            }
+            if format == OutputFormat::Json {
+                cli::print_json(&ports)?;
+            }
+        }
+        ["connect", uses, provides] => {
+            let (uses_component, uses_port) = port_reference(uses)?;
+            let (provides_component, provides_port) = port_reference(provides)?;
+            let connection_id =
+                sandbox.connect(uses_component, uses_port, provides_component, provides_port)?;
+            match format {
+                OutputFormat::Text => println!("{connection_id}"),
+                OutputFormat::Json => cli::print_json(&json!({ "connection_id": connection_id }))?,
+            }
+        }
+        ["disconnect", connection_id] => sandbox.disconnect(connection_id)?,
+        ["connections"] => {
+            let connections = sandbox.connections();
+            if format == OutputFormat::Json {
+                let connections: Vec<_> = connections
+                    .iter()
+                    .map(|c| json!({ "connection_id": c.connection_id, "endpoint": c.endpoint }))
+                    .collect();
+                cli::print_json(&connections)?;
+                return Ok(());
+            }
+            for connection in connections {
+                println!("{} {}", connection.connection_id, connection.endpoint);
+            }
+        }
+        ["query", name, property_ids @ ..] => {
+            let properties = query(sandbox, name, property_ids)?;
+            if format == OutputFormat::Json {
+                cli::print_json(&rpc::properties_to_wire(&properties))?;
+                return Ok(());
+            }
+            for property in properties {
+                println!("{}={}", property.id, property.value);
+            }
+        }
+        ["configure", name, assignments @ ..] if !assignments.is_empty() => {
+            let current = query(sandbox, name, &[])?;
+            let properties = assignments
+                .iter()
+                .map(|assignment| {
+                    let (id, text) = assignment
+                        .split_once('=')
+                        .ok_or_else(|| format!("'{assignment}' is not <id>=<value>"))?;
+                    let typed = current.iter().find(|p| p.id == id).map(|p| &p.value);
+                    let value = AnyValue::parse_literal(text, typed)
+                        .ok_or_else(|| format!("'{text}' is not a value of '{id}'"))?;
+                    Ok(DataType::new(id, value))
+                })
+                .collect::<Result<Properties, Box<dyn std::error::Error>>>()?;
+            sandbox.configure(name, &properties)?;
+        }
+        ["start", name] => sandbox.start(name)?,
+        ["stop", name] => sandbox.stop(name)?,
+        ["release", name] => sandbox.release(name)?,
+        ["logs", names @ ..] => {
+            for record in sandbox.logs(names, None) {
+                println!("{}", log_format(format).format(&record));
+            }
+        }
+        _ => return Err("unknown command, try help".into()),
+    }
+    Ok(())
+}
+
+/// The format of the log records printed in the output format.
+fn log_format(format: OutputFormat) -> LogFormat {
+    match format {
+        OutputFormat::Text => LogFormat::Text,
+        OutputFormat::Json => LogFormat::Json,
+    }
+}
+
+/// Queries properties of a component, all of them when none is given.
+fn query(
+    sandbox: &Sandbox,
+    name: &str,
+    property_ids: &[&str],
+) -> Result<Properties, Box<dyn std::error::Error>> {
+    let properties = property_ids
+        .iter()
+        .map(|id| DataType::new(id, AnyValue::String(String::new())))
+        .collect();
+    Ok(sandbox.query(name, &properties)?)
+}
+
+/// Splits a <component>.<port> reference.
+fn port_reference(reference: &str) -> Result<(&str, &str), Box<dyn std::error::Error>> {
+    reference
+        .rsplit_once('.')
+        .ok_or_else(|| format!("'{reference}' is not <name>.<port>").into())
+}
+
+/**
+ * Splits a command line into words at the whitespaces, but those of
+ * the double quoted strings and of the sequence and struct literals.
+ */
+fn words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let (mut depth, mut quoted, mut escaped) = (0, false, false);
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '[' | '{' if !quoted => depth += 1,
+            ']' | '}' if !quoted && depth > 0 => depth -= 1,
+            c if c.is_whitespace() && !quoted && depth == 0 => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            _ => {}
+        }
+        word.push(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
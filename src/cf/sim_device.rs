@@ -0,0 +1,484 @@
+use std::{collections::HashMap, path::Path, thread, time::Duration};
+
+use super::common_types::{ErrorNumberType, Properties};
+use super::device::{self, AdminType, Device, DeviceTrait, OperationalType, UsageType};
+use super::executable_device::{
+    self, ExecutableDeviceError, ExecutableDeviceTrait, ProcessId, ProcessMetrics, ProcessStatus,
+};
+use super::frontend_tuner::{FrontendTunerDevice, TunerStatus};
+use super::loadable_device::{self, LoadType, LoadableDeviceError, LoadableDeviceTrait};
+
+/**
+ * This type defines the operations of the simulated devices that can be
+ * slowed down or scripted to fail.
+ */
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimOperation {
+    ALLOCATE,
+    LOAD,
+    UNLOAD,
+    EXECUTE,
+    TERMINATE,
+}
+
+/**
+ * Script driving a simulated device: the latency added to each
+ * operation and the calls of each operation that fail.
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SimScript {
+    latencies: HashMap<SimOperation, Duration>,
+    failures: HashMap<SimOperation, Vec<usize>>,
+    calls: HashMap<SimOperation, usize>,
+}
+
+impl SimScript {
+    /// Sets the latency added to each call of the operation.
+    pub fn set_latency(&mut self, operation: SimOperation, latency: Duration) {
+        self.latencies.insert(operation, latency);
+    }
+
+    /// Makes the nth (1-based) call of the operation fail.
+    pub fn set_failure(&mut self, operation: SimOperation, call: usize) {
+        self.failures.entry(operation).or_default().push(call);
+    }
+
+    /// Returns the number of calls made to the operation.
+    pub fn calls(&self, operation: SimOperation) -> usize {
+        self.calls.get(&operation).copied().unwrap_or(0)
+    }
+
+    /// Accounts for a call of the operation, returning true when it shall fail.
+    fn step(&mut self, operation: SimOperation) -> bool {
+        if let Some(latency) = self.latencies.get(&operation) {
+            thread::sleep(*latency);
+        }
+        let call = self.calls.entry(operation).or_insert(0);
+        *call += 1;
+        self.failures
+            .get(&operation)
+            .is_some_and(|calls| calls.contains(call))
+    }
+}
+
+/**
+ * Simulated loadable device: loads are only accounted for, no file is
+ * read or staged, so that deployments can be exercised without any
+ * real hardware.
+ */
+#[derive(Debug)]
+pub struct SimLoadableDevice {
+    device: Device,
+    script: SimScript,
+    loaded_files: HashMap<String, (LoadType, usize)>,
+}
+
+impl SimLoadableDevice {
+    pub fn new(device: Device) -> SimLoadableDevice {
+        SimLoadableDevice {
+            device,
+            script: SimScript::default(),
+            loaded_files: HashMap::new(),
+        }
+    }
+
+    /// Adds a latency to each call of the operation.
+    pub fn with_latency(mut self, operation: SimOperation, latency: Duration) -> SimLoadableDevice {
+        self.script.set_latency(operation, latency);
+        self
+    }
+
+    /// Makes the nth (1-based) call of the operation fail.
+    pub fn with_failure(mut self, operation: SimOperation, call: usize) -> SimLoadableDevice {
+        self.script.set_failure(operation, call);
+        self
+    }
+
+    /// Returns the embedded device state model.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the mutable embedded device state model.
+    pub fn device_mut(&mut self) -> &mut Device {
+        &mut self.device
+    }
+
+    /// Returns the script driving the device.
+    pub fn script(&self) -> &SimScript {
+        &self.script
+    }
+
+    /// Returns the number of outstanding loads of a file.
+    pub fn load_count(&self, file_name: &str) -> usize {
+        self.loaded_files
+            .get(file_name)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Returns the load kind a file has been loaded with.
+    pub fn load_kind(&self, file_name: &str) -> Option<LoadType> {
+        self.loaded_files.get(file_name).map(|(kind, _)| *kind)
+    }
+
+    /// Verifies the device is UNLOCKED and ENABLED.
+    fn check_state(&self) -> Result<(), String> {
+        if self.admin_state() != AdminType::UNLOCKED {
+            return Err(format!("adminState is {:?}", self.admin_state()));
+        }
+        if self.operational_state() == OperationalType::DISABLED {
+            return Err("operationalState is DISABLED".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl DeviceTrait for SimLoadableDevice {
+    fn identifier(&self) -> &str {
+        self.device.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.device.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.device.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.device.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.device.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.device.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.device.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.device.allocation_properties()
+    }
+
+    /// A scripted allocation failure reports the capacities as not available.
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        if self.script.step(SimOperation::ALLOCATE) {
+            self.device.check_allocate_state()?;
+            return Ok(false);
+        }
+        self.device.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.device.deallocate_capacity(capacities)
+    }
+}
+
+impl LoadableDeviceTrait for SimLoadableDevice {
+    fn load(
+        &mut self,
+        _fs: &Path,
+        file_name: &str,
+        load_kind: LoadType,
+    ) -> loadable_device::Result<()> {
+        self.check_state()
+            .map_err(|message| LoadableDeviceError::InvalidState { message })?;
+        if self.script.step(SimOperation::LOAD) {
+            return Err(LoadableDeviceError::LoadFail {
+                error_number: ErrorNumberType::CF_EIO,
+                message: format!("scripted failure loading '{file_name}'"),
+            });
+        }
+
+        self.loaded_files
+            .entry(file_name.to_string())
+            .or_insert((load_kind, 0))
+            .1 += 1;
+        Ok(())
+    }
+
+    fn unload(&mut self, file_name: &str) -> loadable_device::Result<()> {
+        if self.admin_state() == AdminType::LOCKED {
+            return Err(LoadableDeviceError::InvalidState {
+                message: "adminState is LOCKED".to_string(),
+            });
+        }
+        let scripted = self.script.step(SimOperation::UNLOAD);
+        let Some((_, count)) = self.loaded_files.get_mut(file_name).filter(|_| !scripted) else {
+            return Err(LoadableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{file_name}' not loaded"),
+            });
+        };
+
+        *count -= 1;
+        if *count == 0 {
+            self.loaded_files.remove(file_name);
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Simulated executable device: executed files become fake processes,
+ * identified by increasing process ids and kept until terminated. The
+ * fake processes run idle unless given a simulated usage.
+ */
+#[derive(Debug)]
+pub struct SimExecutableDevice {
+    loadable: SimLoadableDevice,
+    processes: HashMap<ProcessId, (String, Properties)>,
+    usages: HashMap<ProcessId, (f32, u64)>,
+    next_process_id: ProcessId,
+}
+
+impl SimExecutableDevice {
+    pub fn new(loadable: SimLoadableDevice) -> SimExecutableDevice {
+        SimExecutableDevice {
+            loadable,
+            processes: HashMap::new(),
+            usages: HashMap::new(),
+            next_process_id: 1000,
+        }
+    }
+
+    /// Returns the embedded loadable device.
+    pub fn loadable(&self) -> &SimLoadableDevice {
+        &self.loadable
+    }
+
+    /// Returns the mutable embedded loadable device.
+    pub fn loadable_mut(&mut self) -> &mut SimLoadableDevice {
+        &mut self.loadable
+    }
+
+    /// Returns the ids of the running fake processes.
+    pub fn process_ids(&self) -> Vec<ProcessId> {
+        let mut ids: Vec<ProcessId> = self.processes.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Returns the name and the parameters a fake process was executed with.
+    pub fn process(&self, process_id: ProcessId) -> Option<&(String, Properties)> {
+        self.processes.get(&process_id)
+    }
+
+    /// Sets the CPU usage (percent of one core) and memory (bytes) reported for a fake process.
+    pub fn set_process_usage(&mut self, process_id: ProcessId, cpu_usage: f32, memory: u64) {
+        self.usages.insert(process_id, (cpu_usage, memory));
+    }
+}
+
+impl DeviceTrait for SimExecutableDevice {
+    fn identifier(&self) -> &str {
+        self.loadable.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.loadable.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.loadable.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.loadable.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.loadable.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.loadable.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.loadable.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.loadable.allocation_properties()
+    }
+
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        self.loadable.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.loadable.deallocate_capacity(capacities)
+    }
+}
+
+impl LoadableDeviceTrait for SimExecutableDevice {
+    fn load(
+        &mut self,
+        fs: &Path,
+        file_name: &str,
+        load_kind: LoadType,
+    ) -> loadable_device::Result<()> {
+        self.loadable.load(fs, file_name, load_kind)
+    }
+
+    fn unload(&mut self, file_name: &str) -> loadable_device::Result<()> {
+        self.loadable.unload(file_name)
+    }
+}
+
+impl ExecutableDeviceTrait for SimExecutableDevice {
+    fn execute(
+        &mut self,
+        name: &str,
+        _options: &Properties,
+        parameters: &Properties,
+    ) -> executable_device::Result<ProcessId> {
+        self.loadable
+            .check_state()
+            .map_err(|message| ExecutableDeviceError::InvalidState { message })?;
+        if self.loadable.load_count(name) == 0 {
+            return Err(ExecutableDeviceError::InvalidFileName {
+                error_number: ErrorNumberType::CF_ENOENT,
+                message: format!("'{name}' not loaded"),
+            });
+        }
+        if self.loadable.script.step(SimOperation::EXECUTE) {
+            return Err(ExecutableDeviceError::ExecuteFail {
+                error_number: ErrorNumberType::CF_EIO,
+                message: format!("scripted failure executing '{name}'"),
+            });
+        }
+
+        self.next_process_id += 1;
+        self.processes
+            .insert(self.next_process_id, (name.to_string(), parameters.clone()));
+        Ok(self.next_process_id)
+    }
+
+    fn terminate(&mut self, process_id: ProcessId) -> executable_device::Result<()> {
+        let scripted = self.loadable.script.step(SimOperation::TERMINATE);
+        if scripted || self.processes.remove(&process_id).is_none() {
+            return Err(ExecutableDeviceError::InvalidProcess {
+                error_number: ErrorNumberType::CF_ESRCH,
+                message: format!("process {process_id} not found"),
+            });
+        }
+        self.usages.remove(&process_id);
+        Ok(())
+    }
+
+    fn process_metrics(
+        &mut self,
+        process_id: ProcessId,
+    ) -> executable_device::Result<ProcessMetrics> {
+        if !self.processes.contains_key(&process_id) {
+            return Err(ExecutableDeviceError::InvalidProcess {
+                error_number: ErrorNumberType::CF_ESRCH,
+                message: format!("process {process_id} not found"),
+            });
+        }
+        let (cpu_usage, memory) = self.usages.get(&process_id).copied().unwrap_or_default();
+        Ok(ProcessMetrics {
+            process_id,
+            cpu_usage,
+            memory,
+            status: ProcessStatus::RUNNING,
+        })
+    }
+}
+
+/**
+ * Simulated front-end tuner device: a tuner device whose allocations
+ * can be slowed down or scripted to fail.
+ */
+#[derive(Debug)]
+pub struct SimTunerDevice {
+    tuner: FrontendTunerDevice,
+    script: SimScript,
+}
+
+impl SimTunerDevice {
+    pub fn new(tuner: FrontendTunerDevice) -> SimTunerDevice {
+        SimTunerDevice {
+            tuner,
+            script: SimScript::default(),
+        }
+    }
+
+    /// Adds a latency to each allocation.
+    pub fn with_latency(mut self, latency: Duration) -> SimTunerDevice {
+        self.script.set_latency(SimOperation::ALLOCATE, latency);
+        self
+    }
+
+    /// Makes the nth (1-based) allocation fail.
+    pub fn with_failure(mut self, call: usize) -> SimTunerDevice {
+        self.script.set_failure(SimOperation::ALLOCATE, call);
+        self
+    }
+
+    /// Returns the status of the tuners.
+    pub fn tuner_status(&self) -> Vec<TunerStatus> {
+        self.tuner.tuner_status()
+    }
+
+    /// Returns the embedded tuner device.
+    pub fn tuner(&self) -> &FrontendTunerDevice {
+        &self.tuner
+    }
+}
+
+impl DeviceTrait for SimTunerDevice {
+    fn identifier(&self) -> &str {
+        self.tuner.identifier()
+    }
+
+    fn label(&self) -> &str {
+        self.tuner.label()
+    }
+
+    fn composite_device(&self) -> Option<&str> {
+        self.tuner.composite_device()
+    }
+
+    fn usage_state(&self) -> UsageType {
+        self.tuner.usage_state()
+    }
+
+    fn admin_state(&self) -> AdminType {
+        self.tuner.admin_state()
+    }
+
+    fn set_admin_state(&mut self, admin_state: AdminType) {
+        self.tuner.set_admin_state(admin_state)
+    }
+
+    fn operational_state(&self) -> OperationalType {
+        self.tuner.operational_state()
+    }
+
+    fn allocation_properties(&self) -> Properties {
+        self.tuner.allocation_properties()
+    }
+
+    /// A scripted allocation failure reports the tuners as not available.
+    fn allocate_capacity(&mut self, capacities: &Properties) -> device::Result<bool> {
+        if self.script.step(SimOperation::ALLOCATE) {
+            self.tuner.device().check_allocate_state()?;
+            return Ok(false);
+        }
+        self.tuner.allocate_capacity(capacities)
+    }
+
+    fn deallocate_capacity(&mut self, capacities: &Properties) -> device::Result<()> {
+        self.tuner.deallocate_capacity(capacities)
+    }
+}
@@ -0,0 +1,274 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::DefaultTerminal;
+use tokio::sync::Notify;
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+
+use scars::cf::cli::{CliOption, CommandLine, COMPLETIONS_OPTION};
+use scars::cf::dashboard::{self, draw, idm_line, odm_line, ApplicationRow, Dashboard, DeviceRow};
+use scars::cf::rpc;
+use scars::cf::rpc::device::device_client::DeviceClient;
+use scars::cf::rpc::device::{AllocationPropertiesRequest, StatusReply, StatusRequest};
+use scars::cf::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+use scars::cf::rpc::domain_manager::{
+    ApplicationMetricsRequest, ApplicationsRequest, DeviceManagersRequest, SubscribeRequest,
+};
+
+/// The time a device is given to answer its status.
+const DEVICE_TIMEOUT: Duration = Duration::from_secs(1);
+
+const USAGE: &str = "usage: scars-top <domain manager endpoint> [--interval <seconds>]";
+
+const COMMAND_LINE: CommandLine = CommandLine {
+    name: "scars-top",
+    options: &[CliOption::value("--interval"), COMPLETIONS_OPTION],
+    commands: &[],
+};
+
+type DashboardRef = Arc<Mutex<Dashboard>>;
+
+/**
+ * Domain dashboard: shows on a single screen the device managers, the
+ * states and capacities of their devices, the running applications with
+ * the health of their components and the recent domain events. The
+ * state is refreshed on each event of the ODM and IDM channels, and at
+ * the interval, 2 seconds by default.
+ *
+ * usage: scars-top <domain manager endpoint> [--interval <seconds>]
+ *        scars-top --completions bash|zsh|fish
+ */
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(script) = COMMAND_LINE.requested_completions(&args)? {
+        print!("{script}");
+        return Ok(());
+    }
+    let (endpoint, interval) = match dashboard::parse_args(&args) {
+        Some(Ok(arguments)) => arguments,
+        Some(Err(e)) => return Err(format!("{e}\n{USAGE}").into()),
+        None => return Err(USAGE.into()),
+    };
+
+    let domain = DomainManagerClient::connect(endpoint.clone()).await?;
+    let dashboard: DashboardRef = Arc::default();
+    let changed = Arc::new(Notify::new());
+    tokio::spawn(follow_odm(
+        domain.clone(),
+        dashboard.clone(),
+        changed.clone(),
+        interval,
+    ));
+    tokio::spawn(follow_idm(
+        domain.clone(),
+        dashboard.clone(),
+        changed.clone(),
+        interval,
+    ));
+    tokio::spawn(refresh(domain, dashboard.clone(), changed, interval));
+
+    //the terminal is driven apart from the runtime fetching the state
+    tokio::task::spawn_blocking(move || {
+        let mut terminal = ratatui::init();
+        let outcome = run(&mut terminal, &endpoint, &dashboard);
+        ratatui::restore();
+        outcome
+    })
+    .await??;
+    Ok(())
+}
+
+/// Draws the dashboard until 'q' or Esc is pressed.
+fn run(
+    terminal: &mut DefaultTerminal,
+    endpoint: &str,
+    dashboard: &DashboardRef,
+) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, endpoint, &dashboard.lock().unwrap()))?;
+        if event::poll(Duration::from_millis(250))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press
+                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Adds an event line on top of the recent ones.
+fn push_event(dashboard: &DashboardRef, line: String) {
+    dashboard
+        .lock()
+        .unwrap()
+        .push_event(SystemTime::now(), line);
+}
+
+/// Refreshes the dashboard at the interval, or once the domain changed.
+async fn refresh(
+    mut domain: DomainManagerClient<Channel>,
+    dashboard: DashboardRef,
+    changed: Arc<Notify>,
+    interval: Duration,
+) {
+    loop {
+        let fetched = fetch(&mut domain).await;
+        {
+            let mut dashboard = dashboard.lock().unwrap();
+            match fetched {
+                Ok((nodes, devices, applications)) => {
+                    dashboard.nodes = nodes;
+                    dashboard.devices = devices;
+                    dashboard.applications = applications;
+                    dashboard.refreshed = Some(SystemTime::now());
+                    dashboard.error = None;
+                }
+                Err(status) => dashboard.error = Some(status.message().to_string()),
+            }
+        }
+        let _ = tokio::time::timeout(interval, changed.notified()).await;
+    }
+}
+
+type Fetched = (
+    Vec<(String, String, bool)>,
+    Vec<DeviceRow>,
+    Vec<ApplicationRow>,
+);
+
+/// Fetches the nodes and the applications of the domain, and the states of the devices.
+async fn fetch(domain: &mut DomainManagerClient<Channel>) -> Result<Fetched, tonic::Status> {
+    let device_managers = domain
+        .device_managers(DeviceManagersRequest {})
+        .await?
+        .into_inner()
+        .device_managers;
+
+    let mut nodes = Vec::new();
+    let mut devices = Vec::new();
+    for node in device_managers {
+        let Some(device_manager) = node.device_manager else {
+            continue;
+        };
+        for device in node.devices {
+            let (status, capacities) = device_state(&device.endpoint).await.unzip();
+            devices.push(DeviceRow {
+                node: device_manager.identifier.clone(),
+                identifier: device.identifier,
+                label: device.label,
+                status,
+                capacities: capacities.unwrap_or_default(),
+            });
+        }
+        nodes.push((
+            device_manager.identifier,
+            device_manager.label,
+            node.available,
+        ));
+    }
+
+    let mut applications = Vec::new();
+    let infos = domain
+        .applications(ApplicationsRequest {})
+        .await?
+        .into_inner()
+        .applications;
+    for info in infos {
+        let request = ApplicationMetricsRequest {
+            identifier: info.identifier.clone(),
+        };
+        let metrics = domain
+            .application_metrics(request)
+            .await
+            .ok()
+            .map(|r| r.into_inner());
+        applications.push(ApplicationRow { info, metrics });
+    }
+    Ok((nodes, devices, applications))
+}
+
+/// Returns the states and the capacities of a device, none when not answering.
+async fn device_state(endpoint: &str) -> Option<(StatusReply, String)> {
+    let query = async {
+        let mut device = DeviceClient::connect(endpoint.to_string()).await.ok()?;
+        let status = device.status(StatusRequest {}).await.ok()?.into_inner();
+        let capacities = device
+            .allocation_properties(AllocationPropertiesRequest {})
+            .await
+            .ok()
+            .and_then(|r| rpc::properties_from_wire(&r.into_inner().properties).ok())
+            .unwrap_or_default()
+            .iter()
+            .map(|p| format!("{}={}", p.id, p.value))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Some((status, capacities))
+    };
+    tokio::time::timeout(DEVICE_TIMEOUT, query)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Shows the events of the ODM channel, resubscribing once lost.
+async fn follow_odm(
+    mut domain: DomainManagerClient<Channel>,
+    dashboard: DashboardRef,
+    changed: Arc<Notify>,
+    interval: Duration,
+) {
+    let mut last_sequence = None;
+    loop {
+        let request = SubscribeRequest {
+            last_sequence,
+            ..Default::default()
+        };
+        match domain.subscribe_odm_events(request).await {
+            Ok(events) => {
+                let mut events = events.into_inner();
+                while let Some(Ok(event)) = events.next().await {
+                    last_sequence = Some(event.sequence);
+                    push_event(&dashboard, format!("ODM {}", odm_line(&event)));
+                    changed.notify_one();
+                }
+                push_event(&dashboard, "ODM subscription lost".to_string());
+            }
+            Err(status) => push_event(&dashboard, format!("ODM {}", status.message())),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Shows the events of the IDM channel, resubscribing once lost.
+async fn follow_idm(
+    mut domain: DomainManagerClient<Channel>,
+    dashboard: DashboardRef,
+    changed: Arc<Notify>,
+    interval: Duration,
+) {
+    let mut last_sequence = None;
+    loop {
+        let request = SubscribeRequest {
+            last_sequence,
+            ..Default::default()
+        };
+        match domain.subscribe_idm_events(request).await {
+            Ok(events) => {
+                let mut events = events.into_inner();
+                while let Some(Ok(event)) = events.next().await {
+                    last_sequence = Some(event.sequence);
+                    push_event(&dashboard, format!("IDM {}", idm_line(&event)));
+                    changed.notify_one();
+                }
+                push_event(&dashboard, "IDM subscription lost".to_string());
+            }
+            Err(status) => push_event(&dashboard, format!("IDM {}", status.message())),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,399 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use zenoh::handlers::FifoChannelHandler;
+use zenoh::pubsub::Subscriber;
+use zenoh::qos::{CongestionControl, Priority};
+use zenoh::sample::Sample;
+use zenoh::{Config, Session};
+
+use super::events::{EventChannel, EventChannelTrait, EventStream};
+
+/**
+ * Convienence enum definition that includes all zenoh channel errors.
+ */
+#[derive(Error, Debug)]
+pub enum ZenohError {
+    /**
+     * This exception indicates the configuration of a zenoh session is
+     * invalid or the session failed to be opened.
+     */
+    #[error("SessionError: message: '{message}'.")]
+    SessionError { message: String },
+    /**
+     * This exception indicates a publisher, subscriber, queryable or
+     * query failed to be declared on a key expression.
+     */
+    #[error("KeyExprError: key_expr: '{key_expr}', message: '{message}'.")]
+    KeyExprError { key_expr: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all zenoh channel returned errors.
+ */
+pub type Result<T, E = ZenohError> = anyhow::Result<T, E>;
+
+/// The default prefix of the key expressions of a domain.
+pub const DEFAULT_KEY_PREFIX: &str = "scars";
+
+fn session_error(e: zenoh::Error) -> ZenohError {
+    ZenohError::SessionError {
+        message: e.to_string(),
+    }
+}
+
+fn key_expr_error(key_expr: &str) -> impl FnOnce(zenoh::Error) -> ZenohError + '_ {
+    move |e| ZenohError::KeyExprError {
+        key_expr: key_expr.to_string(),
+        message: e.to_string(),
+    }
+}
+
+/**
+ * Returns the configuration of a zenoh peer listening on endpoints,
+ * e.g. "tcp/0.0.0.0:7447", and connecting to the peers of others. On
+ * the ad hoc networks without static addressing, the peers find each
+ * other by multicast scouting when enabled.
+ */
+pub fn peer_config(listen: &[&str], connect: &[&str], multicast_scouting: bool) -> Result<Config> {
+    let mut config = Config::default();
+    let endpoints = |endpoints: &[&str]| serde_json::to_string(endpoints).unwrap_or_default();
+    config
+        .insert_json5("mode", "\"peer\"")
+        .and_then(|_| config.insert_json5("listen/endpoints", &endpoints(listen)))
+        .and_then(|_| config.insert_json5("connect/endpoints", &endpoints(connect)))
+        .and_then(|_| {
+            config.insert_json5(
+                "scouting/multicast/enabled",
+                &multicast_scouting.to_string(),
+            )
+        })
+        .map_err(session_error)?;
+    Ok(config)
+}
+
+/// Opens a zenoh session, shared by the channels and announcements declared on it.
+pub async fn open(config: Config) -> Result<Session> {
+    zenoh::open(config).await.map_err(session_error)
+}
+
+/**
+ * This type defines the traffic a channel carries: the events, never
+ * dropped, the publisher blocking on congestion, and the data of the
+ * port connections, the samples being dropped on congestion so that a
+ * slow link does not stall the producer.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZenohTraffic {
+    Events,
+    Data,
+}
+
+/**
+ * Event channel putting its events, encoded in JSON, on a zenoh key
+ * expression, e.g. "scars/events/IDM_Channel", for the disconnected and
+ * ad hoc networks where central gRPC endpoints are impractical. The
+ * subscribers declared on the key expression, in any peer of the zenoh
+ * network, receive the events pushed once their subscription reached
+ * the channel. Cloned channels share the same publisher.
+ */
+#[derive(Clone)]
+pub struct ZenohEventChannel<T> {
+    name: String,
+    key_expr: String,
+    traffic: ZenohTraffic,
+    session: Session,
+    sender: mpsc::UnboundedSender<Vec<u8>>,
+    /// The number of the events the encoding or putting of failed.
+    failed_writes: Arc<Mutex<u64>>,
+    events: PhantomData<fn(T)>,
+}
+
+impl<T> std::fmt::Debug for ZenohEventChannel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ZenohEventChannel")
+            .field("name", &self.name)
+            .field("key_expr", &self.key_expr)
+            .field("traffic", &self.traffic)
+            .field("failed_writes", &*self.failed_writes.lock().unwrap())
+            .finish()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> ZenohEventChannel<T> {
+    /// Declares the publisher of the channel on a key expression of a session.
+    pub async fn declare(
+        session: &Session,
+        name: &str,
+        key_expr: &str,
+        traffic: ZenohTraffic,
+    ) -> Result<ZenohEventChannel<T>> {
+        let (congestion_control, priority) = match traffic {
+            ZenohTraffic::Events => (CongestionControl::Block, Priority::InteractiveHigh),
+            ZenohTraffic::Data => (CongestionControl::Drop, Priority::Data),
+        };
+        let publisher = session
+            .declare_publisher(key_expr.to_string())
+            .congestion_control(congestion_control)
+            .priority(priority)
+            .await
+            .map_err(key_expr_error(key_expr))?;
+        let (sender, mut payloads) = mpsc::unbounded_channel::<Vec<u8>>();
+        let failed_writes = Arc::<Mutex<u64>>::default();
+        let failed = failed_writes.clone();
+        tokio::spawn(async move {
+            while let Some(payload) = payloads.recv().await {
+                if publisher.put(payload).await.is_err() {
+                    *failed.lock().unwrap() += 1;
+                }
+            }
+        });
+        Ok(ZenohEventChannel {
+            name: name.to_string(),
+            key_expr: key_expr.to_string(),
+            traffic,
+            session: session.clone(),
+            sender,
+            failed_writes,
+            events: PhantomData,
+        })
+    }
+
+    pub fn key_expr(&self) -> &str {
+        &self.key_expr
+    }
+
+    pub fn traffic(&self) -> ZenohTraffic {
+        self.traffic
+    }
+
+    /// Returns the number of the events pushed the encoding or putting of failed.
+    pub fn failed_writes(&self) -> u64 {
+        *self.failed_writes.lock().unwrap()
+    }
+
+    /**
+     * Pushes the events received on the key expression on a channel of
+     * the process, e.g. the one a BulkIO provides port receives from for
+     * a connection over zenoh, in a task of the runtime.
+     */
+    pub fn forward(&self, channel: &EventChannel<T>) -> JoinHandle<()>
+    where
+        T: Clone,
+    {
+        let mut events = self.subscribe();
+        let channel = channel.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                channel.push(event);
+            }
+        })
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> EventChannelTrait<T>
+    for ZenohEventChannel<T>
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The events failing to be encoded or put are counted, and dropped.
+    fn push(&self, event: T) {
+        match serde_json::to_vec(&event).map(|payload| self.sender.send(payload)) {
+            Ok(Ok(())) => {}
+            _ => *self.failed_writes.lock().unwrap() += 1,
+        }
+    }
+
+    /// The stream of a subscription failing to be declared is empty.
+    fn subscribe(&self) -> EventStream<T> {
+        let (sender, events) = mpsc::unbounded_channel();
+        let (session, key_expr) = (self.session.clone(), self.key_expr.clone());
+        tokio::spawn(async move {
+            let _ = receive(&session, &key_expr, move |event| sender.send(event).is_ok()).await;
+        });
+        Box::pin(UnboundedReceiverStream::new(events))
+    }
+
+    fn subscribe_from(&self, _last_seen: u64) -> EventStream<(u64, T)> {
+        let mut sequence = 0;
+        Box::pin(self.subscribe().map(move |event| {
+            sequence += 1;
+            (sequence, event)
+        }))
+    }
+}
+
+/**
+ * Declares a subscriber on a key expression of a session, returning the
+ * stream of the events received, those failing to be decoded being
+ * dropped. The key expression may be a pattern of wildcards, matching
+ * the ones of several channels.
+ */
+pub async fn subscribe<T: DeserializeOwned + Send + 'static>(
+    session: &Session,
+    key_expr: &str,
+) -> Result<EventStream<T>> {
+    let (sender, events) = mpsc::unbounded_channel();
+    receive(session, key_expr, move |event| sender.send(event).is_ok()).await?;
+    Ok(Box::pin(UnboundedReceiverStream::new(events)))
+}
+
+/**
+ * Declares a subscriber on a key expression as subscribe does, pushing
+ * the events received on a channel of the process in a task of the
+ * runtime.
+ */
+pub async fn subscribe_to<T: DeserializeOwned + Clone + Send + 'static>(
+    session: &Session,
+    key_expr: &str,
+    channel: &EventChannel<T>,
+) -> Result<JoinHandle<()>> {
+    let channel = channel.clone();
+    receive(session, key_expr, move |event| {
+        channel.push(event);
+        true
+    })
+    .await
+}
+
+async fn receive<T, F>(session: &Session, key_expr: &str, deliver: F) -> Result<JoinHandle<()>>
+where
+    T: DeserializeOwned + Send + 'static,
+    F: FnMut(T) -> bool + Send + 'static,
+{
+    let subscriber = session
+        .declare_subscriber(key_expr.to_string())
+        .await
+        .map_err(key_expr_error(key_expr))?;
+    Ok(tokio::spawn(decode(subscriber, deliver)))
+}
+
+/// Delivers the events received until the subscriber is undeclared or the delivery stops.
+async fn decode<T, F>(subscriber: Subscriber<FifoChannelHandler<Sample>>, mut deliver: F)
+where
+    T: DeserializeOwned,
+    F: FnMut(T) -> bool,
+{
+    while let Ok(sample) = subscriber.recv_async().await {
+        let Ok(event) = serde_json::from_slice(&sample.payload().to_bytes()) else {
+            continue;
+        };
+        if !deliver(event) {
+            break;
+        }
+    }
+}
+
+/**
+ * The endpoint of a CF object announced on the zenoh network, e.g. the
+ * gRPC endpoint of a DomainManager of kind "DomainManager".
+ */
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ZenohEndpoint {
+    pub kind: String,
+    pub identifier: String,
+    pub endpoint: String,
+}
+
+impl ZenohEndpoint {
+    pub fn new(kind: &str, identifier: &str, endpoint: &str) -> ZenohEndpoint {
+        ZenohEndpoint {
+            kind: kind.to_string(),
+            identifier: identifier.to_string(),
+            endpoint: endpoint.to_string(),
+        }
+    }
+}
+
+/**
+ * The announcement of an endpoint, answering the discoveries of the
+ * peers until dropped.
+ */
+#[derive(Debug)]
+pub struct ZenohAnnouncement {
+    key_expr: String,
+    replies: JoinHandle<()>,
+}
+
+impl ZenohAnnouncement {
+    /// Returns the key expression the endpoint is announced on.
+    pub fn key_expr(&self) -> &str {
+        &self.key_expr
+    }
+}
+
+impl Drop for ZenohAnnouncement {
+    fn drop(&mut self) {
+        self.replies.abort();
+    }
+}
+
+/**
+ * Announces an endpoint on the key expression "<prefix>/endpoints/<kind>/<identifier>",
+ * answering the discoveries until the announcement is dropped.
+ */
+pub async fn announce(
+    session: &Session,
+    prefix: &str,
+    endpoint: &ZenohEndpoint,
+) -> Result<ZenohAnnouncement> {
+    let key_expr = format!(
+        "{prefix}/endpoints/{}/{}",
+        endpoint.kind, endpoint.identifier
+    );
+    let payload = serde_json::to_vec(endpoint).unwrap_or_default();
+    let queryable = session
+        .declare_queryable(key_expr.clone())
+        .await
+        .map_err(key_expr_error(&key_expr))?;
+    let reply_key_expr = key_expr.clone();
+    let replies = tokio::spawn(async move {
+        while let Ok(query) = queryable.recv_async().await {
+            let _ = query
+                .reply(reply_key_expr.clone(), payload.clone())
+                .await;
+        }
+    });
+    Ok(ZenohAnnouncement { key_expr, replies })
+}
+
+/**
+ * Returns the endpoints announced on the zenoh network under a prefix,
+ * of one kind or all of them, answering within the timeout, sorted by
+ * kind and identifier.
+ */
+pub async fn discover(
+    session: &Session,
+    prefix: &str,
+    kind: Option<&str>,
+    timeout: Duration,
+) -> Result<Vec<ZenohEndpoint>> {
+    let selector = format!("{prefix}/endpoints/{}/**", kind.unwrap_or("*"));
+    let replies = session
+        .get(selector.clone())
+        .timeout(timeout)
+        .await
+        .map_err(key_expr_error(&selector))?;
+    let mut endpoints = Vec::new();
+    while let Ok(reply) = replies.recv_async().await {
+        let Ok(sample) = reply.result() else {
+            continue;
+        };
+        if let Ok(endpoint) = serde_json::from_slice(&sample.payload().to_bytes()) {
+            endpoints.push(endpoint);
+        }
+    }
+    endpoints.sort();
+    endpoints.dedup();
+    Ok(endpoints)
+}
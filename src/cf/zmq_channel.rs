@@ -0,0 +1,292 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+use zeromq::{
+    PubSocket, PullSocket, PushSocket, Socket, SocketRecv, SocketSend, SubSocket, ZmqMessage,
+};
+
+use super::events::{EventChannel, EventChannelTrait, EventStream};
+
+/**
+ * Convienence enum definition that includes all ZeroMQ channel errors.
+ */
+#[derive(Error, Debug)]
+pub enum ZmqError {
+    /**
+     * This exception indicates a socket failed to bind or connect to an
+     * endpoint.
+     */
+    #[error("SocketError: endpoint: '{endpoint}', message: '{message}'.")]
+    SocketError { endpoint: String, message: String },
+}
+
+/*
+ * Convienence type definition that includes all ZeroMQ channel returned errors.
+ */
+pub type Result<T, E = ZmqError> = anyhow::Result<T, E>;
+
+/**
+ * This type defines the ZeroMQ pattern of a channel: publish/subscribe
+ * for the events every subscriber receives, the messages being sent
+ * with the name of the channel as topic, and push/pull for the data of
+ * the port connections, each message being received by one of the
+ * pulling subscribers in turn.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZmqPattern {
+    PubSub,
+    PushPull,
+}
+
+fn socket_error(endpoint: &str) -> impl FnOnce(zeromq::ZmqError) -> ZmqError + '_ {
+    move |e| ZmqError::SocketError {
+        endpoint: endpoint.to_string(),
+        message: e.to_string(),
+    }
+}
+
+/**
+ * Event channel sending its events, encoded in JSON, on a ZeroMQ socket
+ * bound to an endpoint, e.g. "tcp://0.0.0.0:5556", for the embedded
+ * targets where an HTTP/2 stack is too heavy. The subscribers connect
+ * to the endpoint of the channel, from this process or another one.
+ * ZeroMQ dropping the messages sent without peer, the events pushed
+ * before the subscribers connect are missed. Cloned channels share the
+ * same socket.
+ */
+#[derive(Clone)]
+pub struct ZmqEventChannel<T> {
+    name: String,
+    endpoint: String,
+    pattern: ZmqPattern,
+    sender: mpsc::UnboundedSender<ZmqMessage>,
+    /// The number of the events the encoding or sending of failed.
+    failed_writes: Arc<Mutex<u64>>,
+    events: PhantomData<fn(T)>,
+}
+
+impl<T> std::fmt::Debug for ZmqEventChannel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ZmqEventChannel")
+            .field("name", &self.name)
+            .field("endpoint", &self.endpoint)
+            .field("pattern", &self.pattern)
+            .field("failed_writes", &*self.failed_writes.lock().unwrap())
+            .finish()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> ZmqEventChannel<T> {
+    /**
+     * Binds the socket of the channel, PUB or PUSH after its pattern, to
+     * an endpoint, the port 0 of a tcp endpoint binding an ephemeral
+     * port.
+     */
+    pub async fn bind(
+        name: &str,
+        endpoint: &str,
+        pattern: ZmqPattern,
+    ) -> Result<ZmqEventChannel<T>> {
+        let (sender, messages) = mpsc::unbounded_channel();
+        let failed_writes = Arc::<Mutex<u64>>::default();
+        let bound = match pattern {
+            ZmqPattern::PubSub => {
+                let mut socket = PubSocket::new();
+                let bound = socket
+                    .bind(endpoint)
+                    .await
+                    .map_err(socket_error(endpoint))?;
+                tokio::spawn(send(socket, messages, failed_writes.clone()));
+                bound
+            }
+            ZmqPattern::PushPull => {
+                let mut socket = PushSocket::new();
+                let bound = socket
+                    .bind(endpoint)
+                    .await
+                    .map_err(socket_error(endpoint))?;
+                tokio::spawn(send(socket, messages, failed_writes.clone()));
+                bound
+            }
+        };
+        Ok(ZmqEventChannel {
+            name: name.to_string(),
+            endpoint: bound.to_string(),
+            pattern,
+            sender,
+            failed_writes,
+            events: PhantomData,
+        })
+    }
+
+    /// Returns the endpoint the socket is bound to, its port resolved.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    pub fn pattern(&self) -> ZmqPattern {
+        self.pattern
+    }
+
+    /// Returns the number of the events pushed the encoding or sending of failed.
+    pub fn failed_writes(&self) -> u64 {
+        *self.failed_writes.lock().unwrap()
+    }
+
+    fn encode(&self, event: &T) -> Option<ZmqMessage> {
+        let mut message = ZmqMessage::from(serde_json::to_vec(event).ok()?);
+        if self.pattern == ZmqPattern::PubSub {
+            message.prepend(&ZmqMessage::from(self.name.as_str()));
+        }
+        Some(message)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> EventChannelTrait<T> for ZmqEventChannel<T> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The events failing to be encoded or sent, e.g. without peer, are counted, and dropped.
+    fn push(&self, event: T) {
+        match self.encode(&event).map(|message| self.sender.send(message)) {
+            Some(Ok(())) => {}
+            _ => *self.failed_writes.lock().unwrap() += 1,
+        }
+    }
+
+    /// The stream of a channel failing to be connected to is empty.
+    fn subscribe(&self) -> EventStream<T> {
+        let (sender, events) = mpsc::unbounded_channel();
+        let (name, endpoint, pattern) = (self.name.clone(), self.endpoint.clone(), self.pattern);
+        tokio::spawn(async move {
+            let _ = receive(&name, &endpoint, pattern, move |event| {
+                sender.send(event).is_ok()
+            })
+            .await;
+        });
+        Box::pin(UnboundedReceiverStream::new(events))
+    }
+
+    fn subscribe_from(&self, _last_seen: u64) -> EventStream<(u64, T)> {
+        let mut sequence = 0;
+        Box::pin(self.subscribe().map(move |event| {
+            sequence += 1;
+            (sequence, event)
+        }))
+    }
+}
+
+async fn send<S: SocketSend>(
+    mut socket: S,
+    mut messages: mpsc::UnboundedReceiver<ZmqMessage>,
+    failed_writes: Arc<Mutex<u64>>,
+) {
+    while let Some(message) = messages.recv().await {
+        if socket.send(message).await.is_err() {
+            *failed_writes.lock().unwrap() += 1;
+        }
+    }
+}
+
+/**
+ * Connects a SUB or PULL socket, after the pattern, to the endpoint of
+ * a channel, returning the stream of the events received, those failing
+ * to be decoded being dropped.
+ */
+pub async fn connect<T: DeserializeOwned + Send + 'static>(
+    name: &str,
+    endpoint: &str,
+    pattern: ZmqPattern,
+) -> Result<EventStream<T>> {
+    let (sender, events) = mpsc::unbounded_channel();
+    receive(name, endpoint, pattern, move |event| {
+        sender.send(event).is_ok()
+    })
+    .await?;
+    Ok(Box::pin(UnboundedReceiverStream::new(events)))
+}
+
+/**
+ * Connects to the endpoint of a channel as connect does, pushing the
+ * events received on a channel of the process, e.g. the one a BulkIO
+ * provides port receives from for a connection over ZeroMQ, in a task
+ * of the runtime.
+ */
+pub async fn connect_to<T: DeserializeOwned + Clone + Send + 'static>(
+    name: &str,
+    endpoint: &str,
+    pattern: ZmqPattern,
+    channel: &EventChannel<T>,
+) -> Result<JoinHandle<()>> {
+    let channel = channel.clone();
+    receive(name, endpoint, pattern, move |event| {
+        channel.push(event);
+        true
+    })
+    .await
+}
+
+async fn receive<T, F>(
+    name: &str,
+    endpoint: &str,
+    pattern: ZmqPattern,
+    deliver: F,
+) -> Result<JoinHandle<()>>
+where
+    T: DeserializeOwned + Send + 'static,
+    F: FnMut(T) -> bool + Send + 'static,
+{
+    Ok(match pattern {
+        ZmqPattern::PubSub => {
+            let mut socket = SubSocket::new();
+            socket
+                .connect(endpoint)
+                .await
+                .map_err(socket_error(endpoint))?;
+            socket
+                .subscribe(name)
+                .await
+                .map_err(socket_error(endpoint))?;
+            tokio::spawn(decode(socket, Some(name.to_string()), deliver))
+        }
+        ZmqPattern::PushPull => {
+            let mut socket = PullSocket::new();
+            socket
+                .connect(endpoint)
+                .await
+                .map_err(socket_error(endpoint))?;
+            tokio::spawn(decode(socket, None, deliver))
+        }
+    })
+}
+
+/// Delivers the events received until the socket fails or the delivery stops.
+async fn decode<S, T, F>(mut socket: S, topic: Option<String>, mut deliver: F)
+where
+    S: SocketRecv,
+    T: DeserializeOwned,
+    F: FnMut(T) -> bool,
+{
+    while let Ok(message) = socket.recv().await {
+        // ZeroMQ subscriptions match the topics by prefix
+        let payload = match &topic {
+            Some(topic) if message.get(0).map(|t| &t[..]) != Some(topic.as_bytes()) => continue,
+            Some(_) => message.get(1),
+            None => message.get(0),
+        };
+        let Some(event) = payload.and_then(|p| serde_json::from_slice(p).ok()) else {
+            continue;
+        };
+        if !deliver(event) {
+            break;
+        }
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use scars::cf::aggregate_device::{AggregateDevice, AggregateDeviceError, AggregateDeviceTrait};
+    use scars::cf::device::{AdminType, Device, DeviceTrait, OperationalType, UsageType};
+
+    #[test]
+    fn test_states_aggregation() {
+        let fpga = Arc::new(Mutex::new(Device::new("DCE:fpga", "fpga").with_composite_device("DCE:card")));
+        let dsp = Arc::new(Mutex::new(Device::new("DCE:dsp", "dsp").with_composite_device("DCE:card")));
+
+        let mut card = AggregateDevice::new(Device::new("DCE:card", "card"));
+        card.add_device(fpga.clone(), "DCE:fpga").unwrap();
+        card.add_device(dsp.clone(), "DCE:dsp").unwrap();
+        assert_eq!(card.devices().len(), 2);
+
+        fpga.lock().unwrap().set_usage_state(UsageType::BUSY);
+        assert_eq!(card.usage_state(), UsageType::ACTIVE);
+
+        dsp.lock().unwrap().set_operational_state(OperationalType::DISABLED);
+        assert_eq!(card.operational_state(), OperationalType::DISABLED);
+
+        card.set_admin_state(AdminType::LOCKED);
+        assert_eq!(dsp.lock().unwrap().admin_state(), AdminType::LOCKED);
+        assert_eq!(fpga.lock().unwrap().admin_state(), AdminType::SHUTTING_DOWN);
+        assert_eq!(card.admin_state(), AdminType::SHUTTING_DOWN);
+
+        fpga.lock().unwrap().set_usage_state(UsageType::IDLE);
+        assert_eq!(card.admin_state(), AdminType::LOCKED);
+    }
+
+    #[test]
+    fn test_remove_device() {
+        let fpga = Arc::new(Mutex::new(Device::new("DCE:fpga", "fpga")));
+
+        let mut card = AggregateDevice::new(Device::new("DCE:card", "card"));
+        match card.add_device(fpga.clone(), "DCE:dsp") {
+            Err(AggregateDeviceError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        card.add_device(fpga, "DCE:fpga").unwrap();
+        card.remove_device("DCE:fpga").unwrap();
+        assert!(card.devices().is_empty());
+        match card.remove_device("DCE:fpga") {
+            Err(AggregateDeviceError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    use scars::cf::allocation_guard::AllocationGuard;
+    use scars::cf::allocation_manager::{
+        AllocationManager, AllocationManagerRef, AllocationProperty, AllocationRequest,
+    };
+    use scars::cf::common_types::{ActionType, AnyValue, DataType};
+    use scars::cf::device::{Device, DeviceRef, UsageType};
+    use scars::cf::device_service::DeviceService;
+    use scars::cf::rpc::device::device_client::DeviceClient;
+    use scars::cf::rpc::device::device_server::DeviceServer;
+
+    fn dsp() -> DeviceRef {
+        Arc::new(Mutex::new(
+            Device::new("DCE:dsp", "dsp").with_capacity("mips", AnyValue::ULong(100)),
+        ))
+    }
+
+    fn mips(value: u32) -> Vec<DataType> {
+        vec![DataType::new("mips", AnyValue::ULong(value))]
+    }
+
+    fn usage_state(device: &DeviceRef) -> UsageType {
+        device.lock().unwrap().usage_state()
+    }
+
+    #[test]
+    fn test_device_guard() {
+        let device = dsp();
+
+        let guard = AllocationGuard::allocate_capacity(&device, &mips(60))
+            .unwrap()
+            .unwrap();
+        assert!(AllocationGuard::allocate_capacity(&device, &mips(60))
+            .unwrap()
+            .is_none());
+        drop(guard);
+        assert_eq!(usage_state(&device), UsageType::IDLE);
+
+        //committed allocations are kept
+        AllocationGuard::allocate_capacity(&device, &mips(60))
+            .unwrap()
+            .unwrap()
+            .commit();
+        assert_eq!(usage_state(&device), UsageType::ACTIVE);
+    }
+
+    #[test]
+    fn test_manager_guard() {
+        let device = dsp();
+        let mut manager = AllocationManager::new();
+        manager.register_device(device.clone());
+        let manager: AllocationManagerRef = Arc::new(Mutex::new(manager));
+
+        let request = AllocationRequest {
+            request_id: "r1".to_string(),
+            allocation_properties: vec![AllocationProperty::new(
+                DataType::new("mips", AnyValue::ULong(10)),
+                ActionType::EXTERNAL,
+            )],
+            ..Default::default()
+        };
+        let (responses, guard) = AllocationGuard::allocate(&manager, &[request]).unwrap();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(manager.lock().unwrap().list_allocations().len(), 1);
+
+        //a failing application creation unwinds through the guard
+        let unwound = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let _guard = guard;
+            panic!("application creation failed");
+        }));
+        assert!(unwound.is_err());
+        assert!(manager.lock().unwrap().list_allocations().is_empty());
+        assert_eq!(usage_state(&device), UsageType::IDLE);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remote_guard() {
+        let device = dsp();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(DeviceServer::new(DeviceService::new(device.clone())))
+                .serve_with_incoming(incoming),
+        );
+
+        let mut client = DeviceClient::connect(endpoint).await.unwrap();
+        let guard = AllocationGuard::allocate_remote(&mut client, &mips(100))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(usage_state(&device), UsageType::BUSY);
+
+        drop(guard);
+        for _ in 0..100 {
+            if usage_state(&device) == UsageType::IDLE {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("remote allocation not released");
+    }
+}
@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use scars::cf::allocation_manager::{
+        AllocationManager, AllocationManagerError, AllocationManagerTrait, AllocationProperty,
+        AllocationRequest,
+    };
+    use scars::cf::common_types::{ActionType, AnyValue, DataType};
+    use scars::cf::device::{AdminType, Device, DeviceTrait, OperationalType, UsageType};
+    use scars::cf::events::{DomainManagementEvent, EventChannel, ODM_CHANNEL_NAME};
+    use scars::cf::frontend_tuner::{
+        FrontendTunerDevice, TunerAllocation, TunerCapabilities, TunerType, TUNER_STATUS_ID,
+    };
+    use scars::cf::gpp::{Gpp, OS_NAME_ID, PROCESSOR_CORES_ID};
+
+    fn request(id: &str, cores: u32) -> AllocationRequest {
+        AllocationRequest {
+            request_id: id.to_string(),
+            allocation_properties: vec![
+                AllocationProperty::new(
+                    DataType::new(OS_NAME_ID, AnyValue::String("TempleOS".to_string())),
+                    ActionType::NE,
+                ),
+                AllocationProperty::new(
+                    DataType::new(PROCESSOR_CORES_ID, AnyValue::ULong(cores)),
+                    ActionType::EXTERNAL,
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn cores(gpp: &Arc<Mutex<Gpp>>) -> AnyValue {
+        let props = gpp.lock().unwrap().allocation_properties();
+        props.into_iter().find(|p| p.id == PROCESSOR_CORES_ID).unwrap().value
+    }
+
+    #[test]
+    fn test_allocate_across_devices() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp1 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp1", "gpp1"), cache.path(), 2, 1024, 100.0)));
+        let gpp2 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp2", "gpp2"), cache.path(), 4, 1024, 100.0)));
+
+        let mut am = AllocationManager::new();
+        am.register_device(gpp1.clone());
+        am.register_device(gpp2.clone());
+
+        let mut hinted = request("b", 1);
+        hinted.requested_devices = vec!["gpp2".to_string()];
+        let responses = am.allocate(&[request("a", 2), hinted]).unwrap();
+        assert_eq!(responses[0].allocated_device, "gpp1");
+        assert_eq!(responses[1].allocated_device, "gpp2");
+        assert_eq!(am.list_allocations().len(), 2);
+
+        am.deallocate(&[responses[0].allocation_id.clone()]).unwrap();
+        assert_eq!(am.list_allocations().len(), 1);
+        assert_eq!(cores(&gpp1), AnyValue::ULong(2));
+
+        match am.deallocate(&["unknown".to_string()]) {
+            Err(AllocationManagerError::InvalidAllocationId { invalid_allocation_ids }) => {
+                assert_eq!(invalid_allocation_ids, vec!["unknown".to_string()])
+            }
+            r => panic!("{:?}", r),
+        }
+
+        //candidate devices restrict the evaluation
+        let mut restricted = request("c", 3);
+        restricted.candidate_devices = vec!["gpp1".to_string()];
+        match am.allocate(&[restricted]) {
+            Err(AllocationManagerError::AllocationFailed { request_id, .. }) => assert_eq!(request_id, "c"),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_allocation_timeout() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp1 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp1", "gpp1"), cache.path(), 2, 1024, 100.0)));
+        let gpp2 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp2", "gpp2"), cache.path(), 4, 1024, 100.0)));
+
+        let mut am = AllocationManager::new().with_allocation_timeout(Duration::from_millis(100));
+        am.register_device(gpp1.clone());
+        am.register_device(gpp2.clone());
+
+        //a wedged device is passed over
+        let wedged = gpp1.lock().unwrap();
+        let started = Instant::now();
+        let responses = am.allocate(&[request("a", 1)]).unwrap();
+        assert_eq!(responses[0].allocated_device, "gpp2");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let mut hinted = request("b", 1);
+        hinted.candidate_devices = vec!["gpp1".to_string()];
+        match am.allocate(&[hinted]) {
+            Err(AllocationManagerError::AllocationTimedOut { request_id, message }) => assert_eq!((request_id.as_str(), message.as_str()), ("b", "[\"gpp1\"] did not answer within 100ms")),
+            r => panic!("{:?}", r),
+        }
+
+        //the capacities allocated once it answers are given back
+        drop(wedged);
+        std::thread::sleep(Duration::from_millis(200));
+        assert_eq!(cores(&gpp1), AnyValue::ULong(2));
+        assert_eq!(am.list_allocations().len(), 1);
+    }
+
+    #[test]
+    fn test_allocate_is_transactional() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp", "gpp"), cache.path(), 2, 1024, 100.0)));
+
+        let mut am = AllocationManager::new();
+        am.register_device(gpp.clone());
+
+        match am.allocate(&[request("a", 1), request("b", 4)]) {
+            Err(AllocationManagerError::AllocationFailed { request_id, .. }) => assert_eq!(request_id, "b"),
+            r => panic!("{:?}", r),
+        }
+        assert!(am.list_allocations().is_empty());
+        assert_eq!(cores(&gpp), AnyValue::ULong(2));
+    }
+
+    #[test]
+    fn test_capacity_model() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp1 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp1", "gpp1"), cache.path(), 2, 1024, 100.0)));
+        let gpp2 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp2", "gpp2"), cache.path(), 4, 1024, 100.0)));
+
+        let mut am = AllocationManager::new();
+        am.register_device(gpp1.clone());
+        am.register_device(gpp2.clone());
+        let outstanding = am.allocate(&[request("a", 1)]).unwrap();
+        am.set_available("gpp2", false);
+
+        //the copy starts from the outstanding allocations, the unavailable devices left out
+        let mut model = am.capacity_model();
+        assert_eq!(model.list_allocations(), am.list_allocations());
+        let responses = model.allocate(&[request("b", 1)]).unwrap();
+        assert_eq!(responses[0].allocated_device, "gpp1");
+        match model.allocate(&[request("c", 1)]) {
+            Err(AllocationManagerError::AllocationFailed { request_id, .. }) => assert_eq!(request_id, "c"),
+            r => panic!("{:?}", r),
+        }
+
+        //the capacities are given back to the copy, the devices never being allocated from
+        model.deallocate(&[responses[0].allocation_id.clone()]).unwrap();
+        assert!(model.allocate(&[request("c", 1)]).is_ok());
+        assert_eq!(model.device_capacities()[0].allocations.len(), 2);
+        assert_eq!(cores(&gpp1), AnyValue::ULong(1));
+        assert_eq!(cores(&gpp2), AnyValue::ULong(4));
+        assert_eq!(am.list_allocations().len(), 1);
+        am.deallocate(&[outstanding[0].allocation_id.clone()]).unwrap();
+    }
+
+    #[test]
+    fn test_device_capacities() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp1 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp1", "gpp1"), cache.path(), 2, 1024, 100.0)));
+        let gpp2 = Arc::new(Mutex::new(Gpp::with_capacities(Device::new("gpp2", "gpp2"), cache.path(), 4, 1024, 100.0)));
+        let mut am = AllocationManager::new();
+        am.register_device(gpp1.clone());
+        am.register_device(gpp2.clone());
+        let responses = am.allocate(&[AllocationRequest { source_id: "DCE:fm".to_string(), ..request("a", 2) }]).unwrap();
+
+        //the allocations are reported with the device they are made on, its capacities being current
+        let capacities = am.device_capacities();
+        let ids: Vec<&str> = capacities.iter().map(|c| c.identifier.as_str()).collect();
+        assert_eq!(ids, vec!["gpp1", "gpp2"]);
+        assert_eq!((capacities[0].admin_state, capacities[0].operational_state, capacities[0].usage_state), (AdminType::UNLOCKED, OperationalType::ENABLED, UsageType::BUSY));
+        assert_eq!(capacities[0].allocation_properties, gpp1.lock().unwrap().allocation_properties());
+        assert_eq!(capacities[0].allocations.len(), 1);
+        assert_eq!((capacities[0].allocations[0].allocation_id.as_str(), capacities[0].allocations[0].source_id.as_str()), (responses[0].allocation_id.as_str(), "DCE:fm"));
+        assert_eq!(capacities[0].allocations[0].allocated_capacities, vec![DataType::new(PROCESSOR_CORES_ID, AnyValue::ULong(2))]);
+        assert!(capacities[1].allocations.is_empty());
+        assert_eq!(capacities[1].usage_state, UsageType::IDLE);
+    }
+
+    #[test]
+    fn test_device_hot_plug() {
+        let cache = tempfile::tempdir().unwrap();
+        let sdr = || Arc::new(Mutex::new(Gpp::with_capacities(Device::new("usb-sdr", "sdr"), cache.path(), 2, 1024, 100.0)));
+        let odm = EventChannel::new(ODM_CHANNEL_NAME);
+        let events = odm.subscribe();
+
+        let mut am = AllocationManager::new().with_event_channel(odm, "DOMAIN");
+        am.register_device(sdr());
+        let responses = am.allocate(&[AllocationRequest { source_id: "app".to_string(), ..request("a", 1) }]).unwrap();
+
+        //the device vanishes: its allocation fails and the application is told
+        am.unregister_device("usb-sdr").unwrap();
+        assert!(am.list_allocations()[0].failed);
+        assert!(am.allocate(&[request("b", 1)]).is_err());
+
+        //the device is plugged back in, idle
+        let plugged = sdr();
+        am.register_device(plugged.clone());
+        am.deallocate(&[responses[0].allocation_id.clone()]).unwrap();
+        assert_eq!(plugged.lock().unwrap().usage_state(), UsageType::IDLE);
+
+        let received: Vec<DomainManagementEvent> = events.try_iter().collect();
+        assert!(matches!(received[0], DomainManagementEvent::ObjectAdded { .. }));
+        match &received[1] {
+            DomainManagementEvent::AllocationFailed { source_id, .. } => assert_eq!(source_id, "app"),
+            e => panic!("{:?}", e),
+        }
+        assert!(matches!(received[2], DomainManagementEvent::ObjectRemoved { .. }));
+        assert!(matches!(received[3], DomainManagementEvent::ObjectAdded { .. }));
+    }
+
+    #[test]
+    fn test_struct_allocation_properties() {
+        let capabilities = |rf_flow_id: &str| TunerCapabilities {
+            tuner_type: TunerType::RX_DIGITIZER,
+            min_frequency: 30e6,
+            max_frequency: 3e9,
+            min_bandwidth: 1e6,
+            max_bandwidth: 40e6,
+            min_sample_rate: 1.25e6,
+            max_sample_rate: 50e6,
+            group_id: String::new(),
+            rf_flow_id: rf_flow_id.to_string(),
+        };
+        let rx1 = Arc::new(Mutex::new(FrontendTunerDevice::new(Device::new("rx1", "rx1")).with_tuner(capabilities("ant1"))));
+        let rx2 = Arc::new(Mutex::new(FrontendTunerDevice::new(Device::new("rx2", "rx2")).with_tuner(capabilities("ant2"))));
+
+        let mut am = AllocationManager::new();
+        am.register_device(rx1.clone());
+        am.register_device(rx2.clone());
+
+        //a status struct selects the device, the unadvertised tuner allocation is passed through
+        let status = DataType::new(TUNER_STATUS_ID, AnyValue::Struct(vec![DataType::new(
+            &format!("{TUNER_STATUS_ID}::rf_flow_id"),
+            AnyValue::String("ant2".to_string()),
+        )]));
+        let tuner = TunerAllocation {
+            tuner_type: TunerType::RX_DIGITIZER,
+            allocation_id: "a".to_string(),
+            center_frequency: 100e6,
+            bandwidth: 5e6,
+            bandwidth_tolerance: 20.0,
+            sample_rate: 0.0,
+            sample_rate_tolerance: 10.0,
+            device_control: true,
+            group_id: String::new(),
+            rf_flow_id: String::new(),
+        };
+        let responses = am.allocate(&[AllocationRequest {
+            request_id: "tuner".to_string(),
+            allocation_properties: vec![
+                AllocationProperty::new(status, ActionType::EQ),
+                AllocationProperty::new(tuner.to_property(), ActionType::EQ),
+            ],
+            ..Default::default()
+        }]).unwrap();
+        assert_eq!(responses[0].allocated_device, "rx2");
+        assert_eq!(rx2.lock().unwrap().tuner_status()[0].allocation_ids, vec!["a"]);
+
+        am.deallocate(&[responses[0].allocation_id.clone()]).unwrap();
+        assert!(rx2.lock().unwrap().tuner_status()[0].allocation_ids.is_empty());
+    }
+}
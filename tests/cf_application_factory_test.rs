@@ -0,0 +1,833 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef};
+    use scars::cf::application_factory::{
+        ApplicationFactory, ApplicationFactoryError, DeploymentContext, DeviceAssignmentType, UsesDeviceAssignmentType,
+    };
+    use scars::cf::application::ComponentElementType;
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::device::{AdminType, Device, DeviceTrait};
+    use scars::cf::executable_device::{COMPONENT_IDENTIFIER, NAME_BINDING};
+    use scars::cf::file_manager::{FileManager, FileManagerRef};
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::gpp::{OS_NAME_ID, PROCESSOR_NAME_ID};
+    use scars::cf::profile::ProfileError;
+    use scars::cf::resource::{Resource, ResourceTrait};
+    use scars::cf::sim_device::{SimExecutableDevice, SimLoadableDevice, SimOperation};
+
+    const SAD: &str = r#"<softwareassembly id="DCE:fm" name="fm">
+  <componentfiles>
+    <componentfile id="source_file" type="SPD">
+      <localfile name="../../components/source/source.spd.xml"/>
+    </componentfile>
+    <componentfile id="demod_file" type="SPD">
+      <localfile name="../../components/demod/demod.spd.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="source_file"/>
+      <componentinstantiation id="source_1"/>
+    </componentplacement>
+    <componentplacement>
+      <componentfileref refid="demod_file"/>
+      <componentinstantiation id="demod_1">
+        <componentproperties>
+          <simpleref refid="frequency" value="88.5"/>
+          <simpleref refid="mode" value="stereo"/>
+        </componentproperties>
+      </componentinstantiation>
+    </componentplacement>
+  </partitioning>
+  <assemblycontroller>
+    <componentinstantiationref refid="demod_1"/>
+  </assemblycontroller>
+  <connections>
+    <connectinterface id="samples">
+      <usesport>
+        <usesidentifier>data_out</usesidentifier>
+        <componentinstantiationref refid="source_1"/>
+      </usesport>
+      <providesport>
+        <providesidentifier>data_in</providesidentifier>
+        <componentinstantiationref refid="demod_1"/>
+      </providesport>
+    </connectinterface>
+  </connections>
+</softwareassembly>"#;
+
+    const SOURCE_SPD: &str = r#"<softpkg id="DCE:source" name="source">
+  <implementation id="cpp">
+    <code type="Executable"><localfile name="cpp"/><entrypoint>cpp/source</entrypoint></code>
+  </implementation>
+</softpkg>"#;
+
+    const DEMOD_SPD: &str = r#"<softpkg id="DCE:demod" name="demod">
+  <implementation id="arm">
+    <code type="Executable"><localfile name="arm/demod"/></code>
+    <processor name="armv7"/>
+  </implementation>
+  <implementation id="x86">
+    <code type="Executable"><localfile name="x86/demod"/></code>
+    <processor name="x86_64"/>
+    <os name="Linux"/>
+  </implementation>
+</softpkg>"#;
+
+    const RADIO_SAD: &str = r#"<softwareassembly id="DCE:radio" name="radio">
+  <componentfiles>
+    <componentfile id="demod_file" type="SPD">
+      <localfile name="../../components/demod/demod.spd.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="demod_file"/>
+      <componentinstantiation id="demod_1"/>
+    </componentplacement>
+  </partitioning>
+  <assemblycontroller>
+    <componentinstantiationref refid="demod_1"/>
+  </assemblycontroller>
+  <externalports>
+    <port externalname="rf_in">
+      <providesidentifier>data_in</providesidentifier>
+      <componentinstantiationref refid="demod_1"/>
+    </port>
+  </externalports>
+</softwareassembly>"#;
+
+    const STATION_SAD: &str = r#"<softwareassembly id="DCE:station" name="station">
+  <componentfiles>
+    <componentfile id="source_file" type="SPD">
+      <localfile name="../../components/source/source.spd.xml"/>
+    </componentfile>
+    <componentfile id="radio_file" type="SAD">
+      <localfile name="../radio/radio.sad.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="source_file"/>
+      <componentinstantiation id="source_1"/>
+    </componentplacement>
+    <componentplacement>
+      <componentfileref refid="radio_file"/>
+      <componentinstantiation id="radio_1">
+        <componentproperties>
+          <simpleref refid="frequency" value="99.9"/>
+        </componentproperties>
+      </componentinstantiation>
+    </componentplacement>
+  </partitioning>
+  <connections>
+    <connectinterface id="rf">
+      <usesport>
+        <usesidentifier>data_out</usesidentifier>
+        <componentinstantiationref refid="source_1"/>
+      </usesport>
+      <providesport>
+        <providesidentifier>rf_in</providesidentifier>
+        <componentinstantiationref refid="radio_1"/>
+      </providesport>
+    </connectinterface>
+  </connections>
+</softwareassembly>"#;
+
+    fn write_waveform(root: &Path) {
+        let files = [
+            ("waveforms/fm/fm.sad.xml", SAD),
+            ("waveforms/radio/radio.sad.xml", RADIO_SAD),
+            ("waveforms/station/station.sad.xml", STATION_SAD),
+            ("components/source/source.spd.xml", SOURCE_SPD),
+            ("components/demod/demod.spd.xml", DEMOD_SPD),
+        ];
+        for (name, xml) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, xml).unwrap();
+        }
+    }
+
+    struct Domain {
+        factory: ApplicationFactory,
+        file_manager: FileManagerRef,
+        deployment: DeploymentContext,
+        device: Arc<Mutex<SimExecutableDevice>>,
+        allocation_manager: AllocationManagerRef,
+        registry: ComponentRegistry,
+        source: Arc<Mutex<Resource>>,
+        demod: Arc<Mutex<Resource>>,
+    }
+
+    /// Deploys the waveform on a simulated x86 device, its components registering beforehand.
+    fn domain(root: &Path, device: SimLoadableDevice) -> Domain {
+        let device = Arc::new(Mutex::new(SimExecutableDevice::new(device)));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(device.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+
+        let mut file_manager = FileManager::new();
+        file_manager
+            .mount("/dom", Arc::new(FileSystem::new(root)))
+            .unwrap();
+        let file_manager = Arc::new(Mutex::new(file_manager));
+
+        let registry = ComponentRegistry::new();
+        let source = Arc::new(Mutex::new(Resource::new("source").with_uses_port("data_out")));
+        let demod = Arc::new(Mutex::new(
+            Resource::new("demod")
+                .with_property("frequency", AnyValue::String("100.0".to_string()))
+                .with_property("mode", AnyValue::String("mono".to_string()))
+                .with_provides_port("data_in", "http://127.0.0.1:5001"),
+        ));
+        registry.register_component("fm_1/source_1", source.clone());
+        registry.register_component("fm_1/demod_1", demod.clone());
+
+        let deployment = DeploymentContext::new(file_manager.clone(), allocation_manager.clone(), registry.clone())
+            .with_device(device.clone())
+            .with_resolve_timeout(Duration::from_millis(50));
+        let factory = ApplicationFactory::load(&*file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml")
+            .unwrap()
+            .with_deployment(deployment.clone());
+
+        Domain {
+            factory,
+            file_manager,
+            deployment,
+            device,
+            allocation_manager,
+            registry,
+            source,
+            demod,
+        }
+    }
+
+    fn x86() -> Device {
+        Device::new("DCE:gpp", "gpp")
+            .with_allocation_property(PROCESSOR_NAME_ID, AnyValue::String("x86_64".to_string()))
+            .with_allocation_property(OS_NAME_ID, AnyValue::String("Linux".to_string()))
+    }
+
+    #[test]
+    fn test_create_application() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+
+        //the demod also serves its ports out of process
+        d.registry.register_endpoint("fm_1/demod_1", "http://127.0.0.1:6001");
+
+        let init_configuration = vec![DataType::new("frequency", AnyValue::String("101.1".to_string()))];
+        let mut application = d.factory.create("fm_1", &init_configuration, &[]).unwrap();
+        assert_eq!(application.identifier(), "DCE:fm:fm_1");
+        assert_eq!(application.profile(), "/dom/waveforms/fm/fm.sad.xml");
+        assert_eq!(application.allocation_ids().len(), 2);
+
+        //the first implementation whose dependencies are satisfied is deployed
+        let demod = application.component("demod_1").unwrap();
+        assert_eq!(demod.implementation_id, "x86");
+        assert_eq!(demod.device_id, "DCE:gpp");
+        assert_eq!(demod.loaded_file, "components/demod/x86/demod");
+        let source = application.component("source_1").unwrap();
+        assert_eq!(source.loaded_file, "components/source/cpp/source");
+        assert_eq!(demod.endpoint.as_deref(), Some("http://127.0.0.1:6001"));
+        assert_eq!(source.endpoint, None);
+
+        let (name, parameters) = d.device.lock().unwrap().process(demod.process_id.unwrap()).cloned().unwrap();
+        assert_eq!(name, "components/demod/x86/demod");
+        assert!(parameters.contains(&DataType::new(COMPONENT_IDENTIFIER, AnyValue::String("demod_1:DCE:fm:fm_1".to_string()))));
+        assert!(parameters.contains(&DataType::new(NAME_BINDING, AnyValue::String("fm_1/demod_1".to_string()))));
+
+        //the initial configuration prevails over the SAD values
+        let properties = d.demod.lock().unwrap().query(&vec![]).unwrap();
+        assert_eq!(
+            properties,
+            vec![
+                DataType::new("frequency", AnyValue::String("101.1".to_string())),
+                DataType::new("mode", AnyValue::String("stereo".to_string())),
+                DataType::new("LOG_LEVEL", AnyValue::UShort(8)),
+                DataType::new("LOGGING_CONFIG_URI", AnyValue::String(String::new())),
+            ]
+        );
+
+        assert_eq!(
+            d.source.lock().unwrap().connections("data_out"),
+            vec![("DCE:fm:fm_1/samples".to_string(), "http://127.0.0.1:5001".to_string())]
+        );
+        assert_eq!(application.connections().len(), 1);
+
+        application.release_object().unwrap();
+        assert!(d.source.lock().unwrap().connections("data_out").is_empty());
+        assert!(d.demod.lock().unwrap().released());
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+        assert_eq!(d.registry.endpoint("fm_1/demod_1"), None);
+        assert_eq!(d.device.lock().unwrap().loadable().load_count("components/demod/x86/demod"), 0);
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+        assert!(d.registry.names().is_empty());
+    }
+
+    #[test]
+    fn test_create_rollback() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+
+        //a failing execute releases the component already deployed
+        let d = domain(root.path(), SimLoadableDevice::new(x86()).with_failure(SimOperation::EXECUTE, 2));
+        match d.factory.create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => assert!(message.contains("demod"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+        let device = d.device.lock().unwrap();
+        assert!(device.process_ids().is_empty());
+        assert_eq!(device.loadable().load_count("components/source/cpp/source"), 0);
+        assert_eq!(device.loadable().load_count("components/demod/x86/demod"), 0);
+        drop(device);
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        //unknown init configuration properties
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let init_configuration = vec![DataType::new("gain", AnyValue::Double(1.0))];
+        match d.factory.create("fm_1", &init_configuration, &[]) {
+            Err(ApplicationFactoryError::InvalidInitConfiguration { invalid_properties }) => {
+                assert_eq!(invalid_properties, init_configuration)
+            }
+            r => panic!("{:?}", r),
+        }
+        assert!(d.demod.lock().unwrap().released());
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        //components not registering in time
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        match d.factory.create("fm_2", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => assert!(message.contains("fm_2/source_1"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+
+        //no device satisfies the dependencies
+        let d = domain(root.path(), SimLoadableDevice::new(Device::new("DCE:dsp", "dsp")));
+        match d.factory.create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        let assignment = DeviceAssignmentType {
+            component_id: "demod_1".to_string(),
+            assigned_device_id: "DCE:unknown".to_string(),
+        };
+        match d.factory.create("fm_1", &vec![], std::slice::from_ref(&assignment)) {
+            Err(ApplicationFactoryError::CreateApplicationRequestError { invalid_assignments }) => {
+                assert_eq!(invalid_assignments, vec![assignment])
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_nested_application() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        d.registry.register_component("station_1/source_1", d.source.clone());
+        d.registry.register_component("station_1/radio_1/demod_1", d.demod.clone());
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/station/station.sad.xml").unwrap();
+        let factory = factory.with_deployment(d.deployment.clone());
+        assert_eq!(factory.nested_assembly("radio_file").unwrap().factory.identifier(), "DCE:radio");
+
+        //the components of nested assemblies are assigned through the instantiation
+        let assignment = DeviceAssignmentType {
+            component_id: "radio_1/demod_2".to_string(),
+            assigned_device_id: "DCE:gpp".to_string(),
+        };
+        match factory.create("station_1", &vec![], std::slice::from_ref(&assignment)) {
+            Err(ApplicationFactoryError::CreateApplicationRequestError { invalid_assignments }) => {
+                assert_eq!(invalid_assignments, vec![assignment])
+            }
+            r => panic!("{:?}", r),
+        }
+        let assignment = DeviceAssignmentType {
+            component_id: "radio_1/demod_1".to_string(),
+            assigned_device_id: "DCE:gpp".to_string(),
+        };
+        let mut application = factory.create("station_1", &vec![], &[assignment]).unwrap();
+
+        //the nested application is configured by the componentproperties
+        let radio = application.application("radio_1").unwrap();
+        assert_eq!(radio.identifier(), "DCE:radio:station_1/radio_1");
+        assert_eq!(radio.component("demod_1").unwrap().name_binding, "station_1/radio_1/demod_1");
+        let frequency = vec![DataType::new("frequency", AnyValue::String(String::new()))];
+        assert_eq!(d.demod.lock().unwrap().query(&frequency).unwrap()[0].value, AnyValue::String("99.9".to_string()));
+
+        //the connection reaches the component behind the external port
+        assert_eq!(
+            d.source.lock().unwrap().connections("data_out"),
+            vec![("DCE:station:station_1/rf".to_string(), "http://127.0.0.1:5001".to_string())]
+        );
+        application.start().unwrap();
+        assert!(d.demod.lock().unwrap().started());
+        assert_eq!(d.device.lock().unwrap().process_ids().len(), 2);
+
+        application.release_object().unwrap();
+        assert!(application.applications().is_empty());
+        assert!(d.source.lock().unwrap().connections("data_out").is_empty());
+        assert!(d.demod.lock().unwrap().released());
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+        assert_eq!(d.registry.names(), vec!["fm_1/demod_1", "fm_1/source_1"]);
+    }
+
+    #[test]
+    fn test_nested_recursion() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let loop_sad = STATION_SAD.replace("../radio/radio.sad.xml", "station.sad.xml");
+        std::fs::write(root.path().join("waveforms/station/station.sad.xml"), loop_sad).unwrap();
+
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let loaded = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/station/station.sad.xml");
+        match loaded {
+            Err(ProfileError::InvalidProfile { message, .. }) => assert!(message.contains("nests itself"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_host_collocation() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let collocated = SAD
+            .replace("<partitioning>", "<partitioning><hostcollocation name=\"rf_chain\">")
+            .replace("</partitioning>", "</hostcollocation></partitioning>");
+        std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), collocated).unwrap();
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+
+        //node_a runs no demod implementation, node_b runs both components
+        let dsp = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(Device::new("DCE:dsp", "dsp")))));
+        let gpp = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(x86()))));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(dsp.clone());
+        allocation_manager.register_device(gpp.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let deployment = DeploymentContext::new(d.file_manager.clone(), allocation_manager.clone(), d.registry.clone())
+            .with_host_device(dsp.clone(), "node_a")
+            .with_host_device(gpp.clone(), "node_b")
+            .with_resolve_timeout(Duration::from_millis(50));
+        assert_eq!(deployment.host("DCE:gpp"), "node_b");
+        let factory = d.factory.clone().with_deployment(deployment.clone());
+
+        //verify the assignments of the collocated components share a host
+        let assignments = [
+            DeviceAssignmentType { component_id: "source_1".to_string(), assigned_device_id: "DCE:dsp".to_string() },
+            DeviceAssignmentType { component_id: "demod_1".to_string(), assigned_device_id: "DCE:gpp".to_string() },
+        ];
+        match factory.create("fm_1", &vec![], &assignments) {
+            Err(ApplicationFactoryError::CreateApplicationRequestError { invalid_assignments }) => {
+                assert_eq!(invalid_assignments, vec![assignments[1].clone()])
+            }
+            r => panic!("{:?}", r),
+        }
+
+        //the source fitting on node_a is moved to node_b along with the demod
+        let mut application = factory.create("fm_1", &vec![], &[]).unwrap();
+        assert_eq!(application.component("source_1").unwrap().device_id, "DCE:gpp");
+        assert_eq!(application.component("demod_1").unwrap().device_id, "DCE:gpp");
+        assert!(dsp.lock().unwrap().process_ids().is_empty());
+        assert_eq!(allocation_manager.lock().unwrap().list_allocations().len(), 2);
+        application.release_object().unwrap();
+        assert!(allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        //the source only runs on node_a, the demod only on node_b
+        let source_spd = SOURCE_SPD.replace("</code>", "</code><os name=\"VxWorks\"/>");
+        std::fs::write(root.path().join("components/source/source.spd.xml"), source_spd).unwrap();
+        let vx = Device::new("DCE:vx", "vx").with_allocation_property(OS_NAME_ID, AnyValue::String("VxWorks".to_string()));
+        let vx = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(vx))));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(vx.clone());
+        allocation_manager.register_device(gpp.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let deployment = DeploymentContext::new(d.file_manager.clone(), allocation_manager.clone(), d.registry.clone())
+            .with_host_device(vx, "node_a")
+            .with_host_device(gpp, "node_b");
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap();
+        match factory.with_deployment(deployment).create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => {
+                assert!(message.contains("hostcollocation 'rf_chain'"), "{message}");
+                assert!(message.contains("host 'node_a': ") && message.contains("host 'node_b': "), "{message}");
+            }
+            r => panic!("{:?}", r),
+        }
+        assert!(allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        //the source fitting on both devices of the host is moved off the one the demod needs
+        let lanes = |lanes: u32| format!("</code><dependency type=\"allocation\"><propertyref refid=\"simd_lanes\" value=\"{lanes}\"/></dependency>");
+        std::fs::write(root.path().join("components/source/source.spd.xml"), SOURCE_SPD.replace("</code>", &lanes(4))).unwrap();
+        std::fs::write(root.path().join("components/demod/demod.spd.xml"), DEMOD_SPD.replace("</code>\n    <processor name=\"x86_64\"/>", &format!("{}\n    <processor name=\"x86_64\"/>", lanes(8)))).unwrap();
+        let device = |id: &str, lanes: u32| Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(Device::new(id, id).with_allocation_property(PROCESSOR_NAME_ID, AnyValue::String("x86_64".to_string())).with_allocation_property(OS_NAME_ID, AnyValue::String("Linux".to_string())).with_capacity("simd_lanes", AnyValue::ULong(lanes))))));
+        let (wide, narrow) = (device("DCE:wide", 8), device("DCE:narrow", 4));
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(wide.clone());
+        allocation_manager.register_device(narrow.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let deployment = DeploymentContext::new(d.file_manager.clone(), allocation_manager.clone(), d.registry.clone())
+            .with_host_device(wide.clone(), "node_a")
+            .with_host_device(narrow.clone(), "node_a")
+            .with_resolve_timeout(Duration::from_millis(50));
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap().with_deployment(deployment);
+        let plan = factory.validate("fm_1", &[]).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        let mut application = factory.create("fm_1", &vec![], &[]).unwrap();
+        assert_eq!(application.component("source_1").unwrap().device_id, "DCE:narrow");
+        assert_eq!(application.component("demod_1").unwrap().device_id, "DCE:wide");
+        assert_eq!(wide.lock().unwrap().loadable().device().available_capacity("simd_lanes"), Some(&AnyValue::ULong(0)));
+        application.release_object().unwrap();
+        assert!(allocation_manager.lock().unwrap().list_allocations().is_empty());
+        assert_eq!(narrow.lock().unwrap().loadable().device().available_capacity("simd_lanes"), Some(&AnyValue::ULong(4)));
+    }
+
+    #[test]
+    fn test_device_assignments() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let gpp_2 = Device::new("DCE:gpp_2", "gpp_2")
+            .with_allocation_property(PROCESSOR_NAME_ID, AnyValue::String("x86_64".to_string()))
+            .with_allocation_property(OS_NAME_ID, AnyValue::String("Linux".to_string()));
+        let gpp_2 = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(gpp_2))));
+        let dsp = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(Device::new("DCE:dsp", "dsp")))));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(d.device.clone());
+        allocation_manager.register_device(gpp_2.clone());
+        allocation_manager.register_device(dsp.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let deployment = DeploymentContext::new(d.file_manager.clone(), allocation_manager.clone(), d.registry.clone())
+            .with_device(d.device.clone())
+            .with_device(gpp_2.clone())
+            .with_device(dsp);
+        let factory = d.factory.clone().with_deployment(deployment);
+
+        //the assigned component is placed on its device only
+        let assignment = DeviceAssignmentType {
+            component_id: "demod_1".to_string(),
+            assigned_device_id: "DCE:gpp_2".to_string(),
+        };
+        let mut application = factory.create("fm_1", &vec![], std::slice::from_ref(&assignment)).unwrap();
+        assert_eq!(application.component("demod_1").unwrap().device_id, "DCE:gpp_2");
+        assert_eq!(application.component("source_1").unwrap().device_id, "DCE:gpp");
+        application.release_object().unwrap();
+
+        //verify the assigned device satisfies the dependencies of the component
+        let assignment = DeviceAssignmentType {
+            component_id: "demod_1".to_string(),
+            assigned_device_id: "DCE:dsp".to_string(),
+        };
+        match factory.create("fm_1", &vec![], std::slice::from_ref(&assignment)) {
+            Err(ApplicationFactoryError::CreateApplicationRequestError { invalid_assignments }) => {
+                assert_eq!(invalid_assignments, vec![assignment])
+            }
+            r => panic!("{:?}", r),
+        }
+
+        //verify the assigned device is available
+        gpp_2.lock().unwrap().set_admin_state(AdminType::LOCKED);
+        let assignment = DeviceAssignmentType {
+            component_id: "demod_1".to_string(),
+            assigned_device_id: "DCE:gpp_2".to_string(),
+        };
+        match factory.create("fm_1", &vec![], std::slice::from_ref(&assignment)) {
+            Err(ApplicationFactoryError::CreateApplicationRequestError { invalid_assignments }) => {
+                assert_eq!(invalid_assignments, vec![assignment])
+            }
+            r => panic!("{:?}", r),
+        }
+        assert!(allocation_manager.lock().unwrap().list_allocations().is_empty());
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+    }
+
+    #[test]
+    fn test_uses_devices() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let sad = SAD.replace(
+            "</softwareassembly>",
+            r#"<usesdevicedependencies><usesdevice id="rf" type="usesdevice">
+            <propertyref refid="device_kind" value="TUNER"/></usesdevice></usesdevicedependencies></softwareassembly>"#,
+        );
+        std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), sad).unwrap();
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap();
+
+        //no device satisfies the usesdevice
+        match factory.clone().with_deployment(d.deployment.clone()).create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => assert!(message.contains("usesdevice 'rf'"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+
+        //the used device is allocated along with the components
+        let tuner = Device::new("DCE:tuner", "tuner").with_allocation_property("device_kind", AnyValue::String("TUNER".to_string()));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(d.device.clone());
+        allocation_manager.register_device(Arc::new(Mutex::new(tuner)));
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let deployment = DeploymentContext::new(d.file_manager.clone(), allocation_manager.clone(), d.registry.clone())
+            .with_device(d.device.clone())
+            .with_resolve_timeout(Duration::from_millis(50));
+        let mut application = factory.with_deployment(deployment).create("fm_1", &vec![], &[]).unwrap();
+        assert_eq!(
+            application.uses_devices(),
+            [UsesDeviceAssignmentType {
+                uses_device_id: "rf".to_string(),
+                assigned_device_id: "DCE:tuner".to_string(),
+            }]
+        );
+        assert_eq!(application.allocation_ids().len(), 3);
+        application.release_object().unwrap();
+        assert!(allocation_manager.lock().unwrap().list_allocations().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+
+        //the plan is that of create, nothing being loaded nor executed
+        let plan = d.factory.validate("fm_1", &[]).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert_eq!(
+            plan.component_implementations,
+            vec![
+                ComponentElementType { component_id: "source_1".to_string(), element_id: "cpp".to_string() },
+                ComponentElementType { component_id: "demod_1".to_string(), element_id: "x86".to_string() },
+            ]
+        );
+        assert!(plan.device_assignments.iter().all(|a| a.assigned_device_id == "DCE:gpp"));
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+        assert_eq!(d.device.lock().unwrap().loadable().load_count("components/demod/x86/demod"), 0);
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+        assert_eq!(d.device.lock().unwrap().loadable().script().calls(SimOperation::ALLOCATE), 0);
+
+        //the components of nested assemblies are reported with their path
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/station/station.sad.xml").unwrap();
+        let plan = factory.with_deployment(d.deployment.clone()).validate("station_1", &[]).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert!(plan.device_assignments.iter().any(|a| a.component_id == "radio_1/demod_1"));
+
+        //every problem is reported
+        let sad = SAD.replace(
+            "</softwareassembly>",
+            r#"<usesdevicedependencies><usesdevice id="rf" type="usesdevice">
+            <propertyref refid="device_kind" value="TUNER"/></usesdevice></usesdevicedependencies></softwareassembly>"#,
+        );
+        std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), sad).unwrap();
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap();
+        let assignment = DeviceAssignmentType {
+            component_id: "demod_1".to_string(),
+            assigned_device_id: "DCE:unknown".to_string(),
+        };
+        let plan = factory.clone().with_deployment(d.deployment.clone()).validate("fm_1", &[assignment]).unwrap();
+        assert!(!plan.is_valid());
+        assert_eq!(plan.errors.len(), 2, "{:?}", plan.errors);
+        assert!(plan.errors[0].contains("demod_1"), "{}", plan.errors[0]);
+        assert!(plan.errors[1].contains("usesdevice 'rf'"), "{}", plan.errors[1]);
+        assert_eq!(plan.device_assignments.len(), 2);
+        assert!(plan.uses_devices.is_empty());
+        assert!(d.allocation_manager.lock().unwrap().list_allocations().is_empty());
+
+        match factory.validate("fm_1", &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_implementation_selection() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let spd = r#"<softpkg id="DCE:demod" name="demod">
+  <implementation id="generic">
+    <code type="Executable"><localfile name="generic/demod"/></code>
+  </implementation>
+  <implementation id="arm">
+    <code type="Executable"><localfile name="arm/demod"/></code>
+    <processor name="armv7"/>
+  </implementation>
+  <implementation id="x86">
+    <code type="Executable"><localfile name="x86/demod"/></code>
+    <processor name="x86_64"/>
+    <os name="Linux"/>
+  </implementation>
+  <implementation id="x86_simd">
+    <code type="Executable"><localfile name="x86_simd/demod"/></code>
+    <processor name="x86_64"/>
+    <os name="Linux"/>
+    <dependency type="allocation"><propertyref refid="simd_lanes" value="8"/></dependency>
+  </implementation>
+</softpkg>"#;
+        std::fs::write(root.path().join("components/demod/demod.spd.xml"), spd).unwrap();
+
+        //the best match is chosen, not the first one, the others being reported
+        let d = domain(root.path(), SimLoadableDevice::new(x86().with_capacity("simd_lanes", AnyValue::ULong(4))));
+        let plan = d.factory.validate("fm_1", &[]).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert!(plan.component_implementations.iter().any(|c| c.component_id == "demod_1" && c.element_id == "x86"));
+        assert_eq!(
+            plan.rejected_implementations,
+            vec![
+                "demod_1: 'arm' on 'DCE:gpp': 'processor_name' is x86_64, armv7 required".to_string(),
+                "demod_1: 'x86_simd' on 'DCE:gpp': 'simd_lanes' is 4, 8 required".to_string(),
+            ]
+        );
+
+        //the numeric dependencies are capacities allocated from the device, validate leaving them untouched
+        let d = domain(root.path(), SimLoadableDevice::new(x86().with_capacity("simd_lanes", AnyValue::ULong(12))));
+        let plan = d.factory.validate("fm_1", &[]).unwrap();
+        assert!(plan.component_implementations.iter().any(|c| c.component_id == "demod_1" && c.element_id == "x86_simd"));
+        assert_eq!(d.device.lock().unwrap().loadable().script().calls(SimOperation::ALLOCATE), 0);
+        assert_eq!(d.device.lock().unwrap().loadable().device().available_capacity("simd_lanes"), Some(&AnyValue::ULong(12)));
+        let application = d.factory.create("fm_1", &vec![], &[]).unwrap();
+        assert_eq!(d.device.lock().unwrap().loadable().load_count("components/demod/x86_simd/demod"), 1);
+        assert_eq!(d.device.lock().unwrap().loadable().device().available_capacity("simd_lanes"), Some(&AnyValue::ULong(4)));
+        drop(application);
+
+        //every rejection is told when no implementation fits
+        let generic = "  <implementation id=\"generic\">\n    <code type=\"Executable\"><localfile name=\"generic/demod\"/></code>\n  </implementation>\n";
+        assert!(spd.contains(generic));
+        std::fs::write(root.path().join("components/demod/demod.spd.xml"), spd.replace(generic, "")).unwrap();
+        let d = domain(root.path(), SimLoadableDevice::new(Device::new("DCE:gpp", "gpp").with_allocation_property(PROCESSOR_NAME_ID, AnyValue::String("riscv64".to_string()))));
+        match d.factory.validate("fm_1", &[]) {
+            Ok(plan) => {
+                let error = plan.errors.iter().find(|e| e.contains("demod_1")).unwrap();
+                assert!(error.contains("'arm' on 'DCE:gpp': 'processor_name' is riscv64, armv7 required"), "{error}");
+                assert!(error.contains("'x86' on 'DCE:gpp': 'processor_name' is riscv64, x86_64 required"), "{error}");
+                assert!(error.contains("'x86_simd' on 'DCE:gpp'"), "{error}");
+                assert!(plan.rejected_implementations.is_empty());
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_component_properties() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let prf = r#"<properties>
+  <simple id="frequency" type="double">
+    <value>100.0</value>
+    <range min="87.5" max="108.0"/>
+    <kind kindtype="configure"/>
+  </simple>
+  <simple id="mode" type="string">
+    <value>mono</value>
+    <enumerations>
+      <enumeration label="MONO" value="mono"/>
+      <enumeration label="STEREO" value="stereo"/>
+    </enumerations>
+    <kind kindtype="configure"/>
+  </simple>
+  <simple id="status" type="string" mode="readonly">
+    <value>idle</value>
+    <kind kindtype="configure"/>
+  </simple>
+  <simple id="log_level" type="ulong">
+    <value>3</value>
+    <kind kindtype="execparam"/>
+  </simple>
+</properties>"#;
+        let spd = DEMOD_SPD.replace("<implementation id=\"arm\">", "<propertyfile type=\"PRF\"><localfile name=\"demod.prf.xml\"/></propertyfile>\n  <implementation id=\"arm\">");
+        std::fs::write(root.path().join("components/demod/demod.spd.xml"), spd).unwrap();
+        std::fs::write(root.path().join("components/demod/demod.prf.xml"), prf).unwrap();
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+
+        //the SAD overrides the defaults, the initial configuration overrides the SAD, the values being typed after the PRF
+        let init_configuration = vec![
+            DataType::new("frequency", AnyValue::String("101.1".to_string())),
+            DataType::new("log_level", AnyValue::String("5".to_string())),
+        ];
+        let application = d.factory.create("fm_1", &init_configuration, &[]).unwrap();
+        let configured = d.demod.lock().unwrap().query(&vec![]).unwrap();
+        assert!(configured.contains(&DataType::new("frequency", AnyValue::Double(101.1))), "{configured:?}");
+        assert!(configured.contains(&DataType::new("mode", AnyValue::String("stereo".to_string()))), "{configured:?}");
+
+        //the execparams are execute parameters, not configured
+        let device = d.device.lock().unwrap();
+        let component = application.component("demod_1").unwrap();
+        let (_, parameters) = device.process(component.process_id.unwrap()).unwrap();
+        assert!(parameters.contains(&DataType::new("log_level", AnyValue::ULong(5))), "{parameters:?}");
+        assert!(!parameters.iter().any(|p| p.id == "frequency" || p.id == "status"));
+        drop(device);
+        drop(application);
+
+        //the initial configuration is verified against the PRF before configuring anything
+        for invalid in [
+            DataType::new("frequency", AnyValue::String("120.0".to_string())),
+            DataType::new("status", AnyValue::String("busy".to_string())),
+            DataType::new("volume", AnyValue::Double(1.0)),
+        ] {
+            let d = domain(root.path(), SimLoadableDevice::new(x86()));
+            let init_configuration = vec![DataType::new("mode", AnyValue::String("MONO".to_string())), invalid.clone()];
+            match d.factory.create("fm_1", &init_configuration, &[]) {
+                Err(ApplicationFactoryError::InvalidInitConfiguration { invalid_properties }) => assert_eq!(invalid_properties, vec![invalid]),
+                r => panic!("{:?}", r),
+            }
+            assert!(d.device.lock().unwrap().process_ids().is_empty());
+        }
+
+        //the componentproperties are verified by validate
+        let sad = SAD.replace(r#"<simpleref refid="mode" value="stereo"/>"#, r#"<simpleref refid="mode" value="stereo"/><simpleref refid="status" value="busy"/>"#);
+        std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), sad).unwrap();
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let plan = d.factory.validate("fm_1", &[]).unwrap();
+        assert_eq!(plan.errors.len(), 1, "{:?}", plan.errors);
+        assert!(plan.errors[0].contains("'status' is neither an execparam nor configurable"), "{}", plan.errors[0]);
+    }
+
+    #[test]
+    fn test_connection_targets() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let provides = "<providesport>\n        <providesidentifier>data_in</providesidentifier>\n        <componentinstantiationref refid=\"demod_1\"/>\n      </providesport>";
+        assert!(SAD.contains(provides));
+
+        //the uses port is connected to the endpoint of the target
+        let targets = [
+            (
+                r#"<componentsupportedinterface><supportedidentifier>IDL:CF/Resource:1.0</supportedidentifier><componentinstantiationref refid="demod_1"/></componentsupportedinterface>"#,
+                "http://127.0.0.1:6001",
+            ),
+            (r#"<findby><namingservice name="audio_sink"/></findby>"#, "http://127.0.0.1:6002"),
+            (r#"<findby><stringifiedobjectref>http://127.0.0.1:6003</stringifiedobjectref></findby>"#, "http://127.0.0.1:6003"),
+        ];
+        for (target, endpoint) in targets {
+            let d = domain(root.path(), SimLoadableDevice::new(x86()));
+            d.registry.register_endpoint("fm_1/demod_1", "http://127.0.0.1:6001");
+            d.registry.register_endpoint("audio_sink", "http://127.0.0.1:6002");
+            std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), SAD.replace(provides, target)).unwrap();
+            let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap();
+            let mut application = factory.with_deployment(d.deployment.clone()).create("fm_1", &vec![], &[]).unwrap();
+            assert_eq!(d.source.lock().unwrap().connections("data_out"), vec![("DCE:fm:fm_1/samples".to_string(), endpoint.to_string())]);
+            assert_eq!(application.connections()[0].endpoint, endpoint);
+            application.release_object().unwrap();
+        }
+
+        //the unresolved targets fail the creation
+        let d = domain(root.path(), SimLoadableDevice::new(x86()));
+        let target = r#"<findby><namingservice name="unknown"/></findby>"#;
+        std::fs::write(root.path().join("waveforms/fm/fm.sad.xml"), SAD.replace(provides, target)).unwrap();
+        let factory = ApplicationFactory::load(&*d.file_manager.lock().unwrap(), "/dom/waveforms/fm/fm.sad.xml").unwrap();
+        match factory.with_deployment(d.deployment.clone()).create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => assert!(message.contains("'unknown' not registered"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+        assert!(d.device.lock().unwrap().process_ids().is_empty());
+    }
+}
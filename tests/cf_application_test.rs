@@ -0,0 +1,381 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef};
+    use scars::cf::application::{ApplicationError, ComponentElementType, ComponentOutcome, ComponentReport};
+    use scars::cf::application_factory::{ApplicationFactory, ApplicationFactoryError, DeploymentContext};
+    use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType, Properties};
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::device::Device;
+    use scars::cf::executable_device::{ExecutableDeviceTrait, ProcessStatus};
+    use scars::cf::file_manager::FileManager;
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::profile::sad::PortKind;
+    use scars::cf::resource::{self, Resource, ResourceError, ResourceTrait};
+    use scars::cf::sim_device::{SimExecutableDevice, SimLoadableDevice};
+
+    const SAD: &str = r#"<softwareassembly id="DCE:fm" name="fm">
+  <componentfiles>
+    <componentfile id="comp_file" type="SPD"><localfile name="comp.spd.xml"/></componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="comp_file"/>
+      <componentinstantiation id="sink_1"/>
+      <componentinstantiation id="demod_1" startorder="2"/>
+      <componentinstantiation id="source_1" startorder="1"/>
+      <componentinstantiation id="controller_1" startorder="0"/>
+    </componentplacement>
+  </partitioning>
+  <assemblycontroller>
+    <componentinstantiationref refid="controller_1"/>
+  </assemblycontroller>
+  <externalports>
+    <port externalname="audio">
+      <usesidentifier>audio_out</usesidentifier>
+      <componentinstantiationref refid="sink_1"/>
+    </port>
+    <port>
+      <providesidentifier>data_in</providesidentifier>
+      <componentinstantiationref refid="demod_1"/>
+    </port>
+  </externalports>
+  <externalproperties>
+    <property comprefid="demod_1" propid="frequency" externalpropid="tuned_frequency"/>
+  </externalproperties>
+</softwareassembly>"#;
+
+    const SPD: &str = r#"<softpkg id="DCE:comp" name="comp">
+  <implementation id="cpp"><code type="Executable"><localfile name="comp"/></code></implementation>
+</softpkg>"#;
+
+    /**
+     * Resource logging its start and stop calls, the failing one failing
+     * them and the slow one outlasting the component timeout.
+     */
+    struct Recorder {
+        resource: Resource,
+        log: Arc<Mutex<Vec<String>>>,
+        failing: bool,
+        slow: bool,
+        slow_initialize: bool,
+        panicking: bool,
+    }
+
+    impl ResourceTrait for Recorder {
+        fn identifier(&self) -> &str {
+            self.resource.identifier()
+        }
+        fn started(&self) -> bool {
+            self.resource.started()
+        }
+        fn initialize(&mut self) -> resource::Result<()> {
+            if self.slow_initialize {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            self.resource.initialize()
+        }
+        fn release_object(&mut self) -> resource::Result<()> {
+            self.resource.release_object()
+        }
+        fn configure(&mut self, properties: &Properties) -> resource::Result<()> {
+            self.resource.configure(properties)
+        }
+        fn query(&self, properties: &Properties) -> resource::Result<Properties> {
+            self.resource.query(properties)
+        }
+        fn start(&mut self) -> resource::Result<()> {
+            if self.failing {
+                return Err(ResourceError::StartError {
+                    error_number: ErrorNumberType::CF_EIO,
+                    message: "no signal".to_string(),
+                });
+            }
+            if self.slow {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            self.log.lock().unwrap().push(format!("start {}", self.identifier()));
+            self.resource.start()
+        }
+        fn stop(&mut self) -> resource::Result<()> {
+            if self.panicking {
+                panic!("stuck relay");
+            }
+            if self.failing {
+                return Err(ResourceError::StopError {
+                    error_number: ErrorNumberType::CF_EIO,
+                    message: "stuck".to_string(),
+                });
+            }
+            self.log.lock().unwrap().push(format!("stop {}", self.identifier()));
+            self.resource.stop()
+        }
+        fn get_provides_port(&self, name: &str) -> resource::Result<String> {
+            self.resource.get_provides_port(name)
+        }
+        fn connect_uses_port(&mut self, name: &str, connection_id: &str, endpoint: &str) -> resource::Result<()> {
+            self.resource.connect_uses_port(name, connection_id, endpoint)
+        }
+        fn disconnect_port(&mut self, name: &str, connection_id: &str) -> resource::Result<()> {
+            self.resource.disconnect_port(name, connection_id)
+        }
+    }
+
+    /// Returns a factory of the waveform whose components log into the log, and the device executing them.
+    fn factory(root: &Path, log: &Arc<Mutex<Vec<String>>>, failing: &str, slow: &str) -> (ApplicationFactory, Arc<Mutex<SimExecutableDevice>>) {
+        std::fs::write(root.join("fm.sad.xml"), SAD).unwrap();
+        std::fs::write(root.join("comp.spd.xml"), SPD).unwrap();
+
+        let device = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(Device::new("DCE:gpp", "gpp")))));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(device.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let mut file_manager = FileManager::new();
+        file_manager.mount("/dom", Arc::new(FileSystem::new(root))).unwrap();
+        let file_manager = Arc::new(Mutex::new(file_manager));
+
+        let registry = ComponentRegistry::new();
+        for id in ["sink_1", "demod_1", "source_1", "controller_1"] {
+            let recorder = Recorder {
+                resource: Resource::new(id)
+                    .with_property("frequency", AnyValue::String("100.0".to_string()))
+                    .with_property("gain", AnyValue::Long(1))
+                    .with_uses_port("audio_out")
+                    .with_provides_port("data_in", &format!("http://{id}")),
+                log: log.clone(),
+                failing: id == failing,
+                slow: id == slow,
+                slow_initialize: slow == format!("{id}.initialize"),
+                panicking: slow == format!("{id}.panic"),
+            };
+            registry.register_component(&format!("fm_1/{id}"), Arc::new(Mutex::new(recorder)));
+        }
+
+        let deployment = DeploymentContext::new(file_manager.clone(), allocation_manager, registry)
+            .with_device(device.clone())
+            //a panic is given the time to capture its backtrace
+            .with_component_timeout(Duration::from_millis(if slow.ends_with(".panic") { 5000 } else { 50 }));
+        let factory = ApplicationFactory::load(&*file_manager.lock().unwrap(), "/dom/fm.sad.xml").unwrap();
+        (factory.with_deployment(deployment), device)
+    }
+
+    #[test]
+    fn test_application() {
+        let root = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut application = factory(root.path(), &log, "", "").0.create("fm_1", &vec![], &[]).unwrap();
+
+        assert_eq!(application.component_devices().len(), 4);
+        assert_eq!(
+            application.component_devices()[0],
+            ComponentElementType {
+                component_id: "sink_1:DCE:fm:fm_1".to_string(),
+                element_id: "DCE:gpp".to_string(),
+            }
+        );
+        assert_eq!(application.component_implementations()[1].element_id, "cpp");
+        assert_eq!(application.component_naming_contexts()[2].element_id, "fm_1/source_1");
+
+        let port = &application.external_ports()[0];
+        assert_eq!((port.name.as_str(), port.kind), ("audio", PortKind::USES));
+        assert_eq!(application.external_properties()[0].id, "tuned_frequency");
+
+        //ascending startorder, components without one next, the assembly controller last
+        application.start().unwrap();
+        assert!(application.started());
+        application.stop().unwrap();
+        assert!(!application.started());
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "start source_1", "start demod_1", "start sink_1", "start controller_1",
+                "stop controller_1", "stop sink_1", "stop demod_1", "stop source_1",
+            ]
+        );
+
+        //a started application is stopped when released
+        log.lock().unwrap().clear();
+        application.start().unwrap();
+        application.release_object().unwrap();
+        assert_eq!(log.lock().unwrap()[4], "stop controller_1");
+        assert!(application.components().is_empty());
+    }
+
+    #[test]
+    fn test_start_failure() {
+        let root = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut application = factory(root.path(), &log, "sink_1", "").0.create("fm_1", &vec![], &[]).unwrap();
+
+        //the components following the failing one are not started
+        match application.start() {
+            Err(ApplicationError::StartError { component_id, report, .. }) => {
+                assert_eq!(component_id, "sink_1:DCE:fm:fm_1");
+                let outcomes: Vec<_> = report.into_iter().map(|r| (r.component_id, r.outcome)).collect();
+                assert_eq!(
+                    outcomes,
+                    vec![
+                        ("source_1:DCE:fm:fm_1".to_string(), ComponentOutcome::DONE),
+                        ("demod_1:DCE:fm:fm_1".to_string(), ComponentOutcome::DONE),
+                        ("sink_1:DCE:fm:fm_1".to_string(), ComponentOutcome::FAILED("StartError: num: CF_EIO, msg: 'no signal'.".to_string())),
+                        ("controller_1:DCE:fm:fm_1".to_string(), ComponentOutcome::SKIPPED),
+                    ]
+                );
+            }
+            r => panic!("{:?}", r),
+        }
+        assert!(!application.started());
+        assert_eq!(*log.lock().unwrap(), vec!["start source_1", "start demod_1"]);
+
+        //the stop goes on past the failing component
+        match application.stop() {
+            Err(ApplicationError::StopError { component_id, report, .. }) => {
+                assert_eq!(component_id, "sink_1:DCE:fm:fm_1");
+                assert_eq!(report.len(), 4);
+            }
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(log.lock().unwrap()[2..], ["stop controller_1", "stop demod_1", "stop source_1"]);
+        application.release_object().unwrap();
+    }
+
+    #[test]
+    fn test_component_timeout() {
+        let root = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut application = factory(root.path(), &log, "", "demod_1").0.create("fm_1", &vec![], &[]).unwrap();
+
+        match application.start() {
+            Err(ApplicationError::StartError { component_id, message, report }) => {
+                assert_eq!((component_id.as_str(), message.as_str()), ("demod_1:DCE:fm:fm_1", "timed out"));
+                assert_eq!(
+                    report[1..],
+                    [
+                        ComponentReport { component_id: "demod_1:DCE:fm:fm_1".to_string(), outcome: ComponentOutcome::TIMED_OUT },
+                        ComponentReport { component_id: "sink_1:DCE:fm:fm_1".to_string(), outcome: ComponentOutcome::SKIPPED },
+                        ComponentReport { component_id: "controller_1:DCE:fm:fm_1".to_string(), outcome: ComponentOutcome::SKIPPED },
+                    ]
+                );
+            }
+            r => panic!("{:?}", r),
+        }
+
+        //the slow component eventually answers
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(*log.lock().unwrap(), vec!["start source_1", "start demod_1"]);
+        application.release_object().unwrap();
+
+        //a component not initializing in time fails the create rather than freezing it
+        let (slow_factory, device) = factory(root.path(), &log, "", "demod_1.initialize");
+        match slow_factory.create("fm_1", &vec![], &[]) {
+            Err(ApplicationFactoryError::CreateApplicationError { message }) => assert_eq!(message, "'demod_1': initialize timed out after 50ms"),
+            r => panic!("{:?}", r),
+        }
+        assert!(device.lock().unwrap().process_ids().is_empty());
+
+        //a panicking component fails alone, its resource staying usable
+        let mut application = factory(root.path(), &log, "", "demod_1.panic").0.create("fm_1", &vec![], &[]).unwrap();
+        application.start().unwrap();
+        match application.stop() {
+            Err(ApplicationError::StopError { component_id, message, .. }) => {
+                assert_eq!((component_id.as_str(), message.as_str()), ("demod_1:DCE:fm:fm_1", "the component panicked: stuck relay"));
+            }
+            r => panic!("{:?}", r),
+        }
+        let resource = application.component_resource("demod_1:DCE:fm:fm_1").unwrap();
+        assert!(!resource.is_poisoned());
+        application.release_object().unwrap();
+    }
+
+    #[test]
+    fn test_external_ports_and_properties() {
+        let root = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut application = factory(root.path(), &log, "", "").0.create("fm_1", &vec![], &[]).unwrap();
+        let string = |value: &str| AnyValue::String(value.to_string());
+
+        //the external properties are set on their component, the other ones on the assembly controller
+        application.configure(&vec![DataType::new("tuned_frequency", string("101.1")), DataType::new("gain", AnyValue::Long(3))]).unwrap();
+        let resource = |id: &str| application.component(id).unwrap().resource.clone().unwrap();
+        assert_eq!(resource("demod_1").lock().unwrap().query(&vec![]).unwrap()[0], DataType::new("frequency", string("101.1")));
+        assert_eq!(resource("controller_1").lock().unwrap().query(&vec![]).unwrap()[1], DataType::new("gain", AnyValue::Long(3)));
+
+        assert_eq!(
+            application.query(&vec![DataType::new("tuned_frequency", string(""))]).unwrap(),
+            vec![DataType::new("tuned_frequency", string("101.1"))]
+        );
+        assert_eq!(
+            application.query(&vec![]).unwrap(),
+            vec![
+                DataType::new("tuned_frequency", string("101.1")),
+                DataType::new("frequency", string("100.0")),
+                DataType::new("gain", AnyValue::Long(3)),
+                DataType::new("LOG_LEVEL", AnyValue::UShort(8)),
+                DataType::new("LOGGING_CONFIG_URI", string("")),
+            ]
+        );
+
+        match application.configure(&vec![DataType::new("squelch", AnyValue::Long(1))]) {
+            Err(ResourceError::InvalidConfiguration { invalid_properties, .. }) => assert_eq!(invalid_properties[0].id, "squelch"),
+            r => panic!("{:?}", r),
+        }
+        match application.configure(&vec![DataType::new("gain", AnyValue::Long(4)), DataType::new("squelch", AnyValue::Long(1))]) {
+            Err(ResourceError::PartialConfiguration { invalid_properties }) => assert_eq!(invalid_properties.len(), 1),
+            r => panic!("{:?}", r),
+        }
+        match application.query(&vec![DataType::new("squelch", AnyValue::Long(0))]) {
+            Err(ResourceError::UnknownProperties { invalid_properties }) => assert_eq!(invalid_properties[0].id, "squelch"),
+            r => panic!("{:?}", r),
+        }
+
+        //the external ports are mapped to the component ports
+        assert_eq!(application.get_port("data_in").unwrap(), "http://demod_1");
+        match application.get_port("audio") {
+            Err(ResourceError::UnknownPort { name }) => assert_eq!(name, "audio"),
+            r => panic!("{:?}", r),
+        }
+        application.connect_uses_port("audio", "hmi", "http://127.0.0.1:7001").unwrap();
+        application.disconnect_port("audio", "hmi").unwrap();
+        match application.disconnect_port("audio", "hmi") {
+            Err(ResourceError::InvalidPort { name, .. }) => assert_eq!(name, "audio_out"),
+            r => panic!("{:?}", r),
+        }
+        match application.connect_uses_port("data_in", "hmi", "http://127.0.0.1:7001") {
+            Err(ResourceError::UnknownPort { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        application.release_object().unwrap();
+    }
+
+    #[test]
+    fn test_metrics() {
+        let root = tempfile::tempdir().unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (factory, device) = factory(root.path(), &log, "", "");
+        let mut application = factory.create("fm_1", &vec![], &[]).unwrap();
+
+        let process_id = |id: &str| application.component(id).unwrap().process_id.unwrap();
+        device.lock().unwrap().set_process_usage(process_id("demod_1"), 42.5, 4096);
+        device.lock().unwrap().set_process_usage(process_id("sink_1"), 7.5, 1024);
+
+        //the usage of the components is summed
+        let metrics = application.metrics();
+        assert_eq!(metrics.application_id, application.identifier());
+        assert_eq!(metrics.components.len(), 4);
+        assert_eq!((metrics.cpu_usage, metrics.memory), (50.0, 5120));
+        let demod = metrics.components.iter().find(|c| c.component_id == application.component("demod_1").unwrap().identifier).unwrap();
+        assert_eq!((demod.device_id.as_str(), demod.cpu_usage, demod.memory), ("DCE:gpp", 42.5, 4096));
+        assert!(metrics.components.iter().all(|c| c.status == ProcessStatus::RUNNING));
+
+        //verify the components whose process is gone are terminated
+        device.lock().unwrap().terminate(process_id("sink_1")).unwrap();
+        let metrics = application.metrics();
+        assert_eq!((metrics.cpu_usage, metrics.memory), (42.5, 4096));
+        let sink = metrics.components.iter().find(|c| c.process_id == Some(process_id("sink_1"))).unwrap();
+        assert_eq!((sink.status, sink.memory), (ProcessStatus::TERMINATED, 0));
+        application.release_object().ok();
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    use scars::cf::blocking_pool::{BlockingError, BlockingPool, BlockingPoolMetrics};
+
+    #[tokio::test]
+    async fn test_blocking_pool() {
+        let pool = BlockingPool::new(2);
+        assert_eq!(BlockingPool::new(0).size(), 1);
+
+        //the operations beyond the size of the pool wait in the queue
+        let (release, gate) = mpsc::channel::<()>();
+        let gate = Arc::new(Mutex::new(gate));
+        let runs: Vec<_> = (0..4u32)
+            .map(|i| {
+                let (pool, gate) = (pool.clone(), gate.clone());
+                tokio::spawn(async move { pool.run(move || gate.lock().unwrap().recv().map(|()| i * 2)).await })
+            })
+            .collect();
+        while pool.metrics().queue_depth < 2 || pool.metrics().active < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(pool.metrics().threads, 2);
+        (0..4).for_each(|_| release.send(()).unwrap());
+        let mut results = Vec::new();
+        for run in runs {
+            results.push(run.await.unwrap().unwrap().unwrap());
+        }
+        assert_eq!(results, vec![0, 2, 4, 6]);
+        let metrics = pool.metrics();
+        assert!(metrics.max_queue_depth >= 2, "{metrics:?}");
+        assert_eq!(BlockingPoolMetrics { max_queue_depth: 0, ..metrics }, BlockingPoolMetrics { threads: 2, completed: 4, ..Default::default() });
+
+        //a panicking operation fails alone, the pool going on
+        match pool.run(|| -> u32 { panic!("wedged NFS mount") }).await {
+            Err(BlockingError::OperationPanicked { message }) => assert_eq!(message, "wedged NFS mount"),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(pool.run(|| 7).await.unwrap(), 7);
+        assert_eq!((pool.metrics().threads, pool.metrics().completed), (2, 6));
+    }
+
+    #[test]
+    fn test_run_within() {
+        let pool = BlockingPool::new(1);
+        assert_eq!(pool.run_within(Duration::from_secs(5), || 7, |_| {}).unwrap(), 7);
+
+        //an operation past its timeout hands its result to late
+        let (release, gate) = mpsc::channel::<()>();
+        let (undo, undone) = mpsc::channel();
+        match pool.run_within(Duration::from_millis(50), move || gate.recv().map(|()| 8), move |late| undo.send(late).unwrap()) {
+            Err(BlockingError::TimedOut { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //and the operations queued meanwhile are skipped once given up on
+        let (ran, skipped) = mpsc::channel();
+        match pool.run_within(Duration::from_millis(50), move || ran.send(()).unwrap(), |_| {}) {
+            Err(BlockingError::TimedOut { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        release.send(()).unwrap();
+        assert_eq!(undone.recv_timeout(Duration::from_secs(5)).unwrap().unwrap(), 8);
+        assert_eq!(pool.run_within(Duration::from_secs(5), || 9, |_| {}).unwrap(), 9);
+        assert!(skipped.try_recv().is_err());
+        match pool.run_within(Duration::from_secs(5), || -> u32 { panic!("wedged component") }, |_| {}) {
+            Err(BlockingError::OperationPanicked { message }) => assert_eq!(message, "wedged component"),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_within_saturated() {
+        let pool = BlockingPool::new(1);
+
+        //an operation timed by the only thread of the pool is active beside it
+        let (release, gate) = mpsc::channel::<()>();
+        let inner = pool.clone();
+        let outer = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(move || inner.run_within(Duration::from_secs(5), move || gate.recv().map(|()| 9), |_| {})).await }
+        });
+        while pool.metrics().active < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        //the operations queued meanwhile wait for the thread of the pool
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(|| 7).await }
+        });
+        while pool.metrics().queue_depth < 1 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!((pool.metrics().threads, pool.metrics().active), (1, 2));
+        release.send(()).unwrap();
+        assert_eq!(outer.await.unwrap().unwrap().unwrap().unwrap(), 9);
+        assert_eq!(queued.await.unwrap().unwrap(), 7);
+        let metrics = pool.metrics();
+        assert_eq!((metrics.threads, metrics.active, metrics.queue_depth, metrics.completed), (1, 0, 0, 3));
+    }
+
+    #[tokio::test]
+    async fn test_shared_pool() {
+        //the objects of the process share one pool, sized once
+        let pool = BlockingPool::shared();
+        assert_eq!(BlockingPool::default().size(), pool.size());
+        match BlockingPool::init_shared(pool.size() + 1) {
+            Err(BlockingError::AlreadyInUse { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //an operation timed by a thread of the pool runs beside it rather than waiting for it
+        let nested = BlockingPool::new(1);
+        let inner = nested.clone();
+        assert_eq!(nested.run(move || inner.run_within(Duration::from_secs(5), || 9, |_| {})).await.unwrap().unwrap(), 9);
+        assert_eq!(nested.metrics().threads, 1);
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use scars::cf::buffer_pool::{BufferPool, BufferPoolMetrics, DEFAULT_CHUNK_SIZE};
+
+    #[test]
+    fn test_buffer_pool() {
+        let pool = BufferPool::new(2, 1024);
+
+        //the buffers released are reused, cleared, their capacity kept
+        assert_eq!(pool.chunk_size(), 1024);
+        let mut buffer = pool.acquire();
+        buffer.extend_from_slice(b"tune to 101.1");
+        let capacity = buffer.capacity();
+        drop(buffer);
+        assert_eq!(pool.metrics(), BufferPoolMetrics { acquired: 1, reused: 0, discarded: 0, in_use: 0, max_in_use: 1, pooled: 1, pooled_bytes: capacity });
+        let buffer = pool.acquire();
+        assert_eq!((buffer.len(), buffer.capacity()), (0, capacity));
+        assert_eq!((pool.metrics().reused, pool.metrics().pooled, pool.metrics().in_use), (1, 0, 1));
+        drop(buffer);
+
+        //the buffers past the number pooled, or grown past the chunk size, are dropped
+        let mut buffers: Vec<_> = (0..3).map(|_| pool.acquire()).collect();
+        buffers.iter_mut().for_each(|b| b.resize(16, 0));
+        buffers[0].resize(2048, 0);
+        assert_eq!(pool.metrics().max_in_use, 3);
+        drop(buffers);
+        assert_eq!((pool.metrics().pooled, pool.metrics().discarded, pool.metrics().in_use), (2, 1, 0));
+
+        //the buffers never written are not pooled
+        let empty = BufferPool::default();
+        assert_eq!(empty.chunk_size(), DEFAULT_CHUNK_SIZE);
+        drop(empty.clone().acquire());
+        assert_eq!((empty.metrics().acquired, empty.metrics().pooled, empty.metrics().discarded), (1, 0, 0));
+
+        //a buffer owned by bytes is released once its last slice is dropped
+        let mut buffer = pool.acquire();
+        buffer.extend_from_slice(b"tune to 101.1");
+        let data = Bytes::from_owner(buffer);
+        let slice = data.slice(8..);
+        drop(data);
+        assert_eq!((&slice[..], pool.metrics().in_use), (&b"101.1"[..], 1));
+        drop(slice);
+        assert_eq!((pool.metrics().in_use, pool.metrics().pooled), (0, 2));
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use scars::cf::bulkio::{BulkioInPort, BulkioMessage, BulkioOutPort, BulkioSample, FlowPolicy, PacketCredits, PrecisionUtcTime, StreamSri, TCS_INVALID};
+    use scars::cf::common_types::AnyValue;
+    use scars::cf::events::{EventChannel, EventChannelRef};
+
+    #[test]
+    fn test_precision_utc_time() {
+        let time = PrecisionUtcTime::from_system_time(UNIX_EPOCH + Duration::from_millis(1_500));
+        assert_eq!((time.twsec, time.tfsec), (1.0, 0.5));
+        assert_eq!(time.to_system_time(), Some(UNIX_EPOCH + Duration::from_millis(1_500)));
+
+        //the fractional seconds carry into the whole ones
+        let next = time.offset(0.75);
+        assert_eq!((next.twsec, next.tfsec), (2.0, 0.25));
+        let previous = time.offset(-0.75);
+        assert_eq!((previous.twsec, previous.tfsec), (0.0, 0.75));
+
+        let not_set = PrecisionUtcTime::not_set();
+        assert_eq!(not_set.tcstatus, TCS_INVALID);
+        assert_eq!(not_set.to_system_time(), None);
+    }
+
+    #[test]
+    fn test_stream_sri() {
+        let sri = StreamSri::new("tone").with_sample_rate(1e6).with_complex(true).with_keyword("COL_RF", AnyValue::Double(100e6));
+        assert_eq!((sri.sample_rate(), sri.complex(), sri.xunits), (1e6, true, 1));
+        assert_eq!(sri.keyword("COL_RF"), Some(&AnyValue::Double(100e6)));
+        let sri = sri.with_keyword("COL_RF", AnyValue::Double(101e6));
+        assert_eq!(sri.keywords.len(), 1);
+
+        assert_eq!(<f32 as BulkioSample>::REPID, "IDL:BULKIO/dataFloat:1.0");
+        assert_eq!(<u16 as BulkioSample>::REPID, "IDL:BULKIO/dataUshort:1.0");
+    }
+
+    #[test]
+    fn test_ports() {
+        let channel: EventChannel<BulkioMessage<f32>> = EventChannel::new("dataFloat_out");
+        let mut out_port = BulkioOutPort::new("dataFloat_out", channel.clone());
+        let mut in_port = BulkioInPort::new("dataFloat_in", &channel);
+        assert_eq!(out_port.repository_id(), "IDL:BULKIO/dataFloat:1.0");
+        let time = PrecisionUtcTime::now();
+
+        //a stream pushed without SRI gets the default one
+        out_port.push_packet(vec![1.0, 2.0], time, false, "tone");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert_eq!((block.data, block.sri.clone(), block.sri_changed, block.eos), (vec![1.0, 2.0], StreamSri::new("tone"), true, false));
+        out_port.push_packet(vec![3.0], time.offset(2.0), false, "tone");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert_eq!((block.time, block.sri_changed), (time.offset(2.0), false));
+
+        //the SRI is pushed when it changes, and flagged on the next block
+        let sri = StreamSri::new("tone").with_sample_rate(8000.0);
+        out_port.push_sri(sri.clone());
+        out_port.push_sri(sri.clone());
+        out_port.push_packet(vec![4.0], time, false, "tone");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert_eq!((block.sri.clone(), block.sri_changed), (sri.clone(), true));
+        assert_eq!(channel.last_sequence(), 5);
+
+        //the end of the stream forgets its SRI on both ends
+        out_port.push_packet(vec![], time, true, "tone");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert!(block.eos && !block.sri_changed);
+        assert_eq!(block.sri, sri);
+        assert!(out_port.active_sris().is_empty() && in_port.active_sris().is_empty());
+        out_port.push_packet(vec![5.0], time, false, "tone");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert_eq!((block.sri, block.sri_changed), (StreamSri::new("tone"), true));
+        assert!(in_port.get_packet(Duration::from_millis(10)).is_none());
+    }
+
+    #[test]
+    fn test_connections() {
+        let mut out_port = BulkioOutPort::new("dataShort_out", EventChannel::new("dataShort_out"));
+        let time = PrecisionUtcTime::now();
+        let sri = StreamSri::new("iq").with_complex(true);
+        out_port.push_sri(sri.clone());
+
+        //a connection gets the SRI of the active streams first
+        let transport: EventChannel<BulkioMessage<i16>> = EventChannel::new("connection_1");
+        let mut in_port = BulkioInPort::new("dataShort_in", &transport);
+        out_port.connect("connection_1", Arc::new(transport.clone()) as EventChannelRef<_>);
+        out_port.push_packet(vec![1, -1], time, false, "iq");
+        let block = in_port.get_packet(Duration::from_secs(1)).unwrap();
+        assert_eq!((block.data, block.sri, block.sri_changed), (vec![1, -1], sri, true));
+        assert_eq!(out_port.connection_ids(), vec!["connection_1"]);
+
+        //a connection replaces the one of the same id, until disconnected
+        out_port.connect("connection_1", Arc::new(transport.clone()));
+        assert_eq!(out_port.connection_ids(), vec!["connection_1"]);
+        assert!(out_port.disconnect("connection_1"));
+        assert!(!out_port.disconnect("connection_1"));
+        out_port.push_packet(vec![2, -2], time, true, "iq");
+        assert!(in_port.get_packet(Duration::from_millis(10)).is_none());
+    }
+
+    #[test]
+    fn test_flow_control() {
+        let channel: EventChannel<BulkioMessage<f32>> = EventChannel::new("dataFloat_out");
+        let mut in_port = BulkioInPort::new("dataFloat_in", &channel).with_credits(2);
+        let mut out_port = BulkioOutPort::new("dataFloat_out", channel.clone()).with_credits(in_port.credits().unwrap());
+        let time = PrecisionUtcTime::now();
+
+        //the packets past the credits are dropped, the SRI not taking any
+        for sample in 0..3 {
+            out_port.push_packet(vec![sample as f32], time, false, "tone");
+        }
+        let metrics = out_port.flow_metrics();
+        assert_eq!(metrics[0].0, "dataFloat_out");
+        assert_eq!((metrics[0].1.credits, metrics[0].1.queue_depth, metrics[0].1.sent, metrics[0].1.dropped), (0, 2, 2, 1));
+
+        //each packet taken grants a credit back
+        assert_eq!(in_port.get_packet(Duration::from_secs(1)).unwrap().data, vec![0.0]);
+        let credits = in_port.credits().unwrap();
+        assert_eq!((credits.metrics().credits, credits.metrics().queue_depth), (1, 1));
+        assert_eq!(in_port.get_packet(Duration::from_secs(1)).unwrap().data, vec![1.0]);
+        assert!(in_port.get_packet(Duration::from_millis(10)).is_none());
+
+        //the end of a stream is sent regardless of the credits
+        out_port.push_packet(vec![3.0], time, false, "tone");
+        out_port.push_packet(vec![4.0], time, false, "tone");
+        out_port.push_packet(vec![], time, false, "tone");
+        out_port.push_packet(vec![], time, true, "tone");
+        assert_eq!((credits.metrics().credits, credits.metrics().max_queue_depth, credits.metrics().dropped), (0, 3, 2));
+        assert_eq!(in_port.get_packet(Duration::from_secs(1)).unwrap().data, vec![3.0]);
+        assert_eq!(in_port.get_packet(Duration::from_secs(1)).unwrap().data, vec![4.0]);
+        assert!(in_port.get_packet(Duration::from_secs(1)).unwrap().eos);
+        assert_eq!(credits.metrics().credits, 2);
+    }
+
+    #[test]
+    fn test_blocking_flow_control() {
+        let transport: EventChannel<BulkioMessage<i16>> = EventChannel::new("connection_1");
+        let mut in_port = BulkioInPort::new("dataShort_in", &transport).with_credits(1);
+        let credits = in_port.credits().unwrap();
+        let mut out_port = BulkioOutPort::new("dataShort_out", EventChannel::new("dataShort_out")).with_flow_policy(FlowPolicy::Block(Duration::from_secs(5)));
+        out_port.connect_with_credits("connection_1", Arc::new(transport.clone()), credits.clone());
+        let time = PrecisionUtcTime::now();
+
+        //the producer waits for the consumer to take a packet
+        out_port.push_packet(vec![1], time, false, "iq");
+        let consumer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            let blocks: Vec<Vec<i16>> = (0..2).map(|_| in_port.get_packet(Duration::from_secs(5)).unwrap().data).collect();
+            (in_port, blocks)
+        });
+        out_port.push_packet(vec![2], time, false, "iq");
+        let (in_port, blocks) = consumer.join().unwrap();
+        assert_eq!(blocks, vec![vec![1], vec![2]]);
+        assert_eq!(out_port.flow_metrics(), vec![("connection_1".to_string(), credits.metrics())]);
+        assert_eq!((credits.metrics().blocked, credits.metrics().dropped), (1, 0));
+
+        //the credits time out, until the consumer is dropped
+        let mut out_port = out_port.with_flow_policy(FlowPolicy::Block(Duration::from_millis(10)));
+        out_port.push_packet(vec![3], time, false, "iq");
+        out_port.push_packet(vec![4], time, false, "iq");
+        assert_eq!((credits.metrics().blocked, credits.metrics().dropped), (2, 1));
+        drop(in_port);
+        out_port.push_packet(vec![5], time, false, "iq");
+        assert_eq!(credits.metrics().dropped, 1);
+        assert_eq!(PacketCredits::new(4).metrics().credits, 4);
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::checksum;
+
+    #[test]
+    fn test_crc32c() {
+        //the check value of the Castagnoli polynomial, and the tails shorter than a slice
+        assert_eq!(checksum::crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(checksum::crc32c(b""), 0);
+        assert_eq!(checksum::crc32c(&[0u8; 32]), 0x8a91_36aa);
+        assert_eq!(checksum::crc32c(&[0xffu8; 32]), 0x62a8_ab43);
+
+        //a bit flipped anywhere changes the checksum
+        let data: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let crc = checksum::crc32c(&data);
+        for i in [0, 7, 8, 500, 999] {
+            let mut corrupted = data.clone();
+            corrupted[i] ^= 0x10;
+            assert_ne!(checksum::crc32c(&corrupted), crc);
+        }
+    }
+
+    #[test]
+    fn test_digest() {
+        let hex = |digest: Vec<u8>| digest.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(hex(checksum::digest(b"abc")), "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532");
+        assert_eq!(hex(checksum::digest(b"")), "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a");
+    }
+
+    #[test]
+    fn test_rolling_checksum() {
+        //the checksum rolled over the data is the one of each window
+        let data: Vec<u8> = (0..3000u32).map(|i| (i * 7 % 253) as u8).collect();
+        for window in [1, 16, 1024] {
+            let mut rolling = checksum::RollingChecksum::new(&data[..window]);
+            for i in 0..data.len() - window {
+                assert_eq!(rolling, checksum::RollingChecksum::new(&data[i..i + window]));
+                rolling.roll(data[i], data[i + window]);
+            }
+        }
+        assert_eq!(checksum::RollingChecksum::new(b"").value(), 0);
+        assert_ne!(checksum::RollingChecksum::new(b"ab").value(), checksum::RollingChecksum::new(b"ba").value());
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Stdio};
+    use std::time::Duration;
+
+    use serde_json::json;
+
+    use scars::cf::cli::{self, CliError, CliOption, CommandLine, OutputFormat, Shell, COMPLETIONS_OPTION, FORMAT_OPTION};
+    use scars::cf::common_types::AnyValue;
+    use scars::cf::profile::diff::Change;
+    use scars::cf::profile::lint::Issue;
+
+    const COMMAND_LINE: CommandLine = CommandLine {
+        name: "scars-test",
+        options: &[CliOption::value("--ca"), CliOption::flag("--daemon"), FORMAT_OPTION, COMPLETIONS_OPTION],
+        commands: &["ls", "cat"],
+    };
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_output_format() {
+        let mut words = args(&["http://localhost:5000", "--format", "json", "nodes"]);
+        assert_eq!(OutputFormat::take(&mut words).unwrap(), OutputFormat::Json);
+        assert_eq!(words, args(&["http://localhost:5000", "nodes"]));
+        assert_eq!(OutputFormat::take(&mut words).unwrap(), OutputFormat::Text);
+        match OutputFormat::take(&mut args(&["--format", "yaml"])) { Err(CliError::UnknownFormat { format }) => assert_eq!(format, "yaml"), r => panic!("{:?}", r) }
+        match OutputFormat::take(&mut args(&["nodes", "--format"])) { Err(CliError::MissingValue { .. }) => {}, r => panic!("{:?}", r) }
+
+        //the descriptor types serialize to stable documents
+        let issue = Issue { file_name: "/fm.sad.xml".to_string(), message: "unknown port".to_string() };
+        assert_eq!(serde_json::to_value(&issue).unwrap(), json!({"file_name": "/fm.sad.xml", "message": "unknown port"}));
+        let change = Change::PropertyChanged { component_id: None, property_id: "frequency".to_string(), old: None, new: Some(AnyValue::Double(98.5)) };
+        assert_eq!(serde_json::to_value(&change).unwrap(), json!({"change": "PropertyChanged", "component_id": null, "property_id": "frequency", "old": null, "new": {"Double": 98.5}}));
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(cli::parse_seconds("--interval", "2").unwrap(), Duration::from_secs(2));
+        assert_eq!(cli::parse_seconds("--interval", "0.25").unwrap(), Duration::from_millis(250));
+        for value in ["-0.5", "NaN", "infinity", "0", "1e-10", "1e20", ""] {
+            match cli::parse_seconds("--interval", value) { Err(CliError::InvalidValue { option, value: v }) => assert_eq!((option.as_str(), v.as_str()), ("--interval", value)), r => panic!("{value}: {:?}", r) }
+        }
+    }
+
+    #[test]
+    fn test_take_value() {
+        let mut words = args(&["--blocking-threads", "4", "DCE:domain", "--record", "/tmp/domain.rec", "--open-timeout", "250", "domain"]);
+        assert_eq!(cli::take_value(&mut words, "--record").unwrap().as_deref(), Some("/tmp/domain.rec"));
+        assert_eq!(cli::take_count(&mut words, "--blocking-threads").unwrap(), Some(4));
+        assert_eq!(cli::take_millis(&mut words, "--open-timeout").unwrap(), Some(Duration::from_millis(250)));
+        assert_eq!(words, args(&["DCE:domain", "domain"]));
+        assert_eq!(cli::take_value(&mut words, "--record").unwrap(), None);
+        assert_eq!(cli::take_count(&mut words, "--blocking-threads").unwrap(), None);
+        assert_eq!(cli::take_millis(&mut words, "--open-timeout").unwrap(), None);
+
+        //the option without its value, or with one it does not take
+        match cli::take_value(&mut args(&["DCE:domain", "--record"]), "--record") { Err(CliError::MissingValue { option }) => assert_eq!(option, "--record"), r => panic!("{:?}", r) }
+        for value in ["0", "-1", "two", ""] {
+            match cli::take_count(&mut args(&["--blocking-threads", value]), "--blocking-threads") { Err(CliError::InvalidValue { option, value: v }) => assert_eq!((option.as_str(), v.as_str()), ("--blocking-threads", value)), r => panic!("{value}: {:?}", r) }
+        }
+        for value in ["-1", "0.5", "1s"] {
+            match cli::take_millis(&mut args(&["--open-timeout", value]), "--open-timeout") { Err(CliError::InvalidValue { option, value: v }) => assert_eq!((option.as_str(), v.as_str()), ("--open-timeout", value)), r => panic!("{value}: {:?}", r) }
+        }
+    }
+
+    #[test]
+    fn test_completions() {
+        assert_eq!(COMMAND_LINE.requested_completions(&args(&["ls"])).unwrap(), None);
+        assert_eq!(COMMAND_LINE.requested_completions(&args(&["--completions", "bash"])).unwrap(), Some(COMMAND_LINE.completions(Shell::Bash)));
+        match COMMAND_LINE.requested_completions(&args(&["--completions", "tcsh"])) { Err(CliError::UnknownShell { shell }) => assert_eq!(shell, "tcsh"), r => panic!("{:?}", r) }
+
+        let bash = COMMAND_LINE.completions(Shell::Bash);
+        assert!(bash.contains("--format) COMPREPLY=($(compgen -W \"text json\" -- \"$cur\")); return ;;"), "{bash}");
+        assert!(bash.contains("compgen -W \"--ca --daemon --format --completions\""), "{bash}");
+        assert!(bash.ends_with("complete -o default -F _scars_test scars-test\n"), "{bash}");
+        let zsh = COMMAND_LINE.completions(Shell::Zsh);
+        assert!(zsh.starts_with("#compdef scars-test\n") && zsh.contains("    compadd -- ls cat\n"), "{zsh}");
+        let fish = COMMAND_LINE.completions(Shell::Fish);
+        assert!(fish.contains("complete -c scars-test -l ca -r\ncomplete -c scars-test -l daemon\n"), "{fish}");
+        assert!(fish.contains("-n \"not __fish_seen_subcommand_from ls cat\" -a \"ls cat\""), "{fish}");
+    }
+
+    #[test]
+    fn test_binaries() {
+        let binaries = [
+            ("scars-shell", env!("CARGO_BIN_EXE_scars-shell")),
+            ("scars-device-launcher", env!("CARGO_BIN_EXE_scars-device-launcher")),
+            ("scars-domain-manager", env!("CARGO_BIN_EXE_scars-domain-manager")),
+            ("scars-device-manager", env!("CARGO_BIN_EXE_scars-device-manager")),
+            ("scars-nodebooter", env!("CARGO_BIN_EXE_scars-nodebooter")),
+            #[cfg(feature = "rest-gateway")]
+            ("scars-rest-gateway", env!("CARGO_BIN_EXE_scars-rest-gateway")),
+        ];
+
+        //every command line completes, its --format option included
+        for (name, binary) in binaries {
+            let output = Command::new(binary).args(["--completions", "bash"]).output().unwrap();
+            assert!(output.status.success(), "{name}");
+            let bash = String::from_utf8(output.stdout).unwrap();
+            assert!(bash.contains("--format) COMPREPLY=($(compgen -W \"text json\" -- \"$cur\")); return ;;"), "{bash}");
+            assert!(bash.ends_with(&format!("complete -o default -F _{} {name}\n", name.replace('-', "_"))), "{bash}");
+        }
+    }
+
+    #[test]
+    fn test_json_outputs() {
+        //the shell prints the outputs of the commands alone
+        let mut shell = Command::new(env!("CARGO_BIN_EXE_scars-shell")).args(["--format", "json"]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+        shell.stdin.take().unwrap().write_all(b"components\nconnections\n").unwrap();
+        let output = shell.wait_with_output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "[]\n[]\n");
+
+        //the booters print the addresses they listen on
+        let mut domain = Command::new(env!("CARGO_BIN_EXE_scars-domain-manager")).args(["--format", "json", "DCE:domain", "REDHAWK_DEV"]).stdout(Stdio::piped()).spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(domain.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        domain.kill().unwrap();
+        domain.wait().unwrap();
+        let address: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(address["service"], "DomainManager");
+        assert!(address["address"].as_str().unwrap().starts_with("127.0.0.1:"), "{line}");
+
+        let output = Command::new(env!("CARGO_BIN_EXE_scars-domain-manager")).args(["--format", "yaml", "DCE:domain", "REDHAWK_DEV"]).output().unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("UnknownFormat: format: 'yaml'."));
+    }
+}
@@ -0,0 +1,141 @@
+#[path = "codegen/demod_properties.rs"]
+mod demod_properties;
+
+#[cfg(test)]
+mod tests {
+    use scars::cf::common_types::{AnyValue, DataType, Properties, PropertyValue};
+    use scars::cf::profile::codegen;
+    use scars::cf::profile::prf::PropertiesDescriptor;
+    use scars::cf::resource::ResourceError;
+
+    use super::demod_properties::{Channel, DemodProperties, Gain};
+
+    const PRF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<properties>
+  <description>demod properties</description>
+  <simple id="frequency" name="frequency" type="double" mode="readwrite">
+    <description>The tuned frequency.</description>
+    <value>101.1</value>
+    <units>MHz</units>
+    <range min="87.5" max="108.0"/>
+    <kind kindtype="configure"/>
+    <kind kindtype="execparam"/>
+  </simple>
+  <simple id="mode" type="string">
+    <value>stereo</value>
+    <enumerations>
+      <enumeration label="MONO" value="mono"/>
+      <enumeration label="STEREO" value="stereo"/>
+    </enumerations>
+  </simple>
+  <simple id="processor_name" type="string" mode="readonly">
+    <value>x86_64</value>
+    <kind kindtype="allocation"/>
+    <action type="eq"/>
+  </simple>
+  <simple id="DCE:5d8bfe8b" name="squelchLevel" type="short">
+    <range min="-100" max="0"/>
+  </simple>
+  <simple id="type" type="char"/>
+  <simplesequence id="taps" type="short">
+    <values><value>1</value><value>-2</value></values>
+    <range min="-8" max="8"/>
+    <kind kindtype="property"/>
+  </simplesequence>
+  <struct id="gain" mode="writeonly">
+    <simple id="gain::value" type="float"><value>0.5</value><range min="0" max="1"/></simple>
+    <simple id="gain::auto" type="boolean"/>
+    <configurationkind kindtype="configure"/>
+  </struct>
+  <structsequence id="channels">
+    <struct id="channel">
+      <simple id="channel::id" type="ulong"/>
+      <simplesequence id="channel::tags" type="string"/>
+    </struct>
+    <structvalue>
+      <simpleref refid="channel::id" value="1"/>
+      <simplesequenceref refid="channel::tags"><values><value>left</value></values></simplesequenceref>
+    </structvalue>
+  </structsequence>
+</properties>
+"#;
+
+    #[test]
+    fn test_generate_properties_struct() {
+        let prf = PropertiesDescriptor::parse(PRF, "demod.prf.xml").unwrap();
+        let generated = codegen::properties_struct(&prf, "DemodProperties", "demod.prf.xml");
+        assert_eq!(generated, include_str!("codegen/demod_properties.rs"), "regenerate with: scars-codegen properties demod.prf.xml DemodProperties");
+
+        //without checks nor structs
+        let prf = PropertiesDescriptor::parse(r#"<properties><simple id="type" type="long"/><simplesequence id="2nd-Value" type="octet"><values><value>0</value></values></simplesequence></properties>"#, "plain.prf.xml").unwrap();
+        let generated = codegen::properties_struct(&prf, "Plain", "plain.prf.xml");
+        assert!(generated.contains("use scars::cf::common_types::{DataType, Properties, PropertyValue};"), "{generated}");
+        assert!(generated.contains("/// The properties of plain.prf.xml.\n#[derive(Debug, Clone, PartialEq)]\npub struct Plain {\n    type_: Option<i32>,\n    p_2nd_value: Vec<u8>,\n}"), "{generated}");
+        assert!(generated.contains("pub fn set_type(&mut self, value: i32)"), "{generated}");
+        assert!(!generated.contains("fn invalid("), "{generated}");
+    }
+
+    #[test]
+    fn test_property_value() {
+        assert_eq!(1.5f64.to_any(), AnyValue::Double(1.5));
+        assert_eq!(f64::from_any(&AnyValue::Double(1.5)), Some(1.5));
+        assert_eq!(f64::from_any(&AnyValue::Float(1.5)), None);
+        assert_eq!('x'.to_any(), AnyValue::String("x".to_string()));
+        assert_eq!(char::from_any(&AnyValue::String("xy".to_string())), None);
+        assert_eq!(vec![1u16, 2].to_any(), AnyValue::Sequence(vec![AnyValue::UShort(1), AnyValue::UShort(2)]));
+        assert_eq!(Vec::<u16>::from_any(&AnyValue::Sequence(vec![AnyValue::UShort(1), AnyValue::Long(2)])), None);
+    }
+
+    #[test]
+    fn test_generated_properties() {
+        let mut p = DemodProperties::default();
+        assert_eq!(*p.frequency(), 101.1);
+        assert_eq!(p.mode(), "stereo");
+        assert_eq!(p.squelch_level(), None);
+        assert_eq!(p.gain(), &Gain { value: 0.5, auto: None });
+        assert_eq!(p.channels(), &vec![Channel { id: Some(1), tags: Some(vec!["left".to_string()]) }]);
+        assert_eq!(DemodProperties::SQUELCH_LEVEL_ID, "DCE:5d8bfe8b");
+
+        //ranges and enumerations
+        p.set_frequency(88.0).unwrap();
+        match p.set_frequency(120.0) {
+            Err(ResourceError::InvalidConfiguration { invalid_properties, .. }) => assert_eq!(invalid_properties, vec![DataType::new("frequency", AnyValue::Double(120.0))]),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(*p.frequency(), 88.0);
+        match p.set_mode("quad".to_string()) {
+            Err(ResourceError::InvalidConfiguration { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match p.set_taps(vec![1, 9]) {
+            Err(ResourceError::InvalidConfiguration { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match p.set_gain(Gain { value: 2.0, auto: Some(true) }) {
+            Err(ResourceError::InvalidConfiguration { message, .. }) => assert_eq!(message, "'gain::value' is out of range [0, 1]"),
+            r => panic!("{:?}", r),
+        }
+        p.set_squelch_level(-20).unwrap();
+        assert_eq!(p.squelch_level(), Some(&-20));
+
+        //conversions
+        let properties = Properties::from(&p);
+        assert_eq!(properties.len(), 7);
+        assert_eq!(DemodProperties::try_from(&properties).unwrap(), p);
+
+        //configuration
+        let mut configured = DemodProperties::default();
+        configured.configure(&vec![DataType::new("mode", AnyValue::String("mono".to_string())), DataType::new("gain", Gain { value: 0.2, auto: Some(false) }.to_any())]).unwrap();
+        assert_eq!(configured.mode(), "mono");
+        assert_eq!(configured.gain().auto, Some(false));
+        match configured.configure(&vec![DataType::new("mode", AnyValue::String("stereo".to_string())), DataType::new("processor_name", AnyValue::String("arm".to_string())), DataType::new("taps", AnyValue::Long(1))]) {
+            Err(ResourceError::PartialConfiguration { invalid_properties }) => assert_eq!(invalid_properties.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), vec!["processor_name", "taps"]),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(configured.mode(), "stereo");
+        match configured.configure(&vec![DataType::new("unknown", AnyValue::Long(1))]) {
+            Err(ResourceError::InvalidConfiguration { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType, MAX_LITERAL_DEPTH};
+    use scars_types::wire::{value_from_wire, value_to_wire};
+
+    #[test]
+    fn test_parse_literal() {
+        assert_eq!(AnyValue::parse_literal("true", None), Some(AnyValue::Boolean(true)));
+        assert_eq!(AnyValue::parse_literal("-42", None), Some(AnyValue::Long(-42)));
+        assert_eq!(AnyValue::parse_literal("5000000000", None), Some(AnyValue::LongLong(5000000000)));
+        assert_eq!(AnyValue::parse_literal("2.5e3", None), Some(AnyValue::Double(2500.0)));
+        assert_eq!(AnyValue::parse_literal("rx, tx", None), Some(AnyValue::String("rx, tx".to_string())));
+        assert_eq!(AnyValue::parse_literal("\"a \\\"b\\\"\"", None), Some(AnyValue::String("a \"b\"".to_string())));
+
+        //the values take the type of the like one
+        assert_eq!(AnyValue::parse_literal("8", Some(&AnyValue::Octet(0))), Some(AnyValue::Octet(8)));
+        assert_eq!(AnyValue::parse_literal("256", Some(&AnyValue::Octet(0))), None);
+        assert_eq!(AnyValue::parse_literal("2", Some(&AnyValue::Float(1.0))), Some(AnyValue::Float(2.0)));
+        assert_eq!(AnyValue::parse_literal("12", Some(&AnyValue::String(String::new()))), Some(AnyValue::String("12".to_string())));
+        assert_eq!(AnyValue::parse_literal("12", Some(&AnyValue::Sequence(vec![]))), None);
+    }
+
+    #[test]
+    fn test_parse_structured_literal() {
+        let sequence = AnyValue::parse_literal("[1, 2.5, \"x,y\"]", None).unwrap();
+        assert_eq!(sequence, AnyValue::Sequence(vec![AnyValue::Long(1), AnyValue::Double(2.5), AnyValue::String("x,y".to_string())]));
+        assert_eq!(AnyValue::parse_literal("[]", None), Some(AnyValue::Sequence(vec![])));
+        let like = AnyValue::Sequence(vec![AnyValue::Short(0)]);
+        assert_eq!(AnyValue::parse_literal("[3,4]", Some(&like)), Some(AnyValue::Sequence(vec![AnyValue::Short(3), AnyValue::Short(4)])));
+
+        let like = AnyValue::Struct(vec![DataType::new("gain", AnyValue::Float(0.0)), DataType::new("label", AnyValue::String(String::new()))]);
+        let value = AnyValue::parse_literal("{gain=2, label=rx 1, taps=[1,2]}", Some(&like)).unwrap();
+        let expected = AnyValue::Struct(vec![
+            DataType::new("gain", AnyValue::Float(2.0)),
+            DataType::new("label", AnyValue::String("rx 1".to_string())),
+            DataType::new("taps", AnyValue::Sequence(vec![AnyValue::Long(1), AnyValue::Long(2)])),
+        ]);
+        assert_eq!(value, expected);
+
+        //the displayed values parse back
+        assert_eq!(AnyValue::parse_literal(&expected.to_string(), Some(&like)), Some(expected));
+
+        for invalid in ["[1,2", "{gain}", "{gain=1} x", "[1,,2]", "\"open"] {
+            assert_eq!(AnyValue::parse_literal(invalid, None), None, "{invalid}");
+        }
+        assert_eq!(AnyValue::parse_literal("{gain=x}", Some(&like)), None);
+    }
+
+    #[test]
+    fn test_parse_nested_literal() {
+        //the literals nested up to the limit parse
+        let nested = format!("{}1{}", "[".repeat(MAX_LITERAL_DEPTH), "]".repeat(MAX_LITERAL_DEPTH));
+        let mut value = AnyValue::parse_literal(&nested, None).unwrap();
+        for _ in 0..MAX_LITERAL_DEPTH {
+            value = match value { AnyValue::Sequence(mut items) => items.remove(0), v => panic!("{:?}", v) };
+        }
+        assert_eq!(value, AnyValue::Long(1));
+
+        //the deeper ones are rejected rather than overflowing the stack
+        let deeper = format!("{}1{}", "[".repeat(MAX_LITERAL_DEPTH + 1), "]".repeat(MAX_LITERAL_DEPTH + 1));
+        assert_eq!(AnyValue::parse_literal(&deeper, None), None);
+        assert_eq!(AnyValue::parse_literal(&"[{a=".repeat(100_000), None), None);
+        assert_eq!(AnyValue::parse_literal(&"[".repeat(1_000_000), None), None);
+    }
+
+    #[test]
+    fn test_wire() {
+        //the values are encoded as JSON, the same on the firmware of the co-processors
+        let value = AnyValue::Struct(vec![DataType::new("gain", AnyValue::Float(0.5)), DataType::new("taps", AnyValue::Sequence(vec![AnyValue::UShort(3)]))]);
+        let encoded = value_to_wire(&value);
+        assert_eq!(encoded, r#"{"Struct":[{"id":"gain","value":{"Float":0.5}},{"id":"taps","value":{"Sequence":[{"UShort":3}]}}]}"#);
+        assert_eq!(value_from_wire(&encoded).unwrap(), value);
+        assert_eq!(value_from_wire(r#"{"String":"fm"}"#).unwrap(), AnyValue::String("fm".to_string()));
+        assert!(value_from_wire(r#"{"Quad":1}"#).is_err());
+        assert_eq!(ErrorNumberType::from(std::io::ErrorKind::NotFound), ErrorNumberType::CF_ENOENT);
+        assert_eq!(ErrorNumberType::from(std::io::ErrorKind::StorageFull), ErrorNumberType::CF_ENOSPC);
+        assert_eq!(ErrorNumberType::from(std::io::ErrorKind::ConnectionReset), ErrorNumberType::CF_EIO);
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::connection_manager::{ConnectionManager, ConnectionManagerError, EndpointRequest, EndpointResolution};
+    use scars::cf::resource::Resource;
+
+    fn endpoint(resolution: EndpointResolution, port_name: &str) -> EndpointRequest {
+        EndpointRequest {
+            resolution,
+            port_name: port_name.to_string(),
+        }
+    }
+
+    fn component(name: &str) -> EndpointResolution {
+        EndpointResolution::COMPONENT(name.to_string())
+    }
+
+    #[test]
+    fn test_deferred_connections() {
+        let registry = ComponentRegistry::new();
+        let manager = ConnectionManager::new(registry.clone());
+
+        //neither the demodulator nor the sink are there yet
+        let record_id = manager
+            .connect(endpoint(component("fm_1/demod_1"), "audio_out"), endpoint(component("fm_1/sink_1"), "audio_in"), "operator", "audio")
+            .unwrap();
+        assert_eq!(record_id, "operator:audio");
+        let log = EndpointResolution::SERVICE("log".to_string());
+        manager
+            .connect(endpoint(component("fm_1/demod_1"), "log_out"), endpoint(log.clone(), ""), "operator", "log")
+            .unwrap();
+        assert!(manager.connections().iter().all(|c| !c.connected));
+
+        //completed once both components registered
+        let demod = Arc::new(Mutex::new(Resource::new("demod_1").with_uses_port("audio_out").with_uses_port("log_out")));
+        registry.register_component("fm_1/demod_1", demod.clone());
+        assert!(!manager.connections()[0].connected);
+        let sink = Resource::new("sink_1").with_provides_port("audio_in", "http://127.0.0.1:7001");
+        registry.register_component("fm_1/sink_1", Arc::new(Mutex::new(sink)));
+        assert!(manager.connections()[0].connected);
+        assert_eq!(
+            demod.lock().unwrap().connections("audio_out"),
+            vec![("audio".to_string(), "http://127.0.0.1:7001".to_string())]
+        );
+
+        //completed once the service registered, pending again when it leaves
+        manager.register_object(log.clone(), "http://127.0.0.1:7002");
+        assert!(manager.connections()[1].connected);
+        assert_eq!(demod.lock().unwrap().connections("log_out")[0].1, "http://127.0.0.1:7002");
+        manager.unregister_object(&log);
+        assert!(!manager.connections()[1].connected);
+        assert!(demod.lock().unwrap().connections("log_out").is_empty());
+
+        //a service registering at another endpoint is connected to it
+        manager.register_object(log.clone(), "http://127.0.0.1:7003");
+        manager.register_object(log, "http://127.0.0.1:7004");
+        assert_eq!(
+            demod.lock().unwrap().connections("log_out"),
+            vec![("log".to_string(), "http://127.0.0.1:7004".to_string())]
+        );
+
+        //the established connections are connected right away
+        let object = EndpointResolution::OBJECT("http://127.0.0.1:7005".to_string());
+        manager
+            .connect(endpoint(component("fm_1/demod_1"), "audio_out"), endpoint(object, ""), "operator", "monitor")
+            .unwrap();
+        assert!(manager.connections()[2].connected);
+
+        manager.disconnect("operator:audio").unwrap();
+        assert_eq!(manager.connections().len(), 2);
+        assert_eq!(demod.lock().unwrap().connections("audio_out")[0].0, "monitor");
+    }
+
+    #[test]
+    fn test_connection_errors() {
+        let registry = ComponentRegistry::new();
+        let manager = ConnectionManager::new(registry.clone());
+        registry.register_component("fm_1/demod_1", Arc::new(Mutex::new(Resource::new("demod_1").with_uses_port("audio_out"))));
+        let service = endpoint(EndpointResolution::SERVICE("log".to_string()), "");
+
+        //verify the uses endpoint is a component
+        match manager.connect(service.clone(), service.clone(), "operator", "log") {
+            Err(ConnectionManagerError::InvalidEndpoint { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //verify the existing endpoints accept the connection
+        let object = endpoint(EndpointResolution::OBJECT("http://127.0.0.1:7001".to_string()), "");
+        match manager.connect(endpoint(component("fm_1/demod_1"), "unknown"), object, "operator", "audio") {
+            Err(ConnectionManagerError::InvalidConnection { record_id, .. }) => assert_eq!(record_id, "operator:audio"),
+            r => panic!("{:?}", r),
+        }
+        assert!(manager.connections().is_empty());
+
+        manager.connect(endpoint(component("fm_1/demod_1"), "audio_out"), service.clone(), "operator", "log").unwrap();
+        match manager.connect(endpoint(component("fm_1/demod_1"), "audio_out"), service, "operator", "log") {
+            Err(ConnectionManagerError::InvalidConnection { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        match manager.disconnect("operator:unknown") {
+            Err(ConnectionManagerError::InvalidConnectionId { record_id }) => assert_eq!(record_id, "operator:unknown"),
+            r => panic!("{:?}", r),
+        }
+        manager.disconnect("operator:log").unwrap();
+    }
+}
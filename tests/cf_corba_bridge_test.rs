@@ -0,0 +1,241 @@
+#[cfg(all(test, feature = "corba"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerTrait, AllocationProperty, AllocationRequest};
+    use scars::cf::common_types::{ActionType, AnyValue, DataType, ErrorNumberType, Properties};
+    use scars::cf::corba_bridge::{CorbaBridge, CorbaError, CorbaResource, CorbaObjectTrait, LoopbackOrb, OrbTrait, Result};
+    use scars::cf::device::{AdminType, Device, DeviceTrait, OperationalType, UsageType};
+    use scars::cf::file::{self, FileError, FileTrait};
+    use scars::cf::resource::{Resource, ResourceError, ResourceTrait};
+
+    /// Resource of a fielded component, failing to start and unreachable once released.
+    struct LegacyResource {
+        released: Mutex<bool>,
+    }
+
+    impl CorbaObjectTrait for LegacyResource {
+        fn ior(&self) -> &str {
+            "IOR:legacy"
+        }
+
+        fn invoke(&self, operation: &str, _arguments: &[AnyValue]) -> Result<Option<AnyValue>> {
+            if *self.released.lock().unwrap() {
+                return Err(CorbaError::system_exception("TRANSIENT", "connection refused"));
+            }
+            match operation {
+                "_get_identifier" => Ok(Some(AnyValue::String("DCE:legacy".to_string()))),
+                "start" => Err(CorbaError::user_exception(
+                    "IDL:CF/Resource/StartError:1.0",
+                    vec![DataType::new("errorNumber", AnyValue::ULong(17)), DataType::new("msg", AnyValue::String("no license".to_string()))],
+                )),
+                "releaseObject" => {
+                    *self.released.lock().unwrap() = true;
+                    Ok(None)
+                }
+                "query" => Ok(Some(AnyValue::Long(1))),
+                _ => Err(CorbaError::system_exception("BAD_OPERATION", operation)),
+            }
+        }
+    }
+
+    /// File of octets in memory.
+    struct MemoryFile {
+        file_name: String,
+        data: Vec<u8>,
+        file_pointer: u64,
+    }
+
+    impl FileTrait for MemoryFile {
+        fn file_name(&self) -> &String {
+            &self.file_name
+        }
+
+        fn file_pointer(&self) -> u64 {
+            self.file_pointer
+        }
+
+        fn read(&mut self, buffer: &mut Vec<u8>) -> file::Result<usize> {
+            let data = &self.data[self.file_pointer as usize..];
+            let read = data.len().min(buffer.len());
+            buffer[..read].copy_from_slice(&data[..read]);
+            self.file_pointer += read as u64;
+            Ok(read)
+        }
+
+        fn write(&mut self, data: &[u8]) -> file::Result<()> {
+            self.data.truncate(self.file_pointer as usize);
+            self.data.extend_from_slice(data);
+            self.file_pointer += data.len() as u64;
+            Ok(())
+        }
+
+        fn size_of(&self) -> file::Result<u64> {
+            Ok(self.data.len() as u64)
+        }
+
+        fn close(&mut self) -> file::Result<()> {
+            Ok(())
+        }
+
+        fn set_file_pointer(&mut self, file_pointer: u64) -> file::Result<()> {
+            if file_pointer > self.data.len() as u64 {
+                return Err(FileError::InvalidFilePointer);
+            }
+            self.file_pointer = file_pointer;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_resource() {
+        let bridge = CorbaBridge::new(Arc::new(LoopbackOrb::new()));
+        let resource = Arc::new(Mutex::new(
+            Resource::new("DCE:siggen")
+                .with_property("frequency", AnyValue::Double(1000.0))
+                .with_provides_port("data_in", "local://DCE:siggen/data_in")
+                .with_uses_port("data_out"),
+        ));
+        let ior = bridge.expose_resource(resource.clone()).unwrap();
+
+        //the proxy of the exposed resource drives it
+        let mut proxy = bridge.resource(&ior).unwrap();
+        assert_eq!(proxy.identifier(), "DCE:siggen");
+        proxy.initialize().unwrap();
+        proxy.configure(&vec![DataType::new("frequency", AnyValue::Double(2000.0))]).unwrap();
+        assert_eq!(proxy.query(&vec![DataType::new("frequency", AnyValue::Boolean(false))]).unwrap(), vec![DataType::new("frequency", AnyValue::Double(2000.0))]);
+        proxy.start().unwrap();
+        assert!(proxy.started());
+        assert!(resource.lock().unwrap().started());
+
+        //its CF exceptions come back as the same errors
+        match proxy.query(&vec![DataType::new("amplitude", AnyValue::Boolean(false))]) {
+            Err(ResourceError::UnknownProperties { invalid_properties }) => assert_eq!(invalid_properties[0].id, "amplitude"),
+            r => panic!("{:?}", r),
+        }
+        match proxy.configure(&vec![DataType::new("amplitude", AnyValue::Double(0.5))]) {
+            Err(ResourceError::InvalidConfiguration { invalid_properties, .. }) => assert_eq!(invalid_properties[0].id, "amplitude"),
+            r => panic!("{:?}", r),
+        }
+
+        //the provides ports are endpoints, the uses ports objects connected through
+        assert_eq!(proxy.get_provides_port("data_in").unwrap(), "local://DCE:siggen/data_in");
+        proxy.connect_uses_port("data_out", "c1", "local://DCE:sink/data_in").unwrap();
+        assert_eq!(resource.lock().unwrap().connections("data_out"), vec![("c1".to_string(), "local://DCE:sink/data_in".to_string())]);
+        proxy.disconnect_port("data_out", "c1").unwrap();
+        assert!(resource.lock().unwrap().connections("data_out").is_empty());
+        match proxy.connect_uses_port("control_out", "c2", "local://DCE:sink/data_in") {
+            Err(ResourceError::InvalidPort { name, .. }) => assert_eq!(name, "control_out"),
+            r => panic!("{:?}", r),
+        }
+
+        //a withdrawn resource is no longer reachable
+        bridge.withdraw(&ior).unwrap();
+        assert!(!proxy.started());
+        match proxy.stop() {
+            Err(ResourceError::StopError { error_number: ErrorNumberType::CF_EIO, message }) => assert!(message.contains("OBJECT_NOT_EXIST")),
+            r => panic!("{:?}", r),
+        }
+        match bridge.resource(&ior) {
+            Err(CorbaError::SystemException { name, .. }) => assert_eq!(name, "INV_OBJREF"),
+            r => panic!("{:?}", r.map(|r| r.ior().to_string())),
+        }
+    }
+
+    #[test]
+    fn test_legacy_resource() {
+        let orb = Arc::new(LoopbackOrb::new());
+        let mut proxy = CorbaResource::new(orb, Arc::new(LegacyResource { released: Mutex::new(false) })).unwrap();
+        assert_eq!((proxy.identifier(), proxy.ior()), ("DCE:legacy", "IOR:legacy"));
+
+        match proxy.start() {
+            Err(ResourceError::StartError { error_number, message }) => assert_eq!((error_number, message.as_str()), (ErrorNumberType::CF_EIO, "no license")),
+            r => panic!("{:?}", r),
+        }
+        match proxy.query(&Properties::new()) {
+            Err(ResourceError::InvalidState { message }) => assert!(message.contains("MARSHAL")),
+            r => panic!("{:?}", r),
+        }
+        match proxy.get_provides_port("data_in") {
+            Err(ResourceError::InvalidState { message }) => assert!(message.contains("BAD_OPERATION")),
+            r => panic!("{:?}", r),
+        }
+        proxy.release_object().unwrap();
+        match proxy.initialize() {
+            Err(ResourceError::InitializeError { messages }) => assert!(messages[0].contains("TRANSIENT")),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_device() {
+        let bridge = CorbaBridge::new(Arc::new(LoopbackOrb::new()));
+        let device = Arc::new(Mutex::new(
+            Device::new("DCE:tuner", "tuner")
+                .with_allocation_property("device_kind", AnyValue::String("RX".to_string()))
+                .with_capacity("channels", AnyValue::UShort(2)),
+        ));
+        let ior = bridge.expose_device(device.clone()).unwrap();
+
+        //the proxied device is allocated by the domain as its own
+        let proxy = bridge.device(&ior).unwrap();
+        assert_eq!((proxy.identifier(), proxy.label(), proxy.composite_device()), ("DCE:tuner", "tuner", None));
+        assert_eq!((proxy.usage_state(), proxy.admin_state(), proxy.operational_state()), (UsageType::IDLE, AdminType::UNLOCKED, OperationalType::ENABLED));
+        let mut am = AllocationManager::new();
+        am.register_device(Arc::new(Mutex::new(proxy)));
+        let request = AllocationRequest {
+            request_id: "r1".to_string(),
+            allocation_properties: vec![
+                AllocationProperty::new(DataType::new("device_kind", AnyValue::String("RX".to_string())), ActionType::EQ),
+                AllocationProperty::new(DataType::new("channels", AnyValue::UShort(1)), ActionType::EXTERNAL),
+            ],
+            ..Default::default()
+        };
+        let responses = am.allocate(std::slice::from_ref(&request)).unwrap();
+        assert_eq!(responses[0].allocated_device, "DCE:tuner");
+        assert_eq!(device.lock().unwrap().available_capacity("channels"), Some(&AnyValue::UShort(1)));
+        assert_eq!(device.lock().unwrap().usage_state(), UsageType::ACTIVE);
+
+        //a device being locked is not allocated, a withdrawn one neither
+        let mut proxy = bridge.device(&ior).unwrap();
+        proxy.set_admin_state(AdminType::LOCKED);
+        assert_eq!(device.lock().unwrap().admin_state(), AdminType::SHUTTING_DOWN);
+        assert!(am.allocate(&[AllocationRequest { request_id: "r2".to_string(), ..request.clone() }]).is_err());
+        proxy.set_admin_state(AdminType::UNLOCKED);
+        bridge.withdraw(&ior).unwrap();
+        assert_eq!((proxy.usage_state(), proxy.admin_state(), proxy.operational_state()), (UsageType::BUSY, AdminType::LOCKED, OperationalType::DISABLED));
+        assert!(proxy.allocation_properties().is_empty());
+        assert!(am.allocate(&[AllocationRequest { request_id: "r3".to_string(), ..request }]).is_err());
+    }
+
+    #[test]
+    fn test_file() {
+        let orb = Arc::new(LoopbackOrb::new());
+        let bridge = CorbaBridge::new(orb.clone());
+        let ior = bridge.expose_file(MemoryFile { file_name: "/waveforms/log.txt".to_string(), data: b"hello".to_vec(), file_pointer: 0 }).unwrap();
+
+        let mut proxy = bridge.file(&ior).unwrap();
+        assert_eq!(proxy.file_name(), "/waveforms/log.txt");
+        assert_eq!(proxy.size_of().unwrap(), 5);
+        let mut buffer = vec![0u8; 3];
+        assert_eq!(proxy.read(&mut buffer).unwrap(), 3);
+        assert_eq!(buffer, b"hel");
+        assert_eq!(proxy.file_pointer(), 3);
+        proxy.set_file_pointer(5).unwrap();
+        proxy.write(b" world").unwrap();
+        assert_eq!(proxy.size_of().unwrap(), 11);
+        match proxy.set_file_pointer(20) {
+            Err(FileError::InvalidFilePointer) => {}
+            r => panic!("{:?}", r),
+        }
+        proxy.close().unwrap();
+
+        //the file pointer is kept when the object is gone
+        orb.deactivate(&ior).unwrap();
+        assert_eq!(proxy.file_pointer(), 11);
+        match proxy.size_of() {
+            Err(FileError::FileException { error_number: ErrorNumberType::CF_EIO, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
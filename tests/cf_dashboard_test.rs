@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use scars::cf::cli::CliError;
+    use scars::cf::dashboard::{self, clock, draw, idm_line, odm_line, ApplicationRow, Dashboard, DeviceRow, EVENT_LINES};
+    use scars::cf::rpc::device::{AdminType, OperationalType, StatusReply, UsageType};
+    use scars::cf::rpc::domain_manager::domain_management_event::Event as OdmEvent;
+    use scars::cf::rpc::domain_manager::{AllocationFailed, ApplicationInfo, ApplicationMetricsReply, ComponentMetrics, DomainManagementEvent, DomainObject, ProcessStatus, SourceCategoryType, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Draws the dashboard on a test terminal, returning its lines.
+    fn render(dashboard: &Dashboard) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| draw(frame, "http://localhost:5000", dashboard)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height).map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+    }
+
+    fn rendered(lines: &[String], text: &str) -> bool {
+        lines.iter().any(|l| l.contains(text))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let (endpoint, interval) = dashboard::parse_args(&args(&["http://localhost:5000"])).unwrap().unwrap();
+        assert_eq!((endpoint.as_str(), interval), ("http://localhost:5000", dashboard::DEFAULT_INTERVAL));
+        let (_, interval) = dashboard::parse_args(&args(&["http://localhost:5000", "--interval", "0.5"])).unwrap().unwrap();
+        assert_eq!(interval, Duration::from_millis(500));
+
+        //the intervals a duration cannot hold are usage errors, not panics
+        for seconds in ["-1", "NaN", "inf", "-inf", "0", "1e-12", "1e300", "two"] {
+            match dashboard::parse_args(&args(&["http://localhost:5000", "--interval", seconds])) {
+                Some(Err(CliError::InvalidValue { option, value })) => assert_eq!((option.as_str(), value.as_str()), ("--interval", seconds)),
+                r => panic!("{seconds}: {:?}", r),
+            }
+        }
+
+        //the other command lines are not the ones of scars-top
+        assert!(dashboard::parse_args(&args(&[])).is_none());
+        assert!(dashboard::parse_args(&args(&["http://localhost:5000", "--period", "1"])).is_none());
+        assert!(dashboard::parse_args(&args(&["http://localhost:5000", "--interval"])).is_none());
+    }
+
+    #[test]
+    fn test_invalid_interval() {
+        let output = Command::new(env!("CARGO_BIN_EXE_scars-top")).args(["http://localhost:5000", "--interval", "-1"]).output().unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("InvalidValue: option: '--interval', value: '-1'."), "{stderr}");
+        assert!(stderr.contains("usage: scars-top <domain manager endpoint> [--interval <seconds>]"), "{stderr}");
+        assert!(!stderr.contains("panicked"), "{stderr}");
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut dashboard = Dashboard::default();
+        let lines = render(&dashboard);
+        assert!(rendered(&lines, " Devices ") && rendered(&lines, " Applications ") && rendered(&lines, " Events "), "{lines:#?}");
+        assert!(rendered(&lines, "http://localhost:5000  connecting...  q: quit"), "{lines:#?}");
+
+        dashboard.nodes = vec![("DCE:node1".to_string(), "node1".to_string(), true), ("DCE:node2".to_string(), "node2".to_string(), false)];
+        let status = StatusReply { identifier: "DCE:gpp1".to_string(), label: "gpp1".to_string(), composite_device: None, usage_state: UsageType::Active as i32, admin_state: AdminType::Unlocked as i32, operational_state: OperationalType::Enabled as i32 };
+        dashboard.devices = vec![
+            DeviceRow { node: "DCE:node1".to_string(), identifier: "DCE:gpp1".to_string(), label: "gpp1".to_string(), status: Some(status), capacities: "cpu=4".to_string() },
+            DeviceRow { node: "DCE:node2".to_string(), identifier: "DCE:gpp2".to_string(), label: "gpp2".to_string(), status: None, capacities: String::new() },
+        ];
+        let info = ApplicationInfo { identifier: "DCE:app1".to_string(), name: "tone".to_string(), profile: "/waveforms/tone/tone.sad.xml".to_string(), started: true };
+        let component = ComponentMetrics { component_id: "tone_1".to_string(), device_id: "DCE:gpp1".to_string(), process_id: Some(42), cpu_usage: 12.5, memory: 2048, status: ProcessStatus::Running as i32 };
+        let metrics = ApplicationMetricsReply { identifier: "DCE:app1".to_string(), cpu_usage: 12.5, memory: 2048, components: vec![component] };
+        dashboard.applications = vec![ApplicationRow { info, metrics: Some(metrics) }];
+        let refreshed = UNIX_EPOCH + Duration::from_secs(3600 + 2 * 60 + 3);
+        dashboard.push_event(refreshed, "ODM ObjectAdded DEVICE gpp1 (DCE:gpp1)".to_string());
+        dashboard.refreshed = Some(refreshed);
+
+        //the devices are listed under their nodes, along with their states
+        let lines = render(&dashboard);
+        assert!(rendered(&lines, "node1") && rendered(&lines, "available") && rendered(&lines, "unavailable"), "{lines:#?}");
+        assert!(rendered(&lines, "DCE:gpp1") && rendered(&lines, "UNLOCKED ENABLED ACTIVE") && rendered(&lines, "cpu=4"), "{lines:#?}");
+        assert!(rendered(&lines, "DCE:gpp2") && rendered(&lines, "not answering"), "{lines:#?}");
+        let node2 = lines.iter().position(|l| l.contains("node2")).unwrap();
+        assert!(lines.iter().position(|l| l.contains("gpp1")).unwrap() < node2 && lines.iter().position(|l| l.contains("gpp2")).unwrap() > node2, "{lines:#?}");
+
+        //the applications along with the usage of their components
+        assert!(rendered(&lines, "DCE:app1") && rendered(&lines, "started") && rendered(&lines, "12.5") && rendered(&lines, "2048"), "{lines:#?}");
+        assert!(rendered(&lines, "tone_1") && rendered(&lines, "RUNNING"), "{lines:#?}");
+
+        //the recent events and the time of the refresh
+        assert!(rendered(&lines, "01:02:03 ODM ObjectAdded DEVICE gpp1 (DCE:gpp1)"), "{lines:#?}");
+        assert!(rendered(&lines, "refreshed at 01:02:03"), "{lines:#?}");
+
+        //an error replaces the refresh time
+        dashboard.error = Some("transport error".to_string());
+        assert!(rendered(&render(&dashboard), "http://localhost:5000  transport error  q: quit"));
+    }
+
+    #[test]
+    fn test_events() {
+        let mut dashboard = Dashboard::default();
+        for i in 0..EVENT_LINES + 10 {
+            dashboard.push_event(UNIX_EPOCH, format!("event {i}"));
+        }
+        assert_eq!(dashboard.events.len(), EVENT_LINES);
+        assert_eq!(dashboard.events.front().unwrap(), &format!("00:00:00 event {}", EVENT_LINES + 9));
+        assert_eq!(clock(UNIX_EPOCH + Duration::from_secs(86400 + 23 * 3600 + 59 * 60 + 58)), "23:59:58");
+        assert_eq!(clock(SystemTime::UNIX_EPOCH - Duration::from_secs(1)), "00:00:00");
+
+        let object = DomainObject { producer_id: "DCE:domain".to_string(), source_id: "DCE:gpp1".to_string(), source_name: "gpp1".to_string(), source_category: SourceCategoryType::Device as i32 };
+        let event = DomainManagementEvent { event: Some(OdmEvent::ObjectAdded(object)), sequence: 1 };
+        assert_eq!(odm_line(&event), "ObjectAdded DEVICE gpp1 (DCE:gpp1)");
+        let failed = AllocationFailed { producer_id: "DCE:domain".to_string(), allocation_id: "alloc1".to_string(), source_id: "DCE:app1".to_string(), allocated_device: "DCE:gpp1".to_string() };
+        let event = DomainManagementEvent { event: Some(OdmEvent::AllocationFailed(failed)), sequence: 2 };
+        assert_eq!(odm_line(&event), "AllocationFailed alloc1 on DCE:gpp1 (DCE:app1)");
+        assert_eq!(odm_line(&DomainManagementEvent::default()), "unknown event");
+
+        let event = StateChangeEvent { producer_id: "DCE:node1".to_string(), source_id: "DCE:gpp1".to_string(), state_change_category: StateChangeCategoryType::UsageStateEvent as i32, state_change_from: StateChangeType::Idle as i32, state_change_to: StateChangeType::Busy as i32, sequence: 3 };
+        assert_eq!(idm_line(&event), "USAGE_STATE_EVENT DCE:gpp1 IDLE -> BUSY");
+        let event = StateChangeEvent { state_change_from: 42, ..event };
+        assert_eq!(idm_line(&event), "USAGE_STATE_EVENT DCE:gpp1 ? -> BUSY");
+    }
+}
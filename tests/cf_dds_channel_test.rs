@@ -0,0 +1,107 @@
+#[cfg(all(test, feature = "dds"))]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use rustdds::DomainParticipant;
+    use serde::{Deserialize, Serialize};
+    use tokio_stream::StreamExt;
+
+    use scars::cf::bulkio::{BulkioInPort, BulkioMessage, BulkioOutPort, PrecisionUtcTime, StreamSri};
+    use scars::cf::dds_channel::{DdsError, DdsEventChannel, DdsTopicTrait, LoopbackTopic, Result, RtpsTopic};
+    use scars::cf::events::{EventChannel, EventChannelTrait, EventStream, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+
+    /// Topic of a binding failing to reach the DDS domain.
+    struct UnreachableTopic;
+
+    impl DdsTopicTrait<u32> for UnreachableTopic {
+        fn topic_name(&self) -> &str {
+            "Telemetry"
+        }
+
+        fn write(&self, _sample: &u32) -> Result<()> {
+            Err(DdsError::TopicError { topic: "Telemetry".to_string(), message: "no participant".to_string() })
+        }
+
+        fn read(&self) -> Result<EventStream<u32>> {
+            Err(DdsError::TopicError { topic: "Telemetry".to_string(), message: "no participant".to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dds_event_channel() {
+        let channel = DdsEventChannel::new(Arc::new(LoopbackTopic::new("IDM_Channel")));
+        assert_eq!(channel.name(), "IDM_Channel");
+        let mut events = channel.subscribe_from(0);
+        let busy = StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        };
+        channel.push(busy.clone());
+        channel.push(busy.clone());
+        assert_eq!(events.next().await, Some((1, busy.clone())));
+        assert_eq!(events.next().await, Some((2, busy)));
+
+        //the failed writes are counted, the failed reads giving an empty stream
+        let channel = DdsEventChannel::new(Arc::new(UnreachableTopic));
+        channel.push(1);
+        match channel.publish(&2) {
+            Err(DdsError::TopicError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(channel.failed_writes(), 2);
+        assert_eq!(channel.subscribe().next().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_dds_port_transport() {
+        let topic: LoopbackTopic<BulkioMessage<f32>> = LoopbackTopic::new("SignalData");
+        let mut out_port = BulkioOutPort::new("dataFloat_out", EventChannel::new("dataFloat_out"));
+        let transport = DdsEventChannel::new(Arc::new(topic));
+        out_port.connect("dds_connection", Arc::new(transport.clone()));
+
+        //the provides port receives the samples read from the topic
+        let channel = EventChannel::new("dataFloat_in");
+        let mut in_port = BulkioInPort::new("dataFloat_in", &channel);
+        let forward = transport.forward(&channel);
+        let sri = StreamSri::new("tone").with_sample_rate(8000.0);
+        out_port.push_sri(sri.clone());
+        out_port.push_packet(vec![0.5, -0.5], PrecisionUtcTime::now(), true, "tone");
+        let block = tokio::task::spawn_blocking(move || in_port.get_packet(Duration::from_secs(1))).await.unwrap().unwrap();
+        assert_eq!((block.data, block.sri, block.eos), (vec![0.5, -0.5], sri, true));
+        assert_eq!(transport.failed_writes(), 0);
+        forward.abort();
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+    struct Telemetry {
+        source: String,
+        level: f32,
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rtps_topic() {
+        //two participants of a domain, reaching each other over RTPS
+        let (radio, mission) = (DomainParticipant::new(17).unwrap(), DomainParticipant::new(17).unwrap());
+        let radio = DdsEventChannel::new(Arc::new(RtpsTopic::<Telemetry>::new(&radio, "RadioTelemetry", "Telemetry").unwrap()));
+        let mission = DdsEventChannel::new(Arc::new(RtpsTopic::<Telemetry>::new(&mission, "RadioTelemetry", "Telemetry").unwrap()));
+        assert_eq!(mission.name(), "RadioTelemetry");
+        let mut events = mission.subscribe_from(0);
+        let sample = Telemetry { source: "DCE:gpp".to_string(), level: -42.5 };
+
+        //the samples written before the writer and the reader matched are lost, the writes going on until one is received
+        let received = tokio::time::timeout(Duration::from_secs(20), async {
+            loop {
+                radio.push(sample.clone());
+                if let Ok(event) = tokio::time::timeout(Duration::from_millis(200), events.next()).await {
+                    return event;
+                }
+            }
+        });
+        assert_eq!(received.await.unwrap(), Some((1, sample)));
+        assert_eq!(radio.failed_writes(), 0);
+    }
+}
@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+
+    use scars::cf::common_types::AnyValue;
+    use scars::cf::profile::dcd::DeviceConfiguration;
+    use scars::cf::device_manager::{DeviceManager, DeviceManagerError};
+    use scars::cf::file_system::{FileSystem, FileSystemTrait};
+    use scars::cf::profile::ProfileError;
+    use scars::cf::retry::RetryPolicy;
+    use scars::cf::rpc::device_manager::device_manager_client::DeviceManagerClient;
+    use scars::cf::rpc::device_manager::{
+        GetComponentImplementationIdRequest, RegisteredDevicesRequest, ShutdownRequest,
+    };
+
+    const DCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<deviceconfiguration id="DCE:node" name="node">
+  <devicemanagersoftpkg>
+    <localfile name="/mgr/DeviceManager.spd.xml"/>
+  </devicemanagersoftpkg>
+  <componentfiles>
+    <componentfile id="device_file" type="SPD">
+      <localfile name="/devices/Device/Device.spd.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="device_file"/>
+      <componentinstantiation id="DCE:device_1">
+        <usagename>device_1</usagename>
+        <componentproperties>
+          <simpleref refid="LOG_LEVEL" value="3"/>
+        </componentproperties>
+      </componentinstantiation>
+      <componentinstantiation id="DCE:device_2"/>
+    </componentplacement>
+  </partitioning>
+</deviceconfiguration>
+"#;
+
+    #[test]
+    fn test_parse_dcd() {
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        assert_eq!(dcd.id, "DCE:node");
+        assert_eq!(dcd.name, "node");
+        assert_eq!(
+            dcd.device_manager_softpkg.as_deref(),
+            Some("/mgr/DeviceManager.spd.xml")
+        );
+        assert_eq!(dcd.domain_manager, None);
+        assert_eq!(dcd.placements.len(), 1);
+        assert_eq!(DeviceConfiguration::parse(&dcd.to_xml(), "node.dcd.xml").unwrap(), dcd);
+        let xml = DCD.replace("</deviceconfiguration>", "<domainmanager><namingservice name=\"DOMAIN\"/></domainmanager></deviceconfiguration>");
+        let joining = DeviceConfiguration::parse(&xml, "node.dcd.xml").unwrap();
+        assert_eq!(joining.domain_manager.as_deref(), Some("DOMAIN"));
+        assert_eq!(DeviceConfiguration::parse(&joining.to_xml(), "node.dcd.xml").unwrap(), joining);
+
+        let placement = &dcd.placements[0];
+        assert_eq!(
+            dcd.component_file(placement).unwrap().local_file,
+            "/devices/Device/Device.spd.xml"
+        );
+        let instantiations = &placement.instantiations;
+        assert_eq!(instantiations.len(), 2);
+        assert_eq!(instantiations[0].usage_name.as_deref(), Some("device_1"));
+        assert_eq!(instantiations[0].properties[0].id, "LOG_LEVEL");
+        assert_eq!(
+            instantiations[0].properties[0].value,
+            AnyValue::String("3".to_string())
+        );
+        assert_eq!(instantiations[1].usage_name, None);
+
+        //placements must reference a declared component file
+        let xml = DCD.replace("refid=\"device_file\"", "refid=\"unknown\"");
+        match DeviceConfiguration::parse(&xml, "node.dcd.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match DeviceConfiguration::parse("<softpkg/>", "node.dcd.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match DeviceConfiguration::from_file(Path::new("/nonexistent/node.dcd.xml")) {
+            Err(ProfileError::ProfileNotFound { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    fn device_manager(dcd: &str) -> DeviceManager {
+        let dcd = DeviceConfiguration::parse(dcd, "node.dcd.xml").unwrap();
+        DeviceManager::new(dcd, Path::new("/sdr/dev"))
+            .with_launcher(Path::new(env!("CARGO_BIN_EXE_scars-device-launcher")))
+    }
+
+    #[tokio::test]
+    async fn test_launch_node() {
+        let manager = device_manager(DCD);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let node = tokio::spawn(manager.clone().run(listener));
+
+        //wait for both devices to register
+        let mut registered = Vec::new();
+        for _ in 0..200 {
+            registered = manager.registered_devices();
+            if registered.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(registered.len(), 2);
+        let device_1 = registered
+            .iter()
+            .find(|d| d.identifier == "DCE:device_1")
+            .unwrap();
+        assert_eq!(device_1.label, "device_1");
+        assert_eq!(
+            device_1.profile_name,
+            "/sdr/dev/devices/Device/Device.spd.xml"
+        );
+
+        let mut client = DeviceManagerClient::connect(endpoint).await.unwrap();
+        let devices = client
+            .registered_devices(RegisteredDevicesRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .devices;
+        assert_eq!(devices.len(), 2);
+
+        let implementation_id = |id: &str| GetComponentImplementationIdRequest {
+            component_instantiation_id: id.to_string(),
+        };
+        let reply = client
+            .get_component_implementation_id(implementation_id("DCE:device_2"))
+            .await
+            .unwrap();
+        assert_eq!(reply.into_inner().implementation_id, "device");
+        let reply = client
+            .get_component_implementation_id(implementation_id("DCE:unknown"))
+            .await
+            .unwrap();
+        assert_eq!(reply.into_inner().implementation_id, "");
+
+        //shutdown releases the devices, which unregister
+        client.shutdown(ShutdownRequest {}).await.unwrap();
+        node.await.unwrap().unwrap();
+        assert!(manager.registered_devices().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_files() {
+        let (fs_root, logs) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let log = logs.path().join("device_1.log");
+        std::fs::write(&log, "started\n").unwrap();
+        let xml = DCD.replace(
+            "<simpleref refid=\"LOG_LEVEL\" value=\"3\"/>",
+            &format!("<simpleref refid=\"LOGGING_CONFIG_URI\" value=\"file://{}\"/>", log.display()),
+        );
+        let dcd = DeviceConfiguration::parse(&xml, "node.dcd.xml").unwrap();
+        let manager = DeviceManager::new(dcd, fs_root.path())
+            .with_launcher(Path::new(env!("CARGO_BIN_EXE_scars-device-launcher")));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node = tokio::spawn(manager.clone().run(listener));
+        for _ in 0..200 {
+            if manager.registered_devices().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        //the log file of the component is reachable through the node FileSystem
+        let fs = FileSystem::new(fs_root.path());
+        assert_eq!(fs.read("/logs/DCE:device_1.log").unwrap(), b"started\n");
+        assert!(!fs.exists("/logs/DCE:device_2.log").unwrap());
+
+        manager.shutdown();
+        node.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_domain_manager() {
+        let unused = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let domain_manager = format!("http://{}", unused.local_addr().unwrap());
+        drop(unused);
+
+        let xml = DCD.replace(
+            "</deviceconfiguration>",
+            "<domainmanager><namingservice name=\"DOMAIN\"/></domainmanager></deviceconfiguration>",
+        );
+        let retry_policy = RetryPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(20),
+            max_attempts: 3,
+            ..RetryPolicy::default()
+        };
+        let manager = device_manager(&xml).with_domain_manager(&domain_manager).with_retry_policy(retry_policy);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        match manager.clone().run(listener).await {
+            Err(DeviceManagerError::RegisterError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert!(manager.registered_devices().is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::device::{AdminType, Device, DeviceError, DeviceTrait, UsageType};
+    use scars::cf::events::{
+        EventChannel, StateChangeCategoryType, StateChangeEvent, StateChangeType, IDM_CHANNEL_NAME,
+    };
+
+    #[test]
+    fn test_usage_state_follows_capacities() {
+        let mut d = Device::new("DCE:tuner", "tuner")
+            .with_capacity("channels", AnyValue::UShort(2))
+            .with_capacity("bandwidth", AnyValue::Double(40e6));
+        let channel = vec![DataType::new("channels", AnyValue::UShort(1))];
+
+        assert!(d.allocate_capacity(&channel).unwrap());
+        assert_eq!(d.usage_state(), UsageType::ACTIVE);
+        assert!(d.allocate_capacity(&channel).unwrap());
+        assert_eq!(d.usage_state(), UsageType::BUSY);
+        assert!(!d.allocate_capacity(&channel).unwrap());
+
+        d.deallocate_capacity(&channel).unwrap();
+        assert_eq!(d.usage_state(), UsageType::ACTIVE);
+        d.deallocate_capacity(&channel).unwrap();
+        assert_eq!(d.usage_state(), UsageType::IDLE);
+        assert_eq!(d.available_capacity("channels"), Some(&AnyValue::UShort(2)));
+    }
+
+    #[test]
+    fn test_allocation_is_atomic() {
+        let mut d = Device::new("DCE:tuner", "tuner")
+            .with_capacity("channels", AnyValue::UShort(2))
+            .with_capacity("bandwidth", AnyValue::Double(40e6));
+
+        let too_much = vec![
+            DataType::new("channels", AnyValue::UShort(1)),
+            DataType::new("bandwidth", AnyValue::Double(80e6)),
+        ];
+        assert!(!d.allocate_capacity(&too_much).unwrap());
+        assert_eq!(d.available_capacity("channels"), Some(&AnyValue::UShort(2)));
+        assert_eq!(d.usage_state(), UsageType::IDLE);
+
+        let wrong_type = vec![DataType::new("channels", AnyValue::Long(1))];
+        match d.allocate_capacity(&wrong_type) {
+            Err(DeviceError::InvalidCapacity { capacities, .. }) => assert_eq!(capacities, wrong_type),
+            r => panic!("{:?}", r),
+        }
+
+        d.set_admin_state(AdminType::LOCKED);
+        match d.allocate_capacity(&vec![]) {
+            Err(DeviceError::InvalidState { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_state_change_events() {
+        let idm: EventChannel<StateChangeEvent> = EventChannel::new(IDM_CHANNEL_NAME);
+        let events = idm.subscribe();
+
+        let mut d = Device::new("DCE:tuner", "tuner")
+            .with_capacity("channels", AnyValue::UShort(1))
+            .with_event_channel(idm);
+        d.allocate_capacity(&vec![DataType::new("channels", AnyValue::UShort(1))])
+            .unwrap();
+        d.set_admin_state(AdminType::LOCKED);
+        d.deallocate_capacity(&vec![DataType::new("channels", AnyValue::UShort(1))])
+            .unwrap();
+
+        let received: Vec<(StateChangeCategoryType, StateChangeType, StateChangeType)> = events
+            .try_iter()
+            .map(|e| (e.state_change_category, e.state_change_from, e.state_change_to))
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                (StateChangeCategoryType::USAGE_STATE_EVENT, StateChangeType::IDLE, StateChangeType::BUSY),
+                (StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT, StateChangeType::UNLOCKED, StateChangeType::SHUTTING_DOWN),
+                (StateChangeCategoryType::USAGE_STATE_EVENT, StateChangeType::BUSY, StateChangeType::IDLE),
+                (StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT, StateChangeType::SHUTTING_DOWN, StateChangeType::LOCKED),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1299 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+    use tokio::net::TcpListener;
+
+    use scars::cf::device_manager::DeviceManager;
+    use scars::cf::profile::dcd::DeviceConfiguration;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef, AllocationRequest};
+    use scars::cf::application_factory::DeploymentContext;
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::connection_manager::{EndpointRequest, EndpointResolution};
+    use scars::cf::device::{AdminType, Device, DeviceTrait};
+    use scars::cf::domain_manager::{
+        ApplicationStatus, DomainDevice, DomainManager, DomainManagerError, HeartbeatPolicy, RegisteredDeviceManager,
+        RegisteredService, RemoteDomainAccess, RemoteDomainManager,
+    };
+    use scars::cf::resource::Resource;
+    use scars::cf::retry::RetryPolicy;
+    use scars::cf::sim_device::{SimExecutableDevice, SimLoadableDevice};
+    use scars::cf::events::{
+        DomainManagementEvent, EventChannel, SourceCategoryType, StateChangeCategoryType, StateChangeEvent,
+        StateChangeType, IDM_CHANNEL_NAME, LOG_CHANNEL_NAME, ODM_CHANNEL_NAME,
+    };
+    use scars::cf::file_system::{FileSystem, FileSystemTrait};
+    use scars::cf::log::{LogLevelType, LogRecord, Logger};
+    use scars::cf::rpc;
+    use scars::cf::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+    use scars::cf::rpc::domain_manager::{
+        self as wire, domain_management_event::Event, ApplicationFactoriesRequest, ApplicationMetricsRequest,
+        ApplicationsRequest, ConfigureApplicationRequest, ConnectEndpointsRequest, QueryApplicationRequest,
+        StartApplicationRequest, StopApplicationRequest, DeviceCapacitiesRequest,
+        DeviceManagersRequest, DisconnectEndpointsRequest, ListConnectionsRequest, PushLogRecordsRequest,
+        RegisterDeviceManagerRequest, SubscribeLogRecordsRequest, SubscribeRequest,
+    };
+
+    use crate::common;
+
+    const DCD: &str = r#"<deviceconfiguration id="DCE:node" name="node"/>"#;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_register_device_manager() {
+        let (root_1, root_2) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        std::fs::write(root_2.path().join("gpp.spd.xml"), "<softpkg/>").unwrap();
+        let (endpoint_1, endpoint_2) = (common::serve_file_system(root_1.path()).await, common::serve_file_system(root_2.path()).await);
+
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let node = |endpoint: &str| RegisteredDeviceManager {
+            identifier: "DCE:node".to_string(),
+            label: "node".to_string(),
+            endpoint: endpoint.to_string(),
+        };
+
+        //registering again remounts the file system served by the node
+        domain.register_device_manager(node(&endpoint_1)).unwrap();
+        domain.register_device_manager(node(&endpoint_2)).unwrap();
+        assert_eq!(domain.device_managers().len(), 1);
+        let file_manager = domain.file_manager();
+        assert!(file_manager.lock().unwrap().exists("/node/gpp.spd.xml").unwrap());
+        assert_eq!(file_manager.lock().unwrap().read("/node/gpp.spd.xml").unwrap(), b"<softpkg/>");
+        file_manager.lock().unwrap().write("/node/osc", b"osc").unwrap();
+        assert_eq!(std::fs::read(root_2.path().join("osc")).unwrap(), b"osc");
+
+        //nothing of the domain host is mounted, the node path being the one of its service
+        assert!(!file_manager.lock().unwrap().exists("/node/etc").unwrap());
+        match file_manager.lock().unwrap().read(&format!("/node{}", root_2.path().join("osc").display())) {
+            Err(_) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //another node cannot take the same mount point
+        let mut other = node(&endpoint_1);
+        other.identifier = "DCE:other".to_string();
+        match domain.register_device_manager(other) {
+            Err(DomainManagerError::RegisterError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        domain.unregister_device_manager("DCE:node").unwrap();
+        assert!(file_manager.lock().unwrap().get_mounts().is_empty());
+        match domain.unregister_device_manager("DCE:node") {
+            Err(DomainManagerError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_mount_node_file_system() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("devices")).unwrap();
+        std::fs::write(root.path().join("devices/gpp.spd.xml"), "<softpkg/>").unwrap();
+
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd, root.path()).with_domain_manager(&endpoint);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node_task = tokio::spawn(node.clone().run(listener));
+
+        let file_manager = domain.file_manager();
+        let mut mounted = false;
+        for _ in 0..200 {
+            mounted = file_manager
+                .lock()
+                .unwrap()
+                .exists("/node/devices/gpp.spd.xml")
+                .unwrap();
+            if mounted {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(mounted);
+        assert_eq!(domain.device_managers()[0].identifier, "DCE:node");
+
+        //the node unregisters on shutdown
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        assert!(domain.device_managers().is_empty());
+        assert!(file_manager.lock().unwrap().get_mounts().is_empty());
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    fn device(device_manager_id: &str, identifier: &str) -> DomainDevice {
+        DomainDevice {
+            device_manager_id: device_manager_id.to_string(),
+            identifier: identifier.to_string(),
+            label: identifier.to_string(),
+            profile_name: "/devices/Device/Device.spd.xml".to_string(),
+            endpoint: "http://127.0.0.1:1".to_string(),
+        }
+    }
+
+    fn service(device_manager_id: &str, name: &str) -> RegisteredService {
+        RegisteredService {
+            device_manager_id: device_manager_id.to_string(),
+            name: name.to_string(),
+            endpoint: "http://127.0.0.1:1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_register_devices_and_services() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        for (identifier, label) in [("DCE:node_1", "node_1"), ("DCE:node_2", "node_2")] {
+            domain
+                .register_device_manager(RegisteredDeviceManager {
+                    identifier: identifier.to_string(),
+                    label: label.to_string(),
+                    endpoint: "http://127.0.0.1:1".to_string(),
+                })
+                .unwrap();
+        }
+
+        match domain.register_device(device("DCE:unknown", "DCE:device")) {
+            Err(DomainManagerError::DeviceMgrNotRegistered { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        domain
+            .register_device(device("DCE:node_1", "DCE:device_1"))
+            .unwrap();
+        domain
+            .register_device(device("DCE:node_2", "DCE:device_2"))
+            .unwrap();
+        domain
+            .register_device(device("DCE:node_2", "DCE:device_2"))
+            .unwrap();
+        assert_eq!(domain.devices().len(), 2);
+
+        domain
+            .register_service(service("DCE:node_1", "log"))
+            .unwrap();
+        match domain.register_service(service("DCE:node_2", "log")) {
+            Err(DomainManagerError::RegisterError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(domain.services().len(), 1);
+
+        //unregistering a node drops its devices and services
+        domain.unregister_device_manager("DCE:node_1").unwrap();
+        assert_eq!(domain.devices(), vec![device("DCE:node_2", "DCE:device_2")]);
+        assert!(domain.services().is_empty());
+
+        domain.unregister_device("DCE:device_2").unwrap();
+        match domain.unregister_device("DCE:device_2") {
+            Err(DomainManagerError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match domain.unregister_service("log") {
+            Err(DomainManagerError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert!(domain.applications().is_empty());
+        assert!(domain.application_factories().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_registered_device_capacities() {
+        let gpp = Device::new("DCE:gpp", "gpp").with_capacity("mips", AnyValue::ULong(100));
+        let gpp = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(gpp))));
+        let staging = tempfile::tempdir().unwrap();
+        let device_endpoint = common::serve_device(gpp.clone(), staging.path()).await;
+
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.register_device_manager(RegisteredDeviceManager {
+            identifier: "DCE:node".to_string(),
+            label: "node".to_string(),
+            endpoint: "http://127.0.0.1:1".to_string(),
+        }).unwrap();
+        let mut registered = device("DCE:node", "DCE:gpp");
+        registered.endpoint = device_endpoint;
+        domain.register_device(registered).unwrap();
+
+        //the registered devices are queried through their Device service
+        let capacities = domain.device_capacities();
+        assert_eq!(capacities.len(), 1);
+        assert_eq!((capacities[0].identifier.as_str(), capacities[0].admin_state), ("DCE:gpp", AdminType::UNLOCKED));
+        assert_eq!(capacities[0].allocation_properties, vec![DataType::new("mips", AnyValue::ULong(100))]);
+        gpp.lock().unwrap().allocate_capacity(&vec![DataType::new("mips", AnyValue::ULong(40))]).unwrap();
+        assert_eq!(domain.device_capacities()[0].allocation_properties, vec![DataType::new("mips", AnyValue::ULong(60))]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let devices = client.device_capacities(DeviceCapacitiesRequest {}).await.unwrap().into_inner().devices;
+        assert_eq!(rpc::device_capacities_from_wire(devices[0].clone()).unwrap(), domain.device_capacities()[0]);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unregister_device_allocations() {
+        let staging = tempfile::tempdir().unwrap();
+        let device_endpoint = common::serve_device(common::sim_gpp(), staging.path()).await;
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let node = RegisteredDeviceManager {
+            identifier: "DCE:node".to_string(),
+            label: "node".to_string(),
+            endpoint: "http://127.0.0.1:1".to_string(),
+        };
+        let register = |domain: &DomainManager| {
+            domain.register_device_manager(node.clone()).unwrap();
+            let mut registered = device("DCE:node", "DCE:gpp");
+            registered.endpoint = device_endpoint.clone();
+            domain.register_device(registered).unwrap();
+            let request = AllocationRequest { request_id: "request_1".to_string(), source_id: "DCE:tone:tone_1".to_string(), ..Default::default() };
+            domain.deployment().allocation_manager().lock().unwrap().allocate(&[request]).unwrap()[0].allocation_id.clone()
+        };
+        let events = domain.odm_channel().subscribe();
+
+        //the allocations on an unregistered device are failed, their applications notified
+        let allocation_id = register(&domain);
+        assert!(!domain.allocations()[0].failed);
+        domain.unregister_device("DCE:gpp").unwrap();
+        assert_eq!((domain.allocations()[0].allocation_id.clone(), domain.allocations()[0].failed), (allocation_id.clone(), true));
+        assert!(domain.device_capacities().is_empty());
+        assert_eq!(domain.deployment().host("DCE:gpp"), "DCE:gpp");
+        let failed: Vec<DomainManagementEvent> = events.try_iter().filter(|e| matches!(e, DomainManagementEvent::AllocationFailed { .. })).collect();
+        assert_eq!(failed, vec![DomainManagementEvent::AllocationFailed { producer_id: "DCE:domain".to_string(), allocation_id, source_id: "DCE:tone:tone_1".to_string(), allocated_device: "DCE:gpp".to_string() }]);
+
+        //so are those on the devices of an unregistered node
+        let failed_id = domain.allocations()[0].allocation_id.clone();
+        domain.deployment().allocation_manager().lock().unwrap().deallocate(&[failed_id]).unwrap();
+        let allocation_id = register(&domain);
+        domain.unregister_device_manager("DCE:node").unwrap();
+        assert_eq!((domain.allocations()[0].allocation_id.clone(), domain.allocations()[0].failed), (allocation_id, true));
+        assert!(domain.device_capacities().is_empty());
+        assert_eq!(events.try_iter().filter(|e| matches!(e, DomainManagementEvent::AllocationFailed { .. })).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_node_devices_register_with_domain() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+
+        let dcd = DCD.replace(
+            "/>",
+            r#"><componentfiles><componentfile id="device_file" type="SPD">
+            <localfile name="/devices/Device/Device.spd.xml"/></componentfile></componentfiles>
+            <partitioning><componentplacement><componentfileref refid="device_file"/>
+            <componentinstantiation id="DCE:device"/></componentplacement></partitioning>
+            </deviceconfiguration>"#,
+        );
+        let root = tempfile::tempdir().unwrap();
+        let dcd = DeviceConfiguration::parse(&dcd, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd, root.path())
+            .with_launcher(Path::new(env!("CARGO_BIN_EXE_scars-device-launcher")))
+            .with_domain_manager(&endpoint);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node_task = tokio::spawn(node.clone().run(listener));
+
+        for _ in 0..200 {
+            if !domain.devices().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(domain.devices()[0].identifier, "DCE:device");
+        assert_eq!(domain.devices()[0].device_manager_id, "DCE:node");
+
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let device_managers = client
+            .device_managers(DeviceManagersRequest {})
+            .await
+            .unwrap()
+            .into_inner()
+            .device_managers;
+        assert_eq!(device_managers.len(), 1);
+        assert_eq!(device_managers[0].devices.len(), 1);
+        let factories = client
+            .application_factories(ApplicationFactoriesRequest {})
+            .await
+            .unwrap();
+        assert!(factories.into_inner().application_factories.is_empty());
+
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        assert!(domain.devices().is_empty());
+        assert!(domain.device_managers().is_empty());
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_registered_deployment() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let dom = tempfile::tempdir().unwrap();
+        common::tone_waveform(dom.path());
+        common::write_files(dom.path(), &[("waveforms/tone/osc", "osc")]);
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(dom.path()))).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+
+        //no device registered, no deployment
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        let plan = domain.application_factories()[0].validate("tone_1", &[]).unwrap();
+        assert!(!plan.is_valid());
+        assert!(plan.errors.iter().all(|e| !e.contains("no deployment context")), "{:?}", plan.errors);
+
+        let dcd = DCD.replace(
+            "/>",
+            r#"><componentfiles><componentfile id="gpp_file" type="SPD">
+            <localfile name="/devices/SimExecutableDevice/SimExecutableDevice.spd.xml"/></componentfile></componentfiles>
+            <partitioning><componentplacement><componentfileref refid="gpp_file"/>
+            <componentinstantiation id="DCE:gpp"/></componentplacement></partitioning>
+            </deviceconfiguration>"#,
+        );
+        let root = tempfile::tempdir().unwrap();
+        let dcd = DeviceConfiguration::parse(&dcd, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd, root.path())
+            .with_launcher(Path::new(env!("CARGO_BIN_EXE_scars-device-launcher")))
+            .with_domain_manager(&endpoint);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node_task = tokio::spawn(node.clone().run(listener));
+        for _ in 0..200 {
+            if !domain.devices().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        //the devices registered by the node are the ones the applications are deployed on
+        assert_eq!(domain.deployment().host("DCE:gpp"), "DCE:node");
+        let plan = domain.application_factories()[0].validate("tone_1", &[]).unwrap();
+        assert!(plan.is_valid(), "{:?}", plan.errors);
+        assert_eq!(plan.component_implementations[0].element_id, "cpp");
+        domain.registry().register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        let info = domain.applications()[0].clone();
+        assert_eq!((info.components[0].device_id.as_str(), info.components[0].process_id.is_some()), ("DCE:gpp", true));
+        assert_eq!(domain.device_capacities()[0].identifier, "DCE:gpp");
+        domain.release_application(&identifier).unwrap();
+
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_install_application() {
+        let root = tempfile::tempdir().unwrap();
+        common::write_waveform(root.path());
+
+        let channel = EventChannel::new(ODM_CHANNEL_NAME);
+        let events = channel.subscribe();
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV").with_event_channel(channel);
+        domain
+            .file_manager()
+            .lock()
+            .unwrap()
+            .mount("/dom", Arc::new(FileSystem::new(root.path())))
+            .unwrap();
+
+        let identifier = domain
+            .install_application("/dom/waveforms/fm/fm.sad.xml")
+            .unwrap();
+        assert_eq!(identifier, "DCE:fm");
+        assert_eq!(domain.profile_cache().len(), 4);
+        let factory = &domain.application_factories()[0];
+        assert_eq!(factory.name(), "fm");
+        assert_eq!(
+            factory.component("demod_file").unwrap().spd_file_name,
+            "/dom/components/demod/demod.spd.xml"
+        );
+        match events.try_recv().unwrap() {
+            DomainManagementEvent::ObjectAdded { source_id, .. } => assert_eq!(source_id, "DCE:fm"),
+            e => panic!("{:?}", e),
+        }
+
+        match domain.install_application("/dom/waveforms/fm/fm.sad.xml") {
+            Err(DomainManagerError::ApplicationAlreadyInstalled { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match domain.install_application("/dom/waveforms/am/am.sad.xml") {
+            Err(DomainManagerError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        domain.uninstall_application("DCE:fm").unwrap();
+        assert!(domain.application_factories().is_empty());
+        match events.try_recv().unwrap() {
+            DomainManagementEvent::ObjectRemoved { source_id, .. } => {
+                assert_eq!(source_id, "DCE:fm")
+            }
+            e => panic!("{:?}", e),
+        }
+        match domain.uninstall_application("DCE:fm") {
+            Err(DomainManagerError::InvalidIdentifier { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the referenced profiles must exist and parse
+        std::fs::write(
+            root.path().join("components/demod/demod.prf.xml"),
+            "<props/>",
+        )
+        .unwrap();
+        match domain.install_application("/dom/waveforms/fm/fm.sad.xml") {
+            Err(DomainManagerError::InvalidProfile { file_name, .. }) => {
+                assert_eq!(file_name, "/dom/components/demod/demod.prf.xml")
+            }
+            r => panic!("{:?}", r),
+        }
+        std::fs::remove_file(root.path().join("components/demod/demod.scd.xml")).unwrap();
+        std::fs::write(
+            root.path().join("components/demod/demod.prf.xml"),
+            "<properties/>",
+        )
+        .unwrap();
+        match domain.install_application("/dom/waveforms/fm/fm.sad.xml") {
+            Err(DomainManagerError::ApplicationInstallationError { message }) => {
+                assert!(message.contains("demod.scd.xml"), "{message}")
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+
+    /// Returns a persistent domain deploying on the device, the waveforms of the directory mounted at /dom.
+    fn persistent_domain(root: &Path, device: &Arc<Mutex<SimExecutableDevice>>, registry: &ComponentRegistry) -> DomainManager {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(root))).unwrap();
+
+        let allocation_manager = common::allocation_manager(device);
+        let deployment = common::deployment(domain.file_manager(), allocation_manager, device, registry.clone());
+        domain.with_deployment(deployment).with_persistence(&root.join("domain.json")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_persistence() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        let osc = Arc::new(Mutex::new(Resource::new("osc")));
+        registry.register_component("tone_1/osc_1", osc.clone());
+
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        domain
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: "http://127.0.0.1:1".to_string(),
+            })
+            .unwrap();
+        domain.register_device(device("DCE:node", "DCE:gpp")).unwrap();
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        assert_eq!(identifier, "DCE:tone:tone_1");
+        match domain.create_application("DCE:tone", "tone_1", &vec![], &[]) {
+            Err(DomainManagerError::CreateApplicationError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        let info = domain.applications()[0].clone();
+        assert_eq!(info.status, ApplicationStatus::RUNNING);
+        assert_eq!(info.components[0].name_binding, "tone_1/osc_1");
+        assert_eq!(info.components[0].process_id, gpp.lock().unwrap().process_ids().first().cloned());
+
+        //a restarted DomainManager restores the domain
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        assert_eq!(domain.device_managers()[0].identifier, "DCE:node");
+        assert_eq!(domain.devices()[0].identifier, "DCE:gpp");
+        assert_eq!(domain.application_factories()[0].identifier(), "DCE:tone");
+        let recovered = &domain.applications()[0];
+        assert_eq!(recovered.status, ApplicationStatus::RECOVERED);
+        assert_eq!(recovered.components, info.components);
+        match domain.application_metrics("DCE:tone:tone_1") {
+            Err(DomainManagerError::InvalidIdentifier { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the node does not answer, its devices are gone along with it
+        domain.reconcile().await.unwrap();
+        assert!(domain.device_managers().is_empty());
+        assert!(domain.devices().is_empty());
+        assert_eq!(domain.applications()[0].status, ApplicationStatus::DEAD);
+
+        //the recovered application is torn down through the devices
+        domain.release_application("DCE:tone:tone_1").unwrap();
+        assert!(gpp.lock().unwrap().process_ids().is_empty());
+        assert_eq!(gpp.lock().unwrap().loadable().load_count("waveforms/tone/osc"), 0);
+        assert!(osc.lock().unwrap().released());
+        assert!(registry.names().is_empty());
+        match domain.release_application("DCE:tone:tone_1") {
+            Err(DomainManagerError::InvalidIdentifier { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        assert!(domain.applications().is_empty());
+        assert_eq!(domain.application_factories().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_application_factories() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let sad = std::fs::read_to_string(root.path().join("waveforms/tone/tone.sad.xml")).unwrap();
+        std::fs::write(root.path().join("waveforms/tone/tone2.sad.xml"), sad.replace("DCE:tone", "DCE:tone2")).unwrap();
+
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        domain.install_application("/dom/waveforms/tone/tone2.sad.xml").unwrap();
+
+        //a restarted DomainManager does not parse the installed SADs while booting
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        assert!(domain.profile_cache().is_empty());
+
+        //they are loaded on their first lookup, the ones no longer loading being kept pending
+        std::fs::write(root.path().join("waveforms/tone/tone2.sad.xml"), "<notasad/>").unwrap();
+        match domain.uninstall_application("DCE:tone2") {
+            Err(DomainManagerError::InvalidIdentifier { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(domain.load_application_factories(), 0);
+        let identifiers: Vec<String> = domain.application_factories().iter().map(|f| f.identifier().to_string()).collect();
+        assert_eq!(identifiers, vec!["DCE:tone"]);
+        assert!(!domain.profile_cache().is_empty());
+
+        //and retried on the next lookup
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        std::fs::write(root.path().join("waveforms/tone/tone2.sad.xml"), sad.replace("DCE:tone", "DCE:tone2")).unwrap();
+        domain.uninstall_application("DCE:tone2").unwrap();
+        assert_eq!(domain.load_application_factories(), 0);
+
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        assert_eq!(domain.application_factories().len(), 1);
+        match domain.install_application("/dom/waveforms/tone/tone.sad.xml") {
+            Err(DomainManagerError::ApplicationAlreadyInstalled { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_application_metrics() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        registry.register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        let process_id = gpp.lock().unwrap().process_ids()[0];
+        gpp.lock().unwrap().set_process_usage(process_id, 12.5, 2048);
+
+        //operators query the usage of the running applications through the DomainManager service
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+
+        let request = ApplicationMetricsRequest { identifier: identifier.clone() };
+        let metrics = client.application_metrics(request).await.unwrap().into_inner();
+        assert_eq!((metrics.identifier.as_str(), metrics.cpu_usage, metrics.memory), ("DCE:tone:tone_1", 12.5, 2048));
+        assert_eq!(metrics.components[0].device_id, "DCE:gpp");
+        assert_eq!(metrics.components[0].process_id, Some(process_id));
+        assert_eq!(metrics.components[0].status(), wire::ProcessStatus::Running);
+
+        let unknown = ApplicationMetricsRequest { identifier: "DCE:tone:tone_2".to_string() };
+        assert_eq!(client.application_metrics(unknown).await.unwrap_err().code(), tonic::Code::NotFound);
+
+        //and the capacities of the devices of the AllocationManager
+        let devices = client.device_capacities(DeviceCapacitiesRequest {}).await.unwrap().into_inner().devices;
+        let devices: Vec<_> = devices.into_iter().map(|d| rpc::device_capacities_from_wire(d).unwrap()).collect();
+        assert_eq!(devices, domain.device_capacities());
+        assert_eq!((devices.len(), devices[0].identifier.as_str()), (1, "DCE:gpp"));
+        assert_eq!(devices[0].allocation_properties, gpp.lock().unwrap().allocation_properties());
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_application_control() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        let osc = Resource::new("osc").with_property("frequency", AnyValue::Double(1000.0));
+        registry.register_component("tone_1/osc_1", Arc::new(Mutex::new(osc)));
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+
+        //operators start, stop and configure the running applications through the DomainManager service
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let started = |applications: Vec<wire::ApplicationInfo>| applications.iter().any(|a| a.started);
+
+        client.start_application(StartApplicationRequest { identifier: identifier.clone() }).await.unwrap();
+        assert!(started(client.applications(ApplicationsRequest {}).await.unwrap().into_inner().applications));
+
+        let frequency = rpc::properties_to_wire(&vec![DataType::new("frequency", AnyValue::Double(2000.0))]);
+        let request = ConfigureApplicationRequest { identifier: identifier.clone(), properties: frequency.clone() };
+        client.configure_application(request).await.unwrap();
+        let request = QueryApplicationRequest { identifier: identifier.clone(), properties: Vec::new() };
+        let values = client.query_application(request).await.unwrap().into_inner().properties;
+        assert!(values.contains(&frequency[0]));
+        assert_eq!(domain.query_application(&identifier, &vec![DataType::new("frequency", AnyValue::String(String::new()))]).unwrap()[0].value, AnyValue::Double(2000.0));
+
+        //the components of the running applications are operated by component identifier
+        let component = format!("osc_1:{identifier}");
+        domain.configure_application(&component, &vec![DataType::new("frequency", AnyValue::Double(3000.0))]).unwrap();
+        let request = QueryApplicationRequest { identifier: component, properties: Vec::new() };
+        let values = rpc::properties_from_wire(&client.query_application(request).await.unwrap().into_inner().properties).unwrap();
+        assert!(values.contains(&DataType::new("frequency", AnyValue::Double(3000.0))));
+
+        let unknown = rpc::properties_to_wire(&vec![DataType::new("amplitude", AnyValue::Double(1.0))]);
+        let request = ConfigureApplicationRequest { identifier: identifier.clone(), properties: unknown };
+        assert_eq!(client.configure_application(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        client.stop_application(StopApplicationRequest { identifier: identifier.clone() }).await.unwrap();
+        assert!(!started(client.applications(ApplicationsRequest {}).await.unwrap().into_inner().applications));
+        let request = StartApplicationRequest { identifier: "DCE:tone:tone_2".to_string() };
+        assert_eq!(client.start_application(request).await.unwrap_err().code(), tonic::Code::NotFound);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        let osc = Arc::new(Mutex::new(Resource::new("osc")));
+        registry.register_component("tone_1/osc_1", osc.clone());
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd, root.path()).with_domain_manager(&endpoint);
+        let node_task = tokio::spawn(node.run(TcpListener::bind("127.0.0.1:0").await.unwrap()));
+        let mut registered = false;
+        for _ in 0..200 {
+            registered = !domain.device_managers().is_empty();
+            if registered {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(registered);
+
+        //the applications are released and the nodes shut down before exiting
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        client.shutdown(wire::ShutdownRequest {}).await.unwrap();
+        domain_task.await.unwrap().unwrap();
+        node_task.await.unwrap().unwrap();
+        assert!(domain.applications().is_empty());
+        assert!(gpp.lock().unwrap().process_ids().is_empty());
+        assert!(osc.lock().unwrap().released());
+        assert!(domain.device_managers().is_empty());
+        assert_eq!(domain.file_manager().lock().unwrap().get_mounts().len(), 1);
+
+        //the final state is persisted
+        drop(domain);
+        let domain = persistent_domain(root.path(), &gpp, &registry);
+        assert!(domain.applications().is_empty());
+        assert!(domain.device_managers().is_empty());
+        assert_eq!(domain.application_factories().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_slow_node() {
+        let root = tempfile::tempdir().unwrap();
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+
+        //a node accepting the connections but never answering, registered first
+        let slow = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        domain.register_device_manager(RegisteredDeviceManager {
+            identifier: "DCE:slow".to_string(),
+            label: "slow".to_string(),
+            endpoint: format!("http://{}", slow.local_addr().unwrap()),
+        }).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd, root.path()).with_domain_manager(&endpoint);
+        let node_task = tokio::spawn(node.run(TcpListener::bind("127.0.0.1:0").await.unwrap()));
+        for _ in 0..200 {
+            if domain.device_managers().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(domain.device_managers().len(), 2);
+
+        //the slow node does not use up the time of the other one
+        domain.shutdown();
+        tokio::time::timeout(Duration::from_secs(5), node_task).await.unwrap().unwrap().unwrap();
+        domain_task.await.unwrap().unwrap();
+        assert!(domain.device_managers().is_empty());
+        drop(slow);
+    }
+
+    #[tokio::test]
+    async fn test_federation() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        registry.register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let peer = persistent_domain(root.path(), &gpp, &registry);
+        peer.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        peer.register_device_manager(RegisteredDeviceManager {
+            identifier: "DCE:node".to_string(),
+            label: "node".to_string(),
+            endpoint: "http://127.0.0.1:1".to_string(),
+        }).unwrap();
+        peer.register_device(DomainDevice {
+            device_manager_id: "DCE:node".to_string(),
+            identifier: "DCE:gpp".to_string(),
+            label: "gpp".to_string(),
+            profile_name: "/node/gpp.spd.xml".to_string(),
+            endpoint: "http://127.0.0.1:2".to_string(),
+        }).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let peer_task = tokio::spawn(peer.clone().run(listener));
+
+        let domain = DomainManager::new("DCE:mission", "MISSION")
+            .with_remote_domain("DCE:domain", RemoteDomainAccess::DEPLOY)
+            .with_remote_domain("DCE:observer", RemoteDomainAccess::BROWSE);
+        let events = domain.odm_channel().subscribe();
+        let remote = |identifier: &str| RemoteDomainManager {
+            identifier: identifier.to_string(),
+            label: "REDHAWK_DEV".to_string(),
+            endpoint: endpoint.clone(),
+        };
+
+        //only the allowlisted peers register
+        match domain.register_remote_domain_manager(remote("DCE:rogue")) {
+            Err(DomainManagerError::AccessDenied { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match domain.register_remote_domain_manager(remote("DCE:mission")) {
+            Err(DomainManagerError::RegisterError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        domain.register_remote_domain_manager(remote("DCE:domain")).unwrap();
+        domain.register_remote_domain_manager(remote("DCE:domain")).unwrap();
+        assert_eq!(domain.remote_domain_managers(), vec![remote("DCE:domain")]);
+        match events.try_recv().unwrap() {
+            DomainManagementEvent::ObjectAdded { source_id, source_category: SourceCategoryType::DOMAIN_MANAGER, .. } => assert_eq!(source_id, "DCE:domain"),
+            e => panic!("{:?}", e),
+        }
+        assert!(events.try_recv().is_err());
+
+        //the peer applications and devices are browsed
+        assert!(domain.remote_applications("DCE:domain").await.unwrap().is_empty());
+        let devices = domain.remote_devices("DCE:domain").await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!((devices[0].device_manager_id.as_str(), devices[0].identifier.as_str()), ("DCE:node", "DCE:gpp"));
+
+        //applications are deployed on the peers allowed to
+        let identifier = domain.create_remote_application("DCE:domain", "DCE:tone", "tone_1", &vec![], &[]).await.unwrap();
+        assert_eq!(identifier, "DCE:tone:tone_1");
+        let applications = domain.remote_applications("DCE:domain").await.unwrap();
+        assert_eq!((applications[0].identifier.as_str(), applications[0].name.as_str()), ("DCE:tone:tone_1", "tone_1"));
+        assert_eq!(peer.applications()[0].identifier, "DCE:tone:tone_1");
+        match domain.create_remote_application("DCE:domain", "DCE:unknown", "unknown_1", &vec![], &[]).await {
+            Err(DomainManagerError::RemoteDomainError { identifier, .. }) => assert_eq!(identifier, "DCE:domain"),
+            r => panic!("{:?}", r),
+        }
+
+        domain.register_remote_domain_manager(remote("DCE:observer")).unwrap();
+        assert_eq!(domain.remote_applications("DCE:observer").await.unwrap().len(), 1);
+        match domain.release_remote_application("DCE:observer", &identifier).await {
+            Err(DomainManagerError::AccessDenied { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        domain.release_remote_application("DCE:domain", &identifier).await.unwrap();
+        assert!(peer.applications().is_empty());
+
+        domain.unregister_remote_domain_manager("DCE:observer").unwrap();
+        match domain.remote_applications("DCE:observer").await {
+            Err(DomainManagerError::InvalidIdentifier { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match domain.unregister_remote_domain_manager("DCE:observer") {
+            Err(DomainManagerError::InvalidObjectReference { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        peer.shutdown();
+        peer_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_domain_event_channels() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let events = domain.odm_channel().subscribe();
+        let channels = domain.event_channel_manager();
+        assert_eq!(channels.channel_names(), vec![IDM_CHANNEL_NAME, LOG_CHANNEL_NAME, ODM_CHANNEL_NAME]);
+        assert_eq!(channels.remote_channel_names(), vec![IDM_CHANNEL_NAME, LOG_CHANNEL_NAME, ODM_CHANNEL_NAME]);
+        assert!(channels.get::<DomainManagementEvent>(ODM_CHANNEL_NAME).is_some());
+
+        //the registrations are published on ODM
+        domain
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: "http://127.0.0.1:1".to_string(),
+            })
+            .unwrap();
+        domain.register_device(device("DCE:node", "DCE:gpp")).unwrap();
+        domain.register_device(device("DCE:node", "DCE:gpp")).unwrap();
+        domain.register_service(service("DCE:node", "log")).unwrap();
+        domain.unregister_device_manager("DCE:node").unwrap();
+        let published: Vec<(bool, String, SourceCategoryType)> = events
+            .try_iter()
+            .map(|e| match e {
+                DomainManagementEvent::ObjectAdded { source_id, source_category, .. } => (true, source_id, source_category),
+                DomainManagementEvent::ObjectRemoved { source_id, source_category, .. } => (false, source_id, source_category),
+                e => panic!("{:?}", e),
+            })
+            .collect();
+        assert_eq!(
+            published,
+            vec![
+                (true, "DCE:node".to_string(), SourceCategoryType::DEVICE_MANAGER),
+                (true, "DCE:gpp".to_string(), SourceCategoryType::DEVICE),
+                (true, "log".to_string(), SourceCategoryType::SERVICE),
+                (false, "DCE:gpp".to_string(), SourceCategoryType::DEVICE),
+                (false, "log".to_string(), SourceCategoryType::SERVICE),
+                (false, "DCE:node".to_string(), SourceCategoryType::DEVICE_MANAGER),
+            ]
+        );
+
+        //the administrative state changes of the devices are forwarded from IDM to ODM
+        let state_changes = domain.idm_channel().subscribe();
+        let mut gpp = Device::new("DCE:gpp", "gpp").with_event_channel(domain.idm_channel());
+        gpp.set_admin_state(AdminType::LOCKED);
+        assert_eq!(state_changes.try_recv().unwrap().state_change_to, StateChangeType::LOCKED);
+        assert_eq!(
+            events.try_recv().unwrap(),
+            DomainManagementEvent::AdministrativeStateChanged {
+                producer_id: "DCE:domain".to_string(),
+                source_id: "DCE:gpp".to_string(),
+                state_change_from: StateChangeType::UNLOCKED,
+                state_change_to: StateChangeType::LOCKED,
+            }
+        );
+
+        //external tools subscribe through the DomainManager service
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let mut odm = client.subscribe_odm_events(SubscribeRequest::default()).await.unwrap().into_inner();
+        let mut idm = client.subscribe_idm_events(SubscribeRequest::default()).await.unwrap().into_inner();
+        let state_changes = SubscribeRequest { source_ids: vec!["DCE:g*".to_string()], event_types: vec!["AdministrativeStateChanged".to_string()], ..Default::default() };
+        let mut filtered = client.subscribe_odm_events(state_changes).await.unwrap().into_inner();
+
+        client
+            .register_device_manager(RegisterDeviceManagerRequest {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: "http://127.0.0.1:1".to_string(),
+            })
+            .await
+            .unwrap();
+        let added = odm.message().await.unwrap().unwrap();
+        let added_sequence = added.sequence;
+        match added.event {
+            Some(Event::ObjectAdded(object)) => {
+                assert_eq!(object.source_id, "DCE:node");
+                assert_eq!(object.source_category(), wire::SourceCategoryType::DeviceManager);
+            }
+            e => panic!("{:?}", e),
+        }
+
+        let unlocked = StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::ADMINISTRATIVE_STATE_EVENT,
+            state_change_from: StateChangeType::LOCKED,
+            state_change_to: StateChangeType::UNLOCKED,
+        };
+        client.push_state_change_event(wire::StateChangeEvent::from(&unlocked)).await.unwrap();
+        let pushed = idm.message().await.unwrap().unwrap();
+        assert_eq!(pushed.state_change_to(), wire::StateChangeType::Unlocked);
+        match odm.message().await.unwrap().unwrap().event {
+            Some(Event::AdministrativeStateChanged(changed)) => {
+                assert_eq!(changed.source_id, "DCE:gpp");
+                assert_eq!(changed.state_change_to(), wire::StateChangeType::Unlocked);
+            }
+            e => panic!("{:?}", e),
+        }
+
+        //the filtered subscribers only receive the events of their sources and types
+        let changed = filtered.message().await.unwrap().unwrap();
+        assert_eq!(changed.sequence, added_sequence + 1);
+        assert!(matches!(changed.event, Some(Event::AdministrativeStateChanged(_))));
+
+        //a reconnecting subscriber receives the events it missed
+        let last_seen = SubscribeRequest { last_sequence: Some(added_sequence), ..Default::default() };
+        let mut reconnected = client.subscribe_odm_events(last_seen).await.unwrap().into_inner();
+        let missed = reconnected.message().await.unwrap().unwrap();
+        assert_eq!(missed.sequence, added_sequence + 1);
+        assert!(matches!(missed.event, Some(Event::AdministrativeStateChanged(_))));
+
+        let mut invalid = wire::StateChangeEvent::from(&unlocked);
+        invalid.state_change_to = 42;
+        assert_eq!(client.push_state_change_event(invalid).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_log_channel() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let logger = Logger::new("demod_1:DCE:fm:fm_1", "demod_1").with_consumer(Arc::new(Mutex::new(domain.log_channel())));
+        let records = domain.log_channel().subscribe();
+        logger.log(LogLevelType::ADMINISTRATIVE_EVENT, "started");
+        assert_eq!(records.try_recv().unwrap().message, "started");
+
+        //the subscribers only receive the records passing their filter
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let mut all = client.subscribe_log_records(SubscribeLogRecordsRequest::default()).await.unwrap().into_inner();
+        let mut errors = client
+            .subscribe_log_records(SubscribeLogRecordsRequest { log_level: LogLevelType::USAGE_ERROR.value().into(), producers: vec!["source_1".to_string()], ..Default::default() })
+            .await
+            .unwrap()
+            .into_inner();
+
+        logger.log(LogLevelType::USAGE_ERROR, "unknown mode");
+        let overflow = LogRecord::new("source_1:DCE:fm:fm_1", "source_1", LogLevelType::FLOW_CONTROL_ERROR, "overflow");
+        let overflow = LogRecord { time: UNIX_EPOCH + Duration::from_micros(1_500_000), ..overflow };
+        let tuned = LogRecord::new("source_1:DCE:fm:fm_1", "source_1", LogLevelType::ADMINISTRATIVE_EVENT, "tuned");
+        client.push_log_records(PushLogRecordsRequest { records: vec![(&tuned).into(), (&overflow).into()] }).await.unwrap();
+
+        let messages: Vec<String> = vec![all.message().await, all.message().await, all.message().await].into_iter().map(|r| r.unwrap().unwrap().message).collect();
+        assert_eq!(messages, vec!["unknown mode", "tuned", "overflow"]);
+        let received = errors.message().await.unwrap().unwrap();
+        assert_eq!(rpc::log_record_from_wire(received).unwrap(), overflow);
+
+        let mut invalid = wire::LogRecord::from(&overflow);
+        invalid.level = 26;
+        assert_eq!(client.push_log_records(PushLogRecordsRequest { records: vec![invalid] }).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let request = SubscribeLogRecordsRequest { log_level: 26, ..Default::default() };
+        assert_eq!(client.subscribe_log_records(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        //the retained records are received first, alone when the new ones are not followed
+        let request = SubscribeLogRecordsRequest { producers: vec!["source_*".to_string()], retained: true, retained_only: true, ..Default::default() };
+        let mut retained = client.subscribe_log_records(request.clone()).await.unwrap().into_inner();
+        assert_eq!(retained.message().await.unwrap().unwrap().message, "tuned");
+        assert_eq!(retained.message().await.unwrap().unwrap().message, "overflow");
+        assert!(retained.message().await.unwrap().is_none());
+        let mut followed = client.subscribe_log_records(SubscribeLogRecordsRequest { retained_only: false, ..request }).await.unwrap().into_inner();
+        assert_eq!(followed.message().await.unwrap().unwrap().message, "tuned");
+        assert_eq!(followed.message().await.unwrap().unwrap().message, "overflow");
+        logger.log(LogLevelType::USAGE_ERROR, "ignored");
+        client.push_log_records(PushLogRecordsRequest { records: vec![(&tuned).into()] }).await.unwrap();
+        assert_eq!(followed.message().await.unwrap().unwrap().message, "tuned");
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_connection_manager() {
+        let registry = ComponentRegistry::new();
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(AllocationManager::new()));
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let deployment = DeploymentContext::new(domain.file_manager(), allocation_manager, registry.clone());
+        let domain = domain.with_deployment(deployment);
+        let demod = Arc::new(Mutex::new(Resource::new("demod_1").with_uses_port("log_out")));
+        registry.register_component("fm_1/demod_1", demod.clone());
+
+        //the connection to the service is completed once it registers with the domain
+        let log = EndpointRequest {
+            resolution: EndpointResolution::SERVICE("log".to_string()),
+            port_name: String::new(),
+        };
+        let uses = EndpointRequest {
+            resolution: EndpointResolution::COMPONENT("fm_1/demod_1".to_string()),
+            port_name: "log_out".to_string(),
+        };
+        domain.connection_manager().connect(uses.clone(), log.clone(), "DCE:fm:fm_1", "log").unwrap();
+        assert!(!domain.connection_manager().connections()[0].connected);
+        domain
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: "http://127.0.0.1:1".to_string(),
+            })
+            .unwrap();
+        domain.register_service(service("DCE:node", "log")).unwrap();
+        assert!(domain.connection_manager().connections()[0].connected);
+        assert_eq!(demod.lock().unwrap().connections("log_out")[0].1, "http://127.0.0.1:1");
+
+        //pending again when its node leaves the domain
+        domain.unregister_device_manager("DCE:node").unwrap();
+        assert!(!domain.connection_manager().connections()[0].connected);
+        assert!(demod.lock().unwrap().connections("log_out").is_empty());
+
+        //operators manage the connections through the DomainManager service
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+
+        let monitor = EndpointRequest {
+            resolution: EndpointResolution::OBJECT("http://127.0.0.1:7001".to_string()),
+            port_name: String::new(),
+        };
+        let reply = client
+            .connect_endpoints(ConnectEndpointsRequest {
+                uses_endpoint: Some((&uses).into()),
+                provides_endpoint: Some((&monitor).into()),
+                requester_id: "operator".to_string(),
+                connection_id: "monitor".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.connection_record_id, "operator:monitor");
+        assert_eq!(demod.lock().unwrap().connections("log_out")[0].1, "http://127.0.0.1:7001");
+
+        let connections = client.list_connections(ListConnectionsRequest {}).await.unwrap().into_inner().connections;
+        let listed: Vec<(&str, bool)> = connections.iter().map(|c| (c.connection_record_id.as_str(), c.connected)).collect();
+        assert_eq!(listed, vec![("DCE:fm:fm_1:log", false), ("operator:monitor", true)]);
+        assert_eq!(connections[0].provides_endpoint, Some((&log).into()));
+
+        client
+            .disconnect_endpoints(DisconnectEndpointsRequest { connection_record_id: "operator:monitor".to_string() })
+            .await
+            .unwrap();
+        assert!(demod.lock().unwrap().connections("log_out").is_empty());
+        let unknown = DisconnectEndpointsRequest { connection_record_id: "operator:monitor".to_string() };
+        assert_eq!(client.disconnect_endpoints(unknown).await.unwrap_err().code(), tonic::Code::NotFound);
+        let missing = ConnectEndpointsRequest {
+            uses_endpoint: Some((&uses).into()),
+            provides_endpoint: None,
+            requester_id: "operator".to_string(),
+            connection_id: "missing".to_string(),
+        };
+        assert_eq!(client.connect_endpoints(missing).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_registration_retry() {
+        let root = tempfile::tempdir().unwrap();
+
+        //the DomainManager comes up after the node
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let retry_policy = RetryPolicy {
+            initial_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(100),
+            max_attempts: 50,
+            ..RetryPolicy::default()
+        };
+        let node = DeviceManager::new(dcd, root.path())
+            .with_domain_manager(&format!("http://{address}"))
+            .with_retry_policy(retry_policy);
+        let node_task = tokio::spawn(node.clone().run(TcpListener::bind("127.0.0.1:0").await.unwrap()));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let domain_task = tokio::spawn(domain.clone().run(TcpListener::bind(address).await.unwrap()));
+        let mut registered = false;
+        for _ in 0..200 {
+            registered = !domain.device_managers().is_empty();
+            if registered {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(registered);
+
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat() {
+        let root = tempfile::tempdir().unwrap();
+        let heartbeat = HeartbeatPolicy {
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_millis(200),
+        };
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV").with_heartbeat(heartbeat);
+        let events = domain.odm_channel().subscribe();
+
+        //a node serving the DeviceManager service, registered by hand
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd.clone(), root.path());
+        let node_task = tokio::spawn(node.clone().run(listener));
+        domain
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: format!("http://{address}"),
+            })
+            .unwrap();
+        domain.register_device(device("DCE:node", "DCE:gpp")).unwrap();
+        domain.heartbeat().await;
+        assert!(domain.is_available("DCE:node"));
+        let _ = events.try_iter().count();
+
+        //unavailable along with its devices once silent for the timeout
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        domain.heartbeat().await;
+        assert!(domain.is_available("DCE:gpp"));
+        tokio::time::sleep(heartbeat.timeout).await;
+        domain.heartbeat().await;
+        assert!(!domain.is_available("DCE:node"));
+        assert!(!domain.is_available("DCE:gpp"));
+        let changed: Vec<(String, bool)> = events
+            .try_iter()
+            .map(|e| match e {
+                DomainManagementEvent::AvailabilityChanged { source_id, available, .. } => (source_id, available),
+                e => panic!("{:?}", e),
+            })
+            .collect();
+        assert_eq!(changed, vec![("DCE:node".to_string(), false), ("DCE:gpp".to_string(), false)]);
+        domain.heartbeat().await;
+        assert!(events.try_recv().is_err());
+
+        //available again once answering, the DomainManager service telling so
+        let node = DeviceManager::new(dcd, root.path());
+        let node_task = tokio::spawn(node.clone().run(TcpListener::bind(address).await.unwrap()));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+        let mut available = false;
+        for _ in 0..100 {
+            available = domain.is_available("DCE:gpp");
+            if available {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(available);
+        assert!(matches!(events.try_recv(), Ok(DomainManagementEvent::AvailabilityChanged { available: true, .. })));
+        let mut client = DomainManagerClient::connect(endpoint).await.unwrap();
+        let reply = client.device_managers(DeviceManagersRequest {}).await.unwrap().into_inner();
+        assert!(reply.device_managers[0].available);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_heartbeat_placement() {
+        let heartbeat = HeartbeatPolicy {
+            interval: Duration::from_millis(100),
+            timeout: Duration::from_millis(200),
+        };
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV").with_heartbeat(heartbeat);
+        let dom = tempfile::tempdir().unwrap();
+        common::tone_waveform(dom.path());
+        common::write_files(dom.path(), &[("waveforms/tone/osc", "osc")]);
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(dom.path()))).unwrap();
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+
+        //a node registered by hand, its device served apart and answering throughout
+        let (root, staging) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let dcd = DeviceConfiguration::parse(DCD, "node.dcd.xml").unwrap();
+        let node = DeviceManager::new(dcd.clone(), root.path());
+        let node_task = tokio::spawn(node.clone().run(listener));
+        domain
+            .register_device_manager(RegisteredDeviceManager {
+                identifier: "DCE:node".to_string(),
+                label: "node".to_string(),
+                endpoint: format!("http://{address}"),
+            })
+            .unwrap();
+        let endpoint = common::serve_device(common::sim_gpp(), staging.path()).await;
+        domain.register_device(DomainDevice { endpoint, ..device("DCE:node", "DCE:gpp") }).unwrap();
+        domain.registry().register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        domain.release_application(&identifier).unwrap();
+
+        //the devices of a silent node are neither allocated nor deployed on
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+        domain.heartbeat().await;
+        tokio::time::sleep(heartbeat.timeout).await;
+        domain.heartbeat().await;
+        assert!(!domain.is_available("DCE:gpp"));
+        assert!(!domain.deployment().is_available("DCE:gpp"));
+        domain.registry().register_component("tone_2/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        assert!(domain.create_application("DCE:tone", "tone_2", &vec![], &[]).is_err());
+        assert!(domain.applications().is_empty());
+
+        //and are again once it answers
+        let node = DeviceManager::new(dcd, root.path());
+        let node_task = tokio::spawn(node.clone().run(TcpListener::bind(address).await.unwrap()));
+        for _ in 0..100 {
+            domain.heartbeat().await;
+            if domain.is_available("DCE:gpp") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(domain.deployment().is_available("DCE:gpp"));
+        let identifier = domain.create_application("DCE:tone", "tone_2", &vec![], &[]).unwrap();
+        domain.release_application(&identifier).unwrap();
+
+        node.shutdown();
+        node_task.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef, AllocationManagerTrait, AllocationRequest};
+    use scars::cf::application_factory::DeploymentContext;
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::device::Device;
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::domain_recorder::{
+        self, DomainOperation, DomainRecorder, RecordedOperation, RecordingAllocationManager, RecordingError, ReplayPace,
+    };
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::resource::Resource;
+    use scars::cf::sim_device::{SimExecutableDevice, SimLoadableDevice};
+
+    /// Writes the tone waveform, made of a single oscillator, under waveforms/tone.
+    fn tone_waveform(root: &Path) {
+        let files = [
+            (
+                "waveforms/tone/tone.sad.xml",
+                r#"<softwareassembly id="DCE:tone" name="tone"><componentfiles>
+                <componentfile id="osc_file" type="SPD"><localfile name="osc.spd.xml"/></componentfile>
+                </componentfiles><partitioning><componentplacement><componentfileref refid="osc_file"/>
+                <componentinstantiation id="osc_1"/></componentplacement></partitioning>
+                <assemblycontroller><componentinstantiationref refid="osc_1"/></assemblycontroller></softwareassembly>"#,
+            ),
+            (
+                "waveforms/tone/osc.spd.xml",
+                r#"<softpkg id="DCE:osc" name="osc"><implementation id="cpp">
+                <code type="Executable"><localfile name="osc"/></code></implementation></softpkg>"#,
+            ),
+        ];
+        for (name, xml) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, xml).unwrap();
+        }
+    }
+
+    /// Returns a domain deploying on a simulated GPP, its operations and allocations recorded.
+    fn recorded_domain(root: &Path, recorder: &DomainRecorder) -> DomainManager {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(root))).unwrap();
+
+        let gpp = Arc::new(Mutex::new(SimExecutableDevice::new(SimLoadableDevice::new(Device::new("DCE:gpp", "gpp")))));
+        let registry = ComponentRegistry::new();
+        registry.register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(gpp.clone());
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(allocation_manager));
+        let allocation_manager: AllocationManagerRef =
+            Arc::new(Mutex::new(RecordingAllocationManager::new(allocation_manager, recorder.clone())));
+        let deployment = DeploymentContext::new(domain.file_manager(), allocation_manager, registry)
+            .with_device(gpp)
+            .with_resolve_timeout(Duration::from_millis(50));
+        domain.with_deployment(deployment).with_recorder(recorder.clone())
+    }
+
+    /// Runs the tone waveform on a domain, a creation failing along the way.
+    fn run_tone(domain: &DomainManager) {
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        assert!(domain.create_application("DCE:other", "other_1", &vec![], &[]).is_err());
+        domain.start_application("DCE:tone:tone_1").unwrap();
+        domain.stop_application("DCE:tone:tone_1").unwrap();
+        domain.release_application("DCE:tone:tone_1").unwrap();
+        domain.uninstall_application("DCE:tone").unwrap();
+    }
+
+    #[test]
+    fn test_recording() {
+        let root = tempfile::tempdir().unwrap();
+        tone_waveform(root.path());
+        let recording_file = root.path().join("recording.jsonl");
+
+        let recorder = DomainRecorder::with_file(&recording_file).unwrap();
+        let domain = recorded_domain(root.path(), &recorder);
+        run_tone(&domain);
+
+        //the operations are recorded in the order they complete, the allocations along the deployment
+        let records = recorder.records();
+        let kinds: Vec<&str> = records
+            .iter()
+            .map(|r| match r.operation {
+                DomainOperation::InstallApplication { .. } => "install",
+                DomainOperation::UninstallApplication { .. } => "uninstall",
+                DomainOperation::CreateApplication { .. } => "create",
+                DomainOperation::ReleaseApplication { .. } => "release",
+                DomainOperation::StartApplication { .. } => "start",
+                DomainOperation::StopApplication { .. } => "stop",
+                DomainOperation::ConfigureApplication { .. } => "configure",
+                DomainOperation::Allocate { .. } => "allocate",
+                DomainOperation::Deallocate { .. } => "deallocate",
+            })
+            .filter(|k| !k.ends_with("allocate"))
+            .collect();
+        assert_eq!(kinds, vec!["install", "create", "create", "start", "stop", "release", "uninstall"]);
+        assert!(records.iter().any(|r| r.operation.is_allocation()));
+        assert!(records[0].error.is_none());
+        let failed: Vec<&RecordedOperation> = records.iter().filter(|r| r.error.is_some()).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            failed[0].operation,
+            DomainOperation::CreateApplication {
+                factory_identifier: "DCE:other".to_string(),
+                name: "other_1".to_string(),
+                init_configuration: vec![],
+                device_assignments: vec![],
+            }
+        );
+
+        //the file holds the same recording, appended to by another recorder
+        assert_eq!(domain_recorder::load(&recording_file).unwrap(), records);
+        let other = DomainRecorder::with_file(&recording_file).unwrap();
+        let operation = DomainOperation::ConfigureApplication {
+            identifier: "DCE:tone:tone_1".to_string(),
+            properties: vec![DataType::new("gain", AnyValue::Double(2.0))],
+        };
+        other.record(SystemTime::now(), operation.clone(), None);
+        let loaded = domain_recorder::load(&recording_file).unwrap();
+        assert_eq!(loaded.len(), records.len() + 1);
+        assert_eq!(loaded.last().unwrap().operation, operation);
+
+        //an invalid line is reported with its number
+        std::fs::write(&recording_file, format!("{}\nnot json\n", serde_json::to_string(&records[0]).unwrap())).unwrap();
+        match domain_recorder::load(&recording_file) {
+            Err(RecordingError::InvalidRecord { line: 2, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match domain_recorder::load(&root.path().join("missing.jsonl")) {
+            Err(RecordingError::FileError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_recording_allocation_manager() {
+        let recorder = DomainRecorder::new();
+        let mut allocation_manager = AllocationManager::new();
+        allocation_manager.register_device(Arc::new(Mutex::new(Device::new("DCE:device", "device"))));
+        let mut manager = RecordingAllocationManager::new(Arc::new(Mutex::new(allocation_manager)), recorder.clone());
+
+        let request = AllocationRequest {
+            request_id: "request_1".to_string(),
+            source_id: "DCE:tone:tone_1".to_string(),
+            ..Default::default()
+        };
+        let responses = manager.allocate(&[request]).unwrap();
+        let allocation_ids: Vec<String> = responses.iter().map(|r| r.allocation_id.clone()).collect();
+        assert_eq!(manager.list_allocations().len(), 1);
+        assert_eq!(manager.registered_devices().len(), 1);
+        assert!(manager.deallocate(&["unknown".to_string()]).is_err());
+        manager.deallocate(&allocation_ids).unwrap();
+
+        let records = recorder.records();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0].operation,
+            DomainOperation::Allocate {
+                request_ids: vec!["request_1".to_string()],
+                source_ids: vec!["DCE:tone:tone_1".to_string()],
+                allocation_ids: allocation_ids.clone(),
+            }
+        );
+        assert!(records[1].error.is_some());
+        assert_eq!(records[2].operation, DomainOperation::Deallocate { allocation_ids });
+        assert!(records[2].error.is_none());
+        assert!(domain_recorder::replayed_operations(&records).is_empty());
+    }
+
+    #[test]
+    fn test_replay() {
+        let root = tempfile::tempdir().unwrap();
+        tone_waveform(root.path());
+        let recorder = DomainRecorder::new();
+        run_tone(&recorded_domain(root.path(), &recorder));
+        let recording = recorder.records();
+
+        //replayed against a like domain, the operations have the same outcomes and allocations
+        for pace in [ReplayPace::Sequential, ReplayPace::Timed { speed: 1000.0 }] {
+            let replayed = DomainRecorder::new();
+            let report = domain_recorder::replay(&recorded_domain(root.path(), &replayed), &recording, pace);
+            assert_eq!(report.operations.len(), 7);
+            if pace == ReplayPace::Sequential {
+                assert!(!report.diverges(), "{:?}", report);
+                assert_eq!(report.allocations.as_ref(), Some(&report.recorded_allocations));
+            }
+            assert!(report.operations[2].error.is_some());
+        }
+
+        //without its waveform, the operations fail on the replayed domain
+        let other = tempfile::tempdir().unwrap();
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(other.path()))).unwrap();
+        let report = domain_recorder::replay(&domain, &recording, ReplayPace::Sequential);
+        assert!(report.diverges());
+        assert!(report.operations[0].diverges());
+        assert!(!report.operations[2].diverges());
+        assert!(report.allocations.is_none());
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    use scars::cf::event_service::EventChannelService;
+    use scars::cf::events::{EventChannelManager, EventChannelManagerError, EventFilter, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+    use scars::cf::rpc::event_channel::event_channel_manager_client::EventChannelManagerClient;
+    use scars::cf::rpc::event_channel::event_channel_manager_server::EventChannelManagerServer;
+    use scars::cf::rpc::event_channel::{ListChannelsRequest, PublishRequest, SubscribeChannelRequest};
+
+    fn state_change(source_id: &str, category: StateChangeCategoryType) -> StateChangeEvent {
+        StateChangeEvent {
+            producer_id: source_id.to_string(),
+            source_id: source_id.to_string(),
+            state_change_category: category,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_channels() {
+        let manager = EventChannelManager::new();
+        let states = manager.create_remote::<StateChangeEvent>("IDM_Channel").unwrap().with_history(4);
+        manager.create::<u32>("Telemetry").unwrap();
+        assert_eq!(manager.remote_channel_names(), vec!["IDM_Channel"]);
+
+        states.push(state_change("DCE:gpp_1", StateChangeCategoryType::USAGE_STATE_EVENT));
+        let mut subscription = manager.subscribe_remote("IDM_Channel", Some(0), EventFilter::default()).unwrap();
+        let encoded = tokio_stream::StreamExt::next(&mut subscription.events).await.unwrap();
+        assert_eq!((encoded.sequence, encoded.source_id.as_str(), encoded.event_type.as_str()), (1, "DCE:gpp_1", "USAGE_STATE_EVENT"));
+        assert_eq!(serde_json::from_str::<StateChangeEvent>(&encoded.payload).unwrap(), state_change("DCE:gpp_1", StateChangeCategoryType::USAGE_STATE_EVENT));
+
+        match manager.subscribe_remote("Telemetry", None, EventFilter::default()) {
+            Err(EventChannelManagerError::ChannelNotRemote { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match manager.publish_remote("Unknown", "{}") {
+            Err(EventChannelManagerError::ChannelDoesNotExist { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match manager.publish_remote("IDM_Channel", "{}") {
+            Err(EventChannelManagerError::InvalidEvent { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_channel_service() {
+        let manager = EventChannelManager::new();
+        let states = manager.create_remote::<StateChangeEvent>("IDM_Channel").unwrap();
+        manager.create::<u32>("Telemetry").unwrap();
+        let service = EventChannelService::new(manager);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(Server::builder().add_service(EventChannelManagerServer::new(service.clone())).serve_with_incoming(incoming));
+        let mut client = EventChannelManagerClient::connect(endpoint).await.unwrap();
+        assert_eq!(client.list_channels(ListChannelsRequest {}).await.unwrap().into_inner().names, vec!["IDM_Channel"]);
+
+        //the remote tools subscribe with a filter and publish events as JSON documents
+        let request = SubscribeChannelRequest { channel: "IDM_Channel".to_string(), source_ids: vec!["DCE:gpp_*".to_string()], event_types: vec!["USAGE_STATE_EVENT".to_string()], ..Default::default() };
+        let mut events = client.subscribe(request).await.unwrap().into_inner();
+        let published = [
+            state_change("DCE:gpp_1", StateChangeCategoryType::OPERATIONAL_STATE_EVENT),
+            state_change("DCE:fpga_1", StateChangeCategoryType::USAGE_STATE_EVENT),
+            state_change("DCE:gpp_2", StateChangeCategoryType::USAGE_STATE_EVENT),
+        ];
+        let requests: Vec<PublishRequest> = published.iter().map(|e| PublishRequest { channel: "IDM_Channel".to_string(), payload: serde_json::to_string(e).unwrap() }).collect();
+        assert_eq!(client.publish(tokio_stream::iter(requests)).await.unwrap().into_inner().published, 3);
+        let received = events.message().await.unwrap().unwrap();
+        assert_eq!((received.sequence, received.source_id.as_str()), (3, "DCE:gpp_2"));
+        assert_eq!(serde_json::from_str::<StateChangeEvent>(&received.payload).unwrap(), published[2]);
+
+        //the in-process publishers reach the remote subscribers
+        states.push(state_change("DCE:gpp_3", StateChangeCategoryType::USAGE_STATE_EVENT));
+        assert_eq!(events.message().await.unwrap().unwrap().source_id, "DCE:gpp_3");
+
+        let unknown = SubscribeChannelRequest { channel: "Unknown".to_string(), ..Default::default() };
+        assert_eq!(client.subscribe(unknown).await.unwrap_err().code(), tonic::Code::NotFound);
+        let local = SubscribeChannelRequest { channel: "Telemetry".to_string(), ..Default::default() };
+        assert_eq!(client.subscribe(local).await.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        let invalid = PublishRequest { channel: "IDM_Channel".to_string(), payload: "{}".to_string() };
+        assert_eq!(client.publish(tokio_stream::iter(vec![invalid])).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        //the closed subscriptions end
+        service.close_subscriptions();
+        assert!(events.message().await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,247 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant, UNIX_EPOCH};
+
+    use prost::Message;
+    use tokio_stream::StreamExt;
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::events::{
+        AbnormalComponentTerminationEvent, BackpressurePolicy, BroadcastEventChannel, DomainManagementEvent, EventChannel, EventChannelManager,
+        EventChannelManagerError, EventChannelRef, EventChannelTrait, EventFilter, PropertyChangeEvent, SourceCategoryType, StateChangeCategoryType,
+        StateChangeEvent, StateChangeType, SubscriberMetrics,
+    };
+    use scars::cf::rpc::{self, domain_manager as wire};
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        let channel: EventChannelRef<u32> = Arc::new(EventChannel::new("Telemetry"));
+        assert_eq!(channel.name(), "Telemetry");
+
+        //the subscribers receive the events pushed from their subscription on
+        channel.push(1);
+        let mut first = channel.subscribe();
+        channel.push(2);
+        let mut second = channel.subscribe();
+        channel.push(3);
+        assert_eq!(first.next().await, Some(2));
+        assert_eq!(first.next().await, Some(3));
+        assert_eq!(second.next().await, Some(3));
+
+        //dropped streams are unsubscribed
+        drop(first);
+        channel.push(4);
+        assert_eq!(second.next().await, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_durable_channel() {
+        let channel = EventChannel::new("Alarms").with_history(2);
+        for alarm in ["overheat", "overflow", "underrun"] {
+            channel.push(alarm.to_string());
+        }
+        assert_eq!(channel.last_sequence(), 3);
+
+        //the subscribers attaching with the last event seen receive the retained ones it missed
+        let mut missed = channel.subscribe_from(1);
+        assert_eq!(missed.next().await, Some((2, "overflow".to_string())));
+        assert_eq!(missed.next().await, Some((3, "underrun".to_string())));
+        let mut lagging = channel.subscribe_from(0);
+        channel.push("overload".to_string());
+        assert_eq!(missed.next().await, Some((4, "overload".to_string())));
+        assert_eq!(lagging.next().await, Some((2, "overflow".to_string())));
+
+        //the channels without history only deliver the events pushed from now on
+        let transient = EventChannel::new("Telemetry");
+        transient.push(1);
+        let mut events = transient.subscribe_from(0);
+        transient.push(2);
+        assert_eq!(events.next().await, Some((2, 2)));
+    }
+
+    #[tokio::test]
+    async fn test_backpressure() {
+        //the slow subscribers lose the oldest, or the newest, events past their queue size
+        let channel = EventChannel::new("Telemetry").with_queue_size(2).with_backpressure(BackpressurePolicy::DropOldest);
+        let mut oldest = channel.subscribe_filtered(None, |_| true);
+        (1..=4).for_each(|event| channel.push(event));
+        assert_eq!(oldest.metrics(), SubscriberMetrics { queue_depth: 2, max_queue_depth: 2, dropped: 2, disconnected: false });
+        assert_eq!(oldest.next().await, Some((3, 3)));
+        assert_eq!(oldest.metrics().queue_depth, 1);
+
+        let channel = EventChannel::new("Telemetry").with_queue_size(2);
+        let mut newest = channel.subscribe_filtered(None, |event| event % 2 == 1);
+        (1..=6).for_each(|event| channel.push(event));
+        assert_eq!(newest.next().await, Some((1, 1)));
+        assert_eq!(newest.next().await, Some((3, 3)));
+        assert_eq!(channel.subscriber_metrics(), vec![SubscriberMetrics { queue_depth: 0, max_queue_depth: 2, dropped: 1, disconnected: false }]);
+
+        //the wedged subscribers are disconnected, without stalling the other ones
+        let channel = EventChannel::new("Telemetry").with_queue_size(1).with_backpressure(BackpressurePolicy::Disconnect);
+        let mut wedged = channel.subscribe_filtered(None, |_| true);
+        let mut following = EventChannelTrait::subscribe(&channel);
+        channel.push(1);
+        assert_eq!(following.next().await, Some(1));
+        channel.push(2);
+        assert_eq!(following.next().await, Some(2));
+        assert!(wedged.metrics().disconnected);
+        assert_eq!(wedged.next().await, Some((1, 1)));
+        assert_eq!(wedged.next().await, None);
+        drop(wedged);
+        assert_eq!(channel.subscriber_metrics().len(), 1);
+
+        //the publishers wait for room up to the timeout
+        let channel = EventChannel::new("Telemetry").with_queue_size(1).with_backpressure(BackpressurePolicy::Block(Duration::from_millis(50)));
+        let mut blocking = channel.subscribe_filtered(None, |_| true);
+        channel.push(1);
+        let start = Instant::now();
+        channel.push(2);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(blocking.metrics().dropped, 1);
+        let publisher = std::thread::spawn({
+            let channel = channel.clone();
+            move || channel.push(3)
+        });
+        assert_eq!(blocking.next().await, Some((1, 1)));
+        publisher.join().unwrap();
+        assert_eq!(blocking.next().await, Some((3, 3)));
+        assert_eq!(blocking.metrics().dropped, 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_channel() {
+        let channel = BroadcastEventChannel::new("Telemetry", 2);
+        channel.push(1);
+        let mut events = channel.subscribe();
+        let mut lagging = channel.subscribe_from(0);
+        assert_eq!(channel.subscriber_count(), 2);
+        channel.push(2);
+        assert_eq!(events.next().await, Some(2));
+
+        //the subscribers falling behind miss the oldest events
+        (3..=5).for_each(|event| channel.push(event));
+        assert_eq!(channel.last_sequence(), 5);
+        assert_eq!(lagging.next().await, Some((4, 4)));
+        assert_eq!(lagging.next().await, Some((5, 5)));
+        drop(events);
+        assert_eq!(channel.subscriber_count(), 1);
+
+        //the channels opened from a runtime are broadcast ones, shared by name
+        let manager = EventChannelManager::new();
+        let publisher = manager.open::<u32>("Telemetry").unwrap();
+        let mut subscriber = manager.open::<u32>("Telemetry").unwrap().subscribe();
+        publisher.push(7);
+        assert_eq!(subscriber.next().await, Some(7));
+        assert!(manager.get::<u32>("Telemetry").is_none());
+        assert!(manager.open::<String>("Telemetry").is_err());
+    }
+
+    #[test]
+    fn test_event_channel_manager() {
+        let manager = EventChannelManager::new();
+        let telemetry = manager.create::<u32>("Telemetry").unwrap();
+        manager.add(EventChannel::<String>::new("Alarms")).unwrap();
+        match manager.create::<u32>("Telemetry") {
+            Err(EventChannelManagerError::ChannelAlreadyExists { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(manager.channel_names(), vec!["Alarms", "Telemetry"]);
+
+        //the channels are looked up by name and event type
+        let events = telemetry.subscribe();
+        manager.get::<u32>("Telemetry").unwrap().push(7);
+        assert_eq!(events.try_recv().unwrap(), 7);
+        assert!(manager.get::<String>("Telemetry").is_none());
+        assert!(manager.get::<u32>("Unknown").is_none());
+        assert_eq!(manager.get_or_create::<String>("Alarms").unwrap().name(), "Alarms");
+        assert!(manager.get_or_create::<String>("Telemetry").is_err());
+
+        //the channels opened out of a runtime are in-process ones
+        assert_eq!(manager.open::<u32>("Telemetry").unwrap().name(), "Telemetry");
+        manager.open::<u32>("Status").unwrap();
+        assert!(manager.get::<u32>("Status").is_some());
+
+        manager.remove("Telemetry").unwrap();
+        match manager.remove("Telemetry") {
+            Err(EventChannelManagerError::ChannelDoesNotExist { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(manager.channel_names(), vec!["Alarms", "Status"]);
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let added = DomainManagementEvent::ObjectAdded {
+            producer_id: "DCE:domain".to_string(),
+            source_id: "DCE:gpp_1".to_string(),
+            source_name: "gpp_1".to_string(),
+            source_category: SourceCategoryType::DEVICE,
+        };
+        let busy = StateChangeEvent {
+            producer_id: "DCE:gpp_2".to_string(),
+            source_id: "DCE:gpp_2".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        };
+        assert!(EventFilter::default().matches(&added));
+        assert!(EventFilter::default().matches(&busy));
+
+        //the events pass the filter when both their source and their type match
+        let filter = EventFilter { source_ids: vec!["DCE:gpp_?".to_string()], event_types: vec![] };
+        assert!(filter.matches(&added) && filter.matches(&busy));
+        let filter = EventFilter { source_ids: vec!["DCE:gpp_1".to_string()], event_types: vec!["USAGE_STATE_EVENT".to_string()] };
+        assert!(!filter.matches(&added) && !filter.matches(&busy));
+        let filter = EventFilter { source_ids: vec!["*:gpp_1".to_string(), "*:gpp_2".to_string()], event_types: vec!["ObjectAdded".to_string(), "USAGE_STATE_EVENT".to_string()] };
+        assert!(filter.matches(&added) && filter.matches(&busy));
+        let filter = EventFilter { source_ids: vec![], event_types: vec!["ObjectRemoved".to_string()] };
+        assert!(!filter.matches(&added));
+    }
+
+    fn json_round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(event: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(event).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_event_encodings() {
+        let added = DomainManagementEvent::ObjectAdded {
+            producer_id: "DCE:domain".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            source_name: "gpp".to_string(),
+            source_category: SourceCategoryType::DEVICE,
+        };
+        assert_eq!(json_round_trip(&added), added);
+        let state_change = StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        };
+        assert_eq!(json_round_trip(&state_change), state_change);
+
+        //the property changes keep their typed values, at the microsecond
+        let property_change = PropertyChangeEvent {
+            producer_id: "DCE:fm_1".to_string(),
+            source_id: "demod_1:DCE:fm_1".to_string(),
+            properties: vec![DataType::new("frequency", AnyValue::Double(101.1e6))],
+            time: UNIX_EPOCH + Duration::from_micros(1_792_183_967_036_201),
+        };
+        assert_eq!(json_round_trip(&property_change), property_change);
+        let encoded = wire::PropertyChangeEvent::from(&property_change).encode_to_vec();
+        let decoded = wire::PropertyChangeEvent::decode(encoded.as_slice()).unwrap();
+        assert_eq!(rpc::property_change_event_from_wire(decoded).unwrap(), property_change);
+
+        let termination = AbnormalComponentTerminationEvent {
+            producer_id: "DCE:node".to_string(),
+            device_id: "DCE:gpp".to_string(),
+            component_id: "demod_1:DCE:fm_1".to_string(),
+            application_id: "DCE:fm_1".to_string(),
+        };
+        assert_eq!(json_round_trip(&termination), termination);
+        let encoded = wire::AbnormalComponentTerminationEvent::from(&termination).encode_to_vec();
+        let decoded = wire::AbnormalComponentTerminationEvent::decode(encoded.as_slice()).unwrap();
+        assert_eq!(AbnormalComponentTerminationEvent::from(decoded), termination);
+    }
+}
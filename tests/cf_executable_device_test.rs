@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt, path::Path, thread, time::Duration};
+
+    use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType};
+    use scars::cf::device::Device;
+    use scars::cf::executable_device::{
+        ExecutableDevice, ExecutableDeviceError, ExecutableDeviceTrait, ProcessStatus, PRIORITY_ID,
+    };
+    use scars::cf::loadable_device::{LoadType, LoadableDevice, LoadableDeviceTrait};
+
+    fn script(dir: &Path, name: &str, body: &str) {
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_execute_injects_exec_params() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        script(fs_root.path(), "echo.sh", "echo \"$@\"\nsleep 10");
+
+        let loadable = LoadableDevice::new(Device::new("DCE:gpp", "gpp"), cache.path());
+        let mut d = ExecutableDevice::new(loadable).with_registration_endpoint("http://[::1]:50051");
+        d.load(fs_root.path(), "echo.sh", LoadType::EXECUTABLE).unwrap();
+
+        let parameters = vec![DataType::new("FREQUENCY", AnyValue::Double(100e6))];
+        let pid = d.execute("echo.sh", &vec![], &parameters).unwrap();
+
+        let mut stdout = Vec::new();
+        for _ in 0..100 {
+            stdout = d.stdout(pid).unwrap();
+            if !stdout.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let line = stdout.join(" ");
+        assert!(line.contains("COMPONENT_IDENTIFIER echo_1:DCE:gpp"));
+        assert!(line.contains("NAMING_CONTEXT_IOR http://[::1]:50051"));
+        assert!(line.contains("FREQUENCY 100000000"));
+
+        let metrics = d.process_metrics(pid).unwrap();
+        assert_eq!(metrics.process_id, pid);
+        assert!(metrics.memory > 0);
+        assert!(matches!(metrics.status, ProcessStatus::RUNNING | ProcessStatus::SLEEPING));
+
+        d.terminate(pid).unwrap();
+        assert!(d.process_ids().is_empty());
+        match d.terminate(pid) {
+            Err(ExecutableDeviceError::InvalidProcess { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match d.process_metrics(pid) {
+            Err(ExecutableDeviceError::InvalidProcess { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_abnormal_exit_reported() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        script(fs_root.path(), "crash.sh", "echo boom >&2\nexit 3");
+
+        let loadable = LoadableDevice::new(Device::new("DCE:gpp", "gpp"), cache.path());
+        let mut d = ExecutableDevice::new(loadable);
+        let exits = d.subscribe_exits();
+        d.load(fs_root.path(), "crash.sh", LoadType::EXECUTABLE).unwrap();
+
+        let pid = d.execute("crash.sh", &vec![], &vec![]).unwrap();
+        let exit = exits.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(exit.process_id, pid);
+        assert_eq!(exit.code, Some(3));
+        assert!(exit.abnormal);
+        assert_eq!(exit.stderr, vec!["boom".to_string()]);
+    }
+
+    #[test]
+    fn test_execute_exceptions() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        script(fs_root.path(), "true.sh", "exit 0");
+
+        let loadable = LoadableDevice::new(Device::new("DCE:gpp", "gpp"), cache.path());
+        let mut d = ExecutableDevice::new(loadable);
+
+        match d.execute("true.sh", &vec![], &vec![]) {
+            Err(ExecutableDeviceError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        d.load(fs_root.path(), "true.sh", LoadType::EXECUTABLE).unwrap();
+        let options = vec![DataType::new(PRIORITY_ID, AnyValue::String("high".to_string()))];
+        match d.execute("true.sh", &options, &vec![]) {
+            Err(ExecutableDeviceError::InvalidOptions { invalid_opts }) => {
+                assert_eq!(invalid_opts, options)
+            }
+            r => panic!("{:?}", r),
+        }
+
+        let parameters = vec![DataType::new("", AnyValue::Long(1))];
+        match d.execute("true.sh", &vec![], &parameters) {
+            Err(ExecutableDeviceError::InvalidParameters { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //a file the kernel cannot execute fails the execute rather than the device
+        let path = fs_root.path().join("garbage");
+        fs::write(&path, [0x7f, b'E', b'L', b'F', 0, 0, 0, 0]).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        d.load(fs_root.path(), "garbage", LoadType::EXECUTABLE).unwrap();
+        match d.execute("garbage", &vec![], &vec![]) {
+            Err(ExecutableDeviceError::ExecuteFail { error_number, .. }) => assert_eq!(error_number, ErrorNumberType::CF_ENOEXEC),
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::checksum;
+    use scars::cf::file_archive::{self, ArchiveError, Unpacker};
+    use scars::cf::rpc::file_system::{ArchiveChunk, ArchiveEntry};
+
+    fn unpack(chunks: Vec<ArchiveChunk>) -> Result<Vec<(String, Vec<u8>)>, ArchiveError> {
+        let mut unpacker = Unpacker::new();
+        for chunk in chunks {
+            unpacker.push(chunk)?;
+        }
+        unpacker.finish()
+    }
+
+    #[test]
+    fn test_file_archive() {
+        //the small files share a chunk, the large ones span several
+        let large: Vec<u8> = (0..2500).map(|i| (i % 251) as u8).collect();
+        let files = vec![("/fm.spd.xml".to_string(), b"<softpkg/>".to_vec()), ("/empty.prf.xml".to_string(), Vec::new()), ("/fm.so".to_string(), large), ("/fm.scd.xml".to_string(), b"<softwarecomponent/>".to_vec())];
+        let chunks = file_archive::pack(files.clone(), 1024);
+        let sizes: Vec<(usize, usize)> = chunks.iter().map(|c| (c.entries.len(), c.data.len())).collect();
+        assert_eq!(sizes, vec![(3, 1024), (0, 1024), (1, 482)]);
+        assert!(chunks.iter().all(|c| c.crc32c == Some(checksum::crc32c(&c.data))));
+        assert_eq!(unpack(chunks).unwrap(), files);
+        assert_eq!(unpack(file_archive::pack(files.clone(), 1)).unwrap(), files);
+
+        //an archive of no file is a single empty chunk
+        let chunks = file_archive::pack(Vec::<(String, Vec<u8>)>::new(), 1024);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(unpack(chunks).unwrap(), vec![]);
+
+        //the corrupted chunks are rejected at their offset
+        let mut chunks = file_archive::pack(files.clone(), 1024);
+        chunks[1].data[7] ^= 0x01;
+        match unpack(chunks) {
+            Err(ArchiveError::CorruptedChunk { offset: 1024 }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the data past the last file, and the files cut short, are malformed
+        let entry = |size: u64| ArchiveEntry { file_name: "/fm.so".to_string(), size };
+        for chunks in [
+            vec![ArchiveChunk { entries: vec![entry(2)], data: b"abc".to_vec(), crc32c: None }],
+            vec![ArchiveChunk { entries: vec![entry(4)], data: b"abc".to_vec(), crc32c: None }],
+            vec![ArchiveChunk { entries: vec![], data: Vec::new(), crc32c: None }, ArchiveChunk { entries: vec![entry(0), entry(1)], data: Vec::new(), crc32c: None }],
+        ] {
+            match unpack(chunks) {
+                Err(ArchiveError::MalformedArchive { .. }) => {}
+                r => panic!("{:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn test_malformed_archives() {
+        let chunk = |entries: Vec<ArchiveEntry>, data: &[u8]| ArchiveChunk { entries, data: data.to_vec(), crc32c: Some(checksum::crc32c(data)) };
+        let entry = |file_name: &str, size: u64| ArchiveEntry { file_name: file_name.to_string(), size };
+
+        //the data whose entries were cut off is past the last file
+        let chunks = file_archive::pack(vec![("/fm.so", b"abcdef".to_vec())], 4);
+        assert_eq!(chunks.len(), 2);
+        for chunks in [vec![chunk(vec![], b"abcd"), chunks[1].clone()], vec![chunk(vec![entry("/fm.so", 2)], b"abcd"), chunks[1].clone()]] {
+            match unpack(chunks) {
+                Err(ArchiveError::MalformedArchive { message }) => assert!(message.contains("past the last file"), "{message}"),
+                r => panic!("{:?}", r),
+            }
+        }
+
+        //the size announced is not allocated, the archive ending before it
+        for size in [u64::MAX, u64::MAX / 2, 1 << 40] {
+            let mut unpacker = Unpacker::new();
+            unpacker.push(chunk(vec![entry("/fm.so", size)], b"abc")).unwrap();
+            unpacker.push(chunk(vec![], b"def")).unwrap();
+            match unpacker.finish() {
+                Err(ArchiveError::MalformedArchive { message }) => assert!(message.contains("before the end of '/fm.so'"), "{message}"),
+                r => panic!("{:?}", r),
+            }
+        }
+
+        //the entries escaping the file system are rejected before any data is unpacked
+        for file_name in ["/../etc/passwd", "/sdr/../../etc/passwd", "fm.so", "", "/sdr/.."] {
+            let mut unpacker = Unpacker::new();
+            unpacker.push(chunk(vec![entry("/fm.spd.xml", 3)], b"abc")).unwrap();
+            match unpacker.push(chunk(vec![entry(file_name, 3)], b"abc")) {
+                Err(ArchiveError::MalformedArchive { message }) => assert!(message.contains(&format!("'{file_name}' is not a pathname")), "{message}"),
+                r => panic!("{file_name}: {:?}", r),
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::checksum;
+    use scars::cf::file_delta::{self, DeltaError, DeltaOp};
+    use scars::cf::rpc::file_system::DeltaRequest;
+
+    fn basis(size: usize) -> Vec<u8> {
+        let mut x = 1u32;
+        (0..size).map(|_| { x ^= x << 13; x ^= x >> 17; x ^= x << 5; x as u8 }).collect()
+    }
+
+    fn patch(basis: &[u8], requests: &[DeltaRequest]) -> Result<Vec<u8>, DeltaError> {
+        let mut file = Vec::new();
+        for request in requests {
+            file_delta::patch(basis, requests[0].block_size as usize, request, &mut file)?;
+        }
+        Ok(file)
+    }
+
+    #[test]
+    fn test_file_delta() {
+        //the block size is the square root of the size, within the bounds
+        assert_eq!(file_delta::block_size(0), file_delta::MIN_BLOCK_SIZE);
+        assert_eq!(file_delta::block_size(500 * 1000 * 1000), 22360);
+        assert_eq!(file_delta::block_size(u64::MAX), file_delta::MAX_BLOCK_SIZE);
+
+        //only the data changed is sent, the blocks before and after it are copied
+        let basis = basis(10_500);
+        let signature = file_delta::signature(&basis, 1024);
+        assert_eq!((signature.size, signature.blocks.len()), (10_500, 11));
+        let mut file = basis.clone();
+        file.splice(3000..3010, b"<changed/>".repeat(3));
+        let ops = file_delta::delta(&signature, &file);
+        assert_eq!(ops, vec![DeltaOp::Copy { block: 0, blocks: 2 }, DeltaOp::Data(2048..3092), DeltaOp::Copy { block: 3, blocks: 8 }]);
+        let requests = file_delta::requests("/fpga.bit", 1024, &file, &ops, 1000);
+        let sizes: Vec<(usize, u64)> = requests.iter().map(|r| (r.data.len(), r.blocks)).collect();
+        assert_eq!(sizes, vec![(0, 2), (1000, 0), (44, 8)]);
+        assert_eq!((requests[0].file_name.as_str(), requests[0].block_size), ("/fpga.bit", 1024));
+        assert_eq!(patch(&basis, &requests).unwrap(), file);
+
+        //a file unchanged is copied whole, the short last block included, an empty one is a single empty request
+        assert_eq!(file_delta::delta(&signature, &basis), vec![DeltaOp::Copy { block: 0, blocks: 11 }]);
+        assert_eq!(file_delta::delta(&signature, b"short"), vec![DeltaOp::Data(0..5)]);
+        let requests = file_delta::requests("/fpga.bit", 1024, b"", &file_delta::delta(&signature, b""), 1000);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(patch(&basis, &requests).unwrap(), b"");
+
+        //the corrupted data is rejected at its offset, the blocks out of the basis are malformed
+        let mut requests = file_delta::requests("/fpga.bit", 1024, &file, &ops, 1000);
+        requests[2].data[0] ^= 0x01;
+        match patch(&basis, &requests) {
+            Err(DeltaError::CorruptedChunk { offset: 3048 }) => {}
+            r => panic!("{:?}", r.map(|file| file.len())),
+        }
+        for (block_size, block, blocks) in [(1024, 10, 2), (1024, u64::MAX, 2), (0, 0, 1)] {
+            let request = DeltaRequest { block_size, block, blocks, ..Default::default() };
+            match patch(&basis, &[request]) {
+                Err(DeltaError::MalformedDelta { .. }) => {}
+                r => panic!("{:?}", r.map(|file| file.len())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_delta_edges() {
+        //against an empty basis, the whole file is sent
+        let signature = file_delta::signature(b"", 1024);
+        assert_eq!((signature.size, signature.blocks.len()), (0, 0));
+        let file = basis(3000);
+        let ops = file_delta::delta(&signature, &file);
+        assert_eq!(ops, vec![DeltaOp::Data(0..3000)]);
+        assert_eq!(patch(b"", &file_delta::requests("/fpga.bit", 1024, &file, &ops, 1000)).unwrap(), file);
+
+        //an insertion in the middle of a block sends that block along with the octets inserted
+        let basis = basis(10_500);
+        let signature = file_delta::signature(&basis, 1024);
+        let mut file = basis.clone();
+        file.splice(5000..5000, [0x5a; 100]);
+        let ops = file_delta::delta(&signature, &file);
+        assert_eq!(ops, vec![DeltaOp::Copy { block: 0, blocks: 4 }, DeltaOp::Data(4096..5220), DeltaOp::Copy { block: 5, blocks: 6 }]);
+        assert_eq!(patch(&basis, &file_delta::requests("/fpga.bit", 1024, &file, &ops, 1000)).unwrap(), file);
+
+        //the short last block is copied at the end of the file only
+        let tail = &basis[10_240..];
+        let file = [b"header".as_slice(), tail].concat();
+        let ops = file_delta::delta(&signature, &file);
+        assert_eq!(ops, vec![DeltaOp::Data(0..6), DeltaOp::Copy { block: 10, blocks: 1 }]);
+        assert_eq!(patch(&basis, &file_delta::requests("/fpga.bit", 1024, &file, &ops, 1000)).unwrap(), file);
+        let file = [tail, b"trailer".as_slice()].concat();
+        assert_eq!(file_delta::delta(&signature, &file), vec![DeltaOp::Data(0..267)]);
+    }
+
+    #[test]
+    fn test_delta_requests() {
+        //the blocks copied end with the basis, the short last block included
+        let copied = |block_size: usize, block: u64, blocks: u64| {
+            file_delta::copied(&DeltaRequest { block, blocks, ..Default::default() }, block_size, 10_500)
+        };
+        assert_eq!(copied(1024, 9, 2).unwrap(), Some(9216..10_500));
+        assert_eq!(copied(1024, 0, 0).unwrap(), None);
+        for (block_size, block, blocks) in [(1024, 11, 1), (1024, 10, 2), (1024, u64::MAX, 1), (1024, 1, u64::MAX), (usize::MAX, 1, 1), (0, 0, 1), (0, 3, 1)] {
+            match copied(block_size, block, blocks) {
+                Err(DeltaError::MalformedDelta { message }) => assert!(message.contains("out of the basis"), "{message}"),
+                r => panic!("{block_size} {block} {blocks}: {:?}", r),
+            }
+        }
+
+        //the data of a CRC32C differing is rejected at its offset, the data without one taken as is
+        let data = b"bitstream".to_vec();
+        let crc32c = checksum::crc32c(&data);
+        assert!(file_delta::verify(&DeltaRequest { data: data.clone(), crc32c: Some(crc32c), ..Default::default() }, 0).is_ok());
+        assert!(file_delta::verify(&DeltaRequest { data: data.clone(), crc32c: None, ..Default::default() }, 0).is_ok());
+        match file_delta::verify(&DeltaRequest { data, crc32c: Some(crc32c ^ 1), ..Default::default() }, 4096) {
+            Err(DeltaError::CorruptedChunk { offset: 4096 }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use scars::cf::file_manager::{FileManager, FileManagerError};
+    use scars::cf::file_system::{
+        wildcard_match, FileSystem, FileSystemError, FileSystemTrait, FileType,
+    };
+
+    #[test]
+    fn test_file_system() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+
+        fs.mkdir("/waveforms/fm").unwrap();
+        fs.write("/waveforms/fm/fm.sad.xml", b"<softwareassembly/>")
+            .unwrap();
+        fs.copy("/waveforms/fm/fm.sad.xml", "/waveforms/fm/copy.sad.xml")
+            .unwrap();
+        assert!(fs.exists("/waveforms/fm/copy.sad.xml").unwrap());
+
+        let files = fs.list("/waveforms/fm/*.sad.xml").unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name, "copy.sad.xml");
+        assert_eq!(files[0].kind, FileType::PLAIN);
+        assert_eq!(files[0].size, 19);
+        assert!(fs.list("/waveforms/fm/?.sad.xml").unwrap().is_empty());
+        assert_eq!(
+            fs.list("/waveforms/*").unwrap()[0].kind,
+            FileType::DIRECTORY
+        );
+
+        match fs.rmdir("/waveforms/fm") {
+            Err(FileSystemError::FileException { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match fs.mkdir("/waveforms/fm") {
+            Err(FileSystemError::FileException { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match fs.exists("waveforms") {
+            Err(FileSystemError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match fs.exists("/waveforms/../..") {
+            Err(FileSystemError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //a file renamed replaces the destination
+        fs.write("/waveforms/fm/fm.prf.xml", b"<properties/>").unwrap();
+        fs.rename("/waveforms/fm/fm.prf.xml", "/waveforms/fm/copy.sad.xml")
+            .unwrap();
+        assert!(!fs.exists("/waveforms/fm/fm.prf.xml").unwrap());
+        assert_eq!(
+            fs.read("/waveforms/fm/copy.sad.xml").unwrap(),
+            b"<properties/>"
+        );
+        match fs.rename("/waveforms/fm/fm.sad.xml", "/waveforms/fm/fm.sad.xml") {
+            Err(FileSystemError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        fs.remove("/waveforms/fm/fm.sad.xml").unwrap();
+        fs.remove("/waveforms/fm/copy.sad.xml").unwrap();
+        fs.rmdir("/waveforms/fm").unwrap();
+        assert!(!fs.exists("/waveforms/fm").unwrap());
+
+        //local files outside of the root are linked in, listed as their target
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("gpp_1.log"), "started\n").unwrap();
+        fs.link("/logs/gpp_1.log", &outside.path().join("gpp_1.log")).unwrap();
+        fs.link("/logs/gpp_1.log", &outside.path().join("gpp_1.log")).unwrap();
+        assert_eq!(fs.read("/logs/gpp_1.log").unwrap(), b"started\n");
+        assert_eq!(fs.list("/logs/*.log").unwrap()[0].size, 8);
+        match fs.link("/../gpp_1.log", &outside.path().join("gpp_1.log")) {
+            Err(FileSystemError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        assert!(wildcard_match("*.spd.xml", "gpp.spd.xml"));
+        assert!(wildcard_match("g?p*", "gpp.spd.xml"));
+        assert!(!wildcard_match("*.prf.xml", "gpp.spd.xml"));
+    }
+
+    #[test]
+    fn test_file_manager() {
+        let (node_1, node_2) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        std::fs::write(node_1.path().join("gpp.spd.xml"), "<softpkg/>").unwrap();
+
+        let mut fm = FileManager::new();
+        fm.mount("/node_1", Arc::new(FileSystem::new(node_1.path())))
+            .unwrap();
+        fm.mount("/node_2", Arc::new(FileSystem::new(node_2.path())))
+            .unwrap();
+        match fm.mount("/node_1", Arc::new(FileSystem::new(node_2.path()))) {
+            Err(FileManagerError::MountPointAlreadyExists { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match fm.mount("node_3", Arc::new(FileSystem::new(node_2.path()))) {
+            Err(FileManagerError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(fm.get_mounts().len(), 2);
+
+        //files are reached and copied across mounted file systems
+        assert!(fm.exists("/node_1/gpp.spd.xml").unwrap());
+        assert!(!fm.exists("/node_3/gpp.spd.xml").unwrap());
+        fm.copy("/node_1/gpp.spd.xml", "/node_2/gpp.spd.xml")
+            .unwrap();
+        assert_eq!(fm.read("/node_2/gpp.spd.xml").unwrap(), b"<softpkg/>");
+        fm.rename("/node_2/gpp.spd.xml", "/node_2/gpp.bak.xml")
+            .unwrap();
+        fm.rename("/node_2/gpp.bak.xml", "/node_1/gpp.bak.xml")
+            .unwrap();
+        assert!(!fm.exists("/node_2/gpp.bak.xml").unwrap());
+        assert_eq!(fm.read("/node_1/gpp.bak.xml").unwrap(), b"<softpkg/>");
+        fm.copy("/node_1/gpp.spd.xml", "/node_2/gpp.spd.xml")
+            .unwrap();
+
+        let mounts = fm.list("/*").unwrap();
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].kind, FileType::FILE_SYSTEM);
+        assert_eq!(fm.list("/node_2/*.xml").unwrap().len(), 1);
+
+        //devices load the files from the local directory of their file system
+        let (root, name) = fm.local_file("/node_2/gpp.spd.xml").unwrap();
+        assert_eq!((root.as_path(), name.as_str()), (node_2.path(), "gpp.spd.xml"));
+
+        fm.unmount("/node_2").unwrap();
+        assert!(!fm.exists("/node_2/gpp.spd.xml").unwrap());
+        match fm.unmount("/node_2") {
+            Err(FileManagerError::NonExistentMount { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
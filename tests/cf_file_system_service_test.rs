@@ -0,0 +1,289 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    use scars::cf::blocking_pool::{BlockingPool, DEFAULT_THREADS};
+    use scars::cf::buffer_pool::BufferPool;
+    use scars::cf::file_manager::FileManager;
+    use scars::cf::file_system_bench::{self, BenchConfig, BenchError, TransferMode, TransferOperation};
+    use scars::cf::file_system::{FileSystem, FileSystemTrait};
+    use scars::cf::file_system_service::{FileSystemService, CHUNK_SIZE, MAX_CHUNK_SIZE};
+    use scars::cf::rpc::file_system::file_system_client::FileSystemClient;
+    use scars::cf::rpc::file_system::file_system_server::FileSystemServer;
+    use scars::cf::checksum;
+    use scars::cf::file_archive;
+    use scars::cf::rpc::file_system::{ChunkRange, CopyRequest, DigestRequest, GetManyRequest, FileType, ListRequest, MetricsRequest, MkdirRequest, MoveRequest, QueryRequest, ReadRangeRequest, ReadRequest, RemoveRequest, RmdirRequest, WriteRangeRequest, WriteRequest};
+
+    async fn serve(service: FileSystemService) -> FileSystemClient<tonic::transport::Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(Server::builder().add_service(FileSystemServer::new(service)).serve_with_incoming(incoming));
+        FileSystemClient::connect(endpoint).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_file_system_service() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        //a pool of its own, the shared one being used by the other tests meanwhile
+        let mut client = serve(FileSystemService::new(file_system.clone()).with_blocking_pool(BlockingPool::new(DEFAULT_THREADS))).await;
+
+        //the files are written and read in chunks
+        client.mkdir(MkdirRequest { directory_name: "/waveforms".to_string() }).await.unwrap();
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 7).map(|i| i as u8).collect();
+        let chunks = vec![
+            WriteRequest { file_name: "/waveforms/fm.bin".to_string(), data: data[..CHUNK_SIZE].to_vec(), crc32c: None },
+            WriteRequest { file_name: String::new(), data: data[CHUNK_SIZE..].to_vec(), crc32c: None },
+        ];
+        assert_eq!(client.write(tokio_stream::iter(chunks)).await.unwrap().into_inner().size, data.len() as u64);
+        assert_eq!(file_system.read("/waveforms/fm.bin").unwrap(), data);
+        let mut read = client.read(ReadRequest { file_name: "/waveforms/fm.bin".to_string(), chunk_size: 0 }).await.unwrap().into_inner();
+        let mut received = Vec::new();
+        while let Some(chunk) = read.message().await.unwrap() {
+            assert!(chunk.data.len() <= CHUNK_SIZE);
+            received.extend(chunk.data);
+        }
+        assert_eq!(received, data);
+
+        //the chunks read are buffers of the pool, released once sent
+        let metrics = client.metrics(MetricsRequest {}).await.unwrap().into_inner();
+        let pool = metrics.buffer_pool.unwrap();
+        assert_eq!((pool.acquired, pool.in_use, pool.reused + pool.pooled), (3, 0, 3));
+        assert!(pool.pooled_bytes <= 3 * CHUNK_SIZE as u64, "{pool:?}");
+
+        //the file operations, one per chunk, are run one at a time on a single thread of the blocking pool
+        let threads = metrics.blocking_pool.unwrap();
+        assert_eq!((threads.threads, threads.active, threads.max_queue_depth, threads.completed), (1, 0, 1, 6));
+
+        client.copy(CopyRequest { source_file_name: "/waveforms/fm.bin".to_string(), destination_file_name: "/waveforms/am.bin".to_string() }).await.unwrap();
+        client.r#move(MoveRequest { source_file_name: "/waveforms/fm.bin".to_string(), destination_file_name: "/waveforms/pm.bin".to_string() }).await.unwrap();
+        client.mkdir(MkdirRequest { directory_name: "/waveforms/old".to_string() }).await.unwrap();
+        let files = client.list(ListRequest { pattern: "/waveforms/*".to_string() }).await.unwrap().into_inner().files;
+        let mut names: Vec<(String, FileType)> = files.iter().map(|f| (f.name.clone(), f.kind())).collect();
+        names.sort();
+        assert_eq!(names, vec![("am.bin".to_string(), FileType::Plain), ("old".to_string(), FileType::Directory), ("pm.bin".to_string(), FileType::Plain)]);
+
+        client.remove(RemoveRequest { file_name: "/waveforms/am.bin".to_string() }).await.unwrap();
+        client.rmdir(RmdirRequest { directory_name: "/waveforms/old".to_string() }).await.unwrap();
+        assert!(!file_system.exists("/waveforms/am.bin").unwrap());
+        let missing = client.read(ReadRequest { file_name: "/waveforms/am.bin".to_string(), chunk_size: 0 }).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+        let invalid = client.remove(RemoveRequest { file_name: "waveforms".to_string() }).await.unwrap_err();
+        assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+
+        let spaces = client.query(QueryRequest {}).await.unwrap().into_inner().spaces;
+        assert_eq!(spaces.len(), 1);
+        assert_eq!(spaces[0].mount_point, "/");
+        assert!(spaces[0].available_space <= spaces[0].size);
+    }
+
+    #[tokio::test]
+    async fn test_file_manager_service() {
+        let (dom, node) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let mut file_manager = FileManager::new();
+        file_manager.mount("/dom", Arc::new(FileSystem::new(dom.path()))).unwrap();
+        file_manager.mount("/nodes/node_1", Arc::new(FileSystem::new(node.path()))).unwrap();
+        let mut client = serve(FileSystemService::from_file_manager(Arc::new(Mutex::new(file_manager)))).await;
+
+        //the files move between the mounted file systems
+        std::fs::write(dom.path().join("fm.spd.xml"), "<softpkg/>").unwrap();
+        client.r#move(MoveRequest { source_file_name: "/dom/fm.spd.xml".to_string(), destination_file_name: "/nodes/node_1/fm.spd.xml".to_string() }).await.unwrap();
+        assert!(!dom.path().join("fm.spd.xml").exists());
+        assert_eq!(std::fs::read_to_string(node.path().join("fm.spd.xml")).unwrap(), "<softpkg/>");
+
+        let spaces = client.query(QueryRequest {}).await.unwrap().into_inner().spaces;
+        let mount_points: Vec<&str> = spaces.iter().map(|s| s.mount_point.as_str()).collect();
+        assert_eq!(mount_points, vec!["/dom", "/nodes/node_1"]);
+    }
+
+    #[tokio::test]
+    async fn test_range_transfers() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        let mut client = serve(FileSystemService::new(file_system.clone())).await;
+
+        //the file is created by the first range, then extended or overwritten
+        let write = |offset: u64, data: &[u8]| WriteRangeRequest { file_name: "/fm.bin".to_string(), offset, data: data.to_vec(), crc32c: None };
+        assert_eq!(client.write_range(write(0, b"abcd")).await.unwrap().into_inner().size, 4);
+        assert_eq!(client.write_range(write(4, b"efgh")).await.unwrap().into_inner().size, 8);
+        assert_eq!(client.write_range(write(2, b"XY")).await.unwrap().into_inner().size, 8);
+        assert_eq!(file_system.read("/fm.bin").unwrap(), b"abXYefgh");
+        assert_eq!(client.write_range(write(9, b"z")).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        //the ranges are clipped at the end of the file
+        let read = |offset: u64, size: u64| ReadRangeRequest { file_name: "/fm.bin".to_string(), offset, size };
+        assert_eq!(client.read_range(read(1, 3)).await.unwrap().into_inner().data, &b"bXY"[..]);
+        assert_eq!(client.read_range(read(6, 10)).await.unwrap().into_inner().data, &b"gh"[..]);
+        assert!(client.read_range(read(20, 10)).await.unwrap().into_inner().data.is_empty());
+        client.mkdir(MkdirRequest { directory_name: "/waveforms".to_string() }).await.unwrap();
+        assert_eq!(client.read_range(ReadRangeRequest { file_name: "/waveforms".to_string(), offset: 0, size: 1 }).await.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        assert_eq!(client.read_range(ReadRangeRequest { file_name: "/am.bin".to_string(), offset: 0, size: 1 }).await.unwrap_err().code(), tonic::Code::NotFound);
+
+        //the files are streamed in the chunks requested
+        let mut chunks = client.read(ReadRequest { file_name: "/fm.bin".to_string(), chunk_size: 3 }).await.unwrap().into_inner();
+        let mut sizes = Vec::new();
+        while let Some(chunk) = chunks.message().await.unwrap() {
+            sizes.push(chunk.data.len());
+        }
+        assert_eq!(sizes, vec![3, 3, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_large_file_ranges() {
+        //a sparse IQ recording past 4 GiB, its ranges written in place at offsets beyond 32 bits
+        const SIZE: u64 = 5 << 30;
+        const MARK: u64 = (4 << 30) + 1;
+        let root = tempfile::tempdir().unwrap();
+        std::fs::File::create(root.path().join("iq.bin")).unwrap().set_len(SIZE).unwrap();
+        let mut client = serve(FileSystemService::new(Arc::new(FileSystem::new(root.path())))).await;
+
+        let write = |offset: u64, data: &[u8]| WriteRangeRequest { file_name: "/iq.bin".to_string(), offset, data: data.to_vec(), crc32c: Some(checksum::crc32c(data)) };
+        assert_eq!(client.write_range(write(MARK, b"IQ")).await.unwrap().into_inner().size, SIZE);
+        assert_eq!(client.write_range(write(SIZE, b"EOF")).await.unwrap().into_inner().size, SIZE + 3);
+        assert_eq!(client.write_range(write(SIZE + 4, b"IQ")).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let read = |offset: u64, size: u64| ReadRangeRequest { file_name: "/iq.bin".to_string(), offset, size };
+        let chunk = client.read_range(read(MARK, 2)).await.unwrap().into_inner();
+        assert_eq!((chunk.offset, &chunk.data[..]), (MARK, &b"IQ"[..]));
+        assert_eq!(client.read_range(read(SIZE, u64::MAX)).await.unwrap().into_inner().data, &b"EOF"[..]);
+        //a range is read up to a chunk of the pool, not to the end of the file
+        assert_eq!(client.read_range(read(0, u64::MAX)).await.unwrap().into_inner().data.len(), MAX_CHUNK_SIZE);
+        //nothing was written at the offsets wrapped in 32 bits
+        assert_eq!(client.read_range(read(1, 2)).await.unwrap().into_inner().data, &b"\0\0"[..]);
+        assert_eq!(std::fs::metadata(root.path().join("iq.bin")).unwrap().len(), SIZE + 3);
+    }
+
+    #[tokio::test]
+    async fn test_checksums() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        let mut client = serve(FileSystemService::new(file_system.clone())).await;
+
+        //the chunks whose CRC32C differs are written as received and replied
+        let chunks = vec![
+            WriteRequest { file_name: "/fm.bin".to_string(), data: b"abcd".to_vec(), crc32c: Some(checksum::crc32c(b"abcd")) },
+            WriteRequest { file_name: String::new(), data: b"eXgh".to_vec(), crc32c: Some(checksum::crc32c(b"efgh")) },
+            WriteRequest { file_name: String::new(), data: b"ij".to_vec(), crc32c: None },
+        ];
+        let reply = client.write(tokio_stream::iter(chunks)).await.unwrap().into_inner();
+        assert_eq!((reply.size, reply.corrupted), (10, vec![ChunkRange { offset: 4, size: 4 }]));
+        assert_eq!(file_system.read("/fm.bin").unwrap(), b"abcdeXghij");
+
+        //the ranges whose CRC32C differs are rejected, the others written
+        let write = |data: &[u8], crc32c: u32| WriteRangeRequest { file_name: "/fm.bin".to_string(), offset: 4, data: data.to_vec(), crc32c: Some(crc32c) };
+        assert_eq!(client.write_range(write(b"eXgh", checksum::crc32c(b"efgh"))).await.unwrap_err().code(), tonic::Code::DataLoss);
+        assert_eq!(client.write_range(write(b"efgh", checksum::crc32c(b"efgh"))).await.unwrap().into_inner().size, 10);
+
+        //the chunks read carry their offset and CRC32C, the file its digest
+        let chunk = client.read_range(ReadRangeRequest { file_name: "/fm.bin".to_string(), offset: 2, size: 4 }).await.unwrap().into_inner();
+        assert_eq!((&chunk.data[..], chunk.offset, chunk.crc32c), (&b"cdef"[..], 2, Some(checksum::crc32c(b"cdef"))));
+        let mut read = client.read(ReadRequest { file_name: "/fm.bin".to_string(), chunk_size: 4 }).await.unwrap().into_inner();
+        let mut offsets = Vec::new();
+        while let Some(chunk) = read.message().await.unwrap() {
+            assert_eq!(chunk.crc32c, Some(checksum::crc32c(&chunk.data)));
+            offsets.push(chunk.offset);
+        }
+        assert_eq!(offsets, vec![0, 4, 8]);
+        let digest = client.digest(DigestRequest { file_name: "/fm.bin".to_string() }).await.unwrap().into_inner();
+        assert_eq!((digest.size, digest.sha3_256), (10, checksum::digest(b"abcdefghij")));
+        assert_eq!(client.digest(DigestRequest { file_name: "/am.bin".to_string() }).await.unwrap_err().code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_batched_transfers() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        file_system.mkdir("/waveforms/fm").unwrap();
+        let mut client = serve(FileSystemService::new(file_system.clone())).await;
+
+        //the files are written from the chunks of a single archive
+        let files: Vec<(String, Vec<u8>)> = (0..60).map(|i| (format!("/waveforms/fm/c{i}.spd.xml"), format!("<softpkg id=\"{i}\"/>").into_bytes())).collect();
+        let size: usize = files.iter().map(|(_, data)| data.len()).sum();
+        let reply = client.put_many(tokio_stream::iter(file_archive::pack(files.clone(), 256))).await.unwrap().into_inner();
+        assert_eq!((reply.files, reply.size), (60, size as u64));
+        assert_eq!(file_system.read("/waveforms/fm/c42.spd.xml").unwrap(), b"<softpkg id=\"42\"/>");
+
+        //and read in the chunks of a single archive
+        let file_names: Vec<String> = files.iter().map(|(file_name, _)| file_name.clone()).collect();
+        let mut chunks = client.get_many(GetManyRequest { file_names: file_names.clone(), chunk_size: 0 }).await.unwrap().into_inner();
+        let mut unpacker = file_archive::Unpacker::new();
+        let mut count = 0;
+        while let Some(chunk) = chunks.message().await.unwrap() {
+            unpacker.push(chunk).unwrap();
+            count += 1;
+        }
+        assert_eq!((unpacker.finish().unwrap(), count), (files.clone(), 1));
+
+        //a file missing fails the batch, a corrupted chunk the archive written
+        let missing = GetManyRequest { file_names: vec![file_names[0].clone(), "/waveforms/fm/missing.xml".to_string()], chunk_size: 0 };
+        assert_eq!(client.get_many(missing).await.unwrap_err().code(), tonic::Code::NotFound);
+        let mut chunks = file_archive::pack(files, 256);
+        chunks[2].data[0] ^= 0x01;
+        assert_eq!(client.put_many(tokio_stream::iter(chunks)).await.unwrap_err().code(), tonic::Code::DataLoss);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_size_bounds() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        let service = FileSystemService::new(file_system.clone()).with_buffer_pool(BufferPool::new(4, 1024)).with_max_archive_size(2000);
+        let mut client = serve(service).await;
+        let data: Vec<u8> = (0..3000).map(|i| i as u8).collect();
+        file_system.write("/fm.bin", &data).unwrap();
+
+        //the chunks requested are at most the size of the buffers pooled, which are all reused
+        let mut chunks = client.read(ReadRequest { file_name: "/fm.bin".to_string(), chunk_size: u64::MAX }).await.unwrap().into_inner();
+        let mut sizes = Vec::new();
+        while let Some(chunk) = chunks.message().await.unwrap() {
+            sizes.push(chunk.data.len());
+        }
+        assert_eq!(sizes, vec![1024, 1024, 952]);
+        let pool = client.metrics(MetricsRequest {}).await.unwrap().into_inner().buffer_pool.unwrap();
+        assert_eq!((pool.discarded, pool.in_use), (0, 0));
+        file_system.write("/pm.bin", &data[..1500]).unwrap();
+        let mut chunks = client.get_many(GetManyRequest { file_names: vec!["/pm.bin".to_string()], chunk_size: u64::MAX }).await.unwrap().into_inner();
+        while let Some(chunk) = chunks.message().await.unwrap() {
+            assert!(chunk.data.len() <= 1024);
+        }
+
+        //an archive past the largest one is refused, none of its files read or written
+        let file_names = vec!["/pm.bin".to_string(), "/pm.bin".to_string()];
+        assert_eq!(client.get_many(GetManyRequest { file_names, chunk_size: 0 }).await.unwrap_err().code(), tonic::Code::ResourceExhausted);
+        let files = vec![("/am.bin".to_string(), data.clone())];
+        let status = client.put_many(tokio_stream::iter(file_archive::pack(files, 1024))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert!(!file_system.exists("/am.bin").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_bench() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = Arc::new(FileSystem::new(root.path()));
+        file_system.mkdir("/bench").unwrap();
+        let client = serve(FileSystemService::new(file_system.clone())).await;
+
+        //each mode and operation is measured for every chunk size and parallelism
+        let config = BenchConfig { directory: "/bench".to_string(), file_size: 10_000, chunk_sizes: vec![1024, 4096], parallelism: vec![1, 2], iterations: 2 };
+        let results = file_system_bench::bench(client.clone(), &config).await.unwrap();
+        assert_eq!(results.len(), 16);
+        assert_eq!((results[0].mode, results[0].operation, results[0].chunk_size, results[0].parallelism), (TransferMode::UNARY, TransferOperation::WRITE, 1024, 1));
+        assert_eq!((results[15].mode, results[15].operation, results[15].chunk_size, results[15].parallelism), (TransferMode::STREAMING, TransferOperation::READ, 4096, 2));
+        for result in &results {
+            assert_eq!(result.transfers, result.parallelism * 2);
+            assert!(result.throughput > 0.0);
+            assert!(result.mean_latency <= result.max_latency && result.p95_latency <= result.max_latency);
+        }
+        assert_eq!(file_system_bench::to_table(&results).lines().count(), 17);
+        assert!(file_system.list("/bench/*").unwrap().is_empty());
+
+        match file_system_bench::bench(client, &BenchConfig { chunk_sizes: vec![0], ..config }).await {
+            Err(BenchError::InvalidConfiguration { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -1,12 +1,15 @@
 #[cfg(test)]
 mod tests {
     use std::path::Path;
+    use std::time::{Duration, Instant};
 
-    use scars::cf::file::{File, FileTrait};
+    use scars::cf::common_types::ErrorNumberType;
+    use scars::cf::file::{File, FileError, FileTrait};
+    use scars::cf::file_system::{FileSystem, FileSystemError, FileSystemTrait};
 
     #[test]
     fn it_works() {
-        if let Ok(mut f) = File::open(&String::from("Cargo.toml"), Path::new("./")) {
+        if let Ok(mut f) = File::open(String::from("Cargo.toml"), Path::new("./")) {
             let data = &mut vec![0; 1024];
             let result = f.read(data);
             println!("{:?}", data);
@@ -25,4 +28,263 @@ mod tests {
             Err(e) => print!("{:?}", e),
         }
     }
+
+    #[test]
+    fn test_read_at() {
+        let root = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        std::fs::write(root.path().join("fm.bin"), &data).unwrap();
+        let name = String::from("fm.bin");
+        let mut file = File::open(&name, root.path()).unwrap();
+        file.set_file_pointer(10).unwrap();
+
+        //the disjoint ranges are read in parallel from the same open file, its pointer unmoved
+        std::thread::scope(|scope| {
+            let readers: Vec<_> = (0..8u64)
+                .map(|i| {
+                    let file = &file;
+                    scope.spawn(move || (i, file.read_at(i * 8192, 8192).unwrap()))
+                })
+                .collect();
+            for reader in readers {
+                let (i, range) = reader.join().unwrap();
+                assert_eq!(range, data[i as usize * 8192..(i as usize + 1) * 8192]);
+            }
+        });
+        assert_eq!(file.file_pointer(), 10);
+        let buffer = &mut vec![0; 4];
+        file.read(buffer).unwrap();
+        assert_eq!(buffer, &data[10..14]);
+
+        //the ranges are clipped at the end of the file
+        assert_eq!(
+            file.read_at(64 * 1024 - 3, 10).unwrap(),
+            data[64 * 1024 - 3..]
+        );
+        assert!(file.read_at(100 * 1024, 10).unwrap().is_empty());
+        file.close().unwrap();
+        match file.read_at(0, 1) {
+            Err(FileError::FileException {
+                error_number: ErrorNumberType::CF_EBADF,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the reads and writes interleaved with the positional reads go on from the file pointer
+        let name = String::from("am.bin");
+        std::fs::write(root.path().join("am.bin"), &data).unwrap();
+        let mut file = File::from_handle(&name, std::fs::OpenOptions::new().read(true).write(true).open(root.path().join("am.bin")).unwrap());
+        let buffer = &mut vec![0; 4];
+        for offset in [0, 4, 8] {
+            assert_eq!(file.read_at(60 * 1024, 16).unwrap(), data[60 * 1024..60 * 1024 + 16]);
+            file.read(buffer).unwrap();
+            assert_eq!(buffer, &data[offset..offset + 4]);
+        }
+        assert_eq!(file.read_at(32 * 1024, 4).unwrap(), data[32 * 1024..32 * 1024 + 4]);
+        file.write(b"IQ").unwrap();
+        assert_eq!((file.file_pointer(), &std::fs::read(root.path().join("am.bin")).unwrap()[12..14]), (14, &b"IQ"[..]));
+        file.close().unwrap();
+
+        //the file systems read the ranges of their files in place
+        let file_system = FileSystem::new(root.path());
+        assert_eq!(
+            file_system.read_at("/fm.bin", 100, 5).unwrap(),
+            data[100..105]
+        );
+        std::fs::create_dir(root.path().join("waveforms")).unwrap();
+        match file_system.read_at("/waveforms", 0, 1) {
+            Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_EISDIR,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+        match file_system.read_at("/missing.bin", 0, 1) {
+            Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ENOENT,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //and into the capacity of a buffer, e.g. a chunk buffer of a pool
+        let mut buffer = Vec::with_capacity(8192);
+        file_system
+            .read_at_into("/fm.bin", 8192, 8192, &mut buffer)
+            .unwrap();
+        assert_eq!((&buffer[..], buffer.capacity()), (&data[8192..16384], 8192));
+        assert_eq!(file_system.size("/fm.bin").unwrap(), data.len() as u64);
+    }
+
+    #[test]
+    fn test_write_behind() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("recording.bin");
+        let on_disk = || std::fs::read(&path).unwrap();
+        let name = String::from("recording.bin");
+
+        //the writes are buffered up to the capacity, the file size including them
+        let mut file = File::create(&name, root.path())
+            .unwrap()
+            .with_write_behind(1024, Duration::from_secs(60))
+            .unwrap();
+        file.write(&[1; 600]).unwrap();
+        assert_eq!(
+            (
+                on_disk().len(),
+                file.size_of().unwrap(),
+                file.file_pointer()
+            ),
+            (0, 600, 600)
+        );
+        file.write(&[2; 600]).unwrap();
+        assert_eq!(on_disk().len(), 1200);
+        file.write(&[3; 10]).unwrap();
+        assert_eq!(on_disk().len(), 1200);
+
+        //the reads, and the writes elsewhere than after the dirty octets, see the octets written
+        file.set_file_pointer(1205).unwrap();
+        file.write(b"XY").unwrap();
+        file.set_file_pointer(1198).unwrap();
+        let buffer = &mut vec![0; 10];
+        assert_eq!(file.read(buffer).unwrap(), 10);
+        assert_eq!(buffer, &[2, 2, 3, 3, 3, 3, 3, b'X', b'Y', 3]);
+        assert_eq!(file.read_at(1205, 2).unwrap(), b"XY");
+
+        //the flushes are the durability points
+        file.write(&[4; 5]).unwrap();
+        file.flush().unwrap();
+        assert_eq!(on_disk().len(), 1213);
+        file.write(&[5; 5]).unwrap();
+        file.sync_all().unwrap();
+        assert_eq!(&on_disk()[1213..], &[5; 5]);
+        file.write(&[6; 5]).unwrap();
+        file.close().unwrap();
+        assert_eq!(on_disk().len(), 1223);
+        match file.flush() {
+            Err(FileError::FileException {
+                error_number: ErrorNumberType::CF_EBADF,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the background flusher writes the octets at its interval, the last ones when the file is dropped
+        let mut file = File::create(&name, root.path())
+            .unwrap()
+            .with_write_behind(1024, Duration::from_millis(10))
+            .unwrap();
+        file.write(b"tune to 101.1").unwrap();
+        let started = Instant::now();
+        while on_disk().is_empty() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(on_disk(), b"tune to 101.1");
+        let mut file = File::create(&name, root.path())
+            .unwrap()
+            .with_write_behind(1024, Duration::from_secs(60))
+            .unwrap();
+        file.write(b"tune to 88.5").unwrap();
+        drop(file);
+        assert_eq!(on_disk(), b"tune to 88.5");
+    }
+
+    #[test]
+    fn test_large_files() {
+        //a sparse IQ recording past 4 GiB, the offsets beyond 32 bits not wrapping
+        const SIZE: u64 = 5 << 30;
+        const MARK: u64 = (4 << 30) + 3;
+        let root = tempfile::tempdir().unwrap();
+        let recording = std::fs::File::create(root.path().join("iq.bin")).unwrap();
+        recording.set_len(SIZE).unwrap();
+        std::os::unix::fs::FileExt::write_all_at(&recording, b"IQ", MARK).unwrap();
+
+        let name = String::from("iq.bin");
+        let mut file = File::open(&name, root.path()).unwrap();
+        assert_eq!(file.size_of().unwrap(), SIZE);
+        file.set_file_pointer(MARK).unwrap();
+        assert_eq!(file.file_pointer(), MARK);
+        let buffer = &mut vec![0; 2];
+        file.read(buffer).unwrap();
+        assert_eq!(buffer, b"IQ");
+        assert_eq!(file.file_pointer(), MARK + 2);
+        match file.set_file_pointer(SIZE + 1) {
+            Err(FileError::InvalidFilePointer) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the ranges are clipped at the end of the file, neither overflowing nor allocated past it
+        assert_eq!(file.read_at(MARK, 2).unwrap(), b"IQ");
+        assert_eq!(file.read_at(SIZE - 2, 10).unwrap(), vec![0; 2]);
+        assert!(file.read_at(u64::MAX - 1, usize::MAX).unwrap().is_empty());
+
+        //the file systems write the ranges in place, extending the file at most from its end
+        let file_system = FileSystem::new(root.path());
+        assert_eq!(file_system.read_at("/iq.bin", MARK, 2).unwrap(), b"IQ");
+        assert_eq!(file_system.write_at("/iq.bin", MARK, b"qi").unwrap(), SIZE);
+        assert_eq!(
+            file_system.write_at("/iq.bin", SIZE, b"EOF").unwrap(),
+            SIZE + 3
+        );
+        assert_eq!(file_system.read_at("/iq.bin", MARK, 2).unwrap(), b"qi");
+        assert_eq!(file_system.read_at("/iq.bin", 3, 2).unwrap(), vec![0; 2]);
+        for offset in [SIZE + 4, u64::MAX] {
+            match file_system.write_at("/iq.bin", offset, b"IQ") {
+                Err(FileSystemError::FileException {
+                    error_number: ErrorNumberType::CF_EINVAL,
+                    ..
+                }) => {}
+                r => panic!("{:?}", r),
+            }
+        }
+        assert_eq!(
+            std::fs::metadata(root.path().join("iq.bin")).unwrap().len(),
+            SIZE + 3
+        );
+    }
+
+    #[test]
+    fn test_open_timeout() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("fm.bin"), b"IQ").unwrap();
+        //a fifo without writer never opens for reading, like a file of a hung network mount
+        let status = std::process::Command::new("mkfifo")
+            .arg(root.path().join("hung"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let file_system =
+            FileSystem::new(root.path()).with_open_timeout(Duration::from_millis(100));
+        assert_eq!(file_system.read("/fm.bin").unwrap(), b"IQ");
+        let started = Instant::now();
+        match file_system.read("/hung") {
+            Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ETIMEDOUT,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+        match file_system.read_at("/hung", 0, 2) {
+            Err(FileSystemError::FileException {
+                error_number: ErrorNumberType::CF_ETIMEDOUT,
+                ..
+            }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        //the opens left in the background complete once a writer shows up
+        drop(
+            std::fs::OpenOptions::new()
+                .write(true)
+                .open(root.path().join("hung"))
+                .unwrap(),
+        );
+        assert_eq!(
+            ErrorNumberType::from(std::io::ErrorKind::TimedOut),
+            ErrorNumberType::CF_ETIMEDOUT
+        );
+    }
 }
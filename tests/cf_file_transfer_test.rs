@@ -0,0 +1,328 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::{Channel, Server};
+    use tonic::{Code, Request, Response, Status, Streaming};
+
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::file_system_service::FileSystemService;
+    use scars::cf::file_transfer::{self, ChunkController, TransferError, TransferTuning};
+    use scars::cf::rpc::file_system::file_system_client::FileSystemClient;
+    use scars::cf::rpc::file_system::file_system_server::{self, FileSystemServer};
+    use scars::cf::rpc::file_system::*;
+
+    async fn serve<S: file_system_server::FileSystem>(service: S) -> FileSystemClient<Channel> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(Server::builder().add_service(FileSystemServer::new(service)).serve_with_incoming(incoming));
+        FileSystemClient::connect(endpoint).await.unwrap()
+    }
+
+    /// Forwards the transfers to a service, flipping a bit of each range read the first time, of the second chunk written and of the first range, archive or delta written.
+    struct CorruptingLink {
+        upstream: FileSystemClient<Channel>,
+        ranges_read: Mutex<HashSet<u64>>,
+        range_written: AtomicBool,
+    }
+
+    fn flip(data: &mut [u8]) {
+        if let Some(octet) = data.first_mut() {
+            *octet ^= 0x01;
+        }
+    }
+
+    #[tonic::async_trait]
+    impl file_system_server::FileSystem for CorruptingLink {
+        type readStream = Streaming<FileChunk>;
+
+        async fn read_range(&self, request: Request<ReadRangeRequest>) -> Result<Response<FileChunk>, Status> {
+            let mut chunk = self.upstream.clone().read_range(request.into_inner()).await?.into_inner();
+            if self.ranges_read.lock().unwrap().insert(chunk.offset) {
+                let mut data = chunk.data.to_vec();
+                flip(&mut data);
+                chunk.data = data.into();
+            }
+            Ok(Response::new(chunk))
+        }
+
+        async fn write(&self, request: Request<Streaming<WriteRequest>>) -> Result<Response<WriteReply>, Status> {
+            let mut requests = request.into_inner();
+            let mut chunks = Vec::new();
+            while let Some(chunk) = requests.message().await? {
+                chunks.push(chunk);
+            }
+            if let Some(chunk) = chunks.get_mut(1) {
+                flip(&mut chunk.data);
+            }
+            self.upstream.clone().write(tokio_stream::iter(chunks)).await
+        }
+
+        async fn write_range(&self, request: Request<WriteRangeRequest>) -> Result<Response<WriteReply>, Status> {
+            let mut request = request.into_inner();
+            if !self.range_written.swap(true, Ordering::Relaxed) {
+                flip(&mut request.data);
+            }
+            self.upstream.clone().write_range(request).await
+        }
+
+        async fn digest(&self, request: Request<DigestRequest>) -> Result<Response<DigestReply>, Status> {
+            self.upstream.clone().digest(request.into_inner()).await
+        }
+
+        type get_manyStream = Streaming<ArchiveChunk>;
+
+        async fn get_many(&self, request: Request<GetManyRequest>) -> Result<Response<Self::get_manyStream>, Status> {
+            self.upstream.clone().get_many(request.into_inner()).await
+        }
+
+        async fn put_many(&self, request: Request<Streaming<ArchiveChunk>>) -> Result<Response<PutManyReply>, Status> {
+            let mut requests = request.into_inner();
+            let mut chunks = Vec::new();
+            while let Some(chunk) = requests.message().await? {
+                chunks.push(chunk);
+            }
+            if !self.range_written.swap(true, Ordering::Relaxed) {
+                flip(&mut chunks[0].data);
+            }
+            self.upstream.clone().put_many(tokio_stream::iter(chunks)).await
+        }
+
+        async fn signature(&self, request: Request<SignatureRequest>) -> Result<Response<SignatureReply>, Status> {
+            self.upstream.clone().signature(request.into_inner()).await
+        }
+
+        async fn write_delta(&self, request: Request<Streaming<DeltaRequest>>) -> Result<Response<WriteReply>, Status> {
+            let mut requests = request.into_inner();
+            let mut deltas = Vec::new();
+            while let Some(delta) = requests.message().await? {
+                deltas.push(delta);
+            }
+            if let Some(delta) = deltas.iter_mut().find(|delta| !delta.data.is_empty()) {
+                if !self.range_written.swap(true, Ordering::Relaxed) {
+                    flip(&mut delta.data);
+                }
+            }
+            self.upstream.clone().write_delta(tokio_stream::iter(deltas)).await
+        }
+
+        async fn read(&self, request: Request<ReadRequest>) -> Result<Response<Self::readStream>, Status> {
+            self.upstream.clone().read(request.into_inner()).await
+        }
+
+        async fn list(&self, _request: Request<ListRequest>) -> Result<Response<ListReply>, Status> {
+            Err(Status::unimplemented("list"))
+        }
+
+        async fn remove(&self, _request: Request<RemoveRequest>) -> Result<Response<RemoveReply>, Status> {
+            Err(Status::unimplemented("remove"))
+        }
+
+        async fn copy(&self, _request: Request<CopyRequest>) -> Result<Response<CopyReply>, Status> {
+            Err(Status::unimplemented("copy"))
+        }
+
+        async fn r#move(&self, _request: Request<MoveRequest>) -> Result<Response<MoveReply>, Status> {
+            Err(Status::unimplemented("move"))
+        }
+
+        async fn mkdir(&self, _request: Request<MkdirRequest>) -> Result<Response<MkdirReply>, Status> {
+            Err(Status::unimplemented("mkdir"))
+        }
+
+        async fn rmdir(&self, _request: Request<RmdirRequest>) -> Result<Response<RmdirReply>, Status> {
+            Err(Status::unimplemented("rmdir"))
+        }
+
+        async fn query(&self, _request: Request<QueryRequest>) -> Result<Response<QueryReply>, Status> {
+            Err(Status::unimplemented("query"))
+        }
+
+        async fn metrics(&self, _request: Request<MetricsRequest>) -> Result<Response<MetricsReply>, Status> {
+            Err(Status::unimplemented("metrics"))
+        }
+    }
+
+    /// Records chunks over a link of a rate in bytes per second and of a latency, the chunks completing one after the other.
+    fn simulate(controller: &mut ChunkController, rate: f64, latency: Duration, chunks: usize) {
+        let mut completed = Instant::now();
+        for _ in 0..chunks {
+            let size = controller.chunk_size();
+            let transmission = Duration::from_secs_f64(size as f64 / rate);
+            completed += transmission;
+            controller.record(size, transmission + latency, completed);
+        }
+    }
+
+    #[test]
+    fn test_chunk_controller() {
+        let tuning = TransferTuning::default();
+        let controller = ChunkController::new(tuning.clone()).unwrap();
+        assert_eq!((controller.chunk_size(), controller.in_flight(), controller.throughput(), controller.rtt()), (tuning.initial_chunk_size, 1, None, None));
+
+        //a gigabit LAN reaches the largest chunks
+        let mut controller = ChunkController::new(tuning.clone()).unwrap();
+        simulate(&mut controller, 125e6, Duration::from_micros(200), 50);
+        assert_eq!(controller.chunk_size(), tuning.max_chunk_size);
+        assert!((1..=tuning.max_in_flight).contains(&controller.in_flight()));
+        assert!((controller.throughput().unwrap() - 125e6).abs() < 1e6);
+
+        //a 1 Mb/s radio link takes chunks of 100ms, enough of them in flight to cover its latency
+        let mut controller = ChunkController::new(tuning.clone()).unwrap();
+        simulate(&mut controller, 125e3, Duration::from_millis(150), 100);
+        assert!((12_000..=12_500).contains(&controller.chunk_size()), "{}", controller.chunk_size());
+        assert_eq!(controller.in_flight(), 4);
+        assert!((controller.rtt().unwrap().as_secs_f64() - 0.25).abs() < 0.01);
+
+        //the chunk size and the in-flight window stay within their bounds
+        let tuning = TransferTuning { min_chunk_size: 16 * 1024, max_in_flight: 2, ..TransferTuning::default() };
+        let mut controller = ChunkController::new(tuning).unwrap();
+        simulate(&mut controller, 10e3, Duration::from_secs(2), 20);
+        assert_eq!((controller.chunk_size(), controller.in_flight()), (16 * 1024, 2));
+
+        for tuning in [
+            TransferTuning { min_chunk_size: 0, ..TransferTuning::default() },
+            TransferTuning { min_chunk_size: 2 * 1024 * 1024, ..TransferTuning::default() },
+            TransferTuning { initial_chunk_size: 1024, ..TransferTuning::default() },
+            TransferTuning { max_in_flight: 0, ..TransferTuning::default() },
+            TransferTuning { chunk_time: Duration::ZERO, ..TransferTuning::default() },
+        ] {
+            match ChunkController::new(tuning) {
+                Err(TransferError::InvalidTuning { .. }) => {}
+                r => panic!("{:?}", r),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_transfer() {
+        let root = tempfile::tempdir().unwrap();
+        let client = serve(FileSystemService::new(Arc::new(FileSystem::new(root.path())))).await;
+        let tuning = TransferTuning { min_chunk_size: 1024, initial_chunk_size: 4096, max_chunk_size: 64 * 1024, ..TransferTuning::default() };
+
+        //the files are written and read back whatever the chunks adapted to
+        let data: Vec<u8> = (0..300 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+        let stats = file_transfer::write_file(&client, "/fm.bin", data.clone(), &tuning).await.unwrap();
+        assert_eq!(stats.size, data.len());
+        assert!(stats.chunks >= data.len() / tuning.max_chunk_size);
+        assert_eq!(std::fs::read(root.path().join("fm.bin")).unwrap(), data);
+        let (read, stats) = file_transfer::read_file(&client, "/fm.bin", &tuning).await.unwrap();
+        assert_eq!(read, data);
+        assert_eq!(stats.size, data.len());
+        assert!((tuning.min_chunk_size..=tuning.max_chunk_size).contains(&stats.chunk_size));
+        assert!((1..=tuning.max_in_flight).contains(&stats.in_flight));
+
+        //an empty file is a single empty chunk
+        let stats = file_transfer::write_file(&client, "/empty.bin", Vec::new(), &tuning).await.unwrap();
+        assert_eq!((stats.size, stats.chunks), (0, 1));
+        let (read, stats) = file_transfer::read_file(&client, "/empty.bin", &tuning).await.unwrap();
+        assert_eq!((read, stats.chunks), (Vec::new(), 1));
+
+        //the failed transfers keep the status code of the call
+        match file_transfer::read_file(&client, "/missing.bin", &tuning).await {
+            Err(TransferError::TransferFailed { code: Code::NotFound, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match file_transfer::write_file(&client, "relative.bin", data, &tuning).await {
+            Err(TransferError::TransferFailed { code: Code::InvalidArgument, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match file_transfer::read_file(&client, "/fm.bin", &TransferTuning { max_in_flight: 0, ..tuning }).await {
+            Err(TransferError::InvalidTuning { .. }) => {}
+            r => panic!("{:?}", r.map(|_| ())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_transfers() {
+        let root = tempfile::tempdir().unwrap();
+        let upstream = serve(FileSystemService::new(Arc::new(FileSystem::new(root.path())))).await;
+        let link = || CorruptingLink { upstream: upstream.clone(), ranges_read: Mutex::new(HashSet::new()), range_written: AtomicBool::new(false) };
+        let client = serve(link()).await;
+        let tuning = TransferTuning { min_chunk_size: 1024, initial_chunk_size: 4096, max_chunk_size: 64 * 1024, ..TransferTuning::default() };
+
+        //the chunk written corrupted is written again by range, until received intact
+        let data: Vec<u8> = (0..100 * 1024 + 7).map(|i| (i % 251) as u8).collect();
+        let stats = file_transfer::write_file(&client, "/fm.bin", data.clone(), &tuning).await.unwrap();
+        assert_eq!(stats.retransmissions, 2);
+        assert_eq!(std::fs::read(root.path().join("fm.bin")).unwrap(), data);
+
+        //each range read corrupted is requested again
+        let (read, stats) = file_transfer::read_file(&client, "/fm.bin", &tuning).await.unwrap();
+        assert_eq!(read, data);
+        assert!(stats.retransmissions >= stats.chunks - 1, "{:?}", stats);
+
+        //the chunks corrupted more times than retransmitted fail the transfer
+        let tuning = TransferTuning { max_retransmissions: 0, ..tuning };
+        match file_transfer::read_file(&serve(link()).await, "/fm.bin", &tuning).await {
+            Err(TransferError::TransferFailed { code: Code::DataLoss, .. }) => {}
+            r => panic!("{:?}", r.map(|(_, stats)| stats)),
+        }
+        match file_transfer::write_file(&serve(link()).await, "/fm.bin", data, &tuning).await {
+            Err(TransferError::TransferFailed { code: Code::DataLoss, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batched_transfers() {
+        let root = tempfile::tempdir().unwrap();
+        let upstream = serve(FileSystemService::new(Arc::new(FileSystem::new(root.path())))).await;
+        let client = serve(CorruptingLink { upstream: upstream.clone(), ranges_read: Mutex::new(HashSet::new()), range_written: AtomicBool::new(false) }).await;
+        let tuning = TransferTuning::default();
+
+        //the archive received corrupted is written again
+        let files: Vec<(String, Vec<u8>)> = (0..60).map(|i| (format!("/c{i}.prf.xml"), format!("<properties id=\"{i}\"/>").into_bytes())).collect();
+        let batch: Vec<(&str, &[u8])> = files.iter().map(|(file_name, data)| (file_name.as_str(), data.as_slice())).collect();
+        let stats = file_transfer::put_many(&client, &batch, &tuning).await.unwrap();
+        assert_eq!((stats.size, stats.chunks, stats.retransmissions), (files.iter().map(|(_, data)| data.len()).sum(), 1, 1));
+        assert_eq!(std::fs::read(root.path().join("c59.prf.xml")).unwrap(), b"<properties id=\"59\"/>");
+
+        //the files are read back in the order of their names
+        let file_names: Vec<&str> = batch.iter().rev().map(|(file_name, _)| *file_name).collect();
+        let (contents, stats) = file_transfer::get_many(&client, &file_names, &tuning).await.unwrap();
+        assert_eq!(contents.first().unwrap(), b"<properties id=\"59\"/>");
+        assert_eq!((contents.len(), stats.retransmissions), (60, 0));
+        let (contents, _) = file_transfer::get_many(&client, &[], &tuning).await.unwrap();
+        assert!(contents.is_empty());
+
+        match file_transfer::get_many(&client, &["/c0.prf.xml", "/missing.xml"], &tuning).await {
+            Err(TransferError::TransferFailed { code: Code::NotFound, .. }) => {}
+            r => panic!("{:?}", r.map(|(_, stats)| stats)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delta_transfers() {
+        let root = tempfile::tempdir().unwrap();
+        let upstream = serve(FileSystemService::new(Arc::new(FileSystem::new(root.path())))).await;
+        let client = serve(CorruptingLink { upstream: upstream.clone(), ranges_read: Mutex::new(HashSet::new()), range_written: AtomicBool::new(false) }).await;
+        let tuning = TransferTuning::default();
+
+        //only the blocks changed are sent, the delta received corrupted being sent again
+        let mut x = 7u32;
+        let image: Vec<u8> = (0..300_000).map(|_| { x ^= x << 13; x ^= x >> 17; x ^= x << 5; x as u8 }).collect();
+        std::fs::write(root.path().join("fpga.bit"), &image).unwrap();
+        let mut update = image.clone();
+        update[150_000..156_000].iter_mut().for_each(|octet| *octet = !*octet);
+        let inode = std::os::unix::fs::MetadataExt::ino(&std::fs::metadata(root.path().join("fpga.bit")).unwrap());
+        let stats = file_transfer::sync_file(&client, "/fpga.bit", update.clone(), &tuning).await.unwrap();
+        assert_eq!((stats.size, stats.retransmissions), (300_000, 1));
+        assert!(stats.transferred >= 6000 && stats.transferred < 9000, "{}", stats.transferred);
+        assert_eq!(std::fs::read(root.path().join("fpga.bit")).unwrap(), update);
+        //the file is patched beside it, a chunk at a time, then renamed over it
+        assert!(!root.path().join(".fpga.bit.delta").exists());
+        assert_ne!(std::os::unix::fs::MetadataExt::ino(&std::fs::metadata(root.path().join("fpga.bit")).unwrap()), inode);
+
+        //a file missing is written whole
+        let stats = file_transfer::sync_file(&client, "/new.bit", image.clone(), &tuning).await.unwrap();
+        assert_eq!((stats.size, stats.transferred), (300_000, 300_000));
+        assert_eq!(std::fs::read(root.path().join("new.bit")).unwrap(), image);
+    }
+}
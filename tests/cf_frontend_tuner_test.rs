@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::common_types::AnyValue;
+    use scars::cf::device::{Device, DeviceError, DeviceTrait, UsageType};
+    use scars::cf::frontend_tuner::{
+        FrontendTunerDevice, ListenerAllocation, TunerAllocation, TunerCapabilities, TunerType,
+        TUNER_STATUS_ID,
+    };
+
+    fn receiver() -> FrontendTunerDevice {
+        let capabilities = TunerCapabilities {
+            tuner_type: TunerType::RX_DIGITIZER,
+            min_frequency: 30e6,
+            max_frequency: 3e9,
+            min_bandwidth: 1e6,
+            max_bandwidth: 40e6,
+            min_sample_rate: 1.25e6,
+            max_sample_rate: 50e6,
+            group_id: "front".to_string(),
+            rf_flow_id: "ant1".to_string(),
+        };
+        FrontendTunerDevice::new(Device::new("DCE:rx", "rx"))
+            .with_tuner(capabilities.clone())
+            .with_tuner(capabilities)
+    }
+
+    fn tuner(allocation_id: &str, center_frequency: f64, device_control: bool) -> TunerAllocation {
+        TunerAllocation {
+            tuner_type: TunerType::RX_DIGITIZER,
+            allocation_id: allocation_id.to_string(),
+            center_frequency,
+            bandwidth: 5e6,
+            bandwidth_tolerance: 20.0,
+            sample_rate: 0.0,
+            sample_rate_tolerance: 10.0,
+            device_control,
+            group_id: String::new(),
+            rf_flow_id: "ant1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tuner_allocations() {
+        let mut d = receiver();
+
+        assert!(d
+            .allocate_capacity(&vec![tuner("a", 100e6, true).to_property()])
+            .unwrap());
+        assert_eq!(d.usage_state(), UsageType::ACTIVE);
+        let status = &d.tuner_status()[0];
+        assert_eq!(status.bandwidth, 5e6);
+        assert_eq!(status.sample_rate, 1.25e6);
+
+        //out of range and duplicate requests
+        assert!(!d
+            .allocate_capacity(&vec![tuner("b", 6e9, true).to_property()])
+            .unwrap());
+        match d.allocate_capacity(&vec![tuner("a", 200e6, true).to_property()]) {
+            Err(DeviceError::InvalidCapacity { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //listeners share the controlled tuner
+        assert!(d
+            .allocate_capacity(&vec![tuner("b", 100e6, false).to_property()])
+            .unwrap());
+        let listener = ListenerAllocation {
+            existing_allocation_id: "a".to_string(),
+            listener_allocation_id: "c".to_string(),
+        };
+        assert!(d.allocate_capacity(&vec![listener.to_property()]).unwrap());
+        assert_eq!(d.tuner_status()[0].allocation_ids, vec!["a", "b", "c"]);
+        assert_eq!(d.usage_state(), UsageType::ACTIVE);
+
+        assert!(d
+            .allocate_capacity(&vec![tuner("d", 900e6, true).to_property()])
+            .unwrap());
+        assert_eq!(d.usage_state(), UsageType::BUSY);
+
+        //releasing the controller releases its listeners
+        d.deallocate_capacity(&vec![tuner("a", 100e6, true).to_property()])
+            .unwrap();
+        assert!(d.tuner_status()[0].allocation_ids.is_empty());
+        assert_eq!(d.tuner_index("c"), None);
+        d.deallocate_capacity(&vec![tuner("d", 900e6, true).to_property()])
+            .unwrap();
+        assert_eq!(d.usage_state(), UsageType::IDLE);
+    }
+
+    #[test]
+    fn test_tuner_status_property() {
+        let mut d = receiver();
+        assert!(d
+            .allocate_capacity(&vec![tuner("a", 100e6, true).to_property()])
+            .unwrap());
+
+        let properties = d.allocation_properties();
+        let status = properties.iter().find(|p| p.id == TUNER_STATUS_ID).unwrap();
+        match &status.value {
+            AnyValue::Sequence(tuners) => assert_eq!(tuners.len(), 2),
+            v => panic!("{:?}", v),
+        }
+    }
+}
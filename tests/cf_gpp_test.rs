@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::device::{Device, DeviceError, DeviceTrait, UsageType};
+    use scars::cf::gpp::{Gpp, MEMORY_CAPACITY_ID, PROCESSOR_CORES_ID};
+
+    #[test]
+    fn test_host_capacities() {
+        let cache = tempfile::tempdir().unwrap();
+        let gpp = Gpp::new(Device::new("DCE:gpp", "gpp"), cache.path());
+
+        let props = gpp.allocation_properties();
+        let cores = props.iter().find(|p| p.id == PROCESSOR_CORES_ID).unwrap();
+        match cores.value {
+            AnyValue::ULong(n) => assert!(n > 0),
+            ref v => panic!("{:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_usage_state_degrades() {
+        let cache = tempfile::tempdir().unwrap();
+        let mut gpp = Gpp::with_capacities(Device::new("DCE:gpp", "gpp"), cache.path(), 2, 1024, 100.0);
+        let one_core = vec![DataType::new(PROCESSOR_CORES_ID, AnyValue::ULong(1))];
+
+        assert!(gpp.allocate_capacity(&one_core).unwrap());
+        assert_eq!(gpp.usage_state(), UsageType::ACTIVE);
+        assert!(gpp.allocate_capacity(&one_core).unwrap());
+        assert_eq!(gpp.usage_state(), UsageType::BUSY);
+        assert!(!gpp.allocate_capacity(&one_core).unwrap());
+
+        gpp.deallocate_capacity(&one_core).unwrap();
+        assert_eq!(gpp.usage_state(), UsageType::ACTIVE);
+        gpp.deallocate_capacity(&one_core).unwrap();
+        assert_eq!(gpp.usage_state(), UsageType::IDLE);
+
+        let memory = vec![DataType::new(MEMORY_CAPACITY_ID, AnyValue::Long(1))];
+        match gpp.allocate_capacity(&memory) {
+            Err(DeviceError::InvalidCapacity { capacities, .. }) => assert_eq!(capacities, memory),
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+#[cfg(all(test, feature = "corba"))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerTrait, AllocationProperty, AllocationRequest};
+    use scars::cf::common_types::{ActionType, AnyValue, DataType, ErrorNumberType};
+    use scars::cf::corba_bridge::{CorbaBridge, CorbaError};
+    use scars::cf::device::{AdminType, Device, DeviceTrait, UsageType};
+    use scars::cf::file::{File, FileError, FileTrait};
+    use scars::cf::iiop_orb::IiopOrb;
+    use scars::cf::resource::{Resource, ResourceError, ResourceTrait};
+
+    fn bridges() -> (CorbaBridge, CorbaBridge, IiopOrb) {
+        let server = IiopOrb::bind("127.0.0.1:0").unwrap();
+        let client = IiopOrb::bind("127.0.0.1:0").unwrap().with_timeout(Duration::from_secs(2));
+        (CorbaBridge::new(Arc::new(server.clone())), CorbaBridge::new(Arc::new(client)), server)
+    }
+
+    /// Sends a GIOP message on a new connection, returning the reply read.
+    fn exchange(orb: &IiopOrb, request: &[u8]) -> Vec<u8> {
+        let (host, port) = orb.endpoint();
+        let mut stream = TcpStream::connect((host, port)).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        stream.write_all(request).unwrap();
+        let mut header = [0u8; 12];
+        stream.read_exact(&mut header).unwrap();
+        let mut reply = header.to_vec();
+        reply.resize(12 + u32::from_be_bytes([header[8], header[9], header[10], header[11]]) as usize, 0);
+        stream.read_exact(&mut reply[12..]).unwrap();
+        reply
+    }
+
+    #[test]
+    fn test_resource() {
+        let (server, client, _orb) = bridges();
+        let sink_ior = server.expose_resource(Arc::new(Mutex::new(Resource::new("DCE:sink")))).unwrap();
+        let resource = Arc::new(Mutex::new(Resource::new("DCE:siggen").with_property("frequency", AnyValue::Double(1000.0)).with_provides_port("data_in", &sink_ior).with_uses_port("data_out")));
+        let ior = server.expose_resource(resource.clone()).unwrap();
+        assert!(ior.starts_with("IOR:"));
+
+        //the resource is driven over IIOP, its properties marshaled as CF::Properties
+        let mut proxy = client.resource(&ior).unwrap();
+        assert_eq!(proxy.identifier(), "DCE:siggen");
+        proxy.initialize().unwrap();
+        proxy.configure(&vec![DataType::new("frequency", AnyValue::Double(2000.0))]).unwrap();
+        assert_eq!(proxy.query(&vec![DataType::new("frequency", AnyValue::Boolean(false))]).unwrap(), vec![DataType::new("frequency", AnyValue::Double(2000.0))]);
+        proxy.start().unwrap();
+        assert!(proxy.started());
+        assert!(resource.lock().unwrap().started());
+
+        //the CF exceptions are marshaled with their members
+        match proxy.query(&vec![DataType::new("amplitude", AnyValue::Boolean(false))]) {
+            Err(ResourceError::UnknownProperties { invalid_properties }) => assert_eq!(invalid_properties[0].id, "amplitude"),
+            r => panic!("{:?}", r),
+        }
+        match proxy.configure(&vec![DataType::new("amplitude", AnyValue::Double(0.5))]) {
+            Err(ResourceError::InvalidConfiguration { invalid_properties, .. }) => assert_eq!(invalid_properties[0].id, "amplitude"),
+            r => panic!("{:?}", r),
+        }
+
+        //the port endpoints are object references
+        assert_eq!(client.resource(&proxy.get_provides_port("data_in").unwrap()).unwrap().identifier(), "DCE:sink");
+        proxy.connect_uses_port("data_out", "c1", &sink_ior).unwrap();
+        assert_eq!(resource.lock().unwrap().connections("data_out")[0].0, "c1");
+        proxy.disconnect_port("data_out", "c1").unwrap();
+        assert!(resource.lock().unwrap().connections("data_out").is_empty());
+        match proxy.connect_uses_port("data_out", "c2", "local://DCE:sink/data_in") {
+            Err(ResourceError::InvalidState { message }) => assert!(message.contains("BAD_PARAM")),
+            r => panic!("{:?}", r),
+        }
+        match proxy.connect_uses_port("control_out", "c2", &sink_ior) {
+            Err(ResourceError::InvalidPort { name, .. }) => assert_eq!(name, "control_out"),
+            r => panic!("{:?}", r),
+        }
+
+        //a withdrawn resource no longer exists
+        server.withdraw(&ior).unwrap();
+        assert!(!proxy.started());
+        match proxy.stop() {
+            Err(ResourceError::StopError { error_number: ErrorNumberType::CF_EIO, message }) => assert!(message.contains("OBJECT_NOT_EXIST")),
+            r => panic!("{:?}", r),
+        }
+        match client.resource(&ior) {
+            Err(CorbaError::SystemException { name, .. }) => assert_eq!(name, "OBJECT_NOT_EXIST"),
+            r => panic!("{:?}", r.map(|r| r.ior().to_string())),
+        }
+    }
+
+    #[test]
+    fn test_device() {
+        let (server, client, _orb) = bridges();
+        let device = Arc::new(Mutex::new(Device::new("DCE:tuner", "tuner").with_allocation_property("device_kind", AnyValue::String("RX".to_string())).with_capacity("channels", AnyValue::UShort(2))));
+        let ior = server.expose_device(device.clone()).unwrap();
+
+        let proxy = client.device(&ior).unwrap();
+        assert_eq!((proxy.identifier(), proxy.label(), proxy.composite_device()), ("DCE:tuner", "tuner", None));
+        let mut am = AllocationManager::new();
+        am.register_device(Arc::new(Mutex::new(proxy)));
+        let request = AllocationRequest {
+            request_id: "r1".to_string(),
+            allocation_properties: vec![
+                AllocationProperty::new(DataType::new("device_kind", AnyValue::String("RX".to_string())), ActionType::EQ),
+                AllocationProperty::new(DataType::new("channels", AnyValue::UShort(1)), ActionType::EXTERNAL),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(am.allocate(std::slice::from_ref(&request)).unwrap()[0].allocated_device, "DCE:tuner");
+        assert_eq!(device.lock().unwrap().available_capacity("channels"), Some(&AnyValue::UShort(1)));
+        assert_eq!(device.lock().unwrap().usage_state(), UsageType::ACTIVE);
+
+        let mut proxy = client.device(&ior).unwrap();
+        proxy.set_admin_state(AdminType::LOCKED);
+        assert_eq!(device.lock().unwrap().admin_state(), AdminType::SHUTTING_DOWN);
+    }
+
+    #[test]
+    fn test_file() {
+        let (server, client, orb) = bridges();
+        let ior = server.expose_file(File::from_handle("Cargo.toml", std::fs::File::open("Cargo.toml").unwrap())).unwrap();
+        let size = std::fs::metadata("Cargo.toml").unwrap().len();
+
+        let mut proxy = client.file(&ior).unwrap();
+        assert_eq!(proxy.file_name(), "Cargo.toml");
+        assert_eq!(proxy.size_of().unwrap(), size);
+        let mut buffer = vec![0u8; 9];
+        assert_eq!(proxy.read(&mut buffer).unwrap(), 9);
+        assert_eq!(buffer, b"[package]");
+        assert_eq!(proxy.file_pointer(), 9);
+        match proxy.set_file_pointer(size + 1) {
+            Err(FileError::InvalidFilePointer) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //the clients are told the server shuts down, the calls then failing
+        orb.shutdown();
+        match proxy.size_of() {
+            Err(FileError::FileException { error_number: ErrorNumberType::CF_EIO, message }) => assert!(["TRANSIENT", "TIMEOUT", "COMM_FAILURE"].iter().any(|name| message.contains(name)), "{}", message),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_corbaloc() {
+        let (server, client, orb) = bridges();
+        server.expose_resource(Arc::new(Mutex::new(Resource::new("DCE:siggen")))).unwrap();
+        let (host, port) = orb.endpoint();
+
+        //GIOP 1.0 without a version, 1.2 with it
+        for version in ["", "1.2@"] {
+            let proxy = client.resource(&format!("corbaloc:iiop:{version}{host}:{port}/scars%2f1")).unwrap();
+            assert_eq!(proxy.identifier(), "DCE:siggen");
+            assert!(proxy.ior().starts_with("IOR:"));
+        }
+        let object = client.orb().string_to_object(&format!("corbaloc:iiop:{host}:{port}/scars/2")).unwrap();
+        assert_eq!(object.invoke("_non_existent", &[]).unwrap(), Some(AnyValue::Boolean(true)));
+        let object = client.orb().string_to_object(&format!("corbaloc:iiop:{host}:{port}/scars/1")).unwrap();
+        assert_eq!(object.invoke("_is_a", &[AnyValue::String("IDL:CF/PropertySet:1.0".to_string())]).unwrap(), Some(AnyValue::Boolean(true)));
+        assert_eq!(object.invoke("_is_a", &[AnyValue::String("IDL:CF/Device:1.0".to_string())]).unwrap(), Some(AnyValue::Boolean(false)));
+        match client.orb().string_to_object("corbaloc:rir:/NameService") {
+            Err(CorbaError::SystemException { name, .. }) => assert_eq!(name, "INV_OBJREF"),
+            r => panic!("{:?}", r.map(|r| r.ior().to_string())),
+        }
+    }
+
+    #[test]
+    fn test_wire() {
+        let (server, _client, orb) = bridges();
+        server.expose_resource(Arc::new(Mutex::new(Resource::new("DCE:siggen")))).unwrap();
+        let reply = [b"GIOP\x01\x02\x00\x01\x00\x00\x00\x1b".as_slice(), &[0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 11], b"DCE:siggen\0"].concat();
+
+        //a big-endian GIOP 1.2 request of _get_identifier, by object key
+        let request = [b"GIOP\x01\x02\x00\x00\x00\x00\x00\x30".as_slice(), &[0, 0, 0, 7, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7], b"scars/1\0", &[0, 0, 0, 16], b"_get_identifier\0", &[0, 0, 0, 0]].concat();
+        assert_eq!(exchange(&orb, &request), reply);
+
+        //the same request in little-endian order
+        let request = [b"GIOP\x01\x02\x01\x00\x30\x00\x00\x00".as_slice(), &[7, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 7, 0, 0, 0], b"scars/1\0", &[16, 0, 0, 0], b"_get_identifier\0", &[0, 0, 0, 0]].concat();
+        assert_eq!(exchange(&orb, &request), reply);
+
+        //the objects are located by key
+        let locate = |key: &[u8]| [b"GIOP\x01\x02\x00\x03\x00\x00\x00\x13".as_slice(), &[0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 7], key].concat();
+        assert_eq!(exchange(&orb, &locate(b"scars/1")), b"GIOP\x01\x02\x00\x04\x00\x00\x00\x08\x00\x00\x00\x09\x00\x00\x00\x01");
+        assert_eq!(exchange(&orb, &locate(b"scars/9")), b"GIOP\x01\x02\x00\x04\x00\x00\x00\x08\x00\x00\x00\x09\x00\x00\x00\x00");
+
+        //anything else than GIOP is a message error
+        assert_eq!(exchange(&orb, b"GET / HTTP/1.1\r\n\r\n"), b"GIOP\x01\x02\x00\x06\x00\x00\x00\x00");
+    }
+}
@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+    use tonic::{Request, Response, Status};
+
+    use scars::cf::launcher::{implementation_name, ExecParams, LauncherError};
+    use scars::cf::log::{LogLevelType, LogProducer};
+    use scars::cf::rpc::device::device_client::DeviceClient;
+    use scars::cf::rpc::device::{AdminType, SetAdminStateRequest, StatusRequest};
+    use scars::cf::rpc::device_manager::device_manager_server::{
+        DeviceManager, DeviceManagerServer,
+    };
+    use scars::cf::rpc::device_manager::{
+        GetComponentImplementationIdReply, GetComponentImplementationIdRequest, HeartbeatReply,
+        HeartbeatRequest, RegisterDeviceReply, RegisterDeviceRequest, RegisteredDevicesReply,
+        RegisteredDevicesRequest, ShutdownReply, ShutdownRequest, UnregisterDeviceReply,
+        UnregisterDeviceRequest,
+    };
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_exec_params() {
+        let params = ExecParams::parse(args(&[
+            "DEVICE_ID", "DCE:gpp", "DEVICE_LABEL", "gpp", "DEVICE_MGR_IOR",
+            "http://[::1]:5000", "PROFILE_NAME", "/devices/GPP/GPP.spd.xml", "LOG_LEVEL", "3",
+        ]))
+        .unwrap();
+        assert_eq!(params.device_id, "DCE:gpp");
+        assert_eq!(params.composite_device, None);
+        assert_eq!(params.log_level, Some(LogLevelType::DEGRADED_ALARM));
+        assert_eq!(params.logging_config_uri, None);
+        assert_eq!(params.log_format, None);
+        assert_eq!(params.parameters.len(), 0);
+        assert_eq!(params.device().log_level(), LogLevelType::DEGRADED_ALARM);
+        assert_eq!(implementation_name(&params.profile_name), "gpp");
+
+        match ExecParams::parse(args(&["DEVICE_ID", "DCE:gpp", "DEVICE_LABEL"])) {
+            Err(LauncherError::InvalidExecParams { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match ExecParams::parse(args(&["DEVICE_ID", "DCE:gpp", "DEVICE_LABEL", "gpp"])) {
+            Err(LauncherError::InvalidExecParams { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match ExecParams::parse(args(&["DEVICE_ID", "DCE:gpp", "DEVICE_LABEL", "gpp", "LOG_LEVEL", "CHATTY"])) {
+            Err(LauncherError::InvalidExecParams { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match ExecParams::parse(args(&["DEVICE_ID", "DCE:gpp", "DEVICE_LABEL", "gpp", "LOG_FORMAT", "xml"])) {
+            Err(LauncherError::InvalidExecParams { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match ExecParams::parse(args(&["DEVICE_ID", "DCE:gpp", "DEVICE_LABEL", "gpp", "LOGGING_CONFIG_URI", "file:///var/log/gpp.log?retained=all"])) {
+            Err(LauncherError::InvalidExecParams { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    /// Device manager recording the registrations it receives, the first one being refused as unavailable.
+    struct Registry {
+        events: mpsc::UnboundedSender<(String, String)>,
+        refused: AtomicBool,
+    }
+
+    #[tonic::async_trait]
+    impl DeviceManager for Registry {
+        async fn register_device(
+            &self,
+            request: Request<RegisterDeviceRequest>,
+        ) -> Result<Response<RegisterDeviceReply>, Status> {
+            if !self.refused.swap(true, Ordering::SeqCst) {
+                return Err(Status::unavailable("starting"));
+            }
+            let r = request.into_inner();
+            let _ = self.events.send((r.identifier, r.endpoint));
+            Ok(Response::new(RegisterDeviceReply {}))
+        }
+
+        async fn unregister_device(
+            &self,
+            request: Request<UnregisterDeviceRequest>,
+        ) -> Result<Response<UnregisterDeviceReply>, Status> {
+            let _ = self.events.send((request.into_inner().identifier, String::new()));
+            Ok(Response::new(UnregisterDeviceReply {}))
+        }
+
+        async fn registered_devices(
+            &self,
+            _request: Request<RegisteredDevicesRequest>,
+        ) -> Result<Response<RegisteredDevicesReply>, Status> {
+            Err(Status::unimplemented("registered_devices"))
+        }
+
+        async fn get_component_implementation_id(
+            &self,
+            _request: Request<GetComponentImplementationIdRequest>,
+        ) -> Result<Response<GetComponentImplementationIdReply>, Status> {
+            Err(Status::unimplemented("get_component_implementation_id"))
+        }
+
+        async fn shutdown(
+            &self,
+            _request: Request<ShutdownRequest>,
+        ) -> Result<Response<ShutdownReply>, Status> {
+            Err(Status::unimplemented("shutdown"))
+        }
+
+        async fn heartbeat(
+            &self,
+            _request: Request<HeartbeatRequest>,
+        ) -> Result<Response<HeartbeatReply>, Status> {
+            Ok(Response::new(HeartbeatReply {}))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_and_register() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let device_mgr = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(DeviceManagerServer::new(Registry { events: tx, refused: AtomicBool::new(false) }))
+                .serve_with_incoming(incoming),
+        );
+
+        let mut launcher = Command::new(env!("CARGO_BIN_EXE_scars-device-launcher"))
+            .args(args(&[
+                "DEVICE_ID", "DCE:launched", "DEVICE_LABEL", "launched", "DEVICE_MGR_IOR",
+                &device_mgr, "PROFILE_NAME", "/devices/Device/Device.spd.xml",
+            ]))
+            .spawn()
+            .unwrap();
+
+        //the launched device registers, retrying once refused, and serves its state
+        let (identifier, endpoint) = rx.recv().await.unwrap();
+        assert_eq!(identifier, "DCE:launched");
+
+        let mut device = DeviceClient::connect(endpoint).await.unwrap();
+        device
+            .set_admin_state(SetAdminStateRequest {
+                admin_state: AdminType::Locked.into(),
+            })
+            .await
+            .unwrap();
+        let status = device.status(StatusRequest {}).await.unwrap().into_inner();
+        assert_eq!(status.label, "launched");
+        assert_eq!(status.admin_state(), AdminType::Locked);
+
+        //the terminated device unregisters
+        Command::new("kill")
+            .args(["-TERM", &launcher.id().to_string()])
+            .status()
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap(), ("DCE:launched".to_string(), String::new()));
+        assert!(launcher.wait().unwrap().success());
+    }
+}
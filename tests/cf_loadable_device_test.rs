@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType};
+    use scars::cf::device::{AdminType, Device, DeviceError, DeviceTrait, UsageType};
+    use scars::cf::loadable_device::{
+        LoadType, LoadableDevice, LoadableDeviceError, LoadableDeviceTrait,
+    };
+
+    #[test]
+    fn test_load_reference_counting() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(fs_root.path().join("libdsp.so"), b"dsp").unwrap();
+
+        let mut d = LoadableDevice::new(Device::new("DCE:1", "fpga"), cache.path());
+        d.load(fs_root.path(), "libdsp.so", LoadType::SHARED_LIBRARY).unwrap();
+        d.load(fs_root.path(), "libdsp.so", LoadType::SHARED_LIBRARY).unwrap();
+        assert_eq!(d.load_count("libdsp.so"), 2);
+
+        let staged = d.loaded_path("libdsp.so").unwrap();
+        d.unload("libdsp.so").unwrap();
+        assert!(staged.exists());
+
+        d.unload("libdsp.so").unwrap();
+        assert!(!staged.exists());
+        assert_eq!(d.load_count("libdsp.so"), 0);
+
+        match d.unload("libdsp.so") {
+            Err(LoadableDeviceError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_load_exceptions() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(fs_root.path().join("top.bit"), b"bitstream").unwrap();
+
+        let mut d = LoadableDevice::new(Device::new("DCE:2", "fpga"), cache.path())
+            .with_supported_load_types(&[LoadType::DRIVER]);
+
+        match d.load(fs_root.path(), "top.bit", LoadType::EXECUTABLE) {
+            Err(LoadableDeviceError::InvalidLoadKind) => {}
+            r => panic!("{:?}", r),
+        }
+        match d.load(fs_root.path(), "missing.bit", LoadType::DRIVER) {
+            Err(LoadableDeviceError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        //a full target fails the load rather than the device
+        fs::write(fs_root.path().join("full.bit"), b"bitstream").unwrap();
+        std::os::unix::fs::symlink("/dev/full", cache.path().join("full.bit")).unwrap();
+        match d.load(fs_root.path(), "full.bit", LoadType::DRIVER) {
+            Err(LoadableDeviceError::LoadFail { error_number, .. }) => assert_eq!(error_number, ErrorNumberType::CF_ENOSPC),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(d.load_count("full.bit"), 0);
+
+        //so does a target directory taken by a file
+        fs::create_dir(fs_root.path().join("bits")).unwrap();
+        fs::write(fs_root.path().join("bits/top.bit"), b"bitstream").unwrap();
+        fs::write(cache.path().join("bits"), b"").unwrap();
+        match d.load(fs_root.path(), "bits/top.bit", LoadType::DRIVER) {
+            Err(LoadableDeviceError::LoadFail { error_number, .. }) => assert_eq!(error_number, ErrorNumberType::CF_EEXIST),
+            r => panic!("{:?}", r),
+        }
+
+        //the files are neither staged nor removed outside the cache
+        fs::write(cache.path().join("top.bit"), b"cached").unwrap();
+        for file_name in ["", "/etc/passwd", "../top.bit", "bits/../../top.bit", ".device_state.json"] {
+            match d.load(fs_root.path(), file_name, LoadType::DRIVER) {
+                Err(LoadableDeviceError::InvalidFileName { error_number, .. }) => assert_eq!(error_number, ErrorNumberType::CF_EINVAL),
+                r => panic!("{file_name}: {:?}", r),
+            }
+            match d.unload(file_name) {
+                Err(LoadableDeviceError::InvalidFileName { error_number, .. }) => assert_eq!(error_number, ErrorNumberType::CF_EINVAL),
+                r => panic!("{file_name}: {:?}", r),
+            }
+        }
+        assert!(cache.path().join("top.bit").exists());
+
+        d.set_admin_state(AdminType::LOCKED);
+        match d.load(fs_root.path(), "top.bit", LoadType::DRIVER) {
+            Err(LoadableDeviceError::InvalidState { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_persistence_across_restarts() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(fs_root.path().join("libdsp.so"), b"dsp").unwrap();
+        let device = || Device::new("DCE:3", "dsp").with_capacity("mips", AnyValue::ULong(100));
+
+        let mut d = LoadableDevice::new(device(), cache.path());
+        d.enable_persistence().unwrap();
+        d.load(fs_root.path(), "libdsp.so", LoadType::SHARED_LIBRARY).unwrap();
+        assert!(d
+            .allocate_capacity(&vec![DataType::new("mips", AnyValue::ULong(40))])
+            .unwrap());
+        drop(d);
+
+        //the restarted device remembers the capacity handed out and the loaded file
+        let mut d = LoadableDevice::new(device(), cache.path());
+        d.enable_persistence().unwrap();
+        assert_eq!(d.device().available_capacity("mips"), Some(&AnyValue::ULong(60)));
+        assert_eq!(d.usage_state(), UsageType::ACTIVE);
+        assert_eq!(d.load_count("libdsp.so"), 1);
+
+        d.deallocate_capacity(&vec![DataType::new("mips", AnyValue::ULong(40))])
+            .unwrap();
+        d.unload("libdsp.so").unwrap();
+        drop(d);
+
+        let mut d = LoadableDevice::new(device(), cache.path());
+        d.enable_persistence().unwrap();
+        assert_eq!(d.usage_state(), UsageType::IDLE);
+        assert_eq!(d.load_count("libdsp.so"), 0);
+    }
+
+    #[test]
+    fn test_persistence_failures() {
+        let fs_root = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        fs::write(fs_root.path().join("libdsp.so"), b"dsp").unwrap();
+        fs::write(fs_root.path().join("libfft.so"), b"fft").unwrap();
+        let mips = |value| vec![DataType::new("mips", AnyValue::ULong(value))];
+
+        let mut d = LoadableDevice::new(Device::new("DCE:4", "dsp").with_capacity("mips", AnyValue::ULong(100)), cache.path());
+        d.enable_persistence().unwrap();
+        d.load(fs_root.path(), "libdsp.so", LoadType::SHARED_LIBRARY).unwrap();
+        assert!(d.allocate_capacity(&mips(40)).unwrap());
+
+        //the changes whose state file cannot be written are undone
+        fs::create_dir(cache.path().join(".device_state.json.tmp")).unwrap();
+        match d.load(fs_root.path(), "libdsp.so", LoadType::SHARED_LIBRARY) {
+            Err(LoadableDeviceError::LoadFail { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(d.load_count("libdsp.so"), 1);
+        match d.load(fs_root.path(), "libfft.so", LoadType::SHARED_LIBRARY) {
+            Err(LoadableDeviceError::LoadFail { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(d.load_count("libfft.so"), 0);
+        assert!(!cache.path().join("libfft.so").exists());
+        match d.unload("libdsp.so") {
+            Err(LoadableDeviceError::LoadFail { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(d.load_count("libdsp.so"), 1);
+        assert!(cache.path().join("libdsp.so").exists());
+        match d.allocate_capacity(&mips(20)) {
+            Err(DeviceError::InvalidState { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match d.deallocate_capacity(&mips(40)) {
+            Err(DeviceError::InvalidState { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(d.device().available_capacity("mips"), Some(&AnyValue::ULong(60)));
+
+        //and go through once it can
+        fs::remove_dir(cache.path().join(".device_state.json.tmp")).unwrap();
+        d.deallocate_capacity(&mips(40)).unwrap();
+        d.unload("libdsp.so").unwrap();
+        assert_eq!((d.usage_state(), d.load_count("libdsp.so")), (UsageType::IDLE, 0));
+    }
+}
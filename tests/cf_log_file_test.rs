@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::time::Duration;
+
+    use flate2::read::GzDecoder;
+
+    use scars::cf::log::{LogConsumer, LogFormat, LogLevelType, LogRecord};
+    use scars::cf::log_file::{LogFile, RotationPolicy};
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord::new("gpp_1:DCE:gpp", "gpp_1", LogLevelType::ADMINISTRATIVE_EVENT, message)
+    }
+
+    fn lines(path: &std::path::Path) -> usize {
+        std::fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn test_rotation_policy() {
+        assert_eq!(RotationPolicy::from_uri("file:///var/log/gpp_1.log"), Some(RotationPolicy::default()));
+        let policy = RotationPolicy::from_uri("file:///var/log/gpp_1.log?max_size=0&max_age=86400&retained=2&compress=true").unwrap();
+        assert_eq!(policy.max_size, None);
+        assert_eq!(policy.max_age, Some(Duration::from_secs(86400)));
+        assert_eq!(policy.retained_files, 2);
+        assert!(policy.compress);
+        assert_eq!(RotationPolicy::from_uri("file:///var/log/gpp_1.log?max_size=1M"), None);
+        assert_eq!(RotationPolicy::from_uri("file:///var/log/gpp_1.log?level=7"), None);
+        assert!(!RotationPolicy::never().is_due(u64::MAX, std::time::UNIX_EPOCH));
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/gpp_1.log");
+        let policy = RotationPolicy { max_size: Some(1), max_age: None, retained_files: 2, compress: false };
+        let mut file = LogFile::new(&path, LogFormat::Text, policy);
+
+        //each record fills the file, the oldest rotated one being removed
+        file.write_records(&[record("1"), record("2"), record("3"), record("4")]);
+        assert_eq!(lines(&path), 1);
+        assert!(std::fs::read_to_string(&path).unwrap().ends_with("4\n"));
+        assert!(std::fs::read_to_string(file.rotated_path(1)).unwrap().ends_with("3\n"));
+        assert!(std::fs::read_to_string(file.rotated_path(2)).unwrap().ends_with("2\n"));
+        assert!(!file.rotated_path(3).exists());
+
+        //an existing file is appended to
+        let policy = RotationPolicy { max_size: Some(1024), ..policy };
+        let mut file = LogFile::new(&path, LogFormat::Text, policy);
+        file.write_records(&[record("5")]);
+        assert_eq!(lines(&path), 2);
+    }
+
+    #[test]
+    fn test_age_rotation_and_compression() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gpp_1.log");
+        let policy = RotationPolicy {
+            max_size: None,
+            max_age: Some(Duration::from_millis(50)),
+            retained_files: 1,
+            compress: true,
+        };
+        let mut file = LogFile::new(&path, LogFormat::Json, policy);
+        file.write_records(&[record("1"), record("2")]);
+        assert_eq!(lines(&path), 2);
+
+        std::thread::sleep(Duration::from_millis(100));
+        file.write_records(&[record("3")]);
+        assert_eq!(lines(&path), 1);
+        assert_eq!(file.rotated_path(1), dir.path().join("gpp_1.log.1.gz"));
+        let mut rotated = String::new();
+        GzDecoder::new(std::fs::File::open(file.rotated_path(1)).unwrap())
+            .read_to_string(&mut rotated)
+            .unwrap();
+        assert_eq!(rotated.lines().count(), 2);
+        assert!(!path.with_file_name("gpp_1.log.2.gz").exists());
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    use scars::cf::file_manager::{FileManager, FileManagerRef};
+    use scars::cf::file_system::{FileSystem, FileSystemTrait};
+    use scars::cf::log::{LogConsumer, LogFilter, LogLevelType, LogRecord};
+    use scars::cf::log_service::{LogQuery, LogService};
+    use scars::cf::rpc::domain_manager::LogRecord as WireLogRecord;
+    use scars::cf::rpc::log_service::log_service_client::LogServiceClient;
+    use scars::cf::rpc::log_service::log_service_server::LogServiceServer;
+    use scars::cf::rpc::log_service::{RetrieveRecordsRequest, WriteRecordsRequest};
+
+    /// Returns a record produced at a second of the UNIX epoch.
+    fn record(producer_name: &str, level: LogLevelType, secs: u64, message: &str) -> LogRecord {
+        let record = LogRecord::new(&format!("{producer_name}:DCE:fm:fm_1"), producer_name, level, message);
+        LogRecord { time: UNIX_EPOCH + Duration::from_secs(secs), ..record }
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let root = tempfile::tempdir().unwrap();
+        let mut file_manager = FileManager::new();
+        file_manager.mount("/domain", Arc::new(FileSystem::new(root.path()))).unwrap();
+        let file_manager: FileManagerRef = Arc::new(Mutex::new(file_manager));
+        let mut service = LogService::new(3).with_spill_file(file_manager.clone(), "/domain/spilled.log");
+
+        //the oldest records are spilled once the buffer is full
+        service.write_records(&[record("source_1", LogLevelType::ADMINISTRATIVE_EVENT, 1, "started"), record("demod_1", LogLevelType::ADMINISTRATIVE_EVENT, 2, "started")]);
+        assert_eq!(service.len(), 2);
+        assert!(!file_manager.lock().unwrap().exists("/domain/spilled.log").unwrap());
+        service.write_records(&[
+            record("demod_1", LogLevelType::USAGE_ERROR, 3, "unknown mode"),
+            record("source_1", LogLevelType::FLOW_CONTROL_ERROR, 4, "overflow"),
+            record("demod_1", LogLevelType::PROGRAMMER_DEBUG1, 5, "tuned"),
+        ]);
+        assert_eq!(service.len(), 3);
+        let spilled = String::from_utf8(file_manager.lock().unwrap().read("/domain/spilled.log").unwrap()).unwrap();
+        assert_eq!(spilled, "1.000000 ADMINISTRATIVE_EVENT source_1: started\n2.000000 ADMINISTRATIVE_EVENT demod_1: started\n");
+        service.write_records(&[record("demod_1", LogLevelType::ADMINISTRATIVE_EVENT, 6, "stopped")]);
+        let spilled = String::from_utf8(file_manager.lock().unwrap().read("/domain/spilled.log").unwrap()).unwrap();
+        assert_eq!(spilled.lines().count(), 3);
+
+        //the queries filter the records by time, level and producer
+        let messages = |query: &LogQuery| service.records(query).into_iter().map(|r| r.message).collect::<Vec<String>>();
+        assert_eq!(messages(&LogQuery::default()), vec!["overflow", "tuned", "stopped"]);
+        let from = |secs: u64| Some(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(messages(&LogQuery { from: from(5), to: from(6), ..LogQuery::default() }), vec!["tuned"]);
+        let filter = LogFilter { log_level: Some(LogLevelType::ADMINISTRATIVE_EVENT), producers: vec!["demod_1:DCE:fm:fm_1".to_string()] };
+        assert_eq!(messages(&LogQuery { filter, ..LogQuery::default() }), vec!["stopped"]);
+        let filter = LogFilter { log_level: None, producers: vec!["*:DCE:fm:*".to_string(), "sou?ce_1".to_string()] };
+        assert_eq!(messages(&LogQuery { filter, ..LogQuery::default() }), vec!["overflow", "tuned", "stopped"]);
+        let filter = LogFilter { log_level: None, producers: vec!["demod_?".to_string()] };
+        assert_eq!(messages(&LogQuery { filter, ..LogQuery::default() }), vec!["tuned", "stopped"]);
+    }
+
+    #[tokio::test]
+    async fn test_log_service() {
+        let service = LogService::default();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(Server::builder().add_service(LogServiceServer::new(service.clone())).serve_with_incoming(incoming));
+        let mut client = LogServiceClient::connect(endpoint).await.unwrap();
+
+        let records = [
+            record("source_1", LogLevelType::FLOW_CONTROL_ERROR, 4, "overflow"),
+            record("demod_1", LogLevelType::PROGRAMMER_DEBUG1, 5, "tuned"),
+        ];
+        let wire_records: Vec<WireLogRecord> = records.iter().map(Into::into).collect();
+        client.write_records(WriteRecordsRequest { records: wire_records }).await.unwrap();
+        assert_eq!(service.len(), 2);
+
+        let request = RetrieveRecordsRequest { from_time: Some(4_000_000), to_time: None, log_level: LogLevelType::USAGE_ERROR.value().into(), producers: vec![] };
+        let retrieved = client.retrieve_records(request).await.unwrap().into_inner().records;
+        assert_eq!(retrieved, vec![WireLogRecord::from(&records[0])]);
+        let retrieved = client.retrieve_records(RetrieveRecordsRequest::default()).await.unwrap().into_inner().records;
+        assert_eq!(retrieved.len(), 2);
+
+        let mut invalid = WireLogRecord::from(&records[0]);
+        invalid.level = 0;
+        assert_eq!(client.write_records(WriteRecordsRequest { records: vec![invalid] }).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+        let request = RetrieveRecordsRequest { log_level: 26, ..RetrieveRecordsRequest::default() };
+        assert_eq!(client.retrieve_records(request).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
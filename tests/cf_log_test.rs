@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::log::{log_file, log_file_name, LogConsumer, LogFormat, LogLevelType, LogProducer, LogRecord, LogWriter, Logger};
+    use scars::cf::log_tracing::{FormatLayer, LogLayer, TracingConsumer, LOG_RECORD_TARGET};
+    use scars::cf::resource::{Resource, ResourceError, ResourceTrait};
+
+    #[derive(Default)]
+    struct Records(Vec<LogRecord>);
+
+    impl LogConsumer for Records {
+        fn write_records(&mut self, records: &[LogRecord]) {
+            self.0.extend_from_slice(records);
+        }
+    }
+
+    #[test]
+    fn test_log_levels() {
+        assert_eq!(LogLevelType::SECURITY_ALARM.value(), 1);
+        assert_eq!(LogLevelType::ADMINISTRATIVE_EVENT.value(), 8);
+        assert_eq!(LogLevelType::PROGRAMMER_DEBUG16.value(), 25);
+        for value in 1..=25 {
+            assert_eq!(LogLevelType::from_value(value).unwrap().value(), value);
+        }
+        assert_eq!(LogLevelType::from_value(0), None);
+        assert_eq!(LogLevelType::from_value(26), None);
+        assert_eq!(LogLevelType::from_name("FAILURE_ALARM"), Some(LogLevelType::FAILURE_ALARM));
+        assert_eq!(LogLevelType::from_name("failure_alarm"), None);
+        assert!(LogLevelType::FAILURE_ALARM < LogLevelType::PROGRAMMER_DEBUG1);
+    }
+
+    #[test]
+    fn test_logger() {
+        let records = Arc::new(Mutex::new(Records::default()));
+        let mut logger = Logger::new("demod_1:DCE:fm:fm_1", "demod_1").with_consumer(records.clone());
+        assert_eq!(logger.log_level(), LogLevelType::ADMINISTRATIVE_EVENT);
+        assert_eq!(logger.log_uri(), None);
+
+        //the records less severe than the level are dropped
+        logger.log(LogLevelType::FAILURE_ALARM, "no samples");
+        logger.log(LogLevelType::ADMINISTRATIVE_EVENT, "started");
+        logger.log(LogLevelType::PROGRAMMER_DEBUG1, "tuned");
+        let written: Vec<(LogLevelType, String)> = records.lock().unwrap().0.iter().map(|r| (r.level, r.message.clone())).collect();
+        assert_eq!(written, vec![(LogLevelType::FAILURE_ALARM, "no samples".to_string()), (LogLevelType::ADMINISTRATIVE_EVENT, "started".to_string())]);
+        let record = records.lock().unwrap().0[0].clone();
+        assert_eq!(record.producer_id, "demod_1:DCE:fm:fm_1");
+        assert_eq!(record.producer_name, "demod_1");
+
+        logger.set_log_level(LogLevelType::PROGRAMMER_DEBUG1);
+        logger.set_log_uri(Some("file:///var/log/demod_1.log"));
+        assert!(logger.is_enabled(LogLevelType::PROGRAMMER_DEBUG1));
+        assert!(!logger.is_enabled(LogLevelType::PROGRAMMER_DEBUG2));
+        assert_eq!(logger.log_uri().as_deref(), Some("file:///var/log/demod_1.log"));
+        logger.log(LogLevelType::PROGRAMMER_DEBUG1, "tuned");
+        assert_eq!(records.lock().unwrap().0.len(), 3);
+
+        //every consumer gets the records, the clones sharing the configuration
+        let other = Arc::new(Mutex::new(Records::default()));
+        let mut clone = logger.clone();
+        clone.add_consumer(other.clone());
+        logger.log(LogLevelType::USAGE_ERROR, "unknown mode");
+        assert_eq!(records.lock().unwrap().0.len(), 4);
+        assert_eq!(other.lock().unwrap().0.len(), 1);
+        clone.set_log_level(LogLevelType::FAILURE_ALARM);
+        assert_eq!(logger.log_level(), LogLevelType::FAILURE_ALARM);
+
+        let record = LogRecord { time: UNIX_EPOCH + Duration::from_micros(1_500_000), ..record };
+        assert_eq!(record.to_string(), "1.500000 FAILURE_ALARM demod_1: no samples");
+    }
+
+    /// Layer recording the target and the level of the events.
+    #[derive(Clone, Default)]
+    struct Events(Arc<Mutex<Vec<(String, Level)>>>);
+
+    impl<S: Subscriber> Layer<S> for Events {
+        fn on_event(&self, event: &Event<'_>, _context: Context<'_, S>) {
+            self.0.lock().unwrap().push((event.metadata().target().to_string(), *event.metadata().level()));
+        }
+    }
+
+    #[test]
+    fn test_tracing_bridge() {
+        let records = Arc::new(Mutex::new(Records::default()));
+        let mut logger = Logger::new("demod_1:DCE:fm:fm_1", "demod_1").with_consumer(records.clone());
+        let events = Events::default();
+        let subscriber = tracing_subscriber::registry().with(LogLayer::new(logger.clone())).with(events.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            //the tracing events are captured as records, after the level of the logger
+            tracing::info!(frequency = 101.1, mode = "stereo", "tuned");
+            tracing::error!("no samples");
+            tracing::debug!("dropped");
+            logger.set_log_level(LogLevelType::PROGRAMMER_DEBUG1);
+            tracing::debug!("kept");
+            let written: Vec<(LogLevelType, String)> = records.lock().unwrap().0.iter().map(|r| (r.level, r.message.clone())).collect();
+            assert_eq!(
+                written,
+                vec![
+                    (LogLevelType::ADMINISTRATIVE_EVENT, "tuned frequency=101.1 mode=stereo".to_string()),
+                    (LogLevelType::EXCEPTION_ERROR, "no samples".to_string()),
+                    (LogLevelType::PROGRAMMER_DEBUG1, "kept".to_string()),
+                ]
+            );
+            assert_eq!(records.lock().unwrap().0[0].producer_name, "demod_1");
+
+            //the records of remote producers are re-emitted, but not captured back
+            events.0.lock().unwrap().clear();
+            let remote = LogRecord::new("source_1:DCE:fm:fm_1", "source_1", LogLevelType::FLOW_CONTROL_ERROR, "overflow");
+            TracingConsumer.write_records(&[remote]);
+            assert_eq!(*events.0.lock().unwrap(), vec![(LOG_RECORD_TARGET.to_string(), Level::WARN)]);
+            assert_eq!(records.lock().unwrap().0.len(), 3);
+        });
+
+        assert_eq!(Level::from(LogLevelType::DEGRADED_ALARM), Level::ERROR);
+        assert_eq!(Level::from(LogLevelType::STATISTIC_REPORT), Level::INFO);
+        assert_eq!(Level::from(LogLevelType::PROGRAMMER_DEBUG3), Level::TRACE);
+        for level in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE] {
+            assert_eq!(Level::from(LogLevelType::from(level)), level);
+        }
+    }
+
+    #[test]
+    fn test_logging_properties() {
+        let records = Arc::new(Mutex::new(Records::default()));
+        let exec_params = vec![DataType::new("LOG_LEVEL", AnyValue::String("PROGRAMMER_DEBUG1".to_string())), DataType::new("DEVICE_ID", AnyValue::String("DCE:gpp".to_string()))];
+        let mut resource = Resource::new("demod_1:DCE:fm:fm_1").with_property("frequency", AnyValue::Double(101.1)).with_exec_params(&exec_params);
+        resource.logger().add_consumer(records.clone());
+        assert_eq!(resource.log_level(), LogLevelType::PROGRAMMER_DEBUG1);
+
+        //the level is configured by its name or its value, the URI by a string
+        resource.configure(&vec![DataType::new("LOG_LEVEL", AnyValue::String("FAILURE_ALARM".to_string()))]).unwrap();
+        assert_eq!(resource.log_level(), LogLevelType::FAILURE_ALARM);
+        resource.configure(&vec![DataType::new("LOG_LEVEL", AnyValue::Long(7)), DataType::new("LOGGING_CONFIG_URI", AnyValue::String("file:///var/log/demod_1.log".to_string()))]).unwrap();
+        assert_eq!(resource.log_level(), LogLevelType::USAGE_ERROR);
+        resource.logger().log(LogLevelType::USAGE_ERROR, "unknown mode");
+        resource.logger().log(LogLevelType::ADMINISTRATIVE_EVENT, "started");
+        assert_eq!(records.lock().unwrap().0.len(), 1);
+
+        let queried = resource.query(&vec![DataType::new("LOG_LEVEL", AnyValue::Boolean(false)), DataType::new("LOGGING_CONFIG_URI", AnyValue::Boolean(false))]).unwrap();
+        assert_eq!(queried, vec![DataType::new("LOG_LEVEL", AnyValue::UShort(7)), DataType::new("LOGGING_CONFIG_URI", AnyValue::String("file:///var/log/demod_1.log".to_string()))]);
+        assert_eq!(resource.query(&vec![]).unwrap().len(), 3);
+
+        //the invalid levels are left out of the configuration
+        match resource.configure(&vec![DataType::new("LOG_LEVEL", AnyValue::Double(7.5))]) {
+            Err(ResourceError::InvalidConfiguration { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match resource.configure(&vec![DataType::new("LOG_LEVEL", AnyValue::String("CHATTY".to_string())), DataType::new("LOGGING_CONFIG_URI", AnyValue::String(String::new()))]) {
+            Err(ResourceError::PartialConfiguration { invalid_properties }) => assert_eq!(invalid_properties.len(), 1),
+            r => panic!("{:?}", r),
+        }
+        assert_eq!(resource.log_level(), LogLevelType::USAGE_ERROR);
+        assert_eq!(resource.log_uri(), None);
+    }
+
+    /// Output shared with the test.
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Output {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap().lines().map(str::to_string).collect()
+        }
+    }
+
+    #[test]
+    fn test_log_formats() {
+        let record = LogRecord::new("demod_1:DCE:fm:fm_1", "demod_1", LogLevelType::USAGE_ERROR, "unknown \"wfm\" mode");
+        let record = LogRecord { time: UNIX_EPOCH + Duration::from_micros(1_792_183_967_036_201), ..record };
+        assert_eq!(LogFormat::Text.format(&record), "1792183967.036201 USAGE_ERROR demod_1: unknown \"wfm\" mode");
+        assert_eq!(
+            LogFormat::Json.format(&record),
+            r#"{"level":"USAGE_ERROR","level_value":7,"message":"unknown \"wfm\" mode","producer_id":"demod_1:DCE:fm:fm_1","producer_name":"demod_1","time":"2026-10-16T20:52:47.036201Z"}"#
+        );
+        let leap_day = LogRecord { time: UNIX_EPOCH + Duration::from_secs(951_868_799), ..record.clone() };
+        let json: serde_json::Value = serde_json::from_str(&LogFormat::Json.format(&leap_day)).unwrap();
+        assert_eq!(json["time"], "2000-02-29T23:59:59.000000Z");
+        assert_eq!(LogFormat::from_name("json"), Some(LogFormat::Json));
+        assert_eq!(LogFormat::from_name("xml"), None);
+
+        let mut writer = LogWriter::new(Vec::new(), LogFormat::Json);
+        writer.write_records(&[record.clone(), record]);
+        assert_eq!(String::from_utf8(writer.output().clone()).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_format_layer() {
+        let output = Output::default();
+        let subscriber = tracing_subscriber::registry().with(FormatLayer::new(output.clone(), LogFormat::Json));
+
+        tracing::subscriber::with_default(subscriber, || {
+            //the events are written as records of their target, the re-emitted records of their producer
+            tracing::info!(target: "scars::node", frequency = 101.1, "tuned");
+            let remote = LogRecord::new("source_1:DCE:fm:fm_1", "source_1", LogLevelType::FLOW_CONTROL_ERROR, "overflow");
+            TracingConsumer.write_records(&[remote]);
+        });
+
+        let records: Vec<serde_json::Value> = output.lines().iter().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["producer_id"], "scars::node");
+        assert_eq!(records[0]["level"], "ADMINISTRATIVE_EVENT");
+        assert_eq!(records[0]["message"], "tuned frequency=101.1");
+        assert_eq!(records[1]["producer_name"], "source_1");
+        assert_eq!(records[1]["level"], "FLOW_CONTROL_ERROR");
+        assert_eq!(records[1]["message"], "overflow");
+    }
+
+    #[test]
+    fn test_log_files() {
+        assert_eq!(log_file("file:///var/log/demod_1.log"), Some(PathBuf::from("/var/log/demod_1.log")));
+        assert_eq!(log_file("/var/log/demod_1.log"), Some(PathBuf::from("/var/log/demod_1.log")));
+        assert_eq!(log_file("http://logs/demod_1"), None);
+        assert_eq!(log_file_name("demod_1:DCE:fm:fm_1"), "/logs/demod_1:DCE:fm:fm_1.log");
+        assert_eq!(log_file_name("DCE:a/b"), "/logs/DCE:a_b.log");
+    }
+}
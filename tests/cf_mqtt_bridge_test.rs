@@ -0,0 +1,178 @@
+#[cfg(feature = "mqtt")]
+mod common;
+
+#[cfg(all(test, feature = "mqtt"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+    use tokio_stream::StreamExt;
+    use tonic::transport::Endpoint;
+
+    use scars::cf::common_types::AnyValue;
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::events::{
+        EventStream, StateChangeCategoryType, StateChangeEvent, StateChangeType,
+    };
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::mqtt_bridge::{
+        LoopbackBroker, MqttBridge, MqttClientTrait, MqttMessage, MqttTopics,
+    };
+    use scars::cf::resource::Resource;
+
+    use crate::common;
+
+    /// Returns the topic and JSON payload of the next message.
+    async fn next(messages: &mut EventStream<MqttMessage>) -> (String, Value) {
+        let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+            .await
+            .unwrap()
+            .unwrap();
+        (
+            message.topic,
+            serde_json::from_slice(&message.payload).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_bridge() {
+        let root = tempfile::tempdir().unwrap();
+        common::tone_waveform(root.path());
+        let gpp = common::sim_gpp();
+        let registry = ComponentRegistry::new();
+        let osc = Resource::new("osc").with_property("frequency", AnyValue::Double(1000.0));
+        registry.register_component("tone_1/osc_1", Arc::new(Mutex::new(osc)));
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain
+            .file_manager()
+            .lock()
+            .unwrap()
+            .mount("/dom", Arc::new(FileSystem::new(root.path())))
+            .unwrap();
+        let deployment = common::deployment(
+            domain.file_manager(),
+            common::allocation_manager(&gpp),
+            &gpp,
+            registry,
+        );
+        let domain = domain.with_deployment(deployment);
+        domain
+            .install_application("/dom/waveforms/tone/tone.sad.xml")
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let served = tokio::spawn(domain.clone().run(listener));
+
+        let broker = LoopbackBroker::new();
+        let topics = MqttTopics::with_prefix("fleet/vehicle_7");
+        assert_eq!(
+            topics.device_state,
+            "fleet/vehicle_7/devices/{source_id}/state"
+        );
+        let mut replies = broker.subscribe(&topics.replies).unwrap();
+        let mut applications = broker.subscribe("fleet/vehicle_7/applications/+").unwrap();
+        let mut properties = broker.subscribe("fleet/vehicle_7/properties/#").unwrap();
+        let mut devices = broker.subscribe("fleet/vehicle_7/devices/+/state").unwrap();
+        let bridge = MqttBridge::new(Arc::new(broker.clone()))
+            .with_topics(topics)
+            .with_properties("DCE:tone:tone_1", &["frequency"])
+            .with_poll_interval(Duration::from_millis(20));
+        let channel = Endpoint::from_shared(endpoint)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let bridged = tokio::spawn(bridge.run(channel));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        //the applications created by command are published, along with their selected properties
+        broker.publish("fleet/vehicle_7/commands", json!({"request_id": "1", "command": "create", "factory": "DCE:tone", "name": "tone_1"}).to_string().into_bytes()).unwrap();
+        assert_eq!(
+            next(&mut replies).await.1,
+            json!({"request_id": "1", "ok": true, "identifier": "DCE:tone:tone_1"})
+        );
+        let (topic, added) = next(&mut applications).await;
+        assert_eq!(topic, "fleet/vehicle_7/applications/DCE:tone:tone_1");
+        assert_eq!(added["ObjectAdded"]["source_category"], "APPLICATION");
+        let (topic, changed) = next(&mut properties).await;
+        assert_eq!(
+            (topic.as_str(), &changed["properties"]),
+            (
+                "fleet/vehicle_7/properties/DCE:tone:tone_1",
+                &json!([{"id": "frequency", "value": {"Double": 1000.0}}])
+            )
+        );
+
+        //the configured values are published once changed
+        broker.publish("fleet/vehicle_7/commands", json!({"command": "configure", "identifier": "DCE:tone:tone_1", "properties": [{"id": "frequency", "value": {"Double": 2000.0}}]}).to_string().into_bytes()).unwrap();
+        assert_eq!(next(&mut replies).await.1, json!({"ok": true}));
+        assert_eq!(
+            next(&mut properties).await.1["properties"],
+            json!([{"id": "frequency", "value": {"Double": 2000.0}}])
+        );
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), properties.next())
+                .await
+                .is_err()
+        );
+
+        //the failed commands are replied to with their error
+        broker
+            .publish(
+                "fleet/vehicle_7/commands",
+                json!({"request_id": "3", "command": "start", "identifier": "DCE:tone:tone_2"})
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap();
+        let (_, reply) = next(&mut replies).await;
+        assert_eq!(
+            (&reply["request_id"], &reply["ok"]),
+            (&json!("3"), &json!(false))
+        );
+        broker
+            .publish(
+                "fleet/vehicle_7/commands",
+                b"{\"command\": \"reboot\"}".to_vec(),
+            )
+            .unwrap();
+        let (_, reply) = next(&mut replies).await;
+        assert!(
+            reply["error"].as_str().unwrap().contains("reboot"),
+            "{reply}"
+        );
+
+        //so are the state changes of the devices
+        domain.idm_channel().push(StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        });
+        let (topic, state) = next(&mut devices).await;
+        assert_eq!(
+            (topic.as_str(), &state["state_change_to"]),
+            ("fleet/vehicle_7/devices/DCE:gpp/state", &json!("BUSY"))
+        );
+
+        broker
+            .publish(
+                "fleet/vehicle_7/commands",
+                json!({"command": "release", "identifier": "DCE:tone:tone_1"})
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap();
+        assert_eq!(next(&mut replies).await.1, json!({"ok": true}));
+        assert!(next(&mut applications).await.1["ObjectRemoved"].is_object());
+
+        //the bridge ends with the domain
+        domain.shutdown();
+        served.await.unwrap().unwrap();
+        bridged.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,213 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::path::{Path, PathBuf};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tokio::net::TcpListener;
+
+    use scars::cf::domain_manager::{ApplicationStatus, DomainManager};
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::resource::Resource;
+    use scars::cf::rpc::device_manager::device_manager_client::DeviceManagerClient;
+    use scars::cf::rpc::device_manager::ShutdownRequest;
+    use scars::cf::rpc::domain_manager::domain_manager_client::DomainManagerClient;
+    use scars::cf::rpc::domain_manager::DeviceManagersRequest;
+
+    use crate::common;
+
+    const DMD: &str = r#"<domainmanagerconfiguration id="DCE:domain" name="REDHAWK_DEV">
+    <domainmanagersoftpkg><localfile name="/mgr/DomainManager.spd.xml"/></domainmanagersoftpkg>
+    </domainmanagerconfiguration>"#;
+    const DCD: &str = r#"<deviceconfiguration id="DCE:node" name="node"/>"#;
+    const GPP_DCD: &str = r#"<deviceconfiguration id="DCE:node" name="node"><componentfiles>
+    <componentfile id="gpp_file" type="SPD"><localfile name="/devices/SimExecutableDevice/SimExecutableDevice.spd.xml"/></componentfile>
+    </componentfiles><partitioning><componentplacement><componentfileref refid="gpp_file"/>
+    <componentinstantiation id="DCE:gpp"/></componentplacement></partitioning></deviceconfiguration>"#;
+
+    /// Writes the DMD and the DCD of the booter under the SDR root.
+    fn sdrroot() -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("dom/domain")).unwrap();
+        std::fs::write(root.path().join("dom/domain/DomainManager.dmd.xml"), DMD).unwrap();
+        std::fs::create_dir_all(root.path().join("dev/nodes/node")).unwrap();
+        std::fs::write(root.path().join("dev/nodes/node/DeviceManager.dcd.xml"), DCD).unwrap();
+        root
+    }
+
+    fn booter(root: &Path, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_scars-nodebooter"));
+        command.arg("--sdrroot").arg(root).args(args);
+        command
+    }
+
+    /// Boots, returning the booter along with the endpoints it printed, the rest of its output, e.g. the one of its devices, being drained.
+    fn boot(root: &Path, args: &[&str], endpoints: usize) -> (Child, Vec<String>) {
+        let mut child = booter(root, args).stdout(Stdio::piped()).spawn().unwrap();
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let endpoints = (0..endpoints).map(|_| format!("http://{}", lines.next().unwrap().unwrap())).collect();
+        std::thread::spawn(move || lines.for_each(drop));
+        (child, endpoints)
+    }
+
+    /// Waits for the booter to exit, the clients of the test closing their connections meanwhile.
+    async fn exited(mut child: Child) -> bool {
+        tokio::task::spawn_blocking(move || child.wait().unwrap().success()).await.unwrap()
+    }
+
+    fn pid_file(root: &Path) -> PathBuf {
+        root.join("booter.pid")
+    }
+
+    /// Returns the pid read from the pid file, once written.
+    fn read_pid(path: &Path) -> Option<u32> {
+        std::fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    /// Waits for the pid file to be removed by the booter.
+    fn removed(path: &Path) -> bool {
+        for _ in 0..200 {
+            if !path.exists() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn test_boot() {
+        let root = sdrroot();
+        let pid_file = pid_file(root.path());
+        let (child, endpoints) = boot(root.path(), &["-D", "/domain/DomainManager.dmd.xml", "-d", "/nodes/node/DeviceManager.dcd.xml", "--pid-file", pid_file.to_str().unwrap()], 2);
+        assert_eq!(read_pid(&pid_file), Some(child.id()));
+
+        //the booted DeviceManager registers with the booted DomainManager
+        let mut domain = DomainManagerClient::connect(endpoints[0].clone()).await.unwrap();
+        let mut registered = Vec::new();
+        for _ in 0..200 {
+            registered = domain.device_managers(DeviceManagersRequest {}).await.unwrap().into_inner().device_managers;
+            if !registered.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(registered[0].device_manager.as_ref().unwrap().identifier, "DCE:node");
+
+        //SIGTERM shuts both managers down, removing the pid file
+        drop(domain);
+        assert_eq!(unsafe { libc::kill(child.id() as i32, libc::SIGTERM) }, 0);
+        assert!(exited(child).await);
+        assert!(!pid_file.exists());
+        assert!(DomainManagerClient::connect(endpoints[0].clone()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        let root = sdrroot();
+        let (child, endpoints) = boot(root.path(), &["-d", "/nodes/node/DeviceManager.dcd.xml"], 1);
+
+        //the shutdown operation of the DeviceManager stops the booter
+        let mut node = DeviceManagerClient::connect(endpoints[0].clone()).await.unwrap();
+        node.shutdown(ShutdownRequest {}).await.unwrap();
+        assert!(exited(child).await);
+
+        //the DMD or the DCD to boot must be given
+        let output = booter(root.path(), &["--daemon"]).output().unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("usage: scars-nodebooter"));
+    }
+
+    #[tokio::test]
+    async fn test_pid_file() {
+        let root = sdrroot();
+        let pid_file = pid_file(root.path());
+        let args = ["-d", "/nodes/node/DeviceManager.dcd.xml", "--pid-file", pid_file.to_str().unwrap()];
+
+        //the pid file left by a killed booter is taken over
+        std::fs::write(&pid_file, "4194304\n").unwrap();
+        let (child, endpoints) = boot(root.path(), &args, 1);
+        assert_eq!(read_pid(&pid_file), Some(child.id()));
+
+        //the pid file is held by a single booter at once
+        let output = booter(root.path(), &args).output().unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("booter.pid is held by a running booter"), "{stderr}");
+        assert_eq!(read_pid(&pid_file), Some(child.id()));
+
+        let mut node = DeviceManagerClient::connect(endpoints[0].clone()).await.unwrap();
+        node.shutdown(ShutdownRequest {}).await.unwrap();
+        assert!(exited(child).await);
+        assert!(!pid_file.exists());
+    }
+
+    #[test]
+    fn test_daemon() {
+        let root = sdrroot();
+        let pid_file = pid_file(root.path());
+        let output = booter(root.path(), &["-d", "/nodes/node/DeviceManager.dcd.xml", "--pid-file", pid_file.to_str().unwrap(), "--daemon"]).output().unwrap();
+        assert!(output.status.success());
+        let pid: u32 = String::from_utf8_lossy(&output.stdout).trim().parse().unwrap();
+
+        //the daemon writes its pid, leading its own session
+        let mut written = None;
+        for _ in 0..200 {
+            written = read_pid(&pid_file);
+            if written.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(written, Some(pid));
+        assert_eq!(unsafe { libc::getsid(pid as i32) }, pid as i32);
+        assert_ne!(unsafe { libc::getsid(0) }, pid as i32);
+
+        assert_eq!(unsafe { libc::kill(pid as i32, libc::SIGTERM) }, 0);
+        assert!(removed(&pid_file));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_deploy_on_booted_node() {
+        let dom = tempfile::tempdir().unwrap();
+        common::tone_waveform(dom.path());
+        common::write_files(dom.path(), &[("waveforms/tone/osc", "osc")]);
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(dom.path()))).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+
+        let root = sdrroot();
+        std::fs::write(root.path().join("dev/nodes/node/DeviceManager.dcd.xml"), GPP_DCD).unwrap();
+        let (child, endpoints) = boot(root.path(), &["-d", "/nodes/node/DeviceManager.dcd.xml", "--domain-manager", &endpoint], 1);
+        for _ in 0..200 {
+            if !domain.devices().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(domain.devices()[0].identifier, "DCE:gpp");
+
+        //the application is deployed on the device the booted node registered
+        domain.install_application("/dom/waveforms/tone/tone.sad.xml").unwrap();
+        domain.registry().register_component("tone_1/osc_1", Arc::new(Mutex::new(Resource::new("osc"))));
+        let identifier = domain.create_application("DCE:tone", "tone_1", &vec![], &[]).unwrap();
+        let info = domain.applications()[0].clone();
+        assert_eq!(info.status, ApplicationStatus::RUNNING);
+        assert_eq!((info.components[0].device_id.as_str(), info.components[0].process_id.is_some()), ("DCE:gpp", true));
+        assert_eq!(domain.device_capacities()[0].allocations.len(), info.allocations.len());
+        domain.release_application(&identifier).unwrap();
+
+        let mut node = DeviceManagerClient::connect(endpoints[0].clone()).await.unwrap();
+        node.shutdown(ShutdownRequest {}).await.unwrap();
+        assert!(exited(child).await);
+        assert!(domain.devices().is_empty());
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,780 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::file_system::{FileSystem, FileSystemTrait};
+    use scars::cf::profile::cache::ProfileCache;
+    use scars::cf::profile::diff::{self, Change};
+    use scars::cf::profile::dmd::DomainManagerConfiguration;
+    use scars::cf::profile::lint::{self, Issue};
+    use scars::cf::profile::plan;
+    use scars::cf::common_types::ActionType;
+    use scars::cf::profile::prf::{AccessMode, Property, PropertiesDescriptor, PropertyKind, PropertyType};
+    use scars::cf::profile::sad::{ConnectionTarget, FindBy, PortKind, PortReference, SoftwareAssembly};
+    use scars::cf::profile::scd::SoftwareComponent;
+    use scars::cf::profile::spd::SoftPkg;
+    use scars::cf::profile::validate;
+    use scars::cf::profile::{resolve_file_name, ProfileError};
+
+    const SAD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<softwareassembly id="DCE:fm" name="fm">
+  <componentfiles>
+    <componentfile id="demod_file" type="SPD">
+      <localfile name="../../components/demod/demod.spd.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="demod_file"/>
+      <componentinstantiation id="demod_1">
+        <usagename>demod</usagename>
+      </componentinstantiation>
+    </componentplacement>
+  </partitioning>
+  <assemblycontroller>
+    <componentinstantiationref refid="demod_1"/>
+  </assemblycontroller>
+  <connections>
+    <connectinterface id="loopback">
+      <usesport>
+        <usesidentifier>audio_out</usesidentifier>
+        <componentinstantiationref refid="demod_1"/>
+      </usesport>
+      <providesport>
+        <providesidentifier>audio_in</providesidentifier>
+        <componentinstantiationref refid="demod_1"/>
+      </providesport>
+    </connectinterface>
+  </connections>
+</softwareassembly>
+"#;
+
+    const SPD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<softpkg id="DCE:demod" name="demod">
+  <propertyfile type="PRF">
+    <localfile name="demod.prf.xml"/>
+  </propertyfile>
+  <descriptor>
+    <localfile name="demod.scd.xml"/>
+  </descriptor>
+  <implementation id="cpp">
+    <code type="Executable">
+      <localfile name="cpp"/>
+      <entrypoint>cpp/demod</entrypoint>
+    </code>
+    <processor name="x86_64"/>
+    <os name="Linux" version="6"/>
+    <dependency type="allocation">
+      <propertyref refid="processor_cores" value="2"/>
+    </dependency>
+  </implementation>
+</softpkg>
+"#;
+
+    const SCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<softwarecomponent>
+  <corbaversion>2.2</corbaversion>
+  <componentrepid repid="IDL:CF/Resource:1.0"/>
+  <componenttype>resource</componenttype>
+  <componentfeatures>
+    <supportsinterface repid="IDL:CF/Resource:1.0" supportsname="Resource"/>
+    <ports>
+      <provides repid="IDL:BULKIO/dataFloat:1.0" providesname="audio_in">
+        <porttype type="data"/>
+      </provides>
+      <uses repid="IDL:BULKIO/dataFloat:1.0" usesname="audio_out"/>
+    </ports>
+  </componentfeatures>
+  <interfaces>
+    <interface repid="IDL:CF/Resource:1.0" name="Resource">
+      <inheritsinterface repid="IDL:CF/LifeCycle:1.0"/>
+    </interface>
+    <interface repid="IDL:BULKIO/dataFloat:1.0" name="dataFloat"/>
+  </interfaces>
+</softwarecomponent>
+"#;
+
+    const PRF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<properties>
+  <description>demod properties</description>
+  <simple id="frequency" name="frequency" type="double" mode="readwrite">
+    <value>101.1</value>
+    <units>MHz</units>
+    <range min="87.5" max="108.0"/>
+    <kind kindtype="configure"/>
+    <kind kindtype="execparam"/>
+  </simple>
+  <simple id="mode" type="string">
+    <value>stereo</value>
+    <enumerations>
+      <enumeration label="MONO" value="mono"/>
+      <enumeration label="STEREO" value="stereo"/>
+    </enumerations>
+  </simple>
+  <simple id="processor_name" type="string" mode="readonly">
+    <value>x86_64</value>
+    <kind kindtype="allocation"/>
+    <action type="eq"/>
+  </simple>
+  <simplesequence id="taps" type="short">
+    <values><value>1</value><value>-2</value></values>
+    <kind kindtype="property"/>
+  </simplesequence>
+  <struct id="gain" mode="writeonly">
+    <simple id="gain::value" type="float"><value>0.5</value></simple>
+    <simple id="gain::auto" type="boolean"/>
+    <configurationkind kindtype="configure"/>
+  </struct>
+  <structsequence id="channels">
+    <struct id="channel">
+      <simple id="channel::id" type="ulong"/>
+      <simplesequence id="channel::tags" type="string"/>
+    </struct>
+    <structvalue>
+      <simpleref refid="channel::id" value="1"/>
+      <simplesequenceref refid="channel::tags"><values><value>left</value></values></simplesequenceref>
+    </structvalue>
+  </structsequence>
+</properties>
+"#;
+
+    #[test]
+    fn test_parse_sad() {
+        let sad = SoftwareAssembly::parse(SAD, "/waveforms/fm/fm.sad.xml").unwrap();
+        assert_eq!(sad.id, "DCE:fm");
+        assert_eq!(sad.assembly_controller.as_deref(), Some("demod_1"));
+        assert_eq!(
+            sad.instantiation("demod_1").unwrap().usage_name.as_deref(),
+            Some("demod")
+        );
+        assert_eq!(
+            sad.component_file(&sad.placements[0]).unwrap().local_file,
+            "../../components/demod/demod.spd.xml"
+        );
+        let connection = &sad.connections[0];
+        assert_eq!(connection.id.as_deref(), Some("loopback"));
+        assert_eq!(connection.uses_port.identifier, "audio_out");
+        assert_eq!(
+            connection.target,
+            ConnectionTarget::ProvidesPort(PortReference { identifier: "audio_in".to_string(), component_ref: "demod_1".to_string() })
+        );
+
+        //the target may be the component itself or found by other means
+        let provides = "<providesport>\n        <providesidentifier>audio_in</providesidentifier>\n        <componentinstantiationref refid=\"demod_1\"/>\n      </providesport>";
+        assert!(SAD.contains(provides));
+        let targets = [
+            (
+                r#"<componentsupportedinterface><supportedidentifier>IDL:CF/Resource:1.0</supportedidentifier><componentinstantiationref refid="demod_1"/></componentsupportedinterface>"#,
+                ConnectionTarget::SupportedInterface(PortReference { identifier: "IDL:CF/Resource:1.0".to_string(), component_ref: "demod_1".to_string() }),
+            ),
+            (
+                r#"<findby><namingservice name="fm_1/sink_1"/></findby>"#,
+                ConnectionTarget::FindBy(FindBy::NamingService { name: "fm_1/sink_1".to_string() }),
+            ),
+            (
+                r#"<findby><stringifiedobjectref>http://127.0.0.1:5001</stringifiedobjectref></findby>"#,
+                ConnectionTarget::FindBy(FindBy::StringifiedObjectRef("http://127.0.0.1:5001".to_string())),
+            ),
+            (
+                r#"<findby><domainfinder type="servicename" name="audio"/></findby>"#,
+                ConnectionTarget::FindBy(FindBy::DomainFinder { finder_type: "servicename".to_string(), name: Some("audio".to_string()) }),
+            ),
+        ];
+        for (xml, target) in targets {
+            let parsed = SoftwareAssembly::parse(&SAD.replace(provides, xml), "fm.sad.xml").unwrap();
+            assert_eq!(parsed.connections[0].target, target);
+        }
+        for xml in ["", "<findby/>", r#"<componentsupportedinterface><supportedidentifier>IDL:CF/Resource:1.0</supportedidentifier><componentinstantiationref refid="demod_2"/></componentsupportedinterface>"#] {
+            match SoftwareAssembly::parse(&SAD.replace(provides, xml), "fm.sad.xml") {
+                Err(ProfileError::InvalidProfile { .. }) => {}
+                r => panic!("{xml}: {:?}", r),
+            }
+        }
+
+        //the placements of a host collocation are placements of the partitioning
+        let xml = SAD
+            .replace("<componentplacement>", "<hostcollocation id=\"rf\" name=\"rf_chain\"><componentplacement>")
+            .replace("</componentplacement>", "</componentplacement></hostcollocation>");
+        let collocated = SoftwareAssembly::parse(&xml, "fm.sad.xml").unwrap();
+        assert_eq!(collocated.placements, sad.placements);
+        assert_eq!(collocated.host_collocations[0].name.as_deref(), Some("rf_chain"));
+        assert_eq!(collocated.host_collocations[0].instantiations, vec!["demod_1".to_string()]);
+        assert!(sad.host_collocations.is_empty());
+
+        let xml = SAD.replace(
+            "</softwareassembly>",
+            r#"<usesdevicedependencies><usesdevice id="rf" type="usesdevice"><propertyref refid="device_kind" value="TUNER"/>
+            </usesdevice></usesdevicedependencies></softwareassembly>"#,
+        );
+        let uses_device = &SoftwareAssembly::parse(&xml, "fm.sad.xml").unwrap().uses_devices[0];
+        assert_eq!((uses_device.id.as_str(), uses_device.device_type.as_deref()), ("rf", Some("usesdevice")));
+        assert_eq!(uses_device.properties, vec![DataType::new("device_kind", AnyValue::String("TUNER".to_string()))]);
+
+        let xml = SAD.replace("refid=\"demod_1\"", "refid=\"demod_2\"");
+        match SoftwareAssembly::parse(&xml, "fm.sad.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_parse_spd() {
+        let spd = SoftPkg::parse(SPD, "demod.spd.xml").unwrap();
+        assert_eq!(spd.property_file.as_deref(), Some("demod.prf.xml"));
+        assert_eq!(spd.descriptor.as_deref(), Some("demod.scd.xml"));
+        let implementation = spd.implementation("cpp").unwrap();
+        let code = implementation.code.as_ref().unwrap();
+        assert_eq!(code.code_type, "Executable");
+        assert_eq!(code.entry_point.as_deref(), Some("cpp/demod"));
+        assert_eq!(implementation.processors, vec!["x86_64"]);
+        assert_eq!(implementation.os[0].version.as_deref(), Some("6"));
+        assert_eq!(implementation.dependencies, vec![DataType::new("processor_cores", AnyValue::String("2".to_string()))]);
+        let requirements: Vec<Vec<String>> = implementation.platform_requirements().iter().map(|r| r.iter().map(|p| format!("{}={}", p.id, p.value)).collect()).collect();
+        assert_eq!(requirements, vec![vec!["processor_name=x86_64", "os_name=Linux", "os_version=6"]]);
+
+        match SoftPkg::parse("<softpkg name=\"demod\"/>", "demod.spd.xml") {
+            Err(ProfileError::InvalidProfile { message, .. }) => {
+                assert!(message.contains("line 1"), "{message}")
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_parse_prf() {
+        let prf = PropertiesDescriptor::parse(PRF, "demod.prf.xml").unwrap();
+        assert_eq!(prf.description.as_deref(), Some("demod properties"));
+        assert_eq!(prf.properties.len(), 6);
+
+        let Some(Property::Simple(frequency)) = prf.property("frequency") else { panic!("{:?}", prf.property("frequency")) };
+        assert_eq!(frequency.value_type, PropertyType::DOUBLE);
+        assert_eq!(frequency.units.as_deref(), Some("MHz"));
+        assert_eq!(frequency.kinds, vec![PropertyKind::CONFIGURE, PropertyKind::EXECPARAM]);
+        let range = frequency.range.as_ref().unwrap();
+        assert!(range.contains(&AnyValue::Double(100.0)));
+        assert!(!range.contains(&AnyValue::Double(120.0)));
+        let Some(Property::Simple(mode)) = prf.property("mode") else { panic!() };
+        assert_eq!(mode.kinds, vec![PropertyKind::CONFIGURE]);
+        assert_eq!(mode.enumerations[1].value, AnyValue::String("stereo".to_string()));
+        let Some(Property::Simple(processor)) = prf.property("processor_name") else { panic!() };
+        assert_eq!(processor.action, Some(ActionType::EQ));
+        assert!(!processor.mode.is_writable());
+
+        //the default values of sequences and structs are typed
+        assert_eq!(prf.property("taps").unwrap().value(), Some(AnyValue::Sequence(vec![AnyValue::Short(1), AnyValue::Short(-2)])));
+        let gain = prf.property("gain").unwrap();
+        assert_eq!(gain.mode(), AccessMode::WRITEONLY);
+        assert_eq!(gain.value(), Some(AnyValue::Struct(vec![DataType::new("gain::value", AnyValue::Float(0.5))])));
+        assert_eq!(
+            prf.property("channels").unwrap().value(),
+            Some(AnyValue::Sequence(vec![AnyValue::Struct(vec![
+                DataType::new("channel::id", AnyValue::ULong(1)),
+                DataType::new("channel::tags", AnyValue::Sequence(vec![AnyValue::String("left".to_string())])),
+            ])]))
+        );
+        assert_eq!(
+            prf.values(PropertyKind::CONFIGURE),
+            vec![
+                DataType::new("frequency", AnyValue::Double(101.1)),
+                DataType::new("mode", AnyValue::String("stereo".to_string())),
+                DataType::new("gain", AnyValue::Struct(vec![DataType::new("gain::value", AnyValue::Float(0.5))])),
+                DataType::new("channels", prf.property("channels").unwrap().value().unwrap()),
+            ]
+        );
+        assert_eq!(prf.properties_of_kind(PropertyKind::ALLOCATION).map(Property::id).collect::<Vec<_>>(), vec!["processor_name"]);
+
+        //verify the values match their type
+        let invalid = [
+            r#"<properties><simple id="f" type="long"><value>1.5</value></simple></properties>"#,
+            r#"<properties><simple id="f" type="complex"/></properties>"#,
+            r#"<properties><simple id="f" type="long"/><simple id="f" type="long"/></properties>"#,
+            r#"<properties><structsequence id="s"><struct id="t"/><structvalue><simpleref refid="x" value="1"/></structvalue></structsequence></properties>"#,
+            r#"<properties><simple id="f" type="long"><kind kindtype="unknown"/></simple></properties>"#,
+        ];
+        for xml in invalid {
+            match PropertiesDescriptor::parse(xml, "demod.prf.xml") {
+                Err(ProfileError::InvalidProfile { .. }) => {}
+                r => panic!("{xml}: {:?}", r),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_scd() {
+        let scd = SoftwareComponent::parse(SCD, "demod.scd.xml").unwrap();
+        assert_eq!(scd.repository_id.as_deref(), Some("IDL:CF/Resource:1.0"));
+        assert_eq!(scd.component_type.as_deref(), Some("resource"));
+        assert_eq!(scd.supported_interfaces[0].name, "Resource");
+        let port = scd.port("audio_in").unwrap();
+        assert_eq!((port.kind, port.repository_id.as_str()), (PortKind::PROVIDES, "IDL:BULKIO/dataFloat:1.0"));
+        assert_eq!(port.port_types, vec!["data"]);
+        assert_eq!(scd.port("audio_out").unwrap().kind, PortKind::USES);
+        assert_eq!(scd.interfaces[0].inherits, vec!["IDL:CF/LifeCycle:1.0"]);
+
+        match SoftwareComponent::parse("<softwarecomponent><componentrepid/></softwarecomponent>", "demod.scd.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_parse_dmd() {
+        let dmd = DomainManagerConfiguration::parse(r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE domainmanagerconfiguration PUBLIC "-//JTRS//DTD SCA V2.2.2 DMD//EN" "domainmanagerconfiguration.dtd">
+<domainmanagerconfiguration id="DCE:7f0e5c1a-3b0d-4d4e-9c1e-2f6e1d0a9b11" name="REDHAWK_DEV">
+    <description>The development domain</description>
+    <domainmanagersoftpkg><localfile name="/mgr/DomainManager.spd.xml"/></domainmanagersoftpkg>
+</domainmanagerconfiguration>"#, "DomainManager.dmd.xml").unwrap();
+        assert_eq!(dmd.name, "REDHAWK_DEV");
+        assert_eq!(dmd.description.as_deref(), Some("The development domain"));
+        assert_eq!(dmd.domain_manager_softpkg.as_deref(), Some("/mgr/DomainManager.spd.xml"));
+        assert_eq!(DomainManagerConfiguration::parse(&dmd.to_xml(), "DomainManager.dmd.xml").unwrap(), dmd);
+
+        match DomainManagerConfiguration::parse("<domainmanagerconfiguration id=\"DCE:1\"/>", "DomainManager.dmd.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_write_profiles() {
+        //the written descriptors parse back to the same ones
+        let provides = "<providesport>\n        <providesidentifier>audio_in</providesidentifier>\n        <componentinstantiationref refid=\"demod_1\"/>\n      </providesport>";
+        let sads = [
+            SAD.to_string(),
+            SAD.replace("<usagename>demod</usagename>", "<usagename>demod &amp; \"co\" &lt;1&gt;</usagename>"),
+            SAD.replace("<componentplacement>", "<hostcollocation id=\"rf\" name=\"rf_chain\"><componentplacement>").replace("</componentplacement>", "</componentplacement></hostcollocation>"),
+            SAD.replace(provides, r#"<findby><namingservice name="fm_1/sink_1"/></findby>"#),
+            SAD.replace(provides, r#"<findby><domainfinder type="servicename"/></findby>"#),
+            SAD.replace(provides, r#"<componentsupportedinterface><supportedidentifier>IDL:CF/Resource:1.0</supportedidentifier><componentinstantiationref refid="demod_1"/></componentsupportedinterface>"#),
+            SAD.replace(
+                "</softwareassembly>",
+                r#"<externalports><port><usesidentifier>audio_out</usesidentifier><componentinstantiationref refid="demod_1"/></port>
+                <port externalname="input"><providesidentifier>audio_in</providesidentifier><componentinstantiationref refid="demod_1"/></port></externalports>
+                <externalproperties><property comprefid="demod_1" propid="frequency"/><property comprefid="demod_1" propid="mode" externalpropid="demod_mode"/></externalproperties>
+                <usesdevicedependencies><usesdevice id="rf" type="usesdevice"><propertyref refid="device_kind" value="TUNER"/></usesdevice></usesdevicedependencies></softwareassembly>"#,
+            ),
+            SAD.replace(r#"<componentinstantiation id="demod_1">"#, r#"<componentinstantiation id="demod_1" startorder="2"><componentproperties><simpleref refid="mode" value="mono"/></componentproperties>"#)
+                .replace("<usagename>demod</usagename>", ""),
+        ];
+        for xml in sads {
+            let sad = SoftwareAssembly::parse(&xml, "fm.sad.xml").unwrap();
+            let written = sad.to_xml();
+            assert_eq!(SoftwareAssembly::parse(&written, "fm.sad.xml").unwrap(), sad, "{written}");
+        }
+
+        let spd = SoftPkg::parse(SPD, "demod.spd.xml").unwrap();
+        assert_eq!(SoftPkg::parse(&spd.to_xml(), "demod.spd.xml").unwrap(), spd);
+        let scd = SoftwareComponent::parse(SCD, "demod.scd.xml").unwrap();
+        assert_eq!(SoftwareComponent::parse(&scd.to_xml(), "demod.scd.xml").unwrap(), scd);
+        let prf = PropertiesDescriptor::parse(PRF, "demod.prf.xml").unwrap();
+        let written = prf.to_xml();
+        assert_eq!(PropertiesDescriptor::parse(&written, "demod.prf.xml").unwrap(), prf, "{written}");
+
+        //the defaults are left implicit
+        assert!(!written.contains("readwrite") && written.matches("kindtype=\"configure\"").count() == 1, "{written}");
+        assert!(written.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<properties>\n  <description>demod properties</description>\n  <simple id=\"frequency\" name=\"frequency\" type=\"double\">\n    <value>101.1</value>\n"), "{written}");
+    }
+
+    #[test]
+    fn test_profile_cache() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+        fs.write("/demod.spd.xml", SPD.as_bytes()).unwrap();
+        fs.write("/demod.prf.xml", PRF.as_bytes()).unwrap();
+        fs.write("/demod.scd.xml", SCD.as_bytes()).unwrap();
+
+        //the descriptor is parsed once while the document is unchanged
+        let cache = ProfileCache::new(2);
+        let spd = cache.load::<SoftPkg>(&fs, "/demod.spd.xml").unwrap();
+        assert!(Arc::ptr_eq(&spd, &cache.load::<SoftPkg>(&fs, "/demod.spd.xml").unwrap()));
+        assert_eq!(cache.len(), 1);
+        fs.write("/demod.spd.xml", SPD.replace("cpp/demod", "cpp/demod2").as_bytes()).unwrap();
+        let modified = cache.load::<SoftPkg>(&fs, "/demod.spd.xml").unwrap();
+        assert_eq!(modified.implementations[0].code.as_ref().unwrap().entry_point.as_deref(), Some("cpp/demod2"));
+        assert!(!Arc::ptr_eq(&spd, &modified));
+
+        //the least recently used descriptor is evicted
+        let prf = cache.load::<PropertiesDescriptor>(&fs, "/demod.prf.xml").unwrap();
+        cache.load::<SoftPkg>(&fs, "/demod.spd.xml").unwrap();
+        cache.load::<SoftwareComponent>(&fs, "/demod.scd.xml").unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&modified, &cache.load::<SoftPkg>(&fs, "/demod.spd.xml").unwrap()));
+        assert!(!Arc::ptr_eq(&prf, &cache.load::<PropertiesDescriptor>(&fs, "/demod.prf.xml").unwrap()));
+
+        match cache.load::<SoftPkg>(&fs, "/demod.prf.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match cache.load::<SoftPkg>(&fs, "/missing.spd.xml") {
+            Err(ProfileError::ProfileNotFound { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        assert!(cache.invalidate("/demod.prf.xml"));
+        assert!(!cache.invalidate("/demod.prf.xml"));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(feature = "schema-validation")]
+    #[test]
+    fn test_schema_validation() {
+        use scars::cf::profile::schema::Schema;
+
+        for root in ["softpkg", "properties", "softwareassembly", "deviceconfiguration", "domainmanagerconfiguration", "softwarecomponent"] {
+            assert!(Schema::bundled(root).is_some(), "{root}");
+        }
+        assert!(Schema::bundled("devicepkg").is_none());
+
+        //the DOCTYPE and the test definitions are accepted
+        let xml = PRF.replace("<properties>", "<!DOCTYPE properties PUBLIC \"-//JTRS//DTD SCA V2.2.2 PRF//EN\" \"properties.dtd\">\n<properties>").replace(
+            "</properties>",
+            r#"<test id="1"><description>loopback</description><resultvalue><simple id="r" type="long"/></resultvalue></test></properties>"#,
+        );
+        assert_eq!(PropertiesDescriptor::parse(&xml, "demod.prf.xml").unwrap().properties.len(), 6);
+
+        //every problem is reported with its line
+        let invalid = [
+            (SAD.replace(r#"<componentfileref refid="demod_file"/>"#, "<componentfileref/>"), "<componentfileref> at line 10 misses the 'refid' attribute"),
+            (SAD.replace("<componentfile id", "<componentfile kind=\"spd\" id"), "<componentfile> at line 4 has the undeclared 'kind' attribute"),
+            (SAD.replace("<usagename>demod</usagename>", "<usagename>demod</usagename><usagename>fm</usagename>"), "unexpected <usagename> at line 12 in <componentinstantiation>"),
+            (SAD.replace("<assemblycontroller>", "<externalports/><assemblycontroller>"), "unexpected <assemblycontroller> at line 16 in <softwareassembly>"),
+            (SAD.replace("<usesidentifier>audio_out</usesidentifier>", ""), "unexpected <componentinstantiationref> at line 23 in <usesport>, expected (usesidentifier, (componentinstantiationref | devicethatloadedthiscomponentref | deviceusedbythiscomponentref | findby))"),
+            (SAD.replace("<assemblycontroller>", "<assemblycontroller/><assemblycontroller>"), "<assemblycontroller> at line 16 is incomplete, expected (componentinstantiationref)"),
+            (SAD.replace("<partitioning>", "<partitioning>rf"), "<partitioning> at line 8 does not allow text"),
+            (SAD.replace("<connections>", "<connections><link/>"), "<link> at line 19 is not declared"),
+        ];
+        for (xml, expected) in invalid {
+            match SoftwareAssembly::parse(&xml, "fm.sad.xml") {
+                Err(ProfileError::InvalidProfile { message, .. }) => assert!(message.contains(expected), "{message}"),
+                r => panic!("{xml}: {:?}", r),
+            }
+        }
+
+        match PropertiesDescriptor::parse(&PRF.replace(r#"mode="readonly""#, r#"mode="read""#), "demod.prf.xml") {
+            Err(ProfileError::InvalidProfile { message, .. }) => assert!(message.contains("<simple> at line 18: 'read' is not a value of the 'mode' attribute"), "{message}"),
+            r => panic!("{:?}", r),
+        }
+
+        //the content models are matched with backtracking
+        let dtd = r#"<!-- test -->
+<!ELEMENT a ((b, c) | (b, d))+>
+<!ATTLIST a kind (x | y) "x" version CDATA #FIXED "1.0">
+<!ELEMENT b EMPTY>
+<!ELEMENT c (#PCDATA)>
+<!ELEMENT d (#PCDATA | b)*>"#;
+        let schema = Schema::parse(dtd, "a.dtd").unwrap();
+        let validate = |xml: &str| schema.validate(&roxmltree::Document::parse(xml).unwrap(), "a.xml");
+        assert!(validate("<a><b/><d>x<b/></d><b/><c>y</c></a>").is_ok());
+        assert!(validate("<a kind=\"z\"><b/><c/></a>").is_err());
+        assert!(validate("<a version=\"2.0\"><b/><c/></a>").is_err());
+        assert!(validate("<a><b/></a>").is_err());
+        assert!(validate("<a><b>x</b><c/></a>").is_err());
+        assert!(Schema::parse("<!ELEMENT a (b, c | d)>", "a.dtd").is_err());
+    }
+
+    #[test]
+    fn test_lint() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+        for directory in ["/waveforms", "/waveforms/fm", "/components", "/components/demod"] {
+            fs.mkdir(directory).unwrap();
+        }
+        fs.write("/waveforms/fm/fm.sad.xml", SAD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.spd.xml", SPD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.prf.xml", PRF.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.scd.xml", SCD.as_bytes()).unwrap();
+        assert_eq!(lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap(), vec![]);
+
+        let lint_sad = |xml: &str| {
+            fs.write("/waveforms/fm/fm.sad.xml", xml.as_bytes()).unwrap();
+            lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap().into_iter().map(|i| i.message).collect::<Vec<_>>()
+        };
+        assert_eq!(lint_sad(&SAD.replace("<providesidentifier>audio_in", "<providesidentifier>audio_x")), vec!["connection 'loopback': 'demod_1' has no port 'audio_x' in /components/demod/demod.spd.xml"]);
+        assert_eq!(lint_sad(&SAD.replace("<providesidentifier>audio_in", "<providesidentifier>audio_out")), vec!["connection 'loopback': 'audio_out' of 'demod_1' is not a provides port"]);
+        let supported = |repid: &str| SAD.replace("<providesport>\n        <providesidentifier>audio_in</providesidentifier>\n        <componentinstantiationref refid=\"demod_1\"/>\n      </providesport>", &format!("<componentsupportedinterface><supportedidentifier>{repid}</supportedidentifier><componentinstantiationref refid=\"demod_1\"/></componentsupportedinterface>"));
+        assert_eq!(lint_sad(&supported("IDL:CF/Resource:1.0")), vec!["connection 'loopback': 'IDL:CF/Resource:1.0' is not a 'IDL:BULKIO/dataFloat:1.0'"]);
+        assert_eq!(lint_sad(&supported("IDL:CF/Device:1.0")), vec!["connection 'loopback': /components/demod/demod.spd.xml does not support 'IDL:CF/Device:1.0'"]);
+        let xml = SAD
+            .replace("<componentinstantiation id=\"demod_1\">", "<componentinstantiation id=\"demod_1\" startorder=\"1\">")
+            .replace("</usagename>", "</usagename><componentproperties><simpleref refid=\"volume\" value=\"1\"/></componentproperties>")
+            .replace("</componentplacement>", "<componentinstantiation id=\"demod_2\" startorder=\"1\"/></componentplacement>")
+            .replace("<assemblycontroller>\n    <componentinstantiationref refid=\"demod_1\"/>\n  </assemblycontroller>", "");
+        assert_eq!(lint_sad(&xml), vec!["no assembly controller", "'demod_2' and 'demod_1' have the same start order 1", "'demod_1' has no property 'volume' to override"]);
+
+        //the interfaces of the ports shall be compatible
+        fs.write("/waveforms/fm/fm.sad.xml", SAD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.scd.xml", SCD.replace("repid=\"IDL:BULKIO/dataFloat:1.0\" usesname", "repid=\"IDL:BULKIO/dataShort:1.0\" usesname").as_bytes()).unwrap();
+        assert_eq!(lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap(), vec![Issue { file_name: "/waveforms/fm/fm.sad.xml".to_string(), message: "connection 'loopback': 'IDL:BULKIO/dataFloat:1.0' is not a 'IDL:BULKIO/dataShort:1.0'".to_string() }]);
+        fs.write("/components/demod/demod.scd.xml", SCD.replace("repid=\"IDL:BULKIO/dataFloat:1.0\" usesname", "repid=\"IDL:omg.org/CORBA/Object:1.0\" usesname").as_bytes()).unwrap();
+        assert_eq!(lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap(), vec![]);
+        assert_eq!(lint_sad(&supported("IDL:CF/LifeCycle:1.0")), Vec::<String>::new());
+
+        //the descriptors shall resolve
+        fs.remove("/components/demod/demod.prf.xml").unwrap();
+        let issues = lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap();
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert_eq!(issues[0].file_name, "/components/demod/demod.spd.xml");
+        assert!(issues[0].message.starts_with("ProfileNotFound: file: '/components/demod/demod.prf.xml'"), "{}", issues[0]);
+        fs.remove("/components/demod/demod.spd.xml").unwrap();
+        assert_eq!(lint::lint(&fs, "/waveforms/fm/fm.sad.xml").unwrap().len(), 1);
+        match lint::lint(&fs, "/waveforms/fm/other.sad.xml") {
+            Err(ProfileError::ProfileNotFound { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    const DCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<deviceconfiguration id="DCE:node" name="node">
+  <componentfiles>
+    <componentfile id="gpp_file" type="SPD">
+      <localfile name="../../devices/gpp/gpp.spd.xml"/>
+    </componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="gpp_file"/>
+      <componentinstantiation id="gpp_1">
+        <componentproperties>
+          <simpleref refid="processor_cores" value="2"/>
+        </componentproperties>
+      </componentinstantiation>
+    </componentplacement>
+    <componentplacement>
+      <componentfileref refid="gpp_file"/>
+      <componentinstantiation id="gpp_2"/>
+    </componentplacement>
+  </partitioning>
+</deviceconfiguration>
+"#;
+
+    const GPP_PRF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<properties>
+  <simple id="processor_name" type="string" mode="readonly">
+    <value>x86_64</value>
+    <kind kindtype="allocation"/>
+    <action type="eq"/>
+  </simple>
+  <simple id="os_name" type="string" mode="readonly">
+    <value>Linux</value>
+    <kind kindtype="allocation"/>
+    <action type="eq"/>
+  </simple>
+  <simple id="os_version" type="string" mode="readonly">
+    <value>6</value>
+    <kind kindtype="allocation"/>
+    <action type="eq"/>
+  </simple>
+  <simple id="processor_cores" type="ulong" mode="readonly">
+    <value>4</value>
+    <kind kindtype="allocation"/>
+    <action type="external"/>
+  </simple>
+</properties>
+"#;
+
+    #[test]
+    fn test_plan() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+        for directory in ["/waveforms", "/waveforms/fm", "/components", "/components/demod", "/nodes", "/nodes/node", "/devices", "/devices/gpp"] {
+            fs.mkdir(directory).unwrap();
+        }
+        fs.write("/components/demod/demod.spd.xml", SPD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.prf.xml", PRF.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.scd.xml", SCD.as_bytes()).unwrap();
+        fs.write("/nodes/node/node.dcd.xml", DCD.as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.spd.xml", SPD.replace("demod", "gpp").replace("<dependency type=\"allocation\">\n      <propertyref refid=\"processor_cores\" value=\"2\"/>\n    </dependency>\n", "").as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.prf.xml", GPP_PRF.as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.scd.xml", SCD.replace("resource", "executabledevice").as_bytes()).unwrap();
+
+        //each demod takes 2 cores, gpp_1 has 2 and gpp_2 has 4
+        let plan_demods = |count: usize, collocated: &[usize]| {
+            let instantiations: String = (1..=count).map(|i| format!("<componentplacement><componentfileref refid=\"demod_file\"/><componentinstantiation id=\"demod_{i}\"/></componentplacement>")).collect();
+            let collocation: String = match collocated.is_empty() {
+                true => String::new(),
+                false => format!("<hostcollocation id=\"pair\">{}</hostcollocation>", collocated.iter().map(|i| format!("<componentplacement><componentfileref refid=\"demod_file\"/><componentinstantiation id=\"demod_{i}\"/></componentplacement>")).collect::<String>()),
+            };
+            let sad = format!("<softwareassembly id=\"DCE:fm\" name=\"fm\"><componentfiles><componentfile id=\"demod_file\" type=\"SPD\"><localfile name=\"../../components/demod/demod.spd.xml\"/></componentfile></componentfiles><partitioning>{instantiations}{collocation}</partitioning></softwareassembly>");
+            fs.write("/waveforms/fm/fm.sad.xml", sad.as_bytes()).unwrap();
+            plan::plan(&fs, "/waveforms/fm/fm.sad.xml", &["/nodes/node/node.dcd.xml"]).unwrap()
+        };
+        let plan = plan_demods(3, &[]);
+        assert!(plan.is_feasible(), "{plan:?}");
+        assert_eq!(plan.assignments.iter().map(|a| (a.component_id.as_str(), a.device_id.as_str())).collect::<Vec<_>>(), vec![("demod_1", "gpp_1"), ("demod_2", "gpp_2"), ("demod_3", "gpp_2")]);
+        assert_eq!(plan.assignments[0].implementation_id.as_deref(), Some("cpp"));
+
+        //the collocated components need the 4 cores of gpp_2, demod_1 leaving them
+        let plan = plan_demods(1, &[2, 3]);
+        assert!(plan.is_feasible(), "{plan:?}");
+        assert_eq!(plan.device_of("demod_1"), Some("gpp_1"));
+        assert_eq!(plan.device_of("demod_3"), Some("gpp_2"));
+        fs.write("/nodes/node/node.dcd.xml", DCD.replace("value=\"2\"", "value=\"4\"").replace("<componentinstantiation id=\"gpp_2\"/>", "<componentinstantiation id=\"gpp_2\"><componentproperties><simpleref refid=\"processor_cores\" value=\"2\"/></componentproperties></componentinstantiation>").as_bytes()).unwrap();
+        let plan = plan_demods(1, &[2, 3]);
+        assert!(plan.is_feasible(), "{plan:?}");
+        assert_eq!(plan.device_of("demod_1"), Some("gpp_2"));
+        assert_eq!(plan.device_of("demod_2"), Some("gpp_1"));
+        fs.write("/nodes/node/node.dcd.xml", DCD.as_bytes()).unwrap();
+
+        //the shortfalls are reported
+        let plan = plan_demods(4, &[]);
+        assert!(!plan.is_feasible());
+        assert_eq!(plan.assignments.len(), 3);
+        assert_eq!(plan.rejections.len(), 1);
+        assert_eq!(plan.rejections[0].component_id, "demod_4");
+        assert_eq!(plan.rejections[0].reasons, vec!["'processor_cores' of 'gpp_1' is 0, 2 required", "'processor_cores' of 'gpp_2' is 0, 2 required"]);
+        assert_eq!(plan.shortfalls.len(), 2);
+        assert_eq!((plan.shortfalls[1].device_id.as_str(), plan.shortfalls[1].required.clone(), plan.shortfalls[1].available.clone()), ("gpp_2", AnyValue::ULong(2), AnyValue::ULong(0)));
+
+        //as are the mismatching devices
+        fs.write("/devices/gpp/gpp.prf.xml", GPP_PRF.replace("<value>x86_64</value>", "<value>arm</value>").as_bytes()).unwrap();
+        let plan = plan_demods(1, &[]);
+        assert_eq!(plan.rejections[0].reasons, vec!["'processor_name' of 'gpp_1' is arm, EQ x86_64 required", "'processor_name' of 'gpp_2' is arm, EQ x86_64 required"]);
+        assert!(plan.shortfalls.is_empty());
+        fs.write("/devices/gpp/gpp.scd.xml", SCD.as_bytes()).unwrap();
+        let plan = plan_demods(1, &[]);
+        assert_eq!(plan.rejections[0].reasons, vec!["'gpp_1' does not execute components", "'gpp_2' does not execute components"]);
+        match plan::plan(&fs, "/waveforms/fm/fm.sad.xml", &["/nodes/node/other.dcd.xml"]) {
+            Err(ProfileError::ProfileNotFound { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+        for directory in ["/waveforms", "/waveforms/fm", "/components", "/components/demod", "/nodes", "/nodes/node", "/devices", "/devices/gpp"] {
+            fs.mkdir(directory).unwrap();
+        }
+        fs.write("/waveforms/fm/fm.sad.xml", SAD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.spd.xml", SPD.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.prf.xml", PRF.as_bytes()).unwrap();
+        fs.write("/components/demod/demod.scd.xml", SCD.as_bytes()).unwrap();
+        fs.write("/nodes/node/node.dcd.xml", DCD.as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.spd.xml", SPD.replace("demod", "gpp").replace("<dependency type=\"allocation\">\n      <propertyref refid=\"processor_cores\" value=\"2\"/>\n    </dependency>\n", "").as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.prf.xml", GPP_PRF.as_bytes()).unwrap();
+        fs.write("/devices/gpp/gpp.scd.xml", SCD.replace("resource", "executabledevice").as_bytes()).unwrap();
+
+        let report = validate::validate(&fs, "/waveforms/fm/fm.sad.xml", &[]).unwrap();
+        assert!(report.is_valid(), "{report:?}");
+        assert_eq!(report.plan, None);
+        assert_eq!(report.to_text(), "/waveforms/fm/fm.sad.xml: valid\n");
+
+        let report = validate::validate(&fs, "/waveforms/fm/fm.sad.xml", &["/nodes/node/node.dcd.xml"]).unwrap();
+        assert!(report.is_valid(), "{report:?}");
+        assert!(report.to_text().contains("deploy: 'demod_1' on 'gpp_1' (cpp)"), "{}", report.to_text());
+        let json = report.to_json();
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["deployment"]["assignments"][0]["device_id"], "gpp_1");
+
+        //the devices lacking capacity are reported
+        fs.write("/nodes/node/node.dcd.xml", DCD.replace("value=\"2\"", "value=\"1\"").replace("<componentplacement>\n      <componentfileref refid=\"gpp_file\"/>\n      <componentinstantiation id=\"gpp_2\"/>\n    </componentplacement>", "").as_bytes()).unwrap();
+        let report = validate::validate(&fs, "/waveforms/fm/fm.sad.xml", &["/nodes/node/node.dcd.xml"]).unwrap();
+        assert!(!report.is_valid());
+        let json = report.to_json();
+        assert_eq!(json["deployment"]["feasible"], false);
+        assert_eq!(json["deployment"]["shortfalls"][0]["required"], "2");
+        assert!(report.to_text().contains("rejected: 'demod_1'"), "{}", report.to_text());
+
+        //as are the unreadable descriptors
+        let report = validate::validate(&fs, "/waveforms/fm/fm.sad.xml", &["/nodes/node/other.dcd.xml"]).unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].file_name, "/nodes/node/other.dcd.xml");
+        assert_eq!(report.to_json()["issues"][0]["file_name"], "/nodes/node/other.dcd.xml");
+        match validate::validate(&fs, "/waveforms/fm/other.sad.xml", &[]) {
+            Err(ProfileError::ProfileNotFound { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_resolve_file_name() {
+        let sad = "/waveforms/fm/fm.sad.xml";
+        assert_eq!(
+            resolve_file_name(sad, "../../components/demod/demod.spd.xml"),
+            "/components/demod/demod.spd.xml"
+        );
+        assert_eq!(
+            resolve_file_name(sad, "./fm.prf.xml"),
+            "/waveforms/fm/fm.prf.xml"
+        );
+        assert_eq!(
+            resolve_file_name(sad, "/components/x.spd.xml"),
+            "/components/x.spd.xml"
+        );
+        assert_eq!(resolve_file_name(sad, "../../../x.spd.xml"), "/x.spd.xml");
+    }
+
+    #[test]
+    fn test_diff() {
+        let root = tempfile::tempdir().unwrap();
+        let fs = FileSystem::new(root.path());
+        let sad = SAD
+            .replace("</usagename>", "</usagename>\n        <componentproperties><simpleref refid=\"frequency\" value=\"95.0\"/></componentproperties>")
+            .replace("</componentplacement>", "<componentinstantiation id=\"demod_2\"/></componentplacement>")
+            .replace("<connectinterface id=\"loopback\">", "<connectinterface id=\"monitor\">");
+        let spd = SPD.replace("</softpkg>", "<implementation id=\"arm\"><code type=\"Executable\"><localfile name=\"arm\"/></code><processor name=\"armv7\"/></implementation>\n</softpkg>");
+        let prf = PRF
+            .replace("<value>stereo</value>", "<value>mono</value>")
+            .replace("<simplesequence id=\"taps\" type=\"short\">\n    <values><value>1</value><value>-2</value></values>\n    <kind kindtype=\"property\"/>\n  </simplesequence>\n", "");
+        assert!(!prf.contains("taps"));
+        for (version, sad, spd, prf) in [("v1", SAD, SPD, PRF), ("v2", sad.as_str(), spd.as_str(), prf.as_str())] {
+            for directory in ["", "/waveforms", "/waveforms/fm", "/components", "/components/demod"] {
+                fs.mkdir(&format!("/{version}{directory}")).unwrap();
+            }
+            fs.write(&format!("/{version}/waveforms/fm/fm.sad.xml"), sad.as_bytes()).unwrap();
+            fs.write(&format!("/{version}/components/demod/demod.spd.xml"), spd.as_bytes()).unwrap();
+            fs.write(&format!("/{version}/components/demod/demod.prf.xml"), prf.as_bytes()).unwrap();
+            fs.write(&format!("/{version}/components/demod/demod.scd.xml"), SCD.as_bytes()).unwrap();
+        }
+        let property_changed = |component_id: Option<&str>, property_id: &str, old: &str, new: &str| Change::PropertyChanged {
+            component_id: component_id.map(str::to_string),
+            property_id: property_id.to_string(),
+            old: Some(AnyValue::String(old.to_string())),
+            new: Some(AnyValue::String(new.to_string())),
+        };
+        let taps_removed = |component_id: Option<&str>| Change::PropertyRemoved { component_id: component_id.map(str::to_string), property_id: "taps".to_string() };
+
+        //the property values of an instantiation are the PRF defaults overridden by the SAD
+        let changes = diff::diff(&fs, "/v1/waveforms/fm/fm.sad.xml", "/v2/waveforms/fm/fm.sad.xml").unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                Change::ComponentAdded { id: "demod_2".to_string() },
+                taps_removed(Some("demod_1")),
+                Change::PropertyChanged {
+                    component_id: Some("demod_1".to_string()),
+                    property_id: "frequency".to_string(),
+                    old: Some(AnyValue::Double(101.1)),
+                    new: Some(AnyValue::Double(95.0)),
+                },
+                property_changed(Some("demod_1"), "mode", "stereo", "mono"),
+                Change::ConnectionRemoved { id: "loopback".to_string() },
+                Change::ConnectionAdded { id: "monitor".to_string() },
+            ]
+        );
+        assert_eq!(changes[2].to_string(), "property 'frequency' of 'demod_1' changed: 101.1 -> 95");
+        assert_eq!(diff::diff(&fs, "/v2/waveforms/fm/fm.sad.xml", "/v2/waveforms/fm/fm.sad.xml").unwrap(), vec![]);
+
+        let changes = diff::diff(&fs, "/v1/components/demod/demod.spd.xml", "/v2/components/demod/demod.spd.xml").unwrap();
+        assert_eq!(changes, vec![Change::ImplementationAdded { id: "arm".to_string() }, taps_removed(None), property_changed(None, "mode", "stereo", "mono")]);
+        assert_eq!(changes[0].to_string(), "implementation 'arm' added");
+
+        let changes = diff::diff(&fs, "/v2/components/demod/demod.prf.xml", "/v1/components/demod/demod.prf.xml").unwrap();
+        let taps = Some(AnyValue::Sequence(vec![AnyValue::Short(1), AnyValue::Short(-2)]));
+        assert_eq!(changes, vec![property_changed(None, "mode", "mono", "stereo"), Change::PropertyAdded { component_id: None, property_id: "taps".to_string(), value: taps }]);
+
+        //only the versions of a same kind of descriptor are compared
+        match diff::diff(&fs, "/v1/waveforms/fm/fm.sad.xml", "/v2/components/demod/demod.spd.xml") {
+            Err(ProfileError::InvalidProfile { file_name, .. }) => assert_eq!(file_name, "/v2/components/demod/demod.spd.xml"),
+            r => panic!("{:?}", r),
+        }
+        match diff::diff(&fs, "/v1/components/demod/demod.scd.xml", "/v2/components/demod/demod.scd.xml") {
+            Err(ProfileError::InvalidProfile { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
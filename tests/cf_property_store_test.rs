@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use scars::cf::common_types::{AnyValue, DataType, Properties};
+    use scars::cf::device::{Device, DeviceTrait};
+    use scars::cf::property_store::PropertyStore;
+    use scars::cf::resource::{Resource, ResourceRef, ResourceTrait};
+
+    #[test]
+    fn test_property_store() {
+        let mut store = PropertyStore::new(vec![DataType::new("frequency", AnyValue::Double(100e6))]);
+        let reader = store.reader();
+        let snapshot = reader.load();
+
+        //the readers see the update at once, the snapshots loaded before being kept
+        let previous = store.update(|properties| std::mem::replace(&mut properties[0].value, AnyValue::Double(101e6)));
+        assert_eq!(previous, AnyValue::Double(100e6));
+        assert_eq!(reader.get("frequency"), Some(AnyValue::Double(101e6)));
+        assert_eq!(snapshot[0].value, AnyValue::Double(100e6));
+        assert_eq!(reader.get("gain"), None);
+
+        //a clone is independent of the store
+        let mut clone = store.clone();
+        clone.store(Properties::new());
+        assert_eq!(reader.load().len(), 1);
+        assert!(clone.load().is_empty());
+
+        //the readers never see an update half done
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let reader = store.reader();
+                thread::spawn(move || (0..10_000).all(|_| {
+                    let snapshot = reader.load();
+                    snapshot.len() == 2 || snapshot[0].value == AnyValue::Double(101e6)
+                }))
+            })
+            .collect();
+        for i in 0..1000 {
+            store.update(|properties| {
+                properties.truncate(1);
+                properties[0].value = AnyValue::Double(i as f64);
+                properties.push(DataType::new("gain", AnyValue::Double(10.0)));
+            });
+        }
+        assert!(readers.into_iter().all(|r| r.join().unwrap()));
+    }
+
+    #[test]
+    fn test_component_snapshots() {
+        //the properties of a resource are read without locking it
+        let mut resource = Resource::new("FM_1").with_property("frequency", AnyValue::Double(100e6));
+        let reader = resource.property_reader().unwrap();
+        resource.configure(&vec![DataType::new("frequency", AnyValue::Double(102e6))]).unwrap();
+        assert_eq!(reader.get("frequency"), Some(AnyValue::Double(102e6)));
+        assert_eq!(resource.query(&vec![DataType::new("frequency", AnyValue::Boolean(false))]).unwrap(), reader.load().to_vec());
+        assert!(resource.query(&vec![DataType::new("gain", AnyValue::Boolean(false))]).is_err());
+        assert_eq!(resource.query(&Properties::new()).unwrap().len(), 3);
+
+        //and the allocation properties of a device, on each allocation
+        let mut device = Device::new("DCE:tuner", "tuner").with_allocation_property("model", AnyValue::String("RX".to_string())).with_capacity("channels", AnyValue::UShort(2));
+        let reader = device.allocation_property_reader().unwrap();
+        assert!(device.allocate_capacity(&vec![DataType::new("channels", AnyValue::UShort(1))]).unwrap());
+        assert_eq!(reader.get("channels"), Some(AnyValue::UShort(1)));
+        assert_eq!(reader.load().to_vec(), device.allocation_properties());
+    }
+
+    #[test]
+    fn test_query_locked_component() {
+        //the reader answers while the component is busy, the logging properties included
+        let resource: ResourceRef = Arc::new(Mutex::new(Resource::new("FM_1").with_property("frequency", AnyValue::Double(100e6))));
+        let reader = resource.lock().unwrap().property_reader().unwrap();
+        let _busy = resource.lock().unwrap();
+        assert_eq!(reader.query(&vec![DataType::new("frequency", AnyValue::Boolean(false))]).unwrap(), vec![DataType::new("frequency", AnyValue::Double(100e6))]);
+        assert_eq!(reader.query(&vec![DataType::new("LOG_LEVEL", AnyValue::Boolean(false))]).unwrap().len(), 1);
+        assert_eq!(reader.query(&Properties::new()).unwrap().len(), 3);
+        assert!(reader.query(&vec![DataType::new("gain", AnyValue::Boolean(false))]).is_err());
+    }
+}
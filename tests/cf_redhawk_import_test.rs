@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use scars::cf::profile::prf::PropertiesDescriptor;
+    use scars::cf::profile::sad::SoftwareAssembly;
+    use scars::cf::profile::scd::SoftwareComponent;
+    use scars::cf::profile::spd::SoftPkg;
+    use scars::cf::profile::resolve_file_name;
+    use scars::cf::redhawk_import::{crate_name, ImportError, Importer};
+
+    const SIGGEN_SPD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE softpkg PUBLIC "-//JTRS//DTD SCA V2.2.2 SPD//EN" "softpkg.dtd">
+<softpkg id="DCE:2a9d8d3a-1d53-4d8a-9d4e-7f0a1e5bd9c1" name="rh.SigGen" type="2.0.0" version="2.0.2">
+  <title></title>
+  <author><name>REDHAWK</name></author>
+  <description>Signal generator</description>
+  <propertyfile type="PRF"><localfile name="SigGen.prf.xml"/></propertyfile>
+  <descriptor><localfile name="SigGen.scd.xml"/></descriptor>
+  <implementation id="cpp">
+    <description>The C++ implementation</description>
+    <code type="SharedLibrary"><localfile name="cpp/SigGen.so"/><entrypoint>cpp/SigGen.so</entrypoint></code>
+    <compiler name="/usr/bin/gcc" version="4.8.5"/>
+    <programminglanguage name="C++"/>
+    <humanlanguage name="EN"/>
+    <os name="Linux"/>
+    <processor name="x86_64"/>
+  </implementation>
+  <implementation id="python">
+    <code type="Executable"><localfile name="python"/><entrypoint>python/SigGen.py</entrypoint></code>
+    <programminglanguage name="Python"/>
+    <os name="Linux"/>
+  </implementation>
+</softpkg>"#;
+
+    const SIGGEN_SCD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE softwarecomponent PUBLIC "-//JTRS//DTD SCA V2.2.2 SCD//EN" "softwarecomponent.dtd">
+<softwarecomponent>
+  <corbaversion>2.2</corbaversion>
+  <componentrepid repid="IDL:CF/Resource:1.0"/>
+  <componenttype>resource</componenttype>
+  <componentfeatures>
+    <supportsinterface repid="IDL:CF/Resource:1.0" supportsname="Resource"/>
+    <ports>
+      <uses repid="IDL:BULKIO/dataFloat:1.0" usesname="dataFloat_out"><porttype type="data"/></uses>
+      <provides repid="IDL:ExtendedEvent/MessageEvent:1.0" providesname="message_in"><porttype type="control"/></provides>
+    </ports>
+  </componentfeatures>
+  <interfaces>
+    <interface name="Resource" repid="IDL:CF/Resource:1.0"/>
+  </interfaces>
+</softwarecomponent>"#;
+
+    const SIGGEN_PRF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE properties PUBLIC "-//JTRS//DTD SCA V2.2.2 PRF//EN" "properties.dtd">
+<properties>
+  <simple id="frequency" mode="readwrite" type="double" complex="false">
+    <description>rate at which the periodic output waveforms repeat.</description>
+    <value>1000</value>
+    <units>Hz</units>
+    <range max="1e9" min="0"/>
+    <kind kindtype="property"/>
+    <action type="external"/>
+  </simple>
+  <simple id="shape" mode="readwrite" type="string">
+    <value>sine</value>
+    <enumerations>
+      <enumeration label="sine" value="sine"/>
+      <enumeration label="square" value="square"/>
+    </enumerations>
+    <kind kindtype="property"/>
+    <action type="external"/>
+  </simple>
+  <simple id="stream_id" mode="readwrite" type="string">
+    <value>SigGen Stream</value>
+    <kind kindtype="property"/>
+  </simple>
+</properties>"#;
+
+    const SIGGEN_WAVEFORM_SAD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE softwareassembly PUBLIC "-//JTRS//DTD SCA V2.2.2 SAD//EN" "softwareassembly.dtd">
+<softwareassembly id="DCE:5b1c7c54-36ab-4e2b-8c34-28bd4d9b5e0a" name="SigGenWave">
+  <componentfiles>
+    <componentfile id="SigGen_0c7e" type="SPD"><localfile name="/components/rh/SigGen/SigGen.spd.xml"/></componentfile>
+  </componentfiles>
+  <partitioning>
+    <componentplacement>
+      <componentfileref refid="SigGen_0c7e"/>
+      <componentinstantiation id="SigGen_1" startorder="0">
+        <usagename>SigGen_1</usagename>
+        <componentproperties><simpleref refid="frequency" value="2000"/></componentproperties>
+        <findcomponent><namingservice name="SigGen_1"/></findcomponent>
+      </componentinstantiation>
+    </componentplacement>
+    <componentplacement>
+      <componentfileref refid="SigGen_0c7e"/>
+      <componentinstantiation id="SigGen_2" startorder="1">
+        <usagename>SigGen_2</usagename>
+        <findcomponent><namingservice name="SigGen_2"/></findcomponent>
+      </componentinstantiation>
+    </componentplacement>
+  </partitioning>
+  <assemblycontroller><componentinstantiationref refid="SigGen_1"/></assemblycontroller>
+</softwareassembly>"#;
+
+    /// Writes the SigGen component project in a directory.
+    fn siggen_project(directory: &Path) {
+        std::fs::create_dir_all(directory.join("cpp")).unwrap();
+        std::fs::write(directory.join("SigGen.spd.xml"), SIGGEN_SPD).unwrap();
+        std::fs::write(directory.join("SigGen.scd.xml"), SIGGEN_SCD).unwrap();
+        std::fs::write(directory.join("SigGen.prf.xml"), SIGGEN_PRF).unwrap();
+        std::fs::write(directory.join(".project"), "<projectDescription/>").unwrap();
+        std::fs::write(directory.join("cpp/SigGen.cpp"), "").unwrap();
+    }
+
+    #[test]
+    fn test_crate_name() {
+        assert_eq!(crate_name("rh.SigGen"), "rh_sig_gen");
+        assert_eq!(crate_name("SigGenWave"), "sig_gen_wave");
+        assert_eq!(crate_name("rh.fastfilter"), "rh_fastfilter");
+        assert_eq!(crate_name("TuneFilterDecimate2"), "tune_filter_decimate2");
+        assert_eq!(crate_name("2tone"), "c_2tone");
+    }
+
+    #[test]
+    fn test_import_component() {
+        let (project, destination) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        siggen_project(project.path());
+
+        let report = Importer::new().import(project.path(), destination.path()).unwrap();
+        assert_eq!(report.components.len(), 1);
+        let component = &report.components[0];
+        assert_eq!(component.crate_name, "rh_sig_gen");
+        assert_eq!(component.softpkg_id, "DCE:2a9d8d3a-1d53-4d8a-9d4e-7f0a1e5bd9c1");
+        assert_eq!(component.directory, destination.path().join("rh_sig_gen"));
+        assert!(report.waveform.is_none());
+        assert_eq!(report.warnings.len(), 2);
+
+        //the descriptors keep the component, its implementations replaced by the Rust one
+        let read = |name: &str| std::fs::read_to_string(component.directory.join(name)).unwrap();
+        let spd = SoftPkg::parse(&read("rh_sig_gen.spd.xml"), "rh_sig_gen.spd.xml").unwrap();
+        assert_eq!(spd.name, "rh.SigGen");
+        assert_eq!(spd.property_file.as_deref(), Some("rh_sig_gen.prf.xml"));
+        assert_eq!(spd.descriptor.as_deref(), Some("rh_sig_gen.scd.xml"));
+        assert_eq!(spd.implementations.len(), 1);
+        assert_eq!(spd.implementations[0].id, "rust");
+        let scd = SoftwareComponent::parse(&read("rh_sig_gen.scd.xml"), "rh_sig_gen.scd.xml").unwrap();
+        assert_eq!(scd.ports, SoftwareComponent::parse(SIGGEN_SCD, "SigGen.scd.xml").unwrap().ports);
+        let prf = PropertiesDescriptor::parse(&read("rh_sig_gen.prf.xml"), "rh_sig_gen.prf.xml").unwrap();
+        assert_eq!(prf, PropertiesDescriptor::parse(SIGGEN_PRF, "SigGen.prf.xml").unwrap());
+
+        //the skeleton has the ports of the SCD and the properties of the PRF
+        let lib = read("src/lib.rs");
+        assert!(lib.contains(".with_uses_port(\"dataFloat_out\")"));
+        assert!(lib.contains(".with_provides_port(\"message_in\""));
+        assert!(lib.contains("impl ResourceTrait for RhSigGen"));
+        assert!(read("src/properties.rs").contains("pub fn shape(&self)"));
+        assert!(read("tests/rh_sig_gen_test.rs").contains("Sandbox::new()"));
+
+        //the crate is never overwritten
+        match Importer::new().import(project.path(), destination.path()) {
+            Err(ImportError::ScaffoldError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_import_waveform() {
+        let (sdrroot, project, destination) =
+            (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        siggen_project(&sdrroot.path().join("dom/components/rh/SigGen"));
+        std::fs::write(project.path().join("SigGenWave.sad.xml"), SIGGEN_WAVEFORM_SAD).unwrap();
+
+        let importer = Importer::new().with_sdrroot(sdrroot.path()).with_scars_path(Path::new("/opt/scars"));
+        let report = importer.import(project.path(), destination.path()).unwrap();
+
+        //the component placed twice is imported once
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(report.components[0].directory, destination.path().join("components/rh_sig_gen"));
+        let manifest = std::fs::read_to_string(report.components[0].directory.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("scars = { path = \"/opt/scars\" }"));
+        let waveform = report.waveform.unwrap();
+        assert_eq!(waveform, destination.path().join("waveforms/sig_gen_wave/sig_gen_wave.sad.xml"));
+
+        //the SAD refers to the imported SPD, relative to itself
+        let sad_file = waveform.display().to_string();
+        let sad = SoftwareAssembly::parse(&std::fs::read_to_string(&waveform).unwrap(), &sad_file).unwrap();
+        assert_eq!(sad.name, "SigGenWave");
+        assert_eq!(sad.component_files.len(), 1);
+        let spd_file = resolve_file_name(&sad_file, &sad.component_files[0].local_file);
+        assert_eq!(SoftPkg::parse(&std::fs::read_to_string(spd_file).unwrap(), "spd").unwrap().name, "rh.SigGen");
+        assert_eq!(sad.placements.len(), 2);
+        assert_eq!(sad.assembly_controller.as_deref(), Some("SigGen_1"));
+
+        //the components are looked up in the SDR root
+        let other = tempfile::tempdir().unwrap();
+        match Importer::new().with_sdrroot(other.path()).import(project.path(), other.path()) {
+            Err(ImportError::ProfileError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_unsupported_projects() {
+        let project = tempfile::tempdir().unwrap();
+        match Importer::new().import(project.path(), project.path()) {
+            Err(ImportError::NotAProject { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        siggen_project(project.path());
+        let device_scd = SIGGEN_SCD.replace("<componenttype>resource</componenttype>", "<componenttype>executabledevice</componenttype>");
+        std::fs::write(project.path().join("SigGen.scd.xml"), device_scd).unwrap();
+        let destination = tempfile::tempdir().unwrap();
+        match Importer::new().import(project.path(), destination.path()) {
+            Err(ImportError::UnsupportedComponent { component_type, .. }) => assert_eq!(component_type, "executabledevice"),
+            r => panic!("{:?}", r),
+        }
+
+        std::fs::write(project.path().join("Other.spd.xml"), SIGGEN_SPD).unwrap();
+        match Importer::new().import(project.path(), destination.path()) {
+            Err(ImportError::NotAProject { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+}
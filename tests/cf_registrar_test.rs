@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use tokio::net::TcpListener;
+    use tonic::Code;
+
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::registrar::{register_endpoint, resolve_endpoint};
+    use scars::cf::resource::Resource;
+    use scars::cf::retry::RetryPolicy;
+    use scars::cf::rpc::registrar::registrar_client::RegistrarClient;
+    use scars::cf::rpc::registrar::{ListRequest, RegisterRequest, UnregisterRequest};
+
+    #[test]
+    fn test_registry_endpoints() {
+        let registry = ComponentRegistry::new();
+        let registrations = registry.registrations().subscribe();
+        assert_eq!(registry.resolve_endpoint("fm_1/demod_1", Duration::from_millis(10)), None);
+
+        //a component registering again replaces its endpoint
+        registry.register_endpoint("fm_1/demod_1", "http://127.0.0.1:6001");
+        registry.register_endpoint("fm_1/demod_1", "http://127.0.0.1:6001");
+        registry.register_endpoint("fm_1/demod_1", "http://127.0.0.1:6002");
+        assert_eq!(registry.endpoint("fm_1/demod_1").as_deref(), Some("http://127.0.0.1:6002"));
+        assert_eq!(registrations.try_iter().collect::<Vec<_>>(), vec!["fm_1/demod_1".to_string(), "fm_1/demod_1".to_string()]);
+
+        //the resolution waits for the registration
+        let late = registry.clone();
+        let registering = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            late.register_endpoint("fm_1/source_1", "http://127.0.0.1:6003");
+        });
+        assert_eq!(registry.resolve_endpoint("fm_1/source_1", Duration::from_secs(5)).as_deref(), Some("http://127.0.0.1:6003"));
+        registering.join().unwrap();
+        assert_eq!(
+            registry.endpoints(),
+            vec![
+                ("fm_1/demod_1".to_string(), "http://127.0.0.1:6002".to_string()),
+                ("fm_1/source_1".to_string(), "http://127.0.0.1:6003".to_string()),
+            ]
+        );
+
+        //unregistering a component removes its endpoint
+        registry.register_component("fm_1/source_1", Arc::new(Mutex::new(Resource::new("source"))));
+        assert!(registry.unregister_component("fm_1/source_1").is_some());
+        assert_eq!(registry.endpoint("fm_1/source_1"), None);
+        assert_eq!(registry.unregister_endpoint("fm_1/demod_1").as_deref(), Some("http://127.0.0.1:6002"));
+        assert!(registry.endpoints().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_registrar_service() {
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let registrar = format!("http://{}", listener.local_addr().unwrap());
+        let domain_task = tokio::spawn(domain.clone().run(listener));
+
+        //the deployment resolves the components registering late
+        let resolving = {
+            let registrar = registrar.clone();
+            tokio::spawn(async move { resolve_endpoint(&registrar, "fm_1/demod_1", Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        register_endpoint(&registrar, "fm_1/demod_1", "http://127.0.0.1:6001", RetryPolicy::default()).await.unwrap();
+        assert_eq!(resolving.await.unwrap().unwrap(), "http://127.0.0.1:6001");
+        assert_eq!(domain.registry().endpoint("fm_1/demod_1").as_deref(), Some("http://127.0.0.1:6001"));
+
+        let unknown = resolve_endpoint(&registrar, "fm_1/source_1", Duration::from_millis(10)).await;
+        assert_eq!(unknown.unwrap_err().code(), tonic::Code::NotFound);
+
+        //the waits are bounded
+        let endless = resolve_endpoint(&registrar, "fm_1/source_1", Duration::from_millis(u64::MAX)).await;
+        assert_eq!(endless.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let mut client = RegistrarClient::connect(registrar.clone()).await.unwrap();
+        let empty = RegisterRequest { name: "fm_1/source_1".to_string(), endpoint: String::new() };
+        assert_eq!(client.register(empty).await.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let bindings = client.list(ListRequest {}).await.unwrap().into_inner().bindings;
+        assert_eq!(bindings.len(), 1);
+        assert_eq!((bindings[0].name.as_str(), bindings[0].endpoint.as_str()), ("fm_1/demod_1", "http://127.0.0.1:6001"));
+
+        client.unregister(UnregisterRequest { name: "fm_1/demod_1".to_string() }).await.unwrap();
+        let unregistered = client.unregister(UnregisterRequest { name: "fm_1/demod_1".to_string() }).await;
+        assert_eq!(unregistered.unwrap_err().code(), tonic::Code::NotFound);
+
+        domain.shutdown();
+        domain_task.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_hung_registrar() {
+        //the registrar accepts the connections but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let registrar = format!("http://{}", listener.local_addr().unwrap());
+        let retry_policy = RetryPolicy {
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(20),
+            max_attempts: 3,
+            attempt_timeout: Duration::from_millis(100),
+        };
+
+        let started = Instant::now();
+        let status = register_endpoint(&registrar, "fm_1/demod_1", "http://127.0.0.1:6001", retry_policy).await.unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(started.elapsed() >= Duration::from_millis(300) && started.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+}
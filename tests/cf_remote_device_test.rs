@@ -0,0 +1,109 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt, sync::{Arc, Mutex}, time::Duration};
+
+    use scars::cf::common_types::{AnyValue, DataType, ErrorNumberType};
+    use scars::cf::device::{AdminType, Device, DeviceTrait, OperationalType, UsageType};
+    use scars::cf::executable_device::{ExecutableDevice, ExecutableDeviceError, ExecutableDeviceTrait, ProcessStatus};
+    use scars::cf::loadable_device::{LoadType, LoadableDevice, LoadableDeviceError, LoadableDeviceTrait};
+    use scars::cf::remote_device::RemoteDevice;
+    use scars::cf::rpc::device::device_client::DeviceClient;
+    use scars::cf::rpc::device::{self as wire, LoadRequest};
+
+    use crate::common;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remote_device() {
+        let gpp = common::sim_gpp();
+        let staging = tempfile::tempdir().unwrap();
+        let endpoint = common::serve_device(gpp.clone(), staging.path()).await;
+        let mut device = RemoteDevice::new("DCE:gpp", "gpp", &endpoint).unwrap();
+
+        //the states are the ones of the device served
+        assert_eq!((device.identifier(), device.label(), device.composite_device()), ("DCE:gpp", "gpp", None));
+        assert_eq!((device.admin_state(), device.operational_state(), device.usage_state()), (AdminType::UNLOCKED, OperationalType::ENABLED, UsageType::IDLE));
+        device.set_admin_state(AdminType::LOCKED);
+        assert_eq!(gpp.lock().unwrap().admin_state(), AdminType::LOCKED);
+        match device.load(staging.path(), "missing", LoadType::EXECUTABLE) {
+            Err(LoadableDeviceError::LoadFail { error_number: ErrorNumberType::CF_ENOENT, .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        let fs_root = tempfile::tempdir().unwrap();
+        fs::write(fs_root.path().join("osc"), "osc").unwrap();
+        match device.load(fs_root.path(), "osc", LoadType::EXECUTABLE) {
+            Err(LoadableDeviceError::InvalidState { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        device.set_admin_state(AdminType::UNLOCKED);
+
+        //the files are loaded and executed on the device
+        device.load(fs_root.path(), "osc", LoadType::EXECUTABLE).unwrap();
+        assert_eq!(gpp.lock().unwrap().loadable().load_count("osc"), 1);
+        let parameters = vec![DataType::new("LEVEL", AnyValue::Long(3))];
+        let process_id = device.execute("osc", &vec![], &parameters).unwrap();
+        assert_eq!(gpp.lock().unwrap().process(process_id).unwrap().1, parameters);
+        match device.execute("other", &vec![], &vec![]) {
+            Err(ExecutableDeviceError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        gpp.lock().unwrap().set_process_usage(process_id, 12.5, 4096);
+        let metrics = device.process_metrics(process_id).unwrap();
+        assert_eq!((metrics.process_id, metrics.cpu_usage, metrics.memory), (process_id, 12.5, 4096));
+        device.terminate(process_id).unwrap();
+        assert!(gpp.lock().unwrap().process_ids().is_empty());
+        match device.terminate(process_id) {
+            Err(ExecutableDeviceError::InvalidProcess { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        device.unload("osc").unwrap();
+        assert_eq!(gpp.lock().unwrap().loadable().load_count("osc"), 0);
+        assert_eq!(fs::read_dir(staging.path()).unwrap().count(), 0);
+
+        //a device not answering is never allocated
+        let unreachable = RemoteDevice::new("DCE:gone", "gone", "http://127.0.0.1:1").unwrap().with_timeout(Duration::from_millis(500));
+        assert_eq!((unreachable.admin_state(), unreachable.operational_state(), unreachable.usage_state()), (AdminType::LOCKED, OperationalType::DISABLED, UsageType::BUSY));
+        assert!(unreachable.allocation_properties().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_remote_load_staging() {
+        let (fs_root, cache, staging) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let data: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        fs::create_dir(fs_root.path().join("bin")).unwrap();
+        fs::write(fs_root.path().join("bin/osc.so"), &data).unwrap();
+        let script = fs_root.path().join("osc.sh");
+        fs::write(&script, "#!/bin/sh\nsleep 10\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let loadable = LoadableDevice::new(Device::new("DCE:gpp", "gpp"), cache.path());
+        let gpp = Arc::new(Mutex::new(ExecutableDevice::new(loadable)));
+        let endpoint = common::serve_device(gpp.clone(), staging.path()).await;
+        let mut device = RemoteDevice::new("DCE:gpp", "gpp", &endpoint).unwrap();
+
+        //the files are streamed in chunks to the cache of the device, the executables runnable
+        device.load(fs_root.path(), "bin/osc.so", LoadType::SHARED_LIBRARY).unwrap();
+        assert_eq!(fs::read(cache.path().join("bin/osc.so")).unwrap(), data);
+        device.load(fs_root.path(), "osc.sh", LoadType::EXECUTABLE).unwrap();
+        let process_id = device.execute("osc.sh", &vec![], &vec![]).unwrap();
+        assert!(matches!(device.process_metrics(process_id).unwrap().status, ProcessStatus::RUNNING | ProcessStatus::SLEEPING));
+        device.terminate(process_id).unwrap();
+        assert_eq!(fs::read_dir(staging.path()).unwrap().count(), 0);
+
+        //the file names escaping the staging directory are refused
+        let mut client = DeviceClient::connect(endpoint).await.unwrap();
+        for file_name in ["../escape", "/etc/escape", ""] {
+            let request = LoadRequest { file_name: file_name.to_string(), load_kind: wire::LoadType::Executable.into(), data: b"escape".to_vec(), crc32c: None };
+            let status = client.load(tokio_stream::iter(vec![request])).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+        assert!(!staging.path().parent().unwrap().join("escape").exists());
+
+        //a corrupted chunk fails the load
+        let request = LoadRequest { file_name: "corrupted".to_string(), load_kind: wire::LoadType::Executable.into(), data: b"data".to_vec(), crc32c: Some(0) };
+        let status = client.load(tokio_stream::iter(vec![request])).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+        assert!(!cache.path().join("corrupted").exists());
+    }
+}
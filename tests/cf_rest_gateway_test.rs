@@ -0,0 +1,108 @@
+#[cfg(all(test, feature = "rest-gateway"))]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use hyper::{Body, Client, Method, Request, StatusCode};
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef};
+    use scars::cf::application_factory::DeploymentContext;
+    use scars::cf::component_registry::ComponentRegistry;
+    use scars::cf::domain_manager::DomainManager;
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::rest_gateway::{openapi, GatewayError, RestGateway};
+
+    /// Writes a waveform without component in the directory.
+    fn write_waveform(root: &Path) {
+        let path = root.join("waveforms/empty/empty.sad.xml");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, r#"<softwareassembly id="DCE:empty" name="empty"/>"#).unwrap();
+    }
+
+    /// Sends a request to the gateway, returning the status and body of its response.
+    async fn call(gateway: &str, method: Method, path: &str, body: Vec<u8>) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder().method(method).uri(format!("{gateway}{path}")).body(Body::from(body)).unwrap();
+        let response = Client::new().request(request).await.unwrap();
+        let status = response.status();
+        (status, hyper::body::to_bytes(response.into_body()).await.unwrap().to_vec())
+    }
+
+    async fn call_json(gateway: &str, method: Method, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+        let body = body.map(|b| serde_json::to_vec(&b).unwrap()).unwrap_or_default();
+        let request = Request::builder().method(method).uri(format!("{gateway}{path}")).header("content-type", "application/json").body(Body::from(body)).unwrap();
+        let response = Client::new().request(request).await.unwrap();
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn test_rest_gateway() {
+        let root = tempfile::tempdir().unwrap();
+        write_waveform(root.path());
+        let domain = DomainManager::new("DCE:domain", "REDHAWK_DEV");
+        let allocation_manager: AllocationManagerRef = Arc::new(Mutex::new(AllocationManager::new()));
+        let deployment = DeploymentContext::new(domain.file_manager(), allocation_manager, ComponentRegistry::new());
+        let domain = domain.with_deployment(deployment);
+        domain.file_manager().lock().unwrap().mount("/dom", Arc::new(FileSystem::new(root.path()))).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let served = tokio::spawn(domain.clone().run(listener));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(RestGateway::connect(&endpoint).await.unwrap().serve(listener));
+
+        //the applications are created from the installed factories, the bodies being the canonical JSON of the messages
+        let (status, created) = call_json(&gateway, Method::POST, "/application-factories", Some(json!({"profile_file_name": "/dom/waveforms/empty/empty.sad.xml"}))).await;
+        assert_eq!((status, created), (StatusCode::CREATED, json!({"identifier": "DCE:empty"})));
+        let (status, factories) = call_json(&gateway, Method::GET, "/application-factories", None).await;
+        assert_eq!((status, factories), (StatusCode::OK, json!([{"identifier": "DCE:empty", "name": "empty", "software_profile": "/dom/waveforms/empty/empty.sad.xml"}])));
+        let (status, created) = call_json(&gateway, Method::POST, "/applications", Some(json!({"factory_identifier": "DCE:empty", "name": "empty_1"}))).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let identifier = created["identifier"].as_str().unwrap().to_string();
+        let (status, applications) = call_json(&gateway, Method::GET, "/applications", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((applications[0]["identifier"].as_str(), applications[0]["name"].as_str(), applications[0]["started"].as_bool()), (Some(identifier.as_str()), Some("empty_1"), Some(false)));
+        assert_eq!(call_json(&gateway, Method::POST, &format!("/applications/{identifier}/start"), None).await.0, StatusCode::NO_CONTENT);
+        let (status, properties) = call_json(&gateway, Method::GET, &format!("/applications/{identifier}/properties"), None).await;
+        assert_eq!((status, properties), (StatusCode::OK, json!([])));
+        assert_eq!(call_json(&gateway, Method::POST, &format!("/applications/{identifier}/stop"), None).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(call_json(&gateway, Method::DELETE, &format!("/applications/{identifier}"), None).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(call_json(&gateway, Method::DELETE, "/application-factories/DCE:empty", None).await.0, StatusCode::NO_CONTENT);
+
+        //the failed calls answer the status matching the gRPC one
+        let (status, error) = call_json(&gateway, Method::GET, "/applications/DCE:am/properties?ids=frequency,gain", None).await;
+        assert_eq!((status, error["code"].as_str()), (StatusCode::NOT_FOUND, Some("NotFound")));
+        let (status, error) = call_json(&gateway, Method::PUT, "/applications/DCE:am/properties", Some(json!([{"id": "frequency", "value": {"Double": 101.1e6}}]))).await;
+        assert_eq!((status, error["code"].as_str()), (StatusCode::NOT_FOUND, Some("NotFound")));
+        let (status, _) = call_json(&gateway, Method::POST, "/applications", Some(json!({"factory_identifier": "DCE:am", "name": "am_1"}))).await;
+        assert!(status.is_client_error());
+
+        //the files are written, read, listed and removed
+        let (status, written) = call(&gateway, Method::PUT, "/files/dom/notes.txt", b"tune to 101.1".to_vec()).await;
+        assert_eq!((status, serde_json::from_slice::<Value>(&written).unwrap()), (StatusCode::OK, json!({"size": "13", "corrupted": []})));
+        assert_eq!(call(&gateway, Method::GET, "/files/dom/notes.txt", Vec::new()).await, (StatusCode::OK, b"tune to 101.1".to_vec()));
+        let (status, files) = call_json(&gateway, Method::GET, "/files?pattern=/dom/*", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(files.as_array().unwrap().iter().map(|f| (f["name"].as_str().unwrap(), f["kind"].as_str().unwrap())).collect::<Vec<_>>(), vec![("notes.txt", "PLAIN"), ("waveforms", "DIRECTORY")]);
+        assert_eq!(call(&gateway, Method::DELETE, "/files/dom/notes.txt", Vec::new()).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(call(&gateway, Method::GET, "/files/dom/notes.txt", Vec::new()).await.0, StatusCode::NOT_FOUND);
+
+        //the OpenAPI spec is served
+        let (status, spec) = call_json(&gateway, Method::GET, "/openapi.json", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(spec, serde_json::to_value(openapi()).unwrap());
+        assert!(spec["paths"]["/applications/{identifier}/properties"]["put"].is_object());
+        assert!(spec["components"]["schemas"]["Property"].is_object());
+        assert_eq!((spec["components"]["schemas"]["FileInformation"]["properties"]["kind"]["type"].as_str(), spec["components"]["schemas"]["FileInformation"]["properties"]["size"]["type"].as_str()), (Some("string"), Some("string")));
+
+        match RestGateway::connect("http://127.0.0.1:1").await {
+            Err(GatewayError::TransportError { .. }) => {}
+            r => panic!("{:?}", r.map(|_| ())),
+        }
+        domain.shutdown();
+        served.await.unwrap().unwrap();
+    }
+}
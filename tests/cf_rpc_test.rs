@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::rpc::device::Property;
+    use scars::cf::rpc::domain_manager::{ApplicationMetricsReply, ComponentMetrics, CreateApplicationRequest, DeviceAssignment, ProcessStatus};
+    use scars::cf::rpc::file_system::{FileInformation, FileType, FileChunk};
+    use scars::cf::rpc::{message_from_json, message_to_json, properties_from_wire, properties_to_wire};
+
+    #[test]
+    fn test_message_json() {
+        //the fields are emitted under their proto names, the enums by name and the 64-bit integers as strings
+        let file = FileInformation { name: "tone.sad.xml".to_string(), kind: FileType::Plain as i32, size: 5000000000 };
+        assert_eq!(message_to_json(&file).unwrap(), json!({"name": "tone.sad.xml", "kind": "PLAIN", "size": "5000000000"}));
+        let metrics = ApplicationMetricsReply {
+            identifier: "DCE:tone:tone_1".to_string(),
+            cpu_usage: 0.5,
+            memory: 4096,
+            components: vec![ComponentMetrics { component_id: "osc_1:DCE:tone:tone_1".to_string(), device_id: "DCE:gpp".to_string(), process_id: None, cpu_usage: 0.5, memory: 4096, status: ProcessStatus::Sleeping as i32 }],
+        };
+        assert_eq!(message_to_json(&metrics).unwrap(), json!({"identifier": "DCE:tone:tone_1", "cpu_usage": 0.5, "memory": "4096", "components": [{"component_id": "osc_1:DCE:tone:tone_1", "device_id": "DCE:gpp", "cpu_usage": 0.5, "memory": "4096", "status": "SLEEPING"}]}));
+        assert_eq!(message_to_json(&FileChunk { data: b"tone".to_vec().into(), offset: 4, crc32c: Some(0xec52_91f6) }).unwrap(), json!({"data": "dG9uZQ==", "offset": "4", "crc32c": 3964834294u32}));
+
+        //the messages decode from their canonical JSON, and from their lowerCamelCase names, the missing fields defaulted
+        assert_eq!(message_from_json::<FileInformation>(message_to_json(&file).unwrap()).unwrap(), file);
+        assert_eq!(message_from_json::<ApplicationMetricsReply>(message_to_json(&metrics).unwrap()).unwrap(), metrics);
+        let request: CreateApplicationRequest = message_from_json(json!({"factoryIdentifier": "DCE:tone", "name": "tone_1", "device_assignments": [{"component_id": "osc_1", "assignedDeviceId": "DCE:gpp"}]})).unwrap();
+        assert_eq!(request, CreateApplicationRequest { factory_identifier: "DCE:tone".to_string(), name: "tone_1".to_string(), init_configuration: vec![], device_assignments: vec![DeviceAssignment { component_id: "osc_1".to_string(), assigned_device_id: "DCE:gpp".to_string() }] });
+        assert!(message_from_json::<FileInformation>(json!({"kind": "SOCKET"})).is_err());
+    }
+
+    #[test]
+    fn test_property_json() {
+        //the values of the properties are embedded, the empty ones mapped to null
+        let properties = properties_to_wire(&vec![DataType::new("frequency", AnyValue::Double(101.1e6)), DataType::new("mode", AnyValue::String("FM".to_string()))]);
+        let json = serde_json::to_value(&properties).unwrap();
+        assert_eq!(json, json!([{"id": "frequency", "value": {"Double": 101.1e6}}, {"id": "mode", "value": {"String": "FM"}}]));
+        assert_eq!(properties_from_wire(&serde_json::from_value::<Vec<Property>>(json).unwrap()).unwrap(), vec![DataType::new("frequency", AnyValue::Double(101.1e6)), DataType::new("mode", AnyValue::String("FM".to_string()))]);
+        let query = Property { id: "gain".to_string(), value: String::new() };
+        assert_eq!(serde_json::to_value(&query).unwrap(), json!({"id": "gain", "value": null}));
+        assert_eq!(serde_json::from_value::<Property>(json!({"id": "gain"})).unwrap(), query);
+
+        //a malformed value is mapped to a string
+        assert_eq!(serde_json::to_value(Property { id: "gain".to_string(), value: "{".to_string() }).unwrap(), json!({"id": "gain", "value": "{"}));
+    }
+}
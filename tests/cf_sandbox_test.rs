@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::file_system::FileSystem;
+    use scars::cf::profile::sad::PortKind;
+    use scars::cf::resource::{Resource, ResourceError, ResourceTrait};
+    use scars::cf::sandbox::{Sandbox, SandboxError};
+
+    const SPD: &str = r#"<softpkg id="DCE:demod" name="demod">
+  <propertyfile type="PRF"><localfile name="demod.prf.xml"/></propertyfile>
+  <descriptor><localfile name="demod.scd.xml"/></descriptor>
+  <implementation id="cpp"><code type="Executable"><localfile name="cpp"/></code></implementation>
+</softpkg>
+"#;
+
+    const SCD: &str = r#"<softwarecomponent>
+  <corbaversion>2.2</corbaversion>
+  <componentrepid repid="IDL:CF/Resource:1.0"/>
+  <componenttype>resource</componenttype>
+  <componentfeatures>
+    <ports>
+      <provides repid="IDL:BULKIO/dataFloat:1.0" providesname="audio_in"/>
+      <uses repid="IDL:BULKIO/dataFloat:1.0" usesname="audio_out"/>
+    </ports>
+  </componentfeatures>
+  <interfaces/>
+</softwarecomponent>
+"#;
+
+    const PRF: &str = r#"<properties>
+  <simple id="frequency" type="double"><value>101.1</value><kind kindtype="configure"/></simple>
+  <simple id="processor_name" type="string"><value>x86_64</value><kind kindtype="allocation"/></simple>
+  <struct id="gain">
+    <simple id="gain::value" type="float"><value>0.5</value></simple>
+    <simple id="gain::auto" type="boolean"><value>false</value></simple>
+    <configurationkind kindtype="configure"/>
+  </struct>
+</properties>
+"#;
+
+    fn component_files(root: &std::path::Path) -> FileSystem {
+        let dir = root.join("components/demod");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("demod.spd.xml"), SPD).unwrap();
+        std::fs::write(dir.join("demod.scd.xml"), SCD).unwrap();
+        std::fs::write(dir.join("demod.prf.xml"), PRF).unwrap();
+        FileSystem::new(root)
+    }
+
+    #[test]
+    fn test_launch_and_connect() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = component_files(root.path());
+        let mut sandbox = Sandbox::new();
+
+        //the components are named after their SPD, with the configure properties and the ports of their descriptors
+        let demod = sandbox.launch(&file_system, "/components/demod/demod.spd.xml", None).unwrap();
+        assert_eq!(demod.name, "demod_1");
+        assert_eq!(demod.ports.iter().map(|p| (p.name.as_str(), p.kind)).collect::<Vec<_>>(), vec![("audio_in", PortKind::PROVIDES), ("audio_out", PortKind::USES)]);
+        sandbox.launch(&file_system, "/components/demod/demod.spd.xml", None).unwrap();
+        sandbox.launch(&file_system, "/components/demod/demod.spd.xml", Some("sink")).unwrap();
+        assert_eq!(sandbox.components().iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["demod_1", "demod_2", "sink"]);
+        match sandbox.launch(&file_system, "/components/demod/demod.spd.xml", Some("sink")) { Err(SandboxError::DuplicateName { name }) => assert_eq!(name, "sink"), r => panic!("{:?}", r) }
+        match sandbox.launch(&file_system, "/components/mod/mod.spd.xml", None) { Err(SandboxError::ProfileError { .. }) => {}, r => panic!("{:?}", r) }
+
+        let values = sandbox.query("demod_1", &vec![]).unwrap();
+        assert!(values.contains(&DataType::new("frequency", AnyValue::Double(101.1))));
+        assert!(!values.iter().any(|p| p.id == "processor_name"));
+
+        let connection_id = sandbox.connect("demod_1", "audio_out", "sink", "audio_in").unwrap();
+        assert_eq!(sandbox.connections()[0].endpoint, "sandbox://sink/audio_in");
+        match sandbox.connect("demod_1", "audio_out", "sink", "audio") { Err(SandboxError::ResourceError { source: ResourceError::UnknownPort { .. } }) => {}, r => panic!("{:?}", r) }
+        match sandbox.connect("demod_3", "audio_out", "sink", "audio_in") { Err(SandboxError::UnknownComponent { name }) => assert_eq!(name, "demod_3"), r => panic!("{:?}", r) }
+
+        //releasing a component breaks its connections
+        sandbox.release("sink").unwrap();
+        assert!(sandbox.connections().is_empty());
+        match sandbox.disconnect(&connection_id) { Err(SandboxError::UnknownConnection { .. }) => {}, r => panic!("{:?}", r) }
+        sandbox.release_all().unwrap();
+        assert!(sandbox.components().is_empty());
+    }
+
+    #[test]
+    fn test_control_and_logs() {
+        let root = tempfile::tempdir().unwrap();
+        let file_system = component_files(root.path());
+        let mut sandbox = Sandbox::new();
+        sandbox.launch(&file_system, "/components/demod/demod.spd.xml", None).unwrap();
+
+        sandbox.configure("demod_1", &vec![DataType::new("frequency", AnyValue::Double(98.5))]).unwrap();
+        assert_eq!(sandbox.query("demod_1", &vec![DataType::new("frequency", AnyValue::Double(0.0))]).unwrap()[0].value, AnyValue::Double(98.5));
+        match sandbox.configure("demod_1", &vec![DataType::new("mode", AnyValue::String("mono".to_string()))]) { Err(SandboxError::ResourceError { source: ResourceError::InvalidConfiguration { .. } }) => {}, r => panic!("{:?}", r) }
+        sandbox.start("demod_1").unwrap();
+        assert!(sandbox.component("demod_1").unwrap().resource.lock().unwrap().started());
+        sandbox.stop("demod_1").unwrap();
+
+        //the operations are logged, the records produced after a time being tailed
+        let records = sandbox.logs(&["demod_1"], None);
+        let messages: Vec<&str> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages[1..], ["configured frequency=98.5", "started", "stopped"]);
+        assert!(messages[0].starts_with("launched from"));
+        assert!(sandbox.logs(&["demod_1"], Some(records[2].time)).iter().all(|r| r.message == "stopped"));
+        assert!(sandbox.logs(&["demod_2"], None).is_empty());
+
+        //a component of its own implementation is configured with the defaults of its PRF
+        let resource = Arc::new(Mutex::new(Resource::new("demod").with_property("frequency", AnyValue::Double(0.0)).with_property("gain", AnyValue::Struct(vec![]))));
+        sandbox.launch_resource(&file_system, "/components/demod/demod.spd.xml", Some("own"), resource.clone()).unwrap();
+        assert_eq!(resource.lock().unwrap().query(&vec![DataType::new("frequency", AnyValue::Double(0.0))]).unwrap()[0].value, AnyValue::Double(101.1));
+        assert_eq!(sandbox.logs(&[], None).len(), records.len() + 1);
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use scars::cf::profile::dcd::DeviceConfiguration;
+    use scars::cf::profile::prf::PropertiesDescriptor;
+    use scars::cf::profile::sad::PortKind;
+    use scars::cf::profile::scd::SoftwareComponent;
+    use scars::cf::profile::spd::SoftPkg;
+    use scars::cf::scaffold::{Scaffold, ScaffoldError, ScaffoldKind, SCARS_GIT};
+
+    fn contents<'a>(files: &'a [scars::cf::scaffold::ScaffoldFile], path: &str) -> &'a str {
+        &files.iter().find(|f| f.path == Path::new(path)).unwrap_or_else(|| panic!("{path}")).contents
+    }
+
+    #[test]
+    fn test_component() {
+        let scaffold = Scaffold::new(ScaffoldKind::COMPONENT, "tone-gen").unwrap();
+        let files = scaffold.files();
+        let paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            ["Cargo.toml", "tone-gen.spd.xml", "tone-gen.scd.xml", "tone-gen.prf.xml", "src/properties.rs", "src/lib.rs", "tests/tone_gen_test.rs"]
+                .map(PathBuf::from)
+        );
+
+        //the descriptors refer to each other
+        let spd = SoftPkg::parse(contents(&files, "tone-gen.spd.xml"), "tone-gen.spd.xml").unwrap();
+        assert_eq!(spd.id, "DCE:tone-gen");
+        assert_eq!(spd.property_file.as_deref(), Some("tone-gen.prf.xml"));
+        assert_eq!(spd.descriptor.as_deref(), Some("tone-gen.scd.xml"));
+        let scd = SoftwareComponent::parse(contents(&files, "tone-gen.scd.xml"), "tone-gen.scd.xml").unwrap();
+        assert_eq!(scd.component_type.as_deref(), Some("resource"));
+        assert_eq!(scd.port("data_in").unwrap().kind, PortKind::PROVIDES);
+        assert_eq!(scd.port("data_out").unwrap().kind, PortKind::USES);
+        let prf = PropertiesDescriptor::parse(contents(&files, "tone-gen.prf.xml"), "tone-gen.prf.xml").unwrap();
+        assert_eq!(prf.properties[0].id(), "frequency");
+
+        //the skeleton is wired to the properties struct generated from the PRF
+        assert!(contents(&files, "src/properties.rs").contains("pub struct ToneGenProperties"));
+        assert!(contents(&files, "src/lib.rs").contains("impl ResourceTrait for ToneGen"));
+        let test = contents(&files, "tests/tone_gen_test.rs");
+        assert!(test.contains("use tone_gen::{ToneGen, ToneGenProperties};"));
+        assert!(test.contains("Sandbox::new()"));
+        assert!(!files.iter().any(|f| f.contents.contains("{type_name}") || f.contents.contains("{properties_type}")));
+        assert!(contents(&files, "Cargo.toml").contains(SCARS_GIT));
+    }
+
+    #[test]
+    fn test_node() {
+        let scaffold = Scaffold::new(ScaffoldKind::NODE, "lab_node").unwrap().with_scars_path(Path::new("/opt/scars"));
+        let files = scaffold.files();
+        let manifest = contents(&files, "Cargo.toml");
+        assert!(manifest.contains("scars = { path = \"/opt/scars\" }"));
+        assert!(manifest.contains("[[bin]]\nname = \"lab_node\""));
+
+        let dcd = DeviceConfiguration::parse(contents(&files, "DeviceManager.dcd.xml"), "DeviceManager.dcd.xml").unwrap();
+        assert_eq!(dcd.id, "DCE:lab_node");
+        let file = dcd.component_file(&dcd.placements[0]).unwrap();
+        assert_eq!(file.local_file, "lab_node_device.spd.xml");
+        assert_eq!(dcd.placements[0].instantiations[0].usage_name.as_deref(), Some("lab_node_device_1"));
+        let spd = SoftPkg::parse(contents(&files, &file.local_file), &file.local_file).unwrap();
+        assert_eq!(spd.implementations[0].code.as_ref().unwrap().local_file, "lab_node");
+        let scd = SoftwareComponent::parse(contents(&files, "lab_node_device.scd.xml"), "lab_node_device.scd.xml").unwrap();
+        assert_eq!(scd.component_type.as_deref(), Some("device"));
+        PropertiesDescriptor::parse(contents(&files, "lab_node_device.prf.xml"), "lab_node_device.prf.xml").unwrap();
+
+        assert!(contents(&files, "src/lib.rs").contains("impl DeviceTrait for LabNodeDevice"));
+        assert!(contents(&files, "src/main.rs").contains("use lab_node::LabNodeDevice;"));
+        assert!(contents(&files, "tests/lab_node_test.rs").contains("fn test_lab_node()"));
+    }
+
+    #[test]
+    fn test_write() {
+        let root = tempfile::tempdir().unwrap();
+        let scaffold = Scaffold::new(ScaffoldKind::COMPONENT, "osc").unwrap();
+        let directory = scaffold.write(root.path()).unwrap();
+        assert_eq!(directory, root.path().join("osc"));
+        for file in scaffold.files() {
+            assert_eq!(std::fs::read_to_string(directory.join(&file.path)).unwrap(), file.contents);
+        }
+
+        //an existing crate is never overwritten
+        match scaffold.write(root.path()) {
+            Err(ScaffoldError::AlreadyExists { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        for name in ["", "Osc", "1osc", "osc/gen", "osc gen"] {
+            match Scaffold::new(ScaffoldKind::NODE, name) {
+                Err(ScaffoldError::InvalidName { .. }) => {}
+                r => panic!("{:?}", r),
+            }
+        }
+        assert_eq!(ScaffoldKind::from_name("node"), Some(ScaffoldKind::NODE));
+        assert_eq!(ScaffoldKind::from_name("domain"), None);
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use scars::cf::common_types::{AnyValue, DataType};
+    use scars::cf::device::{Device, DeviceTrait, UsageType};
+    use scars::cf::executable_device::{ExecutableDeviceError, ExecutableDeviceTrait};
+    use scars::cf::frontend_tuner::{
+        FrontendTunerDevice, TunerAllocation, TunerCapabilities, TunerType,
+    };
+    use scars::cf::loadable_device::{LoadType, LoadableDeviceError, LoadableDeviceTrait};
+    use scars::cf::sim_device::{
+        SimExecutableDevice, SimLoadableDevice, SimOperation, SimTunerDevice,
+    };
+
+    #[test]
+    fn test_sim_executable_device() {
+        let device = Device::new("DCE:sim", "sim").with_capacity("mips", AnyValue::ULong(100));
+        let mut d = SimExecutableDevice::new(
+            SimLoadableDevice::new(device)
+                .with_latency(SimOperation::LOAD, Duration::from_millis(20))
+                .with_failure(SimOperation::EXECUTE, 2),
+        );
+
+        let start = Instant::now();
+        d.load(Path::new("/"), "comp", LoadType::EXECUTABLE)
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let parameters = vec![DataType::new("LEVEL", AnyValue::Long(3))];
+        let pid = d.execute("comp", &vec![], &parameters).unwrap();
+        assert_eq!(d.process(pid).unwrap().1, parameters);
+        match d.execute("comp", &vec![], &vec![]) {
+            Err(ExecutableDeviceError::ExecuteFail { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        match d.execute("other", &vec![], &vec![]) {
+            Err(ExecutableDeviceError::InvalidFileName { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+
+        d.terminate(pid).unwrap();
+        assert!(d.process_ids().is_empty());
+        d.unload("comp").unwrap();
+        assert_eq!(d.loadable().load_count("comp"), 0);
+        assert_eq!(d.loadable().script().calls(SimOperation::EXECUTE), 2);
+
+        assert!(d
+            .allocate_capacity(&vec![DataType::new("mips", AnyValue::ULong(100))])
+            .unwrap());
+        assert_eq!(d.usage_state(), UsageType::BUSY);
+    }
+
+    #[test]
+    fn test_sim_scripted_failures() {
+        let mut d = SimLoadableDevice::new(Device::new("DCE:sim", "sim"))
+            .with_failure(SimOperation::LOAD, 1);
+        match d.load(Path::new("/"), "lib", LoadType::SHARED_LIBRARY) {
+            Err(LoadableDeviceError::LoadFail { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+        d.load(Path::new("/"), "lib", LoadType::SHARED_LIBRARY)
+            .unwrap();
+
+        let capabilities = TunerCapabilities {
+            tuner_type: TunerType::RX,
+            min_frequency: 88e6,
+            max_frequency: 108e6,
+            min_bandwidth: 200e3,
+            max_bandwidth: 200e3,
+            min_sample_rate: 250e3,
+            max_sample_rate: 250e3,
+            group_id: String::new(),
+            rf_flow_id: String::new(),
+        };
+        let mut t = SimTunerDevice::new(
+            FrontendTunerDevice::new(Device::new("DCE:fm", "fm")).with_tuner(capabilities),
+        )
+        .with_failure(1);
+        let request = vec![TunerAllocation {
+            tuner_type: TunerType::RX,
+            allocation_id: "fm".to_string(),
+            center_frequency: 100e6,
+            bandwidth: 0.0,
+            bandwidth_tolerance: 10.0,
+            sample_rate: 0.0,
+            sample_rate_tolerance: 10.0,
+            device_control: true,
+            group_id: String::new(),
+            rf_flow_id: String::new(),
+        }
+        .to_property()];
+
+        assert!(!t.allocate_capacity(&request).unwrap());
+        assert!(t.allocate_capacity(&request).unwrap());
+        assert_eq!(t.tuner_status()[0].allocation_ids, vec!["fm"]);
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(all(test, feature = "zenoh"))]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio_stream::StreamExt;
+    use zenoh::Session;
+
+    use scars::cf::bulkio::{BulkioInPort, BulkioMessage, BulkioOutPort, PrecisionUtcTime, StreamSri};
+    use scars::cf::events::{EventChannel, EventChannelTrait, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+    use scars::cf::zenoh_channel::{announce, discover, open, peer_config, subscribe, subscribe_to, ZenohEndpoint, ZenohError, ZenohEventChannel, ZenohTraffic, DEFAULT_KEY_PREFIX};
+
+    /// Opens two peers, the second one connected to the first one.
+    async fn open_peers() -> (Session, Session) {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let locator = format!("tcp/127.0.0.1:{port}");
+        let first = open(peer_config(&[&locator], &[], false).unwrap()).await.unwrap();
+        let second = open(peer_config(&[], &[&locator], false).unwrap()).await.unwrap();
+        (first, second)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_zenoh_event_channel() {
+        let (first, second) = open_peers().await;
+        let channel = ZenohEventChannel::declare(&first, "IDM_Channel", "scars/events/IDM_Channel", ZenohTraffic::Events).await.unwrap();
+        assert_eq!((channel.name(), channel.key_expr(), channel.traffic()), ("IDM_Channel", "scars/events/IDM_Channel", ZenohTraffic::Events));
+        let busy = StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        };
+
+        //the events reach the subscribers of the peers, of the key expression or of a pattern matching it
+        let mut events = channel.subscribe_from(0);
+        let mut other_events = subscribe::<StateChangeEvent>(&second, "scars/events/IDM_Channel").await.unwrap();
+        let mut all_events = subscribe::<StateChangeEvent>(&second, "scars/events/*").await.unwrap();
+        let mut odm_events = subscribe::<StateChangeEvent>(&second, "scars/events/ODM_Channel").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        channel.push(busy.clone());
+        channel.push(busy.clone());
+        assert_eq!(events.next().await, Some((1, busy.clone())));
+        assert_eq!(events.next().await, Some((2, busy.clone())));
+        assert_eq!(other_events.next().await, Some(busy.clone()));
+        assert_eq!(all_events.next().await, Some(busy));
+        assert!(tokio::time::timeout(Duration::from_millis(100), odm_events.next()).await.is_err());
+        assert_eq!(channel.failed_writes(), 0);
+
+        //the invalid key expressions give an error, or an empty stream
+        match ZenohEventChannel::<u32>::declare(&first, "IDM_Channel", "scars/events/#", ZenohTraffic::Events).await {
+            Err(ZenohError::KeyExprError { key_expr, .. }) => assert_eq!(key_expr, "scars/events/#"),
+            r => panic!("{:?}", r),
+        }
+        match subscribe::<u32>(&second, "scars//events").await {
+            Err(ZenohError::KeyExprError { .. }) => {}
+            r => panic!("{:?}", r.map(|_| ())),
+        }
+        match open(peer_config(&["tcp/127.0.0.1"], &[], false).unwrap()).await {
+            Err(ZenohError::SessionError { .. }) => {}
+            r => panic!("{:?}", r.map(|_| ())),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_zenoh_port_transport() {
+        let (first, second) = open_peers().await;
+        let transport = ZenohEventChannel::declare(&first, "dataFloat_out", "scars/data/zenoh_connection", ZenohTraffic::Data).await.unwrap();
+        let mut out_port = BulkioOutPort::new("dataFloat_out", EventChannel::new("dataFloat_out"));
+        out_port.connect("zenoh_connection", Arc::new(transport.clone()));
+
+        //the provides port receives the data of the connection from the peer
+        let channel: EventChannel<BulkioMessage<f32>> = EventChannel::new("dataFloat_in");
+        let mut in_port = BulkioInPort::new("dataFloat_in", &channel);
+        let forward = subscribe_to(&second, "scars/data/zenoh_connection", &channel).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let sri = StreamSri::new("tone").with_sample_rate(8000.0);
+        out_port.push_sri(sri.clone());
+        out_port.push_packet(vec![0.5, -0.5], PrecisionUtcTime::now(), true, "tone");
+        let block = tokio::task::spawn_blocking(move || in_port.get_packet(Duration::from_secs(1))).await.unwrap().unwrap();
+        assert_eq!((block.data, block.sri, block.eos), (vec![0.5, -0.5], sri, true));
+        assert_eq!(transport.failed_writes(), 0);
+        forward.abort();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_zenoh_discovery() {
+        let (first, second) = open_peers().await;
+        let domain = ZenohEndpoint::new("DomainManager", "DCE:domain", "http://10.0.0.1:5000");
+        let node = ZenohEndpoint::new("DeviceManager", "DCE:node", "http://10.0.0.2:5001");
+        let announced = announce(&first, DEFAULT_KEY_PREFIX, &domain).await.unwrap();
+        assert_eq!(announced.key_expr(), "scars/endpoints/DomainManager/DCE:domain");
+        let _node = announce(&second, DEFAULT_KEY_PREFIX, &node).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        //the endpoints announced by the peers are discovered, of all the kinds or of one
+        let timeout = Duration::from_secs(1);
+        assert_eq!(discover(&second, DEFAULT_KEY_PREFIX, None, timeout).await.unwrap(), vec![node.clone(), domain.clone()]);
+        assert_eq!(discover(&first, DEFAULT_KEY_PREFIX, Some("DomainManager"), timeout).await.unwrap(), vec![domain]);
+        assert_eq!(discover(&first, "other_domain", None, timeout).await.unwrap(), vec![]);
+
+        //the endpoints are withdrawn with their announcement
+        drop(announced);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(discover(&second, DEFAULT_KEY_PREFIX, None, timeout).await.unwrap(), vec![node]);
+    }
+}
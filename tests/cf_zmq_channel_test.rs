@@ -0,0 +1,73 @@
+#[cfg(all(test, feature = "zmq"))]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio_stream::StreamExt;
+
+    use scars::cf::bulkio::{BulkioInPort, BulkioMessage, BulkioOutPort, PrecisionUtcTime, StreamSri};
+    use scars::cf::events::{EventChannel, EventChannelTrait, StateChangeCategoryType, StateChangeEvent, StateChangeType};
+    use scars::cf::zmq_channel::{connect, connect_to, ZmqError, ZmqEventChannel, ZmqPattern};
+
+    #[tokio::test]
+    async fn test_zmq_event_channel() {
+        let channel = ZmqEventChannel::bind("IDM_Channel", "tcp://127.0.0.1:0", ZmqPattern::PubSub).await.unwrap();
+        assert_eq!(channel.name(), "IDM_Channel");
+        assert!(channel.endpoint().starts_with("tcp://127.0.0.1:") && !channel.endpoint().ends_with(":0"));
+        let busy = StateChangeEvent {
+            producer_id: "DCE:gpp".to_string(),
+            source_id: "DCE:gpp".to_string(),
+            state_change_category: StateChangeCategoryType::USAGE_STATE_EVENT,
+            state_change_from: StateChangeType::IDLE,
+            state_change_to: StateChangeType::BUSY,
+        };
+
+        //the events are missed until the subscriptions reach the publisher
+        let mut events = channel.subscribe_from(0);
+        let mut other_events = connect::<StateChangeEvent>("IDM_Channel", channel.endpoint(), ZmqPattern::PubSub).await.unwrap();
+        let mut prefixed = connect::<StateChangeEvent>("IDM", channel.endpoint(), ZmqPattern::PubSub).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        channel.push(busy.clone());
+        channel.push(busy.clone());
+        assert_eq!(events.next().await, Some((1, busy.clone())));
+        assert_eq!(events.next().await, Some((2, busy.clone())));
+        assert_eq!(other_events.next().await, Some(busy));
+        assert!(tokio::time::timeout(Duration::from_millis(100), prefixed.next()).await.is_err());
+        assert_eq!(channel.failed_writes(), 0);
+
+        //the failed connections give an error, or an empty stream
+        match connect::<u32>("IDM_Channel", "tcp://127.0.0.1", ZmqPattern::PubSub).await {
+            Err(ZmqError::SocketError { .. }) => {}
+            r => panic!("{:?}", r.map(|_| ())),
+        }
+        match ZmqEventChannel::<u32>::bind("IDM_Channel", "udp://127.0.0.1:0", ZmqPattern::PubSub).await {
+            Err(ZmqError::SocketError { .. }) => {}
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_zmq_port_transport() {
+        let transport = ZmqEventChannel::bind("dataFloat_out", "tcp://127.0.0.1:0", ZmqPattern::PushPull).await.unwrap();
+        let mut out_port = BulkioOutPort::new("dataFloat_out", EventChannel::new("dataFloat_out"));
+        out_port.connect("zmq_connection", Arc::new(transport.clone()));
+
+        //the packets pushed without puller fail
+        out_port.push_packet(vec![0.0], PrecisionUtcTime::now(), true, "tone");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(transport.failed_writes(), 2);
+
+        //the provides port receives the data pulled from the endpoint
+        let channel: EventChannel<BulkioMessage<f32>> = EventChannel::new("dataFloat_in");
+        let mut in_port = BulkioInPort::new("dataFloat_in", &channel);
+        let pull = connect_to("dataFloat_out", transport.endpoint(), ZmqPattern::PushPull, &channel).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let sri = StreamSri::new("tone").with_sample_rate(8000.0);
+        out_port.push_sri(sri.clone());
+        out_port.push_packet(vec![0.5, -0.5], PrecisionUtcTime::now(), true, "tone");
+        let block = tokio::task::spawn_blocking(move || in_port.get_packet(Duration::from_secs(1))).await.unwrap().unwrap();
+        assert_eq!((block.data, block.sri, block.eos), (vec![0.5, -0.5], sri, true));
+        assert_eq!(transport.failed_writes(), 2);
+        pull.abort();
+    }
+}
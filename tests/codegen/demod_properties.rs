@@ -0,0 +1,325 @@
+// Generated by scars-codegen from demod.prf.xml, do not edit.
+
+use scars::cf::common_types::{AnyValue, DataType, Properties, PropertyValue};
+use scars::cf::resource::ResourceError;
+
+/// The value of the 'gain' struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gain {
+    pub value: f32,
+    pub auto: Option<bool>,
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Gain {
+            value: 0.5,
+            auto: None,
+        }
+    }
+}
+
+impl Gain {
+    /// Verifies the fields are within their range and enumerations.
+    pub fn verify(&self) -> Result<(), String> {
+        {
+            let v = &self.value;
+            if !(0.0..=1.0).contains(v) {
+                return Err("'gain::value' is out of range [0, 1]".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PropertyValue for Gain {
+    fn to_any(&self) -> AnyValue {
+        let mut fields = Properties::new();
+        fields.push(DataType::new("gain::value", self.value.to_any()));
+        if let Some(v) = &self.auto {
+            fields.push(DataType::new("gain::auto", v.to_any()));
+        }
+        AnyValue::Struct(fields)
+    }
+
+    fn from_any(value: &AnyValue) -> Option<Self> {
+        let AnyValue::Struct(fields) = value else {
+            return None;
+        };
+        let mut s = Gain::default();
+        for field in fields {
+            match field.id.as_str() {
+                "gain::value" => s.value = PropertyValue::from_any(&field.value)?,
+                "gain::auto" => s.auto = Some(PropertyValue::from_any(&field.value)?),
+                _ => return None,
+            }
+        }
+        Some(s)
+    }
+}
+
+/// The value of the 'channel' struct.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Channel {
+    pub id: Option<u32>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl Channel {
+    /// Verifies the fields are within their range and enumerations.
+    pub fn verify(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl PropertyValue for Channel {
+    fn to_any(&self) -> AnyValue {
+        let mut fields = Properties::new();
+        if let Some(v) = &self.id {
+            fields.push(DataType::new("channel::id", v.to_any()));
+        }
+        if let Some(v) = &self.tags {
+            fields.push(DataType::new("channel::tags", v.to_any()));
+        }
+        AnyValue::Struct(fields)
+    }
+
+    fn from_any(value: &AnyValue) -> Option<Self> {
+        let AnyValue::Struct(fields) = value else {
+            return None;
+        };
+        let mut s = Channel::default();
+        for field in fields {
+            match field.id.as_str() {
+                "channel::id" => s.id = Some(PropertyValue::from_any(&field.value)?),
+                "channel::tags" => s.tags = Some(PropertyValue::from_any(&field.value)?),
+                _ => return None,
+            }
+        }
+        Some(s)
+    }
+}
+
+/// demod properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemodProperties {
+    frequency: f64,
+    mode: String,
+    processor_name: String,
+    squelch_level: Option<i16>,
+    type_: Option<char>,
+    taps: Vec<i16>,
+    gain: Gain,
+    channels: Vec<Channel>,
+}
+
+impl Default for DemodProperties {
+    fn default() -> Self {
+        DemodProperties {
+            frequency: 101.1,
+            mode: "stereo".to_string(),
+            processor_name: "x86_64".to_string(),
+            squelch_level: None,
+            type_: None,
+            taps: vec![1, -2],
+            gain: Gain::default(),
+            channels: vec![Channel { id: Some(1), tags: Some(vec!["left".to_string()]) }],
+        }
+    }
+}
+
+impl DemodProperties {
+    pub const FREQUENCY_ID: &'static str = "frequency";
+    pub const MODE_ID: &'static str = "mode";
+    pub const PROCESSOR_NAME_ID: &'static str = "processor_name";
+    pub const SQUELCH_LEVEL_ID: &'static str = "DCE:5d8bfe8b";
+    pub const TYPE_ID: &'static str = "type";
+    pub const TAPS_ID: &'static str = "taps";
+    pub const GAIN_ID: &'static str = "gain";
+    pub const CHANNELS_ID: &'static str = "channels";
+
+    /// The tuned frequency.
+    pub fn frequency(&self) -> &f64 {
+        &self.frequency
+    }
+
+    pub fn set_frequency(&mut self, value: f64) -> Result<(), ResourceError> {
+        let v = &value;
+        if !(87.5..=108.0).contains(v) {
+            return Err(invalid("frequency", value.to_any(), "is out of range [87.5, 108]"));
+        }
+        self.frequency = value;
+        Ok(())
+    }
+
+    /// The 'mode' property.
+    pub fn mode(&self) -> &String {
+        &self.mode
+    }
+
+    pub fn set_mode(&mut self, value: String) -> Result<(), ResourceError> {
+        let v = &value;
+        if !["mono", "stereo"].contains(&v.as_str()) {
+            return Err(invalid("mode", value.to_any(), "is not one of the enumerations"));
+        }
+        self.mode = value;
+        Ok(())
+    }
+
+    /// The 'processor_name' property.
+    pub fn processor_name(&self) -> &String {
+        &self.processor_name
+    }
+
+    pub fn set_processor_name(&mut self, value: String) -> Result<(), ResourceError> {
+        self.processor_name = value;
+        Ok(())
+    }
+
+    /// The 'DCE:5d8bfe8b' property.
+    pub fn squelch_level(&self) -> Option<&i16> {
+        self.squelch_level.as_ref()
+    }
+
+    pub fn set_squelch_level(&mut self, value: i16) -> Result<(), ResourceError> {
+        let v = &value;
+        if !(-100..=0).contains(v) {
+            return Err(invalid("DCE:5d8bfe8b", value.to_any(), "is out of range [-100, 0]"));
+        }
+        self.squelch_level = Some(value);
+        Ok(())
+    }
+
+    /// The 'type' property.
+    pub fn type_(&self) -> Option<&char> {
+        self.type_.as_ref()
+    }
+
+    pub fn set_type(&mut self, value: char) -> Result<(), ResourceError> {
+        self.type_ = Some(value);
+        Ok(())
+    }
+
+    /// The 'taps' property.
+    pub fn taps(&self) -> &Vec<i16> {
+        &self.taps
+    }
+
+    pub fn set_taps(&mut self, value: Vec<i16>) -> Result<(), ResourceError> {
+        let v = &value;
+        if v.iter().any(|e| !(-8..=8).contains(e)) {
+            return Err(invalid("taps", value.to_any(), "is out of range [-8, 8]"));
+        }
+        self.taps = value;
+        Ok(())
+    }
+
+    /// The 'gain' property.
+    pub fn gain(&self) -> &Gain {
+        &self.gain
+    }
+
+    pub fn set_gain(&mut self, value: Gain) -> Result<(), ResourceError> {
+        if let Err(message) = value.verify() {
+            return Err(ResourceError::InvalidConfiguration {
+                message,
+                invalid_properties: vec![DataType::new("gain", value.to_any())],
+            });
+        }
+        self.gain = value;
+        Ok(())
+    }
+
+    /// The 'channels' property.
+    pub fn channels(&self) -> &Vec<Channel> {
+        &self.channels
+    }
+
+    pub fn set_channels(&mut self, value: Vec<Channel>) -> Result<(), ResourceError> {
+        if let Err(message) = value.iter().try_for_each(|v| v.verify()) {
+            return Err(ResourceError::InvalidConfiguration {
+                message,
+                invalid_properties: vec![DataType::new("channels", value.to_any())],
+            });
+        }
+        self.channels = value;
+        Ok(())
+    }
+
+    /**
+     * Configures the writable properties as the configure operation
+     * does: none being set is an InvalidConfiguration, some not being set
+     * a PartialConfiguration.
+     */
+    pub fn configure(&mut self, properties: &Properties) -> Result<(), ResourceError> {
+        self.apply(properties, true)
+    }
+
+    fn apply(&mut self, properties: &Properties, configuring: bool) -> Result<(), ResourceError> {
+        let mut invalid_properties = Properties::new();
+        for p in properties {
+            let applied = match p.id.as_str() {
+                "frequency" => PropertyValue::from_any(&p.value).map(|v| self.set_frequency(v)),
+                "mode" => PropertyValue::from_any(&p.value).map(|v| self.set_mode(v)),
+                "processor_name" if !configuring => PropertyValue::from_any(&p.value).map(|v| self.set_processor_name(v)),
+                "DCE:5d8bfe8b" => PropertyValue::from_any(&p.value).map(|v| self.set_squelch_level(v)),
+                "type" => PropertyValue::from_any(&p.value).map(|v| self.set_type(v)),
+                "taps" => PropertyValue::from_any(&p.value).map(|v| self.set_taps(v)),
+                "gain" => PropertyValue::from_any(&p.value).map(|v| self.set_gain(v)),
+                "channels" => PropertyValue::from_any(&p.value).map(|v| self.set_channels(v)),
+                _ => None,
+            };
+            if !matches!(applied, Some(Ok(()))) {
+                invalid_properties.push(p.clone());
+            }
+        }
+
+        if invalid_properties.is_empty() {
+            return Ok(());
+        }
+        if invalid_properties.len() == properties.len() {
+            return Err(ResourceError::InvalidConfiguration {
+                message: "invalid properties".to_string(),
+                invalid_properties,
+            });
+        }
+        Err(ResourceError::PartialConfiguration { invalid_properties })
+    }
+}
+
+impl From<&DemodProperties> for Properties {
+    fn from(p: &DemodProperties) -> Properties {
+        let mut properties = Properties::new();
+        properties.push(DataType::new("frequency", p.frequency.to_any()));
+        properties.push(DataType::new("mode", p.mode.to_any()));
+        properties.push(DataType::new("processor_name", p.processor_name.to_any()));
+        if let Some(v) = &p.squelch_level {
+            properties.push(DataType::new("DCE:5d8bfe8b", v.to_any()));
+        }
+        if let Some(v) = &p.type_ {
+            properties.push(DataType::new("type", v.to_any()));
+        }
+        properties.push(DataType::new("taps", p.taps.to_any()));
+        properties.push(DataType::new("gain", p.gain.to_any()));
+        properties.push(DataType::new("channels", p.channels.to_any()));
+        properties
+    }
+}
+
+impl TryFrom<&Properties> for DemodProperties {
+    type Error = ResourceError;
+
+    /// The properties not given keep their default value.
+    fn try_from(properties: &Properties) -> Result<Self, ResourceError> {
+        let mut p = DemodProperties::default();
+        p.apply(properties, false)?;
+        Ok(p)
+    }
+}
+
+fn invalid(id: &str, value: AnyValue, message: &str) -> ResourceError {
+    ResourceError::InvalidConfiguration {
+        message: format!("'{id}' {message}"),
+        invalid_properties: vec![DataType::new(id, value)],
+    }
+}
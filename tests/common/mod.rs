@@ -0,0 +1,132 @@
+//! Fixtures shared by the integration tests: the waveforms written under
+//! a directory and the simulated devices deploying them.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+
+use scars::cf::allocation_manager::{AllocationManager, AllocationManagerRef};
+use scars::cf::application_factory::DeploymentContext;
+use scars::cf::component_registry::ComponentRegistry;
+use scars::cf::device::Device;
+use scars::cf::device_service::DeviceService;
+use scars::cf::executable_device::ExecutableDeviceRef;
+use scars::cf::file_manager::FileManagerRef;
+use scars::cf::file_system::FileSystem;
+use scars::cf::file_system_service::FileSystemService;
+use scars::cf::rpc::device::device_server::DeviceServer;
+use scars::cf::rpc::file_system::file_system_server::FileSystemServer;
+use scars::cf::sim_device::{SimExecutableDevice, SimLoadableDevice};
+
+/// Writes the files, named relative to the directory, creating their parents.
+pub fn write_files(root: &Path, files: &[(&str, &str)]) {
+    for (name, xml) in files {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, xml).unwrap();
+    }
+}
+
+/// Writes the fm waveform, made of a demodulator without executable, under waveforms/fm.
+pub fn write_waveform(root: &Path) {
+    write_files(
+        root,
+        &[
+            (
+                "waveforms/fm/fm.sad.xml",
+                r#"<softwareassembly id="DCE:fm" name="fm"><componentfiles>
+                <componentfile id="demod_file" type="SPD">
+                <localfile name="../../components/demod/demod.spd.xml"/></componentfile>
+                </componentfiles></softwareassembly>"#,
+            ),
+            (
+                "components/demod/demod.spd.xml",
+                r#"<softpkg id="DCE:demod" name="demod">
+                <propertyfile><localfile name="demod.prf.xml"/></propertyfile>
+                <descriptor><localfile name="demod.scd.xml"/></descriptor></softpkg>"#,
+            ),
+            ("components/demod/demod.prf.xml", "<properties/>"),
+            ("components/demod/demod.scd.xml", "<softwarecomponent/>"),
+        ],
+    );
+}
+
+/// Writes the tone waveform, made of a single oscillator, under waveforms/tone.
+pub fn tone_waveform(root: &Path) {
+    write_files(
+        root,
+        &[
+            (
+                "waveforms/tone/tone.sad.xml",
+                r#"<softwareassembly id="DCE:tone" name="tone"><componentfiles>
+                <componentfile id="osc_file" type="SPD"><localfile name="osc.spd.xml"/></componentfile>
+                </componentfiles><partitioning><componentplacement><componentfileref refid="osc_file"/>
+                <componentinstantiation id="osc_1"/></componentplacement></partitioning>
+                <assemblycontroller><componentinstantiationref refid="osc_1"/></assemblycontroller></softwareassembly>"#,
+            ),
+            (
+                "waveforms/tone/osc.spd.xml",
+                r#"<softpkg id="DCE:osc" name="osc"><implementation id="cpp">
+                <code type="Executable"><localfile name="osc"/></code></implementation></softpkg>"#,
+            ),
+        ],
+    );
+}
+
+/// Returns a simulated GPP, the executable device DCE:gpp.
+pub fn sim_gpp() -> Arc<Mutex<SimExecutableDevice>> {
+    Arc::new(Mutex::new(SimExecutableDevice::new(
+        SimLoadableDevice::new(Device::new("DCE:gpp", "gpp")),
+    )))
+}
+
+/// Returns an allocation manager allocating on the device alone.
+pub fn allocation_manager(device: &Arc<Mutex<SimExecutableDevice>>) -> AllocationManagerRef {
+    let mut allocation_manager = AllocationManager::new();
+    allocation_manager.register_device(device.clone());
+    Arc::new(Mutex::new(allocation_manager))
+}
+
+/**
+ * Returns the context deploying on the device, the allocations being
+ * given by the allocation manager and the components resolved from the
+ * registry within 50ms.
+ */
+pub fn deployment(
+    file_manager: FileManagerRef,
+    allocation_manager: AllocationManagerRef,
+    device: &Arc<Mutex<SimExecutableDevice>>,
+    registry: ComponentRegistry,
+) -> DeploymentContext {
+    DeploymentContext::new(file_manager, allocation_manager, registry)
+        .with_device(device.clone())
+        .with_resolve_timeout(Duration::from_millis(50))
+}
+
+/// Serves the FileSystem of the directory, as a node does, returning its endpoint.
+pub async fn serve_file_system(root: &Path) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+    let service = FileSystemService::new(Arc::new(FileSystem::new(root)));
+    tokio::spawn(Server::builder().add_service(FileSystemServer::new(service)).serve_with_incoming(incoming));
+    endpoint
+}
+
+/**
+ * Serves the executable device, as a device launcher does, returning its
+ * endpoint. The files loaded are staged under the directory.
+ */
+pub async fn serve_device(device: ExecutableDeviceRef, staging_dir: &Path) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+    let service = DeviceService::new(device.clone()).with_executable(device, staging_dir);
+    tokio::spawn(Server::builder().add_service(DeviceServer::new(service)).serve_with_incoming(incoming));
+    endpoint
+}